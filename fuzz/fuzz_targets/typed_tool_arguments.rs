@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use vtcode_core::fuzzing::ToolArguments;
+
+fuzz_target!(|args: ToolArguments| {
+    vtcode_core::fuzzing::typed_tool_arguments(&args);
+});
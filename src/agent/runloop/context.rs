@@ -1,5 +1,8 @@
+use std::ops::Range;
+
 use vtcode_core::config::constants::context as context_defaults;
 use vtcode_core::config::loader::VTCodeConfig;
+use vtcode_core::core::token_calibration::estimate_message_tokens;
 use vtcode_core::llm::provider as uni;
 
 #[derive(Clone, Copy)]
@@ -7,6 +10,8 @@ pub(crate) struct ContextTrimConfig {
     pub(crate) max_tokens: usize,
     pub(crate) trim_to_percent: u8,
     pub(crate) preserve_recent_turns: usize,
+    /// Calibration factor applied to character-based token estimates
+    pub(crate) token_scale: f64,
 }
 
 impl ContextTrimConfig {
@@ -22,6 +27,8 @@ impl ContextTrimConfig {
 #[derive(Default)]
 pub(crate) struct ContextTrimOutcome {
     pub(crate) removed_messages: usize,
+    /// The trimmed messages, in conversation order
+    pub(crate) removed: Vec<uni::Message>,
 }
 
 impl ContextTrimOutcome {
@@ -82,6 +89,11 @@ pub(crate) fn apply_aggressive_trim_unified(
     remove
 }
 
+/// Trim the history to the context window. Older messages outside the
+/// preserved recent turns go first in order of `Provenance::trim_rank`, tool
+/// results before the user's own words; an assistant message is trimmed
+/// together with the tool results answering its calls. If that is not enough,
+/// the oldest remaining messages follow, keeping the latest one.
 pub(crate) fn enforce_unified_context_window(
     history: &mut Vec<uni::Message>,
     config: ContextTrimConfig,
@@ -92,7 +104,9 @@ pub(crate) fn enforce_unified_context_window(
 
     let tokens_per_message: Vec<usize> = history
         .iter()
-        .map(approximate_unified_message_tokens)
+        .map(|message| {
+            (estimate_message_tokens(message) as f64 * config.token_scale).ceil() as usize
+        })
         .collect();
     let mut total_tokens: usize = tokens_per_message.iter().sum();
 
@@ -101,33 +115,67 @@ pub(crate) fn enforce_unified_context_window(
     }
 
     let target_tokens = config.target_tokens();
-    let mut remove_count = 0usize;
-    let mut preserve_boundary = history.len().saturating_sub(config.preserve_recent_turns);
-    if preserve_boundary > history.len().saturating_sub(1) {
-        preserve_boundary = history.len().saturating_sub(1);
-    }
-
-    while remove_count < preserve_boundary && total_tokens > config.max_tokens {
-        total_tokens = total_tokens.saturating_sub(tokens_per_message[remove_count]);
-        remove_count += 1;
+    let preserve_boundary = history
+        .len()
+        .saturating_sub(config.preserve_recent_turns)
+        .min(history.len().saturating_sub(1));
+    let mut removed = vec![false; history.len()];
+
+    let mut groups: Vec<_> = message_groups(history)
+        .into_iter()
+        .filter(|group| group.end <= preserve_boundary)
+        .collect();
+    groups.sort_by_key(|group| (history[group.start].provenance().trim_rank(), group.start));
+    for group in groups {
+        if total_tokens <= config.max_tokens {
+            break;
+        }
+        for index in group {
+            removed[index] = true;
+            total_tokens = total_tokens.saturating_sub(tokens_per_message[index]);
+        }
         if total_tokens <= target_tokens {
             break;
         }
     }
 
-    while remove_count < history.len().saturating_sub(1) && total_tokens > config.max_tokens {
-        total_tokens = total_tokens.saturating_sub(tokens_per_message[remove_count]);
-        remove_count += 1;
+    for index in 0..history.len().saturating_sub(1) {
+        if total_tokens <= config.max_tokens {
+            break;
+        }
+        if !removed[index] {
+            removed[index] = true;
+            total_tokens = total_tokens.saturating_sub(tokens_per_message[index]);
+        }
     }
 
-    if remove_count == 0 {
-        return ContextTrimOutcome::default();
-    }
+    let mut outcome = ContextTrimOutcome::default();
+    let mut index = 0usize;
+    history.retain(|message| {
+        let keep = !removed[index];
+        if !keep {
+            outcome.removed.push(message.clone());
+        }
+        index += 1;
+        keep
+    });
+    outcome.removed_messages = outcome.removed.len();
+    outcome
+}
 
-    history.drain(0..remove_count);
-    ContextTrimOutcome {
-        removed_messages: remove_count,
+/// Index ranges that are trimmed together: an assistant message with tool
+/// calls and the tool results that follow it, or a single message.
+fn message_groups(history: &[uni::Message]) -> Vec<Range<usize>> {
+    let mut groups: Vec<Range<usize>> = Vec::new();
+    for (index, message) in history.iter().enumerate() {
+        match groups.last_mut() {
+            Some(group) if message.is_tool_response() && history[group.start].has_tool_calls() => {
+                group.end = index + 1;
+            }
+            _ => groups.push(index..index + 1),
+        }
     }
+    groups
 }
 
 pub(crate) fn load_context_trim_config(vt_cfg: Option<&VTCodeConfig>) -> ContextTrimConfig {
@@ -160,29 +208,10 @@ pub(crate) fn load_context_trim_config(vt_cfg: Option<&VTCodeConfig>) -> Context
         max_tokens,
         trim_to_percent,
         preserve_recent_turns,
+        token_scale: 1.0,
     }
 }
 
-fn approximate_unified_message_tokens(message: &uni::Message) -> usize {
-    let mut total_chars = message.content.len();
-    total_chars += message.role.as_generic_str().len();
-
-    if let Some(tool_calls) = &message.tool_calls {
-        for call in tool_calls {
-            total_chars += call.id.len();
-            total_chars += call.call_type.len();
-            total_chars += call.function.name.len();
-            total_chars += call.function.arguments.len();
-        }
-    }
-
-    if let Some(tool_call_id) = &message.tool_call_id {
-        total_chars += tool_call_id.len();
-    }
-
-    total_chars.div_ceil(context_defaults::CHAR_PER_TOKEN_APPROX)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -197,6 +226,7 @@ mod tests {
             max_tokens: 18,
             trim_to_percent: 70,
             preserve_recent_turns: 3,
+            token_scale: 1.0,
         };
 
         let outcome = enforce_unified_context_window(&mut history, config);
@@ -204,7 +234,7 @@ mod tests {
         assert!(outcome.is_trimmed());
         assert_eq!(original_len - history.len(), outcome.removed_messages);
 
-        let remaining_tokens: usize = history.iter().map(approximate_unified_message_tokens).sum();
+        let remaining_tokens: usize = history.iter().map(estimate_message_tokens).sum();
         assert!(remaining_tokens <= config.max_tokens);
 
         let last_content = history
@@ -214,6 +244,54 @@ mod tests {
         assert!(last_content.contains("assistant step 11"));
     }
 
+    #[test]
+    fn test_enforce_unified_context_window_trims_tool_results_before_user_input() {
+        let mut call = uni::Message::assistant(String::new());
+        call.tool_calls = Some(vec![uni::ToolCall::function(
+            "call_1".to_string(),
+            "read_file".to_string(),
+            "{\"path\":\"src/lib.rs\"}".to_string(),
+        )]);
+        let mut history = vec![
+            uni::Message::user("fix the parser".to_string()),
+            call,
+            uni::Message::tool_response("call_1".to_string(), "x".repeat(400)),
+            uni::Message::assistant("done".to_string()),
+            uni::Message::user("now the lexer".to_string()),
+        ];
+        let config = ContextTrimConfig {
+            max_tokens: 60,
+            trim_to_percent: 80,
+            preserve_recent_turns: 1,
+            token_scale: 1.0,
+        };
+
+        let outcome = enforce_unified_context_window(&mut history, config);
+
+        assert_eq!(outcome.removed_messages, 2);
+        assert!(outcome.removed[1].is_tool_response());
+        assert_eq!(history[0].content, "fix the parser");
+        assert!(!history.iter().any(|message| message.is_tool_response()));
+    }
+
+    #[test]
+    fn test_token_scale_trims_against_calibrated_estimates() {
+        let history: Vec<uni::Message> = (0..10)
+            .map(|i| uni::Message::assistant(format!("assistant step {i}")))
+            .collect();
+        let estimated: usize = history.iter().map(estimate_message_tokens).sum();
+        let mut config = ContextTrimConfig {
+            max_tokens: estimated,
+            trim_to_percent: 80,
+            preserve_recent_turns: 3,
+            token_scale: 1.0,
+        };
+
+        assert!(!enforce_unified_context_window(&mut history.clone(), config).is_trimmed());
+        config.token_scale = 1.5;
+        assert!(enforce_unified_context_window(&mut history.clone(), config).is_trimmed());
+    }
+
     #[test]
     fn test_prune_unified_tool_responses_respects_recent_history() {
         let mut history: Vec<uni::Message> = vec![
@@ -250,6 +328,7 @@ mod tests {
             max_tokens: 140,
             trim_to_percent: 80,
             preserve_recent_turns: 10,
+            token_scale: 1.0,
         };
 
         let removed = apply_aggressive_trim_unified(&mut history, config);
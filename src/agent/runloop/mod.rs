@@ -1,6 +1,9 @@
 use anyhow::Result;
 use vtcode_core::config::loader::ConfigManager;
 use vtcode_core::config::types::AgentConfig as CoreAgentConfig;
+use vtcode_core::core::live_share::ShareLink;
+use vtcode_core::core::run_events::{FailOn, RunEventLog, RunStatus};
+use vtcode_core::core::session_store::StoredSession;
 
 mod context;
 mod git;
@@ -13,15 +16,34 @@ mod ui;
 mod unified;
 mod welcome;
 
+pub(crate) use unified::system_prompt_from_template;
+
+#[allow(clippy::too_many_arguments)]
 pub async fn run_single_agent_loop(
     config: &CoreAgentConfig,
     skip_confirmations: bool,
     full_auto: bool,
-) -> Result<()> {
+    run_events: &RunEventLog,
+    fail_on: &[FailOn],
+    initial_prompt: Option<String>,
+    share: Option<ShareLink>,
+    resume: Option<StoredSession>,
+) -> Result<RunStatus> {
     let cfg_manager = ConfigManager::load_from_workspace(&config.workspace).ok();
     let vt_cfg = cfg_manager.as_ref().map(|manager| manager.config());
 
-    unified::run_single_agent_loop_unified(config, vt_cfg, skip_confirmations, full_auto).await
+    unified::run_single_agent_loop_unified(
+        config,
+        vt_cfg,
+        skip_confirmations,
+        full_auto,
+        run_events,
+        fail_on,
+        initial_prompt,
+        share,
+        resume,
+    )
+    .await
 }
 
 pub(crate) fn is_context_overflow_error(message: &str) -> bool {
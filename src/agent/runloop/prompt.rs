@@ -48,7 +48,7 @@ pub(crate) async fn refine_user_prompt_if_enabled(
     let Ok(refiner) = create_provider_with_config(
         &provider_name,
         Some(cfg.api_key.clone()),
-        None,
+        cfg.base_url.clone(),
         Some(refiner_model.clone()),
         Some(cfg.prompt_cache.clone()),
     ) else {
@@ -73,6 +73,10 @@ pub(crate) async fn refine_user_prompt_if_enabled(
         parallel_tool_calls: None,
         parallel_tool_config: None,
         reasoning_effort,
+        json_mode: false,
+        response_schema: None,
+        code_execution: false,
+        seed: None,
     };
 
     match refiner
@@ -157,7 +161,7 @@ fn keyword_set(text: &str) -> HashSet<String> {
 mod tests {
     use super::*;
     use vtcode_core::config::core::PromptCachingConfig;
-    use vtcode_core::config::types::{ReasoningEffortLevel, UiSurfacePreference};
+    use vtcode_core::config::types::{ReasoningEffortLevel, RenderMode, UiSurfacePreference};
 
     #[tokio::test]
     async fn test_prompt_refinement_applies_to_gemini_when_flag_disabled() {
@@ -170,11 +174,13 @@ mod tests {
                 .to_string(),
             api_key: "test".to_string(),
             provider: "gemini".to_string(),
+            base_url: None,
             workspace: std::env::current_dir().unwrap(),
             verbose: false,
             theme: vtcode_core::ui::theme::DEFAULT_THEME_ID.to_string(),
             reasoning_effort: ReasoningEffortLevel::default(),
             ui_surface: UiSurfacePreference::default(),
+            render_mode: RenderMode::default(),
             prompt_cache: PromptCachingConfig::default(),
         };
 
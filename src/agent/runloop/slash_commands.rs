@@ -1,16 +1,93 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use chrono::Local;
-use serde_json::{Map, Value};
+use serde_json::{Map, Value, json};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
+use vtcode_core::config::constants::tools;
+use vtcode_core::ui::locale::Locale;
+use vtcode_core::ui::multiplexer::{self, Multiplexer, PanePlacement};
 use vtcode_core::ui::slash::SLASH_COMMANDS;
 use vtcode_core::ui::theme;
+use vtcode_core::ui::tui::RatatuiCopyTarget;
 use vtcode_core::utils::ansi::{AnsiRenderer, MessageStyle};
-use vtcode_core::utils::session_archive;
+use vtcode_core::utils::{session_archive, session_title, transcript};
+
+const SPLIT_USAGE: &str = "Usage: /split diff [path] [--window] | /split output [--window]";
+const GIT_USAGE: &str = "Usage: /git status|diff|log|show|branch|blame [args...]";
+/// Git subcommands available through `/git`; all of them leave the repository untouched.
+const READ_ONLY_GIT_COMMANDS: &[&str] = &["status", "diff", "log", "show", "branch", "blame"];
+
+pub enum SplitPaneContent {
+    Diff(Option<String>),
+    Output,
+}
+
+pub enum OutlineRequest {
+    /// Show the outline, refreshing it for the given path when one is provided
+    Show(Option<String>),
+    Hide,
+}
+
+pub enum OfflineRequest {
+    Status,
+    /// Stop provider calls and switch to local assistant mode
+    Enable,
+    /// Probe the provider again and resume provider calls when it is reachable
+    Reconnect,
+}
+
+pub enum DualAnswerRequest {
+    Status,
+    Enable,
+    Disable,
+    /// Ask this model for the second answer, enabling the mode
+    Model(String),
+}
+
+pub enum ToolAccessRequest {
+    Status,
+    /// Expose editing and terminal tools to the model
+    Full,
+    /// Go back to read-only tools
+    ReadOnly,
+}
 
 pub enum SlashCommandOutcome {
     Handled,
     ThemeChanged(String),
-    ExecuteTool { name: String, args: Value },
+    SetRequestPreview(bool),
+    Outline(OutlineRequest),
+    Offline(OfflineRequest),
+    ToolAccess(ToolAccessRequest),
+    Copy(RatatuiCopyTarget),
+    Paste,
+    /// Show or change whether a second model answers each prompt as well
+    DualAnswer(DualAnswerRequest),
+    /// Write a redacted copy of the session, with extra patterns to redact
+    Redact(Vec<String>),
+    /// Open the turn-by-turn timeline of the session
+    Timeline,
+    /// List the context blocks by provenance
+    Context,
+    /// List recorded tool results, or expand the one with this reference number
+    Evidence(Option<usize>),
+    /// Show what changed in the workspace since the previous session
+    Briefing,
+    /// Write `.vtcode/handoff.md` so someone else can continue the session
+    Handoff,
+    /// Show the response language, or switch to this one for the conversation
+    Language(Option<Locale>),
+    /// Set the session title shown in the status bar and session list
+    Rename(String),
+    SplitPane {
+        content: SplitPaneContent,
+        placement: PanePlacement,
+    },
+    ExecuteTool {
+        name: String,
+        args: Value,
+    },
     Exit,
 }
 
@@ -51,7 +128,7 @@ pub fn handle_slash_command(
             for info in SLASH_COMMANDS.iter() {
                 renderer.line(
                     MessageStyle::Info,
-                    &format!("  /{} - {}", info.name, info.description),
+                    &format!("  {} - {}", info.usage, info.description),
                 )?;
             }
             renderer.line(
@@ -96,6 +173,65 @@ pub fn handle_slash_command(
                 args: Value::Object(args_map),
             })
         }
+        "search" => {
+            let pattern = parts.collect::<Vec<_>>().join(" ");
+            if pattern.is_empty() {
+                renderer.line(MessageStyle::Error, "Usage: /search <pattern>")?;
+                return Ok(SlashCommandOutcome::Handled);
+            }
+            Ok(SlashCommandOutcome::ExecuteTool {
+                name: tools::GREP_SEARCH.to_string(),
+                args: json!({ "pattern": pattern, "path": "." }),
+            })
+        }
+        "files" => Ok(SlashCommandOutcome::ExecuteTool {
+            name: tools::LIST_FILES.to_string(),
+            args: json!({ "path": parts.next().unwrap_or(".") }),
+        }),
+        "read" => {
+            let Some(path) = parts.next() else {
+                renderer.line(MessageStyle::Error, "Usage: /read <path>")?;
+                return Ok(SlashCommandOutcome::Handled);
+            };
+            Ok(SlashCommandOutcome::ExecuteTool {
+                name: tools::READ_FILE.to_string(),
+                args: json!({ "path": path }),
+            })
+        }
+        "git" => {
+            let Some(subcommand) = parts.next() else {
+                renderer.line(MessageStyle::Error, GIT_USAGE)?;
+                return Ok(SlashCommandOutcome::Handled);
+            };
+            if !READ_ONLY_GIT_COMMANDS.contains(&subcommand) {
+                renderer.line(MessageStyle::Error, GIT_USAGE)?;
+                return Ok(SlashCommandOutcome::Handled);
+            }
+            let mut command = vec!["git".to_string(), subcommand.to_string()];
+            command.extend(parts.map(str::to_string));
+            Ok(SlashCommandOutcome::ExecuteTool {
+                name: tools::RUN_TERMINAL_CMD.to_string(),
+                args: json!({ "command": command }),
+            })
+        }
+        "offline" => match parts.next().map(|value| value.to_lowercase()).as_deref() {
+            None | Some("status") => Ok(SlashCommandOutcome::Offline(OfflineRequest::Status)),
+            Some("on") => Ok(SlashCommandOutcome::Offline(OfflineRequest::Enable)),
+            Some("off") => Ok(SlashCommandOutcome::Offline(OfflineRequest::Reconnect)),
+            _ => {
+                renderer.line(MessageStyle::Error, "Usage: /offline [on|off]")?;
+                Ok(SlashCommandOutcome::Handled)
+            }
+        },
+        "tools" => match parts.next().map(|value| value.to_lowercase()).as_deref() {
+            None | Some("status") => Ok(SlashCommandOutcome::ToolAccess(ToolAccessRequest::Status)),
+            Some("full") => Ok(SlashCommandOutcome::ToolAccess(ToolAccessRequest::Full)),
+            Some("read") => Ok(SlashCommandOutcome::ToolAccess(ToolAccessRequest::ReadOnly)),
+            _ => {
+                renderer.line(MessageStyle::Error, "Usage: /tools [full|read]")?;
+                Ok(SlashCommandOutcome::Handled)
+            }
+        },
         "sessions" => {
             let limit = parts
                 .next()
@@ -127,8 +263,12 @@ pub fn handle_slash_command(
                                 duration.to_std().unwrap_or_else(|_| Duration::from_secs(0));
                             let duration_label = format_duration_label(duration_std);
                             let tool_count = listing.snapshot.distinct_tools.len();
+                            let title = listing
+                                .title()
+                                .unwrap_or_else(|| "Untitled session".to_string());
                             let header = format!(
-                                "- (ID: {}) {} · Model: {} · Workspace: {}",
+                                "- {} (ID: {}) {} · Model: {} · Workspace: {}",
+                                title,
                                 listing.identifier(),
                                 ended_local,
                                 listing.snapshot.metadata.model,
@@ -168,6 +308,104 @@ pub fn handle_slash_command(
             }
             Ok(SlashCommandOutcome::Handled)
         }
+        "preview" => match parts.next().map(|value| value.to_lowercase()).as_deref() {
+            Some("on") => Ok(SlashCommandOutcome::SetRequestPreview(true)),
+            Some("off") => Ok(SlashCommandOutcome::SetRequestPreview(false)),
+            _ => {
+                renderer.line(MessageStyle::Error, "Usage: /preview on|off")?;
+                Ok(SlashCommandOutcome::Handled)
+            }
+        },
+        "dual" => Ok(SlashCommandOutcome::DualAnswer(match parts.next() {
+            None | Some("status") => DualAnswerRequest::Status,
+            Some("on") => DualAnswerRequest::Enable,
+            Some("off") => DualAnswerRequest::Disable,
+            Some(model) => DualAnswerRequest::Model(model.to_string()),
+        })),
+        "copy" => match parts.next().map(|value| value.to_lowercase()).as_deref() {
+            None | Some("reply") => Ok(SlashCommandOutcome::Copy(RatatuiCopyTarget::LastReply)),
+            Some("code") => Ok(SlashCommandOutcome::Copy(RatatuiCopyTarget::LastCodeBlock)),
+            _ => {
+                renderer.line(MessageStyle::Error, "Usage: /copy [reply|code]")?;
+                Ok(SlashCommandOutcome::Handled)
+            }
+        },
+        "paste" => Ok(SlashCommandOutcome::Paste),
+        "redact" => Ok(SlashCommandOutcome::Redact(
+            parts.map(|pattern| pattern.to_string()).collect(),
+        )),
+        "timeline" => Ok(SlashCommandOutcome::Timeline),
+        "context" => Ok(SlashCommandOutcome::Context),
+        "briefing" => Ok(SlashCommandOutcome::Briefing),
+        "handoff" => Ok(SlashCommandOutcome::Handoff),
+        "evidence" => match parts
+            .next()
+            .map(|value| value.trim_start_matches('[').trim_end_matches(']'))
+        {
+            None => Ok(SlashCommandOutcome::Evidence(None)),
+            Some(value) => match value.parse() {
+                Ok(id) => Ok(SlashCommandOutcome::Evidence(Some(id))),
+                Err(_) => {
+                    renderer.line(MessageStyle::Error, "Usage: /evidence [n]")?;
+                    Ok(SlashCommandOutcome::Handled)
+                }
+            },
+        },
+        "lang" => match parts.next() {
+            None => Ok(SlashCommandOutcome::Language(None)),
+            Some(value) => match Locale::parse(value) {
+                Some(locale) => Ok(SlashCommandOutcome::Language(Some(locale))),
+                None => {
+                    renderer.line(
+                        MessageStyle::Error,
+                        &format!(
+                            "'{}' is not a language tag. Usage: /lang <tag>, e.g. /lang ja",
+                            value
+                        ),
+                    )?;
+                    Ok(SlashCommandOutcome::Handled)
+                }
+            },
+        },
+        "rename" => {
+            let raw = parts.collect::<Vec<_>>().join(" ");
+            match session_title::normalize_title(&raw) {
+                Some(title) => Ok(SlashCommandOutcome::Rename(title)),
+                None => {
+                    renderer.line(MessageStyle::Error, "Usage: /rename <title>")?;
+                    Ok(SlashCommandOutcome::Handled)
+                }
+            }
+        }
+        "split" => {
+            let mut placement = PanePlacement::Split;
+            let mut positional = Vec::new();
+            for part in parts {
+                if part == "--window" {
+                    placement = PanePlacement::Window;
+                } else {
+                    positional.push(part);
+                }
+            }
+            let content = match positional.as_slice() {
+                ["diff"] => SplitPaneContent::Diff(None),
+                ["diff", path] => SplitPaneContent::Diff(Some((*path).to_string())),
+                ["output"] => SplitPaneContent::Output,
+                _ => {
+                    renderer.line(MessageStyle::Error, SPLIT_USAGE)?;
+                    return Ok(SlashCommandOutcome::Handled);
+                }
+            };
+            Ok(SlashCommandOutcome::SplitPane { content, placement })
+        }
+        "outline" => {
+            let request = match parts.next() {
+                None | Some("on") => OutlineRequest::Show(None),
+                Some("off") => OutlineRequest::Hide,
+                Some(path) => OutlineRequest::Show(Some(path.to_string())),
+            };
+            Ok(SlashCommandOutcome::Outline(request))
+        }
         "exit" => Ok(SlashCommandOutcome::Exit),
         _ => {
             renderer.line(
@@ -179,6 +417,74 @@ pub fn handle_slash_command(
     }
 }
 
+/// Open a diff or the agent output in a separate multiplexer pane.
+///
+/// Outside tmux or zellij this only reports that the command is unavailable.
+pub fn open_split_pane(
+    renderer: &mut AnsiRenderer,
+    workspace: &Path,
+    content: SplitPaneContent,
+    placement: PanePlacement,
+) -> Result<()> {
+    let Some(mux) = Multiplexer::detect() else {
+        renderer.line(
+            MessageStyle::Info,
+            "Not running inside tmux or zellij; /split is unavailable.",
+        )?;
+        return Ok(());
+    };
+
+    let (command, label) = match content {
+        SplitPaneContent::Diff(path) => (multiplexer::diff_pane_command(path.as_deref()), "diff"),
+        SplitPaneContent::Output => {
+            let mut text = transcript::snapshot().join("\n");
+            text.push('\n');
+            let path = write_output_snapshot(&text)?;
+            (multiplexer::file_pane_command(&path), "agent output")
+        }
+    };
+
+    let target = match placement {
+        PanePlacement::Split => "pane",
+        PanePlacement::Window => "window",
+    };
+    match mux.open(&command, workspace, placement) {
+        Ok(()) => renderer.line(
+            MessageStyle::Info,
+            &format!(
+                "Opened {} in a new {} {}.",
+                label,
+                mux.kind().label(),
+                target
+            ),
+        )?,
+        Err(err) => renderer.line(
+            MessageStyle::Error,
+            &format!("Failed to open {} {}: {}", mux.kind().label(), target, err),
+        )?,
+    }
+    Ok(())
+}
+
+/// Write `text` to a fresh, owner-only file in the temp directory that
+/// outlives this call, so the new pane can read it after we return.
+fn write_output_snapshot(text: &str) -> Result<PathBuf> {
+    let mut builder = tempfile::Builder::new();
+    builder.prefix("vtcode-output-").suffix(".log");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        builder.permissions(std::fs::Permissions::from_mode(0o600));
+    }
+    let mut file = builder
+        .tempfile()
+        .context("failed to create agent output file")?;
+    file.write_all(text.as_bytes())
+        .with_context(|| format!("failed to write agent output to {}", file.path().display()))?;
+    let (_, path) = file.keep().context("failed to keep agent output file")?;
+    Ok(path)
+}
+
 fn format_duration_label(duration: Duration) -> String {
     let total_seconds = duration.as_secs();
     let hours = total_seconds / 3600;
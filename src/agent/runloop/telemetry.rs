@@ -1,20 +1,25 @@
 use std::path::Path;
+use std::time::Duration;
 
 use vtcode_core::config::loader::VTCodeConfig;
+use vtcode_core::core::journal::JournalOptions;
 use vtcode_core::core::trajectory::TrajectoryLogger;
 
 pub(crate) fn build_trajectory_logger(
     workspace: &Path,
     vt_cfg: Option<&VTCodeConfig>,
 ) -> TrajectoryLogger {
-    vt_cfg
-        .map(|cfg| cfg.telemetry.trajectory_enabled)
-        .map(|enabled| {
-            if enabled {
-                TrajectoryLogger::new(workspace)
-            } else {
-                TrajectoryLogger::disabled()
-            }
-        })
-        .unwrap_or_else(|| TrajectoryLogger::new(workspace))
+    let Some(cfg) = vt_cfg else {
+        return TrajectoryLogger::new(workspace);
+    };
+    let telemetry = &cfg.telemetry;
+    if !telemetry.trajectory_enabled {
+        return TrajectoryLogger::disabled();
+    }
+    let options = JournalOptions {
+        batch_size: telemetry.journal_batch_size,
+        flush_interval: Duration::from_millis(telemetry.journal_flush_interval_ms),
+        fsync: telemetry.journal_fsync,
+    };
+    TrajectoryLogger::with_journal_options(workspace, options)
 }
@@ -1,7 +1,109 @@
+use serde::Deserialize;
 use serde_json::{Map, Number, Value};
+use vtcode_core::llm::provider as uni;
+use vtcode_core::utils::code_fence::strip_code_fence;
 
 const TEXTUAL_TOOL_PREFIXES: &[&str] = &["default_api."];
 
+/// Correction round-trips allowed per turn before an invalid envelope is shown as plain text.
+pub(crate) const MAX_JSON_ENVELOPE_RETRIES: usize = 2;
+
+/// Instructions appended to the system prompt while the JSON tool envelope is active.
+const JSON_ENVELOPE_INSTRUCTIONS: &str = r#"[Response Format]
+Respond with exactly one JSON object and nothing else:
+{"message": "<optional text for the user>", "tool_calls": [{"name": "<tool name>", "arguments": {<tool arguments>}}]}
+- Omit "tool_calls" (or use []) when no tool is needed.
+- "arguments" must be a JSON object matching the tool's parameter schema.
+- Do not wrap the JSON in code fences or add any other keys.
+Available tools and parameter schemas:"#;
+
+/// Strict JSON envelope used for textual tool calls when the provider supports JSON mode.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct JsonToolEnvelope {
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub tool_calls: Vec<JsonToolCall>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct JsonToolCall {
+    pub name: String,
+    #[serde(default = "empty_arguments")]
+    pub arguments: Value,
+}
+
+fn empty_arguments() -> Value {
+    Value::Object(Map::new())
+}
+
+/// Build the system prompt suffix describing the JSON envelope and available tools.
+pub(crate) fn json_envelope_instructions(tools: &[uni::ToolDefinition]) -> String {
+    let mut text = JSON_ENVELOPE_INSTRUCTIONS.to_string();
+    for tool in tools {
+        let schema =
+            serde_json::to_string(&tool.function.parameters).unwrap_or_else(|_| "{}".to_string());
+        text.push_str(&format!(
+            "\n- {}: {} Parameters: {}",
+            tool.function.name, tool.function.description, schema
+        ));
+    }
+    text
+}
+
+/// Parse and validate a JSON envelope response.
+///
+/// On failure returns a diagnostic suitable for sending back to the model.
+pub(crate) fn parse_json_tool_envelope(
+    text: &str,
+    tools: &[uni::ToolDefinition],
+) -> Result<JsonToolEnvelope, String> {
+    let payload = strip_code_fence(text);
+    let envelope: JsonToolEnvelope = serde_json::from_str(payload)
+        .map_err(|err| format!("response is not a valid JSON envelope: {err}"))?;
+
+    for (index, call) in envelope.tool_calls.iter().enumerate() {
+        if !tools.iter().any(|tool| tool.function.name == call.name) {
+            let available: Vec<&str> = tools
+                .iter()
+                .map(|tool| tool.function.name.as_str())
+                .collect();
+            return Err(format!(
+                "tool_calls[{index}] names unknown tool '{}'; available tools: {}",
+                call.name,
+                available.join(", ")
+            ));
+        }
+        if !call.arguments.is_object() {
+            return Err(format!(
+                "tool_calls[{index}].arguments for '{}' must be a JSON object",
+                call.name
+            ));
+        }
+    }
+
+    let has_message = envelope
+        .message
+        .as_deref()
+        .is_some_and(|message| !message.trim().is_empty());
+    if !has_message && envelope.tool_calls.is_empty() {
+        return Err(
+            "envelope must contain a non-empty \"message\" or at least one tool call".to_string(),
+        );
+    }
+
+    Ok(envelope)
+}
+
+/// Message sent back to the model when its envelope fails validation.
+pub(crate) fn json_envelope_correction(diagnostic: &str) -> String {
+    format!(
+        "Your previous response could not be processed: {diagnostic}. Respond again with a single JSON object of the form {{\"message\": \"...\", \"tool_calls\": [{{\"name\": \"...\", \"arguments\": {{}}}}]}}."
+    )
+}
+
 pub(crate) fn detect_textual_tool_call(text: &str) -> Option<(String, Value)> {
     for prefix in TEXTUAL_TOOL_PREFIXES {
         let mut search_start = 0usize;
@@ -153,6 +255,49 @@ fn parse_scalar_value(input: &str) -> Value {
 mod tests {
     use super::*;
 
+    fn envelope_tools() -> Vec<uni::ToolDefinition> {
+        vec![uni::ToolDefinition::function(
+            "read_file".to_string(),
+            "Read a file".to_string(),
+            serde_json::json!({"type": "object", "properties": {"path": {"type": "string"}}}),
+        )]
+    }
+
+    #[test]
+    fn test_parse_json_tool_envelope_accepts_tool_calls() {
+        let text = "```json\n{\"message\": \"Reading\", \"tool_calls\": [{\"name\": \"read_file\", \"arguments\": {\"path\": \"a.rs\"}}]}\n```";
+        let envelope = parse_json_tool_envelope(text, &envelope_tools()).expect("valid envelope");
+        assert_eq!(envelope.message.as_deref(), Some("Reading"));
+        assert_eq!(envelope.tool_calls[0].name, "read_file");
+        assert_eq!(
+            envelope.tool_calls[0].arguments,
+            serde_json::json!({"path": "a.rs"})
+        );
+    }
+
+    #[test]
+    fn test_parse_json_tool_envelope_reports_diagnostics() {
+        let tools = envelope_tools();
+        let err = parse_json_tool_envelope("read the file please", &tools).unwrap_err();
+        assert!(err.contains("not a valid JSON envelope"));
+
+        let err =
+            parse_json_tool_envelope(r#"{"tool_calls": [{"name": "nope"}]}"#, &tools).unwrap_err();
+        assert!(err.contains("unknown tool 'nope'"));
+
+        let err = parse_json_tool_envelope(
+            r#"{"tool_calls": [{"name": "read_file", "arguments": "a.rs"}]}"#,
+            &tools,
+        )
+        .unwrap_err();
+        assert!(err.contains("must be a JSON object"));
+
+        let err = parse_json_tool_envelope(r#"{"msg": "hi"}"#, &tools).unwrap_err();
+        assert!(err.contains("unknown field"));
+
+        assert!(parse_json_tool_envelope("{}", &tools).is_err());
+    }
+
     #[test]
     fn test_detect_textual_tool_call_parses_python_style_arguments() {
         let message = "call\nprint(default_api.read_file(path='CLAUDE.md'))";
@@ -7,6 +7,11 @@ use vtcode_core::config::constants::{defaults, tools};
 use vtcode_core::config::loader::VTCodeConfig;
 use vtcode_core::tools::{PlanCompletionState, StepStatus, TaskPlan};
 use vtcode_core::utils::ansi::{AnsiRenderer, MessageStyle};
+use vtcode_core::utils::long_lines::clip_line;
+
+/// Characters of a single output line shown before the rest is elided, so
+/// minified files and JSON blobs do not flood the terminal when wrapped.
+const MAX_DISPLAY_LINE_CHARS: usize = 1_000;
 
 pub(crate) fn render_tool_output(
     renderer: &mut AnsiRenderer,
@@ -313,7 +318,7 @@ fn render_stream_section(
         let display = if line.is_empty() {
             "".to_string()
         } else {
-            format!("  {}", line)
+            format!("  {}", clip_line(line, MAX_DISPLAY_LINE_CHARS))
         };
         if let Some(style) = select_line_style(tool_name, line, git_styles, ls_styles) {
             renderer.line_with_style(style, &display)?;
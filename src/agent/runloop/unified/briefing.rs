@@ -0,0 +1,51 @@
+use anyhow::Result;
+use std::path::Path;
+use vtcode_core::core::briefing::{Briefing, WorkspaceCheckpoint};
+use vtcode_core::core::change_ledger::ChangeLedger;
+use vtcode_core::core::evidence::{EvidenceKind, EvidenceLog};
+use vtcode_core::utils::ansi::{AnsiRenderer, MessageStyle};
+
+/// Briefing on the commits that landed since the previous session here.
+pub(crate) fn load_briefing(workspace: &Path) -> Option<Briefing> {
+    let checkpoint = WorkspaceCheckpoint::load(workspace)?;
+    Briefing::build(workspace, &checkpoint, true)
+}
+
+pub(crate) fn render_briefing(
+    briefing: Option<&Briefing>,
+    renderer: &mut AnsiRenderer,
+) -> Result<()> {
+    let Some(briefing) = briefing else {
+        return renderer.line(
+            MessageStyle::Info,
+            "No new commits since your last session in this workspace.",
+        );
+    };
+    for line in briefing.render_lines() {
+        renderer.line(MessageStyle::Info, &line)?;
+    }
+    renderer.line_if_not_empty(MessageStyle::Output)
+}
+
+/// Remember where this session left the workspace for the next briefing.
+pub(crate) fn save_checkpoint(
+    workspace: &Path,
+    changes: &ChangeLedger,
+    evidence: &EvidenceLog,
+) -> Result<()> {
+    let mut files: Vec<String> = changes.files().into_iter().map(|file| file.path).collect();
+    files.extend(
+        evidence
+            .entries()
+            .iter()
+            .filter(|entry| entry.kind == EvidenceKind::Read && !entry.summary.is_empty())
+            .map(|entry| {
+                let path = Path::new(&entry.summary);
+                path.strip_prefix(workspace)
+                    .unwrap_or(path)
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            }),
+    );
+    WorkspaceCheckpoint::capture(workspace, files, changes.requests()).save(workspace)
+}
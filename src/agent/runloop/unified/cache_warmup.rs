@@ -0,0 +1,176 @@
+use std::time::Duration;
+
+use vtcode_core::config::constants::context as context_defaults;
+use vtcode_core::config::core::PromptCacheWarmupConfig;
+use vtcode_core::config::loader::VTCodeConfig;
+use vtcode_core::config::types::AgentConfig as CoreAgentConfig;
+use vtcode_core::llm::provider as uni;
+
+const WARMUP_MESSAGE: &str = "Reply with OK.";
+
+/// Result of the session-start cache warming request.
+#[derive(Debug, PartialEq)]
+pub(crate) enum WarmupOutcome {
+    Skipped(String),
+    Warmed {
+        prefix_tokens: usize,
+        cache_write_tokens: Option<u32>,
+        cache_read_tokens: Option<u32>,
+    },
+    Failed(String),
+}
+
+impl WarmupOutcome {
+    /// Status line for the transcript; skips stay quiet.
+    pub(crate) fn message(&self) -> Option<String> {
+        match self {
+            Self::Skipped(_) => None,
+            Self::Warmed {
+                prefix_tokens,
+                cache_write_tokens,
+                cache_read_tokens,
+            } => {
+                let detail = match (cache_write_tokens, cache_read_tokens) {
+                    (_, Some(read)) if *read > 0 => format!("{read} tokens already cached"),
+                    (Some(written), _) if *written > 0 => format!("{written} tokens cached"),
+                    _ => format!("~{prefix_tokens} tokens"),
+                };
+                Some(format!("Prompt cache warmed ({detail})."))
+            }
+            Self::Failed(error) => Some(format!("Prompt cache warm-up failed: {error}")),
+        }
+    }
+}
+
+/// Send the stable prefix of every request (system prompt and tool schemas)
+/// with a one-token completion so the first user turn reads it from the cache.
+pub(crate) async fn warm_prompt_cache(
+    provider: &dyn uni::LLMProvider,
+    config: &CoreAgentConfig,
+    vt_cfg: Option<&VTCodeConfig>,
+    system_prompt: &str,
+    tools: &[uni::ToolDefinition],
+) -> WarmupOutcome {
+    let warmup = &config.prompt_cache.warmup;
+    if !config.prompt_cache.enabled || !warmup.enabled {
+        return WarmupOutcome::Skipped("disabled".to_string());
+    }
+    if !provider.supports_prompt_caching(&config.model) {
+        return WarmupOutcome::Skipped(format!(
+            "{} does not cache prompts for {}",
+            provider.name(),
+            config.model
+        ));
+    }
+    let prefix_tokens = estimate_prefix_tokens(system_prompt, tools);
+    if let Some(reason) = skip_reason(warmup, prefix_tokens, tightest_budget(vt_cfg)) {
+        return WarmupOutcome::Skipped(reason);
+    }
+
+    let request = uni::LLMRequest {
+        messages: vec![uni::Message::user(WARMUP_MESSAGE.to_string())],
+        system_prompt: Some(system_prompt.to_string()),
+        tools: Some(tools.to_vec()),
+        model: config.model.clone(),
+        max_tokens: Some(1),
+        temperature: Some(0.7),
+        stream: false,
+        tool_choice: Some(uni::ToolChoice::auto()),
+        parallel_tool_calls: None,
+        parallel_tool_config: None,
+        reasoning_effort: None,
+        json_mode: false,
+        response_schema: None,
+        code_execution: false,
+        seed: None,
+    };
+    let timeout = Duration::from_millis(warmup.timeout_ms);
+    match tokio::time::timeout(timeout, provider.generate(request)).await {
+        Ok(Ok(response)) => {
+            let usage = response.usage.as_ref();
+            WarmupOutcome::Warmed {
+                prefix_tokens,
+                cache_write_tokens: usage.and_then(|usage| usage.cache_creation_tokens),
+                cache_read_tokens: usage
+                    .and_then(|usage| usage.cache_read_tokens.or(usage.cached_prompt_tokens)),
+            }
+        }
+        Ok(Err(error)) => WarmupOutcome::Failed(error.to_string()),
+        Err(_) => WarmupOutcome::Failed(format!("timed out after {}ms", warmup.timeout_ms)),
+    }
+}
+
+fn estimate_prefix_tokens(system_prompt: &str, tools: &[uni::ToolDefinition]) -> usize {
+    let tool_chars = serde_json::to_string(tools)
+        .map(|json| json.len())
+        .unwrap_or(0);
+    (system_prompt.len() + tool_chars).div_ceil(context_defaults::CHAR_PER_TOKEN_APPROX)
+}
+
+/// The smallest per-request token cap configured under `[router.budgets]`.
+fn tightest_budget(vt_cfg: Option<&VTCodeConfig>) -> Option<usize> {
+    vt_cfg?
+        .router
+        .budgets
+        .values()
+        .filter_map(|budget| budget.max_tokens)
+        .min()
+}
+
+fn skip_reason(
+    config: &PromptCacheWarmupConfig,
+    prefix_tokens: usize,
+    budget_cap: Option<usize>,
+) -> Option<String> {
+    if prefix_tokens < config.min_prefix_tokens as usize {
+        return Some(format!(
+            "prefix of ~{prefix_tokens} tokens is below the {} token caching minimum",
+            config.min_prefix_tokens
+        ));
+    }
+    if prefix_tokens > config.max_prefix_tokens as usize {
+        return Some(format!(
+            "prefix of ~{prefix_tokens} tokens exceeds warmup.max_prefix_tokens ({})",
+            config.max_prefix_tokens
+        ));
+    }
+    match budget_cap {
+        Some(cap) if cap < prefix_tokens => Some(format!(
+            "router budget caps requests at {cap} tokens, below the ~{prefix_tokens} token prefix"
+        )),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_short_oversized_and_over_budget_prefixes() {
+        let config = PromptCacheWarmupConfig::default();
+        assert!(skip_reason(&config, 4_000, None).is_none());
+        assert!(skip_reason(&config, 4_000, Some(8_000)).is_none());
+        assert!(skip_reason(&config, 200, None).is_some());
+        assert!(skip_reason(&config, 100_000, None).is_some());
+        assert!(
+            skip_reason(&config, 4_000, Some(2_000))
+                .unwrap()
+                .contains("router budget")
+        );
+    }
+
+    #[test]
+    fn reports_cache_reads_before_writes() {
+        let outcome = WarmupOutcome::Warmed {
+            prefix_tokens: 3_000,
+            cache_write_tokens: Some(0),
+            cache_read_tokens: Some(2_900),
+        };
+        assert_eq!(
+            outcome.message().as_deref(),
+            Some("Prompt cache warmed (2900 tokens already cached).")
+        );
+        assert_eq!(WarmupOutcome::Skipped("disabled".into()).message(), None);
+    }
+}
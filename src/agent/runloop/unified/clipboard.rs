@@ -0,0 +1,109 @@
+use anyhow::Result;
+
+use vtcode_core::llm::provider as uni;
+use vtcode_core::ui::tui::{RatatuiCopyTarget, RatatuiHandle};
+use vtcode_core::utils::ansi::{AnsiRenderer, MessageStyle};
+use vtcode_core::utils::clipboard::{self, CopyMethod};
+
+const DISABLED_MESSAGE: &str =
+    "Clipboard access is disabled. Set `clipboard = true` under [ui] in vtcode.toml to enable it.";
+
+/// Copy the selection, the last reply or its last code block to the clipboard.
+pub(crate) fn copy_to_clipboard(
+    target: RatatuiCopyTarget,
+    history: &[uni::Message],
+    enabled: bool,
+    handle: &RatatuiHandle,
+    renderer: &mut AnsiRenderer,
+) -> Result<()> {
+    if !enabled {
+        renderer.line(MessageStyle::Info, DISABLED_MESSAGE)?;
+        return Ok(());
+    }
+    let label = match target {
+        RatatuiCopyTarget::Selection(_) => "selection",
+        RatatuiCopyTarget::LastReply => "last reply",
+        RatatuiCopyTarget::LastCodeBlock => "last code block",
+    };
+    let Some(text) = copy_text(target, history) else {
+        renderer.line(MessageStyle::Info, &format!("No {label} to copy yet."))?;
+        return Ok(());
+    };
+
+    let lines = text.lines().count();
+    let message = match clipboard::copy(&text) {
+        CopyMethod::System(program) => {
+            format!("Copied {label} ({lines} lines) to the clipboard with {program}.")
+        }
+        CopyMethod::Osc52 => {
+            handle.set_terminal_clipboard(text);
+            format!("Sent {label} ({lines} lines) to the terminal clipboard (OSC 52).")
+        }
+    };
+    renderer.line(MessageStyle::Info, &message)
+}
+
+/// Insert the clipboard into the input as a fenced block.
+pub(crate) fn paste_from_clipboard(
+    enabled: bool,
+    handle: &RatatuiHandle,
+    renderer: &mut AnsiRenderer,
+) -> Result<()> {
+    if !enabled {
+        return renderer.line(MessageStyle::Info, DISABLED_MESSAGE);
+    }
+    match clipboard::paste() {
+        Ok(text) if text.trim().is_empty() => {
+            renderer.line(MessageStyle::Info, "Clipboard is empty.")
+        }
+        Ok(text) => {
+            let lines = text.trim_end_matches('\n').lines().count();
+            handle.insert_input(clipboard::fenced_block(&text));
+            renderer.line(
+                MessageStyle::Info,
+                &format!(
+                    "Inserted {lines} lines from the clipboard. Add your question and press Enter."
+                ),
+            )
+        }
+        Err(err) => renderer.line(MessageStyle::Error, &format!("Paste failed: {err}")),
+    }
+}
+
+fn copy_text(target: RatatuiCopyTarget, history: &[uni::Message]) -> Option<String> {
+    let mut replies = history
+        .iter()
+        .rev()
+        .filter(|message| message.role == uni::MessageRole::Assistant)
+        .map(|message| message.content.trim())
+        .filter(|content| !content.is_empty());
+    match target {
+        RatatuiCopyTarget::Selection(text) => Some(text),
+        RatatuiCopyTarget::LastReply => replies.next().map(str::to_string),
+        RatatuiCopyTarget::LastCodeBlock => replies.find_map(clipboard::last_code_block),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copies_latest_reply_and_searches_back_for_code() {
+        let history = vec![
+            uni::Message::user("hi".to_string()),
+            uni::Message::assistant("Run:\n```sh\ncargo test\n```".to_string()),
+            uni::Message::user("thanks".to_string()),
+            uni::Message::assistant("You're welcome.".to_string()),
+        ];
+        assert_eq!(
+            copy_text(RatatuiCopyTarget::LastReply, &history).as_deref(),
+            Some("You're welcome.")
+        );
+        assert_eq!(
+            copy_text(RatatuiCopyTarget::LastCodeBlock, &history).as_deref(),
+            Some("cargo test")
+        );
+        assert_eq!(copy_text(RatatuiCopyTarget::LastReply, &history[..1]), None);
+    }
+}
@@ -0,0 +1,146 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+use vtcode_core::config::core::CodeExecutionConfig;
+use vtcode_core::config::loader::VTCodeConfig;
+use vtcode_core::llm::provider::CodeExecutionStep;
+use vtcode_core::utils::ansi::{AnsiRenderer, MessageStyle};
+use vtcode_core::utils::scratch::save_generated_file;
+
+const MAX_OUTPUT_LINES: usize = 40;
+
+/// Shows steps of the provider's hosted code interpreter in the transcript and
+/// saves the files it produces to the scratch directory.
+pub(crate) struct CodeExecutionOutput {
+    enabled: bool,
+    workspace: PathBuf,
+    scratch_dir: PathBuf,
+}
+
+impl CodeExecutionOutput {
+    pub(crate) fn new(workspace: &Path, vt_cfg: Option<&VTCodeConfig>) -> Self {
+        let config: CodeExecutionConfig = vt_cfg
+            .map(|cfg| cfg.tools.code_execution.clone())
+            .unwrap_or_default();
+        Self {
+            enabled: config.enabled,
+            workspace: workspace.to_path_buf(),
+            scratch_dir: workspace.join(config.scratch_dir),
+        }
+    }
+
+    /// Whether requests should offer the provider's code interpreter.
+    pub(crate) fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub(crate) fn render(
+        &self,
+        renderer: &mut AnsiRenderer,
+        step: &CodeExecutionStep,
+    ) -> Result<()> {
+        let saved = match step {
+            CodeExecutionStep::File(file) => Some(
+                save_generated_file(&self.scratch_dir, file)
+                    .map(|path| {
+                        path.strip_prefix(&self.workspace)
+                            .map(Path::to_path_buf)
+                            .unwrap_or(path)
+                    })
+                    .map_err(|err| format!("{err:#}")),
+            ),
+            _ => None,
+        };
+        renderer.line_if_not_empty(MessageStyle::CodeExecution)?;
+        for line in step_lines(step, saved) {
+            renderer.line(MessageStyle::CodeExecution, &line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Transcript lines for one step; `saved` is where a generated file went.
+fn step_lines(step: &CodeExecutionStep, saved: Option<Result<PathBuf, String>>) -> Vec<String> {
+    let (mut lines, body) = match step {
+        CodeExecutionStep::Code { language, code } => (
+            vec![format!(
+                "Code interpreter ran {}:",
+                language.as_deref().unwrap_or("code")
+            )],
+            code.as_str(),
+        ),
+        CodeExecutionStep::Output { output, succeeded } => {
+            let header = if *succeeded { "Output:" } else { "Failed:" };
+            if output.trim().is_empty() {
+                return vec![format!("{header} (nothing printed)")];
+            }
+            (vec![header.to_string()], output.as_str())
+        }
+        CodeExecutionStep::File(file) => {
+            let kind = file.mime_type.as_deref().unwrap_or("file");
+            let line = match saved {
+                Some(Ok(path)) => format!(
+                    "Generated {kind} ({} bytes) saved to {}",
+                    file.data.len(),
+                    path.display()
+                ),
+                Some(Err(err)) => format!("Generated {kind} could not be saved: {err}"),
+                None => format!("Generated {kind} ({} bytes)", file.data.len()),
+            };
+            return vec![line];
+        }
+    };
+
+    let body_lines: Vec<&str> = body.trim_end().lines().collect();
+    lines.extend(
+        body_lines
+            .iter()
+            .take(MAX_OUTPUT_LINES)
+            .map(|line| format!("    {line}")),
+    );
+    if body_lines.len() > MAX_OUTPUT_LINES {
+        lines.push(format!(
+            "    ... {} more lines",
+            body_lines.len() - MAX_OUTPUT_LINES
+        ));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vtcode_core::llm::provider::GeneratedFile;
+
+    #[test]
+    fn formats_code_output_and_saved_files() {
+        let code = CodeExecutionStep::Code {
+            language: Some("python".to_string()),
+            code: "import math\nprint(math.pi)\n".to_string(),
+        };
+        assert_eq!(
+            step_lines(&code, None),
+            [
+                "Code interpreter ran python:",
+                "    import math",
+                "    print(math.pi)"
+            ]
+        );
+
+        let failed = CodeExecutionStep::Output {
+            output: String::new(),
+            succeeded: false,
+        };
+        assert_eq!(step_lines(&failed, None), ["Failed: (nothing printed)"]);
+
+        let file = CodeExecutionStep::File(GeneratedFile {
+            name: None,
+            mime_type: Some("image/png".to_string()),
+            data: vec![0; 12],
+        });
+        assert_eq!(
+            step_lines(&file, Some(Ok(PathBuf::from(".vtcode/scratch/output.png")))),
+            ["Generated image/png (12 bytes) saved to .vtcode/scratch/output.png"]
+        );
+    }
+}
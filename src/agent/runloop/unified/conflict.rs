@@ -0,0 +1,251 @@
+use anyhow::Result;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::Notify;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::task;
+
+use vtcode_core::tools::EditConflict;
+use vtcode_core::ui::tui::{RatatuiEvent, RatatuiHandle};
+use vtcode_core::utils::ansi::{AnsiRenderer, MessageStyle};
+
+const CONFLICT_PLACEHOLDER: &str =
+    "Keep [m]ine · take [a]gent · restore [b]ase · write conflict [c] markers (Esc keeps mine)";
+const MAX_REGION_LINES: usize = 12;
+
+/// Which version of a conflicted file ends up on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ConflictChoice {
+    /// The user's version, already on disk
+    Mine,
+    Agent,
+    Base,
+    /// Both versions with conflict markers, to resolve in an editor
+    Markers,
+}
+
+impl ConflictChoice {
+    fn parse(input: &str) -> Option<Self> {
+        match input.trim().to_lowercase().as_str() {
+            "m" | "mine" => Some(Self::Mine),
+            "a" | "agent" => Some(Self::Agent),
+            "b" | "base" => Some(Self::Base),
+            "c" | "markers" | "conflict" => Some(Self::Markers),
+            _ => None,
+        }
+    }
+
+    /// The content to write, or `None` to leave the file as it is.
+    pub(crate) fn content(self, conflict: &EditConflict) -> Option<&str> {
+        match self {
+            Self::Mine => None,
+            Self::Agent => Some(&conflict.agent),
+            Self::Base => Some(&conflict.base),
+            Self::Markers => Some(&conflict.merged),
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Mine => "mine",
+            Self::Agent => "agent",
+            Self::Base => "base",
+            Self::Markers => "markers",
+        }
+    }
+
+    /// What the model is told the file now holds.
+    fn note(self) -> &'static str {
+        match self {
+            Self::Mine => {
+                "The user kept their version; your edit was not applied. Read the file again and redo the edit on top of it if it is still needed."
+            }
+            Self::Agent => "The user chose your version; it was written over their changes.",
+            Self::Base => {
+                "The user restored the version you last read; both their changes and your edit were discarded."
+            }
+            Self::Markers => {
+                "The user will resolve the conflict in their editor; the file now contains conflict markers. Do not edit it until they are done."
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ConflictOutcome {
+    Resolved(ConflictChoice),
+    Exit,
+    Interrupt,
+}
+
+/// Lines describing each overlapping region as mine, agent and base.
+pub(crate) fn build_conflict_lines(conflict: &EditConflict) -> Vec<String> {
+    let mut lines = vec![format!(
+        "{} was changed outside the agent while it was editing; {} region(s) overlap:",
+        conflict.path,
+        conflict.hunks.len()
+    )];
+    for hunk in &conflict.hunks {
+        lines.push(format!("── line {} ──", hunk.line));
+        for (label, region) in [
+            ("mine", &hunk.mine),
+            ("agent", &hunk.agent),
+            ("base", &hunk.base),
+        ] {
+            lines.push(format!("{label}:"));
+            if region.is_empty() {
+                lines.push("    (empty)".to_string());
+                continue;
+            }
+            let region_lines: Vec<&str> = region.lines().collect();
+            lines.extend(
+                region_lines
+                    .iter()
+                    .take(MAX_REGION_LINES)
+                    .map(|line| format!("    {line}")),
+            );
+            if region_lines.len() > MAX_REGION_LINES {
+                lines.push(format!(
+                    "    ... {} more lines",
+                    region_lines.len() - MAX_REGION_LINES
+                ));
+            }
+        }
+    }
+    lines
+}
+
+/// Replace the full versions in a conflict tool error with the user's choice,
+/// keeping only the overlapping regions for the model.
+pub(crate) fn record_resolution(
+    output: &mut Value,
+    conflict: &EditConflict,
+    choice: ConflictChoice,
+) {
+    output["error"]["conflict"] = json!({
+        "path": conflict.path,
+        "hunks": conflict.hunks,
+        "resolution": choice.as_str(),
+        "note": choice.note(),
+    });
+}
+
+/// Show the overlapping regions of an edit conflict and ask which version to
+/// keep. Callers restore the input placeholder afterwards.
+pub(crate) async fn resolve_edit_conflict(
+    conflict: &EditConflict,
+    renderer: &mut AnsiRenderer,
+    handle: &RatatuiHandle,
+    events: &mut UnboundedReceiver<RatatuiEvent>,
+    ctrl_c_flag: &Arc<AtomicBool>,
+    ctrl_c_notify: &Arc<Notify>,
+) -> Result<ConflictOutcome> {
+    renderer.line_if_not_empty(MessageStyle::Info)?;
+    for (index, line) in build_conflict_lines(conflict).iter().enumerate() {
+        let style = if index == 0 {
+            MessageStyle::Error
+        } else if line.starts_with("──") {
+            MessageStyle::Tool
+        } else {
+            MessageStyle::Output
+        };
+        renderer.line(style, line)?;
+    }
+    renderer.line(MessageStyle::Info, "")?;
+
+    handle.set_placeholder(Some(CONFLICT_PLACEHOLDER.to_string()));
+    task::yield_now().await;
+
+    loop {
+        if ctrl_c_flag.load(Ordering::SeqCst) {
+            return Ok(ConflictOutcome::Interrupt);
+        }
+
+        let notify = ctrl_c_notify.clone();
+        let maybe_event = tokio::select! {
+            _ = notify.notified(), if !ctrl_c_flag.load(Ordering::SeqCst) => None,
+            event = events.recv() => event,
+        };
+
+        let Some(event) = maybe_event else {
+            if ctrl_c_flag.load(Ordering::SeqCst) {
+                return Ok(ConflictOutcome::Interrupt);
+            }
+            return Ok(ConflictOutcome::Exit);
+        };
+
+        match event {
+            RatatuiEvent::Submit(input) => match ConflictChoice::parse(&input) {
+                Some(choice) => return Ok(ConflictOutcome::Resolved(choice)),
+                None => renderer.line(
+                    MessageStyle::Info,
+                    "Respond with 'm' (mine), 'a' (agent), 'b' (base) or 'c' (conflict markers).",
+                )?,
+            },
+            RatatuiEvent::Cancel => return Ok(ConflictOutcome::Resolved(ConflictChoice::Mine)),
+            RatatuiEvent::Exit => return Ok(ConflictOutcome::Exit),
+            RatatuiEvent::Interrupt => return Ok(ConflictOutcome::Interrupt),
+            RatatuiEvent::ExplainSelection(_)
+            | RatatuiEvent::Copy(_)
+            | RatatuiEvent::Fork(_)
+            | RatatuiEvent::Undo
+            | RatatuiEvent::ToggleVoice
+            | RatatuiEvent::ScrollLineUp
+            | RatatuiEvent::ScrollLineDown
+            | RatatuiEvent::ScrollPageUp
+            | RatatuiEvent::ScrollPageDown => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vtcode_core::tools::edit_conflict::ConflictHunk;
+
+    #[test]
+    fn renders_regions_and_records_the_choice() {
+        let conflict = EditConflict {
+            path: "src/lib.rs".to_string(),
+            base: "a\nb\n".to_string(),
+            mine: "a\nB\n".to_string(),
+            agent: "a\nbee\n".to_string(),
+            merged: "a\n<<<<<<< mine\nB\n||||||| base\nb\n=======\nbee\n>>>>>>> agent\n"
+                .to_string(),
+            hunks: vec![ConflictHunk {
+                line: 2,
+                base: "b\n".to_string(),
+                mine: "B\n".to_string(),
+                agent: "bee\n".to_string(),
+            }],
+        };
+        let lines = build_conflict_lines(&conflict);
+        assert_eq!(
+            lines[1..],
+            [
+                "── line 2 ──",
+                "mine:",
+                "    B",
+                "agent:",
+                "    bee",
+                "base:",
+                "    b"
+            ]
+        );
+
+        assert_eq!(ConflictChoice::parse(" A "), Some(ConflictChoice::Agent));
+        assert_eq!(ConflictChoice::parse("x"), None);
+        assert_eq!(
+            ConflictChoice::Markers.content(&conflict),
+            Some(conflict.merged.as_str())
+        );
+        assert_eq!(ConflictChoice::Mine.content(&conflict), None);
+
+        let mut output = json!({ "error": { "conflict": conflict.to_json_value() } });
+        record_resolution(&mut output, &conflict, ConflictChoice::Mine);
+        assert_eq!(output["error"]["conflict"]["resolution"], "mine");
+        assert!(output["error"]["conflict"].get("agent").is_none());
+        assert!(EditConflict::from_tool_output(&output).is_none());
+    }
+}
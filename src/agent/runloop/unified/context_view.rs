@@ -0,0 +1,116 @@
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use vtcode_core::config::constants::context as context_defaults;
+use vtcode_core::core::provenance::Provenance;
+use vtcode_core::core::token_calibration::estimate_message_tokens;
+use vtcode_core::llm::provider as uni;
+use vtcode_core::project_doc;
+use vtcode_core::utils::ansi::{AnsiRenderer, MessageStyle};
+
+use crate::agent::runloop::context::ContextTrimConfig;
+
+/// Largest blocks listed under each group.
+const LARGEST_LISTED: usize = 3;
+
+/// Blocks of one provenance category as `(citation, tokens)`.
+type Group = (&'static str, Vec<(String, usize)>);
+
+/// What makes up the context of the next request.
+pub(crate) struct ContextSources<'a> {
+    pub(crate) history: &'a [uni::Message],
+    pub(crate) system_prompt: &'a str,
+    pub(crate) summary: Option<&'a str>,
+    /// Blocks context providers added to the last request
+    pub(crate) gathered: &'a [(Provenance, usize)],
+    pub(crate) workspace: &'a Path,
+    pub(crate) project_doc_max_bytes: usize,
+    pub(crate) trim: ContextTrimConfig,
+}
+
+/// List the context blocks grouped by where they came from, in the order
+/// they are trimmed under budget pressure.
+pub(crate) fn show_context(sources: ContextSources<'_>, renderer: &mut AnsiRenderer) -> Result<()> {
+    let mut groups: BTreeMap<u8, Group> = BTreeMap::new();
+    let mut add = |provenance: &Provenance, tokens: usize| {
+        groups
+            .entry(provenance.trim_rank())
+            .or_insert_with(|| (provenance.category(), Vec::new()))
+            .1
+            .push((provenance.citation(), tokens));
+    };
+    for message in sources.history {
+        let tokens =
+            (estimate_message_tokens(message) as f64 * sources.trim.token_scale).ceil() as usize;
+        add(&message.provenance(), tokens);
+    }
+    for (provenance, tokens) in sources.gathered {
+        add(provenance, *tokens);
+    }
+    if let Some(summary) = sources.summary {
+        add(&Provenance::Memory, text_tokens(summary));
+    }
+    // Project docs are part of the instructions; they are counted once, as docs.
+    let mut doc_tokens = 0;
+    if let Ok(Some(bundle)) =
+        project_doc::read_project_doc(sources.workspace, sources.project_doc_max_bytes)
+    {
+        doc_tokens = text_tokens(&bundle.contents);
+        let paths = bundle
+            .sources
+            .iter()
+            .map(|path| {
+                let path = path.strip_prefix(sources.workspace).unwrap_or(path);
+                path.display().to_string()
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        add(&Provenance::ProjectDoc { path: paths }, doc_tokens);
+    }
+
+    let system_tokens = text_tokens(sources.system_prompt).saturating_sub(doc_tokens);
+    let total: usize = system_tokens
+        + groups
+            .values()
+            .flat_map(|(_, blocks)| blocks.iter().map(|(_, tokens)| tokens))
+            .sum::<usize>();
+    renderer.line(
+        MessageStyle::Info,
+        &format!(
+            "Context: ~{total} of {} tokens in {} messages",
+            sources.trim.max_tokens,
+            sources.history.len()
+        ),
+    )?;
+    renderer.line(
+        MessageStyle::Info,
+        &format!("  instructions: ~{system_tokens} tokens"),
+    )?;
+    for (category, blocks) in groups.values_mut() {
+        let tokens: usize = blocks.iter().map(|(_, tokens)| tokens).sum();
+        renderer.line(
+            MessageStyle::Info,
+            &format!("  {category}: {} block(s), ~{tokens} tokens", blocks.len()),
+        )?;
+        blocks.sort_by(|a, b| b.1.cmp(&a.1));
+        for (citation, tokens) in blocks.iter().take(LARGEST_LISTED) {
+            renderer.line(
+                MessageStyle::Info,
+                &format!("    [{citation}] ~{tokens} tokens"),
+            )?;
+        }
+    }
+    renderer.line(
+        MessageStyle::Info,
+        &format!(
+            "Over budget, messages older than the last {} are trimmed in the order above, \
+             tool results first and your own messages last.",
+            sources.trim.preserve_recent_turns
+        ),
+    )
+}
+
+fn text_tokens(text: &str) -> usize {
+    text.len().div_ceil(context_defaults::CHAR_PER_TOKEN_APPROX)
+}
@@ -0,0 +1,387 @@
+use anyhow::{Context, Result, anyhow};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::Notify;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::task::{self, JoinHandle};
+use unicode_width::UnicodeWidthChar;
+
+use vtcode_core::config::api_keys::{ApiKeySources, get_api_key};
+use vtcode_core::config::core::{AgentDualAnswerConfig, DualAnswerLayout};
+use vtcode_core::config::types::AgentConfig as CoreAgentConfig;
+use vtcode_core::llm::factory::create_provider_with_config;
+use vtcode_core::llm::provider::{self as uni, LLMError};
+use vtcode_core::models::ModelId;
+use vtcode_core::ui::tui::{RatatuiEvent, RatatuiHandle};
+use vtcode_core::utils::ansi::{AnsiRenderer, MessageStyle};
+
+use crate::agent::runloop::slash_commands::DualAnswerRequest;
+
+const COLUMN_GAP: &str = " │ ";
+const COLLAPSED_LINES: usize = 3;
+const DEFAULT_WIDTH: usize = 100;
+
+/// A second model asked the same prompts as the session's model.
+pub(crate) struct DualAnswer {
+    provider: Arc<dyn uni::LLMProvider>,
+    pub(crate) model: String,
+    layout: DualAnswerLayout,
+}
+
+impl DualAnswer {
+    /// Connect to `model`, on the provider from `settings`, the model id, or
+    /// the session's provider, in that order.
+    pub(crate) fn connect(
+        settings: &AgentDualAnswerConfig,
+        model: &str,
+        config: &CoreAgentConfig,
+    ) -> Result<Self> {
+        let model = model.trim();
+        if model.is_empty() {
+            return Err(anyhow!(
+                "No second model configured. Set `model` under [agent.dual_answer] \
+                 or use /dual <model>."
+            ));
+        }
+        Ok(Self {
+            provider: connect_model(&settings.provider, model, config)?,
+            model: model.to_string(),
+            layout: settings.layout,
+        })
+    }
+
+    /// Start asking the second model, without tools, while the turn runs.
+    pub(crate) fn spawn(
+        &self,
+        messages: Vec<uni::Message>,
+        system_prompt: String,
+    ) -> PendingAnswer {
+        let provider = self.provider.clone();
+        let request = uni::LLMRequest {
+            messages,
+            system_prompt: Some(system_prompt),
+            tools: None,
+            model: self.model.clone(),
+            max_tokens: Some(2000),
+            temperature: Some(0.7),
+            stream: false,
+            tool_choice: Some(uni::ToolChoice::none()),
+            parallel_tool_calls: None,
+            parallel_tool_config: None,
+            reasoning_effort: None,
+            json_mode: false,
+            response_schema: None,
+            code_execution: false,
+            seed: None,
+        };
+        PendingAnswer(tokio::spawn(async move {
+            let response = provider.generate(request).await?;
+            Ok(response.content.unwrap_or_default())
+        }))
+    }
+}
+
+/// A client for `model` on `provider`, or when that is empty on the provider
+/// the model id names, falling back to the session's. The session's key and
+/// base URL are reused for its own provider.
+pub(crate) fn connect_model(
+    provider: &str,
+    model: &str,
+    config: &CoreAgentConfig,
+) -> Result<Arc<dyn uni::LLMProvider>> {
+    let provider_name = if !provider.trim().is_empty() {
+        provider.trim().to_lowercase()
+    } else if let Ok(id) = model.parse::<ModelId>() {
+        id.provider().to_string()
+    } else {
+        config.provider.to_lowercase()
+    };
+    let (api_key, base_url) = if provider_name.eq_ignore_ascii_case(&config.provider) {
+        (config.api_key.clone(), config.base_url.clone())
+    } else {
+        (
+            get_api_key(&provider_name, &ApiKeySources::default())?,
+            None,
+        )
+    };
+    let provider = create_provider_with_config(
+        &provider_name,
+        Some(api_key),
+        base_url,
+        Some(model.to_string()),
+        Some(config.prompt_cache.clone()),
+    )
+    .with_context(|| format!("Failed to initialize {provider_name} for {model}"))?;
+    Ok(Arc::from(provider))
+}
+
+/// Apply `/dual`: show the second model, or turn the mode on or off.
+pub(crate) fn handle_dual_request(
+    request: DualAnswerRequest,
+    dual: &mut Option<DualAnswer>,
+    settings: &AgentDualAnswerConfig,
+    config: &CoreAgentConfig,
+    renderer: &mut AnsiRenderer,
+) -> Result<()> {
+    let model = match request {
+        DualAnswerRequest::Status => {
+            let message = match dual.as_ref() {
+                Some(dual) => format!(
+                    "Second answers from {}. Turn off with /dual off.",
+                    dual.model
+                ),
+                None => {
+                    "Second answers are off. Turn on with /dual on or /dual <model>.".to_string()
+                }
+            };
+            return renderer.line(MessageStyle::Info, &message);
+        }
+        DualAnswerRequest::Disable => {
+            *dual = None;
+            return renderer.line(MessageStyle::Info, "Second answers turned off.");
+        }
+        DualAnswerRequest::Enable => settings.model.clone(),
+        DualAnswerRequest::Model(model) => model,
+    };
+    match DualAnswer::connect(settings, &model, config) {
+        Ok(connected) => {
+            renderer.line(
+                MessageStyle::Info,
+                &format!(
+                    "{} will also answer each prompt; pick the answer to keep after each turn.",
+                    connected.model
+                ),
+            )?;
+            *dual = Some(connected);
+        }
+        Err(err) => renderer.line(MessageStyle::Error, &format!("{err:#}"))?,
+    }
+    Ok(())
+}
+
+/// The second model's answer in flight; dropping it cancels the request.
+pub(crate) struct PendingAnswer(JoinHandle<Result<String, LLMError>>);
+
+impl Drop for PendingAnswer {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AnswerChoice {
+    Primary,
+    Secondary,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PickOutcome {
+    Answered(AnswerChoice),
+    Exit,
+    Interrupt,
+}
+
+/// Show the second model's answer next to `primary` and ask which one enters
+/// the conversation. Returns the text to keep. Callers restore the input
+/// placeholder afterwards.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn pick_answer(
+    dual: &DualAnswer,
+    mut pending: PendingAnswer,
+    primary_model: &str,
+    primary: String,
+    renderer: &mut AnsiRenderer,
+    handle: &RatatuiHandle,
+    events: &mut UnboundedReceiver<RatatuiEvent>,
+    ctrl_c_flag: &Arc<AtomicBool>,
+    ctrl_c_notify: &Arc<Notify>,
+) -> Result<(String, PickOutcome)> {
+    handle.set_placeholder(Some(format!("Waiting for {}…", dual.model)));
+    let notify = ctrl_c_notify.clone();
+    let secondary = tokio::select! {
+        _ = notify.notified(), if !ctrl_c_flag.load(Ordering::SeqCst) => {
+            return Ok((primary, PickOutcome::Interrupt));
+        }
+        joined = &mut pending.0 => joined,
+    };
+    let secondary = match secondary {
+        Ok(result) => result.map_err(anyhow::Error::from),
+        Err(err) => Err(err.into()),
+    };
+    let secondary = match secondary {
+        Ok(text) if !text.trim().is_empty() => text,
+        Ok(_) => {
+            renderer.line(
+                MessageStyle::Info,
+                &format!("{} returned an empty answer.", dual.model),
+            )?;
+            return Ok((primary, PickOutcome::Answered(AnswerChoice::Primary)));
+        }
+        Err(err) => {
+            renderer.line(
+                MessageStyle::Error,
+                &format!("{} could not answer: {}", dual.model, err),
+            )?;
+            return Ok((primary, PickOutcome::Answered(AnswerChoice::Primary)));
+        }
+    };
+
+    renderer.line_if_not_empty(MessageStyle::Info)?;
+    let left = format!("[1] {primary_model}");
+    let right = format!("[2] {}", dual.model);
+    match dual.layout {
+        DualAnswerLayout::SideBySide => {
+            let width = crossterm::terminal::size()
+                .map(|(columns, _)| columns as usize)
+                .unwrap_or(DEFAULT_WIDTH);
+            for line in side_by_side(
+                &format!("{left}\n\n{primary}"),
+                &format!("{right}\n\n{secondary}"),
+                width,
+            ) {
+                renderer.line(MessageStyle::Response, &line)?;
+            }
+        }
+        DualAnswerLayout::Collapsed => render_collapsed(&right, &secondary, renderer)?,
+    }
+
+    handle.set_placeholder(Some(format!(
+        "Keep which answer: [1] {primary_model} · [2] {}{}",
+        dual.model,
+        if dual.layout == DualAnswerLayout::Collapsed {
+            " · [v]iew [2] in full"
+        } else {
+            ""
+        }
+    )));
+    task::yield_now().await;
+
+    loop {
+        if ctrl_c_flag.load(Ordering::SeqCst) {
+            return Ok((primary, PickOutcome::Interrupt));
+        }
+        let notify = ctrl_c_notify.clone();
+        let maybe_event = tokio::select! {
+            _ = notify.notified(), if !ctrl_c_flag.load(Ordering::SeqCst) => None,
+            event = events.recv() => event,
+        };
+        let Some(event) = maybe_event else {
+            if ctrl_c_flag.load(Ordering::SeqCst) {
+                return Ok((primary, PickOutcome::Interrupt));
+            }
+            return Ok((primary, PickOutcome::Exit));
+        };
+
+        match event {
+            RatatuiEvent::Submit(input) => match input.trim().to_lowercase().as_str() {
+                "1" => return Ok((primary, PickOutcome::Answered(AnswerChoice::Primary))),
+                "2" => return Ok((secondary, PickOutcome::Answered(AnswerChoice::Secondary))),
+                "v" | "view" => {
+                    renderer.line(MessageStyle::Info, &right)?;
+                    renderer.line(MessageStyle::Response, &secondary)?;
+                }
+                _ => renderer.line(
+                    MessageStyle::Info,
+                    "Respond with '1' or '2' to keep that answer.",
+                )?,
+            },
+            RatatuiEvent::Cancel => {
+                return Ok((primary, PickOutcome::Answered(AnswerChoice::Primary)));
+            }
+            RatatuiEvent::Exit => return Ok((primary, PickOutcome::Exit)),
+            RatatuiEvent::Interrupt => return Ok((primary, PickOutcome::Interrupt)),
+            RatatuiEvent::ExplainSelection(_)
+            | RatatuiEvent::Copy(_)
+            | RatatuiEvent::Fork(_)
+            | RatatuiEvent::Undo
+            | RatatuiEvent::ToggleVoice
+            | RatatuiEvent::ScrollLineUp
+            | RatatuiEvent::ScrollLineDown
+            | RatatuiEvent::ScrollPageUp
+            | RatatuiEvent::ScrollPageDown => {}
+        }
+    }
+}
+
+fn render_collapsed(label: &str, secondary: &str, renderer: &mut AnsiRenderer) -> Result<()> {
+    renderer.line(MessageStyle::Info, label)?;
+    let lines: Vec<&str> = secondary.lines().collect();
+    for line in lines.iter().take(COLLAPSED_LINES) {
+        renderer.line(MessageStyle::Response, line)?;
+    }
+    if lines.len() > COLLAPSED_LINES {
+        renderer.line(
+            MessageStyle::Info,
+            &format!("… {} more lines", lines.len() - COLLAPSED_LINES),
+        )?;
+    }
+    Ok(())
+}
+
+/// Lay out two texts in columns that fit `width` terminal cells.
+fn side_by_side(left: &str, right: &str, width: usize) -> Vec<String> {
+    let column = width.saturating_sub(display_width(COLUMN_GAP)).max(20) / 2;
+    let left = wrap(left, column);
+    let right = wrap(right, column);
+    (0..left.len().max(right.len()))
+        .map(|row| {
+            let cell = left.get(row).map(String::as_str).unwrap_or("");
+            let pad = column.saturating_sub(display_width(cell));
+            let line = format!(
+                "{cell}{}{COLUMN_GAP}{}",
+                " ".repeat(pad),
+                right.get(row).map(String::as_str).unwrap_or("")
+            );
+            line.trim_end().to_string()
+        })
+        .collect()
+}
+
+/// Break each line of `text` into pieces at most `width` cells wide,
+/// preferring spaces.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut rows = Vec::new();
+    for line in text.lines() {
+        let mut row = String::new();
+        for word in line.split(' ') {
+            let needed = display_width(word) + usize::from(!row.is_empty());
+            if display_width(&row) + needed > width && !row.is_empty() {
+                rows.push(std::mem::take(&mut row));
+            }
+            if !row.is_empty() {
+                row.push(' ');
+            }
+            for ch in word.chars() {
+                if display_width(&row) + ch.width().unwrap_or(0) > width {
+                    rows.push(std::mem::take(&mut row));
+                }
+                row.push(ch);
+            }
+        }
+        rows.push(row);
+    }
+    rows
+}
+
+fn display_width(text: &str) -> usize {
+    text.chars().map(|ch| ch.width().unwrap_or(0)).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lays_out_answers_in_columns() {
+        let lines = side_by_side("[1] a\nshort", "[2] b\nthe quick brown fox jumps", 43);
+        assert_eq!(
+            lines,
+            vec![
+                "[1] a                │ [2] b",
+                "short                │ the quick brown fox",
+                "                     │ jumps",
+            ]
+        );
+        assert_eq!(wrap("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+    }
+}
@@ -0,0 +1,98 @@
+use anyhow::Result;
+use vtcode_core::core::evidence::{ClaimLink, ClaimStatus, EvidenceLog};
+use vtcode_core::utils::ansi::{AnsiRenderer, MessageStyle};
+
+/// Longest claim quoted in the evidence list.
+const MAX_CLAIM_CHARS: usize = 80;
+/// Tool results listed by `/evidence` without a reference number.
+const MAX_LISTED: usize = 20;
+
+/// List the claims in the latest answer with the references that back them.
+pub(crate) fn render_claim_links(
+    links: &[ClaimLink],
+    evidence: &EvidenceLog,
+    renderer: &mut AnsiRenderer,
+) -> Result<()> {
+    if links.is_empty() {
+        return Ok(());
+    }
+    renderer.line(MessageStyle::Info, "Evidence:")?;
+    for link in links {
+        let claim = quote(&link.claim);
+        let references = link
+            .evidence
+            .iter()
+            .filter_map(|id| evidence.get(*id))
+            .map(|entry| format!("[{}] {}", entry.id, entry.label()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let (style, line) = match link.status {
+            ClaimStatus::Supported => (
+                MessageStyle::Info,
+                format!("  ✓ {} ← {}", claim, references),
+            ),
+            ClaimStatus::Contradicted => (
+                MessageStyle::Error,
+                format!("  ✗ {} contradicted by {}", claim, references),
+            ),
+            ClaimStatus::Unsupported => (
+                MessageStyle::Info,
+                format!("  ? {} has no supporting tool result", claim),
+            ),
+        };
+        renderer.line(style, &line)?;
+    }
+    if links.iter().any(|link| !link.evidence.is_empty()) {
+        renderer.line(MessageStyle::Info, "Expand a reference with /evidence <n>.")?;
+    }
+    Ok(())
+}
+
+/// Expand one reference, or list the most recent ones.
+pub(crate) fn show_evidence(
+    evidence: &EvidenceLog,
+    id: Option<usize>,
+    renderer: &mut AnsiRenderer,
+) -> Result<()> {
+    let Some(id) = id else {
+        let entries = evidence.entries();
+        if entries.is_empty() {
+            return renderer.line(MessageStyle::Info, "No tool results recorded yet.");
+        }
+        let skipped = entries.len().saturating_sub(MAX_LISTED);
+        for entry in &entries[skipped..] {
+            renderer.line(
+                MessageStyle::Info,
+                &format!("[{}] turn {}: {}", entry.id, entry.turn, entry.label()),
+            )?;
+        }
+        return Ok(());
+    };
+    let Some(entry) = evidence.get(id) else {
+        return renderer.line(
+            MessageStyle::Error,
+            &format!(
+                "No tool result [{}]; /evidence lists the recorded ones.",
+                id
+            ),
+        );
+    };
+    renderer.line(
+        MessageStyle::Tool,
+        &format!("[{}] turn {}: {}", entry.id, entry.turn, entry.label()),
+    )?;
+    if entry.excerpt.trim().is_empty() {
+        renderer.line(MessageStyle::Info, "(no output)")
+    } else {
+        renderer.line(MessageStyle::Output, &entry.excerpt)
+    }
+}
+
+fn quote(claim: &str) -> String {
+    if claim.chars().count() > MAX_CLAIM_CHARS {
+        let cut: String = claim.chars().take(MAX_CLAIM_CHARS).collect();
+        format!("\"{}…\"", cut.trim_end())
+    } else {
+        format!("\"{}\"", claim)
+    }
+}
@@ -0,0 +1,177 @@
+use regex::Regex;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+const MAX_REFERENCED_FILES: usize = 3;
+const CONTEXT_LINES: usize = 10;
+const MAX_LEADING_LINES: usize = 40;
+
+/// `path:line[:column]` as printed by rustc, tsc, go, eslint and most linters.
+static LOCATION_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?P<path>[A-Za-z0-9_./\\-]*[A-Za-z0-9_-]\.[A-Za-z0-9]+)(?::(?P<line>\d+))?")
+        .expect("valid location pattern")
+});
+
+/// `File "path", line N` as printed in Python tracebacks.
+static PYTHON_FRAME_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"File "(?P<path>[^"]+)", line (?P<line>\d+)"#).expect("valid frame pattern")
+});
+
+/// Prompt sent for the Ctrl+E "explain this error" action.
+pub(crate) struct ExplainPrompt {
+    pub text: String,
+    /// Workspace-relative files whose contents were attached
+    pub files: Vec<String>,
+}
+
+/// Ask the agent to explain and fix `selection`, attaching the surroundings
+/// of any workspace files it references.
+pub(crate) fn build_explain_prompt(workspace: &Path, selection: &str) -> ExplainPrompt {
+    let mut text = String::from(
+        "Explain and fix this error. Identify the root cause, then make the smallest change that resolves it.\n\nSelected output:\n```text\n",
+    );
+    text.push_str(selection.trim_end());
+    text.push_str("\n```\n");
+
+    let mut files = Vec::new();
+    for (relative, path, line) in referenced_locations(workspace, selection) {
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let Some(snippet) = numbered_snippet(&content, line) else {
+            continue;
+        };
+        let display = relative.to_string_lossy().replace('\\', "/");
+        let language = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or("text");
+        if files.is_empty() {
+            text.push_str("\nContext from referenced files:\n");
+        }
+        text.push_str(&format!(
+            "\n{} (lines {}-{}):\n```{}\n{}```\n",
+            display, snippet.first, snippet.last, language, snippet.body
+        ));
+        files.push(display);
+    }
+
+    ExplainPrompt { text, files }
+}
+
+/// Files inside the workspace that `text` points at, in order of appearance.
+fn referenced_locations(workspace: &Path, text: &str) -> Vec<(PathBuf, PathBuf, Option<usize>)> {
+    let workspace = workspace
+        .canonicalize()
+        .unwrap_or_else(|_| workspace.to_path_buf());
+    let mut captures: Vec<(usize, &str, Option<usize>)> = PYTHON_FRAME_PATTERN
+        .captures_iter(text)
+        .chain(LOCATION_PATTERN.captures_iter(text))
+        .filter_map(|capture| {
+            let path = capture.name("path")?;
+            let line = capture
+                .name("line")
+                .and_then(|line| line.as_str().parse().ok());
+            Some((path.start(), path.as_str(), line))
+        })
+        .collect();
+    captures.sort_by_key(|(start, _, line)| (*start, line.is_none()));
+
+    let mut seen = HashSet::new();
+    let mut locations = Vec::new();
+    for (_, raw, line) in captures {
+        let candidate = Path::new(raw);
+        let joined = if candidate.is_absolute() {
+            candidate.to_path_buf()
+        } else {
+            workspace.join(candidate)
+        };
+        let Ok(path) = joined.canonicalize() else {
+            continue;
+        };
+        if !path.is_file() || !seen.insert(path.clone()) {
+            continue;
+        }
+        let Ok(relative) = path.strip_prefix(&workspace).map(Path::to_path_buf) else {
+            continue;
+        };
+        locations.push((relative, path, line));
+        if locations.len() == MAX_REFERENCED_FILES {
+            break;
+        }
+    }
+    locations
+}
+
+struct Snippet {
+    first: usize,
+    last: usize,
+    body: String,
+}
+
+/// Lines around `line` (1-based), or the start of the file without one.
+fn numbered_snippet(content: &str, line: Option<usize>) -> Option<Snippet> {
+    let lines: Vec<&str> = content.lines().collect();
+    if lines.is_empty() {
+        return None;
+    }
+    let (start, end) = match line {
+        Some(line) if line >= 1 && line <= lines.len() => (
+            line.saturating_sub(CONTEXT_LINES + 1),
+            (line + CONTEXT_LINES).min(lines.len()),
+        ),
+        Some(_) => return None,
+        None => (0, MAX_LEADING_LINES.min(lines.len())),
+    };
+    let width = end.to_string().len();
+    let mut body = String::new();
+    for (index, text) in lines[start..end].iter().enumerate() {
+        let number = start + index + 1;
+        let marker = if Some(number) == line { '>' } else { ' ' };
+        body.push_str(&format!("{}{:>width$} | {}\n", marker, number, text));
+    }
+    Some(Snippet {
+        first: start + 1,
+        last: end,
+        body,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn attaches_context_around_referenced_lines() {
+        let dir = TempDir::new().expect("tempdir");
+        fs::create_dir_all(dir.path().join("src")).expect("src dir");
+        let source: String = (1..=30).map(|n| format!("line {}\n", n)).collect();
+        fs::write(dir.path().join("src/lib.rs"), &source).expect("lib.rs");
+        fs::write(dir.path().join("app.py"), "import os\nprint(x)\n").expect("app.py");
+
+        let selection = "error[E0425]: cannot find value `x`\n  --> src/lib.rs:20:9\n   |\n  --> src/lib.rs:21:1\nTraceback:\n  File \"app.py\", line 2, in <module>\nsee https://example.com/docs.html";
+        let prompt = build_explain_prompt(dir.path(), selection);
+
+        assert_eq!(prompt.files, vec!["src/lib.rs", "app.py"]);
+        assert!(prompt.text.contains("src/lib.rs (lines 10-30):\n```rs\n"));
+        assert!(prompt.text.contains(">20 | line 20\n"));
+        assert!(!prompt.text.contains(" 9 | line 9\n"));
+        assert!(
+            prompt
+                .text
+                .contains("app.py (lines 1-2):\n```py\n 1 | import os\n>2 | print(x)\n")
+        );
+    }
+
+    #[test]
+    fn ignores_paths_outside_the_workspace() {
+        let dir = TempDir::new().expect("tempdir");
+        let prompt =
+            build_explain_prompt(dir.path(), "failed at ../../etc/hosts.txt:1 and main.rs:3");
+        assert!(prompt.files.is_empty());
+        assert!(!prompt.text.contains("Context from referenced files"));
+    }
+}
@@ -0,0 +1,179 @@
+//! Forking the conversation from an earlier turn
+//!
+//! Alt+F while scrolled back in the transcript forks the conversation after
+//! the turn in view. [`ForkPoints`] keeps the history as it stood when each
+//! prompt was shown, so forking after turn `n` restores the history from the
+//! start of turn `n + 1`. The turns the fork drops are written to their own
+//! session archive, where `vtcode sessions list` finds them. Workspace files
+//! are left as they are.
+
+use anyhow::{Result, anyhow, bail};
+
+use vtcode_core::core::tool_result_store::ToolResultStore;
+use vtcode_core::llm::provider as uni;
+use vtcode_core::ui::tui::RatatuiHandle;
+use vtcode_core::utils::ansi::{AnsiRenderer, MessageStyle};
+use vtcode_core::utils::session_archive::{SessionArchive, SessionMessage};
+use vtcode_core::utils::transcript;
+
+/// Turns that can be forked from; older checkpoints are dropped.
+const MAX_FORK_POINTS: usize = 64;
+const FORK_STATUS_MARKER: &str = " · forked at turn ";
+
+pub(crate) struct ForkPoint {
+    history: Vec<uni::Message>,
+    /// Length of the session transcript when the prompt was shown
+    transcript_len: usize,
+}
+
+/// History checkpoints, one per prompt shown in the transcript.
+#[derive(Default)]
+pub(crate) struct ForkPoints {
+    points: Vec<Option<ForkPoint>>,
+}
+
+impl ForkPoints {
+    /// Remember `history` as it is just before the next prompt is shown.
+    pub(crate) fn record(&mut self, history: &[uni::Message]) {
+        self.points.push(Some(ForkPoint {
+            history: history.to_vec(),
+            transcript_len: transcript::len(),
+        }));
+        if let Some(stale) = self.points.len().checked_sub(MAX_FORK_POINTS + 1) {
+            self.points[stale] = None;
+        }
+    }
+
+    /// Take the checkpoint for forking after `turn` (counted from 0) and
+    /// forget the turns after it.
+    pub(crate) fn fork_after(&mut self, turn: usize) -> Result<ForkPoint> {
+        if turn + 1 >= self.points.len() {
+            bail!(
+                "Turn {} is the latest turn; there is nothing to fork.",
+                turn + 1
+            );
+        }
+        let point = self.points[turn + 1].take().ok_or_else(|| {
+            anyhow!(
+                "Turn {} is too far back to fork from; only the last {} turns are kept.",
+                turn + 1,
+                MAX_FORK_POINTS
+            )
+        })?;
+        self.points.truncate(turn + 1);
+        Ok(point)
+    }
+}
+
+/// Status bar center text for a conversation forked after turn `turn`.
+pub(crate) fn fork_status(center_status: &str, turn: usize) -> String {
+    let base = center_status
+        .split(FORK_STATUS_MARKER)
+        .next()
+        .unwrap_or(center_status);
+    format!("{base}{FORK_STATUS_MARKER}{turn}")
+}
+
+/// Fork the conversation after `turn`: archive the turns after it as a
+/// branch, restore the history and transcript of that point, and drop the
+/// later turns from the view. Returns the 1-based turn forked at.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn fork_conversation(
+    points: &mut ForkPoints,
+    turn: usize,
+    history: &mut Vec<uni::Message>,
+    archive: Option<&SessionArchive>,
+    tool_results: &ToolResultStore,
+    distinct_tools: Vec<String>,
+    handle: &RatatuiHandle,
+    renderer: &mut AnsiRenderer,
+) -> Result<Option<usize>> {
+    let point = match points.fork_after(turn) {
+        Ok(point) => point,
+        Err(err) => {
+            renderer.line(MessageStyle::Info, &err.to_string())?;
+            return Ok(None);
+        }
+    };
+    let forked_at = turn + 1;
+
+    let saved = archive.map(|archive| {
+        archive
+            .branch(&format!("branch after turn {forked_at}"))
+            .and_then(|branch| {
+                let messages = tool_results
+                    .rehydrate(history)
+                    .iter()
+                    .map(SessionMessage::from)
+                    .collect();
+                branch.finalize(
+                    transcript::snapshot(),
+                    history.len(),
+                    distinct_tools,
+                    messages,
+                )?;
+                Ok(branch.identifier())
+            })
+    });
+
+    *history = point.history;
+    transcript::replace_last(transcript::len().saturating_sub(point.transcript_len), &[]);
+    handle.truncate_turns(forked_at);
+
+    let branch_note = match saved {
+        Some(Ok(identifier)) => format!("The later turns were saved as session {identifier}."),
+        Some(Err(err)) => format!("The later turns could not be archived: {err}."),
+        None => "The later turns were discarded; session archiving is disabled.".to_string(),
+    };
+    renderer.line(
+        MessageStyle::Info,
+        &format!(
+            "Forked the conversation after turn {forked_at}. {branch_note} Workspace files were not changed; /timeline shows what earlier turns edited."
+        ),
+    )?;
+    renderer.line_if_not_empty(MessageStyle::Output)?;
+    Ok(Some(forked_at))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(turns: usize) -> Vec<uni::Message> {
+        (0..turns)
+            .flat_map(|turn| {
+                [
+                    uni::Message::user(format!("question {turn}")),
+                    uni::Message::assistant(format!("answer {turn}")),
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn forks_restore_the_history_at_the_end_of_the_turn() {
+        let mut points = ForkPoints::default();
+        for turn in 0..3 {
+            points.record(&history(turn));
+        }
+
+        assert!(points.fork_after(2).is_err());
+        let point = points.fork_after(0).unwrap();
+        assert_eq!(point.history.len(), 2);
+        assert_eq!(point.history[1].content, "answer 0");
+        // The next prompt becomes turn 2 again
+        points.record(&point.history);
+        assert_eq!(points.points.len(), 2);
+    }
+
+    #[test]
+    fn old_checkpoints_are_dropped() {
+        let mut points = ForkPoints::default();
+        for turn in 0..MAX_FORK_POINTS + 3 {
+            points.record(&history(turn.min(2)));
+        }
+        let err = points.fork_after(0).err().unwrap();
+        assert!(err.to_string().contains("too far back"));
+        assert!(points.fork_after(MAX_FORK_POINTS).is_ok());
+    }
+}
@@ -0,0 +1,65 @@
+use anyhow::Result;
+use std::path::Path;
+
+use vtcode_core::core::change_ledger::ChangeLedger;
+use vtcode_core::core::handoff::Handoff;
+use vtcode_core::llm::provider as uni;
+use vtcode_core::utils::ansi::{AnsiRenderer, MessageStyle};
+
+/// Sources for the handoff document of the running session.
+pub(crate) struct HandoffSources<'a> {
+    pub session_id: String,
+    pub history: &'a [uni::Message],
+    pub summary: Option<&'a str>,
+    pub changes: &'a ChangeLedger,
+}
+
+/// Have the model write the handoff document and save it to the workspace.
+pub(crate) async fn write_handoff(
+    sources: HandoffSources<'_>,
+    provider: &dyn uni::LLMProvider,
+    model: &str,
+    workspace: &Path,
+    renderer: &mut AnsiRenderer,
+) -> Result<()> {
+    if sources.history.is_empty() && sources.summary.is_none() {
+        return renderer.line(
+            MessageStyle::Info,
+            "Nothing to hand off yet; send a request first.",
+        );
+    }
+    renderer.line(MessageStyle::Info, "Writing the handoff document…")?;
+    let handoff = match Handoff::generate(
+        sources.session_id,
+        sources.history,
+        sources.summary,
+        sources.changes,
+        provider,
+        model,
+    )
+    .await
+    {
+        Ok(handoff) => handoff,
+        Err(err) => {
+            return renderer.line(
+                MessageStyle::Error,
+                &format!("Could not write the handoff: {err:#}"),
+            );
+        }
+    };
+    match handoff.write(workspace) {
+        Ok(path) => renderer.line(
+            MessageStyle::Info,
+            &format!(
+                "Handoff written to {} ({} files touched, {} next steps).",
+                path.display(),
+                handoff.files.len(),
+                handoff.notes.next_steps.len()
+            ),
+        ),
+        Err(err) => renderer.line(
+            MessageStyle::Error,
+            &format!("Failed to write the handoff: {err:#}"),
+        ),
+    }
+}
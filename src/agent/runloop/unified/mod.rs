@@ -1,7 +1,35 @@
+mod briefing;
+mod cache_warmup;
+mod clipboard;
+mod code_execution;
+mod conflict;
+mod context_view;
 mod display;
+mod dual_answer;
+mod evidence;
+mod explain;
+mod fork;
+mod handoff;
+mod offline;
+mod outline;
+mod path_grant;
+mod preview;
 mod prompts;
+mod redact;
+mod refusal;
+mod risk;
+mod sensitive;
 mod session_setup;
+mod session_summary;
 mod shell;
+mod spend;
+mod subagents;
+mod summarization;
+mod timeline;
+mod tool_cancel;
 mod turn;
+mod undo;
+mod voice;
 
+pub(crate) use prompts::system_prompt_from_template;
 pub(crate) use turn::run_single_agent_loop_unified;
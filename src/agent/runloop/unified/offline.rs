@@ -0,0 +1,288 @@
+use anyhow::Result;
+use std::time::Duration;
+use vtcode_core::config::core::{LocalModelConfig, OfflineConfig};
+use vtcode_core::config::loader::VTCodeConfig;
+use vtcode_core::core::daemon::WarmState;
+use vtcode_core::core::offline::{
+    Connectivity, is_connectivity_error, is_network_tool, probe_endpoint, provider_endpoint,
+};
+use vtcode_core::llm::factory::create_provider_with_config;
+use vtcode_core::llm::provider::{self as uni, LLMProvider};
+use vtcode_core::ui::tui::RatatuiHandle;
+use vtcode_core::utils::ansi::{AnsiRenderer, MessageStyle};
+
+/// Commands accepted without a leading `/` in local assistant mode.
+const LOCAL_COMMANDS: &[&str] = &["search", "files", "read", "outline", "git", "offline"];
+
+const LOCAL_ASSISTANT_HELP: &str = "Local assistant commands: search <pattern>, files [path], \
+read <path>, outline <path>, git status|diff|log|show|branch|blame, offline off (reconnect)";
+
+/// Tracks whether provider calls are possible and swaps in the local model while offline.
+pub(crate) struct OfflineMode {
+    handle: RatatuiHandle,
+    endpoint: Option<&'static str>,
+    probe_timeout: Duration,
+    config: OfflineConfig,
+    /// Why the session is offline; `None` while online
+    reason: Option<String>,
+    /// Remote provider parked while the local model serves requests
+    parked_provider: Option<Box<dyn LLMProvider>>,
+}
+
+impl OfflineMode {
+    pub(crate) fn new(
+        handle: RatatuiHandle,
+        provider: &str,
+        vt_cfg: Option<&VTCodeConfig>,
+    ) -> Self {
+        let config = vt_cfg.map(|cfg| cfg.offline.clone()).unwrap_or_default();
+        Self {
+            handle,
+            endpoint: provider_endpoint(provider),
+            probe_timeout: Duration::from_millis(config.probe_timeout_ms.max(100)),
+            config,
+            reason: None,
+            parked_provider: None,
+        }
+    }
+
+    pub(crate) fn is_active(&self) -> bool {
+        self.reason.is_some()
+    }
+
+    /// Offline with no local model: free-form prompts cannot be answered.
+    pub(crate) fn is_local_only(&self) -> bool {
+        self.is_active() && self.parked_provider.is_none()
+    }
+
+    /// Model to request while the local model is serving.
+    pub(crate) fn model_override(&self) -> Option<&str> {
+        if self.is_active() && self.parked_provider.is_some() {
+            self.config
+                .local_model
+                .as_ref()
+                .map(|local| local.model.as_str())
+        } else {
+            None
+        }
+    }
+
+    /// Tool declarations usable in the current mode.
+    pub(crate) fn available_tools(
+        &self,
+        tools: &[uni::ToolDefinition],
+    ) -> Vec<uni::ToolDefinition> {
+        if !self.is_active() {
+            return tools.to_vec();
+        }
+        tools
+            .iter()
+            .filter(|tool| !is_network_tool(&tool.function.name))
+            .cloned()
+            .collect()
+    }
+
+    /// Probe the provider before the first request, or use the reachability a
+    /// running daemon last observed.
+    pub(crate) async fn detect_at_startup(
+        &mut self,
+        provider_client: &mut Box<dyn LLMProvider>,
+        renderer: &mut AnsiRenderer,
+        warm_state: Option<&WarmState>,
+    ) -> Result<()> {
+        if self.config.force {
+            return self.enter(
+                "offline mode is forced by [offline] force = true".to_string(),
+                provider_client,
+                renderer,
+            );
+        }
+        if !self.config.detect {
+            return Ok(());
+        }
+        let cached = self
+            .endpoint
+            .zip(warm_state)
+            .and_then(|(endpoint, state)| state.connectivity(endpoint));
+        let connectivity = match cached {
+            Some(connectivity) => connectivity,
+            None => self.probe().await,
+        };
+        if let Connectivity::Offline { reason } = connectivity {
+            self.enter(reason, provider_client, renderer)?;
+        }
+        Ok(())
+    }
+
+    /// Switch to offline mode when a failed request turns out to be a lost connection.
+    pub(crate) async fn handle_provider_error(
+        &mut self,
+        error: &uni::LLMError,
+        provider_client: &mut Box<dyn LLMProvider>,
+        renderer: &mut AnsiRenderer,
+    ) -> Result<bool> {
+        if self.is_active() || !is_connectivity_error(error) {
+            return Ok(false);
+        }
+        match self.probe().await {
+            Connectivity::Offline { reason } => {
+                self.enter(reason, provider_client, renderer)?;
+                Ok(true)
+            }
+            Connectivity::Online => Ok(false),
+        }
+    }
+
+    pub(crate) fn force_offline(
+        &mut self,
+        provider_client: &mut Box<dyn LLMProvider>,
+        renderer: &mut AnsiRenderer,
+    ) -> Result<()> {
+        if self.is_active() {
+            return self.render_status(renderer);
+        }
+        self.enter(
+            "offline mode enabled with /offline on".to_string(),
+            provider_client,
+            renderer,
+        )
+    }
+
+    pub(crate) async fn reconnect(
+        &mut self,
+        provider_client: &mut Box<dyn LLMProvider>,
+        renderer: &mut AnsiRenderer,
+    ) -> Result<()> {
+        if !self.is_active() {
+            renderer.line(MessageStyle::Info, "Already online.")?;
+            return Ok(());
+        }
+        match self.probe().await {
+            Connectivity::Online => {
+                if let Some(remote) = self.parked_provider.take() {
+                    *provider_client = remote;
+                }
+                self.reason = None;
+                self.handle
+                    .update_status_bar(Some(String::new()), None, None);
+                renderer.line(
+                    MessageStyle::Info,
+                    &format!(
+                        "Back online. Requests go to {} again.",
+                        provider_client.name()
+                    ),
+                )?;
+            }
+            Connectivity::Offline { reason } => {
+                renderer.line(MessageStyle::Error, &format!("Still offline: {}", reason))?;
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn render_status(&self, renderer: &mut AnsiRenderer) -> Result<()> {
+        match &self.reason {
+            None => renderer.line(MessageStyle::Info, "Online. Provider calls are enabled.")?,
+            Some(reason) => {
+                renderer.line(MessageStyle::Info, &format!("Offline: {}", reason))?;
+                match self.model_override() {
+                    Some(model) => renderer.line(
+                        MessageStyle::Info,
+                        &format!("Requests are served by the local model {}.", model),
+                    )?,
+                    None => renderer.line(MessageStyle::Info, LOCAL_ASSISTANT_HELP)?,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Map plain input in local assistant mode onto a slash command.
+    ///
+    /// Returns `None` after explaining the mode when the input is not a local command.
+    pub(crate) fn local_command_input(
+        &self,
+        input: &str,
+        renderer: &mut AnsiRenderer,
+    ) -> Result<Option<String>> {
+        let command = input.split_whitespace().next().unwrap_or("").to_lowercase();
+        if LOCAL_COMMANDS.contains(&command.as_str()) {
+            return Ok(Some(input.to_string()));
+        }
+        renderer.line(
+            MessageStyle::Info,
+            "Provider calls are disabled while offline, so this request was not sent.",
+        )?;
+        renderer.line(MessageStyle::Info, LOCAL_ASSISTANT_HELP)?;
+        Ok(None)
+    }
+
+    async fn probe(&self) -> Connectivity {
+        match self.endpoint {
+            Some(endpoint) => probe_endpoint(endpoint, self.probe_timeout).await,
+            None => Connectivity::Online,
+        }
+    }
+
+    fn enter(
+        &mut self,
+        reason: String,
+        provider_client: &mut Box<dyn LLMProvider>,
+        renderer: &mut AnsiRenderer,
+    ) -> Result<()> {
+        renderer.line(MessageStyle::Error, &format!("Offline: {}", reason))?;
+        self.reason = Some(reason);
+
+        if let Some(local) = self.config.local_model.clone() {
+            match create_local_provider(&local) {
+                Ok(provider) => {
+                    self.parked_provider = Some(std::mem::replace(provider_client, provider));
+                    self.handle.update_status_bar(
+                        Some(format!("offline · {}", local.model)),
+                        None,
+                        None,
+                    );
+                    renderer.line(
+                        MessageStyle::Info,
+                        &format!(
+                            "Using the local model {} at {}. Network tools are disabled; /offline off reconnects.",
+                            local.model, local.base_url
+                        ),
+                    )?;
+                    return Ok(());
+                }
+                Err(err) => {
+                    renderer.line(
+                        MessageStyle::Error,
+                        &format!("Local model unavailable: {}", err),
+                    )?;
+                }
+            }
+        }
+
+        self.handle
+            .update_status_bar(Some("offline · local assistant".to_string()), None, None);
+        renderer.line(
+            MessageStyle::Info,
+            "Provider calls are disabled. Local tools keep working; type a command without the leading /.",
+        )?;
+        renderer.line(MessageStyle::Info, LOCAL_ASSISTANT_HELP)?;
+        Ok(())
+    }
+}
+
+fn create_local_provider(local: &LocalModelConfig) -> Result<Box<dyn LLMProvider>> {
+    let api_key = local
+        .api_key_env
+        .as_deref()
+        .and_then(|name| std::env::var(name).ok())
+        .unwrap_or_default();
+    let provider = create_provider_with_config(
+        &local.provider,
+        Some(api_key),
+        Some(local.base_url.clone()),
+        Some(local.model.clone()),
+        None,
+    )?;
+    Ok(provider)
+}
@@ -0,0 +1,100 @@
+use anyhow::{Context, Result, anyhow};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use vtcode_core::config::constants::tools;
+use vtcode_core::tools::tree_sitter::{SyntaxTreeCache, TreeSitterAnalyzer};
+use vtcode_core::ui::tui::{RatatuiHandle, RatatuiOutlineEntry};
+
+/// Keeps the outline panel in sync with the file under discussion.
+pub(crate) struct OutlineTracker {
+    workspace: PathBuf,
+    analyzer: Option<TreeSitterAnalyzer>,
+    current: Option<PathBuf>,
+    auto_show: bool,
+}
+
+impl OutlineTracker {
+    pub(crate) fn new(workspace: &Path, tree_cache: SyntaxTreeCache, auto_show: bool) -> Self {
+        let analyzer = match TreeSitterAnalyzer::new() {
+            Ok(analyzer) => Some(analyzer.with_tree_cache(tree_cache)),
+            Err(err) => {
+                tracing::debug!("symbol outline disabled: {err:#}");
+                None
+            }
+        };
+        Self {
+            workspace: workspace.to_path_buf(),
+            analyzer,
+            current: None,
+            auto_show,
+        }
+    }
+
+    /// Follow file tools so the outline shows the file the agent last touched.
+    pub(crate) fn observe_tool_call(&mut self, handle: &RatatuiHandle, name: &str, args: &Value) {
+        if !matches!(
+            name,
+            tools::READ_FILE | tools::WRITE_FILE | tools::EDIT_FILE | tools::CREATE_FILE
+        ) {
+            return;
+        }
+        let Some(path) = args.get("path").and_then(Value::as_str) else {
+            return;
+        };
+        match self.focus(handle, Path::new(path)) {
+            Ok(_) => {
+                if self.auto_show {
+                    handle.set_outline_visible(true);
+                }
+            }
+            Err(err) => {
+                tracing::debug!("no outline for {path}: {err:#}");
+                self.current = None;
+                handle.clear_outline();
+            }
+        }
+    }
+
+    /// Show the outline of `path` and return the number of symbols found.
+    pub(crate) fn focus(&mut self, handle: &RatatuiHandle, path: &Path) -> Result<usize> {
+        let analyzer = self
+            .analyzer
+            .as_mut()
+            .ok_or_else(|| anyhow!("tree-sitter is unavailable"))?;
+        let absolute = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            self.workspace.join(path)
+        };
+        let source = std::fs::read_to_string(&absolute)
+            .with_context(|| format!("failed to read {}", absolute.display()))?;
+        let outline = analyzer.symbol_outline(&absolute, &source)?;
+
+        let title = absolute
+            .strip_prefix(&self.workspace)
+            .unwrap_or(&absolute)
+            .display()
+            .to_string();
+        let entries: Vec<RatatuiOutlineEntry> = outline
+            .iter()
+            .map(|entry| RatatuiOutlineEntry {
+                name: entry.name.clone(),
+                kind: entry.kind_label().to_string(),
+                line: entry.line,
+                depth: entry.depth,
+            })
+            .collect();
+        let count = entries.len();
+        handle.set_outline(title, entries);
+        self.current = Some(absolute);
+        Ok(count)
+    }
+
+    /// Re-read the focused file, e.g. after it changed outside a tool call.
+    pub(crate) fn refresh(&mut self, handle: &RatatuiHandle) -> Result<Option<usize>> {
+        match self.current.clone() {
+            Some(path) => self.focus(handle, &path).map(Some),
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use anyhow::Result;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::Notify;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::task;
+
+use vtcode_core::tools::WritePermissionRequired;
+use vtcode_core::ui::tui::{RatatuiEvent, RatatuiHandle};
+use vtcode_core::utils::ansi::{AnsiRenderer, MessageStyle};
+use vtcode_core::utils::dot_config::WriteGrantRecord;
+
+const GRANT_PLACEHOLDER: &str =
+    "Allow writing to this path: [y]es · for an [h]our · [n]o (Esc declines)";
+const HOUR_SECS: u64 = 60 * 60;
+
+/// The user's answer to a write outside the workspace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum GrantChoice {
+    /// Allowed until the grant is removed from the trust store
+    Allow,
+    AllowForHour,
+    Decline,
+}
+
+impl GrantChoice {
+    fn parse(input: &str) -> Option<Self> {
+        match input.trim().to_lowercase().as_str() {
+            "y" | "yes" | "allow" => Some(Self::Allow),
+            "h" | "hour" => Some(Self::AllowForHour),
+            "n" | "no" => Some(Self::Decline),
+            _ => None,
+        }
+    }
+
+    /// The grant to record, or `None` when the write was declined.
+    pub(crate) fn grant(self, path: &str, now: u64) -> Option<WriteGrantRecord> {
+        let expires_at = match self {
+            Self::Allow => None,
+            Self::AllowForHour => Some(now + HOUR_SECS),
+            Self::Decline => return None,
+        };
+        Some(WriteGrantRecord {
+            path: path.to_string(),
+            granted_at: now,
+            expires_at,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum GrantOutcome {
+    Answered(GrantChoice),
+    Exit,
+    Interrupt,
+}
+
+/// Mark a permission request in a tool error as declined, so the model does
+/// not ask again.
+pub(crate) fn record_declined(output: &mut Value, request: &WritePermissionRequired) {
+    output["error"]["permission_required"] = json!({
+        "path": request.path,
+        "granted": false,
+        "note": "The user declined write access to this path. Nothing was written; do not retry the write.",
+    });
+}
+
+/// Show the path a tool wants to write outside the workspace and ask whether
+/// to allow it. Callers restore the input placeholder afterwards.
+pub(crate) async fn request_write_grant(
+    request: &WritePermissionRequired,
+    tool_name: &str,
+    renderer: &mut AnsiRenderer,
+    handle: &RatatuiHandle,
+    events: &mut UnboundedReceiver<RatatuiEvent>,
+    ctrl_c_flag: &Arc<AtomicBool>,
+    ctrl_c_notify: &Arc<Notify>,
+) -> Result<GrantOutcome> {
+    renderer.line_if_not_empty(MessageStyle::Info)?;
+    renderer.line(
+        MessageStyle::Error,
+        &format!("{tool_name} wants to write outside the workspace:"),
+    )?;
+    renderer.line(MessageStyle::Output, &format!("    {}", request.path))?;
+    renderer.line(
+        MessageStyle::Info,
+        "An allowed path is kept in the workspace trust store and the session log.",
+    )?;
+
+    handle.set_placeholder(Some(GRANT_PLACEHOLDER.to_string()));
+    task::yield_now().await;
+
+    loop {
+        if ctrl_c_flag.load(Ordering::SeqCst) {
+            return Ok(GrantOutcome::Interrupt);
+        }
+
+        let notify = ctrl_c_notify.clone();
+        let maybe_event = tokio::select! {
+            _ = notify.notified(), if !ctrl_c_flag.load(Ordering::SeqCst) => None,
+            event = events.recv() => event,
+        };
+
+        let Some(event) = maybe_event else {
+            if ctrl_c_flag.load(Ordering::SeqCst) {
+                return Ok(GrantOutcome::Interrupt);
+            }
+            return Ok(GrantOutcome::Exit);
+        };
+
+        match event {
+            RatatuiEvent::Submit(input) => match GrantChoice::parse(&input) {
+                Some(choice) => return Ok(GrantOutcome::Answered(choice)),
+                None => renderer.line(
+                    MessageStyle::Info,
+                    "Respond with 'y' (allow), 'h' (allow for an hour) or 'n' (decline).",
+                )?,
+            },
+            RatatuiEvent::Cancel => return Ok(GrantOutcome::Answered(GrantChoice::Decline)),
+            RatatuiEvent::Exit => return Ok(GrantOutcome::Exit),
+            RatatuiEvent::Interrupt => return Ok(GrantOutcome::Interrupt),
+            RatatuiEvent::ExplainSelection(_)
+            | RatatuiEvent::Copy(_)
+            | RatatuiEvent::Fork(_)
+            | RatatuiEvent::Undo
+            | RatatuiEvent::ToggleVoice
+            | RatatuiEvent::ScrollLineUp
+            | RatatuiEvent::ScrollLineDown
+            | RatatuiEvent::ScrollPageUp
+            | RatatuiEvent::ScrollPageDown => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answers_become_grants() {
+        assert_eq!(GrantChoice::parse(" Y "), Some(GrantChoice::Allow));
+        assert_eq!(GrantChoice::parse("x"), None);
+
+        let grant = GrantChoice::AllowForHour
+            .grant("/home/me/.config/tool/config", 100)
+            .unwrap();
+        assert_eq!(grant.expires_at, Some(100 + HOUR_SECS));
+        assert_eq!(
+            GrantChoice::Allow.grant("/tmp/x", 100).unwrap().expires_at,
+            None
+        );
+        assert!(GrantChoice::Decline.grant("/tmp/x", 100).is_none());
+
+        let request = WritePermissionRequired {
+            path: "/tmp/x".to_string(),
+        };
+        let mut output = json!({ "error": { "permission_required": request.to_json_value() } });
+        assert!(WritePermissionRequired::from_tool_output(&output).is_some());
+        record_declined(&mut output, &request);
+        assert!(WritePermissionRequired::from_tool_output(&output).is_none());
+    }
+}
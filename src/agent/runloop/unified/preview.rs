@@ -0,0 +1,295 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::Notify;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::task;
+
+use vtcode_core::llm::provider as uni;
+use vtcode_core::ui::tui::{RatatuiEvent, RatatuiHandle};
+
+const PREVIEW_PLACEHOLDER: &str =
+    "Send request? [s]end · [e]dit · [c]ancel (PgUp/PgDn scrolls the preview)";
+const PREVIEW_TITLE: &str = "Request preview · PgUp/PgDn scroll";
+const EDIT_PLACEHOLDER: &str = "Type a replacement for the latest user message (Enter keeps it)";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RequestPreviewOutcome {
+    Send,
+    Cancel,
+    Exit,
+    Interrupt,
+}
+
+enum PreviewInput {
+    Text(String),
+    Cancel,
+    Exit,
+    Interrupt,
+}
+
+/// Build the plain-text lines describing an assembled request exactly as it will be sent.
+pub(crate) fn build_request_preview_lines(
+    provider_name: &str,
+    request: &uni::LLMRequest,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+    lines.push(format!(
+        "Request preview · provider {} · model {}",
+        provider_name, request.model
+    ));
+
+    let mut settings = Vec::new();
+    if let Some(max_tokens) = request.max_tokens {
+        settings.push(format!("max_tokens={max_tokens}"));
+    }
+    if let Some(temperature) = request.temperature {
+        settings.push(format!("temperature={temperature}"));
+    }
+    if let Some(choice) = request.tool_choice.as_ref() {
+        let rendered = serde_json::to_string(&choice.to_provider_format(provider_name))
+            .unwrap_or_else(|_| "auto".to_string());
+        settings.push(format!("tool_choice={rendered}"));
+    }
+    if let Some(effort) = request.reasoning_effort.as_ref() {
+        settings.push(format!("reasoning_effort={effort}"));
+    }
+    settings.push(format!("stream={}", request.stream));
+    lines.push(format!("Settings: {}", settings.join(", ")));
+
+    lines.push(String::new());
+    lines.push("── System prompt ──".to_string());
+    match request.system_prompt.as_deref() {
+        Some(prompt) if !prompt.trim().is_empty() => {
+            lines.extend(prompt.lines().map(str::to_string));
+        }
+        _ => lines.push("(none)".to_string()),
+    }
+
+    lines.push(String::new());
+    lines.push(format!("── Messages ({}) ──", request.messages.len()));
+    for (index, message) in request.messages.iter().enumerate() {
+        let mut header = format!("[{}] {}", index + 1, message.role.as_generic_str());
+        if let Some(call_id) = message.tool_call_id.as_ref() {
+            header.push_str(&format!(" (tool_call_id: {call_id})"));
+        }
+        lines.push(header);
+        if message.content.is_empty() {
+            lines.push("    (empty)".to_string());
+        } else {
+            lines.extend(message.content.lines().map(|line| format!("    {line}")));
+        }
+        if let Some(calls) = message.tool_calls.as_ref() {
+            for call in calls {
+                lines.push(format!(
+                    "    → {}({}) id={}",
+                    call.function.name, call.function.arguments, call.id
+                ));
+            }
+        }
+    }
+
+    lines.push(String::new());
+    let tools = request.tools.as_deref().unwrap_or(&[]);
+    lines.push(format!("── Tools ({}) ──", tools.len()));
+    for tool in tools {
+        lines.push(format!(
+            "{}: {}",
+            tool.function.name, tool.function.description
+        ));
+        let schema = serde_json::to_string_pretty(&tool.function.parameters)
+            .unwrap_or_else(|_| "{}".to_string());
+        lines.extend(schema.lines().map(|line| format!("    {line}")));
+    }
+
+    lines
+}
+
+fn show_request_preview(handle: &RatatuiHandle, provider_name: &str, request: &uni::LLMRequest) {
+    handle.show_pager(
+        PREVIEW_TITLE.to_string(),
+        build_request_preview_lines(provider_name, request),
+    );
+}
+
+/// Replace the content of the most recent user message, returning whether anything changed.
+pub(crate) fn replace_latest_user_message(messages: &mut [uni::Message], text: &str) -> bool {
+    let Some(message) = messages
+        .iter_mut()
+        .rev()
+        .find(|message| message.role == uni::MessageRole::User)
+    else {
+        return false;
+    };
+    if message.content == text {
+        return false;
+    }
+    message.content = text.to_string();
+    true
+}
+
+async fn next_preview_input(
+    events: &mut UnboundedReceiver<RatatuiEvent>,
+    ctrl_c_flag: &Arc<AtomicBool>,
+    ctrl_c_notify: &Arc<Notify>,
+) -> PreviewInput {
+    loop {
+        if ctrl_c_flag.load(Ordering::SeqCst) {
+            return PreviewInput::Interrupt;
+        }
+
+        let notify = ctrl_c_notify.clone();
+        let maybe_event = tokio::select! {
+            _ = notify.notified(), if !ctrl_c_flag.load(Ordering::SeqCst) => None,
+            event = events.recv() => event,
+        };
+
+        let Some(event) = maybe_event else {
+            if ctrl_c_flag.load(Ordering::SeqCst) {
+                return PreviewInput::Interrupt;
+            }
+            return PreviewInput::Exit;
+        };
+
+        match event {
+            RatatuiEvent::Submit(text) => return PreviewInput::Text(text),
+            RatatuiEvent::Cancel => return PreviewInput::Cancel,
+            RatatuiEvent::Exit => return PreviewInput::Exit,
+            RatatuiEvent::Interrupt => return PreviewInput::Interrupt,
+            RatatuiEvent::ExplainSelection(_)
+            | RatatuiEvent::Copy(_)
+            | RatatuiEvent::Fork(_)
+            | RatatuiEvent::Undo
+            | RatatuiEvent::ToggleVoice
+            | RatatuiEvent::ScrollLineUp
+            | RatatuiEvent::ScrollLineDown
+            | RatatuiEvent::ScrollPageUp
+            | RatatuiEvent::ScrollPageDown => {}
+        }
+    }
+}
+
+/// Show the assembled request in a pager over the transcript and let the user
+/// send, edit, or cancel it. The pager is closed before returning.
+///
+/// Edits replace the latest user message in `request.messages`; callers should
+/// propagate the edited messages back into their history and restore the input
+/// placeholder once the review completes.
+pub(crate) async fn review_request_preview(
+    provider_name: &str,
+    request: &mut uni::LLMRequest,
+    handle: &RatatuiHandle,
+    events: &mut UnboundedReceiver<RatatuiEvent>,
+    ctrl_c_flag: &Arc<AtomicBool>,
+    ctrl_c_notify: &Arc<Notify>,
+) -> RequestPreviewOutcome {
+    show_request_preview(handle, provider_name, request);
+
+    // The pager covers the transcript, so feedback goes in the placeholder
+    let mut notice: Option<&str> = None;
+    let outcome = loop {
+        let placeholder = match notice.take() {
+            Some(notice) => format!("{notice} {PREVIEW_PLACEHOLDER}"),
+            None => PREVIEW_PLACEHOLDER.to_string(),
+        };
+        handle.set_placeholder(Some(placeholder));
+        task::yield_now().await;
+
+        let input = match next_preview_input(events, ctrl_c_flag, ctrl_c_notify).await {
+            PreviewInput::Text(text) => text,
+            PreviewInput::Cancel => break RequestPreviewOutcome::Cancel,
+            PreviewInput::Exit => break RequestPreviewOutcome::Exit,
+            PreviewInput::Interrupt => break RequestPreviewOutcome::Interrupt,
+        };
+
+        match input.trim().to_lowercase().as_str() {
+            "" | "s" | "send" | "y" | "yes" => break RequestPreviewOutcome::Send,
+            "c" | "cancel" | "n" | "no" => break RequestPreviewOutcome::Cancel,
+            "e" | "edit" => {
+                handle.set_placeholder(Some(EDIT_PLACEHOLDER.to_string()));
+                task::yield_now().await;
+                let replacement = match next_preview_input(events, ctrl_c_flag, ctrl_c_notify).await
+                {
+                    PreviewInput::Text(text) => text,
+                    PreviewInput::Cancel => continue,
+                    PreviewInput::Exit => break RequestPreviewOutcome::Exit,
+                    PreviewInput::Interrupt => break RequestPreviewOutcome::Interrupt,
+                };
+                let replacement = replacement.trim();
+                if replacement.is_empty() {
+                    notice = Some("Kept the original user message.");
+                    continue;
+                }
+                if replace_latest_user_message(&mut request.messages, replacement) {
+                    notice = Some("Updated the latest user message.");
+                    show_request_preview(handle, provider_name, request);
+                } else {
+                    notice = Some("No user message to edit; request left unchanged.");
+                }
+            }
+            _ => notice = Some("Unknown answer."),
+        }
+    };
+
+    handle.close_pager();
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample_request() -> uni::LLMRequest {
+        uni::LLMRequest {
+            messages: vec![
+                uni::Message::user("first question".to_string()),
+                uni::Message::assistant("answer".to_string()),
+                uni::Message::user("follow up".to_string()),
+            ],
+            system_prompt: Some("You are helpful.\nBe concise.".to_string()),
+            tools: Some(vec![uni::ToolDefinition::function(
+                "read_file".to_string(),
+                "Read a file".to_string(),
+                json!({"type": "object", "properties": {"path": {"type": "string"}}}),
+            )]),
+            model: "test-model".to_string(),
+            max_tokens: Some(2000),
+            temperature: Some(0.7),
+            stream: false,
+            tool_choice: Some(uni::ToolChoice::auto()),
+            parallel_tool_calls: None,
+            parallel_tool_config: None,
+            reasoning_effort: None,
+            json_mode: false,
+            response_schema: None,
+            code_execution: false,
+            seed: None,
+        }
+    }
+
+    #[test]
+    fn preview_lists_system_prompt_messages_and_tools() {
+        let lines = build_request_preview_lines("openai", &sample_request());
+        assert!(lines[0].contains("model test-model"));
+        assert!(lines.iter().any(|line| line == "Be concise."));
+        assert!(lines.iter().any(|line| line == "── Messages (3) ──"));
+        assert!(lines.iter().any(|line| line == "[3] user"));
+        assert!(lines.iter().any(|line| line == "read_file: Read a file"));
+        assert!(lines.iter().any(|line| line.contains("\"path\"")));
+    }
+
+    #[test]
+    fn edit_replaces_only_latest_user_message() {
+        let mut request = sample_request();
+        assert!(replace_latest_user_message(
+            &mut request.messages,
+            "rewritten"
+        ));
+        assert_eq!(request.messages[0].content, "first question");
+        assert_eq!(request.messages[2].content, "rewritten");
+        assert!(!replace_latest_user_message(
+            &mut request.messages,
+            "rewritten"
+        ));
+    }
+}
@@ -1,8 +1,20 @@
 use std::path::Path;
 
+const RISK_MAP_PROMPT_LIMIT: usize = 10;
+
 pub(crate) fn read_system_prompt(workspace: &Path, session_addendum: Option<&str>) -> String {
-    let mut prompt = vtcode_core::prompts::read_system_prompt_from_md()
+    let template = vtcode_core::prompts::read_system_prompt_from_md()
         .unwrap_or_else(|_| "You are a helpful coding assistant for a Rust workspace.".to_string());
+    system_prompt_from_template(template, workspace, session_addendum)
+}
+
+/// The session system prompt built on `template` instead of `prompts/system.md`.
+pub(crate) fn system_prompt_from_template(
+    template: String,
+    workspace: &Path,
+    session_addendum: Option<&str>,
+) -> String {
+    let mut prompt = template;
 
     if let Some(overview) = vtcode_core::utils::utils::build_project_overview(workspace) {
         prompt.push_str("\n\n## PROJECT OVERVIEW\n");
@@ -14,6 +26,15 @@ pub(crate) fn read_system_prompt(workspace: &Path, session_addendum: Option<&str
         prompt.push_str(&guidelines);
     }
 
+    if let Some(risk) = vtcode_core::core::risk_map::RiskMap::load(workspace)
+        .ok()
+        .flatten()
+        .and_then(|map| map.prompt_section(RISK_MAP_PROMPT_LIMIT))
+    {
+        prompt.push_str("\n\n## RISK MAP\n");
+        prompt.push_str(&risk);
+    }
+
     if let Some(addendum) = session_addendum {
         let trimmed = addendum.trim();
         if !trimmed.is_empty() {
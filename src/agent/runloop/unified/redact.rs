@@ -0,0 +1,57 @@
+use anyhow::Result;
+
+use vtcode_core::config::core::RedactionConfig;
+use vtcode_core::llm::provider as uni;
+use vtcode_core::utils::ansi::{AnsiRenderer, MessageStyle};
+use vtcode_core::utils::redact::{Redactor, WORKSPACE_PLACEHOLDER};
+use vtcode_core::utils::session_archive::{
+    SessionArchive, SessionMessage, redacted_copy_path, write_snapshot,
+};
+use vtcode_core::utils::transcript;
+
+/// Write a redacted copy of the running session next to the archived ones.
+pub(crate) fn redact_current_session(
+    archive: Option<&SessionArchive>,
+    history: &[uni::Message],
+    distinct_tools: Vec<String>,
+    config: &RedactionConfig,
+    workspace: &std::path::Path,
+    patterns: &[String],
+    renderer: &mut AnsiRenderer,
+) -> Result<()> {
+    let Some(archive) = archive else {
+        return renderer.line(
+            MessageStyle::Error,
+            "Session archiving is unavailable, so there is no transcript to redact.",
+        );
+    };
+
+    let mut redactor = match patterns
+        .iter()
+        .try_fold(Redactor::new(config)?, |redactor, pattern| {
+            redactor.with_pattern(pattern)
+        }) {
+        Ok(redactor) => redactor.with_known_path(workspace, WORKSPACE_PLACEHOLDER),
+        Err(err) => return renderer.line(MessageStyle::Error, &err.to_string()),
+    };
+
+    let messages: Vec<SessionMessage> = history.iter().map(SessionMessage::from).collect();
+    let snapshot = archive.snapshot(
+        transcript::snapshot(),
+        history.len(),
+        distinct_tools,
+        messages,
+    );
+    let redacted = redactor.redact_snapshot(&snapshot);
+    let path = redacted_copy_path(&archive.identifier())?;
+    write_snapshot(&path, &redacted)?;
+
+    renderer.line(
+        MessageStyle::Info,
+        &format!("Redacted {}.", redactor.report().summary()),
+    )?;
+    renderer.line(
+        MessageStyle::Info,
+        &format!("Sanitized copy written to {}", path.display()),
+    )
+}
@@ -0,0 +1,136 @@
+use anyhow::{Context, Result};
+use vtcode_core::config::api_keys::{ApiKeySources, get_api_key};
+use vtcode_core::config::core::{RefusalConfig, RefusalRetry};
+use vtcode_core::config::loader::VTCodeConfig;
+use vtcode_core::llm::factory::create_provider_with_config;
+use vtcode_core::llm::provider::{self as uni, LLMProvider};
+use vtcode_core::llm::refusal::{Refusal, sanitize_for_retry};
+use vtcode_core::utils::ansi::{AnsiRenderer, MessageStyle};
+
+/// What the turn loop does after a refusal.
+pub(crate) enum RefusalOutcome {
+    /// Send the request again
+    Retry,
+    /// Drop the request; the refusal has been reported
+    GiveUp,
+}
+
+/// Reports safety refusals and applies the configured retry once per turn.
+pub(crate) struct RefusalHandler {
+    config: RefusalConfig,
+    retried: bool,
+    /// Primary provider parked while the fallback finishes the turn
+    parked_provider: Option<Box<dyn LLMProvider>>,
+}
+
+impl RefusalHandler {
+    pub(crate) fn new(vt_cfg: Option<&VTCodeConfig>) -> Self {
+        Self {
+            config: vt_cfg.map(|cfg| cfg.refusal.clone()).unwrap_or_default(),
+            retried: false,
+            parked_provider: None,
+        }
+    }
+
+    /// Model to request while the fallback provider serves the turn.
+    pub(crate) fn model_override(&self) -> Option<&str> {
+        self.parked_provider
+            .as_ref()
+            .and(self.config.fallback_model.as_deref())
+    }
+
+    /// Allow a new retry and put the primary provider back for the next turn.
+    pub(crate) fn start_turn(&mut self, provider_client: &mut Box<dyn LLMProvider>) {
+        self.retried = false;
+        if let Some(primary) = self.parked_provider.take() {
+            *provider_client = primary;
+        }
+    }
+
+    /// Report `refusal` and decide whether to retry. A sanitized retry
+    /// rewrites `history` in place; a fallback retry swaps `provider_client`.
+    pub(crate) fn handle(
+        &mut self,
+        refusal: &Refusal,
+        history: &mut [uni::Message],
+        provider_client: &mut Box<dyn LLMProvider>,
+        renderer: &mut AnsiRenderer,
+    ) -> Result<RefusalOutcome> {
+        renderer.line(MessageStyle::Refusal, &refusal.summary())?;
+        if self.retried {
+            renderer.line(
+                MessageStyle::Info,
+                "The retry was declined as well. Rephrase the request or switch models.",
+            )?;
+            return Ok(RefusalOutcome::GiveUp);
+        }
+
+        match self.config.retry {
+            RefusalRetry::Off => {
+                renderer.line(
+                    MessageStyle::Info,
+                    "The refusal was kept out of the conversation. Rephrase the request, or set `retry` under [refusal] in vtcode.toml to retry automatically.",
+                )?;
+                Ok(RefusalOutcome::GiveUp)
+            }
+            RefusalRetry::Sanitized => {
+                let withheld = sanitize_for_retry(history);
+                if withheld == 0 {
+                    renderer.line(
+                        MessageStyle::Info,
+                        "This turn has no tool output to withhold, so the request was not retried.",
+                    )?;
+                    return Ok(RefusalOutcome::GiveUp);
+                }
+                self.retried = true;
+                renderer.line(
+                    MessageStyle::Info,
+                    &format!(
+                        "Retrying with {} tool result{} from this turn withheld.",
+                        withheld,
+                        if withheld == 1 { "" } else { "s" }
+                    ),
+                )?;
+                Ok(RefusalOutcome::Retry)
+            }
+            RefusalRetry::Fallback => match self.create_fallback() {
+                Ok(fallback) => {
+                    self.retried = true;
+                    renderer.line(
+                        MessageStyle::Info,
+                        &format!(
+                            "Retrying with {} ({}) for the rest of this turn.",
+                            fallback.name(),
+                            self.config.fallback_model.as_deref().unwrap_or_default()
+                        ),
+                    )?;
+                    self.parked_provider = Some(std::mem::replace(provider_client, fallback));
+                    Ok(RefusalOutcome::Retry)
+                }
+                Err(err) => {
+                    renderer.line(
+                        MessageStyle::Error,
+                        &format!("Fallback provider unavailable: {:#}", err),
+                    )?;
+                    Ok(RefusalOutcome::GiveUp)
+                }
+            },
+        }
+    }
+
+    fn create_fallback(&self) -> Result<Box<dyn LLMProvider>> {
+        let provider = self
+            .config
+            .fallback_provider
+            .as_deref()
+            .context("[refusal] fallback_provider is not set")?;
+        let model = self
+            .config
+            .fallback_model
+            .clone()
+            .context("[refusal] fallback_model is not set")?;
+        let api_key = get_api_key(provider, &ApiKeySources::default()).ok();
+        let fallback = create_provider_with_config(provider, api_key, None, Some(model), None)?;
+        Ok(fallback)
+    }
+}
@@ -0,0 +1,137 @@
+use serde_json::Value;
+use std::path::Path;
+
+use vtcode_core::config::constants::tools;
+use vtcode_core::core::risk_map::{FileRisk, RiskLevel, RiskMap};
+use vtcode_core::ui::diff_renderer::generate_unified_diff;
+
+const MAX_PREVIEW_LINES: usize = 80;
+const PATCH_FILE_MARKERS: [&str; 3] = ["*** Update File: ", "*** Add File: ", "*** Delete File: "];
+
+/// A diff preview for a write tool that targets high-risk files.
+pub(crate) struct RiskyEditPreview {
+    pub(crate) files: Vec<FileRisk>,
+    pub(crate) lines: Vec<String>,
+}
+
+/// Build a diff preview when a write tool call touches a high-risk file.
+///
+/// Returns `None` for read-only tools and for edits that only touch files
+/// outside the high-risk band.
+pub(crate) fn risky_edit_preview(
+    map: &RiskMap,
+    workspace: &Path,
+    tool: &str,
+    args: &Value,
+) -> Option<RiskyEditPreview> {
+    let path = args.get("path").and_then(Value::as_str);
+    let (files, diff) = match tool {
+        tools::EDIT_FILE | tools::WRITE_FILE | tools::CREATE_FILE | tools::DELETE_FILE => {
+            let path = path?;
+            let risk = high_risk_file(map, workspace, path)?;
+            let current = std::fs::read_to_string(workspace.join(path)).unwrap_or_default();
+            let updated = updated_content(tool, args, &current)?;
+            (
+                vec![risk.clone()],
+                generate_unified_diff(&current, &updated, path),
+            )
+        }
+        tools::APPLY_PATCH => {
+            let patch = args.get("input").and_then(Value::as_str)?;
+            let files: Vec<FileRisk> = patch_targets(patch)
+                .filter_map(|target| high_risk_file(map, workspace, target).cloned())
+                .collect();
+            if files.is_empty() {
+                return None;
+            }
+            (files, patch.to_string())
+        }
+        _ => return None,
+    };
+
+    let mut lines: Vec<String> = diff.lines().map(str::to_string).collect();
+    if lines.len() > MAX_PREVIEW_LINES {
+        let hidden = lines.len() - MAX_PREVIEW_LINES;
+        lines.truncate(MAX_PREVIEW_LINES);
+        lines.push(format!("... {hidden} more diff lines"));
+    }
+    Some(RiskyEditPreview { files, lines })
+}
+
+fn high_risk_file<'a>(map: &'a RiskMap, workspace: &Path, path: &str) -> Option<&'a FileRisk> {
+    map.file(workspace, path)
+        .filter(|file| file.level == RiskLevel::High)
+}
+
+/// The file content a write tool would leave behind.
+fn updated_content(tool: &str, args: &Value, current: &str) -> Option<String> {
+    match tool {
+        tools::EDIT_FILE => {
+            let old = args.get("old_str").and_then(Value::as_str)?;
+            let new = args.get("new_str").and_then(Value::as_str)?;
+            Some(current.replacen(old, new, 1))
+        }
+        tools::WRITE_FILE | tools::CREATE_FILE => {
+            let content = args.get("content").and_then(Value::as_str)?;
+            if args.get("mode").and_then(Value::as_str) == Some("append") {
+                Some(format!("{current}{content}"))
+            } else {
+                Some(content.to_string())
+            }
+        }
+        tools::DELETE_FILE => Some(String::new()),
+        _ => None,
+    }
+}
+
+fn patch_targets(patch: &str) -> impl Iterator<Item = &str> {
+    patch.lines().filter_map(|line| {
+        PATCH_FILE_MARKERS
+            .iter()
+            .find_map(|marker| line.strip_prefix(marker))
+            .map(str::trim)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn map_with(path: &str, level: RiskLevel) -> RiskMap {
+        RiskMap {
+            generated_at: chrono::Utc::now(),
+            churn_days: 180,
+            files: vec![FileRisk {
+                path: path.to_string(),
+                score: 90.0,
+                level,
+                churn: 12,
+                lines: 400,
+                complexity: 60,
+                todos: 3,
+            }],
+        }
+    }
+
+    #[test]
+    fn previews_edits_to_high_risk_files_only() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("hot.rs"), "fn a() {}\nfn b() {}\n").unwrap();
+        let map = map_with("hot.rs", RiskLevel::High);
+
+        let args = json!({"path": "hot.rs", "old_str": "fn b() {}", "new_str": "fn c() {}"});
+        let preview = risky_edit_preview(&map, dir.path(), tools::EDIT_FILE, &args).unwrap();
+        assert_eq!(preview.files[0].path, "hot.rs");
+        assert!(preview.lines.iter().any(|line| line == "-fn b() {}"));
+        assert!(preview.lines.iter().any(|line| line == "+fn c() {}"));
+
+        assert!(risky_edit_preview(&map, dir.path(), tools::READ_FILE, &args).is_none());
+        let calm = map_with("hot.rs", RiskLevel::Medium);
+        assert!(risky_edit_preview(&calm, dir.path(), tools::EDIT_FILE, &args).is_none());
+
+        let patch = json!({"input": "*** Begin Patch\n*** Update File: hot.rs\n@@\n-fn a() {}\n+fn z() {}\n*** End Patch"});
+        let preview = risky_edit_preview(&map, dir.path(), tools::APPLY_PATCH, &patch).unwrap();
+        assert_eq!(preview.files.len(), 1);
+    }
+}
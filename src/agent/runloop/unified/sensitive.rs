@@ -0,0 +1,108 @@
+use anyhow::Result;
+use std::path::Path;
+
+use vtcode_core::core::guardrails::{Guardrails, OVERRIDE_PHRASE};
+use vtcode_core::core::sensitive_files::{
+    SensitiveFile, SensitivePaths, ignore_suggestions, scan_workspace, set_read_denied,
+};
+use vtcode_core::utils::ansi::{AnsiRenderer, MessageStyle};
+
+const MAX_LISTED_FILES: usize = 10;
+
+/// Paths flagged in `workspace`, scanning it when this is the first session
+/// there, with their reads denied in `guardrails` and left out of the results
+/// of tools that walk the workspace.
+pub(crate) async fn load_sensitive_paths(
+    workspace: &Path,
+    guardrails: &mut Guardrails,
+    renderer: &mut AnsiRenderer,
+) -> Result<SensitivePaths> {
+    let paths = match SensitivePaths::load(workspace) {
+        Some(paths) => paths,
+        None => {
+            let files = scan_workspace(workspace);
+            let paths = SensitivePaths::from_scan(&files);
+            if let Err(err) = paths.save(workspace) {
+                tracing::warn!(error = %err, "failed to save sensitive path scan");
+            }
+            if !files.is_empty() {
+                let suggestions = ignore_suggestions(workspace, &files).await;
+                for line in first_run_lines(&files, &suggestions) {
+                    renderer.line(MessageStyle::Info, &line)?;
+                }
+                renderer.line_if_not_empty(MessageStyle::Output)?;
+            }
+            paths
+        }
+    };
+    guardrails.deny_reads(paths.denied());
+    set_read_denied(paths.denied());
+    Ok(paths)
+}
+
+/// Let tools read `path` in this workspace from now on.
+pub(crate) fn allow_sensitive_path(
+    workspace: &Path,
+    paths: &mut SensitivePaths,
+    guardrails: &mut Guardrails,
+    path: &str,
+) -> Result<()> {
+    guardrails.allow_read(path);
+    if paths.allow(path) {
+        set_read_denied(paths.denied());
+        paths.save(workspace)?;
+    }
+    Ok(())
+}
+
+fn first_run_lines(files: &[SensitiveFile], suggestions: &[String]) -> Vec<String> {
+    let mut lines = vec![format!(
+        "First session in this workspace: {} {} may contain secrets. Tools cannot read {} until you allow it:",
+        files.len(),
+        if files.len() == 1 { "file" } else { "files" },
+        if files.len() == 1 { "it" } else { "them" },
+    )];
+    lines.extend(
+        files
+            .iter()
+            .take(MAX_LISTED_FILES)
+            .map(|file| format!("  {} ({})", file.path, file.reason)),
+    );
+    if files.len() > MAX_LISTED_FILES {
+        lines.push(format!("  ... and {} more", files.len() - MAX_LISTED_FILES));
+    }
+    lines.push(format!(
+        "Type '{OVERRIDE_PHRASE}' when a tool asks for one of them to allow it."
+    ));
+    if !suggestions.is_empty() {
+        lines.push(format!(
+            "To keep them out of the agent's file listings, add to .vtcodegitignore: {}",
+            suggestions.join(", ")
+        ));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_flagged_files_and_suggestions() {
+        let files = [SensitiveFile {
+            path: ".env".to_string(),
+            pattern: ".env",
+            reason: "environment variables",
+        }];
+        let lines = first_run_lines(&files, &[".env".to_string()]);
+        assert_eq!(
+            lines,
+            [
+                "First session in this workspace: 1 file may contain secrets. Tools cannot read it until you allow it:",
+                "  .env (environment variables)",
+                "Type 'override' when a tool asks for one of them to allow it.",
+                "To keep them out of the agent's file listings, add to .vtcodegitignore: .env",
+            ]
+        );
+    }
+}
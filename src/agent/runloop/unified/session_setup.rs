@@ -1,12 +1,25 @@
 use anyhow::{Context, Result, anyhow};
+use std::time::Duration;
 
+use vtcode_core::config::api_keys::{ApiKeySources, get_api_key, resolve_credential_profiles};
+use vtcode_core::config::constants::tools as tool_names;
 use vtcode_core::config::loader::VTCodeConfig;
+use vtcode_core::config::oauth::TokenSource;
 use vtcode_core::config::types::AgentConfig as CoreAgentConfig;
+use vtcode_core::core::agent::subagents::spawn_subagents_tool;
+use vtcode_core::core::daemon::{WarmState, fetch_symbol_index, fetch_warm_state};
 use vtcode_core::core::decision_tracker::DecisionTracker;
+use vtcode_core::core::prompt_injection::UNTRUSTED_CONTENT_DIRECTIVE;
+use vtcode_core::core::tool_stats::ToolStats;
+use vtcode_core::core::tool_verbosity::full_output_tool;
 use vtcode_core::core::trajectory::TrajectoryLogger;
-use vtcode_core::llm::{factory::create_provider_with_config, provider as uni};
+use vtcode_core::llm::{
+    CredentialUsage, FailoverClient, FailoverTarget, FailoverUsage, OAuthProvider,
+    RotatingProvider, factory::create_provider_with_config, provider as uni,
+};
 use vtcode_core::models::ModelId;
 use vtcode_core::tools::ToolRegistry;
+use vtcode_core::tools::WorkspaceEnv;
 use vtcode_core::tools::build_function_declarations;
 
 use super::prompts::read_system_prompt;
@@ -14,10 +27,18 @@ use crate::agent::runloop::context::ContextTrimConfig;
 use crate::agent::runloop::context::load_context_trim_config;
 use crate::agent::runloop::telemetry::build_trajectory_logger;
 use crate::agent::runloop::welcome::{SessionBootstrap, prepare_session_bootstrap};
+use crate::workspace_trust;
+
+/// How long to wait for the symbol index a daemon serves.
+const SYMBOL_INDEX_TIMEOUT: Duration = Duration::from_secs(10);
 
 pub(crate) struct SessionState {
     pub session_bootstrap: SessionBootstrap,
     pub provider_client: Box<dyn uni::LLMProvider>,
+    /// Set when requests rotate over named credential profiles
+    pub credential_usage: Option<CredentialUsage>,
+    /// Set when `[llm.failover]` lists providers to fall back to
+    pub failover_usage: Option<FailoverUsage>,
     pub tool_registry: ToolRegistry,
     pub tools: Vec<uni::ToolDefinition>,
     pub trim_config: ContextTrimConfig,
@@ -26,6 +47,10 @@ pub(crate) struct SessionState {
     pub trajectory: TrajectoryLogger,
     pub base_system_prompt: String,
     pub full_auto_allowlist: Option<Vec<String>>,
+    /// State served by a running `vtcode daemon`, when one answered
+    pub warm_state: Option<WarmState>,
+    /// Tool usage learned in earlier sessions; `None` with `[tools.stats]` off
+    pub tool_stats: Option<ToolStats>,
 }
 
 pub(crate) async fn initialize_session(
@@ -33,7 +58,17 @@ pub(crate) async fn initialize_session(
     vt_cfg: Option<&VTCodeConfig>,
     full_auto: bool,
 ) -> Result<SessionState> {
-    let session_bootstrap = prepare_session_bootstrap(config, vt_cfg);
+    let daemon_cfg = vt_cfg.map(|cfg| cfg.daemon.clone()).unwrap_or_default();
+    let warm_state = if daemon_cfg.attach {
+        fetch_warm_state(
+            &config.workspace,
+            Duration::from_millis(daemon_cfg.connect_timeout_ms),
+        )
+        .await
+    } else {
+        None
+    };
+    let session_bootstrap = prepare_session_bootstrap(config, vt_cfg, warm_state.as_ref());
     let provider_name = if config.provider.trim().is_empty() {
         config
             .model
@@ -44,14 +79,11 @@ pub(crate) async fn initialize_session(
     } else {
         config.provider.to_lowercase()
     };
-    let provider_client = create_provider_with_config(
-        &provider_name,
-        Some(config.api_key.clone()),
-        None,
-        Some(config.model.clone()),
-        Some(config.prompt_cache.clone()),
-    )
-    .context("Failed to initialize provider client")?;
+    let trajectory = build_trajectory_logger(&config.workspace, vt_cfg);
+    let (provider_client, credential_usage) =
+        create_session_provider(&provider_name, config, vt_cfg, &trajectory).await?;
+    let (provider_client, failover_usage) =
+        with_failover(provider_client, &provider_name, config, vt_cfg, &trajectory)?;
 
     let mut tool_registry = ToolRegistry::new(config.workspace.clone());
     tool_registry.initialize_async().await?;
@@ -62,6 +94,30 @@ pub(crate) async fn initialize_session(
                 err
             );
         }
+        match WorkspaceEnv::load(&config.workspace, &cfg.tools.env, &cfg.security.redaction) {
+            Ok(env) => tool_registry.set_workspace_env(env),
+            Err(err) => eprintln!(
+                "Warning: Failed to load workspace environment files: {}",
+                err
+            ),
+        }
+    }
+    if warm_state.is_some() {
+        // The index can be large; tools parse files themselves until it arrives
+        let index = tool_registry.symbol_index();
+        let workspace = config.workspace.clone();
+        tokio::spawn(async move {
+            if let Some(served) = fetch_symbol_index(&workspace, SYMBOL_INDEX_TIMEOUT).await {
+                index.lock().adopt(served);
+            }
+        });
+    }
+    match workspace_trust::write_grants(&config.workspace) {
+        Ok(grants) => tool_registry.path_grants().set(grants),
+        Err(err) => eprintln!(
+            "Warning: Failed to load write grants for paths outside the workspace: {}",
+            err
+        ),
     }
 
     let mut full_auto_allowlist = None;
@@ -77,24 +133,59 @@ pub(crate) async fn initialize_session(
         full_auto_allowlist = Some(allowlist);
     }
 
+    let sql_enabled = vt_cfg.is_some_and(|cfg| cfg.tools.sql.enabled);
+    let vulnerability_scan_enabled = vt_cfg.is_some_and(|cfg| cfg.tools.vulnerability_scan.enabled);
+    let semantic_search_enabled = vt_cfg.is_some_and(|cfg| cfg.tools.semantic_search.enabled);
     let declarations = build_function_declarations();
-    let tools: Vec<uni::ToolDefinition> = declarations
+    let mut tools: Vec<uni::ToolDefinition> = declarations
         .into_iter()
+        .filter(|decl| sql_enabled || !tool_names::SQL_TOOLS.contains(&decl.name.as_str()))
+        .filter(|decl| vulnerability_scan_enabled || decl.name != tool_names::VULNERABILITY_SCAN)
+        .filter(|decl| semantic_search_enabled || decl.name != tool_names::SEMANTIC_SEARCH)
         .map(|decl| uni::ToolDefinition::function(decl.name, decl.description, decl.parameters))
         .collect();
+    if vt_cfg.is_none_or(|cfg| cfg.tools.verbosity.enabled) {
+        tools.push(full_output_tool());
+    }
+    if let Some(subagents) = vt_cfg.map(|cfg| &cfg.agents.subagents)
+        && subagents.enabled
+        && !subagents.definitions.is_empty()
+    {
+        tools.push(spawn_subagents_tool(subagents));
+    }
+
+    let stats_cfg = vt_cfg
+        .map(|cfg| cfg.tools.stats.clone())
+        .unwrap_or_default();
+    let tool_stats = stats_cfg
+        .enabled
+        .then(|| ToolStats::load(&config.workspace));
+    if stats_cfg.guide_model
+        && let Some(stats) = &tool_stats
+    {
+        stats.annotate(&mut tools);
+    }
 
     let trim_config = load_context_trim_config(vt_cfg);
     let conversation_history: Vec<uni::Message> = vec![];
     let ledger = DecisionTracker::new();
-    let trajectory = build_trajectory_logger(&config.workspace, vt_cfg);
-    let base_system_prompt = read_system_prompt(
+    let mut base_system_prompt = read_system_prompt(
         &config.workspace,
         session_bootstrap.prompt_addendum.as_deref(),
     );
+    if let Some(cfg) = vt_cfg
+        && cfg.security.prompt_injection.enabled
+        && cfg.security.prompt_injection.system_directive
+    {
+        base_system_prompt.push_str("\n\n## UNTRUSTED CONTENT\n");
+        base_system_prompt.push_str(UNTRUSTED_CONTENT_DIRECTIVE);
+    }
 
     Ok(SessionState {
         session_bootstrap,
         provider_client,
+        credential_usage,
+        failover_usage,
         tool_registry,
         tools,
         trim_config,
@@ -103,5 +194,118 @@ pub(crate) async fn initialize_session(
         trajectory,
         base_system_prompt,
         full_auto_allowlist,
+        warm_state,
+        tool_stats,
     })
 }
+
+/// Create the session provider, routing requests through the provider's
+/// credential profiles when any are configured, or through its OAuth sign-in.
+async fn create_session_provider(
+    provider_name: &str,
+    config: &CoreAgentConfig,
+    vt_cfg: Option<&VTCodeConfig>,
+    trajectory: &TrajectoryLogger,
+) -> Result<(Box<dyn uni::LLMProvider>, Option<CredentialUsage>)> {
+    let create = |api_key: String| {
+        create_provider_with_config(
+            provider_name,
+            Some(api_key),
+            config.base_url.clone(),
+            Some(config.model.clone()),
+            Some(config.prompt_cache.clone()),
+        )
+        .context("Failed to initialize provider client")
+    };
+
+    let Some(credentials) = vt_cfg.map(|cfg| &cfg.credentials) else {
+        return Ok((create(config.api_key.clone())?, None));
+    };
+    let resolved = resolve_credential_profiles(provider_name, credentials)?;
+    if resolved.is_empty() {
+        if let Some(tokens) = TokenSource::for_provider(provider_name, credentials)? {
+            let build = {
+                let provider_name = provider_name.to_string();
+                let model = config.model.clone();
+                let base_url = config.base_url.clone();
+                let prompt_cache = config.prompt_cache.clone();
+                Box::new(move |token: String| {
+                    create_provider_with_config(
+                        &provider_name,
+                        Some(token),
+                        base_url.clone(),
+                        Some(model.clone()),
+                        Some(prompt_cache.clone()),
+                    )
+                })
+            };
+            let provider = OAuthProvider::connect(provider_name, tokens, build)
+                .await
+                .context("Failed to initialize provider client")?;
+            return Ok((Box::new(provider), None));
+        }
+        return Ok((create(config.api_key.clone())?, None));
+    }
+
+    let rotate_on_quota = credentials
+        .for_provider(provider_name)
+        .map(|provider| provider.rotate_on_quota)
+        .unwrap_or(true);
+    let mut profiles = Vec::with_capacity(resolved.len());
+    for credential in resolved {
+        profiles.push((credential.profile, create(credential.api_key)?));
+    }
+    let rotating = RotatingProvider::new(provider_name, profiles, rotate_on_quota)
+        .with_trajectory(trajectory.clone());
+    let usage = rotating.usage();
+    Ok((Box::new(rotating), Some(usage)))
+}
+
+/// Put the session provider first in a failover chain with the providers of
+/// `[llm.failover]`, when failover is enabled and lists any.
+fn with_failover(
+    primary: Box<dyn uni::LLMProvider>,
+    provider_name: &str,
+    config: &CoreAgentConfig,
+    vt_cfg: Option<&VTCodeConfig>,
+    trajectory: &TrajectoryLogger,
+) -> Result<(Box<dyn uni::LLMProvider>, Option<FailoverUsage>)> {
+    let Some(failover) = vt_cfg
+        .map(|cfg| &cfg.llm.failover)
+        .filter(|failover| failover.enabled && !failover.providers.is_empty())
+    else {
+        return Ok((primary, None));
+    };
+    let mut targets = vec![FailoverTarget {
+        provider: provider_name.to_string(),
+        model: config.model.clone(),
+        client: primary,
+    }];
+    for target in &failover.providers {
+        let api_key = match &target.api_key_env {
+            Some(name) => Some(std::env::var(name).with_context(|| {
+                format!(
+                    "{name} is not set for failover provider {}",
+                    target.provider
+                )
+            })?),
+            None => get_api_key(&target.provider, &ApiKeySources::default()).ok(),
+        };
+        let client = create_provider_with_config(
+            &target.provider,
+            api_key,
+            target.base_url.clone(),
+            Some(target.model.clone()),
+            Some(config.prompt_cache.clone()),
+        )
+        .with_context(|| format!("Failed to initialize failover provider {}", target.provider))?;
+        targets.push(FailoverTarget {
+            provider: target.provider.clone(),
+            model: target.model.clone(),
+            client,
+        });
+    }
+    let client = FailoverClient::new(targets, failover).with_trajectory(trajectory.clone());
+    let usage = client.usage();
+    Ok((Box::new(client), Some(usage)))
+}
@@ -0,0 +1,140 @@
+use anyhow::Result;
+use chrono::Utc;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::Notify;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::task;
+
+use vtcode_core::core::session_summary::SessionSummary;
+use vtcode_core::ui::tui::{RatatuiEvent, RatatuiHandle};
+use vtcode_core::utils::ansi::{AnsiRenderer, MessageStyle};
+use vtcode_core::utils::session_archive::SessionArchive;
+
+const SUMMARY_PLACEHOLDER: &str =
+    "Save summary? [s]ummary.md · [c]hangelog · [b]oth · [n]o (Enter skips)";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SummaryTarget {
+    SessionFile,
+    Changelog,
+    Both,
+    Skip,
+}
+
+fn parse_target(input: &str) -> Option<SummaryTarget> {
+    match input.trim().to_lowercase().as_str() {
+        "s" | "summary" | "summary.md" => Some(SummaryTarget::SessionFile),
+        "c" | "changelog" => Some(SummaryTarget::Changelog),
+        "b" | "both" => Some(SummaryTarget::Both),
+        "" | "n" | "no" | "skip" => Some(SummaryTarget::Skip),
+        _ => None,
+    }
+}
+
+/// Identifier for the session's summary directory, shared with the archive file when there is one.
+pub(crate) fn summary_session_id(archive: Option<&SessionArchive>) -> String {
+    archive
+        .and_then(|archive| archive.path().file_stem())
+        .and_then(|stem| stem.to_str())
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("session-{}", Utc::now().format("%Y%m%dT%H%M%SZ")))
+}
+
+/// Show the session summary and write it wherever the user picks.
+pub(crate) async fn offer_session_summary(
+    summary: &SessionSummary,
+    workspace: &Path,
+    renderer: &mut AnsiRenderer,
+    handle: &RatatuiHandle,
+    events: &mut UnboundedReceiver<RatatuiEvent>,
+    ctrl_c_flag: &Arc<AtomicBool>,
+    ctrl_c_notify: &Arc<Notify>,
+) -> Result<()> {
+    renderer.line_if_not_empty(MessageStyle::Output)?;
+    for line in summary.to_markdown().lines() {
+        let style = if line.starts_with('#') {
+            MessageStyle::Tool
+        } else {
+            MessageStyle::Info
+        };
+        renderer.line(style, line)?;
+    }
+    renderer.line(MessageStyle::Info, "")?;
+
+    let target = loop {
+        handle.set_placeholder(Some(SUMMARY_PLACEHOLDER.to_string()));
+        task::yield_now().await;
+
+        let Some(input) = next_summary_input(events, ctrl_c_flag, ctrl_c_notify).await else {
+            break SummaryTarget::Skip;
+        };
+        match parse_target(&input) {
+            Some(target) => break target,
+            None => renderer.line(
+                MessageStyle::Info,
+                "Respond with 's' for summary.md, 'c' for CHANGELOG.md, 'b' for both, or 'n' to skip.",
+            )?,
+        }
+    };
+
+    if matches!(target, SummaryTarget::SessionFile | SummaryTarget::Both) {
+        match summary.write_session_file(workspace) {
+            Ok(path) => renderer.line(
+                MessageStyle::Info,
+                &format!("Summary written to {}", path.display()),
+            )?,
+            Err(err) => renderer.line(
+                MessageStyle::Error,
+                &format!("Failed to write summary: {}", err),
+            )?,
+        }
+    }
+    if matches!(target, SummaryTarget::Changelog | SummaryTarget::Both) {
+        match summary.append_to_changelog(workspace) {
+            Ok(path) => renderer.line(
+                MessageStyle::Info,
+                &format!("Changelog entry added to {}", path.display()),
+            )?,
+            Err(err) => renderer.line(
+                MessageStyle::Error,
+                &format!("Failed to update changelog: {}", err),
+            )?,
+        }
+    }
+    Ok(())
+}
+
+/// Next submitted line, or `None` when the user cancels, exits, or interrupts.
+async fn next_summary_input(
+    events: &mut UnboundedReceiver<RatatuiEvent>,
+    ctrl_c_flag: &Arc<AtomicBool>,
+    ctrl_c_notify: &Arc<Notify>,
+) -> Option<String> {
+    loop {
+        if ctrl_c_flag.load(Ordering::SeqCst) {
+            return None;
+        }
+
+        let notify = ctrl_c_notify.clone();
+        let event = tokio::select! {
+            _ = notify.notified(), if !ctrl_c_flag.load(Ordering::SeqCst) => None,
+            event = events.recv() => event,
+        }?;
+
+        match event {
+            RatatuiEvent::Submit(text) => return Some(text),
+            RatatuiEvent::Cancel | RatatuiEvent::Exit | RatatuiEvent::Interrupt => return None,
+            RatatuiEvent::ExplainSelection(_)
+            | RatatuiEvent::Copy(_)
+            | RatatuiEvent::Fork(_)
+            | RatatuiEvent::Undo
+            | RatatuiEvent::ToggleVoice
+            | RatatuiEvent::ScrollLineUp
+            | RatatuiEvent::ScrollLineDown
+            | RatatuiEvent::ScrollPageUp
+            | RatatuiEvent::ScrollPageDown => {}
+        }
+    }
+}
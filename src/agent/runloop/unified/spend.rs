@@ -0,0 +1,117 @@
+use anyhow::Result;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::Notify;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::task;
+
+use vtcode_core::config::BudgetAction;
+use vtcode_core::core::spend_tracker::SpendTracker;
+use vtcode_core::llm::provider as uni;
+use vtcode_core::ui::tui::{RatatuiEvent, RatatuiHandle};
+use vtcode_core::utils::ansi::{AnsiRenderer, MessageStyle};
+
+const BUDGET_PLACEHOLDER: &str = "Keep going past the spend budget? [y]es · [n]o";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BudgetOutcome {
+    Continue,
+    Stop,
+    Exit,
+    Interrupt,
+}
+
+/// Add the usage of a response to the spend totals. `served` is the
+/// `provider/model` a failover chain actually used, when there is one.
+pub(crate) fn record_response_spend(
+    tracker: &mut SpendTracker,
+    served: Option<String>,
+    provider: &str,
+    model: &str,
+    usage: Option<&uni::Usage>,
+) {
+    match served.as_deref().and_then(|label| label.split_once('/')) {
+        Some((provider, model)) => tracker.record(provider, model, usage),
+        None => tracker.record(provider, model, usage),
+    };
+}
+
+/// Check the spend budgets before a request goes out. Depending on
+/// `action`, a used-up budget stops the request or asks whether to keep going
+/// for the rest of the session.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn check_spend_budget(
+    tracker: &mut SpendTracker,
+    action: BudgetAction,
+    renderer: &mut AnsiRenderer,
+    handle: &RatatuiHandle,
+    events: &mut UnboundedReceiver<RatatuiEvent>,
+    default_placeholder: Option<String>,
+    ctrl_c_flag: &Arc<AtomicBool>,
+    ctrl_c_notify: &Arc<Notify>,
+) -> Result<BudgetOutcome> {
+    let Some(exceeded) = tracker.exceeded() else {
+        return Ok(BudgetOutcome::Continue);
+    };
+    renderer.line_if_not_empty(MessageStyle::Info)?;
+    renderer.line(MessageStyle::Error, &format!("Spend budget: {exceeded}."))?;
+    if action == BudgetAction::Abort {
+        renderer.line(
+            MessageStyle::Info,
+            "The request was not sent. Raise the limits under [llm.spend] to continue.",
+        )?;
+        return Ok(BudgetOutcome::Stop);
+    }
+    renderer.line(
+        MessageStyle::Info,
+        "Answer 'yes' to keep going for the rest of this session; anything else stops the request.",
+    )?;
+
+    handle.set_placeholder(Some(BUDGET_PLACEHOLDER.to_string()));
+    task::yield_now().await;
+    let outcome = loop {
+        if ctrl_c_flag.load(Ordering::SeqCst) {
+            break BudgetOutcome::Interrupt;
+        }
+
+        let notify = ctrl_c_notify.clone();
+        let maybe_event = tokio::select! {
+            _ = notify.notified(), if !ctrl_c_flag.load(Ordering::SeqCst) => None,
+            event = events.recv() => event,
+        };
+
+        let Some(event) = maybe_event else {
+            if ctrl_c_flag.load(Ordering::SeqCst) {
+                break BudgetOutcome::Interrupt;
+            }
+            break BudgetOutcome::Exit;
+        };
+
+        match event {
+            RatatuiEvent::Submit(input) => {
+                if matches!(input.trim().to_lowercase().as_str(), "y" | "yes") {
+                    tracker.allow_overrun();
+                    break BudgetOutcome::Continue;
+                }
+                break BudgetOutcome::Stop;
+            }
+            RatatuiEvent::Cancel => break BudgetOutcome::Stop,
+            RatatuiEvent::Exit => break BudgetOutcome::Exit,
+            RatatuiEvent::Interrupt => break BudgetOutcome::Interrupt,
+            RatatuiEvent::ExplainSelection(_)
+            | RatatuiEvent::Copy(_)
+            | RatatuiEvent::Fork(_)
+            | RatatuiEvent::Undo
+            | RatatuiEvent::ToggleVoice
+            | RatatuiEvent::ScrollLineUp
+            | RatatuiEvent::ScrollLineDown
+            | RatatuiEvent::ScrollPageUp
+            | RatatuiEvent::ScrollPageDown => {}
+        }
+    };
+    handle.set_placeholder(default_placeholder);
+    if outcome == BudgetOutcome::Stop {
+        renderer.line(MessageStyle::Info, "The request was not sent.")?;
+    }
+    Ok(outcome)
+}
@@ -0,0 +1,117 @@
+use anyhow::Result;
+use std::collections::HashMap;
+
+use vtcode_core::config::SubagentsConfig;
+use vtcode_core::config::loader::VTCodeConfig;
+use vtcode_core::config::types::AgentConfig as CoreAgentConfig;
+use vtcode_core::core::agent::subagents::{Subagent, SubagentReport, SubagentTask, parse_tasks};
+use vtcode_core::utils::ansi::{AnsiRenderer, MessageStyle};
+
+use super::dual_answer::connect_model;
+
+const TASK_PREVIEW_CHARS: usize = 80;
+
+/// The agents `spawn_subagents` can start, connected on first use.
+pub(crate) struct Subagents {
+    config: SubagentsConfig,
+    connected: HashMap<String, Subagent>,
+}
+
+impl Subagents {
+    /// `None` unless `[agents.subagents]` is enabled and defines an agent.
+    pub(crate) fn new(vt_cfg: Option<&VTCodeConfig>) -> Option<Self> {
+        let config = vt_cfg?.agents.subagents.clone();
+        (config.enabled && !config.definitions.is_empty()).then(|| Self {
+            config,
+            connected: HashMap::new(),
+        })
+    }
+
+    pub(crate) fn max_parallel(&self) -> usize {
+        self.config.max_parallel
+    }
+
+    /// The agent and task of each entry of a call, or the error to send back
+    /// to the model.
+    pub(crate) fn prepare(
+        &mut self,
+        args: &serde_json::Value,
+        config: &CoreAgentConfig,
+    ) -> Result<Vec<(Subagent, SubagentTask)>, String> {
+        let tasks = parse_tasks(&self.config, args)?;
+        let mut jobs = Vec::with_capacity(tasks.len());
+        for task in tasks {
+            let agent = self
+                .connect(&task.agent, config)
+                .map_err(|err| format!("Could not start `{}`: {err:#}", task.agent))?;
+            jobs.push((agent, task));
+        }
+        Ok(jobs)
+    }
+
+    fn connect(&mut self, name: &str, config: &CoreAgentConfig) -> Result<Subagent> {
+        if let Some(agent) = self.connected.get(name) {
+            return Ok(agent.clone());
+        }
+        let Some(definition) = self.config.definitions.iter().find(|def| def.name == name) else {
+            anyhow::bail!("no sub-agent named `{name}`");
+        };
+        let model = match definition.model.trim() {
+            "" => config.model.clone(),
+            model => model.to_string(),
+        };
+        let provider = connect_model(&definition.provider, &model, config)?;
+        let agent = Subagent::new(definition.clone(), provider, model);
+        self.connected.insert(name.to_string(), agent.clone());
+        Ok(agent)
+    }
+}
+
+/// One line per agent about to start.
+pub(crate) fn render_started(
+    renderer: &mut AnsiRenderer,
+    jobs: &[(Subagent, SubagentTask)],
+) -> Result<()> {
+    for (agent, task) in jobs {
+        let first_line = task.task.lines().next().unwrap_or_default();
+        let mut preview: String = first_line.chars().take(TASK_PREVIEW_CHARS).collect();
+        if preview.len() < task.task.trim_end().len() {
+            preview.push('…');
+        }
+        renderer.line(
+            MessageStyle::Info,
+            &format!("{} ({}): {preview}", agent.name(), agent.model()),
+        )?;
+    }
+    Ok(())
+}
+
+/// One line per finished agent; the reports themselves go to the model.
+pub(crate) fn render_reports(
+    renderer: &mut AnsiRenderer,
+    reports: &[SubagentReport],
+) -> Result<()> {
+    for report in reports {
+        let tools = if report.tools_used.is_empty() {
+            "no tools".to_string()
+        } else {
+            report.tools_used.join(", ")
+        };
+        match &report.error {
+            None => renderer.line(
+                MessageStyle::Info,
+                &format!(
+                    "{} reported after {} turn{} ({tools})",
+                    report.agent,
+                    report.turns,
+                    if report.turns == 1 { "" } else { "s" }
+                ),
+            )?,
+            Some(error) => renderer.line(
+                MessageStyle::Error,
+                &format!("{} stopped: {error}", report.agent),
+            )?,
+        }
+    }
+    Ok(())
+}
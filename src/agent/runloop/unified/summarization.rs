@@ -0,0 +1,119 @@
+use anyhow::{Context, Result};
+
+use vtcode_core::config::SummarizationConfig;
+use vtcode_core::config::api_keys::{ApiKeySources, get_api_key};
+use vtcode_core::config::loader::VTCodeConfig;
+use vtcode_core::config::types::AgentConfig as CoreAgentConfig;
+use vtcode_core::core::history_summary::{HistorySummarizer, SummaryModel};
+use vtcode_core::llm::factory::create_provider_with_config;
+use vtcode_core::llm::provider as uni;
+use vtcode_core::utils::ansi::{AnsiRenderer, MessageStyle};
+
+/// Section of the system prompt that carries the summary.
+const SUMMARY_HEADER: &str = "[Earlier Conversation Summary]";
+
+/// Summarizer for trimmed history, or `None` when `[context.summarization]`
+/// is off or its model is unavailable without a fallback.
+pub(crate) fn create_history_summarizer(
+    config: &CoreAgentConfig,
+    vt_cfg: Option<&VTCodeConfig>,
+    renderer: &mut AnsiRenderer,
+) -> Result<Option<HistorySummarizer>> {
+    let Some(settings) = vt_cfg
+        .map(|cfg| &cfg.context.summarization)
+        .filter(|settings| settings.enabled)
+    else {
+        return Ok(None);
+    };
+    let dedicated = match create_summary_model(config, settings) {
+        Ok(model) => model,
+        Err(err) if settings.fallback_to_main => {
+            renderer.line(
+                MessageStyle::Info,
+                &format!(
+                    "Summarization model unavailable ({err:#}); using {}.",
+                    config.model
+                ),
+            )?;
+            None
+        }
+        Err(err) => {
+            renderer.line(
+                MessageStyle::Error,
+                &format!("Summarization is off: {err:#}"),
+            )?;
+            return Ok(None);
+        }
+    };
+    Ok(Some(HistorySummarizer::new(settings, dedicated)))
+}
+
+fn create_summary_model(
+    config: &CoreAgentConfig,
+    settings: &SummarizationConfig,
+) -> Result<Option<SummaryModel>> {
+    if settings.provider.is_none() && settings.model.is_none() && settings.base_url.is_none() {
+        return Ok(None);
+    }
+    let provider = settings.provider.as_deref().unwrap_or(&config.provider);
+    let model = match &settings.model {
+        Some(model) => model.clone(),
+        None if settings.provider.is_none() && settings.base_url.is_none() => config.model.clone(),
+        None => {
+            anyhow::bail!("[context.summarization] model is required with provider or base_url")
+        }
+    };
+    let api_key = match &settings.api_key_env {
+        Some(name) => Some(std::env::var(name).with_context(|| format!("{name} is not set"))?),
+        None if provider == config.provider => Some(config.api_key.clone()),
+        None => get_api_key(provider, &ApiKeySources::default()).ok(),
+    };
+    let provider = create_provider_with_config(
+        provider,
+        api_key,
+        settings.base_url.clone(),
+        Some(model.clone()),
+        None,
+    )?;
+    Ok(Some(SummaryModel { provider, model }))
+}
+
+/// Fold `trimmed` into `summary`, reporting failures without interrupting the turn.
+pub(crate) async fn update_history_summary(
+    summarizer: &HistorySummarizer,
+    summary: &mut Option<String>,
+    trimmed: &[uni::Message],
+    provider: &dyn uni::LLMProvider,
+    main_model: &str,
+    renderer: &mut AnsiRenderer,
+) -> Result<()> {
+    match summarizer
+        .summarize(summary.as_deref(), trimmed, provider, main_model)
+        .await
+    {
+        Ok(updated) => {
+            if let Some(reason) = &updated.fallback_reason {
+                renderer.line(
+                    MessageStyle::Info,
+                    &format!(
+                        "Summarized the trimmed messages with {} instead ({reason}).",
+                        updated.model
+                    ),
+                )?;
+            }
+            *summary = Some(updated.text);
+        }
+        Err(err) => {
+            renderer.line(
+                MessageStyle::Error,
+                &format!("Could not summarize the trimmed messages: {err:#}"),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// The summary as a system prompt section.
+pub(crate) fn summary_prompt_section(summary: &str) -> String {
+    format!("{SUMMARY_HEADER}\n{summary}")
+}
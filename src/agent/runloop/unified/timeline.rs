@@ -0,0 +1,33 @@
+use anyhow::Result;
+
+use vtcode_core::core::timeline::SessionTimeline;
+use vtcode_core::ui::tui::{RatatuiHandle, RatatuiTimelineEntry};
+use vtcode_core::utils::ansi::{AnsiRenderer, MessageStyle};
+
+/// Open the timeline view with one entry per completed turn.
+pub(crate) fn show_timeline(
+    timeline: &SessionTimeline,
+    handle: &RatatuiHandle,
+    renderer: &mut AnsiRenderer,
+) -> Result<()> {
+    if timeline.is_empty() {
+        return renderer.line(
+            MessageStyle::Info,
+            "No completed turns yet; the timeline fills in as the session runs.",
+        );
+    }
+    let entries = timeline
+        .turns()
+        .iter()
+        .enumerate()
+        .map(|(index, turn)| RatatuiTimelineEntry {
+            turn: turn.number,
+            request: turn.request.clone(),
+            reply: turn.reply.clone(),
+            changed: turn.changed.clone(),
+            diff: timeline.diff_at(index),
+        })
+        .collect();
+    handle.show_timeline(entries);
+    Ok(())
+}
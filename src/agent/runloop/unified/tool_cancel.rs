@@ -0,0 +1,185 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::Notify;
+use tokio::sync::mpsc::UnboundedReceiver;
+
+use vtcode_core::core::agent::subagents::{Subagent, SubagentReport, SubagentTask, run_subagents};
+use vtcode_core::core::guardrails::Guardrails;
+use vtcode_core::core::prompt_injection::InjectionShield;
+use vtcode_core::llm::provider::ToolDefinition;
+use vtcode_core::tools::cancellation::CancellationToken;
+use vtcode_core::tools::registry::{
+    ToolCallOutcome, ToolCallRequest, ToolErrorType, ToolExecutionError, ToolRegistry,
+};
+use vtcode_core::ui::tui::RatatuiEvent;
+
+/// Why a running tool call was stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ToolStop {
+    /// Esc: stop the call and hand control back for the next prompt
+    Cancel,
+    Exit,
+    /// Ctrl+C
+    Interrupt,
+}
+
+fn stop_for(event: &RatatuiEvent) -> Option<ToolStop> {
+    match event {
+        RatatuiEvent::Cancel => Some(ToolStop::Cancel),
+        RatatuiEvent::Exit => Some(ToolStop::Exit),
+        RatatuiEvent::Interrupt => Some(ToolStop::Interrupt),
+        _ => None,
+    }
+}
+
+/// Run a tool call while still listening to the user. Esc, Ctrl+C or exit
+/// cancel the call; the registry gives it a short grace period to stop and
+/// then reports it as cancelled. Anything else typed meanwhile is queued in
+/// `deferred` for the main loop.
+pub(crate) async fn execute_cancellable(
+    tool_registry: &mut ToolRegistry,
+    name: &str,
+    args: Value,
+    events: &mut UnboundedReceiver<RatatuiEvent>,
+    deferred: &mut VecDeque<RatatuiEvent>,
+    ctrl_c_flag: &Arc<AtomicBool>,
+    ctrl_c_notify: &Arc<Notify>,
+) -> (Result<Value>, Option<ToolStop>) {
+    run_cancellable(
+        |cancel| tool_registry.execute_tool_cancellable(name, args, cancel),
+        events,
+        deferred,
+        ctrl_c_flag,
+        ctrl_c_notify,
+    )
+    .await
+}
+
+/// Run a batch of tool calls the same way as [`execute_cancellable`], with
+/// consecutive read-only calls running at the same time.
+pub(crate) async fn execute_batch_cancellable(
+    tool_registry: &mut ToolRegistry,
+    calls: Vec<ToolCallRequest>,
+    events: &mut UnboundedReceiver<RatatuiEvent>,
+    deferred: &mut VecDeque<RatatuiEvent>,
+    ctrl_c_flag: &Arc<AtomicBool>,
+    ctrl_c_notify: &Arc<Notify>,
+) -> (Vec<ToolCallOutcome>, Option<ToolStop>) {
+    run_cancellable(
+        |cancel| tool_registry.execute_tools_parallel(calls, cancel),
+        events,
+        deferred,
+        ctrl_c_flag,
+        ctrl_c_notify,
+    )
+    .await
+}
+
+/// Run the sub-agents of a `spawn_subagents` call the same way as
+/// [`execute_cancellable`]; a stop cancels every agent still working.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn run_subagents_cancellable(
+    tool_registry: &ToolRegistry,
+    jobs: Vec<(Subagent, SubagentTask)>,
+    tools: &[ToolDefinition],
+    guardrails: &Guardrails,
+    shield: &InjectionShield,
+    max_parallel: usize,
+    events: &mut UnboundedReceiver<RatatuiEvent>,
+    deferred: &mut VecDeque<RatatuiEvent>,
+    ctrl_c_flag: &Arc<AtomicBool>,
+    ctrl_c_notify: &Arc<Notify>,
+) -> (Vec<SubagentReport>, Option<ToolStop>) {
+    run_cancellable(
+        |cancel| {
+            run_subagents(
+                jobs,
+                tool_registry,
+                tools,
+                guardrails,
+                shield,
+                max_parallel,
+                cancel,
+            )
+        },
+        events,
+        deferred,
+        ctrl_c_flag,
+        ctrl_c_notify,
+    )
+    .await
+}
+
+async fn run_cancellable<F: Future>(
+    start: impl FnOnce(CancellationToken) -> F,
+    events: &mut UnboundedReceiver<RatatuiEvent>,
+    deferred: &mut VecDeque<RatatuiEvent>,
+    ctrl_c_flag: &Arc<AtomicBool>,
+    ctrl_c_notify: &Arc<Notify>,
+) -> (F::Output, Option<ToolStop>) {
+    let cancel = CancellationToken::new();
+    let call = start(cancel.clone());
+    tokio::pin!(call);
+
+    let mut stop = None;
+    loop {
+        if stop.is_none() && ctrl_c_flag.load(Ordering::SeqCst) {
+            stop = Some(ToolStop::Interrupt);
+            cancel.cancel();
+        }
+
+        let notify = ctrl_c_notify.clone();
+        tokio::select! {
+            result = &mut call => return (result, stop),
+            _ = notify.notified(), if stop.is_none() => {}
+            event = events.recv(), if stop.is_none() => match event {
+                Some(event) => match stop_for(&event) {
+                    Some(reason) => {
+                        stop = Some(reason);
+                        cancel.cancel();
+                    }
+                    None => deferred.push_back(event),
+                },
+                None => {
+                    stop = Some(ToolStop::Exit);
+                    cancel.cancel();
+                }
+            },
+        }
+    }
+}
+
+/// Tool response for a call skipped because an earlier one was cancelled.
+pub(crate) fn skipped_response(name: &str) -> String {
+    ToolExecutionError::new(
+        name.to_string(),
+        ToolErrorType::Cancelled,
+        "Not run: the user cancelled an earlier tool call in this turn".to_string(),
+    )
+    .to_json_value()
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_stop_keys_cancel_a_running_tool() {
+        assert_eq!(stop_for(&RatatuiEvent::Cancel), Some(ToolStop::Cancel));
+        assert_eq!(stop_for(&RatatuiEvent::Exit), Some(ToolStop::Exit));
+        assert_eq!(
+            stop_for(&RatatuiEvent::Interrupt),
+            Some(ToolStop::Interrupt)
+        );
+        assert_eq!(stop_for(&RatatuiEvent::Submit("next".to_string())), None);
+        assert_eq!(stop_for(&RatatuiEvent::ScrollPageUp), None);
+
+        let skipped: Value = serde_json::from_str(&skipped_response("grep_search")).unwrap();
+        assert_eq!(skipped["error"]["tool_name"], "grep_search");
+    }
+}
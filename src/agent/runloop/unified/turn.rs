@@ -1,9 +1,10 @@
 use anyhow::{Context, Result};
 use futures::StreamExt;
-use std::collections::{BTreeSet, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::path::Path;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::sync::Notify;
 use tokio::sync::mpsc::UnboundedReceiver;
 use tokio::task;
@@ -11,22 +12,68 @@ use tokio::time::sleep;
 
 use serde_json::Value;
 use unicode_width::UnicodeWidthStr;
+use vtcode_core::config::UiPerformanceConfig;
+use vtcode_core::config::constants::context as context_defaults;
 use vtcode_core::config::constants::defaults;
+use vtcode_core::config::constants::project_doc as project_doc_constants;
 use vtcode_core::config::constants::tools as tool_names;
 use vtcode_core::config::loader::VTCodeConfig;
 use vtcode_core::config::types::AgentConfig as CoreAgentConfig;
-use vtcode_core::core::decision_tracker::{Action as DTAction, DecisionOutcome};
+use vtcode_core::core::agent::subagents::merge_reports;
+use vtcode_core::core::change_ledger::ChangeLedger;
+use vtcode_core::core::context_providers::{ContextProviderRegistry, ContextQuery};
+use vtcode_core::core::decision_tracker::{Action as DTAction, DecisionOutcome, DecisionTracker};
+use vtcode_core::core::evidence::EvidenceLog;
+use vtcode_core::core::guardrails::{
+    GuardrailKind, GuardrailViolation, Guardrails, OVERRIDE_PHRASE,
+};
+use vtcode_core::core::live_share::ShareLink;
+use vtcode_core::core::progress::ProgressEvent;
+use vtcode_core::core::prompt_injection::InjectionShield;
+use vtcode_core::core::provenance::Provenance;
+use vtcode_core::core::reproducibility::Reproducibility;
+use vtcode_core::core::risk_map::RiskMap;
 use vtcode_core::core::router::{Router, TaskClass};
+use vtcode_core::core::run_events::{
+    FailOn, RunEventLog, RunStatus, SELF_ASSESSMENT_PROMPT, self_assessment,
+};
+use vtcode_core::core::session_store::{SessionSnapshot, SessionStore, StoredSession};
+use vtcode_core::core::session_summary::SessionSummary;
+use vtcode_core::core::spend_tracker::SpendTracker;
+use vtcode_core::core::timeline::SessionTimeline;
+use vtcode_core::core::token_calibration::{
+    TokenCalibration, estimate_request_tokens, reported_prompt_tokens,
+};
+use vtcode_core::core::tool_exposure::{READ_ONLY_LEVEL, ToolExposure};
+use vtcode_core::core::tool_result_store::ToolResultStore;
+use vtcode_core::core::tool_schema::{ConstrainedDecoding, ToolSchemaBudget, tool_envelope_schema};
+use vtcode_core::core::tool_stats::output_succeeded;
+use vtcode_core::core::tool_verbosity::{ToolVerbosity, full_output_response};
+use vtcode_core::core::trajectory::{SessionRecord, config_hash};
+use vtcode_core::core::turn_latency::TurnLatency;
 use vtcode_core::llm::error_display;
 use vtcode_core::llm::provider::{self as uni, LLMStreamEvent};
-use vtcode_core::tools::registry::{ToolErrorType, ToolExecutionError, ToolPermissionDecision};
+use vtcode_core::llm::refusal::Refusal;
+use vtcode_core::tools::EditConflict;
+use vtcode_core::tools::WorkspaceEnv;
+use vtcode_core::tools::WritePermissionRequired;
+use vtcode_core::tools::registry::{
+    ToolCallOutcome, ToolCallRequest, ToolErrorType, ToolExecutionError, ToolPermissionDecision,
+};
+use vtcode_core::tools::vulnerabilities::VulnerabilityContextProvider;
+use vtcode_core::ui::locale::{self, Locale, Message, set_ui_locale};
 use vtcode_core::ui::theme;
 use vtcode_core::ui::tui::{
     RatatuiEvent, RatatuiHandle, RatatuiTextStyle, convert_style as convert_ratatui_style,
     spawn_session, theme_from_styles,
 };
 use vtcode_core::utils::ansi::{AnsiRenderer, MessageStyle};
-use vtcode_core::utils::session_archive::{SessionArchive, SessionArchiveMetadata, SessionMessage};
+use vtcode_core::utils::environment_manifest::EnvironmentManifest;
+use vtcode_core::utils::session_archive::{
+    SessionArchive, SessionArchiveMetadata, SessionMessage, resolve_sessions_dir,
+};
+use vtcode_core::utils::session_compaction::compact_if_due;
+use vtcode_core::utils::session_title::title_from_exchange;
 use vtcode_core::utils::transcript;
 
 use crate::agent::runloop::context::{
@@ -35,14 +82,55 @@ use crate::agent::runloop::context::{
 use crate::agent::runloop::git::confirm_changes_with_git_diff;
 use crate::agent::runloop::is_context_overflow_error;
 use crate::agent::runloop::prompt::refine_user_prompt_if_enabled;
-use crate::agent::runloop::slash_commands::{SlashCommandOutcome, handle_slash_command};
-use crate::agent::runloop::text_tools::detect_textual_tool_call;
+use crate::agent::runloop::slash_commands::{
+    OfflineRequest, OutlineRequest, SlashCommandOutcome, ToolAccessRequest, handle_slash_command,
+    open_split_pane,
+};
+use crate::agent::runloop::text_tools::{
+    MAX_JSON_ENVELOPE_RETRIES, detect_textual_tool_call, json_envelope_correction,
+    json_envelope_instructions, parse_json_tool_envelope,
+};
 use crate::agent::runloop::tool_output::render_tool_output;
 use crate::agent::runloop::ui::render_session_banner;
-
+use crate::workspace_trust;
+
+use super::briefing::{load_briefing, render_briefing, save_checkpoint};
+use super::cache_warmup::{WarmupOutcome, warm_prompt_cache};
+use super::clipboard::{copy_to_clipboard, paste_from_clipboard};
+use super::code_execution::CodeExecutionOutput;
+use super::conflict::{ConflictChoice, ConflictOutcome, record_resolution, resolve_edit_conflict};
+use super::context_view::{ContextSources, show_context};
 use super::display::{display_user_message, ensure_turn_bottom_gap, persist_theme_preference};
+use super::dual_answer::{
+    AnswerChoice, DualAnswer, PendingAnswer, PickOutcome, handle_dual_request, pick_answer,
+};
+use super::evidence::{render_claim_links, show_evidence};
+use super::explain::build_explain_prompt;
+use super::fork::{ForkPoints, fork_conversation, fork_status};
+use super::handoff::{HandoffSources, write_handoff};
+use super::offline::OfflineMode;
+use super::outline::OutlineTracker;
+use super::path_grant::{GrantOutcome, record_declined, request_write_grant};
+use super::preview::{RequestPreviewOutcome, review_request_preview};
+use super::redact::redact_current_session;
+use super::refusal::{RefusalHandler, RefusalOutcome};
+use super::risk::{RiskyEditPreview, risky_edit_preview};
+use super::sensitive::{allow_sensitive_path, load_sensitive_paths};
 use super::session_setup::{SessionState, initialize_session};
+use super::session_summary::{offer_session_summary, summary_session_id};
 use super::shell::{derive_recent_tool_output, should_short_circuit_shell};
+use super::spend::{BudgetOutcome, check_spend_budget, record_response_spend};
+use super::subagents::{Subagents, render_reports, render_started};
+use super::summarization::{
+    create_history_summarizer, summary_prompt_section, update_history_summary,
+};
+use super::timeline::show_timeline;
+use super::tool_cancel::{
+    ToolStop, execute_batch_cancellable, execute_cancellable, run_subagents_cancellable,
+    skipped_response,
+};
+use super::undo::{attach_backup, back_up_before, undo_last_command};
+use super::voice::VoiceInput;
 
 #[derive(Default)]
 struct SessionStats {
@@ -143,6 +231,7 @@ fn render_tool_call_summary(
     renderer: &mut AnsiRenderer,
     tool_name: &str,
     args: &Value,
+    workspace_env: &WorkspaceEnv,
 ) -> Result<()> {
     let (headline, used_keys) = describe_tool_action(tool_name, args);
     renderer.line(MessageStyle::Info, &format!("→ {}", headline))?;
@@ -151,6 +240,11 @@ fn render_tool_call_summary(
     for bullet in bullets {
         renderer.line(MessageStyle::Output, &format!("    • {bullet}"))?;
     }
+    if matches!(tool_name, tool_names::RUN_TERMINAL_CMD | tool_names::BASH)
+        && let Some(summary) = workspace_env.summary()
+    {
+        renderer.line(MessageStyle::Output, &format!("    • Env: {summary}"))?;
+    }
 
     Ok(())
 }
@@ -480,7 +574,7 @@ async fn prompt_tool_permission(
     renderer.line(MessageStyle::Info, "")?;
 
     let _placeholder_guard = PlaceholderGuard::new(handle, default_placeholder);
-    let prompt_placeholder = Some(format!("Approve '{}' tool? y/n (Esc to cancel)", tool_name));
+    let prompt_placeholder = Some(locale::format(Message::ApproveTool, &[("tool", tool_name)]));
     handle.set_placeholder(prompt_placeholder);
 
     // Yield once so the UI processes the prompt lines and placeholder update
@@ -536,7 +630,12 @@ async fn prompt_tool_permission(
             RatatuiEvent::Interrupt => {
                 return Ok(HitlDecision::Interrupt);
             }
-            RatatuiEvent::ScrollLineUp
+            RatatuiEvent::ExplainSelection(_)
+            | RatatuiEvent::Copy(_)
+            | RatatuiEvent::Fork(_)
+            | RatatuiEvent::Undo
+            | RatatuiEvent::ToggleVoice
+            | RatatuiEvent::ScrollLineUp
             | RatatuiEvent::ScrollLineDown
             | RatatuiEvent::ScrollPageUp
             | RatatuiEvent::ScrollPageDown => {}
@@ -581,6 +680,245 @@ async fn ensure_tool_permission(
     }
 }
 
+/// Show the diff for an edit to a high-risk file and ask for confirmation.
+///
+/// Unlike the policy prompt, approval here is never remembered: every edit to
+/// a risky file gets its own preview.
+#[allow(clippy::too_many_arguments)]
+async fn confirm_risky_edit(
+    tool_name: &str,
+    preview: &RiskyEditPreview,
+    renderer: &mut AnsiRenderer,
+    handle: &RatatuiHandle,
+    events: &mut UnboundedReceiver<RatatuiEvent>,
+    default_placeholder: Option<String>,
+    ctrl_c_flag: &Arc<AtomicBool>,
+    ctrl_c_notify: &Arc<Notify>,
+) -> Result<ToolPermissionFlow> {
+    renderer.line_if_not_empty(MessageStyle::Info)?;
+    for file in &preview.files {
+        renderer.line(
+            MessageStyle::Info,
+            &format!(
+                "{} is high risk (score {:.0}: {} recent commits, {} decision points, {} TODOs). Review the change:",
+                file.path, file.score, file.churn, file.complexity, file.todos
+            ),
+        )?;
+    }
+    for line in &preview.lines {
+        renderer.line(MessageStyle::Output, line)?;
+    }
+    let decision = prompt_tool_permission(
+        tool_name,
+        renderer,
+        handle,
+        events,
+        ctrl_c_flag,
+        ctrl_c_notify,
+        default_placeholder,
+    )
+    .await?;
+    Ok(match decision {
+        HitlDecision::Approved => ToolPermissionFlow::Approved,
+        HitlDecision::Denied => ToolPermissionFlow::Denied,
+        HitlDecision::Exit => ToolPermissionFlow::Exit,
+        HitlDecision::Interrupt => ToolPermissionFlow::Interrupted,
+    })
+}
+
+/// Tell the user what a resumed session brought back.
+fn render_resumed_session(
+    saved: &StoredSession,
+    provider: &str,
+    model: &str,
+    renderer: &mut AnsiRenderer,
+) -> Result<()> {
+    let label = saved
+        .title
+        .clone()
+        .or_else(|| saved.first_prompt_preview())
+        .unwrap_or_else(|| "untitled".to_string());
+    renderer.line(
+        MessageStyle::Info,
+        &format!(
+            "Resumed session {} \"{}\" ({} turns, {} messages).",
+            saved.id,
+            label,
+            saved.turns,
+            saved.messages.len()
+        ),
+    )?;
+    if saved.provider != provider || saved.model != model {
+        renderer.line(
+            MessageStyle::Info,
+            &format!(
+                "It was started with {}/{}; continuing with {}/{}.",
+                saved.provider, saved.model, provider, model
+            ),
+        )?;
+    }
+    renderer.line_if_not_empty(MessageStyle::Output)?;
+    Ok(())
+}
+
+/// Store the session title and show it in the status bar. Returns the new
+/// center status so spinners restore it.
+fn apply_session_title(
+    title: &str,
+    archive: Option<&mut SessionArchive>,
+    model_status: &str,
+    forked_at: Option<usize>,
+    handle: &RatatuiHandle,
+) -> String {
+    if let Some(archive) = archive {
+        archive.set_title(title);
+    }
+    let mut center_status = format!("{title} · {model_status}");
+    if let Some(turn) = forked_at {
+        center_status = fork_status(&center_status, turn);
+    }
+    handle.update_status_bar(None, Some(center_status.clone()), None);
+    center_status
+}
+
+/// Put an idle session to sleep: pause the UI's periodic work, drop cached
+/// file contents and indexes, and close pooled provider connections. All of
+/// it comes back on demand once the user returns.
+async fn suspend_idle_session(
+    minutes: u64,
+    handle: &RatatuiHandle,
+    tool_registry: &vtcode_core::tools::registry::ToolRegistry,
+    provider: &dyn uni::LLMProvider,
+    renderer: &mut AnsiRenderer,
+) -> Result<()> {
+    let released = tool_registry.release_idle_resources().await;
+    provider.release_connections();
+    handle.suspend();
+    renderer.line(
+        MessageStyle::Info,
+        &format!(
+            "Idle for {minutes} min: released {released} cached entries and provider \
+             connections. Press any key to resume."
+        ),
+    )?;
+    Ok(())
+}
+
+/// The part of the system prompt that does not depend on the request: the
+/// base prompt, the decision ledger, the tool exposure note and the full-auto
+/// self-assessment. The prompt cache warm-up sends it as is.
+fn stable_system_prompt(
+    base_system_prompt: &str,
+    ledger: &DecisionTracker,
+    vt_cfg: Option<&VTCodeConfig>,
+    exposure: &ToolExposure,
+    full_auto: bool,
+) -> String {
+    let (lg_enabled, lg_max, lg_include) = vt_cfg
+        .map(|cfg| {
+            (
+                cfg.context.ledger.enabled,
+                cfg.context.ledger.max_entries,
+                cfg.context.ledger.include_in_prompt,
+            )
+        })
+        .unwrap_or((true, 12, true));
+    let mut system_prompt = if lg_enabled && lg_include {
+        format!(
+            "{}\n\n[Decision Ledger]\n{}",
+            base_system_prompt,
+            ledger.render_ledger_brief(lg_max)
+        )
+    } else {
+        base_system_prompt.to_string()
+    };
+    if let Some(note) = exposure.prompt_note() {
+        system_prompt.push_str("\n\n");
+        system_prompt.push_str(note);
+    }
+    if full_auto {
+        system_prompt.push_str("\n\n");
+        system_prompt.push_str(SELF_ASSESSMENT_PROMPT);
+    }
+    system_prompt
+}
+
+/// Ask the user to type the override phrase before running a call that trips
+/// a guardrail. Anything else denies the call.
+#[allow(clippy::too_many_arguments)]
+async fn confirm_guardrail_override(
+    violation: &GuardrailViolation,
+    allow_override: bool,
+    renderer: &mut AnsiRenderer,
+    handle: &RatatuiHandle,
+    events: &mut UnboundedReceiver<RatatuiEvent>,
+    default_placeholder: Option<String>,
+    ctrl_c_flag: &Arc<AtomicBool>,
+    ctrl_c_notify: &Arc<Notify>,
+) -> Result<ToolPermissionFlow> {
+    renderer.line_if_not_empty(MessageStyle::Info)?;
+    renderer.line(MessageStyle::Error, &format!("Guardrail: {violation}."))?;
+    if !allow_override {
+        renderer.line(MessageStyle::Info, "The call was blocked.")?;
+        return Ok(ToolPermissionFlow::Denied);
+    }
+    let hint = if violation.kind == GuardrailKind::SensitivePath {
+        format!(
+            "Type '{OVERRIDE_PHRASE}' to let tools read it in this workspace from now on; anything else blocks it."
+        )
+    } else {
+        format!("Type '{OVERRIDE_PHRASE}' to run it anyway; anything else blocks it.")
+    };
+    renderer.line(MessageStyle::Info, &hint)?;
+
+    let _placeholder_guard = PlaceholderGuard::new(handle, default_placeholder);
+    handle.set_placeholder(Some(format!(
+        "Type '{OVERRIDE_PHRASE}' to run the blocked call (Esc to block)"
+    )));
+    task::yield_now().await;
+
+    loop {
+        if ctrl_c_flag.load(Ordering::SeqCst) {
+            return Ok(ToolPermissionFlow::Interrupted);
+        }
+
+        let notify = ctrl_c_notify.clone();
+        let maybe_event = tokio::select! {
+            _ = notify.notified(), if !ctrl_c_flag.load(Ordering::SeqCst) => None,
+            event = events.recv() => event,
+        };
+
+        let Some(event) = maybe_event else {
+            if ctrl_c_flag.load(Ordering::SeqCst) {
+                return Ok(ToolPermissionFlow::Interrupted);
+            }
+            return Ok(ToolPermissionFlow::Exit);
+        };
+
+        match event {
+            RatatuiEvent::Submit(input) => {
+                if input.trim() == OVERRIDE_PHRASE {
+                    return Ok(ToolPermissionFlow::Approved);
+                }
+                renderer.line(MessageStyle::Info, "The call was blocked.")?;
+                return Ok(ToolPermissionFlow::Denied);
+            }
+            RatatuiEvent::Cancel => return Ok(ToolPermissionFlow::Denied),
+            RatatuiEvent::Exit => return Ok(ToolPermissionFlow::Exit),
+            RatatuiEvent::Interrupt => return Ok(ToolPermissionFlow::Interrupted),
+            RatatuiEvent::ExplainSelection(_)
+            | RatatuiEvent::Copy(_)
+            | RatatuiEvent::Fork(_)
+            | RatatuiEvent::Undo
+            | RatatuiEvent::ToggleVoice
+            | RatatuiEvent::ScrollLineUp
+            | RatatuiEvent::ScrollLineDown
+            | RatatuiEvent::ScrollPageUp
+            | RatatuiEvent::ScrollPageDown => {}
+        }
+    }
+}
+
 fn apply_prompt_style(handle: &RatatuiHandle) {
     let styles = theme::active_styles();
     let style = convert_ratatui_style(styles.primary);
@@ -659,10 +997,21 @@ impl StatusTickerInner {
         }
         let shimmer = Self::shimmer_text(&self.label, step);
         let elapsed = Self::format_elapsed(self.started_at.elapsed());
-        let text = format!("{spinner_frame} {shimmer} ({elapsed} • Esc to interrupt)");
+        let text = format!(
+            "{spinner_frame} {shimmer} ({elapsed} • {})",
+            locale::text(Message::EscToInterrupt)
+        );
         self.handle.update_status_bar(None, Some(text), None);
     }
 
+    /// Static status for minimal rendering, without the frame, shimmer or timer.
+    fn show(&self) {
+        if self.active.load(Ordering::SeqCst) {
+            let text = format!("{} ({})", self.label, locale::text(Message::EscToInterrupt));
+            self.handle.update_status_bar(None, Some(text), None);
+        }
+    }
+
     fn stop(&self) {
         if self.active.swap(false, Ordering::SeqCst) {
             if let Some(original) = &self.restore {
@@ -746,6 +1095,7 @@ impl PlaceholderSpinner {
         message: impl Into<String>,
         status_label: Option<String>,
         status_restore: Option<String>,
+        animate: bool,
     ) -> Self {
         let message = message.into();
         let active = Arc::new(AtomicBool::new(true));
@@ -763,16 +1113,25 @@ impl PlaceholderSpinner {
             let style = spinner_style.clone();
             let mut index = 0usize;
             let frame_count = PLACEHOLDER_SPINNER_FRAMES.len().max(1);
-            while spinner_active.load(Ordering::SeqCst) {
-                let frame = PLACEHOLDER_SPINNER_FRAMES[index % frame_count];
-                spinner_handle.set_placeholder_with_style(
-                    Some(format!("{frame} {message}")),
-                    Some(style.clone()),
-                );
+            if !animate {
+                spinner_handle
+                    .set_placeholder_with_style(Some(message.clone()), Some(style.clone()));
                 if let Some(status) = status_for_task.as_ref() {
-                    status.tick(frame, index);
+                    status.show();
+                }
+            }
+            while spinner_active.load(Ordering::SeqCst) {
+                if animate {
+                    let frame = PLACEHOLDER_SPINNER_FRAMES[index % frame_count];
+                    spinner_handle.set_placeholder_with_style(
+                        Some(format!("{frame} {message}")),
+                        Some(style.clone()),
+                    );
+                    if let Some(status) = status_for_task.as_ref() {
+                        status.tick(frame, index);
+                    }
+                    index = (index + 1) % frame_count;
                 }
-                index = (index + 1) % frame_count;
                 sleep(Duration::from_millis(120)).await;
             }
             if let Some(status) = status_for_task.as_ref() {
@@ -860,6 +1219,8 @@ async fn stream_and_render_response(
     request: uni::LLMRequest,
     spinner: &PlaceholderSpinner,
     renderer: &mut AnsiRenderer,
+    first_token: &mut Option<Instant>,
+    code_execution: &CodeExecutionOutput,
 ) -> Result<(uni::LLMResponse, bool), uni::LLMError> {
     let mut stream = provider.stream(request).await?;
     let provider_name = provider.name();
@@ -878,11 +1239,13 @@ async fn stream_and_render_response(
         }
     };
     let mut emitted_tokens = false;
+    let mut streamed_steps = false;
 
     while let Some(event_result) = stream.next().await {
         match event_result {
             Ok(LLMStreamEvent::Token { delta }) => {
                 finish_spinner(&mut spinner_active);
+                first_token.get_or_insert_with(Instant::now);
                 aggregated.push_str(&delta);
                 if supports_streaming_markdown {
                     rendered_line_count = renderer
@@ -900,7 +1263,26 @@ async fn stream_and_render_response(
                 }
                 emitted_tokens = true;
             }
-            Ok(LLMStreamEvent::Reasoning { .. }) => {}
+            Ok(LLMStreamEvent::Reasoning { .. }) => {
+                first_token.get_or_insert_with(Instant::now);
+            }
+            Ok(LLMStreamEvent::CodeExecution { step }) => {
+                finish_spinner(&mut spinner_active);
+                first_token.get_or_insert_with(Instant::now);
+                if emitted_tokens && !supports_streaming_markdown {
+                    renderer
+                        .line_if_not_empty(response_style)
+                        .map_err(|err| map_render_error(provider_name, err))?;
+                }
+                code_execution
+                    .render(renderer, &step)
+                    .map_err(|err| map_render_error(provider_name, err))?;
+                // Text after the step starts a new response block below it.
+                aggregated.clear();
+                rendered_line_count = 0;
+                needs_indent = true;
+                streamed_steps = true;
+            }
             Ok(LLMStreamEvent::Completed { response }) => {
                 final_response = Some(response);
             }
@@ -921,6 +1303,14 @@ async fn stream_and_render_response(
         uni::LLMError::Provider(formatted_error)
     })?;
 
+    if !streamed_steps {
+        for step in &response.code_execution {
+            code_execution
+                .render(renderer, step)
+                .map_err(|err| map_render_error(provider_name, err))?;
+        }
+    }
+
     if aggregated.is_empty() {
         if let Some(content) = response.content.clone() {
             if !content.is_empty() {
@@ -957,35 +1347,83 @@ enum TurnLoopResult {
     Cancelled,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn run_single_agent_loop_unified(
     config: &CoreAgentConfig,
     vt_cfg: Option<&VTCodeConfig>,
     skip_confirmations: bool,
     full_auto: bool,
-) -> Result<()> {
+    run_events: &RunEventLog,
+    fail_on: &[FailOn],
+    initial_prompt: Option<String>,
+    share: Option<ShareLink>,
+    resume: Option<StoredSession>,
+) -> Result<RunStatus> {
     let SessionState {
         session_bootstrap,
-        provider_client,
+        mut provider_client,
+        credential_usage,
+        failover_usage,
         mut tool_registry,
         tools,
-        trim_config,
+        mut trim_config,
         mut conversation_history,
         mut ledger,
         trajectory: traj,
         base_system_prompt,
         full_auto_allowlist,
+        warm_state,
+        mut tool_stats,
     } = initialize_session(config, vt_cfg, full_auto).await?;
+    let guide_with_stats = vt_cfg.is_none_or(|cfg| cfg.tools.stats.guide_model);
 
     let active_styles = theme::active_styles();
     let theme_spec = theme_from_styles(&active_styles);
     let default_placeholder = session_bootstrap.placeholder.clone();
+    let locale_config = vt_cfg
+        .map(|cfg| cfg.agent.locale.clone())
+        .unwrap_or_default();
+    let mut response_locale = Locale::parse(&locale_config.language).unwrap_or_else(|| {
+        tracing::warn!(
+            language = %locale_config.language,
+            "invalid [agent.locale] language; answering in English"
+        );
+        Locale::default()
+    });
+    if locale_config.localize_ui {
+        set_ui_locale(&response_locale);
+    }
+
+    // Low-bandwidth rendering paces redraws of streamed output and keeps
+    // spinners still, so slow SSH links are not flooded with frames
+    let minimal_render = config.render_mode.is_minimal();
+    let animate_spinners = !minimal_render;
+    let show_latency = vt_cfg.is_some_and(|cfg| cfg.ui.show_latency_breakdown);
+    let frame_interval = if minimal_render {
+        Duration::from_millis(
+            vt_cfg
+                .map(|cfg| cfg.ui.performance.minimal_frame_interval_ms)
+                .unwrap_or_else(|| UiPerformanceConfig::default().minimal_frame_interval_ms),
+        )
+    } else {
+        Duration::ZERO
+    };
     let session = spawn_session(
         theme_spec.clone(),
         default_placeholder.clone(),
         config.ui_surface,
+        frame_interval,
     )
     .context("failed to launch ratatui session")?;
     let handle = session.handle.clone();
+    handle.set_workspace(config.workspace.clone());
+    {
+        // Workspace indexing reports its progress in the status bar
+        let handle = handle.clone();
+        tool_registry.set_progress_sink(Arc::new(move |event: &ProgressEvent| {
+            handle.update_status_bar(Some(event.status_text()), None, None);
+        }));
+    }
     let highlight_config = vt_cfg
         .map(|cfg| cfg.syntax_highlighting.clone())
         .unwrap_or_default();
@@ -1009,9 +1447,33 @@ pub(crate) async fn run_single_agent_loop_unified(
         workspace_label,
         workspace_path,
         config.model.clone(),
-        provider_label,
+        provider_label.clone(),
         config.theme.clone(),
         config.reasoning_effort.as_str().to_string(),
+    )
+    .with_environment(EnvironmentManifest::capture(
+        vt_cfg.map(config_hash).unwrap_or_default(),
+        BTreeMap::from([
+            ("chat".to_string(), config.model.clone()),
+            (
+                "router".to_string(),
+                vt_cfg
+                    .map(|cfg| cfg.router.llm_router_model.clone())
+                    .unwrap_or_default(),
+            ),
+            (
+                "prompt refiner".to_string(),
+                vt_cfg
+                    .map(|cfg| cfg.agent.refine_prompts_model.clone())
+                    .unwrap_or_default(),
+            ),
+        ]),
+    ));
+    let mut session_record = SessionRecord::new(
+        String::new(),
+        provider_label,
+        config.model.clone(),
+        vt_cfg.map(config_hash).unwrap_or_default(),
     );
     let mut session_archive_error: Option<String> = None;
     let mut session_archive = match SessionArchive::new(archive_metadata) {
@@ -1030,8 +1492,13 @@ pub(crate) async fn run_single_agent_loop_unified(
     let reasoning_label = vt_cfg
         .map(|cfg| cfg.agent.reasoning_effort.as_str().to_string())
         .unwrap_or_else(|| config.reasoning_effort.as_str().to_string());
-    let center_status = format!("{} · {}", config.model, reasoning_label);
+    let model_status = format!("{} · {}", config.model, reasoning_label);
+    let mut center_status = model_status.clone();
     handle.update_status_bar(None, Some(center_status.clone()), None);
+    let mut title_locked = false;
+    let mut session_title: Option<String> = None;
+    let mut fork_points = ForkPoints::default();
+    let mut forked_at: Option<usize> = None;
 
     render_session_banner(&mut renderer, config, &session_bootstrap)?;
     if let Some(text) = session_bootstrap.welcome_text.as_ref() {
@@ -1066,6 +1533,47 @@ pub(crate) async fn run_single_agent_loop_unified(
         }
     }
 
+    if let Some(summary) = tool_registry.mocks().summary() {
+        renderer.line(MessageStyle::Info, &summary)?;
+    }
+    let workspace_env = tool_registry.workspace_env();
+    if let Some(summary) = workspace_env.summary() {
+        renderer.line(
+            MessageStyle::Info,
+            &format!("Terminal commands run with {summary}"),
+        )?;
+    }
+    if !workspace_env.withheld().is_empty() {
+        renderer.line(
+            MessageStyle::Info,
+            &format!(
+                "Withheld secret-looking variables: {} (add them to [tools.env] allow to pass them)",
+                workspace_env.withheld().join(", ")
+            ),
+        )?;
+    }
+
+    if let Some(sessions) = vt_cfg.map(|cfg| cfg.sessions.clone())
+        && sessions.auto_compact
+        && let Ok(sessions_dir) = resolve_sessions_dir()
+    {
+        let workspace = config.workspace.clone();
+        tokio::task::spawn_blocking(move || {
+            match compact_if_due(sessions_dir, &sessions, workspace) {
+                Ok(Some(report)) if report.compacted > 0 || report.dropped > 0 => {
+                    tracing::info!(
+                        compacted = report.compacted,
+                        dropped = report.dropped,
+                        freed = report.bytes_freed(),
+                        "compacted old sessions"
+                    );
+                }
+                Ok(_) => {}
+                Err(err) => tracing::warn!("session compaction failed: {err:#}"),
+            }
+        });
+    }
+
     let ctrl_c_flag = Arc::new(AtomicBool::new(false));
     let ctrl_c_notify = Arc::new(Notify::new());
     {
@@ -1080,17 +1588,259 @@ pub(crate) async fn run_single_agent_loop_unified(
     }
 
     let mut session_stats = SessionStats::default();
+    let mut request_preview_enabled = false;
+    let dual_settings = vt_cfg
+        .map(|cfg| cfg.agent.dual_answer.clone())
+        .unwrap_or_default();
+    let mut dual_answer = None;
+    if dual_settings.enabled {
+        match DualAnswer::connect(&dual_settings, &dual_settings.model, config) {
+            Ok(dual) => dual_answer = Some(dual),
+            Err(err) => renderer.line(
+                MessageStyle::Error,
+                &format!("Second answers are off: {err:#}"),
+            )?,
+        }
+    }
+    let mut subagents = Subagents::new(vt_cfg);
+    let mut reproducibility =
+        vt_cfg.and_then(|cfg| Reproducibility::new(&cfg.agent.reproducibility));
+    if let Some(repro) = &reproducibility {
+        renderer.line(
+            MessageStyle::Info,
+            &format!(
+                "Reproducibility mode: seed {}, temperature {}",
+                repro.seed(),
+                repro.temperature()
+            ),
+        )?;
+        if !provider_client.supports_seed(&config.model) {
+            renderer.line(
+                MessageStyle::Error,
+                &format!(
+                    "{} does not take a seed for {}; only the temperature is fixed.",
+                    provider_client.name(),
+                    config.model
+                ),
+            )?;
+        }
+    }
+    let json_textual_tools = vt_cfg
+        .map(|cfg| cfg.tools.json_textual_tools)
+        .unwrap_or(true);
+    let mut json_tool_protocol = false;
+    let mut outline = OutlineTracker::new(
+        &config.workspace,
+        tool_registry.tree_cache(),
+        vt_cfg
+            .map(|cfg| cfg.ui.show_symbol_outline)
+            .unwrap_or(false),
+    );
+    let mut offline = OfflineMode::new(handle.clone(), provider_client.name(), vt_cfg);
+    let mut refusals = RefusalHandler::new(vt_cfg);
+    let code_execution = CodeExecutionOutput::new(&config.workspace, vt_cfg);
+    offline
+        .detect_at_startup(&mut provider_client, &mut renderer, warm_state.as_ref())
+        .await?;
+    let briefing_enabled = vt_cfg.is_none_or(|cfg| cfg.ui.session_briefing);
+    let mut briefing = if briefing_enabled {
+        load_briefing(&config.workspace)
+    } else {
+        None
+    };
+    if briefing.is_some() {
+        render_briefing(briefing.as_ref(), &mut renderer)?;
+    }
+    let mut changes = ChangeLedger::new();
+    let mut timeline = SessionTimeline::new(config.workspace.clone());
+    let mut evidence_log = EvidenceLog::new();
+    let risk_map = RiskMap::load(&config.workspace).ok().flatten();
+    let mut guardrails = match vt_cfg {
+        Some(cfg) => Guardrails::new(&cfg.security.guardrails, &config.workspace)?,
+        None => Guardrails::disabled(&config.workspace),
+    };
+    let mut last_backup = None;
+    let mut tool_results = ToolResultStore::new(
+        &vt_cfg
+            .map(|cfg| cfg.context.spill.clone())
+            .unwrap_or_default(),
+    );
+    let verbosity = ToolVerbosity::new(
+        &vt_cfg
+            .map(|cfg| cfg.tools.verbosity.clone())
+            .unwrap_or_default(),
+    );
+    let injection_shield = match vt_cfg {
+        Some(cfg) => InjectionShield::new(&cfg.security.prompt_injection, &config.workspace)?,
+        None => InjectionShield::disabled(&config.workspace),
+    };
+    let mut sensitive_paths = match vt_cfg {
+        Some(cfg) if cfg.security.guardrails.enabled && cfg.security.guardrails.sensitive_files => {
+            Some(load_sensitive_paths(&config.workspace, &mut guardrails, &mut renderer).await?)
+        }
+        _ => None,
+    };
+    let mut exposure = ToolExposure::new(
+        &vt_cfg
+            .map(|cfg| cfg.tools.exposure.clone())
+            .unwrap_or_default(),
+    );
+    let schema_config = vt_cfg
+        .map(|cfg| cfg.tools.schema.clone())
+        .unwrap_or_default();
+    let constrained_config = vt_cfg
+        .map(|cfg| cfg.tools.constrained_decoding.clone())
+        .unwrap_or_default();
+    let history_summarizer = create_history_summarizer(config, vt_cfg, &mut renderer)?;
+    let mut history_summary: Option<String> = None;
+    let mut gathered_context: Vec<(Provenance, usize)> = Vec::new();
+    let mut token_calibration = vt_cfg
+        .is_none_or(|cfg| cfg.context.calibrate_tokens)
+        .then(TokenCalibration::load_default);
+    let spend_config = vt_cfg.map(|cfg| cfg.llm.spend.clone()).unwrap_or_default();
+    let budget_action = spend_config.on_exceeded;
+    let mut spend_tracker = spend_config
+        .enabled
+        .then(|| SpendTracker::load_default(spend_config));
+    let context_providers = vt_cfg
+        .map(|cfg| {
+            let settings = &cfg.context.providers;
+            let mut registry = if settings.enabled {
+                ContextProviderRegistry::from_config(settings, &config.workspace)
+            } else {
+                ContextProviderRegistry::new(settings)
+            };
+            if cfg.tools.vulnerability_scan.enabled {
+                registry.register(Arc::new(VulnerabilityContextProvider::new(
+                    &config.workspace,
+                    cfg.tools.vulnerability_scan.clone(),
+                )));
+            }
+            registry
+        })
+        .filter(|registry| !registry.is_empty());
+    let clipboard_enabled = vt_cfg.map(|cfg| cfg.ui.clipboard).unwrap_or(true);
+    let mut voice_input =
+        VoiceInput::new(&vt_cfg.map(|cfg| cfg.ui.voice.clone()).unwrap_or_default());
+    let idle = vt_cfg.map(|cfg| cfg.agent.idle.clone()).unwrap_or_default();
+    let idle_timeout = (idle.enabled && idle.minutes > 0)
+        .then(|| Duration::from_secs(idle.minutes.saturating_mul(60)));
     let mut events = session.events;
+    let mut remote_input = None;
+    if let Some(share) = share {
+        let access = if share.remote_input.is_some() {
+            "viewers can send prompts"
+        } else {
+            "read-only"
+        };
+        renderer.line(
+            MessageStyle::Info,
+            &format!("Sharing this session ({access}) at {}", share.url),
+        )?;
+        remote_input = share.remote_input;
+    }
+    let mut resumed_turns = 0;
+    let session_store = match resume {
+        Some(saved) => {
+            let store = SessionStore::resume(&config.workspace, &saved);
+            conversation_history = saved.history();
+            history_summary = saved.history_summary.clone();
+            ledger = saved.decisions.clone();
+            resumed_turns = saved.turns;
+            if let Some(title) = saved.title.as_deref() {
+                title_locked = true;
+                session_title = Some(title.to_string());
+                center_status = apply_session_title(
+                    title,
+                    session_archive.as_mut(),
+                    &model_status,
+                    forked_at,
+                    &handle,
+                );
+            }
+            render_resumed_session(
+                &saved,
+                &session_record.provider,
+                &config.model,
+                &mut renderer,
+            )?;
+            store
+        }
+        None => SessionStore::create(&config.workspace),
+    };
+    if !offline.is_active() {
+        // Input stays enabled: a first message typed meanwhile is queued until
+        // the warm-up finishes. Tools come first in the cached prefix, so warm
+        // with the set and prompt the first turn sends.
+        let warm_tools =
+            ToolSchemaBudget::new(&schema_config, &config.model, trim_config.max_tokens)
+                .shape(
+                    exposure.available_tools(&tools),
+                    TaskClass::Standard,
+                    &[],
+                    tool_stats.as_ref(),
+                )
+                .tools;
+        let warm_prompt =
+            stable_system_prompt(&base_system_prompt, &ledger, vt_cfg, &exposure, full_auto);
+        let outcome = warm_prompt_cache(
+            provider_client.as_ref(),
+            config,
+            vt_cfg,
+            &warm_prompt,
+            &warm_tools,
+        )
+        .await;
+        if let WarmupOutcome::Skipped(reason) = &outcome {
+            tracing::debug!(%reason, "skipped prompt cache warm-up");
+        }
+        if let Some(message) = outcome.message() {
+            renderer.line(MessageStyle::Info, &message)?;
+        }
+    }
+    // Handled before new input: the opening task of commands that start a
+    // session with one, and input typed while a tool was running.
+    let mut pending_events: VecDeque<RatatuiEvent> = initial_prompt
+        .map(RatatuiEvent::Submit)
+        .into_iter()
+        .collect();
     loop {
         if ctrl_c_flag.load(Ordering::SeqCst) {
             break;
         }
 
-        let maybe_event = tokio::select! {
-            biased;
-
-            _ = ctrl_c_notify.notified() => None,
-            event = events.recv() => event,
+        let maybe_event = match pending_events.pop_front() {
+            Some(event) => Some(event),
+            None => {
+                let mut suspended = false;
+                loop {
+                    tokio::select! {
+                        biased;
+
+                        _ = ctrl_c_notify.notified() => break None,
+                        event = events.recv() => break event,
+                        Some(prompt) = async { remote_input.as_mut()?.recv().await },
+                            if remote_input.is_some() =>
+                        {
+                            renderer.line(MessageStyle::Info, "Prompt from a remote viewer:")?;
+                            break Some(RatatuiEvent::Submit(prompt));
+                        }
+                        _ = sleep(idle_timeout.unwrap_or_default()),
+                            if idle_timeout.is_some() && !suspended =>
+                        {
+                            suspended = true;
+                            suspend_idle_session(
+                                idle.minutes,
+                                &handle,
+                                &tool_registry,
+                                provider_client.as_ref(),
+                                &mut renderer,
+                            )
+                            .await?;
+                        }
+                    }
+                }
+            }
         };
 
         let Some(event) = maybe_event else {
@@ -1099,6 +1849,54 @@ pub(crate) async fn run_single_agent_loop_unified(
 
         let submitted = match event {
             RatatuiEvent::Submit(text) => text,
+            RatatuiEvent::ExplainSelection(selection) => {
+                let prompt = build_explain_prompt(&config.workspace, &selection);
+                let message = if prompt.files.is_empty() {
+                    "Explaining the selected output.".to_string()
+                } else {
+                    format!(
+                        "Explaining the selected output with context from {}.",
+                        prompt.files.join(", ")
+                    )
+                };
+                renderer.line(MessageStyle::Info, &message)?;
+                prompt.text
+            }
+            RatatuiEvent::Copy(target) => {
+                copy_to_clipboard(
+                    target,
+                    &conversation_history,
+                    clipboard_enabled,
+                    &handle,
+                    &mut renderer,
+                )?;
+                continue;
+            }
+            RatatuiEvent::Fork(turn) => {
+                if let Some(turn) = fork_conversation(
+                    &mut fork_points,
+                    turn,
+                    &mut conversation_history,
+                    session_archive.as_ref(),
+                    &tool_results,
+                    session_stats.sorted_tools(),
+                    &handle,
+                    &mut renderer,
+                )? {
+                    forked_at = Some(turn);
+                    center_status = fork_status(&center_status, turn);
+                    handle.update_status_bar(None, Some(center_status.clone()), None);
+                }
+                continue;
+            }
+            RatatuiEvent::Undo => {
+                undo_last_command(&config.workspace, &mut last_backup, &mut renderer)?;
+                continue;
+            }
+            RatatuiEvent::ToggleVoice => {
+                voice_input.toggle(&handle, &mut renderer).await?;
+                continue;
+            }
             RatatuiEvent::Cancel => {
                 renderer.line(
                     MessageStyle::Info,
@@ -1107,7 +1905,7 @@ pub(crate) async fn run_single_agent_loop_unified(
                 continue;
             }
             RatatuiEvent::Exit => {
-                renderer.line(MessageStyle::Info, "Goodbye!")?;
+                renderer.line(MessageStyle::Info, locale::text(Message::Goodbye))?;
                 break;
             }
             RatatuiEvent::Interrupt => {
@@ -1128,17 +1926,31 @@ pub(crate) async fn run_single_agent_loop_unified(
         match input_owned.as_str() {
             "" => continue,
             "exit" | "quit" => {
-                renderer.line(MessageStyle::Info, "Goodbye!")?;
+                renderer.line(MessageStyle::Info, locale::text(Message::Goodbye))?;
                 break;
             }
             "help" => {
-                renderer.line(MessageStyle::Info, "Commands: exit, help")?;
+                if offline.is_active() {
+                    offline.render_status(&mut renderer)?;
+                } else {
+                    renderer.line(MessageStyle::Info, "Commands: exit, help")?;
+                }
                 continue;
             }
             _ => {}
         }
 
-        if let Some(command_input) = input_owned.strip_prefix('/') {
+        let command_input = match input_owned.strip_prefix('/') {
+            Some(rest) => Some(rest.to_string()),
+            None if offline.is_local_only() => {
+                match offline.local_command_input(&input_owned, &mut renderer)? {
+                    Some(command) => Some(command),
+                    None => continue,
+                }
+            }
+            None => None,
+        };
+        if let Some(command_input) = command_input.as_deref() {
             match handle_slash_command(command_input, &mut renderer)? {
                 SlashCommandOutcome::Handled => {
                     continue;
@@ -1150,28 +1962,279 @@ pub(crate) async fn run_single_agent_loop_unified(
                     apply_prompt_style(&handle);
                     continue;
                 }
-                SlashCommandOutcome::ExecuteTool { name, args } => {
-                    match ensure_tool_permission(
-                        &mut tool_registry,
-                        &name,
-                        &mut renderer,
+                SlashCommandOutcome::SetRequestPreview(enabled) => {
+                    request_preview_enabled = enabled;
+                    let message = if enabled {
+                        "Request preview enabled. Each request will be shown before it is sent."
+                    } else {
+                        "Request preview disabled."
+                    };
+                    renderer.line(MessageStyle::Info, message)?;
+                    continue;
+                }
+                SlashCommandOutcome::Copy(target) => {
+                    copy_to_clipboard(
+                        target,
+                        &conversation_history,
+                        clipboard_enabled,
                         &handle,
-                        &mut events,
-                        default_placeholder.clone(),
-                        &ctrl_c_flag,
-                        &ctrl_c_notify,
-                    )
-                    .await
-                    {
-                        Ok(ToolPermissionFlow::Approved) => {
+                        &mut renderer,
+                    )?;
+                    continue;
+                }
+                SlashCommandOutcome::Paste => {
+                    paste_from_clipboard(clipboard_enabled, &handle, &mut renderer)?;
+                    continue;
+                }
+                SlashCommandOutcome::Redact(patterns) => {
+                    let redaction = vt_cfg
+                        .map(|cfg| cfg.security.redaction.clone())
+                        .unwrap_or_default();
+                    redact_current_session(
+                        session_archive.as_ref(),
+                        &tool_results.rehydrate(&conversation_history),
+                        session_stats.sorted_tools(),
+                        &redaction,
+                        &config.workspace,
+                        &patterns,
+                        &mut renderer,
+                    )?;
+                    continue;
+                }
+                SlashCommandOutcome::Timeline => {
+                    show_timeline(&timeline, &handle, &mut renderer)?;
+                    continue;
+                }
+                SlashCommandOutcome::Context => {
+                    show_context(
+                        ContextSources {
+                            history: &conversation_history,
+                            system_prompt: &base_system_prompt,
+                            summary: history_summary.as_deref(),
+                            gathered: &gathered_context,
+                            workspace: &config.workspace,
+                            project_doc_max_bytes: vt_cfg
+                                .map_or(project_doc_constants::DEFAULT_MAX_BYTES, |cfg| {
+                                    cfg.agent.project_doc_max_bytes
+                                }),
+                            trim: trim_config,
+                        },
+                        &mut renderer,
+                    )?;
+                    continue;
+                }
+                SlashCommandOutcome::Language(None) => {
+                    renderer.line(
+                        MessageStyle::Info,
+                        &format!(
+                            "Response language: {} ({}). Switch with /lang <tag>, e.g. /lang ja.",
+                            response_locale.name(),
+                            response_locale.tag()
+                        ),
+                    )?;
+                    continue;
+                }
+                SlashCommandOutcome::Language(Some(next)) => {
+                    response_locale = next;
+                    if locale_config.localize_ui {
+                        set_ui_locale(&response_locale);
+                        handle.update_status_bar(
+                            Some(locale::text(Message::StatusHelp).to_string()),
+                            None,
+                            Some(locale::text(Message::NavigationHint).to_string()),
+                        );
+                    }
+                    renderer.line(
+                        MessageStyle::Info,
+                        &locale::format(
+                            Message::LanguageChanged,
+                            &[("language", &response_locale.name())],
+                        ),
+                    )?;
+                    continue;
+                }
+                SlashCommandOutcome::Briefing => {
+                    if briefing.is_none() {
+                        briefing = load_briefing(&config.workspace);
+                    }
+                    render_briefing(briefing.as_ref(), &mut renderer)?;
+                    continue;
+                }
+                SlashCommandOutcome::Handoff => {
+                    if offline.is_active() {
+                        renderer.line(
+                            MessageStyle::Info,
+                            "The handoff is written by the model; reconnect with /offline off first.",
+                        )?;
+                        continue;
+                    }
+                    write_handoff(
+                        HandoffSources {
+                            session_id: summary_session_id(session_archive.as_ref()),
+                            history: &tool_results.rehydrate(&conversation_history),
+                            summary: history_summary.as_deref(),
+                            changes: &changes,
+                        },
+                        provider_client.as_ref(),
+                        &config.model,
+                        &config.workspace,
+                        &mut renderer,
+                    )
+                    .await?;
+                    continue;
+                }
+                SlashCommandOutcome::Evidence(id) => {
+                    show_evidence(&evidence_log, id, &mut renderer)?;
+                    continue;
+                }
+                SlashCommandOutcome::Rename(title) => {
+                    title_locked = true;
+                    session_title = Some(title.clone());
+                    center_status = apply_session_title(
+                        &title,
+                        session_archive.as_mut(),
+                        &model_status,
+                        forked_at,
+                        &handle,
+                    );
+                    renderer.line(
+                        MessageStyle::Info,
+                        &format!("Session renamed to \"{title}\"."),
+                    )?;
+                    continue;
+                }
+                SlashCommandOutcome::SplitPane { content, placement } => {
+                    open_split_pane(&mut renderer, &config.workspace, content, placement)?;
+                    continue;
+                }
+                SlashCommandOutcome::ToolAccess(request) => {
+                    let message = match request {
+                        ToolAccessRequest::Status if exposure.is_restricted() => {
+                            "Read-only tools only. Use /tools full to enable editing and terminal tools."
+                        }
+                        ToolAccessRequest::Status => "All tools are enabled.",
+                        ToolAccessRequest::Full => {
+                            exposure.escalate();
+                            "Editing and terminal tools enabled for this session."
+                        }
+                        ToolAccessRequest::ReadOnly => {
+                            exposure.set_level(READ_ONLY_LEVEL);
+                            "Back to read-only tools; editing and terminal tools are withheld."
+                        }
+                    };
+                    renderer.line(MessageStyle::Info, message)?;
+                    continue;
+                }
+                SlashCommandOutcome::DualAnswer(request) => {
+                    handle_dual_request(
+                        request,
+                        &mut dual_answer,
+                        &dual_settings,
+                        config,
+                        &mut renderer,
+                    )?;
+                    continue;
+                }
+                SlashCommandOutcome::Offline(request) => {
+                    match request {
+                        OfflineRequest::Status => offline.render_status(&mut renderer)?,
+                        OfflineRequest::Enable => {
+                            offline.force_offline(&mut provider_client, &mut renderer)?
+                        }
+                        OfflineRequest::Reconnect => {
+                            offline
+                                .reconnect(&mut provider_client, &mut renderer)
+                                .await?
+                        }
+                    }
+                    continue;
+                }
+                SlashCommandOutcome::Outline(OutlineRequest::Hide) => {
+                    handle.set_outline_visible(false);
+                    continue;
+                }
+                SlashCommandOutcome::Outline(OutlineRequest::Show(path)) => {
+                    let result = match path.as_deref() {
+                        Some(path) => outline.focus(&handle, Path::new(path)).map(Some),
+                        None => outline.refresh(&handle),
+                    };
+                    match result {
+                        Ok(Some(count)) => {
+                            handle.set_outline_visible(true);
+                            renderer.line(
+                                MessageStyle::Info,
+                                &format!(
+                                    "Outline shows {} symbols. Press Ctrl+O to navigate it.",
+                                    count
+                                ),
+                            )?;
+                        }
+                        Ok(None) => {
+                            renderer.line(
+                                MessageStyle::Info,
+                                "No file is in focus yet. Use /outline <path>.",
+                            )?;
+                        }
+                        Err(err) => {
+                            renderer.line(
+                                MessageStyle::Error,
+                                &format!("Unable to build outline: {:#}", err),
+                            )?;
+                        }
+                    }
+                    continue;
+                }
+                SlashCommandOutcome::ExecuteTool { name, args } => {
+                    match ensure_tool_permission(
+                        &mut tool_registry,
+                        &name,
+                        &mut renderer,
+                        &handle,
+                        &mut events,
+                        default_placeholder.clone(),
+                        &ctrl_c_flag,
+                        &ctrl_c_notify,
+                    )
+                    .await
+                    {
+                        Ok(ToolPermissionFlow::Approved) => {
                             let tool_spinner = PlaceholderSpinner::new(
                                 &handle,
                                 default_placeholder.clone(),
-                                format!("Running tool: {}", name),
+                                locale::format(Message::RunningTool, &[("tool", &name)]),
                                 None,
                                 Some(center_status.clone()),
+                                animate_spinners,
                             );
-                            match tool_registry.execute_tool(&name, args.clone()).await {
+                            let (result, stop) = execute_cancellable(
+                                &mut tool_registry,
+                                &name,
+                                args.clone(),
+                                &mut events,
+                                &mut pending_events,
+                                &ctrl_c_flag,
+                                &ctrl_c_notify,
+                            )
+                            .await;
+                            match stop {
+                                Some(ToolStop::Exit) => {
+                                    tool_spinner.finish();
+                                    renderer
+                                        .line(MessageStyle::Info, locale::text(Message::Goodbye))?;
+                                    break;
+                                }
+                                Some(ToolStop::Interrupt) => break,
+                                Some(ToolStop::Cancel) => {
+                                    tool_spinner.finish();
+                                    renderer.line(
+                                        MessageStyle::Info,
+                                        &format!("Tool {} cancelled.", name),
+                                    )?;
+                                    continue;
+                                }
+                                None => {}
+                            }
+                            match result {
                                 Ok(tool_output) => {
                                     tool_spinner.finish();
                                     session_stats.record_tool(&name);
@@ -1180,6 +2243,7 @@ pub(crate) async fn run_single_agent_loop_unified(
                                         &name,
                                         &args,
                                         true,
+                                        tool_registry.tool_schema_version(&name),
                                     );
                                     render_tool_output(
                                         &mut renderer,
@@ -1187,6 +2251,7 @@ pub(crate) async fn run_single_agent_loop_unified(
                                         &tool_output,
                                         vt_cfg,
                                     )?;
+                                    outline.observe_tool_call(&handle, &name, &args);
                                 }
                                 Err(err) => {
                                     tool_spinner.finish();
@@ -1195,6 +2260,7 @@ pub(crate) async fn run_single_agent_loop_unified(
                                         &name,
                                         &args,
                                         false,
+                                        tool_registry.tool_schema_version(&name),
                                     );
                                     renderer.line(
                                         MessageStyle::Error,
@@ -1211,7 +2277,13 @@ pub(crate) async fn run_single_agent_loop_unified(
                                 format!("Tool '{}' execution denied by policy", name),
                             )
                             .to_json_value();
-                            traj.log_tool_call(conversation_history.len(), &name, &args, false);
+                            traj.log_tool_call(
+                                conversation_history.len(),
+                                &name,
+                                &args,
+                                false,
+                                tool_registry.tool_schema_version(&name),
+                            );
                             render_tool_output(
                                 &mut renderer,
                                 Some(name.as_str()),
@@ -1221,14 +2293,20 @@ pub(crate) async fn run_single_agent_loop_unified(
                             continue;
                         }
                         Ok(ToolPermissionFlow::Exit) => {
-                            renderer.line(MessageStyle::Info, "Goodbye!")?;
+                            renderer.line(MessageStyle::Info, locale::text(Message::Goodbye))?;
                             break;
                         }
                         Ok(ToolPermissionFlow::Interrupted) => {
                             break;
                         }
                         Err(err) => {
-                            traj.log_tool_call(conversation_history.len(), &name, &args, false);
+                            traj.log_tool_call(
+                                conversation_history.len(),
+                                &name,
+                                &args,
+                                false,
+                                tool_registry.tool_schema_version(&name),
+                            );
                             renderer.line(
                                 MessageStyle::Error,
                                 &format!("Failed to evaluate policy for tool '{}': {}", name, err),
@@ -1238,7 +2316,7 @@ pub(crate) async fn run_single_agent_loop_unified(
                     continue;
                 }
                 SlashCommandOutcome::Exit => {
-                    renderer.line(MessageStyle::Info, "Goodbye!")?;
+                    renderer.line(MessageStyle::Info, locale::text(Message::Goodbye))?;
                     break;
                 }
             }
@@ -1247,14 +2325,58 @@ pub(crate) async fn run_single_agent_loop_unified(
         let input = input_owned.as_str();
 
         let refined_user = refine_user_prompt_if_enabled(input, config, vt_cfg).await;
+        fork_points.record(&conversation_history);
         // Display the user message with ratatui border decoration
         display_user_message(&mut renderer, &refined_user)?;
-        conversation_history.push(uni::Message::user(refined_user));
+        changes.begin_request(input);
+        run_events.turn_started(input);
+        let mut latency = TurnLatency::start();
+        let mut provided_context = None;
+        if let Some(registry) = context_providers.as_ref() {
+            let gathered = registry
+                .gather(&ContextQuery {
+                    text: input.to_string(),
+                    workspace: config.workspace.clone(),
+                })
+                .await;
+            for (provider, reason) in &gathered.failures {
+                renderer.line(
+                    MessageStyle::Info,
+                    &format!("Context provider {provider} skipped: {reason}"),
+                )?;
+            }
+            provided_context = gathered.prompt_section();
+            gathered_context = gathered
+                .blocks
+                .iter()
+                .map(|(provider, block)| {
+                    let source = block.source.clone().unwrap_or_else(|| provider.clone());
+                    let tokens = block
+                        .content
+                        .len()
+                        .div_ceil(context_defaults::CHAR_PER_TOKEN_APPROX);
+                    (Provenance::AutoGathered { source }, tokens)
+                })
+                .collect();
+        }
+        evidence_log.begin_turn();
+        tool_registry.begin_turn();
+        if let Some(repro) = reproducibility.as_mut() {
+            repro.begin_turn(&refined_user);
+            if let Some(archive) = session_archive.as_mut() {
+                archive.set_reproducibility(repro.record().clone());
+            }
+        }
+        conversation_history
+            .push(uni::Message::user(refined_user).with_provenance(Provenance::UserInput));
         let _pruned_tools = prune_unified_tool_responses(
             &mut conversation_history,
             trim_config.preserve_recent_turns,
         );
         // Removed: Tool response pruning message
+        if let Some(calibration) = token_calibration.as_ref() {
+            trim_config.token_scale = calibration.factor(provider_client.name(), &config.model);
+        }
         let trim_result = enforce_unified_context_window(&mut conversation_history, trim_config);
         if trim_result.is_trimmed() {
             renderer.line(
@@ -1264,6 +2386,17 @@ pub(crate) async fn run_single_agent_loop_unified(
                     trim_result.removed_messages, trim_config.max_tokens,
                 ),
             )?;
+            if let Some(summarizer) = history_summarizer.as_ref() {
+                update_history_summary(
+                    summarizer,
+                    &mut history_summary,
+                    &trim_result.removed,
+                    provider_client.as_ref(),
+                    &config.model,
+                    &mut renderer,
+                )
+                .await?;
+            }
         }
 
         let mut working_history = conversation_history.clone();
@@ -1276,6 +2409,9 @@ pub(crate) async fn run_single_agent_loop_unified(
         let mut any_write_effect = false;
         let mut last_tool_stdout: Option<String> = None;
         let mut bottom_gap_applied = false;
+        let mut envelope_failures = 0usize;
+        let mut second_answer: Option<PendingAnswer> = None;
+        refusals.start_turn(&mut provider_client);
 
         let turn_result = 'outer: loop {
             if ctrl_c_flag.load(Ordering::SeqCst) {
@@ -1301,7 +2437,9 @@ pub(crate) async fn run_single_agent_loop_unified(
 
             let _ = enforce_unified_context_window(&mut working_history, trim_config);
 
-            let decision = if let Some(cfg) = vt_cfg.filter(|cfg| cfg.router.enabled) {
+            let decision = if let Some(cfg) =
+                vt_cfg.filter(|cfg| cfg.router.enabled && !offline.is_active())
+            {
                 Router::route_async(cfg, config, &config.api_key, input).await
             } else {
                 Router::route(&VTCodeConfig::default(), config, input)
@@ -1319,7 +2457,11 @@ pub(crate) async fn run_single_agent_loop_unified(
                 &input.chars().take(120).collect::<String>(),
             );
 
-            let active_model = decision.selected_model;
+            let mut active_model = refusals
+                .model_override()
+                .or(offline.model_override())
+                .map(str::to_string)
+                .unwrap_or(decision.selected_model);
             let (max_tokens_opt, parallel_cfg_opt) = if let Some(vt) = vt_cfg {
                 let key = match decision.class {
                     TaskClass::Simple => "simple",
@@ -1342,16 +2484,6 @@ pub(crate) async fn run_single_agent_loop_unified(
                 (None, None)
             };
 
-            let (lg_enabled, lg_max, lg_include) = vt_cfg
-                .map(|cfg| {
-                    (
-                        cfg.context.ledger.enabled,
-                        cfg.context.ledger.max_entries,
-                        cfg.context.ledger.include_in_prompt,
-                    )
-                })
-                .unwrap_or((true, 12, true));
-
             ledger.start_turn(
                 working_history.len(),
                 working_history
@@ -1364,23 +2496,85 @@ pub(crate) async fn run_single_agent_loop_unified(
                 .collect();
             ledger.update_available_tools(tool_names);
 
-            let system_prompt = if lg_enabled && lg_include {
-                format!(
-                    "{}\n\n[Decision Ledger]\n{}",
-                    base_system_prompt,
-                    ledger.render_ledger_brief(lg_max)
+            let mut system_prompt =
+                stable_system_prompt(&base_system_prompt, &ledger, vt_cfg, &exposure, full_auto);
+            if let Some(summary) = history_summary.as_deref() {
+                system_prompt.push_str("\n\n");
+                system_prompt.push_str(&summary_prompt_section(summary));
+            }
+            if let Some(section) = provided_context.as_deref() {
+                system_prompt.push_str("\n\n");
+                system_prompt.push_str(section);
+            }
+            if guide_with_stats
+                && let Some(guidance) = tool_stats
+                    .as_ref()
+                    .and_then(|stats| stats.tool_guidance(decision.class))
+            {
+                system_prompt.push_str("\n\n");
+                system_prompt.push_str(&guidance);
+            }
+            if let Some(directive) = response_locale.response_directive() {
+                system_prompt.push_str("\n\n");
+                system_prompt.push_str(&directive);
+            }
+
+            let constrained = ConstrainedDecoding::new(
+                &constrained_config,
+                &active_model,
+                provider_client.supports_response_schema(&active_model),
+            );
+            if !json_tool_protocol && constrained.starts_with_envelope() {
+                json_tool_protocol = true;
+            }
+            let shaped_tools =
+                ToolSchemaBudget::new(&schema_config, &active_model, trim_config.max_tokens).shape(
+                    exposure.available_tools(&offline.available_tools(&tools)),
+                    decision.class,
+                    &working_history,
+                    tool_stats.as_ref(),
+                );
+            if let Some(note) = shaped_tools.prompt_note() {
+                system_prompt.push_str("\n\n");
+                system_prompt.push_str(&note);
+            }
+            let turn_tools = shaped_tools.tools;
+            let response_schema = (json_tool_protocol && constrained.constrains())
+                .then(|| tool_envelope_schema(&turn_tools));
+            let (request_system_prompt, request_tools, request_tool_choice) = if json_tool_protocol
+            {
+                (
+                    format!(
+                        "{}\n\n{}",
+                        system_prompt,
+                        json_envelope_instructions(&turn_tools)
+                    ),
+                    None,
+                    None,
                 )
             } else {
-                base_system_prompt.clone()
+                (
+                    system_prompt.clone(),
+                    Some(turn_tools),
+                    Some(uni::ToolChoice::auto()),
+                )
             };
 
-            let mut attempt_history = working_history.clone();
+            tool_results.spill(&mut working_history);
+            let mut attempt_history = tool_results.rehydrate(&working_history);
+            if loop_guard == 1
+                && !offline.is_active()
+                && let Some(dual) = dual_answer.as_ref()
+            {
+                second_answer = Some(dual.spawn(attempt_history.clone(), system_prompt.clone()));
+            }
             let mut retry_attempts = 0usize;
+            let mut estimated_prompt_tokens;
             let (response, response_streamed) = loop {
                 retry_attempts += 1;
                 let _ = enforce_unified_context_window(&mut attempt_history, trim_config);
 
-                let use_streaming = provider_client.supports_streaming();
+                let use_streaming = provider_client.supports_streaming() && !json_tool_protocol;
                 let reasoning_effort = vt_cfg.and_then(|cfg| {
                     if provider_client.supports_reasoning_effort(&active_model) {
                         Some(cfg.agent.reasoning_effort.as_str().to_string())
@@ -1388,36 +2582,108 @@ pub(crate) async fn run_single_agent_loop_unified(
                         None
                     }
                 });
-                let request = uni::LLMRequest {
+                let mut request = uni::LLMRequest {
                     messages: attempt_history.clone(),
-                    system_prompt: Some(system_prompt.clone()),
-                    tools: Some(tools.clone()),
+                    system_prompt: Some(request_system_prompt.clone()),
+                    tools: request_tools.clone(),
                     model: active_model.clone(),
                     max_tokens: max_tokens_opt.or(Some(2000)),
                     temperature: Some(0.7),
                     stream: use_streaming,
-                    tool_choice: Some(uni::ToolChoice::auto()),
+                    tool_choice: request_tool_choice.clone(),
                     parallel_tool_calls: None,
                     parallel_tool_config: parallel_cfg_opt.clone(),
                     reasoning_effort,
+                    json_mode: json_tool_protocol,
+                    response_schema: response_schema.clone(),
+                    code_execution: code_execution.enabled()
+                        && provider_client.supports_code_execution(&active_model),
+                    seed: None,
                 };
+                if let Some(repro) = &reproducibility {
+                    repro.apply(&mut request);
+                }
+
+                if request_preview_enabled {
+                    let outcome = review_request_preview(
+                        provider_client.name(),
+                        &mut request,
+                        &handle,
+                        &mut events,
+                        &ctrl_c_flag,
+                        &ctrl_c_notify,
+                    )
+                    .await;
+                    handle.set_placeholder(default_placeholder.clone());
+                    match outcome {
+                        RequestPreviewOutcome::Send => {
+                            attempt_history.clone_from(&request.messages);
+                        }
+                        RequestPreviewOutcome::Cancel => {
+                            renderer.line(MessageStyle::Info, "Request cancelled.")?;
+                            ensure_turn_bottom_gap(&mut renderer, &mut bottom_gap_applied)?;
+                            break 'outer TurnLoopResult::Aborted;
+                        }
+                        RequestPreviewOutcome::Exit => {
+                            renderer.line(MessageStyle::Info, locale::text(Message::Goodbye))?;
+                            break 'outer TurnLoopResult::Cancelled;
+                        }
+                        RequestPreviewOutcome::Interrupt => {
+                            break 'outer TurnLoopResult::Cancelled;
+                        }
+                    }
+                }
 
+                if let Some(tracker) = spend_tracker.as_mut() {
+                    match check_spend_budget(
+                        tracker,
+                        budget_action,
+                        &mut renderer,
+                        &handle,
+                        &mut events,
+                        default_placeholder.clone(),
+                        &ctrl_c_flag,
+                        &ctrl_c_notify,
+                    )
+                    .await?
+                    {
+                        BudgetOutcome::Continue => {}
+                        BudgetOutcome::Stop => {
+                            ensure_turn_bottom_gap(&mut renderer, &mut bottom_gap_applied)?;
+                            break 'outer TurnLoopResult::Aborted;
+                        }
+                        BudgetOutcome::Exit => {
+                            renderer.line(MessageStyle::Info, locale::text(Message::Goodbye))?;
+                            break 'outer TurnLoopResult::Cancelled;
+                        }
+                        BudgetOutcome::Interrupt => {
+                            break 'outer TurnLoopResult::Cancelled;
+                        }
+                    }
+                }
+
+                estimated_prompt_tokens = estimate_request_tokens(&request);
                 let status_label = derive_status_label(&attempt_history);
                 let thinking_spinner = PlaceholderSpinner::new(
                     &handle,
                     default_placeholder.clone(),
-                    "Thinking...",
+                    locale::text(Message::Thinking),
                     Some(status_label),
                     Some(center_status.clone()),
+                    animate_spinners,
                 );
                 let mut spinner_active = true;
                 task::yield_now().await;
+                let request_started = Instant::now();
+                let mut first_token = None;
                 let result = if use_streaming {
                     let outcome = stream_and_render_response(
                         provider_client.as_ref(),
                         request,
                         &thinking_spinner,
                         &mut renderer,
+                        &mut first_token,
+                        &code_execution,
                     )
                     .await;
                     spinner_active = false;
@@ -1428,13 +2694,76 @@ pub(crate) async fn run_single_agent_loop_unified(
                         .await
                         .map(|resp| (resp, false))
                 };
+                latency.record_request(request_started, first_token, result.is_ok());
 
                 if spinner_active {
                     thinking_spinner.finish();
                 }
 
+                if let Some(usage) = credential_usage.as_ref() {
+                    for rotation in usage.take_rotations() {
+                        renderer.line(
+                            MessageStyle::Info,
+                            &format!(
+                                "Credential profile '{}' hit its quota; switched to '{}'.",
+                                rotation.from, rotation.to
+                            ),
+                        )?;
+                    }
+                }
+                if let Some(usage) = failover_usage.as_ref() {
+                    for failover in usage.take_failovers() {
+                        renderer.line(
+                            MessageStyle::Info,
+                            &format!(
+                                "{} failed ({}); retried with {}.",
+                                failover.from, failover.reason, failover.to
+                            ),
+                        )?;
+                    }
+                }
+
                 match result {
                     Ok((result, streamed_tokens)) => {
+                        if let Some(refusal) =
+                            Refusal::detect(provider_client.name(), &active_model, &result)
+                        {
+                            // The refusal text never enters the conversation.
+                            session_record.record_usage(result.usage.as_ref());
+                            if let Some(tracker) = spend_tracker.as_mut() {
+                                record_response_spend(
+                                    tracker,
+                                    failover_usage
+                                        .as_ref()
+                                        .and_then(|usage| usage.last_served()),
+                                    provider_client.name(),
+                                    &active_model,
+                                    result.usage.as_ref(),
+                                );
+                            }
+                            match refusals.handle(
+                                &refusal,
+                                &mut attempt_history,
+                                &mut provider_client,
+                                &mut renderer,
+                            )? {
+                                RefusalOutcome::Retry => {
+                                    if let Some(model) = refusals.model_override() {
+                                        active_model = model.to_string();
+                                    }
+                                    continue;
+                                }
+                                RefusalOutcome::GiveUp => {
+                                    ensure_turn_bottom_gap(&mut renderer, &mut bottom_gap_applied)?;
+                                    break 'outer TurnLoopResult::Aborted;
+                                }
+                            }
+                        }
+                        if !use_streaming {
+                            for step in &result.code_execution {
+                                code_execution.render(&mut renderer, step)?;
+                            }
+                        }
                         working_history = attempt_history.clone();
                         break (result, streamed_tokens);
                     }
@@ -1464,9 +2793,25 @@ pub(crate) async fn run_single_agent_loop_unified(
                                     ),
                                 )?;
                                 conversation_history.clone_from(&attempt_history);
+                                tool_results.spill(&mut conversation_history);
                                 continue;
                             }
                         }
+                        run_events.provider_error(&error_text);
+
+                        if offline
+                            .handle_provider_error(&error, &mut provider_client, &mut renderer)
+                            .await?
+                        {
+                            if offline.model_override().is_some() {
+                                renderer.line(
+                                    MessageStyle::Info,
+                                    "Send the request again to use the local model.",
+                                )?;
+                            }
+                            ensure_turn_bottom_gap(&mut renderer, &mut bottom_gap_applied)?;
+                            break 'outer TurnLoopResult::Aborted;
+                        }
 
                         let has_tool = working_history
                             .iter()
@@ -1492,15 +2837,88 @@ pub(crate) async fn run_single_agent_loop_unified(
                     }
                 }
             };
+            session_record.record_usage(response.usage.as_ref());
+            if let Some(tracker) = spend_tracker.as_mut() {
+                record_response_spend(
+                    tracker,
+                    failover_usage
+                        .as_ref()
+                        .and_then(|usage| usage.last_served()),
+                    provider_client.name(),
+                    &active_model,
+                    response.usage.as_ref(),
+                );
+            }
+            if let (Some(calibration), Some(usage)) =
+                (token_calibration.as_mut(), response.usage.as_ref())
+            {
+                calibration.record(
+                    provider_client.name(),
+                    &active_model,
+                    estimated_prompt_tokens,
+                    reported_prompt_tokens(provider_client.name(), usage),
+                );
+            }
 
             let mut final_text = response.content.clone();
             let mut tool_calls = response.tool_calls.clone().unwrap_or_default();
             let mut interpreted_textual_call = false;
 
-            if tool_calls.is_empty()
+            if json_tool_protocol
+                && tool_calls.is_empty()
+                && let Some(text) = final_text.clone()
+            {
+                match parse_json_tool_envelope(&text, &tools) {
+                    Ok(envelope) => {
+                        envelope_failures = 0;
+                        for (index, call) in envelope.tool_calls.into_iter().enumerate() {
+                            let arguments = serde_json::to_string(&call.arguments)
+                                .unwrap_or_else(|_| "{}".to_string());
+                            tool_calls.push(uni::ToolCall::function(
+                                format!("call_json_{}_{}", working_history.len(), index),
+                                call.name,
+                                arguments,
+                            ));
+                        }
+                        final_text = envelope
+                            .message
+                            .filter(|message| !message.trim().is_empty());
+                        if !tool_calls.is_empty()
+                            && let Some(message) = final_text.as_deref()
+                        {
+                            renderer.line(MessageStyle::Response, message)?;
+                        }
+                    }
+                    Err(diagnostic) if envelope_failures < MAX_JSON_ENVELOPE_RETRIES => {
+                        envelope_failures += 1;
+                        renderer.line(
+                            MessageStyle::Info,
+                            &format!(
+                                "Model response was not a valid JSON envelope ({}); asking it to correct ({}/{}).",
+                                diagnostic, envelope_failures, MAX_JSON_ENVELOPE_RETRIES
+                            ),
+                        )?;
+                        working_history.push(uni::Message::assistant(text));
+                        working_history
+                            .push(uni::Message::user(json_envelope_correction(&diagnostic)));
+                        continue;
+                    }
+                    Err(_) => {}
+                }
+            } else if tool_calls.is_empty()
                 && let Some(text) = final_text.clone()
                 && let Some((name, args)) = detect_textual_tool_call(&text)
             {
+                if json_textual_tools
+                    && (provider_client.supports_json_mode(&active_model)
+                        || constrained.constrains())
+                {
+                    json_tool_protocol = true;
+                    renderer.line(
+                        MessageStyle::Info,
+                        "Switching textual tool calls to provider JSON mode for the rest of this session.",
+                    )?;
+                }
                 let args_display =
                     serde_json::to_string(&args).unwrap_or_else(|_| "{}".to_string());
                 renderer.line(
@@ -1534,12 +2952,79 @@ pub(crate) async fn run_single_agent_loop_unified(
                     assistant_text,
                     tool_calls.clone(),
                 ));
+                let mut tool_stop = None;
+                // Leading read-only calls that need no confirmation run
+                // together up front; the loop below reports them in order.
+                let mut prefetched: HashMap<String, ToolCallOutcome> = HashMap::new();
+                let mut batch: Vec<(String, ToolCallRequest)> = Vec::new();
+                for call in &tool_calls {
+                    let name = call.function.name.as_str();
+                    let args = call
+                        .parsed_arguments()
+                        .unwrap_or_else(|_| serde_json::json!({}));
+                    let eligible = tool_registry.is_read_only_tool(name)
+                        && !exposure.withholds(name)
+                        && guardrails.check(name, &args).is_none()
+                        && matches!(
+                            tool_registry.evaluate_tool_policy(name),
+                            Ok(ToolPermissionDecision::Allow)
+                        );
+                    if !eligible {
+                        break;
+                    }
+                    batch.push((call.id.clone(), ToolCallRequest::new(name, args)));
+                }
+                if batch.len() > 1 {
+                    let names: Vec<&str> = batch
+                        .iter()
+                        .map(|(_, request)| request.name.as_str())
+                        .collect();
+                    let batch_spinner = PlaceholderSpinner::new(
+                        &handle,
+                        default_placeholder.clone(),
+                        locale::format(Message::RunningTool, &[("tool", &names.join(", "))]),
+                        None,
+                        Some(center_status.clone()),
+                        animate_spinners,
+                    );
+                    let (ids, requests): (Vec<String>, Vec<ToolCallRequest>) =
+                        batch.into_iter().unzip();
+                    let (outcomes, stop) = execute_batch_cancellable(
+                        &mut tool_registry,
+                        requests,
+                        &mut events,
+                        &mut pending_events,
+                        &ctrl_c_flag,
+                        &ctrl_c_notify,
+                    )
+                    .await;
+                    batch_spinner.finish();
+                    if stop.is_some() {
+                        tool_stop = stop;
+                        renderer.line(MessageStyle::Info, "Tools cancelled.")?;
+                    } else {
+                        prefetched = ids.into_iter().zip(outcomes).collect();
+                    }
+                }
                 for call in &tool_calls {
                     let name = call.function.name.as_str();
+                    if tool_stop.is_some() {
+                        working_history.push(
+                            uni::Message::tool_response(call.id.clone(), skipped_response(name))
+                                .with_provenance(Provenance::tool_call(call)),
+                        );
+                        continue;
+                    }
                     let args_val = call
                         .parsed_arguments()
                         .unwrap_or_else(|_| serde_json::json!({}));
-                    render_tool_call_summary(&mut renderer, name, &args_val)?;
+                    render_tool_call_summary(
+                        &mut renderer,
+                        name,
+                        &args_val,
+                        tool_registry.workspace_env(),
+                    )?;
+                    run_events.tool_call(name, &args_val);
                     let dec_id = ledger.record_decision(
                         format!("Execute tool '{}' to progress task", name),
                         DTAction::ToolCall {
@@ -1550,35 +3035,491 @@ pub(crate) async fn run_single_agent_loop_unified(
                         None,
                     );
 
-                    match ensure_tool_permission(
-                        &mut tool_registry,
-                        name,
-                        &mut renderer,
-                        &handle,
-                        &mut events,
-                        default_placeholder.clone(),
-                        &ctrl_c_flag,
-                        &ctrl_c_notify,
-                    )
-                    .await
+                    if name == tool_names::GET_FULL_OUTPUT && verbosity.enabled() {
+                        let output = full_output_response(&tool_results, &args_val);
+                        let succeeded = output_succeeded(&output);
+                        run_events.tool_output(name, &output);
+                        if !succeeded {
+                            render_tool_output(&mut renderer, Some(name), &output, vt_cfg)?;
+                        }
+                        working_history.push(
+                            uni::Message::tool_response(call.id.clone(), output.to_string())
+                                .with_provenance(Provenance::tool_call(call)),
+                        );
+                        ledger.record_outcome(
+                            &dec_id,
+                            if succeeded {
+                                DecisionOutcome::Success {
+                                    result: "tool_ok".to_string(),
+                                    metrics: Default::default(),
+                                }
+                            } else {
+                                DecisionOutcome::Failure {
+                                    error: "stored output not found".to_string(),
+                                    recovery_attempts: 0,
+                                    context_preserved: true,
+                                }
+                            },
+                        );
+                        continue;
+                    }
+
+                    if name == tool_names::SPAWN_SUBAGENTS
+                        && let Some(subagents) = subagents.as_mut()
                     {
+                        let output = match subagents.prepare(&args_val, config) {
+                            Err(message) => serde_json::json!({ "error": message }),
+                            Ok(jobs) => {
+                                render_started(&mut renderer, &jobs)?;
+                                let names: Vec<&str> =
+                                    jobs.iter().map(|(agent, _)| agent.name()).collect();
+                                let subagent_spinner = PlaceholderSpinner::new(
+                                    &handle,
+                                    default_placeholder.clone(),
+                                    locale::format(
+                                        Message::RunningTool,
+                                        &[("tool", &names.join(", "))],
+                                    ),
+                                    None,
+                                    Some(center_status.clone()),
+                                    animate_spinners,
+                                );
+                                let max_parallel = subagents.max_parallel();
+                                let (reports, stop) = run_subagents_cancellable(
+                                    &tool_registry,
+                                    jobs,
+                                    &tools,
+                                    &guardrails,
+                                    &injection_shield,
+                                    max_parallel,
+                                    &mut events,
+                                    &mut pending_events,
+                                    &ctrl_c_flag,
+                                    &ctrl_c_notify,
+                                )
+                                .await;
+                                subagent_spinner.finish();
+                                render_reports(&mut renderer, &reports)?;
+                                if stop.is_some() {
+                                    tool_stop = stop;
+                                    renderer.line(MessageStyle::Info, "Sub-agents cancelled.")?;
+                                }
+                                merge_reports(&reports)
+                            }
+                        };
+                        let succeeded = output_succeeded(&output);
+                        run_events.tool_output(name, &output);
+                        if let Some(error) = output.get("error").and_then(serde_json::Value::as_str)
+                        {
+                            renderer.line(MessageStyle::Error, error)?;
+                        }
+                        working_history.push(
+                            uni::Message::tool_response(call.id.clone(), output.to_string())
+                                .with_provenance(Provenance::tool_call(call)),
+                        );
+                        ledger.record_outcome(
+                            &dec_id,
+                            if succeeded {
+                                DecisionOutcome::Success {
+                                    result: "tool_ok".to_string(),
+                                    metrics: Default::default(),
+                                }
+                            } else {
+                                DecisionOutcome::Failure {
+                                    error: "sub-agents did not all report".to_string(),
+                                    recovery_attempts: 0,
+                                    context_preserved: true,
+                                }
+                            },
+                        );
+                        continue;
+                    }
+
+                    if exposure.withholds(name) {
+                        let flow = if skip_confirmations {
+                            ToolPermissionFlow::Approved
+                        } else {
+                            renderer.line(
+                                MessageStyle::Info,
+                                &format!(
+                                    "`{name}` needs editing and terminal tools, which are not enabled yet. Approve to enable them for this session."
+                                ),
+                            )?;
+                            match prompt_tool_permission(
+                                name,
+                                &mut renderer,
+                                &handle,
+                                &mut events,
+                                &ctrl_c_flag,
+                                &ctrl_c_notify,
+                                default_placeholder.clone(),
+                            )
+                            .await?
+                            {
+                                HitlDecision::Approved => ToolPermissionFlow::Approved,
+                                HitlDecision::Denied => ToolPermissionFlow::Denied,
+                                HitlDecision::Exit => ToolPermissionFlow::Exit,
+                                HitlDecision::Interrupt => ToolPermissionFlow::Interrupted,
+                            }
+                        };
+                        match flow {
+                            ToolPermissionFlow::Approved => {
+                                exposure.escalate();
+                                renderer.line(
+                                    MessageStyle::Info,
+                                    "Editing and terminal tools enabled for this session.",
+                                )?;
+                            }
+                            ToolPermissionFlow::Denied => {
+                                let reason = format!(
+                                    "Tool '{name}' is not enabled yet; only read-only tools are available"
+                                );
+                                run_events.tool_denied(name, &reason);
+                                let denial = ToolExecutionError::new(
+                                    name.to_string(),
+                                    ToolErrorType::PolicyViolation,
+                                    reason.clone(),
+                                )
+                                .to_json_value();
+                                working_history.push(
+                                    uni::Message::tool_response(
+                                        call.id.clone(),
+                                        denial.to_string(),
+                                    )
+                                    .with_provenance(Provenance::tool_call(call)),
+                                );
+                                ledger.record_outcome(
+                                    &dec_id,
+                                    DecisionOutcome::Failure {
+                                        error: reason,
+                                        recovery_attempts: 0,
+                                        context_preserved: true,
+                                    },
+                                );
+                                continue;
+                            }
+                            ToolPermissionFlow::Exit => {
+                                renderer
+                                    .line(MessageStyle::Info, locale::text(Message::Goodbye))?;
+                                break 'outer TurnLoopResult::Cancelled;
+                            }
+                            ToolPermissionFlow::Interrupted => {
+                                break 'outer TurnLoopResult::Cancelled;
+                            }
+                        }
+                    }
+                    if let Some(violation) = guardrails.check(name, &args_val) {
+                        let flow = confirm_guardrail_override(
+                            &violation,
+                            guardrails.allows_override() && !skip_confirmations,
+                            &mut renderer,
+                            &handle,
+                            &mut events,
+                            default_placeholder.clone(),
+                            &ctrl_c_flag,
+                            &ctrl_c_notify,
+                        )
+                        .await?;
+                        match flow {
+                            ToolPermissionFlow::Approved => {
+                                if violation.kind == GuardrailKind::SensitivePath
+                                    && let Some(paths) = sensitive_paths.as_mut()
+                                {
+                                    allow_sensitive_path(
+                                        &config.workspace,
+                                        paths,
+                                        &mut guardrails,
+                                        &violation.target,
+                                    )?;
+                                }
+                            }
+                            ToolPermissionFlow::Denied => {
+                                let reason = format!("Blocked by guardrail: {violation}");
+                                run_events.tool_denied(name, &reason);
+                                traj.log_tool_call(
+                                    working_history.len(),
+                                    name,
+                                    &args_val,
+                                    false,
+                                    tool_registry.tool_schema_version(name),
+                                );
+                                let denial = ToolExecutionError::new(
+                                    name.to_string(),
+                                    ToolErrorType::PolicyViolation,
+                                    reason.clone(),
+                                )
+                                .to_json_value();
+                                working_history.push(
+                                    uni::Message::tool_response(
+                                        call.id.clone(),
+                                        denial.to_string(),
+                                    )
+                                    .with_provenance(Provenance::tool_call(call)),
+                                );
+                                ledger.record_outcome(
+                                    &dec_id,
+                                    DecisionOutcome::Failure {
+                                        error: reason,
+                                        recovery_attempts: 0,
+                                        context_preserved: true,
+                                    },
+                                );
+                                continue;
+                            }
+                            ToolPermissionFlow::Exit => {
+                                renderer
+                                    .line(MessageStyle::Info, locale::text(Message::Goodbye))?;
+                                break 'outer TurnLoopResult::Cancelled;
+                            }
+                            ToolPermissionFlow::Interrupted => {
+                                break 'outer TurnLoopResult::Cancelled;
+                            }
+                        }
+                    }
+                    let approval_started = Instant::now();
+                    let permission = if prefetched.contains_key(&call.id) {
+                        Ok(ToolPermissionFlow::Approved)
+                    } else {
+                        ensure_tool_permission(
+                            &mut tool_registry,
+                            name,
+                            &mut renderer,
+                            &handle,
+                            &mut events,
+                            default_placeholder.clone(),
+                            &ctrl_c_flag,
+                            &ctrl_c_notify,
+                        )
+                        .await
+                    };
+                    let risky_preview = match (&permission, risk_map.as_ref()) {
+                        (Ok(ToolPermissionFlow::Approved), Some(map))
+                            if full_auto_allowlist.is_none() =>
+                        {
+                            risky_edit_preview(map, &config.workspace, name, &args_val)
+                        }
+                        _ => None,
+                    };
+                    let permission = match risky_preview {
+                        Some(preview) => {
+                            confirm_risky_edit(
+                                name,
+                                &preview,
+                                &mut renderer,
+                                &handle,
+                                &mut events,
+                                default_placeholder.clone(),
+                                &ctrl_c_flag,
+                                &ctrl_c_notify,
+                            )
+                            .await
+                        }
+                        None => permission,
+                    };
+                    latency.record_wait(approval_started.elapsed());
+                    match permission {
                         Ok(ToolPermissionFlow::Approved) => {
                             let tool_spinner = PlaceholderSpinner::new(
                                 &handle,
                                 default_placeholder.clone(),
-                                format!("Running tool: {}", name),
+                                locale::format(Message::RunningTool, &[("tool", name)]),
                                 None,
                                 Some(center_status.clone()),
+                                animate_spinners,
                             );
-                            match tool_registry.execute_tool(name, args_val.clone()).await {
-                                Ok(tool_output) => {
+                            let target_existed =
+                                ChangeLedger::target_exists(&config.workspace, name, &args_val);
+                            timeline.capture_baseline(name, &args_val);
+                            let backup = back_up_before(
+                                &config.workspace,
+                                &guardrails,
+                                name,
+                                &args_val,
+                                &mut renderer,
+                            )?;
+                            let (result, stop, elapsed) = match prefetched.remove(&call.id) {
+                                Some(outcome) => (outcome.result, None, outcome.elapsed),
+                                None => {
+                                    let started = Instant::now();
+                                    let (result, stop) = execute_cancellable(
+                                        &mut tool_registry,
+                                        name,
+                                        args_val.clone(),
+                                        &mut events,
+                                        &mut pending_events,
+                                        &ctrl_c_flag,
+                                        &ctrl_c_notify,
+                                    )
+                                    .await;
+                                    (result, stop, started.elapsed())
+                                }
+                            };
+                            if stop.is_some() {
+                                tool_stop = stop;
+                                renderer.line(
+                                    MessageStyle::Info,
+                                    &format!("Tool {} cancelled.", name),
+                                )?;
+                            }
+                            let succeeded = result.as_ref().is_ok_and(output_succeeded);
+                            latency.record_tool(name, elapsed, succeeded);
+                            if let Some(stats) = tool_stats.as_mut() {
+                                let queued = result
+                                    .as_ref()
+                                    .ok()
+                                    .and_then(|output| output.pointer("/queue/waited_ms"))
+                                    .and_then(|value| value.as_u64())
+                                    .map(Duration::from_millis)
+                                    .unwrap_or_default();
+                                stats.record(
+                                    name,
+                                    &args_val,
+                                    succeeded,
+                                    elapsed.saturating_sub(queued),
+                                );
+                            }
+                            match result {
+                                Ok(mut tool_output) => {
                                     tool_spinner.finish();
+                                    if let Some(request) =
+                                        WritePermissionRequired::from_tool_output(&tool_output)
+                                    {
+                                        let prompt_started = Instant::now();
+                                        let outcome = request_write_grant(
+                                            &request,
+                                            name,
+                                            &mut renderer,
+                                            &handle,
+                                            &mut events,
+                                            &ctrl_c_flag,
+                                            &ctrl_c_notify,
+                                        )
+                                        .await?;
+                                        handle.set_placeholder(default_placeholder.clone());
+                                        latency.record_wait(prompt_started.elapsed());
+                                        let choice = match outcome {
+                                            GrantOutcome::Answered(choice) => choice,
+                                            GrantOutcome::Exit => {
+                                                renderer.line(
+                                                    MessageStyle::Info,
+                                                    locale::text(Message::Goodbye),
+                                                )?;
+                                                break 'outer TurnLoopResult::Cancelled;
+                                            }
+                                            GrantOutcome::Interrupt => {
+                                                break 'outer TurnLoopResult::Cancelled;
+                                            }
+                                        };
+                                        let now = SystemTime::now()
+                                            .duration_since(UNIX_EPOCH)
+                                            .unwrap_or_default()
+                                            .as_secs();
+                                        match choice.grant(&request.path, now) {
+                                            Some(grant) => {
+                                                traj.log_write_grant(
+                                                    &grant.path,
+                                                    true,
+                                                    grant.expires_at,
+                                                );
+                                                if let Err(err) =
+                                                    workspace_trust::persist_write_grant(
+                                                        &config.workspace,
+                                                        grant.clone(),
+                                                    )
+                                                {
+                                                    renderer.line(
+                                                        MessageStyle::Error,
+                                                        &format!(
+                                                            "Failed to save the grant for {}: {}. It applies to this \
+                                                             session only.",
+                                                            grant.path, err
+                                                        ),
+                                                    )?;
+                                                }
+                                                tool_registry.path_grants().grant(grant);
+                                                // Approved above; no second policy prompt
+                                                tool_registry.mark_tool_preapproved(name);
+                                                tool_output = tool_registry
+                                                    .execute_tool(name, args_val.clone())
+                                                    .await
+                                                    .unwrap_or_else(|err| {
+                                                        ToolExecutionError::new(
+                                                            name.to_string(),
+                                                            ToolErrorType::ExecutionError,
+                                                            err.to_string(),
+                                                        )
+                                                        .to_json_value()
+                                                    });
+                                            }
+                                            None => {
+                                                traj.log_write_grant(&request.path, false, None);
+                                                record_declined(&mut tool_output, &request);
+                                            }
+                                        }
+                                    }
+                                    if let Some(conflict) =
+                                        EditConflict::from_tool_output(&tool_output)
+                                    {
+                                        let prompt_started = Instant::now();
+                                        let outcome = resolve_edit_conflict(
+                                            &conflict,
+                                            &mut renderer,
+                                            &handle,
+                                            &mut events,
+                                            &ctrl_c_flag,
+                                            &ctrl_c_notify,
+                                        )
+                                        .await?;
+                                        handle.set_placeholder(default_placeholder.clone());
+                                        latency.record_wait(prompt_started.elapsed());
+                                        let choice = match outcome {
+                                            ConflictOutcome::Resolved(choice) => choice,
+                                            ConflictOutcome::Exit => {
+                                                renderer.line(
+                                                    MessageStyle::Info,
+                                                    locale::text(Message::Goodbye),
+                                                )?;
+                                                break 'outer TurnLoopResult::Cancelled;
+                                            }
+                                            ConflictOutcome::Interrupt => {
+                                                break 'outer TurnLoopResult::Cancelled;
+                                            }
+                                        };
+                                        let choice = match choice.content(&conflict) {
+                                            Some(content) => match tool_registry
+                                                .resolve_edit_conflict(&conflict.path, content)
+                                                .await
+                                            {
+                                                Ok(()) => choice,
+                                                Err(err) => {
+                                                    renderer.line(
+                                                        MessageStyle::Error,
+                                                        &format!(
+                                                            "Failed to write {}: {}. Kept your version.",
+                                                            conflict.path, err
+                                                        ),
+                                                    )?;
+                                                    ConflictChoice::Mine
+                                                }
+                                            },
+                                            None => choice,
+                                        };
+                                        record_resolution(&mut tool_output, &conflict, choice);
+                                    }
                                     session_stats.record_tool(name);
+                                    run_events.tool_output(name, &tool_output);
                                     traj.log_tool_call(
                                         working_history.len(),
                                         name,
                                         &args_val,
                                         true,
+                                        tool_registry.tool_schema_version(name),
+                                    );
+                                    evidence_log.record(
+                                        name,
+                                        &args_val,
+                                        &tool_output,
+                                        output_succeeded(&tool_output),
                                     );
                                     render_tool_output(
                                         &mut renderer,
@@ -1586,6 +3527,22 @@ pub(crate) async fn run_single_agent_loop_unified(
                                         &tool_output,
                                         vt_cfg,
                                     )?;
+                                    if let Some(backup) = backup {
+                                        attach_backup(
+                                            &mut tool_output,
+                                            &backup,
+                                            &config.workspace,
+                                            &mut renderer,
+                                        )?;
+                                        last_backup = Some(backup);
+                                    }
+                                    outline.observe_tool_call(&handle, name, &args_val);
+                                    if exposure.escalates_on_plan(name) && exposure.escalate() {
+                                        renderer.line(
+                                            MessageStyle::Info,
+                                            "Plan recorded; editing and terminal tools are now available.",
+                                        )?;
+                                    }
                                     last_tool_stdout = tool_output
                                         .get("stdout")
                                         .and_then(|value| value.as_str())
@@ -1613,10 +3570,13 @@ pub(crate) async fn run_single_agent_loop_unified(
                                             | "create_file"
                                             | "delete_file"
                                             | "srgn"
+                                            | "organize_imports"
+                                            | "config_edit"
                                     ) {
                                         any_write_effect = true;
                                     }
 
+                                    let mut changes_kept = true;
                                     if !modified_files.is_empty()
                                         && confirm_changes_with_git_diff(
                                             &modified_files,
@@ -1629,15 +3589,52 @@ pub(crate) async fn run_single_agent_loop_unified(
                                             "Changes applied successfully.",
                                         )?;
                                     } else if !modified_files.is_empty() {
+                                        changes_kept = false;
                                         renderer.line(MessageStyle::Info, "Changes discarded.")?;
                                     }
+                                    if changes_kept {
+                                        let recorded = changes.record_tool_result(
+                                            name,
+                                            &args_val,
+                                            &tool_output,
+                                            target_existed,
+                                            Some(&dec_id),
+                                        );
+                                        let recent = changes.changes();
+                                        for change in &recent[recent.len() - recorded..] {
+                                            run_events.file_changed(&change.path, change.kind);
+                                        }
+                                    }
 
-                                    let content = serde_json::to_string(&tool_output)
-                                        .unwrap_or("{}".to_string());
-                                    working_history.push(uni::Message::tool_response(
-                                        call.id.clone(),
-                                        content,
-                                    ));
+                                    let shielded =
+                                        injection_shield.shield(name, &args_val, &tool_output);
+                                    if let Some(shielded) = &shielded
+                                        && shielded.stripped > 0
+                                    {
+                                        renderer.line(
+                                            MessageStyle::Info,
+                                            &format!(
+                                                "Removed {} instruction-like line(s) from untrusted output of {}.",
+                                                shielded.stripped, shielded.source
+                                            ),
+                                        )?;
+                                    }
+                                    let sent = shielded
+                                        .as_ref()
+                                        .map_or(&tool_output, |shielded| &shielded.output);
+                                    let compacted = verbosity.compact(
+                                        name,
+                                        sent,
+                                        output_succeeded(&tool_output),
+                                        &mut tool_results,
+                                    );
+                                    let content =
+                                        serde_json::to_string(compacted.as_ref().unwrap_or(sent))
+                                            .unwrap_or("{}".to_string());
+                                    working_history.push(
+                                        uni::Message::tool_response(call.id.clone(), content)
+                                            .with_provenance(Provenance::tool_call(call)),
+                                    );
                                     ledger.record_outcome(
                                         &dec_id,
                                         DecisionOutcome::Success {
@@ -1663,6 +3660,7 @@ pub(crate) async fn run_single_agent_loop_unified(
                                 Err(error) => {
                                     tool_spinner.finish();
                                     session_stats.record_tool(name);
+                                    run_events.tool_result(name, false, Some(&error.to_string()));
                                     renderer.line(
                                         MessageStyle::Tool,
                                         &format!("Tool {} failed.", name),
@@ -1672,17 +3670,19 @@ pub(crate) async fn run_single_agent_loop_unified(
                                         name,
                                         &args_val,
                                         false,
+                                        tool_registry.tool_schema_version(name),
                                     );
                                     renderer.line(
                                         MessageStyle::Error,
                                         &format!("Tool error: {error}"),
                                     )?;
                                     let err = serde_json::json!({ "error": error.to_string() });
+                                    evidence_log.record(name, &args_val, &err, false);
                                     let content = err.to_string();
-                                    working_history.push(uni::Message::tool_response(
-                                        call.id.clone(),
-                                        content,
-                                    ));
+                                    working_history.push(
+                                        uni::Message::tool_response(call.id.clone(), content)
+                                            .with_provenance(Provenance::tool_call(call)),
+                                    );
                                     let _ = last_tool_stdout.take();
                                     ledger.record_outcome(
                                         &dec_id,
@@ -1697,18 +3697,27 @@ pub(crate) async fn run_single_agent_loop_unified(
                         }
                         Ok(ToolPermissionFlow::Denied) => {
                             session_stats.record_tool(name);
+                            run_events.tool_denied(name, "denied by policy");
                             let denial = ToolExecutionError::new(
                                 name.to_string(),
                                 ToolErrorType::PolicyViolation,
                                 format!("Tool '{}' execution denied by policy", name),
                             )
                             .to_json_value();
-                            traj.log_tool_call(working_history.len(), name, &args_val, false);
+                            traj.log_tool_call(
+                                working_history.len(),
+                                name,
+                                &args_val,
+                                false,
+                                tool_registry.tool_schema_version(name),
+                            );
                             render_tool_output(&mut renderer, Some(name), &denial, vt_cfg)?;
                             let content =
                                 serde_json::to_string(&denial).unwrap_or("{}".to_string());
-                            working_history
-                                .push(uni::Message::tool_response(call.id.clone(), content));
+                            working_history.push(
+                                uni::Message::tool_response(call.id.clone(), content)
+                                    .with_provenance(Provenance::tool_call(call)),
+                            );
                             ledger.record_outcome(
                                 &dec_id,
                                 DecisionOutcome::Failure {
@@ -1720,14 +3729,20 @@ pub(crate) async fn run_single_agent_loop_unified(
                             continue;
                         }
                         Ok(ToolPermissionFlow::Exit) => {
-                            renderer.line(MessageStyle::Info, "Goodbye!")?;
+                            renderer.line(MessageStyle::Info, locale::text(Message::Goodbye))?;
                             break 'outer TurnLoopResult::Cancelled;
                         }
                         Ok(ToolPermissionFlow::Interrupted) => {
                             break 'outer TurnLoopResult::Cancelled;
                         }
                         Err(err) => {
-                            traj.log_tool_call(working_history.len(), name, &args_val, false);
+                            traj.log_tool_call(
+                                working_history.len(),
+                                name,
+                                &args_val,
+                                false,
+                                tool_registry.tool_schema_version(name),
+                            );
                             renderer.line(
                                 MessageStyle::Error,
                                 &format!("Failed to evaluate policy for tool '{}': {}", name, err),
@@ -1738,10 +3753,10 @@ pub(crate) async fn run_single_agent_loop_unified(
                                     name, err
                                 )
                             });
-                            working_history.push(uni::Message::tool_response(
-                                call.id.clone(),
-                                err_json.to_string(),
-                            ));
+                            working_history.push(
+                                uni::Message::tool_response(call.id.clone(), err_json.to_string())
+                                    .with_provenance(Provenance::tool_call(call)),
+                            );
                             let _ = last_tool_stdout.take();
                             ledger.record_outcome(
                                 &dec_id,
@@ -1758,6 +3773,21 @@ pub(crate) async fn run_single_agent_loop_unified(
                         }
                     }
                 }
+                match tool_stop {
+                    Some(ToolStop::Cancel) => {
+                        working_history.push(uni::Message::assistant(
+                            "Stopped: the user cancelled the running tool call.".to_string(),
+                        ));
+                        ensure_turn_bottom_gap(&mut renderer, &mut bottom_gap_applied)?;
+                        break 'outer TurnLoopResult::Completed;
+                    }
+                    Some(ToolStop::Exit) => {
+                        renderer.line(MessageStyle::Info, locale::text(Message::Goodbye))?;
+                        break 'outer TurnLoopResult::Cancelled;
+                    }
+                    Some(ToolStop::Interrupt) => break 'outer TurnLoopResult::Cancelled,
+                    None => {}
+                }
                 continue;
             }
 
@@ -1779,7 +3809,10 @@ pub(crate) async fn run_single_agent_loop_unified(
                             ))],
                             system_prompt: Some(review_system.clone()),
                             tools: None,
-                            model: config.model.clone(),
+                            model: offline
+                                .model_override()
+                                .map(str::to_string)
+                                .unwrap_or_else(|| config.model.clone()),
                             max_tokens: Some(2000),
                             temperature: Some(0.5),
                             stream: false,
@@ -1793,8 +3826,14 @@ pub(crate) async fn run_single_agent_loop_unified(
                                     None
                                 }
                             }),
+                            json_mode: false,
+                            response_schema: None,
+                            code_execution: false,
+                            seed: None,
                         };
+                        let review_started = Instant::now();
                         let rr = provider_client.generate(review_req).await.ok();
+                        latency.record_request(review_started, None, rr.is_some());
                         if let Some(r) = rr.and_then(|result| result.content)
                             && !r.trim().is_empty()
                         {
@@ -1819,6 +3858,34 @@ pub(crate) async fn run_single_agent_loop_unified(
                 if !suppress_response && !streamed_matches_output {
                     renderer.line(MessageStyle::Response, &text)?;
                 }
+                if let (Some(dual), Some(pending)) = (dual_answer.as_ref(), second_answer.take()) {
+                    let (kept, outcome) = pick_answer(
+                        dual,
+                        pending,
+                        &active_model,
+                        text,
+                        &mut renderer,
+                        &handle,
+                        &mut events,
+                        &ctrl_c_flag,
+                        &ctrl_c_notify,
+                    )
+                    .await?;
+                    handle.set_placeholder(default_placeholder.clone());
+                    match outcome {
+                        PickOutcome::Answered(AnswerChoice::Secondary) => renderer.line(
+                            MessageStyle::Info,
+                            &format!("Keeping the answer from {}.", dual.model),
+                        )?,
+                        PickOutcome::Answered(AnswerChoice::Primary) => {}
+                        PickOutcome::Exit => {
+                            renderer.line(MessageStyle::Info, locale::text(Message::Goodbye))?;
+                            break 'outer TurnLoopResult::Cancelled;
+                        }
+                        PickOutcome::Interrupt => break 'outer TurnLoopResult::Cancelled,
+                    }
+                    text = kept;
+                }
                 ensure_turn_bottom_gap(&mut renderer, &mut bottom_gap_applied)?;
                 working_history.push(uni::Message::assistant(text));
                 let _ = last_tool_stdout.take();
@@ -1828,16 +3895,69 @@ pub(crate) async fn run_single_agent_loop_unified(
             break TurnLoopResult::Completed;
         };
 
+        if let Some(stats) = tool_stats.as_mut()
+            && let Err(err) = stats.save()
+        {
+            renderer.line(
+                MessageStyle::Info,
+                &format!("Failed to save tool statistics: {}", err),
+            )?;
+        }
+
+        if let Some(calibration) = token_calibration.as_mut()
+            && let Err(err) = calibration.save()
+        {
+            renderer.line(
+                MessageStyle::Info,
+                &format!("Failed to save token calibration: {}", err),
+            )?;
+        }
+
+        if let Some(tracker) = spend_tracker.as_mut() {
+            if let Err(err) = tracker.save() {
+                renderer.line(
+                    MessageStyle::Info,
+                    &format!("Failed to save spend totals: {}", err),
+                )?;
+            }
+            handle.update_status_bar(None, None, Some(tracker.status_text()));
+        }
+
+        if show_latency && !matches!(turn_result, TurnLoopResult::Cancelled) {
+            handle.update_status_bar(Some(latency.finish().summary()), None, None);
+        }
+
         match turn_result {
             TurnLoopResult::Cancelled => {
+                run_events.turn_completed("cancelled");
                 break;
             }
             TurnLoopResult::Aborted => {
+                run_events.turn_completed("aborted");
                 let _ = conversation_history.pop();
                 continue;
             }
             TurnLoopResult::Completed => {
+                run_events.turn_completed("completed");
                 conversation_history = working_history;
+                let reply = conversation_history
+                    .last()
+                    .filter(|message| message.role == uni::MessageRole::Assistant)
+                    .map(|message| message.content.clone())
+                    .unwrap_or_default();
+                if !title_locked && let Some(title) = title_from_exchange(input, &reply) {
+                    title_locked = true;
+                    session_title = Some(title.clone());
+                    center_status = apply_session_title(
+                        &title,
+                        session_archive.as_mut(),
+                        &model_status,
+                        forked_at,
+                        &handle,
+                    );
+                }
+                timeline.record_turn(input, reply, &changes);
+                session_record.turns += 1;
 
                 let _pruned_after_turn = prune_unified_tool_responses(
                     &mut conversation_history,
@@ -1870,16 +3990,67 @@ pub(crate) async fn run_single_agent_loop_unified(
                             "Note: The assistant mentioned edits but no write tool ran.",
                         )?;
                     }
+                    let links = evidence_log.link_claims(text);
+                    for link in &links {
+                        traj.log_claim(link, &evidence_log);
+                    }
+                    if vt_cfg.is_none_or(|cfg| cfg.ui.show_claim_evidence) {
+                        render_claim_links(&links, &evidence_log, &mut renderer)?;
+                    }
+                }
+
+                let messages = tool_results.rehydrate(&conversation_history);
+                let snapshot = SessionSnapshot {
+                    provider: &session_record.provider,
+                    model: &config.model,
+                    title: session_title.as_deref(),
+                    turns: resumed_turns + session_record.turns,
+                    history_summary: history_summary.as_deref(),
+                    messages: &messages,
+                    decisions: &ledger,
+                };
+                if let Err(err) = session_store.save(snapshot) {
+                    tracing::warn!(error = %err, "failed to save session for resume");
                 }
             }
         }
     }
 
+    if !ctrl_c_flag.load(Ordering::SeqCst)
+        && let Some(summary) = SessionSummary::build(
+            summary_session_id(session_archive.as_ref()),
+            &changes,
+            &ledger,
+        )
+    {
+        offer_session_summary(
+            &summary,
+            &config.workspace,
+            &mut renderer,
+            &handle,
+            &mut events,
+            &ctrl_c_flag,
+            &ctrl_c_notify,
+        )
+        .await?;
+    }
+
+    session_record.session_id = summary_session_id(session_archive.as_ref());
+    session_record.reverts = timeline.revert_count();
+    session_record.interrupted = ctrl_c_flag.load(Ordering::SeqCst);
+    traj.log_session(session_record);
+    if briefing_enabled
+        && let Err(err) = save_checkpoint(&config.workspace, &changes, &evidence_log)
+    {
+        tracing::debug!(%err, "failed to save the workspace checkpoint");
+    }
+
     let transcript_lines = transcript::snapshot();
     if let Some(archive) = session_archive.take() {
         let distinct_tools = session_stats.sorted_tools();
         let total_messages = conversation_history.len();
-        let session_messages: Vec<SessionMessage> = conversation_history
+        let session_messages: Vec<SessionMessage> = tool_results
+            .rehydrate(&conversation_history)
             .iter()
             .map(SessionMessage::from)
             .collect();
@@ -1906,6 +4077,23 @@ pub(crate) async fn run_single_agent_loop_unified(
         }
     }
 
+    traj.flush();
+    let status = if ctrl_c_flag.load(Ordering::SeqCst) {
+        RunStatus::Interrupted
+    } else if full_auto || !fail_on.is_empty() {
+        // Unattended and gated runs report what happened; interactive
+        // sessions end successfully whatever their turns ran into
+        let resolved = conversation_history
+            .iter()
+            .rev()
+            .find(|message| message.role == uni::MessageRole::Assistant)
+            .filter(|_| full_auto)
+            .and_then(|message| self_assessment(&message.content));
+        run_events.completed_status(fail_on, resolved)
+    } else {
+        RunStatus::Success
+    };
+    run_events.finish(status, None);
     handle.shutdown();
-    Ok(())
+    Ok(status)
 }
@@ -0,0 +1,82 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::path::Path;
+
+use vtcode_core::core::command_backup::CommandBackup;
+use vtcode_core::core::guardrails::Guardrails;
+use vtcode_core::utils::ansi::{AnsiRenderer, MessageStyle};
+
+/// Back up the files a destructive-but-allowed command may change. A failed
+/// backup is reported and the command runs anyway, as it would have without
+/// one.
+pub(crate) fn back_up_before(
+    workspace: &Path,
+    guardrails: &Guardrails,
+    tool: &str,
+    args: &Value,
+    renderer: &mut AnsiRenderer,
+) -> Result<Option<CommandBackup>> {
+    let Some(command) = guardrails.backup_rule(tool, args) else {
+        return Ok(None);
+    };
+    match CommandBackup::capture(workspace, &command) {
+        Ok(backup) if backup.files.is_empty() => Ok(None),
+        Ok(backup) => Ok(Some(backup)),
+        Err(err) => {
+            renderer.line(
+                MessageStyle::Error,
+                &format!("Could not back up files before `{command}`: {err}"),
+            )?;
+            Ok(None)
+        }
+    }
+}
+
+/// Record the backup in the tool result and tell the user how to undo.
+pub(crate) fn attach_backup(
+    output: &mut Value,
+    backup: &CommandBackup,
+    workspace: &Path,
+    renderer: &mut AnsiRenderer,
+) -> Result<()> {
+    if let Some(object) = output.as_object_mut() {
+        object.insert("backup".to_string(), backup.to_json_value(workspace));
+    }
+    renderer.line(
+        MessageStyle::Info,
+        &format!(
+            "Backed up {} file(s) before `{}`; press Alt+U to undo.",
+            backup.files.len(),
+            backup.command
+        ),
+    )
+}
+
+/// Alt+U: restore the files saved before the last destructive command.
+pub(crate) fn undo_last_command(
+    workspace: &Path,
+    last_backup: &mut Option<CommandBackup>,
+    renderer: &mut AnsiRenderer,
+) -> Result<()> {
+    let Some(backup) = last_backup.take() else {
+        return renderer.line(MessageStyle::Info, "Nothing to undo.");
+    };
+    match backup.restore(workspace) {
+        Ok(count) => renderer.line(
+            MessageStyle::Info,
+            &format!(
+                "Restored {count} file(s) from before `{}`. Files it created were left in place.",
+                backup.command
+            ),
+        ),
+        Err(err) => {
+            let message = format!(
+                "Failed to undo `{}`: {err}. The backup is in {}.",
+                backup.command,
+                backup.dir(workspace).display()
+            );
+            *last_backup = Some(backup);
+            renderer.line(MessageStyle::Error, &message)
+        }
+    }
+}
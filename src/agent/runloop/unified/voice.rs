@@ -0,0 +1,105 @@
+use anyhow::Result;
+
+use vtcode_core::config::VoiceConfig;
+use vtcode_core::ui::tui::RatatuiHandle;
+use vtcode_core::utils::ansi::{AnsiRenderer, MessageStyle};
+
+/// Push-to-talk state for Alt+V.
+pub(crate) struct VoiceInput {
+    #[cfg(feature = "voice")]
+    config: VoiceConfig,
+    #[cfg(feature = "voice")]
+    recording: Option<vtcode_core::voice::Recording>,
+}
+
+#[cfg(feature = "voice")]
+impl VoiceInput {
+    pub(crate) fn new(config: &VoiceConfig) -> Self {
+        Self {
+            config: config.clone(),
+            recording: None,
+        }
+    }
+
+    /// Alt+V: start recording, or stop and put the transcript in the input
+    /// for review.
+    pub(crate) async fn toggle(
+        &mut self,
+        handle: &RatatuiHandle,
+        renderer: &mut AnsiRenderer,
+    ) -> Result<()> {
+        use std::time::Duration;
+        use vtcode_core::voice::{Recording, Transcriber, encode_wav};
+
+        let Some(recording) = self.recording.take() else {
+            // Check the backend before recording so a missing model is not
+            // discovered only after the user has spoken.
+            if let Err(err) = Transcriber::from_config(&self.config) {
+                return renderer.line(MessageStyle::Error, &err.to_string());
+            }
+            let limit = self.config.max_seconds.max(1);
+            return match Recording::start(Duration::from_secs(limit)) {
+                Ok(recording) => {
+                    self.recording = Some(recording);
+                    renderer.line(
+                        MessageStyle::Info,
+                        &format!(
+                            "Recording… press Alt+V again to stop (recording ends by itself after {limit}s)."
+                        ),
+                    )
+                }
+                Err(err) => renderer.line(MessageStyle::Error, &err.to_string()),
+            };
+        };
+
+        let audio = match tokio::task::spawn_blocking(move || recording.stop()).await? {
+            Ok(audio) => audio,
+            Err(err) => return renderer.line(MessageStyle::Error, &err.to_string()),
+        };
+        if audio.samples.is_empty() {
+            return renderer.line(MessageStyle::Info, "Nothing was recorded.");
+        }
+        renderer.line(
+            MessageStyle::Info,
+            &format!(
+                "Transcribing {:.1}s of audio…",
+                audio.duration().as_secs_f32()
+            ),
+        )?;
+        let transcript = match Transcriber::from_config(&self.config) {
+            Ok(transcriber) => transcriber.transcribe(encode_wav(&audio)).await,
+            Err(err) => Err(err),
+        };
+        match transcript {
+            Ok(text) if text.is_empty() => {
+                renderer.line(MessageStyle::Info, "No speech was recognized.")
+            }
+            Ok(text) => {
+                handle.insert_input(text);
+                renderer.line(
+                    MessageStyle::Info,
+                    "Transcript added to the prompt. Edit it if needed and press Enter to send.",
+                )
+            }
+            Err(err) => renderer.line(MessageStyle::Error, &err.to_string()),
+        }
+    }
+}
+
+#[cfg(not(feature = "voice"))]
+impl VoiceInput {
+    pub(crate) fn new(_config: &VoiceConfig) -> Self {
+        Self {}
+    }
+
+    pub(crate) async fn toggle(
+        &mut self,
+        _handle: &RatatuiHandle,
+        renderer: &mut AnsiRenderer,
+    ) -> Result<()> {
+        renderer.line(
+            MessageStyle::Info,
+            "Voice input is not part of this build. Reinstall with `cargo install vtcode --features voice` to use Alt+V.",
+        )
+    }
+}
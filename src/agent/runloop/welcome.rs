@@ -5,6 +5,7 @@ use vtcode_core::config::constants::project_doc as project_doc_constants;
 use vtcode_core::config::core::AgentOnboardingConfig;
 use vtcode_core::config::loader::VTCodeConfig;
 use vtcode_core::config::types::AgentConfig as CoreAgentConfig;
+use vtcode_core::core::daemon::WarmState;
 use vtcode_core::project_doc;
 use vtcode_core::ui::styled::Styles;
 use vtcode_core::utils::utils::{
@@ -23,13 +24,18 @@ pub(crate) struct SessionBootstrap {
 pub(crate) fn prepare_session_bootstrap(
     runtime_cfg: &CoreAgentConfig,
     vt_cfg: Option<&VTCodeConfig>,
+    warm_state: Option<&WarmState>,
 ) -> SessionBootstrap {
     let onboarding_cfg = vt_cfg
         .map(|cfg| cfg.agent.onboarding.clone())
         .unwrap_or_default();
 
     let project_overview = build_project_overview(&runtime_cfg.workspace);
-    let language_summary = summarize_workspace_languages(&runtime_cfg.workspace);
+    // A running daemon has already scanned the workspace
+    let language_summary = match warm_state {
+        Some(state) => state.language_summary.clone(),
+        None => summarize_workspace_languages(&runtime_cfg.workspace),
+    };
     let guideline_highlights = if onboarding_cfg.include_guideline_highlights {
         let max_bytes = vt_cfg
             .map(|cfg| cfg.agent.project_doc_max_bytes)
@@ -269,7 +275,7 @@ mod tests {
     use std::fs;
     use tempfile::tempdir;
     use vtcode_core::config::core::PromptCachingConfig;
-    use vtcode_core::config::types::{ReasoningEffortLevel, UiSurfacePreference};
+    use vtcode_core::config::types::{ReasoningEffortLevel, RenderMode, UiSurfacePreference};
 
     #[test]
     fn test_prepare_session_bootstrap_builds_sections() {
@@ -300,15 +306,17 @@ mod tests {
                 .to_string(),
             api_key: "test".to_string(),
             provider: "gemini".to_string(),
+            base_url: None,
             workspace: tmp.path().to_path_buf(),
             verbose: false,
             theme: vtcode_core::ui::theme::DEFAULT_THEME_ID.to_string(),
             reasoning_effort: ReasoningEffortLevel::default(),
             ui_surface: UiSurfacePreference::default(),
+            render_mode: RenderMode::default(),
             prompt_cache: PromptCachingConfig::default(),
         };
 
-        let bootstrap = prepare_session_bootstrap(&runtime_cfg, Some(&vt_cfg));
+        let bootstrap = prepare_session_bootstrap(&runtime_cfg, Some(&vt_cfg), None);
 
         let welcome = bootstrap.welcome_text.expect("welcome text");
         assert!(welcome.contains("Tip one"));
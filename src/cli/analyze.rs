@@ -1,29 +1,96 @@
-use anyhow::Result;
-use console::style;
+use anyhow::{Context, Result};
+use console::{StyledObject, style};
+use indicatif::{HumanBytes, ProgressBar, ProgressDrawTarget, ProgressStyle};
 use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
 use vtcode_core::config::types::AgentConfig as CoreAgentConfig;
+use vtcode_core::core::diff_analysis::{DiffRange, DiffReport};
+use vtcode_core::core::progress::{Progress, ProgressEvent};
+use vtcode_core::core::risk_map::{FileRisk, RiskLevel, RiskMap, RiskOptions};
+use vtcode_core::core::turn_latency::format_duration;
 use walkdir::WalkDir;
 
+const RISK_BAR_WIDTH: usize = 5;
+
 /// Handle the analyze command
-pub async fn handle_analyze_command(config: &CoreAgentConfig) -> Result<()> {
-    println!("{}", style("Analyze workspace mode selected").blue().bold());
-    println!("Workspace: {}", config.workspace.display());
+pub async fn handle_analyze_command(config: &CoreAgentConfig, quiet: bool) -> Result<()> {
+    if !quiet {
+        println!("{}", style("Analyze workspace mode selected").blue().bold());
+        println!("Workspace: {}", config.workspace.display());
+    }
 
     // Workspace analysis implementation
-    analyze_workspace(&config.workspace).await?;
+    analyze_workspace(&config.workspace, &mut analysis_progress(quiet)).await?;
 
     Ok(())
 }
 
-/// Analyze the workspace and provide insights
-async fn analyze_workspace(workspace_path: &Path) -> Result<()> {
-    println!("Analyzing workspace structure...");
+/// Progress of an analysis on stderr: a bar with the ETA while a phase runs
+/// (drawn only on a terminal) and its timing once it ends. Silent with
+/// `--quiet`.
+pub(crate) fn analysis_progress(quiet: bool) -> Progress {
+    if quiet {
+        return Progress::disabled();
+    }
+    let bar = ProgressBar::with_draw_target(None, ProgressDrawTarget::stderr());
+    Progress::new(Arc::new(move |event: &ProgressEvent| match event {
+        ProgressEvent::PhaseStarted { phase, total_files } => {
+            bar.reset();
+            match total_files {
+                Some(total) => {
+                    bar.set_length(*total);
+                    bar.set_style(
+                        ProgressStyle::with_template(
+                            "{prefix} [{bar:30}] {pos}/{len} files  {msg}",
+                        )
+                        .unwrap_or_else(|_| ProgressStyle::default_bar())
+                        .progress_chars("=> "),
+                    );
+                }
+                None => {
+                    bar.unset_length();
+                    bar.set_style(
+                        ProgressStyle::with_template("{spinner} {prefix} {pos} files  {msg}")
+                            .unwrap_or_else(|_| ProgressStyle::default_spinner()),
+                    );
+                }
+            }
+            bar.set_prefix(phase.clone());
+            bar.set_message("");
+            bar.enable_steady_tick(Duration::from_millis(120));
+        }
+        ProgressEvent::Advanced {
+            files,
+            bytes,
+            eta_ms,
+            ..
+        } => {
+            bar.set_position(*files);
+            bar.set_message(match eta_ms {
+                Some(eta) => format!(
+                    "{} · ETA {}",
+                    HumanBytes(*bytes),
+                    format_duration(Duration::from_millis(*eta))
+                ),
+                None => HumanBytes(*bytes).to_string(),
+            });
+        }
+        ProgressEvent::PhaseFinished { .. } => {
+            bar.finish_and_clear();
+            eprintln!("{}", style(format!("  {}", event.status_text())).dim());
+        }
+    }))
+}
 
+/// Analyze the workspace and provide insights
+async fn analyze_workspace(workspace_path: &Path, progress: &mut Progress) -> Result<()> {
     // Count files and directories
     let mut total_files = 0;
     let mut total_dirs = 0;
     let mut language_files = std::collections::HashMap::new();
 
+    progress.start_phase("scanning workspace", None);
     for entry in WalkDir::new(workspace_path)
         .follow_links(true)
         .into_iter()
@@ -33,6 +100,7 @@ async fn analyze_workspace(workspace_path: &Path) -> Result<()> {
             total_dirs += 1;
         } else if entry.file_type().is_file() {
             total_files += 1;
+            progress.advance(entry.metadata().map_or(0, |metadata| metadata.len()));
 
             // Count files by extension
             if let Some(ext) = entry.path().extension().and_then(|e| e.to_str()) {
@@ -40,6 +108,7 @@ async fn analyze_workspace(workspace_path: &Path) -> Result<()> {
             }
         }
     }
+    progress.finish_phase();
 
     println!("  Total directories: {}", total_dirs);
     println!("  Total files: {}", total_files);
@@ -52,7 +121,161 @@ async fn analyze_workspace(workspace_path: &Path) -> Result<()> {
 
     // Placeholder for deeper analysis (tree-sitter integration lives in core)
 
-    println!("Workspace analysis complete!");
+    Ok(())
+}
+
+/// Handle `vtcode analyze --risk`
+pub async fn handle_risk_map_command(
+    config: &CoreAgentConfig,
+    top: usize,
+    churn_days: u32,
+    quiet: bool,
+) -> Result<()> {
+    if !quiet {
+        println!("{}", style("Building workspace risk map...").blue().bold());
+    }
+    let workspace = config.workspace.clone();
+    let options = RiskOptions {
+        churn_days,
+        ..RiskOptions::default()
+    };
+    let mut progress = analysis_progress(quiet);
+    let map = tokio::task::spawn_blocking(move || {
+        RiskMap::compute_with_progress(&workspace, &options, &mut progress)
+    })
+    .await
+    .context("risk map task failed")??;
 
+    if map.files.is_empty() {
+        println!("No source files found that tree-sitter can analyze.");
+        return Ok(());
+    }
+
+    let path = map.save(&config.workspace)?;
+    let high = map.high_risk().count();
+    println!(
+        "{} files ranked · {} high risk · churn window {} days",
+        map.files.len(),
+        high,
+        map.churn_days
+    );
+    println!();
+    render_heatmap(&map, top);
+    println!();
+    println!(
+        "Saved to {}. Chat sessions will brief the agent on high-risk files and preview diffs before editing them.",
+        path.display()
+    );
     Ok(())
 }
+
+/// Handle `vtcode analyze --diff <RANGE>`
+pub async fn handle_diff_analysis_command(
+    config: &CoreAgentConfig,
+    range: &str,
+    json: bool,
+    quiet: bool,
+) -> Result<()> {
+    let report = diff_report(config, range, analysis_progress(quiet)).await?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print!("{}", report.to_markdown());
+    }
+    Ok(())
+}
+
+/// Differential analysis of `range` in the workspace repository.
+pub(crate) async fn diff_report(
+    config: &CoreAgentConfig,
+    range: &str,
+    mut progress: Progress,
+) -> Result<DiffReport> {
+    let range = DiffRange::parse(range)?;
+    let workspace = config.workspace.clone();
+    tokio::task::spawn_blocking(move || {
+        DiffReport::compute_with_progress(&workspace, &range, &mut progress)
+    })
+    .await
+    .context("diff analysis task failed")?
+}
+
+fn render_heatmap(map: &RiskMap, top: usize) {
+    let max = |value: fn(&FileRisk) -> f64| map.files.iter().map(value).fold(0.0_f64, f64::max);
+    let max_churn = max(|file| file.churn as f64);
+    let max_lines = max(|file| file.lines as f64);
+    let max_complexity = max(|file| file.complexity as f64);
+    let max_todos = max(|file| file.todos as f64);
+
+    println!(
+        "{}",
+        style(format!(
+            "{:<width$}  {:>5}  {:>5}  {:>6}  {:>5}  {:>5}  File",
+            "Risk",
+            "Score",
+            "Churn",
+            "Lines",
+            "Cmplx",
+            "TODOs",
+            width = RISK_BAR_WIDTH
+        ))
+        .bold()
+    );
+    for file in map.files.iter().take(top) {
+        println!(
+            "{}  {}  {}  {}  {}  {}  {}",
+            risk_bar(file),
+            level_style(file.level, format!("{:>5.1}", file.score)),
+            heat(file.churn as f64, max_churn, format!("{:>5}", file.churn)),
+            heat(file.lines as f64, max_lines, format!("{:>6}", file.lines)),
+            heat(
+                file.complexity as f64,
+                max_complexity,
+                format!("{:>5}", file.complexity)
+            ),
+            heat(file.todos as f64, max_todos, format!("{:>5}", file.todos)),
+            file.path
+        );
+    }
+    if map.files.len() > top {
+        println!(
+            "{}",
+            style(format!(
+                "... {} more files (use --top to show more)",
+                map.files.len() - top
+            ))
+            .dim()
+        );
+    }
+}
+
+fn risk_bar(file: &FileRisk) -> StyledObject<String> {
+    let filled = ((file.score / 100.0) * RISK_BAR_WIDTH as f64).ceil() as usize;
+    let filled = filled.min(RISK_BAR_WIDTH);
+    let bar = format!(
+        "{}{}",
+        "█".repeat(filled),
+        "░".repeat(RISK_BAR_WIDTH - filled)
+    );
+    level_style(file.level, bar)
+}
+
+fn level_style(level: RiskLevel, text: String) -> StyledObject<String> {
+    match level {
+        RiskLevel::High => style(text).red().bold(),
+        RiskLevel::Medium => style(text).yellow(),
+        RiskLevel::Low => style(text).green(),
+    }
+}
+
+/// Color a metric cell by how close it is to the workspace maximum.
+fn heat(value: f64, max: f64, text: String) -> StyledObject<String> {
+    let ratio = if max > 0.0 { value / max } else { 0.0 };
+    if ratio >= 0.66 {
+        style(text).red()
+    } else if ratio >= 0.33 {
+        style(text).yellow()
+    } else {
+        style(text).dim()
+    }
+}
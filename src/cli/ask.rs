@@ -3,13 +3,28 @@ use console::style;
 use futures::StreamExt;
 use std::io::{self, Write};
 use vtcode_core::{
+    code::codebase_qa::{
+        CITED_ANSWER_SYSTEM_PROMPT, CitedAnswer, ExcerptCollector, RetrievalOptions,
+        citation_feedback, cited_answer_prompt, parse_cited_answer,
+    },
     config::types::AgentConfig as CoreAgentConfig,
+    core::run_events::{FailOn, RunStatus},
     llm::{
         factory::{create_provider_for_model, create_provider_with_config},
-        provider::{LLMRequest, LLMResponse, LLMStreamEvent, Message, ToolChoice},
+        provider::{
+            LLMError, LLMProvider, LLMRequest, LLMResponse, LLMStreamEvent, Message, ToolChoice,
+        },
     },
+    utils::ansi_sanitize::terminal_supports_hyperlinks,
 };
 
+/// Options for `vtcode ask`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AskOptions {
+    pub cite: bool,
+    pub json: bool,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum AskRequestMode {
     Streaming,
@@ -40,11 +55,36 @@ fn print_final_response(printed_any: bool, response: Option<LLMResponse>) {
 }
 
 /// Handle the ask command - single prompt, no tools
-pub async fn handle_ask_command(config: &CoreAgentConfig, prompt: &str) -> Result<()> {
+///
+/// Provider failures end with [`RunStatus::ProviderError`] rather than an error
+/// so that scripts can tell them apart. Ask runs make no edits, so only
+/// `--fail-on warnings` can fail a successful answer.
+pub async fn handle_ask_command(
+    config: &CoreAgentConfig,
+    prompt: &str,
+    options: AskOptions,
+    fail_on: &[FailOn],
+) -> Result<RunStatus> {
     if prompt.trim().is_empty() {
         anyhow::bail!("No prompt provided. Use: vtcode ask \"Your question here\"");
     }
+    let outcome = if options.cite {
+        run_cited_ask(config, prompt, options.json).await
+    } else {
+        run_plain_ask(config, prompt).await.map(|()| false)
+    };
+    match outcome {
+        Ok(warned) if warned && fail_on.contains(&FailOn::Warnings) => Ok(RunStatus::FailOn),
+        Ok(_) => Ok(RunStatus::Success),
+        Err(err) if err.chain().any(|cause| cause.is::<LLMError>()) => {
+            eprintln!("{} {err:#}", style("Provider error:").red().bold());
+            Ok(RunStatus::ProviderError)
+        }
+        Err(err) => Err(err),
+    }
+}
 
+async fn run_plain_ask(config: &CoreAgentConfig, prompt: &str) -> Result<()> {
     println!("{}", style("Single Prompt Mode").blue().bold());
     println!("Provider: {}", &config.provider);
     println!("Model: {}", &config.model);
@@ -59,7 +99,7 @@ pub async fn handle_ask_command(config: &CoreAgentConfig, prompt: &str) -> Resul
         Err(_) => create_provider_with_config(
             &config.provider,
             Some(config.api_key.clone()),
-            None,
+            config.base_url.clone(),
             Some(config.model.clone()),
             Some(config.prompt_cache.clone()),
         )
@@ -84,6 +124,10 @@ pub async fn handle_ask_command(config: &CoreAgentConfig, prompt: &str) -> Resul
         parallel_tool_calls: None,
         parallel_tool_config: None,
         reasoning_effort,
+        json_mode: false,
+        response_schema: None,
+        code_execution: false,
+        seed: None,
     };
 
     match request_mode {
@@ -118,6 +162,7 @@ pub async fn handle_ask_command(config: &CoreAgentConfig, prompt: &str) -> Resul
                         print!("{}", delta);
                         io::stdout().flush().ok();
                     }
+                    Ok(LLMStreamEvent::CodeExecution { .. }) => {}
                     Ok(LLMStreamEvent::Completed { response }) => {
                         final_response = Some(response);
                     }
@@ -145,3 +190,90 @@ pub async fn handle_ask_command(config: &CoreAgentConfig, prompt: &str) -> Resul
 
     Ok(())
 }
+
+/// Answer from workspace excerpts, allowing one retry when citations don't
+/// resolve; returns whether some citations still did not resolve.
+async fn run_cited_ask(config: &CoreAgentConfig, question: &str, json: bool) -> Result<bool> {
+    eprintln!("{}", style("Codebase Q&A mode").blue().bold());
+
+    let collector = ExcerptCollector::new(&config.workspace, RetrievalOptions::default());
+    let excerpts = collector.collect(question)?;
+    if excerpts.is_empty() {
+        anyhow::bail!(
+            "No workspace files mention the terms of the question; name the identifiers or files you are asking about"
+        );
+    }
+    let files = excerpts
+        .iter()
+        .map(|excerpt| &excerpt.path)
+        .collect::<std::collections::BTreeSet<_>>()
+        .len();
+    eprintln!(
+        "Answering from {} excerpts in {} files",
+        excerpts.len(),
+        files
+    );
+
+    let provider = super::create_cli_provider(config, "ask")?;
+    let mut messages = vec![Message::user(cited_answer_prompt(question, &excerpts))];
+    let (reply, mut answer) =
+        request_cited_answer(config, provider.as_ref(), &messages, question).await?;
+    if !answer.is_verified() {
+        eprintln!("Some citations did not resolve; asking the model to correct them");
+        messages.push(Message::assistant(reply));
+        messages.push(Message::user(citation_feedback(
+            &answer.rejected,
+            !answer.citations.is_empty(),
+        )));
+        answer = request_cited_answer(config, provider.as_ref(), &messages, question)
+            .await?
+            .1;
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&answer)?);
+    } else {
+        print!("{}", answer.render(terminal_supports_hyperlinks()));
+    }
+    if answer.citations.is_empty() {
+        anyhow::bail!("The answer has no citations that resolve to workspace files");
+    }
+    Ok(!answer.is_verified())
+}
+
+async fn request_cited_answer(
+    config: &CoreAgentConfig,
+    provider: &dyn LLMProvider,
+    messages: &[Message],
+    question: &str,
+) -> Result<(String, CitedAnswer)> {
+    let reasoning_effort = if provider.supports_reasoning_effort(&config.model) {
+        Some(config.reasoning_effort.as_str().to_string())
+    } else {
+        None
+    };
+    let request = LLMRequest {
+        messages: messages.to_vec(),
+        system_prompt: Some(CITED_ANSWER_SYSTEM_PROMPT.to_string()),
+        tools: None,
+        model: config.model.clone(),
+        max_tokens: None,
+        temperature: None,
+        stream: false,
+        tool_choice: Some(ToolChoice::none()),
+        parallel_tool_calls: None,
+        parallel_tool_config: None,
+        reasoning_effort,
+        json_mode: provider.supports_json_mode(&config.model),
+        response_schema: None,
+        code_execution: false,
+        seed: None,
+    };
+    let response = provider
+        .generate(request)
+        .await
+        .context("Completion failed")?;
+    let reply = response.content.unwrap_or_default();
+    let answer = parse_cited_answer(&config.workspace, question, &reply)?;
+    Ok((reply, answer))
+}
@@ -0,0 +1,95 @@
+use anyhow::{Result, anyhow};
+use chrono::Utc;
+use console::style;
+use vtcode_core::cli::args::AuthCommands;
+use vtcode_core::config::CredentialsConfig;
+use vtcode_core::config::oauth::{DeviceFlowClient, TokenStore};
+
+/// Handle `vtcode auth`
+pub async fn handle_auth_command(
+    credentials: &CredentialsConfig,
+    command: &AuthCommands,
+) -> Result<()> {
+    let store = TokenStore::default_location()?;
+    match command {
+        AuthCommands::Login { provider } => login(credentials, &store, provider).await,
+        AuthCommands::Logout { provider } => {
+            if store.remove(provider)? {
+                println!("Signed out of {}", provider);
+            } else {
+                println!("No stored sign-in for {}", provider);
+            }
+            Ok(())
+        }
+        AuthCommands::Status => status(credentials, &store),
+    }
+}
+
+async fn login(credentials: &CredentialsConfig, store: &TokenStore, provider: &str) -> Result<()> {
+    let settings = credentials.oauth_for(provider).ok_or_else(|| {
+        anyhow!(
+            "No OAuth settings for '{}'. Add a [credentials.{}.oauth] section with client_id, device_authorization_url and token_url to vtcode.toml.",
+            provider,
+            provider.to_lowercase()
+        )
+    })?;
+
+    let client = DeviceFlowClient::new(settings.clone())?;
+    let device = client.request_device_code().await?;
+
+    println!(
+        "{}",
+        style(format!("Sign in to {}", provider)).blue().bold()
+    );
+    match &device.verification_uri_complete {
+        Some(uri) => println!(
+            "Open {} and confirm the code {}",
+            uri,
+            style(&device.user_code).bold()
+        ),
+        None => println!(
+            "Open {} and enter the code {}",
+            device.verification_uri,
+            style(&device.user_code).bold()
+        ),
+    }
+    println!("Waiting for approval...");
+
+    let token = client.poll_for_token(&device).await?;
+    store.save(provider, &token)?;
+    println!(
+        "Signed in to {}. Tokens saved to {}",
+        provider,
+        store.path_for(provider).display()
+    );
+    Ok(())
+}
+
+fn status(credentials: &CredentialsConfig, store: &TokenStore) -> Result<()> {
+    println!("{}", style("OAuth sign-ins").blue().bold());
+    let mut any = false;
+    for (provider, provider_credentials) in &credentials.providers {
+        if provider_credentials.oauth.is_none() {
+            continue;
+        }
+        any = true;
+        let state = match store.load(provider)? {
+            None => "not signed in".to_string(),
+            Some(token) => match token.expires_at {
+                Some(expires_at) if expires_at <= Utc::now() && token.refresh_token.is_none() => {
+                    "expired, sign in again".to_string()
+                }
+                Some(expires_at) if expires_at <= Utc::now() => {
+                    "access token expired, refreshes on next use".to_string()
+                }
+                Some(expires_at) => format!("signed in, access token valid until {}", expires_at),
+                None => "signed in".to_string(),
+            },
+        };
+        println!("  {}: {}", provider, state);
+    }
+    if !any {
+        println!("(no providers have [credentials.<provider>.oauth] settings)");
+    }
+    Ok(())
+}
@@ -0,0 +1,316 @@
+use anyhow::{Context, Result, anyhow, bail};
+use console::style;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::fs;
+use std::path::{Path, PathBuf};
+use vtcode_core::cli::RateLimiter;
+use vtcode_core::config::types::AgentConfig as CoreAgentConfig;
+use vtcode_core::core::batch_refactor::{
+    BatchEdit, BatchPlan, BatchSpec, BatchStore, BatchTransaction, FileStatus, PlanStatus,
+    apply_replacement, recover_interrupted_batch, run_validation,
+};
+use vtcode_core::llm::provider::{LLMProvider, LLMRequest, Message, ToolChoice};
+use vtcode_core::tools::project_commands::CommandCatalog;
+use vtcode_core::utils::code_fence::strip_code_fence;
+
+/// Files listed by `--dry-run` before the list is abbreviated
+const DRY_RUN_PREVIEW_FILES: usize = 50;
+
+const REWRITE_SYSTEM_PROMPT: &str = "You apply one mechanical change to a single file. \
+Return the complete updated file content and nothing else: no explanations and no Markdown fences. \
+Do not make changes beyond the instruction.";
+
+/// Options for `vtcode batch`
+#[derive(Debug, Clone)]
+pub struct BatchOptions {
+    pub find: Option<String>,
+    pub replace: Option<String>,
+    pub instruction: Option<String>,
+    pub literal: bool,
+    pub include: Vec<String>,
+    pub batch_size: usize,
+    pub validate: Option<String>,
+    /// Skip validation instead of using the project's build or test command
+    pub no_validate: bool,
+    pub requests_per_minute: usize,
+    /// Plan id to resume, or `latest`
+    pub resume: Option<String>,
+    pub dry_run: bool,
+}
+
+/// Handle the batch refactor command
+pub async fn handle_batch_command(config: &CoreAgentConfig, options: BatchOptions) -> Result<()> {
+    let workspace = config.workspace.as_path();
+    let store = BatchStore::new(workspace);
+
+    let mut plan = match options.resume.as_deref() {
+        Some(id) => {
+            let mut plan = if id == "latest" {
+                store.latest_unfinished()?
+            } else {
+                store.load(id)?
+            };
+            if options.validate.is_some() {
+                plan.validation_command = options.validate.clone();
+            }
+            if plan.status == PlanStatus::Completed {
+                println!("Batch plan {} is already complete.", plan.id);
+                return Ok(());
+            }
+            let restored = recover_interrupted_batch(workspace, &store, &plan)?;
+            if restored > 0 {
+                println!(
+                    "Rolled back {} files from the interrupted batch {}",
+                    restored,
+                    plan.completed_batches + 1
+                );
+            }
+            println!(
+                "{} plan {} at batch {}/{}",
+                style("Resuming").blue().bold(),
+                plan.id,
+                plan.completed_batches + 1,
+                plan.batch_count()
+            );
+            plan
+        }
+        None => {
+            let plan = BatchPlan::create(workspace, build_spec(workspace, &options)?)?;
+            print_plan(&plan, options.dry_run);
+            if options.dry_run || plan.files.is_empty() {
+                return Ok(());
+            }
+            plan
+        }
+    };
+
+    let provider = match plan.edit {
+        BatchEdit::Instruction { .. } => Some(super::create_cli_provider(config, "batch")?),
+        BatchEdit::Replace { .. } => None,
+    };
+    let limiter = RateLimiter::new(options.requests_per_minute.max(1), usize::MAX);
+
+    plan.status = PlanStatus::InProgress;
+    plan.failure = None;
+    store.save(&plan)?;
+
+    let regex = plan.regex()?;
+    let progress = ProgressBar::new(plan.files.len() as u64);
+    progress.set_style(
+        ProgressStyle::with_template("{spinner} [{bar:30}] {pos}/{len} files  {msg}")
+            .unwrap_or_else(|_| ProgressStyle::default_bar())
+            .progress_chars("=> "),
+    );
+    progress.set_position(plan.batch_range(plan.completed_batches).start as u64);
+
+    for index in plan.completed_batches..plan.batch_count() {
+        let range = plan.batch_range(index);
+        let files: Vec<PathBuf> = plan.files[range.clone()]
+            .iter()
+            .map(|file| file.path.clone())
+            .collect();
+        progress.set_message(format!("batch {}/{}", index + 1, plan.batch_count()));
+
+        let transaction = BatchTransaction::begin(workspace, &store, &plan, &files)?;
+        for (offset, file) in files.iter().enumerate() {
+            let original = transaction.original(file).unwrap_or_default().to_string();
+            let updated = match &plan.edit {
+                BatchEdit::Replace { replacement } => Ok(apply_replacement(
+                    &regex,
+                    &original,
+                    replacement,
+                    plan.literal,
+                )),
+                BatchEdit::Instruction { text } => {
+                    let provider = provider
+                        .as_deref()
+                        .ok_or_else(|| anyhow!("no provider for instruction edits"))?;
+                    limiter.wait_for_api_request().await?;
+                    rewrite_with_model(config, provider, text, &plan.pattern, file, &original).await
+                }
+            };
+
+            let status = match updated {
+                Ok(updated) if updated == original => FileStatus::Unchanged,
+                Ok(updated) => {
+                    if let Err(err) = fs::write(workspace.join(file), updated) {
+                        progress.abandon();
+                        transaction.rollback()?;
+                        return Err(err)
+                            .with_context(|| format!("failed to write {}", file.display()));
+                    }
+                    FileStatus::Applied
+                }
+                Err(err) => {
+                    progress.println(format!(
+                        "{} {}: {:#}",
+                        style("skipped").yellow(),
+                        file.display(),
+                        err
+                    ));
+                    FileStatus::Failed
+                }
+            };
+            plan.files[range.start + offset].status = status;
+            progress.inc(1);
+        }
+
+        if let Some(command) = plan.validation_command.clone() {
+            progress.set_message(format!("validating batch {}", index + 1));
+            let outcome = run_validation(workspace, &command)?;
+            if !outcome.success {
+                progress.abandon();
+                transaction.rollback()?;
+                for file in &mut plan.files[range] {
+                    file.status = FileStatus::Pending;
+                }
+                plan.status = PlanStatus::Failed;
+                plan.failure = Some(format!(
+                    "batch {} failed validation `{}`",
+                    index + 1,
+                    command
+                ));
+                store.save(&plan)?;
+                eprintln!("{}", outcome.output);
+                bail!(
+                    "Batch {} failed validation and was rolled back. Fix the problem and run `vtcode batch --resume {}`",
+                    index + 1,
+                    plan.id
+                );
+            }
+        }
+
+        transaction.commit()?;
+        plan.completed_batches = index + 1;
+        store.save(&plan)?;
+    }
+
+    plan.status = PlanStatus::Completed;
+    store.save(&plan)?;
+    progress.finish_with_message("done");
+    print_summary(&plan);
+    Ok(())
+}
+
+fn build_spec(workspace: &Path, options: &BatchOptions) -> Result<BatchSpec> {
+    let pattern = options
+        .find
+        .clone()
+        .ok_or_else(|| anyhow!("--find is required unless --resume is given"))?;
+    let edit = match (&options.replace, &options.instruction) {
+        (Some(replacement), None) => BatchEdit::Replace {
+            replacement: replacement.clone(),
+        },
+        (None, Some(text)) => BatchEdit::Instruction { text: text.clone() },
+        _ => bail!("Specify exactly one of --replace or --instruction"),
+    };
+    Ok(BatchSpec {
+        pattern,
+        literal: options.literal,
+        edit,
+        include: options.include.clone(),
+        batch_size: options.batch_size,
+        validation_command: validation_command(workspace, options),
+    })
+}
+
+/// `--validate`, or else the project's own build (or test) command.
+fn validation_command(workspace: &Path, options: &BatchOptions) -> Option<String> {
+    if options.no_validate {
+        return None;
+    }
+    options.validate.clone().or_else(|| {
+        CommandCatalog::discover(workspace)
+            .validation_command()
+            .map(|command| command.command.clone())
+    })
+}
+
+fn print_plan(plan: &BatchPlan, dry_run: bool) {
+    println!("{}", style("Batch refactor plan").blue().bold());
+    println!("Plan: {}", plan.id);
+    println!(
+        "{} matches in {} files, {} batches of up to {}",
+        plan.total_matches(),
+        plan.files.len(),
+        plan.batch_count(),
+        plan.batch_size
+    );
+    if let Some(command) = &plan.validation_command {
+        println!("Validation: {}", command);
+    }
+    if plan.files.is_empty() {
+        println!("No files match the pattern.");
+        return;
+    }
+    if dry_run {
+        for file in plan.files.iter().take(DRY_RUN_PREVIEW_FILES) {
+            println!("  {} ({} matches)", file.path.display(), file.matches);
+        }
+        if plan.files.len() > DRY_RUN_PREVIEW_FILES {
+            println!(
+                "  ... and {} more",
+                plan.files.len() - DRY_RUN_PREVIEW_FILES
+            );
+        }
+        println!("Dry run: no files were changed.");
+    }
+}
+
+fn print_summary(plan: &BatchPlan) {
+    let count = |status: FileStatus| plan.files.iter().filter(|f| f.status == status).count();
+    println!(
+        "Applied {} files, {} unchanged, {} skipped",
+        count(FileStatus::Applied),
+        count(FileStatus::Unchanged),
+        count(FileStatus::Failed)
+    );
+}
+
+async fn rewrite_with_model(
+    config: &CoreAgentConfig,
+    provider: &dyn LLMProvider,
+    instruction: &str,
+    pattern: &str,
+    path: &Path,
+    content: &str,
+) -> Result<String> {
+    let prompt = format!(
+        "Instruction: {}\nThe change targets matches of `{}`.\n\nFile: {}\n```\n{}\n```",
+        instruction,
+        pattern,
+        path.display(),
+        content
+    );
+    let request = LLMRequest {
+        messages: vec![Message::user(prompt)],
+        system_prompt: Some(REWRITE_SYSTEM_PROMPT.to_string()),
+        tools: None,
+        model: config.model.clone(),
+        max_tokens: None,
+        temperature: None,
+        stream: false,
+        tool_choice: Some(ToolChoice::none()),
+        parallel_tool_calls: None,
+        parallel_tool_config: None,
+        reasoning_effort: None,
+        json_mode: false,
+        response_schema: None,
+        code_execution: false,
+        seed: None,
+    };
+    let response = provider
+        .generate(request)
+        .await
+        .context("Rewrite request failed")?;
+    let text = response
+        .content
+        .filter(|text| !text.trim().is_empty())
+        .ok_or_else(|| anyhow!("model returned no content"))?;
+
+    let mut updated = strip_code_fence(&text).to_string();
+    if content.ends_with('\n') && !updated.ends_with('\n') {
+        updated.push('\n');
+    }
+    Ok(updated)
+}
@@ -0,0 +1,197 @@
+use anyhow::{Context, Result};
+use chrono::Local;
+use console::style;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use vtcode_core::config::api_keys::{ApiKeySources, get_api_key};
+use vtcode_core::config::loader::{ConfigManager, VTCodeConfig};
+use vtcode_core::core::trajectory::config_hash;
+use vtcode_core::utils::bug_report::{BugReportBundle, last_turns, tail_lines};
+use vtcode_core::utils::dot_config::DotManager;
+use vtcode_core::utils::environment_manifest::EnvironmentManifest;
+use vtcode_core::utils::redact::{Redactor, WORKSPACE_PLACEHOLDER};
+use vtcode_core::utils::session_archive;
+
+/// Log files included, newest first.
+const MAX_LOG_FILES: usize = 5;
+/// Lines kept from the end of each log file.
+const LOG_TAIL_LINES: usize = 200;
+
+#[derive(Debug, Clone)]
+pub struct BugReportOptions {
+    pub session: String,
+    pub turns: usize,
+    pub output: Option<PathBuf>,
+}
+
+/// Handle `vtcode bug-report`
+pub fn handle_bug_report_command(
+    workspace: &Path,
+    config_manager: &ConfigManager,
+    options: BugReportOptions,
+) -> Result<()> {
+    let cfg = config_manager.config();
+    let mut redactor =
+        Redactor::new(&cfg.security.redaction)?.with_known_path(workspace, WORKSPACE_PLACEHOLDER);
+    let mut bundle = BugReportBundle::new();
+    let mut skipped = Vec::new();
+
+    let mut environment = EnvironmentManifest::capture(
+        config_hash(cfg),
+        BTreeMap::from([
+            ("chat".to_string(), cfg.agent.default_model.clone()),
+            ("router".to_string(), cfg.router.llm_router_model.clone()),
+            (
+                "prompt refiner".to_string(),
+                cfg.agent.refine_prompts_model.clone(),
+            ),
+        ]),
+    );
+    for value in environment.env.values_mut() {
+        *value = redactor.redact(value);
+    }
+    bundle.add(
+        "environment.json",
+        serde_json::to_string_pretty(&environment)?,
+    );
+
+    let config_toml = toml::to_string_pretty(cfg).context("failed to serialize configuration")?;
+    bundle.add("config.toml", redactor.redact(&config_toml));
+
+    bundle.add(
+        "doctor.txt",
+        redactor.redact(&doctor_report(workspace, config_manager, &environment)),
+    );
+
+    match session_archive::find_session(&options.session) {
+        Ok(listing) => {
+            let trimmed = last_turns(&listing.snapshot, options.turns);
+            let redacted = redactor.redact_snapshot(&trimmed);
+            bundle.add(
+                format!("session/{}.json", listing.identifier()),
+                serde_json::to_string_pretty(&redacted)?,
+            );
+        }
+        Err(err) => skipped.push(format!("session: {err}")),
+    }
+
+    for (folder, log) in recent_logs(workspace) {
+        let Ok(text) = fs::read_to_string(&log) else {
+            continue;
+        };
+        let name = log
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        bundle.add(
+            format!("logs/{folder}/{name}"),
+            redactor.redact(&tail_lines(&text, LOG_TAIL_LINES)),
+        );
+    }
+
+    let path = options.output.unwrap_or_else(|| {
+        PathBuf::from(format!(
+            "vtcode-bug-report-{}.zip",
+            Local::now().format("%Y%m%dT%H%M%S")
+        ))
+    });
+    bundle.write_zip(&path)?;
+
+    println!(
+        "{} {}",
+        style("Bug report written to").green(),
+        path.display()
+    );
+    for name in bundle.names() {
+        println!("  {name}");
+    }
+    for reason in &skipped {
+        println!("  {} {}", style("skipped").yellow(), reason);
+    }
+    println!("  Replaced: {}", redactor.report().summary());
+    println!(
+        "{}",
+        style("Unzip and review the bundle before attaching it to an issue.").dim()
+    );
+    Ok(())
+}
+
+/// Read-only checks of the setup the report was collected on.
+fn doctor_report(
+    workspace: &Path,
+    config_manager: &ConfigManager,
+    environment: &EnvironmentManifest,
+) -> String {
+    let cfg: &VTCodeConfig = config_manager.config();
+    let provider = &cfg.agent.provider;
+    let mut lines = vec![
+        format!("workspace: {}", workspace.display()),
+        match config_manager.config_path() {
+            Some(path) => format!("config: {}", path.display()),
+            None => "config: defaults (no vtcode.toml found)".to_string(),
+        },
+        format!("provider: {provider}"),
+        format!(
+            "api key for {provider}: {}",
+            if get_api_key(provider, &ApiKeySources::default()).is_ok() {
+                "found"
+            } else {
+                "missing"
+            }
+        ),
+    ];
+    lines.extend(
+        environment
+            .tools
+            .iter()
+            .map(|(tool, version)| format!("{tool}: {version}")),
+    );
+    match DotManager::new() {
+        Ok(manager) => {
+            let dir = manager.config_dir();
+            lines.push(format!(
+                "dot folder: {} ({})",
+                dir.display(),
+                if dir.is_dir() { "present" } else { "missing" }
+            ));
+        }
+        Err(err) => lines.push(format!("dot folder: {err}")),
+    }
+    lines.push(match session_archive::list_recent_sessions(0) {
+        Ok(sessions) => format!("archived sessions: {}", sessions.len()),
+        Err(err) => format!("archived sessions: {err}"),
+    });
+    let mut report = lines.join("\n");
+    report.push('\n');
+    report
+}
+
+/// The most recently modified files in the workspace and dot folder log
+/// directories, with the folder they are filed under in the bundle.
+fn recent_logs(workspace: &Path) -> Vec<(&'static str, PathBuf)> {
+    let mut dirs = vec![("workspace", workspace.join("logs"))];
+    if let Ok(manager) = DotManager::new() {
+        dirs.push(("vtcode", manager.logs_dir()));
+    }
+    let mut logs: Vec<(SystemTime, &'static str, PathBuf)> = Vec::new();
+    for (label, dir) in dirs {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if let (true, Ok(modified)) = (metadata.is_file(), metadata.modified()) {
+                logs.push((modified, label, entry.path()));
+            }
+        }
+    }
+    logs.sort_by(|a, b| b.0.cmp(&a.0));
+    logs.into_iter()
+        .take(MAX_LOG_FILES)
+        .map(|(_, label, path)| (label, path))
+        .collect()
+}
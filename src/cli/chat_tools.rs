@@ -1,23 +1,61 @@
 use anyhow::Result;
+use std::path::Path;
 use vtcode_core::config::types::AgentConfig as CoreAgentConfig;
+use vtcode_core::core::live_share::ShareLink;
+use vtcode_core::core::run_events::{FailOn, RunEventLog, RunStatus};
+use vtcode_core::core::session_store::StoredSession;
 use vtcode_core::utils::dot_config::WorkspaceTrustLevel;
 
 use crate::workspace_trust::{WorkspaceTrustGateResult, ensure_workspace_trust};
 
+/// Run a chat session and report how it ended; see [`RunStatus::exit_code`].
+/// `resume` continues a session saved under `.vtcode/sessions`.
+#[allow(clippy::too_many_arguments)]
 pub async fn handle_chat_command(
     config: &CoreAgentConfig,
     skip_confirmations: bool,
     full_auto: bool,
-) -> Result<()> {
+    events_file: Option<&Path>,
+    fail_on: &[FailOn],
+    initial_prompt: Option<String>,
+    share: Option<ShareLink>,
+    resume: Option<StoredSession>,
+) -> Result<RunStatus> {
+    let run_events = match events_file {
+        Some(path) => RunEventLog::open(path)?,
+        None => RunEventLog::disabled(),
+    };
     match ensure_workspace_trust(&config.workspace, full_auto)? {
         WorkspaceTrustGateResult::Trusted(level) => {
             if full_auto && level != WorkspaceTrustLevel::FullAuto {
-                return Ok(());
+                run_events.finish(
+                    RunStatus::Failed,
+                    Some("workspace is not trusted for full-auto runs"),
+                );
+                return Ok(RunStatus::Failed);
             }
         }
         WorkspaceTrustGateResult::Aborted => {
-            return Ok(());
+            run_events.finish(
+                RunStatus::Interrupted,
+                Some("workspace trust prompt aborted"),
+            );
+            return Ok(RunStatus::Interrupted);
         }
     }
-    crate::agent::runloop::run_single_agent_loop(config, skip_confirmations, full_auto).await
+    let result = crate::agent::runloop::run_single_agent_loop(
+        config,
+        skip_confirmations,
+        full_auto,
+        &run_events,
+        fail_on,
+        initial_prompt,
+        share,
+        resume,
+    )
+    .await;
+    if let Err(err) = &result {
+        run_events.finish(RunStatus::Failed, Some(&format!("{err:#}")));
+    }
+    result
 }
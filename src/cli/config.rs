@@ -2,8 +2,10 @@ use anyhow::Result;
 use console::style;
 use std::fs;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use vtcode_core::config::editor::ConfigDocument;
 use vtcode_core::config::{ConfigManager, VTCodeConfig};
+use vtcode_core::ui::config_editor::run_config_editor;
 
 /// Handle the config command
 pub async fn handle_config_command(output: Option<&Path>, use_home_dir: bool) -> Result<()> {
@@ -41,6 +43,35 @@ pub async fn handle_config_command(output: Option<&Path>, use_home_dir: bool) ->
     Ok(())
 }
 
+/// Handle `vtcode config edit`
+pub fn handle_config_edit_command(workspace: &Path, use_home_dir: bool) -> Result<()> {
+    let path = if use_home_dir {
+        ConfigManager::user_config_path()
+            .ok_or_else(|| anyhow::anyhow!("Could not determine the home directory"))?
+    } else {
+        workspace_config_path(workspace)
+    };
+
+    let document = ConfigDocument::load(&path)?;
+    if run_config_editor(document)? {
+        println!("Configuration saved to {}", path.display());
+    } else {
+        println!("No changes written to {}", path.display());
+    }
+    Ok(())
+}
+
+/// The workspace config file `vtcode` loads, or `vtcode.toml` when none exists yet
+fn workspace_config_path(workspace: &Path) -> PathBuf {
+    let primary = workspace.join("vtcode.toml");
+    let fallback = workspace.join(".vtcode").join("vtcode.toml");
+    if !primary.exists() && fallback.exists() {
+        fallback
+    } else {
+        primary
+    }
+}
+
 /// Generate default configuration content
 /// This function creates a complete configuration by:
 /// 1. Loading existing vtcode.toml if it exists (preserving user customizations)
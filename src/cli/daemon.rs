@@ -0,0 +1,145 @@
+use anyhow::{Context, Result, bail};
+use chrono::Utc;
+use console::style;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+use vtcode_core::cli::args::DaemonCommands;
+use vtcode_core::config::loader::VTCodeConfig;
+use vtcode_core::core::daemon::{
+    DaemonReply, DaemonRequest, DaemonServer, send_request, socket_path,
+};
+use vtcode_core::core::offline::provider_endpoint;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+const STARTUP_WAIT: Duration = Duration::from_secs(5);
+
+/// Handle `vtcode daemon`
+pub async fn handle_daemon_command(
+    workspace: &Path,
+    config: &VTCodeConfig,
+    provider: &str,
+    command: &DaemonCommands,
+) -> Result<()> {
+    let socket = socket_path(workspace)?;
+    match command {
+        DaemonCommands::Start => start(workspace, &socket).await,
+        DaemonCommands::Run => run(workspace, config, provider, &socket).await,
+        DaemonCommands::Stop => {
+            match send_request(&socket, DaemonRequest::Shutdown, REQUEST_TIMEOUT).await {
+                Ok(reply) => {
+                    println!("Stopped daemon (pid {})", reply.pid);
+                    Ok(())
+                }
+                Err(_) => {
+                    println!("No daemon is running for {}", workspace.display());
+                    Ok(())
+                }
+            }
+        }
+        DaemonCommands::Status => {
+            match send_request(&socket, DaemonRequest::Status, REQUEST_TIMEOUT).await {
+                Ok(reply) => print_status(&reply, &socket),
+                Err(_) => println!("No daemon is running for {}", workspace.display()),
+            }
+            Ok(())
+        }
+    }
+}
+
+async fn start(workspace: &Path, socket: &Path) -> Result<()> {
+    if let Ok(reply) = send_request(socket, DaemonRequest::Status, REQUEST_TIMEOUT).await {
+        println!("Daemon already running (pid {})", reply.pid);
+        return Ok(());
+    }
+    if let Some(parent) = socket.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let log_path = socket.with_extension("log");
+    let log = std::fs::File::create(&log_path)
+        .with_context(|| format!("failed to create {}", log_path.display()))?;
+
+    let exe = std::env::current_exe().context("failed to locate the vtcode executable")?;
+    let mut command = Command::new(exe);
+    command
+        .arg(workspace)
+        .args(["daemon", "run"])
+        .stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log);
+    #[cfg(unix)]
+    {
+        // Leave the terminal's process group so closing it does not stop the daemon.
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    command.spawn().context("failed to start the daemon")?;
+
+    let deadline = tokio::time::Instant::now() + STARTUP_WAIT;
+    while tokio::time::Instant::now() < deadline {
+        if let Ok(reply) = send_request(socket, DaemonRequest::Status, REQUEST_TIMEOUT).await {
+            println!(
+                "{} (pid {}) for {}",
+                style("Daemon started").green(),
+                reply.pid,
+                workspace.display()
+            );
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    bail!(
+        "The daemon did not start; see {} for details",
+        log_path.display()
+    )
+}
+
+async fn run(workspace: &Path, config: &VTCodeConfig, provider: &str, socket: &Path) -> Result<()> {
+    let endpoints = provider_endpoint(provider)
+        .map(|endpoint| vec![endpoint.to_string()])
+        .unwrap_or_default();
+    let server = DaemonServer::new(
+        workspace.to_path_buf(),
+        endpoints,
+        Duration::from_secs(config.daemon.refresh_interval_secs),
+        Duration::from_millis(config.offline.probe_timeout_ms.max(100)),
+        config.tools.imports.max_index_files,
+    );
+    println!("Serving {} on {}", workspace.display(), socket.display());
+    tokio::select! {
+        result = server.run(socket) => result,
+        _ = tokio::signal::ctrl_c() => {
+            let _ = std::fs::remove_file(socket);
+            Ok(())
+        }
+    }
+}
+
+fn print_status(reply: &DaemonReply, socket: &Path) {
+    let uptime = Utc::now().signed_duration_since(reply.started_at);
+    println!(
+        "{} (pid {}, up {}m)",
+        style("Daemon running").green(),
+        reply.pid,
+        uptime.num_minutes()
+    );
+    println!("  Socket: {}", socket.display());
+    match reply.state.refreshed_at {
+        Some(at) => println!(
+            "  Refreshed: {}s ago",
+            Utc::now().signed_duration_since(at).num_seconds()
+        ),
+        None => println!("  Refreshed: first refresh in progress"),
+    }
+    if let Some(summary) = &reply.state.language_summary {
+        println!("  Languages: {}", summary);
+    }
+    println!("  Symbol index: {} files", reply.state.indexed_files);
+    for (endpoint, status) in &reply.state.endpoints {
+        match status {
+            None => println!("  {}: reachable", endpoint),
+            Some(reason) => println!("  {}: {}", endpoint, reason),
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use anyhow::{Context, Result, bail};
+use console::style;
+use std::collections::BTreeSet;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use vtcode_core::config::types::AgentConfig as CoreAgentConfig;
+use vtcode_core::core::ci_failures::{CiFailure, CiRun, classify_failures, clean_log, fix_prompt};
+
+/// Failures listed in the commit message body
+const COMMIT_BODY_FAILURES: usize = 10;
+
+/// Options for `vtcode fix-ci`
+#[derive(Debug, Clone)]
+pub struct FixCiOptions {
+    pub run: String,
+    pub commit: bool,
+}
+
+/// Handle the fix-ci command
+pub async fn handle_fix_ci_command(
+    config: &CoreAgentConfig,
+    options: FixCiOptions,
+    skip_confirmations: bool,
+    full_auto: bool,
+    events_file: Option<&Path>,
+) -> Result<()> {
+    let workspace = config.workspace.as_path();
+    let run = CiRun::parse(&options.run, workspace)?;
+    println!(
+        "{} {}",
+        style("Fetching CI log for").blue().bold(),
+        run.label()
+    );
+    let log = clean_log(&run.fetch_log(workspace)?);
+    let failures = classify_failures(&log, workspace);
+    print_failures(&failures);
+
+    let repo = repo_root(workspace);
+    let dirty_before = match &repo {
+        Some(repo) => changed_files(repo)?,
+        None => BTreeSet::new(),
+    };
+    if !dirty_before.is_empty() {
+        println!(
+            "{} {} files already had uncommitted changes; they are left out of the fix commit.",
+            style("Note:").yellow(),
+            dirty_before.len()
+        );
+    }
+
+    super::handle_chat_command(
+        config,
+        skip_confirmations,
+        full_auto,
+        events_file,
+        &[],
+        Some(fix_prompt(&run, &failures, &log)),
+        None,
+        None,
+    )
+    .await?;
+
+    let Some(repo) = repo else {
+        println!("Not in a git repository; review the changes before committing them.");
+        return Ok(());
+    };
+    let changed: Vec<String> = changed_files(&repo)?
+        .difference(&dirty_before)
+        .cloned()
+        .collect();
+    if changed.is_empty() {
+        println!("No files were changed.");
+        return Ok(());
+    }
+
+    println!(
+        "{}",
+        style("Changes made for the failing run").blue().bold()
+    );
+    for file in &changed {
+        println!("  {}", file);
+    }
+
+    let commit = options.commit || (io::stdin().is_terminal() && confirm("Commit these changes?")?);
+    if !commit {
+        println!("Left the changes uncommitted.");
+        return Ok(());
+    }
+    let (subject, body) = commit_message(&run, &failures);
+    git(&repo, &["add", "-A", "--"], &changed)?;
+    git(&repo, &["commit", "-m", &subject, "-m", &body], &[])?;
+    println!("{} {}", style("Committed").green(), subject);
+    Ok(())
+}
+
+fn print_failures(failures: &[CiFailure]) {
+    if failures.is_empty() {
+        println!("No failures recognised; the agent will start from the end of the log.");
+        return;
+    }
+    println!("Found {} failures:", failures.len());
+    for failure in failures {
+        let target = failure
+            .test
+            .clone()
+            .or_else(|| failure.place())
+            .unwrap_or_default();
+        println!(
+            "  {} {} {}",
+            style(format!("[{}]", failure.kind)).yellow(),
+            target,
+            style(&failure.message).dim()
+        );
+    }
+}
+
+fn commit_message(run: &CiRun, failures: &[CiFailure]) -> (String, String) {
+    let label = match run {
+        CiRun::File(path) => path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| run.label()),
+        CiRun::GitHub { .. } => run.label(),
+    };
+    let subject = format!("Fix CI failures from {}", label);
+    let mut body: Vec<String> = failures
+        .iter()
+        .take(COMMIT_BODY_FAILURES)
+        .map(|failure| {
+            let target = failure
+                .test
+                .clone()
+                .or_else(|| failure.place())
+                .unwrap_or_default();
+            format!("- {} {}: {}", failure.kind, target, failure.message)
+        })
+        .collect();
+    if failures.len() > COMMIT_BODY_FAILURES {
+        body.push(format!(
+            "- and {} more",
+            failures.len() - COMMIT_BODY_FAILURES
+        ));
+    }
+    if body.is_empty() {
+        body.push("Fixes the failure reported in the CI log.".to_string());
+    }
+    (subject, body.join("\n"))
+}
+
+fn repo_root(workspace: &Path) -> Option<PathBuf> {
+    git(workspace, &["rev-parse", "--show-toplevel"], &[])
+        .ok()
+        .map(|root| PathBuf::from(root.trim()))
+}
+
+/// Paths with uncommitted changes, relative to the repository root.
+fn changed_files(repo: &Path) -> Result<BTreeSet<String>> {
+    let status = git(
+        repo,
+        &["status", "--porcelain", "--untracked-files=all"],
+        &[],
+    )?;
+    Ok(status
+        .lines()
+        .filter_map(|line| line.get(3..))
+        .map(|path| match path.split_once(" -> ") {
+            Some((_, renamed)) => renamed.to_string(),
+            None => path.to_string(),
+        })
+        .collect())
+}
+
+fn git(dir: &Path, args: &[&str], paths: &[String]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .args(paths)
+        .current_dir(dir)
+        .output()
+        .with_context(|| format!("Failed to run git {}", args.join(" ")))?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn confirm(question: &str) -> Result<bool> {
+    print!("{} [y/N]: ", question);
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(matches!(input.trim().to_lowercase().as_str(), "y" | "yes"))
+}
@@ -6,7 +6,7 @@ use std::path::Path;
 use vtcode_core::config::core::PromptCachingConfig;
 use vtcode_core::config::loader::VTCodeConfig;
 use vtcode_core::config::types::{
-    AgentConfig as CoreAgentConfig, ReasoningEffortLevel, UiSurfacePreference,
+    AgentConfig as CoreAgentConfig, ReasoningEffortLevel, RenderMode, UiSurfacePreference,
 };
 use vtcode_core::ui::theme::DEFAULT_THEME_ID;
 
@@ -36,14 +36,16 @@ pub async fn handle_init_command(workspace: &Path, force: bool, run: bool) -> Re
             model: String::new(),
             api_key: String::new(),
             provider: String::new(),
+            base_url: None,
             workspace: workspace.to_path_buf(),
             verbose: false,
             theme: DEFAULT_THEME_ID.to_string(),
             reasoning_effort: ReasoningEffortLevel::default(),
             ui_surface: UiSurfacePreference::default(),
+            render_mode: RenderMode::default(),
             prompt_cache: PromptCachingConfig::default(),
         };
-        handle_chat_command(&config, false, false)
+        handle_chat_command(&config, false, false, None, &[], None, None, None)
             .await
             .with_context(|| "failed to start chat session")?;
     }
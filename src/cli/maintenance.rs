@@ -0,0 +1,105 @@
+use anyhow::{Context, Result};
+use console::style;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use vtcode_core::config::ConfigManager;
+use vtcode_core::utils::dot_config::DotManager;
+use vtcode_core::utils::dot_maintenance::DotMaintenance;
+use vtcode_core::utils::session_archive::SESSION_DIR_ENV;
+use vtcode_core::utils::session_compaction::SessionCompactor;
+
+/// Handle `vtcode maintenance`
+pub fn handle_maintenance_command(
+    workspace: &Path,
+    prune_older_than: Option<Duration>,
+) -> Result<()> {
+    let manager = DotManager::new().context("failed to locate the VTCode dot directory")?;
+    let dot_dir = manager.config_dir().to_path_buf();
+    let sessions_dir = std::env::var_os(SESSION_DIR_ENV)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| manager.sessions_dir());
+    let mut maintenance = DotMaintenance::new(manager)
+        .with_workspace(workspace.to_path_buf())
+        .with_sessions_dir(sessions_dir.clone());
+    if let Some(age) = prune_older_than {
+        maintenance = maintenance.prune_older_than(age);
+    }
+    let report = maintenance.run()?;
+
+    // Repairs come first so a broken vtcode.toml cannot stop them; compaction
+    // then falls back to the default settings.
+    let sessions_config = ConfigManager::load_from_workspace(workspace)
+        .map(|manager| manager.config().sessions.clone())
+        .unwrap_or_default();
+    let mut compactor = SessionCompactor::new(sessions_dir, &sessions_config)
+        .with_workspace(workspace.to_path_buf());
+    if let Some(age) = prune_older_than {
+        compactor = compactor.prune_older_than(age);
+    }
+    let compaction = compactor.run()?;
+
+    println!(
+        "{} {}",
+        style("Maintenance of").bold(),
+        style(dot_dir.display()).bold()
+    );
+    for store in &report.stores {
+        let mut status = Vec::new();
+        if !store.repairs.is_empty() {
+            status.push(format!("{} repaired", store.repairs.len()));
+        }
+        if store.pruned > 0 {
+            status.push(format!("{} pruned", store.pruned));
+        }
+        let status = if status.is_empty() {
+            style("ok".to_string()).green()
+        } else {
+            style(status.join(", ")).yellow()
+        };
+        println!(
+            "  {:<12} {:>6} checked  {}",
+            store.store, store.checked, status
+        );
+        for repair in &store.repairs {
+            println!("    {} {}", style("•").dim(), repair);
+        }
+    }
+
+    if compaction.compacted > 0 || compaction.dropped > 0 {
+        println!(
+            "  {:<12} {} compacted into the archive, {} old snapshots dropped, {} freed",
+            "archive",
+            compaction.compacted,
+            compaction.dropped,
+            format_size(compaction.bytes_freed())
+        );
+    }
+
+    if report.repairs() == 0 && report.pruned() == 0 {
+        println!("{}", style("Nothing to repair.").green());
+    } else {
+        println!(
+            "{} {} repaired, {} pruned, {} freed",
+            style("Done:").green(),
+            report.repairs(),
+            report.pruned(),
+            format_size(report.bytes_freed())
+        );
+    }
+    Ok(())
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
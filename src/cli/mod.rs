@@ -3,37 +3,66 @@
 // Feature-gated tool-capable chat; fallback to minimal REPL
 pub mod analyze;
 pub mod ask;
+pub mod auth;
+pub mod batch;
 pub mod benchmark;
+pub mod bug_report;
 pub mod chat_tools;
 pub mod compress_context;
 pub mod config;
 pub mod create_project;
+pub mod daemon;
+pub mod fix_ci;
 pub mod init;
 pub mod init_project;
+pub mod maintenance;
 pub mod man;
 pub mod performance;
+pub mod prompts;
+pub mod resume;
 pub mod revert;
+pub mod review;
+pub mod session;
+pub mod share;
 pub mod snapshots;
+pub mod stats;
 pub mod trajectory;
 
 // Re-export command handlers for backward compatibility
-pub use analyze::handle_analyze_command;
-pub use ask::handle_ask_command as handle_ask_single_command;
+pub use analyze::{handle_analyze_command, handle_diff_analysis_command, handle_risk_map_command};
+pub use ask::{AskOptions, handle_ask_command as handle_ask_single_command};
+pub use auth::handle_auth_command;
+pub use batch::{BatchOptions, handle_batch_command};
 pub use benchmark::handle_benchmark_command;
+pub use bug_report::{BugReportOptions, handle_bug_report_command};
 // Use the modular runloop by default
 pub use chat_tools::handle_chat_command;
 pub use compress_context::handle_compress_context_command;
-pub use config::handle_config_command;
+pub use config::{handle_config_command, handle_config_edit_command};
 pub use create_project::handle_create_project_command;
+pub use daemon::handle_daemon_command;
+pub use fix_ci::{FixCiOptions, handle_fix_ci_command};
 pub use init::handle_init_command;
 pub use init_project::handle_init_project_command;
+pub use maintenance::handle_maintenance_command;
 pub use man::handle_man_command;
 pub use performance::handle_performance_command;
+pub use prompts::handle_prompts_command;
+pub use resume::{handle_resume_command, handle_resume_list_command};
 pub use revert::handle_revert_command;
+pub use review::{ReviewOptions, handle_review_command};
+pub use session::handle_session_command;
+pub use share::handle_share_command;
 pub use snapshots::{handle_cleanup_snapshots_command, handle_snapshots_command};
+pub use stats::handle_stats_command;
 pub use trajectory::handle_trajectory_command as handle_trajectory_logs_command;
+pub use trajectory::handle_trajectory_trends_command;
 
+use anyhow::{Context, Result};
 use std::path::Path;
+use vtcode_core::config::types::AgentConfig as CoreAgentConfig;
+use vtcode_core::llm::factory::{create_provider_for_model, create_provider_with_config};
+use vtcode_core::llm::provider::LLMProvider;
 
 /// Export the current workspace directory through the expected environment variable
 pub fn set_workspace_env(path: &Path) {
@@ -42,3 +71,25 @@ pub fn set_workspace_env(path: &Path) {
         std::env::set_var("WORKSPACE_DIR", path);
     }
 }
+
+/// Create the LLM provider for a one-shot CLI command from the agent config
+pub(crate) fn create_cli_provider(
+    config: &CoreAgentConfig,
+    command: &str,
+) -> Result<Box<dyn LLMProvider>> {
+    match create_provider_for_model(
+        &config.model,
+        config.api_key.clone(),
+        Some(config.prompt_cache.clone()),
+    ) {
+        Ok(provider) => Ok(provider),
+        Err(_) => create_provider_with_config(
+            &config.provider,
+            Some(config.api_key.clone()),
+            config.base_url.clone(),
+            Some(config.model.clone()),
+            Some(config.prompt_cache.clone()),
+        )
+        .with_context(|| format!("Failed to initialize provider for {} command", command)),
+    }
+}
@@ -0,0 +1,231 @@
+use anyhow::{Context, Result, bail};
+use console::{Alignment, Style, Term, pad_str, style, truncate_str};
+use std::path::Path;
+use vtcode_core::cli::args::PromptsCommands;
+use vtcode_core::config::constants::tools as tool_names;
+use vtcode_core::config::loader::VTCodeConfig;
+use vtcode_core::config::types::AgentConfig as CoreAgentConfig;
+use vtcode_core::core::prompt_replay::{
+    PromptReplay, REPLAY_CACHE_DIR, RecordedToolOutputs, ResponseCache, align_lines, recorded_turns,
+};
+use vtcode_core::llm::provider::{MessageRole, ToolDefinition};
+use vtcode_core::tools::build_function_declarations;
+use vtcode_core::utils::session_archive;
+
+use crate::agent::runloop::system_prompt_from_template;
+
+const DEFAULT_WIDTH: usize = 120;
+const MIN_COLUMN_WIDTH: usize = 20;
+
+/// Handle `vtcode prompts`
+pub async fn handle_prompts_command(
+    config: &CoreAgentConfig,
+    vt_cfg: &VTCodeConfig,
+    command: &PromptsCommands,
+) -> Result<()> {
+    match command {
+        PromptsCommands::Diff {
+            session,
+            prompt,
+            turns,
+            max_steps,
+            no_cache,
+        } => {
+            diff(
+                config, vt_cfg, session, prompt, turns, *max_steps, *no_cache,
+            )
+            .await
+        }
+    }
+}
+
+async fn diff(
+    config: &CoreAgentConfig,
+    vt_cfg: &VTCodeConfig,
+    session: &str,
+    prompt_path: &Path,
+    selected: &[usize],
+    max_steps: usize,
+    no_cache: bool,
+) -> Result<()> {
+    let listing = session_archive::find_session(session)?;
+    let messages = &listing.snapshot.messages;
+    let turns = recorded_turns(messages);
+    if turns.is_empty() {
+        bail!(
+            "Session {} has no user turns to replay",
+            listing.identifier()
+        );
+    }
+    if let Some(turn) = selected
+        .iter()
+        .find(|turn| **turn == 0 || **turn > turns.len())
+    {
+        bail!(
+            "Turn {} is out of range; the session has {} turns",
+            turn,
+            turns.len()
+        );
+    }
+
+    let template = std::fs::read_to_string(prompt_path)
+        .with_context(|| format!("Failed to read prompt template {}", prompt_path.display()))?;
+    let system_prompt = system_prompt_from_template(template, &config.workspace, None);
+    let provider = super::create_cli_provider(config, "prompts diff")?;
+    let cache = if no_cache {
+        ResponseCache::disabled()
+    } else {
+        ResponseCache::new(&config.workspace)
+    };
+    let mut replay = PromptReplay::new(
+        provider.as_ref(),
+        config.model.clone(),
+        system_prompt,
+        replay_tools(vt_cfg),
+        cache,
+    )
+    .with_max_steps(max_steps)
+    .with_reproducibility(listing.snapshot.reproducibility.clone());
+    let mut outputs = RecordedToolOutputs::from_messages(messages);
+
+    println!(
+        "{} {} with {} on {}",
+        style("Replaying").bold(),
+        style(listing.identifier()).bold(),
+        prompt_path.display(),
+        config.model
+    );
+    if let Some(record) = &listing.snapshot.reproducibility {
+        println!(
+            "{}",
+            style(format!(
+                "Recorded in reproducibility mode: seed {}, temperature {}",
+                record.seed, record.temperature
+            ))
+            .dim()
+        );
+    }
+    let has_tool_results = messages
+        .iter()
+        .any(|message| message.role == MessageRole::Tool);
+    let has_tool_calls = messages.iter().any(|message| message.tool_calls.is_some());
+    if has_tool_results && !has_tool_calls {
+        println!(
+            "{}",
+            style(
+                "This session was archived without its tool calls; replayed calls get no recorded output."
+            )
+            .yellow()
+        );
+    }
+
+    let width = Term::stdout()
+        .size_checked()
+        .map(|(_, cols)| usize::from(cols))
+        .unwrap_or(DEFAULT_WIDTH);
+    let column = (width.saturating_sub(5) / 2).max(MIN_COLUMN_WIDTH);
+
+    let mut replayed_count = 0;
+    let mut output_changes = 0;
+    let mut tool_changes = 0;
+    for (index, turn) in turns.iter().enumerate() {
+        let number = index + 1;
+        if !selected.is_empty() && !selected.contains(&number) {
+            continue;
+        }
+        let replayed = replay
+            .replay_turn(turn, &mut outputs)
+            .await
+            .with_context(|| format!("Failed to replay turn {number}"))?;
+        replayed_count += 1;
+
+        let prompt_line = turn.prompt.lines().next().unwrap_or_default();
+        println!();
+        println!(
+            "{} {}",
+            style(format!("Turn {number}:")).bold(),
+            truncate_str(prompt_line, width.saturating_sub(10), "…")
+        );
+        println!(
+            "  {} │ {}",
+            pad_str(
+                &style("recorded").dim().to_string(),
+                column,
+                Alignment::Left,
+                None
+            ),
+            style("replayed").dim()
+        );
+
+        let rows = align_lines(&turn.recorded.lines(), &replayed.lines());
+        let mut changed = false;
+        for (left, right) in rows {
+            let (marker, left_style, right_style) = match (&left, &right) {
+                (Some(a), Some(b)) if a == b => (' ', Style::new().dim(), Style::new().dim()),
+                (Some(_), Some(_)) => ('~', Style::new().red(), Style::new().green()),
+                (Some(_), None) => ('-', Style::new().red(), Style::new().dim()),
+                (None, _) => ('+', Style::new().dim(), Style::new().green()),
+            };
+            changed |= marker != ' ';
+            let cell = |text: Option<String>| {
+                let text = text.unwrap_or_default();
+                pad_str(&text, column, Alignment::Left, Some("…")).into_owned()
+            };
+            println!(
+                "{} {} │ {}",
+                marker,
+                left_style.apply_to(cell(left)),
+                right_style.apply_to(cell(right))
+            );
+        }
+
+        let recorded_tools = turn.recorded.tool_sequence();
+        let replayed_tools = replayed.tool_sequence();
+        let tools_changed = recorded_tools != replayed_tools;
+        output_changes += usize::from(changed);
+        tool_changes += usize::from(tools_changed);
+        let summary = if tools_changed {
+            style(format!(
+                "Tool calls changed: {} recorded, {} replayed",
+                recorded_tools.len(),
+                replayed_tools.len()
+            ))
+            .yellow()
+        } else if changed {
+            style("Same tool calls; output changed".to_string()).yellow()
+        } else {
+            style("No change".to_string()).green()
+        };
+        println!("  {summary}");
+    }
+
+    let stats = replay.stats();
+    println!();
+    println!(
+        "{} of {} turns changed; tool call sequences changed in {}.",
+        output_changes, replayed_count, tool_changes
+    );
+    println!(
+        "{}",
+        style(format!(
+            "{} model requests, {} from the cache in {}.",
+            stats.requests, stats.cache_hits, REPLAY_CACHE_DIR
+        ))
+        .dim()
+    );
+    Ok(())
+}
+
+/// The tools a chat session would offer, so the replayed model sees the same set.
+fn replay_tools(vt_cfg: &VTCodeConfig) -> Vec<ToolDefinition> {
+    let sql_enabled = vt_cfg.tools.sql.enabled;
+    let vulnerability_scan_enabled = vt_cfg.tools.vulnerability_scan.enabled;
+    let semantic_search_enabled = vt_cfg.tools.semantic_search.enabled;
+    build_function_declarations()
+        .into_iter()
+        .filter(|decl| sql_enabled || !tool_names::SQL_TOOLS.contains(&decl.name.as_str()))
+        .filter(|decl| vulnerability_scan_enabled || decl.name != tool_names::VULNERABILITY_SCAN)
+        .filter(|decl| semantic_search_enabled || decl.name != tool_names::SEMANTIC_SEARCH)
+        .map(|decl| ToolDefinition::function(decl.name, decl.description, decl.parameters))
+        .collect()
+}
@@ -0,0 +1,63 @@
+use anyhow::Result;
+use console::style;
+use std::path::Path;
+use vtcode_core::config::types::AgentConfig as CoreAgentConfig;
+use vtcode_core::core::run_events::{FailOn, RunStatus};
+use vtcode_core::core::session_store::{SESSION_STORE_DIR, find_session, list_sessions};
+
+/// Handle `vtcode resume --list`: show the sessions saved in `workspace`
+pub fn handle_resume_list_command(workspace: &Path) -> Result<()> {
+    let sessions = list_sessions(workspace);
+    if sessions.is_empty() {
+        println!(
+            "No saved sessions in {}; chat sessions are saved after every turn.",
+            workspace.join(SESSION_STORE_DIR).display()
+        );
+        return Ok(());
+    }
+    println!("{}", style("Saved sessions").magenta().bold());
+    for session in sessions {
+        let label = session
+            .title
+            .clone()
+            .or_else(|| session.first_prompt_preview())
+            .unwrap_or_else(|| "untitled".to_string());
+        println!(
+            "{}  {}  {}",
+            style(&session.id).cyan(),
+            style(format!(
+                "{} · {} turns · {}",
+                session.updated_at.format("%Y-%m-%d %H:%M"),
+                session.turns,
+                session.model
+            ))
+            .dim(),
+            label
+        );
+    }
+    println!("\nContinue one with {}", style("vtcode resume <id>").bold());
+    Ok(())
+}
+
+/// Handle `vtcode resume`: continue a saved chat session
+pub async fn handle_resume_command(
+    config: &CoreAgentConfig,
+    session: &str,
+    skip_confirmations: bool,
+    full_auto: bool,
+    events_file: Option<&Path>,
+    fail_on: &[FailOn],
+) -> Result<RunStatus> {
+    let saved = find_session(&config.workspace, session)?;
+    super::handle_chat_command(
+        config,
+        skip_confirmations,
+        full_auto,
+        events_file,
+        fail_on,
+        None,
+        None,
+        Some(saved),
+    )
+    .await
+}
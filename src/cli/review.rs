@@ -0,0 +1,214 @@
+use anyhow::{Context, Result};
+use console::style;
+use std::path::PathBuf;
+use std::process::Command;
+use vtcode_core::{
+    code::security_review::{
+        CollectorOptions, SECURITY_REVIEW_SYSTEM_PROMPT, SecurityCandidateCollector,
+        SecurityReport, parse_findings, security_review_prompt,
+    },
+    config::types::AgentConfig as CoreAgentConfig,
+    llm::provider::{LLMProvider, LLMRequest, Message, ToolChoice},
+};
+
+/// Maximum diff size sent to the model in general review mode
+const MAX_DIFF_CHARS: usize = 60_000;
+
+const GENERAL_REVIEW_SYSTEM_PROMPT: &str = "You are a senior engineer reviewing a change. \
+Point out bugs, risky behaviour, missing tests and unclear code, citing file and line where possible. \
+Be concise and skip praise.";
+
+/// Options for `vtcode review`
+#[derive(Debug, Clone)]
+pub struct ReviewOptions {
+    pub security: bool,
+    /// Git range reviewed instead of the uncommitted diff
+    pub diff: Option<String>,
+    pub paths: Vec<PathBuf>,
+    pub json: bool,
+    pub output: Option<PathBuf>,
+    pub max_locations: usize,
+}
+
+/// Handle the review command
+pub async fn handle_review_command(config: &CoreAgentConfig, options: ReviewOptions) -> Result<()> {
+    let provider = super::create_cli_provider(config, "review")?;
+    if options.security {
+        run_security_review(config, provider.as_ref(), &options).await
+    } else {
+        run_diff_review(config, provider.as_ref(), options.diff.as_deref()).await
+    }
+}
+
+fn review_request(
+    config: &CoreAgentConfig,
+    provider: &dyn LLMProvider,
+    system_prompt: &str,
+    prompt: String,
+    json_mode: bool,
+) -> LLMRequest {
+    let reasoning_effort = if provider.supports_reasoning_effort(&config.model) {
+        Some(config.reasoning_effort.as_str().to_string())
+    } else {
+        None
+    };
+    LLMRequest {
+        messages: vec![Message::user(prompt)],
+        system_prompt: Some(system_prompt.to_string()),
+        tools: None,
+        model: config.model.clone(),
+        max_tokens: None,
+        temperature: None,
+        stream: false,
+        tool_choice: Some(ToolChoice::none()),
+        parallel_tool_calls: None,
+        parallel_tool_config: None,
+        reasoning_effort,
+        json_mode: json_mode && provider.supports_json_mode(&config.model),
+        response_schema: None,
+        code_execution: false,
+        seed: None,
+    }
+}
+
+async fn run_diff_review(
+    config: &CoreAgentConfig,
+    provider: &dyn LLMProvider,
+    range: Option<&str>,
+) -> Result<()> {
+    println!("{}", style("Review mode").blue().bold());
+
+    let report = match range {
+        Some(range) => Some(
+            super::analyze::diff_report(config, range, super::analyze::analysis_progress(false))
+                .await?,
+        ),
+        None => None,
+    };
+    let diff_args = match &report {
+        Some(report) => vec![
+            "diff",
+            report.base_commit.as_str(),
+            report.head_commit.as_str(),
+        ],
+        None => vec!["diff", "HEAD"],
+    };
+    let output = Command::new("git")
+        .args(&diff_args)
+        .current_dir(&config.workspace)
+        .output()
+        .context("Failed to run git diff")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git diff failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let mut diff = String::from_utf8_lossy(&output.stdout).into_owned();
+    if diff.trim().is_empty() {
+        match range {
+            Some(range) => println!("No changes in {range} to review."),
+            None => println!("No uncommitted changes to review."),
+        }
+        return Ok(());
+    }
+    if diff.len() > MAX_DIFF_CHARS {
+        let mut cut = MAX_DIFF_CHARS;
+        while !diff.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        diff.truncate(cut);
+        diff.push_str("\n[diff truncated]");
+    }
+
+    let mut prompt = String::new();
+    if let Some(report) = &report {
+        prompt.push_str(&format!(
+            "Structured analysis of the change, for context:\n\n{}\n",
+            report.to_markdown()
+        ));
+    }
+    prompt.push_str(&format!("Review this diff:\n\n```diff\n{}\n```", diff));
+    let request = review_request(
+        config,
+        provider,
+        GENERAL_REVIEW_SYSTEM_PROMPT,
+        prompt,
+        false,
+    );
+    let response = provider
+        .generate(request)
+        .await
+        .context("Review request failed")?;
+    println!("{}", response.content.unwrap_or_default());
+    Ok(())
+}
+
+async fn run_security_review(
+    config: &CoreAgentConfig,
+    provider: &dyn LLMProvider,
+    options: &ReviewOptions,
+) -> Result<()> {
+    eprintln!("{}", style("Security review mode").blue().bold());
+
+    let collector_options = CollectorOptions {
+        max_candidates_per_category: options.max_locations.max(1),
+        ..CollectorOptions::default()
+    };
+    let mut collector = SecurityCandidateCollector::new(&config.workspace, collector_options)?;
+    let collected = collector.collect(&options.paths)?;
+    eprintln!(
+        "Scanned {} files, {} locations selected for review",
+        collected.scanned_files,
+        collected.total()
+    );
+
+    let mut report = SecurityReport {
+        workspace: config.workspace.display().to_string(),
+        scanned_files: collected.scanned_files,
+        candidates_reviewed: collected.total(),
+        ..Default::default()
+    };
+
+    for (category, candidates) in &collected.by_category {
+        eprintln!(
+            "  Reviewing {} ({} locations)...",
+            category.label(),
+            candidates.len()
+        );
+        let prompt = security_review_prompt(*category, candidates);
+        let request = review_request(
+            config,
+            provider,
+            SECURITY_REVIEW_SYSTEM_PROMPT,
+            prompt,
+            true,
+        );
+        let findings = match provider.generate(request).await {
+            Ok(response) => parse_findings(*category, &response.content.unwrap_or_default()),
+            Err(err) => Err(anyhow::Error::new(err)),
+        };
+        match findings {
+            Ok(findings) => report.findings.extend(findings),
+            Err(err) => report
+                .errors
+                .push(format!("{}: {:#}", category.label(), err)),
+        }
+    }
+    report.sort();
+
+    let rendered = if options.json {
+        serde_json::to_string_pretty(&report)?
+    } else {
+        report.to_markdown()
+    };
+    match &options.output {
+        Some(path) => {
+            std::fs::write(path, rendered)
+                .with_context(|| format!("Failed to write report to {}", path.display()))?;
+            eprintln!("Report written to {}", path.display());
+        }
+        None => println!("{}", rendered),
+    }
+    Ok(())
+}
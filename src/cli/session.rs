@@ -0,0 +1,176 @@
+use anyhow::Result;
+use chrono::Local;
+use console::style;
+use std::path::{Path, PathBuf};
+use vtcode_core::cli::args::SessionCommands;
+use vtcode_core::config::core::RedactionConfig;
+use vtcode_core::utils::redact::{Redactor, WORKSPACE_PLACEHOLDER};
+use vtcode_core::utils::session_archive::{self, redacted_copy_path, write_snapshot};
+use vtcode_core::utils::session_compaction::{ARCHIVE_DIR, ArchiveIndex};
+
+/// Handle `vtcode session`
+pub fn handle_session_command(config: &RedactionConfig, command: &SessionCommands) -> Result<()> {
+    match command {
+        SessionCommands::List { limit } => list(*limit),
+        SessionCommands::Info { id } => info(id),
+        SessionCommands::Search { query, limit } => search(&query.join(" "), *limit),
+        SessionCommands::Redact {
+            id,
+            patterns,
+            output,
+        } => redact(config, id, patterns, output.clone()),
+    }
+}
+
+fn list(limit: usize) -> Result<()> {
+    let listings = session_archive::list_recent_sessions(limit)?;
+    if listings.is_empty() {
+        println!("No archived sessions found.");
+        return Ok(());
+    }
+    for listing in listings {
+        let title = listing
+            .title()
+            .unwrap_or_else(|| "Untitled session".to_string());
+        let ended = listing
+            .snapshot
+            .ended_at
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M");
+        println!("{}", style(title).bold());
+        println!(
+            "  {} · {} · {} · {} msgs",
+            style(listing.identifier()).dim(),
+            ended,
+            listing.snapshot.metadata.workspace_label,
+            listing.snapshot.total_messages
+        );
+    }
+    Ok(())
+}
+
+fn info(id: &str) -> Result<()> {
+    let listing = session_archive::find_session(id)?;
+    let snapshot = &listing.snapshot;
+    let metadata = &snapshot.metadata;
+    println!(
+        "{}",
+        style(
+            listing
+                .title()
+                .unwrap_or_else(|| "Untitled session".to_string())
+        )
+        .bold()
+    );
+    println!("  id: {}", listing.identifier());
+    println!(
+        "  {} → {}",
+        snapshot
+            .started_at
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M"),
+        snapshot
+            .ended_at
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M")
+    );
+    println!("  workspace: {}", metadata.workspace_path);
+    println!(
+        "  {} / {} · reasoning {} · {} msgs",
+        metadata.provider, metadata.model, metadata.reasoning_effort, snapshot.total_messages
+    );
+    if !snapshot.distinct_tools.is_empty() {
+        println!("  tools used: {}", snapshot.distinct_tools.join(", "));
+    }
+
+    println!();
+    println!("{}", style("Environment").bold());
+    match &metadata.environment {
+        Some(environment) => {
+            for line in environment.lines() {
+                println!("  {line}");
+            }
+        }
+        None => println!(
+            "  {}",
+            style("Not recorded; the session predates environment manifests.").dim()
+        ),
+    }
+    Ok(())
+}
+
+fn search(query: &str, limit: usize) -> Result<()> {
+    let sessions_dir = session_archive::resolve_sessions_dir()?;
+    let index = ArchiveIndex::load(&sessions_dir)?;
+    let found = index.search(query);
+    if found.is_empty() {
+        println!(
+            "No compacted session matches \"{query}\". Recent sessions are compacted once they are older than [sessions] compact_after_days; `vtcode session list` shows those."
+        );
+        return Ok(());
+    }
+    let archive = sessions_dir.join(ARCHIVE_DIR);
+    for session in found.iter().take(limit) {
+        let title = session.title.as_deref().unwrap_or("Untitled session");
+        let ended = session
+            .ended_at
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M");
+        println!("{}", style(title).bold());
+        println!(
+            "  {} · {} · {} · {} msgs",
+            style(&session.identifier).dim(),
+            ended,
+            session.workspace_label,
+            session.total_messages
+        );
+        for line in session.summary.lines() {
+            println!("  {}", style(line).dim());
+        }
+        println!("  {}", archive.join(&session.file).display());
+    }
+    if found.len() > limit {
+        println!("… {} more; raise --limit to see them", found.len() - limit);
+    }
+    Ok(())
+}
+
+fn redact(
+    config: &RedactionConfig,
+    id: &str,
+    patterns: &[String],
+    output: Option<PathBuf>,
+) -> Result<()> {
+    let listing = session_archive::find_session(id)?;
+    let identifier = listing.identifier();
+    let mut redactor = Redactor::new(config)?.with_known_path(
+        Path::new(&listing.snapshot.metadata.workspace_path),
+        WORKSPACE_PLACEHOLDER,
+    );
+    for pattern in patterns {
+        redactor = redactor.with_pattern(pattern)?;
+    }
+
+    let redacted = redactor.redact_snapshot(&listing.snapshot);
+    let path = match output {
+        Some(path) => path,
+        None => redacted_copy_path(&identifier)?,
+    };
+    write_snapshot(&path, &redacted)?;
+
+    println!(
+        "{} {}",
+        style("Redacted copy of").green(),
+        style(&identifier).bold()
+    );
+    println!("  Replaced: {}", redactor.report().summary());
+    println!("  Written to {}", path.display());
+    if redacted.metadata.environment.is_some() {
+        println!("  Includes the environment manifest (see `vtcode session info`)");
+    }
+    println!(
+        "{}",
+        style("Review the copy before sharing; only the configured patterns were checked.").dim()
+    );
+    Ok(())
+}
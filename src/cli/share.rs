@@ -0,0 +1,39 @@
+use anyhow::Result;
+use console::style;
+use std::path::Path;
+use vtcode_core::config::types::AgentConfig as CoreAgentConfig;
+use vtcode_core::core::live_share::LiveShare;
+use vtcode_core::core::run_events::RunStatus;
+
+/// Handle `vtcode share`: run a chat session while serving a live view of it
+pub async fn handle_share_command(
+    config: &CoreAgentConfig,
+    listen: &str,
+    allow_input: bool,
+    skip_confirmations: bool,
+    full_auto: bool,
+    events_file: Option<&Path>,
+) -> Result<RunStatus> {
+    let mut share = LiveShare::start(listen, allow_input).await?;
+    let link = share.link();
+    println!("{} {}", style("Sharing this session at").green(), link.url);
+    if allow_input {
+        println!(
+            "{}",
+            style("Viewers with the link can send prompts to the session.").yellow()
+        );
+    }
+    let status = super::handle_chat_command(
+        config,
+        skip_confirmations,
+        full_auto,
+        events_file,
+        &[],
+        None,
+        Some(link),
+        None,
+    )
+    .await;
+    drop(share);
+    status
+}
@@ -0,0 +1,98 @@
+use anyhow::{Result, anyhow};
+use chrono::Local;
+use console::style;
+use vtcode_core::config::SpendConfig;
+use vtcode_core::core::spend_tracker::{SpendTotals, default_ledger_path, format_usd, read_ledger};
+
+/// Handle `vtcode stats`
+pub fn handle_stats_command(config: &SpendConfig, spend: bool, days: usize) -> Result<()> {
+    if spend {
+        show_spend(config, days)?;
+    }
+    Ok(())
+}
+
+fn show_spend(config: &SpendConfig, days: usize) -> Result<()> {
+    let path = default_ledger_path().ok_or_else(|| anyhow!("no home directory to read from"))?;
+    let ledger = read_ledger(&path);
+
+    println!(
+        "{} {}",
+        style("Spend").magenta().bold(),
+        style(path.display()).dim()
+    );
+    if !config.enabled {
+        println!("Spend tracking is off; enable it with [llm.spend] enabled = true.");
+    }
+    if ledger.is_empty() {
+        println!("No spend recorded yet; totals are written after each turn.");
+        return Ok(());
+    }
+
+    println!(
+        "\n{:<12} {:<16} {:>8} {:>12} {:>12} {:>10}",
+        "date", "provider", "requests", "prompt", "completion", "cost"
+    );
+    let mut total = SpendTotals::default();
+    let skip = ledger.len().saturating_sub(days);
+    for (day, providers) in ledger.iter().skip(skip) {
+        for (provider, totals) in providers {
+            let unpriced = if totals.unpriced_requests > 0 {
+                style(format!(" ({} unpriced)", totals.unpriced_requests))
+                    .dim()
+                    .to_string()
+            } else {
+                String::new()
+            };
+            println!(
+                "{:<12} {:<16} {:>8} {:>12} {:>12} {:>10}{}",
+                day,
+                provider,
+                totals.requests,
+                totals.prompt_tokens,
+                totals.completion_tokens,
+                format_usd(totals.cost_usd),
+                unpriced
+            );
+            total.add(totals);
+        }
+    }
+    println!(
+        "{:<12} {:<16} {:>8} {:>12} {:>12} {:>10}",
+        style("total").bold(),
+        "",
+        total.requests,
+        total.prompt_tokens,
+        total.completion_tokens,
+        style(format_usd(total.cost_usd)).bold()
+    );
+
+    let today = Local::now().format("%Y-%m-%d").to_string();
+    let today_cost: f64 = ledger.get(&today).map_or(0.0, |providers| {
+        providers.values().map(|t| t.cost_usd).sum()
+    });
+    println!();
+    if config.daily_usd > 0.0 {
+        let marker = if today_cost >= config.daily_usd {
+            style("⚠").yellow()
+        } else {
+            style("✓").green()
+        };
+        println!(
+            "{} Today: {} of the {} daily budget",
+            marker,
+            format_usd(today_cost),
+            format_usd(config.daily_usd)
+        );
+    } else {
+        println!("Today: {} (no daily budget)", format_usd(today_cost));
+    }
+    if config.session_usd > 0.0 {
+        println!("Session budget: {}", format_usd(config.session_usd));
+    }
+    println!(
+        "{}",
+        style("Costs are estimates from the catalog prices in docs/models.json.").dim()
+    );
+    Ok(())
+}
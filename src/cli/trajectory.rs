@@ -8,6 +8,7 @@ use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::PathBuf;
 use vtcode_core::config::types::AgentConfig as CoreAgentConfig;
+use vtcode_core::core::trajectory_trends::{TrendPeriod, TrendReport, read_sessions};
 
 #[derive(Debug, Deserialize)]
 #[serde(tag = "kind")]
@@ -179,6 +180,74 @@ pub async fn handle_trajectory_command(
     Ok(())
 }
 
+/// Show per-period session aggregates and regressions after model or
+/// configuration changes.
+pub async fn handle_trajectory_trends_command(
+    file: Option<PathBuf>,
+    period: &str,
+    top: usize,
+) -> Result<()> {
+    let period: TrendPeriod = period.parse()?;
+    let workspace = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let log_path = file.unwrap_or_else(|| workspace.join("logs/trajectory.jsonl"));
+    let sessions = read_sessions(&log_path)?;
+
+    println!(
+        "{} {}",
+        style("Trajectory Trends").magenta().bold(),
+        style(log_path.display()).dim()
+    );
+    if sessions.is_empty() {
+        println!("No session records yet; they are written when a chat session ends.");
+        return Ok(());
+    }
+
+    let report = TrendReport::build(&sessions, period);
+    println!(
+        "{} sessions, {} model or config changes",
+        style(report.sessions).cyan(),
+        style(report.changes.len()).cyan()
+    );
+
+    println!("\n{}", style("Periods").bold());
+    println!(
+        "{:<12} {:>8} {:>7} {:>8} {:>10} {:>11}",
+        "period", "sessions", "turns", "reverts", "tokens", "tool errors"
+    );
+    let skip = report.periods.len().saturating_sub(top);
+    for aggregate in report.periods.iter().skip(skip) {
+        println!(
+            "{:<12} {:>8} {:>7.1} {:>8.1} {:>10.0} {:>10.1}%",
+            aggregate.period,
+            aggregate.sessions,
+            aggregate.avg_turns,
+            aggregate.avg_reverts,
+            aggregate.avg_tokens,
+            aggregate.tool_error_rate * 100.0
+        );
+    }
+
+    if !report.changes.is_empty() {
+        println!("\n{}", style("Regressions").bold());
+        if report.regressions.is_empty() {
+            println!(
+                "{} No regressions after model or config changes",
+                style("✓").green()
+            );
+        }
+        for regression in &report.regressions {
+            println!(
+                "{} {} ({})",
+                style("⚠").yellow(),
+                regression.describe(),
+                style(format_timestamp(regression.change.ts)).dim()
+            );
+        }
+    }
+
+    Ok(())
+}
+
 fn format_timestamp(ts: i64) -> String {
     if let Some(dt) = DateTime::<Utc>::from_timestamp(ts, 0) {
         dt.format("%Y-%m-%d %H:%M:%S UTC").to_string()
@@ -5,12 +5,19 @@
 use anyhow::{Context, Result, anyhow, bail};
 use clap::Parser;
 use colorchoice::ColorChoice as GlobalColorChoice;
+use console::style;
 use std::path::PathBuf;
-use vtcode_core::cli::args::{Cli, Commands};
-use vtcode_core::config::api_keys::{ApiKeySources, get_api_key, load_dotenv};
+use vtcode_core::cli::args::{Cli, Commands, ConfigCommands};
+use vtcode_core::config::api_keys::{
+    ApiKeySources, get_api_key, load_dotenv, resolve_credential_profiles,
+};
 use vtcode_core::config::loader::ConfigManager;
+use vtcode_core::config::model_pin::{ModelPin, ModelSelection, check_pinned_model, select_model};
+use vtcode_core::config::oauth::TokenSource;
 use vtcode_core::config::types::AgentConfig as CoreAgentConfig;
+use vtcode_core::core::run_events::RunStatus;
 use vtcode_core::ui::theme::{self as ui_theme, DEFAULT_THEME_ID};
+use vtcode_core::utils::{index_exclude, network};
 use vtcode_core::{initialize_dot_folder, load_user_config, update_theme_preference};
 
 mod agent;
@@ -48,6 +55,20 @@ async fn main() -> Result<()> {
 
     cli::set_workspace_env(&workspace);
 
+    // The editor has to open broken or missing configs and needs no API key
+    if let Some(Commands::Config {
+        command: Some(ConfigCommands::Edit { global }),
+        ..
+    }) = &args.command
+    {
+        return cli::handle_config_edit_command(&workspace, *global);
+    }
+
+    // Maintenance repairs the stores a broken startup would trip over
+    if let Some(Commands::Maintenance { prune_older_than }) = &args.command {
+        return cli::handle_maintenance_command(&workspace, *prune_older_than);
+    }
+
     // Load configuration (vtcode.toml or defaults) from resolved workspace
     let config_manager = ConfigManager::load_from_workspace(&workspace).with_context(|| {
         format!(
@@ -56,6 +77,65 @@ async fn main() -> Result<()> {
         )
     })?;
     let cfg = config_manager.config();
+    index_exclude::configure(&workspace, &cfg.index.exclude);
+    let network_settings = network::configure(&cfg.network, &workspace)
+        .context("Invalid [network] settings in vtcode.toml")?;
+    for scope in network_settings.insecure_scopes() {
+        eprintln!(
+            "{}",
+            style(format!(
+                "WARNING: TLS certificate verification is disabled for {scope}. \
+                 Anyone on the network path can read and alter these requests, \
+                 including API keys. Remove danger_accept_invalid_certs from [network] \
+                 as soon as possible."
+            ))
+            .red()
+            .bold()
+        );
+    }
+
+    // Signing in must work before any API key is available
+    if let Some(Commands::Auth { command }) = &args.command {
+        return cli::handle_auth_command(&cfg.credentials, command).await;
+    }
+
+    // Archived sessions are local files; redacting them needs no API key
+    if let Some(Commands::Session { command }) = &args.command {
+        return cli::handle_session_command(&cfg.security.redaction, command);
+    }
+
+    // A bug report must be collectable when the provider is what is broken
+    if let Some(Commands::BugReport {
+        session,
+        turns,
+        output,
+    }) = &args.command
+    {
+        let options = cli::BugReportOptions {
+            session: session.clone(),
+            turns: *turns,
+            output: output.clone(),
+        };
+        return cli::handle_bug_report_command(&workspace, &config_manager, options);
+    }
+
+    // Spend totals are a local file; reading them needs no API key
+    if let Some(Commands::Stats { spend, days }) = &args.command {
+        return cli::handle_stats_command(&cfg.llm.spend, *spend, *days);
+    }
+
+    if let Some(Commands::Resume { list: true, .. }) = &args.command {
+        return cli::handle_resume_list_command(&workspace);
+    }
+
+    // The daemon only caches local state and probes endpoints; it needs no API key
+    if let Some(Commands::Daemon { command }) = &args.command {
+        let provider = args
+            .provider
+            .clone()
+            .unwrap_or_else(|| cfg.agent.provider.clone());
+        return cli::handle_daemon_command(&workspace, cfg, &provider, command).await;
+    }
 
     if args.full_auto {
         let automation_cfg = &cfg.automation.full_auto;
@@ -88,15 +168,46 @@ async fn main() -> Result<()> {
 
     let skip_confirmations = args.skip_confirmations || args.full_auto;
 
-    // Resolve provider/model/theme with CLI override
-    let provider = args
-        .provider
-        .clone()
-        .unwrap_or_else(|| cfg.agent.provider.clone());
-    let model = args
-        .model
-        .clone()
-        .unwrap_or_else(|| cfg.agent.default_model.clone());
+    // Resolve provider/model/theme with CLI override, unless the workspace pins them
+    let pin_source = config_manager
+        .config_path()
+        .filter(|_| cfg.agent.pin.enabled);
+    let pin = match pin_source {
+        Some(source) if source.starts_with(&workspace) => Some(ModelPin {
+            provider: &cfg.agent.provider,
+            model: &cfg.agent.default_model,
+            source,
+        }),
+        Some(source) => {
+            eprintln!(
+                "Warning: [agent.pin] in {} is ignored; pins apply only in a workspace vtcode.toml.",
+                source.display()
+            );
+            None
+        }
+        None => None,
+    };
+    let selection = select_model(
+        pin,
+        args.provider.as_deref(),
+        args.model.as_deref(),
+        &cfg.agent.provider,
+        &cfg.agent.default_model,
+        args.pin_policy(),
+    )?;
+    if let Some(notice) = &selection.notice {
+        eprintln!("{notice}");
+    }
+    if selection.pinned
+        && let Some(pin) = pin
+        && let Some(warning) =
+            check_pinned_model(&cfg.agent.pin, pin, cfg.context.max_context_tokens)?
+    {
+        eprintln!("Warning: {warning}");
+    }
+    let ModelSelection {
+        provider, model, ..
+    } = selection;
 
     initialize_dot_folder().ok();
     let user_theme_pref = load_user_config().ok().and_then(|dot| {
@@ -130,23 +241,46 @@ async fn main() -> Result<()> {
 
     update_theme_preference(&theme_selection).ok();
 
-    // Resolve API key for chosen provider
-    let api_key = get_api_key(&provider, &ApiKeySources::default())
-        .with_context(|| format!("API key not found for provider '{}'", provider))?;
+    // Resolve API key for chosen provider, preferring the workspace's credential profile
+    // and then an OAuth sign-in
+    let api_key = match resolve_credential_profiles(&provider, &cfg.credentials)?
+        .into_iter()
+        .next()
+    {
+        Some(credential) => credential.api_key,
+        None => match TokenSource::for_provider(&provider, &cfg.credentials)? {
+            Some(tokens) => tokens.access_token().await?,
+            None => get_api_key(&provider, &ApiKeySources::default()).with_context(|| {
+                format!(
+                    "API key not found for provider '{}'. Set one or run `vtcode auth login --provider {}`",
+                    provider, provider
+                )
+            })?,
+        },
+    };
 
     // Bridge to local CLI modules
     let core_cfg = CoreAgentConfig {
         model: model.clone(),
         api_key,
         provider: provider.clone(),
+        // The endpoint belongs to the configured provider, not one picked with --provider
+        base_url: cfg
+            .agent
+            .base_url
+            .clone()
+            .filter(|_| provider.eq_ignore_ascii_case(&cfg.agent.provider)),
         workspace: workspace.clone(),
         verbose: args.verbose,
         theme: theme_selection.clone(),
         reasoning_effort: cfg.agent.reasoning_effort,
         ui_surface: cfg.agent.ui_surface,
+        render_mode: args.render.unwrap_or(cfg.ui.performance.render_mode),
         prompt_cache: cfg.prompt_cache.clone(),
     };
 
+    // Chat and ask runs report failures through documented exit codes
+    let mut status = RunStatus::Success;
     match &args.command {
         Some(Commands::ToolPolicy { command }) => {
             vtcode_core::cli::tool_policy_commands::handle_tool_policy_command(command.clone())
@@ -156,23 +290,158 @@ async fn main() -> Result<()> {
             vtcode_core::cli::models_commands::handle_models_command(&args, command).await?;
         }
         Some(Commands::Chat) => {
-            cli::handle_chat_command(&core_cfg, skip_confirmations, args.full_auto).await?;
+            status = cli::handle_chat_command(
+                &core_cfg,
+                skip_confirmations,
+                args.full_auto,
+                args.events_file.as_deref(),
+                &args.fail_on,
+                None,
+                None,
+                None,
+            )
+            .await?;
+        }
+        Some(Commands::Share {
+            listen,
+            allow_input,
+        }) => {
+            status = cli::handle_share_command(
+                &core_cfg,
+                listen,
+                *allow_input,
+                skip_confirmations,
+                args.full_auto,
+                args.events_file.as_deref(),
+            )
+            .await?;
+        }
+        Some(Commands::Resume { session, .. }) => {
+            status = cli::handle_resume_command(
+                &core_cfg,
+                session,
+                skip_confirmations,
+                args.full_auto,
+                args.events_file.as_deref(),
+                &args.fail_on,
+            )
+            .await?;
         }
-        Some(Commands::Ask { prompt }) => {
-            cli::handle_ask_single_command(&core_cfg, prompt).await?;
+        Some(Commands::Ask { prompt, cite, json }) => {
+            let options = cli::AskOptions {
+                cite: *cite,
+                json: *json,
+            };
+            status =
+                cli::handle_ask_single_command(&core_cfg, prompt, options, &args.fail_on).await?;
         }
         Some(Commands::ChatVerbose) => {
             // Reuse chat path; verbose behavior is handled in the module if applicable
-            cli::handle_chat_command(&core_cfg, skip_confirmations, args.full_auto).await?;
+            status = cli::handle_chat_command(
+                &core_cfg,
+                skip_confirmations,
+                args.full_auto,
+                args.events_file.as_deref(),
+                &args.fail_on,
+                None,
+                None,
+                None,
+            )
+            .await?;
         }
-        Some(Commands::Analyze) => {
-            cli::handle_analyze_command(&core_cfg).await?;
+        Some(Commands::Analyze {
+            risk,
+            diff,
+            json,
+            top,
+            churn_days,
+            quiet,
+        }) => {
+            if let Some(range) = diff {
+                cli::handle_diff_analysis_command(&core_cfg, range, *json, *quiet).await?;
+            } else if *risk {
+                cli::handle_risk_map_command(&core_cfg, *top, *churn_days, *quiet).await?;
+            } else {
+                cli::handle_analyze_command(&core_cfg, *quiet).await?;
+            }
+        }
+        Some(Commands::Batch {
+            find,
+            replace,
+            instruction,
+            literal,
+            include,
+            batch_size,
+            validate,
+            no_validate,
+            requests_per_minute,
+            resume,
+            dry_run,
+        }) => {
+            let options = cli::BatchOptions {
+                find: find.clone(),
+                replace: replace.clone(),
+                instruction: instruction.clone(),
+                literal: *literal,
+                include: include.clone(),
+                batch_size: *batch_size,
+                validate: validate.clone(),
+                no_validate: *no_validate,
+                requests_per_minute: *requests_per_minute,
+                resume: resume.clone(),
+                dry_run: *dry_run,
+            };
+            cli::handle_batch_command(&core_cfg, options).await?;
+        }
+        Some(Commands::Review {
+            security,
+            diff,
+            paths,
+            json,
+            output,
+            max_locations,
+        }) => {
+            let options = cli::ReviewOptions {
+                security: *security,
+                diff: diff.clone(),
+                paths: paths.clone(),
+                json: *json,
+                output: output.clone(),
+                max_locations: *max_locations,
+            };
+            cli::handle_review_command(&core_cfg, options).await?;
+        }
+        Some(Commands::Prompts { command }) => {
+            cli::handle_prompts_command(&core_cfg, cfg, command).await?;
+        }
+        Some(Commands::FixCi { run, commit }) => {
+            let options = cli::FixCiOptions {
+                run: run.clone(),
+                commit: *commit,
+            };
+            cli::handle_fix_ci_command(
+                &core_cfg,
+                options,
+                skip_confirmations,
+                args.full_auto,
+                args.events_file.as_deref(),
+            )
+            .await?;
         }
         Some(Commands::Performance) => {
             cli::handle_performance_command().await?;
         }
-        Some(Commands::Trajectory { file, top }) => {
-            cli::handle_trajectory_logs_command(&core_cfg, file.clone(), *top).await?;
+        Some(Commands::Trajectory {
+            file,
+            top,
+            trends,
+            period,
+        }) => {
+            if *trends {
+                cli::handle_trajectory_trends_command(file.clone(), period, *top).await?;
+            } else {
+                cli::handle_trajectory_logs_command(&core_cfg, file.clone(), *top).await?;
+            }
         }
         Some(Commands::CreateProject { name, features }) => {
             cli::handle_create_project_command(&core_cfg, name, features).await?;
@@ -192,7 +461,7 @@ async fn main() -> Result<()> {
         Some(Commands::Init) => {
             cli::handle_init_command(&workspace, false, false).await?;
         }
-        Some(Commands::Config { output, global }) => {
+        Some(Commands::Config { output, global, .. }) => {
             cli::handle_config_command(output.as_deref(), *global).await?;
         }
         Some(Commands::InitProject {
@@ -210,10 +479,23 @@ async fn main() -> Result<()> {
         }
         _ => {
             // Default to chat
-            cli::handle_chat_command(&core_cfg, skip_confirmations, args.full_auto).await?;
+            status = cli::handle_chat_command(
+                &core_cfg,
+                skip_confirmations,
+                args.full_auto,
+                args.events_file.as_deref(),
+                &args.fail_on,
+                None,
+                None,
+                None,
+            )
+            .await?;
         }
     }
 
+    if status != RunStatus::Success {
+        std::process::exit(status.exit_code());
+    }
     Ok(())
 }
 
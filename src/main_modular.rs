@@ -126,11 +126,13 @@ async fn main() -> Result<()> {
         model: model.clone(),
         api_key: api_key.clone(),
         provider: model.provider().to_string(),
+        base_url: vtcode_config.agent.base_url.clone(),
         workspace: workspace.clone(),
         verbose: args.verbose,
         theme: defaults::DEFAULT_THEME.to_string(),
         reasoning_effort: ReasoningEffortLevel::default(),
         ui_surface: vtcode_config.agent.ui_surface,
+        render_mode: vtcode_config.ui.performance.render_mode,
         prompt_cache: PromptCachingConfig::default(),
     };
 
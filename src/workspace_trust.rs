@@ -5,7 +5,7 @@ use std::time::{SystemTime, UNIX_EPOCH};
 use anyhow::{Context, Result};
 use console::{Color, style};
 use vtcode_core::utils::dot_config::{
-    WorkspaceTrustLevel, WorkspaceTrustRecord, get_dot_manager, load_user_config,
+    WorkspaceTrustLevel, WorkspaceTrustRecord, WriteGrantRecord, get_dot_manager, load_user_config,
 };
 
 const WARNING_RGB: (u8, u8, u8) = (166, 51, 51);
@@ -176,10 +176,7 @@ pub fn workspace_trust_level(workspace: &Path) -> Result<Option<WorkspaceTrustLe
 }
 
 fn persist_trust_decision(workspace_key: &str, level: WorkspaceTrustLevel) -> Result<()> {
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs();
+    let timestamp = unix_now();
     let manager = get_dot_manager();
     let guard = manager
         .lock()
@@ -197,6 +194,55 @@ fn persist_trust_decision(workspace_key: &str, level: WorkspaceTrustLevel) -> Re
         .context("Failed to persist workspace trust decision")
 }
 
+/// Unexpired grants to write outside `workspace`.
+pub fn write_grants(workspace: &Path) -> Result<Vec<WriteGrantRecord>> {
+    let workspace_key = canonicalize_workspace(workspace)?;
+    let config =
+        load_user_config().context("Failed to load user configuration for write grants")?;
+    let now = unix_now();
+    Ok(config
+        .workspace_trust
+        .write_grants
+        .get(&workspace_key)
+        .map(|grants| {
+            grants
+                .iter()
+                .filter(|grant| !grant.is_expired(now))
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+/// Record a grant to write outside `workspace`, replacing an earlier grant
+/// for the same path and dropping expired ones.
+pub fn persist_write_grant(workspace: &Path, grant: WriteGrantRecord) -> Result<()> {
+    let workspace_key = canonicalize_workspace(workspace)?;
+    let now = unix_now();
+    let manager = get_dot_manager();
+    let guard = manager
+        .lock()
+        .expect("Workspace trust manager mutex poisoned");
+    guard
+        .update_config(|cfg| {
+            let grants = cfg
+                .workspace_trust
+                .write_grants
+                .entry(workspace_key)
+                .or_default();
+            grants.retain(|existing| existing.path != grant.path && !existing.is_expired(now));
+            grants.push(grant);
+        })
+        .context("Failed to persist write grant")
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
 fn canonicalize_workspace(workspace: &Path) -> Result<String> {
     let canonical = workspace.canonicalize().with_context(|| {
         format!(
@@ -19,7 +19,8 @@ fn test_provider_factory_basic() {
     assert!(providers.contains(&"anthropic".to_string()));
     assert!(providers.contains(&"openrouter".to_string()));
     assert!(providers.contains(&"xai".to_string()));
-    assert_eq!(providers.len(), 5);
+    assert!(providers.contains(&"ollama".to_string()));
+    assert_eq!(providers.len(), 6);
 }
 
 #[test]
@@ -119,6 +120,7 @@ fn test_anthropic_tool_message_handling() {
         content: "Tool result".to_string(),
         tool_calls: None,
         tool_call_id: None,
+        provenance: None,
     };
 
     let request = LLMRequest {
@@ -133,6 +135,10 @@ fn test_anthropic_tool_message_handling() {
         parallel_tool_calls: None,
         parallel_tool_config: None,
         reasoning_effort: None,
+        json_mode: false,
+        response_schema: None,
+        code_execution: false,
+        seed: None,
     };
 
     // Validate request shape instead of internal conversion
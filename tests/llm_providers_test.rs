@@ -21,7 +21,8 @@ fn test_provider_factory_creation() {
     assert!(providers.contains(&"anthropic".to_string()));
     assert!(providers.contains(&"openrouter".to_string()));
     assert!(providers.contains(&"xai".to_string()));
-    assert_eq!(providers.len(), 5);
+    assert!(providers.contains(&"ollama".to_string()));
+    assert_eq!(providers.len(), 6);
 }
 
 #[test]
@@ -260,6 +261,10 @@ fn test_request_validation() {
         parallel_tool_calls: None,
         parallel_tool_config: None,
         reasoning_effort: None,
+        json_mode: false,
+        response_schema: None,
+        code_execution: false,
+        seed: None,
     };
     assert!(gemini.validate_request(&valid_gemini_request).is_ok());
 
@@ -275,6 +280,10 @@ fn test_request_validation() {
         parallel_tool_calls: None,
         parallel_tool_config: None,
         reasoning_effort: None,
+        json_mode: false,
+        response_schema: None,
+        code_execution: false,
+        seed: None,
     };
     assert!(openai.validate_request(&valid_openai_request).is_ok());
 
@@ -290,6 +299,10 @@ fn test_request_validation() {
         parallel_tool_calls: None,
         parallel_tool_config: None,
         reasoning_effort: None,
+        json_mode: false,
+        response_schema: None,
+        code_execution: false,
+        seed: None,
     };
     assert!(anthropic.validate_request(&valid_anthropic_request).is_ok());
 
@@ -305,6 +318,10 @@ fn test_request_validation() {
         parallel_tool_calls: None,
         parallel_tool_config: None,
         reasoning_effort: None,
+        json_mode: false,
+        response_schema: None,
+        code_execution: false,
+        seed: None,
     };
     assert!(
         openrouter
@@ -324,6 +341,10 @@ fn test_request_validation() {
         parallel_tool_calls: None,
         parallel_tool_config: None,
         reasoning_effort: None,
+        json_mode: false,
+        response_schema: None,
+        code_execution: false,
+        seed: None,
     };
     assert!(xai.validate_request(&valid_xai_request).is_ok());
 
@@ -340,6 +361,10 @@ fn test_request_validation() {
         parallel_tool_calls: None,
         parallel_tool_config: None,
         reasoning_effort: None,
+        json_mode: false,
+        response_schema: None,
+        code_execution: false,
+        seed: None,
     };
     assert!(gemini.validate_request(&invalid_request).is_err());
     assert!(openai.validate_request(&invalid_request).is_err());
@@ -357,6 +382,7 @@ fn test_anthropic_tool_message_handling() {
         content: "Tool result content".to_string(),
         tool_calls: None,
         tool_call_id: Some("tool_123".to_string()),
+        provenance: None,
     };
 
     let request = LLMRequest {
@@ -371,6 +397,10 @@ fn test_anthropic_tool_message_handling() {
         parallel_tool_calls: None,
         parallel_tool_config: None,
         reasoning_effort: None,
+        json_mode: false,
+        response_schema: None,
+        code_execution: false,
+        seed: None,
     };
 
     // Use the public validator as a proxy for ensuring request shape is acceptable
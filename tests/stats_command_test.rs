@@ -6,7 +6,7 @@ use vtcode_core::{
     config::ReasoningEffortLevel,
     config::constants::models::google::GEMINI_2_5_FLASH_PREVIEW,
     config::core::PromptCachingConfig,
-    config::types::{AgentConfig, UiSurfacePreference},
+    config::types::{AgentConfig, RenderMode, UiSurfacePreference},
     handle_stats_command,
     ui::theme::DEFAULT_THEME_ID,
 };
@@ -19,11 +19,13 @@ async fn test_handle_stats_command_returns_agent_metrics() -> Result<()> {
         model: GEMINI_2_5_FLASH_PREVIEW.to_string(),
         api_key: "test_key".to_string(),
         provider: "gemini".to_string(),
+        base_url: None,
         workspace: temp_dir.path().to_path_buf(),
         verbose: false,
         theme: DEFAULT_THEME_ID.to_string(),
         reasoning_effort: ReasoningEffortLevel::default(),
         ui_surface: UiSurfacePreference::default(),
+        render_mode: RenderMode::default(),
         prompt_cache: PromptCachingConfig::default(),
     };
     let mut agent = Agent::new(config)?;
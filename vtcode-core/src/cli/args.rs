@@ -1,9 +1,13 @@
 //! CLI argument parsing and configuration
 
+use crate::config::model_pin::PinPolicy;
 use crate::config::models::ModelId;
-use clap::{ColorChoice, Parser, Subcommand, ValueHint};
+use crate::config::types::RenderMode;
+use crate::core::run_events::FailOn;
+use clap::{ArgGroup, ColorChoice, Parser, Subcommand, ValueHint};
 use colorchoice_clap::Color as ColorSelection;
 use std::path::PathBuf;
+use std::time::Duration;
 
 /// Main CLI structure for vtcode with advanced features
 #[derive(Parser, Debug)]
@@ -56,6 +60,20 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub provider: Option<String>,
 
+    /// **Keep the workspace's pinned model**
+    ///
+    /// When `[agent.pin]` is enabled in the workspace vtcode.toml, use the
+    /// pinned provider and model even if `--provider` or `--model` differ
+    #[arg(long, global = true, conflicts_with = "override_pin")]
+    pub respect_pin: bool,
+
+    /// **Use `--provider`/`--model` instead of the workspace's pinned model**
+    ///
+    /// Without this flag or `--respect-pin`, a model that differs from the
+    /// workspace pin is refused
+    #[arg(long, global = true)]
+    pub override_pin: bool,
+
     /// **API key environment variable**\n\n**Auto-detects based on provider:**\n• Gemini: `GEMINI_API_KEY`\n• OpenAI: `OPENAI_API_KEY`\n• Anthropic: `ANTHROPIC_API_KEY`\n• DeepSeek: `DEEPSEEK_API_KEY`\n• OpenRouter: `OPENROUTER_API_KEY`\n• xAI: `XAI_API_KEY`\n\n**Override:** --api-key-env CUSTOM_KEY
     #[arg(long, global = true, default_value = crate::config::constants::defaults::DEFAULT_API_KEY_ENV)]
     pub api_key_env: String,
@@ -122,6 +140,13 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub show_file_diffs: bool,
 
+    /// **Chat UI render mode** (auto, full, minimal)
+    ///
+    /// minimal paces redraws and drops spinners for high-latency SSH links;
+    /// auto picks it over SSH. Overrides `[ui.performance] render_mode`.
+    #[arg(long = "render", global = true, value_name = "MODE", value_parser = parse_render_mode)]
+    pub render: Option<RenderMode>,
+
     /// **Maximum concurrent async operations**
     ///
     /// Default: 5
@@ -197,6 +222,30 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub full_auto: bool,
 
+    /// **Write JSONL progress events to a file**
+    ///
+    /// One JSON object per line: turn_started, tool_call, tool_result,
+    /// file_changed, turn_completed and run_finished (with the exit status).
+    /// Use `-` to stream the events to stderr.
+    /// Useful for: monitoring and gating full-auto runs in CI
+    #[arg(long, global = true, value_name = "PATH")]
+    pub events_file: Option<PathBuf>,
+
+    /// **Fail the run on edits or warnings**
+    ///
+    /// `edits`: exit with status 6 when any file changed, for review-only runs.
+    /// `warnings`: exit with status 6 when a tool call failed or a cited
+    /// answer kept unresolved citations. Repeat or separate with commas.
+    /// Applies to `ask`, full-auto and other chat runs
+    #[arg(
+        long = "fail-on",
+        global = true,
+        value_name = "CONDITION",
+        value_delimiter = ',',
+        value_parser = parse_fail_on
+    )]
+    pub fail_on: Vec<FailOn>,
+
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
@@ -221,8 +270,24 @@ pub enum Commands {
     ///   • Code explanations
     ///   • Simple queries
     ///
-    /// Example: vtcode ask "Explain Rust ownership"
-    Ask { prompt: String },
+    /// With --cite, the question is answered from workspace excerpts and every
+    /// claim cites a file and line range that is checked to exist.
+    ///
+    /// Examples:
+    ///   vtcode ask "Explain Rust ownership"
+    ///   vtcode ask --cite "How are tool calls retried?"
+    ///   vtcode ask --cite --json "Where is the config loaded?"
+    Ask {
+        prompt: String,
+
+        /// Answer from the workspace with citations validated against its files
+        #[arg(long)]
+        cite: bool,
+
+        /// Emit the cited answer as JSON (requires --cite)
+        #[arg(long, requires = "cite")]
+        json: bool,
+    },
 
     /// **Verbose interactive chat** with enhanced transparency
     ///
@@ -242,9 +307,159 @@ pub enum Commands {
     ///   • Code complexity metrics
     ///   • Dependency insights
     ///   • Symbol extraction
+    ///   • Risk map (--risk): files ranked by churn, size, complexity and TODOs
+    ///   • Differential analysis (--diff): symbols changed, complexity delta,
+    ///     new TODOs and dependency changes between two git refs
+    ///   • Progress with an ETA on stderr; --quiet for scripts
     ///
     /// Usage: vtcode analyze
-    Analyze,
+    ///        vtcode analyze --risk --top 30
+    ///        vtcode analyze --diff main..HEAD --json
+    Analyze {
+        /// Rank files by change risk and store the map for chat sessions
+        #[arg(long)]
+        risk: bool,
+
+        /// Analyze only what changed in a git range (BASE..HEAD, BASE...HEAD or BASE)
+        #[arg(long, value_name = "RANGE", conflicts_with = "risk")]
+        diff: Option<String>,
+
+        /// Print the --diff report as JSON instead of Markdown
+        #[arg(long, requires = "diff")]
+        json: bool,
+
+        /// Number of files shown in the risk table
+        #[arg(long, default_value_t = 20)]
+        top: usize,
+
+        /// Days of git history counted as churn
+        #[arg(long, default_value_t = 180)]
+        churn_days: u32,
+
+        /// Print only the results, without progress or phase timings
+        #[arg(short, long)]
+        quiet: bool,
+    },
+
+    /// **Batch refactor** - apply one mechanical change across many files
+    ///
+    /// Features:
+    ///   • Plans the change set before touching any file
+    ///   • Applies edits in batches, rolling a batch back if validation fails
+    ///   • Rate-limits model requests in --instruction mode
+    ///   • Resumes after interruption with --resume
+    ///
+    /// Examples:
+    ///   vtcode batch --find old_key --replace new_key --literal --include '**/*.toml'
+    ///   vtcode batch --find 'use_legacy\(' --instruction "Migrate to use_modern()" --validate "cargo check"
+    ///   vtcode batch --resume
+    Batch {
+        /// Pattern selecting files and matches (regular expression unless --literal)
+        #[arg(long, required_unless_present = "resume")]
+        find: Option<String>,
+
+        /// Replacement text; regex captures like $1 are expanded unless --literal
+        #[arg(long, conflicts_with = "instruction")]
+        replace: Option<String>,
+
+        /// Instruction for the model to apply to each matching file
+        #[arg(long)]
+        instruction: Option<String>,
+
+        /// Treat --find and --replace as literal text
+        #[arg(long)]
+        literal: bool,
+
+        /// Glob limiting which files are considered (repeatable)
+        #[arg(long = "include")]
+        include: Vec<String>,
+
+        /// Number of files applied per batch
+        #[arg(long, default_value_t = 20)]
+        batch_size: usize,
+
+        /// Command run after each batch; a failure rolls the batch back (overrides the stored command on --resume).
+        /// Defaults to the project's build or test command from its Makefile, justfile, package.json or CI
+        #[arg(long)]
+        validate: Option<String>,
+
+        /// Do not validate batches with the project's own build or test command
+        #[arg(long, conflicts_with = "validate")]
+        no_validate: bool,
+
+        /// Maximum model requests per minute in --instruction mode
+        #[arg(long, default_value_t = 30)]
+        requests_per_minute: usize,
+
+        /// Resume a stored plan by id (defaults to the latest unfinished plan)
+        #[arg(long, num_args = 0..=1, default_missing_value = "latest")]
+        resume: Option<String>,
+
+        /// Print the plan without changing any files
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// **Review code changes** or run a targeted security review
+    ///
+    /// Modes:
+    ///   • default - review the uncommitted git diff
+    ///   • --diff - review the changes in a git range, with the `analyze --diff`
+    ///     report as context
+    ///   • --security - collect input, auth, crypto and secret handling code and
+    ///     report findings with CWE tags, severity and remediation
+    ///
+    /// Examples:
+    ///   vtcode review
+    ///   vtcode review --diff main..HEAD
+    ///   vtcode review --security
+    ///   vtcode review --security src/api --json --output security.json
+    Review {
+        /// Run the security-focused review instead of the general diff review
+        #[arg(long)]
+        security: bool,
+
+        /// Review the changes in a git range instead of the uncommitted diff
+        #[arg(long, value_name = "RANGE", conflicts_with = "security")]
+        diff: Option<String>,
+
+        /// Files or directories to review (security mode; defaults to the workspace)
+        paths: Vec<std::path::PathBuf>,
+
+        /// Emit the security report as JSON instead of Markdown
+        #[arg(long)]
+        json: bool,
+
+        /// Write the report to a file instead of stdout
+        #[arg(long)]
+        output: Option<std::path::PathBuf>,
+
+        /// Maximum code locations reviewed per security category
+        #[arg(long, default_value_t = 25)]
+        max_locations: usize,
+    },
+
+    /// **Fix a failing CI run** with a focused agent session
+    ///
+    /// Features:
+    ///   • Reads the failed steps of a GitHub Actions run through `gh`, or a saved log
+    ///   • Classifies compile errors, lints, failing tests and formatting diffs
+    ///   • Maps runner paths to workspace files and starts the agent on them
+    ///   • Summarizes the changes and offers to commit them
+    ///
+    /// Examples:
+    ///   vtcode fix-ci --run https://github.com/owner/repo/actions/runs/123456789
+    ///   vtcode fix-ci --run 123456789 --commit
+    ///   vtcode fix-ci --run ci.log
+    FixCi {
+        /// GitHub Actions run URL or id, or the path of a saved CI log
+        #[arg(long, value_name = "URL|ID|PATH")]
+        run: String,
+
+        /// Commit the changes without asking once the session ends
+        #[arg(long)]
+        commit: bool,
+    },
 
     /// **Display performance metrics** and system status\n\n**Shows:**\n• Token usage and API costs\n• Response times and latency\n• Tool execution statistics\n• Memory usage patterns\n\n**Usage:** vtcode performance
     Performance,
@@ -256,12 +471,18 @@ pub enum Commands {
     /// Options:
     ///   • --file to specify an alternate path
     ///   • --top to limit report rows (default: 10)
+    ///   • --trends to show session trends and regressions instead
+    ///   • --period to group trends by day, week or month (default: week)
     ///
     /// Shows:
     ///   • Class distribution with percentages
     ///   • Model usage statistics
     ///   • Tool success rates with status indicators
     ///   • Time range of logged activity
+    ///
+    /// With --trends:
+    ///   • Turns, reverts, tokens and tool error rate per period
+    ///   • Regressions after model or configuration changes
     #[command(name = "trajectory")]
     Trajectory {
         /// Optional path to trajectory JSONL file
@@ -270,6 +491,31 @@ pub enum Commands {
         /// Number of top entries to show for each section
         #[arg(long, default_value_t = 10)]
         top: usize,
+        /// Show per-session trends over time and flag regressions
+        #[arg(long)]
+        trends: bool,
+        /// Trend period: day, week or month
+        #[arg(long, default_value = "week", requires = "trends")]
+        period: String,
+    },
+
+    /// **Usage statistics across sessions**
+    ///
+    /// Features:
+    ///   • --spend: tokens and estimated cost per day and provider
+    ///   • Budget usage against the [llm.spend] limits
+    ///
+    /// Examples:
+    ///   vtcode stats --spend
+    ///   vtcode stats --spend --days 30
+    #[command(group(ArgGroup::new("view").required(true).args(["spend"])))]
+    Stats {
+        /// Show token spend and estimated cost
+        #[arg(long)]
+        spend: bool,
+        /// Number of most recent days to show
+        #[arg(long, default_value_t = 7)]
+        days: usize,
     },
 
     /// **Benchmark against SWE-bench evaluation framework**
@@ -416,6 +662,7 @@ pub enum Commands {
     ///   vtcode config
     ///   vtcode config --output ./custom-config.toml
     ///   vtcode config --global
+    ///   vtcode config edit
     Config {
         /// Output file path - where to save the configuration file
         #[arg(long)]
@@ -424,6 +671,159 @@ pub enum Commands {
         /// Create in user home directory - creates ~/.vtcode/vtcode.toml
         #[arg(long)]
         global: bool,
+
+        #[command(subcommand)]
+        command: Option<ConfigCommands>,
+    },
+
+    /// **Sign in to providers and gateways** - OAuth device flow login
+    ///
+    /// Features:
+    ///   • Device code sign-in for providers with [credentials.<provider>.oauth]
+    ///   • Tokens stored in ~/.vtcode/auth and refreshed automatically
+    ///
+    /// Examples:
+    ///   vtcode auth login --provider openai
+    ///   vtcode auth status
+    ///   vtcode auth logout --provider openai
+    Auth {
+        #[command(subcommand)]
+        command: AuthCommands,
+    },
+
+    /// **Work with archived chat sessions**
+    ///
+    /// Features:
+    ///   • List recent sessions by title
+    ///   • Redact secrets, emails, paths and custom patterns before sharing
+    ///   • Stable placeholders keep references consistent across the transcript
+    ///
+    /// Examples:
+    ///   vtcode sessions list
+    ///   vtcode session info latest
+    ///   vtcode session redact latest
+    ///   vtcode session redact session-myapp-20250925T101530Z --pattern 'ACME-\d+'
+    #[command(alias = "sessions")]
+    Session {
+        #[command(subcommand)]
+        command: SessionCommands,
+    },
+
+    /// **Collect a sanitized bundle to attach to a bug report**
+    ///
+    /// Features:
+    ///   • Environment manifest, redacted configuration and read-only health checks
+    ///   • The last turns of a session and the tail of recent logs, redacted
+    ///   • Written as one zip file ready to attach to a GitHub issue
+    ///
+    /// Examples:
+    ///   vtcode bug-report
+    ///   vtcode bug-report --session session-myapp-20250925T101530Z --turns 10
+    BugReport {
+        /// Session to include: an identifier, a unique prefix of one, or "latest"
+        #[arg(long, default_value = "latest")]
+        session: String,
+
+        /// Number of turns of the session to include
+        #[arg(long, default_value_t = 5)]
+        turns: usize,
+
+        /// Where to write the bundle (default: vtcode-bug-report-<timestamp>.zip)
+        #[arg(short, long, value_hint = ValueHint::FilePath)]
+        output: Option<std::path::PathBuf>,
+    },
+
+    /// **Start a chat session others can watch live**
+    ///
+    /// Features:
+    ///   • Serves a read-only view of the transcript as a web page
+    ///   • Updates stream to viewers as the session progresses
+    ///   • Every request needs the random token in the printed link
+    ///   • Viewers can only send prompts with --allow-input
+    ///
+    /// Examples:
+    ///   vtcode share
+    ///   vtcode share --listen 0.0.0.0:7878
+    Share {
+        /// Address to serve the view on; use 0.0.0.0:<port> to reach it from the network
+        #[arg(long, default_value = "127.0.0.1:7878")]
+        listen: String,
+
+        /// Let viewers submit prompts to the session
+        #[arg(long)]
+        allow_input: bool,
+    },
+
+    /// **Continue a chat session saved in this workspace**
+    ///
+    /// Features:
+    ///   • Chat sessions are saved to .vtcode/sessions after every turn
+    ///   • Restores the conversation, tool results and decision ledger
+    ///   • Keeps saving to the same session as it continues
+    ///
+    /// Examples:
+    ///   vtcode resume
+    ///   vtcode resume 20261018T0912
+    ///   vtcode resume --list
+    Resume {
+        /// Session to continue: an identifier, a unique prefix of one, or "latest"
+        #[arg(default_value = "latest")]
+        session: String,
+
+        /// List saved sessions instead of resuming one
+        #[arg(long)]
+        list: bool,
+    },
+
+    /// **Compare agent behavior across system prompt changes**
+    ///
+    /// Features:
+    ///   • Replays each turn of an archived session with a modified prompt template
+    ///   • Recorded tool outputs stand in for tools, which never run
+    ///   • Model responses are cached in .vtcode/replay, so reruns are deterministic
+    ///   • Side-by-side diff of agent output and tool call sequences per turn
+    ///
+    /// Examples:
+    ///   vtcode prompts diff latest --prompt prompts/system.md
+    ///   vtcode prompts diff session-myapp-20250925T101530Z --prompt new.md --turn 3
+    Prompts {
+        #[command(subcommand)]
+        command: PromptsCommands,
+    },
+
+    /// **Keep workspace state warm in the background** - faster chat startup
+    ///
+    /// Features:
+    ///   • Keeps the language summary, provider reachability and symbol index fresh
+    ///   • Chat sessions read them over a unix socket instead of rebuilding them
+    ///   • `run` stays in the foreground for launchd, systemd or brew services
+    ///
+    /// Examples:
+    ///   vtcode daemon start
+    ///   vtcode daemon status
+    ///   vtcode daemon stop
+    Daemon {
+        #[command(subcommand)]
+        command: DaemonCommands,
+    },
+
+    /// **Check and repair the ~/.vtcode dot folder**
+    ///
+    /// Features:
+    ///   • Validates config, trust store, tool policies, caches and sessions
+    ///   • Backs up and rebuilds unreadable settings, keeping what still parses
+    ///   • Deletes corrupt cache entries, orphaned redacted copies and stale sockets
+    ///   • Optional retention cleanup of old sessions, caches, logs and backups
+    ///   • Compacts sessions older than `[sessions] compact_after_days` into
+    ///     the searchable archive
+    ///
+    /// Examples:
+    ///   vtcode maintenance
+    ///   vtcode maintenance --prune-older-than 30d
+    Maintenance {
+        /// Also delete sessions, cache entries, logs and backups older than this (e.g. 30d, 12h)
+        #[arg(long, value_name = "AGE", value_parser = humantime::parse_duration)]
+        prune_older_than: Option<Duration>,
     },
 
     /// **Manage tool execution policies** - control which tools the agent can use
@@ -468,6 +868,118 @@ pub enum Commands {
     },
 }
 
+/// Configuration file commands
+#[derive(Subcommand, Debug, Clone)]
+pub enum ConfigCommands {
+    /// Edit vtcode.toml in an interactive form, keeping comments intact
+    Edit {
+        /// Edit ~/.vtcode/vtcode.toml instead of the workspace configuration
+        #[arg(long)]
+        global: bool,
+    },
+}
+
+/// OAuth sign-in commands
+#[derive(Subcommand, Debug, Clone)]
+pub enum AuthCommands {
+    /// Sign in with the provider's device flow and store the tokens
+    Login {
+        /// Provider whose [credentials.<provider>.oauth] settings to use
+        #[arg(long)]
+        provider: String,
+    },
+
+    /// Delete the stored tokens for a provider
+    Logout {
+        #[arg(long)]
+        provider: String,
+    },
+
+    /// Show sign-in state for every provider with OAuth settings
+    Status,
+}
+
+/// Archived session commands
+#[derive(Subcommand, Debug, Clone)]
+pub enum SessionCommands {
+    /// List recent sessions with their titles, newest first
+    List {
+        /// Maximum number of sessions to show
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+    },
+
+    /// Show a session's details and the environment it ran in
+    Info {
+        /// Session identifier from /sessions, a unique prefix of one, or "latest"
+        id: String,
+    },
+
+    /// Search compacted sessions in the archive by title, summary, workspace, model or tool
+    Search {
+        /// Words that must all appear, case-insensitively
+        #[arg(required = true)]
+        query: Vec<String>,
+
+        /// Maximum number of sessions to show
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+    },
+
+    /// Write a copy of a session with sensitive content replaced by placeholders
+    Redact {
+        /// Session identifier from /sessions, a unique prefix of one, or "latest"
+        id: String,
+
+        /// Additional regular expression to redact (repeatable)
+        #[arg(long = "pattern", value_name = "REGEX")]
+        patterns: Vec<String>,
+
+        /// Where to write the redacted copy (default: ~/.vtcode/sessions/redacted/<id>.json)
+        #[arg(short, long, value_hint = ValueHint::FilePath)]
+        output: Option<std::path::PathBuf>,
+    },
+}
+
+/// Prompt engineering commands
+#[derive(Subcommand, Debug, Clone)]
+pub enum PromptsCommands {
+    /// Replay an archived session with a modified system prompt and diff the results
+    Diff {
+        /// Session identifier from /sessions, a unique prefix of one, or "latest"
+        session: String,
+
+        /// Prompt template used in place of prompts/system.md for the replay
+        #[arg(long, value_hint = ValueHint::FilePath)]
+        prompt: std::path::PathBuf,
+
+        /// Only replay this turn (1-based, repeatable)
+        #[arg(long = "turn", value_name = "N")]
+        turns: Vec<usize>,
+
+        /// Model requests allowed per replayed turn
+        #[arg(long, default_value_t = 10)]
+        max_steps: usize,
+
+        /// Send every request to the model instead of using cached responses
+        #[arg(long)]
+        no_cache: bool,
+    },
+}
+
+/// Background daemon commands
+#[derive(Subcommand, Debug, Clone)]
+pub enum DaemonCommands {
+    /// Start a daemon for the workspace in the background
+    Start,
+    /// Serve the workspace in the foreground until stopped
+    Run,
+    /// Stop the workspace daemon
+    Stop,
+    /// Show whether a daemon serves the workspace and what it has cached
+    Status,
+}
+
 /// Model management commands with concise, actionable help
 #[derive(Subcommand, Debug)]
 pub enum ModelCommands {
@@ -605,6 +1117,8 @@ impl Default for Cli {
             workspace_path: None,
             model: Some(ModelId::default().as_str().to_string()),
             provider: Some("gemini".to_string()),
+            respect_pin: false,
+            override_pin: false,
             api_key_env: "GEMINI_API_KEY".to_string(),
             workspace: None,
             enable_tree_sitter: false,
@@ -612,6 +1126,7 @@ impl Default for Cli {
             research_preview: false,
             security_level: "moderate".to_string(),
             show_file_diffs: false,
+            render: None,
             max_concurrent_ops: 5,
             api_rate_limit: 30,
             max_tool_calls: 10,
@@ -622,6 +1137,8 @@ impl Default for Cli {
             theme: None,
             skip_confirmations: false,
             full_auto: false,
+            events_file: None,
+            fail_on: Vec::new(),
             debug: false,
             command: Some(Commands::Chat),
         }
@@ -629,6 +1146,17 @@ impl Default for Cli {
 }
 
 impl Cli {
+    /// How `--provider`/`--model` treat a workspace model pin
+    pub fn pin_policy(&self) -> PinPolicy {
+        if self.override_pin {
+            PinPolicy::Override
+        } else if self.respect_pin {
+            PinPolicy::Respect
+        } else {
+            PinPolicy::Strict
+        }
+    }
+
     /// Get the model to use, with fallback to default
     pub fn get_model(&self) -> String {
         self.model
@@ -820,3 +1348,17 @@ impl Cli {
         self.debug || self.verbose
     }
 }
+
+fn parse_fail_on(value: &str) -> Result<FailOn, String> {
+    FailOn::parse(value)
+        .ok_or_else(|| format!("expected one of: {}", FailOn::allowed_values().join(", ")))
+}
+
+fn parse_render_mode(value: &str) -> Result<RenderMode, String> {
+    RenderMode::parse(value).ok_or_else(|| {
+        format!(
+            "expected one of: {}",
+            RenderMode::allowed_values().join(", ")
+        )
+    })
+}
@@ -243,6 +243,13 @@ impl ManPageGenerator {
                 bold("OPTIONS"),
                 roman("] "),
                 bold("analyze"),
+                roman(" ["),
+                bold("--risk"),
+                roman("] ["),
+                bold("--top"),
+                roman(" N] ["),
+                bold("--churn-days"),
+                roman(" DAYS]"),
             ])
             .control("SH", ["DESCRIPTION"])
             .text([
@@ -271,9 +278,16 @@ impl ManPageGenerator {
             .control("TP", [])
             .text([bold("Symbol Extraction")])
             .text([roman("Functions, classes, and other code symbols")])
+            .control("TP", [])
+            .text([bold("Risk Map")])
+            .text([roman(
+                "With --risk, ranks files by git churn, size, control-flow complexity and TODO density, and saves the map to .vtcode/risk_map.json so chat sessions preview diffs before editing high-risk files",
+            )])
             .control("SH", ["EXAMPLES"])
             .text([roman("Analyze current workspace:")])
             .text([bold("  vtcode analyze")])
+            .text([roman("Show the 30 riskiest files:")])
+            .text([bold("  vtcode analyze --risk --top 30")])
             .control("SH", ["SEE ALSO"])
             .text([bold("vtcode(1)"), roman(", "), bold("vtcode-chat(1)")])
             .render();
@@ -126,6 +126,12 @@ fn is_provider_configured(config: &DotConfig, provider: &str) -> bool {
             .as_ref()
             .map(|p| p.enabled)
             .unwrap_or(false),
+        "ollama" => config
+            .providers
+            .ollama
+            .as_ref()
+            .map(|p| p.enabled)
+            .unwrap_or(false),
         _ => false,
     }
 }
@@ -188,6 +194,7 @@ async fn handle_config_provider(
         "openai" | "anthropic" | "gemini" | "openrouter" => {
             configure_standard_provider(&mut config, provider, api_key, model)?;
         }
+        "ollama" => configure_local_provider(&mut config, api_key, base_url, model),
         _ => return Err(anyhow!("Unsupported provider: {}", provider)),
     }
 
@@ -241,6 +248,26 @@ fn configure_standard_provider(
     Ok(())
 }
 
+/// Configure a provider served on the local machine, which needs no API key
+fn configure_local_provider(
+    config: &mut DotConfig,
+    api_key: Option<&str>,
+    base_url: Option<&str>,
+    model: Option<&str>,
+) {
+    let provider_config = config.providers.ollama.get_or_insert_with(Default::default);
+    if let Some(key) = api_key {
+        provider_config.api_key = Some(key.to_string());
+    }
+    if let Some(url) = base_url {
+        provider_config.base_url = Some(url.to_string());
+    }
+    if let Some(m) = model {
+        provider_config.model = Some(m.to_string());
+    }
+    provider_config.enabled = true;
+}
+
 /// Test provider connectivity
 async fn handle_test_provider(_cli: &Cli, provider: &str) -> Result<()> {
     println!("{} Testing {}...", "🔍".blue(), provider.bold());
@@ -257,6 +284,7 @@ async fn handle_test_provider(_cli: &Cli, provider: &str) -> Result<()> {
             content: "Respond with 'OK' if you receive this message.".to_string(),
             tool_calls: None,
             tool_call_id: None,
+            provenance: None,
         }],
         system_prompt: None,
         tools: None,
@@ -268,6 +296,10 @@ async fn handle_test_provider(_cli: &Cli, provider: &str) -> Result<()> {
         parallel_tool_calls: None,
         parallel_tool_config: None,
         reasoning_effort: None,
+        json_mode: false,
+        response_schema: None,
+        code_execution: false,
+        seed: None,
     };
 
     match provider_instance.generate(test_request).await {
@@ -316,6 +348,7 @@ fn get_provider_credentials(
         "gemini" => Ok(get_config(config.providers.gemini.as_ref())),
         "openrouter" => Ok(get_config(config.providers.openrouter.as_ref())),
         "xai" => Ok(get_config(config.providers.xai.as_ref())),
+        "ollama" => Ok(get_config(config.providers.ollama.as_ref())),
         _ => Err(anyhow!("Unknown provider: {}", provider)),
     }
 }
@@ -0,0 +1,191 @@
+//! Parsing and validation of cited answers
+
+use super::retrieval::numbered_lines;
+use crate::utils::code_fence::strip_code_fence;
+use crate::utils::workspace_path::resolve_in_workspace;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::path::Path;
+
+/// Longest excerpt kept with a citation; longer ranges are still valid.
+const MAX_CITED_LINES: usize = 40;
+
+/// A citation that resolves to existing lines of a workspace file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Citation {
+    /// Marker number used in the answer text, e.g. `[1]`
+    pub id: usize,
+    /// Path relative to the workspace root
+    pub file: String,
+    /// 1-based first line
+    pub start_line: usize,
+    /// 1-based last line, inclusive
+    pub end_line: usize,
+    /// `file://` URI of the file with a `#L<start>-L<end>` fragment
+    pub uri: String,
+    /// The cited lines, numbered
+    pub snippet: String,
+}
+
+impl Citation {
+    /// `file:start-end`, or `file:line` for a single line.
+    pub fn location(&self) -> String {
+        if self.start_line == self.end_line {
+            format!("{}:{}", self.file, self.start_line)
+        } else {
+            format!("{}:{}-{}", self.file, self.start_line, self.end_line)
+        }
+    }
+}
+
+/// A citation from the model that does not point at real lines.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RejectedCitation {
+    pub id: usize,
+    pub file: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Why the citation could not be resolved
+    pub reason: String,
+}
+
+/// An answer whose citations have been checked against the workspace.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CitedAnswer {
+    pub question: String,
+    pub answer: String,
+    pub citations: Vec<Citation>,
+    /// Citations that were dropped, with the reason
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rejected: Vec<RejectedCitation>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawAnswer {
+    answer: String,
+    #[serde(default)]
+    citations: Vec<RawCitation>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawCitation {
+    id: usize,
+    file: String,
+    start_line: usize,
+    #[serde(default)]
+    end_line: Option<usize>,
+}
+
+/// Parse the model response and resolve its citations under `workspace`.
+pub fn parse_cited_answer(workspace: &Path, question: &str, response: &str) -> Result<CitedAnswer> {
+    let body = strip_code_fence(response);
+    let raw: RawAnswer = serde_json::from_str(body.trim()).context("invalid cited answer JSON")?;
+
+    let mut answer = CitedAnswer {
+        question: question.to_string(),
+        answer: raw.answer,
+        ..Default::default()
+    };
+    for citation in raw.citations {
+        let end_line = citation.end_line.unwrap_or(citation.start_line);
+        match resolve_citation(workspace, &citation.file, citation.start_line, end_line) {
+            Ok((file, uri, snippet)) => answer.citations.push(Citation {
+                id: citation.id,
+                file,
+                start_line: citation.start_line,
+                end_line,
+                uri,
+                snippet,
+            }),
+            Err(reason) => answer.rejected.push(RejectedCitation {
+                id: citation.id,
+                file: citation.file,
+                start_line: citation.start_line,
+                end_line,
+                reason,
+            }),
+        }
+    }
+    answer.citations.sort_by_key(|citation| citation.id);
+    Ok(answer)
+}
+
+/// The workspace-relative path, URI and numbered lines of a citation.
+fn resolve_citation(
+    workspace: &Path,
+    file: &str,
+    start_line: usize,
+    end_line: usize,
+) -> std::result::Result<(String, String, String), String> {
+    let path = resolve_in_workspace(workspace, file).map_err(|err| err.to_string())?;
+    if !path.is_file() {
+        return Err("file does not exist".to_string());
+    }
+    let source = std::fs::read_to_string(&path).map_err(|err| err.to_string())?;
+    let lines: Vec<&str> = source.lines().collect();
+    if start_line == 0 || end_line < start_line {
+        return Err(format!("invalid line range {}-{}", start_line, end_line));
+    }
+    if end_line > lines.len() {
+        return Err(format!("file has {} lines", lines.len()));
+    }
+
+    let root = resolve_in_workspace(workspace, "").map_err(|err| err.to_string())?;
+    let relative = path
+        .strip_prefix(&root)
+        .unwrap_or(&path)
+        .to_string_lossy()
+        .into_owned();
+    let uri = format!("file://{}#L{}-L{}", path.display(), start_line, end_line);
+    let snippet_end = end_line.min(start_line + MAX_CITED_LINES - 1);
+    Ok((
+        relative,
+        uri,
+        numbered_lines(&lines, start_line, snippet_end),
+    ))
+}
+
+impl CitedAnswer {
+    /// Whether the answer cites something and every citation resolved.
+    pub fn is_verified(&self) -> bool {
+        !self.citations.is_empty() && self.rejected.is_empty()
+    }
+
+    /// Answer text followed by its sources. With `hyperlinks`, each source is
+    /// an OSC 8 link to the file so terminals can open it.
+    pub fn render(&self, hyperlinks: bool) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "{}", self.answer.trim_end());
+        if !self.citations.is_empty() {
+            out.push_str("\nSources:\n");
+            for citation in &self.citations {
+                let location = citation.location();
+                if hyperlinks {
+                    let _ = writeln!(
+                        out,
+                        "  [{}] \x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\",
+                        citation.id, citation.uri, location
+                    );
+                } else {
+                    let _ = writeln!(out, "  [{}] {}", citation.id, location);
+                }
+            }
+        }
+        if !self.rejected.is_empty() {
+            out.push_str("\nUnverified citations:\n");
+            for rejected in &self.rejected {
+                let _ = writeln!(
+                    out,
+                    "  [{}] {}:{}-{} ({})",
+                    rejected.id,
+                    rejected.file,
+                    rejected.start_line,
+                    rejected.end_line,
+                    rejected.reason
+                );
+            }
+        }
+        out
+    }
+}
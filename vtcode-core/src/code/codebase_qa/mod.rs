@@ -0,0 +1,96 @@
+//! Codebase questions answered with verified citations for `vtcode ask --cite`
+//!
+//! Excerpts that mention the terms of the question are collected from the
+//! workspace with numbered lines. The model answers from them and cites each
+//! claim as a file and line range. Every citation is then resolved against the
+//! workspace, and ranges that don't exist are rejected, so the answer that
+//! reaches the user can be checked line by line.
+
+pub mod citations;
+pub mod prompts;
+pub mod retrieval;
+
+pub use citations::{Citation, CitedAnswer, RejectedCitation, parse_cited_answer};
+pub use prompts::{CITED_ANSWER_SYSTEM_PROMPT, citation_feedback, cited_answer_prompt};
+pub use retrieval::{Excerpt, ExcerptCollector, RetrievalOptions, question_terms};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn collects_excerpts_around_question_terms() {
+        let collector = ExcerptCollector::new(
+            "/work",
+            RetrievalOptions {
+                context_lines: 1,
+                ..RetrievalOptions::default()
+            },
+        );
+        let terms = question_terms("How does the retry backoff work?");
+        assert_eq!(terms, vec!["backoff", "retry"]);
+
+        let source = "fn a() {}\n\nfn retry() {\n    sleep(backoff);\n}\n\n\n\nfn b() {}\n";
+        let excerpts = collector.scan_file(Path::new("/work/src/net.rs"), source, &terms);
+        assert_eq!(excerpts.len(), 1);
+        assert_eq!(excerpts[0].path, Path::new("src/net.rs"));
+        assert_eq!((excerpts[0].start_line, excerpts[0].end_line), (2, 5));
+        assert_eq!(excerpts[0].score, 2);
+        assert!(excerpts[0].text.contains("    4 |     sleep(backoff);"));
+    }
+
+    #[test]
+    fn resolves_citations_and_rejects_missing_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), "one\ntwo\nthree\n").unwrap();
+
+        let response = "```json\n{\"answer\": \"It counts [1][2][3][4].\", \"citations\": [\
+            {\"id\": 2, \"file\": \"src/lib.rs\", \"start_line\": 2, \"end_line\": 3},\
+            {\"id\": 1, \"file\": \"src/lib.rs\", \"start_line\": 1},\
+            {\"id\": 3, \"file\": \"src/lib.rs\", \"start_line\": 3, \"end_line\": 9},\
+            {\"id\": 4, \"file\": \"../secret.rs\", \"start_line\": 1}]}\n```";
+        let answer = parse_cited_answer(dir.path(), "What does it count?", response).unwrap();
+
+        assert_eq!(answer.citations.len(), 2);
+        assert_eq!(answer.citations[0].location(), "src/lib.rs:1");
+        assert_eq!(answer.citations[1].location(), "src/lib.rs:2-3");
+        assert_eq!(answer.citations[1].snippet, "    2 | two\n    3 | three");
+        assert!(answer.citations[1].uri.ends_with("src/lib.rs#L2-L3"));
+        assert_eq!(answer.rejected.len(), 2);
+        assert_eq!(answer.rejected[0].reason, "file has 3 lines");
+        assert!(!answer.is_verified());
+
+        let rendered = answer.render(false);
+        assert!(rendered.contains("  [2] src/lib.rs:2-3"));
+        assert!(rendered.contains("Unverified citations:\n  [3] src/lib.rs:3-9"));
+        assert!(answer.render(true).contains("\x1b]8;;file://"));
+        assert!(parse_cited_answer(dir.path(), "q", "not json").is_err());
+    }
+
+    #[test]
+    fn prompt_lists_excerpts_and_feedback_names_bad_citations() {
+        let excerpt = Excerpt {
+            path: "src/net.rs".into(),
+            start_line: 2,
+            end_line: 5,
+            score: 2,
+            text: "    4 |     sleep(backoff);".to_string(),
+        };
+        let prompt = cited_answer_prompt("How does retry work?", &[excerpt]);
+        assert!(prompt.contains("### src/net.rs:2-5"));
+        assert!(prompt.contains("\"start_line\""));
+
+        let rejected = RejectedCitation {
+            id: 3,
+            file: "src/lib.rs".to_string(),
+            start_line: 3,
+            end_line: 9,
+            reason: "file has 3 lines".to_string(),
+        };
+        let feedback = citation_feedback(&[rejected], true);
+        assert!(feedback.contains("[3] src/lib.rs:3-9: file has 3 lines"));
+        assert!(citation_feedback(&[], false).starts_with("Your answer has no citations."));
+    }
+}
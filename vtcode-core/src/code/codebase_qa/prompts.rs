@@ -0,0 +1,57 @@
+//! Prompt templates for cited codebase answers
+
+use super::citations::RejectedCitation;
+use super::retrieval::Excerpt;
+use std::fmt::Write;
+
+/// System prompt for `vtcode ask --cite`.
+pub const CITED_ANSWER_SYSTEM_PROMPT: &str = "You answer questions about a codebase using only the excerpts provided. \
+Support every statement about the code with a citation of the file and line range it comes from, using the line numbers shown in the excerpts. \
+Mark citations in the answer text as [1], [2], and so on. Never cite lines that are not shown. \
+If the excerpts do not answer the question, say so instead of guessing. \
+Respond with a single JSON object and nothing else.";
+
+/// Build the prompt for `question` over the selected excerpts.
+pub fn cited_answer_prompt(question: &str, excerpts: &[Excerpt]) -> String {
+    let mut prompt = String::new();
+    let _ = writeln!(prompt, "Question: {}\n", question.trim());
+    prompt.push_str("Workspace excerpts:\n");
+    for excerpt in excerpts {
+        let _ = writeln!(
+            prompt,
+            "\n### {}:{}-{}\n```\n{}\n```",
+            excerpt.path.display(),
+            excerpt.start_line,
+            excerpt.end_line,
+            excerpt.text
+        );
+    }
+    prompt.push_str(
+        "\nReturn JSON of the form:\n\
+{\"answer\": \"Markdown answer with markers like [1]\", \
+\"citations\": [{\"id\": 1, \"file\": \"path/as/shown\", \"start_line\": 10, \"end_line\": 14}]}\n\
+Keep line ranges tight around the lines that support the claim.",
+    );
+    prompt
+}
+
+/// Follow-up asking the model to fix citations that did not resolve.
+pub fn citation_feedback(rejected: &[RejectedCitation], cited_any: bool) -> String {
+    let mut prompt = String::new();
+    if rejected.is_empty() && !cited_any {
+        prompt.push_str("Your answer has no citations. ");
+    } else {
+        prompt.push_str("These citations do not point at existing lines:\n");
+        for citation in rejected {
+            let _ = writeln!(
+                prompt,
+                "- [{}] {}:{}-{}: {}",
+                citation.id, citation.file, citation.start_line, citation.end_line, citation.reason
+            );
+        }
+    }
+    prompt.push_str(
+        "Answer again in the same JSON form, citing only files and line numbers shown in the excerpts.",
+    );
+    prompt
+}
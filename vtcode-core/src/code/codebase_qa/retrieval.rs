@@ -0,0 +1,212 @@
+//! Selects workspace excerpts relevant to a question
+
+use crate::core::sensitive_files::is_read_denied;
+use crate::utils::index_exclude;
+use anyhow::Result;
+use ignore::WalkBuilder;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// Words too common in questions to say anything about the code.
+const STOP_WORDS: &[&str] = &[
+    "about", "all", "and", "any", "are", "can", "does", "for", "from", "get", "has", "have", "how",
+    "into", "its", "not", "the", "there", "this", "that", "use", "used", "uses", "using", "what",
+    "when", "where", "which", "who", "why", "with", "work", "works", "you", "code", "file",
+    "files", "function", "happen", "happens", "handle", "handled", "explain",
+];
+
+/// Limits applied while selecting excerpts.
+#[derive(Debug, Clone, Copy)]
+pub struct RetrievalOptions {
+    /// Maximum excerpts sent to the model
+    pub max_excerpts: usize,
+    /// Maximum excerpts taken from a single file
+    pub max_excerpts_per_file: usize,
+    /// Lines included before and after each matching line
+    pub context_lines: usize,
+    /// Files larger than this are skipped
+    pub max_file_bytes: u64,
+}
+
+impl Default for RetrievalOptions {
+    fn default() -> Self {
+        Self {
+            max_excerpts: 12,
+            max_excerpts_per_file: 3,
+            context_lines: 8,
+            max_file_bytes: 512 * 1024,
+        }
+    }
+}
+
+/// A numbered range of lines from one workspace file.
+#[derive(Debug, Clone, Serialize)]
+pub struct Excerpt {
+    /// Path relative to the workspace root
+    pub path: PathBuf,
+    /// 1-based first line
+    pub start_line: usize,
+    /// 1-based last line, inclusive
+    pub end_line: usize,
+    /// Number of question terms found in the excerpt and its path
+    pub score: usize,
+    /// The lines, each prefixed with its number
+    pub text: String,
+}
+
+/// Terms of `question` worth searching for: identifiers of three or more
+/// characters, lowercased, without common question words.
+pub fn question_terms(question: &str) -> Vec<String> {
+    let mut terms = BTreeSet::new();
+    for word in question.split(|c: char| !(c.is_alphanumeric() || c == '_')) {
+        let word = word.trim_matches('_').to_lowercase();
+        if word.chars().count() >= 3
+            && !word.chars().all(|c| c.is_ascii_digit())
+            && !STOP_WORDS.contains(&word.as_str())
+        {
+            terms.insert(word);
+        }
+    }
+    terms.into_iter().collect()
+}
+
+/// Walks the workspace and keeps the line ranges that mention the question
+/// terms most.
+pub struct ExcerptCollector {
+    root: PathBuf,
+    options: RetrievalOptions,
+}
+
+impl ExcerptCollector {
+    pub fn new(root: impl Into<PathBuf>, options: RetrievalOptions) -> Self {
+        Self {
+            root: root.into(),
+            options,
+        }
+    }
+
+    /// Collect the best excerpts for `question`, ordered by path and line.
+    pub fn collect(&self, question: &str) -> Result<Vec<Excerpt>> {
+        let terms = question_terms(question);
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut excerpts = Vec::new();
+        let exclusions = index_exclude::current();
+        let root = self.root.clone();
+        let walker = WalkBuilder::new(&self.root)
+            .filter_entry(move |entry| {
+                !exclusions.is_excluded(
+                    entry.path(),
+                    entry.file_type().is_some_and(|ft| ft.is_dir()),
+                ) && !is_read_denied(&root, entry.path())
+            })
+            .build();
+        for entry in walker.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+                continue;
+            }
+            let too_large = entry
+                .metadata()
+                .map(|meta| meta.len() > self.options.max_file_bytes)
+                .unwrap_or(true);
+            if too_large {
+                continue;
+            }
+            let Ok(source) = std::fs::read_to_string(path) else {
+                continue;
+            };
+            excerpts.extend(self.scan_file(path, &source, &terms));
+        }
+
+        excerpts.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then_with(|| a.path.cmp(&b.path))
+                .then_with(|| a.start_line.cmp(&b.start_line))
+        });
+        excerpts.truncate(self.options.max_excerpts);
+        excerpts.sort_by(|a, b| {
+            a.path
+                .cmp(&b.path)
+                .then_with(|| a.start_line.cmp(&b.start_line))
+        });
+        Ok(excerpts)
+    }
+
+    /// Excerpts of one file around the lines that mention `terms`.
+    pub fn scan_file(&self, path: &Path, source: &str, terms: &[String]) -> Vec<Excerpt> {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path).to_path_buf();
+        let path_text = relative.to_string_lossy().to_lowercase();
+        let path_terms: BTreeSet<&str> = terms
+            .iter()
+            .filter(|term| path_text.contains(term.as_str()))
+            .map(String::as_str)
+            .collect();
+
+        let lines: Vec<&str> = source.lines().collect();
+        let hits: Vec<(usize, BTreeSet<&str>)> = lines
+            .iter()
+            .enumerate()
+            .filter_map(|(index, line)| {
+                let line = line.to_lowercase();
+                let found: BTreeSet<&str> = terms
+                    .iter()
+                    .filter(|term| line.contains(term.as_str()))
+                    .map(String::as_str)
+                    .collect();
+                (!found.is_empty()).then_some((index, found))
+            })
+            .collect();
+        if hits.is_empty() {
+            return Vec::new();
+        }
+
+        // Merge hits whose context windows touch into one range.
+        let context = self.options.context_lines;
+        let mut ranges: Vec<(usize, usize, BTreeSet<&str>)> = Vec::new();
+        for (index, found) in hits {
+            let start = index.saturating_sub(context);
+            let end = (index + context).min(lines.len() - 1);
+            match ranges.last_mut() {
+                Some(last) if start <= last.1 + 1 => {
+                    last.1 = end;
+                    last.2.extend(found);
+                }
+                _ => ranges.push((start, end, found)),
+            }
+        }
+
+        let mut excerpts: Vec<Excerpt> = ranges
+            .into_iter()
+            .map(|(start, end, mut found)| {
+                found.extend(path_terms.iter().copied());
+                Excerpt {
+                    path: relative.clone(),
+                    start_line: start + 1,
+                    end_line: end + 1,
+                    score: found.len(),
+                    text: numbered_lines(&lines, start + 1, end + 1),
+                }
+            })
+            .collect();
+        excerpts.sort_by(|a, b| b.score.cmp(&a.score).then(a.start_line.cmp(&b.start_line)));
+        excerpts.truncate(self.options.max_excerpts_per_file);
+        excerpts
+    }
+}
+
+/// Lines `start..=end` (1-based) of `lines`, each prefixed with its number.
+pub fn numbered_lines(lines: &[&str], start: usize, end: usize) -> String {
+    let end = end.min(lines.len());
+    let start = start.max(1).min(end + 1);
+    lines[start - 1..end]
+        .iter()
+        .enumerate()
+        .map(|(offset, text)| format!("{:>5} | {}", start + offset, text))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -1,2 +1,4 @@
 pub mod code_completion;
 pub mod code_quality;
+pub mod codebase_qa;
+pub mod security_review;
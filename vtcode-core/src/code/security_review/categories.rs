@@ -0,0 +1,127 @@
+//! Security finding categories and the queries used to locate relevant code
+
+use serde::{Deserialize, Serialize};
+
+/// Category of security-sensitive code reviewed by `vtcode review --security`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecurityCategory {
+    InputValidation,
+    Injection,
+    Authentication,
+    Cryptography,
+    SecretsExposure,
+}
+
+impl SecurityCategory {
+    pub const ALL: [SecurityCategory; 5] = [
+        SecurityCategory::InputValidation,
+        SecurityCategory::Injection,
+        SecurityCategory::Authentication,
+        SecurityCategory::Cryptography,
+        SecurityCategory::SecretsExposure,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::InputValidation => "Input validation",
+            Self::Injection => "Injection",
+            Self::Authentication => "Authentication & authorization",
+            Self::Cryptography => "Cryptography",
+            Self::SecretsExposure => "Secrets exposure",
+        }
+    }
+
+    /// CWE identifiers most commonly reported for this category.
+    pub fn cwe_ids(self) -> &'static [&'static str] {
+        match self {
+            Self::InputValidation => &["CWE-20", "CWE-22", "CWE-502"],
+            Self::Injection => &["CWE-78", "CWE-89", "CWE-94"],
+            Self::Authentication => &["CWE-287", "CWE-306", "CWE-862"],
+            Self::Cryptography => &["CWE-327", "CWE-328", "CWE-330"],
+            Self::SecretsExposure => &["CWE-798", "CWE-532"],
+        }
+    }
+
+    /// Lowercase fragments of function names that suggest the category.
+    pub fn symbol_hints(self) -> &'static [&'static str] {
+        match self {
+            Self::InputValidation => &[
+                "parse",
+                "deserialize",
+                "decode",
+                "validate",
+                "sanitize",
+                "handle_request",
+                "upload",
+            ],
+            Self::Injection => &["exec", "query", "shell", "command", "eval", "render"],
+            Self::Authentication => &[
+                "auth",
+                "login",
+                "logout",
+                "session",
+                "token",
+                "password",
+                "permission",
+                "credential",
+            ],
+            Self::Cryptography => &[
+                "encrypt", "decrypt", "cipher", "hash", "sign", "verify", "random", "nonce",
+            ],
+            Self::SecretsExposure => &["secret", "api_key", "apikey", "private_key"],
+        }
+    }
+
+    /// Regular expressions matched line by line against file contents.
+    pub fn content_patterns(self) -> &'static [&'static str] {
+        match self {
+            Self::InputValidation => &[
+                r"\b(serde_json::from_(str|slice|reader)|json\.loads|JSON\.parse|pickle\.loads|yaml\.load|ObjectInputStream)\b",
+                r"\b(request|req)\.(args|form|query|params|body|headers)\b",
+                r"\.\./|\bos\.path\.join\(|\bpath\.join\(",
+            ],
+            Self::Injection => &[
+                r"\b(Command::new|subprocess\.|os\.system|os\.popen|child_process|exec\.Command|Runtime\.getRuntime\(\)\.exec)",
+                r"\beval\(|\bexec\(|new Function\(",
+                r#"(?i)"\s*(select|insert|update|delete)\s[^"]*"\s*(\+|%|\.format)|format!\(\s*"(?i:select|insert|update|delete)\s"#,
+                r"\binnerHTML\s*=|dangerouslySetInnerHTML",
+            ],
+            Self::Authentication => &[
+                r"(?i)\b(authori[sz]e|authenticate|is_admin|jwt|bearer|csrf|session_id|set_cookie)\b",
+                r"(?i)verify\s*=\s*false|danger_accept_invalid_certs|InsecureSkipVerify",
+            ],
+            Self::Cryptography => &[
+                r"(?i)\b(md5|sha1|des|rc4|ecb)\b",
+                r"\b(Math\.random|random\.random|rand::random|thread_rng)\b",
+                r"(?i)\b(aes|rsa|hmac|pbkdf2|bcrypt|argon2|scrypt)\b",
+            ],
+            Self::SecretsExposure => &[
+                r#"(?i)(api[_-]?key|secret|passw(or)?d|private[_-]?key|access[_-]?token)\s*[:=]\s*["'][^"'\s]{6,}["']"#,
+                r"-----BEGIN [A-Z ]*PRIVATE KEY-----",
+                r"\b(AKIA[0-9A-Z]{16}|sk-[A-Za-z0-9]{20,}|ghp_[A-Za-z0-9]{36})\b",
+            ],
+        }
+    }
+
+    /// Category-specific review instructions appended to the prompt template.
+    pub fn prompt_focus(self) -> &'static str {
+        match self {
+            Self::InputValidation => {
+                "Trace untrusted data (request fields, CLI arguments, environment, files, deserialized payloads) to where it is used. Report missing validation, unsafe deserialization, and path traversal through user-controlled paths."
+            }
+            Self::Injection => {
+                "Check whether attacker-influenced strings reach shells, process spawning, SQL queries, template rendering, eval, or HTML sinks without escaping or parameterisation."
+            }
+            Self::Authentication => {
+                "Look for missing or bypassable authentication and authorization checks, insecure session or token handling, and disabled certificate verification."
+            }
+            Self::Cryptography => {
+                "Flag weak or broken algorithms, ECB mode, hard-coded keys or IVs, non-cryptographic randomness used for security decisions, and incorrect signature or MAC verification."
+            }
+            Self::SecretsExposure => {
+                "Identify hard-coded credentials, private keys, and tokens, and places where secrets are logged or returned to users."
+            }
+        }
+    }
+}
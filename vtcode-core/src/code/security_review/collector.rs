@@ -0,0 +1,218 @@
+//! Collects security-relevant code locations using symbol and pattern queries
+
+use super::categories::SecurityCategory;
+use crate::tools::tree_sitter::{SymbolKind, TreeSitterAnalyzer};
+use anyhow::{Context, Result};
+use ignore::WalkBuilder;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Extensions scanned in addition to tree-sitter supported source files.
+const CONFIG_EXTENSIONS: &[&str] = &["toml", "yaml", "yml", "json", "env", "ini", "cfg", "conf"];
+
+/// Limits applied while collecting candidates.
+#[derive(Debug, Clone, Copy)]
+pub struct CollectorOptions {
+    /// Maximum candidates kept per category
+    pub max_candidates_per_category: usize,
+    /// Files larger than this are skipped
+    pub max_file_bytes: u64,
+    /// Lines of context included around each match
+    pub context_lines: usize,
+}
+
+impl Default for CollectorOptions {
+    fn default() -> Self {
+        Self {
+            max_candidates_per_category: 25,
+            max_file_bytes: 512 * 1024,
+            context_lines: 3,
+        }
+    }
+}
+
+/// A code location that should be reviewed for one category.
+#[derive(Debug, Clone, Serialize)]
+pub struct SecurityCandidate {
+    pub category: SecurityCategory,
+    /// Path relative to the workspace root
+    pub path: PathBuf,
+    /// 1-based line number
+    pub line: usize,
+    /// Why the location was selected, e.g. "symbol `login`"
+    pub reason: String,
+    /// Numbered source lines around the location
+    pub snippet: String,
+}
+
+/// Candidates grouped by category.
+#[derive(Debug, Default)]
+pub struct CollectedCandidates {
+    pub scanned_files: usize,
+    pub by_category: BTreeMap<SecurityCategory, Vec<SecurityCandidate>>,
+}
+
+impl CollectedCandidates {
+    pub fn total(&self) -> usize {
+        self.by_category.values().map(Vec::len).sum()
+    }
+}
+
+/// Walks the workspace and selects code handling input, auth, crypto and secrets.
+pub struct SecurityCandidateCollector {
+    root: PathBuf,
+    options: CollectorOptions,
+    analyzer: TreeSitterAnalyzer,
+    patterns: Vec<(SecurityCategory, Regex)>,
+}
+
+impl SecurityCandidateCollector {
+    pub fn new(root: impl Into<PathBuf>, options: CollectorOptions) -> Result<Self> {
+        let mut patterns = Vec::new();
+        for category in SecurityCategory::ALL {
+            for pattern in category.content_patterns() {
+                let regex = Regex::new(pattern)
+                    .with_context(|| format!("invalid security pattern: {}", pattern))?;
+                patterns.push((category, regex));
+            }
+        }
+
+        Ok(Self {
+            root: root.into(),
+            options,
+            analyzer: TreeSitterAnalyzer::new()?,
+            patterns,
+        })
+    }
+
+    /// Collect candidates from `targets`, or the whole workspace when empty.
+    pub fn collect(&mut self, targets: &[PathBuf]) -> Result<CollectedCandidates> {
+        let targets = if targets.is_empty() {
+            vec![self.root.clone()]
+        } else {
+            targets
+                .iter()
+                .map(|target| {
+                    if target.is_absolute() {
+                        target.clone()
+                    } else {
+                        self.root.join(target)
+                    }
+                })
+                .collect()
+        };
+
+        let mut collected = CollectedCandidates::default();
+        let mut seen = HashSet::new();
+        for target in targets {
+            for entry in WalkBuilder::new(&target).build().filter_map(|e| e.ok()) {
+                let path = entry.path();
+                if !entry.file_type().is_some_and(|ft| ft.is_file()) || !self.should_scan(path) {
+                    continue;
+                }
+                let Ok(source) = std::fs::read_to_string(path) else {
+                    continue;
+                };
+                collected.scanned_files += 1;
+                for candidate in self.scan_file(path, &source) {
+                    let key = (candidate.category, candidate.path.clone(), candidate.line);
+                    if seen.insert(key) {
+                        collected
+                            .by_category
+                            .entry(candidate.category)
+                            .or_default()
+                            .push(candidate);
+                    }
+                }
+            }
+        }
+
+        for candidates in collected.by_category.values_mut() {
+            // Prefer symbol matches: they anchor the model on a whole function.
+            candidates.sort_by_key(|c| (!c.reason.starts_with("symbol"), c.path.clone(), c.line));
+            candidates.truncate(self.options.max_candidates_per_category);
+        }
+
+        Ok(collected)
+    }
+
+    fn should_scan(&self, path: &Path) -> bool {
+        let too_large = std::fs::metadata(path)
+            .map(|meta| meta.len() > self.options.max_file_bytes)
+            .unwrap_or(true);
+        if too_large {
+            return false;
+        }
+        if self.analyzer.detect_language_from_path(path).is_ok() {
+            return true;
+        }
+        let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        file_name.starts_with(".env") || CONFIG_EXTENSIONS.contains(&extension)
+    }
+
+    /// Find candidates in a single file.
+    pub fn scan_file(&mut self, path: &Path, source: &str) -> Vec<SecurityCandidate> {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path).to_path_buf();
+        let lines: Vec<&str> = source.lines().collect();
+        let mut candidates = Vec::new();
+
+        if let Ok(language) = self.analyzer.detect_language_from_path(path)
+            && let Ok(tree) = self.analyzer.parse(source, language)
+            && let Ok(symbols) = self.analyzer.extract_symbols(&tree, source, language)
+        {
+            for symbol in symbols
+                .iter()
+                .filter(|s| matches!(s.kind, SymbolKind::Function | SymbolKind::Method))
+            {
+                let name = symbol.name.to_lowercase();
+                for category in SecurityCategory::ALL {
+                    if category
+                        .symbol_hints()
+                        .iter()
+                        .any(|hint| name.contains(hint))
+                    {
+                        let line = symbol.position.row + 1;
+                        candidates.push(SecurityCandidate {
+                            category,
+                            path: relative.clone(),
+                            line,
+                            reason: format!("symbol `{}`", symbol.name),
+                            snippet: self.snippet(&lines, line),
+                        });
+                    }
+                }
+            }
+        }
+
+        for (index, text) in lines.iter().enumerate() {
+            for (category, regex) in &self.patterns {
+                if let Some(found) = regex.find(text) {
+                    let line = index + 1;
+                    candidates.push(SecurityCandidate {
+                        category: *category,
+                        path: relative.clone(),
+                        line,
+                        reason: format!("pattern `{}`", found.as_str().trim()),
+                        snippet: self.snippet(&lines, line),
+                    });
+                }
+            }
+        }
+
+        candidates
+    }
+
+    fn snippet(&self, lines: &[&str], line: usize) -> String {
+        let start = line.saturating_sub(self.options.context_lines + 1);
+        let end = (line + self.options.context_lines).min(lines.len());
+        lines[start..end]
+            .iter()
+            .enumerate()
+            .map(|(offset, text)| format!("{:>5} | {}", start + offset + 1, text))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
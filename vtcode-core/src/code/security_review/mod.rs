@@ -0,0 +1,82 @@
+//! Security review support for `vtcode review --security`
+//!
+//! The review runs in three stages: candidate collection selects code that
+//! handles input, authentication, cryptography or secrets using tree-sitter
+//! symbol queries and line patterns; each category is then reviewed with its
+//! own prompt template; the model responses are merged into a structured
+//! [`SecurityReport`] with CWE tags, severities and remediation advice.
+
+pub mod categories;
+pub mod collector;
+pub mod prompts;
+pub mod report;
+
+pub use categories::SecurityCategory;
+pub use collector::{
+    CollectedCandidates, CollectorOptions, SecurityCandidate, SecurityCandidateCollector,
+};
+pub use prompts::{SECURITY_REVIEW_SYSTEM_PROMPT, security_review_prompt};
+pub use report::{SecurityFinding, SecurityReport, Severity, parse_findings};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn collector_finds_symbols_and_patterns() {
+        let mut collector =
+            SecurityCandidateCollector::new("/work", CollectorOptions::default()).unwrap();
+        let source = "use std::process::Command;\n\nfn login(user: &str) -> bool {\n    let api_key = \"sk-test-123456\";\n    Command::new(\"sh\").arg(user).status().is_ok()\n}\n";
+        let candidates = collector.scan_file(Path::new("/work/src/auth.rs"), source);
+
+        let has = |category: SecurityCategory, line: usize| {
+            candidates
+                .iter()
+                .any(|c| c.category == category && c.line == line)
+        };
+        assert!(has(SecurityCategory::Authentication, 3));
+        assert!(has(SecurityCategory::SecretsExposure, 4));
+        assert!(has(SecurityCategory::Injection, 5));
+        assert!(
+            candidates
+                .iter()
+                .all(|c| c.path == Path::new("src/auth.rs"))
+        );
+    }
+
+    #[test]
+    fn prompt_includes_cwe_tags_and_locations() {
+        let candidate = SecurityCandidate {
+            category: SecurityCategory::Cryptography,
+            path: "src/hash.rs".into(),
+            line: 7,
+            reason: "pattern `md5`".to_string(),
+            snippet: "    7 | md5::compute(password)".to_string(),
+        };
+        let prompt = security_review_prompt(SecurityCategory::Cryptography, &[candidate]);
+        assert!(prompt.contains("CWE-327"));
+        assert!(prompt.contains("src/hash.rs:7"));
+        assert!(prompt.contains("\"findings\""));
+    }
+
+    #[test]
+    fn parses_fenced_findings_and_renders_report() {
+        let response = "```json\n{\"findings\": [{\"title\": \"Weak hash\", \"severity\": \"HIGH\", \"file\": \"src/hash.rs\", \"line\": 7, \"description\": \"MD5 is broken\", \"remediation\": \"Use argon2\"}]}\n```";
+        let findings = parse_findings(SecurityCategory::Cryptography, response).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].severity, Severity::High);
+        assert_eq!(findings[0].cwe, "CWE-327");
+
+        let mut report = SecurityReport {
+            workspace: "/work".to_string(),
+            findings,
+            ..Default::default()
+        };
+        report.sort();
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("[HIGH] Weak hash (CWE-327)"));
+        assert!(markdown.contains("`src/hash.rs:7`"));
+        assert!(parse_findings(SecurityCategory::Injection, "not json").is_err());
+    }
+}
@@ -0,0 +1,49 @@
+//! Security-focused prompt templates, one per finding category
+
+use super::categories::SecurityCategory;
+use super::collector::SecurityCandidate;
+use std::fmt::Write;
+
+/// System prompt shared by every security review request.
+pub const SECURITY_REVIEW_SYSTEM_PROMPT: &str = "You are an application security reviewer performing taint-style analysis. \
+For each code location, identify where untrusted data enters (sources), how it flows, and whether it reaches a dangerous operation (sinks) without adequate validation, encoding, or authorization. \
+Only report issues supported by the code shown; do not speculate about code you cannot see. \
+Respond with a single JSON object and nothing else.";
+
+/// Build the review prompt for one category and its candidate locations.
+pub fn security_review_prompt(
+    category: SecurityCategory,
+    candidates: &[SecurityCandidate],
+) -> String {
+    let mut prompt = String::new();
+    let _ = writeln!(prompt, "Category: {}", category.label());
+    let _ = writeln!(
+        prompt,
+        "Relevant weaknesses: {}",
+        category.cwe_ids().join(", ")
+    );
+    let _ = writeln!(prompt, "Focus: {}", category.prompt_focus());
+    prompt.push('\n');
+    prompt.push_str("Review the following code locations:\n");
+
+    for (index, candidate) in candidates.iter().enumerate() {
+        let _ = writeln!(
+            prompt,
+            "\n### Location {} — {}:{} ({})\n```\n{}\n```",
+            index + 1,
+            candidate.path.display(),
+            candidate.line,
+            candidate.reason,
+            candidate.snippet
+        );
+    }
+
+    prompt.push_str(
+        "\nReturn JSON of the form:\n\
+{\"findings\": [{\"title\": \"short summary\", \"cwe\": \"CWE-89\", \
+\"severity\": \"critical|high|medium|low|info\", \"file\": \"path\", \"line\": 42, \
+\"description\": \"source-to-sink explanation\", \"remediation\": \"concrete fix\"}]}\n\
+Use an empty findings array when no location is vulnerable.",
+    );
+    prompt
+}
@@ -0,0 +1,188 @@
+//! Structured security review report
+
+use super::categories::SecurityCategory;
+use crate::utils::code_fence::strip_code_fence;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Critical,
+    High,
+    Medium,
+    Low,
+    Info,
+}
+
+impl Severity {
+    pub const ALL: [Severity; 5] = [
+        Severity::Critical,
+        Severity::High,
+        Severity::Medium,
+        Severity::Low,
+        Severity::Info,
+    ];
+
+    /// Parse a model-provided severity, defaulting to `Medium` when unknown.
+    pub fn parse_lenient(value: &str) -> Self {
+        match value.trim().to_lowercase().as_str() {
+            "critical" => Self::Critical,
+            "high" => Self::High,
+            "low" => Self::Low,
+            "info" | "informational" | "none" => Self::Info,
+            _ => Self::Medium,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Critical => "critical",
+            Self::High => "high",
+            Self::Medium => "medium",
+            Self::Low => "low",
+            Self::Info => "info",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecurityFinding {
+    pub category: SecurityCategory,
+    pub title: String,
+    pub cwe: String,
+    pub severity: Severity,
+    pub file: Option<String>,
+    pub line: Option<usize>,
+    pub description: String,
+    pub remediation: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawFindings {
+    #[serde(default)]
+    findings: Vec<RawFinding>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawFinding {
+    title: String,
+    #[serde(default)]
+    cwe: Option<String>,
+    #[serde(default)]
+    severity: Option<String>,
+    #[serde(default)]
+    file: Option<String>,
+    #[serde(default)]
+    line: Option<usize>,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    remediation: String,
+}
+
+/// Parse the model response for `category` into findings.
+pub fn parse_findings(category: SecurityCategory, response: &str) -> Result<Vec<SecurityFinding>> {
+    let body = strip_code_fence(response);
+    let raw: RawFindings = serde_json::from_str(body.trim())
+        .with_context(|| format!("invalid findings JSON for {}", category.label()))?;
+
+    Ok(raw
+        .findings
+        .into_iter()
+        .map(|finding| SecurityFinding {
+            category,
+            title: finding.title,
+            cwe: finding
+                .cwe
+                .filter(|cwe| !cwe.trim().is_empty())
+                .unwrap_or_else(|| category.cwe_ids()[0].to_string()),
+            severity: finding
+                .severity
+                .as_deref()
+                .map(Severity::parse_lenient)
+                .unwrap_or(Severity::Medium),
+            file: finding.file,
+            line: finding.line,
+            description: finding.description,
+            remediation: finding.remediation,
+        })
+        .collect())
+}
+
+/// Outcome of a security review run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SecurityReport {
+    pub workspace: String,
+    pub scanned_files: usize,
+    pub candidates_reviewed: usize,
+    pub findings: Vec<SecurityFinding>,
+    /// Categories whose review could not be completed, with the reason
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+impl SecurityReport {
+    /// Order findings by severity, then location.
+    pub fn sort(&mut self) {
+        self.findings.sort_by(|a, b| {
+            a.severity
+                .cmp(&b.severity)
+                .then_with(|| a.file.cmp(&b.file))
+                .then_with(|| a.line.cmp(&b.line))
+        });
+    }
+
+    pub fn count(&self, severity: Severity) -> usize {
+        self.findings
+            .iter()
+            .filter(|finding| finding.severity == severity)
+            .count()
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# Security Review\n");
+        let _ = writeln!(out, "- Workspace: `{}`", self.workspace);
+        let _ = writeln!(out, "- Files scanned: {}", self.scanned_files);
+        let _ = writeln!(out, "- Locations reviewed: {}", self.candidates_reviewed);
+        let counts = Severity::ALL
+            .iter()
+            .map(|severity| format!("{} {}", self.count(*severity), severity.label()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let _ = writeln!(out, "- Findings: {}\n", counts);
+
+        if self.findings.is_empty() {
+            out.push_str("No security findings.\n");
+        }
+        for (index, finding) in self.findings.iter().enumerate() {
+            let location = match (&finding.file, finding.line) {
+                (Some(file), Some(line)) => format!("{}:{}", file, line),
+                (Some(file), None) => file.clone(),
+                _ => "unknown location".to_string(),
+            };
+            let _ = writeln!(
+                out,
+                "## {}. [{}] {} ({})\n",
+                index + 1,
+                finding.severity.label().to_uppercase(),
+                finding.title,
+                finding.cwe
+            );
+            let _ = writeln!(out, "- Category: {}", finding.category.label());
+            let _ = writeln!(out, "- Location: `{}`\n", location);
+            let _ = writeln!(out, "{}\n", finding.description);
+            let _ = writeln!(out, "**Remediation:** {}\n", finding.remediation);
+        }
+
+        if !self.errors.is_empty() {
+            out.push_str("## Incomplete categories\n\n");
+            for error in &self.errors {
+                let _ = writeln!(out, "- {}", error);
+            }
+        }
+        out
+    }
+}
@@ -5,8 +5,10 @@
 //! prioritizing security by checking environment variables first, then .env files, and finally
 //! falling back to configuration file values.
 
-use anyhow::Result;
+use crate::config::core::{CredentialProfile, CredentialsConfig, KeychainEntry};
+use anyhow::{Context, Result};
 use std::env;
+use std::process::Command;
 
 /// API key sources for different providers
 #[derive(Debug, Clone)]
@@ -127,7 +129,7 @@ pub fn load_dotenv() -> Result<()> {
 /// 1. First checks environment variables (highest priority for security)
 /// 2. Then checks .env file values
 /// 3. Falls back to configuration file values if neither above is set
-/// 4. Supports all major providers: Gemini, Anthropic, OpenAI, OpenRouter, and xAI
+/// 4. Supports all major providers: Gemini, Anthropic, OpenAI, OpenRouter, xAI and Ollama
 /// 5. Automatically infers the correct environment variable based on provider
 ///
 /// # Arguments
@@ -148,6 +150,7 @@ pub fn get_api_key(provider: &str, sources: &ApiKeySources) -> Result<String> {
         "deepseek" => "DEEPSEEK_API_KEY",
         "openrouter" => "OPENROUTER_API_KEY",
         "xai" => "XAI_API_KEY",
+        "ollama" => "OLLAMA_API_KEY",
         _ => "GEMINI_API_KEY",
     };
 
@@ -165,10 +168,143 @@ pub fn get_api_key(provider: &str, sources: &ApiKeySources) -> Result<String> {
         "openai" => get_openai_api_key(sources),
         "openrouter" => get_openrouter_api_key(sources),
         "xai" => get_xai_api_key(sources),
+        // A local Ollama server needs no key; one is only set for a proxy in front of it.
+        "ollama" => Ok(String::new()),
         _ => Err(anyhow::anyhow!("Unsupported provider: {}", provider)),
     }
 }
 
+/// An API key read from a named credential profile
+#[derive(Debug, Clone)]
+pub struct ResolvedCredential {
+    /// Profile name from `[credentials.<provider>]`
+    pub profile: String,
+    pub api_key: String,
+}
+
+/// Resolve the credential profiles configured for a provider in rotation order
+///
+/// The order starts at the workspace's `active` profile and wraps around the
+/// list. Profiles whose key cannot be read are skipped with a warning so one
+/// stale entry does not block the others.
+///
+/// # Returns
+///
+/// * `Ok(vec![])` - No profiles are configured for the provider
+/// * `Ok(credentials)` - At least one profile resolved to a key
+/// * `Err` - Profiles are configured but none of them yielded a key, or
+///   `active` names an unknown profile
+pub fn resolve_credential_profiles(
+    provider: &str,
+    credentials: &CredentialsConfig,
+) -> Result<Vec<ResolvedCredential>> {
+    let Some(config) = credentials.for_provider(provider) else {
+        return Ok(Vec::new());
+    };
+
+    let start = match config.active.as_deref() {
+        Some(active) => config
+            .profiles
+            .iter()
+            .position(|profile| profile.name == active)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Active credential profile '{}' is not defined for {}",
+                    active,
+                    provider
+                )
+            })?,
+        None => 0,
+    };
+
+    let count = config.profiles.len();
+    let mut resolved = Vec::with_capacity(count);
+    for profile in config.profiles.iter().cycle().skip(start).take(count) {
+        match read_profile_key(profile) {
+            Ok(api_key) => resolved.push(ResolvedCredential {
+                profile: profile.name.clone(),
+                api_key,
+            }),
+            Err(err) => eprintln!(
+                "Warning: skipping credential profile '{}' for {}: {:#}",
+                profile.name, provider, err
+            ),
+        }
+    }
+
+    if resolved.is_empty() {
+        return Err(anyhow::anyhow!(
+            "None of the credential profiles configured for {} provided an API key",
+            provider
+        ));
+    }
+    Ok(resolved)
+}
+
+/// Read the key of a single credential profile
+fn read_profile_key(profile: &CredentialProfile) -> Result<String> {
+    if let Some(var) = &profile.api_key_env
+        && let Ok(key) = env::var(var)
+        && !key.is_empty()
+    {
+        return Ok(key);
+    }
+
+    if let Some(entry) = &profile.keychain {
+        match read_keychain(entry) {
+            Ok(key) => return Ok(key),
+            Err(err) if profile.api_key.is_none() => return Err(err),
+            Err(_) => {}
+        }
+    }
+
+    if let Some(key) = &profile.api_key
+        && !key.is_empty()
+    {
+        return Ok(key.clone());
+    }
+
+    match &profile.api_key_env {
+        Some(var) => Err(anyhow::anyhow!("{} is not set", var)),
+        None => Err(anyhow::anyhow!(
+            "no api_key_env, keychain or api_key configured"
+        )),
+    }
+}
+
+/// Look up a generic password in the system keychain
+///
+/// Uses `security` on macOS and `secret-tool` (libsecret) elsewhere.
+fn read_keychain(entry: &KeychainEntry) -> Result<String> {
+    let output = if cfg!(target_os = "macos") {
+        Command::new("security")
+            .args(["find-generic-password", "-w", "-s"])
+            .arg(&entry.service)
+            .arg("-a")
+            .arg(&entry.account)
+            .output()
+            .context("failed to run `security`")?
+    } else {
+        Command::new("secret-tool")
+            .args(["lookup", "service"])
+            .arg(&entry.service)
+            .arg("account")
+            .arg(&entry.account)
+            .output()
+            .context("failed to run `secret-tool`")?
+    };
+
+    let key = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !output.status.success() || key.is_empty() {
+        return Err(anyhow::anyhow!(
+            "no keychain entry for service '{}' and account '{}'",
+            entry.service,
+            entry.account
+        ));
+    }
+    Ok(key)
+}
+
 /// Get API key for a specific environment variable with fallback
 fn get_api_key_with_fallback(
     env_var: &str,
@@ -398,6 +534,71 @@ mod tests {
         assert_eq!(result.unwrap(), "config-key");
     }
 
+    fn env_profile(name: &str, var: &str) -> CredentialProfile {
+        CredentialProfile {
+            name: name.to_string(),
+            api_key_env: Some(var.to_string()),
+            keychain: None,
+            api_key: None,
+        }
+    }
+
+    #[test]
+    fn test_resolve_credential_profiles_starts_at_active_and_skips_missing() {
+        unsafe {
+            env::set_var("TEST_PROFILE_KEY_A", "key-a");
+            env::set_var("TEST_PROFILE_KEY_C", "key-c");
+        }
+
+        let mut credentials = CredentialsConfig::default();
+        credentials.providers.insert(
+            "openai".to_string(),
+            crate::config::core::ProviderCredentials {
+                active: Some("team-b".to_string()),
+                profiles: vec![
+                    env_profile("team-a", "TEST_PROFILE_KEY_A"),
+                    env_profile("team-b", "TEST_PROFILE_KEY_B_UNSET"),
+                    env_profile("team-c", "TEST_PROFILE_KEY_C"),
+                ],
+                ..Default::default()
+            },
+        );
+
+        let resolved = resolve_credential_profiles("OpenAI", &credentials).unwrap();
+        let order: Vec<(&str, &str)> = resolved
+            .iter()
+            .map(|c| (c.profile.as_str(), c.api_key.as_str()))
+            .collect();
+        assert_eq!(order, vec![("team-c", "key-c"), ("team-a", "key-a")]);
+        assert!(
+            resolve_credential_profiles("gemini", &credentials)
+                .unwrap()
+                .is_empty()
+        );
+
+        unsafe {
+            env::remove_var("TEST_PROFILE_KEY_A");
+            env::remove_var("TEST_PROFILE_KEY_C");
+        }
+    }
+
+    #[test]
+    fn test_resolve_credential_profiles_rejects_unknown_active() {
+        let mut credentials = CredentialsConfig::default();
+        credentials.providers.insert(
+            "openai".to_string(),
+            crate::config::core::ProviderCredentials {
+                active: Some("missing".to_string()),
+                profiles: vec![CredentialProfile {
+                    api_key: Some("inline".to_string()),
+                    ..env_profile("team-a", "TEST_PROFILE_KEY_UNUSED")
+                }],
+                ..Default::default()
+            },
+        );
+        assert!(resolve_credential_profiles("openai", &credentials).is_err());
+    }
+
     #[test]
     fn test_get_api_key_error_when_not_found() {
         let sources = ApiKeySources {
@@ -108,6 +108,16 @@ pub mod models {
         pub const GROK_2_VISION: &str = "grok-2-vision";
     }
 
+    // Ollama models served on the local machine; any pulled model works
+    pub mod ollama {
+        pub const DEFAULT_MODEL: &str = "gpt-oss:20b";
+        pub const SUPPORTED_MODELS: &[&str] =
+            &["gpt-oss:20b", "qwen3-coder:30b", "qwen3:8b", "llama3.1:8b"];
+
+        pub const GPT_OSS_20B: &str = "gpt-oss:20b";
+        pub const QWEN3_CODER_30B: &str = "qwen3-coder:30b";
+    }
+
     // Backwards compatibility - keep old constants working
     pub const GEMINI_2_5_FLASH_PREVIEW: &str = google::GEMINI_2_5_FLASH_PREVIEW;
     pub const GEMINI_2_5_FLASH: &str = google::GEMINI_2_5_FLASH;
@@ -157,6 +167,10 @@ pub mod prompt_cache {
     pub const OPENROUTER_CACHE_DISCOUNT_ENABLED: bool = true;
     pub const XAI_CACHE_ENABLED: bool = true;
     pub const DEEPSEEK_CACHE_ENABLED: bool = true;
+
+    pub const WARMUP_MIN_PREFIX_TOKENS: u32 = 1_024;
+    pub const WARMUP_MAX_PREFIX_TOKENS: u32 = 64_000;
+    pub const WARMUP_TIMEOUT_MS: u64 = 15_000;
 }
 
 /// Model validation and helper functions
@@ -171,6 +185,7 @@ pub mod model_helpers {
             "anthropic" => Some(models::anthropic::SUPPORTED_MODELS),
             "openrouter" => Some(models::openrouter::SUPPORTED_MODELS),
             "xai" => Some(models::xai::SUPPORTED_MODELS),
+            "ollama" => Some(models::ollama::SUPPORTED_MODELS),
             _ => None,
         }
     }
@@ -183,6 +198,7 @@ pub mod model_helpers {
             "anthropic" => Some(models::anthropic::DEFAULT_MODEL),
             "openrouter" => Some(models::openrouter::DEFAULT_MODEL),
             "xai" => Some(models::xai::DEFAULT_MODEL),
+            "ollama" => Some(models::ollama::DEFAULT_MODEL),
             _ => None,
         }
     }
@@ -239,6 +255,7 @@ pub mod urls {
     pub const ANTHROPIC_API_VERSION: &str = "2023-06-01";
     pub const OPENROUTER_API_BASE: &str = "https://openrouter.ai/api/v1";
     pub const XAI_API_BASE: &str = "https://api.x.ai/v1";
+    pub const OLLAMA_API_BASE: &str = "http://localhost:11434";
 }
 
 /// Tool name constants to avoid hardcoding strings throughout the codebase
@@ -258,6 +275,31 @@ pub mod tools {
     pub const SRGN: &str = "srgn";
     pub const CURL: &str = "curl";
     pub const UPDATE_PLAN: &str = "update_plan";
+    pub const ORGANIZE_IMPORTS: &str = "organize_imports";
+    pub const COVERAGE_REPORT: &str = "coverage_report";
+    pub const CONFIG_EDIT: &str = "config_edit";
+    pub const BOOTSTRAP_ENV: &str = "bootstrap_env";
+    pub const PROJECT_COMMANDS: &str = "project_commands";
+    pub const CALL_GRAPH: &str = "call_graph";
+    pub const FETCH_DOCS: &str = "fetch_docs";
+    pub const SQL_QUERY: &str = "sql_query";
+    pub const SQL_SCHEMA: &str = "sql_schema";
+    pub const SQL_EXECUTE: &str = "sql_execute";
+    /// Tools hidden from the model unless `[tools.sql] enabled` is set
+    pub const SQL_TOOLS: &[&str] = &[SQL_QUERY, SQL_SCHEMA, SQL_EXECUTE];
+    /// Hidden from the model unless `[tools.vulnerability_scan] enabled` is set
+    pub const VULNERABILITY_SCAN: &str = "vulnerability_scan";
+    /// Hidden from the model unless `[tools.semantic_search] enabled` is set
+    pub const SEMANTIC_SEARCH: &str = "semantic_search";
+    pub const GIT_STATUS: &str = "git_status";
+    pub const GIT_DIFF: &str = "git_diff";
+    pub const GIT_LOG: &str = "git_log";
+    pub const GIT_COMMIT: &str = "git_commit";
+    pub const GIT_BRANCH: &str = "git_branch";
+    /// Handled by the chat loop; offered while `[tools.verbosity]` is enabled
+    pub const GET_FULL_OUTPUT: &str = "get_full_output";
+    /// Handled by the chat loop; offered while `[agents.subagents]` is enabled
+    pub const SPAWN_SUBAGENTS: &str = "spawn_subagents";
 
     // Explorer-specific tools
     pub const FILE_METADATA: &str = "file_metadata";
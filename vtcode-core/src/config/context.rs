@@ -49,6 +49,18 @@ pub struct ContextFeaturesConfig {
     pub trim_to_percent: u8,
     #[serde(default = "default_preserve_recent_turns")]
     pub preserve_recent_turns: usize,
+    /// Summaries of messages trimmed from the conversation
+    #[serde(default)]
+    pub summarization: SummarizationConfig,
+    /// External sources consulted for context on each request
+    #[serde(default)]
+    pub providers: ContextProvidersConfig,
+    /// Correct token estimates per model with the usage providers report
+    #[serde(default = "default_calibrate_tokens")]
+    pub calibrate_tokens: bool,
+    /// Large tool results kept on disk instead of in memory
+    #[serde(default)]
+    pub spill: ToolResultSpillConfig,
 }
 
 impl Default for ContextFeaturesConfig {
@@ -58,10 +70,18 @@ impl Default for ContextFeaturesConfig {
             max_context_tokens: default_max_context_tokens(),
             trim_to_percent: default_trim_to_percent(),
             preserve_recent_turns: default_preserve_recent_turns(),
+            summarization: SummarizationConfig::default(),
+            providers: ContextProvidersConfig::default(),
+            calibrate_tokens: default_calibrate_tokens(),
+            spill: ToolResultSpillConfig::default(),
         }
     }
 }
 
+fn default_calibrate_tokens() -> bool {
+    true
+}
+
 fn default_max_context_tokens() -> usize {
     context_defaults::DEFAULT_MAX_TOKENS
 }
@@ -73,3 +93,201 @@ fn default_trim_to_percent() -> u8 {
 fn default_preserve_recent_turns() -> usize {
     context_defaults::DEFAULT_PRESERVE_RECENT_TURNS
 }
+
+/// How much a history summary keeps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SummaryDetail {
+    /// Goals, decisions and outcomes
+    #[default]
+    Brief,
+    /// Also files touched, commands run and open questions
+    Detailed,
+}
+
+/// Summaries of trimmed history (`[context.summarization]`)
+///
+/// When the conversation outgrows the context window, the oldest messages are
+/// dropped. With summarization enabled they are first condensed into a running
+/// summary that stays in the system prompt. A cheaper hosted model or a local
+/// one can write the summary; the main model takes over when it fails.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SummarizationConfig {
+    /// Summarize messages before they are trimmed
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Provider for summaries; defaults to the session's provider
+    #[serde(default)]
+    pub provider: Option<String>,
+
+    /// Model for summaries; defaults to the session's model. Required when
+    /// `provider` is set.
+    #[serde(default)]
+    pub model: Option<String>,
+
+    /// Endpoint of the summarization provider, e.g. a local Ollama or LM Studio
+    /// server at `http://localhost:11434/v1`
+    #[serde(default)]
+    pub base_url: Option<String>,
+
+    /// Environment variable holding the summarization provider's API key;
+    /// defaults to the provider's usual variable
+    #[serde(default)]
+    pub api_key_env: Option<String>,
+
+    /// Upper bound on the summary's length in words
+    #[serde(default = "default_max_summary_words")]
+    pub max_words: usize,
+
+    #[serde(default)]
+    pub detail: SummaryDetail,
+
+    #[serde(default = "default_summary_temperature")]
+    pub temperature: f32,
+
+    /// Retry with the main model when the summarization model fails
+    #[serde(default = "default_fallback_to_main")]
+    pub fallback_to_main: bool,
+}
+
+impl Default for SummarizationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            provider: None,
+            model: None,
+            base_url: None,
+            api_key_env: None,
+            max_words: default_max_summary_words(),
+            detail: SummaryDetail::default(),
+            temperature: default_summary_temperature(),
+            fallback_to_main: default_fallback_to_main(),
+        }
+    }
+}
+
+fn default_max_summary_words() -> usize {
+    250
+}
+
+fn default_summary_temperature() -> f32 {
+    0.2
+}
+
+fn default_fallback_to_main() -> bool {
+    true
+}
+
+/// Context providers, under `[context.providers]`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ContextProvidersConfig {
+    #[serde(default = "default_providers_enabled")]
+    pub enabled: bool,
+
+    /// Token budget for all provider context added to one request
+    #[serde(default = "default_providers_max_tokens")]
+    pub max_tokens: usize,
+
+    /// Token budget for the context of a single provider
+    #[serde(default = "default_providers_max_tokens_per_provider")]
+    pub max_tokens_per_provider: usize,
+
+    /// How long results are reused for the same query; 0 disables caching
+    #[serde(default = "default_providers_cache_ttl_seconds")]
+    pub cache_ttl_seconds: u64,
+
+    /// Providers that take longer are skipped for that request
+    #[serde(default = "default_providers_timeout_seconds")]
+    pub timeout_seconds: u64,
+
+    /// Programs run with the query on stdin, whose output becomes context
+    #[serde(default)]
+    pub command: Vec<CommandContextProviderConfig>,
+}
+
+impl Default for ContextProvidersConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_providers_enabled(),
+            max_tokens: default_providers_max_tokens(),
+            max_tokens_per_provider: default_providers_max_tokens_per_provider(),
+            cache_ttl_seconds: default_providers_cache_ttl_seconds(),
+            timeout_seconds: default_providers_timeout_seconds(),
+            command: Vec::new(),
+        }
+    }
+}
+
+/// A `[[context.providers.command]]` entry
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CommandContextProviderConfig {
+    /// Label shown with the provider's context
+    pub name: String,
+
+    /// Program and arguments, run from the workspace root
+    pub command: Vec<String>,
+}
+
+fn default_providers_enabled() -> bool {
+    true
+}
+
+fn default_providers_max_tokens() -> usize {
+    2_000
+}
+
+fn default_providers_max_tokens_per_provider() -> usize {
+    1_000
+}
+
+fn default_providers_cache_ttl_seconds() -> u64 {
+    300
+}
+
+fn default_providers_timeout_seconds() -> u64 {
+    5
+}
+
+/// Spilling large tool results to disk (`[context.spill]`)
+///
+/// Tool results larger than `threshold_bytes` are written to a temporary
+/// directory for the session and replaced in the conversation by a reference
+/// with a short preview. They are read back when a request is sent and when
+/// the session is archived, so long sessions with big search results or file
+/// reads keep a bounded memory footprint.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ToolResultSpillConfig {
+    #[serde(default = "default_spill_enabled")]
+    pub enabled: bool,
+
+    /// Tool results larger than this many bytes are spilled
+    #[serde(default = "default_spill_threshold_bytes")]
+    pub threshold_bytes: usize,
+
+    /// Characters of a spilled result kept in memory as a preview
+    #[serde(default = "default_spill_preview_chars")]
+    pub preview_chars: usize,
+}
+
+impl Default for ToolResultSpillConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_spill_enabled(),
+            threshold_bytes: default_spill_threshold_bytes(),
+            preview_chars: default_spill_preview_chars(),
+        }
+    }
+}
+
+fn default_spill_enabled() -> bool {
+    true
+}
+
+fn default_spill_threshold_bytes() -> usize {
+    32 * 1024
+}
+
+fn default_spill_preview_chars() -> usize {
+    500
+}
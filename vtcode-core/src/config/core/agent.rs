@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 /// Agent-wide configuration
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct AgentConfig {
-    /// AI provider for single agent mode (gemini, openai, anthropic, openrouter, xai)
+    /// AI provider for single agent mode (gemini, openai, anthropic, openrouter, xai, ollama)
     #[serde(default = "default_provider")]
     pub provider: String,
 
@@ -13,6 +13,10 @@ pub struct AgentConfig {
     #[serde(default = "default_model")]
     pub default_model: String,
 
+    /// Endpoint override for the provider, e.g. the address of an Ollama server
+    #[serde(default)]
+    pub base_url: Option<String>,
+
     /// UI theme identifier controlling ANSI styling
     #[serde(default = "default_theme")]
     pub theme: String,
@@ -57,6 +61,21 @@ pub struct AgentConfig {
     /// Maximum bytes of AGENTS.md content to load from project hierarchy
     #[serde(default = "default_project_doc_max_bytes")]
     pub project_doc_max_bytes: usize,
+
+    #[serde(default)]
+    pub locale: AgentLocaleConfig,
+
+    #[serde(default)]
+    pub pin: AgentPinConfig,
+
+    #[serde(default)]
+    pub idle: AgentIdleConfig,
+
+    #[serde(default)]
+    pub dual_answer: AgentDualAnswerConfig,
+
+    #[serde(default)]
+    pub reproducibility: AgentReproducibilityConfig,
 }
 
 impl Default for AgentConfig {
@@ -64,6 +83,7 @@ impl Default for AgentConfig {
         Self {
             provider: default_provider(),
             default_model: default_model(),
+            base_url: None,
             theme: default_theme(),
             ui_surface: UiSurfacePreference::default(),
             max_conversation_turns: default_max_conversation_turns(),
@@ -75,6 +95,11 @@ impl Default for AgentConfig {
             refine_prompts_model: String::new(),
             onboarding: AgentOnboardingConfig::default(),
             project_doc_max_bytes: default_project_doc_max_bytes(),
+            locale: AgentLocaleConfig::default(),
+            pin: AgentPinConfig::default(),
+            idle: AgentIdleConfig::default(),
+            dual_answer: AgentDualAnswerConfig::default(),
+            reproducibility: AgentReproducibilityConfig::default(),
         }
     }
 }
@@ -115,6 +140,155 @@ fn default_project_doc_max_bytes() -> usize {
     project_doc::DEFAULT_MAX_BYTES
 }
 
+/// Response language, under `[agent.locale]`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AgentLocaleConfig {
+    /// BCP 47 language tag for explanations, e.g. "ja"; `/lang` changes it per conversation
+    #[serde(default = "default_locale_language")]
+    pub language: String,
+
+    /// Also show built-in UI strings in this language when a translation exists
+    #[serde(default = "default_localize_ui")]
+    pub localize_ui: bool,
+}
+
+impl Default for AgentLocaleConfig {
+    fn default() -> Self {
+        Self {
+            language: default_locale_language(),
+            localize_ui: default_localize_ui(),
+        }
+    }
+}
+
+fn default_locale_language() -> String {
+    "en".to_string()
+}
+
+fn default_localize_ui() -> bool {
+    true
+}
+
+/// Workspace model pin, under `[agent.pin]`
+///
+/// Only honored in a workspace `vtcode.toml`. A pinned provider and
+/// `default_model` are checked against `docs/models.json` at startup, and
+/// `--model`/`--provider` may replace them only with `--override-pin`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AgentPinConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Smallest context window, in tokens, the pinned model must offer
+    #[serde(default)]
+    pub min_context_tokens: Option<usize>,
+
+    /// Refuse pinned models that cannot call tools
+    #[serde(default = "default_pin_require_tool_calls")]
+    pub require_tool_calls: bool,
+}
+
+impl Default for AgentPinConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_context_tokens: None,
+            require_tool_calls: default_pin_require_tool_calls(),
+        }
+    }
+}
+
+fn default_pin_require_tool_calls() -> bool {
+    true
+}
+
+/// Idle suspend, under `[agent.idle]`
+///
+/// After `minutes` without input the chat UI stops redrawing, cached file
+/// contents and indexes are dropped and pooled provider connections are
+/// closed. The next keypress resumes the session; caches and connections are
+/// rebuilt as they are needed.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AgentIdleConfig {
+    #[serde(default = "default_idle_enabled")]
+    pub enabled: bool,
+
+    /// Minutes without input before the session is suspended
+    #[serde(default = "default_idle_minutes")]
+    pub minutes: u64,
+}
+
+impl Default for AgentIdleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_idle_enabled(),
+            minutes: default_idle_minutes(),
+        }
+    }
+}
+
+fn default_idle_enabled() -> bool {
+    true
+}
+
+fn default_idle_minutes() -> u64 {
+    30
+}
+
+/// Second opinions, under `[agent.dual_answer]`
+///
+/// Each prompt is also sent to `model`, concurrently with the main request and
+/// without tools. Once the main model answers, both answers are shown and the
+/// user picks the one that enters the conversation. `/dual` toggles the mode
+/// for the running session.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct AgentDualAnswerConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Provider of the second model (empty = the session's provider)
+    #[serde(default)]
+    pub provider: String,
+
+    /// Model asked for the second answer
+    #[serde(default)]
+    pub model: String,
+
+    #[serde(default)]
+    pub layout: DualAnswerLayout,
+}
+
+/// Reproducible runs, under `[agent.reproducibility]`
+///
+/// Every request of a turn carries that turn's seed, derived from `seed` and
+/// the prompt, and uses `temperature`. The seeds are recorded in the session
+/// archive so `vtcode prompts diff` replays with the same ones. Providers
+/// without seed support (Anthropic) only get the temperature.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct AgentReproducibilityConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Base seed; a random one is picked and shown for each session when unset
+    #[serde(default)]
+    pub seed: Option<u64>,
+
+    /// Temperature forced on every request
+    #[serde(default)]
+    pub temperature: f32,
+}
+
+/// How the second answer is shown next to the main one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DualAnswerLayout {
+    /// Two columns, main answer on the left
+    #[default]
+    SideBySide,
+    /// The first lines of the second answer, expanded on request
+    Collapsed,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct AgentOnboardingConfig {
     /// Toggle onboarding message rendering
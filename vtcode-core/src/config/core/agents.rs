@@ -0,0 +1,151 @@
+use crate::config::constants::tools;
+use serde::{Deserialize, Serialize};
+
+/// Agents the session's model can hand work to (`[agents]`)
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct AgentsConfig {
+    /// Scoped child agents started through the `spawn_subagents` tool
+    #[serde(default)]
+    pub subagents: SubagentsConfig,
+}
+
+/// Scoped child agents (`[agents.subagents]`)
+///
+/// Off by default. When enabled, the model gets a `spawn_subagents` tool that
+/// hands tasks to the agents defined here. Each child runs on its own model
+/// with its own tool allowlist and context window, sees only its task, and
+/// answers with a report; the reports of one call come back together as the
+/// tool result. Children never prompt: a tool outside their allowlist, or one
+/// whose policy would ask first, is refused to them.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SubagentsConfig {
+    /// Offer the `spawn_subagents` tool
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Children of one call running at the same time
+    #[serde(default = "default_max_parallel")]
+    pub max_parallel: usize,
+
+    /// Agents the model can choose from; an explorer and a test writer when
+    /// not set
+    #[serde(default = "default_definitions")]
+    pub definitions: Vec<SubagentDefinition>,
+}
+
+impl Default for SubagentsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_parallel: default_max_parallel(),
+            definitions: default_definitions(),
+        }
+    }
+}
+
+/// One child agent (`[[agents.subagents.definitions]]`)
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct SubagentDefinition {
+    /// Name the model uses to pick the agent, e.g. `explorer`
+    pub name: String,
+
+    /// When to use the agent, shown to the model
+    #[serde(default)]
+    pub description: String,
+
+    /// Model id; empty uses the session's model
+    #[serde(default)]
+    pub model: String,
+
+    /// Provider of `model`; empty infers it from the model id, falling back
+    /// to the session's provider
+    #[serde(default)]
+    pub provider: String,
+
+    /// Tools the agent may call; `"*"` allows every tool and an empty list
+    /// none
+    #[serde(default)]
+    pub tools: Vec<String>,
+
+    /// Approximate tokens of conversation the agent keeps; the oldest tool
+    /// results are dropped beyond it
+    #[serde(default = "default_context_tokens")]
+    pub context_tokens: usize,
+
+    /// Model responses before the agent must report
+    #[serde(default = "default_max_turns")]
+    pub max_turns: usize,
+
+    /// Added to the agent's system prompt
+    #[serde(default)]
+    pub instructions: String,
+}
+
+impl SubagentDefinition {
+    /// Whether the agent may call `tool`.
+    pub fn allows(&self, tool: &str) -> bool {
+        self.tools
+            .iter()
+            .any(|allowed| allowed == tools::WILDCARD_ALL || allowed == tool)
+    }
+}
+
+fn default_max_parallel() -> usize {
+    3
+}
+
+fn default_context_tokens() -> usize {
+    32_000
+}
+
+fn default_max_turns() -> usize {
+    12
+}
+
+fn default_definitions() -> Vec<SubagentDefinition> {
+    let names = |list: &[&str]| list.iter().map(|name| name.to_string()).collect();
+    vec![
+        SubagentDefinition {
+            name: "explorer".to_string(),
+            description: "Reads and searches the workspace to answer a question about the code; \
+                          cannot change anything"
+                .to_string(),
+            model: String::new(),
+            provider: String::new(),
+            tools: names(&[
+                tools::READ_FILE,
+                tools::LIST_FILES,
+                tools::GREP_SEARCH,
+                tools::AST_GREP_SEARCH,
+                tools::CALL_GRAPH,
+                tools::GIT_STATUS,
+                tools::GIT_DIFF,
+                tools::GIT_LOG,
+            ]),
+            context_tokens: default_context_tokens(),
+            max_turns: default_max_turns(),
+            instructions: "Investigate, then report what you found with file paths and line \
+                           numbers. Do not propose edits unless asked."
+                .to_string(),
+        },
+        SubagentDefinition {
+            name: "test-writer".to_string(),
+            description: "Writes or extends tests for given code and runs them".to_string(),
+            model: String::new(),
+            provider: String::new(),
+            tools: names(&[
+                tools::READ_FILE,
+                tools::LIST_FILES,
+                tools::GREP_SEARCH,
+                tools::WRITE_FILE,
+                tools::EDIT_FILE,
+                tools::RUN_TERMINAL_CMD,
+            ]),
+            context_tokens: default_context_tokens(),
+            max_turns: 20,
+            instructions: "Follow the test layout and style already in the project. Run the \
+                           tests you write and report the files changed and the result."
+                .to_string(),
+        },
+    ]
+}
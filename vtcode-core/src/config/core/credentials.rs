@@ -0,0 +1,176 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Named API key profiles, keyed by provider name (`[credentials.openai]`).
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct CredentialsConfig {
+    #[serde(flatten)]
+    pub providers: BTreeMap<String, ProviderCredentials>,
+}
+
+impl CredentialsConfig {
+    /// Profiles configured for `provider`, if any.
+    pub fn for_provider(&self, provider: &str) -> Option<&ProviderCredentials> {
+        self.providers
+            .get(&provider.to_lowercase())
+            .filter(|credentials| !credentials.profiles.is_empty())
+    }
+
+    /// OAuth settings configured for `provider`, if any.
+    pub fn oauth_for(&self, provider: &str) -> Option<&OAuthSettings> {
+        self.providers
+            .get(&provider.to_lowercase())
+            .and_then(|credentials| credentials.oauth.as_ref())
+    }
+}
+
+/// Credential profiles for a single provider.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProviderCredentials {
+    /// Profile used first in this workspace; defaults to the first listed profile
+    #[serde(default)]
+    pub active: Option<String>,
+
+    /// Move on to the next profile when a key reports an exhausted quota
+    #[serde(default = "default_rotate_on_quota")]
+    pub rotate_on_quota: bool,
+
+    /// Profiles in rotation order
+    #[serde(default)]
+    pub profiles: Vec<CredentialProfile>,
+
+    /// Sign in with an OAuth device flow instead of using API keys
+    #[serde(default)]
+    pub oauth: Option<OAuthSettings>,
+}
+
+impl Default for ProviderCredentials {
+    fn default() -> Self {
+        Self {
+            active: None,
+            rotate_on_quota: default_rotate_on_quota(),
+            profiles: Vec::new(),
+            oauth: None,
+        }
+    }
+}
+
+/// One API key and where to read it from.
+///
+/// Sources are tried in order: `api_key_env`, `keychain`, then `api_key`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CredentialProfile {
+    /// Name shown in logs and used by `active`
+    pub name: String,
+
+    /// Environment variable holding the key
+    #[serde(default)]
+    pub api_key_env: Option<String>,
+
+    /// Entry in the system keychain holding the key
+    #[serde(default)]
+    pub keychain: Option<KeychainEntry>,
+
+    /// Key stored inline (prefer `api_key_env` or `keychain`)
+    #[serde(default)]
+    pub api_key: Option<String>,
+}
+
+/// A generic password in the macOS Keychain or the Secret Service on Linux.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct KeychainEntry {
+    #[serde(default = "default_keychain_service")]
+    pub service: String,
+    pub account: String,
+}
+
+/// OAuth 2.0 device authorization grant (RFC 8628) for a provider or gateway.
+///
+/// After `vtcode auth login --provider <name>`, the stored access token is sent
+/// wherever the provider client would send its API key and is refreshed before
+/// it expires.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OAuthSettings {
+    pub client_id: String,
+
+    /// Endpoint that issues device and user codes
+    pub device_authorization_url: String,
+
+    /// Endpoint that exchanges device codes and refresh tokens for access tokens
+    pub token_url: String,
+
+    #[serde(default)]
+    pub scopes: Vec<String>,
+
+    /// Audience parameter some identity providers require
+    #[serde(default)]
+    pub audience: Option<String>,
+
+    /// Refresh the access token this many seconds before it expires
+    #[serde(default = "default_refresh_margin_secs")]
+    pub refresh_margin_secs: u64,
+}
+
+fn default_refresh_margin_secs() -> u64 {
+    60
+}
+
+fn default_rotate_on_quota() -> bool {
+    true
+}
+
+fn default_keychain_service() -> String {
+    "vtcode".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_provider_tables() {
+        let config: CredentialsConfig = toml::from_str(
+            r#"
+            [openai]
+            active = "team-b"
+
+            [[openai.profiles]]
+            name = "team-a"
+            api_key_env = "OPENAI_API_KEY_TEAM_A"
+
+            [[openai.profiles]]
+            name = "team-b"
+            keychain = { account = "openai-team-b" }
+            "#,
+        )
+        .expect("credentials");
+
+        let openai = config.for_provider("OpenAI").expect("openai profiles");
+        assert_eq!(openai.active.as_deref(), Some("team-b"));
+        assert!(openai.rotate_on_quota);
+        assert_eq!(openai.profiles.len(), 2);
+        let keychain = openai.profiles[1].keychain.as_ref().expect("keychain");
+        assert_eq!(keychain.service, "vtcode");
+        assert!(config.for_provider("gemini").is_none());
+        assert!(config.oauth_for("openai").is_none());
+    }
+
+    #[test]
+    fn parses_oauth_without_profiles() {
+        let config: CredentialsConfig = toml::from_str(
+            r#"
+            [anthropic.oauth]
+            client_id = "vtcode-cli"
+            device_authorization_url = "https://login.example.com/oauth/device/code"
+            token_url = "https://login.example.com/oauth/token"
+            scopes = ["offline_access", "models:invoke"]
+            "#,
+        )
+        .expect("credentials");
+
+        let oauth = config.oauth_for("Anthropic").expect("oauth settings");
+        assert_eq!(oauth.client_id, "vtcode-cli");
+        assert_eq!(oauth.refresh_margin_secs, 60);
+        assert!(config.for_provider("anthropic").is_none());
+    }
+}
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+/// Background daemon settings (`[daemon]`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DaemonConfig {
+    /// Let chat sessions use the startup checks and symbol index of a running
+    /// `vtcode daemon`
+    #[serde(default = "default_attach")]
+    pub attach: bool,
+
+    /// How long a chat session waits for the daemon before starting cold, in
+    /// milliseconds
+    #[serde(default = "default_connect_timeout_ms")]
+    pub connect_timeout_ms: u64,
+
+    /// How often the daemon refreshes the workspace language summary, provider
+    /// reachability and symbol index, in seconds
+    #[serde(default = "default_refresh_interval_secs")]
+    pub refresh_interval_secs: u64,
+}
+
+impl Default for DaemonConfig {
+    fn default() -> Self {
+        Self {
+            attach: default_attach(),
+            connect_timeout_ms: default_connect_timeout_ms(),
+            refresh_interval_secs: default_refresh_interval_secs(),
+        }
+    }
+}
+
+fn default_attach() -> bool {
+    true
+}
+
+fn default_connect_timeout_ms() -> u64 {
+    150
+}
+
+fn default_refresh_interval_secs() -> u64 {
+    60
+}
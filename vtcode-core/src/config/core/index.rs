@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+/// Workspace indexing settings (`[index]`)
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct IndexConfig {
+    /// Generated and vendored paths left out of search, indexing and listings
+    #[serde(default)]
+    pub exclude: IndexExcludeConfig,
+}
+
+/// Exclusion profiles (`[index.exclude]`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct IndexExcludeConfig {
+    /// Apply the exclusions at all
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+
+    /// Built-in ecosystem profiles: `rust`, `node`, `python`, `go`, `jvm`,
+    /// `dotnet`, `protobuf`, or `auto` to pick them from the workspace manifests
+    #[serde(default = "default_profiles")]
+    pub profiles: Vec<String>,
+
+    /// Extra patterns: `dir/` for a directory name anywhere, `*.ext` for a file
+    /// name anywhere, or a glob with `/` relative to the workspace root
+    #[serde(default)]
+    pub patterns: Vec<String>,
+}
+
+impl Default for IndexExcludeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            profiles: default_profiles(),
+            patterns: Vec::new(),
+        }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_profiles() -> Vec<String> {
+    vec!["auto".to_string()]
+}
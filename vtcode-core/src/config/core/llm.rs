@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+
+/// Provider client settings shared by every session.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct LlmConfig {
+    /// Ordered fallback providers used when the session provider fails.
+    #[serde(default)]
+    pub failover: FailoverConfig,
+
+    /// Token spend tracking and budgets.
+    #[serde(default)]
+    pub spend: SpendConfig,
+}
+
+/// Failover from the session provider to other providers (`[llm.failover]`).
+///
+/// Off by default. When enabled, a request that fails with HTTP 429, a 5xx
+/// status, a network error or a timeout is retried with the next provider in
+/// `providers`. A provider that fails `failure_threshold` times in a row is
+/// skipped for `cooldown_secs`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FailoverConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Providers tried after the session provider, in order.
+    #[serde(default)]
+    pub providers: Vec<FailoverTargetConfig>,
+
+    /// Seconds an attempt may take before the next provider is tried; 0 waits indefinitely.
+    #[serde(default = "default_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+
+    /// Consecutive failures after which a provider is marked unhealthy.
+    #[serde(default = "default_failure_threshold")]
+    pub failure_threshold: u32,
+
+    /// Seconds an unhealthy provider is skipped before it is tried again.
+    #[serde(default = "default_cooldown_secs")]
+    pub cooldown_secs: u64,
+}
+
+impl Default for FailoverConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            providers: Vec::new(),
+            request_timeout_secs: default_request_timeout_secs(),
+            failure_threshold: default_failure_threshold(),
+            cooldown_secs: default_cooldown_secs(),
+        }
+    }
+}
+
+/// One fallback provider and the model requested from it.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FailoverTargetConfig {
+    /// Provider name, e.g. "anthropic" or "openrouter"
+    pub provider: String,
+
+    /// Model requested from this provider
+    pub model: String,
+
+    /// Base URL override, for self-hosted or proxied endpoints
+    #[serde(default)]
+    pub base_url: Option<String>,
+
+    /// Environment variable holding the API key; the provider's usual key otherwise
+    #[serde(default)]
+    pub api_key_env: Option<String>,
+}
+
+/// Spend tracking and budgets (`[llm.spend]`).
+///
+/// Token usage is priced from the model catalog and totalled per provider for
+/// the session and the day. A limit of 0 disables that budget.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SpendConfig {
+    #[serde(default = "default_spend_enabled")]
+    pub enabled: bool,
+
+    /// Estimated USD a single session may spend.
+    #[serde(default)]
+    pub session_usd: f64,
+
+    /// Estimated USD all sessions may spend per local calendar day.
+    #[serde(default)]
+    pub daily_usd: f64,
+
+    /// What happens once a budget is used up: "prompt" or "abort".
+    #[serde(default)]
+    pub on_exceeded: BudgetAction,
+}
+
+impl Default for SpendConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_spend_enabled(),
+            session_usd: 0.0,
+            daily_usd: 0.0,
+            on_exceeded: BudgetAction::default(),
+        }
+    }
+}
+
+/// Reaction to a request that would start past a spend budget.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetAction {
+    /// Ask once whether to keep going for the rest of the session.
+    #[default]
+    Prompt,
+    /// Refuse further requests.
+    Abort,
+}
+
+fn default_spend_enabled() -> bool {
+    true
+}
+
+fn default_request_timeout_secs() -> u64 {
+    120
+}
+
+fn default_failure_threshold() -> u32 {
+    2
+}
+
+fn default_cooldown_secs() -> u64 {
+    60
+}
@@ -1,17 +1,47 @@
 pub mod agent;
+pub mod agents;
 pub mod automation;
 pub mod commands;
+pub mod credentials;
+pub mod daemon;
+pub mod index;
+pub mod llm;
+pub mod network;
+pub mod offline;
 pub mod prompt_cache;
+pub mod refusal;
 pub mod security;
+pub mod sessions;
 pub mod tools;
 
-pub use agent::{AgentConfig, AgentOnboardingConfig};
+pub use agent::{
+    AgentConfig, AgentDualAnswerConfig, AgentIdleConfig, AgentLocaleConfig, AgentOnboardingConfig,
+    AgentPinConfig, AgentReproducibilityConfig, DualAnswerLayout,
+};
+pub use agents::{AgentsConfig, SubagentDefinition, SubagentsConfig};
 pub use automation::{AutomationConfig, FullAutoConfig};
 pub use commands::CommandsConfig;
+pub use credentials::{
+    CredentialProfile, CredentialsConfig, KeychainEntry, OAuthSettings, ProviderCredentials,
+};
+pub use daemon::DaemonConfig;
+pub use index::{IndexConfig, IndexExcludeConfig};
+pub use llm::{BudgetAction, FailoverConfig, FailoverTargetConfig, LlmConfig, SpendConfig};
+pub use network::{NetworkConfig, ProviderNetworkConfig};
+pub use offline::{LocalModelConfig, OfflineConfig};
 pub use prompt_cache::{
     AnthropicPromptCacheSettings, DeepSeekPromptCacheSettings, GeminiPromptCacheMode,
     GeminiPromptCacheSettings, OpenAIPromptCacheSettings, OpenRouterPromptCacheSettings,
-    PromptCachingConfig, ProviderPromptCachingConfig, XAIPromptCacheSettings,
+    PromptCacheWarmupConfig, PromptCachingConfig, ProviderPromptCachingConfig,
+    XAIPromptCacheSettings,
+};
+pub use refusal::{RefusalConfig, RefusalRetry};
+pub use security::{GuardrailsConfig, PromptInjectionConfig, RedactionConfig, SecurityConfig};
+pub use sessions::SessionsConfig;
+pub use tools::{
+    BootstrapConfig, CodeExecutionConfig, ConcurrencyConfig, ConstrainedDecodingConfig,
+    CoverageCommand, CoverageConfig, EmbeddingProvider, ExposureConfig, ImportsConfig, PathDisplay,
+    SemanticSearchConfig, SqlConfig, SqlDatabaseConfig, SqlEngine, ToolMockEntry, ToolMocksConfig,
+    ToolPolicy, ToolSchemaConfig, ToolStatsConfig, ToolVerbosityConfig, ToolsConfig,
+    VerbosityPolicy, VulnerabilityScanConfig, WorkspaceEnvConfig, WriteQuotaConfig,
 };
-pub use security::SecurityConfig;
-pub use tools::{ToolPolicy, ToolsConfig};
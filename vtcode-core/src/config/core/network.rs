@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Proxy and TLS settings for outgoing HTTP requests (`[network]`)
+///
+/// The top-level settings apply to every client vtcode builds: providers,
+/// OAuth sign-in and the curl tool. Entries under `[network.providers.<name>]`
+/// replace them for one provider. Without a proxy here, the usual
+/// `HTTPS_PROXY`, `HTTP_PROXY` and `NO_PROXY` variables apply.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct NetworkConfig {
+    /// Proxy URL for all requests, e.g. "http://proxy.corp.example:3128"
+    #[serde(default)]
+    pub proxy: Option<String>,
+
+    /// Hosts that bypass `proxy`, in `NO_PROXY` syntax
+    #[serde(default)]
+    pub no_proxy: Vec<String>,
+
+    /// PEM files with extra root certificates, e.g. a corporate CA bundle
+    #[serde(default)]
+    pub ca_certificates: Vec<PathBuf>,
+
+    /// Skip certificate verification entirely. Insecure; for debugging only.
+    #[serde(default)]
+    pub danger_accept_invalid_certs: bool,
+
+    /// Overrides per provider name, e.g. `[network.providers.anthropic]`
+    #[serde(default)]
+    pub providers: BTreeMap<String, ProviderNetworkConfig>,
+}
+
+/// Proxy and TLS overrides for one provider. Unset fields fall back to the
+/// top-level `[network]` settings.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ProviderNetworkConfig {
+    #[serde(default)]
+    pub proxy: Option<String>,
+
+    /// Root certificates used in addition to the top-level ones
+    #[serde(default)]
+    pub ca_certificates: Vec<PathBuf>,
+
+    #[serde(default)]
+    pub danger_accept_invalid_certs: Option<bool>,
+}
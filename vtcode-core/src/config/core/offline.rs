@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+
+/// Offline detection and the local assistant fallback.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OfflineConfig {
+    /// Probe the provider endpoint at startup and fall back to local mode when unreachable.
+    #[serde(default = "default_detect")]
+    pub detect: bool,
+
+    /// Start in local assistant mode without probing the network.
+    #[serde(default)]
+    pub force: bool,
+
+    /// Connection timeout for the reachability probe, in milliseconds.
+    #[serde(default = "default_probe_timeout_ms")]
+    pub probe_timeout_ms: u64,
+
+    /// Optional locally hosted model used while offline.
+    #[serde(default)]
+    pub local_model: Option<LocalModelConfig>,
+}
+
+impl Default for OfflineConfig {
+    fn default() -> Self {
+        Self {
+            detect: default_detect(),
+            force: false,
+            probe_timeout_ms: default_probe_timeout_ms(),
+            local_model: None,
+        }
+    }
+}
+
+/// A model served on the local machine, e.g. through Ollama or LM Studio.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LocalModelConfig {
+    /// Provider protocol spoken by the local server (usually "openai")
+    #[serde(default = "default_local_provider")]
+    pub provider: String,
+
+    /// Model identifier understood by the local server
+    pub model: String,
+
+    /// Base URL of the local server
+    #[serde(default = "default_local_base_url")]
+    pub base_url: String,
+
+    /// Environment variable holding an API key, for servers that require one
+    #[serde(default)]
+    pub api_key_env: Option<String>,
+}
+
+fn default_detect() -> bool {
+    true
+}
+
+fn default_probe_timeout_ms() -> u64 {
+    1500
+}
+
+fn default_local_provider() -> String {
+    "openai".to_string()
+}
+
+fn default_local_base_url() -> String {
+    "http://localhost:11434/v1".to_string()
+}
@@ -32,6 +32,10 @@ pub struct PromptCachingConfig {
     /// Provider specific overrides
     #[serde(default)]
     pub providers: ProviderPromptCachingConfig,
+
+    /// Cache-warming request sent when a chat session starts
+    #[serde(default)]
+    pub warmup: PromptCacheWarmupConfig,
 }
 
 impl Default for PromptCachingConfig {
@@ -44,6 +48,7 @@ impl Default for PromptCachingConfig {
             enable_auto_cleanup: default_auto_cleanup(),
             min_quality_threshold: default_min_quality_threshold(),
             providers: ProviderPromptCachingConfig::default(),
+            warmup: PromptCacheWarmupConfig::default(),
         }
     }
 }
@@ -59,6 +64,40 @@ impl PromptCachingConfig {
     }
 }
 
+/// Session-start cache warming
+///
+/// Sends the stable request prefix (system prompt and tool schemas) with a
+/// one-token completion so the first real turn reads it from the provider
+/// cache instead of paying to write it.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PromptCacheWarmupConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Skip warming when the prefix is shorter than providers will cache
+    #[serde(default = "default_warmup_min_prefix_tokens")]
+    pub min_prefix_tokens: u32,
+
+    /// Skip warming when the prefix is larger than this many tokens
+    #[serde(default = "default_warmup_max_prefix_tokens")]
+    pub max_prefix_tokens: u32,
+
+    /// Give up on the warming request after this long
+    #[serde(default = "default_warmup_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+impl Default for PromptCacheWarmupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_true(),
+            min_prefix_tokens: default_warmup_min_prefix_tokens(),
+            max_prefix_tokens: default_warmup_max_prefix_tokens(),
+            timeout_ms: default_warmup_timeout_ms(),
+        }
+    }
+}
+
 /// Per-provider configuration overrides
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ProviderPromptCachingConfig {
@@ -290,6 +329,18 @@ fn default_true() -> bool {
     true
 }
 
+fn default_warmup_min_prefix_tokens() -> u32 {
+    prompt_cache::WARMUP_MIN_PREFIX_TOKENS
+}
+
+fn default_warmup_max_prefix_tokens() -> u32 {
+    prompt_cache::WARMUP_MAX_PREFIX_TOKENS
+}
+
+fn default_warmup_timeout_ms() -> u64 {
+    prompt_cache::WARMUP_TIMEOUT_MS
+}
+
 fn default_openai_min_prefix_tokens() -> u32 {
     prompt_cache::OPENAI_MIN_PREFIX_TOKENS
 }
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+/// How the agent reacts when a provider refuses a request on safety grounds.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct RefusalConfig {
+    /// Retry strategy after a refusal: "off", "sanitized" or "fallback".
+    #[serde(default)]
+    pub retry: RefusalRetry,
+
+    /// Provider used for the retry when `retry = "fallback"`.
+    #[serde(default)]
+    pub fallback_provider: Option<String>,
+
+    /// Model requested from the fallback provider.
+    #[serde(default)]
+    pub fallback_model: Option<String>,
+}
+
+/// Retry strategy applied once per turn after a refusal.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RefusalRetry {
+    /// Report the refusal and leave the request unanswered.
+    #[default]
+    Off,
+    /// Resend the request with this turn's tool output withheld.
+    Sanitized,
+    /// Resend the request to `fallback_provider`.
+    Fallback,
+}
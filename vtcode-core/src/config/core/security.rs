@@ -17,6 +17,18 @@ pub struct SecurityConfig {
     /// when no write tool was executed. Defaults to false for safety.
     #[serde(default)]
     pub auto_apply_detected_patches: bool,
+
+    /// What `/redact` and `vtcode session redact` remove from shared transcripts
+    #[serde(default)]
+    pub redaction: RedactionConfig,
+
+    /// Protected paths and destructive command patterns checked before tools run
+    #[serde(default)]
+    pub guardrails: GuardrailsConfig,
+
+    /// How tool output from the web and third-party files is fenced off
+    #[serde(default)]
+    pub prompt_injection: PromptInjectionConfig,
 }
 
 impl Default for SecurityConfig {
@@ -25,10 +37,195 @@ impl Default for SecurityConfig {
             human_in_the_loop: default_true(),
             require_write_tool_for_claims: default_true(),
             auto_apply_detected_patches: false,
+            redaction: RedactionConfig::default(),
+            guardrails: GuardrailsConfig::default(),
+            prompt_injection: PromptInjectionConfig::default(),
+        }
+    }
+}
+
+/// Transcript redaction rules
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RedactionConfig {
+    /// Replace API keys, tokens, private keys and password assignments
+    #[serde(default = "default_true")]
+    pub secrets: bool,
+
+    /// Replace email addresses
+    #[serde(default = "default_true")]
+    pub emails: bool,
+
+    /// Replace absolute paths; the workspace and home directories become
+    /// `<WORKSPACE>` and `<HOME>` so relative structure stays readable
+    #[serde(default = "default_true")]
+    pub paths: bool,
+
+    /// Additional regular expressions to redact; when a pattern has a capture
+    /// group only the first group is replaced
+    #[serde(default)]
+    pub patterns: Vec<String>,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            secrets: default_true(),
+            emails: default_true(),
+            paths: default_true(),
+            patterns: Vec::new(),
+        }
+    }
+}
+
+/// Static guardrails applied on top of tool policies
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GuardrailsConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Globs for files the agent must not edit; patterns without a `/` match
+    /// file names anywhere in the workspace
+    #[serde(default = "default_protected_paths")]
+    pub protected_paths: Vec<String>,
+
+    /// Regular expressions for terminal commands that are refused
+    #[serde(default = "default_destructive_commands")]
+    pub destructive_commands: Vec<String>,
+
+    /// Regular expressions for terminal commands that may run but could lose
+    /// work; the files they may change are backed up first so they can be undone
+    #[serde(default = "default_backup_commands")]
+    pub backup_commands: Vec<String>,
+
+    /// Let the user run a blocked call by typing `override`; full-auto runs
+    /// and `--skip-confirmations` never override
+    #[serde(default = "default_true")]
+    pub allow_override: bool,
+
+    /// Scan a workspace for files that look like secrets the first time a
+    /// session runs there, and block tools from reading them until allowed
+    #[serde(default = "default_true")]
+    pub sensitive_files: bool,
+}
+
+impl Default for GuardrailsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_true(),
+            protected_paths: default_protected_paths(),
+            destructive_commands: default_destructive_commands(),
+            backup_commands: default_backup_commands(),
+            allow_override: default_true(),
+            sensitive_files: default_true(),
         }
     }
 }
 
+/// Prompt-injection hardening for untrusted tool output
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PromptInjectionConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Tools whose output always comes from outside the workspace
+    #[serde(default = "default_untrusted_tools")]
+    pub untrusted_tools: Vec<String>,
+
+    /// Regular expressions for terminal commands whose output is untrusted
+    #[serde(default = "default_untrusted_commands")]
+    pub untrusted_commands: Vec<String>,
+
+    /// Globs for workspace files whose contents are untrusted; files outside
+    /// the workspace always are
+    #[serde(default = "default_untrusted_paths")]
+    pub untrusted_paths: Vec<String>,
+
+    /// Replace instruction-like lines ("ignore previous instructions", fake
+    /// `system:` turns) inside untrusted content
+    #[serde(default)]
+    pub strip_instructions: bool,
+
+    /// Additional case-insensitive regular expressions for lines to strip
+    #[serde(default)]
+    pub instruction_patterns: Vec<String>,
+
+    /// Tell the model in the system prompt that delimited content is data
+    #[serde(default = "default_true")]
+    pub system_directive: bool,
+}
+
+impl Default for PromptInjectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_true(),
+            untrusted_tools: default_untrusted_tools(),
+            untrusted_commands: default_untrusted_commands(),
+            untrusted_paths: default_untrusted_paths(),
+            strip_instructions: false,
+            instruction_patterns: Vec::new(),
+            system_directive: default_true(),
+        }
+    }
+}
+
+fn default_untrusted_tools() -> Vec<String> {
+    vec!["curl".to_string(), "fetch_docs".to_string()]
+}
+
+fn default_untrusted_commands() -> Vec<String> {
+    vec![r"\b(curl|wget|http|lynx|w3m)\b".to_string()]
+}
+
+fn default_untrusted_paths() -> Vec<String> {
+    ["node_modules/**", "vendor/**", "third_party/**"]
+        .into_iter()
+        .map(str::to_string)
+        .collect()
+}
+
+fn default_protected_paths() -> Vec<String> {
+    [
+        "*.lock",
+        ".env",
+        ".env.*",
+        "secrets/**",
+        ".github/workflows/**",
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .collect()
+}
+
+fn default_destructive_commands() -> Vec<String> {
+    [
+        r"\brm\s+(-\S+\s+)*(/|/\*|~/?|\$HOME/?)(\s|$)",
+        r"\bgit\s+push\b.*\s(--force(-with-lease)?|-f)\b.*\b(main|master)\b",
+        r"\bgit\s+push\b.*\b(main|master)\b.*\s(--force(-with-lease)?|-f)\b",
+        r"\bgit\s+push\b.*\s\+(main|master)\b",
+        r"\bmkfs(\.\w+)?\b",
+        r"\bdd\b.*\bof=/dev/",
+        r":\(\)\s*\{\s*:\s*\|\s*:\s*&\s*\}\s*;\s*:",
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .collect()
+}
+
+fn default_backup_commands() -> Vec<String> {
+    [
+        r"\bgit\s+reset\b.*\s--hard\b",
+        r"\bgit\s+clean\s+(-\S+\s+)*-\S*f",
+        r"\bgit\s+checkout\s+(\S+\s+)?--\s",
+        r"\bgit\s+checkout\s+\.(\s|$)",
+        r"\bgit\s+restore\b",
+        r"\brm\s+(-\S+\s+)*-\S*[rR]",
+        r"\bfind\b.*\s-delete\b",
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .collect()
+}
+
 fn default_true() -> bool {
     true
 }
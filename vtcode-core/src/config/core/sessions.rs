@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+
+/// Compaction of the session archive (`[sessions]`)
+///
+/// Sessions that ended more than `compact_after_days` ago are summarized,
+/// their long tool results cut, and moved gzip-compressed into
+/// `~/.vtcode/sessions/archive/` with an index that `vtcode session search`
+/// reads. `vtcode maintenance` also runs it on demand.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SessionsConfig {
+    /// Compact in the background when a chat starts, at most once a day
+    #[serde(default = "default_auto_compact")]
+    pub auto_compact: bool,
+
+    /// Age in days after which an ended session is compacted
+    #[serde(default = "default_compact_after_days")]
+    pub compact_after_days: u64,
+
+    /// Tool results longer than this many characters are cut when compacting
+    #[serde(default = "default_max_tool_output_chars")]
+    pub max_tool_output_chars: usize,
+}
+
+impl Default for SessionsConfig {
+    fn default() -> Self {
+        Self {
+            auto_compact: default_auto_compact(),
+            compact_after_days: default_compact_after_days(),
+            max_tool_output_chars: default_max_tool_output_chars(),
+        }
+    }
+}
+
+fn default_auto_compact() -> bool {
+    true
+}
+
+fn default_compact_after_days() -> u64 {
+    30
+}
+
+fn default_max_tool_output_chars() -> usize {
+    1000
+}
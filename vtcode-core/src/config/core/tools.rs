@@ -22,6 +22,83 @@ pub struct ToolsConfig {
     ///
     #[serde(default = "default_max_tool_loops")]
     pub max_tool_loops: usize,
+
+    /// Switch textual tool calls to a strict JSON envelope when the provider
+    /// supports JSON mode
+    ///
+    /// Applies once the model falls back to emitting tool calls as text instead
+    /// of native function calls.
+    #[serde(default = "default_json_textual_tools")]
+    pub json_textual_tools: bool,
+
+    /// How paths inside the workspace appear in tool results
+    #[serde(default)]
+    pub path_display: PathDisplay,
+
+    /// Import management for the `organize_imports` tool
+    #[serde(default)]
+    pub imports: ImportsConfig,
+
+    /// Limits on how many tool executions run at the same time
+    #[serde(default)]
+    pub concurrency: ConcurrencyConfig,
+
+    /// Coverage commands for the `coverage_report` tool
+    #[serde(default)]
+    pub coverage: CoverageConfig,
+
+    /// Toolchain checks and installation for the `bootstrap_env` tool
+    #[serde(default)]
+    pub bootstrap: BootstrapConfig,
+
+    /// Development databases for the `sql_query`, `sql_schema` and
+    /// `sql_execute` tools
+    #[serde(default)]
+    pub sql: SqlConfig,
+
+    /// Dependency advisories from cargo-audit, npm audit and pip-audit
+    #[serde(default)]
+    pub vulnerability_scan: VulnerabilityScanConfig,
+
+    /// Embeddings index for the `semantic_search` tool
+    #[serde(default)]
+    pub semantic_search: SemanticSearchConfig,
+
+    /// The provider's hosted code interpreter
+    #[serde(default)]
+    pub code_execution: CodeExecutionConfig,
+
+    /// Size and count limits on file writes
+    #[serde(default)]
+    pub write_quota: WriteQuotaConfig,
+
+    /// Which tools the model sees as the conversation progresses
+    #[serde(default)]
+    pub exposure: ExposureConfig,
+
+    /// Tool usage statistics learned across sessions
+    #[serde(default)]
+    pub stats: ToolStatsConfig,
+
+    /// Smaller tool schemas for models with small context windows
+    #[serde(default)]
+    pub schema: ToolSchemaConfig,
+
+    /// Grammar-constrained tool calls for local and OSS models
+    #[serde(default)]
+    pub constrained_decoding: ConstrainedDecodingConfig,
+
+    /// Variables from the workspace `.env` and `.envrc` for terminal commands
+    #[serde(default)]
+    pub env: WorkspaceEnvConfig,
+
+    /// How much of each tool result is sent to the model
+    #[serde(default)]
+    pub verbosity: ToolVerbosityConfig,
+
+    /// Canned tool responses for demos and tutorials
+    #[serde(default)]
+    pub mocks: ToolMocksConfig,
 }
 
 impl Default for ToolsConfig {
@@ -33,6 +110,703 @@ impl Default for ToolsConfig {
             default_policy: default_tool_policy(),
             policies,
             max_tool_loops: default_max_tool_loops(),
+            json_textual_tools: default_json_textual_tools(),
+            path_display: PathDisplay::default(),
+            imports: ImportsConfig::default(),
+            concurrency: ConcurrencyConfig::default(),
+            coverage: CoverageConfig::default(),
+            bootstrap: BootstrapConfig::default(),
+            sql: SqlConfig::default(),
+            vulnerability_scan: VulnerabilityScanConfig::default(),
+            semantic_search: SemanticSearchConfig::default(),
+            code_execution: CodeExecutionConfig::default(),
+            write_quota: WriteQuotaConfig::default(),
+            exposure: ExposureConfig::default(),
+            stats: ToolStatsConfig::default(),
+            schema: ToolSchemaConfig::default(),
+            constrained_decoding: ConstrainedDecodingConfig::default(),
+            env: WorkspaceEnvConfig::default(),
+            verbosity: ToolVerbosityConfig::default(),
+            mocks: ToolMocksConfig::default(),
+        }
+    }
+}
+
+/// Import management settings (`[tools.imports]`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ImportsConfig {
+    /// Organize imports of each file written by `write_file` or `edit_file`
+    ///
+    /// The automatic pass only adds missing imports and sorts; unused imports
+    /// are left for an explicit `organize_imports` call, since a multi-step
+    /// edit often adds an import before the code that uses it.
+    #[serde(default)]
+    pub organize_on_edit: bool,
+
+    /// Add imports for referenced symbols found in the workspace symbol index
+    #[serde(default = "default_true")]
+    pub add_missing: bool,
+
+    /// Remove imports whose names are no longer referenced
+    #[serde(default = "default_true")]
+    pub remove_unused: bool,
+
+    /// Sort imports within the groups a file already uses
+    #[serde(default = "default_true")]
+    pub sort: bool,
+
+    /// Maximum number of files scanned per language for the symbol index
+    #[serde(default = "default_max_index_files")]
+    pub max_index_files: usize,
+}
+
+impl Default for ImportsConfig {
+    fn default() -> Self {
+        Self {
+            organize_on_edit: false,
+            add_missing: default_true(),
+            remove_unused: default_true(),
+            sort: default_true(),
+            max_index_files: default_max_index_files(),
+        }
+    }
+}
+
+/// Tool concurrency limits (`[tools.concurrency]`)
+///
+/// Executions over a limit wait for a free slot instead of failing; tools that
+/// waited report their queue position in their output.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ConcurrencyConfig {
+    /// Maximum tool executions running at once across all tools
+    #[serde(default = "default_max_parallel")]
+    pub max_parallel: usize,
+
+    /// Read-only calls of one model response (reads, listings, searches) run
+    /// concurrently up to this many at a time; 1 runs them one after another
+    #[serde(default = "default_max_parallel_reads")]
+    pub max_parallel_reads: usize,
+
+    /// Per-tool caps keyed by tool name, e.g. `run_terminal_cmd = 2`
+    #[serde(default)]
+    pub tools: IndexMap<String, usize>,
+
+    /// Caps for terminal commands keyed by command prefix, e.g. `"cargo build" = 1`
+    ///
+    /// A command counts against every prefix it starts with, word by word.
+    #[serde(default)]
+    pub commands: IndexMap<String, usize>,
+}
+
+impl Default for ConcurrencyConfig {
+    fn default() -> Self {
+        let mut commands = IndexMap::new();
+        commands.insert("cargo build".to_string(), 1);
+        Self {
+            max_parallel: default_max_parallel(),
+            max_parallel_reads: default_max_parallel_reads(),
+            tools: IndexMap::new(),
+            commands,
+        }
+    }
+}
+
+/// Coverage settings for the `coverage_report` tool (`[tools.coverage]`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CoverageConfig {
+    /// Seconds a coverage command may run before it is stopped
+    #[serde(default = "default_coverage_timeout_secs")]
+    pub timeout_secs: u64,
+
+    /// Coverage command per language, keyed by `rust`, `python`, `javascript`
+    /// or `typescript`
+    #[serde(default = "default_coverage_languages")]
+    pub languages: IndexMap<String, CoverageCommand>,
+}
+
+impl Default for CoverageConfig {
+    fn default() -> Self {
+        Self {
+            timeout_secs: default_coverage_timeout_secs(),
+            languages: default_coverage_languages(),
+        }
+    }
+}
+
+/// How to collect coverage for one language
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct CoverageCommand {
+    /// Shell command that runs the tests and writes an LCOV report
+    pub command: String,
+
+    /// Path of the LCOV report, relative to the workspace root
+    pub report: String,
+}
+
+impl CoverageCommand {
+    fn new(command: &str, report: &str) -> Self {
+        Self {
+            command: command.to_string(),
+            report: report.to_string(),
+        }
+    }
+}
+
+/// Toolchain bootstrap settings for the `bootstrap_env` tool (`[tools.bootstrap]`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BootstrapConfig {
+    /// Let the tool run the installation commands it proposes when asked to;
+    /// otherwise it only reports them
+    #[serde(default)]
+    pub allow_install: bool,
+
+    /// Seconds a single installation command may run before it is stopped
+    #[serde(default = "default_bootstrap_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+impl Default for BootstrapConfig {
+    fn default() -> Self {
+        Self {
+            allow_install: false,
+            timeout_secs: default_bootstrap_timeout_secs(),
+        }
+    }
+}
+
+/// Provider-hosted code execution (`[tools.code_execution]`)
+///
+/// When enabled, providers that run code on their side (OpenAI's code
+/// interpreter, Gemini's code execution) are offered it alongside the local
+/// tools. Nothing runs on this machine; files the code produces are saved to
+/// `scratch_dir`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CodeExecutionConfig {
+    /// Offer the provider's code interpreter to the model
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Directory, relative to the workspace, that generated files are saved to
+    #[serde(default = "default_code_execution_scratch_dir")]
+    pub scratch_dir: String,
+}
+
+impl Default for CodeExecutionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            scratch_dir: default_code_execution_scratch_dir(),
+        }
+    }
+}
+
+/// Database access for the SQL tools (`[tools.sql]`)
+///
+/// The tools stay hidden from the model until `enabled` is set. Queries run in
+/// read-only sessions; `sql_execute` also needs `allow_writes` on the database.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SqlConfig {
+    /// Expose the SQL tools to the model
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Seconds a statement may run before the client is stopped
+    #[serde(default = "default_sql_timeout_secs")]
+    pub timeout_secs: u64,
+
+    /// Rows returned by a query before the result is truncated
+    #[serde(default = "default_sql_max_rows")]
+    pub max_rows: usize,
+
+    /// Databases keyed by the name the tools refer to them with
+    #[serde(default)]
+    pub databases: IndexMap<String, SqlDatabaseConfig>,
+}
+
+impl Default for SqlConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            timeout_secs: default_sql_timeout_secs(),
+            max_rows: default_sql_max_rows(),
+            databases: IndexMap::new(),
+        }
+    }
+}
+
+/// One database reachable by the SQL tools (`[tools.sql.databases.<name>]`)
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct SqlDatabaseConfig {
+    /// `sqlite`, `postgres` or `mysql`
+    pub engine: SqlEngine,
+
+    /// Connection URL, or for SQLite a file path relative to the workspace
+    #[serde(default)]
+    pub url: Option<String>,
+
+    /// Environment variable holding the URL, so credentials stay out of the file
+    #[serde(default)]
+    pub url_env: Option<String>,
+
+    /// Let `sql_execute` run migrations and other mutations on this database
+    #[serde(default)]
+    pub allow_writes: bool,
+}
+
+/// Database engine, reached through its command-line client
+/// (`sqlite3`, `psql` or `mysql`)
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SqlEngine {
+    Sqlite,
+    #[serde(alias = "postgresql")]
+    Postgres,
+    #[serde(alias = "mariadb")]
+    Mysql,
+}
+
+/// Dependency vulnerability scanning (`[tools.vulnerability_scan]`)
+///
+/// Off by default. When enabled, the `vulnerability_scan` tool runs the audit
+/// tool for each manifest in the workspace (`cargo audit`, `npm audit`,
+/// `pip-audit`) or reads reports they already wrote, and known advisories are
+/// added to the context of each request so the agent avoids vulnerable
+/// versions. Results are cached until a lockfile changes or they grow older
+/// than `refresh_hours`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct VulnerabilityScanConfig {
+    /// Expose the tool to the model and add findings to the context
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Run the audit tools; when off, only `reports` are read
+    #[serde(default = "default_true")]
+    pub run_scanners: bool,
+
+    /// Seconds an audit tool may run before it is stopped
+    #[serde(default = "default_vulnerability_scan_timeout_secs")]
+    pub timeout_secs: u64,
+
+    /// Hours before cached results are scanned again even if no lockfile changed
+    #[serde(default = "default_vulnerability_scan_refresh_hours")]
+    pub refresh_hours: u64,
+
+    /// Existing JSON reports to read, relative to the workspace
+    #[serde(default)]
+    pub reports: Vec<String>,
+
+    /// Findings listed in the context before the rest are summarized
+    #[serde(default = "default_vulnerability_scan_max_context_findings")]
+    pub max_context_findings: usize,
+}
+
+impl Default for VulnerabilityScanConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            run_scanners: true,
+            timeout_secs: default_vulnerability_scan_timeout_secs(),
+            refresh_hours: default_vulnerability_scan_refresh_hours(),
+            reports: Vec::new(),
+            max_context_findings: default_vulnerability_scan_max_context_findings(),
+        }
+    }
+}
+
+/// Embeddings index for the `semantic_search` tool (`[tools.semantic_search]`)
+///
+/// Off by default. When enabled, workspace files are split into chunks along
+/// tree-sitter definitions, embedded with `provider` and stored in
+/// `.vtcode/index/`. Only files that changed since the last search are
+/// embedded again.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SemanticSearchConfig {
+    /// Expose the tool to the model
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Where embeddings come from
+    #[serde(default)]
+    pub provider: EmbeddingProvider,
+
+    /// Embedding model; `nomic-embed-text` for Ollama and
+    /// `text-embedding-3-small` for OpenAI when unset
+    #[serde(default)]
+    pub model: Option<String>,
+
+    /// Server URL, for Ollama on another machine or an OpenAI-compatible server
+    #[serde(default)]
+    pub base_url: Option<String>,
+
+    /// Environment variable holding the API key; `OPENAI_API_KEY` when unset
+    #[serde(default)]
+    pub api_key_env: Option<String>,
+
+    /// Longest chunk in lines; longer definitions are split at nested ones
+    #[serde(default = "default_semantic_max_chunk_lines")]
+    pub max_chunk_lines: usize,
+
+    /// Files indexed before the rest of the workspace is skipped
+    #[serde(default = "default_max_index_files")]
+    pub max_files: usize,
+
+    /// Chunks sent per embedding request
+    #[serde(default = "default_semantic_batch_size")]
+    pub batch_size: usize,
+}
+
+impl Default for SemanticSearchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            provider: EmbeddingProvider::default(),
+            model: None,
+            base_url: None,
+            api_key_env: None,
+            max_chunk_lines: default_semantic_max_chunk_lines(),
+            max_files: default_max_index_files(),
+            batch_size: default_semantic_batch_size(),
+        }
+    }
+}
+
+/// Source of embeddings for `semantic_search`
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingProvider {
+    /// Hashed identifier and word features computed in process; needs no
+    /// model or network but only matches related wording
+    #[default]
+    Local,
+    /// An embedding model served by Ollama
+    Ollama,
+    /// The OpenAI embeddings API or a server compatible with it
+    OpenAI,
+}
+
+/// Canned tool responses (`[tools.mocks]`)
+///
+/// Off by default. When enabled, a tool call matching an entry returns the
+/// entry's response after its latency instead of running, so scripted demos
+/// and recorded tutorials behave the same on every run. Approval prompts and
+/// policies still apply. With `strict`, calls no entry matches fail instead of
+/// touching the workspace or network.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ToolMocksConfig {
+    /// Answer matching tool calls from `entries`
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Fail tool calls that no entry matches instead of running them
+    #[serde(default)]
+    pub strict: bool,
+
+    /// Checked in order; the first entry that matches answers the call
+    #[serde(default)]
+    pub entries: Vec<ToolMockEntry>,
+}
+
+/// One canned response (`[[tools.mocks.entries]]`)
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct ToolMockEntry {
+    /// Tool name, e.g. `read_file`
+    pub tool: String,
+
+    /// Arguments the call must have. String values are glob patterns, and an
+    /// argument given as a list of strings, such as a command, is matched as
+    /// the words joined by spaces. Arguments not listed are not checked.
+    #[serde(default)]
+    pub args: serde_json::Map<String, serde_json::Value>,
+
+    /// Result returned for every matching call
+    #[serde(default)]
+    pub response: Option<serde_json::Value>,
+
+    /// Results returned for successive matching calls, the last one repeating;
+    /// takes precedence over `response`
+    #[serde(default)]
+    pub responses: Vec<serde_json::Value>,
+
+    /// Simulated time the call takes
+    #[serde(default)]
+    pub latency_ms: u64,
+}
+
+/// Workspace environment files for terminal commands (`[tools.env]`)
+///
+/// Off by default. When enabled, `run_terminal_cmd` and `bash` run with the
+/// variables of `files`; `.envrc` goes through `direnv export json` when
+/// direnv is installed and the file is allowed. Variables that look like
+/// secrets (by name, or by value under `[security.redaction]`) are withheld
+/// unless listed in `allow`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WorkspaceEnvConfig {
+    /// Load the files into command environments
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Files relative to the workspace, later files overriding earlier ones
+    #[serde(default = "default_env_files")]
+    pub files: Vec<String>,
+
+    /// When non-empty, only these variables are injected; listing a variable
+    /// here also passes it when it looks like a secret
+    #[serde(default)]
+    pub allow: Vec<String>,
+
+    /// Variables never injected
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+impl Default for WorkspaceEnvConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            files: default_env_files(),
+            allow: Vec::new(),
+            deny: Vec::new(),
+        }
+    }
+}
+
+/// Limits on what the file tools may write (`[tools.write_quota]`)
+///
+/// A write over a limit is refused with a quota error instead of being
+/// truncated. A limit of 0 disables it.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WriteQuotaConfig {
+    /// Largest content a single write or edit may write, in bytes
+    #[serde(default = "default_max_write_bytes")]
+    pub max_write_bytes: u64,
+
+    /// New files the agent may create in one turn
+    #[serde(default = "default_max_files_created_per_turn")]
+    pub max_files_created_per_turn: usize,
+
+    /// Total bytes the file tools may write in one session
+    #[serde(default = "default_max_session_write_bytes")]
+    pub max_session_bytes: u64,
+}
+
+impl Default for WriteQuotaConfig {
+    fn default() -> Self {
+        Self {
+            max_write_bytes: default_max_write_bytes(),
+            max_files_created_per_turn: default_max_files_created_per_turn(),
+            max_session_bytes: default_max_session_write_bytes(),
+        }
+    }
+}
+
+/// Progressive tool exposure (`[tools.exposure]`)
+///
+/// Sessions start with the read-only tools (reading, listing and searching)
+/// and unlock editing and terminal tools once the model records a plan, the
+/// user approves a call to one of them, or the user runs `/tools full`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ExposureConfig {
+    /// Start each session with read-only tools; `false` exposes every tool
+    #[serde(default = "default_true")]
+    pub progressive: bool,
+
+    /// Unlock editing and terminal tools when the model records a plan with
+    /// `update_plan`
+    #[serde(default = "default_true")]
+    pub escalate_on_plan: bool,
+}
+
+impl Default for ExposureConfig {
+    fn default() -> Self {
+        Self {
+            progressive: default_true(),
+            escalate_on_plan: default_true(),
+        }
+    }
+}
+
+/// Tool usage statistics (`[tools.stats]`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ToolStatsConfig {
+    /// Record success rates and durations of tool calls per workspace under
+    /// `~/.vtcode/cache/tool_stats/`
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Add what was learned to tool descriptions and per-turn tool guidance
+    #[serde(default = "default_true")]
+    pub guide_model: bool,
+}
+
+impl Default for ToolStatsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_true(),
+            guide_model: default_true(),
+        }
+    }
+}
+
+/// How paths appear in tool results
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PathDisplay {
+    /// Relative to the workspace root, without a leading `./`
+    #[default]
+    Relative,
+    /// Absolute
+    Absolute,
+}
+
+/// How much of a tool result the model sees
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum VerbosityPolicy {
+    /// Always the whole result
+    #[default]
+    Full,
+    /// A summary when the tool succeeded, the whole result when it failed
+    Adaptive,
+    /// Always a summary
+    Summary,
+}
+
+/// Tool output verbosity (`[tools.verbosity]`)
+///
+/// A summary keeps the exit code, the short fields of the result, line and
+/// item counts, and key lines such as errors, warnings and test totals. The
+/// full result stays available to the model through the `get_full_output`
+/// tool for the rest of the session.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ToolVerbosityConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Policy for tools not listed in `policies`
+    #[serde(default)]
+    pub default_policy: VerbosityPolicy,
+
+    /// Policies by tool name
+    #[serde(default = "default_verbosity_policies")]
+    pub policies: IndexMap<String, VerbosityPolicy>,
+
+    /// Results smaller than this are always sent whole
+    #[serde(default = "default_verbosity_min_bytes")]
+    pub min_bytes: usize,
+
+    /// Most key lines kept from each long text field of a summary
+    #[serde(default = "default_verbosity_key_lines")]
+    pub key_lines: usize,
+}
+
+impl Default for ToolVerbosityConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_true(),
+            default_policy: VerbosityPolicy::default(),
+            policies: default_verbosity_policies(),
+            min_bytes: default_verbosity_min_bytes(),
+            key_lines: default_verbosity_key_lines(),
+        }
+    }
+}
+
+fn default_verbosity_policies() -> IndexMap<String, VerbosityPolicy> {
+    let mut policies = IndexMap::new();
+    policies.insert("run_terminal_cmd".to_string(), VerbosityPolicy::Adaptive);
+    policies.insert("bash".to_string(), VerbosityPolicy::Adaptive);
+    policies
+}
+
+fn default_verbosity_min_bytes() -> usize {
+    2048
+}
+
+fn default_verbosity_key_lines() -> usize {
+    12
+}
+
+/// Tool schema size limits (`[tools.schema]`)
+///
+/// The full set of tool schemas costs several thousand tokens. For models whose
+/// context window is at most `compact_below_tokens`, descriptions are shortened
+/// and seldom-used optional parameters are left out; at most
+/// `subset_below_tokens`, only the tools that fit the request are sent.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ToolSchemaConfig {
+    /// Compact schemas for context windows up to this many tokens; 0 disables
+    #[serde(default = "default_schema_compact_below_tokens")]
+    pub compact_below_tokens: usize,
+
+    /// Longest tool description kept when compacting, in characters.
+    /// Parameter descriptions get half of it.
+    #[serde(default = "default_schema_max_description_chars")]
+    pub max_description_chars: usize,
+
+    /// Optional parameters kept per tool when compacting, preferring the ones
+    /// calls in this workspace actually pass
+    #[serde(default = "default_schema_max_optional_params")]
+    pub max_optional_params: usize,
+
+    /// Send only the tools relevant to the request for context windows up to
+    /// this many tokens; 0 disables
+    #[serde(default = "default_schema_subset_below_tokens")]
+    pub subset_below_tokens: usize,
+
+    /// Most tools sent when sending a subset
+    #[serde(default = "default_schema_max_tools")]
+    pub max_tools: usize,
+
+    /// Context window in tokens by model id or id prefix, e.g.
+    /// `"qwen2.5-coder" = 16384`. Other models use `[context] max_context_tokens`.
+    #[serde(default)]
+    pub context_windows: IndexMap<String, usize>,
+}
+
+impl Default for ToolSchemaConfig {
+    fn default() -> Self {
+        Self {
+            compact_below_tokens: default_schema_compact_below_tokens(),
+            max_description_chars: default_schema_max_description_chars(),
+            max_optional_params: default_schema_max_optional_params(),
+            subset_below_tokens: default_schema_subset_below_tokens(),
+            max_tools: default_schema_max_tools(),
+            context_windows: IndexMap::new(),
+        }
+    }
+}
+
+/// Grammar-constrained decoding of the JSON tool envelope
+///
+/// Providers that compile a JSON schema into a decoding grammar (Ollama,
+/// llama.cpp and other self-hosted OpenAI-compatible servers) can only produce
+/// envelopes whose tool names and arguments match the tool schemas. Smaller
+/// models then stop emitting malformed tool arguments.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ConstrainedDecodingConfig {
+    /// Attach the envelope schema to JSON envelope requests when the model
+    /// supports it
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Capability by model id or id prefix, overriding what the provider
+    /// reports: `true` for a model behind a grammar-aware gateway, `false` for
+    /// one whose backend rejects schemas
+    #[serde(default)]
+    pub models: IndexMap<String, bool>,
+
+    /// Model ids or id prefixes that use the constrained envelope for tool
+    /// calls from the first request instead of native function calling
+    #[serde(default)]
+    pub envelope_models: Vec<String>,
+}
+
+impl Default for ConstrainedDecodingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            models: IndexMap::new(),
+            envelope_models: Vec::new(),
         }
     }
 }
@@ -56,3 +830,124 @@ fn default_tool_policy() -> ToolPolicy {
 fn default_max_tool_loops() -> usize {
     defaults::DEFAULT_MAX_TOOL_LOOPS
 }
+
+fn default_json_textual_tools() -> bool {
+    true
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_max_parallel() -> usize {
+    4
+}
+
+fn default_max_parallel_reads() -> usize {
+    4
+}
+
+fn default_max_index_files() -> usize {
+    5_000
+}
+
+fn default_coverage_timeout_secs() -> u64 {
+    900
+}
+
+fn default_bootstrap_timeout_secs() -> u64 {
+    600
+}
+
+fn default_sql_timeout_secs() -> u64 {
+    30
+}
+
+fn default_sql_max_rows() -> usize {
+    200
+}
+
+fn default_vulnerability_scan_timeout_secs() -> u64 {
+    300
+}
+
+fn default_vulnerability_scan_refresh_hours() -> u64 {
+    24
+}
+
+fn default_vulnerability_scan_max_context_findings() -> usize {
+    15
+}
+
+fn default_semantic_max_chunk_lines() -> usize {
+    60
+}
+
+fn default_semantic_batch_size() -> usize {
+    32
+}
+
+fn default_code_execution_scratch_dir() -> String {
+    ".vtcode/scratch".to_string()
+}
+
+fn default_schema_compact_below_tokens() -> usize {
+    32_000
+}
+
+fn default_schema_max_description_chars() -> usize {
+    200
+}
+
+fn default_schema_max_optional_params() -> usize {
+    3
+}
+
+fn default_schema_subset_below_tokens() -> usize {
+    16_000
+}
+
+fn default_schema_max_tools() -> usize {
+    10
+}
+
+fn default_env_files() -> Vec<String> {
+    vec![".env".to_string(), ".envrc".to_string()]
+}
+
+fn default_max_write_bytes() -> u64 {
+    10 * 1024 * 1024
+}
+
+fn default_max_files_created_per_turn() -> usize {
+    50
+}
+
+fn default_max_session_write_bytes() -> u64 {
+    200 * 1024 * 1024
+}
+
+fn default_coverage_languages() -> IndexMap<String, CoverageCommand> {
+    let istanbul = CoverageCommand::new(
+        "npx jest --coverage --coverageReporters=lcov",
+        "coverage/lcov.info",
+    );
+    let mut languages = IndexMap::new();
+    languages.insert(
+        "rust".to_string(),
+        CoverageCommand::new(
+            "cargo llvm-cov --lcov --output-path target/llvm-cov/lcov.info",
+            "target/llvm-cov/lcov.info",
+        ),
+    );
+    languages.insert(
+        "python".to_string(),
+        CoverageCommand::new(
+            "coverage run -m pytest && coverage lcov -o coverage.lcov",
+            "coverage.lcov",
+        ),
+    );
+    languages.insert("javascript".to_string(), istanbul.clone());
+    languages.insert("typescript".to_string(), istanbul);
+    languages
+}
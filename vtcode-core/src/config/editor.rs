@@ -0,0 +1,660 @@
+//! Structured, comment-preserving editing of `vtcode.toml`
+//!
+//! [`ConfigDocument`] wraps the file as a `toml_edit` document so values can be
+//! changed one at a time while comments, ordering and formatting of everything
+//! else stay intact. Each edit is validated against its [`FieldKind`] and the
+//! whole document is re-checked against [`VTCodeConfig`] before it is kept.
+//! [`editor_sections`] describes the fields offered by `vtcode config edit`.
+
+use crate::config::loader::VTCodeConfig;
+use crate::config::models::{ModelId, Provider};
+use crate::config::types::{ReasoningEffortLevel, UiSurfacePreference};
+use crate::tools::build_function_declarations;
+use crate::ui::theme::available_themes;
+use anyhow::{Context, Result, anyhow, bail};
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Item, Table, TableLike, Value};
+
+/// How a field is edited and validated.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldKind {
+    Bool,
+    Integer {
+        min: i64,
+        max: i64,
+    },
+    Text,
+    /// One of `options`; free-form values are accepted when `allow_custom` is set
+    Choice {
+        options: Vec<String>,
+        allow_custom: bool,
+    },
+}
+
+/// A single editable setting.
+#[derive(Debug, Clone)]
+pub struct ConfigField {
+    /// Table path followed by the key, e.g. `["agent", "provider"]`
+    pub path: Vec<String>,
+    pub label: String,
+    pub help: String,
+    pub kind: FieldKind,
+}
+
+impl ConfigField {
+    fn new(path: &str, label: &str, help: &str, kind: FieldKind) -> Self {
+        Self {
+            path: path.split('.').map(str::to_string).collect(),
+            label: label.to_string(),
+            help: help.to_string(),
+            kind,
+        }
+    }
+
+    /// Dotted key, e.g. `agent.provider`
+    pub fn key(&self) -> String {
+        self.path.join(".")
+    }
+}
+
+/// A group of fields shown together, matching a top-level table.
+#[derive(Debug, Clone)]
+pub struct ConfigSection {
+    pub name: &'static str,
+    pub title: &'static str,
+    pub fields: Vec<ConfigField>,
+}
+
+fn choice<I, S>(options: I) -> FieldKind
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    FieldKind::Choice {
+        options: options.into_iter().map(Into::into).collect(),
+        allow_custom: false,
+    }
+}
+
+fn policy_choice() -> FieldKind {
+    choice(["allow", "prompt", "deny"])
+}
+
+fn turns() -> FieldKind {
+    FieldKind::Integer {
+        min: 1,
+        max: 10_000,
+    }
+}
+
+fn passes() -> FieldKind {
+    FieldKind::Integer { min: 1, max: 10 }
+}
+
+/// Sections and fields offered by the config editor.
+///
+/// Tool policy fields cover every built-in tool plus any tool already listed
+/// under `[tools.policies]` in `document`.
+pub fn editor_sections(document: &ConfigDocument) -> Vec<ConfigSection> {
+    let providers = Provider::all_providers()
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    let models = FieldKind::Choice {
+        options: ModelId::all_models()
+            .iter()
+            .map(|model| model.as_str().to_string())
+            .collect(),
+        allow_custom: true,
+    };
+    let efforts = [
+        ReasoningEffortLevel::Low,
+        ReasoningEffortLevel::Medium,
+        ReasoningEffortLevel::High,
+    ]
+    .map(ReasoningEffortLevel::as_str);
+    let surfaces = [
+        UiSurfacePreference::Auto,
+        UiSurfacePreference::Alternate,
+        UiSurfacePreference::Inline,
+    ]
+    .map(UiSurfacePreference::as_str);
+
+    let agent = vec![
+        ConfigField::new(
+            "agent.provider",
+            "Provider",
+            "LLM provider; its API key is read from the matching environment variable",
+            choice(providers),
+        ),
+        ConfigField::new(
+            "agent.default_model",
+            "Default model",
+            "Model used for chat sessions; type to filter or enter any model id",
+            models,
+        ),
+        ConfigField::new(
+            "agent.theme",
+            "Theme",
+            "Color theme for the terminal UI",
+            choice(available_themes()),
+        ),
+        ConfigField::new(
+            "agent.ui_surface",
+            "UI surface",
+            "Render chat in the alternate screen, inline, or pick automatically",
+            choice(surfaces),
+        ),
+        ConfigField::new(
+            "agent.reasoning_effort",
+            "Reasoning effort",
+            "Reasoning effort for models that support it",
+            choice(efforts),
+        ),
+        ConfigField::new(
+            "agent.max_conversation_turns",
+            "Max conversation turns",
+            "Turns before a session ends automatically",
+            turns(),
+        ),
+        ConfigField::new(
+            "agent.enable_self_review",
+            "Self-review",
+            "Run an extra pass that reviews the final response",
+            FieldKind::Bool,
+        ),
+        ConfigField::new(
+            "agent.max_review_passes",
+            "Self-review passes",
+            "Maximum number of self-review passes",
+            passes(),
+        ),
+        ConfigField::new(
+            "agent.refine_prompts_enabled",
+            "Prompt refinement",
+            "Refine prompts before sending them to the model",
+            FieldKind::Bool,
+        ),
+        ConfigField::new(
+            "agent.refine_prompts_max_passes",
+            "Refinement passes",
+            "Maximum number of prompt refinement passes",
+            passes(),
+        ),
+        ConfigField::new(
+            "agent.refine_prompts_model",
+            "Refinement model",
+            "Model used for prompt refinement; empty picks an efficient sibling",
+            FieldKind::Text,
+        ),
+    ];
+
+    let mut tools = vec![
+        ConfigField::new(
+            "tools.default_policy",
+            "Default policy",
+            "Policy for tools without an explicit entry",
+            policy_choice(),
+        ),
+        ConfigField::new(
+            "tools.max_tool_loops",
+            "Max tool loops",
+            "Tool-call rounds allowed per user turn",
+            turns(),
+        ),
+        ConfigField::new(
+            "tools.json_textual_tools",
+            "JSON textual tools",
+            "Use provider JSON mode when the model emits tool calls as text",
+            FieldKind::Bool,
+        ),
+        ConfigField::new(
+            "tools.constrained_decoding.enabled",
+            "Constrained decoding",
+            "Constrain JSON tool envelopes with a grammar on models that support it",
+            FieldKind::Bool,
+        ),
+        ConfigField::new(
+            "tools.path_display",
+            "Path display",
+            "Show paths in tool results relative to the workspace or absolute",
+            choice(["relative", "absolute"]),
+        ),
+        ConfigField::new(
+            "tools.imports.organize_on_edit",
+            "Organize imports on edit",
+            "Add missing imports and sort them after write_file and edit_file",
+            FieldKind::Bool,
+        ),
+        ConfigField::new(
+            "tools.concurrency.max_parallel",
+            "Max parallel tools",
+            "Tool executions allowed to run at the same time",
+            FieldKind::Integer { min: 1, max: 64 },
+        ),
+        ConfigField::new(
+            "tools.semantic_search.enabled",
+            "Semantic search",
+            "Offer semantic_search, which finds code through an embeddings index",
+            FieldKind::Bool,
+        ),
+        ConfigField::new(
+            "tools.semantic_search.provider",
+            "Embedding provider",
+            "Where semantic_search embeddings come from",
+            choice(["local", "ollama", "openai"]),
+        ),
+        ConfigField::new(
+            "tools.coverage.timeout_secs",
+            "Coverage timeout",
+            "Seconds a coverage_report run may take",
+            FieldKind::Integer { min: 10, max: 7200 },
+        ),
+    ];
+    let mut tool_names: Vec<String> = build_function_declarations()
+        .into_iter()
+        .map(|declaration| declaration.name)
+        .collect();
+    for name in document.table_keys(&["tools", "policies"]) {
+        if !tool_names.contains(&name) {
+            tool_names.push(name);
+        }
+    }
+    for name in tool_names {
+        tools.push(ConfigField {
+            path: vec!["tools".to_string(), "policies".to_string(), name.clone()],
+            label: format!("Policy: {}", name),
+            help: format!("Whether {} runs, asks first, or is blocked", name),
+            kind: policy_choice(),
+        });
+    }
+
+    let security = vec![
+        ConfigField::new(
+            "security.human_in_the_loop",
+            "Human in the loop",
+            "Ask for confirmation before destructive actions",
+            FieldKind::Bool,
+        ),
+        ConfigField::new(
+            "security.require_write_tool_for_claims",
+            "Require write tool for claims",
+            "Only report file changes that were made through a write tool",
+            FieldKind::Bool,
+        ),
+        ConfigField::new(
+            "security.auto_apply_detected_patches",
+            "Auto-apply patches",
+            "Apply patches found in responses without asking",
+            FieldKind::Bool,
+        ),
+    ];
+
+    let ui = vec![
+        ConfigField::new(
+            "ui.tool_output_mode",
+            "Tool output",
+            "Show compact or full tool output in the transcript",
+            choice(["compact", "full"]),
+        ),
+        ConfigField::new(
+            "ui.show_symbol_outline",
+            "Symbol outline",
+            "Open the outline panel when the agent reads or edits a file",
+            FieldKind::Bool,
+        ),
+        ConfigField::new(
+            "ui.clipboard",
+            "Clipboard",
+            "Allow /copy, /paste and the copy keybindings to use the system clipboard",
+            FieldKind::Bool,
+        ),
+    ];
+
+    vec![
+        ConfigSection {
+            name: "agent",
+            title: "Agent",
+            fields: agent,
+        },
+        ConfigSection {
+            name: "tools",
+            title: "Tools",
+            fields: tools,
+        },
+        ConfigSection {
+            name: "security",
+            title: "Security",
+            fields: security,
+        },
+        ConfigSection {
+            name: "ui",
+            title: "UI",
+            fields: ui,
+        },
+    ]
+}
+
+/// Check `raw` against the field kind and convert it to a TOML value.
+pub fn parse_field_value(field: &ConfigField, raw: &str) -> Result<Value> {
+    let trimmed = raw.trim();
+    match &field.kind {
+        FieldKind::Bool => match trimmed.to_lowercase().as_str() {
+            "true" | "yes" | "on" => Ok(Value::from(true)),
+            "false" | "no" | "off" => Ok(Value::from(false)),
+            _ => bail!("{} must be true or false", field.label),
+        },
+        FieldKind::Integer { min, max } => {
+            let number: i64 = trimmed
+                .parse()
+                .map_err(|_| anyhow!("{} must be a whole number", field.label))?;
+            if number < *min || number > *max {
+                bail!("{} must be between {} and {}", field.label, min, max);
+            }
+            Ok(Value::from(number))
+        }
+        FieldKind::Text => Ok(Value::from(raw)),
+        FieldKind::Choice {
+            options,
+            allow_custom,
+        } => {
+            if options.iter().any(|option| option == trimmed)
+                || (*allow_custom && !trimmed.is_empty())
+            {
+                Ok(Value::from(trimmed))
+            } else {
+                bail!("{} must be one of: {}", field.label, options.join(", "))
+            }
+        }
+    }
+}
+
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.value().clone(),
+        Value::Integer(number) => number.value().to_string(),
+        Value::Float(number) => number.value().to_string(),
+        Value::Boolean(flag) => flag.value().to_string(),
+        other => other.to_string().trim().to_string(),
+    }
+}
+
+fn default_text(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// A configuration file opened for editing.
+pub struct ConfigDocument {
+    path: PathBuf,
+    document: DocumentMut,
+    defaults: toml::Value,
+    dirty: bool,
+}
+
+impl ConfigDocument {
+    /// Open `path`, starting from an empty document when it does not exist yet.
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let document = if path.exists() {
+            let content = fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            content
+                .parse::<DocumentMut>()
+                .with_context(|| format!("Failed to parse {}", path.display()))?
+        } else {
+            DocumentMut::new()
+        };
+        Ok(Self::from_document(path, document))
+    }
+
+    fn from_document(path: PathBuf, document: DocumentMut) -> Self {
+        let defaults = toml::Value::try_from(VTCodeConfig::default())
+            .unwrap_or_else(|_| toml::Value::Table(Default::default()));
+        Self {
+            path,
+            document,
+            defaults,
+            dirty: false,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether there are edits that have not been saved.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Whether the file sets the field explicitly (instead of using the default).
+    pub fn is_set(&self, field: &ConfigField) -> bool {
+        self.value(field).is_some()
+    }
+
+    fn value(&self, field: &ConfigField) -> Option<&Value> {
+        let (key, tables) = field.path.split_last()?;
+        let mut table: &dyn TableLike = self.document.as_table();
+        for name in tables {
+            table = table.get(name)?.as_table_like()?;
+        }
+        table.get(key)?.as_value()
+    }
+
+    /// Current value of the field as text, falling back to the built-in default.
+    pub fn display_value(&self, field: &ConfigField) -> String {
+        if let Some(value) = self.value(field) {
+            return value_text(value);
+        }
+        let mut current = &self.defaults;
+        for name in &field.path {
+            match current.get(name) {
+                Some(next) => current = next,
+                None => return String::new(),
+            }
+        }
+        default_text(current)
+    }
+
+    /// Keys of the table at `path`, in file order.
+    pub fn table_keys(&self, path: &[&str]) -> Vec<String> {
+        let mut table: &dyn TableLike = self.document.as_table();
+        for name in path {
+            match table.get(name).and_then(Item::as_table_like) {
+                Some(next) => table = next,
+                None => return Vec::new(),
+            }
+        }
+        table.iter().map(|(key, _)| key.to_string()).collect()
+    }
+
+    /// Validate `raw` for the field and store it, keeping comments around the key.
+    pub fn set(&mut self, field: &ConfigField, raw: &str) -> Result<()> {
+        let mut value = parse_field_value(field, raw)?;
+        let (key, tables) = field
+            .path
+            .split_last()
+            .ok_or_else(|| anyhow!("field has an empty path"))?;
+
+        let previous = self.document.clone();
+        let table = table_mut(&mut self.document, tables)?;
+        match table.get_mut(key) {
+            Some(item) => {
+                if let Some(existing) = item.as_value() {
+                    *value.decor_mut() = existing.decor().clone();
+                }
+                *item = Item::Value(value);
+            }
+            None => {
+                table.insert(key, Item::Value(value));
+            }
+        }
+        self.commit(previous)
+    }
+
+    /// Remove the field from the file so the built-in default applies again.
+    pub fn reset(&mut self, field: &ConfigField) -> Result<()> {
+        let Some((key, tables)) = field.path.split_last() else {
+            return Ok(());
+        };
+        if !self.is_set(field) {
+            return Ok(());
+        }
+        let previous = self.document.clone();
+        table_mut(&mut self.document, tables)?.remove(key);
+        self.commit(previous)
+    }
+
+    /// Keep an edit only if the whole document still loads as a configuration.
+    fn commit(&mut self, previous: DocumentMut) -> Result<()> {
+        if let Err(err) = self.validate() {
+            self.document = previous;
+            return Err(err);
+        }
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Parse the document as a full configuration.
+    pub fn validate(&self) -> Result<VTCodeConfig> {
+        VTCodeConfig::from_toml_str(&self.document.to_string()).context("Invalid configuration")
+    }
+
+    /// Write the document back to its file.
+    pub fn save(&mut self) -> Result<()> {
+        self.validate()?;
+        if let Some(parent) = self.path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        fs::write(&self.path, self.document.to_string())
+            .with_context(|| format!("Failed to write {}", self.path.display()))?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+/// Walk to the table at `path`, creating missing tables along the way.
+fn table_mut<'a>(document: &'a mut DocumentMut, path: &[String]) -> Result<&'a mut dyn TableLike> {
+    let mut table: &mut dyn TableLike = document.as_table_mut();
+    for name in path {
+        let item = table.entry(name).or_insert_with(|| {
+            let mut created = Table::new();
+            created.set_implicit(true);
+            Item::Table(created)
+        });
+        table = item
+            .as_table_like_mut()
+            .ok_or_else(|| anyhow!("`{}` is not a table", name))?;
+    }
+    Ok(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"# Project settings
+[agent]
+# Provider comment
+provider = "openai" # trailing note
+max_conversation_turns = 150
+
+[tools.policies]
+read_file = "allow"
+"#;
+
+    fn field(sections: &[ConfigSection], key: &str) -> ConfigField {
+        sections
+            .iter()
+            .flat_map(|section| section.fields.iter())
+            .find(|field| field.key() == key)
+            .cloned()
+            .unwrap_or_else(|| panic!("missing field {key}"))
+    }
+
+    fn sample() -> ConfigDocument {
+        ConfigDocument::from_document(
+            PathBuf::from("vtcode.toml"),
+            SAMPLE.parse().expect("sample"),
+        )
+    }
+
+    #[test]
+    fn edits_keep_comments_and_create_missing_tables() {
+        let mut document = sample();
+        let sections = editor_sections(&document);
+
+        document
+            .set(&field(&sections, "agent.provider"), "anthropic")
+            .expect("provider");
+        document
+            .set(&field(&sections, "security.human_in_the_loop"), "false")
+            .expect("security");
+
+        let written = document.document.to_string();
+        assert!(written.contains("# Provider comment\nprovider = \"anthropic\" # trailing note"));
+        assert!(written.starts_with("# Project settings"));
+        assert!(written.contains("[security]\nhuman_in_the_loop = false"));
+        assert!(document.is_dirty());
+    }
+
+    #[test]
+    fn rejects_invalid_values_and_falls_back_to_defaults() {
+        let mut document = sample();
+        let sections = editor_sections(&document);
+        let turns = field(&sections, "agent.max_conversation_turns");
+
+        assert!(document.set(&turns, "lots").is_err());
+        assert!(document.set(&turns, "0").is_err());
+        assert!(
+            document
+                .set(&field(&sections, "tools.default_policy"), "sometimes")
+                .is_err()
+        );
+        assert!(
+            document
+                .set(
+                    &field(&sections, "agent.default_model"),
+                    "vendor/custom-model"
+                )
+                .is_ok()
+        );
+        assert_eq!(document.display_value(&turns), "150");
+
+        document.reset(&turns).expect("reset");
+        assert!(!document.is_set(&turns));
+        assert_eq!(
+            document.display_value(&turns),
+            VTCodeConfig::default()
+                .agent
+                .max_conversation_turns
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn lists_policies_for_built_in_and_configured_tools() {
+        let document = ConfigDocument::from_document(
+            PathBuf::from("vtcode.toml"),
+            "[tools.policies]\nmy_plugin = \"deny\"\n"
+                .parse()
+                .expect("doc"),
+        );
+        let sections = editor_sections(&document);
+        let plugin = field(&sections, "tools.policies.my_plugin");
+        assert_eq!(document.display_value(&plugin), "deny");
+        assert!(
+            sections[1]
+                .fields
+                .iter()
+                .any(|field| field.key() == "tools.policies.read_file")
+        );
+    }
+}
@@ -0,0 +1,303 @@
+//! Programmatic configuration for embedders
+//!
+//! [`VTCodeConfigBuilder`] assembles a [`VTCodeConfig`] from layers, each one
+//! overriding the keys it sets in the layers before it:
+//!
+//! 1. built-in defaults
+//! 2. a `vtcode.toml`, given with [`VTCodeConfigBuilder::file`] or found with
+//!    the CLI's search order by [`VTCodeConfigBuilder::workspace`]
+//! 3. `VTCODE__<TABLE>__<KEY>` environment variables, when enabled with
+//!    [`VTCodeConfigBuilder::env`]
+//! 4. values set on the builder
+//!
+//! The merged layers are parsed by [`VTCodeConfig::from_toml_str`], the same
+//! function the loader uses for files, so the builder accepts and rejects
+//! exactly what a `vtcode.toml` would.
+
+use super::{ConfigManager, VTCodeConfig};
+use crate::config::core::ToolPolicy;
+use crate::config::models::Provider;
+use crate::config::types::{ReasoningEffortLevel, UiSurfacePreference};
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+/// Prefix of environment variables read by [`VTCodeConfigBuilder::env`].
+/// `__` separates tables and keys: `VTCODE__TOOLS__POLICIES__RUN_TERMINAL_CMD=deny`.
+pub const CONFIG_ENV_PREFIX: &str = "VTCODE__";
+
+#[derive(Debug, Clone)]
+enum FileLayer {
+    File(PathBuf),
+    Workspace(PathBuf),
+}
+
+/// Builder for [`VTCodeConfig`], created with [`VTCodeConfig::builder`].
+#[derive(Debug, Clone, Default)]
+pub struct VTCodeConfigBuilder {
+    file: Option<FileLayer>,
+    env: bool,
+    overrides: Table,
+    errors: Vec<String>,
+}
+
+impl VTCodeConfigBuilder {
+    /// Start from the configuration file at `path`, which must exist.
+    pub fn file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.file = Some(FileLayer::File(path.into()));
+        self
+    }
+
+    /// Start from the file the CLI would load for `workspace`, if there is one.
+    pub fn workspace(mut self, workspace: impl Into<PathBuf>) -> Self {
+        self.file = Some(FileLayer::Workspace(workspace.into()));
+        self
+    }
+
+    /// Apply `VTCODE__*` environment variables over the file.
+    pub fn env(mut self, enabled: bool) -> Self {
+        self.env = enabled;
+        self
+    }
+
+    pub fn provider(self, provider: Provider) -> Self {
+        self.set("agent.provider", provider.to_string())
+    }
+
+    /// Model id; any id is accepted, as in `agent.default_model`.
+    pub fn model(self, model: impl Into<String>) -> Self {
+        self.set("agent.default_model", model.into())
+    }
+
+    pub fn theme(self, theme: impl Into<String>) -> Self {
+        self.set("agent.theme", theme.into())
+    }
+
+    pub fn ui_surface(self, surface: UiSurfacePreference) -> Self {
+        self.set("agent.ui_surface", surface.as_str())
+    }
+
+    pub fn reasoning_effort(self, effort: ReasoningEffortLevel) -> Self {
+        self.set("agent.reasoning_effort", effort.as_str())
+    }
+
+    pub fn max_conversation_turns(self, turns: usize) -> Self {
+        self.set_serialized(&["agent", "max_conversation_turns"], &turns)
+    }
+
+    /// Policy for tools without an entry in `[tools.policies]`.
+    pub fn default_tool_policy(self, policy: ToolPolicy) -> Self {
+        self.set_serialized(&["tools", "default_policy"], &policy)
+    }
+
+    /// Policy for one tool, e.g. `run_terminal_cmd`.
+    pub fn tool_policy(self, tool: &str, policy: ToolPolicy) -> Self {
+        self.set_serialized(&["tools", "policies", tool], &policy)
+    }
+
+    pub fn human_in_the_loop(self, enabled: bool) -> Self {
+        self.set("security.human_in_the_loop", enabled)
+    }
+
+    /// Set any setting by its dotted key, e.g. `("ui.clipboard", false)`.
+    pub fn set(mut self, key: &str, value: impl Into<Value>) -> Self {
+        let path: Vec<&str> = key.split('.').collect();
+        if path.iter().any(|segment| segment.is_empty()) {
+            self.errors
+                .push(format!("Invalid configuration key '{}'", key));
+            return self;
+        }
+        insert_path(&mut self.overrides, &path, value.into());
+        self
+    }
+
+    fn set_serialized<T: Serialize>(mut self, path: &[&str], value: &T) -> Self {
+        match Value::try_from(value) {
+            Ok(value) => insert_path(&mut self.overrides, path, value),
+            Err(err) => self
+                .errors
+                .push(format!("Invalid value for {}: {}", path.join("."), err)),
+        }
+        self
+    }
+
+    /// Merge the layers and parse the result like a configuration file.
+    pub fn build(self) -> Result<VTCodeConfig> {
+        if let Some(error) = self.errors.first() {
+            bail!("{}", error);
+        }
+        let mut table = match &self.file {
+            Some(FileLayer::File(path)) => read_table(path)?,
+            Some(FileLayer::Workspace(workspace)) => {
+                match ConfigManager::workspace_config_path(workspace) {
+                    Some(path) => read_table(&path)?,
+                    None => Table::new(),
+                }
+            }
+            None => Table::new(),
+        };
+        if self.env {
+            merge(&mut table, env_overrides(std::env::vars()));
+        }
+        merge(&mut table, self.overrides);
+        let content = toml::to_string(&table).context("Failed to serialize configuration")?;
+        VTCodeConfig::from_toml_str(&content).context("Invalid configuration")
+    }
+}
+
+fn read_table(path: &Path) -> Result<Table> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+    toml::from_str(&content)
+        .with_context(|| format!("Failed to parse config file: {}", path.display()))
+}
+
+fn insert_path(table: &mut Table, path: &[&str], value: Value) {
+    let Some((key, tables)) = path.split_last() else {
+        return;
+    };
+    let mut current = table;
+    for name in tables {
+        let entry = current
+            .entry(name.to_string())
+            .or_insert_with(|| Value::Table(Table::new()));
+        if !entry.is_table() {
+            *entry = Value::Table(Table::new());
+        }
+        let Value::Table(next) = entry else {
+            unreachable!("entry was just made a table");
+        };
+        current = next;
+    }
+    current.insert(key.to_string(), value);
+}
+
+/// Overlay `layer` on `base`, merging tables key by key.
+fn merge(base: &mut Table, layer: Table) {
+    for (key, value) in layer {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(existing)), Value::Table(nested)) => merge(existing, nested),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Settings from `VTCODE__*` variables. Values are read as TOML literals
+/// (`true`, `42`, `["a", "b"]`) and fall back to plain strings.
+fn env_overrides(vars: impl IntoIterator<Item = (String, String)>) -> Table {
+    let mut table = Table::new();
+    for (name, raw) in vars {
+        let Some(key) = name.strip_prefix(CONFIG_ENV_PREFIX) else {
+            continue;
+        };
+        let key = key.to_ascii_lowercase();
+        let path: Vec<&str> = key.split("__").collect();
+        if path.iter().any(|segment| segment.is_empty()) {
+            continue;
+        }
+        let value = toml::from_str::<Table>(&format!("value = {}", raw))
+            .ok()
+            .and_then(|mut parsed| parsed.remove("value"))
+            .unwrap_or_else(|| Value::String(raw.clone()));
+        insert_path(&mut table, &path, value);
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn layers_file_env_and_overrides_in_order() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("vtcode.toml");
+        fs::write(
+            &path,
+            "[agent]\nprovider = \"gemini\"\ntheme = \"ciapre-dark\"\nmax_conversation_turns = 40\n\n[tools.policies]\nread_file = \"allow\"\n",
+        )
+        .unwrap();
+
+        let mut table = read_table(&path).unwrap();
+        merge(
+            &mut table,
+            env_overrides([
+                ("VTCODE__AGENT__MAX_CONVERSATION_TURNS".into(), "80".into()),
+                ("VTCODE__AGENT__PROVIDER".into(), "openai".into()),
+                (
+                    "VTCODE__TOOLS__POLICIES__RUN_TERMINAL_CMD".into(),
+                    "deny".into(),
+                ),
+                ("VTCODE_COMMANDS_DENY_LIST".into(), "rm".into()),
+            ]),
+        );
+        let from_env = VTCodeConfig::from_toml_str(&toml::to_string(&table).unwrap()).unwrap();
+        assert_eq!(from_env.agent.provider, "openai");
+        assert_eq!(from_env.agent.max_conversation_turns, 80);
+        assert_eq!(from_env.agent.theme, "ciapre-dark");
+        assert_eq!(
+            from_env.tools.policies.get("run_terminal_cmd"),
+            Some(&ToolPolicy::Deny)
+        );
+
+        let config = VTCodeConfig::builder()
+            .file(&path)
+            .provider(Provider::Anthropic)
+            .tool_policy("run_terminal_cmd", ToolPolicy::Prompt)
+            .reasoning_effort(ReasoningEffortLevel::High)
+            .set("ui.clipboard", false)
+            .build()
+            .unwrap();
+        assert_eq!(config.agent.provider, "anthropic");
+        assert_eq!(config.agent.max_conversation_turns, 40);
+        assert_eq!(config.agent.reasoning_effort, ReasoningEffortLevel::High);
+        assert_eq!(
+            config.tools.policies.get("read_file"),
+            Some(&ToolPolicy::Allow)
+        );
+        assert_eq!(
+            config.tools.policies.get("run_terminal_cmd"),
+            Some(&ToolPolicy::Prompt)
+        );
+        assert!(!config.ui.clipboard);
+    }
+
+    #[test]
+    fn rejects_what_the_loader_rejects() {
+        let invalid = VTCodeConfig::builder()
+            .set("tools.default_policy", "sometimes")
+            .build();
+        assert!(invalid.is_err());
+        assert!(VTCodeConfig::from_toml_str("[tools]\ndefault_policy = \"sometimes\"").is_err());
+
+        assert!(
+            VTCodeConfig::builder()
+                .set("agent.max_conversation_turns", "many")
+                .build()
+                .is_err()
+        );
+        assert!(
+            VTCodeConfig::builder()
+                .set("agent..theme", "x")
+                .build()
+                .is_err()
+        );
+        assert!(
+            VTCodeConfig::builder()
+                .file("/nonexistent/vtcode.toml")
+                .build()
+                .is_err()
+        );
+
+        let defaults = VTCodeConfig::builder().build().unwrap();
+        assert_eq!(
+            defaults.agent.provider,
+            VTCodeConfig::default().agent.provider
+        );
+    }
+}
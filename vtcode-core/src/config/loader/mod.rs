@@ -1,6 +1,8 @@
 use crate::config::context::ContextFeaturesConfig;
 use crate::config::core::{
-    AgentConfig, AutomationConfig, CommandsConfig, PromptCachingConfig, SecurityConfig, ToolsConfig,
+    AgentConfig, AgentsConfig, AutomationConfig, CommandsConfig, CredentialsConfig, DaemonConfig,
+    IndexConfig, LlmConfig, NetworkConfig, OfflineConfig, PromptCachingConfig, RefusalConfig,
+    SecurityConfig, SessionsConfig, ToolsConfig,
 };
 use crate::config::router::RouterConfig;
 use crate::config::telemetry::TelemetryConfig;
@@ -11,6 +13,10 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+mod builder;
+
+pub use builder::{CONFIG_ENV_PREFIX, VTCodeConfigBuilder};
+
 /// Syntax highlighting configuration
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct SyntaxHighlightingConfig {
@@ -84,12 +90,16 @@ impl Default for SyntaxHighlightingConfig {
 }
 
 /// Main configuration structure for VTCode
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct VTCodeConfig {
     /// Agent-wide settings
     #[serde(default)]
     pub agent: AgentConfig,
 
+    /// Sub-agents the session's model can hand tasks to
+    #[serde(default)]
+    pub agents: AgentsConfig,
+
     /// Tool execution policies
     #[serde(default)]
     pub tools: ToolsConfig,
@@ -133,28 +143,52 @@ pub struct VTCodeConfig {
     /// Prompt cache configuration (local + provider integration)
     #[serde(default)]
     pub prompt_cache: PromptCachingConfig,
-}
 
-impl Default for VTCodeConfig {
-    fn default() -> Self {
-        Self {
-            agent: AgentConfig::default(),
-            tools: ToolsConfig::default(),
-            commands: CommandsConfig::default(),
-            security: SecurityConfig::default(),
-            ui: UiConfig::default(),
-            pty: PtyConfig::default(),
-            context: ContextFeaturesConfig::default(),
-            router: RouterConfig::default(),
-            telemetry: TelemetryConfig::default(),
-            syntax_highlighting: SyntaxHighlightingConfig::default(),
-            automation: AutomationConfig::default(),
-            prompt_cache: PromptCachingConfig::default(),
-        }
-    }
+    /// Offline detection and local assistant mode
+    #[serde(default)]
+    pub offline: OfflineConfig,
+
+    /// Named API key profiles per provider
+    #[serde(default)]
+    pub credentials: CredentialsConfig,
+
+    /// Provider client settings such as failover
+    #[serde(default)]
+    pub llm: LlmConfig,
+
+    /// Background daemon that keeps workspace state warm
+    #[serde(default)]
+    pub daemon: DaemonConfig,
+
+    /// Handling of provider safety refusals
+    #[serde(default)]
+    pub refusal: RefusalConfig,
+
+    /// Generated-code exclusions for search, indexing and file listing
+    #[serde(default)]
+    pub index: IndexConfig,
+
+    /// Proxy and TLS settings for outgoing requests
+    #[serde(default)]
+    pub network: NetworkConfig,
+
+    /// Compaction and archival of old sessions
+    #[serde(default)]
+    pub sessions: SessionsConfig,
 }
 
 impl VTCodeConfig {
+    /// Build a configuration in code, optionally on top of a file and the environment.
+    pub fn builder() -> VTCodeConfigBuilder {
+        VTCodeConfigBuilder::default()
+    }
+
+    /// Parse configuration text. Files, the config editor and
+    /// [`VTCodeConfigBuilder`] all go through this, so they accept the same values.
+    pub fn from_toml_str(content: &str) -> Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+
     /// Bootstrap project with config + gitignore
     pub fn bootstrap_project<P: AsRef<Path>>(workspace: P, force: bool) -> Result<Vec<String>> {
         Self::bootstrap_project_with_options(workspace, force, false)
@@ -291,6 +325,11 @@ impl ConfigManager {
         dirs::home_dir()
     }
 
+    /// Path of the user-level configuration file (~/.vtcode/vtcode.toml)
+    pub fn user_config_path() -> Option<PathBuf> {
+        Self::get_home_dir().map(|home| home.join(".vtcode").join("vtcode.toml"))
+    }
+
     /// Load configuration from a specific workspace
     pub fn load_from_workspace(workspace: impl AsRef<Path>) -> Result<Self> {
         let workspace = workspace.as_ref();
@@ -301,65 +340,68 @@ impl ConfigManager {
             .as_ref()
             .and_then(|pm| pm.identify_current_project().ok());
 
-        // Try vtcode.toml in workspace root first
-        let config_path = workspace.join("vtcode.toml");
-        if config_path.exists() {
-            let config = Self::load_from_file(&config_path)?;
+        let Some(config_path) =
+            Self::find_config_file(workspace, project_manager.as_ref(), project_name.as_deref())
+        else {
+            // Use default configuration if no file found
             return Ok(Self {
-                config: config.config,
-                config_path: config.config_path,
+                config: VTCodeConfig::default(),
+                config_path: None,
                 project_manager,
                 project_name,
             });
+        };
+
+        let config = Self::load_from_file(&config_path)?;
+        Ok(Self {
+            config: config.config,
+            config_path: config.config_path,
+            project_manager,
+            project_name,
+        })
+    }
+
+    /// Path of the file [`ConfigManager::load_from_workspace`] would load.
+    pub fn workspace_config_path(workspace: impl AsRef<Path>) -> Option<PathBuf> {
+        let workspace = workspace.as_ref();
+        let project_manager = SimpleProjectManager::new(workspace.to_path_buf());
+        let project_name = project_manager.identify_current_project().ok();
+        Self::find_config_file(workspace, Some(&project_manager), project_name.as_deref())
+    }
+
+    fn find_config_file(
+        workspace: &Path,
+        project_manager: Option<&SimpleProjectManager>,
+        project_name: Option<&str>,
+    ) -> Option<PathBuf> {
+        // Try vtcode.toml in workspace root first
+        let config_path = workspace.join("vtcode.toml");
+        if config_path.exists() {
+            return Some(config_path);
         }
 
         // Try .vtcode/vtcode.toml in workspace
         let fallback_path = workspace.join(".vtcode").join("vtcode.toml");
         if fallback_path.exists() {
-            let config = Self::load_from_file(&fallback_path)?;
-            return Ok(Self {
-                config: config.config,
-                config_path: config.config_path,
-                project_manager,
-                project_name,
-            });
+            return Some(fallback_path);
         }
 
         // Try ~/.vtcode/vtcode.toml in user home directory
-        if let Some(home_dir) = Self::get_home_dir() {
-            let home_config_path = home_dir.join(".vtcode").join("vtcode.toml");
-            if home_config_path.exists() {
-                let config = Self::load_from_file(&home_config_path)?;
-                return Ok(Self {
-                    config: config.config,
-                    config_path: config.config_path,
-                    project_manager,
-                    project_name,
-                });
-            }
+        if let Some(home_config_path) = Self::user_config_path()
+            && home_config_path.exists()
+        {
+            return Some(home_config_path);
         }
 
         // Try project-specific configuration
-        if let (Some(pm), Some(pname)) = (&project_manager, &project_name) {
+        if let (Some(pm), Some(pname)) = (project_manager, project_name) {
             let project_config_path = pm.config_dir(pname).join("vtcode.toml");
             if project_config_path.exists() {
-                let config = Self::load_from_file(&project_config_path)?;
-                return Ok(Self {
-                    config: config.config,
-                    config_path: config.config_path,
-                    project_manager: Some(pm.clone()),
-                    project_name: Some(pname.clone()),
-                });
+                return Some(project_config_path);
             }
         }
 
-        // Use default configuration if no file found
-        Ok(Self {
-            config: VTCodeConfig::default(),
-            config_path: None,
-            project_manager,
-            project_name,
-        })
+        None
     }
 
     /// Load configuration from a specific file
@@ -368,7 +410,7 @@ impl ConfigManager {
         let content = std::fs::read_to_string(path)
             .with_context(|| format!("Failed to read config file: {}", path.display()))?;
 
-        let config: VTCodeConfig = toml::from_str(&content)
+        let config = VTCodeConfig::from_toml_str(&content)
             .with_context(|| format!("Failed to parse config file: {}", path.display()))?;
 
         // Initialize project manager but don't set project name since we're loading from file
@@ -176,20 +176,36 @@ pub mod constants;
 pub mod context;
 pub mod core;
 pub mod defaults;
+pub mod editor;
 pub mod loader;
+pub mod model_catalog;
+pub mod model_pin;
 pub mod models;
+pub mod oauth;
 pub mod router;
 pub mod telemetry;
 pub mod types;
 
 // Re-export main types for backward compatibility
-pub use context::{ContextFeaturesConfig, LedgerConfig};
+pub use context::{
+    CommandContextProviderConfig, ContextFeaturesConfig, ContextProvidersConfig, LedgerConfig,
+    SummarizationConfig, SummaryDetail, ToolResultSpillConfig,
+};
 pub use core::{
-    AgentConfig, AutomationConfig, CommandsConfig, FullAutoConfig, SecurityConfig, ToolPolicy,
-    ToolsConfig,
+    AgentConfig, AgentDualAnswerConfig, AgentIdleConfig, AgentPinConfig,
+    AgentReproducibilityConfig, AgentsConfig, AutomationConfig, BootstrapConfig, BudgetAction,
+    CodeExecutionConfig, CommandsConfig, ConcurrencyConfig, ConstrainedDecodingConfig,
+    CoverageCommand, CoverageConfig, CredentialProfile, CredentialsConfig, DualAnswerLayout,
+    EmbeddingProvider, FailoverConfig, FailoverTargetConfig, FullAutoConfig, ImportsConfig,
+    KeychainEntry, LlmConfig, LocalModelConfig, NetworkConfig, OAuthSettings, OfflineConfig,
+    PathDisplay, ProviderCredentials, ProviderNetworkConfig, RefusalConfig, RefusalRetry,
+    SecurityConfig, SemanticSearchConfig, SessionsConfig, SpendConfig, SqlConfig,
+    SqlDatabaseConfig, SqlEngine, SubagentDefinition, SubagentsConfig, ToolMockEntry,
+    ToolMocksConfig, ToolPolicy, ToolVerbosityConfig, ToolsConfig, VerbosityPolicy,
+    VulnerabilityScanConfig, WorkspaceEnvConfig, WriteQuotaConfig,
 };
 pub use defaults::{ContextStoreDefaults, PerformanceDefaults, ScenarioDefaults};
-pub use loader::{ConfigManager, VTCodeConfig};
+pub use loader::{ConfigManager, VTCodeConfig, VTCodeConfigBuilder};
 pub use router::{ComplexityModelMap, ResourceBudget, RouterConfig};
 pub use telemetry::TelemetryConfig;
 pub use types::ReasoningEffortLevel;
@@ -213,12 +229,158 @@ impl Default for ToolOutputMode {
 pub struct UiConfig {
     #[serde(default = "default_tool_output_mode")]
     pub tool_output_mode: ToolOutputMode,
+
+    /// Open the symbol outline panel automatically when the agent works on a file
+    #[serde(default)]
+    pub show_symbol_outline: bool,
+
+    /// Allow copying to and pasting from the system clipboard
+    #[serde(default = "default_clipboard")]
+    pub clipboard: bool,
+
+    /// List the tool results behind claims such as "tests pass" under each answer
+    #[serde(default = "default_show_claim_evidence")]
+    pub show_claim_evidence: bool,
+
+    /// Summarize commits and CI status since the previous session before the first prompt
+    #[serde(default = "default_session_briefing")]
+    pub session_briefing: bool,
+
+    /// Show where each turn's time went (first token, generation, tool calls,
+    /// overhead) in the status bar when the turn ends
+    #[serde(default)]
+    pub show_latency_breakdown: bool,
+
+    #[serde(default)]
+    pub performance: UiPerformanceConfig,
+
+    #[serde(default)]
+    pub voice: VoiceConfig,
+}
+
+/// Redraw settings for slow terminals, under `[ui.performance]`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UiPerformanceConfig {
+    /// `auto` switches to `minimal` over SSH; `--render` overrides it
+    #[serde(default)]
+    pub render_mode: types::RenderMode,
+
+    /// Shortest gap between redraws of streamed output in minimal mode
+    #[serde(default = "default_minimal_frame_interval_ms")]
+    pub minimal_frame_interval_ms: u64,
+}
+
+fn default_minimal_frame_interval_ms() -> u64 {
+    200
+}
+
+impl Default for UiPerformanceConfig {
+    fn default() -> Self {
+        Self {
+            render_mode: types::RenderMode::default(),
+            minimal_frame_interval_ms: default_minimal_frame_interval_ms(),
+        }
+    }
+}
+
+/// Where voice input is transcribed, under `[ui.voice]`
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptionBackend {
+    /// A local whisper.cpp build; audio never leaves the machine
+    #[default]
+    WhisperCpp,
+    /// An OpenAI-compatible `/audio/transcriptions` endpoint
+    #[serde(rename = "openai")]
+    OpenAI,
+}
+
+/// Push-to-talk voice input (Alt+V), under `[ui.voice]`. Only builds with the
+/// `voice` feature can record.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct VoiceConfig {
+    #[serde(default)]
+    pub backend: TranscriptionBackend,
+
+    /// whisper.cpp command line program
+    #[serde(default = "default_whisper_command")]
+    pub whisper_command: String,
+
+    /// Path to the ggml model whisper.cpp loads, e.g. `ggml-base.en.bin`
+    #[serde(default)]
+    pub whisper_model: Option<String>,
+
+    /// Transcription model for the `openai` backend
+    #[serde(default = "default_transcription_model")]
+    pub model: String,
+
+    /// Root of an OpenAI-compatible API; defaults to OpenAI
+    #[serde(default)]
+    pub base_url: Option<String>,
+
+    /// Environment variable holding the API key; defaults to `OPENAI_API_KEY`
+    #[serde(default)]
+    pub api_key_env: Option<String>,
+
+    /// Spoken language as an ISO 639-1 code; detected when unset
+    #[serde(default)]
+    pub language: Option<String>,
+
+    /// Recording stops by itself after this long
+    #[serde(default = "default_max_recording_seconds")]
+    pub max_seconds: u64,
+}
+
+fn default_whisper_command() -> String {
+    "whisper-cli".to_string()
+}
+
+fn default_transcription_model() -> String {
+    "whisper-1".to_string()
+}
+
+fn default_max_recording_seconds() -> u64 {
+    120
+}
+
+impl Default for VoiceConfig {
+    fn default() -> Self {
+        Self {
+            backend: TranscriptionBackend::default(),
+            whisper_command: default_whisper_command(),
+            whisper_model: None,
+            model: default_transcription_model(),
+            base_url: None,
+            api_key_env: None,
+            language: None,
+            max_seconds: default_max_recording_seconds(),
+        }
+    }
+}
+
+fn default_clipboard() -> bool {
+    true
+}
+
+fn default_show_claim_evidence() -> bool {
+    true
+}
+
+fn default_session_briefing() -> bool {
+    true
 }
 
 impl Default for UiConfig {
     fn default() -> Self {
         Self {
             tool_output_mode: default_tool_output_mode(),
+            show_symbol_outline: false,
+            clipboard: default_clipboard(),
+            show_claim_evidence: default_show_claim_evidence(),
+            session_briefing: default_session_briefing(),
+            show_latency_breakdown: false,
+            performance: UiPerformanceConfig::default(),
+            voice: VoiceConfig::default(),
         }
     }
 }
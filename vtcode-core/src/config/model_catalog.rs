@@ -0,0 +1,133 @@
+//! Model capabilities from `docs/models.json`
+//!
+//! The catalog is compiled in and looked up by provider and model id, so
+//! startup checks can tell whether a model calls tools and how large its
+//! context window is without a network request.
+
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+static CATALOG: Lazy<HashMap<String, CatalogProvider>> = Lazy::new(|| {
+    serde_json::from_str(include_str!("../../../docs/models.json")).unwrap_or_else(|err| {
+        tracing::warn!(error = %err, "failed to parse the bundled model catalog");
+        HashMap::new()
+    })
+});
+
+#[derive(Deserialize)]
+struct CatalogProvider {
+    #[serde(default)]
+    models: HashMap<String, CatalogModel>,
+}
+
+#[derive(Deserialize)]
+struct CatalogModel {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    tool_call: bool,
+    #[serde(default)]
+    limit: CatalogLimit,
+    #[serde(default)]
+    cost: Option<ModelPricing>,
+}
+
+#[derive(Deserialize, Default)]
+struct CatalogLimit {
+    context: Option<usize>,
+    output: Option<usize>,
+}
+
+/// What the catalog records about a model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelCapabilities {
+    pub name: String,
+    pub tool_call: bool,
+    pub context_tokens: Option<usize>,
+    pub output_tokens: Option<usize>,
+}
+
+/// USD per million tokens, as listed in the catalog.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct ModelPricing {
+    pub input: f64,
+    pub output: f64,
+    /// Cached input read back; billed at `input` when not listed
+    #[serde(default)]
+    pub cache_read: Option<f64>,
+    /// Input written to the cache; billed at `input` when not listed
+    #[serde(default)]
+    pub cache_write: Option<f64>,
+}
+
+/// Capabilities of `model` as served by `provider`, `None` when the catalog
+/// does not list it.
+pub fn capabilities(provider: &str, model: &str) -> Option<ModelCapabilities> {
+    let provider = CATALOG.get(catalog_provider_id(provider))?;
+    let entry = provider.models.get(model)?;
+    Some(ModelCapabilities {
+        name: if entry.name.is_empty() {
+            model.to_string()
+        } else {
+            entry.name.clone()
+        },
+        tool_call: entry.tool_call,
+        context_tokens: entry.limit.context,
+        output_tokens: entry.limit.output,
+    })
+}
+
+/// Token prices of `model` as served by `provider`, `None` when the catalog
+/// does not list them.
+pub fn pricing(provider: &str, model: &str) -> Option<ModelPricing> {
+    CATALOG
+        .get(catalog_provider_id(provider))?
+        .models
+        .get(model)?
+        .cost
+}
+
+/// Catalog models of `provider` that call tools, largest context first.
+pub fn tool_capable_models(provider: &str) -> Vec<String> {
+    let Some(provider) = CATALOG.get(catalog_provider_id(provider)) else {
+        return Vec::new();
+    };
+    let mut models: Vec<(&String, &CatalogModel)> = provider
+        .models
+        .iter()
+        .filter(|(_, model)| model.tool_call)
+        .collect();
+    models.sort_by(|(a_id, a), (b_id, b)| {
+        b.limit
+            .context
+            .cmp(&a.limit.context)
+            .then_with(|| a_id.cmp(b_id))
+    });
+    models.into_iter().map(|(id, _)| id.clone()).collect()
+}
+
+/// The catalog files Gemini models under `google`.
+fn catalog_provider_id(provider: &str) -> &str {
+    match provider {
+        "gemini" => "google",
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn looks_up_bundled_models() {
+        let gpt = capabilities("openai", "gpt-4.1").expect("gpt-4.1 is listed");
+        assert!(gpt.tool_call);
+        assert!(gpt.context_tokens.is_some());
+        assert!(capabilities("gemini", "gemini-2.5-pro").is_some());
+        assert!(capabilities("openai", "no-such-model").is_none());
+        assert!(!tool_capable_models("deepseek").is_empty());
+        let price = pricing("deepseek", "deepseek-chat").expect("deepseek-chat is priced");
+        assert_eq!(price.cache_read, Some(0.07));
+    }
+}
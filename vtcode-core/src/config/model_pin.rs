@@ -0,0 +1,228 @@
+//! Workspace model pins
+//!
+//! A workspace `vtcode.toml` with `[agent.pin] enabled = true` pins its
+//! `provider` and `default_model`. [`select_model`] keeps `--provider` and
+//! `--model` from silently replacing the pin, and [`check_pinned_model`]
+//! validates the pinned model against the bundled model catalog.
+
+use crate::config::core::AgentPinConfig;
+use crate::config::model_catalog::{capabilities, tool_capable_models};
+use anyhow::{Result, bail};
+use std::path::Path;
+
+/// Models suggested when a pinned model fails a check.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// How `--provider`/`--model` treat a workspace pin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PinPolicy {
+    /// Refuse a different model until the user picks one of the others
+    #[default]
+    Strict,
+    /// Keep the pinned model (`--respect-pin`)
+    Respect,
+    /// Use the requested model for this run (`--override-pin`)
+    Override,
+}
+
+/// The provider and model a workspace pins, and the file that pins them.
+#[derive(Debug, Clone, Copy)]
+pub struct ModelPin<'a> {
+    pub provider: &'a str,
+    pub model: &'a str,
+    pub source: &'a Path,
+}
+
+/// The provider and model a run uses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelSelection {
+    pub provider: String,
+    pub model: String,
+    /// Whether the selection is the workspace pin
+    pub pinned: bool,
+    /// Note for the user when the pin and the command line disagreed
+    pub notice: Option<String>,
+}
+
+/// Pick the provider and model from the command line, the pin and the
+/// configured defaults.
+pub fn select_model(
+    pin: Option<ModelPin<'_>>,
+    cli_provider: Option<&str>,
+    cli_model: Option<&str>,
+    default_provider: &str,
+    default_model: &str,
+    policy: PinPolicy,
+) -> Result<ModelSelection> {
+    let Some(pin) = pin else {
+        return Ok(ModelSelection {
+            provider: cli_provider.unwrap_or(default_provider).to_string(),
+            model: cli_model.unwrap_or(default_model).to_string(),
+            pinned: false,
+            notice: None,
+        });
+    };
+    let provider = cli_provider.unwrap_or(pin.provider);
+    let model = cli_model.unwrap_or(pin.model);
+    let pinned = format!("{}/{}", pin.provider, pin.model);
+    let requested = format!("{provider}/{model}");
+    if provider == pin.provider && model == pin.model {
+        return Ok(ModelSelection {
+            provider: provider.to_string(),
+            model: model.to_string(),
+            pinned: true,
+            notice: None,
+        });
+    }
+    match policy {
+        PinPolicy::Strict => bail!(
+            "{} pins {pinned} for this workspace, but {requested} was requested. \
+             Pass --respect-pin to use {pinned}, or --override-pin to use {requested} for this run.",
+            pin.source.display()
+        ),
+        PinPolicy::Respect => Ok(ModelSelection {
+            provider: pin.provider.to_string(),
+            model: pin.model.to_string(),
+            pinned: true,
+            notice: Some(format!(
+                "Using {pinned} pinned by {} instead of {requested}.",
+                pin.source.display()
+            )),
+        }),
+        PinPolicy::Override => Ok(ModelSelection {
+            provider: provider.to_string(),
+            model: model.to_string(),
+            pinned: false,
+            notice: Some(format!(
+                "Overriding the {pinned} pin from {} with {requested} for this run.",
+                pin.source.display()
+            )),
+        }),
+    }
+}
+
+/// Check a pinned model against the catalog. Returns a warning when the
+/// catalog does not list the model, and an error naming alternatives when it
+/// lacks a capability the pin requires.
+pub fn check_pinned_model(
+    settings: &AgentPinConfig,
+    pin: ModelPin<'_>,
+    context_budget: usize,
+) -> Result<Option<String>> {
+    let Some(model) = capabilities(pin.provider, pin.model) else {
+        return Ok(Some(format!(
+            "{}/{} is not in the model catalog; its capabilities were not checked.",
+            pin.provider, pin.model
+        )));
+    };
+    let alternatives = || {
+        let suggestions: Vec<String> = tool_capable_models(pin.provider)
+            .into_iter()
+            .filter(|id| {
+                settings.min_context_tokens.is_none_or(|min| {
+                    capabilities(pin.provider, id)
+                        .and_then(|candidate| candidate.context_tokens)
+                        .is_some_and(|context| context >= min)
+                })
+            })
+            .take(MAX_SUGGESTIONS)
+            .collect();
+        if suggestions.is_empty() {
+            format!("Change [agent] default_model in {}.", pin.source.display())
+        } else {
+            format!(
+                "Change [agent] default_model in {} to a model such as {}.",
+                pin.source.display(),
+                suggestions.join(", ")
+            )
+        }
+    };
+    if settings.require_tool_calls && !model.tool_call {
+        bail!(
+            "The pinned model {} ({}) cannot call tools, which the agent needs. {}",
+            pin.model,
+            model.name,
+            alternatives()
+        );
+    }
+    if let Some(context) = model.context_tokens {
+        if let Some(min) = settings.min_context_tokens
+            && context < min
+        {
+            bail!(
+                "The pinned model {} has a {context}-token context window, below the {min} tokens [agent.pin] min_context_tokens requires. {}",
+                pin.model,
+                alternatives()
+            );
+        }
+        if context_budget > context {
+            return Ok(Some(format!(
+                "[context] max_context_tokens is {context_budget}, more than the {context}-token window of {}; lower it so trimming starts in time.",
+                pin.model
+            )));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_line_models_need_an_explicit_pin_choice() {
+        let source = Path::new("/repo/vtcode.toml");
+        let pin = ModelPin {
+            provider: "openai",
+            model: "gpt-4.1",
+            source,
+        };
+        let select = |model, policy| {
+            select_model(Some(pin), None, model, "gemini", "gemini-2.5-flash", policy)
+        };
+
+        let kept = select(None, PinPolicy::Strict).unwrap();
+        assert_eq!((kept.model.as_str(), kept.pinned), ("gpt-4.1", true));
+        let err = select(Some("gpt-5"), PinPolicy::Strict).unwrap_err();
+        assert!(err.to_string().contains("--override-pin"));
+        let respected = select(Some("gpt-5"), PinPolicy::Respect).unwrap();
+        assert_eq!(respected.model, "gpt-4.1");
+        let overridden = select(Some("gpt-5"), PinPolicy::Override).unwrap();
+        assert_eq!(
+            (overridden.model.as_str(), overridden.pinned),
+            ("gpt-5", false)
+        );
+        assert!(overridden.notice.is_some());
+    }
+
+    #[test]
+    fn pinned_models_are_checked_against_the_catalog() {
+        let source = Path::new("/repo/vtcode.toml");
+        let pin = ModelPin {
+            provider: "openai",
+            model: "gpt-4.1",
+            source,
+        };
+        let mut settings = AgentPinConfig::default();
+        assert!(check_pinned_model(&settings, pin, 1_000).unwrap().is_none());
+        assert!(
+            check_pinned_model(&settings, pin, 100_000_000)
+                .unwrap()
+                .is_some()
+        );
+
+        settings.min_context_tokens = Some(100_000_000);
+        let err = check_pinned_model(&settings, pin, 1_000).unwrap_err();
+        assert!(err.to_string().contains("min_context_tokens"));
+
+        let unlisted = ModelPin {
+            model: "local-finetune",
+            ..pin
+        };
+        assert!(
+            check_pinned_model(&settings, unlisted, 1_000)
+                .unwrap()
+                .is_some()
+        );
+    }
+}
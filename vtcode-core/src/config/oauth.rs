@@ -0,0 +1,502 @@
+//! OAuth device flow sign-in and token storage for providers and gateways.
+//!
+//! `vtcode auth login --provider <name>` runs the device authorization grant
+//! (RFC 8628) against the endpoints in `[credentials.<name>.oauth]` and stores
+//! the resulting tokens under `~/.vtcode/auth/<name>.json`. [`TokenSource`]
+//! hands out access tokens to provider clients and refreshes them before they
+//! expire.
+
+use crate::config::core::{CredentialsConfig, OAuthSettings};
+use crate::utils::network;
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// Tokens issued for a provider.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OAuthToken {
+    pub access_token: String,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    #[serde(default = "default_token_type")]
+    pub token_type: String,
+    /// `None` when the server did not say when the token expires
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub scope: Option<String>,
+}
+
+impl OAuthToken {
+    fn from_response(response: TokenResponse, issued_at: DateTime<Utc>) -> Self {
+        Self {
+            access_token: response.access_token,
+            refresh_token: response.refresh_token,
+            token_type: response.token_type.unwrap_or_else(default_token_type),
+            expires_at: response
+                .expires_in
+                .map(|secs| issued_at + ChronoDuration::seconds(secs as i64)),
+            scope: response.scope,
+        }
+    }
+
+    /// Whether the token expires within `margin` of now.
+    pub fn expires_within(&self, margin: Duration) -> bool {
+        let margin = ChronoDuration::from_std(margin).unwrap_or(ChronoDuration::zero());
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= Utc::now() + margin)
+    }
+}
+
+fn default_token_type() -> String {
+    "Bearer".to_string()
+}
+
+/// Stored tokens, one JSON file per provider.
+#[derive(Debug, Clone)]
+pub struct TokenStore {
+    dir: PathBuf,
+}
+
+impl TokenStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Store under `~/.vtcode/auth`.
+    pub fn default_location() -> Result<Self> {
+        let home = dirs::home_dir().context("Could not determine home directory")?;
+        Ok(Self::new(home.join(".vtcode").join("auth")))
+    }
+
+    pub fn path_for(&self, provider: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", provider.to_lowercase()))
+    }
+
+    pub fn load(&self, provider: &str) -> Result<Option<OAuthToken>> {
+        let path = self.path_for(provider);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read token file {}", path.display()))?;
+        let token = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse token file {}", path.display()))?;
+        Ok(Some(token))
+    }
+
+    /// Write the token, readable only by the current user.
+    pub fn save(&self, provider: &str, token: &OAuthToken) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create token directory {}", self.dir.display()))?;
+        let path = self.path_for(provider);
+        let temp = path.with_extension("json.tmp");
+        let content = serde_json::to_string_pretty(token).context("Failed to serialize token")?;
+        write_private(&temp, &content)
+            .with_context(|| format!("Failed to write token file {}", temp.display()))?;
+        fs::rename(&temp, &path)
+            .with_context(|| format!("Failed to write token file {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Delete the stored token. Returns whether one existed.
+    pub fn remove(&self, provider: &str) -> Result<bool> {
+        let path = self.path_for(provider);
+        if !path.exists() {
+            return Ok(false);
+        }
+        fs::remove_file(&path)
+            .with_context(|| format!("Failed to remove token file {}", path.display()))?;
+        Ok(true)
+    }
+}
+
+#[cfg(unix)]
+fn write_private(path: &Path, content: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    file.write_all(content.as_bytes())
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &Path, content: &str) -> std::io::Result<()> {
+    fs::write(path, content)
+}
+
+/// Codes returned by the device authorization endpoint.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeviceAuthorization {
+    pub device_code: String,
+    pub user_code: String,
+    #[serde(alias = "verification_url")]
+    pub verification_uri: String,
+    #[serde(default)]
+    pub verification_uri_complete: Option<String>,
+    pub expires_in: u64,
+    #[serde(default = "default_poll_interval")]
+    pub interval: u64,
+}
+
+fn default_poll_interval() -> u64 {
+    5
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    token_type: Option<String>,
+    #[serde(default)]
+    expires_in: Option<u64>,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    #[serde(default)]
+    scope: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenErrorResponse {
+    error: String,
+    #[serde(default)]
+    error_description: Option<String>,
+}
+
+impl TokenErrorResponse {
+    fn describe(&self) -> String {
+        match &self.error_description {
+            Some(description) => format!("{} ({})", self.error, description),
+            None => self.error.clone(),
+        }
+    }
+}
+
+/// What to do after a token request during device flow polling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PollStatus {
+    Pending,
+    SlowDown,
+    Denied,
+    Expired,
+    Failed,
+}
+
+impl PollStatus {
+    fn from_error(error: &str) -> Self {
+        match error {
+            "authorization_pending" => Self::Pending,
+            "slow_down" => Self::SlowDown,
+            "access_denied" => Self::Denied,
+            "expired_token" => Self::Expired,
+            _ => Self::Failed,
+        }
+    }
+}
+
+/// Client for the device authorization and token endpoints.
+#[derive(Debug, Clone)]
+pub struct DeviceFlowClient {
+    settings: OAuthSettings,
+    http: reqwest::Client,
+}
+
+impl DeviceFlowClient {
+    pub fn new(settings: OAuthSettings) -> Result<Self> {
+        Ok(Self {
+            settings,
+            http: network::client(None)?,
+        })
+    }
+
+    /// Start a sign-in and obtain the code the user enters in the browser.
+    pub async fn request_device_code(&self) -> Result<DeviceAuthorization> {
+        let scope = self.settings.scopes.join(" ");
+        let mut form = vec![("client_id", self.settings.client_id.as_str())];
+        if !scope.is_empty() {
+            form.push(("scope", scope.as_str()));
+        }
+        if let Some(audience) = &self.settings.audience {
+            form.push(("audience", audience.as_str()));
+        }
+
+        let response = self
+            .http
+            .post(&self.settings.device_authorization_url)
+            .form(&form)
+            .send()
+            .await
+            .context("Failed to reach the device authorization endpoint")?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            bail!("Device authorization failed (HTTP {}): {}", status, body);
+        }
+        response
+            .json()
+            .await
+            .context("Invalid device authorization response")
+    }
+
+    /// Poll the token endpoint until the user approves or denies the sign-in.
+    pub async fn poll_for_token(&self, device: &DeviceAuthorization) -> Result<OAuthToken> {
+        let deadline = Utc::now() + ChronoDuration::seconds(device.expires_in as i64);
+        let mut interval = device.interval.max(1);
+        loop {
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+            if Utc::now() > deadline {
+                bail!("The device code expired before sign-in completed");
+            }
+            let result = self
+                .token_request(&[
+                    ("grant_type", DEVICE_CODE_GRANT),
+                    ("device_code", device.device_code.as_str()),
+                    ("client_id", self.settings.client_id.as_str()),
+                ])
+                .await?;
+            let error = match result {
+                Ok(token) => return Ok(token),
+                Err(error) => error,
+            };
+            match PollStatus::from_error(&error.error) {
+                PollStatus::Pending => {}
+                PollStatus::SlowDown => interval += 5,
+                PollStatus::Denied => bail!("Sign-in was denied"),
+                PollStatus::Expired => bail!("The device code expired before sign-in completed"),
+                PollStatus::Failed => bail!("Sign-in failed: {}", error.describe()),
+            }
+        }
+    }
+
+    /// Exchange the refresh token for a new access token.
+    ///
+    /// Servers that do not rotate refresh tokens omit one from the response, in
+    /// which case the existing refresh token is kept.
+    pub async fn refresh(&self, token: &OAuthToken) -> Result<OAuthToken> {
+        let refresh_token = token
+            .refresh_token
+            .as_deref()
+            .ok_or_else(|| anyhow!("No refresh token stored"))?;
+        let mut refreshed = self
+            .token_request(&[
+                ("grant_type", "refresh_token"),
+                ("refresh_token", refresh_token),
+                ("client_id", self.settings.client_id.as_str()),
+            ])
+            .await?
+            .map_err(|error| anyhow!("Token refresh failed: {}", error.describe()))?;
+        if refreshed.refresh_token.is_none() {
+            refreshed.refresh_token = token.refresh_token.clone();
+        }
+        Ok(refreshed)
+    }
+
+    async fn token_request(
+        &self,
+        form: &[(&str, &str)],
+    ) -> Result<std::result::Result<OAuthToken, TokenErrorResponse>> {
+        let issued_at = Utc::now();
+        let response = self
+            .http
+            .post(&self.settings.token_url)
+            .form(form)
+            .send()
+            .await
+            .context("Failed to reach the token endpoint")?;
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .context("Failed to read token response")?;
+        if status.is_success() {
+            let parsed: TokenResponse =
+                serde_json::from_str(&body).context("Invalid token response")?;
+            return Ok(Ok(OAuthToken::from_response(parsed, issued_at)));
+        }
+        match serde_json::from_str::<TokenErrorResponse>(&body) {
+            Ok(error) => Ok(Err(error)),
+            Err(_) => bail!("Token request failed (HTTP {}): {}", status, body),
+        }
+    }
+}
+
+/// Shared access token for one provider, refreshed on demand.
+#[derive(Clone)]
+pub struct TokenSource {
+    provider: String,
+    client: DeviceFlowClient,
+    store: TokenStore,
+    margin: Duration,
+    token: Arc<Mutex<OAuthToken>>,
+}
+
+impl TokenSource {
+    pub fn new(
+        provider: impl Into<String>,
+        settings: OAuthSettings,
+        store: TokenStore,
+        token: OAuthToken,
+    ) -> Result<Self> {
+        Ok(Self {
+            provider: provider.into().to_lowercase(),
+            margin: Duration::from_secs(settings.refresh_margin_secs),
+            client: DeviceFlowClient::new(settings)?,
+            store,
+            token: Arc::new(Mutex::new(token)),
+        })
+    }
+
+    /// Token source for a provider that has signed in, or `None` if it has not.
+    pub fn load(
+        provider: &str,
+        settings: &OAuthSettings,
+        store: TokenStore,
+    ) -> Result<Option<Self>> {
+        store
+            .load(provider)?
+            .map(|token| Self::new(provider, settings.clone(), store, token))
+            .transpose()
+    }
+
+    /// Token source for `provider` when it has OAuth settings and a stored sign-in.
+    pub fn for_provider(provider: &str, credentials: &CredentialsConfig) -> Result<Option<Self>> {
+        let Some(settings) = credentials.oauth_for(provider) else {
+            return Ok(None);
+        };
+        Self::load(provider, settings, TokenStore::default_location()?)
+    }
+
+    pub fn provider(&self) -> &str {
+        &self.provider
+    }
+
+    /// Current access token, refreshed first if it is about to expire.
+    pub async fn access_token(&self) -> Result<String> {
+        let mut token = self.token.lock().await;
+        if token.expires_within(self.margin) {
+            self.refresh_locked(&mut token).await?;
+        }
+        Ok(token.access_token.clone())
+    }
+
+    /// Refresh after the server rejected `rejected`.
+    ///
+    /// If another request already replaced that token, the newer one is
+    /// returned without contacting the token endpoint again.
+    pub async fn force_refresh(&self, rejected: &str) -> Result<String> {
+        let mut token = self.token.lock().await;
+        if token.access_token == rejected {
+            self.refresh_locked(&mut token).await?;
+        }
+        Ok(token.access_token.clone())
+    }
+
+    async fn refresh_locked(&self, token: &mut OAuthToken) -> Result<()> {
+        if token.refresh_token.is_none() {
+            bail!(
+                "The {} access token expired. Run `vtcode auth login --provider {}` to sign in again.",
+                self.provider,
+                self.provider
+            );
+        }
+        let refreshed = self.client.refresh(token).await.with_context(|| {
+            format!(
+                "Could not refresh the {} access token. Run `vtcode auth login --provider {}` to sign in again.",
+                self.provider, self.provider
+            )
+        })?;
+        self.store.save(&self.provider, &refreshed)?;
+        *token = refreshed;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn token(expires_at: Option<DateTime<Utc>>) -> OAuthToken {
+        OAuthToken {
+            access_token: "access".to_string(),
+            refresh_token: Some("refresh".to_string()),
+            token_type: default_token_type(),
+            expires_at,
+            scope: None,
+        }
+    }
+
+    #[test]
+    fn computes_expiry_from_token_response() {
+        let issued_at = Utc::now();
+        let parsed: TokenResponse = serde_json::from_str(
+            r#"{"access_token":"abc","expires_in":3600,"refresh_token":"def"}"#,
+        )
+        .expect("token response");
+        let token = OAuthToken::from_response(parsed, issued_at);
+        assert_eq!(token.token_type, "Bearer");
+        assert_eq!(
+            token.expires_at,
+            Some(issued_at + ChronoDuration::seconds(3600))
+        );
+        assert!(!token.expires_within(Duration::from_secs(60)));
+        assert!(token.expires_within(Duration::from_secs(3601)));
+        assert!(!self::token(None).expires_within(Duration::from_secs(u32::MAX as u64)));
+    }
+
+    #[test]
+    fn stores_tokens_per_provider() {
+        let dir = TempDir::new().expect("tempdir");
+        let store = TokenStore::new(dir.path().join("auth"));
+        assert!(store.load("openai").expect("load").is_none());
+
+        let saved = token(Some(Utc::now()));
+        store.save("OpenAI", &saved).expect("save");
+        assert_eq!(store.load("openai").expect("load"), Some(saved));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(store.path_for("openai"))
+                .expect("metadata")
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        assert!(store.remove("openai").expect("remove"));
+        assert!(!store.remove("openai").expect("remove"));
+    }
+
+    #[test]
+    fn classifies_device_flow_errors() {
+        assert_eq!(
+            PollStatus::from_error("authorization_pending"),
+            PollStatus::Pending
+        );
+        assert_eq!(PollStatus::from_error("slow_down"), PollStatus::SlowDown);
+        assert_eq!(PollStatus::from_error("access_denied"), PollStatus::Denied);
+        assert_eq!(PollStatus::from_error("expired_token"), PollStatus::Expired);
+        assert_eq!(PollStatus::from_error("invalid_grant"), PollStatus::Failed);
+
+        let device: DeviceAuthorization = serde_json::from_str(
+            r#"{"device_code":"d","user_code":"ABCD-EFGH","verification_url":"https://example.com/device","expires_in":900}"#,
+        )
+        .expect("device authorization");
+        assert_eq!(device.verification_uri, "https://example.com/device");
+        assert_eq!(device.interval, 5);
+    }
+}
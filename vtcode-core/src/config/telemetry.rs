@@ -1,15 +1,43 @@
 use serde::{Deserialize, Serialize};
 
+/// When the telemetry journal calls `fsync` on its log files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JournalFsyncPolicy {
+    /// Leave syncing to the operating system
+    Never,
+    /// Sync after every batch written to disk
+    #[default]
+    Batch,
+    /// Sync only on explicit flush and on shutdown
+    Shutdown,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TelemetryConfig {
     #[serde(default = "default_true")]
     pub trajectory_enabled: bool,
+
+    /// Number of buffered records that triggers a journal write
+    #[serde(default = "default_journal_batch_size")]
+    pub journal_batch_size: usize,
+
+    /// Maximum delay in milliseconds before buffered records are written
+    #[serde(default = "default_journal_flush_interval_ms")]
+    pub journal_flush_interval_ms: u64,
+
+    /// Fsync policy for journal files
+    #[serde(default)]
+    pub journal_fsync: JournalFsyncPolicy,
 }
 
 impl Default for TelemetryConfig {
     fn default() -> Self {
         Self {
             trajectory_enabled: true,
+            journal_batch_size: default_journal_batch_size(),
+            journal_flush_interval_ms: default_journal_flush_interval_ms(),
+            journal_fsync: JournalFsyncPolicy::default(),
         }
     }
 }
@@ -17,3 +45,11 @@ impl Default for TelemetryConfig {
 fn default_true() -> bool {
     true
 }
+
+fn default_journal_batch_size() -> usize {
+    64
+}
+
+fn default_journal_flush_interval_ms() -> u64 {
+    250
+}
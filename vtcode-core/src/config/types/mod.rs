@@ -149,17 +149,102 @@ impl<'de> Deserialize<'de> for UiSurfacePreference {
     }
 }
 
+/// How much the interactive chat UI redraws
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RenderMode {
+    /// Minimal over SSH, full otherwise
+    Auto,
+    Full,
+    /// Paced redraws without spinners or animations, for slow links
+    Minimal,
+}
+
+impl RenderMode {
+    /// String representation used in configuration and logging
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Full => "full",
+            Self::Minimal => "minimal",
+        }
+    }
+
+    /// Parse a render mode from configuration or command-line input
+    pub fn parse(value: &str) -> Option<Self> {
+        let normalized = value.trim();
+        if normalized.eq_ignore_ascii_case("auto") {
+            Some(Self::Auto)
+        } else if normalized.eq_ignore_ascii_case("full") {
+            Some(Self::Full)
+        } else if normalized.eq_ignore_ascii_case("minimal") {
+            Some(Self::Minimal)
+        } else {
+            None
+        }
+    }
+
+    /// Enumerate the accepted configuration values for validation messaging
+    pub fn allowed_values() -> &'static [&'static str] {
+        &["auto", "full", "minimal"]
+    }
+
+    /// Whether to render in low-bandwidth mode, resolving `auto` from the
+    /// SSH environment variables.
+    pub fn is_minimal(self) -> bool {
+        match self {
+            Self::Auto => crate::utils::clipboard::is_remote_session(),
+            Self::Full => false,
+            Self::Minimal => true,
+        }
+    }
+}
+
+impl Default for RenderMode {
+    fn default() -> Self {
+        Self::Auto
+    }
+}
+
+impl fmt::Display for RenderMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for RenderMode {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = String::deserialize(deserializer)?;
+        if let Some(parsed) = Self::parse(&raw) {
+            Ok(parsed)
+        } else {
+            tracing::warn!(
+                input = raw,
+                allowed = ?Self::allowed_values(),
+                "Invalid render mode provided; falling back to default"
+            );
+            Ok(Self::default())
+        }
+    }
+}
+
 /// Configuration for the agent
 #[derive(Debug, Clone)]
 pub struct AgentConfig {
     pub model: String,
     pub api_key: String,
     pub provider: String,
+    /// Endpoint override from `[agent] base_url`
+    pub base_url: Option<String>,
     pub workspace: std::path::PathBuf,
     pub verbose: bool,
     pub theme: String,
     pub reasoning_effort: ReasoningEffortLevel,
     pub ui_surface: UiSurfacePreference,
+    pub render_mode: RenderMode,
     pub prompt_cache: PromptCachingConfig,
 }
 
@@ -170,13 +255,13 @@ pub enum CapabilityLevel {
     Basic,
     /// Can read files
     FileReading,
-    /// Can read files and list directories
+    /// Can read, list and search files
     FileListing,
     /// Can read files, list directories, and run bash commands
     Bash,
     /// Can read files, list directories, run bash commands, and edit files
     Editing,
-    /// Full capabilities including code search
+    /// Full capabilities
     CodeSearch,
 }
 
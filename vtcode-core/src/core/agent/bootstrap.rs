@@ -11,9 +11,11 @@ use std::time::{SystemTime, UNIX_EPOCH};
 
 use anyhow::{Context, Result};
 
+use crate::config::ContextProvidersConfig;
 use crate::config::models::ModelId;
 use crate::config::types::{AgentConfig, SessionInfo};
 use crate::core::agent::compaction::CompactionEngine;
+use crate::core::context_providers::ContextProviderRegistry;
 use crate::core::conversation_summarizer::ConversationSummarizer;
 use crate::core::decision_tracker::DecisionTracker;
 use crate::core::error_recovery::ErrorRecoveryManager;
@@ -34,6 +36,7 @@ pub struct AgentComponentSet {
     pub summarizer: ConversationSummarizer,
     pub tree_sitter_analyzer: TreeSitterAnalyzer,
     pub compaction_engine: Arc<CompactionEngine>,
+    pub context_providers: Arc<ContextProviderRegistry>,
     pub session_info: SessionInfo,
 }
 
@@ -50,6 +53,7 @@ pub struct AgentComponentBuilder<'config> {
     summarizer: Option<ConversationSummarizer>,
     tree_sitter_analyzer: Option<TreeSitterAnalyzer>,
     compaction_engine: Option<Arc<CompactionEngine>>,
+    context_providers: Option<Arc<ContextProviderRegistry>>,
     session_info: Option<SessionInfo>,
 }
 
@@ -65,6 +69,7 @@ impl<'config> AgentComponentBuilder<'config> {
             summarizer: None,
             tree_sitter_analyzer: None,
             compaction_engine: None,
+            context_providers: None,
             session_info: None,
         }
     }
@@ -111,6 +116,12 @@ impl<'config> AgentComponentBuilder<'config> {
         self
     }
 
+    /// Override the context providers consulted on each request.
+    pub fn with_context_providers(mut self, registry: Arc<ContextProviderRegistry>) -> Self {
+        self.context_providers = Some(registry);
+        self
+    }
+
     /// Override the session metadata.
     pub fn with_session_info(mut self, session_info: SessionInfo) -> Self {
         self.session_info = Some(session_info);
@@ -124,7 +135,7 @@ impl<'config> AgentComponentBuilder<'config> {
             None => create_llm_client(self.config)?,
         };
 
-        let tree_sitter_analyzer = match self.tree_sitter_analyzer.take() {
+        let mut tree_sitter_analyzer = match self.tree_sitter_analyzer.take() {
             Some(analyzer) => analyzer,
             None => TreeSitterAnalyzer::new()
                 .context("Failed to initialize tree-sitter analyzer for agent components")?,
@@ -133,6 +144,8 @@ impl<'config> AgentComponentBuilder<'config> {
         let tool_registry = self
             .tool_registry
             .unwrap_or_else(|| Arc::new(ToolRegistry::new(self.config.workspace.clone())));
+        // Edits made through the registry keep the analyzer's trees up to date.
+        tree_sitter_analyzer.set_tree_cache(tool_registry.tree_cache());
 
         let decision_tracker = self.decision_tracker.unwrap_or_else(DecisionTracker::new);
 
@@ -146,6 +159,12 @@ impl<'config> AgentComponentBuilder<'config> {
             .compaction_engine
             .unwrap_or_else(|| Arc::new(CompactionEngine::new()));
 
+        let context_providers = self.context_providers.unwrap_or_else(|| {
+            Arc::new(ContextProviderRegistry::new(
+                &ContextProvidersConfig::default(),
+            ))
+        });
+
         let session_info = match self.session_info.take() {
             Some(info) => info,
             None => create_session_info()
@@ -160,6 +179,7 @@ impl<'config> AgentComponentBuilder<'config> {
             summarizer,
             tree_sitter_analyzer,
             compaction_engine,
+            context_providers,
             session_info,
         })
     }
@@ -203,8 +223,11 @@ mod tests {
             model: models::GEMINI_2_5_FLASH_PREVIEW.to_string(),
             api_key: "test-api-key".to_string(),
             provider: Provider::Gemini.to_string(),
+            base_url: None,
             workspace: temp_dir.path().to_path_buf(),
             verbose: false,
+            ui_surface: Default::default(),
+            render_mode: Default::default(),
             theme: "default".to_string(),
             reasoning_effort: ReasoningEffortLevel::default(),
             prompt_cache: PromptCachingConfig::default(),
@@ -226,8 +249,11 @@ mod tests {
             model: models::GEMINI_2_5_FLASH_PREVIEW.to_string(),
             api_key: "test-api-key".to_string(),
             provider: Provider::Gemini.to_string(),
+            base_url: None,
             workspace: temp_dir.path().to_path_buf(),
             verbose: true,
+            ui_surface: Default::default(),
+            render_mode: Default::default(),
             theme: "custom".to_string(),
             reasoning_effort: ReasoningEffortLevel::High,
             prompt_cache: PromptCachingConfig::default(),
@@ -5,6 +5,7 @@ use crate::config::models::ModelId;
 use crate::config::types::*;
 use crate::core::agent::bootstrap::{AgentComponentBuilder, AgentComponentSet};
 use crate::core::agent::compaction::CompactionEngine;
+use crate::core::context_providers::ContextProviderRegistry;
 use crate::core::conversation_summarizer::ConversationSummarizer;
 use crate::core::decision_tracker::DecisionTracker;
 use crate::core::error_recovery::{ErrorRecoveryManager, ErrorType};
@@ -25,6 +26,7 @@ pub struct Agent {
     summarizer: ConversationSummarizer,
     tree_sitter_analyzer: TreeSitterAnalyzer,
     compaction_engine: Arc<CompactionEngine>,
+    context_providers: Arc<ContextProviderRegistry>,
     session_info: SessionInfo,
     start_time: std::time::Instant,
 }
@@ -51,6 +53,7 @@ impl Agent {
             summarizer: components.summarizer,
             tree_sitter_analyzer: components.tree_sitter_analyzer,
             compaction_engine: components.compaction_engine,
+            context_providers: components.context_providers,
             session_info: components.session_info,
             start_time: std::time::Instant::now(),
         }
@@ -180,6 +183,11 @@ impl Agent {
         Arc::clone(&self.compaction_engine)
     }
 
+    /// Get the context providers consulted on each request
+    pub fn context_providers(&self) -> Arc<ContextProviderRegistry> {
+        Arc::clone(&self.context_providers)
+    }
+
     /// Make intelligent compaction decision using context analysis
     pub async fn make_intelligent_compaction_decision(
         &self,
@@ -253,10 +261,10 @@ impl Agent {
                 )
             })?;
 
-        // Parse the file
-        let syntax_tree = self
-            .tree_sitter_analyzer
-            .parse(source_code, language.clone())?;
+        // Parse the file, reusing the previous tree when the file was seen before
+        let syntax_tree =
+            self.tree_sitter_analyzer
+                .parse_incremental(file_path, source_code, language)?;
 
         // Extract symbols
         let symbols = self
@@ -445,12 +453,14 @@ impl AgentBuilder {
                 model: ModelId::default().as_str().to_string(),
                 api_key: String::new(),
                 provider: "gemini".to_string(),
+                base_url: None,
                 workspace: std::env::current_dir()
                     .unwrap_or_else(|_| std::path::PathBuf::from(".")),
                 verbose: false,
                 theme: crate::config::constants::defaults::DEFAULT_THEME.to_string(),
                 reasoning_effort: ReasoningEffortLevel::default(),
                 ui_surface: UiSurfacePreference::default(),
+                render_mode: RenderMode::default(),
                 prompt_cache: PromptCachingConfig::default(),
             },
         }
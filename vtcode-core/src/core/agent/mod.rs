@@ -12,6 +12,7 @@ pub mod performance;
 pub mod semantic;
 pub mod snapshots;
 pub mod stats;
+pub mod subagents;
 pub mod types;
 
 // Re-export main types for convenience
@@ -314,6 +314,10 @@ impl AgentRunner {
                         }
                     })
                 },
+                json_mode: false,
+                response_schema: None,
+                code_execution: false,
+                seed: None,
             };
 
             // Use provider-specific client for OpenAI/Anthropic (and generic support for others)
@@ -1005,6 +1009,7 @@ impl AgentRunner {
             .filter(|decl| self.is_tool_allowed(&decl.name))
             .map(|decl| Tool {
                 function_declarations: vec![decl],
+                code_execution: None,
             })
             .collect();
 
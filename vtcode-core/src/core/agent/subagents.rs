@@ -0,0 +1,670 @@
+//! Scoped child agents for `[agents.subagents]`
+//!
+//! The session's model hands tasks to named agents through the
+//! `spawn_subagents` tool. Each [`Subagent`] runs its own tool-calling loop on
+//! its own model, sees only its task, may call only the tools its definition
+//! allows, and keeps its conversation under its `context_tokens` budget by
+//! blanking the oldest tool results. It never prompts: tools whose policy
+//! would ask first, and calls the session's guardrails block, are refused to
+//! it, and untrusted output is shielded as in the chat loop. [`run_subagents`]
+//! runs the tasks of one call concurrently on forks of the session's registry,
+//! and [`merge_reports`] turns their final messages into one tool result.
+
+use crate::config::constants::{context::CHAR_PER_TOKEN_APPROX, tools};
+use crate::config::{SubagentDefinition, SubagentsConfig};
+use crate::core::guardrails::Guardrails;
+use crate::core::prompt_injection::InjectionShield;
+use crate::llm::provider::{
+    LLMProvider, LLMRequest, Message, MessageRole, ToolChoice, ToolDefinition,
+};
+use crate::tools::ToolRegistry;
+use crate::tools::registry::{ToolErrorType, ToolExecutionError, ToolPermissionDecision};
+use futures::StreamExt;
+use futures::stream;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+
+const DROPPED_RESULT: &str = "[result dropped to fit the context window]";
+const FINAL_TURN_NOTICE: &str =
+    "You have no turns left. Write your report now from what you have found so far.";
+
+/// One task of a `spawn_subagents` call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubagentTask {
+    pub agent: String,
+    pub task: String,
+}
+
+/// The tasks of a `spawn_subagents` call, or the message to send back to the
+/// model when they cannot run.
+pub fn parse_tasks(config: &SubagentsConfig, args: &Value) -> Result<Vec<SubagentTask>, String> {
+    let Some(entries) = args.get("tasks").and_then(Value::as_array) else {
+        return Err("`tasks` must be a list of { agent, task } objects".to_string());
+    };
+    if entries.is_empty() {
+        return Err("`tasks` is empty".to_string());
+    }
+    entries
+        .iter()
+        .map(|entry| {
+            let agent = entry.get("agent").and_then(Value::as_str).unwrap_or("");
+            let task = entry.get("task").and_then(Value::as_str).unwrap_or("");
+            if task.trim().is_empty() {
+                return Err(format!("The task for `{agent}` is empty"));
+            }
+            if !config.definitions.iter().any(|def| def.name == agent) {
+                return Err(format!(
+                    "No sub-agent named `{agent}`; choose one of {}",
+                    agent_names(config)
+                ));
+            }
+            Ok(SubagentTask {
+                agent: agent.to_string(),
+                task: task.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Declaration of the `spawn_subagents` tool.
+pub fn spawn_subagents_tool(config: &SubagentsConfig) -> ToolDefinition {
+    let agents: Vec<String> = config
+        .definitions
+        .iter()
+        .map(|def| format!("- {}: {}", def.name, def.description))
+        .collect();
+    ToolDefinition::function(
+        tools::SPAWN_SUBAGENTS.to_string(),
+        format!(
+            "Hand self-contained tasks to sub-agents that work in parallel, each with its own \
+             context and a limited set of tools, and return their reports. A sub-agent sees only \
+             its task, so include every path, name and constraint it needs and say what its \
+             report must contain. Agents:\n{}",
+            agents.join("\n")
+        ),
+        json!({
+            "type": "object",
+            "properties": {
+                "tasks": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "agent": {
+                                "type": "string",
+                                "enum": config
+                                    .definitions
+                                    .iter()
+                                    .map(|def| def.name.as_str())
+                                    .collect::<Vec<_>>()
+                            },
+                            "task": {
+                                "type": "string",
+                                "description": "Complete instructions for the sub-agent"
+                            }
+                        },
+                        "required": ["agent", "task"]
+                    }
+                }
+            },
+            "required": ["tasks"]
+        }),
+    )
+}
+
+/// What a sub-agent reported back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubagentReport {
+    pub agent: String,
+    pub task: String,
+    /// The agent's final message
+    pub report: String,
+    /// Model responses used
+    pub turns: usize,
+    /// Distinct tools called, in first-use order
+    pub tools_used: Vec<String>,
+    /// Why the agent stopped early, if it did
+    pub error: Option<String>,
+}
+
+/// A child agent bound to its model.
+#[derive(Clone)]
+pub struct Subagent {
+    definition: SubagentDefinition,
+    provider: Arc<dyn LLMProvider>,
+    model: String,
+}
+
+impl Subagent {
+    pub fn new(
+        definition: SubagentDefinition,
+        provider: Arc<dyn LLMProvider>,
+        model: String,
+    ) -> Self {
+        Self {
+            definition,
+            provider,
+            model,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.definition.name
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Work on `task` with the tools of `available` the definition allows,
+    /// calling them on `registry` under the session's `guardrails` and
+    /// `shield`.
+    pub async fn run(
+        &self,
+        task: &str,
+        mut registry: ToolRegistry,
+        available: &[ToolDefinition],
+        guardrails: &Guardrails,
+        shield: &InjectionShield,
+        cancel: CancellationToken,
+    ) -> SubagentReport {
+        let definition = &self.definition;
+        let tools: Vec<ToolDefinition> = available
+            .iter()
+            .filter(|tool| !is_orchestration_tool(tool.function_name()))
+            .filter(|tool| definition.allows(tool.function_name()))
+            .cloned()
+            .collect();
+        let budget_chars = definition
+            .context_tokens
+            .saturating_mul(CHAR_PER_TOKEN_APPROX);
+        let mut report = SubagentReport {
+            agent: definition.name.clone(),
+            task: task.to_string(),
+            report: String::new(),
+            turns: 0,
+            tools_used: Vec::new(),
+            error: None,
+        };
+        let mut messages = vec![Message::user(task.to_string())];
+        let max_turns = definition.max_turns.max(1);
+
+        for turn in 0..max_turns {
+            // The last turn keeps the tools declared, as earlier calls refer
+            // to them, but may not call them.
+            let last_turn = turn > 0 && turn + 1 == max_turns;
+            let mut system_prompt = self.system_prompt();
+            if last_turn {
+                system_prompt.push_str("\n\n");
+                system_prompt.push_str(FINAL_TURN_NOTICE);
+            }
+            fit_context(&mut messages, budget_chars);
+            let request = LLMRequest {
+                messages: messages.clone(),
+                system_prompt: Some(system_prompt),
+                tools: (!tools.is_empty()).then(|| tools.clone()),
+                model: self.model.clone(),
+                max_tokens: None,
+                temperature: None,
+                stream: false,
+                tool_choice: (!tools.is_empty()).then(|| {
+                    if last_turn {
+                        ToolChoice::none()
+                    } else {
+                        ToolChoice::auto()
+                    }
+                }),
+                parallel_tool_calls: None,
+                parallel_tool_config: None,
+                reasoning_effort: None,
+                json_mode: false,
+                response_schema: None,
+                code_execution: false,
+                seed: None,
+            };
+            let response = tokio::select! {
+                response = self.provider.generate(request) => response,
+                _ = cancel.cancelled() => {
+                    report.error = Some("cancelled".to_string());
+                    return report;
+                }
+            };
+            report.turns += 1;
+            let response = match response {
+                Ok(response) => response,
+                Err(err) => {
+                    report.error = Some(err.to_string());
+                    return report;
+                }
+            };
+            let text = response.content.unwrap_or_default();
+            let calls = response.tool_calls.unwrap_or_default();
+            if calls.is_empty() {
+                report.report = text.trim().to_string();
+                return report;
+            }
+
+            messages.push(Message::assistant_with_tools(text, calls.clone()));
+            for call in calls {
+                let name = call.function.name.clone();
+                let output = match call.parsed_arguments() {
+                    Ok(args) => {
+                        let output = self
+                            .call_tool(
+                                &mut registry,
+                                &name,
+                                args.clone(),
+                                guardrails,
+                                cancel.clone(),
+                            )
+                            .await;
+                        shield
+                            .shield(&name, &args, &output)
+                            .map_or(output, |shielded| shielded.output)
+                    }
+                    Err(err) => json!({ "error": format!("Invalid arguments: {err}") }),
+                };
+                if !report.tools_used.contains(&name) {
+                    report.tools_used.push(name);
+                }
+                let mut content = output.to_string();
+                cut_to(&mut content, budget_chars / 4);
+                messages.push(Message::tool_response(call.id, content));
+            }
+        }
+
+        report.error = Some(format!("no report after {max_turns} turns"));
+        report
+    }
+
+    async fn call_tool(
+        &self,
+        registry: &mut ToolRegistry,
+        name: &str,
+        args: Value,
+        guardrails: &Guardrails,
+        cancel: CancellationToken,
+    ) -> Value {
+        if !self.definition.allows(name) || is_orchestration_tool(name) {
+            return json!({
+                "error": format!("`{name}` is not available to the {} agent", self.definition.name)
+            });
+        }
+        // Sub-agents cannot ask for an override, so a violation is final
+        if let Some(violation) = guardrails.check(name, &args) {
+            return ToolExecutionError::new(
+                name.to_string(),
+                ToolErrorType::PolicyViolation,
+                format!("Blocked by guardrail: {violation}"),
+            )
+            .to_json_value();
+        }
+        match registry.evaluate_tool_policy(name) {
+            Ok(ToolPermissionDecision::Allow) => {}
+            Ok(ToolPermissionDecision::Prompt) => {
+                return json!({
+                    "error": format!(
+                        "`{name}` needs approval, which sub-agents cannot ask for; say in your \
+                         report what you needed it for"
+                    )
+                });
+            }
+            Ok(ToolPermissionDecision::Deny) => {
+                return json!({ "error": format!("`{name}` is denied by the tool policy") });
+            }
+            Err(err) => return json!({ "error": err.to_string() }),
+        }
+        match registry.execute_tool_cancellable(name, args, cancel).await {
+            Ok(output) => output,
+            Err(err) => json!({ "error": err.to_string() }),
+        }
+    }
+
+    fn system_prompt(&self) -> String {
+        let mut prompt = format!(
+            "You are the {} sub-agent of VT Code, working on a single task handed to you by the \
+             main coding agent. You cannot ask questions; make reasonable assumptions and note \
+             them. The main agent sees only your final message, so end with a complete report: \
+             what you found or changed, with file paths and line numbers where they help.",
+            self.definition.name
+        );
+        if !self.definition.instructions.trim().is_empty() {
+            prompt.push_str("\n\n");
+            prompt.push_str(self.definition.instructions.trim());
+        }
+        prompt
+    }
+}
+
+/// Run every task on its agent, at most `max_parallel` at a time, each on a
+/// fork of `registry` under the session's `guardrails` and `shield`. Reports
+/// are in the order of `jobs`.
+pub async fn run_subagents(
+    jobs: Vec<(Subagent, SubagentTask)>,
+    registry: &ToolRegistry,
+    available: &[ToolDefinition],
+    guardrails: &Guardrails,
+    shield: &InjectionShield,
+    max_parallel: usize,
+    cancel: CancellationToken,
+) -> Vec<SubagentReport> {
+    stream::iter(jobs)
+        .map(|(agent, task)| {
+            let registry = registry.fork();
+            let cancel = cancel.clone();
+            async move {
+                agent
+                    .run(&task.task, registry, available, guardrails, shield, cancel)
+                    .await
+            }
+        })
+        .buffered(max_parallel.max(1))
+        .collect()
+        .await
+}
+
+/// The tool result of a `spawn_subagents` call.
+pub fn merge_reports(reports: &[SubagentReport]) -> Value {
+    let results: Vec<Value> = reports
+        .iter()
+        .map(|report| {
+            let mut entry = json!({
+                "agent": report.agent,
+                "task": report.task,
+                "report": report.report,
+                "turns": report.turns,
+                "tools_used": report.tools_used,
+            });
+            if let Some(error) = &report.error {
+                entry["error"] = json!(error);
+            }
+            entry
+        })
+        .collect();
+    json!({
+        "success": reports.iter().all(|report| report.error.is_none()),
+        "results": results,
+    })
+}
+
+fn agent_names(config: &SubagentsConfig) -> String {
+    config
+        .definitions
+        .iter()
+        .map(|def| format!("`{}`", def.name))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Tools handled by the chat loop, which a sub-agent cannot use.
+fn is_orchestration_tool(name: &str) -> bool {
+    name == tools::SPAWN_SUBAGENTS || name == tools::GET_FULL_OUTPUT
+}
+
+/// Blank the oldest tool results until the conversation fits `budget_chars`.
+/// The task and the latest exchange are always kept.
+fn fit_context(messages: &mut [Message], budget_chars: usize) {
+    let mut total: usize = messages.iter().map(|message| message.content.len()).sum();
+    let keep_from = messages
+        .iter()
+        .rposition(|message| message.role == MessageRole::Assistant)
+        .unwrap_or(messages.len());
+    for message in messages[..keep_from].iter_mut() {
+        if total <= budget_chars {
+            break;
+        }
+        if message.role == MessageRole::Tool && message.content != DROPPED_RESULT {
+            total = total - message.content.len() + DROPPED_RESULT.len();
+            message.content = DROPPED_RESULT.to_string();
+        }
+    }
+}
+
+fn cut_to(text: &mut String, max_chars: usize) {
+    if text.len() <= max_chars {
+        return;
+    }
+    let mut end = max_chars;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text.truncate(end);
+    text.push_str("… [cut to fit the context window]");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::provider::{FinishReason, LLMError, LLMResponse, ToolCall};
+    use async_trait::async_trait;
+    use parking_lot::Mutex;
+
+    /// Plays scripted responses and records the requests it gets.
+    struct ScriptedProvider {
+        responses: Mutex<Vec<LLMResponse>>,
+        requests: Mutex<Vec<LLMRequest>>,
+    }
+
+    #[async_trait]
+    impl LLMProvider for ScriptedProvider {
+        fn name(&self) -> &str {
+            "scripted"
+        }
+
+        async fn generate(&self, request: LLMRequest) -> Result<LLMResponse, LLMError> {
+            self.requests.lock().push(request);
+            Ok(self.responses.lock().remove(0))
+        }
+
+        fn supported_models(&self) -> Vec<String> {
+            Vec::new()
+        }
+
+        fn validate_request(&self, _request: &LLMRequest) -> Result<(), LLMError> {
+            Ok(())
+        }
+    }
+
+    fn response(content: &str, calls: Vec<ToolCall>) -> LLMResponse {
+        LLMResponse {
+            content: Some(content.to_string()),
+            tool_calls: (!calls.is_empty()).then_some(calls),
+            usage: None,
+            finish_reason: FinishReason::Stop,
+            reasoning: None,
+            code_execution: Vec::new(),
+        }
+    }
+
+    fn call(id: &str, name: &str, args: Value) -> ToolCall {
+        ToolCall::function(id.to_string(), name.to_string(), args.to_string())
+    }
+
+    #[tokio::test]
+    async fn explorer_reads_with_its_tools_and_is_refused_the_rest() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "the cache key is the path\n").unwrap();
+        let mut registry = ToolRegistry::new(dir.path().to_path_buf());
+        registry.allow_all_tools().unwrap();
+
+        let config = SubagentsConfig::default();
+        let explorer = config
+            .definitions
+            .iter()
+            .find(|def| def.name == "explorer")
+            .unwrap()
+            .clone();
+        let provider = Arc::new(ScriptedProvider {
+            responses: Mutex::new(vec![
+                response(
+                    "",
+                    vec![
+                        call("1", tools::READ_FILE, json!({ "path": "notes.txt" })),
+                        call(
+                            "2",
+                            tools::WRITE_FILE,
+                            json!({ "path": "x.txt", "content": "x" }),
+                        ),
+                    ],
+                ),
+                response("The cache key is the path (notes.txt:1).", Vec::new()),
+            ]),
+            requests: Mutex::default(),
+        });
+        let available: Vec<ToolDefinition> = crate::tools::build_function_declarations()
+            .into_iter()
+            .map(|decl| ToolDefinition::function(decl.name, decl.description, decl.parameters))
+            .chain([spawn_subagents_tool(&config)])
+            .collect();
+
+        let tasks = parse_tasks(
+            &config,
+            &json!({ "tasks": [{ "agent": "explorer", "task": "How is the cache keyed?" }] }),
+        )
+        .unwrap();
+        let jobs = tasks
+            .into_iter()
+            .map(|task| {
+                let agent = Subagent::new(explorer.clone(), provider.clone(), "m".to_string());
+                (agent, task)
+            })
+            .collect();
+        let guardrails = Guardrails::disabled(dir.path());
+        let shield = InjectionShield::disabled(dir.path());
+        let reports = run_subagents(
+            jobs,
+            &registry,
+            &available,
+            &guardrails,
+            &shield,
+            2,
+            CancellationToken::new(),
+        )
+        .await;
+
+        assert_eq!(reports.len(), 1);
+        let report = &reports[0];
+        assert_eq!(report.report, "The cache key is the path (notes.txt:1).");
+        assert_eq!(report.turns, 2);
+        assert_eq!(report.tools_used, [tools::READ_FILE, tools::WRITE_FILE]);
+        assert!(report.error.is_none());
+        assert!(!dir.path().join("x.txt").exists());
+
+        let requests = provider.requests.lock();
+        let offered: Vec<&str> = requests[0]
+            .tools
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(ToolDefinition::function_name)
+            .collect();
+        assert!(offered.contains(&tools::READ_FILE));
+        assert!(!offered.contains(&tools::WRITE_FILE));
+        assert!(!offered.contains(&tools::SPAWN_SUBAGENTS));
+        let results: Vec<&str> = requests[1]
+            .messages
+            .iter()
+            .filter(|message| message.role == MessageRole::Tool)
+            .map(|message| message.content.as_str())
+            .collect();
+        assert!(results[0].contains("the cache key is the path"));
+        assert!(results[1].contains("not available to the explorer agent"));
+
+        let merged = merge_reports(&reports);
+        assert_eq!(merged["success"], true);
+        assert_eq!(merged["results"][0]["agent"], "explorer");
+    }
+
+    #[tokio::test]
+    async fn guardrails_refuse_calls_and_untrusted_output_is_shielded() {
+        use crate::config::core::{GuardrailsConfig, PromptInjectionConfig};
+
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join(".env"), "API_KEY=secret\n").unwrap();
+        std::fs::create_dir(dir.path().join("vendor")).unwrap();
+        std::fs::write(dir.path().join("vendor/README.md"), "vendored notes\n").unwrap();
+        let mut registry = ToolRegistry::new(dir.path().to_path_buf());
+        registry.allow_all_tools().unwrap();
+
+        let explorer = SubagentsConfig::default()
+            .definitions
+            .into_iter()
+            .find(|def| def.name == "explorer")
+            .unwrap();
+        let provider = Arc::new(ScriptedProvider {
+            responses: Mutex::new(vec![
+                response(
+                    "",
+                    vec![
+                        call("1", tools::READ_FILE, json!({ "path": ".env" })),
+                        call("2", tools::READ_FILE, json!({ "path": "vendor/README.md" })),
+                    ],
+                ),
+                response("Done.", Vec::new()),
+            ]),
+            requests: Mutex::default(),
+        });
+        let available: Vec<ToolDefinition> = crate::tools::build_function_declarations()
+            .into_iter()
+            .map(|decl| ToolDefinition::function(decl.name, decl.description, decl.parameters))
+            .collect();
+        let mut guardrails = Guardrails::new(&GuardrailsConfig::default(), dir.path()).unwrap();
+        guardrails.deny_reads([".env"]);
+        let shield = InjectionShield::new(&PromptInjectionConfig::default(), dir.path()).unwrap();
+
+        let agent = Subagent::new(explorer, provider.clone(), "m".to_string());
+        let report = agent
+            .run(
+                "Summarize the config",
+                registry,
+                &available,
+                &guardrails,
+                &shield,
+                CancellationToken::new(),
+            )
+            .await;
+        assert!(report.error.is_none());
+
+        let requests = provider.requests.lock();
+        let results: Vec<&str> = requests[1]
+            .messages
+            .iter()
+            .filter(|message| message.role == MessageRole::Tool)
+            .map(|message| message.content.as_str())
+            .collect();
+        assert!(results[0].contains("Blocked by guardrail"));
+        assert!(!results[0].contains("API_KEY"));
+        assert!(results[1].contains("<untrusted-content"));
+        assert!(results[1].contains("vendored notes"));
+    }
+
+    #[test]
+    fn rejects_unknown_agents_and_empty_tasks() {
+        let config = SubagentsConfig::default();
+        let unknown = parse_tasks(
+            &config,
+            &json!({ "tasks": [{ "agent": "x", "task": "t" }] }),
+        );
+        assert!(unknown.unwrap_err().contains("`explorer`, `test-writer`"));
+        let empty = parse_tasks(
+            &config,
+            &json!({ "tasks": [{ "agent": "explorer", "task": " " }] }),
+        );
+        assert!(empty.is_err());
+    }
+
+    #[test]
+    fn blanks_the_oldest_tool_results_first() {
+        let mut messages = vec![
+            Message::user("task".to_string()),
+            Message::assistant_with_tools(String::new(), Vec::new()),
+            Message::tool_response("1".to_string(), "a".repeat(100)),
+            Message::assistant_with_tools(String::new(), Vec::new()),
+            Message::tool_response("2".to_string(), "b".repeat(100)),
+        ];
+        fit_context(&mut messages, 150);
+        assert_eq!(messages[2].content, DROPPED_RESULT);
+        assert_eq!(messages[4].content, "b".repeat(100));
+    }
+}
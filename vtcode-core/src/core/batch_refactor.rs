@@ -0,0 +1,450 @@
+//! Batch refactoring across many files.
+//!
+//! A [`BatchPlan`] records the change set for a mechanical edit ("rename this
+//! config key everywhere"): which files match, how they are split into
+//! batches, and how far execution has progressed. Each batch is applied inside
+//! a [`BatchTransaction`] that keeps the original contents on disk until the
+//! batch is validated, so a failed validation or an interrupted run can be
+//! rolled back and the plan resumed from the first unfinished batch.
+
+use crate::utils::utils::write_atomic;
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{DateTime, Utc};
+use ignore::WalkBuilder;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Directory, relative to the workspace, where plans and backups are stored
+pub const BATCH_STATE_DIR: &str = ".vtcode/batch";
+
+/// Lines of validation output kept when a batch fails
+const VALIDATION_OUTPUT_LINES: usize = 40;
+
+/// What to change in each matching file.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BatchEdit {
+    /// Replace every match of the pattern; `$1`-style captures are expanded
+    Replace { replacement: String },
+    /// Ask the model to rewrite each matching file following an instruction
+    Instruction { text: String },
+}
+
+/// Parameters used to build a plan.
+#[derive(Debug, Clone)]
+pub struct BatchSpec {
+    /// Regular expression (or literal text) selecting files and matches
+    pub pattern: String,
+    /// Treat `pattern` as literal text instead of a regular expression
+    pub literal: bool,
+    pub edit: BatchEdit,
+    /// Glob patterns (relative to the workspace) limiting which files are considered
+    pub include: Vec<String>,
+    pub batch_size: usize,
+    /// Shell command run after each batch, e.g. `cargo check`
+    pub validation_command: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileStatus {
+    Pending,
+    Applied,
+    Unchanged,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedFile {
+    /// Path relative to the workspace root
+    pub path: PathBuf,
+    /// Matches found when the plan was created
+    pub matches: usize,
+    pub status: FileStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanStatus {
+    Planned,
+    InProgress,
+    Failed,
+    Completed,
+}
+
+/// Persisted change set and progress of a batch refactor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchPlan {
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    pub pattern: String,
+    pub literal: bool,
+    pub edit: BatchEdit,
+    pub include: Vec<String>,
+    pub batch_size: usize,
+    pub validation_command: Option<String>,
+    pub files: Vec<PlannedFile>,
+    /// Number of batches applied and validated so far
+    pub completed_batches: usize,
+    pub status: PlanStatus,
+    /// Reason the last run stopped, if it failed
+    pub failure: Option<String>,
+}
+
+impl BatchPlan {
+    /// Scan `workspace` and record every file matching the spec.
+    pub fn create(workspace: &Path, spec: BatchSpec) -> Result<Self> {
+        if spec.pattern.is_empty() {
+            bail!("batch pattern cannot be empty");
+        }
+        let regex = build_regex(&spec.pattern, spec.literal)?;
+        let include = spec
+            .include
+            .iter()
+            .map(|glob| {
+                glob::Pattern::new(glob).with_context(|| format!("invalid include glob: {}", glob))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut files = Vec::new();
+        for entry in WalkBuilder::new(workspace).build().filter_map(|e| e.ok()) {
+            if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+                continue;
+            }
+            let relative = entry
+                .path()
+                .strip_prefix(workspace)
+                .unwrap_or(entry.path())
+                .to_path_buf();
+            if relative.starts_with(".vtcode") {
+                continue;
+            }
+            if !include.is_empty() && !include.iter().any(|glob| glob.matches_path(&relative)) {
+                continue;
+            }
+            let Ok(content) = fs::read_to_string(entry.path()) else {
+                continue;
+            };
+            let matches = regex.find_iter(&content).count();
+            if matches > 0 {
+                files.push(PlannedFile {
+                    path: relative,
+                    matches,
+                    status: FileStatus::Pending,
+                });
+            }
+        }
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let created_at = Utc::now();
+        Ok(Self {
+            id: created_at.format("%Y%m%dT%H%M%S%3f").to_string(),
+            created_at,
+            pattern: spec.pattern,
+            literal: spec.literal,
+            edit: spec.edit,
+            include: spec.include,
+            batch_size: spec.batch_size.max(1),
+            validation_command: spec.validation_command,
+            files,
+            completed_batches: 0,
+            status: PlanStatus::Planned,
+            failure: None,
+        })
+    }
+
+    pub fn regex(&self) -> Result<Regex> {
+        build_regex(&self.pattern, self.literal)
+    }
+
+    pub fn total_matches(&self) -> usize {
+        self.files.iter().map(|file| file.matches).sum()
+    }
+
+    pub fn batch_count(&self) -> usize {
+        self.files.len().div_ceil(self.batch_size)
+    }
+
+    /// Index range into `files` covered by batch `index`.
+    pub fn batch_range(&self, index: usize) -> std::ops::Range<usize> {
+        let start = (index * self.batch_size).min(self.files.len());
+        let end = (start + self.batch_size).min(self.files.len());
+        start..end
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.completed_batches >= self.batch_count()
+    }
+}
+
+fn build_regex(pattern: &str, literal: bool) -> Result<Regex> {
+    let source = if literal {
+        regex::escape(pattern)
+    } else {
+        pattern.to_string()
+    };
+    Regex::new(&source).with_context(|| format!("invalid batch pattern: {}", pattern))
+}
+
+/// Apply a `Replace` edit to `content`.
+pub fn apply_replacement(regex: &Regex, content: &str, replacement: &str, literal: bool) -> String {
+    if literal {
+        regex
+            .replace_all(content, regex::NoExpand(replacement))
+            .into_owned()
+    } else {
+        regex.replace_all(content, replacement).into_owned()
+    }
+}
+
+/// Stores plans and in-flight batch backups under [`BATCH_STATE_DIR`].
+#[derive(Debug, Clone)]
+pub struct BatchStore {
+    dir: PathBuf,
+}
+
+impl BatchStore {
+    pub fn new(workspace: &Path) -> Self {
+        Self {
+            dir: workspace.join(BATCH_STATE_DIR),
+        }
+    }
+
+    fn plan_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+
+    fn backup_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.backup.json", id))
+    }
+
+    /// Persist `plan`, replacing the previous state atomically.
+    pub fn save(&self, plan: &BatchPlan) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("failed to create {}", self.dir.display()))?;
+        write_atomic(&self.plan_path(&plan.id), &serde_json::to_vec_pretty(plan)?)
+    }
+
+    pub fn load(&self, id: &str) -> Result<BatchPlan> {
+        let path = self.plan_path(id);
+        let data =
+            fs::read(&path).with_context(|| format!("no batch plan at {}", path.display()))?;
+        serde_json::from_slice(&data).with_context(|| format!("corrupt batch plan {}", id))
+    }
+
+    /// Most recently created plan that has not completed.
+    pub fn latest_unfinished(&self) -> Result<BatchPlan> {
+        let mut plans = Vec::new();
+        if let Ok(entries) = fs::read_dir(&self.dir) {
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if let Some(id) = name.strip_suffix(".json")
+                    && !id.ends_with(".backup")
+                    && let Ok(plan) = self.load(id)
+                    && plan.status != PlanStatus::Completed
+                {
+                    plans.push(plan);
+                }
+            }
+        }
+        plans
+            .into_iter()
+            .max_by_key(|plan| plan.created_at)
+            .ok_or_else(|| anyhow!("no unfinished batch plan to resume"))
+    }
+}
+
+/// Restore files from an interrupted batch of `plan`, if any.
+///
+/// Returns the number of restored files.
+pub fn recover_interrupted_batch(
+    workspace: &Path,
+    store: &BatchStore,
+    plan: &BatchPlan,
+) -> Result<usize> {
+    let path = store.backup_path(&plan.id);
+    if !path.exists() {
+        return Ok(0);
+    }
+    let transaction = BatchTransaction::from_backup(workspace, path)?;
+    let restored = transaction.originals.len();
+    transaction.rollback()?;
+    Ok(restored)
+}
+
+/// Original contents of the files touched by one batch.
+///
+/// The backup is written to disk before any file is modified, so an
+/// interrupted run can be undone by [`recover_interrupted_batch`].
+pub struct BatchTransaction {
+    workspace: PathBuf,
+    backup_path: PathBuf,
+    originals: BTreeMap<PathBuf, String>,
+}
+
+impl BatchTransaction {
+    pub fn begin(
+        workspace: &Path,
+        store: &BatchStore,
+        plan: &BatchPlan,
+        files: &[PathBuf],
+    ) -> Result<Self> {
+        let mut originals = BTreeMap::new();
+        for file in files {
+            let content = fs::read_to_string(workspace.join(file))
+                .with_context(|| format!("failed to read {}", file.display()))?;
+            originals.insert(file.clone(), content);
+        }
+        fs::create_dir_all(&store.dir)?;
+        let backup_path = store.backup_path(&plan.id);
+        write_atomic(&backup_path, &serde_json::to_vec(&originals)?)?;
+        Ok(Self {
+            workspace: workspace.to_path_buf(),
+            backup_path,
+            originals,
+        })
+    }
+
+    fn from_backup(workspace: &Path, backup_path: PathBuf) -> Result<Self> {
+        let data = fs::read(&backup_path)?;
+        let originals = serde_json::from_slice(&data)
+            .with_context(|| format!("corrupt batch backup {}", backup_path.display()))?;
+        Ok(Self {
+            workspace: workspace.to_path_buf(),
+            backup_path,
+            originals,
+        })
+    }
+
+    /// Original content of `file` captured when the batch began.
+    pub fn original(&self, file: &Path) -> Option<&str> {
+        self.originals.get(file).map(String::as_str)
+    }
+
+    /// Keep the batch's edits and discard the backup.
+    pub fn commit(self) -> Result<()> {
+        fs::remove_file(&self.backup_path)
+            .with_context(|| format!("failed to remove {}", self.backup_path.display()))
+    }
+
+    /// Restore every file to its original content and discard the backup.
+    pub fn rollback(self) -> Result<()> {
+        for (file, content) in &self.originals {
+            fs::write(self.workspace.join(file), content)
+                .with_context(|| format!("failed to restore {}", file.display()))?;
+        }
+        fs::remove_file(&self.backup_path)
+            .with_context(|| format!("failed to remove {}", self.backup_path.display()))
+    }
+}
+
+/// Result of running the validation command after a batch.
+#[derive(Debug, Clone)]
+pub struct ValidationOutcome {
+    pub success: bool,
+    /// Tail of the combined stdout/stderr
+    pub output: String,
+}
+
+/// Run `command` through the shell in `workspace`.
+pub fn run_validation(workspace: &Path, command: &str) -> Result<ValidationOutcome> {
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .current_dir(workspace)
+        .output()
+        .with_context(|| format!("failed to run validation command `{}`", command))?;
+    let combined = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let lines: Vec<&str> = combined.lines().collect();
+    let tail = lines[lines.len().saturating_sub(VALIDATION_OUTPUT_LINES)..].join("\n");
+    Ok(ValidationOutcome {
+        success: output.status.success(),
+        output: tail,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn workspace_with_files(count: usize) -> TempDir {
+        let dir = TempDir::new().unwrap();
+        for index in 0..count {
+            fs::write(
+                dir.path().join(format!("config_{index}.toml")),
+                "old_key = 1\nother = 2\n",
+            )
+            .unwrap();
+        }
+        fs::write(dir.path().join("unrelated.toml"), "other = 2\n").unwrap();
+        dir
+    }
+
+    fn spec(batch_size: usize) -> BatchSpec {
+        BatchSpec {
+            pattern: "old_key".to_string(),
+            literal: true,
+            edit: BatchEdit::Replace {
+                replacement: "new_key".to_string(),
+            },
+            include: vec!["*.toml".to_string()],
+            batch_size,
+            validation_command: None,
+        }
+    }
+
+    #[test]
+    fn plan_selects_matching_files_and_splits_batches() {
+        let dir = workspace_with_files(5);
+        let plan = BatchPlan::create(dir.path(), spec(2)).unwrap();
+
+        assert_eq!(plan.files.len(), 5);
+        assert_eq!(plan.total_matches(), 5);
+        assert_eq!(plan.batch_count(), 3);
+        assert_eq!(plan.batch_range(2), 4..5);
+
+        let store = BatchStore::new(dir.path());
+        store.save(&plan).unwrap();
+        assert_eq!(store.latest_unfinished().unwrap().id, plan.id);
+    }
+
+    #[test]
+    fn rollback_and_recovery_restore_original_contents() {
+        let dir = workspace_with_files(2);
+        let plan = BatchPlan::create(dir.path(), spec(2)).unwrap();
+        let store = BatchStore::new(dir.path());
+        let files: Vec<PathBuf> = plan.files.iter().map(|f| f.path.clone()).collect();
+        let regex = plan.regex().unwrap();
+
+        // Simulate an interrupted batch: edits written, backup left behind.
+        let transaction = BatchTransaction::begin(dir.path(), &store, &plan, &files).unwrap();
+        for file in &files {
+            let original = transaction.original(file).unwrap();
+            let updated = apply_replacement(&regex, original, "new_key", true);
+            fs::write(dir.path().join(file), updated).unwrap();
+        }
+        drop(transaction);
+
+        assert_eq!(
+            recover_interrupted_batch(dir.path(), &store, &plan).unwrap(),
+            2
+        );
+        for file in &files {
+            let content = fs::read_to_string(dir.path().join(file)).unwrap();
+            assert!(content.starts_with("old_key"));
+        }
+        assert_eq!(
+            recover_interrupted_batch(dir.path(), &store, &plan).unwrap(),
+            0
+        );
+    }
+}
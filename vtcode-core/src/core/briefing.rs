@@ -0,0 +1,423 @@
+//! "What changed since I left" briefing for returning sessions
+//!
+//! When a chat session ends, a [`WorkspaceCheckpoint`] records the commit the
+//! workspace was on, the files the session changed or read and the requests it
+//! served. The next session in the same workspace builds a [`Briefing`] from
+//! git: the commits that landed since, the files they changed (highlighting the
+//! ones the earlier conversation dealt with) and, when the GitHub CLI is
+//! available, the status of the latest CI run on the branch.
+
+use crate::utils::dot_config::{DotManager, workspace_key};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Requests kept in a checkpoint.
+const MAX_TOPICS: usize = 10;
+/// Characters kept per request.
+const MAX_TOPIC_CHARS: usize = 200;
+/// Files kept in a checkpoint.
+const MAX_CHECKPOINT_FILES: usize = 200;
+/// Commits listed in the briefing.
+const MAX_LISTED_COMMITS: usize = 10;
+/// Changed files listed in the briefing besides the relevant ones.
+const MAX_LISTED_FILES: usize = 8;
+/// How long the CI lookup may take before it is skipped.
+const CI_TIMEOUT: Duration = Duration::from_secs(3);
+const FIELD_SEPARATOR: char = '\u{1f}';
+
+/// Where a workspace was left at the end of a session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkspaceCheckpoint {
+    pub ended_at: DateTime<Utc>,
+    /// Commit checked out when the session ended
+    pub head: Option<String>,
+    pub branch: Option<String>,
+    /// Workspace-relative files the session changed or read
+    pub files: Vec<String>,
+    /// Requests the session served, most recent last
+    pub topics: Vec<String>,
+}
+
+impl WorkspaceCheckpoint {
+    /// Capture the current commit along with the session's files and requests.
+    pub fn capture(workspace: &Path, files: Vec<String>, topics: &[String]) -> Self {
+        let mut files: Vec<String> = files
+            .into_iter()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        files.truncate(MAX_CHECKPOINT_FILES);
+        let skip = topics.len().saturating_sub(MAX_TOPICS);
+        let topics = topics[skip..]
+            .iter()
+            .map(|topic| topic.chars().take(MAX_TOPIC_CHARS).collect())
+            .collect();
+        Self {
+            ended_at: Utc::now(),
+            head: git(workspace, &["rev-parse", "HEAD"]),
+            branch: git(workspace, &["rev-parse", "--abbrev-ref", "HEAD"])
+                .filter(|branch| branch != "HEAD"),
+            files,
+            topics,
+        }
+    }
+
+    /// Checkpoint left by the previous session in `workspace`, if any.
+    pub fn load(workspace: &Path) -> Option<Self> {
+        let path = checkpoint_path(workspace)?;
+        let content = std::fs::read_to_string(path).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    pub fn save(&self, workspace: &Path) -> Result<()> {
+        let Some(path) = checkpoint_path(workspace) else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+fn checkpoint_path(workspace: &Path) -> Option<PathBuf> {
+    let dot = DotManager::new().ok()?;
+    Some(
+        dot.cache_dir("checkpoints")
+            .join(format!("{}.json", workspace_key(workspace))),
+    )
+}
+
+/// A commit that landed while the session was away.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commit {
+    pub hash: String,
+    pub author: String,
+    pub when: String,
+    pub subject: String,
+}
+
+/// Latest CI run on the branch.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct CiStatus {
+    #[serde(rename = "workflowName", default)]
+    pub workflow: String,
+    pub status: String,
+    #[serde(default)]
+    pub conclusion: String,
+    #[serde(default)]
+    pub url: String,
+}
+
+impl CiStatus {
+    fn describe(&self) -> String {
+        let state = if self.status == "completed" {
+            self.conclusion.as_str()
+        } else {
+            self.status.as_str()
+        };
+        let mut line = format!("CI: {} {}", self.workflow, state.replace('_', " "));
+        if !self.url.is_empty() {
+            line.push_str(&format!(" ({})", self.url));
+        }
+        line
+    }
+}
+
+/// What happened in the workspace since the last session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Briefing {
+    pub since: DateTime<Utc>,
+    pub commits: Vec<Commit>,
+    pub changed_files: Vec<String>,
+    /// Changed files the previous session changed, read or talked about
+    pub relevant_files: Vec<String>,
+    pub ci: Option<CiStatus>,
+}
+
+impl Briefing {
+    /// Briefing for a workspace left at `checkpoint`; `None` when no commits
+    /// landed since.
+    pub fn build(
+        workspace: &Path,
+        checkpoint: &WorkspaceCheckpoint,
+        with_ci: bool,
+    ) -> Option<Self> {
+        let head = git(workspace, &["rev-parse", "HEAD"])?;
+        if checkpoint.head.as_deref() == Some(head.as_str()) {
+            return None;
+        }
+        let format = format!("--format=%h{0}%an{0}%ar{0}%s", FIELD_SEPARATOR);
+        // Use the recorded commit when it still exists; after a rebase or a
+        // fresh clone, fall back to the time the session ended.
+        let base = checkpoint.head.as_deref().filter(|head| {
+            git(
+                workspace,
+                &["cat-file", "-e", &format!("{head}^{{commit}}")],
+            )
+            .is_some()
+        });
+        let since = format!("--since={}", checkpoint.ended_at.to_rfc3339());
+        let (log, files) = match base {
+            Some(base) => {
+                let range = format!("{base}..HEAD");
+                (
+                    git(workspace, &["log", "--no-merges", &format, &range]),
+                    git(
+                        workspace,
+                        &["diff", "--name-only", "--relative", base, "HEAD"],
+                    ),
+                )
+            }
+            None => (
+                git(workspace, &["log", "--no-merges", &format, &since]),
+                git(
+                    workspace,
+                    &["log", "--name-only", "--relative", "--format=", &since],
+                ),
+            ),
+        };
+        let commits = parse_commits(&log.unwrap_or_default());
+        if commits.is_empty() {
+            return None;
+        }
+        let changed_files: Vec<String> = files
+            .unwrap_or_default()
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let relevant_files = relevant_files(&changed_files, checkpoint);
+        let ci = with_ci
+            .then(|| ci_status(workspace, checkpoint.branch.as_deref()))
+            .flatten();
+        Some(Self {
+            since: checkpoint.ended_at,
+            commits,
+            changed_files,
+            relevant_files,
+            ci,
+        })
+    }
+
+    /// Lines shown before the first prompt.
+    pub fn render_lines(&self) -> Vec<String> {
+        let mut lines = vec![format!(
+            "Since your last session ({}): {} new commit{}, {} file{} changed.",
+            self.since
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M"),
+            self.commits.len(),
+            if self.commits.len() == 1 { "" } else { "s" },
+            self.changed_files.len(),
+            if self.changed_files.len() == 1 {
+                ""
+            } else {
+                "s"
+            },
+        )];
+        for commit in self.commits.iter().take(MAX_LISTED_COMMITS) {
+            lines.push(format!(
+                "  {} {} ({}, {})",
+                commit.hash, commit.subject, commit.author, commit.when
+            ));
+        }
+        if self.commits.len() > MAX_LISTED_COMMITS {
+            lines.push(format!(
+                "  … and {} more",
+                self.commits.len() - MAX_LISTED_COMMITS
+            ));
+        }
+        if !self.relevant_files.is_empty() {
+            lines.push(format!(
+                "Touches what you worked on last time: {}",
+                self.relevant_files.join(", ")
+            ));
+        }
+        let others: Vec<&String> = self
+            .changed_files
+            .iter()
+            .filter(|file| !self.relevant_files.contains(file))
+            .collect();
+        if !others.is_empty() {
+            let mut listed = others
+                .iter()
+                .take(MAX_LISTED_FILES)
+                .map(|file| file.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            if others.len() > MAX_LISTED_FILES {
+                listed.push_str(&format!(" and {} more", others.len() - MAX_LISTED_FILES));
+            }
+            let label = if self.relevant_files.is_empty() {
+                "Changed"
+            } else {
+                "Also changed"
+            };
+            lines.push(format!("{}: {}", label, listed));
+        }
+        if let Some(ci) = &self.ci {
+            lines.push(ci.describe());
+        }
+        lines
+    }
+}
+
+fn parse_commits(log: &str) -> Vec<Commit> {
+    log.lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(4, FIELD_SEPARATOR);
+            Some(Commit {
+                hash: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                when: fields.next()?.to_string(),
+                subject: fields.next()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Changed files the checkpoint's session touched, or whose name appears in
+/// one of its requests.
+fn relevant_files(changed: &[String], checkpoint: &WorkspaceCheckpoint) -> Vec<String> {
+    let topics: Vec<String> = checkpoint
+        .topics
+        .iter()
+        .map(|topic| topic.to_lowercase())
+        .collect();
+    changed
+        .iter()
+        .filter(|file| {
+            if checkpoint.files.contains(file) {
+                return true;
+            }
+            let path = Path::new(file.as_str());
+            [path.file_name(), path.file_stem()]
+                .into_iter()
+                .flatten()
+                .filter_map(|name| name.to_str())
+                .filter(|name| name.len() >= 4)
+                .any(|name| {
+                    let name = name.to_lowercase();
+                    topics.iter().any(|topic| contains_word(topic, &name))
+                })
+        })
+        .cloned()
+        .collect()
+}
+
+fn contains_word(text: &str, word: &str) -> bool {
+    let is_word = |ch: char| ch.is_alphanumeric() || ch == '_';
+    text.match_indices(word).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + word.len()..].chars().next();
+        !before.is_some_and(is_word) && !after.is_some_and(is_word)
+    })
+}
+
+/// Latest GitHub Actions run on `branch` through `gh`; `None` when `gh` is
+/// missing, signed out or slower than [`CI_TIMEOUT`].
+fn ci_status(workspace: &Path, branch: Option<&str>) -> Option<CiStatus> {
+    let mut command = Command::new("gh");
+    command
+        .args([
+            "run",
+            "list",
+            "--limit",
+            "1",
+            "--json",
+            "workflowName,status,conclusion,url",
+        ])
+        .current_dir(workspace);
+    if let Some(branch) = branch {
+        command.args(["--branch", branch]);
+    }
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = sender.send(command.output());
+    });
+    let output = receiver.recv_timeout(CI_TIMEOUT).ok()?.ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let runs: Vec<CiStatus> = serde_json::from_slice(&output.stdout).ok()?;
+    runs.into_iter().next()
+}
+
+fn git(workspace: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(workspace)
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkpoint(files: &[&str], topics: &[&str]) -> WorkspaceCheckpoint {
+        WorkspaceCheckpoint {
+            ended_at: Utc::now(),
+            head: Some("abc123".to_string()),
+            branch: Some("main".to_string()),
+            files: files.iter().map(|file| file.to_string()).collect(),
+            topics: topics.iter().map(|topic| topic.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn highlights_files_from_the_previous_conversation() {
+        let changed: Vec<String> = ["src/parser.rs", "src/router.rs", "README.md", "src/lib.rs"]
+            .iter()
+            .map(|file| file.to_string())
+            .collect();
+        let previous = checkpoint(&["src/lib.rs"], &["Why does the Router drop requests?"]);
+        assert_eq!(
+            relevant_files(&changed, &previous),
+            vec!["src/router.rs".to_string(), "src/lib.rs".to_string()]
+        );
+        assert!(!contains_word("the routers table", "router"));
+    }
+
+    #[test]
+    fn renders_commits_files_and_ci() {
+        let commits = parse_commits(
+            "a1b2c3d\u{1f}Ana\u{1f}2 hours ago\u{1f}Fix flaky test\ne4f5a6b\u{1f}Bo\u{1f}1 day ago\u{1f}Bump deps\n",
+        );
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].subject, "Fix flaky test");
+
+        let briefing = Briefing {
+            since: Utc::now(),
+            commits,
+            changed_files: vec!["Cargo.lock".to_string(), "src/lib.rs".to_string()],
+            relevant_files: vec!["src/lib.rs".to_string()],
+            ci: Some(CiStatus {
+                workflow: "CI".to_string(),
+                status: "completed".to_string(),
+                conclusion: "failure".to_string(),
+                url: String::new(),
+            }),
+        };
+        let lines = briefing.render_lines();
+        assert!(lines[0].ends_with("2 new commits, 2 files changed."));
+        assert_eq!(lines[1], "  a1b2c3d Fix flaky test (Ana, 2 hours ago)");
+        assert_eq!(lines[3], "Touches what you worked on last time: src/lib.rs");
+        assert_eq!(lines[4], "Also changed: Cargo.lock");
+        assert_eq!(lines[5], "CI: CI failure");
+    }
+}
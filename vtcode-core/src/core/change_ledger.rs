@@ -0,0 +1,337 @@
+//! Record of the workspace files changed during a session
+//!
+//! The chat loop feeds every successful write tool call into a [`ChangeLedger`]
+//! together with the user request it was serving, so end-of-session summaries
+//! can describe what changed and why without re-reading diffs.
+
+use crate::config::constants::tools;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// How a file was changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChangeKind {
+    Created,
+    Modified,
+    Deleted,
+}
+
+impl ChangeKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            ChangeKind::Created => "Added",
+            ChangeKind::Modified => "Updated",
+            ChangeKind::Deleted => "Removed",
+        }
+    }
+}
+
+/// A single change made by a tool call.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileChange {
+    pub path: String,
+    pub kind: ChangeKind,
+    pub tool: String,
+    /// Index into [`ChangeLedger::requests`] of the request being served
+    pub request: Option<usize>,
+    /// Decision tracker id of the tool call that made the change
+    pub decision_id: Option<String>,
+}
+
+/// Net effect of the session on one file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileSummary {
+    pub path: String,
+    pub kind: ChangeKind,
+    pub tools: Vec<String>,
+    /// Requests that touched the file, in order
+    pub requests: Vec<usize>,
+}
+
+/// Ordered log of file changes and the user requests behind them.
+#[derive(Debug, Clone, Default)]
+pub struct ChangeLedger {
+    requests: Vec<String>,
+    changes: Vec<FileChange>,
+}
+
+impl ChangeLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start attributing changes to a new user request.
+    pub fn begin_request(&mut self, goal: impl Into<String>) {
+        self.requests.push(goal.into());
+    }
+
+    pub fn requests(&self) -> &[String] {
+        &self.requests
+    }
+
+    pub fn changes(&self) -> &[FileChange] {
+        &self.changes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Whether the file targeted by a path-based write tool exists yet.
+    ///
+    /// Call before running the tool so [`ChangeLedger::record_tool_result`] can
+    /// tell new files from edits.
+    pub fn target_exists(workspace: &Path, tool: &str, args: &Value) -> Option<bool> {
+        if !matches!(tool, tools::WRITE_FILE | tools::CREATE_FILE) {
+            return None;
+        }
+        let path = args.get("path").and_then(Value::as_str)?;
+        Some(workspace.join(path).exists())
+    }
+
+    /// Record the files changed by a successful tool call.
+    ///
+    /// Returns the number of changes recorded; non-write tools record nothing.
+    pub fn record_tool_result(
+        &mut self,
+        tool: &str,
+        args: &Value,
+        output: &Value,
+        target_existed: Option<bool>,
+        decision_id: Option<&str>,
+    ) -> usize {
+        if output.get("skipped").and_then(Value::as_bool) == Some(true) {
+            return 0;
+        }
+        let arg_path = args.get("path").and_then(Value::as_str);
+        let changes: Vec<(String, ChangeKind)> = match tool {
+            tools::WRITE_FILE | tools::CREATE_FILE => arg_path
+                .map(|path| {
+                    let kind = match target_existed {
+                        Some(true) => ChangeKind::Modified,
+                        Some(false) => ChangeKind::Created,
+                        None if tool == tools::CREATE_FILE => ChangeKind::Created,
+                        None => ChangeKind::Modified,
+                    };
+                    vec![(path.to_string(), kind)]
+                })
+                .unwrap_or_default(),
+            tools::EDIT_FILE => arg_path
+                .map(|path| vec![(path.to_string(), ChangeKind::Modified)])
+                .unwrap_or_default(),
+            tools::DELETE_FILE => arg_path
+                .map(|path| vec![(path.to_string(), ChangeKind::Deleted)])
+                .unwrap_or_default(),
+            tools::ORGANIZE_IMPORTS | tools::CONFIG_EDIT => arg_path
+                .filter(|_| {
+                    output.get("changed").and_then(Value::as_bool) == Some(true)
+                        && output.get("dry_run").and_then(Value::as_bool) != Some(true)
+                })
+                .map(|path| vec![(path.to_string(), ChangeKind::Modified)])
+                .unwrap_or_default(),
+            tools::APPLY_PATCH => output
+                .get("applied")
+                .and_then(Value::as_array)
+                .map(|lines| {
+                    lines
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .flat_map(parse_patch_result)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            _ => output
+                .get("modified_files")
+                .and_then(Value::as_array)
+                .map(|files| {
+                    files
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .map(|path| (path.to_string(), ChangeKind::Modified))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        };
+
+        let count = changes.len();
+        for (path, kind) in changes {
+            self.record(path, kind, tool, decision_id);
+        }
+        count
+    }
+
+    pub fn record(
+        &mut self,
+        path: impl Into<String>,
+        kind: ChangeKind,
+        tool: &str,
+        decision_id: Option<&str>,
+    ) {
+        self.changes.push(FileChange {
+            path: normalize_path(&path.into()),
+            kind,
+            tool: tool.to_string(),
+            request: self.requests.len().checked_sub(1),
+            decision_id: decision_id.map(str::to_string),
+        });
+    }
+
+    /// Net change per file, sorted by path.
+    ///
+    /// Files created and then deleted within the session are left out.
+    pub fn files(&self) -> Vec<FileSummary> {
+        let mut files: BTreeMap<&str, (ChangeKind, ChangeKind, FileSummary)> = BTreeMap::new();
+        for change in &self.changes {
+            let entry = files.entry(change.path.as_str()).or_insert_with(|| {
+                (
+                    change.kind,
+                    change.kind,
+                    FileSummary {
+                        path: change.path.clone(),
+                        kind: change.kind,
+                        tools: Vec::new(),
+                        requests: Vec::new(),
+                    },
+                )
+            });
+            entry.1 = change.kind;
+            if !entry.2.tools.contains(&change.tool) {
+                entry.2.tools.push(change.tool.clone());
+            }
+            if let Some(request) = change.request
+                && !entry.2.requests.contains(&request)
+            {
+                entry.2.requests.push(request);
+            }
+        }
+
+        files
+            .into_values()
+            .filter_map(|(first, last, mut summary)| {
+                summary.kind = match (first, last) {
+                    (ChangeKind::Created, ChangeKind::Deleted) => return None,
+                    (ChangeKind::Created, _) => ChangeKind::Created,
+                    (_, ChangeKind::Deleted) => ChangeKind::Deleted,
+                    _ => ChangeKind::Modified,
+                };
+                Some(summary)
+            })
+            .collect()
+    }
+
+    /// Decision id of the most recent recorded change.
+    pub fn last_decision_id(&self) -> Option<&str> {
+        self.changes
+            .iter()
+            .rev()
+            .find_map(|change| change.decision_id.as_deref())
+    }
+}
+
+fn parse_patch_result(line: &str) -> Vec<(String, ChangeKind)> {
+    if let Some(path) = line.strip_prefix("Added file: ") {
+        return vec![(path.to_string(), ChangeKind::Created)];
+    }
+    if let Some(path) = line.strip_prefix("Deleted file: ") {
+        return vec![(path.to_string(), ChangeKind::Deleted)];
+    }
+    if let Some(rest) = line.strip_prefix("Updated file: ") {
+        return match rest.split_once(" -> ") {
+            Some((from, to)) => vec![
+                (from.to_string(), ChangeKind::Deleted),
+                (to.to_string(), ChangeKind::Created),
+            ],
+            None => vec![(rest.to_string(), ChangeKind::Modified)],
+        };
+    }
+    Vec::new()
+}
+
+fn normalize_path(path: &str) -> String {
+    path.trim().trim_start_matches("./").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn records_write_tools_against_the_current_request() {
+        let mut ledger = ChangeLedger::new();
+        ledger.begin_request("add a parser");
+        ledger.record_tool_result(
+            tools::WRITE_FILE,
+            &json!({"path": "./src/parser.rs", "content": ""}),
+            &json!({"success": true}),
+            Some(false),
+            Some("dec_1"),
+        );
+        ledger.record_tool_result(
+            tools::READ_FILE,
+            &json!({"path": "src/lib.rs"}),
+            &json!({"content": ""}),
+            None,
+            Some("dec_2"),
+        );
+        ledger.begin_request("wire it up");
+        ledger.record_tool_result(
+            tools::APPLY_PATCH,
+            &json!({"input": "..."}),
+            &json!({"applied": ["Updated file: src/lib.rs", "Updated file: src/old.rs -> src/new.rs"]}),
+            None,
+            Some("dec_3"),
+        );
+        ledger.record_tool_result(
+            tools::EDIT_FILE,
+            &json!({"path": "src/parser.rs"}),
+            &json!({"success": true}),
+            None,
+            Some("dec_4"),
+        );
+
+        let files = ledger.files();
+        let summary: Vec<(&str, ChangeKind, Vec<usize>)> = files
+            .iter()
+            .map(|file| (file.path.as_str(), file.kind, file.requests.clone()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("src/lib.rs", ChangeKind::Modified, vec![1]),
+                ("src/new.rs", ChangeKind::Created, vec![1]),
+                ("src/old.rs", ChangeKind::Deleted, vec![1]),
+                ("src/parser.rs", ChangeKind::Created, vec![0, 1]),
+            ]
+        );
+        assert_eq!(ledger.last_decision_id(), Some("dec_4"));
+    }
+
+    #[test]
+    fn drops_files_created_and_deleted_in_the_same_session() {
+        let mut ledger = ChangeLedger::new();
+        ledger.record("scratch.txt", ChangeKind::Created, tools::CREATE_FILE, None);
+        ledger.record("scratch.txt", ChangeKind::Deleted, tools::DELETE_FILE, None);
+        ledger.record_tool_result(
+            tools::SRGN,
+            &json!({}),
+            &json!({"modified_files": ["src/a.rs"]}),
+            None,
+            None,
+        );
+        ledger.record_tool_result(
+            tools::WRITE_FILE,
+            &json!({"path": "src/b.rs"}),
+            &json!({"success": true, "skipped": true}),
+            Some(true),
+            None,
+        );
+
+        let files = ledger.files();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, "src/a.rs");
+        assert_eq!(files[0].tools, vec![tools::SRGN.to_string()]);
+    }
+}
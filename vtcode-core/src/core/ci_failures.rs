@@ -0,0 +1,475 @@
+//! Failures extracted from CI logs
+//!
+//! `vtcode fix-ci` reads the log of a failing run, either from GitHub Actions
+//! through the `gh` CLI or from a local file, and classifies what broke:
+//! compiler errors, lints, failing tests and formatting diffs. Locations are
+//! mapped from the CI runner's checkout (`/home/runner/work/app/app/src/...`)
+//! to files in the local workspace so the agent starts from the right places.
+
+use anyhow::{Context, Result, bail};
+use regex::Regex;
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::LazyLock;
+
+/// Lines of the cleaned log quoted in the fix prompt.
+const EXCERPT_LINES: usize = 120;
+/// Characters of the cleaned log quoted in the fix prompt.
+const EXCERPT_CHARS: usize = 12_000;
+/// Failures listed in the fix prompt before the list is abbreviated.
+const MAX_LISTED_FAILURES: usize = 25;
+
+static ANSI: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\x1b\[[0-9;?]*[A-Za-z]").expect("valid regex"));
+static TIMESTAMP: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(\.\d+)?Z ?").expect("valid regex")
+});
+static RUST_HEADER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^error(\[E\d+\])?: (.+)$").expect("valid regex"));
+static RUST_LOCATION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*--> (.+?):(\d+):\d+$").expect("valid regex"));
+static RUST_TEST: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^test (\S+) \.\.\. FAILED$").expect("valid regex"));
+static RUST_PANIC: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^thread '([^']+)' panicked at (.+?):(\d+):\d+:?(?: (.+))?$").expect("valid regex")
+});
+static RUSTFMT_DIFF: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^Diff in (.+?)(?: at line |:)(\d+):?$").expect("valid regex"));
+static TSC_ERROR: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(.+?)\((\d+),\d+\): error (TS\d+: .+)$").expect("valid regex"));
+static GENERIC_ERROR: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^([\w./\\-]+\.\w+):(\d+)(?::\d+)?: (?:fatal )?error:? (.+)$").expect("valid regex")
+});
+static PYTEST_FAILED: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^FAILED ([^:\s]+)::(\S+)(?: - (.+))?$").expect("valid regex"));
+static GO_FAIL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^--- FAIL: (\S+)").expect("valid regex"));
+static GO_LOCATION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s+(\S+_test\.go):(\d+): (.+)$").expect("valid regex"));
+static GITHUB_RUN_URL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"github\.com/([^/\s]+/[^/\s]+)/actions/runs/(\d+)").expect("valid regex")
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FailureKind {
+    Compile,
+    Lint,
+    Test,
+    Format,
+}
+
+impl fmt::Display for FailureKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Compile => "compile",
+            Self::Lint => "lint",
+            Self::Test => "test",
+            Self::Format => "format",
+        })
+    }
+}
+
+/// One failure found in a CI log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CiFailure {
+    pub kind: FailureKind,
+    pub message: String,
+    /// Failing test, for test failures
+    pub test: Option<String>,
+    /// Location as printed in the log
+    pub location: Option<String>,
+    /// Workspace-relative file the location refers to, when it exists locally
+    pub file: Option<PathBuf>,
+    pub line: Option<usize>,
+}
+
+impl CiFailure {
+    fn new(kind: FailureKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            test: None,
+            location: None,
+            file: None,
+            line: None,
+        }
+    }
+
+    fn at(mut self, location: &str, line: Option<usize>) -> Self {
+        self.location = Some(location.to_string());
+        self.line = line;
+        self
+    }
+
+    /// `src/lib.rs:12`, falling back to the location printed in the log.
+    pub fn place(&self) -> Option<String> {
+        let path = match &self.file {
+            Some(file) => file.display().to_string(),
+            None => self.location.clone()?,
+        };
+        Some(match self.line {
+            Some(line) => format!("{path}:{line}"),
+            None => path,
+        })
+    }
+}
+
+/// The run whose log `vtcode fix-ci --run` reads.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CiRun {
+    File(PathBuf),
+    GitHub {
+        /// `owner/name`; the current repository when `None`
+        repo: Option<String>,
+        run_id: String,
+    },
+}
+
+impl CiRun {
+    /// Parse a GitHub Actions run URL, a run id, or the path of a log file.
+    pub fn parse(input: &str, workspace: &Path) -> Result<Self> {
+        let input = input.trim();
+        if let Some(captures) = GITHUB_RUN_URL.captures(input) {
+            return Ok(Self::GitHub {
+                repo: Some(captures[1].to_string()),
+                run_id: captures[2].to_string(),
+            });
+        }
+        let path = workspace.join(input);
+        if path.is_file() {
+            return Ok(Self::File(path));
+        }
+        if !input.is_empty() && input.chars().all(|ch| ch.is_ascii_digit()) {
+            return Ok(Self::GitHub {
+                repo: None,
+                run_id: input.to_string(),
+            });
+        }
+        bail!(
+            "'{}' is not a GitHub Actions run URL, a run id, or a log file",
+            input
+        )
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            Self::File(path) => path.display().to_string(),
+            Self::GitHub {
+                repo: Some(repo),
+                run_id,
+            } => format!("{repo}#{run_id}"),
+            Self::GitHub { repo: None, run_id } => format!("#{run_id}"),
+        }
+    }
+
+    /// Fetch the raw log. GitHub runs are read through `gh run view`, which
+    /// must be installed and signed in.
+    pub fn fetch_log(&self, workspace: &Path) -> Result<String> {
+        match self {
+            Self::File(path) => std::fs::read_to_string(path)
+                .with_context(|| format!("failed to read {}", path.display())),
+            Self::GitHub { repo, run_id } => {
+                // Prefer the failed steps only; fall back to the full log for
+                // runs that were cancelled or failed outside a step.
+                for scope in ["--log-failed", "--log"] {
+                    let mut command = Command::new("gh");
+                    command
+                        .args(["run", "view", run_id.as_str(), scope])
+                        .current_dir(workspace);
+                    if let Some(repo) = repo {
+                        command.args(["-R", repo.as_str()]);
+                    }
+                    let output = command
+                        .output()
+                        .context("failed to run `gh`; install the GitHub CLI or pass a log file")?;
+                    if !output.status.success() {
+                        bail!(
+                            "gh run view {} failed: {}",
+                            run_id,
+                            String::from_utf8_lossy(&output.stderr).trim()
+                        );
+                    }
+                    let log = String::from_utf8_lossy(&output.stdout).into_owned();
+                    if !log.trim().is_empty() {
+                        return Ok(log);
+                    }
+                }
+                bail!("run {} has no log output", run_id)
+            }
+        }
+    }
+}
+
+/// Remove colour codes and the job, step and timestamp columns GitHub adds to
+/// every log line.
+pub fn clean_log(raw: &str) -> String {
+    let mut cleaned = String::with_capacity(raw.len());
+    for line in raw.lines() {
+        let line = ANSI.replace_all(line, "");
+        // `gh run view --log` prints `job<TAB>step<TAB>timestamp text`.
+        let line = match line.splitn(3, '\t').collect::<Vec<_>>()[..] {
+            [_, _, rest] if TIMESTAMP.is_match(rest) => rest,
+            _ => line.as_ref(),
+        };
+        cleaned.push_str(&TIMESTAMP.replace(line, ""));
+        cleaned.push('\n');
+    }
+    cleaned
+}
+
+/// Failures found in a cleaned log, in order of appearance and without
+/// duplicates, with locations mapped into `workspace`.
+pub fn classify_failures(log: &str, workspace: &Path) -> Vec<CiFailure> {
+    let mut failures: Vec<CiFailure> = Vec::new();
+    // Rust diagnostic message waiting for its `-->` line
+    let mut pending: Option<String> = None;
+    let mut in_diagnostic = false;
+    // Test failure whose panic message is on the next line
+    let mut panic_message: Option<usize> = None;
+    let mut current_go_test: Option<String> = None;
+
+    for line in log.lines() {
+        let trimmed = line.trim_end();
+        if let Some(captures) = RUST_HEADER.captures(trimmed) {
+            pending = Some(captures[2].to_string());
+            in_diagnostic = false;
+            continue;
+        }
+        if let Some(captures) = RUST_LOCATION.captures(trimmed) {
+            if let Some(message) = pending.take() {
+                failures.push(
+                    CiFailure::new(FailureKind::Compile, message)
+                        .at(&captures[1], captures[2].parse().ok()),
+                );
+                in_diagnostic = true;
+            }
+            continue;
+        }
+        if trimmed.is_empty() {
+            in_diagnostic = false;
+            continue;
+        }
+        // Clippy lints and denied rustc warnings say so in their notes.
+        if in_diagnostic
+            && ["clippy::", "rust-clippy", "#[deny(", "`-D "]
+                .iter()
+                .any(|marker| trimmed.contains(marker))
+            && let Some(last) = failures.last_mut()
+        {
+            last.kind = FailureKind::Lint;
+        }
+        if let Some(index) = panic_message.take()
+            && !trimmed.starts_with("note:")
+        {
+            failures[index].message = trimmed.trim().to_string();
+            continue;
+        }
+        if let Some(captures) = RUST_TEST.captures(trimmed) {
+            let mut failure = CiFailure::new(FailureKind::Test, "test failed");
+            failure.test = Some(captures[1].to_string());
+            failures.push(failure);
+        } else if let Some(captures) = RUST_PANIC.captures(trimmed) {
+            let test = &captures[1];
+            let line = captures[3].parse().ok();
+            let index = match failures.iter().position(|failure| {
+                failure.test.as_deref() == Some(test) && failure.location.is_none()
+            }) {
+                Some(index) => {
+                    failures[index] = failures[index].clone().at(&captures[2], line);
+                    index
+                }
+                None => {
+                    let mut failure =
+                        CiFailure::new(FailureKind::Test, "panicked").at(&captures[2], line);
+                    failure.test = Some(test.to_string());
+                    failures.push(failure);
+                    failures.len() - 1
+                }
+            };
+            match captures.get(4) {
+                Some(message) => failures[index].message = message.as_str().to_string(),
+                None => panic_message = Some(index),
+            }
+        } else if let Some(captures) = RUSTFMT_DIFF.captures(trimmed) {
+            failures.push(
+                CiFailure::new(FailureKind::Format, "not formatted")
+                    .at(&captures[1], captures[2].parse().ok()),
+            );
+        } else if let Some(captures) = TSC_ERROR.captures(trimmed) {
+            failures.push(
+                CiFailure::new(FailureKind::Compile, &captures[3])
+                    .at(&captures[1], captures[2].parse().ok()),
+            );
+        } else if let Some(captures) = GENERIC_ERROR.captures(trimmed) {
+            failures.push(
+                CiFailure::new(FailureKind::Compile, &captures[3])
+                    .at(&captures[1], captures[2].parse().ok()),
+            );
+        } else if let Some(captures) = PYTEST_FAILED.captures(trimmed) {
+            let message = captures
+                .get(3)
+                .map(|message| message.as_str())
+                .unwrap_or("test failed");
+            let mut failure = CiFailure::new(FailureKind::Test, message).at(&captures[1], None);
+            failure.test = Some(captures[2].to_string());
+            failures.push(failure);
+        } else if let Some(captures) = GO_FAIL.captures(trimmed) {
+            let mut failure = CiFailure::new(FailureKind::Test, "test failed");
+            failure.test = Some(captures[1].to_string());
+            current_go_test = failure.test.clone();
+            failures.push(failure);
+        } else if let Some(captures) = GO_LOCATION.captures(line)
+            && let Some(test) = current_go_test.as_deref()
+            && let Some(failure) = failures
+                .iter_mut()
+                .find(|failure| failure.test.as_deref() == Some(test) && failure.location.is_none())
+        {
+            failure.message = captures[3].to_string();
+            failure.location = Some(captures[1].to_string());
+            failure.line = captures[2].parse().ok();
+        }
+    }
+
+    let mut seen = HashSet::new();
+    failures.retain(|failure| {
+        seen.insert((
+            failure.kind,
+            failure.test.clone(),
+            failure.location.clone(),
+            failure.line,
+            failure.message.clone(),
+        ))
+    });
+    for failure in &mut failures {
+        failure.file = failure
+            .location
+            .as_deref()
+            .and_then(|location| map_to_workspace(location, workspace));
+    }
+    failures
+}
+
+/// Workspace-relative path of a file named in a CI log. Absolute paths from
+/// the runner are matched by their longest suffix that exists locally.
+pub fn map_to_workspace(location: &str, workspace: &Path) -> Option<PathBuf> {
+    let normalized = location.replace('\\', "/");
+    let normalized = normalized.trim_start_matches("./");
+    let parts: Vec<&str> = normalized
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .collect();
+    (0..parts.len()).find_map(|start| {
+        let relative: PathBuf = parts[start..].iter().collect();
+        workspace.join(&relative).is_file().then_some(relative)
+    })
+}
+
+/// Prompt that starts the fix session.
+pub fn fix_prompt(run: &CiRun, failures: &[CiFailure], log: &str) -> String {
+    let mut prompt = format!(
+        "CI run {} failed. Fix the failures below with focused changes: do not refactor unrelated code or touch files that are not involved.\n",
+        run.label()
+    );
+    if failures.is_empty() {
+        prompt.push_str(
+            "\nNo failures could be classified automatically; find the cause in the log excerpt.\n",
+        );
+    } else {
+        prompt.push_str("\nFailures:\n");
+        for (index, failure) in failures.iter().take(MAX_LISTED_FAILURES).enumerate() {
+            prompt.push_str(&format!("{}. [{}] ", index + 1, failure.kind));
+            if let Some(test) = &failure.test {
+                prompt.push_str(&format!("{} ", test));
+            }
+            if let Some(place) = failure.place() {
+                prompt.push_str(&format!("at {} ", place));
+            }
+            prompt.push_str(&format!("- {}\n", failure.message));
+        }
+        if failures.len() > MAX_LISTED_FAILURES {
+            prompt.push_str(&format!(
+                "... and {} more in the log.\n",
+                failures.len() - MAX_LISTED_FAILURES
+            ));
+        }
+    }
+    prompt.push_str(
+        "\nAfter editing, run the narrowest local command that reproduced each failure (for example one test, or the formatter in check mode) to confirm it passes. Finish with a short summary of what you changed and why.\n",
+    );
+    prompt.push_str(&format!(
+        "\nLog excerpt:\n```text\n{}\n```",
+        log_excerpt(log)
+    ));
+    prompt
+}
+
+/// The end of the log, where the failures are usually summarized.
+fn log_excerpt(log: &str) -> String {
+    let lines: Vec<&str> = log.lines().filter(|line| !line.trim().is_empty()).collect();
+    let mut excerpt = String::new();
+    for line in lines.iter().rev().take(EXCERPT_LINES) {
+        if excerpt.len() + line.len() + 1 > EXCERPT_CHARS {
+            break;
+        }
+        excerpt.insert_str(0, &format!("{line}\n"));
+    }
+    excerpt.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_rust_failures_and_maps_runner_paths() -> Result<()> {
+        let workspace = tempfile::tempdir()?;
+        std::fs::create_dir_all(workspace.path().join("src"))?;
+        std::fs::write(workspace.path().join("src/lib.rs"), "")?;
+        std::fs::write(workspace.path().join("src/main.rs"), "")?;
+
+        let raw = "\
+build\tRun cargo build\t2025-01-02T03:04:05.6789012Z \u{1b}[1m\u{1b}[31merror[E0308]\u{1b}[0m: mismatched types
+build\tRun cargo build\t2025-01-02T03:04:05.6789012Z   --> src/lib.rs:12:5
+error: this `if` has identical blocks
+  --> src/lib.rs:20:1
+   |
+   = help: for further information visit https://rust-lang.github.io/rust-clippy/master/index.html
+test parser::tests::parses ... FAILED
+---- parser::tests::parses stdout ----
+thread 'parser::tests::parses' panicked at src/lib.rs:40:9:
+assertion `left == right` failed
+Diff in /home/runner/work/app/app/src/main.rs at line 3:
+error: could not compile `app` due to 2 previous errors
+";
+        let log = clean_log(raw);
+        assert!(log.starts_with("error[E0308]: mismatched types\n"));
+
+        let failures = classify_failures(&log, workspace.path());
+        let summary: Vec<(FailureKind, Option<String>)> = failures
+            .iter()
+            .map(|failure| (failure.kind, failure.place()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (FailureKind::Compile, Some("src/lib.rs:12".to_string())),
+                (FailureKind::Lint, Some("src/lib.rs:20".to_string())),
+                (FailureKind::Test, Some("src/lib.rs:40".to_string())),
+                (FailureKind::Format, Some("src/main.rs:3".to_string())),
+            ]
+        );
+        assert_eq!(failures[2].test.as_deref(), Some("parser::tests::parses"));
+        assert_eq!(failures[2].message, "assertion `left == right` failed");
+
+        let run = CiRun::parse(
+            "https://github.com/acme/app/actions/runs/42/job/7",
+            workspace.path(),
+        )?;
+        assert_eq!(run.label(), "acme/app#42");
+        let prompt = fix_prompt(&run, &failures, &log);
+        assert!(prompt.contains("2. [lint] at src/lib.rs:20 - this `if` has identical blocks"));
+        Ok(())
+    }
+}
@@ -0,0 +1,250 @@
+//! Backups taken before destructive-but-allowed terminal commands
+//!
+//! Guardrails let some commands through that can still lose work, such as
+//! `git reset --hard`, `git clean -fd` or `rm -r` inside the workspace. Before
+//! one of them runs, the chat loop copies the files it may touch into
+//! `.vtcode/backups/commands/<id>/` so the user can put them back with a
+//! single keystroke. For git commands the affected files are the ones with
+//! uncommitted changes; for other commands they are the workspace paths named
+//! on the command line. Restoring copies the files back; files the command
+//! created are left alone.
+
+use anyhow::{Context, Result, bail};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+use walkdir::WalkDir;
+
+pub const BACKUP_DIR: &str = ".vtcode/backups/commands";
+const MANIFEST_FILE: &str = "manifest.json";
+const FILES_DIR: &str = "files";
+/// Backups larger than this are refused rather than slowing the command down.
+const MAX_FILES: usize = 5_000;
+const MAX_BYTES: u64 = 100 * 1024 * 1024;
+/// Older backups are removed once this many exist.
+const KEEP_BACKUPS: usize = 20;
+const SKIPPED_DIRS: &[&str] = &[".git", ".vtcode"];
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CommandBackup {
+    pub id: String,
+    pub command: String,
+    /// Workspace-relative paths of the copied files
+    pub files: Vec<String>,
+}
+
+impl CommandBackup {
+    /// Copy the files `command` may change. Nothing is written when no
+    /// files are affected.
+    pub fn capture(workspace: &Path, command: &str) -> Result<Self> {
+        let files = affected_files(workspace, command)?;
+        let total: u64 = files
+            .iter()
+            .filter_map(|file| fs::metadata(workspace.join(file)).ok())
+            .map(|metadata| metadata.len())
+            .sum();
+        if files.len() > MAX_FILES || total > MAX_BYTES {
+            bail!(
+                "{} files ({} MB) is too much to back up",
+                files.len(),
+                total / (1024 * 1024)
+            );
+        }
+
+        let backup = Self {
+            id: Local::now().format("%Y%m%dT%H%M%S%3f").to_string(),
+            command: command.to_string(),
+            files,
+        };
+        if backup.files.is_empty() {
+            return Ok(backup);
+        }
+        let dir = backup.dir(workspace);
+        for file in &backup.files {
+            let target = dir.join(FILES_DIR).join(file);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("failed to create {}", parent.display()))?;
+            }
+            fs::copy(workspace.join(file), &target)
+                .with_context(|| format!("failed to back up {file}"))?;
+        }
+        fs::write(
+            dir.join(MANIFEST_FILE),
+            serde_json::to_string_pretty(&backup)?,
+        )
+        .with_context(|| format!("failed to write backup manifest in {}", dir.display()))?;
+        prune(&workspace.join(BACKUP_DIR));
+        Ok(backup)
+    }
+
+    /// Put the backed-up files back, returning how many were restored.
+    pub fn restore(&self, workspace: &Path) -> Result<usize> {
+        let source = self.dir(workspace).join(FILES_DIR);
+        for file in &self.files {
+            let target = workspace.join(file);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("failed to create {}", parent.display()))?;
+            }
+            fs::copy(source.join(file), &target)
+                .with_context(|| format!("failed to restore {file}"))?;
+        }
+        Ok(self.files.len())
+    }
+
+    pub fn dir(&self, workspace: &Path) -> PathBuf {
+        workspace.join(BACKUP_DIR).join(&self.id)
+    }
+
+    /// Summary attached to the tool result.
+    pub fn to_json_value(&self, workspace: &Path) -> Value {
+        serde_json::json!({
+            "id": self.id,
+            "files": self.files.len(),
+            "path": self.dir(workspace).display().to_string(),
+        })
+    }
+}
+
+fn affected_files(workspace: &Path, command: &str) -> Result<Vec<String>> {
+    let words = shell_words::split(command).unwrap_or_else(|_| {
+        command
+            .split_whitespace()
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    });
+    let mut files = if words.first().map(String::as_str) == Some("git") {
+        git_changed_files(workspace)?
+    } else {
+        named_files(workspace, words.iter().skip(1))
+    };
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// Files with uncommitted changes, including untracked ones.
+fn git_changed_files(workspace: &Path) -> Result<Vec<String>> {
+    let output = Command::new("git")
+        .args(["status", "--porcelain", "-z", "--untracked-files=all"])
+        .current_dir(workspace)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .context("failed to run git status")?;
+    if !output.status.success() {
+        bail!("git status failed in {}", workspace.display());
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut entries = stdout.split('\0').filter(|entry| !entry.is_empty());
+    let mut files = Vec::new();
+    while let Some(entry) = entries.next() {
+        let Some(path) = entry.get(3..) else {
+            continue;
+        };
+        // Renames and copies are followed by the original path.
+        if matches!(entry.as_bytes()[0], b'R' | b'C') {
+            entries.next();
+        }
+        if workspace.join(path).is_file() {
+            files.push(path.to_string());
+        }
+    }
+    Ok(files)
+}
+
+/// Files under the workspace paths and globs named as arguments.
+fn named_files<'a>(workspace: &Path, args: impl Iterator<Item = &'a String>) -> Vec<String> {
+    let mut files = Vec::new();
+    for arg in args.filter(|arg| !arg.starts_with('-')) {
+        let paths: Vec<PathBuf> = if arg.contains(['*', '?', '[']) {
+            glob::glob(&workspace.join(arg).to_string_lossy())
+                .map(|matches| matches.flatten().collect())
+                .unwrap_or_default()
+        } else {
+            vec![workspace.join(arg)]
+        };
+        for path in paths {
+            let Some(relative) = workspace_relative(workspace, &path) else {
+                continue;
+            };
+            let walker = WalkDir::new(&path).into_iter().filter_entry(|entry| {
+                !SKIPPED_DIRS.contains(&entry.file_name().to_string_lossy().as_ref())
+            });
+            if relative.as_os_str().is_empty() || path.is_dir() {
+                files.extend(
+                    walker
+                        .flatten()
+                        .filter(|entry| entry.file_type().is_file())
+                        .filter_map(|entry| workspace_relative(workspace, entry.path()))
+                        .map(|path| path.to_string_lossy().into_owned()),
+                );
+            } else if path.is_file() {
+                files.push(relative.to_string_lossy().into_owned());
+            }
+        }
+    }
+    files
+}
+
+/// `path` relative to the workspace, or `None` when it is outside it.
+fn workspace_relative(workspace: &Path, path: &Path) -> Option<PathBuf> {
+    let relative = path.strip_prefix(workspace).ok()?;
+    let mut normalized = PathBuf::new();
+    for component in relative.components() {
+        match component {
+            Component::Normal(part) => normalized.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    Some(normalized)
+}
+
+fn prune(root: &Path) {
+    let Ok(entries) = fs::read_dir(root) else {
+        return;
+    };
+    let mut ids: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+    ids.sort();
+    let excess = ids.len().saturating_sub(KEEP_BACKUPS);
+    for dir in &ids[..excess] {
+        let _ = fs::remove_dir_all(dir);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn backs_up_named_paths_and_restores_them() {
+        let workspace = tempdir().unwrap();
+        let root = workspace.path();
+        fs::create_dir_all(root.join("build/out")).unwrap();
+        fs::write(root.join("build/out/app.bin"), "binary").unwrap();
+        fs::write(root.join("notes.txt"), "keep me").unwrap();
+        fs::write(root.join("other.txt"), "untouched").unwrap();
+
+        let backup = CommandBackup::capture(root, "rm -rf build notes.txt ../elsewhere").unwrap();
+        assert_eq!(backup.files, vec!["build/out/app.bin", "notes.txt"]);
+        assert!(backup.dir(root).join(MANIFEST_FILE).is_file());
+
+        fs::remove_dir_all(root.join("build")).unwrap();
+        fs::remove_file(root.join("notes.txt")).unwrap();
+        assert_eq!(backup.restore(root).unwrap(), 2);
+        assert_eq!(
+            fs::read_to_string(root.join("build/out/app.bin")).unwrap(),
+            "binary"
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("notes.txt")).unwrap(),
+            "keep me"
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use crate::config::constants::models;
+use crate::core::provenance::Provenance;
 use crate::llm::provider::{LLMProvider, LLMRequest, Message, MessageRole};
 use serde::{Deserialize, Serialize};
 // std::collections::HashMap import removed as it's not used
@@ -106,6 +107,7 @@ impl ContextCompressor {
                 content: format!("Previous conversation summary: {}", summary),
                 tool_calls: None,
                 tool_call_id: None,
+                provenance: Some(Provenance::Memory),
             });
         }
 
@@ -239,12 +241,14 @@ impl ContextCompressor {
                     content: system_prompt,
                     tool_calls: None,
                     tool_call_id: None,
+                    provenance: None,
                 },
                 Message {
                     role: MessageRole::User,
                     content: user_prompt,
                     tool_calls: None,
                     tool_call_id: None,
+                    provenance: None,
                 },
             ],
             system_prompt: None,
@@ -257,6 +261,10 @@ impl ContextCompressor {
             parallel_tool_calls: None,
             parallel_tool_config: None,
             reasoning_effort: None,
+            json_mode: false,
+            response_schema: None,
+            code_execution: false,
+            seed: None,
         };
 
         let response = self
@@ -345,12 +353,14 @@ mod tests {
                 content: "Hello world".to_string(),
                 tool_calls: None,
                 tool_call_id: None,
+                provenance: None,
             },
             Message {
                 role: MessageRole::Assistant,
                 content: "Hi there! How can I help you?".to_string(),
                 tool_calls: None,
                 tool_call_id: None,
+                provenance: None,
             },
         ];
 
@@ -374,6 +384,7 @@ mod tests {
             content: "x".repeat(400), // ~100 tokens
             tool_calls: None,
             tool_call_id: None,
+            provenance: None,
         }];
 
         assert!(compressor.needs_compression(&messages));
@@ -401,6 +412,7 @@ mod tests {
                 usage: None,
                 finish_reason: FinishReason::Stop,
                 reasoning: None,
+                code_execution: Vec::new(),
             })
         }
 
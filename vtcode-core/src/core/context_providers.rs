@@ -0,0 +1,363 @@
+//! Extension point for domain-specific context sources
+//!
+//! A [`ContextProvider`] turns the user's request into [`ContextBlock`]s from a
+//! source the agent cannot reach with its tools, such as an internal wiki, a
+//! ticket tracker or a telemetry dashboard. Embedders register providers on a
+//! [`ContextProviderRegistry`]; `[[context.providers.command]]` entries in
+//! `vtcode.toml` add providers backed by external programs. The registry
+//! queries providers concurrently, reuses recent results for the same query,
+//! skips providers that fail or time out, and keeps the combined context within
+//! the configured token budgets.
+
+use crate::config::{CommandContextProviderConfig, ContextProvidersConfig};
+use anyhow::{Context, Result, bail};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::time::timeout;
+
+/// Rough characters per token for budgeting.
+const CHARS_PER_TOKEN: usize = 4;
+/// Smallest remainder worth filling with a truncated block.
+const MIN_TRUNCATED_TOKENS: usize = 50;
+const SECTION_HEADER: &str = "[Additional Context]";
+
+/// What a provider is asked for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextQuery {
+    /// The user's request
+    pub text: String,
+    pub workspace: PathBuf,
+}
+
+/// A piece of context from a provider.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ContextBlock {
+    pub title: String,
+    pub content: String,
+    /// Where the content came from, such as a URL or ticket id
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+impl ContextBlock {
+    pub fn new(title: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            content: content.into(),
+            source: None,
+        }
+    }
+
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    fn tokens(&self) -> usize {
+        (self.title.len() + self.content.len()).div_ceil(CHARS_PER_TOKEN)
+    }
+
+    /// The block cut down to about `tokens` tokens, `None` when too little fits.
+    fn fit(mut self, tokens: usize) -> Option<Self> {
+        if self.tokens() <= tokens {
+            return Some(self);
+        }
+        if tokens < MIN_TRUNCATED_TOKENS {
+            return None;
+        }
+        let chars = (tokens * CHARS_PER_TOKEN).saturating_sub(self.title.len());
+        self.content = self.content.chars().take(chars).collect();
+        self.content.push_str(" …");
+        Some(self)
+    }
+}
+
+/// A source of context consulted on each request.
+#[async_trait]
+pub trait ContextProvider: Send + Sync {
+    /// Short label shown with the provider's context and in errors
+    fn name(&self) -> &str;
+
+    /// Context relevant to `query`, most relevant first.
+    async fn gather(&self, query: &ContextQuery) -> Result<Vec<ContextBlock>>;
+}
+
+/// Context gathered for one request.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GatheredContext {
+    /// Provider name and block, in registration order
+    pub blocks: Vec<(String, ContextBlock)>,
+    /// Providers that failed or timed out, with the reason
+    pub failures: Vec<(String, String)>,
+}
+
+impl GatheredContext {
+    /// The blocks as a system prompt section, `None` when there are none.
+    pub fn prompt_section(&self) -> Option<String> {
+        if self.blocks.is_empty() {
+            return None;
+        }
+        let mut section = String::from(SECTION_HEADER);
+        section.push_str(
+            "\nContext from external sources for this request. Use it when relevant and cite the source.",
+        );
+        for (provider, block) in &self.blocks {
+            section.push_str(&format!("\n\n## {} ({provider})", block.title));
+            if let Some(source) = &block.source {
+                section.push_str(&format!("\nSource: {source}"));
+            }
+            section.push('\n');
+            section.push_str(block.content.trim_end());
+        }
+        Some(section)
+    }
+}
+
+struct CachedBlocks {
+    at: Instant,
+    blocks: Vec<ContextBlock>,
+}
+
+/// Registered context providers and the budgets and cache they share.
+pub struct ContextProviderRegistry {
+    providers: Vec<Arc<dyn ContextProvider>>,
+    max_tokens: usize,
+    max_tokens_per_provider: usize,
+    cache_ttl: Duration,
+    timeout: Duration,
+    cache: Mutex<HashMap<(String, String), CachedBlocks>>,
+}
+
+impl ContextProviderRegistry {
+    /// Empty registry with the budgets of `config`.
+    pub fn new(config: &ContextProvidersConfig) -> Self {
+        Self {
+            providers: Vec::new(),
+            max_tokens: config.max_tokens,
+            max_tokens_per_provider: config.max_tokens_per_provider,
+            cache_ttl: Duration::from_secs(config.cache_ttl_seconds),
+            timeout: Duration::from_secs(config.timeout_seconds.max(1)),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Registry with the command providers configured in `config`.
+    pub fn from_config(config: &ContextProvidersConfig, workspace: &Path) -> Self {
+        let mut registry = Self::new(config);
+        for entry in &config.command {
+            registry.register(Arc::new(CommandContextProvider::new(entry, workspace)));
+        }
+        registry
+    }
+
+    pub fn register(&mut self, provider: Arc<dyn ContextProvider>) {
+        self.providers.push(provider);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+
+    /// Ask every provider about `query` and keep what fits the budgets.
+    pub async fn gather(&self, query: &ContextQuery) -> GatheredContext {
+        let results = futures::future::join_all(
+            self.providers
+                .iter()
+                .map(|provider| self.gather_from(provider.as_ref(), query)),
+        )
+        .await;
+
+        let mut gathered = GatheredContext::default();
+        let mut remaining = self.max_tokens;
+        for (provider, result) in self.providers.iter().zip(results) {
+            let blocks = match result {
+                Ok(blocks) => blocks,
+                Err(err) => {
+                    gathered
+                        .failures
+                        .push((provider.name().to_string(), format!("{err:#}")));
+                    continue;
+                }
+            };
+            let mut provider_remaining = self.max_tokens_per_provider;
+            for block in blocks {
+                let Some(block) = block.fit(provider_remaining.min(remaining)) else {
+                    break;
+                };
+                provider_remaining = provider_remaining.saturating_sub(block.tokens());
+                remaining = remaining.saturating_sub(block.tokens());
+                gathered.blocks.push((provider.name().to_string(), block));
+            }
+        }
+        gathered
+    }
+
+    async fn gather_from(
+        &self,
+        provider: &dyn ContextProvider,
+        query: &ContextQuery,
+    ) -> Result<Vec<ContextBlock>> {
+        let key = (provider.name().to_string(), query.text.clone());
+        if let Some(cached) = self.cache.lock().get(&key)
+            && cached.at.elapsed() < self.cache_ttl
+        {
+            return Ok(cached.blocks.clone());
+        }
+        let blocks = timeout(self.timeout, provider.gather(query))
+            .await
+            .with_context(|| format!("timed out after {}s", self.timeout.as_secs()))??;
+        if !self.cache_ttl.is_zero() {
+            self.cache.lock().insert(
+                key,
+                CachedBlocks {
+                    at: Instant::now(),
+                    blocks: blocks.clone(),
+                },
+            );
+        }
+        Ok(blocks)
+    }
+}
+
+/// Provider backed by a program that reads the query on stdin.
+///
+/// The program runs from the workspace root with `VTCODE_CONTEXT_QUERY` set.
+/// Output that parses as a JSON array of `{"title", "content", "source"}`
+/// objects gives one block each; any other output becomes a single block.
+/// Empty output means nothing relevant was found.
+pub struct CommandContextProvider {
+    name: String,
+    command: Vec<String>,
+    workspace: PathBuf,
+}
+
+impl CommandContextProvider {
+    pub fn new(config: &CommandContextProviderConfig, workspace: &Path) -> Self {
+        Self {
+            name: config.name.clone(),
+            command: config.command.clone(),
+            workspace: workspace.to_path_buf(),
+        }
+    }
+}
+
+#[async_trait]
+impl ContextProvider for CommandContextProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn gather(&self, query: &ContextQuery) -> Result<Vec<ContextBlock>> {
+        let Some((program, args)) = self.command.split_first() else {
+            bail!("no command configured");
+        };
+        let mut child = Command::new(program)
+            .args(args)
+            .current_dir(&self.workspace)
+            .env("VTCODE_CONTEXT_QUERY", &query.text)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("failed to run {program}"))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(query.text.as_bytes()).await?;
+        }
+        let output = child.wait_with_output().await?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!("{program} exited with {}: {}", output.status, stderr.trim());
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stdout = stdout.trim();
+        if stdout.is_empty() {
+            return Ok(Vec::new());
+        }
+        if let Ok(blocks) = serde_json::from_str::<Vec<ContextBlock>>(stdout) {
+            return Ok(blocks);
+        }
+        Ok(vec![ContextBlock::new(self.name.clone(), stdout)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct Wiki {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl ContextProvider for Wiki {
+        fn name(&self) -> &str {
+            "wiki"
+        }
+
+        async fn gather(&self, query: &ContextQuery) -> Result<Vec<ContextBlock>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(vec![
+                ContextBlock::new("Deploys", format!("How to {}", query.text))
+                    .with_source("wiki/deploys"),
+                ContextBlock::new("Runbook", "x".repeat(4_000)),
+                ContextBlock::new("Glossary", "never reached"),
+            ])
+        }
+    }
+
+    struct Broken;
+
+    #[async_trait]
+    impl ContextProvider for Broken {
+        fn name(&self) -> &str {
+            "tickets"
+        }
+
+        async fn gather(&self, _query: &ContextQuery) -> Result<Vec<ContextBlock>> {
+            bail!("unauthorized")
+        }
+    }
+
+    #[tokio::test]
+    async fn gathers_within_budget_and_caches_results() {
+        let config = ContextProvidersConfig {
+            max_tokens_per_provider: 300,
+            ..ContextProvidersConfig::default()
+        };
+        let wiki = Arc::new(Wiki {
+            calls: AtomicUsize::new(0),
+        });
+        let mut registry = ContextProviderRegistry::new(&config);
+        registry.register(wiki.clone());
+        registry.register(Arc::new(Broken));
+        let query = ContextQuery {
+            text: "roll back a deploy".to_string(),
+            workspace: PathBuf::from("."),
+        };
+
+        let gathered = registry.gather(&query).await;
+        let titles: Vec<&str> = gathered
+            .blocks
+            .iter()
+            .map(|(_, block)| block.title.as_str())
+            .collect();
+        assert_eq!(titles, ["Deploys", "Runbook"]);
+        assert!(gathered.blocks[1].1.content.ends_with(" …"));
+        assert_eq!(gathered.failures[0].0, "tickets");
+        let section = gathered.prompt_section().unwrap();
+        assert!(section.contains("## Deploys (wiki)\nSource: wiki/deploys\nHow to roll back"));
+
+        registry.gather(&query).await;
+        assert_eq!(wiki.calls.load(Ordering::SeqCst), 1);
+    }
+}
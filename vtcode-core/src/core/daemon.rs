@@ -0,0 +1,446 @@
+//! Background daemon that keeps workspace state warm between launches
+//!
+//! `vtcode daemon run` serves one workspace over a unix socket under
+//! `~/.vtcode/daemon/`. Every `[daemon] refresh_interval_secs` it refreshes
+//! the language summary shown in the welcome text, the reachability of the
+//! provider endpoints and the workspace [`SymbolIndex`], which it keeps in
+//! memory with its tree-sitter parsers so that only changed files are parsed
+//! again. A chat session asks for the [`WarmState`] with a short timeout and
+//! skips the language scan and the connectivity probe when the daemon
+//! answers, then fetches the symbol index in the background so that
+//! organize_imports and call_graph start from it instead of parsing the whole
+//! workspace. Without a daemon it starts as before. Syntax trees and provider
+//! connections cannot cross the process boundary and stay per session.
+//!
+//! The protocol is one JSON [`DaemonRequest`] per line, answered by one JSON
+//! [`DaemonReply`] per line.
+
+use crate::core::offline::{Connectivity, probe_endpoint};
+use crate::tools::imports::SymbolIndex;
+use crate::utils::dot_config::{DotManager, workspace_key};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Overrides the directory holding daemon sockets.
+pub const DAEMON_DIR_ENV: &str = "VT_DAEMON_DIR";
+
+/// State the daemon keeps ready for new sessions.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WarmState {
+    pub workspace: PathBuf,
+    /// Detected languages with file counts, as shown in the welcome text
+    pub language_summary: Option<String>,
+    /// Reachability per provider endpoint: `None` when reachable, otherwise
+    /// the reason it is not
+    pub endpoints: BTreeMap<String, Option<String>>,
+    /// Files in the symbol index served with [`DaemonRequest::SymbolIndex`]
+    #[serde(default)]
+    pub indexed_files: usize,
+    /// When the state was last refreshed; `None` until the first refresh ends
+    pub refreshed_at: Option<DateTime<Utc>>,
+}
+
+impl WarmState {
+    /// Cached reachability of `endpoint`, if the daemon probes it.
+    pub fn connectivity(&self, endpoint: &str) -> Option<Connectivity> {
+        self.endpoints.get(endpoint).map(|status| match status {
+            None => Connectivity::Online,
+            Some(reason) => Connectivity::Offline {
+                reason: reason.clone(),
+            },
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum DaemonRequest {
+    /// Current state without waiting for a refresh
+    Status,
+    /// Refresh now and return the new state
+    Refresh,
+    /// Current state along with the workspace symbol index
+    SymbolIndex,
+    /// Stop the daemon after replying
+    Shutdown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonReply {
+    pub pid: u32,
+    pub started_at: DateTime<Utc>,
+    pub state: WarmState,
+    /// Set in answers to [`DaemonRequest::SymbolIndex`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbol_index: Option<SymbolIndex>,
+}
+
+/// Socket of the daemon serving `workspace`.
+pub fn socket_path(workspace: &Path) -> Result<PathBuf> {
+    let dir = match std::env::var_os(DAEMON_DIR_ENV) {
+        Some(dir) => PathBuf::from(dir),
+        None => DotManager::new()
+            .context("failed to locate the VTCode dot directory")?
+            .daemon_dir(),
+    };
+    Ok(dir.join(format!("{}.sock", workspace_key(workspace))))
+}
+
+/// Recompute the warm state of `workspace`.
+pub async fn collect_state(
+    workspace: &Path,
+    endpoints: &[String],
+    probe_timeout: Duration,
+) -> WarmState {
+    let root = workspace.to_path_buf();
+    let language_summary = tokio::task::spawn_blocking(move || {
+        crate::utils::utils::summarize_workspace_languages(&root)
+    })
+    .await
+    .unwrap_or(None);
+    let mut statuses = BTreeMap::new();
+    for endpoint in endpoints {
+        let status = match probe_endpoint(endpoint, probe_timeout).await {
+            Connectivity::Online => None,
+            Connectivity::Offline { reason } => Some(reason),
+        };
+        statuses.insert(endpoint.clone(), status);
+    }
+    WarmState {
+        workspace: workspace.to_path_buf(),
+        language_summary,
+        endpoints: statuses,
+        // Counted by the daemon, which owns the index
+        indexed_files: 0,
+        refreshed_at: Some(Utc::now()),
+    }
+}
+
+/// Send one request to the daemon at `socket`.
+pub async fn send_request(
+    socket: &Path,
+    request: DaemonRequest,
+    timeout: Duration,
+) -> Result<DaemonReply> {
+    tokio::time::timeout(timeout, imp::exchange(socket, request))
+        .await
+        .context("the daemon did not answer in time")?
+}
+
+/// Warm state for `workspace` from a running daemon, or `None` when no daemon
+/// answers within `timeout` or it has not finished its first refresh.
+pub async fn fetch_warm_state(workspace: &Path, timeout: Duration) -> Option<WarmState> {
+    let socket = socket_path(workspace).ok()?;
+    if !socket.exists() {
+        return None;
+    }
+    let reply = send_request(&socket, DaemonRequest::Status, timeout)
+        .await
+        .ok()?;
+    reply.state.refreshed_at.is_some().then_some(reply.state)
+}
+
+/// Symbol index served by the daemon for `workspace`, or `None` when no
+/// daemon answers within `timeout` or it has not finished its first refresh.
+pub async fn fetch_symbol_index(workspace: &Path, timeout: Duration) -> Option<SymbolIndex> {
+    let socket = socket_path(workspace).ok()?;
+    let reply = send_request(&socket, DaemonRequest::SymbolIndex, timeout)
+        .await
+        .ok()?;
+    reply.state.refreshed_at?;
+    reply.symbol_index
+}
+
+/// Serves the warm state of one workspace.
+pub struct DaemonServer {
+    workspace: PathBuf,
+    endpoints: Vec<String>,
+    refresh_interval: Duration,
+    probe_timeout: Duration,
+    max_index_files: usize,
+}
+
+impl DaemonServer {
+    pub fn new(
+        workspace: PathBuf,
+        endpoints: Vec<String>,
+        refresh_interval: Duration,
+        probe_timeout: Duration,
+        max_index_files: usize,
+    ) -> Self {
+        Self {
+            workspace,
+            endpoints,
+            refresh_interval: refresh_interval.max(Duration::from_secs(5)),
+            probe_timeout,
+            max_index_files,
+        }
+    }
+
+    /// Listen on `socket` until a shutdown request arrives.
+    pub async fn run(self, socket: &Path) -> Result<()> {
+        imp::serve(self, socket).await
+    }
+}
+
+#[cfg(unix)]
+mod imp {
+    use super::*;
+    use crate::tools::imports::ImportLanguage;
+    use crate::tools::tree_sitter::TreeSitterAnalyzer;
+    use anyhow::bail;
+    use std::sync::Arc;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{UnixListener, UnixStream};
+    use tokio::sync::{Notify, RwLock};
+
+    pub(super) async fn exchange(socket: &Path, request: DaemonRequest) -> Result<DaemonReply> {
+        let stream = UnixStream::connect(socket)
+            .await
+            .with_context(|| format!("no daemon listening on {}", socket.display()))?;
+        let (reader, mut writer) = stream.into_split();
+        let mut line = serde_json::to_string(&request)?;
+        line.push('\n');
+        writer.write_all(line.as_bytes()).await?;
+        let mut reply = String::new();
+        BufReader::new(reader).read_line(&mut reply).await?;
+        serde_json::from_str(&reply).context("the daemon sent an invalid reply")
+    }
+
+    /// The symbol index and the parsers that keep it current.
+    struct WarmIndex {
+        index: SymbolIndex,
+        analyzer: Option<TreeSitterAnalyzer>,
+    }
+
+    impl WarmIndex {
+        /// Parse the files changed since the last refresh.
+        fn refresh(&mut self, workspace: &Path) {
+            if self.analyzer.is_none() {
+                match TreeSitterAnalyzer::new() {
+                    Ok(analyzer) => self.analyzer = Some(analyzer),
+                    Err(err) => {
+                        tracing::warn!(error = %err, "failed to start tree-sitter parsers");
+                        return;
+                    }
+                }
+            }
+            if let Some(analyzer) = self.analyzer.as_mut() {
+                for family in ImportLanguage::FAMILIES {
+                    if let Err(err) = self.index.refresh(workspace, family, analyzer) {
+                        tracing::warn!(error = %err, family = family.name(), "failed to index symbols");
+                    }
+                }
+            }
+        }
+    }
+
+    struct Shared {
+        server: DaemonServer,
+        started_at: DateTime<Utc>,
+        state: RwLock<WarmState>,
+        index: Arc<parking_lot::Mutex<WarmIndex>>,
+        /// Copy of the index as of the last refresh, so answers never wait
+        /// for one in progress
+        symbols: RwLock<SymbolIndex>,
+        shutdown: Notify,
+    }
+
+    impl Shared {
+        async fn refresh(&self) {
+            let mut state = collect_state(
+                &self.server.workspace,
+                &self.server.endpoints,
+                self.server.probe_timeout,
+            )
+            .await;
+            let index = Arc::clone(&self.index);
+            let workspace = self.server.workspace.clone();
+            let refreshed = tokio::task::spawn_blocking(move || {
+                let mut index = index.lock();
+                index.refresh(&workspace);
+                index.index.clone()
+            })
+            .await;
+            if let Ok(symbols) = refreshed {
+                state.indexed_files = symbols.len();
+                *self.symbols.write().await = symbols;
+            }
+            *self.state.write().await = state;
+        }
+
+        async fn reply(&self, with_index: bool) -> DaemonReply {
+            DaemonReply {
+                pid: std::process::id(),
+                started_at: self.started_at,
+                state: self.state.read().await.clone(),
+                symbol_index: if with_index {
+                    Some(self.symbols.read().await.clone())
+                } else {
+                    None
+                },
+            }
+        }
+    }
+
+    pub(super) async fn serve(server: DaemonServer, socket: &Path) -> Result<()> {
+        if socket.exists() {
+            if UnixStream::connect(socket).await.is_ok() {
+                bail!("A daemon is already running on {}", socket.display());
+            }
+            std::fs::remove_file(socket)
+                .with_context(|| format!("failed to remove stale socket {}", socket.display()))?;
+        }
+        if let Some(parent) = socket.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        let listener = UnixListener::bind(socket)
+            .with_context(|| format!("failed to listen on {}", socket.display()))?;
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(socket, std::fs::Permissions::from_mode(0o600))?;
+        }
+
+        let shared = Arc::new(Shared {
+            state: RwLock::new(WarmState {
+                workspace: server.workspace.clone(),
+                ..WarmState::default()
+            }),
+            index: Arc::new(parking_lot::Mutex::new(WarmIndex {
+                index: SymbolIndex::new(server.max_index_files),
+                analyzer: None,
+            })),
+            symbols: RwLock::new(SymbolIndex::new(server.max_index_files)),
+            server,
+            started_at: Utc::now(),
+            shutdown: Notify::new(),
+        });
+
+        let refresher = {
+            let shared = Arc::clone(&shared);
+            tokio::spawn(async move {
+                let mut ticks = tokio::time::interval(shared.server.refresh_interval);
+                loop {
+                    ticks.tick().await;
+                    shared.refresh().await;
+                }
+            })
+        };
+
+        loop {
+            tokio::select! {
+                accepted = listener.accept() => {
+                    let Ok((stream, _)) = accepted else { continue };
+                    let shared = Arc::clone(&shared);
+                    tokio::spawn(async move {
+                        let _ = handle_connection(stream, &shared).await;
+                    });
+                }
+                _ = shared.shutdown.notified() => break,
+            }
+        }
+
+        refresher.abort();
+        let _ = std::fs::remove_file(socket);
+        Ok(())
+    }
+
+    async fn handle_connection(stream: UnixStream, shared: &Shared) -> Result<()> {
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        while let Some(line) = lines.next_line().await? {
+            let request: DaemonRequest = match serde_json::from_str(&line) {
+                Ok(request) => request,
+                Err(_) => continue,
+            };
+            if request == DaemonRequest::Refresh {
+                shared.refresh().await;
+            }
+            let reply = shared.reply(request == DaemonRequest::SymbolIndex).await;
+            let mut payload = serde_json::to_string(&reply)?;
+            payload.push('\n');
+            writer.write_all(payload.as_bytes()).await?;
+            if request == DaemonRequest::Shutdown {
+                shared.shutdown.notify_one();
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(unix))]
+mod imp {
+    use super::*;
+    use anyhow::bail;
+
+    pub(super) async fn exchange(_socket: &Path, _request: DaemonRequest) -> Result<DaemonReply> {
+        bail!("vtcode daemon needs unix domain sockets")
+    }
+
+    pub(super) async fn serve(_server: DaemonServer, _socket: &Path) -> Result<()> {
+        bail!("vtcode daemon needs unix domain sockets")
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::tools::imports::ImportLanguage;
+
+    #[tokio::test]
+    async fn serves_warm_state_until_shutdown() -> Result<()> {
+        let workspace = tempfile::tempdir()?;
+        std::fs::write(workspace.path().join("main.rs"), "fn main() {}\n")?;
+        std::fs::write(workspace.path().join("config.rs"), "pub struct Config;\n")?;
+        let sockets = tempfile::tempdir()?;
+        let socket = sockets.path().join("ws.sock");
+
+        let server = DaemonServer::new(
+            workspace.path().to_path_buf(),
+            Vec::new(),
+            Duration::from_secs(60),
+            Duration::from_millis(100),
+            100,
+        );
+        let task = tokio::spawn({
+            let socket = socket.clone();
+            async move { server.run(&socket).await }
+        });
+        while !socket.exists() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let timeout = Duration::from_secs(5);
+        let reply = send_request(&socket, DaemonRequest::Refresh, timeout).await?;
+        assert_eq!(reply.pid, std::process::id());
+        assert!(reply.state.refreshed_at.is_some());
+        assert!(
+            reply
+                .state
+                .language_summary
+                .as_deref()
+                .is_some_and(|summary| summary.contains("Rust"))
+        );
+        assert_eq!(reply.state.indexed_files, 2);
+        assert!(reply.symbol_index.is_none());
+
+        let index = send_request(&socket, DaemonRequest::SymbolIndex, timeout)
+            .await?
+            .symbol_index
+            .expect("symbol index");
+        let found = index.lookup(ImportLanguage::Rust, "Config");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, Path::new("config.rs"));
+
+        send_request(&socket, DaemonRequest::Shutdown, timeout).await?;
+        task.await??;
+        assert!(!socket.exists());
+        Ok(())
+    }
+}
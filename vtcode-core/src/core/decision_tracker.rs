@@ -75,6 +75,7 @@ pub enum DecisionOutcome {
 }
 
 /// Decision tracker for maintaining transparency
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DecisionTracker {
     decisions: Vec<Decision>,
     current_context: DecisionContext,
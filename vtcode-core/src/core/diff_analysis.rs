@@ -0,0 +1,939 @@
+//! Differential analysis between two git refs.
+//!
+//! `vtcode analyze --diff main..HEAD` restricts workspace analysis to what
+//! changed in a range: definitions added, removed or modified (from
+//! tree-sitter outlines of both versions), the change in control-flow
+//! complexity per file, TODO markers introduced by added lines, and
+//! dependencies added, removed or updated in package manifests. The report
+//! renders as Markdown for people and as JSON for tools, and `vtcode review
+//! --diff` sends it to the model alongside the diff.
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::process::Command;
+
+use crate::core::progress::Progress;
+use crate::core::risk_map::{TODO_PATTERN, decision_points};
+use crate::tools::tree_sitter::{OutlineEntry, TreeSitterAnalyzer};
+
+/// Files larger than this are counted but not parsed
+const MAX_PARSE_BYTES: usize = 1024 * 1024;
+
+/// A range of commits, as written on the command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffRange {
+    pub base: String,
+    pub head: String,
+    /// `base...head`: compare against the merge base of the two refs
+    pub merge_base: bool,
+}
+
+impl DiffRange {
+    /// Parse `base..head`, `base...head` or a single `base` (meaning
+    /// `base..HEAD`). An empty side defaults to `HEAD`, as in git.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+        let (base, head, merge_base) = if let Some((base, head)) = spec.split_once("...") {
+            (base, head, true)
+        } else if let Some((base, head)) = spec.split_once("..") {
+            (base, head, false)
+        } else {
+            (spec, "HEAD", false)
+        };
+        if base.is_empty() && head.is_empty() {
+            bail!("Invalid diff range `{spec}`; expected BASE..HEAD");
+        }
+        let side = |side: &str| {
+            if side.is_empty() {
+                "HEAD".to_string()
+            } else {
+                side.to_string()
+            }
+        };
+        Ok(Self {
+            base: side(base),
+            head: side(head),
+            merge_base,
+        })
+    }
+
+    pub fn label(&self) -> String {
+        let dots = if self.merge_base { "..." } else { ".." };
+        format!("{}{dots}{}", self.base, self.head)
+    }
+
+    /// Commit ids of the two sides of the range.
+    fn resolve(&self, workspace: &Path) -> Result<(String, String)> {
+        let head = rev_parse(workspace, &self.head)?;
+        let base = if self.merge_base {
+            git(workspace, &["merge-base", &self.base, &self.head])
+                .with_context(|| format!("No merge base between {} and {}", self.base, self.head))?
+                .trim()
+                .to_string()
+        } else {
+            rev_parse(workspace, &self.base)?
+        };
+        Ok((base, head))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileStatus {
+    Added,
+    Modified,
+    Deleted,
+    Renamed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Change {
+    Added,
+    Removed,
+    Modified,
+}
+
+impl Change {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Added => "added",
+            Self::Removed => "removed",
+            Self::Modified => "modified",
+        }
+    }
+}
+
+/// A definition that differs between the two sides.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymbolChange {
+    /// Name qualified by enclosing definitions, e.g. `Service::start`
+    pub name: String,
+    pub kind: String,
+    pub change: Change,
+    /// 1-based line on the head side, or on the base side when removed
+    pub line: usize,
+}
+
+/// One changed file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileChange {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+    pub status: FileStatus,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+    /// Decision points on each side; absent when the side is missing or
+    /// tree-sitter cannot parse the file
+    pub complexity_before: Option<usize>,
+    pub complexity_after: Option<usize>,
+    pub symbols: Vec<SymbolChange>,
+}
+
+impl FileChange {
+    pub fn complexity_delta(&self) -> i64 {
+        self.complexity_after.unwrap_or(0) as i64 - self.complexity_before.unwrap_or(0) as i64
+    }
+}
+
+/// A TODO, FIXME, HACK or XXX marker on an added line.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NewTodo {
+    pub path: String,
+    pub line: usize,
+    pub text: String,
+}
+
+/// A dependency that changed in a package manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DependencyChange {
+    pub manifest: String,
+    /// Manifest section, e.g. `dev-dependencies` or `devDependencies`
+    pub section: String,
+    pub name: String,
+    pub change: Change,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<String>,
+}
+
+/// What changed between two refs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiffReport {
+    pub range: String,
+    pub base_commit: String,
+    pub head_commit: String,
+    pub files: Vec<FileChange>,
+    pub todos: Vec<NewTodo>,
+    pub dependencies: Vec<DependencyChange>,
+}
+
+impl DiffReport {
+    /// Analyze the changes in `range` in the repository at `workspace`.
+    pub fn compute(workspace: &Path, range: &DiffRange) -> Result<Self> {
+        Self::compute_with_progress(workspace, range, &mut Progress::disabled())
+    }
+
+    /// [`DiffReport::compute`], reporting each phase to `progress`.
+    pub fn compute_with_progress(
+        workspace: &Path,
+        range: &DiffRange,
+        progress: &mut Progress,
+    ) -> Result<Self> {
+        progress.start_phase("reading git diff", None);
+        let (base, head) = range.resolve(workspace)?;
+        let statuses = changed_files(workspace, &base, &head)?;
+        let line_counts = line_counts(workspace, &base, &head)?;
+        progress.start_phase("analyzing changes", Some(statuses.len() as u64));
+
+        let mut old_analyzer = TreeSitterAnalyzer::new()?;
+        let mut new_analyzer = TreeSitterAnalyzer::new()?;
+        let mut files = Vec::new();
+        let mut dependencies = Vec::new();
+        for (path, old_path, status) in statuses {
+            let (lines_added, lines_removed) = line_counts.get(&path).copied().unwrap_or_default();
+            let before = old_path
+                .as_deref()
+                .and_then(|old| show(workspace, &base, old));
+            let after = (status != FileStatus::Deleted)
+                .then(|| show(workspace, &head, &path))
+                .flatten();
+
+            let old = before
+                .as_deref()
+                .and_then(|source| parsed(&mut old_analyzer, old_path.as_deref()?, source));
+            let new = after
+                .as_deref()
+                .and_then(|source| parsed(&mut new_analyzer, &path, source));
+            let symbols = symbol_changes(
+                old.as_ref()
+                    .map(|file| (file.outline.as_slice(), before.as_deref().unwrap_or(""))),
+                new.as_ref()
+                    .map(|file| (file.outline.as_slice(), after.as_deref().unwrap_or(""))),
+            );
+            progress.advance(after.as_deref().or(before.as_deref()).map_or(0, str::len) as u64);
+            dependencies.extend(dependency_changes(
+                &path,
+                before.as_deref(),
+                after.as_deref(),
+            ));
+            files.push(FileChange {
+                old_path: old_path.filter(|old| *old != path),
+                path,
+                status,
+                lines_added,
+                lines_removed,
+                complexity_before: old.map(|file| file.complexity),
+                complexity_after: new.map(|file| file.complexity),
+                symbols,
+            });
+        }
+
+        progress.start_phase("scanning for new TODOs", None);
+        let todos = new_todos(workspace, &base, &head)?;
+        progress.finish_phase();
+        Ok(Self {
+            range: range.label(),
+            base_commit: base,
+            head_commit: head,
+            files,
+            todos,
+            dependencies,
+        })
+    }
+
+    pub fn complexity_delta(&self) -> i64 {
+        self.files.iter().map(FileChange::complexity_delta).sum()
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let added: usize = self.files.iter().map(|file| file.lines_added).sum();
+        let removed: usize = self.files.iter().map(|file| file.lines_removed).sum();
+        let mut out = format!(
+            "# Changes in {} ({}..{})\n\n{} files changed, +{added} -{removed} lines, complexity {:+}\n",
+            self.range,
+            short(&self.base_commit),
+            short(&self.head_commit),
+            self.files.len(),
+            self.complexity_delta()
+        );
+
+        if !self.files.is_empty() {
+            out.push_str(
+                "\n## Files\n\n| File | Status | Lines | Complexity |\n|---|---|---|---|\n",
+            );
+            for file in &self.files {
+                let path = match &file.old_path {
+                    Some(old) => format!("{old} → {}", file.path),
+                    None => file.path.clone(),
+                };
+                let complexity = match (file.complexity_before, file.complexity_after) {
+                    (None, None) => "-".to_string(),
+                    (before, after) => format!(
+                        "{} → {} ({:+})",
+                        before.unwrap_or(0),
+                        after.unwrap_or(0),
+                        file.complexity_delta()
+                    ),
+                };
+                out.push_str(&format!(
+                    "| {path} | {:?} | +{} -{} | {complexity} |\n",
+                    file.status, file.lines_added, file.lines_removed
+                ));
+            }
+        }
+
+        let changed: Vec<&FileChange> = self
+            .files
+            .iter()
+            .filter(|file| !file.symbols.is_empty())
+            .collect();
+        if !changed.is_empty() {
+            out.push_str("\n## Symbols\n");
+            for file in changed {
+                out.push_str(&format!("\n{}\n", file.path));
+                for symbol in &file.symbols {
+                    out.push_str(&format!(
+                        "- {} {} `{}` (line {})\n",
+                        symbol.change.label(),
+                        symbol.kind,
+                        symbol.name,
+                        symbol.line
+                    ));
+                }
+            }
+        }
+
+        if !self.todos.is_empty() {
+            out.push_str("\n## New TODOs\n\n");
+            for todo in &self.todos {
+                out.push_str(&format!("- {}:{} {}\n", todo.path, todo.line, todo.text));
+            }
+        }
+
+        if !self.dependencies.is_empty() {
+            out.push_str("\n## Dependencies\n\n");
+            for dependency in &self.dependencies {
+                let versions = match (&dependency.before, &dependency.after) {
+                    (Some(before), Some(after)) => format!(" {before} → {after}"),
+                    (Some(version), None) | (None, Some(version)) => format!(" {version}"),
+                    (None, None) => String::new(),
+                };
+                out.push_str(&format!(
+                    "- {} [{}]: {} `{}`{versions}\n",
+                    dependency.manifest,
+                    dependency.section,
+                    dependency.change.label(),
+                    dependency.name
+                ));
+            }
+        }
+        out
+    }
+}
+
+struct ParsedFile {
+    outline: Vec<OutlineEntry>,
+    complexity: usize,
+}
+
+fn parsed(analyzer: &mut TreeSitterAnalyzer, path: &str, source: &str) -> Option<ParsedFile> {
+    if source.len() > MAX_PARSE_BYTES {
+        return None;
+    }
+    let language = analyzer.detect_language_from_path(path).ok()?;
+    let complexity = decision_points(analyzer.parse(source, language).ok()?.root_node());
+    let outline = analyzer
+        .symbol_outline(Path::new(path), source)
+        .unwrap_or_default();
+    Some(ParsedFile {
+        outline,
+        complexity,
+    })
+}
+
+/// Outline entries keyed by their qualified name, with the text of their lines.
+fn keyed<'a>(
+    outline: &'a [OutlineEntry],
+    source: &'a str,
+) -> BTreeMap<String, (&'a OutlineEntry, String)> {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut parents: Vec<&str> = Vec::new();
+    let mut keyed = BTreeMap::new();
+    for entry in outline {
+        parents.truncate(entry.depth);
+        parents.push(&entry.name);
+        let mut key = parents.join("::");
+        // Overloads and repeated definitions keep separate keys
+        let mut occurrence = 1;
+        while keyed.contains_key(&key) {
+            occurrence += 1;
+            key = format!("{}#{occurrence}", parents.join("::"));
+        }
+        let start = entry.line.saturating_sub(1).min(lines.len());
+        let end = entry.end_line.clamp(start, lines.len());
+        keyed.insert(key, (entry, lines[start..end].join("\n")));
+    }
+    keyed
+}
+
+fn symbol_changes(
+    old: Option<(&[OutlineEntry], &str)>,
+    new: Option<(&[OutlineEntry], &str)>,
+) -> Vec<SymbolChange> {
+    let old = old
+        .map(|(outline, source)| keyed(outline, source))
+        .unwrap_or_default();
+    let new = new
+        .map(|(outline, source)| keyed(outline, source))
+        .unwrap_or_default();
+    let change = |name: &str, entry: &OutlineEntry, change| SymbolChange {
+        name: name.split('#').next().unwrap_or(name).to_string(),
+        kind: entry.kind_label().to_string(),
+        change,
+        line: entry.line,
+    };
+
+    let mut changes = Vec::new();
+    for (name, (entry, text)) in &new {
+        match old.get(name) {
+            None => changes.push(change(name, entry, Change::Added)),
+            Some((_, old_text)) if old_text != text => {
+                changes.push(change(name, entry, Change::Modified))
+            }
+            Some(_) => {}
+        }
+    }
+    for (name, (entry, _)) in &old {
+        if !new.contains_key(name) {
+            changes.push(change(name, entry, Change::Removed));
+        }
+    }
+    changes.sort_by_key(|symbol| (symbol.change == Change::Removed, symbol.line));
+    changes
+}
+
+/// `(path, path on the base side, status)` of each changed file.
+fn changed_files(
+    workspace: &Path,
+    base: &str,
+    head: &str,
+) -> Result<Vec<(String, Option<String>, FileStatus)>> {
+    let output = git(
+        workspace,
+        &["diff", "--name-status", "-M", "-z", base, head],
+    )?;
+    let mut fields = output.split('\0').filter(|field| !field.is_empty());
+    let mut files = Vec::new();
+    while let Some(status) = fields.next() {
+        let Some(path) = fields.next() else { break };
+        let file = match status.chars().next() {
+            Some('A') => (path.to_string(), None, FileStatus::Added),
+            Some('D') => (
+                path.to_string(),
+                Some(path.to_string()),
+                FileStatus::Deleted,
+            ),
+            Some('R') | Some('C') => {
+                let Some(new_path) = fields.next() else { break };
+                let status = if status.starts_with('R') {
+                    FileStatus::Renamed
+                } else {
+                    FileStatus::Added
+                };
+                let old_path = (status == FileStatus::Renamed).then(|| path.to_string());
+                (new_path.to_string(), old_path, status)
+            }
+            _ => (
+                path.to_string(),
+                Some(path.to_string()),
+                FileStatus::Modified,
+            ),
+        };
+        files.push(file);
+    }
+    Ok(files)
+}
+
+/// Lines added and removed per path on the head side; binary files count 0.
+fn line_counts(
+    workspace: &Path,
+    base: &str,
+    head: &str,
+) -> Result<HashMap<String, (usize, usize)>> {
+    let output = git(workspace, &["diff", "--numstat", "-M", "-z", base, head])?;
+    let mut fields = output.split('\0');
+    let mut counts = HashMap::new();
+    while let Some(record) = fields.next() {
+        let mut parts = record.splitn(3, '\t');
+        let (Some(added), Some(removed), Some(path)) = (parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        let path = if path.is_empty() {
+            // Renames: the old and new path follow as separate fields
+            fields.next();
+            fields.next().unwrap_or_default()
+        } else {
+            path
+        };
+        counts.insert(
+            path.to_string(),
+            (added.parse().unwrap_or(0), removed.parse().unwrap_or(0)),
+        );
+    }
+    Ok(counts)
+}
+
+/// TODO markers on added lines whose text was not already in the base
+/// version of the file, so moved code does not count.
+fn new_todos(workspace: &Path, base: &str, head: &str) -> Result<Vec<NewTodo>> {
+    let diff = git(
+        workspace,
+        &[
+            "diff",
+            "-U0",
+            "--no-color",
+            "--no-ext-diff",
+            "-M",
+            base,
+            head,
+        ],
+    )?;
+    let mut old_sources: HashMap<String, Option<String>> = HashMap::new();
+    let mut todos = Vec::new();
+    for (path, old_path, line, text) in added_lines(&diff) {
+        if !TODO_PATTERN.is_match(text) {
+            continue;
+        }
+        let trimmed = text.trim();
+        if let Some(old_path) = old_path {
+            let old_source = old_sources
+                .entry(old_path.to_string())
+                .or_insert_with(|| show(workspace, base, old_path));
+            if old_source
+                .as_deref()
+                .is_some_and(|source| source.contains(trimmed))
+            {
+                continue;
+            }
+        }
+        todos.push(NewTodo {
+            path: path.to_string(),
+            line,
+            text: trimmed.to_string(),
+        });
+    }
+    Ok(todos)
+}
+
+/// `(path, base-side path, head line, text)` of each added line in a
+/// zero-context unified diff.
+fn added_lines(diff: &str) -> Vec<(&str, Option<&str>, usize, &str)> {
+    let mut lines = Vec::new();
+    let mut path = "";
+    let mut old_path = None;
+    let mut line = 0;
+    let (mut old_remaining, mut new_remaining) = (0usize, 0usize);
+    for raw in diff.lines() {
+        if old_remaining > 0 || new_remaining > 0 {
+            if let Some(text) = raw.strip_prefix('+') {
+                lines.push((path, old_path, line, text));
+                line += 1;
+                new_remaining = new_remaining.saturating_sub(1);
+            } else if raw.starts_with('-') {
+                old_remaining = old_remaining.saturating_sub(1);
+            }
+            continue;
+        }
+        if let Some(old) = raw.strip_prefix("--- ") {
+            old_path = old.strip_prefix("a/");
+        } else if let Some(new) = raw.strip_prefix("+++ ") {
+            path = new.strip_prefix("b/").unwrap_or(new);
+        } else if let Some(header) = raw.strip_prefix("@@ -") {
+            let mut ranges = header.split_whitespace();
+            old_remaining = hunk_range(ranges.next().unwrap_or("")).1;
+            let (start, count) = hunk_range(ranges.next().unwrap_or("").trim_start_matches('+'));
+            line = start;
+            new_remaining = count;
+        }
+    }
+    lines
+}
+
+/// `start,count` of a hunk header side; a missing count means one line.
+fn hunk_range(range: &str) -> (usize, usize) {
+    let (start, count) = range.split_once(',').unwrap_or((range, "1"));
+    (start.parse().unwrap_or(0), count.parse().unwrap_or(0))
+}
+
+/// Dependencies by `(section, name)` with their version requirement.
+type Dependencies = BTreeMap<(String, String), String>;
+
+fn dependency_changes(
+    path: &str,
+    before: Option<&str>,
+    after: Option<&str>,
+) -> Vec<DependencyChange> {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    let parse: fn(&str) -> Dependencies = match file_name {
+        "Cargo.toml" => cargo_dependencies,
+        "package.json" => npm_dependencies,
+        "pyproject.toml" => pyproject_dependencies,
+        "go.mod" => go_dependencies,
+        name if name.starts_with("requirements") && name.ends_with(".txt") => {
+            requirements_dependencies
+        }
+        _ => return Vec::new(),
+    };
+    let before = before.map(parse).unwrap_or_default();
+    let after = after.map(parse).unwrap_or_default();
+
+    let change = |(section, name): &(String, String),
+                  change,
+                  before: Option<&String>,
+                  after: Option<&String>| {
+        DependencyChange {
+            manifest: path.to_string(),
+            section: section.clone(),
+            name: name.clone(),
+            change,
+            before: before.cloned(),
+            after: after.cloned(),
+        }
+    };
+    let mut changes = Vec::new();
+    for (key, version) in &after {
+        match before.get(key) {
+            None => changes.push(change(key, Change::Added, None, Some(version))),
+            Some(old) if old != version => {
+                changes.push(change(key, Change::Modified, Some(old), Some(version)))
+            }
+            Some(_) => {}
+        }
+    }
+    for (key, version) in &before {
+        if !after.contains_key(key) {
+            changes.push(change(key, Change::Removed, Some(version), None));
+        }
+    }
+    changes
+}
+
+fn cargo_dependencies(source: &str) -> Dependencies {
+    let Ok(manifest) = source.parse::<toml::Value>() else {
+        return Dependencies::new();
+    };
+    let mut dependencies = Dependencies::new();
+    let sections = [
+        ("dependencies", manifest.get("dependencies")),
+        ("dev-dependencies", manifest.get("dev-dependencies")),
+        ("build-dependencies", manifest.get("build-dependencies")),
+        (
+            "workspace.dependencies",
+            manifest
+                .get("workspace")
+                .and_then(|w| w.get("dependencies")),
+        ),
+    ];
+    for (section, table) in sections {
+        let Some(table) = table.and_then(toml::Value::as_table) else {
+            continue;
+        };
+        for (name, spec) in table {
+            let version = match spec {
+                toml::Value::String(version) => version.clone(),
+                toml::Value::Table(spec) => ["version", "path", "git"]
+                    .iter()
+                    .find_map(|key| spec.get(*key).and_then(toml::Value::as_str))
+                    .map(str::to_string)
+                    .or_else(|| {
+                        spec.contains_key("workspace")
+                            .then(|| "workspace".to_string())
+                    })
+                    .unwrap_or_default(),
+                other => other.to_string(),
+            };
+            dependencies.insert((section.to_string(), name.clone()), version);
+        }
+    }
+    dependencies
+}
+
+fn npm_dependencies(source: &str) -> Dependencies {
+    let Ok(manifest) = serde_json::from_str::<serde_json::Value>(source) else {
+        return Dependencies::new();
+    };
+    let mut dependencies = Dependencies::new();
+    for section in [
+        "dependencies",
+        "devDependencies",
+        "peerDependencies",
+        "optionalDependencies",
+    ] {
+        if let Some(table) = manifest.get(section).and_then(|value| value.as_object()) {
+            for (name, version) in table {
+                let version = version
+                    .as_str()
+                    .map_or_else(|| version.to_string(), str::to_string);
+                dependencies.insert((section.to_string(), name.clone()), version);
+            }
+        }
+    }
+    dependencies
+}
+
+fn pyproject_dependencies(source: &str) -> Dependencies {
+    let Ok(manifest) = source.parse::<toml::Value>() else {
+        return Dependencies::new();
+    };
+    let mut dependencies = Dependencies::new();
+    let project = manifest.get("project");
+    let mut requirements = |section: &str, list: Option<&toml::Value>| {
+        for requirement in list
+            .and_then(toml::Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(toml::Value::as_str)
+        {
+            if let Some((name, version)) = requirement_spec(requirement) {
+                dependencies.insert((section.to_string(), name), version);
+            }
+        }
+    };
+    requirements(
+        "project.dependencies",
+        project.and_then(|p| p.get("dependencies")),
+    );
+    if let Some(groups) = project
+        .and_then(|p| p.get("optional-dependencies"))
+        .and_then(toml::Value::as_table)
+    {
+        for (group, list) in groups {
+            requirements(
+                &format!("project.optional-dependencies.{group}"),
+                Some(list),
+            );
+        }
+    }
+    let poetry = manifest.get("tool").and_then(|tool| tool.get("poetry"));
+    for section in ["dependencies", "dev-dependencies"] {
+        if let Some(table) = poetry
+            .and_then(|poetry| poetry.get(section))
+            .and_then(toml::Value::as_table)
+        {
+            for (name, spec) in table {
+                let version = spec
+                    .as_str()
+                    .map_or_else(|| spec.to_string(), str::to_string);
+                dependencies.insert((format!("tool.poetry.{section}"), name.clone()), version);
+            }
+        }
+    }
+    dependencies
+}
+
+fn requirements_dependencies(source: &str) -> Dependencies {
+    source
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('-'))
+        .filter_map(requirement_spec)
+        .map(|(name, version)| (("requirements".to_string(), name), version))
+        .collect()
+}
+
+/// Name and version specifier of a PEP 508 requirement like `requests>=2.31`.
+fn requirement_spec(requirement: &str) -> Option<(String, String)> {
+    let requirement = requirement.trim();
+    let end = requirement
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        .unwrap_or(requirement.len());
+    let name = &requirement[..end];
+    if name.is_empty() {
+        return None;
+    }
+    Some((name.to_lowercase(), requirement[end..].trim().to_string()))
+}
+
+fn go_dependencies(source: &str) -> Dependencies {
+    let mut dependencies = Dependencies::new();
+    let mut in_block = false;
+    for line in source.lines() {
+        let line = line.split("//").next().unwrap_or("").trim();
+        let spec = if in_block {
+            if line == ")" {
+                in_block = false;
+                continue;
+            }
+            line
+        } else if let Some(rest) = line.strip_prefix("require") {
+            let rest = rest.trim();
+            if rest == "(" {
+                in_block = true;
+                continue;
+            }
+            rest
+        } else {
+            continue;
+        };
+        let mut parts = spec.split_whitespace();
+        if let (Some(module), Some(version)) = (parts.next(), parts.next()) {
+            dependencies.insert(
+                ("require".to_string(), module.to_string()),
+                version.to_string(),
+            );
+        }
+    }
+    dependencies
+}
+
+fn rev_parse(workspace: &Path, rev: &str) -> Result<String> {
+    let commit = format!("{rev}^{{commit}}");
+    git(workspace, &["rev-parse", "--verify", "--quiet", &commit])
+        .with_context(|| format!("Unknown git revision `{rev}`"))
+        .map(|output| output.trim().to_string())
+}
+
+/// Contents of `path` at `rev`, if it exists there and is text.
+fn show(workspace: &Path, rev: &str, path: &str) -> Option<String> {
+    let output = Command::new("git")
+        .args(["show", &format!("{rev}:{path}")])
+        .current_dir(workspace)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
+
+fn git(workspace: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(workspace)
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn short(commit: &str) -> &str {
+    &commit[..commit.len().min(8)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn run_git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .expect("git");
+        assert!(status.status.success(), "git {args:?} failed");
+    }
+
+    #[test]
+    fn parses_ranges() {
+        let range = DiffRange::parse("main...feature").unwrap();
+        assert_eq!(
+            (range.base.as_str(), range.head.as_str()),
+            ("main", "feature")
+        );
+        assert!(range.merge_base);
+        assert_eq!(DiffRange::parse("v1.2").unwrap().label(), "v1.2..HEAD");
+        assert_eq!(DiffRange::parse("..topic").unwrap().base, "HEAD");
+        assert!(DiffRange::parse("..").is_err());
+    }
+
+    #[test]
+    fn reports_symbols_todos_and_dependencies_between_commits() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        run_git(root, &["init", "-q"]);
+        fs::write(
+            root.join("lib.rs"),
+            "fn keep() {}\n\nfn change(x: bool) {\n    if x {}\n}\n\nfn drop_me() {}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"demo\"\n\n[dependencies]\nserde = \"1.0\"\nold = \"0.1\"\n",
+        )
+        .unwrap();
+        run_git(root, &["add", "-A"]);
+        run_git(root, &["commit", "-q", "-m", "base"]);
+
+        fs::write(
+            root.join("lib.rs"),
+            "fn keep() {}\n\nfn change(x: bool) {\n    if x {}\n    // TODO: handle y\n    if !x {}\n}\n\nfn added() {}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"demo\"\n\n[dependencies]\nserde = \"1.1\"\nanyhow = { version = \"1\" }\n",
+        )
+        .unwrap();
+        run_git(root, &["commit", "-q", "-am", "head"]);
+
+        let report = DiffReport::compute(root, &DiffRange::parse("HEAD~1").unwrap()).unwrap();
+        let lib = report
+            .files
+            .iter()
+            .find(|file| file.path == "lib.rs")
+            .unwrap();
+        let symbols: Vec<(&str, Change)> = lib
+            .symbols
+            .iter()
+            .map(|symbol| (symbol.name.as_str(), symbol.change))
+            .collect();
+        assert_eq!(
+            symbols,
+            vec![
+                ("change", Change::Modified),
+                ("added", Change::Added),
+                ("drop_me", Change::Removed),
+            ]
+        );
+        assert_eq!(lib.complexity_delta(), 1);
+        assert_eq!(report.todos.len(), 1);
+        assert_eq!(
+            (report.todos[0].path.as_str(), report.todos[0].line),
+            ("lib.rs", 5)
+        );
+
+        let dependencies: Vec<(&str, Change)> = report
+            .dependencies
+            .iter()
+            .map(|dependency| (dependency.name.as_str(), dependency.change))
+            .collect();
+        assert_eq!(
+            dependencies,
+            vec![
+                ("anyhow", Change::Added),
+                ("serde", Change::Modified),
+                ("old", Change::Removed),
+            ]
+        );
+        assert!(
+            report
+                .to_markdown()
+                .contains("- modified fn `change` (line 3)")
+        );
+    }
+}
@@ -0,0 +1,411 @@
+//! Links between the agent's claims and the tool results behind them
+//!
+//! Every tool result in a session is kept as numbered [`Evidence`]. When the
+//! agent's final answer asserts something checkable ("tests pass", "the build
+//! is clean", "the function is unused"), [`EvidenceLog::link_claims`] pairs the
+//! sentence with the tool results that support it, flags claims the latest
+//! result contradicts, and reports claims made without any supporting result.
+
+use crate::config::constants::tools;
+use crate::tools::registry::command_words;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::LazyLock;
+
+/// Longest excerpt of a tool result kept for expansion.
+const MAX_EXCERPT_CHARS: usize = 4000;
+/// Trailing lines kept from terminal output.
+const MAX_EXCERPT_LINES: usize = 40;
+/// Search results linked to a single claim.
+const MAX_LINKED_SEARCHES: usize = 3;
+
+static TESTS_FAIL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\btests?\b[^.]{0,40}\b(fail|fails|failed|failing|broken)\b")
+        .expect("valid claim pattern")
+});
+static TESTS_PASS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"\b(tests?|test suite)\b[^.]{0,40}\b(pass|passes|passed|passing|green|succeed|succeeds)\b",
+    )
+    .expect("valid claim pattern")
+});
+static BUILD_CLEAN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(builds|compiles|compiled|build (succeeds|passes|is clean)|no (compiler )?(errors|warnings)|clippy is clean)\b")
+        .expect("valid claim pattern")
+});
+static NO_REFERENCES: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(unused|not used|never (used|called|referenced)|no (other )?(references|callers|usages|uses|matches)|not referenced|dead code|does(n't| not) exist)\b")
+        .expect("valid claim pattern")
+});
+
+/// What a tool result can vouch for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvidenceKind {
+    TestRun,
+    Build,
+    Search,
+    Read,
+    Command,
+    Other,
+}
+
+/// One tool result, numbered in session order.
+#[derive(Debug, Clone, Serialize)]
+pub struct Evidence {
+    pub id: usize,
+    pub turn: usize,
+    pub tool: String,
+    pub kind: EvidenceKind,
+    /// Command, pattern or path the tool ran on
+    pub summary: String,
+    pub ok: bool,
+    /// Output shown when the reference is expanded
+    #[serde(skip)]
+    pub excerpt: String,
+}
+
+impl Evidence {
+    /// Short reference such as `run_terminal_cmd \`cargo test\` (failed)`.
+    pub fn label(&self) -> String {
+        let status = match (self.kind, self.ok) {
+            (EvidenceKind::Search, _) | (EvidenceKind::Read, _) => String::new(),
+            (_, true) => " (succeeded)".to_string(),
+            (_, false) => " (failed)".to_string(),
+        };
+        if self.summary.is_empty() {
+            format!("{}{}", self.tool, status)
+        } else {
+            format!("{} `{}`{}", self.tool, self.summary, status)
+        }
+    }
+}
+
+/// A checkable assertion in the agent's answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaimKind {
+    TestsPass,
+    TestsFail,
+    BuildClean,
+    NoReferences,
+}
+
+/// How well the session's tool results back a claim.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaimStatus {
+    Supported,
+    Contradicted,
+    Unsupported,
+}
+
+/// A claim and the evidence it was linked to.
+#[derive(Debug, Clone, Serialize)]
+pub struct ClaimLink {
+    /// Sentence the claim was found in
+    pub claim: String,
+    pub kind: ClaimKind,
+    pub status: ClaimStatus,
+    pub evidence: Vec<usize>,
+}
+
+/// Tool results recorded during a session.
+#[derive(Debug, Default)]
+pub struct EvidenceLog {
+    entries: Vec<Evidence>,
+    turn: usize,
+}
+
+impl EvidenceLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a new user turn; later results are recorded against it.
+    pub fn begin_turn(&mut self) -> usize {
+        self.turn += 1;
+        self.turn
+    }
+
+    /// Record a tool result and return its reference number.
+    pub fn record(&mut self, tool: &str, args: &Value, output: &Value, ok: bool) -> usize {
+        let (kind, summary) = classify(tool, args);
+        let id = self.entries.len() + 1;
+        self.entries.push(Evidence {
+            id,
+            turn: self.turn,
+            tool: tool.to_string(),
+            kind,
+            summary,
+            ok,
+            excerpt: excerpt(output),
+        });
+        id
+    }
+
+    /// Number of the current turn.
+    pub fn turn(&self) -> usize {
+        self.turn
+    }
+
+    pub fn get(&self, id: usize) -> Option<&Evidence> {
+        id.checked_sub(1).and_then(|index| self.entries.get(index))
+    }
+
+    pub fn entries(&self) -> &[Evidence] {
+        &self.entries
+    }
+
+    /// Link the checkable claims in `answer` to the results recorded so far.
+    /// Search results only count when they come from the current turn; test
+    /// and build runs from earlier turns still count while no newer run exists.
+    pub fn link_claims(&self, answer: &str) -> Vec<ClaimLink> {
+        let mut links: Vec<ClaimLink> = Vec::new();
+        for sentence in sentences(answer) {
+            let lowered = sentence.to_lowercase();
+            let Some(kind) = claim_kind(&lowered) else {
+                continue;
+            };
+            if links.iter().any(|link| link.kind == kind) {
+                continue;
+            }
+            let (status, evidence) = match kind {
+                ClaimKind::TestsPass | ClaimKind::TestsFail => {
+                    let latest = self.latest(&[EvidenceKind::TestRun]);
+                    let expects_pass = kind == ClaimKind::TestsPass;
+                    judge(latest, |run| run.ok == expects_pass)
+                }
+                ClaimKind::BuildClean => {
+                    let latest = self.latest(&[EvidenceKind::Build, EvidenceKind::TestRun]);
+                    judge(latest, |run| run.ok)
+                }
+                ClaimKind::NoReferences => {
+                    let searches: Vec<usize> = self
+                        .entries
+                        .iter()
+                        .rev()
+                        .filter(|entry| {
+                            entry.turn == self.turn && entry.kind == EvidenceKind::Search
+                        })
+                        .take(MAX_LINKED_SEARCHES)
+                        .map(|entry| entry.id)
+                        .collect();
+                    if searches.is_empty() {
+                        (ClaimStatus::Unsupported, searches)
+                    } else {
+                        (ClaimStatus::Supported, searches)
+                    }
+                }
+            };
+            links.push(ClaimLink {
+                claim: sentence,
+                kind,
+                status,
+                evidence,
+            });
+        }
+        links
+    }
+
+    fn latest(&self, kinds: &[EvidenceKind]) -> Option<&Evidence> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|entry| kinds.contains(&entry.kind))
+    }
+}
+
+fn judge(
+    latest: Option<&Evidence>,
+    agrees: impl Fn(&Evidence) -> bool,
+) -> (ClaimStatus, Vec<usize>) {
+    match latest {
+        Some(run) if agrees(run) => (ClaimStatus::Supported, vec![run.id]),
+        Some(run) => (ClaimStatus::Contradicted, vec![run.id]),
+        None => (ClaimStatus::Unsupported, Vec::new()),
+    }
+}
+
+fn claim_kind(sentence: &str) -> Option<ClaimKind> {
+    if TESTS_FAIL.is_match(sentence) {
+        Some(ClaimKind::TestsFail)
+    } else if TESTS_PASS.is_match(sentence) {
+        Some(ClaimKind::TestsPass)
+    } else if BUILD_CLEAN.is_match(sentence) {
+        Some(ClaimKind::BuildClean)
+    } else if NO_REFERENCES.is_match(sentence) {
+        Some(ClaimKind::NoReferences)
+    } else {
+        None
+    }
+}
+
+/// Sentences of prose, skipping fenced code blocks and list markers.
+fn sentences(text: &str) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut in_fence = false;
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        let trimmed = trimmed.trim_start_matches(['-', '*', '+', '>', ' ']);
+        for sentence in trimmed.split_inclusive(['.', '!', '?']) {
+            let sentence = sentence.trim();
+            if sentence.chars().any(char::is_alphabetic) {
+                result.push(sentence.to_string());
+            }
+        }
+    }
+    result
+}
+
+fn classify(tool: &str, args: &Value) -> (EvidenceKind, String) {
+    let string_arg = |key: &str| {
+        args.get(key)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    };
+    match tool {
+        tools::RUN_TERMINAL_CMD | tools::BASH => {
+            let mut words = command_words(args);
+            if words.is_empty() {
+                words = string_arg("bash_command")
+                    .split_whitespace()
+                    .map(str::to_string)
+                    .collect();
+            }
+            let command = words.join(" ");
+            (command_kind(&words), command)
+        }
+        tools::COVERAGE_REPORT => (EvidenceKind::TestRun, String::new()),
+        tools::GREP_SEARCH | tools::SIMPLE_SEARCH | tools::AST_GREP_SEARCH | tools::SRGN => {
+            (EvidenceKind::Search, string_arg("pattern"))
+        }
+        tools::READ_FILE | tools::LIST_FILES => (EvidenceKind::Read, string_arg("path")),
+        _ => (EvidenceKind::Other, String::new()),
+    }
+}
+
+fn command_kind(words: &[String]) -> EvidenceKind {
+    let program = words
+        .first()
+        .map(|word| word.rsplit('/').next().unwrap_or(word))
+        .unwrap_or_default();
+    let subcommand = words.get(1).map(String::as_str).unwrap_or_default();
+    let script = words.get(2).map(String::as_str).unwrap_or_default();
+    match (program, subcommand) {
+        ("cargo", "test" | "nextest") | ("go", "test") | ("pytest" | "jest" | "vitest", _) => {
+            EvidenceKind::TestRun
+        }
+        ("npm" | "pnpm" | "yarn" | "bun", "test") | ("make", "test" | "check") => {
+            EvidenceKind::TestRun
+        }
+        ("npm" | "pnpm" | "yarn" | "bun", "run") if script.starts_with("test") => {
+            EvidenceKind::TestRun
+        }
+        ("python" | "python3", "-m") if script == "pytest" => EvidenceKind::TestRun,
+        ("cargo", "build" | "check" | "clippy") | ("go", "build" | "vet") | ("tsc", _) => {
+            EvidenceKind::Build
+        }
+        ("npm" | "pnpm" | "yarn" | "bun", "run") if script.starts_with("build") => {
+            EvidenceKind::Build
+        }
+        ("make", _) => EvidenceKind::Build,
+        ("rg" | "grep", _) | ("git", "grep") => EvidenceKind::Search,
+        _ => EvidenceKind::Command,
+    }
+}
+
+/// Output kept for expansion: the tail of terminal output, or the result JSON.
+fn excerpt(output: &Value) -> String {
+    let stream = |key: &str| output.get(key).and_then(Value::as_str).unwrap_or_default();
+    let text = if output.get("stdout").is_some() || output.get("stderr").is_some() {
+        let combined = format!("{}\n{}", stream("stdout"), stream("stderr"));
+        let lines: Vec<&str> = combined.trim().lines().collect();
+        lines[lines.len().saturating_sub(MAX_EXCERPT_LINES)..].join("\n")
+    } else {
+        serde_json::to_string_pretty(output).unwrap_or_default()
+    };
+    if text.chars().count() > MAX_EXCERPT_CHARS {
+        let cut: String = text.chars().take(MAX_EXCERPT_CHARS).collect();
+        format!("{}\n…", cut)
+    } else {
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn links_test_claims_to_the_latest_run() {
+        let mut log = EvidenceLog::new();
+        log.begin_turn();
+        let failing = json!({ "exit_code": 101, "stdout": "test result: FAILED" });
+        let passing = json!({ "exit_code": 0, "stdout": "test result: ok" });
+        let cargo_test = json!({ "command": ["cargo", "test"] });
+        log.record(tools::RUN_TERMINAL_CMD, &cargo_test, &failing, false);
+        log.record(
+            tools::READ_FILE,
+            &json!({ "path": "src/lib.rs" }),
+            &json!({}),
+            true,
+        );
+
+        let links = log.link_claims("I fixed the parser. All tests pass now.");
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].claim, "All tests pass now.");
+        assert_eq!(links[0].kind, ClaimKind::TestsPass);
+        assert_eq!(links[0].status, ClaimStatus::Contradicted);
+        assert_eq!(links[0].evidence, vec![1]);
+
+        log.begin_turn();
+        let scoped = json!({ "command": "cargo test -p core" });
+        let id = log.record(tools::RUN_TERMINAL_CMD, &scoped, &passing, true);
+        let links = log.link_claims("- The tests are passing and the build is clean.");
+        assert_eq!(links[0].status, ClaimStatus::Supported);
+        assert_eq!(links[0].evidence, vec![id]);
+        let evidence = log.get(id).unwrap();
+        assert_eq!(
+            evidence.label(),
+            "run_terminal_cmd `cargo test -p core` (succeeded)"
+        );
+        assert_eq!(evidence.excerpt, "test result: ok");
+    }
+
+    #[test]
+    fn absence_claims_need_a_search_from_the_same_turn() {
+        let mut log = EvidenceLog::new();
+        log.begin_turn();
+        let pattern = json!({ "pattern": "old_helper" });
+        log.record(
+            tools::GREP_SEARCH,
+            &pattern,
+            &json!({ "matches": [] }),
+            true,
+        );
+
+        let answer = "`old_helper` is unused, so I removed it.\n```rust\n// tests pass\n```";
+        let links = log.link_claims(answer);
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].kind, ClaimKind::NoReferences);
+        assert_eq!(links[0].status, ClaimStatus::Supported);
+        assert_eq!(links[0].evidence, vec![1]);
+
+        log.begin_turn();
+        let links = log.link_claims("The function is never called.");
+        assert_eq!(links[0].status, ClaimStatus::Unsupported);
+        assert!(links[0].evidence.is_empty());
+        assert!(log.link_claims("Renamed the module.").is_empty());
+    }
+}
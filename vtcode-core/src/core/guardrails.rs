@@ -0,0 +1,532 @@
+//! Static guardrails for protected paths and destructive commands
+//!
+//! Tool policies decide whether a tool may run at all; guardrails look at what
+//! a call would do. Before a tool runs, [`Guardrails::check`] matches the files
+//! it would write against `[security.guardrails] protected_paths` and the
+//! terminal command it would run against `destructive_commands`. Files the
+//! first-run scan flagged as secrets (see [`crate::core::sensitive_files`]) are
+//! also off limits to tools that read them until the user allows them. A match
+//! blocks the call unless the user overrides it by typing [`OVERRIDE_PHRASE`].
+//! Commands matching `backup_commands` are not blocked; [`Guardrails::backup_rule`]
+//! tells the chat loop to back up the files they may change before they run.
+
+use crate::config::constants::tools;
+use crate::config::core::GuardrailsConfig;
+use anyhow::{Context, Result};
+use glob::{MatchOptions, Pattern};
+use regex::Regex;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+
+/// What the user has to type to run a blocked call.
+pub const OVERRIDE_PHRASE: &str = "override";
+
+const PATH_MATCH: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Which guardrail a call tripped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GuardrailKind {
+    ProtectedPath,
+    DestructiveCommand,
+    SensitivePath,
+}
+
+/// A blocked tool call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuardrailViolation {
+    pub kind: GuardrailKind,
+    /// The configured pattern that matched
+    pub rule: String,
+    /// The path or command that matched it
+    pub target: String,
+}
+
+impl fmt::Display for GuardrailViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            GuardrailKind::ProtectedPath => write!(
+                f,
+                "{} is a protected path (matches `{}`)",
+                self.target, self.rule
+            ),
+            GuardrailKind::DestructiveCommand => write!(
+                f,
+                "`{}` matches the destructive command pattern `{}`",
+                self.target, self.rule
+            ),
+            GuardrailKind::SensitivePath => write!(
+                f,
+                "{} may contain secrets and has not been allowed in this workspace",
+                self.target
+            ),
+        }
+    }
+}
+
+/// Compiled guardrail rules for a workspace.
+pub struct Guardrails {
+    workspace: PathBuf,
+    protected_paths: Vec<Pattern>,
+    destructive_commands: Vec<Regex>,
+    backup_commands: Vec<Regex>,
+    /// Workspace-relative paths no tool may read or write
+    read_denied: BTreeSet<String>,
+    allow_override: bool,
+}
+
+impl Guardrails {
+    pub fn new(config: &GuardrailsConfig, workspace: &Path) -> Result<Self> {
+        if !config.enabled {
+            return Ok(Self::disabled(workspace));
+        }
+        let protected_paths = config
+            .protected_paths
+            .iter()
+            .map(|pattern| {
+                Pattern::new(pattern)
+                    .with_context(|| format!("Invalid protected path pattern `{pattern}`"))
+            })
+            .collect::<Result<_>>()?;
+        let destructive_commands = config
+            .destructive_commands
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern)
+                    .with_context(|| format!("Invalid destructive command pattern `{pattern}`"))
+            })
+            .collect::<Result<_>>()?;
+        let backup_commands = config
+            .backup_commands
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern)
+                    .with_context(|| format!("Invalid backup command pattern `{pattern}`"))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            workspace: workspace.to_path_buf(),
+            protected_paths,
+            destructive_commands,
+            backup_commands,
+            read_denied: BTreeSet::new(),
+            allow_override: config.allow_override,
+        })
+    }
+
+    pub fn disabled(workspace: &Path) -> Self {
+        Self {
+            workspace: workspace.to_path_buf(),
+            protected_paths: Vec::new(),
+            destructive_commands: Vec::new(),
+            backup_commands: Vec::new(),
+            read_denied: BTreeSet::new(),
+            allow_override: false,
+        }
+    }
+
+    pub fn allows_override(&self) -> bool {
+        self.allow_override
+    }
+
+    /// Block every tool from touching `paths` (workspace-relative).
+    pub fn deny_reads<'a>(&mut self, paths: impl IntoIterator<Item = &'a str>) {
+        let paths: Vec<String> = paths
+            .into_iter()
+            .map(|path| self.relative_path(path))
+            .collect();
+        self.read_denied.extend(paths);
+    }
+
+    /// Lift the block on `path`; returns whether it was denied.
+    pub fn allow_read(&mut self, path: &str) -> bool {
+        self.read_denied.remove(&self.relative_path(path))
+    }
+
+    /// The first guardrail the call would trip, if any.
+    pub fn check(&self, tool: &str, args: &Value) -> Option<GuardrailViolation> {
+        let written = written_paths(tool, args);
+        for path in &written {
+            if let Some(violation) = self.check_path(path) {
+                return Some(violation);
+            }
+        }
+        let command = command_line(tool, args);
+        if !self.read_denied.is_empty() {
+            let command_words = command
+                .iter()
+                .flat_map(|command| command.split_whitespace())
+                .map(|word| word.trim_matches(|ch| matches!(ch, '"' | '\'' | ';' | '<' | '>')))
+                .map(str::to_string);
+            let touched = written
+                .into_iter()
+                .chain(read_paths(tool, args))
+                .chain(command_words);
+            for path in touched {
+                let relative = self.relative_path(&path);
+                if self.read_denied.contains(&relative) {
+                    return Some(GuardrailViolation {
+                        kind: GuardrailKind::SensitivePath,
+                        rule: relative.clone(),
+                        target: relative,
+                    });
+                }
+            }
+            // srgn takes a glob that may select sensitive files without naming them
+            if tool == tools::SRGN
+                && let Some(glob) = args.get("path").and_then(Value::as_str)
+                && let Ok(pattern) = Pattern::new(&self.relative_path(glob))
+                && let Some(path) = self.read_denied.iter().find(|path| pattern.matches(path))
+            {
+                return Some(GuardrailViolation {
+                    kind: GuardrailKind::SensitivePath,
+                    rule: pattern.as_str().to_string(),
+                    target: path.clone(),
+                });
+            }
+        }
+        let command = command?;
+        self.destructive_commands
+            .iter()
+            .find(|pattern| pattern.is_match(&command))
+            .map(|pattern| GuardrailViolation {
+                kind: GuardrailKind::DestructiveCommand,
+                rule: pattern.as_str().to_string(),
+                target: command.clone(),
+            })
+    }
+
+    /// The command line of a call that should be backed up before it runs.
+    pub fn backup_rule(&self, tool: &str, args: &Value) -> Option<String> {
+        let command = command_line(tool, args)?;
+        self.backup_commands
+            .iter()
+            .any(|pattern| pattern.is_match(&command))
+            .then_some(command)
+    }
+
+    fn check_path(&self, path: &str) -> Option<GuardrailViolation> {
+        let relative = self.relative_path(path);
+        let name = Path::new(&relative).file_name()?.to_str()?.to_string();
+        self.protected_paths
+            .iter()
+            .find(|pattern| {
+                if pattern.as_str().contains('/') {
+                    pattern.matches_with(&relative, PATH_MATCH)
+                } else {
+                    pattern.matches_with(&name, PATH_MATCH)
+                }
+            })
+            .map(|pattern| GuardrailViolation {
+                kind: GuardrailKind::ProtectedPath,
+                rule: pattern.as_str().to_string(),
+                target: relative,
+            })
+    }
+
+    /// Workspace-relative, `/`-separated form of `path` with `.` and `..`
+    /// resolved lexically.
+    fn relative_path(&self, path: &str) -> String {
+        let path = Path::new(path.trim());
+        let path = path.strip_prefix(&self.workspace).unwrap_or(path);
+        let mut parts: Vec<String> = Vec::new();
+        for component in path.components() {
+            match component {
+                Component::Normal(part) => parts.push(part.to_string_lossy().into_owned()),
+                Component::ParentDir => {
+                    parts.pop();
+                }
+                _ => {}
+            }
+        }
+        parts.join("/")
+    }
+}
+
+/// Files a call would create, modify or delete.
+fn written_paths(tool: &str, args: &Value) -> Vec<String> {
+    let arg = |key: &str| args.get(key).and_then(Value::as_str).map(str::to_string);
+    match tool {
+        tools::WRITE_FILE
+        | tools::CREATE_FILE
+        | tools::EDIT_FILE
+        | tools::DELETE_FILE
+        | tools::SRGN
+        | tools::ORGANIZE_IMPORTS
+        | tools::CONFIG_EDIT => arg("path").into_iter().collect(),
+        tools::APPLY_PATCH => arg("input")
+            .map(|patch| {
+                patch
+                    .lines()
+                    .filter_map(|line| {
+                        [
+                            "*** Add File: ",
+                            "*** Update File: ",
+                            "*** Delete File: ",
+                            "*** Move to: ",
+                        ]
+                        .iter()
+                        .find_map(|prefix| line.strip_prefix(prefix))
+                        .map(|path| path.trim().to_string())
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        tools::BASH => match args.get("bash_command").and_then(Value::as_str) {
+            Some("rm" | "mkdir") => arg("path").into_iter().collect(),
+            Some("cp") => arg("dest").into_iter().collect(),
+            Some("mv") => arg("source").into_iter().chain(arg("dest")).collect(),
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// Files a call would read.
+pub(crate) fn read_paths(tool: &str, args: &Value) -> Vec<String> {
+    match tool {
+        tools::READ_FILE
+        | tools::FILE_METADATA
+        | tools::GREP_SEARCH
+        | tools::AST_GREP_SEARCH
+        | tools::TREE_SITTER_ANALYZE
+        | tools::SRGN => args
+            .get("path")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .into_iter()
+            .collect(),
+        tools::SIMPLE_SEARCH => ["path", "file_path"]
+            .iter()
+            .filter_map(|key| args.get(*key).and_then(Value::as_str))
+            .map(str::to_string)
+            .collect(),
+        tools::BASH => ["path", "source"]
+            .iter()
+            .filter_map(|key| args.get(*key).and_then(Value::as_str))
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The shell command line a call would run.
+pub(crate) fn command_line(tool: &str, args: &Value) -> Option<String> {
+    let strings = |value: Option<&Value>| -> Vec<String> {
+        value
+            .and_then(Value::as_array)
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    match tool {
+        tools::RUN_TERMINAL_CMD => match args.get("command")? {
+            Value::String(command) => Some(command.clone()),
+            value => Some(strings(Some(value)).join(" ")),
+        },
+        tools::BASH => {
+            let program = args.get("bash_command").and_then(Value::as_str)?;
+            let mut parts = vec![program.to_string()];
+            if program == "run" {
+                parts = vec![args.get("command").and_then(Value::as_str)?.to_string()];
+                parts.extend(strings(args.get("args")));
+            } else {
+                if args.get("recursive").and_then(Value::as_bool) == Some(true) {
+                    parts.push("-r".to_string());
+                }
+                if args.get("force").and_then(Value::as_bool) == Some(true) {
+                    parts.push("-f".to_string());
+                }
+                for key in ["path", "source", "dest"] {
+                    if let Some(value) = args.get(key).and_then(Value::as_str) {
+                        parts.push(value.to_string());
+                    }
+                }
+            }
+            Some(parts.join(" "))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn guardrails() -> Guardrails {
+        Guardrails::new(&GuardrailsConfig::default(), Path::new("/work")).expect("defaults")
+    }
+
+    #[test]
+    fn blocks_edits_to_protected_paths() {
+        let rails = guardrails();
+        let lock = rails
+            .check(
+                tools::EDIT_FILE,
+                &json!({"path": "./crates/app/Cargo.lock"}),
+            )
+            .expect("lock file");
+        assert_eq!(lock.kind, GuardrailKind::ProtectedPath);
+        assert_eq!(lock.target, "crates/app/Cargo.lock");
+
+        let patch = "*** Begin Patch\n*** Update File: src/lib.rs\n*** Add File: .github/workflows/ci.yml\n*** End Patch";
+        let workflow = rails
+            .check(tools::APPLY_PATCH, &json!({"input": patch}))
+            .expect("workflow");
+        assert_eq!(workflow.rule, ".github/workflows/**");
+        assert!(
+            rails
+                .check(
+                    tools::WRITE_FILE,
+                    &json!({"path": "/work/secrets/../src/main.rs"})
+                )
+                .is_none()
+        );
+        assert!(
+            rails
+                .check(
+                    tools::WRITE_FILE,
+                    &json!({"path": "/work/secrets/prod.key"})
+                )
+                .is_some()
+        );
+        assert!(
+            rails
+                .check(tools::READ_FILE, &json!({"path": ".env"}))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn refuses_destructive_commands() {
+        let rails = guardrails();
+        for command in [
+            json!(["rm", "-rf", "/"]),
+            json!(["bash", "-lc", "rm -r -f ~/"]),
+            json!(["git", "push", "--force", "origin", "main"]),
+            json!(["git", "push", "origin", "master", "-f"]),
+            json!(["git", "push", "origin", "+main"]),
+            json!("dd if=/dev/zero of=/dev/sda"),
+        ] {
+            assert!(
+                rails
+                    .check(tools::RUN_TERMINAL_CMD, &json!({"command": command}))
+                    .is_some(),
+                "{command} should be refused"
+            );
+        }
+        for command in [
+            json!(["rm", "-rf", "target/"]),
+            json!(["git", "push", "--force", "origin", "feature/maintenance"]),
+            json!(["git", "push", "origin", "main"]),
+        ] {
+            assert!(
+                rails
+                    .check(tools::RUN_TERMINAL_CMD, &json!({"command": command}))
+                    .is_none(),
+                "{command} should be allowed"
+            );
+        }
+        let bash = rails
+            .check(
+                tools::BASH,
+                &json!({"bash_command": "rm", "path": "/", "recursive": true, "force": true}),
+            )
+            .expect("bash rm");
+        assert_eq!(bash.target, "rm -r -f /");
+    }
+
+    #[test]
+    fn backs_up_commands_that_may_lose_work() {
+        let rails = guardrails();
+        for command in [
+            json!(["git", "reset", "--hard", "HEAD~1"]),
+            json!("git clean -fd"),
+            json!(["git", "checkout", "--", "src/lib.rs"]),
+            json!("rm -rf target/ build/"),
+            json!("find . -name '*.orig' -delete"),
+        ] {
+            assert!(
+                rails
+                    .backup_rule(tools::RUN_TERMINAL_CMD, &json!({"command": command}))
+                    .is_some(),
+                "{command} should be backed up"
+            );
+        }
+        for command in [
+            json!("git reset HEAD src/lib.rs"),
+            json!("git checkout -b feature"),
+            json!("rm notes.txt"),
+        ] {
+            assert!(
+                rails
+                    .backup_rule(tools::RUN_TERMINAL_CMD, &json!({"command": command}))
+                    .is_none(),
+                "{command} needs no backup"
+            );
+        }
+    }
+
+    #[test]
+    fn blocks_reads_of_sensitive_paths_until_allowed() {
+        let mut rails = guardrails();
+        rails.deny_reads([".env", "config/credentials.json"]);
+        let read = rails
+            .check(
+                tools::READ_FILE,
+                &json!({"path": "/work/config/credentials.json"}),
+            )
+            .expect("credentials");
+        assert_eq!(read.kind, GuardrailKind::SensitivePath);
+        assert_eq!(read.target, "config/credentials.json");
+        let cat = rails
+            .check(
+                tools::RUN_TERMINAL_CMD,
+                &json!({"command": ["bash", "-lc", "cat ./.env | head"]}),
+            )
+            .expect("cat");
+        assert_eq!(cat.target, ".env");
+        assert!(
+            rails
+                .check(tools::READ_FILE, &json!({"path": ".env.example"}))
+                .is_none()
+        );
+        let head = rails
+            .check(
+                tools::SIMPLE_SEARCH,
+                &json!({"command": "head", "file_path": ".env"}),
+            )
+            .expect("head");
+        assert_eq!(head.target, ".env");
+        let srgn = rails
+            .check(tools::SRGN, &json!({"path": "./**/*.json"}))
+            .expect("srgn glob");
+        assert_eq!(srgn.target, "config/credentials.json");
+        assert!(
+            rails
+                .check(tools::SRGN, &json!({"path": "src/**/*.rs"}))
+                .is_none()
+        );
+
+        assert!(rails.allow_read("/work/config/credentials.json"));
+        assert!(
+            rails
+                .check(
+                    tools::READ_FILE,
+                    &json!({"path": "config/credentials.json"})
+                )
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,229 @@
+//! Handoff documents for picking a session up elsewhere
+//!
+//! `/handoff` asks the model to condense the conversation into the current
+//! goal, the state of the work, open questions and next steps, adds the files
+//! touched from the [`ChangeLedger`], and writes the result to
+//! `.vtcode/handoff.md`. The file starts with a small front matter block
+//! naming the schema, and its sections always appear in the same order under
+//! the same headings, so another developer or another agent tool can read it
+//! without knowing anything about vtcode.
+
+use crate::core::change_ledger::{ChangeLedger, FileSummary};
+use crate::core::history_summary::transcript_text;
+use crate::llm::provider::{LLMProvider, LLMRequest, Message};
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::Utc;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Workspace-relative location of the handoff document.
+pub const HANDOFF_FILE: &str = ".vtcode/handoff.md";
+/// Identifies the layout of the document; bump when sections change.
+pub const HANDOFF_SCHEMA: &str = "vtcode-handoff/1";
+
+const MAX_OUTPUT_TOKENS: u32 = 2_000;
+
+/// Sections written by the model.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct HandoffNotes {
+    #[serde(default)]
+    pub goal: String,
+    #[serde(default)]
+    pub state: String,
+    #[serde(default)]
+    pub open_questions: Vec<String>,
+    #[serde(default)]
+    pub next_steps: Vec<String>,
+}
+
+impl HandoffNotes {
+    /// Parse the model's reply, tolerating a fenced block or text around the object.
+    pub fn parse(reply: &str) -> Result<Self> {
+        let start = reply.find('{');
+        let end = reply.rfind('}');
+        let json = match (start, end) {
+            (Some(start), Some(end)) if start < end => &reply[start..=end],
+            _ => bail!("the reply has no JSON object"),
+        };
+        let notes: Self =
+            serde_json::from_str(json).context("the reply is not a handoff object")?;
+        if notes.goal.trim().is_empty() {
+            bail!("the reply has no goal");
+        }
+        Ok(notes)
+    }
+}
+
+/// Everything another developer or agent needs to continue the session.
+#[derive(Debug, Clone)]
+pub struct Handoff {
+    pub session_id: String,
+    /// UTC time the document was written, RFC 3339
+    pub created_at: String,
+    /// Model that wrote the notes
+    pub model: String,
+    pub notes: HandoffNotes,
+    pub files: Vec<FileSummary>,
+}
+
+impl Handoff {
+    /// Ask `model` to describe the session from `history`, continuing
+    /// `summary` of any messages already trimmed from it.
+    pub async fn generate(
+        session_id: impl Into<String>,
+        history: &[Message],
+        summary: Option<&str>,
+        changes: &ChangeLedger,
+        provider: &dyn LLMProvider,
+        model: &str,
+    ) -> Result<Self> {
+        let response = provider
+            .generate(request(model, history, summary, changes))
+            .await
+            .map_err(|err| anyhow!("{model}: {err}"))?;
+        let notes = HandoffNotes::parse(response.content.as_deref().unwrap_or_default())
+            .with_context(|| format!("{model} did not return a usable handoff"))?;
+        Ok(Self {
+            session_id: session_id.into(),
+            created_at: Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            model: model.to_string(),
+            notes,
+            files: changes.files(),
+        })
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!(
+            "---\nschema: {HANDOFF_SCHEMA}\nsession: {}\ncreated: {}\nmodel: {}\n---\n\n# Handoff\n\n",
+            self.session_id, self.created_at, self.model
+        );
+        out.push_str("## Goal\n\n");
+        out.push_str(self.notes.goal.trim());
+        out.push_str("\n\n## State of work\n\n");
+        out.push_str(non_empty(&self.notes.state, "Not started."));
+        out.push_str("\n\n## Files touched\n\n");
+        if self.files.is_empty() {
+            out.push_str("- None\n");
+        }
+        for file in &self.files {
+            out.push_str(&format!(
+                "- `{}` ({})\n",
+                file.path,
+                file.kind.label().to_lowercase()
+            ));
+        }
+        out.push_str("\n## Open questions\n\n");
+        push_list(&mut out, &self.notes.open_questions);
+        out.push_str("\n## Next steps\n\n");
+        for (index, step) in self.notes.next_steps.iter().enumerate() {
+            out.push_str(&format!("{}. {}\n", index + 1, step.trim()));
+        }
+        if self.notes.next_steps.is_empty() {
+            out.push_str("- None\n");
+        }
+        out
+    }
+
+    /// Write the document to [`HANDOFF_FILE`], replacing any earlier one.
+    pub fn write(&self, workspace: &Path) -> Result<PathBuf> {
+        let path = workspace.join(HANDOFF_FILE);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        fs::write(&path, self.to_markdown())
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+fn request(
+    model: &str,
+    history: &[Message],
+    summary: Option<&str>,
+    changes: &ChangeLedger,
+) -> LLMRequest {
+    let instructions = "You are handing a coding session over to someone who has not seen it, either a developer or another coding agent. From the transcript, reply with only a JSON object with these fields: \"goal\" (what the user is ultimately trying to achieve, one or two sentences), \"state\" (what has been done, what works and what is broken or half-finished, as a short paragraph), \"open_questions\" (decisions or unknowns still waiting on the user, as strings) and \"next_steps\" (concrete actions in the order to take them, as strings). Keep identifiers, paths, commands and error messages exact. Leave a list empty rather than inventing entries.";
+    let mut transcript = String::new();
+    if let Some(summary) = summary {
+        transcript.push_str("Summary of earlier messages:\n");
+        transcript.push_str(summary);
+        transcript.push_str("\n\n");
+    }
+    let files = changes.files();
+    if !files.is_empty() {
+        transcript.push_str("Files changed this session:\n");
+        for file in &files {
+            transcript.push_str(&format!("- {} {}\n", file.kind.label(), file.path));
+        }
+        transcript.push('\n');
+    }
+    transcript.push_str("Transcript:\n");
+    transcript.push_str(&transcript_text(history));
+
+    LLMRequest {
+        messages: vec![Message::user(transcript)],
+        system_prompt: Some(instructions.to_string()),
+        tools: None,
+        model: model.to_string(),
+        max_tokens: Some(MAX_OUTPUT_TOKENS),
+        temperature: Some(0.2),
+        stream: false,
+        tool_choice: None,
+        parallel_tool_calls: None,
+        parallel_tool_config: None,
+        reasoning_effort: None,
+        json_mode: true,
+        response_schema: None,
+        code_execution: false,
+        seed: None,
+    }
+}
+
+fn non_empty<'a>(text: &'a str, fallback: &'a str) -> &'a str {
+    let text = text.trim();
+    if text.is_empty() { fallback } else { text }
+}
+
+fn push_list(out: &mut String, items: &[String]) {
+    if items.is_empty() {
+        out.push_str("- None\n");
+    }
+    for item in items {
+        out.push_str(&format!("- {}\n", item.trim()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::change_ledger::ChangeKind;
+
+    #[test]
+    fn parses_fenced_reply_and_renders_every_section() {
+        let reply = "```json\n{\"goal\":\"Add retries to the fetch client\",\"state\":\"Backoff is in place; tests for timeouts fail.\",\"open_questions\":[\"Should 429 be retried?\"],\"next_steps\":[\"Fix the timeout test\",\"Run cargo test\"]}\n```";
+        let notes = HandoffNotes::parse(reply).expect("notes");
+        assert_eq!(notes.open_questions, vec!["Should 429 be retried?"]);
+
+        let handoff = Handoff {
+            session_id: "session-1".to_string(),
+            created_at: "2026-01-02T03:04:05Z".to_string(),
+            model: "test-model".to_string(),
+            notes,
+            files: vec![FileSummary {
+                path: "src/fetch.rs".to_string(),
+                kind: ChangeKind::Modified,
+                tools: vec!["edit_file".to_string()],
+                requests: vec![0],
+            }],
+        };
+        let markdown = handoff.to_markdown();
+        assert!(markdown.starts_with("---\nschema: vtcode-handoff/1\nsession: session-1\n"));
+        assert!(markdown.contains("## Files touched\n\n- `src/fetch.rs` (updated)\n"));
+        assert!(markdown.contains("## Next steps\n\n1. Fix the timeout test\n2. Run cargo test\n"));
+
+        assert!(HandoffNotes::parse("no object here").is_err());
+        assert!(HandoffNotes::parse("{\"state\":\"done\"}").is_err());
+    }
+}
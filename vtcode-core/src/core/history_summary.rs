@@ -0,0 +1,280 @@
+//! Running summary of conversation history trimmed from the context window
+//!
+//! With `[context.summarization] enabled = true`, messages that are about to
+//! be trimmed are condensed, together with the previous summary, into a new
+//! summary that the chat loop keeps in the system prompt. The summary can be
+//! written by a dedicated model, typically a cheaper hosted one or a model
+//! served locally, and falls back to the session's main model when that fails
+//! and `fallback_to_main` is set. Each message is labelled with its
+//! provenance and the summary is asked to cite those labels, so facts carried
+//! forward can be traced to the user, a tool result or an earlier summary.
+
+use crate::config::{SummarizationConfig, SummaryDetail};
+use crate::llm::provider::{LLMProvider, LLMRequest, Message, MessageRole};
+use anyhow::{Result, anyhow, bail};
+
+/// Characters kept from each message sent for summarization.
+const MAX_MESSAGE_CHARS: usize = 2_000;
+/// Output token allowance per requested word.
+const TOKENS_PER_WORD: usize = 2;
+
+/// A provider and model that write summaries.
+pub struct SummaryModel {
+    pub provider: Box<dyn LLMProvider>,
+    pub model: String,
+}
+
+/// A summary and the model that wrote it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistorySummary {
+    pub text: String,
+    pub model: String,
+    /// Why the dedicated model was passed over for the main one
+    pub fallback_reason: Option<String>,
+}
+
+/// Writes summaries of trimmed history.
+pub struct HistorySummarizer {
+    dedicated: Option<SummaryModel>,
+    fallback_to_main: bool,
+    max_words: usize,
+    detail: SummaryDetail,
+    temperature: f32,
+}
+
+impl HistorySummarizer {
+    /// Summarizer using `dedicated` when set and the main model otherwise.
+    pub fn new(config: &SummarizationConfig, dedicated: Option<SummaryModel>) -> Self {
+        Self {
+            dedicated,
+            fallback_to_main: config.fallback_to_main,
+            max_words: config.max_words.max(1),
+            detail: config.detail,
+            temperature: config.temperature,
+        }
+    }
+
+    /// Fold `messages` into `previous`, the summary of what was trimmed before.
+    pub async fn summarize(
+        &self,
+        previous: Option<&str>,
+        messages: &[Message],
+        main: &dyn LLMProvider,
+        main_model: &str,
+    ) -> Result<HistorySummary> {
+        let fallback_reason = match &self.dedicated {
+            Some(dedicated) => {
+                match self
+                    .generate(
+                        dedicated.provider.as_ref(),
+                        &dedicated.model,
+                        previous,
+                        messages,
+                    )
+                    .await
+                {
+                    Ok(text) => {
+                        return Ok(HistorySummary {
+                            text,
+                            model: dedicated.model.clone(),
+                            fallback_reason: None,
+                        });
+                    }
+                    Err(err) if self.fallback_to_main => Some(format!("{err:#}")),
+                    Err(err) => return Err(err),
+                }
+            }
+            None => None,
+        };
+        let text = self.generate(main, main_model, previous, messages).await?;
+        Ok(HistorySummary {
+            text,
+            model: main_model.to_string(),
+            fallback_reason,
+        })
+    }
+
+    async fn generate(
+        &self,
+        provider: &dyn LLMProvider,
+        model: &str,
+        previous: Option<&str>,
+        messages: &[Message],
+    ) -> Result<String> {
+        let response = provider
+            .generate(self.request(model, previous, messages))
+            .await
+            .map_err(|err| anyhow!("{model}: {err}"))?;
+        let text = response.content.unwrap_or_default().trim().to_string();
+        if text.is_empty() {
+            bail!("{model} returned an empty summary");
+        }
+        Ok(text)
+    }
+
+    fn request(&self, model: &str, previous: Option<&str>, messages: &[Message]) -> LLMRequest {
+        let focus = match self.detail {
+            SummaryDetail::Brief => "the user's goals, decisions made and their outcomes",
+            SummaryDetail::Detailed => {
+                "the user's goals, decisions made and their outcomes, files read or changed, commands run with their results, and questions still open"
+            }
+        };
+        let instructions = format!(
+            "You maintain a running summary of a coding session whose earliest messages are being removed to save space. Merge the earlier summary, if any, with the transcript into one summary covering {focus}. Keep identifiers, paths and error messages exact. Each transcript line starts with its source in square brackets, such as [user] or [tool:read_file src/lib.rs]; end each point of the summary with the sources it rests on in the same form, and keep the citations of the earlier summary. Write at most {} words of plain prose or bullets, with no preamble.",
+            self.max_words
+        );
+        let mut transcript = String::new();
+        if let Some(previous) = previous {
+            transcript.push_str("Earlier summary:\n");
+            transcript.push_str(previous);
+            transcript.push_str("\n\n");
+        }
+        transcript.push_str("Transcript:\n");
+        transcript.push_str(&transcript_text(messages));
+
+        LLMRequest {
+            messages: vec![Message::user(transcript)],
+            system_prompt: Some(instructions),
+            tools: None,
+            model: model.to_string(),
+            max_tokens: Some((self.max_words * TOKENS_PER_WORD) as u32),
+            temperature: Some(self.temperature),
+            stream: false,
+            tool_choice: None,
+            parallel_tool_calls: None,
+            parallel_tool_config: None,
+            reasoning_effort: None,
+            json_mode: false,
+            response_schema: None,
+            code_execution: false,
+            seed: None,
+        }
+    }
+}
+
+/// Messages as plain text labelled with their source and role, each clipped
+/// to keep the request small.
+pub(crate) fn transcript_text(messages: &[Message]) -> String {
+    let mut text = String::new();
+    for message in messages {
+        let role = match message.role {
+            MessageRole::System => "System",
+            MessageRole::User => "User",
+            MessageRole::Assistant => "Assistant",
+            MessageRole::Tool => "Tool result",
+        };
+        let citation = format!("[{}]", message.provenance().citation());
+        let content = message.content.trim();
+        if !content.is_empty() {
+            let clipped: String = content.chars().take(MAX_MESSAGE_CHARS).collect();
+            let ellipsis = if clipped.len() < content.len() {
+                " …"
+            } else {
+                ""
+            };
+            text.push_str(&format!("{citation} {role}: {clipped}{ellipsis}\n"));
+        }
+        for call in message.tool_calls.iter().flatten() {
+            text.push_str(&format!(
+                "{citation} Tool call: {} {}\n",
+                call.function.name, call.function.arguments
+            ));
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::provenance::Provenance;
+    use crate::llm::provider::{FinishReason, LLMError, LLMResponse};
+
+    struct FixedProvider(Option<&'static str>);
+
+    #[async_trait::async_trait]
+    impl LLMProvider for FixedProvider {
+        fn name(&self) -> &str {
+            "fixed"
+        }
+
+        async fn generate(&self, request: LLMRequest) -> Result<LLMResponse, LLMError> {
+            let Some(reply) = self.0 else {
+                return Err(LLMError::Network("connection refused".to_string()));
+            };
+            assert!(request.messages[0].content.contains("User: fix the build"));
+            assert!(
+                request.messages[0]
+                    .content
+                    .contains("[tool:read_file src/lib.rs] Tool result:")
+            );
+            Ok(LLMResponse {
+                content: Some(reply.to_string()),
+                tool_calls: None,
+                usage: None,
+                finish_reason: FinishReason::Stop,
+                reasoning: None,
+                code_execution: Vec::new(),
+            })
+        }
+
+        fn supported_models(&self) -> Vec<String> {
+            Vec::new()
+        }
+
+        fn validate_request(&self, _request: &LLMRequest) -> Result<(), LLMError> {
+            Ok(())
+        }
+    }
+
+    fn local(reply: Option<&'static str>) -> Option<SummaryModel> {
+        Some(SummaryModel {
+            provider: Box::new(FixedProvider(reply)),
+            model: "qwen2.5:3b".to_string(),
+        })
+    }
+
+    #[tokio::test]
+    async fn prefers_the_dedicated_model_and_falls_back_to_main() {
+        let messages = [
+            Message::user("fix the build".to_string()),
+            Message::tool_response("call_1".to_string(), "fn main() {}".to_string())
+                .with_provenance(Provenance::ToolResult {
+                    tool: "read_file".to_string(),
+                    target: Some("src/lib.rs".to_string()),
+                }),
+        ];
+        let main = FixedProvider(Some("Main summary"));
+        let mut config = SummarizationConfig::default();
+
+        let summarizer = HistorySummarizer::new(&config, local(Some("Local summary")));
+        let summary = summarizer
+            .summarize(None, &messages, &main, "gpt-5")
+            .await
+            .unwrap();
+        assert_eq!(summary.text, "Local summary");
+        assert_eq!(summary.model, "qwen2.5:3b");
+
+        let summarizer = HistorySummarizer::new(&config, local(None));
+        let summary = summarizer
+            .summarize(Some("Earlier"), &messages, &main, "gpt-5")
+            .await
+            .unwrap();
+        assert_eq!(summary.text, "Main summary");
+        assert!(
+            summary
+                .fallback_reason
+                .unwrap()
+                .contains("connection refused")
+        );
+
+        config.fallback_to_main = false;
+        let summarizer = HistorySummarizer::new(&config, local(None));
+        assert!(
+            summarizer
+                .summarize(None, &messages, &main, "gpt-5")
+                .await
+                .is_err()
+        );
+    }
+}
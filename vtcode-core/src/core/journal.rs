@@ -0,0 +1,339 @@
+//! Write-behind journal for append-only JSONL telemetry and audit files.
+//!
+//! Records are handed to a background writer thread over an unbounded channel so
+//! the caller never touches the filesystem on the hot path. The writer batches
+//! records, applies the configured fsync policy, and drains everything that is
+//! still buffered when the last handle is dropped, when [`JournalWriter::flush`]
+//! is called, or when the process panics.
+
+use crate::config::telemetry::JournalFsyncPolicy;
+use once_cell::sync::Lazy;
+use std::fs::{File, OpenOptions, create_dir_all};
+use std::io::Write;
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, Once, Weak};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+const WORKER_THREAD_NAME: &str = "vtcode-journal";
+const PANIC_FLUSH_TIMEOUT: Duration = Duration::from_millis(500);
+
+static LIVE_JOURNALS: Lazy<Mutex<Vec<Weak<JournalInner>>>> = Lazy::new(|| Mutex::new(Vec::new()));
+static PANIC_HOOK: Once = Once::new();
+
+/// Tuning knobs for a [`JournalWriter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JournalOptions {
+    /// Number of buffered records that triggers an immediate write
+    pub batch_size: usize,
+    /// Maximum time a record may sit in the buffer before being written
+    pub flush_interval: Duration,
+    /// When to call `fsync` on the journal file
+    pub fsync: JournalFsyncPolicy,
+}
+
+impl Default for JournalOptions {
+    fn default() -> Self {
+        Self {
+            batch_size: 64,
+            flush_interval: Duration::from_millis(250),
+            fsync: JournalFsyncPolicy::default(),
+        }
+    }
+}
+
+enum JournalCommand {
+    Record(String),
+    Flush(Sender<()>),
+    Shutdown,
+}
+
+struct JournalInner {
+    path: PathBuf,
+    sender: Sender<JournalCommand>,
+    worker: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl JournalInner {
+    fn flush_with_timeout(&self, timeout: Option<Duration>) -> bool {
+        let (ack_tx, ack_rx) = mpsc::channel();
+        if self.sender.send(JournalCommand::Flush(ack_tx)).is_err() {
+            return false;
+        }
+        match timeout {
+            Some(limit) => ack_rx.recv_timeout(limit).is_ok(),
+            None => ack_rx.recv().is_ok(),
+        }
+    }
+}
+
+impl Drop for JournalInner {
+    fn drop(&mut self) {
+        let _ = self.sender.send(JournalCommand::Shutdown);
+        let worker = self.worker.lock().ok().and_then(|mut guard| guard.take());
+        if let Some(handle) = worker
+            && handle.thread().id() != thread::current().id()
+        {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Cloneable handle to a background journal writer.
+///
+/// All clones share one writer thread; the thread drains and exits once the
+/// last clone is dropped.
+#[derive(Clone)]
+pub struct JournalWriter {
+    inner: Arc<JournalInner>,
+}
+
+impl JournalWriter {
+    /// Start a writer thread appending to `path`.
+    pub fn spawn(path: impl Into<PathBuf>, options: JournalOptions) -> std::io::Result<Self> {
+        let path = path.into();
+        let (sender, receiver) = mpsc::channel();
+        let worker_path = path.clone();
+        let worker = thread::Builder::new()
+            .name(WORKER_THREAD_NAME.to_string())
+            .spawn(move || run_worker(worker_path, receiver, options))?;
+
+        let inner = Arc::new(JournalInner {
+            path,
+            sender,
+            worker: Mutex::new(Some(worker)),
+        });
+        register_live_journal(&inner);
+
+        Ok(Self { inner })
+    }
+
+    /// Path of the file this journal appends to.
+    pub fn path(&self) -> &Path {
+        &self.inner.path
+    }
+
+    /// Queue a single line for writing. Never blocks on I/O.
+    pub fn append(&self, line: String) {
+        let _ = self.inner.sender.send(JournalCommand::Record(line));
+    }
+
+    /// Block until every record queued so far has been written (and synced,
+    /// unless the fsync policy is `never`). Returns `false` if the writer is gone.
+    pub fn flush(&self) -> bool {
+        self.inner.flush_with_timeout(None)
+    }
+}
+
+/// Flush every live journal, waiting a bounded amount of time for each one.
+pub fn flush_all_journals() {
+    let journals: Vec<Arc<JournalInner>> = match LIVE_JOURNALS.lock() {
+        Ok(mut guard) => {
+            guard.retain(|entry| entry.strong_count() > 0);
+            guard.iter().filter_map(Weak::upgrade).collect()
+        }
+        Err(_) => return,
+    };
+    for journal in journals {
+        journal.flush_with_timeout(Some(PANIC_FLUSH_TIMEOUT));
+    }
+}
+
+fn register_live_journal(inner: &Arc<JournalInner>) {
+    if let Ok(mut guard) = LIVE_JOURNALS.lock() {
+        guard.retain(|entry| entry.strong_count() > 0);
+        guard.push(Arc::downgrade(inner));
+    }
+
+    PANIC_HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let on_worker = thread::current().name() == Some(WORKER_THREAD_NAME);
+            if !on_worker {
+                flush_all_journals();
+            }
+            previous(info);
+        }));
+    });
+}
+
+struct JournalSink {
+    path: PathBuf,
+    file: Option<File>,
+    fsync: JournalFsyncPolicy,
+}
+
+impl JournalSink {
+    fn write_batch(&mut self, buffer: &mut Vec<String>) {
+        if buffer.is_empty() {
+            return;
+        }
+        let mut payload = String::with_capacity(buffer.iter().map(|line| line.len() + 1).sum());
+        for line in buffer.drain(..) {
+            payload.push_str(&line);
+            payload.push('\n');
+        }
+        let sync_batch = self.fsync == JournalFsyncPolicy::Batch;
+        let Some(file) = self.file() else {
+            return;
+        };
+        let written = file.write_all(payload.as_bytes()).is_ok();
+        if written && sync_batch {
+            let _ = file.sync_data();
+        }
+        if !written {
+            // Reopen on the next batch in case the file was rotated or removed.
+            self.file = None;
+        }
+    }
+
+    fn sync(&mut self) {
+        if self.fsync == JournalFsyncPolicy::Never {
+            return;
+        }
+        if let Some(file) = self.file.as_mut() {
+            let _ = file.sync_data();
+        }
+    }
+
+    fn file(&mut self) -> Option<&mut File> {
+        if self.file.is_none() {
+            if let Some(parent) = self.path.parent() {
+                let _ = create_dir_all(parent);
+            }
+            self.file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .ok();
+        }
+        self.file.as_mut()
+    }
+}
+
+fn run_worker(path: PathBuf, receiver: Receiver<JournalCommand>, options: JournalOptions) {
+    let batch_size = options.batch_size.max(1);
+    let mut sink = JournalSink {
+        path,
+        file: None,
+        fsync: options.fsync,
+    };
+    let mut buffer: Vec<String> = Vec::with_capacity(batch_size);
+    // Set when the oldest buffered record arrived, so a steady stream of
+    // records cannot keep postponing the time-based flush.
+    let mut deadline: Option<Instant> = None;
+
+    loop {
+        let command = match deadline.filter(|_| !buffer.is_empty()) {
+            None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            Some(deadline) => {
+                receiver.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            }
+        };
+
+        match command {
+            Ok(JournalCommand::Record(line)) => {
+                if buffer.is_empty() {
+                    deadline = Some(Instant::now() + options.flush_interval);
+                }
+                buffer.push(line);
+                if buffer.len() >= batch_size {
+                    sink.write_batch(&mut buffer);
+                }
+            }
+            Ok(JournalCommand::Flush(ack)) => {
+                sink.write_batch(&mut buffer);
+                sink.sync();
+                let _ = ack.send(());
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                sink.write_batch(&mut buffer);
+            }
+            Ok(JournalCommand::Shutdown) | Err(RecvTimeoutError::Disconnected) => {
+                // Drain anything that raced in behind the shutdown request.
+                while let Ok(command) = receiver.try_recv() {
+                    match command {
+                        JournalCommand::Record(line) => buffer.push(line),
+                        JournalCommand::Flush(ack) => {
+                            let _ = ack.send(());
+                        }
+                        JournalCommand::Shutdown => {}
+                    }
+                }
+                sink.write_batch(&mut buffer);
+                sink.sync();
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn options(batch_size: usize) -> JournalOptions {
+        JournalOptions {
+            batch_size,
+            flush_interval: Duration::from_secs(60),
+            fsync: JournalFsyncPolicy::Never,
+        }
+    }
+
+    #[test]
+    fn flush_writes_buffered_records_in_order() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("logs/journal.jsonl");
+        let journal = JournalWriter::spawn(&path, options(100)).unwrap();
+
+        journal.append("{\"n\":1}".to_string());
+        journal.append("{\"n\":2}".to_string());
+        assert!(journal.flush());
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert_eq!(content, "{\"n\":1}\n{\"n\":2}\n");
+    }
+
+    #[test]
+    fn steady_records_do_not_postpone_the_timed_flush() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("journal.jsonl");
+        let journal = JournalWriter::spawn(
+            &path,
+            JournalOptions {
+                flush_interval: Duration::from_millis(50),
+                ..options(1_000)
+            },
+        )
+        .unwrap();
+
+        let started = Instant::now();
+        while started.elapsed() < Duration::from_millis(500) {
+            journal.append("tick".to_string());
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let content = fs::read_to_string(&path).unwrap_or_default();
+        assert!(content.starts_with("tick\n"));
+    }
+
+    #[test]
+    fn dropping_last_handle_drains_buffer() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("journal.jsonl");
+        let journal = JournalWriter::spawn(&path, options(100)).unwrap();
+        let clone = journal.clone();
+
+        clone.append("first".to_string());
+        drop(clone);
+        journal.append("second".to_string());
+        drop(journal);
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert_eq!(content, "first\nsecond\n");
+    }
+}
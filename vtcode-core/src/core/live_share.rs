@@ -0,0 +1,489 @@
+//! Read-only live view of a chat session
+//!
+//! `vtcode share --listen <addr>` starts a chat session and serves its
+//! transcript over HTTP so a pair-programming partner can follow along in a
+//! browser. Every request needs the random token printed with the link, either
+//! as `?token=` or as an `Authorization: Bearer` header. The page subscribes to
+//! `/events`, a server-sent event stream of [`TranscriptUpdate`]s: how many
+//! lines the viewer should keep, followed by the lines that replace the rest.
+//! Streamed replies rewrite their last lines, so updates are not append-only.
+//!
+//! Viewers cannot type into the session unless it was started with
+//! `--allow-input`; then `POST /input` submits a prompt as if it had been
+//! typed locally, and the chat loop picks it up the next time it waits for
+//! input.
+
+use crate::utils::transcript;
+use anyhow::{Context, Result};
+use rand::{Rng, distributions::Alphanumeric};
+use serde::Serialize;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Semaphore;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::task::{JoinHandle, JoinSet};
+use tokio::time::sleep;
+
+const TOKEN_LENGTH: usize = 32;
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+/// Comment lines keep idle connections from being closed by proxies.
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+/// How long a client may take to send its request head.
+const HEAD_TIMEOUT: Duration = Duration::from_secs(5);
+/// Open connections at once, viewers included; more are closed on accept.
+const MAX_CONNECTIONS: usize = 64;
+const MAX_INPUT_BYTES: usize = 16 * 1024;
+const INPUT_FORM_MARKER: &str = "<!-- input -->";
+
+const PAGE: &str = r#"<!doctype html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="referrer" content="no-referrer">
+<title>VT Code session</title>
+<style>
+  body { margin: 0; background: #1e1e1e; color: #d4d4d4; font: 14px/1.4 monospace; }
+  header { position: sticky; top: 0; padding: 6px 12px; background: #2d2d2d; color: #9cdcfe; }
+  pre { margin: 0; padding: 12px; white-space: pre-wrap; word-break: break-word; }
+  form { position: sticky; bottom: 0; display: flex; gap: 8px; padding: 8px 12px;
+         background: #2d2d2d; }
+  input { flex: 1; background: #1e1e1e; color: inherit; border: 1px solid #555; padding: 4px; }
+</style>
+</head>
+<body>
+<header id="status">Connecting...</header>
+<pre id="log"></pre>
+<!-- input -->
+<script>
+  const token = new URLSearchParams(location.search).get("token") || "";
+  const status = document.getElementById("status");
+  const log = document.getElementById("log");
+  let lines = [];
+  const source = new EventSource("/events?token=" + encodeURIComponent(token));
+  source.onopen = () => { status.textContent = "Live, read-only"; };
+  source.onerror = () => { status.textContent = "Disconnected, retrying..."; };
+  source.onmessage = (event) => {
+    const update = JSON.parse(event.data);
+    const follow = innerHeight + scrollY >= document.body.scrollHeight - 40;
+    lines = lines.slice(0, update.keep).concat(update.lines);
+    log.textContent = lines.join("\n");
+    if (follow) scrollTo(0, document.body.scrollHeight);
+  };
+  const form = document.getElementById("input");
+  if (form) {
+    form.onsubmit = async (event) => {
+      event.preventDefault();
+      const prompt = form.elements.prompt;
+      const response = await fetch("/input", {
+        method: "POST",
+        headers: { Authorization: "Bearer " + token },
+        body: prompt.value,
+      });
+      if (response.ok) prompt.value = "";
+    };
+  }
+</script>
+</body>
+</html>
+"#;
+
+const INPUT_FORM: &str = r#"<form id="input">
+  <input name="prompt" autocomplete="off" placeholder="Send a prompt to the session">
+  <button>Send</button>
+</form>"#;
+
+/// A running share server; stops serving when dropped.
+pub struct LiveShare {
+    addr: SocketAddr,
+    token: String,
+    remote_input: Option<UnboundedReceiver<String>>,
+    server: JoinHandle<()>,
+}
+
+/// What the chat loop needs from a share: the link to show and, when remote
+/// input is enabled, the prompts viewers submit.
+pub struct ShareLink {
+    pub url: String,
+    pub remote_input: Option<UnboundedReceiver<String>>,
+}
+
+impl LiveShare {
+    pub async fn start(listen: &str, allow_input: bool) -> Result<Self> {
+        let listener = TcpListener::bind(listen)
+            .await
+            .with_context(|| format!("failed to listen on {listen}"))?;
+        let addr = listener.local_addr()?;
+        let token: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(TOKEN_LENGTH)
+            .map(char::from)
+            .collect();
+        let (sender, remote_input) = if allow_input {
+            let (sender, receiver) = mpsc::unbounded_channel();
+            (Some(sender), Some(receiver))
+        } else {
+            (None, None)
+        };
+        let server = tokio::spawn(serve(listener, token.clone(), sender));
+        Ok(Self {
+            addr,
+            token,
+            remote_input,
+            server,
+        })
+    }
+
+    /// Link for viewers. A wildcard listen address is replaced by this
+    /// machine's address on the local network.
+    pub fn url(&self) -> String {
+        let ip = match self.addr.ip() {
+            ip if ip.is_unspecified() => lan_address().unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+            ip => ip,
+        };
+        format!(
+            "http://{}/?token={}",
+            SocketAddr::new(ip, self.addr.port()),
+            self.token
+        )
+    }
+
+    pub fn link(&mut self) -> ShareLink {
+        ShareLink {
+            url: self.url(),
+            remote_input: self.remote_input.take(),
+        }
+    }
+}
+
+impl Drop for LiveShare {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+/// Change between what a viewer has and the current transcript.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct TranscriptUpdate<'a> {
+    /// Lines the viewer keeps from what it already has
+    pub keep: usize,
+    /// Lines that follow them
+    pub lines: &'a [String],
+}
+
+impl<'a> TranscriptUpdate<'a> {
+    pub fn between(sent: &[String], current: &'a [String]) -> Option<Self> {
+        let keep = sent
+            .iter()
+            .zip(current)
+            .take_while(|(sent, current)| sent == current)
+            .count();
+        (keep != sent.len() || keep != current.len()).then(|| Self {
+            keep,
+            lines: &current[keep..],
+        })
+    }
+}
+
+/// The address other machines on the network reach this one at. Connecting
+/// a UDP socket sends nothing; it only picks the outgoing interface.
+fn lan_address() -> Option<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("192.0.2.1:80").ok()?;
+    Some(socket.local_addr().ok()?.ip())
+}
+
+async fn serve(listener: TcpListener, token: String, input: Option<UnboundedSender<String>>) {
+    // Dropping the set when the server is aborted closes every viewer.
+    let mut connections = JoinSet::new();
+    let slots = Arc::new(Semaphore::new(MAX_CONNECTIONS));
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let Ok((stream, _)) = accepted else {
+                    continue;
+                };
+                let Ok(slot) = slots.clone().try_acquire_owned() else {
+                    continue;
+                };
+                let token = token.clone();
+                let input = input.clone();
+                connections.spawn(async move {
+                    let _ = handle_connection(stream, &token, input, HEAD_TIMEOUT).await;
+                    drop(slot);
+                });
+            }
+            Some(_) = connections.join_next() => {}
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Request {
+    method: String,
+    path: String,
+    token: Option<String>,
+    content_length: usize,
+}
+
+impl Request {
+    fn parse(head: &str) -> Option<Self> {
+        let mut lines = head.lines();
+        let mut parts = lines.next()?.split_whitespace();
+        let method = parts.next()?.to_string();
+        let target = parts.next()?;
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let mut token = query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(name, _)| *name == "token")
+            .map(|(_, value)| value.to_string());
+        let mut content_length = 0;
+        for line in lines {
+            let Some((name, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.parse().ok()?;
+            } else if name.eq_ignore_ascii_case("authorization")
+                && let Some(bearer) = value.strip_prefix("Bearer ")
+            {
+                token = Some(bearer.trim().to_string());
+            }
+        }
+        Some(Self {
+            method,
+            path: path.to_string(),
+            token,
+            content_length,
+        })
+    }
+
+    fn authorized(&self, token: &str) -> bool {
+        self.token
+            .as_deref()
+            .is_some_and(|given| constant_time_eq(given.as_bytes(), token.as_bytes()))
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// The request head up to its blank line, or `None` when the client closes
+/// the connection first. Stops one byte past [`MAX_HEAD_BYTES`].
+async fn read_head(reader: &mut BufReader<TcpStream>) -> Result<Option<String>> {
+    let mut head = String::new();
+    loop {
+        // Cap each read so a client that never sends a newline cannot grow
+        // the head past the limit.
+        let budget = (MAX_HEAD_BYTES + 1 - head.len()) as u64;
+        if (&mut *reader).take(budget).read_line(&mut head).await? == 0 {
+            return Ok(None);
+        }
+        if head.len() > MAX_HEAD_BYTES || head.ends_with("\r\n\r\n") || head.ends_with("\n\n") {
+            return Ok(Some(head));
+        }
+    }
+}
+
+async fn handle_connection(
+    stream: TcpStream,
+    token: &str,
+    input: Option<UnboundedSender<String>>,
+    head_timeout: Duration,
+) -> Result<()> {
+    let mut reader = BufReader::new(stream);
+    let head = match tokio::time::timeout(head_timeout, read_head(&mut reader)).await {
+        Ok(head) => match head? {
+            Some(head) => head,
+            None => return Ok(()),
+        },
+        Err(_) => return respond(reader.get_mut(), "408 Request Timeout", "").await,
+    };
+    if head.len() > MAX_HEAD_BYTES {
+        return respond(reader.get_mut(), "431 Request Header Fields Too Large", "").await;
+    }
+    let Some(request) = Request::parse(&head) else {
+        return respond(reader.get_mut(), "400 Bad Request", "").await;
+    };
+    if !request.authorized(token) {
+        return respond(
+            reader.get_mut(),
+            "401 Unauthorized",
+            "Missing or wrong token.\n",
+        )
+        .await;
+    }
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/") => {
+            let page = PAGE.replace(
+                INPUT_FORM_MARKER,
+                if input.is_some() { INPUT_FORM } else { "" },
+            );
+            respond_with(
+                reader.get_mut(),
+                "200 OK",
+                "text/html; charset=utf-8",
+                &page,
+            )
+            .await
+        }
+        ("GET", "/events") => stream_transcript(reader.get_mut()).await,
+        ("POST", "/input") => {
+            let Some(sender) = input else {
+                return respond(
+                    reader.get_mut(),
+                    "403 Forbidden",
+                    "Remote input is disabled for this session.\n",
+                )
+                .await;
+            };
+            if request.content_length > MAX_INPUT_BYTES {
+                return respond(reader.get_mut(), "413 Payload Too Large", "").await;
+            }
+            let mut body = vec![0; request.content_length];
+            reader.read_exact(&mut body).await?;
+            let prompt = String::from_utf8_lossy(&body).trim().to_string();
+            if !prompt.is_empty() {
+                let _ = sender.send(prompt);
+            }
+            respond(reader.get_mut(), "204 No Content", "").await
+        }
+        _ => respond(reader.get_mut(), "404 Not Found", "").await,
+    }
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, body: &str) -> Result<()> {
+    respond_with(stream, status, "text/plain; charset=utf-8", body).await
+}
+
+async fn respond_with(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &str,
+) -> Result<()> {
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n\
+         Cache-Control: no-store\r\nReferrer-Policy: no-referrer\r\n\
+         Connection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// Send the transcript as server-sent events until the viewer disconnects.
+async fn stream_transcript(stream: &mut TcpStream) -> Result<()> {
+    stream
+        .write_all(
+            b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-store\r\n\
+              Connection: keep-alive\r\n\r\n",
+        )
+        .await?;
+    let mut sent: Vec<String> = Vec::new();
+    let mut revision = None;
+    let mut quiet = Duration::ZERO;
+    loop {
+        let current = transcript::revision();
+        if revision != Some(current) {
+            revision = Some(current);
+            let lines = transcript::snapshot();
+            if let Some(update) = TranscriptUpdate::between(&sent, &lines) {
+                let event = format!("data: {}\n\n", serde_json::to_string(&update)?);
+                stream.write_all(event.as_bytes()).await?;
+                quiet = Duration::ZERO;
+            }
+            sent = lines;
+        }
+        if quiet >= KEEPALIVE_INTERVAL {
+            stream.write_all(b": keepalive\n\n").await?;
+            quiet = Duration::ZERO;
+        }
+        sleep(POLL_INTERVAL).await;
+        quiet += POLL_INTERVAL;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn updates_keep_the_unchanged_prefix() {
+        let lines = |items: &[&str]| items.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let sent = lines(&["> fix it", "Working"]);
+        let current = lines(&["> fix it", "Working on", "src/lib.rs"]);
+        let update = TranscriptUpdate::between(&sent, &current).unwrap();
+        assert_eq!(update.keep, 1);
+        assert_eq!(update.lines, &current[1..]);
+        assert!(TranscriptUpdate::between(&current, &current).is_none());
+        assert_eq!(
+            TranscriptUpdate::between(&current, &sent).unwrap().lines,
+            &sent[1..]
+        );
+    }
+
+    #[test]
+    fn requests_need_the_token() {
+        let query = Request::parse("GET /events?token=abc HTTP/1.1\r\nHost: x\r\n\r\n").unwrap();
+        assert_eq!(query.path, "/events");
+        assert!(query.authorized("abc"));
+        assert!(!query.authorized("abd"));
+
+        let header = Request::parse(
+            "POST /input HTTP/1.1\r\nAuthorization: Bearer abc\r\nContent-Length: 5\r\n\r\n",
+        )
+        .unwrap();
+        assert_eq!(header.content_length, 5);
+        assert!(header.authorized("abc"));
+        assert!(
+            !Request::parse("GET / HTTP/1.1\r\n\r\n")
+                .unwrap()
+                .authorized("abc")
+        );
+    }
+
+    #[tokio::test]
+    async fn head_without_newline_is_rejected_at_the_limit() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            handle_connection(stream, "abc", None, HEAD_TIMEOUT).await
+        });
+
+        let mut client = TcpStream::connect(addr).await.unwrap();
+        client
+            .write_all(&vec![b'a'; MAX_HEAD_BYTES + 1])
+            .await
+            .unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 431"));
+        server.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn silent_clients_time_out() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            handle_connection(stream, "abc", None, Duration::from_millis(100)).await
+        });
+
+        let mut client = TcpStream::connect(addr).await.unwrap();
+        client.write_all(b"GET / HTTP/1.1\r\n").await.unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 408"));
+        server.await.unwrap().unwrap();
+    }
+}
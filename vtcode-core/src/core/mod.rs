@@ -9,12 +9,32 @@
 //!
 //! - **Agent**: Main agent implementation with conversation management
 //! - **Context Compression**: Intelligent context management and summarization
+//! - **Context Providers**: Embedder- and config-registered sources of request context
+//! - **History Summary**: Running summary of trimmed history, written by a dedicated or the main model
 //! - **Performance Monitoring**: Real-time metrics and benchmarking
 //! - **Prompt Caching**: Strategic caching for improved response times
+//! - **Prompt Replay**: Archived sessions replayed against a modified system prompt
 //! - **Decision Tracking**: Audit trail of agent decisions and actions
 //! - **Error Recovery**: Intelligent error handling with context preservation
 //! - **Timeout Detection**: Prevents runaway operations
 //! - **Trajectory Management**: Session state and history tracking
+//! - **Trajectory Trends**: Per-period session aggregates and regressions after model or config changes
+//! - **Evidence**: Links between claims in the agent's answers and the tool results behind them
+//! - **Journal**: Write-behind batching for telemetry and audit logs
+//! - **Batch Refactor**: Resumable, validated edits across many files
+//! - **Briefing**: Commits, changed files and CI status since the previous session in a workspace
+//! - **Offline Mode**: Connectivity probes for the local assistant fallback
+//! - **Change Ledger**: Files changed during a session and the requests behind them
+//! - **Session Summary**: End-of-session change summaries and changelog entries
+//! - **Timeline**: Turn-by-turn checkpoints of the files changed in a session
+//! - **Run Events**: JSONL progress events for automated runs
+//! - **Guardrails**: Protected paths and destructive command patterns
+//! - **Sensitive Files**: First-run scan for secrets that tools may not read until allowed
+//! - **Daemon**: Warm symbol index, language summary and provider reachability served over a unix socket
+//! - **Token Calibration**: Per-model correction of token estimates from reported usage
+//! - **Tool Exposure**: Read-only tools first, editing and terminal tools on escalation
+//! - **Tool Schemas**: Compacted schemas and tool subsets for small context windows
+//! - **Progress**: Phase progress and ETA of workspace scans for the CLI and the status bar
 //!
 //! ## Key Components
 //!
@@ -51,14 +71,49 @@
 //! ```
 
 pub mod agent;
+pub mod batch_refactor;
+pub mod briefing;
+pub mod change_ledger;
+pub mod ci_failures;
+pub mod command_backup;
 pub mod context_compression;
+pub mod context_providers;
 pub mod conversation_summarizer;
+pub mod daemon;
 pub mod decision_tracker;
+pub mod diff_analysis;
 pub mod error_recovery;
+pub mod evidence;
+pub mod guardrails;
+pub mod handoff;
+pub mod history_summary;
+pub mod journal;
+pub mod live_share;
+pub mod offline;
 pub mod orchestrator_retry;
 pub mod performance_monitor;
 pub mod performance_profiler;
+pub mod progress;
 pub mod prompt_caching;
+pub mod prompt_injection;
+pub mod prompt_replay;
+pub mod provenance;
+pub mod reproducibility;
+pub mod risk_map;
 pub mod router;
+pub mod run_events;
+pub mod sensitive_files;
+pub mod session_store;
+pub mod session_summary;
+pub mod spend_tracker;
+pub mod timeline;
 pub mod timeout_detector;
+pub mod token_calibration;
+pub mod tool_exposure;
+pub mod tool_result_store;
+pub mod tool_schema;
+pub mod tool_stats;
+pub mod tool_verbosity;
 pub mod trajectory;
+pub mod trajectory_trends;
+pub mod turn_latency;
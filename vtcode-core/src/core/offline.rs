@@ -0,0 +1,139 @@
+//! Connectivity detection for offline operation
+//!
+//! Chat sessions probe the provider endpoint before sending requests. When it
+//! cannot be reached they stop calling the provider and keep the workspace
+//! tools (search, file reads, symbol outlines, git) available locally, or hand
+//! requests to a locally hosted model when one is configured.
+
+use crate::config::constants::{tools, urls};
+use crate::llm::provider::LLMError;
+use crate::utils::network;
+use reqwest::Url;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// Tools that need network access and are withheld while offline.
+pub const NETWORK_TOOLS: &[&str] = &[tools::CURL];
+
+/// Result of a reachability probe.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Connectivity {
+    Online,
+    Offline { reason: String },
+}
+
+impl Connectivity {
+    pub fn is_online(&self) -> bool {
+        matches!(self, Self::Online)
+    }
+}
+
+/// Default API endpoint for a provider name.
+pub fn provider_endpoint(provider: &str) -> Option<&'static str> {
+    match provider.to_lowercase().as_str() {
+        "gemini" => Some(urls::GEMINI_API_BASE),
+        "openai" => Some(urls::OPENAI_API_BASE),
+        "anthropic" => Some(urls::ANTHROPIC_API_BASE),
+        "openrouter" => Some(urls::OPENROUTER_API_BASE),
+        "xai" => Some(urls::XAI_API_BASE),
+        _ => None,
+    }
+}
+
+/// Provider whose default endpoint is `url`.
+fn endpoint_provider(url: &str) -> Option<&'static str> {
+    ["gemini", "openai", "anthropic", "openrouter", "xai"]
+        .into_iter()
+        .find(|provider| provider_endpoint(provider) == Some(url))
+}
+
+/// Whether a provider error may mean the network is gone.
+pub fn is_connectivity_error(error: &LLMError) -> bool {
+    matches!(error, LLMError::Network(_))
+}
+
+pub fn is_network_tool(name: &str) -> bool {
+    NETWORK_TOOLS.contains(&name)
+}
+
+fn host_and_port(url: &str) -> Option<(String, u16)> {
+    let parsed = Url::parse(url).ok()?;
+    let host = parsed.host_str()?.to_string();
+    let port = parsed.port_or_known_default()?;
+    Some((host, port))
+}
+
+/// Check that the host of `url` resolves and accepts a TCP connection. When
+/// `[network]` routes the endpoint through a proxy, the proxy is checked
+/// instead.
+///
+/// URLs that cannot be parsed are reported as online so a malformed custom
+/// endpoint never blocks the provider.
+pub async fn probe_endpoint(url: &str, timeout: Duration) -> Connectivity {
+    let settings = network::current();
+    let target = settings.proxy(endpoint_provider(url)).unwrap_or(url);
+    let Some((host, port)) = host_and_port(target) else {
+        return Connectivity::Online;
+    };
+
+    let probe = tokio::task::spawn_blocking(move || {
+        let target = format!("{}:{}", host, port);
+        let addresses = target
+            .to_socket_addrs()
+            .map_err(|err| format!("cannot resolve {}: {}", host, err))?;
+        let mut last_error = format!("no addresses found for {}", host);
+        for address in addresses {
+            match TcpStream::connect_timeout(&address, timeout) {
+                Ok(_) => return Ok(()),
+                Err(err) => last_error = format!("cannot connect to {}: {}", target, err),
+            }
+        }
+        Err(last_error)
+    });
+
+    // Name resolution has no timeout of its own, so bound the whole probe.
+    match tokio::time::timeout(timeout * 2, probe).await {
+        Ok(Ok(Ok(()))) => Connectivity::Online,
+        Ok(Ok(Err(reason))) => Connectivity::Offline { reason },
+        Ok(Err(err)) => Connectivity::Offline {
+            reason: format!("connectivity probe failed: {}", err),
+        },
+        Err(_) => Connectivity::Offline {
+            reason: format!("no response from {} within {:?}", url, timeout * 2),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn parses_host_and_default_port() {
+        assert_eq!(
+            host_and_port(urls::OPENAI_API_BASE),
+            Some(("api.openai.com".to_string(), 443))
+        );
+        assert_eq!(
+            host_and_port("http://localhost:11434/v1"),
+            Some(("localhost".to_string(), 11434))
+        );
+        assert_eq!(host_and_port("not a url"), None);
+    }
+
+    #[tokio::test]
+    async fn probe_distinguishes_open_and_closed_ports() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let open = format!("http://{}", listener.local_addr().expect("addr"));
+        assert!(
+            probe_endpoint(&open, Duration::from_millis(500))
+                .await
+                .is_online()
+        );
+
+        drop(listener);
+        let closed = probe_endpoint(&open, Duration::from_millis(500)).await;
+        assert!(!closed.is_online());
+    }
+}
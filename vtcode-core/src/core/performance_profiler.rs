@@ -51,6 +51,8 @@ impl PerformanceProfiler {
 
     /// Record a completed operation
     pub fn record_operation(&self, operation: &str, duration: Duration, success: bool) {
+        // Read the map before taking an entry: `len()` waits on the entry's shard.
+        let memory_usage_mb = self.get_current_memory_mb();
         let mut entry = self
             .metrics
             .entry(operation.to_string())
@@ -79,8 +81,7 @@ impl PerformanceProfiler {
 
         entry.avg_duration = entry.total_duration / entry.operation_count as u32;
 
-        // Update memory usage
-        entry.memory_usage_mb = self.get_current_memory_mb();
+        entry.memory_usage_mb = memory_usage_mb;
     }
 
     /// Get current memory usage in MB
@@ -0,0 +1,292 @@
+//! Progress of long workspace scans
+//!
+//! Indexing and `vtcode analyze` read every source file of the workspace,
+//! which takes a while on large repositories. They report to a [`Progress`]
+//! when a phase starts (with the number of files when it is known), after
+//! each file and when the phase ends. [`Progress`] turns that into
+//! [`ProgressEvent`]s for a [`ProgressSink`]: per-file updates at most every
+//! [`EMIT_INTERVAL`], with the time left estimated by an [`EtaEstimator`].
+//! The CLI draws the events as a progress bar and chat sessions show them in
+//! the status bar.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use indicatif::HumanBytes;
+use serde::Serialize;
+
+use crate::core::turn_latency::format_duration;
+
+/// Minimum time between two [`ProgressEvent::Advanced`] events of a phase.
+pub const EMIT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Weight of the latest measurement in the smoothed time per file.
+const SMOOTHING: f64 = 0.3;
+
+/// Receives the events of a [`Progress`].
+pub type ProgressSink = Arc<dyn Fn(&ProgressEvent) + Send + Sync>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    PhaseStarted {
+        phase: String,
+        total_files: Option<u64>,
+    },
+    Advanced {
+        phase: String,
+        files: u64,
+        total_files: Option<u64>,
+        bytes: u64,
+        eta_ms: Option<u64>,
+    },
+    PhaseFinished {
+        phase: String,
+        files: u64,
+        bytes: u64,
+        elapsed_ms: u64,
+    },
+}
+
+impl ProgressEvent {
+    /// One line for a status bar, e.g. `indexing calls 1200/4800 files · ETA 8.0s`.
+    pub fn status_text(&self) -> String {
+        match self {
+            Self::PhaseStarted { phase, .. } => format!("{phase}…"),
+            Self::Advanced {
+                phase,
+                files,
+                total_files,
+                eta_ms,
+                ..
+            } => {
+                let mut text = match total_files {
+                    Some(total) => format!("{phase} {files}/{total} files"),
+                    None => format!("{phase} {files} files"),
+                };
+                if let Some(eta) = eta_ms {
+                    text.push_str(&format!(
+                        " · ETA {}",
+                        format_duration(Duration::from_millis(*eta))
+                    ));
+                }
+                text
+            }
+            Self::PhaseFinished {
+                phase,
+                files,
+                bytes,
+                elapsed_ms,
+            } => {
+                let elapsed = format_duration(Duration::from_millis(*elapsed_ms));
+                if *files == 0 {
+                    format!("{phase}: done in {elapsed}")
+                } else {
+                    format!(
+                        "{phase}: {files} files, {} in {elapsed}",
+                        HumanBytes(*bytes)
+                    )
+                }
+            }
+        }
+    }
+}
+
+/// Estimates the time left from a smoothed time per file, so a few slow
+/// files do not swing the estimate.
+#[derive(Debug, Clone, Default)]
+pub struct EtaEstimator {
+    per_file: Option<f64>,
+    last: Option<(u64, Duration)>,
+}
+
+impl EtaEstimator {
+    /// `done` files have been processed `elapsed` after the phase started.
+    pub fn record(&mut self, done: u64, elapsed: Duration) {
+        let (prev_done, prev_elapsed) = self.last.unwrap_or((0, Duration::ZERO));
+        if done > prev_done {
+            let rate =
+                elapsed.saturating_sub(prev_elapsed).as_secs_f64() / (done - prev_done) as f64;
+            self.per_file = Some(match self.per_file {
+                Some(smoothed) => smoothed + SMOOTHING * (rate - smoothed),
+                None => rate,
+            });
+            self.last = Some((done, elapsed));
+        }
+    }
+
+    /// Time left until `total` files are done, once a rate is known.
+    pub fn remaining(&self, done: u64, total: u64) -> Option<Duration> {
+        let per_file = self.per_file?;
+        Some(Duration::from_secs_f64(
+            per_file * total.saturating_sub(done) as f64,
+        ))
+    }
+}
+
+struct Phase {
+    name: String,
+    total_files: Option<u64>,
+    files: u64,
+    bytes: u64,
+    started: Instant,
+    last_emit: Option<Instant>,
+    eta: EtaEstimator,
+}
+
+/// Progress of one scan; does nothing without a sink.
+#[derive(Default)]
+pub struct Progress {
+    sink: Option<ProgressSink>,
+    phase: Option<Phase>,
+}
+
+impl Progress {
+    pub fn new(sink: ProgressSink) -> Self {
+        Self {
+            sink: Some(sink),
+            phase: None,
+        }
+    }
+
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Start `name`, finishing the phase before it.
+    pub fn start_phase(&mut self, name: &str, total_files: Option<u64>) {
+        self.finish_phase();
+        if self.sink.is_none() {
+            return;
+        }
+        self.phase = Some(Phase {
+            name: name.to_string(),
+            total_files,
+            files: 0,
+            bytes: 0,
+            started: Instant::now(),
+            last_emit: None,
+            eta: EtaEstimator::default(),
+        });
+        self.emit(ProgressEvent::PhaseStarted {
+            phase: name.to_string(),
+            total_files,
+        });
+    }
+
+    /// One more file of `bytes` processed in the current phase.
+    pub fn advance(&mut self, bytes: u64) {
+        let Some(phase) = self.phase.as_mut() else {
+            return;
+        };
+        phase.files += 1;
+        phase.bytes += bytes;
+        let now = Instant::now();
+        let last_file = phase.total_files == Some(phase.files);
+        if !last_file
+            && phase
+                .last_emit
+                .is_some_and(|last| now.duration_since(last) < EMIT_INTERVAL)
+        {
+            return;
+        }
+        phase.last_emit = Some(now);
+        phase
+            .eta
+            .record(phase.files, now.duration_since(phase.started));
+        let eta_ms = phase
+            .total_files
+            .and_then(|total| phase.eta.remaining(phase.files, total))
+            .map(|eta| eta.as_millis() as u64);
+        let event = ProgressEvent::Advanced {
+            phase: phase.name.clone(),
+            files: phase.files,
+            total_files: phase.total_files,
+            bytes: phase.bytes,
+            eta_ms,
+        };
+        self.emit(event);
+    }
+
+    pub fn finish_phase(&mut self) {
+        let Some(phase) = self.phase.take() else {
+            return;
+        };
+        self.emit(ProgressEvent::PhaseFinished {
+            phase: phase.name,
+            files: phase.files,
+            bytes: phase.bytes,
+            elapsed_ms: phase.started.elapsed().as_millis() as u64,
+        });
+    }
+
+    fn emit(&self, event: ProgressEvent) {
+        if let Some(sink) = &self.sink {
+            sink(&event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn smooths_the_rate_into_an_eta() {
+        let mut eta = EtaEstimator::default();
+        assert_eq!(eta.remaining(0, 10), None);
+
+        eta.record(10, Duration::from_secs(10));
+        assert_eq!(eta.remaining(10, 100), Some(Duration::from_secs(90)));
+
+        // A slow stretch moves the estimate only part of the way
+        eta.record(20, Duration::from_secs(30));
+        let remaining = eta.remaining(20, 100).unwrap();
+        assert!(remaining > Duration::from_secs(80) && remaining < Duration::from_secs(160));
+        assert_eq!(eta.remaining(100, 100), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn reports_phases_with_totals() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        let mut progress = Progress::new(Arc::new(move |event: &ProgressEvent| {
+            recorded.lock().unwrap().push(event.clone());
+        }));
+
+        progress.start_phase("analyze", Some(3));
+        for _ in 0..3 {
+            progress.advance(100);
+        }
+        progress.start_phase("git history", None);
+        progress.finish_phase();
+
+        let events = events.lock().unwrap();
+        assert!(matches!(
+            &events[0],
+            ProgressEvent::PhaseStarted { phase, total_files: Some(3) } if phase == "analyze"
+        ));
+        // The first and the last file are always reported
+        let advanced: Vec<u64> = events
+            .iter()
+            .filter_map(|event| match event {
+                ProgressEvent::Advanced { files, .. } => Some(*files),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(advanced.first(), Some(&1));
+        assert_eq!(advanced.last(), Some(&3));
+        assert!(events.iter().any(|event| matches!(
+            event,
+            ProgressEvent::PhaseFinished { phase, files: 3, bytes: 300, .. } if phase == "analyze"
+        )));
+        assert!(
+            events
+                .last()
+                .unwrap()
+                .status_text()
+                .starts_with("git history: done")
+        );
+    }
+}
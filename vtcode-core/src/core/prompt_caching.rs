@@ -419,12 +419,14 @@ impl PromptOptimizer {
                     content: system_prompt,
                     tool_calls: None,
                     tool_call_id: None,
+                    provenance: None,
                 },
                 Message {
                     role: MessageRole::User,
                     content: user_prompt,
                     tool_calls: None,
                     tool_call_id: None,
+                    provenance: None,
                 },
             ],
             system_prompt: None,
@@ -437,6 +439,10 @@ impl PromptOptimizer {
             parallel_tool_calls: None,
             parallel_tool_config: None,
             reasoning_effort: None,
+            json_mode: false,
+            response_schema: None,
+            code_execution: false,
+            seed: None,
         };
 
         let response = self
@@ -0,0 +1,489 @@
+//! Replay of archived sessions against a different system prompt
+//!
+//! Each user turn of a recording is sent again on top of the recorded history
+//! before it, so turns are compared independently of how earlier replayed
+//! turns went. Tools never run during a replay: a call matching a recorded one
+//! (same tool, same arguments) gets the recorded output, anything else gets an
+//! error saying nothing was recorded for it. Model responses are cached under
+//! `.vtcode/replay` keyed by the full request, so replaying the same session
+//! with the same prompt again gives the same result without new requests.
+//! Sessions recorded in reproducibility mode are replayed with the seed each
+//! turn had and the recorded temperature.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::core::reproducibility::{ReproducibilityRecord, turn_seed};
+use crate::llm::provider::{
+    LLMProvider, LLMRequest, Message, MessageRole, ToolCall, ToolDefinition,
+};
+use crate::ui::diff_renderer::{DiffLineType, DiffRenderer};
+use crate::utils::session_archive::SessionMessage;
+
+/// Response cache directory, relative to the workspace.
+pub const REPLAY_CACHE_DIR: &str = ".vtcode/replay";
+
+/// Returned for tool calls the recording has no output for.
+const NOT_RECORDED: &str =
+    "Replay: no output was recorded for this call; tools are not run during a replay.";
+
+/// Lines the diff looks ahead for a match after a changed line.
+const ALIGN_LOOKAHEAD: usize = 8;
+
+/// One thing the agent did in a turn.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceStep {
+    /// Text shown to the user
+    Output(String),
+    ToolCall {
+        name: String,
+        arguments: String,
+    },
+}
+
+/// What the agent said and which tools it called in one turn, in order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TurnTrace {
+    pub steps: Vec<TraceStep>,
+    /// The replay stopped at the step limit before the agent finished
+    pub truncated: bool,
+}
+
+impl TurnTrace {
+    /// Tool calls in order, as `name arguments`.
+    pub fn tool_sequence(&self) -> Vec<String> {
+        self.steps
+            .iter()
+            .filter_map(|step| match step {
+                TraceStep::ToolCall { name, arguments } => {
+                    Some(format!("{name} {}", canonical_arguments(arguments)))
+                }
+                TraceStep::Output(_) => None,
+            })
+            .collect()
+    }
+
+    /// The trace as lines for diffing: output text line by line and tool calls
+    /// as `→ name arguments`.
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for step in &self.steps {
+            match step {
+                TraceStep::Output(text) => {
+                    lines.extend(text.trim().lines().map(str::to_string));
+                }
+                TraceStep::ToolCall { name, arguments } => {
+                    lines.push(format!("→ {name} {}", canonical_arguments(arguments)));
+                }
+            }
+        }
+        if self.truncated {
+            lines.push("… stopped at the step limit".to_string());
+        }
+        lines
+    }
+}
+
+/// A user turn from a recording with the history it was answered on.
+#[derive(Debug, Clone)]
+pub struct RecordedTurn {
+    pub prompt: String,
+    pub history: Vec<Message>,
+    pub recorded: TurnTrace,
+}
+
+/// Split recorded messages into user turns.
+pub fn recorded_turns(messages: &[SessionMessage]) -> Vec<RecordedTurn> {
+    let mut turns: Vec<RecordedTurn> = Vec::new();
+    for (index, message) in messages.iter().enumerate() {
+        match message.role {
+            MessageRole::User => turns.push(RecordedTurn {
+                prompt: message.content.clone(),
+                history: messages[..index].iter().map(Message::from).collect(),
+                recorded: TurnTrace::default(),
+            }),
+            MessageRole::Assistant => {
+                let Some(turn) = turns.last_mut() else {
+                    continue;
+                };
+                if !message.content.trim().is_empty() {
+                    turn.recorded
+                        .steps
+                        .push(TraceStep::Output(message.content.clone()));
+                }
+                for call in message.tool_calls.iter().flatten() {
+                    turn.recorded.steps.push(TraceStep::ToolCall {
+                        name: call.function.name.clone(),
+                        arguments: call.function.arguments.clone(),
+                    });
+                }
+            }
+            MessageRole::System | MessageRole::Tool => {}
+        }
+    }
+    turns
+}
+
+/// Tool outputs from a recording, looked up by tool name and arguments.
+#[derive(Debug, Default)]
+pub struct RecordedToolOutputs {
+    outputs: HashMap<(String, String), Vec<String>>,
+    served: HashMap<(String, String), usize>,
+}
+
+impl RecordedToolOutputs {
+    pub fn from_messages(messages: &[SessionMessage]) -> Self {
+        let mut calls: HashMap<&str, (String, String)> = HashMap::new();
+        let mut outputs: HashMap<(String, String), Vec<String>> = HashMap::new();
+        for message in messages {
+            for call in message.tool_calls.iter().flatten() {
+                calls.insert(
+                    call.id.as_str(),
+                    (
+                        call.function.name.clone(),
+                        canonical_arguments(&call.function.arguments),
+                    ),
+                );
+            }
+            if message.role == MessageRole::Tool
+                && let Some(key) = message.tool_call_id.as_deref().and_then(|id| calls.get(id))
+            {
+                outputs
+                    .entry(key.clone())
+                    .or_default()
+                    .push(message.content.clone());
+            }
+        }
+        Self {
+            outputs,
+            served: HashMap::new(),
+        }
+    }
+
+    /// The recorded output for a call. Repeated identical calls get the
+    /// recorded outputs in order, then the last one again.
+    pub fn output(&mut self, name: &str, arguments: &str) -> Option<String> {
+        let key = (name.to_string(), canonical_arguments(arguments));
+        let outputs = self.outputs.get(&key)?;
+        let served = self.served.entry(key).or_insert(0);
+        let output = outputs.get(*served).or_else(|| outputs.last())?.clone();
+        *served += 1;
+        Some(output)
+    }
+}
+
+/// Arguments re-serialized so that formatting differences do not matter.
+fn canonical_arguments(arguments: &str) -> String {
+    serde_json::from_str::<Value>(arguments)
+        .map(|value| value.to_string())
+        .unwrap_or_else(|_| arguments.trim().to_string())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedResponse {
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default)]
+    tool_calls: Option<Vec<ToolCall>>,
+}
+
+/// Model responses keyed by a hash of the request.
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: Option<PathBuf>,
+}
+
+impl ResponseCache {
+    pub fn new(workspace: &Path) -> Self {
+        Self {
+            dir: Some(workspace.join(REPLAY_CACHE_DIR)),
+        }
+    }
+
+    /// A cache that never hits and stores nothing.
+    pub fn disabled() -> Self {
+        Self { dir: None }
+    }
+
+    fn path(&self, request: &LLMRequest) -> Result<Option<PathBuf>> {
+        let Some(dir) = &self.dir else {
+            return Ok(None);
+        };
+        let payload = serde_json::to_vec(request).context("failed to serialize request")?;
+        let key = format!("{:x}", Sha256::digest(&payload));
+        Ok(Some(dir.join(format!("{key}.json"))))
+    }
+
+    fn get(&self, request: &LLMRequest) -> Result<Option<CachedResponse>> {
+        let Some(path) = self.path(request)? else {
+            return Ok(None);
+        };
+        let Ok(payload) = fs::read_to_string(&path) else {
+            return Ok(None);
+        };
+        Ok(serde_json::from_str(&payload).ok())
+    }
+
+    fn put(&self, request: &LLMRequest, response: &CachedResponse) -> Result<()> {
+        let Some(path) = self.path(request)? else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        let payload = serde_json::to_string_pretty(response)?;
+        fs::write(&path, payload).with_context(|| format!("failed to write {}", path.display()))
+    }
+}
+
+/// Requests made by a replay and how many were answered from the cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReplayStats {
+    pub requests: usize,
+    pub cache_hits: usize,
+}
+
+/// Replays recorded turns with a given system prompt.
+pub struct PromptReplay<'a> {
+    provider: &'a dyn LLMProvider,
+    model: String,
+    system_prompt: String,
+    tools: Vec<ToolDefinition>,
+    cache: ResponseCache,
+    max_steps: usize,
+    reproducibility: Option<ReproducibilityRecord>,
+    stats: ReplayStats,
+}
+
+impl<'a> PromptReplay<'a> {
+    pub fn new(
+        provider: &'a dyn LLMProvider,
+        model: impl Into<String>,
+        system_prompt: impl Into<String>,
+        tools: Vec<ToolDefinition>,
+        cache: ResponseCache,
+    ) -> Self {
+        Self {
+            provider,
+            model: model.into(),
+            system_prompt: system_prompt.into(),
+            tools,
+            cache,
+            max_steps: 10,
+            reproducibility: None,
+            stats: ReplayStats::default(),
+        }
+    }
+
+    /// Model requests allowed per turn before the replay of it stops.
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps.max(1);
+        self
+    }
+
+    /// Seeds and temperature the session was recorded with, if any.
+    pub fn with_reproducibility(mut self, record: Option<ReproducibilityRecord>) -> Self {
+        self.reproducibility = record;
+        self
+    }
+
+    pub fn stats(&self) -> ReplayStats {
+        self.stats
+    }
+
+    pub async fn replay_turn(
+        &mut self,
+        turn: &RecordedTurn,
+        outputs: &mut RecordedToolOutputs,
+    ) -> Result<TurnTrace> {
+        let mut messages = turn.history.clone();
+        messages.push(Message::user(turn.prompt.clone()));
+        let mut trace = TurnTrace::default();
+        let seed = self
+            .reproducibility
+            .as_ref()
+            .map(|record| turn_seed(record.seed, &turn.prompt));
+
+        for _ in 0..self.max_steps {
+            let request = self.request(messages.clone(), seed);
+            let response = self.respond(&request).await?;
+            let content = response.content.unwrap_or_default();
+            if !content.trim().is_empty() {
+                trace.steps.push(TraceStep::Output(content.clone()));
+            }
+
+            let calls = response.tool_calls.unwrap_or_default();
+            if calls.is_empty() {
+                return Ok(trace);
+            }
+            messages.push(Message::assistant_with_tools(content, calls.clone()));
+            for call in calls {
+                let output = outputs
+                    .output(&call.function.name, &call.function.arguments)
+                    .unwrap_or_else(|| json!({ "error": NOT_RECORDED }).to_string());
+                trace.steps.push(TraceStep::ToolCall {
+                    name: call.function.name,
+                    arguments: call.function.arguments,
+                });
+                messages.push(Message::tool_response(call.id, output));
+            }
+        }
+
+        trace.truncated = true;
+        Ok(trace)
+    }
+
+    fn request(&self, messages: Vec<Message>, seed: Option<u64>) -> LLMRequest {
+        LLMRequest {
+            messages,
+            system_prompt: Some(self.system_prompt.clone()),
+            tools: (!self.tools.is_empty()).then(|| self.tools.clone()),
+            model: self.model.clone(),
+            max_tokens: None,
+            temperature: Some(
+                self.reproducibility
+                    .as_ref()
+                    .map_or(0.0, |record| record.temperature),
+            ),
+            stream: false,
+            tool_choice: None,
+            parallel_tool_calls: None,
+            parallel_tool_config: None,
+            reasoning_effort: None,
+            json_mode: false,
+            response_schema: None,
+            code_execution: false,
+            seed,
+        }
+    }
+
+    async fn respond(&mut self, request: &LLMRequest) -> Result<CachedResponse> {
+        self.stats.requests += 1;
+        if let Some(cached) = self.cache.get(request)? {
+            self.stats.cache_hits += 1;
+            return Ok(cached);
+        }
+        let response = self
+            .provider
+            .generate(request.clone())
+            .await
+            .map_err(|err| anyhow::anyhow!("{err}"))?;
+        let cached = CachedResponse {
+            content: response.content,
+            tool_calls: response.tool_calls,
+        };
+        self.cache.put(request, &cached)?;
+        Ok(cached)
+    }
+}
+
+/// Pair up the lines of two traces for side-by-side display: equal lines
+/// share a row, changed lines are placed next to each other, and lines on one
+/// side only leave the other side empty.
+pub fn align_lines(left: &[String], right: &[String]) -> Vec<(Option<String>, Option<String>)> {
+    let diff = DiffRenderer::new(false, ALIGN_LOOKAHEAD, false).generate_diff(
+        &left.join("\n"),
+        &right.join("\n"),
+        "",
+    );
+    let mut rows = Vec::new();
+    let mut removed: Vec<String> = Vec::new();
+    let mut added: Vec<String> = Vec::new();
+    let flush = |rows: &mut Vec<_>, removed: &mut Vec<String>, added: &mut Vec<String>| {
+        let count = removed.len().max(added.len());
+        let mut removed = removed.drain(..);
+        let mut added = added.drain(..);
+        for _ in 0..count {
+            rows.push((removed.next(), added.next()));
+        }
+    };
+    for line in diff.lines {
+        match line.line_type {
+            DiffLineType::Removed => removed.push(line.content),
+            DiffLineType::Added => added.push(line.content),
+            DiffLineType::Context => {
+                flush(&mut rows, &mut removed, &mut added);
+                rows.push((Some(line.content.clone()), Some(line.content)));
+            }
+            DiffLineType::Header => {}
+        }
+    }
+    flush(&mut rows, &mut removed, &mut added);
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recording() -> Vec<SessionMessage> {
+        let mut assistant = SessionMessage::new(MessageRole::Assistant, "Looking.");
+        assistant.tool_calls = Some(vec![ToolCall::function(
+            "call_1".to_string(),
+            "read_file".to_string(),
+            r#"{ "path": "src/lib.rs" }"#.to_string(),
+        )]);
+        vec![
+            SessionMessage::new(MessageRole::User, "What does lib.rs export?"),
+            assistant,
+            SessionMessage::with_tool_call_id(
+                MessageRole::Tool,
+                "pub mod config;",
+                Some("call_1".to_string()),
+            ),
+            SessionMessage::new(MessageRole::Assistant, "It exports `config`."),
+            SessionMessage::new(MessageRole::User, "Thanks"),
+            SessionMessage::new(MessageRole::Assistant, "You're welcome."),
+        ]
+    }
+
+    #[test]
+    fn splits_turns_and_serves_recorded_tool_outputs() {
+        let messages = recording();
+        let turns = recorded_turns(&messages);
+        assert_eq!(turns.len(), 2);
+        assert!(turns[0].history.is_empty());
+        assert_eq!(turns[1].history.len(), 4);
+        assert_eq!(
+            turns[0].recorded.lines(),
+            [
+                "Looking.",
+                r#"→ read_file {"path":"src/lib.rs"}"#,
+                "It exports `config`."
+            ]
+        );
+
+        let mut outputs = RecordedToolOutputs::from_messages(&messages);
+        assert_eq!(
+            outputs.output("read_file", r#"{"path":"src/lib.rs"}"#),
+            Some("pub mod config;".to_string())
+        );
+        assert_eq!(
+            outputs.output("read_file", r#"{"path": "src/lib.rs"}"#),
+            Some("pub mod config;".to_string())
+        );
+        assert_eq!(outputs.output("read_file", r#"{"path":"README.md"}"#), None);
+    }
+
+    #[test]
+    fn aligns_changed_lines_side_by_side() {
+        let left: Vec<String> = ["a", "b", "c"].iter().map(|s| s.to_string()).collect();
+        let right: Vec<String> = ["a", "B", "c", "d"].iter().map(|s| s.to_string()).collect();
+        let some = |s: &str| Some(s.to_string());
+        assert_eq!(
+            align_lines(&left, &right),
+            vec![
+                (some("a"), some("a")),
+                (some("b"), some("B")),
+                (some("c"), some("c")),
+                (None, some("d")),
+            ]
+        );
+    }
+}
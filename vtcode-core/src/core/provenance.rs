@@ -0,0 +1,152 @@
+//! Where each block of context came from
+//!
+//! Messages in the conversation carry a [`Provenance`] so the rest of the
+//! context pipeline can tell the user's words from a tool result, a carried
+//! over summary or a snippet gathered without being asked for. Trimming under
+//! budget pressure drops the sources that are cheapest to recover first (see
+//! [`Provenance::trim_rank`]), history summaries label each message with its
+//! [`Provenance::citation`] and cite those labels, and `/context` lists the
+//! blocks grouped by source.
+
+use crate::llm::provider::{Message, MessageRole, ToolCall};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Argument names that identify what a tool call acted on, in order of preference.
+const TARGET_ARGS: &[&str] = &["path", "file_path", "command", "pattern", "url", "query"];
+const MAX_TARGET_CHARS: usize = 80;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Provenance {
+    /// Typed by the user
+    UserInput,
+    /// Written by the model
+    Model,
+    /// Output of a tool call; `target` is the path, command or pattern it used
+    ToolResult {
+        tool: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        target: Option<String>,
+    },
+    /// Carried over within the session, such as the summary of trimmed history
+    Memory,
+    /// Project instructions such as AGENTS.md
+    ProjectDoc { path: String },
+    /// Added by a context provider without being asked for
+    AutoGathered { source: String },
+}
+
+impl Provenance {
+    /// Provenance of the result of `call`.
+    pub fn tool_call(call: &ToolCall) -> Self {
+        let args = serde_json::from_str::<Value>(&call.function.arguments).unwrap_or(Value::Null);
+        Self::ToolResult {
+            tool: call.function.name.clone(),
+            target: tool_target(&args),
+        }
+    }
+
+    /// Best guess for a message that was never tagged.
+    pub fn infer(message: &Message) -> Self {
+        match message.role {
+            MessageRole::User => Self::UserInput,
+            MessageRole::Assistant => Self::Model,
+            MessageRole::Tool => Self::ToolResult {
+                tool: "tool".to_string(),
+                target: None,
+            },
+            MessageRole::System => Self::Memory,
+        }
+    }
+
+    /// Order in which blocks are trimmed under budget pressure, lowest first.
+    /// Tool results can be fetched again and gathered context is refreshed
+    /// each request, while the user's own words cannot be recovered.
+    pub fn trim_rank(&self) -> u8 {
+        match self {
+            Self::ToolResult { .. } => 0,
+            Self::AutoGathered { .. } => 1,
+            Self::Model => 2,
+            Self::Memory => 3,
+            Self::ProjectDoc { .. } => 4,
+            Self::UserInput => 5,
+        }
+    }
+
+    /// Short label used to cite the block, e.g. `tool:read_file src/lib.rs`.
+    pub fn citation(&self) -> String {
+        match self {
+            Self::UserInput => "user".to_string(),
+            Self::Model => "assistant".to_string(),
+            Self::ToolResult { tool, target } => match target {
+                Some(target) => format!("tool:{tool} {target}"),
+                None => format!("tool:{tool}"),
+            },
+            Self::Memory => "memory".to_string(),
+            Self::ProjectDoc { path } => format!("doc:{path}"),
+            Self::AutoGathered { source } => format!("auto:{source}"),
+        }
+    }
+
+    /// Name of the group the block is listed under in `/context`.
+    pub fn category(&self) -> &'static str {
+        match self {
+            Self::UserInput => "user input",
+            Self::Model => "model replies",
+            Self::ToolResult { .. } => "tool results",
+            Self::Memory => "memory",
+            Self::ProjectDoc { .. } => "project docs",
+            Self::AutoGathered { .. } => "auto-gathered",
+        }
+    }
+}
+
+/// What a tool call acted on, from its arguments.
+fn tool_target(args: &Value) -> Option<String> {
+    let target = TARGET_ARGS.iter().find_map(|name| match args.get(*name)? {
+        Value::String(value) => Some(value.clone()),
+        Value::Array(items) => Some(
+            items
+                .iter()
+                .filter_map(Value::as_str)
+                .collect::<Vec<_>>()
+                .join(" "),
+        ),
+        _ => None,
+    })?;
+    let target = target.trim();
+    if target.is_empty() {
+        return None;
+    }
+    let mut clipped: String = target.chars().take(MAX_TARGET_CHARS).collect();
+    if clipped.len() < target.len() {
+        clipped.push('…');
+    }
+    Some(clipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tags_tool_results_with_their_target() {
+        let call = ToolCall::function(
+            "call_1".to_string(),
+            "read_file".to_string(),
+            r#"{"path":"src/lib.rs","max_lines":40}"#.to_string(),
+        );
+        let provenance = Provenance::tool_call(&call);
+        assert_eq!(provenance.citation(), "tool:read_file src/lib.rs");
+
+        let message = Message::tool_response("call_1".to_string(), "fn main() {}".to_string())
+            .with_provenance(provenance.clone());
+        assert_eq!(message.provenance(), provenance);
+        assert_eq!(
+            Message::user("hi".to_string()).provenance(),
+            Provenance::UserInput
+        );
+        assert!(provenance.trim_rank() < Provenance::UserInput.trim_rank());
+    }
+}
@@ -0,0 +1,136 @@
+//! Reproducible runs: fixed sampling seeds and temperature per turn.
+//!
+//! A turn's seed is derived from the session seed and the turn's prompt, so a
+//! replay recomputes the same seed for the same prompt without having to line
+//! up turn numbers with the recording.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::config::AgentReproducibilityConfig;
+use crate::llm::provider::LLMRequest;
+
+/// Seeds a session ran with, stored in its archive.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReproducibilityRecord {
+    /// Session seed every turn seed derives from
+    pub seed: u64,
+    /// Temperature forced on every request
+    pub temperature: f32,
+    /// Seed of each turn, in order
+    #[serde(default)]
+    pub turn_seeds: Vec<u64>,
+}
+
+/// Seed and temperature state of a session in reproducibility mode.
+#[derive(Debug, Clone)]
+pub struct Reproducibility {
+    record: ReproducibilityRecord,
+    current: Option<u64>,
+}
+
+impl Reproducibility {
+    /// `None` unless `[agent.reproducibility]` is enabled. Without a
+    /// configured seed the session picks one at random; it is recorded either
+    /// way.
+    pub fn new(config: &AgentReproducibilityConfig) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        Some(Self {
+            record: ReproducibilityRecord {
+                seed: config.seed.unwrap_or_else(rand::random),
+                temperature: config.temperature,
+                turn_seeds: Vec::new(),
+            },
+            current: None,
+        })
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.record.seed
+    }
+
+    pub fn temperature(&self) -> f32 {
+        self.record.temperature
+    }
+
+    /// Starts a turn on `prompt` and returns its seed.
+    pub fn begin_turn(&mut self, prompt: &str) -> u64 {
+        let seed = turn_seed(self.record.seed, prompt);
+        self.record.turn_seeds.push(seed);
+        self.current = Some(seed);
+        seed
+    }
+
+    /// Pins the request to the current turn's seed and the configured
+    /// temperature.
+    pub fn apply(&self, request: &mut LLMRequest) {
+        request.seed = self.current;
+        request.temperature = Some(self.record.temperature);
+    }
+
+    pub fn record(&self) -> &ReproducibilityRecord {
+        &self.record
+    }
+}
+
+/// Seed of the turn started by `prompt` in a session seeded with `seed`.
+pub fn turn_seed(seed: u64, prompt: &str) -> u64 {
+    let mut hasher = Sha256::new();
+    hasher.update(seed.to_le_bytes());
+    hasher.update(prompt.as_bytes());
+    let digest = hasher.finalize();
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    u64::from_le_bytes(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turn_seeds_follow_session_seed_and_prompt() {
+        let config = AgentReproducibilityConfig {
+            enabled: true,
+            seed: Some(42),
+            temperature: 0.0,
+        };
+        let mut first = Reproducibility::new(&config).unwrap();
+        let mut second = Reproducibility::new(&config).unwrap();
+
+        let seed = first.begin_turn("fix the build");
+        assert_eq!(seed, second.begin_turn("fix the build"));
+        assert_eq!(seed, turn_seed(42, "fix the build"));
+        assert_ne!(seed, turn_seed(43, "fix the build"));
+        assert_ne!(seed, first.begin_turn("now the tests"));
+        assert_eq!(first.record().turn_seeds.len(), 2);
+
+        let mut request = LLMRequest {
+            messages: Vec::new(),
+            system_prompt: None,
+            tools: None,
+            model: "model".to_string(),
+            max_tokens: None,
+            temperature: Some(0.7),
+            stream: false,
+            tool_choice: None,
+            parallel_tool_calls: None,
+            parallel_tool_config: None,
+            reasoning_effort: None,
+            json_mode: false,
+            response_schema: None,
+            code_execution: false,
+            seed: None,
+        };
+        second.apply(&mut request);
+        assert_eq!(request.seed, Some(seed));
+        assert_eq!(request.temperature, Some(0.0));
+    }
+
+    #[test]
+    fn disabled_without_flag() {
+        assert!(Reproducibility::new(&AgentReproducibilityConfig::default()).is_none());
+    }
+}
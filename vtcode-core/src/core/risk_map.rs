@@ -0,0 +1,442 @@
+//! Workspace risk map.
+//!
+//! Ranks source files by how risky they are to change, combining recent git
+//! churn, file size, control-flow complexity (decision points counted from the
+//! tree-sitter syntax tree) and TODO/FIXME density. Each signal is converted to
+//! a percentile rank across the workspace before weighting, so the score says
+//! how a file compares with its neighbours rather than against fixed limits.
+//!
+//! `vtcode analyze --risk` stores the map in `.vtcode/risk_map.json`; chat
+//! sessions load it to brief the agent on high-risk files and to preview diffs
+//! before those files are edited.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use ignore::WalkBuilder;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::LazyLock;
+
+use crate::core::progress::Progress;
+use crate::tools::tree_sitter::TreeSitterAnalyzer;
+
+/// File, relative to the workspace, where the risk map is stored
+pub const RISK_MAP_FILE: &str = ".vtcode/risk_map.json";
+
+const CHURN_WEIGHT: f64 = 0.35;
+const COMPLEXITY_WEIGHT: f64 = 0.25;
+const SIZE_WEIGHT: f64 = 0.2;
+const TODO_WEIGHT: f64 = 0.2;
+
+const HIGH_RISK_SCORE: f64 = 75.0;
+const MEDIUM_RISK_SCORE: f64 = 50.0;
+
+/// Syntax node kinds that add a branch to the control flow.
+const DECISION_KINDS: &[&str] = &[
+    // Rust
+    "if_expression",
+    "match_arm",
+    "while_expression",
+    "loop_expression",
+    "for_expression",
+    // Python
+    "if_statement",
+    "elif_clause",
+    "for_statement",
+    "while_statement",
+    "except_clause",
+    "conditional_expression",
+    "boolean_operator",
+    "case_clause",
+    // JavaScript, TypeScript and Java
+    "for_in_statement",
+    "enhanced_for_statement",
+    "do_statement",
+    "switch_case",
+    "switch_block_statement_group",
+    "catch_clause",
+    "ternary_expression",
+    // Go
+    "expression_case",
+    "type_case",
+    "communication_case",
+    // Swift
+    "guard_statement",
+    "repeat_while_statement",
+    "switch_entry",
+    "catch_block",
+];
+
+pub(crate) static TODO_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(TODO|FIXME|HACK|XXX)\b").expect("valid TODO pattern"));
+
+/// How risky a file is to change, relative to the rest of the workspace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskLevel {
+    Low,
+    Medium,
+    High,
+}
+
+impl RiskLevel {
+    fn from_score(score: f64) -> Self {
+        if score >= HIGH_RISK_SCORE {
+            Self::High
+        } else if score >= MEDIUM_RISK_SCORE {
+            Self::Medium
+        } else {
+            Self::Low
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        }
+    }
+}
+
+/// Risk signals and score of one file.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FileRisk {
+    /// Workspace-relative path with `/` separators
+    pub path: String,
+    /// Weighted percentile score from 0 to 100
+    pub score: f64,
+    pub level: RiskLevel,
+    /// Commits touching the file within the churn window
+    pub churn: usize,
+    pub lines: usize,
+    /// Control-flow decision points
+    pub complexity: usize,
+    /// TODO, FIXME, HACK and XXX markers
+    pub todos: usize,
+}
+
+impl FileRisk {
+    fn measured(path: String, churn: usize, lines: usize, complexity: usize, todos: usize) -> Self {
+        Self {
+            path,
+            score: 0.0,
+            level: RiskLevel::Low,
+            churn,
+            lines,
+            complexity,
+            todos,
+        }
+    }
+
+    /// TODO markers per thousand lines.
+    pub fn todo_density(&self) -> f64 {
+        if self.lines == 0 {
+            0.0
+        } else {
+            self.todos as f64 * 1000.0 / self.lines as f64
+        }
+    }
+}
+
+/// Limits for building a risk map.
+#[derive(Debug, Clone)]
+pub struct RiskOptions {
+    /// Days of git history counted as churn
+    pub churn_days: u32,
+    /// Files larger than this are skipped
+    pub max_file_bytes: u64,
+}
+
+impl Default for RiskOptions {
+    fn default() -> Self {
+        Self {
+            churn_days: 180,
+            max_file_bytes: 1024 * 1024,
+        }
+    }
+}
+
+/// Files of a workspace ranked from most to least risky.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskMap {
+    pub generated_at: DateTime<Utc>,
+    pub churn_days: u32,
+    pub files: Vec<FileRisk>,
+}
+
+impl RiskMap {
+    /// Measure every source file tree-sitter can parse and rank them.
+    pub fn compute(workspace: &Path, options: &RiskOptions) -> Result<Self> {
+        Self::compute_with_progress(workspace, options, &mut Progress::disabled())
+    }
+
+    /// [`RiskMap::compute`], reporting each phase to `progress`.
+    pub fn compute_with_progress(
+        workspace: &Path,
+        options: &RiskOptions,
+        progress: &mut Progress,
+    ) -> Result<Self> {
+        progress.start_phase("git history", None);
+        let churn = git_churn(workspace, options.churn_days);
+        let mut analyzer = TreeSitterAnalyzer::new()?;
+
+        progress.start_phase("listing files", None);
+        let mut candidates = Vec::new();
+        for entry in WalkBuilder::new(workspace).build().filter_map(|e| e.ok()) {
+            if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+                continue;
+            }
+            let relative = entry
+                .path()
+                .strip_prefix(workspace)
+                .unwrap_or(entry.path())
+                .to_path_buf();
+            let size = entry.metadata().map_or(0, |metadata| metadata.len());
+            if relative.starts_with(".vtcode") || size > options.max_file_bytes {
+                continue;
+            }
+            let Ok(language) = analyzer.detect_language_from_path(&relative) else {
+                continue;
+            };
+            progress.advance(size);
+            candidates.push((entry.into_path(), relative, language, size));
+        }
+
+        progress.start_phase("analyzing", Some(candidates.len() as u64));
+        let mut files = Vec::new();
+        for (absolute, relative, language, size) in candidates {
+            progress.advance(size);
+            let Ok(source) = fs::read_to_string(&absolute) else {
+                continue;
+            };
+            let complexity = analyzer
+                .parse(&source, language)
+                .map(|tree| decision_points(tree.root_node()))
+                .unwrap_or(0);
+            let path = normalize_path(&relative);
+            files.push(FileRisk::measured(
+                path.clone(),
+                churn.get(&path).copied().unwrap_or(0),
+                source.lines().count(),
+                complexity,
+                TODO_PATTERN.find_iter(&source).count(),
+            ));
+        }
+        progress.finish_phase();
+
+        Ok(Self {
+            generated_at: Utc::now(),
+            churn_days: options.churn_days,
+            files: rank(files),
+        })
+    }
+
+    /// Load the stored risk map, if `vtcode analyze --risk` has been run.
+    pub fn load(workspace: &Path) -> Result<Option<Self>> {
+        let path = workspace.join(RISK_MAP_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let map = serde_json::from_str(&content)
+            .with_context(|| format!("failed to parse {}", path.display()))?;
+        Ok(Some(map))
+    }
+
+    pub fn save(&self, workspace: &Path) -> Result<PathBuf> {
+        let path = workspace.join(RISK_MAP_FILE);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        Ok(path)
+    }
+
+    /// Risk entry for a workspace-relative or absolute path.
+    pub fn file(&self, workspace: &Path, path: &str) -> Option<&FileRisk> {
+        let candidate = Path::new(path);
+        let relative = candidate.strip_prefix(workspace).unwrap_or(candidate);
+        let key = normalize_path(relative);
+        self.files.iter().find(|file| file.path == key)
+    }
+
+    pub fn high_risk(&self) -> impl Iterator<Item = &FileRisk> {
+        self.files
+            .iter()
+            .filter(|file| file.level == RiskLevel::High)
+    }
+
+    /// System prompt section listing the riskiest files, if there are any.
+    pub fn prompt_section(&self, limit: usize) -> Option<String> {
+        let risky: Vec<&FileRisk> = self.high_risk().take(limit).collect();
+        if risky.is_empty() {
+            return None;
+        }
+        let mut section = String::from(
+            "These files rank highest in the workspace risk map (frequent changes, complex control flow, size, open TODOs). Read the surrounding code before editing them, keep changes small, and expect the user to review a diff preview before each edit.\n",
+        );
+        for file in risky {
+            section.push_str(&format!(
+                "- {} (risk {:.0}: {} commits in {} days, {} lines, {} decision points, {} TODOs)\n",
+                file.path,
+                file.score,
+                file.churn,
+                self.churn_days,
+                file.lines,
+                file.complexity,
+                file.todos
+            ));
+        }
+        Some(section)
+    }
+}
+
+/// Score files by the weighted percentile rank of each signal, riskiest first.
+///
+/// Without any git history churn carries no information, so its weight is
+/// spread over the other signals.
+fn rank(mut files: Vec<FileRisk>) -> Vec<FileRisk> {
+    let churn_weight = if files.iter().any(|file| file.churn > 0) {
+        CHURN_WEIGHT
+    } else {
+        0.0
+    };
+    let total_weight = churn_weight + COMPLEXITY_WEIGHT + SIZE_WEIGHT + TODO_WEIGHT;
+    let churn = percentiles(&files, |file| file.churn as f64);
+    let complexity = percentiles(&files, |file| file.complexity as f64);
+    let size = percentiles(&files, |file| file.lines as f64);
+    let todos = percentiles(&files, FileRisk::todo_density);
+    for (index, file) in files.iter_mut().enumerate() {
+        let score = churn_weight * churn[index]
+            + COMPLEXITY_WEIGHT * complexity[index]
+            + SIZE_WEIGHT * size[index]
+            + TODO_WEIGHT * todos[index];
+        file.score = (score / total_weight * 1000.0).round() / 10.0;
+        file.level = RiskLevel::from_score(file.score);
+    }
+    files.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.path.cmp(&b.path)));
+    files
+}
+
+/// Share of the other files with a strictly lower value, from 0 to 1.
+fn percentiles(files: &[FileRisk], value: impl Fn(&FileRisk) -> f64) -> Vec<f64> {
+    let values: Vec<f64> = files.iter().map(&value).collect();
+    let mut sorted = values.clone();
+    sorted.sort_by(f64::total_cmp);
+    let others = (values.len().max(2) - 1) as f64;
+    values
+        .iter()
+        .map(|value| sorted.partition_point(|other| other < value) as f64 / others)
+        .collect()
+}
+
+/// Decision points (branches) in a syntax tree.
+pub(crate) fn decision_points(node: tree_sitter::Node) -> usize {
+    let mut count = 0;
+    let mut cursor = node.walk();
+    let mut stack = vec![node];
+    while let Some(node) = stack.pop() {
+        if DECISION_KINDS.contains(&node.kind()) {
+            count += 1;
+        }
+        stack.extend(node.children(&mut cursor));
+    }
+    count
+}
+
+/// Commits per file within the last `days` days; empty outside a git repository.
+fn git_churn(workspace: &Path, days: u32) -> HashMap<String, usize> {
+    let output = Command::new("git")
+        .args([
+            "log",
+            &format!("--since={} days ago", days),
+            "--name-only",
+            "--format=",
+            "--no-renames",
+            "--relative",
+        ])
+        .current_dir(workspace)
+        .output();
+    let mut churn = HashMap::new();
+    if let Ok(output) = output
+        && output.status.success()
+    {
+        for path in String::from_utf8_lossy(&output.stdout).lines() {
+            if !path.is_empty() {
+                *churn.entry(path.to_string()).or_insert(0) += 1;
+            }
+        }
+    }
+    churn
+}
+
+fn normalize_path(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    path.strip_prefix("./").unwrap_or(&path).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn counts_decision_points_from_the_syntax_tree() {
+        let source = "fn f(x: i32) -> i32 {\n    let identifier = 1;\n    if x > 0 { for _ in 0..x {} }\n    match x { 0 => 1, _ => identifier }\n}\n";
+        let mut analyzer = TreeSitterAnalyzer::new().expect("analyzer");
+        let tree = analyzer
+            .parse(source, crate::tools::tree_sitter::LanguageSupport::Rust)
+            .expect("parse");
+        assert_eq!(decision_points(tree.root_node()), 4);
+    }
+
+    #[test]
+    fn ranks_files_by_combined_signals() {
+        let files = rank(vec![
+            FileRisk::measured("src/quiet.rs".into(), 0, 40, 1, 0),
+            FileRisk::measured("src/hot.rs".into(), 12, 900, 80, 6),
+            FileRisk::measured("src/big.rs".into(), 1, 1200, 30, 0),
+        ]);
+        let order: Vec<&str> = files.iter().map(|file| file.path.as_str()).collect();
+        assert_eq!(order, vec!["src/hot.rs", "src/big.rs", "src/quiet.rs"]);
+        assert_eq!(files[0].level, RiskLevel::High);
+        assert_eq!(files[2].score, 0.0);
+    }
+
+    #[test]
+    fn stores_and_looks_up_files() {
+        let dir = TempDir::new().expect("tempdir");
+        fs::create_dir_all(dir.path().join("src")).expect("src");
+        fs::write(
+            dir.path().join("src/lib.rs"),
+            "// TODO: split\nfn a(x: bool) { if x {} }\n",
+        )
+        .expect("lib.rs");
+        fs::write(dir.path().join("src/small.rs"), "fn b() {}\n").expect("small.rs");
+        fs::write(dir.path().join("notes.md"), "TODO\n").expect("notes");
+
+        let map = RiskMap::compute(dir.path(), &RiskOptions::default()).expect("compute");
+        assert_eq!(map.files.len(), 2);
+        map.save(dir.path()).expect("save");
+
+        let loaded = RiskMap::load(dir.path()).expect("load").expect("stored");
+        let lib = loaded
+            .file(dir.path(), &dir.path().join("src/lib.rs").to_string_lossy())
+            .expect("lib.rs entry");
+        assert_eq!((lib.todos, lib.complexity, lib.lines), (1, 1, 2));
+        assert!(loaded.file(dir.path(), "./src/small.rs").is_some());
+        assert!(
+            loaded
+                .prompt_section(5)
+                .is_some_and(|section| section.contains("- src/lib.rs"))
+        );
+    }
+}
@@ -173,6 +173,10 @@ impl Router {
                     parallel_tool_calls: None,
                     parallel_tool_config: None,
                     reasoning_effort,
+                    json_mode: false,
+                    response_schema: None,
+                    code_execution: false,
+                    seed: None,
                 };
                 if let Ok(resp) = provider.generate(req).await {
                     if let Some(text) = resp.content {
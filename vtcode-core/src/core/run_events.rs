@@ -0,0 +1,445 @@
+//! Machine-readable progress events for automated runs
+//!
+//! With `--events-file`, the chat loop writes one JSON object per line as the
+//! run progresses so CI jobs and external monitors can follow and gate
+//! full-auto runs:
+//!
+//! ```text
+//! {"seq":1,"ts":1760000000,"event":"turn_started","turn":1,"input":"fix the build"}
+//! {"seq":2,"ts":1760000002,"event":"tool_call","turn":1,"tool":"edit_file","args":{...}}
+//! {"seq":3,"ts":1760000002,"event":"tool_result","turn":1,"tool":"edit_file","success":true}
+//! {"seq":4,"ts":1760000002,"event":"file_changed","turn":1,"path":"src/lib.rs","change":"modified"}
+//! {"seq":5,"ts":1760000005,"event":"turn_completed","turn":1,"status":"completed"}
+//! {"seq":6,"ts":1760000005,"event":"run_finished","status":"success","exit_code":0,"turns":1}
+//! ```
+//!
+//! File output goes through a [`JournalWriter`]; `-` streams events to stderr
+//! because stdout carries the terminal UI.
+//!
+//! The log also counts edits, failed tool calls, policy denials and provider
+//! errors, from which [`RunEventLog::completed_status`] decides the exit status
+//! of full-auto and `--fail-on` runs.
+
+use crate::core::change_ledger::ChangeKind;
+use crate::core::journal::{JournalOptions, JournalWriter};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+
+/// Line the agent ends its final reply with in full-auto runs.
+pub const SELF_ASSESSMENT_PREFIX: &str = "Task status:";
+
+/// System prompt section asking the agent to assess its own result.
+pub const SELF_ASSESSMENT_PROMPT: &str = "[Task Status]\nThis run is unattended. When you stop working on the task, end your final reply with a line `Task status: resolved` if the task is done, or `Task status: unresolved - <reason>` if it is not.";
+
+/// How a run ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Success,
+    Interrupted,
+    Failed,
+    /// A provider request failed
+    ProviderError,
+    /// A tool call was denied by policy or a guardrail
+    PolicyDenied,
+    /// The agent reported that it did not finish the task
+    Unresolved,
+    /// A `--fail-on` condition was met
+    FailOn,
+}
+
+impl RunStatus {
+    /// Process exit status reported for the run.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            RunStatus::Success => 0,
+            RunStatus::Failed => 1,
+            RunStatus::ProviderError => 3,
+            RunStatus::PolicyDenied => 4,
+            RunStatus::Unresolved => 5,
+            RunStatus::FailOn => 6,
+            RunStatus::Interrupted => 130,
+        }
+    }
+}
+
+/// Outcomes that fail an otherwise successful run (`--fail-on`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailOn {
+    /// Any file was created, modified or deleted
+    Edits,
+    /// Any tool call failed, or a cited answer kept unresolved citations
+    Warnings,
+}
+
+impl FailOn {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "edits" => Some(Self::Edits),
+            "warnings" => Some(Self::Warnings),
+            _ => None,
+        }
+    }
+
+    pub fn allowed_values() -> &'static [&'static str] {
+        &["edits", "warnings"]
+    }
+}
+
+/// The agent's verdict from the last line of its final reply, `None` when
+/// the reply does not include one.
+pub fn self_assessment(reply: &str) -> Option<bool> {
+    let line = reply.lines().rev().find(|line| !line.trim().is_empty())?;
+    let verdict = line
+        .trim()
+        .trim_matches('*')
+        .strip_prefix(SELF_ASSESSMENT_PREFIX)?
+        .trim_start()
+        .to_ascii_lowercase();
+    if verdict.starts_with("unresolved") {
+        Some(false)
+    } else if verdict.starts_with("resolved") {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum RunEvent<'a> {
+    TurnStarted {
+        turn: usize,
+        input: &'a str,
+    },
+    ToolCall {
+        turn: usize,
+        tool: &'a str,
+        args: &'a Value,
+    },
+    ToolResult {
+        turn: usize,
+        tool: &'a str,
+        success: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<&'a str>,
+    },
+    FileChanged {
+        turn: usize,
+        path: &'a str,
+        change: &'static str,
+    },
+    TurnCompleted {
+        turn: usize,
+        status: &'a str,
+    },
+    ProviderError {
+        turn: usize,
+        error: &'a str,
+    },
+    RunFinished {
+        status: RunStatus,
+        exit_code: i32,
+        turns: usize,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<&'a str>,
+    },
+}
+
+#[derive(Serialize)]
+struct Envelope<'a> {
+    seq: u64,
+    ts: i64,
+    #[serde(flatten)]
+    event: RunEvent<'a>,
+}
+
+enum EventSink {
+    Disabled,
+    Journal(JournalWriter),
+    Stderr,
+}
+
+/// Writer for the run's JSONL event stream.
+pub struct RunEventLog {
+    sink: EventSink,
+    seq: AtomicU64,
+    turn: AtomicUsize,
+    finished: AtomicBool,
+    edits: AtomicUsize,
+    tool_failures: AtomicUsize,
+    denials: AtomicUsize,
+    provider_errors: AtomicUsize,
+}
+
+impl RunEventLog {
+    /// Write events to `target`, replacing any previous file; `-` means stderr.
+    pub fn open(target: &Path) -> Result<Self> {
+        if target == Path::new("-") {
+            return Ok(Self::with_sink(EventSink::Stderr));
+        }
+        if let Some(parent) = target.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        std::fs::File::create(target)
+            .with_context(|| format!("Failed to create events file {}", target.display()))?;
+        let journal = JournalWriter::spawn(target, JournalOptions::default())
+            .context("Failed to start the events writer")?;
+        Ok(Self::with_sink(EventSink::Journal(journal)))
+    }
+
+    pub fn disabled() -> Self {
+        Self::with_sink(EventSink::Disabled)
+    }
+
+    fn with_sink(sink: EventSink) -> Self {
+        Self {
+            sink,
+            seq: AtomicU64::new(0),
+            turn: AtomicUsize::new(0),
+            finished: AtomicBool::new(false),
+            edits: AtomicUsize::new(0),
+            tool_failures: AtomicUsize::new(0),
+            denials: AtomicUsize::new(0),
+            provider_errors: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !matches!(self.sink, EventSink::Disabled)
+    }
+
+    /// Start a new turn; later events are attributed to it.
+    pub fn turn_started(&self, input: &str) {
+        let turn = self.turn.fetch_add(1, Ordering::SeqCst) + 1;
+        self.emit(RunEvent::TurnStarted { turn, input });
+    }
+
+    pub fn tool_call(&self, tool: &str, args: &Value) {
+        self.emit(RunEvent::ToolCall {
+            turn: self.current_turn(),
+            tool,
+            args,
+        });
+    }
+
+    pub fn tool_result(&self, tool: &str, success: bool, error: Option<&str>) {
+        if !success {
+            self.tool_failures.fetch_add(1, Ordering::SeqCst);
+        }
+        self.emit(RunEvent::ToolResult {
+            turn: self.current_turn(),
+            tool,
+            success,
+            error,
+        });
+    }
+
+    /// Report a tool's JSON output, reading `success` and `error` from it.
+    pub fn tool_output(&self, tool: &str, output: &Value) {
+        let error = output.get("error").map(|error| match error.as_str() {
+            Some(text) => text.to_string(),
+            None => error.to_string(),
+        });
+        let success = output
+            .get("success")
+            .and_then(Value::as_bool)
+            .unwrap_or(error.is_none());
+        self.tool_result(tool, success, error.as_deref());
+    }
+
+    /// Report a tool call that policy or a guardrail refused to run.
+    pub fn tool_denied(&self, tool: &str, reason: &str) {
+        self.denials.fetch_add(1, Ordering::SeqCst);
+        self.emit(RunEvent::ToolResult {
+            turn: self.current_turn(),
+            tool,
+            success: false,
+            error: Some(reason),
+        });
+    }
+
+    pub fn provider_error(&self, error: &str) {
+        self.provider_errors.fetch_add(1, Ordering::SeqCst);
+        self.emit(RunEvent::ProviderError {
+            turn: self.current_turn(),
+            error,
+        });
+    }
+
+    pub fn file_changed(&self, path: &str, kind: ChangeKind) {
+        self.edits.fetch_add(1, Ordering::SeqCst);
+        let change = match kind {
+            ChangeKind::Created => "created",
+            ChangeKind::Modified => "modified",
+            ChangeKind::Deleted => "deleted",
+        };
+        self.emit(RunEvent::FileChanged {
+            turn: self.current_turn(),
+            path,
+            change,
+        });
+    }
+
+    /// `status` is `completed`, `cancelled` or `aborted`.
+    pub fn turn_completed(&self, status: &str) {
+        self.emit(RunEvent::TurnCompleted {
+            turn: self.current_turn(),
+            status,
+        });
+    }
+
+    /// Record the end of the run and flush. Only the first call is written.
+    pub fn finish(&self, status: RunStatus, error: Option<&str>) {
+        if self.finished.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        self.emit(RunEvent::RunFinished {
+            status,
+            exit_code: status.exit_code(),
+            turns: self.current_turn(),
+            error,
+        });
+        if let EventSink::Journal(journal) = &self.sink {
+            journal.flush();
+        }
+    }
+
+    /// Status of a run that ran to completion, given the `--fail-on`
+    /// conditions and the agent's self-assessment.
+    pub fn completed_status(&self, fail_on: &[FailOn], resolved: Option<bool>) -> RunStatus {
+        let count = |counter: &AtomicUsize| counter.load(Ordering::SeqCst);
+        if count(&self.provider_errors) > 0 {
+            return RunStatus::ProviderError;
+        }
+        if count(&self.denials) > 0 {
+            return RunStatus::PolicyDenied;
+        }
+        if resolved == Some(false) {
+            return RunStatus::Unresolved;
+        }
+        let triggered = fail_on.iter().any(|condition| match condition {
+            FailOn::Edits => count(&self.edits) > 0,
+            FailOn::Warnings => count(&self.tool_failures) > 0,
+        });
+        if triggered {
+            RunStatus::FailOn
+        } else {
+            RunStatus::Success
+        }
+    }
+
+    fn current_turn(&self) -> usize {
+        self.turn.load(Ordering::SeqCst)
+    }
+
+    fn emit(&self, event: RunEvent<'_>) {
+        if !self.is_enabled() {
+            return;
+        }
+        let envelope = Envelope {
+            seq: self.seq.fetch_add(1, Ordering::SeqCst) + 1,
+            ts: chrono::Utc::now().timestamp(),
+            event,
+        };
+        let Ok(line) = serde_json::to_string(&envelope) else {
+            return;
+        };
+        match &self.sink {
+            EventSink::Disabled => {}
+            EventSink::Journal(journal) => journal.append(line),
+            EventSink::Stderr => {
+                let mut stderr = std::io::stderr().lock();
+                let _ = writeln!(stderr, "{line}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn writes_one_event_per_line() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("ci/events.jsonl");
+        let log = RunEventLog::open(&path).expect("open");
+        log.turn_started("fix the build");
+        log.tool_call("edit_file", &json!({"path": "src/lib.rs"}));
+        log.tool_output("edit_file", &json!({"success": true}));
+        log.file_changed("src/lib.rs", ChangeKind::Modified);
+        log.turn_completed("completed");
+        log.finish(RunStatus::Success, None);
+        log.finish(RunStatus::Failed, Some("ignored"));
+
+        let events: Vec<Value> = std::fs::read_to_string(&path)
+            .expect("read")
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("json line"))
+            .collect();
+        let kinds: Vec<&str> = events
+            .iter()
+            .map(|event| event["event"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                "turn_started",
+                "tool_call",
+                "tool_result",
+                "file_changed",
+                "turn_completed",
+                "run_finished"
+            ]
+        );
+        assert_eq!(events[3]["change"], "modified");
+        assert_eq!(events[3]["turn"], 1);
+        assert_eq!(events[5]["exit_code"], 0);
+        assert_eq!(events[5]["seq"], 6);
+    }
+
+    #[test]
+    fn completed_status_applies_fail_on_and_self_assessment() {
+        let log = RunEventLog::disabled();
+        assert_eq!(
+            log.completed_status(&[FailOn::Edits], None),
+            RunStatus::Success
+        );
+        log.file_changed("src/lib.rs", ChangeKind::Modified);
+        assert_eq!(
+            log.completed_status(&[FailOn::Warnings], None),
+            RunStatus::Success
+        );
+        assert_eq!(
+            log.completed_status(&[FailOn::Edits], None),
+            RunStatus::FailOn
+        );
+        assert_eq!(
+            log.completed_status(&[], Some(false)),
+            RunStatus::Unresolved
+        );
+        log.tool_denied("run_terminal_cmd", "denied by policy");
+        assert_eq!(
+            log.completed_status(&[], Some(true)),
+            RunStatus::PolicyDenied
+        );
+        assert_eq!(RunStatus::PolicyDenied.exit_code(), 4);
+
+        assert_eq!(
+            self_assessment("Fixed it.\n\nTask status: resolved"),
+            Some(true)
+        );
+        assert_eq!(
+            self_assessment("**Task status: unresolved - tests still fail**"),
+            Some(false)
+        );
+        assert_eq!(self_assessment("Done."), None);
+    }
+}
@@ -0,0 +1,274 @@
+//! First-run scan for files that look like secrets
+//!
+//! The first time a chat session runs in a workspace, [`scan_workspace`] looks
+//! for environment files, private keys and credential files. The paths found
+//! are stored per workspace in `~/.vtcode/cache/sensitive_paths/` and handed to
+//! the guardrails, which block reads of them until the user allows each one.
+//! The denied paths are also installed with [`set_read_denied`] so that tools
+//! walking the whole workspace, like grep_search and the indexers, drop them
+//! from their results ([`is_read_denied`]).
+//! Patterns that would keep them out of the agent's file listings are offered
+//! as `.vtcodegitignore` additions.
+
+use crate::utils::dot_config::{DotManager, workspace_key};
+use crate::utils::vtcodegitignore::VTCodeGitignore;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use glob::Pattern;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use walkdir::{DirEntry, WalkDir};
+
+/// How deep the scan descends below the workspace root.
+const MAX_DEPTH: usize = 6;
+/// Entries visited before the scan gives up on large trees.
+const MAX_ENTRIES: usize = 20_000;
+const SKIPPED_DIRS: &[&str] = &[
+    ".git",
+    ".vtcode",
+    "node_modules",
+    "target",
+    "vendor",
+    "dist",
+    "build",
+    ".venv",
+    "venv",
+];
+
+/// File name patterns and what a match probably holds.
+const SENSITIVE_PATTERNS: &[(&str, &str)] = &[
+    (".env", "environment variables"),
+    (".env.*", "environment variables"),
+    ("*.pem", "a certificate or private key"),
+    ("*.key", "a private key"),
+    ("id_rsa", "an SSH private key"),
+    ("id_ecdsa", "an SSH private key"),
+    ("id_ed25519", "an SSH private key"),
+    ("*.p12", "a certificate bundle"),
+    ("*.pfx", "a certificate bundle"),
+    ("*.jks", "a Java keystore"),
+    ("*.keystore", "a keystore"),
+    ("credentials.json", "credentials"),
+    ("service-account*.json", "cloud service account credentials"),
+    ("secrets.json", "secrets"),
+    ("secrets.yaml", "secrets"),
+    ("secrets.yml", "secrets"),
+    (".netrc", "login credentials"),
+    (".pgpass", "database passwords"),
+    (".npmrc", "registry tokens"),
+    (".pypirc", "registry tokens"),
+];
+
+/// Environment file variants that are meant to be committed.
+const TEMPLATE_SUFFIXES: &[&str] = &[".example", ".sample", ".template", ".dist"];
+
+/// A file the scan flagged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SensitiveFile {
+    /// Workspace-relative, `/`-separated path
+    pub path: String,
+    /// The pattern it matched
+    pub pattern: &'static str,
+    /// What the file probably holds
+    pub reason: &'static str,
+}
+
+/// Files that look like secrets, in path order.
+pub fn scan_workspace(workspace: &Path) -> Vec<SensitiveFile> {
+    let patterns: Vec<(Pattern, &'static str, &'static str)> = SENSITIVE_PATTERNS
+        .iter()
+        .filter_map(|(pattern, reason)| {
+            Pattern::new(pattern)
+                .ok()
+                .map(|compiled| (compiled, *pattern, *reason))
+        })
+        .collect();
+
+    let mut found = Vec::new();
+    let entries = WalkDir::new(workspace)
+        .max_depth(MAX_DEPTH)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| entry.depth() == 0 || !is_skipped_dir(entry))
+        .take(MAX_ENTRIES)
+        .filter_map(|entry| entry.ok());
+    for entry in entries {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy();
+        if TEMPLATE_SUFFIXES
+            .iter()
+            .any(|suffix| name.ends_with(suffix))
+        {
+            continue;
+        }
+        let Some((_, pattern, reason)) = patterns
+            .iter()
+            .find(|(compiled, _, _)| compiled.matches(&name))
+        else {
+            continue;
+        };
+        let Ok(relative) = entry.path().strip_prefix(workspace) else {
+            continue;
+        };
+        found.push(SensitiveFile {
+            path: relative.to_string_lossy().replace('\\', "/"),
+            pattern,
+            reason,
+        });
+    }
+    found
+}
+
+fn is_skipped_dir(entry: &DirEntry) -> bool {
+    entry.file_type().is_dir()
+        && entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| SKIPPED_DIRS.contains(&name))
+}
+
+/// Patterns to add to `.vtcodegitignore` so that none of `files` shows up in
+/// the agent's listings, leaving out files it already excludes.
+pub async fn ignore_suggestions(workspace: &Path, files: &[SensitiveFile]) -> Vec<String> {
+    let existing = VTCodeGitignore::from_directory(workspace).await.ok();
+    let mut suggestions = Vec::new();
+    for file in files {
+        let excluded = existing
+            .as_ref()
+            .is_some_and(|ignore| ignore.should_exclude(&workspace.join(&file.path)));
+        if !excluded && !suggestions.iter().any(|entry| entry == file.pattern) {
+            suggestions.push(file.pattern.to_string());
+        }
+    }
+    suggestions
+}
+
+/// Sensitive paths of one workspace and the ones the user allowed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SensitivePaths {
+    pub scanned_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub flagged: BTreeSet<String>,
+    #[serde(default)]
+    pub allowed: BTreeSet<String>,
+}
+
+impl SensitivePaths {
+    pub fn from_scan(files: &[SensitiveFile]) -> Self {
+        Self {
+            scanned_at: Some(Utc::now()),
+            flagged: files.iter().map(|file| file.path.clone()).collect(),
+            allowed: BTreeSet::new(),
+        }
+    }
+
+    /// Paths recorded for `workspace`; `None` before its first scan.
+    pub fn load(workspace: &Path) -> Option<Self> {
+        let content = std::fs::read_to_string(state_path(workspace)?).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    pub fn save(&self, workspace: &Path) -> Result<()> {
+        let Some(path) = state_path(workspace) else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Flagged paths the user has not allowed.
+    pub fn denied(&self) -> impl Iterator<Item = &str> {
+        self.flagged.difference(&self.allowed).map(String::as_str)
+    }
+
+    /// Let the agent read `path`; returns whether it was denied before.
+    pub fn allow(&mut self, path: &str) -> bool {
+        self.flagged.contains(path) && self.allowed.insert(path.to_string())
+    }
+}
+
+static READ_DENIED: Lazy<RwLock<Arc<BTreeSet<String>>>> =
+    Lazy::new(|| RwLock::new(Arc::new(BTreeSet::new())));
+
+/// Install the workspace-relative paths that tools enumerating files must
+/// leave out, replacing the previous set.
+pub fn set_read_denied<'a>(paths: impl IntoIterator<Item = &'a str>) {
+    *READ_DENIED.write() = Arc::new(paths.into_iter().map(str::to_string).collect());
+}
+
+/// Whether `path`, absolute under `root` or relative to it, is a sensitive
+/// file installed by [`set_read_denied`].
+pub fn is_read_denied(root: &Path, path: &Path) -> bool {
+    let denied = READ_DENIED.read().clone();
+    if denied.is_empty() {
+        return false;
+    }
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    let key = relative
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+    denied.contains(&key)
+}
+
+fn state_path(workspace: &Path) -> Option<PathBuf> {
+    let dot = DotManager::new().ok()?;
+    Some(
+        dot.cache_dir("sensitive_paths")
+            .join(format!("{}.json", workspace_key(workspace))),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn flags_secrets_and_suggests_ignore_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for file in [
+            ".env",
+            ".env.example",
+            "config/credentials.json",
+            "deploy/tls/server.pem",
+            "node_modules/pkg/.env",
+            "src/main.rs",
+        ] {
+            let path = root.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "x").unwrap();
+        }
+        std::fs::write(root.join(".vtcodegitignore"), "*.pem\n").unwrap();
+
+        let found = scan_workspace(root);
+        let paths: Vec<&str> = found.iter().map(|file| file.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [".env", "config/credentials.json", "deploy/tls/server.pem"]
+        );
+        assert_eq!(
+            ignore_suggestions(root, &found).await,
+            [".env", "credentials.json"]
+        );
+
+        let mut state = SensitivePaths::from_scan(&found);
+        assert!(state.allow("config/credentials.json"));
+        assert!(!state.allow("src/main.rs"));
+        let denied: Vec<&str> = state.denied().collect();
+        assert_eq!(denied, [".env", "deploy/tls/server.pem"]);
+    }
+}
@@ -0,0 +1,283 @@
+//! Resumable copies of chat sessions
+//!
+//! After every completed turn the chat loop writes the whole session to
+//! `.vtcode/sessions/<id>.json` in the workspace: the conversation with tool
+//! results restored to their full text, the summary of trimmed messages and
+//! the decision ledger. The file is replaced atomically, so a crash leaves
+//! the state of the last finished turn. `vtcode resume` loads it and carries
+//! on under the same id.
+//!
+//! This is separate from the archive under `~/.vtcode/sessions`, which keeps
+//! a transcript of each session for reading and sharing once it ends.
+
+use crate::core::decision_tracker::DecisionTracker;
+use crate::llm::provider::Message;
+use crate::utils::session_archive::SessionMessage;
+use crate::utils::utils::write_atomic;
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const SESSION_STORE_DIR: &str = ".vtcode/sessions";
+/// Bump when the layout of [`StoredSession`] changes incompatibly.
+const STORE_SCHEMA: u32 = 1;
+const PREVIEW_CHARS: usize = 80;
+
+/// Everything needed to continue a session.
+#[derive(Serialize, Deserialize)]
+pub struct StoredSession {
+    pub schema: u32,
+    pub id: String,
+    pub provider: String,
+    pub model: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Completed turns
+    pub turns: usize,
+    /// Summary of messages already trimmed from `messages`
+    #[serde(default)]
+    pub history_summary: Option<String>,
+    /// Conversation with every tool result in full
+    pub messages: Vec<SessionMessage>,
+    pub decisions: DecisionTracker,
+}
+
+impl StoredSession {
+    /// The conversation as provider messages.
+    pub fn history(&self) -> Vec<Message> {
+        self.messages.iter().map(Message::from).collect()
+    }
+
+    /// Start of the first user message, for listings.
+    pub fn first_prompt_preview(&self) -> Option<String> {
+        self.messages
+            .iter()
+            .find(|message| message.role == crate::llm::provider::MessageRole::User)
+            .map(|message| preview(&message.content))
+    }
+}
+
+/// Session state of the running turn loop, borrowed for one save.
+pub struct SessionSnapshot<'a> {
+    pub provider: &'a str,
+    pub model: &'a str,
+    pub title: Option<&'a str>,
+    pub turns: usize,
+    pub history_summary: Option<&'a str>,
+    /// Conversation with spilled tool results already restored
+    pub messages: &'a [Message],
+    pub decisions: &'a DecisionTracker,
+}
+
+/// Saved sessions of one workspace.
+pub struct SessionStore {
+    dir: PathBuf,
+    id: String,
+    created_at: DateTime<Utc>,
+}
+
+impl SessionStore {
+    /// Store for a new session with a fresh id.
+    pub fn create(workspace: &Path) -> Self {
+        let created_at = Utc::now();
+        Self {
+            dir: workspace.join(SESSION_STORE_DIR),
+            id: created_at.format("%Y%m%dT%H%M%S%3f").to_string(),
+            created_at,
+        }
+    }
+
+    /// Store that keeps writing to the file of a resumed session.
+    pub fn resume(workspace: &Path, session: &StoredSession) -> Self {
+        Self {
+            dir: workspace.join(SESSION_STORE_DIR),
+            id: session.id.clone(),
+            created_at: session.created_at,
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn path(&self) -> PathBuf {
+        session_path(&self.dir, &self.id)
+    }
+
+    /// Replace the saved copy with `state`.
+    pub fn save(&self, state: SessionSnapshot<'_>) -> Result<PathBuf> {
+        #[derive(Serialize)]
+        struct Borrowed<'a> {
+            schema: u32,
+            id: &'a str,
+            provider: &'a str,
+            model: &'a str,
+            title: Option<&'a str>,
+            created_at: DateTime<Utc>,
+            updated_at: DateTime<Utc>,
+            turns: usize,
+            history_summary: Option<&'a str>,
+            messages: Vec<SessionMessage>,
+            decisions: &'a DecisionTracker,
+        }
+
+        let record = Borrowed {
+            schema: STORE_SCHEMA,
+            id: &self.id,
+            provider: state.provider,
+            model: state.model,
+            title: state.title,
+            created_at: self.created_at,
+            updated_at: Utc::now(),
+            turns: state.turns,
+            history_summary: state.history_summary,
+            messages: state.messages.iter().map(SessionMessage::from).collect(),
+            decisions: state.decisions,
+        };
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("failed to create {}", self.dir.display()))?;
+        let path = self.path();
+        write_atomic(&path, &serde_json::to_vec(&record)?)?;
+        Ok(path)
+    }
+}
+
+/// Load the session `id` saved in `workspace`.
+pub fn load_session(workspace: &Path, id: &str) -> Result<StoredSession> {
+    let path = session_path(&workspace.join(SESSION_STORE_DIR), id);
+    let data =
+        fs::read(&path).with_context(|| format!("no saved session at {}", path.display()))?;
+    let session: StoredSession = serde_json::from_slice(&data)
+        .with_context(|| format!("saved session {id} is unreadable"))?;
+    if session.schema > STORE_SCHEMA {
+        bail!(
+            "saved session {id} was written by a newer vtcode (schema {})",
+            session.schema
+        );
+    }
+    Ok(session)
+}
+
+/// Sessions saved in `workspace`, most recently updated first. Unreadable
+/// files are skipped.
+pub fn list_sessions(workspace: &Path) -> Vec<StoredSession> {
+    let dir = workspace.join(SESSION_STORE_DIR);
+    let mut sessions = Vec::new();
+    if let Ok(entries) = fs::read_dir(&dir) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if let Some(id) = name.strip_suffix(".json")
+                && let Ok(session) = load_session(workspace, id)
+            {
+                sessions.push(session);
+            }
+        }
+    }
+    sessions.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    sessions
+}
+
+/// Find a saved session by id, unique id prefix, or `latest` for the most
+/// recently updated one.
+pub fn find_session(workspace: &Path, id: &str) -> Result<StoredSession> {
+    let id = id.trim().trim_end_matches(".json");
+    let sessions = list_sessions(workspace);
+    if id.eq_ignore_ascii_case("latest") {
+        return sessions
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("No saved sessions in this workspace"));
+    }
+    let mut matches: Vec<StoredSession> = sessions
+        .into_iter()
+        .filter(|session| session.id.starts_with(id))
+        .collect();
+    if let Some(position) = matches.iter().position(|session| session.id == id) {
+        return Ok(matches.swap_remove(position));
+    }
+    match matches.len() {
+        0 => Err(anyhow!(
+            "No saved session matches '{}'. Run `vtcode resume --list` to see them.",
+            id
+        )),
+        1 => Ok(matches.remove(0)),
+        count => Err(anyhow!(
+            "'{}' matches {} sessions; use a longer identifier",
+            id,
+            count
+        )),
+    }
+}
+
+fn session_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{id}.json"))
+}
+
+fn preview(text: &str) -> String {
+    let line = text.lines().next().unwrap_or_default().trim();
+    if line.chars().count() <= PREVIEW_CHARS {
+        return line.to_string();
+    }
+    let mut truncated: String = line.chars().take(PREVIEW_CHARS - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::provider::ToolCall;
+
+    #[test]
+    fn saves_and_resumes_full_sessions() {
+        let workspace = tempfile::tempdir().unwrap();
+        let mut decisions = DecisionTracker::new();
+        decisions.record_goal("Fix the failing parser test".to_string());
+        let messages = vec![
+            Message::user("Fix the failing parser test".to_string()),
+            Message::assistant_with_tools(
+                String::new(),
+                vec![ToolCall::function(
+                    "call_1".to_string(),
+                    "read_file".to_string(),
+                    "{\"path\":\"src/parser.rs\"}".to_string(),
+                )],
+            ),
+            Message::tool_response("call_1".to_string(), "fn parse() {}".repeat(500)),
+            Message::assistant("Fixed.".to_string()),
+        ];
+
+        let store = SessionStore::create(workspace.path());
+        let state = || SessionSnapshot {
+            provider: "openai",
+            model: "gpt-5",
+            title: Some("Parser fix"),
+            turns: 1,
+            history_summary: Some("Earlier: set up the project."),
+            messages: &messages,
+            decisions: &decisions,
+        };
+        store.save(state()).unwrap();
+
+        let session = find_session(workspace.path(), "latest").unwrap();
+        assert_eq!(session.id, store.id());
+        assert_eq!(session.history(), messages);
+        assert_eq!(session.turns, 1);
+        assert_eq!(session.decisions.get_decisions().len(), 1);
+        assert_eq!(
+            session.first_prompt_preview().as_deref(),
+            Some("Fix the failing parser test")
+        );
+
+        let resumed = SessionStore::resume(workspace.path(), &session);
+        resumed.save(state()).unwrap();
+        assert_eq!(resumed.path(), store.path());
+        assert_eq!(list_sessions(workspace.path()).len(), 1);
+        assert!(find_session(workspace.path(), &store.id()[..8]).is_ok());
+        assert!(find_session(workspace.path(), "missing").is_err());
+    }
+}
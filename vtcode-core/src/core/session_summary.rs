@@ -0,0 +1,420 @@
+//! End-of-session change summaries
+//!
+//! [`SessionSummary`] is assembled from the [`ChangeLedger`] (what was changed
+//! and for which request) and the [`DecisionTracker`] (what failed, and whether
+//! anything was verified after the last edit). It renders to a standalone
+//! `summary.md` and to an entry for the project's `CHANGELOG.md`.
+
+use crate::config::constants::tools;
+use crate::core::change_ledger::{ChangeKind, ChangeLedger, FileSummary};
+use crate::core::decision_tracker::{Action, DecisionOutcome, DecisionTracker};
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const MAX_GOAL_CHARS: usize = 120;
+const MAX_FAILURE_FOLLOW_UPS: usize = 5;
+const CHANGELOG_FILE: &str = "CHANGELOG.md";
+const UNRELEASED_HEADING: &str = "## [Unreleased]";
+
+/// Command fragments that count as verifying an edit.
+const VERIFICATION_MARKERS: &[&str] = &[
+    "test", "check", "clippy", "build", "lint", "pytest", "jest", "vitest", "tsc",
+];
+
+/// Concise description of the changes made during one session.
+#[derive(Debug, Clone)]
+pub struct SessionSummary {
+    pub session_id: String,
+    /// UTC date the summary was generated, `YYYY-MM-DD`
+    pub date: String,
+    pub files: Vec<FileSummary>,
+    /// Requests that led to changes, paired with the files each touched
+    pub goals: Vec<(String, Vec<String>)>,
+    pub follow_ups: Vec<String>,
+}
+
+impl SessionSummary {
+    /// Summarize the session, or `None` when no files changed.
+    pub fn build(
+        session_id: impl Into<String>,
+        changes: &ChangeLedger,
+        decisions: &DecisionTracker,
+    ) -> Option<Self> {
+        let files = changes.files();
+        if files.is_empty() {
+            return None;
+        }
+
+        let goals = changes
+            .requests()
+            .iter()
+            .enumerate()
+            .filter_map(|(index, goal)| {
+                let touched: Vec<String> = files
+                    .iter()
+                    .filter(|file| file.requests.contains(&index))
+                    .map(|file| file.path.clone())
+                    .collect();
+                (!touched.is_empty()).then(|| (condense_goal(goal), touched))
+            })
+            .collect();
+
+        Some(Self {
+            session_id: session_id.into(),
+            date: Utc::now().format("%Y-%m-%d").to_string(),
+            follow_ups: follow_ups(&files, changes, decisions),
+            files,
+            goals,
+        })
+    }
+
+    /// Headline used for the changelog entry.
+    pub fn title(&self) -> String {
+        self.goals
+            .first()
+            .map(|(goal, _)| goal.clone())
+            .unwrap_or_else(|| format!("{} files changed", self.files.len()))
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        out.push_str("# Session summary\n\n");
+        out.push_str(&format!(
+            "Session `{}` · {} · {}\n\n",
+            self.session_id,
+            self.date,
+            pluralize_files(self.files.len())
+        ));
+
+        out.push_str("## What changed\n\n");
+        for file in &self.files {
+            out.push_str(&format!("- {}\n", describe_file(file)));
+        }
+
+        if !self.goals.is_empty() {
+            out.push_str("\n## Why\n\n");
+            for (goal, touched) in &self.goals {
+                out.push_str(&format!("- {} ({})\n", goal, touched.join(", ")));
+            }
+        }
+
+        out.push_str("\n## Files touched\n\n");
+        for file in &self.files {
+            out.push_str(&format!("- `{}`\n", file.path));
+        }
+
+        out.push_str("\n## Follow-ups\n\n");
+        if self.follow_ups.is_empty() {
+            out.push_str("- None\n");
+        } else {
+            for follow_up in &self.follow_ups {
+                out.push_str(&format!("- {}\n", follow_up));
+            }
+        }
+        out
+    }
+
+    /// Entry inserted under the `[Unreleased]` heading of `CHANGELOG.md`.
+    pub fn changelog_entry(&self) -> String {
+        let mut out = format!("#### {}: {}\n\n", self.date, self.title());
+        for file in &self.files {
+            out.push_str(&format!("- {}\n", describe_file(file)));
+        }
+        out
+    }
+
+    /// Write `summary.md` to `.vtcode/sessions/<id>/` in the workspace.
+    pub fn write_session_file(&self, workspace: &Path) -> Result<PathBuf> {
+        let dir = workspace
+            .join(".vtcode")
+            .join("sessions")
+            .join(&self.session_id);
+        fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+        let path = dir.join("summary.md");
+        fs::write(&path, self.to_markdown())
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        Ok(path)
+    }
+
+    /// Add the changelog entry to the workspace `CHANGELOG.md`, creating it if needed.
+    pub fn append_to_changelog(&self, workspace: &Path) -> Result<PathBuf> {
+        let path = workspace.join(CHANGELOG_FILE);
+        let existing = if path.exists() {
+            fs::read_to_string(&path)
+                .with_context(|| format!("failed to read {}", path.display()))?
+        } else {
+            String::new()
+        };
+        let updated = insert_changelog_entry(&existing, &self.changelog_entry());
+        fs::write(&path, updated).with_context(|| format!("failed to write {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+/// Place `entry` first under `## [Unreleased]`, adding the heading when it is missing.
+fn insert_changelog_entry(existing: &str, entry: &str) -> String {
+    if existing.trim().is_empty() {
+        return format!("# Changelog\n\n{}\n\n{}", UNRELEASED_HEADING, entry);
+    }
+
+    let lines: Vec<&str> = existing.lines().collect();
+    let mut out = String::with_capacity(existing.len() + entry.len() + 32);
+    let push_lines = |out: &mut String, lines: &[&str]| {
+        for line in lines {
+            out.push_str(line);
+            out.push('\n');
+        }
+    };
+
+    if let Some(heading) = lines
+        .iter()
+        .position(|line| line.starts_with(UNRELEASED_HEADING))
+    {
+        let mut body = heading + 1;
+        while body < lines.len() && lines[body].trim().is_empty() {
+            body += 1;
+        }
+        push_lines(&mut out, &lines[..=heading]);
+        out.push('\n');
+        out.push_str(entry);
+        if body < lines.len() {
+            out.push('\n');
+            push_lines(&mut out, &lines[body..]);
+        }
+        return out;
+    }
+
+    let section = lines
+        .iter()
+        .position(|line| line.starts_with("## "))
+        .unwrap_or(lines.len());
+    push_lines(&mut out, &lines[..section]);
+    if !out.ends_with("\n\n") {
+        out.push('\n');
+    }
+    out.push_str(UNRELEASED_HEADING);
+    out.push_str("\n\n");
+    out.push_str(entry);
+    if section < lines.len() {
+        out.push('\n');
+        push_lines(&mut out, &lines[section..]);
+    }
+    out
+}
+
+fn follow_ups(
+    files: &[FileSummary],
+    changes: &ChangeLedger,
+    decisions: &DecisionTracker,
+) -> Vec<String> {
+    let decisions = decisions.get_decisions();
+    let mut follow_ups = Vec::new();
+
+    for decision in decisions {
+        let (Action::ToolCall { name, args, .. }, Some(DecisionOutcome::Failure { error, .. })) =
+            (&decision.action, &decision.outcome)
+        else {
+            continue;
+        };
+        let target = args
+            .get("path")
+            .and_then(Value::as_str)
+            .map(|path| format!(" on `{}`", path))
+            .unwrap_or_default();
+        let reason = error.lines().next().unwrap_or_default().trim();
+        let item = format!("Revisit `{}`{}: {}", name, target, reason);
+        if !follow_ups.contains(&item) {
+            follow_ups.push(item);
+        }
+        if follow_ups.len() == MAX_FAILURE_FOLLOW_UPS {
+            break;
+        }
+    }
+
+    let last_change = changes
+        .last_decision_id()
+        .and_then(|id| decisions.iter().position(|decision| decision.id == id));
+    let verified = last_change.is_some_and(|index| {
+        decisions[index + 1..]
+            .iter()
+            .any(|decision| is_successful_verification(&decision.action, &decision.outcome))
+    });
+    if !verified {
+        follow_ups.push("Run the tests; nothing was verified after the last edit.".to_string());
+    }
+
+    if files.iter().any(|file| file.kind == ChangeKind::Deleted) {
+        follow_ups.push("Check for remaining references to removed files.".to_string());
+    }
+
+    follow_ups
+}
+
+fn is_successful_verification(action: &Action, outcome: &Option<DecisionOutcome>) -> bool {
+    let Action::ToolCall { name, args, .. } = action else {
+        return false;
+    };
+    if !matches!(outcome, Some(DecisionOutcome::Success { .. }))
+        || !matches!(name.as_str(), tools::RUN_TERMINAL_CMD | tools::BASH)
+    {
+        return false;
+    }
+    let command = match args.get("command").or_else(|| args.get("bash_command")) {
+        Some(Value::String(command)) => command.clone(),
+        Some(Value::Array(parts)) => parts
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(" "),
+        _ => return false,
+    };
+    let command = command.to_lowercase();
+    VERIFICATION_MARKERS
+        .iter()
+        .any(|marker| command.contains(marker))
+}
+
+fn describe_file(file: &FileSummary) -> String {
+    format!("{} `{}`", file.kind.label(), file.path)
+}
+
+fn pluralize_files(count: usize) -> String {
+    if count == 1 {
+        "1 file changed".to_string()
+    } else {
+        format!("{} files changed", count)
+    }
+}
+
+/// First line of a request, shortened for headings and bullets.
+fn condense_goal(goal: &str) -> String {
+    let line = goal
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+    if line.chars().count() <= MAX_GOAL_CHARS {
+        return line.to_string();
+    }
+    let mut short: String = line.chars().take(MAX_GOAL_CHARS - 1).collect();
+    short.push('…');
+    short
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tool_call(tracker: &mut DecisionTracker, name: &str, args: Value, ok: bool) -> String {
+        let id = tracker.record_decision(
+            "run tool".to_string(),
+            Action::ToolCall {
+                name: name.to_string(),
+                args,
+                expected_outcome: String::new(),
+            },
+            None,
+        );
+        let outcome = if ok {
+            DecisionOutcome::Success {
+                result: "tool_ok".to_string(),
+                metrics: Default::default(),
+            }
+        } else {
+            DecisionOutcome::Failure {
+                error: "old_str not found\nmore detail".to_string(),
+                recovery_attempts: 0,
+                context_preserved: true,
+            }
+        };
+        tracker.record_outcome(&id, outcome);
+        id
+    }
+
+    #[test]
+    fn summarizes_changes_goals_and_follow_ups() {
+        let mut tracker = DecisionTracker::new();
+        let mut ledger = ChangeLedger::new();
+        ledger.begin_request("Explain the config loader");
+        ledger.begin_request("Add a --json flag to the list command\nKeep output stable");
+
+        let args = json!({"path": "src/cli.rs"});
+        let id = tool_call(&mut tracker, tools::EDIT_FILE, args.clone(), true);
+        ledger.record_tool_result(
+            tools::EDIT_FILE,
+            &args,
+            &json!({"success": true}),
+            None,
+            Some(&id),
+        );
+        tool_call(
+            &mut tracker,
+            tools::EDIT_FILE,
+            json!({"path": "src/main.rs"}),
+            false,
+        );
+
+        let summary = SessionSummary::build("session-1", &ledger, &tracker).expect("summary");
+        assert_eq!(summary.title(), "Add a --json flag to the list command");
+        assert_eq!(
+            summary.goals,
+            vec![(
+                "Add a --json flag to the list command".to_string(),
+                vec!["src/cli.rs".to_string()]
+            )]
+        );
+        assert_eq!(
+            summary.follow_ups,
+            vec![
+                "Revisit `edit_file` on `src/main.rs`: old_str not found".to_string(),
+                "Run the tests; nothing was verified after the last edit.".to_string(),
+            ]
+        );
+
+        let markdown = summary.to_markdown();
+        assert!(markdown.contains("## What changed\n\n- Updated `src/cli.rs`\n"));
+        assert!(markdown.contains("## Files touched\n\n- `src/cli.rs`\n"));
+
+        tool_call(
+            &mut tracker,
+            tools::RUN_TERMINAL_CMD,
+            json!({"command": ["cargo", "test"]}),
+            true,
+        );
+        let summary = SessionSummary::build("session-1", &ledger, &tracker).expect("summary");
+        assert_eq!(summary.follow_ups.len(), 1);
+    }
+
+    #[test]
+    fn skips_sessions_without_changes() {
+        let mut ledger = ChangeLedger::new();
+        ledger.begin_request("What does this do?");
+        assert!(SessionSummary::build("session-1", &ledger, &DecisionTracker::new()).is_none());
+    }
+
+    #[test]
+    fn inserts_changelog_entries_under_unreleased() {
+        let entry = "#### 2026-01-02: Add flag\n\n- Updated `src/cli.rs`\n";
+
+        let existing = "# Changelog\n\n## [Unreleased] - Latest\n\n### Older\n\n- thing\n";
+        assert_eq!(
+            insert_changelog_entry(existing, entry),
+            "# Changelog\n\n## [Unreleased] - Latest\n\n#### 2026-01-02: Add flag\n\n- Updated `src/cli.rs`\n\n### Older\n\n- thing\n"
+        );
+
+        let released = "# Changelog\n\n## [1.0.0]\n\n- first\n";
+        assert_eq!(
+            insert_changelog_entry(released, entry),
+            "# Changelog\n\n## [Unreleased]\n\n#### 2026-01-02: Add flag\n\n- Updated `src/cli.rs`\n\n## [1.0.0]\n\n- first\n"
+        );
+
+        assert_eq!(
+            insert_changelog_entry("", entry),
+            format!("# Changelog\n\n## [Unreleased]\n\n{}", entry)
+        );
+    }
+}
@@ -0,0 +1,388 @@
+//! Token spend and budgets
+//!
+//! [`SpendTracker`] prices the usage of every model response with the
+//! per-million-token rates in `docs/models.json`, totals it per provider for
+//! the session and the local calendar day, and reports when a `[llm.spend]`
+//! budget is used up. Daily totals of all sessions are kept in
+//! `~/.vtcode/spend.json`, which `vtcode stats --spend` reads.
+
+use crate::config::core::SpendConfig;
+use crate::config::model_catalog::{self, ModelPricing};
+use crate::llm::provider::Usage;
+use anyhow::{Context, Result};
+use chrono::{Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+const SPEND_FILE: &str = ".vtcode/spend.json";
+const TOKENS_PER_PRICE_UNIT: f64 = 1_000_000.0;
+
+/// Tokens and estimated cost of one or more responses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SpendTotals {
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cost_usd: f64,
+    /// Requests to models the catalog has no prices for, counted at no cost
+    #[serde(default)]
+    pub unpriced_requests: u64,
+}
+
+impl SpendTotals {
+    pub fn add(&mut self, other: &SpendTotals) {
+        self.requests += other.requests;
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.cost_usd += other.cost_usd;
+        self.unpriced_requests += other.unpriced_requests;
+    }
+}
+
+/// Daily totals per provider, keyed by `YYYY-MM-DD` and provider name.
+pub type SpendLedger = BTreeMap<String, BTreeMap<String, SpendTotals>>;
+
+/// The budget that a session ran into.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BudgetExceeded {
+    pub scope: BudgetScope,
+    pub limit_usd: f64,
+    pub spent_usd: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetScope {
+    Session,
+    Daily,
+}
+
+impl fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scope = match self.scope {
+            BudgetScope::Session => "session",
+            BudgetScope::Daily => "daily",
+        };
+        write!(
+            f,
+            "the {scope} budget of {} is used up ({} spent)",
+            format_usd(self.limit_usd),
+            format_usd(self.spent_usd)
+        )
+    }
+}
+
+/// Session and daily spend, checked against the configured budgets.
+#[derive(Debug)]
+pub struct SpendTracker {
+    path: Option<PathBuf>,
+    config: SpendConfig,
+    session: BTreeMap<String, SpendTotals>,
+    /// Day the daily figures below belong to
+    day: NaiveDate,
+    /// Spend already in the ledger for `day` when it was last read
+    day_baseline_usd: f64,
+    /// Spend of this session on `day`
+    day_session_usd: f64,
+    /// Spend not yet written to the ledger, per day and provider
+    pending: SpendLedger,
+    /// Set once the user chose to continue past a budget
+    overrun_allowed: bool,
+}
+
+impl SpendTracker {
+    /// Tracker with its daily totals kept in memory only.
+    pub fn in_memory(config: SpendConfig) -> Self {
+        Self::with_path(None, config, &SpendLedger::new())
+    }
+
+    /// Tracker with its daily totals stored in the user's dot folder.
+    pub fn load_default(config: SpendConfig) -> Self {
+        match default_ledger_path() {
+            Some(path) => Self::load(&path, config),
+            None => Self::in_memory(config),
+        }
+    }
+
+    /// Tracker with its daily totals stored at `path`.
+    pub fn load(path: &Path, config: SpendConfig) -> Self {
+        Self::with_path(Some(path.to_path_buf()), config, &read_ledger(path))
+    }
+
+    fn with_path(path: Option<PathBuf>, config: SpendConfig, ledger: &SpendLedger) -> Self {
+        let day = Local::now().date_naive();
+        Self {
+            path,
+            config,
+            session: BTreeMap::new(),
+            day,
+            day_baseline_usd: day_cost(ledger, day),
+            day_session_usd: 0.0,
+            pending: SpendLedger::new(),
+            overrun_allowed: false,
+        }
+    }
+
+    /// Price `usage` of a response from `model` and add it to the totals.
+    pub fn record(&mut self, provider: &str, model: &str, usage: Option<&Usage>) -> SpendTotals {
+        let Some(usage) = usage else {
+            return SpendTotals::default();
+        };
+        let spend = price_usage(
+            provider,
+            usage,
+            model_catalog::pricing(provider, model).as_ref(),
+        );
+        let today = Local::now().date_naive();
+        if today != self.day {
+            self.day_baseline_usd = day_cost(&self.ledger_snapshot(), today);
+            self.day_session_usd = 0.0;
+            self.day = today;
+        }
+        self.session
+            .entry(provider.to_string())
+            .or_default()
+            .add(&spend);
+        self.pending
+            .entry(day_key(today))
+            .or_default()
+            .entry(provider.to_string())
+            .or_default()
+            .add(&spend);
+        self.day_session_usd += spend.cost_usd;
+        spend
+    }
+
+    /// Totals of this session per provider.
+    pub fn session_totals(&self) -> &BTreeMap<String, SpendTotals> {
+        &self.session
+    }
+
+    pub fn session_cost(&self) -> f64 {
+        self.session.values().map(|totals| totals.cost_usd).sum()
+    }
+
+    /// Spend of all sessions today, as far as this session knows.
+    pub fn daily_cost(&self) -> f64 {
+        self.day_baseline_usd + self.day_session_usd
+    }
+
+    /// The first budget that is used up, unless the user already chose to
+    /// continue past it.
+    pub fn exceeded(&self) -> Option<BudgetExceeded> {
+        if self.overrun_allowed {
+            return None;
+        }
+        let session = self.session_cost();
+        if self.config.session_usd > 0.0 && session >= self.config.session_usd {
+            return Some(BudgetExceeded {
+                scope: BudgetScope::Session,
+                limit_usd: self.config.session_usd,
+                spent_usd: session,
+            });
+        }
+        let daily = self.daily_cost();
+        if self.config.daily_usd > 0.0 && daily >= self.config.daily_usd {
+            return Some(BudgetExceeded {
+                scope: BudgetScope::Daily,
+                limit_usd: self.config.daily_usd,
+                spent_usd: daily,
+            });
+        }
+        None
+    }
+
+    /// Stop reporting exceeded budgets for the rest of the session.
+    pub fn allow_overrun(&mut self) {
+        self.overrun_allowed = true;
+    }
+
+    /// Short summary for the status bar.
+    pub fn status_text(&self) -> String {
+        format!(
+            "{} session · {} today",
+            format_usd(self.session_cost()),
+            format_usd(self.daily_cost())
+        )
+    }
+
+    /// Add the spend recorded since the last save to the ledger on disk,
+    /// keeping what other sessions wrote in the meantime.
+    pub fn save(&mut self) -> Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+        let ledger = self.ledger_snapshot();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        let json = serde_json::to_string_pretty(&ledger)?;
+        std::fs::write(path, json)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        self.day_baseline_usd = day_cost(&ledger, self.day) - self.day_session_usd;
+        self.pending.clear();
+        Ok(())
+    }
+
+    /// The ledger on disk with the unsaved spend added.
+    fn ledger_snapshot(&self) -> SpendLedger {
+        let mut ledger = self.path.as_deref().map(read_ledger).unwrap_or_default();
+        for (day, providers) in &self.pending {
+            let entry = ledger.entry(day.clone()).or_default();
+            for (provider, totals) in providers {
+                entry.entry(provider.clone()).or_default().add(totals);
+            }
+        }
+        ledger
+    }
+}
+
+/// Location of the daily totals in the user's dot folder.
+pub fn default_ledger_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(SPEND_FILE))
+}
+
+/// Daily totals stored at `path`; unreadable data reads as empty.
+pub fn read_ledger(path: &Path) -> SpendLedger {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+/// Tokens and cost of one response. Anthropic reports cached and
+/// cache-written input separately from `input_tokens`; other providers count
+/// cached input as part of the prompt tokens.
+pub fn price_usage(provider: &str, usage: &Usage, pricing: Option<&ModelPricing>) -> SpendTotals {
+    let prompt = u64::from(usage.prompt_tokens);
+    let completion = u64::from(usage.completion_tokens);
+    let (uncached, cache_read, cache_write) = if provider == "anthropic" {
+        (
+            prompt,
+            u64::from(usage.cache_read_tokens.unwrap_or(0)),
+            u64::from(usage.cache_creation_tokens.unwrap_or(0)),
+        )
+    } else {
+        let cached = u64::from(
+            usage
+                .cached_prompt_tokens
+                .or(usage.cache_read_tokens)
+                .unwrap_or(0),
+        )
+        .min(prompt);
+        (prompt - cached, cached, 0)
+    };
+    let mut totals = SpendTotals {
+        requests: 1,
+        prompt_tokens: uncached + cache_read + cache_write,
+        completion_tokens: completion,
+        ..Default::default()
+    };
+    match pricing {
+        Some(price) => {
+            let cost = uncached as f64 * price.input
+                + cache_read as f64 * price.cache_read.unwrap_or(price.input)
+                + cache_write as f64 * price.cache_write.unwrap_or(price.input)
+                + completion as f64 * price.output;
+            totals.cost_usd = cost / TOKENS_PER_PRICE_UNIT;
+        }
+        None => totals.unpriced_requests = 1,
+    }
+    totals
+}
+
+pub fn format_usd(amount: f64) -> String {
+    if amount > 0.0 && amount < 0.01 {
+        format!("${amount:.4}")
+    } else {
+        format!("${amount:.2}")
+    }
+}
+
+fn day_key(day: NaiveDate) -> String {
+    day.format("%Y-%m-%d").to_string()
+}
+
+fn day_cost(ledger: &SpendLedger, day: NaiveDate) -> f64 {
+    ledger.get(&day_key(day)).map_or(0.0, |providers| {
+        providers.values().map(|t| t.cost_usd).sum()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(prompt: u32, completion: u32) -> Usage {
+        Usage {
+            prompt_tokens: prompt,
+            completion_tokens: completion,
+            total_tokens: prompt + completion,
+            cached_prompt_tokens: None,
+            cache_creation_tokens: None,
+            cache_read_tokens: None,
+        }
+    }
+
+    #[test]
+    fn prices_cached_input_per_provider() {
+        let price = ModelPricing {
+            input: 3.0,
+            output: 15.0,
+            cache_read: Some(0.3),
+            cache_write: Some(3.75),
+        };
+        let mut openai = usage(1_000_000, 100_000);
+        openai.cached_prompt_tokens = Some(500_000);
+        let spend = price_usage("openai", &openai, Some(&price));
+        assert!((spend.cost_usd - (1.5 + 0.15 + 1.5)).abs() < 1e-9);
+        assert_eq!(spend.prompt_tokens, 1_000_000);
+
+        let mut anthropic = usage(1_000_000, 0);
+        anthropic.cache_read_tokens = Some(1_000_000);
+        anthropic.cache_creation_tokens = Some(1_000_000);
+        let spend = price_usage("anthropic", &anthropic, Some(&price));
+        assert!((spend.cost_usd - (3.0 + 0.3 + 3.75)).abs() < 1e-9);
+        assert_eq!(spend.prompt_tokens, 3_000_000);
+
+        let spend = price_usage("ollama", &usage(10, 10), None);
+        assert_eq!((spend.cost_usd, spend.unpriced_requests), (0.0, 1));
+    }
+
+    #[test]
+    fn budgets_include_earlier_sessions_and_survive_saves() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("spend.json");
+        let config = SpendConfig {
+            session_usd: 2.0,
+            daily_usd: 1.5,
+            ..Default::default()
+        };
+
+        let mut first = SpendTracker::load(&path, config.clone());
+        // deepseek-chat: $0.57 input and $1.68 output per million tokens
+        first.record("deepseek", "deepseek-chat", Some(&usage(1_000_000, 0)));
+        assert!(first.exceeded().is_none());
+        first.save().unwrap();
+
+        let mut second = SpendTracker::load(&path, config);
+        assert!((second.daily_cost() - 0.57).abs() < 1e-9);
+        second.record("deepseek", "deepseek-chat", Some(&usage(0, 600_000)));
+        let exceeded = second.exceeded().expect("daily budget is used up");
+        assert_eq!(exceeded.scope, BudgetScope::Daily);
+        second.save().unwrap();
+        assert!((second.daily_cost() - (0.57 + 1.008)).abs() < 1e-9);
+
+        let ledger = read_ledger(&path);
+        let today = ledger.values().next().expect("one day");
+        assert_eq!(today["deepseek"].requests, 2);
+
+        second.allow_overrun();
+        assert!(second.exceeded().is_none());
+    }
+}
@@ -0,0 +1,244 @@
+//! Turn-by-turn checkpoints of the workspace files changed in a session
+//!
+//! The chat loop records a [`TimelineTurn`] after every completed turn with the
+//! request, the reply and the contents of every file the session has changed
+//! so far. Each file keeps the baseline it had before the session first wrote
+//! to it, so [`SessionTimeline::diff_at`] shows the cumulative workspace diff
+//! at any turn; stepping through the turns shows when a change first appeared.
+
+use crate::config::constants::tools;
+use crate::core::change_ledger::ChangeLedger;
+use crate::ui::diff_renderer::generate_unified_diff;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Files larger than this are left out of checkpoints.
+const MAX_TRACKED_BYTES: u64 = 512 * 1024;
+
+/// Workspace state at the end of one turn.
+#[derive(Debug, Clone)]
+pub struct TimelineTurn {
+    /// 1-based turn number
+    pub number: usize,
+    pub request: String,
+    pub reply: String,
+    /// Contents of each changed file; `None` when the file does not exist
+    pub files: BTreeMap<String, Option<String>>,
+    /// Files whose contents changed during this turn
+    pub changed: Vec<String>,
+}
+
+/// Ordered checkpoints for the current session.
+#[derive(Debug, Clone)]
+pub struct SessionTimeline {
+    workspace: PathBuf,
+    baselines: BTreeMap<String, Option<String>>,
+    turns: Vec<TimelineTurn>,
+}
+
+impl SessionTimeline {
+    pub fn new(workspace: impl Into<PathBuf>) -> Self {
+        Self {
+            workspace: workspace.into(),
+            baselines: BTreeMap::new(),
+            turns: Vec::new(),
+        }
+    }
+
+    pub fn turns(&self) -> &[TimelineTurn] {
+        &self.turns
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.turns.is_empty()
+    }
+
+    /// Remember the current contents of the file a write tool is about to
+    /// change. Call before running the tool; only the first call per file
+    /// counts.
+    pub fn capture_baseline(&mut self, tool: &str, args: &Value) {
+        if !matches!(
+            tool,
+            tools::WRITE_FILE
+                | tools::CREATE_FILE
+                | tools::EDIT_FILE
+                | tools::DELETE_FILE
+                | tools::SRGN
+                | tools::ORGANIZE_IMPORTS
+                | tools::CONFIG_EDIT
+        ) {
+            return;
+        }
+        let Some(path) = args.get("path").and_then(Value::as_str) else {
+            return;
+        };
+        let path = path.trim().trim_start_matches("./").to_string();
+        if self.baselines.contains_key(&path) {
+            return;
+        }
+        let contents = read_tracked(&self.workspace.join(&path));
+        self.baselines.insert(path, contents);
+    }
+
+    /// Checkpoint the files recorded in `ledger` at the end of a turn.
+    pub fn record_turn(
+        &mut self,
+        request: impl Into<String>,
+        reply: impl Into<String>,
+        ledger: &ChangeLedger,
+    ) {
+        let mut files = BTreeMap::new();
+        for change in ledger.changes() {
+            if files.contains_key(&change.path) {
+                continue;
+            }
+            if !self.baselines.contains_key(&change.path) {
+                // Files changed by tools without a `path` argument (patches,
+                // commands) fall back to the committed version.
+                let baseline = git_head_contents(&self.workspace, &change.path);
+                self.baselines.insert(change.path.clone(), baseline);
+            }
+            let contents = read_tracked(&self.workspace.join(&change.path));
+            files.insert(change.path.clone(), contents);
+        }
+
+        let previous = self.turns.last().map(|turn| &turn.files);
+        let changed = files
+            .iter()
+            .filter(|(path, contents)| {
+                let before = previous
+                    .and_then(|files| files.get(*path))
+                    .or_else(|| self.baselines.get(*path));
+                before != Some(*contents)
+            })
+            .map(|(path, _)| path.clone())
+            .collect();
+
+        self.turns.push(TimelineTurn {
+            number: self.turns.len() + 1,
+            request: request.into(),
+            reply: reply.into(),
+            files,
+            changed,
+        });
+    }
+
+    /// Unified diff of the workspace at the end of turn `index` (0-based)
+    /// against the session baseline.
+    pub fn diff_at(&self, index: usize) -> String {
+        let Some(turn) = self.turns.get(index) else {
+            return String::new();
+        };
+        let mut diff = String::new();
+        for (path, contents) in &turn.files {
+            let baseline = self.baselines.get(path).cloned().flatten();
+            if baseline.as_ref() == contents.as_ref() {
+                continue;
+            }
+            diff.push_str(&generate_unified_diff(
+                baseline.as_deref().unwrap_or_default(),
+                contents.as_deref().unwrap_or_default(),
+                path,
+            ));
+        }
+        diff
+    }
+
+    /// Number of times a turn put a file back to contents it had at an
+    /// earlier checkpoint (the session baseline or the end of an older turn),
+    /// undoing work from a previous turn.
+    pub fn revert_count(&self) -> usize {
+        let mut reverts = 0;
+        for (index, turn) in self.turns.iter().enumerate() {
+            for path in &turn.changed {
+                let contents = turn.files.get(path);
+                let reverted = self.turns[..index.saturating_sub(1)]
+                    .iter()
+                    .filter_map(|earlier| earlier.files.get(path))
+                    .chain(self.baselines.get(path))
+                    .any(|state| Some(state) == contents);
+                if reverted {
+                    reverts += 1;
+                }
+            }
+        }
+        reverts
+    }
+}
+
+fn read_tracked(path: &Path) -> Option<String> {
+    let metadata = std::fs::metadata(path).ok()?;
+    if metadata.len() > MAX_TRACKED_BYTES {
+        return None;
+    }
+    std::fs::read_to_string(path).ok()
+}
+
+fn git_head_contents(workspace: &Path, path: &str) -> Option<String> {
+    let output = Command::new("git")
+        .arg("show")
+        .arg(format!("HEAD:{path}"))
+        .current_dir(workspace)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn diffs_each_turn_against_the_session_baseline() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let file = dir.path().join("lib.rs");
+        std::fs::write(&file, "fn a() {}\n").expect("write");
+        let mut timeline = SessionTimeline::new(dir.path());
+        let mut ledger = ChangeLedger::new();
+        let args = json!({"path": "lib.rs"});
+
+        ledger.begin_request("add b");
+        timeline.capture_baseline(tools::EDIT_FILE, &args);
+        std::fs::write(&file, "fn a() {}\nfn b() {}\n").expect("write");
+        ledger.record_tool_result(tools::EDIT_FILE, &args, &json!({}), None, None);
+        timeline.record_turn("add b", "Added b.", &ledger);
+
+        ledger.begin_request("explain");
+        timeline.record_turn("explain", "It adds b.", &ledger);
+
+        ledger.begin_request("drop a");
+        timeline.capture_baseline(tools::EDIT_FILE, &args);
+        std::fs::write(&file, "fn b() {}\n").expect("write");
+        ledger.record_tool_result(tools::EDIT_FILE, &args, &json!({}), None, None);
+        timeline.record_turn("drop a", "Removed a.", &ledger);
+
+        let turns = timeline.turns();
+        assert_eq!(turns.len(), 3);
+        assert_eq!(turns[0].changed, vec!["lib.rs".to_string()]);
+        assert!(turns[1].changed.is_empty());
+        assert_eq!(turns[2].changed, vec!["lib.rs".to_string()]);
+
+        let first = timeline.diff_at(0);
+        assert!(first.starts_with("--- a/lib.rs\n+++ b/lib.rs\n"));
+        assert!(first.contains("+fn b() {}"));
+        assert_eq!(timeline.diff_at(1), first);
+        let last = timeline.diff_at(2);
+        assert!(last.contains("-fn a() {}"));
+        assert!(!last.contains("+fn a() {}"));
+        assert!(timeline.diff_at(3).is_empty());
+        assert_eq!(timeline.revert_count(), 0);
+
+        ledger.begin_request("bring a back");
+        timeline.capture_baseline(tools::EDIT_FILE, &args);
+        std::fs::write(&file, "fn a() {}\nfn b() {}\n").expect("write");
+        ledger.record_tool_result(tools::EDIT_FILE, &args, &json!({}), None, None);
+        timeline.record_turn("bring a back", "Restored a.", &ledger);
+        assert_eq!(timeline.revert_count(), 1);
+    }
+}
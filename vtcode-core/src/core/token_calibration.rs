@@ -0,0 +1,191 @@
+//! Per-model correction of character-based token estimates
+//!
+//! Context budgeting estimates tokens from character counts, which suits
+//! OpenAI's tokenizers but can be well off for Anthropic and Gemini models.
+//! [`TokenCalibration`] compares the estimate for each request with the
+//! prompt tokens the provider reports, keeps a running correction factor per
+//! provider and model, and persists the factors in
+//! `~/.vtcode/cache/token_calibration.json` so later sessions start calibrated.
+
+use crate::config::constants::context as context_defaults;
+use crate::core::tool_result_store::content_len;
+use crate::llm::provider::{LLMRequest, Message, Usage};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+const CALIBRATION_FILE: &str = ".vtcode/cache/token_calibration.json";
+/// Requests smaller than this are dominated by fixed overhead and skipped.
+const MIN_SAMPLE_TOKENS: usize = 256;
+/// Samples needed before the factor is applied.
+const MIN_SAMPLES: u64 = 3;
+/// Weight floor for new samples, so the factor keeps tracking changes.
+const MIN_SAMPLE_WEIGHT: f64 = 0.1;
+const MIN_FACTOR: f64 = 0.25;
+const MAX_FACTOR: f64 = 4.0;
+
+/// Correction for one provider and model.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelCalibration {
+    /// Actual prompt tokens per estimated token
+    pub factor: f64,
+    pub samples: u64,
+}
+
+/// Correction factors for token estimates, keyed by `provider/model`.
+#[derive(Debug, Default)]
+pub struct TokenCalibration {
+    path: Option<PathBuf>,
+    models: BTreeMap<String, ModelCalibration>,
+    dirty: bool,
+}
+
+impl TokenCalibration {
+    /// Calibration kept in memory only.
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Calibration stored in the user's dot folder.
+    pub fn load_default() -> Self {
+        match dirs::home_dir() {
+            Some(home) => Self::load(&home.join(CALIBRATION_FILE)),
+            None => Self::in_memory(),
+        }
+    }
+
+    /// Calibration stored at `path`; unreadable data starts over.
+    pub fn load(path: &Path) -> Self {
+        let models = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path.to_path_buf()),
+            models,
+            dirty: false,
+        }
+    }
+
+    pub fn get(&self, provider: &str, model: &str) -> Option<ModelCalibration> {
+        self.models.get(&key(provider, model)).copied()
+    }
+
+    /// Factor to multiply estimates by; 1.0 until enough samples are in.
+    pub fn factor(&self, provider: &str, model: &str) -> f64 {
+        self.get(provider, model)
+            .filter(|calibration| calibration.samples >= MIN_SAMPLES)
+            .map_or(1.0, |calibration| calibration.factor)
+    }
+
+    /// Fold in a request that was estimated at `estimated` tokens and billed
+    /// at `actual` prompt tokens.
+    pub fn record(&mut self, provider: &str, model: &str, estimated: usize, actual: usize) {
+        if estimated < MIN_SAMPLE_TOKENS || actual == 0 {
+            return;
+        }
+        let ratio = (actual as f64 / estimated as f64).clamp(MIN_FACTOR, MAX_FACTOR);
+        let entry = self
+            .models
+            .entry(key(provider, model))
+            .or_insert(ModelCalibration {
+                factor: ratio,
+                samples: 0,
+            });
+        let weight = (1.0 / (entry.samples + 1) as f64).max(MIN_SAMPLE_WEIGHT);
+        entry.factor += (ratio - entry.factor) * weight;
+        entry.samples += 1;
+        self.dirty = true;
+    }
+
+    /// Write the factors back if they changed.
+    pub fn save(&mut self) -> Result<()> {
+        let Some(path) = self.path.as_ref().filter(|_| self.dirty) else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        let json = serde_json::to_string_pretty(&self.models)?;
+        std::fs::write(path, json)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+fn key(provider: &str, model: &str) -> String {
+    format!("{provider}/{model}")
+}
+
+/// Uncalibrated estimate of a message, in the units [`TokenCalibration`]
+/// corrects.
+pub fn estimate_message_tokens(message: &Message) -> usize {
+    // Spilled tool results are sent in full
+    let mut total_chars = content_len(&message.content);
+    total_chars += message.role.as_generic_str().len();
+    for call in message.tool_calls.iter().flatten() {
+        total_chars += call.id.len();
+        total_chars += call.call_type.len();
+        total_chars += call.function.name.len();
+        total_chars += call.function.arguments.len();
+    }
+    if let Some(tool_call_id) = &message.tool_call_id {
+        total_chars += tool_call_id.len();
+    }
+    total_chars.div_ceil(context_defaults::CHAR_PER_TOKEN_APPROX)
+}
+
+/// Uncalibrated estimate of everything `request` sends as prompt.
+pub fn estimate_request_tokens(request: &LLMRequest) -> usize {
+    let messages: usize = request.messages.iter().map(estimate_message_tokens).sum();
+    let system = request.system_prompt.as_deref().map_or(0, str::len);
+    let tools = request
+        .tools
+        .as_ref()
+        .and_then(|tools| serde_json::to_string(tools).ok())
+        .map_or(0, |json| json.len());
+    messages + (system + tools).div_ceil(context_defaults::CHAR_PER_TOKEN_APPROX)
+}
+
+/// Prompt tokens the provider processed. Anthropic reports cached and
+/// cache-written input separately from `input_tokens`.
+pub fn reported_prompt_tokens(provider: &str, usage: &Usage) -> usize {
+    let mut tokens = usage.prompt_tokens as usize;
+    if provider == "anthropic" {
+        tokens += usage.cache_creation_tokens.unwrap_or(0) as usize;
+        tokens += usage.cache_read_tokens.unwrap_or(0) as usize;
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converges_on_the_reported_ratio_and_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("token_calibration.json");
+        let mut calibration = TokenCalibration::load(&path);
+
+        calibration.record("anthropic", "claude-sonnet-4", 1_000, 1_300);
+        calibration.record("anthropic", "claude-sonnet-4", 100, 900);
+        assert_eq!(calibration.factor("anthropic", "claude-sonnet-4"), 1.0);
+        for _ in 0..20 {
+            calibration.record("anthropic", "claude-sonnet-4", 2_000, 2_400);
+        }
+        let factor = calibration.factor("anthropic", "claude-sonnet-4");
+        assert!((1.2..1.25).contains(&factor), "{factor}");
+        assert_eq!(calibration.factor("gemini", "gemini-2.5-pro"), 1.0);
+
+        calibration.save().unwrap();
+        let reloaded = TokenCalibration::load(&path);
+        assert_eq!(
+            reloaded.get("anthropic", "claude-sonnet-4"),
+            calibration.get("anthropic", "claude-sonnet-4")
+        );
+    }
+}
@@ -0,0 +1,165 @@
+//! Capability level of the tools offered to the model
+//!
+//! With `[tools.exposure] progressive = true`, a session starts at
+//! [`READ_ONLY_LEVEL`]: the model can read, list and search files and record a
+//! plan, while editing and terminal tools are left out of the request. That
+//! keeps early prompts small and rules out destructive calls before the task
+//! is understood. The level rises to [`FULL_LEVEL`] when the model records a
+//! plan, when the user approves a call to a withheld tool, or with
+//! `/tools full`.
+
+use crate::config::constants::tools;
+use crate::config::core::ExposureConfig;
+use crate::config::types::CapabilityLevel;
+use crate::llm::provider::ToolDefinition;
+use crate::tools::{build_function_declarations_for_level, tool_capability};
+use std::collections::HashSet;
+
+/// Level for the read-only phase.
+pub const READ_ONLY_LEVEL: CapabilityLevel = CapabilityLevel::FileListing;
+/// Level with every tool available.
+pub const FULL_LEVEL: CapabilityLevel = CapabilityLevel::CodeSearch;
+
+/// Tools the model may currently see and call.
+#[derive(Debug, Clone)]
+pub struct ToolExposure {
+    level: CapabilityLevel,
+    escalate_on_plan: bool,
+    exposed: HashSet<String>,
+}
+
+impl ToolExposure {
+    pub fn new(config: &ExposureConfig) -> Self {
+        let level = if config.progressive {
+            READ_ONLY_LEVEL
+        } else {
+            FULL_LEVEL
+        };
+        Self {
+            level,
+            escalate_on_plan: config.escalate_on_plan,
+            exposed: exposed_tools(level),
+        }
+    }
+
+    pub fn level(&self) -> CapabilityLevel {
+        self.level
+    }
+
+    pub fn is_restricted(&self) -> bool {
+        self.level < FULL_LEVEL
+    }
+
+    /// Change the level; returns whether it changed.
+    pub fn set_level(&mut self, level: CapabilityLevel) -> bool {
+        if self.level == level {
+            return false;
+        }
+        self.level = level;
+        self.exposed = exposed_tools(level);
+        true
+    }
+
+    /// Unlock every tool; returns whether anything was withheld before.
+    pub fn escalate(&mut self) -> bool {
+        self.set_level(FULL_LEVEL)
+    }
+
+    /// Whether `tool` is a built-in the current level withholds.
+    pub fn withholds(&self, tool: &str) -> bool {
+        tool_capability(tool).is_some_and(|required| required > self.level)
+    }
+
+    /// Whether a successful `update_plan` call should unlock every tool.
+    pub fn escalates_on_plan(&self, tool: &str) -> bool {
+        self.escalate_on_plan && self.is_restricted() && tool == tools::UPDATE_PLAN
+    }
+
+    /// The subset of `tools` exposed at the current level. Tools that are not
+    /// built-ins (for example MCP tools) are left to their own policies.
+    pub fn available_tools(&self, definitions: &[ToolDefinition]) -> Vec<ToolDefinition> {
+        definitions
+            .iter()
+            .filter(|tool| {
+                let name = tool.function.name.as_str();
+                self.exposed.contains(name) || tool_capability(name).is_none()
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Note appended to the system prompt while tools are withheld.
+    pub fn prompt_note(&self) -> Option<&'static str> {
+        if !self.is_restricted() {
+            return None;
+        }
+        Some(if self.escalate_on_plan {
+            "[Tool Access]\nOnly read-only tools are available for now. Investigate first; once you know what to change, record the steps with update_plan and editing and terminal tools become available."
+        } else {
+            "[Tool Access]\nOnly read-only tools are available for now. If the task needs edits or terminal commands, say so; the user can enable them with /tools full."
+        })
+    }
+}
+
+fn exposed_tools(level: CapabilityLevel) -> HashSet<String> {
+    build_function_declarations_for_level(level)
+        .into_iter()
+        .map(|declaration| declaration.name)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn starts_read_only_and_escalates() {
+        let mut exposure = ToolExposure::new(&ExposureConfig::default());
+        let names = [
+            tools::READ_FILE,
+            tools::GREP_SEARCH,
+            tools::UPDATE_PLAN,
+            tools::EDIT_FILE,
+            tools::RUN_TERMINAL_CMD,
+            "mcp_fetch",
+        ];
+        let definitions: Vec<ToolDefinition> = names
+            .iter()
+            .map(|name| ToolDefinition::function(name.to_string(), String::new(), json!({})))
+            .collect();
+        let offered = |exposure: &ToolExposure| -> Vec<String> {
+            exposure
+                .available_tools(&definitions)
+                .into_iter()
+                .map(|tool| tool.function.name)
+                .collect()
+        };
+        assert_eq!(
+            offered(&exposure),
+            [
+                tools::READ_FILE,
+                tools::GREP_SEARCH,
+                tools::UPDATE_PLAN,
+                "mcp_fetch"
+            ]
+        );
+        assert!(exposure.withholds(tools::EDIT_FILE));
+        assert!(exposure.withholds(tools::APPLY_PATCH));
+        assert!(!exposure.withholds("mcp_fetch"));
+        assert!(exposure.escalates_on_plan(tools::UPDATE_PLAN));
+        assert!(exposure.prompt_note().is_some());
+
+        assert!(exposure.escalate());
+        assert!(!exposure.escalate());
+        assert_eq!(offered(&exposure), names);
+        assert!(!exposure.withholds(tools::RUN_TERMINAL_CMD));
+        assert!(exposure.prompt_note().is_none());
+
+        let everything = ToolExposure::new(&ExposureConfig {
+            progressive: false,
+            escalate_on_plan: true,
+        });
+        assert!(!everything.is_restricted());
+    }
+}
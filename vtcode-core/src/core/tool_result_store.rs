@@ -0,0 +1,246 @@
+//! Spilling large tool results to disk
+//!
+//! Search results and file reads can run to megabytes, and every one of them
+//! stays in the conversation history (and in each fork checkpoint) for the
+//! rest of the session. [`ToolResultStore::spill`] moves tool responses over
+//! the configured size into a temporary directory and leaves a small
+//! reference with a preview in the message. [`ToolResultStore::rehydrate`]
+//! puts the full text back into a copy of the history when it is needed in
+//! full: for a provider request or when the session is archived. The
+//! directory is removed when the store is dropped.
+//!
+//! The same directory keeps the full text of results that were sent to the
+//! model as a summary (see [`crate::core::tool_verbosity`]); [`ToolResultStore::keep`]
+//! stores one and [`ToolResultStore::full_output`] reads it back by id.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tempfile::TempDir;
+
+use crate::config::ToolResultSpillConfig;
+use crate::llm::provider::{Message, MessageRole};
+
+/// Start of the message content that marks a spilled tool result.
+const SPILL_MARKER: &str = "{\"spilled_tool_result\":";
+
+/// What stays in the conversation in place of a spilled result.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct SpillReference {
+    id: usize,
+    bytes: usize,
+    preview: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SpilledMessage {
+    spilled_tool_result: SpillReference,
+}
+
+/// Tool results of one session that were moved to disk.
+pub struct ToolResultStore {
+    config: ToolResultSpillConfig,
+    /// Created on the first spill
+    dir: Option<TempDir>,
+    next_id: usize,
+    spilled_bytes: u64,
+}
+
+impl ToolResultStore {
+    pub fn new(config: &ToolResultSpillConfig) -> Self {
+        Self {
+            config: config.clone(),
+            dir: None,
+            next_id: 0,
+            spilled_bytes: 0,
+        }
+    }
+
+    pub fn disabled() -> Self {
+        Self::new(&ToolResultSpillConfig {
+            enabled: false,
+            ..ToolResultSpillConfig::default()
+        })
+    }
+
+    /// Bytes of tool output spilled to disk so far.
+    pub fn spilled_bytes(&self) -> u64 {
+        self.spilled_bytes
+    }
+
+    /// Move tool responses over the threshold to disk. Returns how many were
+    /// spilled; a result that cannot be written stays in memory.
+    pub fn spill(&mut self, history: &mut [Message]) -> usize {
+        if !self.config.enabled {
+            return 0;
+        }
+        let mut spilled = 0;
+        for message in history.iter_mut() {
+            if message.role != MessageRole::Tool
+                || message.content.len() <= self.config.threshold_bytes
+                || is_spilled(&message.content)
+            {
+                continue;
+            }
+            match self.write(&message.content) {
+                Ok(reference) => {
+                    message.content = reference;
+                    spilled += 1;
+                }
+                Err(err) => {
+                    tracing::warn!(%err, "failed to spill a tool result to disk");
+                    break;
+                }
+            }
+        }
+        spilled
+    }
+
+    /// A copy of `history` with spilled results read back from disk. A result
+    /// whose file is gone keeps its reference and preview.
+    pub fn rehydrate(&self, history: &[Message]) -> Vec<Message> {
+        history
+            .iter()
+            .map(|message| {
+                let mut message = message.clone();
+                if let Some(content) = self.load(&message.content) {
+                    message.content = content;
+                }
+                message
+            })
+            .collect()
+    }
+
+    /// Full text of a spilled result, or `None` if `content` is not a
+    /// reference this store can resolve.
+    pub fn load(&self, content: &str) -> Option<String> {
+        let reference = parse_reference(content)?;
+        let dir = self.dir.as_ref()?;
+        match fs::read_to_string(spill_path(dir.path(), reference.id)) {
+            Ok(text) => Some(text),
+            Err(err) => {
+                tracing::warn!(%err, id = reference.id, "spilled tool result is missing");
+                None
+            }
+        }
+    }
+
+    /// Store the full text of a result that was summarized for the model.
+    /// Returns the id to read it back with [`Self::full_output`].
+    pub fn keep(&mut self, content: &str) -> Result<usize> {
+        self.write_file(content)
+    }
+
+    /// Full text stored under `id` by [`Self::keep`] or a spill.
+    pub fn full_output(&self, id: usize) -> Option<String> {
+        let dir = self.dir.as_ref()?;
+        fs::read_to_string(spill_path(dir.path(), id)).ok()
+    }
+
+    fn write(&mut self, content: &str) -> Result<String> {
+        let id = self.write_file(content)?;
+        self.spilled_bytes += content.len() as u64;
+
+        let reference = SpilledMessage {
+            spilled_tool_result: SpillReference {
+                id,
+                bytes: content.len(),
+                preview: content.chars().take(self.config.preview_chars).collect(),
+            },
+        };
+        Ok(serde_json::to_string(&reference)?)
+    }
+
+    fn write_file(&mut self, content: &str) -> Result<usize> {
+        let dir = match &self.dir {
+            Some(dir) => dir,
+            None => self.dir.insert(
+                tempfile::Builder::new()
+                    .prefix("vtcode-tool-results-")
+                    .tempdir()
+                    .context("failed to create the tool result spill directory")?,
+            ),
+        };
+        let id = self.next_id;
+        let path = spill_path(dir.path(), id);
+        fs::write(&path, content).with_context(|| format!("failed to write {}", path.display()))?;
+        self.next_id += 1;
+        Ok(id)
+    }
+}
+
+/// Whether message content is a reference to a spilled result.
+pub fn is_spilled(content: &str) -> bool {
+    content.starts_with(SPILL_MARKER)
+}
+
+/// Length of the text a message stands for: the original size of a spilled
+/// result, the content length otherwise.
+pub fn content_len(content: &str) -> usize {
+    parse_reference(content).map_or(content.len(), |reference| reference.bytes)
+}
+
+fn parse_reference(content: &str) -> Option<SpillReference> {
+    if !is_spilled(content) {
+        return None;
+    }
+    serde_json::from_str::<SpilledMessage>(content)
+        .ok()
+        .map(|message| message.spilled_tool_result)
+}
+
+fn spill_path(dir: &Path, id: usize) -> PathBuf {
+    dir.join(format!("{id}.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store(threshold_bytes: usize) -> ToolResultStore {
+        ToolResultStore::new(&ToolResultSpillConfig {
+            enabled: true,
+            threshold_bytes,
+            preview_chars: 8,
+        })
+    }
+
+    #[test]
+    fn spills_large_tool_results_and_rehydrates_them() {
+        let large = format!("{{\"content\":\"{}\"}}", "x".repeat(200));
+        let mut history = vec![
+            Message::user("find it".to_string()),
+            Message::tool_response("call_1".to_string(), large.clone()),
+            Message::tool_response("call_2".to_string(), "{\"ok\":true}".to_string()),
+        ];
+        let mut store = store(100);
+
+        assert_eq!(store.spill(&mut history), 1);
+        assert!(is_spilled(&history[1].content));
+        assert!(history[1].content.len() < 100);
+        assert_eq!(history[2].content, "{\"ok\":true}");
+        assert_eq!(store.spilled_bytes(), large.len() as u64);
+        assert_eq!(content_len(&history[1].content), large.len());
+        // Already spilled results are left alone
+        assert_eq!(store.spill(&mut history), 0);
+
+        let full = store.rehydrate(&history);
+        assert_eq!(full[1].content, large);
+        assert_eq!(full[1].tool_call_id.as_deref(), Some("call_1"));
+        assert_eq!(full[2].content, history[2].content);
+    }
+
+    #[test]
+    fn leaves_other_messages_and_disabled_stores_alone() {
+        let large = "y".repeat(500);
+        let mut history = vec![Message::user(large.clone())];
+        assert_eq!(store(100).spill(&mut history), 0);
+        assert_eq!(history[0].content, large);
+
+        let mut history = vec![Message::tool_response("call".to_string(), large.clone())];
+        assert_eq!(ToolResultStore::disabled().spill(&mut history), 0);
+        assert_eq!(history[0].content, large);
+    }
+}
@@ -0,0 +1,546 @@
+//! Tool schemas sized for the model's context window
+//!
+//! Models with 8–16k token windows lose a large share of their context to the
+//! full set of tool schemas. With `[tools.schema]`, requests to such models
+//! carry compacted schemas: descriptions are cut at a sentence boundary and
+//! optional parameters are limited to the ones calls in this workspace
+//! actually pass. For the smallest windows only the tools relevant to the
+//! request are sent, ranked by what the router classified the request as,
+//! which tools the conversation has used or mentioned, and how often each
+//! tool is used in the workspace. Tools that were left out are listed in the
+//! system prompt; naming one brings it back on the next request.
+//!
+//! When tool calls go through the JSON envelope and the model's backend can
+//! compile a JSON schema into a decoding grammar, the request also carries a
+//! schema of the envelope built from the same tool definitions, so the model
+//! can only name offered tools and pass arguments of the declared shape.
+
+use crate::config::constants::tools;
+use crate::config::core::{ConstrainedDecodingConfig, ToolSchemaConfig};
+use crate::core::router::TaskClass;
+use crate::core::tool_stats::ToolStats;
+use crate::llm::provider::{Message, ToolDefinition};
+use indexmap::IndexMap;
+use serde_json::{Value, json};
+use std::collections::HashSet;
+
+/// Tools sent in every subset: reading, searching, editing, running commands
+/// and recording a plan, which also unlocks withheld tools.
+const CORE_TOOLS: &[&str] = &[
+    tools::READ_FILE,
+    tools::LIST_FILES,
+    tools::GREP_SEARCH,
+    tools::EDIT_FILE,
+    tools::RUN_TERMINAL_CMD,
+    tools::UPDATE_PLAN,
+];
+
+/// Context window of `model`: the entry in `context_windows` that matches it
+/// exactly, else the longest entry it starts with, else `default_window`.
+pub fn context_window(config: &ToolSchemaConfig, model: &str, default_window: usize) -> usize {
+    by_model(&config.context_windows, model).unwrap_or(default_window)
+}
+
+fn by_model<T: Copy>(entries: &IndexMap<String, T>, model: &str) -> Option<T> {
+    if let Some(value) = entries.get(model) {
+        return Some(*value);
+    }
+    entries
+        .iter()
+        .filter(|(prefix, _)| model.starts_with(prefix.as_str()))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, value)| *value)
+}
+
+/// Whether JSON envelope requests to one model carry a response schema.
+#[derive(Debug, Clone, Copy)]
+pub struct ConstrainedDecoding {
+    constrain: bool,
+    envelope_first: bool,
+}
+
+impl ConstrainedDecoding {
+    /// `provider_supports` is what the provider reports for `model`; entries
+    /// in `[tools.constrained_decoding] models` override it.
+    pub fn new(config: &ConstrainedDecodingConfig, model: &str, provider_supports: bool) -> Self {
+        let constrain =
+            config.enabled && by_model(&config.models, model).unwrap_or(provider_supports);
+        let envelope_first = constrain
+            && config
+                .envelope_models
+                .iter()
+                .any(|prefix| model.starts_with(prefix.as_str()));
+        Self {
+            constrain,
+            envelope_first,
+        }
+    }
+
+    /// Whether envelope requests are constrained to the envelope schema.
+    pub fn constrains(&self) -> bool {
+        self.constrain
+    }
+
+    /// Whether tool calls use the envelope from the first request.
+    pub fn starts_with_envelope(&self) -> bool {
+        self.envelope_first
+    }
+}
+
+/// JSON schema of the tool envelope
+/// `{"message": "...", "tool_calls": [{"name": ..., "arguments": {...}}]}`
+/// in which every call names one of `definitions` and passes arguments
+/// matching its parameter schema.
+pub fn tool_envelope_schema(definitions: &[ToolDefinition]) -> Value {
+    let calls: Vec<Value> = definitions
+        .iter()
+        .map(|tool| {
+            let mut arguments = tool.function.parameters.clone();
+            if !arguments.is_object() || arguments.get("type").is_none() {
+                arguments = json!({ "type": "object" });
+            }
+            json!({
+                "type": "object",
+                "properties": {
+                    "name": { "const": tool.function.name },
+                    "arguments": arguments,
+                },
+                "required": ["name", "arguments"],
+                "additionalProperties": false,
+            })
+        })
+        .collect();
+    json!({
+        "type": "object",
+        "properties": {
+            "message": { "type": "string" },
+            "tool_calls": { "type": "array", "items": { "anyOf": calls } },
+        },
+        "additionalProperties": false,
+    })
+}
+
+/// How much of the tool schema set a request to one model can afford.
+#[derive(Debug, Clone)]
+pub struct ToolSchemaBudget {
+    compact: bool,
+    subset: bool,
+    max_description_chars: usize,
+    max_optional_params: usize,
+    max_tools: usize,
+}
+
+/// Tool definitions for one request and the names of the tools left out.
+#[derive(Debug, Clone, Default)]
+pub struct ShapedTools {
+    pub tools: Vec<ToolDefinition>,
+    pub omitted: Vec<String>,
+}
+
+impl ShapedTools {
+    /// Note appended to the system prompt when tools were left out.
+    pub fn prompt_note(&self) -> Option<String> {
+        if self.omitted.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "[Tool Subset]\nTo fit the context window only some tools are offered. Also available: {}. Mention one by name if you need it and it is offered from the next request on.",
+            self.omitted.join(", ")
+        ))
+    }
+}
+
+impl ToolSchemaBudget {
+    pub fn new(config: &ToolSchemaConfig, model: &str, default_window: usize) -> Self {
+        let window = context_window(config, model, default_window);
+        let below = |limit: usize| limit > 0 && window <= limit;
+        Self {
+            compact: below(config.compact_below_tokens),
+            subset: below(config.subset_below_tokens),
+            max_description_chars: config.max_description_chars,
+            max_optional_params: config.max_optional_params,
+            max_tools: config.max_tools,
+        }
+    }
+
+    /// Whether the full schema set is sent unchanged.
+    pub fn is_unlimited(&self) -> bool {
+        !self.compact && !self.subset
+    }
+
+    /// Compact and select `definitions` for a request the router classified
+    /// as `class`, given the conversation so far.
+    pub fn shape(
+        &self,
+        definitions: Vec<ToolDefinition>,
+        class: TaskClass,
+        history: &[Message],
+        stats: Option<&ToolStats>,
+    ) -> ShapedTools {
+        let (mut tools, omitted) = if self.subset {
+            self.select(definitions, class, history, stats)
+        } else {
+            (definitions, Vec::new())
+        };
+        if self.compact || self.subset {
+            for tool in &mut tools {
+                self.compact(tool, stats);
+            }
+        }
+        ShapedTools { tools, omitted }
+    }
+
+    fn select(
+        &self,
+        definitions: Vec<ToolDefinition>,
+        class: TaskClass,
+        history: &[Message],
+        stats: Option<&ToolStats>,
+    ) -> (Vec<ToolDefinition>, Vec<String>) {
+        let mentioned = mentioned_tools(&definitions, history);
+        let intended = intent_tools(class);
+        let priority = |name: &str| {
+            if CORE_TOOLS.contains(&name) {
+                3
+            } else if mentioned.contains(name) {
+                2
+            } else if intended.contains(&name) {
+                1
+            } else {
+                0
+            }
+        };
+
+        let mut ranked: Vec<(usize, u8, u64)> = definitions
+            .iter()
+            .enumerate()
+            .map(|(index, tool)| {
+                let name = tool.function.name.as_str();
+                let calls = stats
+                    .and_then(|stats| stats.tool(name))
+                    .map_or(0, |record| record.calls);
+                (index, priority(name), calls)
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then(b.2.cmp(&a.2)).then(a.0.cmp(&b.0)));
+        let keep: HashSet<usize> = ranked
+            .iter()
+            .enumerate()
+            .filter(|(rank, (_, priority, _))| *rank < self.max_tools || *priority == 3)
+            .map(|(_, (index, _, _))| *index)
+            .collect();
+
+        let mut selected = Vec::new();
+        let mut omitted = Vec::new();
+        for (index, tool) in definitions.into_iter().enumerate() {
+            if keep.contains(&index) {
+                selected.push(tool);
+            } else {
+                omitted.push(tool.function.name);
+            }
+        }
+        (selected, omitted)
+    }
+
+    fn compact(&self, tool: &mut ToolDefinition, stats: Option<&ToolStats>) {
+        let name = tool.function.name.clone();
+        if self.max_description_chars > 0 {
+            tool.function.description =
+                shorten(&tool.function.description, self.max_description_chars);
+            shorten_parameter_descriptions(
+                &mut tool.function.parameters,
+                (self.max_description_chars / 2).max(1),
+            );
+        }
+        drop_optional_parameters(
+            &mut tool.function.parameters,
+            self.max_optional_params,
+            |argument| stats.map_or(0, |stats| stats.argument_calls(&name, argument)),
+        );
+    }
+}
+
+/// Extra tools worth sending for each kind of request.
+fn intent_tools(class: TaskClass) -> &'static [&'static str] {
+    match class {
+        TaskClass::Simple => &[tools::SIMPLE_SEARCH, tools::FILE_METADATA],
+        TaskClass::Standard => &[tools::WRITE_FILE, tools::APPLY_PATCH],
+        TaskClass::Complex => &[
+            tools::APPLY_PATCH,
+            tools::AST_GREP_SEARCH,
+            tools::CALL_GRAPH,
+            tools::PROJECT_OVERVIEW,
+            tools::WRITE_FILE,
+        ],
+        TaskClass::CodegenHeavy => &[
+            tools::WRITE_FILE,
+            tools::CREATE_FILE,
+            tools::APPLY_PATCH,
+            tools::ORGANIZE_IMPORTS,
+        ],
+        TaskClass::RetrievalHeavy => &[
+            tools::SIMPLE_SEARCH,
+            tools::AST_GREP_SEARCH,
+            tools::PROJECT_OVERVIEW,
+            tools::TREE_SITTER_ANALYZE,
+            tools::CURL,
+            tools::FETCH_DOCS,
+        ],
+    }
+}
+
+/// Tools the conversation called or named.
+fn mentioned_tools<'a>(definitions: &'a [ToolDefinition], history: &[Message]) -> HashSet<&'a str> {
+    let mut called = HashSet::new();
+    for message in history {
+        for call in message.tool_calls.iter().flatten() {
+            called.insert(call.function.name.as_str());
+        }
+    }
+    definitions
+        .iter()
+        .map(|tool| tool.function.name.as_str())
+        .filter(|name| {
+            called.contains(name)
+                || history
+                    .iter()
+                    .any(|message| mentions(&message.content, name))
+        })
+        .collect()
+}
+
+/// Whether `text` contains `name` as a whole word.
+fn mentions(text: &str, name: &str) -> bool {
+    let is_word = |ch: char| ch.is_alphanumeric() || ch == '_';
+    text.match_indices(name).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + name.len()..].chars().next();
+        !before.is_some_and(is_word) && !after.is_some_and(is_word)
+    })
+}
+
+/// `text` cut to at most `max` characters, at the last sentence end in the
+/// second half of the limit or else at a word boundary with an ellipsis.
+fn shorten(text: &str, max: usize) -> String {
+    let text = text.trim();
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let end = text
+        .char_indices()
+        .nth(max)
+        .map_or(text.len(), |(index, _)| index);
+    let prefix = &text[..end];
+    let sentence_end = prefix
+        .match_indices(". ")
+        .chain(prefix.match_indices(".\n"))
+        .map(|(index, _)| index + 1)
+        .filter(|index| prefix[..*index].chars().count() >= max / 2)
+        .max();
+    if let Some(index) = sentence_end {
+        return prefix[..index].to_string();
+    }
+    let cut = prefix
+        .rfind(char::is_whitespace)
+        .filter(|index| *index > 0)
+        .unwrap_or(end);
+    format!("{}…", prefix[..cut].trim_end())
+}
+
+fn shorten_parameter_descriptions(schema: &mut Value, max: usize) {
+    let Some(object) = schema.as_object_mut() else {
+        return;
+    };
+    if let Some(Value::String(description)) = object.get_mut("description") {
+        *description = shorten(description, max);
+    }
+    if let Some(Value::Object(properties)) = object.get_mut("properties") {
+        for property in properties.values_mut() {
+            shorten_parameter_descriptions(property, max);
+        }
+    }
+    if let Some(items) = object.get_mut("items") {
+        shorten_parameter_descriptions(items, max);
+    }
+}
+
+/// Keep the `max` optional top-level parameters with the most recorded uses;
+/// ties go to the parameter listed first.
+fn drop_optional_parameters(schema: &mut Value, max: usize, uses: impl Fn(&str) -> u64) {
+    let required: HashSet<String> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|names| {
+            names
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+    let Some(Value::Object(properties)) = schema.get_mut("properties") else {
+        return;
+    };
+    let mut optional: Vec<(usize, String)> = properties
+        .keys()
+        .enumerate()
+        .filter(|(_, name)| !required.contains(*name))
+        .map(|(index, name)| (index, name.clone()))
+        .collect();
+    if optional.len() <= max {
+        return;
+    }
+    optional.sort_by(|a, b| uses(&b.1).cmp(&uses(&a.1)).then(a.0.cmp(&b.0)));
+    for (_, name) in optional.into_iter().skip(max) {
+        properties.remove(&name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::time::Duration;
+
+    fn tool(name: &str) -> ToolDefinition {
+        ToolDefinition::function(name.to_string(), format!("{name} tool"), json!({}))
+    }
+
+    fn small_model_config() -> ToolSchemaConfig {
+        let mut config = ToolSchemaConfig::default();
+        config.context_windows.insert("tiny".to_string(), 8_192);
+        config
+            .context_windows
+            .insert("tiny-large".to_string(), 64_000);
+        config
+    }
+
+    #[test]
+    fn looks_up_windows_and_compacts_schemas() {
+        let config = small_model_config();
+        assert_eq!(context_window(&config, "tiny-coder", 90_000), 8_192);
+        assert_eq!(context_window(&config, "tiny-large-v2", 90_000), 64_000);
+        assert!(ToolSchemaBudget::new(&config, "gpt-5", 90_000).is_unlimited());
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut stats = ToolStats::at(dir.path().join("stats.json"));
+        stats.record(
+            tools::GREP_SEARCH,
+            &json!({ "pattern": "x", "glob": "*.rs" }),
+            true,
+            Duration::from_millis(5),
+        );
+
+        let description = format!(
+            "Search files with a regular expression. {}",
+            "Matches are grouped by file and include line numbers. ".repeat(6)
+        );
+        let parameters = json!({
+            "type": "object",
+            "properties": {
+                "pattern": { "type": "string", "description": "x ".repeat(200) },
+                "case_sensitive": { "type": "boolean" },
+                "context_lines": { "type": "integer" },
+                "glob": { "type": "string" },
+                "max_results": { "type": "integer" },
+                "path": { "type": "string" }
+            },
+            "required": ["pattern"]
+        });
+        let definition =
+            ToolDefinition::function(tools::GREP_SEARCH.to_string(), description, parameters);
+
+        let budget = ToolSchemaBudget::new(&config, "tiny-coder", 90_000);
+        let shaped = budget.shape(vec![definition], TaskClass::Standard, &[], Some(&stats));
+        let function = &shaped.tools[0].function;
+        assert!(function.description.len() <= 200);
+        assert!(function.description.ends_with('.'));
+        let properties = function.parameters["properties"].as_object().unwrap();
+        let kept: Vec<&str> = properties.keys().map(String::as_str).collect();
+        assert_eq!(kept, ["case_sensitive", "context_lines", "glob", "pattern"]);
+        let pattern = properties["pattern"]["description"].as_str().unwrap();
+        assert!(pattern.chars().count() <= 101 && pattern.ends_with('…'));
+    }
+
+    #[test]
+    fn selects_tools_by_intent_and_mentions() {
+        let mut config = small_model_config();
+        config.max_tools = 8;
+        let names = [
+            tools::READ_FILE,
+            tools::LIST_FILES,
+            tools::GREP_SEARCH,
+            tools::EDIT_FILE,
+            tools::RUN_TERMINAL_CMD,
+            tools::UPDATE_PLAN,
+            tools::SIMPLE_SEARCH,
+            tools::CURL,
+            tools::SQL_QUERY,
+            tools::WRITE_FILE,
+        ];
+        let definitions: Vec<ToolDefinition> = names.iter().map(|name| tool(name)).collect();
+        let history = vec![Message::user(
+            "Fetch the changelog with curl, not curling_iron".to_string(),
+        )];
+
+        let budget = ToolSchemaBudget::new(&config, "tiny", 90_000);
+        let shaped = budget.shape(definitions, TaskClass::Simple, &history, None);
+        let sent: Vec<&str> = shaped
+            .tools
+            .iter()
+            .map(|tool| tool.function.name.as_str())
+            .collect();
+        assert_eq!(sent.len(), 8);
+        assert!(sent.contains(&tools::CURL));
+        assert!(sent.contains(&tools::SIMPLE_SEARCH));
+        assert_eq!(shaped.omitted, [tools::SQL_QUERY, tools::WRITE_FILE]);
+        assert!(
+            shaped
+                .prompt_note()
+                .unwrap()
+                .contains("sql_query, write_file")
+        );
+        assert!(!mentions("curling_iron", tools::CURL));
+    }
+
+    #[test]
+    fn constrains_envelopes_to_the_offered_tools() {
+        let mut config = ConstrainedDecodingConfig::default();
+        config.models.insert("gpt-oss".to_string(), true);
+        config.models.insert("qwen3:0.6b".to_string(), false);
+        config.envelope_models.push("qwen3".to_string());
+
+        assert!(ConstrainedDecoding::new(&config, "gpt-oss-20b", false).constrains());
+        assert!(!ConstrainedDecoding::new(&config, "gpt-5", false).constrains());
+        let small = ConstrainedDecoding::new(&config, "qwen3:0.6b", true);
+        assert!(!small.constrains() && !small.starts_with_envelope());
+        assert!(ConstrainedDecoding::new(&config, "qwen3:8b", true).starts_with_envelope());
+        config.enabled = false;
+        assert!(!ConstrainedDecoding::new(&config, "gpt-oss-20b", true).constrains());
+
+        let read_file = ToolDefinition::function(
+            tools::READ_FILE.to_string(),
+            "Read a file".to_string(),
+            json!({
+                "type": "object",
+                "properties": { "path": { "type": "string" } },
+                "required": ["path"]
+            }),
+        );
+        let schema = tool_envelope_schema(&[read_file, tool(tools::LIST_FILES)]);
+        let calls = schema["properties"]["tool_calls"]["items"]["anyOf"]
+            .as_array()
+            .unwrap();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0]["properties"]["name"]["const"], tools::READ_FILE);
+        assert_eq!(
+            calls[0]["properties"]["arguments"]["required"],
+            json!(["path"])
+        );
+        assert_eq!(
+            calls[1]["properties"]["arguments"],
+            json!({ "type": "object" })
+        );
+        assert_eq!(schema["additionalProperties"], false);
+    }
+}
@@ -0,0 +1,386 @@
+//! Tool usage statistics learned across sessions
+//!
+//! Every tool call records whether it succeeded and how long it ran, per
+//! workspace, in `~/.vtcode/cache/tool_stats/`. Terminal commands are also
+//! tracked by their leading words (`cargo build`, `npm test`). Once a tool has
+//! enough samples the aggregates are appended to its description ("`cargo
+//! build` takes ~90s here"), and quick lookups get a note steering the model to
+//! the search tool that answers fastest and most reliably in this workspace.
+//!
+//! Durations are exponential moving averages so they follow the workspace as
+//! it grows instead of being anchored to its first sessions.
+
+use crate::config::constants::tools;
+use crate::core::router::TaskClass;
+use crate::llm::provider::ToolDefinition;
+use crate::tools::registry::command_words;
+use crate::utils::dot_config::{DotManager, workspace_key};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Calls needed before a tool or command is described from its statistics.
+pub const MIN_SAMPLES: u64 = 3;
+/// Weight of the newest call in the typical duration.
+const DURATION_WEIGHT: f64 = 0.3;
+/// Terminal commands at least this slow are called out in the description.
+const SLOW_COMMAND_MS: f64 = 10_000.0;
+/// Success rate below which a search tool is not recommended.
+const RELIABLE_RATE: f64 = 0.8;
+const SEARCH_TOOLS: &[&str] = &[
+    tools::SIMPLE_SEARCH,
+    tools::GREP_SEARCH,
+    tools::AST_GREP_SEARCH,
+];
+
+/// Aggregated outcomes of one tool or terminal command.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct UsageRecord {
+    pub calls: u64,
+    pub failures: u64,
+    /// Moving average of the run time in milliseconds
+    pub typical_ms: f64,
+    pub last_used: Option<DateTime<Utc>>,
+}
+
+impl UsageRecord {
+    fn observe(&mut self, success: bool, elapsed: Duration) {
+        let ms = elapsed.as_secs_f64() * 1000.0;
+        self.typical_ms = if self.calls == 0 {
+            ms
+        } else {
+            self.typical_ms * (1.0 - DURATION_WEIGHT) + ms * DURATION_WEIGHT
+        };
+        self.calls += 1;
+        if !success {
+            self.failures += 1;
+        }
+        self.last_used = Some(Utc::now());
+    }
+
+    pub fn success_rate(&self) -> f64 {
+        if self.calls == 0 {
+            return 0.0;
+        }
+        (self.calls - self.failures) as f64 / self.calls as f64
+    }
+
+    fn is_established(&self) -> bool {
+        self.calls >= MIN_SAMPLES
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StatsFile {
+    #[serde(default)]
+    tools: BTreeMap<String, UsageRecord>,
+    /// Keyed by command prefix, e.g. `cargo build`
+    #[serde(default)]
+    commands: BTreeMap<String, UsageRecord>,
+    /// Calls that passed each argument, keyed by tool and then argument name
+    #[serde(default)]
+    arguments: BTreeMap<String, BTreeMap<String, u64>>,
+}
+
+/// Statistics for one workspace.
+#[derive(Debug, Default)]
+pub struct ToolStats {
+    path: Option<PathBuf>,
+    data: StatsFile,
+    dirty: bool,
+}
+
+impl ToolStats {
+    /// Statistics of `workspace` from earlier sessions. A missing or unreadable
+    /// file starts empty; it is rewritten on the next save.
+    pub fn load(workspace: &Path) -> Self {
+        match DotManager::new() {
+            Ok(dot) => Self::at(
+                dot.cache_dir("tool_stats")
+                    .join(format!("{}.json", workspace_key(workspace))),
+            ),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Statistics stored in `path`.
+    pub fn at(path: PathBuf) -> Self {
+        let data = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            data,
+            dirty: false,
+        }
+    }
+
+    /// Record one call of `tool` with `args`.
+    pub fn record(&mut self, tool: &str, args: &Value, success: bool, elapsed: Duration) {
+        self.data
+            .tools
+            .entry(tool.to_string())
+            .or_default()
+            .observe(success, elapsed);
+        if let Some(object) = args.as_object() {
+            let counts = self.data.arguments.entry(tool.to_string()).or_default();
+            for name in object.keys() {
+                *counts.entry(name.clone()).or_default() += 1;
+            }
+        }
+        if matches!(tool, tools::RUN_TERMINAL_CMD | tools::BASH)
+            && let Some(prefix) = command_prefix(args)
+        {
+            self.data
+                .commands
+                .entry(prefix)
+                .or_default()
+                .observe(success, elapsed);
+        }
+        self.dirty = true;
+    }
+
+    /// Write the statistics if anything was recorded since the last save.
+    pub fn save(&mut self) -> Result<()> {
+        let Some(path) = self.path.as_ref().filter(|_| self.dirty) else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        let content = serde_json::to_string_pretty(&self.data)?;
+        std::fs::write(path, content)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    pub fn tool(&self, tool: &str) -> Option<&UsageRecord> {
+        self.data.tools.get(tool)
+    }
+
+    pub fn command(&self, prefix: &str) -> Option<&UsageRecord> {
+        self.data.commands.get(prefix)
+    }
+
+    /// How many recorded calls of `tool` passed `argument`.
+    pub fn argument_calls(&self, tool: &str, argument: &str) -> u64 {
+        self.data
+            .arguments
+            .get(tool)
+            .and_then(|counts| counts.get(argument))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// What was learned about `tool`, phrased for its description.
+    pub fn description_note(&self, tool: &str) -> Option<String> {
+        let record = self.tool(tool).filter(|record| record.is_established())?;
+        let mut note = format!(
+            "Observed in this workspace: {:.0}% of {} calls succeeded, typically {}.",
+            record.success_rate() * 100.0,
+            record.calls,
+            format_duration(record.typical_ms)
+        );
+        if matches!(tool, tools::RUN_TERMINAL_CMD | tools::BASH) {
+            let mut slow: Vec<(&String, &UsageRecord)> = self
+                .data
+                .commands
+                .iter()
+                .filter(|(_, record)| {
+                    record.is_established() && record.typical_ms >= SLOW_COMMAND_MS
+                })
+                .collect();
+            slow.sort_by(|a, b| b.1.typical_ms.total_cmp(&a.1.typical_ms));
+            let described: Vec<String> = slow
+                .iter()
+                .take(4)
+                .map(|(prefix, record)| {
+                    format!(
+                        "`{}` takes {} here",
+                        prefix,
+                        format_duration(record.typical_ms)
+                    )
+                })
+                .collect();
+            if !described.is_empty() {
+                note.push_str(&format!(" Note: {}.", described.join(", ")));
+            }
+        }
+        Some(note)
+    }
+
+    /// Append [`Self::description_note`] to each tool description.
+    pub fn annotate(&self, definitions: &mut [ToolDefinition]) {
+        for definition in definitions {
+            if let Some(note) = self.description_note(&definition.function.name) {
+                definition.function.description.push_str("\n\n");
+                definition.function.description.push_str(&note);
+            }
+        }
+    }
+
+    /// Guidance for the turn, based on how the router classified the request.
+    /// Quick lookups are pointed at the search tool that has been fastest and
+    /// reliable in this workspace when the alternatives are clearly worse.
+    pub fn tool_guidance(&self, class: TaskClass) -> Option<String> {
+        if class != TaskClass::Simple {
+            return None;
+        }
+        let established: Vec<(&str, &UsageRecord)> = SEARCH_TOOLS
+            .iter()
+            .filter_map(|name| {
+                self.tool(name)
+                    .filter(|record| record.is_established())
+                    .map(|record| (*name, record))
+            })
+            .collect();
+        let (best, best_record) = established
+            .iter()
+            .filter(|(_, record)| record.success_rate() >= RELIABLE_RATE)
+            .min_by(|a, b| a.1.typical_ms.total_cmp(&b.1.typical_ms))?;
+        let worse: Vec<String> = established
+            .iter()
+            .filter(|(name, _)| name != best)
+            .filter_map(|(name, record)| {
+                if record.success_rate() < RELIABLE_RATE {
+                    Some(format!(
+                        "{} failed {:.0}% of the time",
+                        name,
+                        (1.0 - record.success_rate()) * 100.0
+                    ))
+                } else if record.typical_ms >= best_record.typical_ms * 2.0 {
+                    Some(format!(
+                        "{} takes {}",
+                        name,
+                        format_duration(record.typical_ms)
+                    ))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        if worse.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "[Tool Guidance]\nThis looks like a quick lookup. Here {} typically answers in {} ({:.0}% success) while {}; prefer {} unless the query needs another tool's matching.",
+            best,
+            format_duration(best_record.typical_ms),
+            best_record.success_rate() * 100.0,
+            worse.join(" and "),
+            best
+        ))
+    }
+}
+
+/// Whether a tool output reports success.
+pub fn output_succeeded(output: &Value) -> bool {
+    if let Some(success) = output.get("success").and_then(Value::as_bool) {
+        return success;
+    }
+    if output.get("error").is_some() {
+        return false;
+    }
+    output
+        .get("exit_code")
+        .and_then(Value::as_i64)
+        .is_none_or(|code| code == 0)
+}
+
+/// Program and subcommand of a terminal call: `cargo build --release` is
+/// tracked as `cargo build`, `ls -la src` as `ls`.
+fn command_prefix(args: &Value) -> Option<String> {
+    let words = command_words(args);
+    let program = words.first()?;
+    let program = program.rsplit('/').next().unwrap_or(program);
+    match words.get(1) {
+        Some(sub)
+            if sub.starts_with(|ch: char| ch.is_ascii_alphabetic())
+                && sub
+                    .chars()
+                    .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | ':')) =>
+        {
+            Some(format!("{program} {sub}"))
+        }
+        _ => Some(program.to_string()),
+    }
+}
+
+fn format_duration(ms: f64) -> String {
+    if ms < 1000.0 {
+        format!("{:.0}ms", ms)
+    } else if ms < 10_000.0 {
+        format!("{:.1}s", ms / 1000.0)
+    } else if ms < 120_000.0 {
+        format!("~{:.0}s", ms / 1000.0)
+    } else {
+        format!("~{:.0}m", ms / 60_000.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn learns_across_sessions_and_guides_quick_lookups() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("stats.json");
+
+        let mut stats = ToolStats::at(path.clone());
+        let build = json!({ "command": ["cargo", "build", "--release"] });
+        for _ in 0..3 {
+            stats.record(
+                tools::RUN_TERMINAL_CMD,
+                &build,
+                true,
+                Duration::from_secs(90),
+            );
+            stats.record(
+                tools::SIMPLE_SEARCH,
+                &json!({}),
+                true,
+                Duration::from_millis(200),
+            );
+            stats.record(
+                tools::AST_GREP_SEARCH,
+                &json!({}),
+                true,
+                Duration::from_secs(4),
+            );
+        }
+        stats.record(
+            tools::GREP_SEARCH,
+            &json!({}),
+            true,
+            Duration::from_millis(300),
+        );
+        stats.save()?;
+
+        let stats = ToolStats::at(path);
+        assert_eq!(stats.command("cargo build").map(|r| r.calls), Some(3));
+        assert_eq!(stats.argument_calls(tools::RUN_TERMINAL_CMD, "command"), 3);
+        assert_eq!(stats.argument_calls(tools::RUN_TERMINAL_CMD, "timeout"), 0);
+        let note = stats.description_note(tools::RUN_TERMINAL_CMD).unwrap();
+        assert!(note.contains("100% of 3 calls"));
+        assert!(note.contains("`cargo build` takes ~90s here"));
+        assert!(stats.description_note(tools::GREP_SEARCH).is_none());
+
+        let guidance = stats.tool_guidance(TaskClass::Simple).unwrap();
+        assert!(guidance.contains("simple_search typically answers in 200ms"));
+        assert!(guidance.contains("ast_grep_search takes 4.0s"));
+        assert!(stats.tool_guidance(TaskClass::Complex).is_none());
+
+        assert!(!output_succeeded(&json!({ "exit_code": 1 })));
+        assert!(output_succeeded(&json!({ "stdout": "ok" })));
+        Ok(())
+    }
+}
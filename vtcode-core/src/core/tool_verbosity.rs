@@ -0,0 +1,284 @@
+//! Compact tool results for the model
+//!
+//! A passing `cargo test` or a long build log costs thousands of tokens and
+//! tells the model little beyond "it worked". With `[tools.verbosity]`,
+//! results of tools whose policy allows it are replaced in the conversation by
+//! a summary: the short fields of the result, line and item counts, and key
+//! lines such as errors, warnings and test totals. The full result goes to the
+//! [`ToolResultStore`] and the model can fetch it with `get_full_output`.
+//! Failed calls under the `adaptive` policy are always sent whole, since that
+//! is when the details matter.
+
+use serde_json::{Map, Value, json};
+
+use crate::config::constants::tools;
+use crate::config::{ToolVerbosityConfig, VerbosityPolicy};
+use crate::core::tool_result_store::ToolResultStore;
+use crate::llm::provider::ToolDefinition;
+
+/// Strings up to this many characters are kept as they are.
+const SHORT_STRING_CHARS: usize = 200;
+/// Items of a long array shown in a summary.
+const ARRAY_SAMPLE: usize = 5;
+/// Last lines of a text field always kept, since that is where totals and
+/// final errors tend to be.
+const TAIL_LINES: usize = 3;
+/// Lines containing these words (case-insensitive) are key lines.
+const KEY_LINE_MARKERS: &[&str] = &[
+    "error",
+    "warning",
+    "fail",
+    "panic",
+    "exception",
+    "traceback",
+    "test result",
+    "passed",
+    "finished",
+];
+
+/// Decides per tool whether a result is summarized.
+pub struct ToolVerbosity {
+    config: ToolVerbosityConfig,
+}
+
+impl ToolVerbosity {
+    pub fn new(config: &ToolVerbosityConfig) -> Self {
+        Self {
+            config: config.clone(),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    pub fn policy(&self, tool: &str) -> VerbosityPolicy {
+        if !self.config.enabled {
+            return VerbosityPolicy::Full;
+        }
+        self.config
+            .policies
+            .get(tool)
+            .copied()
+            .unwrap_or(self.config.default_policy)
+    }
+
+    /// Summary to send instead of `output`, with the full result kept in
+    /// `store`. `None` when the result should be sent whole.
+    pub fn compact(
+        &self,
+        tool: &str,
+        output: &Value,
+        succeeded: bool,
+        store: &mut ToolResultStore,
+    ) -> Option<Value> {
+        match self.policy(tool) {
+            VerbosityPolicy::Full => return None,
+            VerbosityPolicy::Adaptive if !succeeded => return None,
+            VerbosityPolicy::Adaptive | VerbosityPolicy::Summary => {}
+        }
+        let fields = output.as_object()?;
+        let full = serde_json::to_string(output).ok()?;
+        if full.len() < self.config.min_bytes {
+            return None;
+        }
+        let mut summary = summarize_fields(fields, self.config.key_lines);
+        match store.keep(&full) {
+            Ok(id) => {
+                summary.insert("result_id".to_string(), json!(id));
+                summary.insert(
+                    "summarized".to_string(),
+                    json!(format!(
+                        "Output shortened from {} bytes; call {} with this result_id for all of it.",
+                        full.len(),
+                        tools::GET_FULL_OUTPUT
+                    )),
+                );
+            }
+            Err(err) => {
+                tracing::warn!(%err, tool, "failed to keep a full tool result; sending it whole");
+                return None;
+            }
+        }
+        Some(Value::Object(summary))
+    }
+}
+
+/// Response to a `get_full_output` call.
+pub fn full_output_response(store: &ToolResultStore, args: &Value) -> Value {
+    let Some(id) = args.get("result_id").and_then(Value::as_u64) else {
+        return json!({ "error": "result_id must be a non-negative integer" });
+    };
+    match store.full_output(id as usize) {
+        Some(text) => serde_json::from_str(&text).unwrap_or(Value::String(text)),
+        None => json!({
+            "error": format!("No stored output with result_id {id}; it may be from an earlier session")
+        }),
+    }
+}
+
+/// Declaration of the `get_full_output` tool.
+pub fn full_output_tool() -> ToolDefinition {
+    ToolDefinition::function(
+        tools::GET_FULL_OUTPUT.to_string(),
+        "Fetch the complete result of an earlier tool call whose output was summarized. \
+         Use the result_id from the summary; only needed when the summary lacks a detail."
+            .to_string(),
+        json!({
+            "type": "object",
+            "properties": {
+                "result_id": {
+                    "type": "integer",
+                    "description": "result_id from the summarized tool output"
+                }
+            },
+            "required": ["result_id"]
+        }),
+    )
+}
+
+fn summarize_fields(fields: &Map<String, Value>, key_lines: usize) -> Map<String, Value> {
+    let mut summary = Map::new();
+    for (name, value) in fields {
+        let compacted = match value {
+            Value::String(text) if text.chars().count() > SHORT_STRING_CHARS => {
+                summarize_text(text, key_lines)
+            }
+            Value::Array(items) if items.len() > ARRAY_SAMPLE => json!({
+                "items": items.len(),
+                "first": &items[..ARRAY_SAMPLE],
+            }),
+            Value::Object(inner) => {
+                let nested = serde_json::to_string(inner).map_or(0, |text| text.len());
+                if nested > SHORT_STRING_CHARS {
+                    Value::Object(summarize_fields(inner, key_lines))
+                } else {
+                    value.clone()
+                }
+            }
+            _ => value.clone(),
+        };
+        summary.insert(name.clone(), compacted);
+    }
+    summary
+}
+
+fn summarize_text(text: &str, key_lines: usize) -> Value {
+    let lines: Vec<&str> = text.lines().collect();
+    let tail_start = lines.len().saturating_sub(TAIL_LINES);
+    let mut kept: Vec<usize> = lines[..tail_start]
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| is_key_line(line))
+        .map(|(index, _)| index)
+        .take(key_lines.saturating_sub(TAIL_LINES))
+        .collect();
+    kept.extend(tail_start..lines.len());
+    json!({
+        "lines": lines.len(),
+        "bytes": text.len(),
+        "key_lines": kept
+            .into_iter()
+            .map(|index| truncate(lines[index].trim_end()))
+            .collect::<Vec<_>>(),
+    })
+}
+
+fn is_key_line(line: &str) -> bool {
+    let lower = line.to_lowercase();
+    KEY_LINE_MARKERS.iter().any(|marker| lower.contains(marker))
+}
+
+fn truncate(line: &str) -> String {
+    if line.chars().count() <= SHORT_STRING_CHARS {
+        return line.to_string();
+    }
+    let mut shortened: String = line.chars().take(SHORT_STRING_CHARS - 1).collect();
+    shortened.push('…');
+    shortened
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cargo_test_output(success: bool) -> Value {
+        let mut stdout: String = (0..200)
+            .map(|index| format!("test module::case_{index} ... ok\n"))
+            .collect();
+        stdout.push_str("warning: unused variable `x`\n");
+        stdout.push_str("test result: ok. 200 passed; 0 failed\n\n");
+        json!({
+            "success": success,
+            "exit_code": if success { 0 } else { 101 },
+            "command": "cargo test",
+            "stdout": stdout,
+            "stderr": "",
+        })
+    }
+
+    #[test]
+    fn summarizes_successful_commands_and_keeps_the_full_result() {
+        let verbosity = ToolVerbosity::new(&ToolVerbosityConfig::default());
+        let mut store = ToolResultStore::disabled();
+        let output = cargo_test_output(true);
+
+        let summary = verbosity
+            .compact("run_terminal_cmd", &output, true, &mut store)
+            .expect("large successful output is summarized");
+        assert_eq!(summary["exit_code"], 0);
+        assert_eq!(summary["command"], "cargo test");
+        assert_eq!(summary["stdout"]["lines"], 203);
+        let key_lines = summary["stdout"]["key_lines"].as_array().unwrap();
+        assert!(
+            key_lines
+                .iter()
+                .any(|line| line == "warning: unused variable `x`")
+        );
+        assert!(
+            key_lines
+                .iter()
+                .any(|line| line == "test result: ok. 200 passed; 0 failed")
+        );
+        assert!(serde_json::to_string(&summary).unwrap().len() < 1024);
+
+        let full = full_output_response(&store, &json!({ "result_id": summary["result_id"] }));
+        assert_eq!(full, output);
+        assert!(full_output_response(&store, &json!({ "result_id": 99 }))["error"].is_string());
+    }
+
+    #[test]
+    fn sends_failures_small_results_and_full_policy_tools_whole() {
+        let verbosity = ToolVerbosity::new(&ToolVerbosityConfig::default());
+        let mut store = ToolResultStore::disabled();
+        let failed = cargo_test_output(false);
+
+        assert!(
+            verbosity
+                .compact("run_terminal_cmd", &failed, false, &mut store)
+                .is_none()
+        );
+        assert!(
+            verbosity
+                .compact("read_file", &cargo_test_output(true), true, &mut store)
+                .is_none()
+        );
+        let small = json!({ "success": true, "exit_code": 0, "stdout": "ok" });
+        assert!(
+            verbosity
+                .compact("bash", &small, true, &mut store)
+                .is_none()
+        );
+
+        let mut config = ToolVerbosityConfig::default();
+        config
+            .policies
+            .insert("run_terminal_cmd".to_string(), VerbosityPolicy::Summary);
+        let always = ToolVerbosity::new(&config);
+        assert!(
+            always
+                .compact("run_terminal_cmd", &failed, false, &mut store)
+                .is_some()
+        );
+    }
+}
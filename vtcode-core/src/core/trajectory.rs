@@ -1,44 +1,138 @@
-use serde::Serialize;
-use std::fs::{OpenOptions, create_dir_all};
-use std::io::Write;
-use std::path::{Path, PathBuf};
+use crate::core::evidence::{ClaimKind, ClaimLink, ClaimStatus, Evidence, EvidenceLog};
+use crate::core::journal::{JournalOptions, JournalWriter};
+use crate::llm::provider::Usage;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::fs::create_dir_all;
+use std::path::Path;
+use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct TrajectoryLogger {
-    path: PathBuf,
-    enabled: bool,
+    journal: Option<JournalWriter>,
+    /// Tool calls logged since the session started, shared between clones
+    tally: Arc<Mutex<ToolTally>>,
+}
+
+#[derive(Debug, Default)]
+struct ToolTally {
+    calls: usize,
+    errors: usize,
+}
+
+/// Totals for one chat session, logged as a `session` record when it ends.
+///
+/// These records are what `vtcode trajectory --trends` aggregates over time.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub session_id: String,
+    pub provider: String,
+    pub model: String,
+    /// Short hash of the configuration the session ran with
+    #[serde(default)]
+    pub config_hash: String,
+    /// Completed turns, i.e. user requests the agent finished
+    pub turns: usize,
+    #[serde(default)]
+    pub tool_calls: usize,
+    #[serde(default)]
+    pub tool_errors: usize,
+    /// Turns that put a file back to an earlier state
+    #[serde(default)]
+    pub reverts: usize,
+    #[serde(default)]
+    pub prompt_tokens: u64,
+    #[serde(default)]
+    pub completion_tokens: u64,
+    #[serde(default)]
+    pub interrupted: bool,
+    /// Unix timestamps of the session start and end
+    pub started_at: i64,
+    pub ts: i64,
+}
+
+impl SessionRecord {
+    pub fn new(
+        session_id: impl Into<String>,
+        provider: impl Into<String>,
+        model: impl Into<String>,
+        config_hash: impl Into<String>,
+    ) -> Self {
+        let now = chrono::Utc::now().timestamp();
+        Self {
+            session_id: session_id.into(),
+            provider: provider.into(),
+            model: model.into(),
+            config_hash: config_hash.into(),
+            started_at: now,
+            ts: now,
+            ..Default::default()
+        }
+    }
+
+    /// Add the token usage reported for one model response.
+    pub fn record_usage(&mut self, usage: Option<&Usage>) {
+        if let Some(usage) = usage {
+            self.prompt_tokens += u64::from(usage.prompt_tokens);
+            self.completion_tokens += u64::from(usage.completion_tokens);
+        }
+    }
+
+    pub fn total_tokens(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+}
+
+/// Short, stable hash of a configuration, used to spot sessions that ran
+/// with different settings. Keys are sorted before hashing.
+pub fn config_hash<T: Serialize>(config: &T) -> String {
+    use sha2::{Digest, Sha256};
+    let Ok(value) = serde_json::to_value(config) else {
+        return String::new();
+    };
+    let mut hasher = Sha256::new();
+    hasher.update(value.to_string().as_bytes());
+    let digest = format!("{:x}", hasher.finalize());
+    digest[..12].to_string()
 }
 
 impl TrajectoryLogger {
     pub fn new(workspace: &Path) -> Self {
+        Self::with_journal_options(workspace, JournalOptions::default())
+    }
+
+    /// Create a logger whose records are written by a background journal
+    /// configured with `options`.
+    pub fn with_journal_options(workspace: &Path, options: JournalOptions) -> Self {
         let dir = workspace.join("logs");
         let _ = create_dir_all(&dir);
         let path = dir.join("trajectory.jsonl");
         Self {
-            path,
-            enabled: true,
+            journal: JournalWriter::spawn(path, options).ok(),
+            tally: Arc::default(),
         }
     }
 
     pub fn disabled() -> Self {
         Self {
-            path: PathBuf::from("/dev/null"),
-            enabled: false,
+            journal: None,
+            tally: Arc::default(),
         }
     }
 
     pub fn log<T: Serialize>(&self, record: &T) {
-        if !self.enabled {
+        let Some(journal) = self.journal.as_ref() else {
             return;
-        }
+        };
         if let Ok(line) = serde_json::to_string(record) {
-            if let Ok(mut f) = OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open(&self.path)
-            {
-                let _ = writeln!(f, "{}", line);
-            }
+            journal.append(line);
+        }
+    }
+
+    /// Block until all queued records have been written to disk.
+    pub fn flush(&self) {
+        if let Some(journal) = self.journal.as_ref() {
+            journal.flush();
         }
     }
 
@@ -63,7 +157,112 @@ impl TrajectoryLogger {
         self.log(&rec);
     }
 
-    pub fn log_tool_call(&self, turn: usize, name: &str, args: &serde_json::Value, ok: bool) {
+    /// Record which credential profile served a provider request.
+    pub fn log_credential(&self, provider: &str, profile: &str, rotated_from: &[String]) {
+        #[derive(Serialize)]
+        struct CredentialRec<'a> {
+            kind: &'static str,
+            provider: &'a str,
+            profile: &'a str,
+            #[serde(skip_serializing_if = "<[String]>::is_empty")]
+            rotated_from: &'a [String],
+            ts: i64,
+        }
+        let rec = CredentialRec {
+            kind: "credential",
+            provider,
+            profile,
+            rotated_from,
+            ts: chrono::Utc::now().timestamp(),
+        };
+        self.log(&rec);
+    }
+
+    /// Record which provider served a request when failover is configured.
+    pub fn log_provider(&self, provider: &str, model: &str, failed_over_from: &[String]) {
+        #[derive(Serialize)]
+        struct ProviderRec<'a> {
+            kind: &'static str,
+            provider: &'a str,
+            model: &'a str,
+            #[serde(skip_serializing_if = "<[String]>::is_empty")]
+            failed_over_from: &'a [String],
+            ts: i64,
+        }
+        let rec = ProviderRec {
+            kind: "provider",
+            provider,
+            model,
+            failed_over_from,
+            ts: chrono::Utc::now().timestamp(),
+        };
+        self.log(&rec);
+    }
+
+    /// Record the user's answer to a request to write outside the workspace.
+    pub fn log_write_grant(&self, path: &str, granted: bool, expires_at: Option<u64>) {
+        #[derive(Serialize)]
+        struct WriteGrantRec<'a> {
+            kind: &'static str,
+            path: &'a str,
+            granted: bool,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            expires_at: Option<u64>,
+            ts: i64,
+        }
+        let rec = WriteGrantRec {
+            kind: "write_grant",
+            path,
+            granted,
+            expires_at,
+            ts: chrono::Utc::now().timestamp(),
+        };
+        self.log(&rec);
+    }
+
+    /// Record a claim from the agent's answer with the tool results linked to it.
+    pub fn log_claim(&self, link: &ClaimLink, evidence: &EvidenceLog) {
+        #[derive(Serialize)]
+        struct ClaimRec<'a> {
+            kind: &'static str,
+            turn: usize,
+            claim: &'a str,
+            claim_kind: ClaimKind,
+            status: ClaimStatus,
+            evidence: Vec<&'a Evidence>,
+            ts: i64,
+        }
+        let rec = ClaimRec {
+            kind: "claim",
+            turn: evidence.turn(),
+            claim: &link.claim,
+            claim_kind: link.kind,
+            status: link.status,
+            evidence: link
+                .evidence
+                .iter()
+                .filter_map(|id| evidence.get(*id))
+                .collect(),
+            ts: chrono::Utc::now().timestamp(),
+        };
+        self.log(&rec);
+    }
+
+    pub fn log_tool_call(
+        &self,
+        turn: usize,
+        name: &str,
+        args: &serde_json::Value,
+        ok: bool,
+        schema_version: Option<u32>,
+    ) {
+        {
+            let mut tally = self.tally.lock();
+            tally.calls += 1;
+            if !ok {
+                tally.errors += 1;
+            }
+        }
         #[derive(Serialize)]
         struct ToolRec<'a> {
             kind: &'static str,
@@ -71,6 +270,8 @@ impl TrajectoryLogger {
             name: &'a str,
             args: serde_json::Value,
             ok: bool,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            schema_version: Option<u32>,
             ts: i64,
         }
         let rec = ToolRec {
@@ -79,10 +280,31 @@ impl TrajectoryLogger {
             name,
             args: args.clone(),
             ok,
+            schema_version,
             ts: chrono::Utc::now().timestamp(),
         };
         self.log(&rec);
     }
+
+    /// Log the end of a session, filling in the tool calls tallied so far.
+    pub fn log_session(&self, mut record: SessionRecord) {
+        #[derive(Serialize)]
+        struct SessionRec<'a> {
+            kind: &'static str,
+            #[serde(flatten)]
+            record: &'a SessionRecord,
+        }
+        {
+            let tally = self.tally.lock();
+            record.tool_calls = tally.calls;
+            record.tool_errors = tally.errors;
+        }
+        record.ts = chrono::Utc::now().timestamp();
+        self.log(&SessionRec {
+            kind: "session",
+            record: &record,
+        });
+    }
 }
 
 #[cfg(test)]
@@ -103,6 +325,7 @@ mod tests {
             "standard",
             "test user input for logging",
         );
+        logger.flush();
 
         // Check that the log file was created and contains expected content
         let log_path = temp_dir.path().join("logs/trajectory.jsonl");
@@ -121,4 +344,37 @@ mod tests {
         assert_eq!(record["input_preview"], "test user input for logging");
         assert!(record["ts"].is_number());
     }
+
+    #[test]
+    fn session_record_includes_tallied_tool_calls() {
+        let temp_dir = TempDir::new().unwrap();
+        let logger = TrajectoryLogger::new(temp_dir.path());
+        let args = serde_json::json!({});
+        logger.log_tool_call(1, "read_file", &args, true, None);
+        logger
+            .clone()
+            .log_tool_call(1, "edit_file", &args, false, None);
+
+        let mut session = SessionRecord::new("s1", "openai", "gpt-5", "abc123");
+        session.turns = 2;
+        session.record_usage(Some(&Usage {
+            prompt_tokens: 100,
+            completion_tokens: 20,
+            total_tokens: 120,
+            cached_prompt_tokens: None,
+            cache_creation_tokens: None,
+            cache_read_tokens: None,
+        }));
+        logger.log_session(session);
+        logger.flush();
+
+        let content = fs::read_to_string(temp_dir.path().join("logs/trajectory.jsonl")).unwrap();
+        let line = content.lines().last().unwrap();
+        let record: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert_eq!(record["kind"], "session");
+        let parsed: SessionRecord = serde_json::from_str(line).unwrap();
+        assert_eq!((parsed.tool_calls, parsed.tool_errors), (2, 1));
+        assert_eq!(parsed.total_tokens(), 120);
+        assert_eq!(parsed.config_hash, "abc123");
+    }
 }
@@ -0,0 +1,402 @@
+//! Long-term trends over the `session` records of the trajectory log
+//!
+//! Sessions are grouped by day, week or month to show how turns to
+//! completion, reverts, token spend and tool failures move over time. Points
+//! where the model or the configuration changed are checked for regressions
+//! by comparing the sessions just before the change with those just after.
+
+use super::trajectory::SessionRecord;
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::str::FromStr;
+
+/// Sessions compared on each side of a change.
+const COMPARISON_WINDOW: usize = 10;
+/// Fewest sessions needed on each side before a change is judged.
+const MIN_SESSIONS_PER_SIDE: usize = 3;
+/// Relative increase reported as a regression.
+const REGRESSION_THRESHOLD: f64 = 0.2;
+
+/// Read every `session` record from a trajectory log, oldest first.
+pub fn read_sessions(path: &Path) -> Result<Vec<SessionRecord>> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut sessions = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(&line) else {
+            continue;
+        };
+        if value.get("kind").and_then(|kind| kind.as_str()) != Some("session") {
+            continue;
+        }
+        if let Ok(session) = serde_json::from_value::<SessionRecord>(value) {
+            sessions.push(session);
+        }
+    }
+    sessions.sort_by_key(|session| session.ts);
+    Ok(sessions)
+}
+
+/// Length of the buckets sessions are grouped into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrendPeriod {
+    Day,
+    Week,
+    Month,
+}
+
+impl TrendPeriod {
+    /// Label of the bucket containing `ts`, e.g. `2025-W14`.
+    pub fn bucket(self, ts: i64) -> String {
+        let Some(time) = DateTime::<Utc>::from_timestamp(ts, 0) else {
+            return ts.to_string();
+        };
+        let format = match self {
+            Self::Day => "%Y-%m-%d",
+            Self::Week => "%G-W%V",
+            Self::Month => "%Y-%m",
+        };
+        time.format(format).to_string()
+    }
+}
+
+impl FromStr for TrendPeriod {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "day" | "daily" => Ok(Self::Day),
+            "week" | "weekly" => Ok(Self::Week),
+            "month" | "monthly" => Ok(Self::Month),
+            other => bail!("Unknown trend period '{}'; use day, week or month", other),
+        }
+    }
+}
+
+/// Session metrics tracked over time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrendMetric {
+    Turns,
+    Reverts,
+    Tokens,
+    ToolErrorRate,
+}
+
+impl TrendMetric {
+    pub const ALL: [TrendMetric; 4] = [
+        TrendMetric::Turns,
+        TrendMetric::Reverts,
+        TrendMetric::Tokens,
+        TrendMetric::ToolErrorRate,
+    ];
+
+    fn value(self, session: &SessionRecord) -> f64 {
+        match self {
+            Self::Turns => session.turns as f64,
+            Self::Reverts => session.reverts as f64,
+            Self::Tokens => session.total_tokens() as f64,
+            Self::ToolErrorRate if session.tool_calls == 0 => 0.0,
+            Self::ToolErrorRate => session.tool_errors as f64 / session.tool_calls as f64,
+        }
+    }
+
+    /// Smallest absolute change worth reporting, so that a handful of
+    /// sessions moving from one revert to two is not flagged.
+    fn min_delta(self) -> f64 {
+        match self {
+            Self::Turns | Self::Reverts => 0.5,
+            Self::Tokens => 1_000.0,
+            Self::ToolErrorRate => 0.05,
+        }
+    }
+
+    fn format(self, value: f64) -> String {
+        match self {
+            Self::Turns | Self::Reverts => format!("{:.1}", value),
+            Self::Tokens => format!("{:.0}", value),
+            Self::ToolErrorRate => format!("{:.1}%", value * 100.0),
+        }
+    }
+}
+
+/// Averages for the sessions of one period.
+#[derive(Debug, Clone, Serialize)]
+pub struct PeriodAggregate {
+    pub period: String,
+    pub sessions: usize,
+    pub interrupted: usize,
+    pub avg_turns: f64,
+    pub avg_reverts: f64,
+    pub avg_tokens: f64,
+    /// Failed tool calls over all tool calls in the period
+    pub tool_error_rate: f64,
+}
+
+/// A model or configuration change between two consecutive sessions.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangePoint {
+    /// Index of the first session after the change
+    pub index: usize,
+    pub ts: i64,
+    /// `model` or `config`
+    pub field: &'static str,
+    pub from: String,
+    pub to: String,
+}
+
+impl ChangePoint {
+    fn describe(&self) -> String {
+        match self.field {
+            "model" => format!("the model changed from {} to {}", self.from, self.to),
+            _ => format!(
+                "the configuration changed ({} → {})",
+                short_hash(&self.from),
+                short_hash(&self.to)
+            ),
+        }
+    }
+}
+
+/// A metric that got worse after a change.
+#[derive(Debug, Clone, Serialize)]
+pub struct Regression {
+    pub metric: TrendMetric,
+    pub change: ChangePoint,
+    /// Mean over the sessions before and after the change
+    pub before: f64,
+    pub after: f64,
+    pub sessions_before: usize,
+    pub sessions_after: usize,
+}
+
+impl Regression {
+    /// Relative increase, or `None` when the metric was zero before.
+    pub fn increase(&self) -> Option<f64> {
+        (self.before > 0.0).then(|| (self.after - self.before) / self.before)
+    }
+
+    /// One-line summary, e.g. "Sessions now take 30% more turns since ...".
+    pub fn describe(&self) -> String {
+        let more = match self.increase() {
+            Some(increase) => format!("{:.0}% more", increase * 100.0),
+            None => "more".to_string(),
+        };
+        let what = match self.metric {
+            TrendMetric::Turns => format!("Sessions now take {} turns", more),
+            TrendMetric::Reverts => format!("Sessions now revert {} file changes", more),
+            TrendMetric::Tokens => format!("Sessions now spend {} tokens", more),
+            TrendMetric::ToolErrorRate => format!("Tool calls now fail {} often", more),
+        };
+        format!(
+            "{} since {} ({} → {}, {} sessions before, {} after)",
+            what,
+            self.change.describe(),
+            self.metric.format(self.before),
+            self.metric.format(self.after),
+            self.sessions_before,
+            self.sessions_after
+        )
+    }
+}
+
+/// Per-period aggregates and detected regressions.
+#[derive(Debug, Clone, Serialize)]
+pub struct TrendReport {
+    pub sessions: usize,
+    pub periods: Vec<PeriodAggregate>,
+    pub changes: Vec<ChangePoint>,
+    pub regressions: Vec<Regression>,
+}
+
+impl TrendReport {
+    /// Build the report for sessions ordered oldest first.
+    pub fn build(sessions: &[SessionRecord], period: TrendPeriod) -> Self {
+        let changes = change_points(sessions);
+        let mut regressions = Vec::new();
+        for (position, change) in changes.iter().enumerate() {
+            // Compare within the stable stretches on either side of the change.
+            let previous = position
+                .checked_sub(1)
+                .map_or(0, |prior| changes[prior].index);
+            let next = changes
+                .get(position + 1)
+                .map_or(sessions.len(), |later| later.index);
+            let before_start = previous.max(change.index.saturating_sub(COMPARISON_WINDOW));
+            let after_end = next.min(change.index + COMPARISON_WINDOW);
+            let before = &sessions[before_start..change.index];
+            let after = &sessions[change.index..after_end];
+            if before.len() < MIN_SESSIONS_PER_SIDE || after.len() < MIN_SESSIONS_PER_SIDE {
+                continue;
+            }
+
+            for metric in TrendMetric::ALL {
+                let before_mean = mean(before, metric);
+                let after_mean = mean(after, metric);
+                let delta = after_mean - before_mean;
+                if delta >= metric.min_delta() && delta >= before_mean * REGRESSION_THRESHOLD {
+                    regressions.push(Regression {
+                        metric,
+                        change: change.clone(),
+                        before: before_mean,
+                        after: after_mean,
+                        sessions_before: before.len(),
+                        sessions_after: after.len(),
+                    });
+                }
+            }
+        }
+
+        Self {
+            sessions: sessions.len(),
+            periods: aggregate_periods(sessions, period),
+            changes,
+            regressions,
+        }
+    }
+}
+
+fn change_points(sessions: &[SessionRecord]) -> Vec<ChangePoint> {
+    let mut changes = Vec::new();
+    for (index, pair) in sessions.windows(2).enumerate() {
+        let (previous, current) = (&pair[0], &pair[1]);
+        let (field, from, to) = if previous.model != current.model {
+            ("model", &previous.model, &current.model)
+        } else if !previous.config_hash.is_empty()
+            && !current.config_hash.is_empty()
+            && previous.config_hash != current.config_hash
+        {
+            ("config", &previous.config_hash, &current.config_hash)
+        } else {
+            continue;
+        };
+        changes.push(ChangePoint {
+            index: index + 1,
+            ts: current.started_at,
+            field,
+            from: from.clone(),
+            to: to.clone(),
+        });
+    }
+    changes
+}
+
+fn aggregate_periods(sessions: &[SessionRecord], period: TrendPeriod) -> Vec<PeriodAggregate> {
+    let mut periods: Vec<(String, Vec<&SessionRecord>)> = Vec::new();
+    for session in sessions {
+        let bucket = period.bucket(session.ts);
+        match periods.last_mut() {
+            Some((label, members)) if *label == bucket => members.push(session),
+            _ => periods.push((bucket, vec![session])),
+        }
+    }
+
+    periods
+        .into_iter()
+        .map(|(period, members)| {
+            let count = members.len() as f64;
+            let average = |value: fn(&SessionRecord) -> f64| {
+                members.iter().map(|session| value(session)).sum::<f64>() / count
+            };
+            let calls: usize = members.iter().map(|session| session.tool_calls).sum();
+            let errors: usize = members.iter().map(|session| session.tool_errors).sum();
+            PeriodAggregate {
+                sessions: members.len(),
+                interrupted: members.iter().filter(|session| session.interrupted).count(),
+                avg_turns: average(|session| session.turns as f64),
+                avg_reverts: average(|session| session.reverts as f64),
+                avg_tokens: average(|session| session.total_tokens() as f64),
+                tool_error_rate: if calls == 0 {
+                    0.0
+                } else {
+                    errors as f64 / calls as f64
+                },
+                period,
+            }
+        })
+        .collect()
+}
+
+fn mean(sessions: &[SessionRecord], metric: TrendMetric) -> f64 {
+    sessions
+        .iter()
+        .map(|session| metric.value(session))
+        .sum::<f64>()
+        / sessions.len() as f64
+}
+
+fn short_hash(hash: &str) -> &str {
+    hash.get(..8).unwrap_or(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 86_400;
+
+    fn session(day: i64, model: &str, turns: usize) -> SessionRecord {
+        SessionRecord {
+            session_id: format!("s{}", day),
+            provider: "openai".to_string(),
+            model: model.to_string(),
+            config_hash: "cfg1".to_string(),
+            turns,
+            tool_calls: 10,
+            tool_errors: 1,
+            prompt_tokens: 5_000,
+            completion_tokens: 500,
+            started_at: day * DAY,
+            ts: day * DAY + 600,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn flags_more_turns_after_model_change() {
+        let mut sessions: Vec<_> = (0..5).map(|day| session(day, "model-a", 4)).collect();
+        sessions.extend((5..10).map(|day| session(day, "model-b", 6)));
+        let report = TrendReport::build(&sessions, TrendPeriod::Day);
+
+        assert_eq!(report.periods.len(), 10);
+        assert_eq!(report.changes.len(), 1);
+        assert_eq!(report.regressions.len(), 1);
+        let regression = &report.regressions[0];
+        assert_eq!(regression.metric, TrendMetric::Turns);
+        assert_eq!(
+            regression.describe(),
+            "Sessions now take 50% more turns since the model changed from model-a to model-b \
+             (4.0 → 6.0, 5 sessions before, 5 after)"
+        );
+    }
+
+    #[test]
+    fn ignores_changes_without_enough_sessions_or_small_deltas() {
+        let mut sessions: Vec<_> = (0..2).map(|day| session(day, "model-a", 2)).collect();
+        sessions.extend((2..6).map(|day| session(day, "model-b", 8)));
+        let mut config_change: Vec<_> = (6..10).map(|day| session(day, "model-b", 8)).collect();
+        for session in &mut config_change {
+            session.config_hash = "cfg2".to_string();
+            session.tool_errors = 2;
+        }
+        sessions.extend(config_change);
+
+        let report = TrendReport::build(&sessions, TrendPeriod::Week);
+        assert_eq!(report.changes.len(), 2);
+        assert_eq!(report.changes[1].field, "config");
+        // Too few sessions before the model change to judge it; the config
+        // change doubles the tool error rate but leaves turns alone.
+        assert_eq!(report.regressions.len(), 1);
+        assert_eq!(report.regressions[0].metric, TrendMetric::ToolErrorRate);
+        assert_eq!(report.periods.iter().map(|p| p.sessions).sum::<usize>(), 10);
+        assert_eq!(
+            "monthly".parse::<TrendPeriod>().unwrap(),
+            TrendPeriod::Month
+        );
+        assert!("hourly".parse::<TrendPeriod>().is_err());
+    }
+}
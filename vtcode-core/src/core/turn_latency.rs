@@ -0,0 +1,188 @@
+//! Where the time of one turn went
+//!
+//! [`TurnLatency`] is started when the user submits a prompt and is told about
+//! each model request, tool call and approval prompt of the turn. The
+//! resulting [`LatencyBreakdown`] splits the wall time into time to first
+//! token, generation, tool execution per call and the remaining overhead
+//! (context trimming, rendering, retries), with time spent waiting on the user
+//! left out. Requests and tool calls are also recorded in the global
+//! [`PROFILER`](crate::core::performance_profiler::PROFILER).
+
+use crate::core::performance_profiler::PROFILER;
+use std::time::{Duration, Instant};
+
+/// Tool calls named in the compact summary; the rest are counted.
+const SUMMARY_TOOL_CALLS: usize = 3;
+
+#[derive(Debug, Clone)]
+pub struct TurnLatency {
+    started: Instant,
+    first_token: Option<Duration>,
+    generation: Duration,
+    requests: usize,
+    tools: Vec<(String, Duration)>,
+    waiting: Duration,
+}
+
+impl Default for TurnLatency {
+    fn default() -> Self {
+        Self::start()
+    }
+}
+
+impl TurnLatency {
+    pub fn start() -> Self {
+        Self {
+            started: Instant::now(),
+            first_token: None,
+            generation: Duration::ZERO,
+            requests: 0,
+            tools: Vec::new(),
+            waiting: Duration::ZERO,
+        }
+    }
+
+    /// A model request that started at `started`. `first_token` is when the
+    /// first streamed token arrived, if the response was streamed.
+    pub fn record_request(
+        &mut self,
+        started: Instant,
+        first_token: Option<Instant>,
+        success: bool,
+    ) {
+        let elapsed = started.elapsed();
+        self.generation += elapsed;
+        self.requests += 1;
+        if self.first_token.is_none() {
+            let arrived = first_token.unwrap_or_else(Instant::now);
+            self.first_token = Some(arrived.saturating_duration_since(self.started));
+        }
+        PROFILER.record_operation("llm_request", elapsed, success);
+    }
+
+    pub fn record_tool(&mut self, name: &str, elapsed: Duration, success: bool) {
+        self.tools.push((name.to_string(), elapsed));
+        PROFILER.record_operation(&format!("tool:{}", name), elapsed, success);
+    }
+
+    /// Time spent waiting on the user, such as an approval prompt.
+    pub fn record_wait(&mut self, elapsed: Duration) {
+        self.waiting += elapsed;
+    }
+
+    pub fn finish(&self) -> LatencyBreakdown {
+        let total = self.started.elapsed().saturating_sub(self.waiting);
+        let tool_time: Duration = self.tools.iter().map(|(_, elapsed)| *elapsed).sum();
+        LatencyBreakdown {
+            total,
+            first_token: self.first_token,
+            generation: self.generation,
+            requests: self.requests,
+            tools: self.tools.clone(),
+            overhead: total.saturating_sub(self.generation + tool_time),
+        }
+    }
+}
+
+/// Latency of a finished turn.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatencyBreakdown {
+    /// Wall time of the turn, without time spent waiting on the user
+    pub total: Duration,
+    /// From submitting the prompt to the first token of the first response
+    pub first_token: Option<Duration>,
+    /// Time inside model requests, including time to first token
+    pub generation: Duration,
+    pub requests: usize,
+    /// Each tool call in order
+    pub tools: Vec<(String, Duration)>,
+    pub overhead: Duration,
+}
+
+impl LatencyBreakdown {
+    /// One line for the status bar, e.g.
+    /// `4.2s · first token 0.8s · gen 2.1s · tools 1.5s (run_terminal_cmd 1.2s, read_file 0.3s) · overhead 0.6s`.
+    /// The slowest calls are named; the rest are counted.
+    pub fn summary(&self) -> String {
+        let mut parts = vec![format_duration(self.total)];
+        if let Some(first_token) = self.first_token {
+            parts.push(format!("first token {}", format_duration(first_token)));
+        }
+        let generation = format_duration(self.generation);
+        parts.push(if self.requests > 1 {
+            format!("gen {} ({} requests)", generation, self.requests)
+        } else {
+            format!("gen {}", generation)
+        });
+        if !self.tools.is_empty() {
+            let tool_time: Duration = self.tools.iter().map(|(_, elapsed)| *elapsed).sum();
+            let mut slowest: Vec<&(String, Duration)> = self.tools.iter().collect();
+            slowest.sort_by(|a, b| b.1.cmp(&a.1));
+            let mut calls: Vec<String> = slowest
+                .iter()
+                .take(SUMMARY_TOOL_CALLS)
+                .map(|(name, elapsed)| format!("{} {}", name, format_duration(*elapsed)))
+                .collect();
+            if slowest.len() > SUMMARY_TOOL_CALLS {
+                calls.push(format!("+{} more", slowest.len() - SUMMARY_TOOL_CALLS));
+            }
+            parts.push(format!(
+                "tools {} ({})",
+                format_duration(tool_time),
+                calls.join(", ")
+            ));
+        }
+        parts.push(format!("overhead {}", format_duration(self.overhead)));
+        parts.join(" · ")
+    }
+}
+
+/// `850ms`, `4.2s` or `2m05s`.
+pub fn format_duration(duration: Duration) -> String {
+    let millis = duration.as_millis();
+    if millis < 1_000 {
+        format!("{}ms", millis)
+    } else if millis < 60_000 {
+        format!("{:.1}s", duration.as_secs_f64())
+    } else {
+        let secs = duration.as_secs();
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_the_slowest_calls() {
+        let breakdown = LatencyBreakdown {
+            total: Duration::from_millis(4_200),
+            first_token: Some(Duration::from_millis(800)),
+            generation: Duration::from_millis(2_100),
+            requests: 2,
+            tools: vec![
+                ("read_file".to_string(), Duration::from_millis(40)),
+                ("run_terminal_cmd".to_string(), Duration::from_millis(1_200)),
+                ("grep_search".to_string(), Duration::from_millis(150)),
+                ("list_files".to_string(), Duration::from_millis(10)),
+            ],
+            overhead: Duration::from_millis(700),
+        };
+        assert_eq!(
+            breakdown.summary(),
+            "4.2s · first token 800ms · gen 2.1s (2 requests) · tools 1.4s (run_terminal_cmd 1.2s, grep_search 150ms, read_file 40ms, +1 more) · overhead 700ms"
+        );
+        assert_eq!(format_duration(Duration::from_secs(125)), "2m05s");
+
+        let mut latency = TurnLatency::start();
+        latency.record_request(Instant::now(), None, true);
+        latency.record_tool("read_file", Duration::from_millis(5), true);
+        latency.record_wait(Duration::from_secs(3600));
+        let breakdown = latency.finish();
+        assert_eq!(breakdown.total, Duration::ZERO);
+        assert_eq!(breakdown.requests, 1);
+        assert!(breakdown.first_token.is_some());
+        assert!(PROFILER.get_metrics("tool:read_file").is_some());
+    }
+}
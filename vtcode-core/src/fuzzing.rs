@@ -0,0 +1,154 @@
+//! Entry points for fuzzing the handling of model output
+//!
+//! Enabled by the `fuzzing` feature and driven by the cargo-fuzz targets in
+//! `fuzz/`. Each function feeds its input through the same parsing the agent
+//! applies to tool calls. Errors are expected and ignored; a panic means
+//! malformed model output could bring down the agent process.
+
+use crate::tools::apply_patch::{ApplyPatchInput, Patch, PatchOperation};
+use crate::tools::types::{EditInput, EnhancedTerminalInput, Input, ListInput, WriteInput};
+use crate::utils::ansi_sanitize::{AnsiSanitizer, HyperlinkMode, sanitize_ansi};
+use crate::utils::workspace_path::resolve_in_workspace;
+#[cfg(feature = "ui")]
+use ansi_to_tui::IntoText;
+use arbitrary::Arbitrary;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::path::Path;
+
+/// Workspace the fuzzed paths are resolved against; never touched on disk.
+const WORKSPACE: &str = "/fuzz/workspace";
+
+/// Typed arguments of the tools whose inputs have a schema.
+#[derive(Debug, Arbitrary)]
+pub enum ToolArguments {
+    Read(Input),
+    Write(WriteInput),
+    Edit(EditInput),
+    List(ListInput),
+    Terminal(EnhancedTerminalInput),
+    Patch(ApplyPatchInput),
+}
+
+/// Raw bytes as a model's tool call arguments: parsed as JSON, then as every
+/// tool input type, and each path argument resolved against the workspace.
+pub fn tool_arguments(data: &[u8]) {
+    let Ok(args) = serde_json::from_slice::<Value>(data) else {
+        return;
+    };
+    if let Ok(input) = parse::<Input>(&args) {
+        workspace_path(&input.path);
+    }
+    if let Ok(input) = parse::<WriteInput>(&args) {
+        workspace_path(&input.path);
+    }
+    if let Ok(input) = parse::<EditInput>(&args) {
+        workspace_path(&input.path);
+    }
+    if let Ok(input) = parse::<ListInput>(&args) {
+        workspace_path(&input.path);
+    }
+    if let Ok(input) = parse::<EnhancedTerminalInput>(&args)
+        && let Some(dir) = &input.working_dir
+    {
+        workspace_path(dir);
+    }
+    if let Ok(input) = parse::<ApplyPatchInput>(&args) {
+        patch(&input.input);
+    }
+}
+
+/// Structured arguments: serializing and parsing them again must give the
+/// same JSON, then they go through [`tool_arguments`].
+pub fn typed_tool_arguments(args: &ToolArguments) {
+    let json = match args {
+        ToolArguments::Read(input) => round_trip(input),
+        ToolArguments::Write(input) => round_trip(input),
+        ToolArguments::Edit(input) => round_trip(input),
+        ToolArguments::List(input) => round_trip(input),
+        ToolArguments::Terminal(input) => round_trip(input),
+        ToolArguments::Patch(input) => round_trip(input),
+    };
+    tool_arguments(json.to_string().as_bytes());
+}
+
+/// Parse an `apply_patch` body and apply its hunks to a small file.
+pub fn patch(input: &str) {
+    let Ok(patch) = Patch::parse(input) else {
+        return;
+    };
+    let workspace = Path::new(WORKSPACE);
+    for operation in &patch.operations {
+        match operation {
+            PatchOperation::AddFile { path, .. } | PatchOperation::DeleteFile { path } => {
+                let _ = resolve_in_workspace(workspace, path);
+            }
+            PatchOperation::UpdateFile {
+                path,
+                new_path,
+                hunks,
+            } => {
+                let _ = resolve_in_workspace(workspace, path);
+                if let Some(new_path) = new_path {
+                    let _ = resolve_in_workspace(workspace, new_path);
+                }
+                let _ =
+                    Patch::apply_hunks_to_content("fn main() {\n    println!(\"hi\");\n}\n", hunks);
+            }
+        }
+    }
+}
+
+/// Sanitize terminal output whole and split at every third character, and
+/// convert the result for the transcript when the `ui` feature is on.
+pub fn ansi(output: &str) {
+    for mode in [HyperlinkMode::Footnote, HyperlinkMode::Osc8] {
+        to_transcript(&sanitize_ansi(output, mode).with_footnotes());
+
+        let mut sanitizer = AnsiSanitizer::new(mode);
+        let chars: Vec<char> = output.chars().collect();
+        for chunk in chars.chunks(3) {
+            let chunk: String = chunk.iter().collect();
+            to_transcript(&sanitizer.sanitize(&chunk).text);
+        }
+        sanitizer.finish();
+    }
+}
+
+/// Resolve a path argument; a resolved path must stay in the workspace and
+/// resolve to itself.
+pub fn workspace_path(raw: &str) {
+    let workspace = Path::new(WORKSPACE);
+    if let Ok(resolved) = resolve_in_workspace(workspace, raw) {
+        assert!(
+            resolved.starts_with(workspace),
+            "{raw:?} left the workspace"
+        );
+        let again = resolve_in_workspace(workspace, &resolved.to_string_lossy())
+            .expect("a resolved path resolves again");
+        assert_eq!(again, resolved);
+    }
+}
+
+/// Convert sanitized output into transcript lines, as the ratatui sink does.
+fn to_transcript(text: &str) {
+    #[cfg(feature = "ui")]
+    let _ = text.into_text();
+    #[cfg(not(feature = "ui"))]
+    let _ = text;
+}
+
+fn parse<T: DeserializeOwned>(args: &Value) -> serde_json::Result<T> {
+    serde_json::from_value(args.clone())
+}
+
+fn round_trip<T: Serialize + DeserializeOwned>(input: &T) -> Value {
+    let json = serde_json::to_value(input).expect("tool arguments serialize");
+    let parsed: T = serde_json::from_value(json.clone()).expect("serialized arguments parse");
+    assert_eq!(
+        serde_json::to_value(&parsed).expect("tool arguments serialize"),
+        json
+    );
+    json
+}
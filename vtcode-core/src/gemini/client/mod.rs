@@ -8,6 +8,7 @@ use crate::gemini::models::{GenerateContentRequest, GenerateContentResponse};
 use crate::gemini::streaming::{
     StreamingError, StreamingMetrics, StreamingProcessor, StreamingResponse,
 };
+use crate::utils::network;
 use anyhow::{Context, Result};
 use reqwest::Client as ReqwestClient;
 use std::time::Instant;
@@ -29,7 +30,7 @@ impl Client {
 
     /// Create a client with custom configuration
     pub fn with_config(api_key: String, model: String, config: ClientConfig) -> Self {
-        let http_client = ReqwestClient::builder()
+        let http_client = network::client_builder(Some("gemini"))
             .pool_max_idle_per_host(config.pool_max_idle_per_host)
             .pool_idle_timeout(config.pool_idle_timeout)
             .tcp_keepalive(config.tcp_keepalive)
@@ -67,6 +67,42 @@ pub enum Part {
     FunctionResponse {
         function_response: crate::gemini::function_calling::FunctionResponse,
     },
+    #[serde(rename_all = "camelCase")]
+    ExecutableCode {
+        executable_code: ExecutableCode,
+    },
+    #[serde(rename_all = "camelCase")]
+    CodeExecutionResult {
+        code_execution_result: CodeExecutionResult,
+    },
+    #[serde(rename_all = "camelCase")]
+    InlineData {
+        inline_data: Blob,
+    },
+}
+
+/// Code the model ran with the code execution tool
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutableCode {
+    #[serde(default)]
+    pub language: Option<String>,
+    pub code: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodeExecutionResult {
+    /// `OUTCOME_OK`, `OUTCOME_FAILED` or `OUTCOME_DEADLINE_EXCEEDED`
+    pub outcome: String,
+    #[serde(default)]
+    pub output: Option<String>,
+}
+
+/// Base64-encoded file content, such as a chart produced by executed code
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Blob {
+    pub mime_type: String,
+    pub data: String,
 }
 
 impl Part {
@@ -81,8 +117,19 @@ impl Part {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tool {
-    #[serde(rename = "functionDeclarations")]
+    #[serde(
+        rename = "functionDeclarations",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub function_declarations: Vec<FunctionDeclaration>,
+    /// Enables the hosted code execution tool; serialized as `{}`
+    #[serde(
+        rename = "codeExecution",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub code_execution: Option<Value>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
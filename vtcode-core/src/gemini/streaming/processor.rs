@@ -7,6 +7,7 @@ use crate::gemini::models::{Content, Part};
 use crate::gemini::streaming::{
     StreamingCandidate, StreamingError, StreamingMetrics, StreamingResponse,
 };
+use crate::llm::sse::drain_utf8;
 use futures::stream::StreamExt;
 use reqwest::Response;
 use serde_json::Value;
@@ -34,11 +35,15 @@ impl Default for StreamingConfig {
     }
 }
 
+/// Callback for parts that are not text, such as code execution results
+pub type PartObserver = Box<dyn FnMut(&Part) + Send>;
+
 /// Streaming processor for handling real-time responses from the Gemini API
 pub struct StreamingProcessor {
     config: StreamingConfig,
     metrics: StreamingMetrics,
     current_event_data: String,
+    on_part: Option<PartObserver>,
 }
 
 impl StreamingProcessor {
@@ -48,6 +53,7 @@ impl StreamingProcessor {
             config: StreamingConfig::default(),
             metrics: StreamingMetrics::default(),
             current_event_data: String::new(),
+            on_part: None,
         }
     }
 
@@ -57,9 +63,16 @@ impl StreamingProcessor {
             config,
             metrics: StreamingMetrics::default(),
             current_event_data: String::new(),
+            on_part: None,
         }
     }
 
+    /// Call `observer` with each part that is not text as it arrives.
+    pub fn with_part_observer(mut self, observer: PartObserver) -> Self {
+        self.on_part = Some(observer);
+        self
+    }
+
     /// Process a streaming response from the Gemini API
     ///
     /// This method takes a response and processes it in real-time, calling the provided
@@ -95,6 +108,8 @@ impl StreamingProcessor {
 
         let mut _has_valid_content = false;
         let mut buffer = String::new();
+        // Bytes of a UTF-8 character split across chunks.
+        let mut pending = Vec::new();
 
         // Wait for the first chunk with a longer timeout
         let first_chunk_result = timeout(self.config.first_chunk_timeout, stream.next()).await;
@@ -105,7 +120,8 @@ impl StreamingProcessor {
                 self.metrics.total_bytes += bytes.len();
 
                 // Process the first chunk
-                buffer.push_str(&String::from_utf8_lossy(&bytes));
+                pending.extend_from_slice(&bytes);
+                buffer.push_str(&drain_utf8(&mut pending));
                 match self.process_buffer(&mut buffer, &mut accumulated_response, &mut on_chunk) {
                     Ok(valid) => _has_valid_content = valid,
                     Err(e) => return Err(e),
@@ -140,7 +156,8 @@ impl StreamingProcessor {
                     self.metrics.total_bytes += bytes.len();
 
                     // Add to buffer
-                    buffer.push_str(&String::from_utf8_lossy(&bytes));
+                    pending.extend_from_slice(&bytes);
+                    buffer.push_str(&drain_utf8(&mut pending));
 
                     // Process buffer
                     match self.process_buffer(&mut buffer, &mut accumulated_response, &mut on_chunk)
@@ -166,6 +183,7 @@ impl StreamingProcessor {
         }
 
         // Process any remaining data in the buffer
+        buffer.push_str(&String::from_utf8_lossy(&pending));
         if !buffer.is_empty() {
             match self.process_remaining_buffer(
                 &mut buffer,
@@ -300,7 +318,10 @@ impl StreamingProcessor {
             return Ok(false);
         }
 
-        if trimmed.starts_with("event:") || trimmed.starts_with("id:") {
+        if trimmed.starts_with("event:")
+            || trimmed.starts_with("id:")
+            || trimmed.starts_with("retry:")
+        {
             return Ok(false);
         }
 
@@ -462,7 +483,7 @@ impl StreamingProcessor {
 
     /// Process a streaming candidate and extract content
     fn process_candidate<F>(
-        &self,
+        &mut self,
         candidate: &StreamingCandidate,
         on_chunk: &mut F,
     ) -> Result<bool, StreamingError>
@@ -487,6 +508,14 @@ impl StreamingProcessor {
                 Part::FunctionResponse { .. } => {
                     _has_valid_content = true;
                 }
+                Part::ExecutableCode { .. }
+                | Part::CodeExecutionResult { .. }
+                | Part::InlineData { .. } => {
+                    if let Some(observer) = self.on_part.as_mut() {
+                        observer(part);
+                    }
+                    _has_valid_content = true;
+                }
             }
         }
 
@@ -526,7 +555,7 @@ impl StreamingProcessor {
                     return Err(StreamingError::ApiError {
                         status_code: code,
                         message,
-                        is_retryable: code == 429,
+                        is_retryable: code == 429 || code >= 500,
                     });
                 }
 
@@ -771,4 +800,27 @@ mod tests {
         };
         assert_eq!(combined, "Hello world");
     }
+
+    #[test]
+    fn test_ignores_heartbeats_and_retry_hints() {
+        let mut processor = StreamingProcessor::new();
+        let mut accumulated = StreamingResponse {
+            candidates: Vec::new(),
+            usage_metadata: None,
+        };
+        let mut received_chunks: Vec<String> = Vec::new();
+        let mut buffer = String::from(
+            ": keep-alive\n\nretry: 3000\nid: 1\ndata: {\"candidates\":[{\"index\":0,\"content\":{\"role\":\"model\",\"parts\":[{\"text\":\"Hi\"}]}}]}\n\n",
+        );
+
+        let mut on_chunk = |chunk: &str| {
+            received_chunks.push(chunk.to_string());
+            Ok(())
+        };
+        let has_valid = processor
+            .process_buffer(&mut buffer, &mut accumulated, &mut on_chunk)
+            .expect("heartbeats and retry hints are not payload");
+        assert!(has_valid);
+        assert_eq!(received_chunks, vec!["Hi"]);
+    }
 }
@@ -106,6 +106,8 @@ pub mod commands;
 pub mod config;
 pub mod constants;
 pub mod core;
+#[cfg(feature = "fuzzing")]
+pub mod fuzzing;
 pub mod gemini;
 pub mod llm;
 pub mod markdown_storage;
@@ -120,6 +122,8 @@ pub mod tools;
 pub mod types;
 pub mod ui;
 pub mod utils;
+#[cfg(feature = "voice")]
+pub mod voice;
 
 // Re-exports for convenience
 pub use bash_runner::BashRunner;
@@ -1,7 +1,14 @@
 use super::provider::LLMError;
-use super::providers::{
-    AnthropicProvider, GeminiProvider, OpenAIProvider, OpenRouterProvider, XAIProvider,
-};
+#[cfg(feature = "provider-anthropic")]
+use super::providers::AnthropicProvider;
+#[cfg(feature = "provider-gemini")]
+use super::providers::GeminiProvider;
+#[cfg(feature = "provider-openai")]
+use super::providers::OpenAIProvider;
+#[cfg(feature = "provider-openrouter")]
+use super::providers::OpenRouterProvider;
+#[cfg(feature = "provider-xai")]
+use super::providers::XAIProvider;
 use super::types::{BackendKind, LLMResponse};
 use crate::config::models::{ModelId, Provider};
 use async_trait::async_trait;
@@ -18,21 +25,67 @@ pub trait LLMClient: Send + Sync {
 pub type AnyClient = Box<dyn LLMClient>;
 
 /// Create a client based on the model ID
+///
+/// Models whose provider was left out of the build get a client that fails
+/// every request with an error naming the missing cargo feature.
 pub fn make_client(api_key: String, model: ModelId) -> AnyClient {
     match model.provider() {
+        #[cfg(feature = "provider-gemini")]
         Provider::Gemini => Box::new(GeminiProvider::with_model(
             api_key,
             model.as_str().to_string(),
         )),
+        #[cfg(feature = "provider-openai")]
         Provider::OpenAI => Box::new(OpenAIProvider::with_model(
             api_key,
             model.as_str().to_string(),
         )),
+        #[cfg(feature = "provider-anthropic")]
         Provider::Anthropic => Box::new(AnthropicProvider::new(api_key)),
+        #[cfg(feature = "provider-openrouter")]
         Provider::OpenRouter => Box::new(OpenRouterProvider::with_model(
             api_key,
             model.as_str().to_string(),
         )),
+        #[cfg(feature = "provider-xai")]
         Provider::XAI => Box::new(XAIProvider::with_model(api_key, model.as_str().to_string())),
+        #[allow(unreachable_patterns)]
+        provider => {
+            let _ = api_key;
+            Box::new(UnavailableClient {
+                provider,
+                model: model.as_str().to_string(),
+            })
+        }
+    }
+}
+
+/// Stand-in for a provider whose cargo feature is disabled
+struct UnavailableClient {
+    provider: Provider,
+    model: String,
+}
+
+#[async_trait]
+impl LLMClient for UnavailableClient {
+    async fn generate(&mut self, _prompt: &str) -> Result<LLMResponse, LLMError> {
+        Err(LLMError::InvalidRequest(format!(
+            "Provider '{}' is not compiled in; enable the `provider-{}` feature of vtcode-core",
+            self.provider, self.provider
+        )))
+    }
+
+    fn backend_kind(&self) -> BackendKind {
+        match self.provider {
+            Provider::Gemini => BackendKind::Gemini,
+            Provider::OpenAI => BackendKind::OpenAI,
+            Provider::Anthropic => BackendKind::Anthropic,
+            Provider::OpenRouter => BackendKind::OpenRouter,
+            Provider::XAI => BackendKind::XAI,
+        }
+    }
+
+    fn model_id(&self) -> &str {
+        &self.model
     }
 }
@@ -1,10 +1,29 @@
-use super::providers::{
-    AnthropicProvider, GeminiProvider, OpenAIProvider, OpenRouterProvider, XAIProvider,
-};
+#[cfg(feature = "provider-anthropic")]
+use super::providers::AnthropicProvider;
+#[cfg(feature = "provider-gemini")]
+use super::providers::GeminiProvider;
+#[cfg(feature = "provider-ollama")]
+use super::providers::OllamaProvider;
+#[cfg(feature = "provider-openai")]
+use super::providers::OpenAIProvider;
+#[cfg(feature = "provider-openrouter")]
+use super::providers::OpenRouterProvider;
+#[cfg(feature = "provider-xai")]
+use super::providers::XAIProvider;
 use crate::config::core::PromptCachingConfig;
 use crate::llm::provider::{LLMError, LLMProvider};
 use std::collections::HashMap;
 
+/// Providers shipped with vtcode-core, each behind a `provider-<name>` feature
+const BUILTIN_PROVIDERS: [&str; 6] = [
+    "gemini",
+    "openai",
+    "anthropic",
+    "openrouter",
+    "xai",
+    "ollama",
+];
+
 /// LLM provider factory and registry
 pub struct LLMFactory {
     providers: HashMap<String, Box<dyn Fn(ProviderConfig) -> Box<dyn LLMProvider> + Send + Sync>>,
@@ -24,8 +43,14 @@ impl LLMFactory {
             providers: HashMap::new(),
         };
 
-        // Register built-in providers
-        factory.register_provider(
+        factory.register_builtin_providers();
+        factory
+    }
+
+    /// Register the providers compiled into this build
+    fn register_builtin_providers(&mut self) {
+        #[cfg(feature = "provider-gemini")]
+        self.register_provider(
             "gemini",
             Box::new(|config: ProviderConfig| {
                 let ProviderConfig {
@@ -43,7 +68,8 @@ impl LLMFactory {
             }),
         );
 
-        factory.register_provider(
+        #[cfg(feature = "provider-openai")]
+        self.register_provider(
             "openai",
             Box::new(|config: ProviderConfig| {
                 let ProviderConfig {
@@ -61,7 +87,8 @@ impl LLMFactory {
             }),
         );
 
-        factory.register_provider(
+        #[cfg(feature = "provider-anthropic")]
+        self.register_provider(
             "anthropic",
             Box::new(|config: ProviderConfig| {
                 let ProviderConfig {
@@ -79,7 +106,8 @@ impl LLMFactory {
             }),
         );
 
-        factory.register_provider(
+        #[cfg(feature = "provider-openrouter")]
+        self.register_provider(
             "openrouter",
             Box::new(|config: ProviderConfig| {
                 let ProviderConfig {
@@ -97,7 +125,8 @@ impl LLMFactory {
             }),
         );
 
-        factory.register_provider(
+        #[cfg(feature = "provider-xai")]
+        self.register_provider(
             "xai",
             Box::new(|config: ProviderConfig| {
                 let ProviderConfig {
@@ -115,7 +144,24 @@ impl LLMFactory {
             }),
         );
 
-        factory
+        #[cfg(feature = "provider-ollama")]
+        self.register_provider(
+            "ollama",
+            Box::new(|config: ProviderConfig| {
+                let ProviderConfig {
+                    api_key,
+                    base_url,
+                    model,
+                    prompt_cache,
+                } = config;
+                Box::new(OllamaProvider::from_config(
+                    api_key,
+                    model,
+                    base_url,
+                    prompt_cache,
+                )) as Box<dyn LLMProvider>
+            }),
+        );
     }
 
     /// Register a new provider
@@ -134,7 +180,14 @@ impl LLMFactory {
         config: ProviderConfig,
     ) -> Result<Box<dyn LLMProvider>, LLMError> {
         let factory_fn = self.providers.get(provider_name).ok_or_else(|| {
-            LLMError::InvalidRequest(format!("Unknown provider: {}", provider_name))
+            if BUILTIN_PROVIDERS.contains(&provider_name) {
+                LLMError::InvalidRequest(format!(
+                    "Provider '{}' is not compiled in; enable the `provider-{}` feature of vtcode-core",
+                    provider_name, provider_name
+                ))
+            } else {
+                LLMError::InvalidRequest(format!("Unknown provider: {}", provider_name))
+            }
         })?;
 
         Ok(factory_fn(config))
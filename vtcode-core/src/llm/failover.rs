@@ -0,0 +1,465 @@
+//! Failover across providers
+//!
+//! [`FailoverClient`] sends each request to the session provider first and,
+//! when it fails with a rate limit, a 5xx status, a network error or a
+//! timeout, retries the request with the next provider of `[llm.failover]`
+//! using that provider's model. Errors that another provider would repeat,
+//! such as an invalid request or a rejected key, are returned as they are.
+//! Each provider's successes and failures are tracked; one that keeps failing
+//! is moved to the back of the order until its cooldown passes.
+
+use crate::config::FailoverConfig;
+use crate::core::trajectory::TrajectoryLogger;
+use crate::llm::provider::{LLMError, LLMProvider, LLMRequest, LLMResponse, LLMStream};
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A provider in the failover order and the model requested from it.
+pub struct FailoverTarget {
+    pub provider: String,
+    pub model: String,
+    pub client: Box<dyn LLMProvider>,
+}
+
+impl FailoverTarget {
+    fn label(&self) -> String {
+        format!("{}/{}", self.provider, self.model)
+    }
+}
+
+/// A request moving from one provider to the next.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailoverEvent {
+    pub from: String,
+    pub to: String,
+    /// Short description of the failure, e.g. `rate limited`
+    pub reason: String,
+}
+
+/// Health of one provider over the session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderHealth {
+    /// `provider/model`
+    pub label: String,
+    pub successes: u64,
+    pub failures: u64,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+    /// False while the provider is cooling down after repeated failures
+    pub healthy: bool,
+}
+
+/// Shared view of which provider is serving requests.
+///
+/// Cloned out of the client before it is boxed so the chat loop can report
+/// failovers to the user.
+#[derive(Debug, Clone, Default)]
+pub struct FailoverUsage {
+    inner: Arc<Mutex<UsageState>>,
+}
+
+#[derive(Debug, Default)]
+struct UsageState {
+    last_served: Option<String>,
+    failovers: Vec<FailoverEvent>,
+    health: Vec<HealthState>,
+}
+
+#[derive(Debug, Default)]
+struct HealthState {
+    successes: u64,
+    failures: u64,
+    consecutive_failures: u32,
+    last_error: Option<String>,
+    unhealthy_until: Option<Instant>,
+}
+
+impl HealthState {
+    fn is_healthy(&self, now: Instant) -> bool {
+        self.unhealthy_until.is_none_or(|until| now >= until)
+    }
+}
+
+impl FailoverUsage {
+    /// `provider/model` that served the most recent successful request.
+    pub fn last_served(&self) -> Option<String> {
+        self.inner.lock().last_served.clone()
+    }
+
+    /// Failovers since the previous call, oldest first.
+    pub fn take_failovers(&self) -> Vec<FailoverEvent> {
+        std::mem::take(&mut self.inner.lock().failovers)
+    }
+}
+
+/// Provider that falls back to other providers when one is unavailable.
+pub struct FailoverClient {
+    targets: Vec<FailoverTarget>,
+    request_timeout: Option<Duration>,
+    failure_threshold: u32,
+    cooldown: Duration,
+    usage: FailoverUsage,
+    trajectory: Option<TrajectoryLogger>,
+}
+
+impl FailoverClient {
+    /// Create a client from targets in failover order; the first is the
+    /// session provider and keeps the model named in each request.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `targets` is empty.
+    pub fn new(targets: Vec<FailoverTarget>, config: &FailoverConfig) -> Self {
+        assert!(
+            !targets.is_empty(),
+            "FailoverClient needs at least one provider"
+        );
+        let usage = FailoverUsage::default();
+        usage
+            .inner
+            .lock()
+            .health
+            .resize_with(targets.len(), HealthState::default);
+        Self {
+            targets,
+            request_timeout: (config.request_timeout_secs > 0)
+                .then(|| Duration::from_secs(config.request_timeout_secs)),
+            failure_threshold: config.failure_threshold.max(1),
+            cooldown: Duration::from_secs(config.cooldown_secs),
+            usage,
+            trajectory: None,
+        }
+    }
+
+    /// Log the provider serving each request to the trajectory log.
+    pub fn with_trajectory(mut self, trajectory: TrajectoryLogger) -> Self {
+        self.trajectory = Some(trajectory);
+        self
+    }
+
+    pub fn usage(&self) -> FailoverUsage {
+        self.usage.clone()
+    }
+
+    /// Health of each provider, in failover order.
+    pub fn health(&self) -> Vec<ProviderHealth> {
+        let now = Instant::now();
+        let state = self.usage.inner.lock();
+        self.targets
+            .iter()
+            .zip(&state.health)
+            .map(|(target, health)| ProviderHealth {
+                label: target.label(),
+                successes: health.successes,
+                failures: health.failures,
+                consecutive_failures: health.consecutive_failures,
+                last_error: health.last_error.clone(),
+                healthy: health.is_healthy(now),
+            })
+            .collect()
+    }
+
+    /// Healthy providers in configured order, then the ones cooling down.
+    fn order(&self) -> Vec<usize> {
+        let now = Instant::now();
+        let state = self.usage.inner.lock();
+        let (mut healthy, cooling): (Vec<usize>, Vec<usize>) =
+            (0..self.targets.len()).partition(|&index| state.health[index].is_healthy(now));
+        healthy.extend(cooling);
+        healthy
+    }
+
+    fn primary(&self) -> &dyn LLMProvider {
+        self.targets[0].client.as_ref()
+    }
+
+    fn record_success(&self, index: usize, failed: &[String]) {
+        let target = &self.targets[index];
+        {
+            let mut state = self.usage.inner.lock();
+            let health = &mut state.health[index];
+            health.successes += 1;
+            health.consecutive_failures = 0;
+            health.unhealthy_until = None;
+            state.last_served = Some(target.label());
+        }
+        if let Some(trajectory) = &self.trajectory {
+            trajectory.log_provider(&target.provider, &target.model, failed);
+        }
+    }
+
+    fn record_failure(&self, index: usize, reason: &str, next: Option<usize>) {
+        let mut state = self.usage.inner.lock();
+        let health = &mut state.health[index];
+        health.failures += 1;
+        health.consecutive_failures += 1;
+        health.last_error = Some(reason.to_string());
+        if health.consecutive_failures >= self.failure_threshold {
+            health.unhealthy_until = Some(Instant::now() + self.cooldown);
+        }
+        if let Some(next) = next {
+            state.failovers.push(FailoverEvent {
+                from: self.targets[index].label(),
+                to: self.targets[next].label(),
+                reason: reason.to_string(),
+            });
+        }
+    }
+
+    /// Run `call` against each provider in turn until one succeeds or fails
+    /// with an error that another provider would repeat.
+    async fn attempt<T>(
+        &self,
+        request: LLMRequest,
+        call: impl for<'a> Fn(&'a dyn LLMProvider, LLMRequest) -> BoxFuture<'a, Result<T, LLMError>>,
+    ) -> Result<T, LLMError> {
+        let order = self.order();
+        let mut failed = Vec::new();
+        let mut last_error = None;
+        for (position, &index) in order.iter().enumerate() {
+            let target = &self.targets[index];
+            let mut request = request.clone();
+            if index != 0 {
+                request.model = target.model.clone();
+            }
+            let pending = call(target.client.as_ref(), request);
+            let result = match self.request_timeout {
+                Some(limit) => tokio::time::timeout(limit, pending)
+                    .await
+                    .unwrap_or_else(|_| {
+                        Err(LLMError::Network(format!(
+                            "timed out after {}s",
+                            limit.as_secs()
+                        )))
+                    }),
+                None => pending.await,
+            };
+            match result {
+                Ok(value) => {
+                    self.record_success(index, &failed);
+                    return Ok(value);
+                }
+                Err(err) => {
+                    let Some(reason) = failover_reason(&err) else {
+                        return Err(err);
+                    };
+                    self.record_failure(index, reason, order.get(position + 1).copied());
+                    failed.push(target.label());
+                    last_error = Some(err);
+                }
+            }
+        }
+        Err(last_error.unwrap_or(LLMError::RateLimit))
+    }
+}
+
+/// Why `err` justifies trying another provider, or `None` when it does not.
+fn failover_reason(err: &LLMError) -> Option<&'static str> {
+    match err {
+        LLMError::RateLimit => Some("rate limited"),
+        LLMError::Network(message) if message.contains("timed out") => Some("timed out"),
+        LLMError::Network(_) => Some("network error"),
+        LLMError::Provider(message) if message.contains("HTTP 5") => Some("server error"),
+        LLMError::Provider(message) if message.contains("timed out") => Some("timed out"),
+        _ => None,
+    }
+}
+
+#[async_trait]
+impl LLMProvider for FailoverClient {
+    fn name(&self) -> &str {
+        self.primary().name()
+    }
+
+    fn supports_streaming(&self) -> bool {
+        self.primary().supports_streaming()
+    }
+
+    fn supports_reasoning(&self, model: &str) -> bool {
+        self.primary().supports_reasoning(model)
+    }
+
+    fn supports_reasoning_effort(&self, model: &str) -> bool {
+        self.primary().supports_reasoning_effort(model)
+    }
+
+    fn supports_json_mode(&self, model: &str) -> bool {
+        self.primary().supports_json_mode(model)
+    }
+
+    fn supports_response_schema(&self, model: &str) -> bool {
+        self.primary().supports_response_schema(model)
+    }
+
+    fn supports_prompt_caching(&self, model: &str) -> bool {
+        self.primary().supports_prompt_caching(model)
+    }
+
+    fn supports_code_execution(&self, model: &str) -> bool {
+        self.primary().supports_code_execution(model)
+    }
+
+    fn supports_seed(&self, model: &str) -> bool {
+        self.primary().supports_seed(model)
+    }
+
+    async fn generate(&self, request: LLMRequest) -> Result<LLMResponse, LLMError> {
+        self.attempt(request, |client, request| client.generate(request))
+            .await
+    }
+
+    async fn stream(&self, request: LLMRequest) -> Result<LLMStream, LLMError> {
+        // Failures surface before the first event, so only the initial call fails over.
+        self.attempt(request, |client, request| client.stream(request))
+            .await
+    }
+
+    fn supported_models(&self) -> Vec<String> {
+        self.primary().supported_models()
+    }
+
+    fn validate_request(&self, request: &LLMRequest) -> Result<(), LLMError> {
+        self.primary().validate_request(request)
+    }
+
+    fn release_connections(&self) {
+        for target in &self.targets {
+            target.client.release_connections();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::provider::{FinishReason, Message};
+
+    /// The error a [`ScriptedProvider`] fails with, shared with the test.
+    type Script = Arc<Mutex<Option<fn() -> LLMError>>>;
+
+    /// Answers with the requested model, or fails with `error` while it is set.
+    struct ScriptedProvider {
+        error: Script,
+    }
+
+    #[async_trait]
+    impl LLMProvider for ScriptedProvider {
+        fn name(&self) -> &str {
+            "scripted"
+        }
+
+        async fn generate(&self, request: LLMRequest) -> Result<LLMResponse, LLMError> {
+            if let Some(error) = *self.error.lock() {
+                return Err(error());
+            }
+            Ok(LLMResponse {
+                content: Some(request.model),
+                tool_calls: None,
+                usage: None,
+                finish_reason: FinishReason::Stop,
+                reasoning: None,
+                code_execution: Vec::new(),
+            })
+        }
+
+        fn supported_models(&self) -> Vec<String> {
+            Vec::new()
+        }
+
+        fn validate_request(&self, _request: &LLMRequest) -> Result<(), LLMError> {
+            Ok(())
+        }
+    }
+
+    fn target(provider: &str, model: &str) -> (FailoverTarget, Script) {
+        let error: Script = Arc::default();
+        let target = FailoverTarget {
+            provider: provider.to_string(),
+            model: model.to_string(),
+            client: Box::new(ScriptedProvider {
+                error: error.clone(),
+            }),
+        };
+        (target, error)
+    }
+
+    fn request() -> LLMRequest {
+        LLMRequest {
+            messages: vec![Message::user("hi".to_string())],
+            system_prompt: None,
+            tools: None,
+            model: "gpt-5".to_string(),
+            max_tokens: None,
+            temperature: None,
+            stream: false,
+            tool_choice: None,
+            parallel_tool_calls: None,
+            parallel_tool_config: None,
+            reasoning_effort: None,
+            json_mode: false,
+            response_schema: None,
+            code_execution: false,
+            seed: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn fails_over_on_server_errors_and_skips_unhealthy_providers() {
+        let (primary, primary_error) = target("openai", "gpt-5");
+        let (backup, _) = target("anthropic", "claude-sonnet-4");
+        let client = FailoverClient::new(
+            vec![primary, backup],
+            &FailoverConfig {
+                enabled: true,
+                failure_threshold: 2,
+                ..FailoverConfig::default()
+            },
+        );
+        let usage = client.usage();
+
+        *primary_error.lock() = Some(|| LLMError::Provider("OpenAI HTTP 503: overloaded".into()));
+        let response = client.generate(request()).await.expect("failed over");
+        assert_eq!(response.content.as_deref(), Some("claude-sonnet-4"));
+        assert_eq!(
+            usage.take_failovers(),
+            vec![FailoverEvent {
+                from: "openai/gpt-5".to_string(),
+                to: "anthropic/claude-sonnet-4".to_string(),
+                reason: "server error".to_string(),
+            }]
+        );
+        assert!(client.health()[0].healthy);
+
+        *primary_error.lock() = Some(|| LLMError::RateLimit);
+        client.generate(request()).await.expect("failed over");
+        assert!(!client.health()[0].healthy);
+        assert_eq!(client.health()[0].consecutive_failures, 2);
+
+        // The primary is cooling down, so the backup is tried first.
+        *primary_error.lock() = None;
+        client.generate(request()).await.expect("served");
+        assert_eq!(usage.take_failovers().len(), 1);
+        assert_eq!(
+            usage.last_served().as_deref(),
+            Some("anthropic/claude-sonnet-4")
+        );
+    }
+
+    #[tokio::test]
+    async fn returns_errors_another_provider_would_repeat() {
+        let (primary, primary_error) = target("openai", "gpt-5");
+        let (backup, _) = target("anthropic", "claude-sonnet-4");
+        let client = FailoverClient::new(vec![primary, backup], &FailoverConfig::default());
+
+        *primary_error.lock() = Some(|| LLMError::InvalidRequest("bad schema".into()));
+        assert!(matches!(
+            client.generate(request()).await,
+            Err(LLMError::InvalidRequest(_))
+        ));
+        assert!(client.usage().take_failovers().is_empty());
+        assert_eq!(client.health()[0].failures, 0);
+    }
+}
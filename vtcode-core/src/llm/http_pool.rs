@@ -0,0 +1,43 @@
+//! HTTP client whose connection pool can be dropped
+//!
+//! `reqwest::Client` keeps idle keep-alive connections to the provider open
+//! for as long as the client lives. [`PooledClient`] hands out clones of one
+//! client and can replace it with a fresh one, which closes those connections
+//! once in-flight requests finish; the next request connects again. Clients
+//! are built with the provider's `[network]` proxy and TLS settings; when that
+//! fails, every request reports why instead of going out without them.
+
+use crate::llm::provider::LLMError;
+use crate::utils::network;
+use parking_lot::RwLock;
+use reqwest::Client;
+
+#[derive(Debug)]
+pub struct PooledClient {
+    provider: &'static str,
+    client: RwLock<Result<Client, String>>,
+}
+
+impl PooledClient {
+    /// Client for requests to `provider`.
+    pub fn for_provider(provider: &'static str) -> Self {
+        Self {
+            provider,
+            client: RwLock::new(build(provider)),
+        }
+    }
+
+    /// The current client; clones share its connection pool.
+    pub fn get(&self) -> Result<Client, LLMError> {
+        self.client.read().clone().map_err(LLMError::Network)
+    }
+
+    /// Drop the current pool, closing its idle connections.
+    pub fn release(&self) {
+        *self.client.write() = build(self.provider);
+    }
+}
+
+fn build(provider: &str) -> Result<Client, String> {
+    network::client(Some(provider)).map_err(|error| format!("{error:#}"))
+}
@@ -164,8 +164,14 @@
 pub mod client;
 pub mod error_display;
 pub mod factory;
+pub mod failover;
+pub mod http_pool;
+pub mod oauth;
 pub mod provider;
 pub mod providers;
+pub mod refusal;
+pub mod rotation;
+pub mod sse;
 pub mod types;
 
 #[cfg(test)]
@@ -174,6 +180,17 @@ mod error_display_test;
 // Re-export main types for backward compatibility
 pub use client::{AnyClient, make_client};
 pub use factory::{create_provider_with_config, get_factory};
+pub use failover::{FailoverClient, FailoverEvent, FailoverTarget, FailoverUsage, ProviderHealth};
+pub use oauth::{OAuthProvider, TokenClientBuilder};
 pub use provider::{LLMStream, LLMStreamEvent};
-pub use providers::{AnthropicProvider, GeminiProvider, OpenAIProvider, XAIProvider};
+#[cfg(feature = "provider-anthropic")]
+pub use providers::AnthropicProvider;
+#[cfg(feature = "provider-gemini")]
+pub use providers::GeminiProvider;
+#[cfg(feature = "provider-openai")]
+pub use providers::OpenAIProvider;
+#[cfg(feature = "provider-xai")]
+pub use providers::XAIProvider;
+pub use refusal::Refusal;
+pub use rotation::{CredentialUsage, RotatingProvider, RotationEvent};
 pub use types::{BackendKind, LLMError, LLMResponse};
@@ -0,0 +1,277 @@
+//! Provider clients authenticated with OAuth access tokens
+//!
+//! [`OAuthProvider`] builds the underlying provider client with the current
+//! access token of a [`TokenSource`] in place of an API key. Tokens are
+//! refreshed before they expire, and a request rejected with
+//! `LLMError::Authentication` is retried once with a freshly refreshed token.
+
+use crate::config::oauth::TokenSource;
+use crate::llm::provider::{LLMError, LLMProvider, LLMRequest, LLMResponse, LLMStream};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+/// Builds a provider client that sends the given access token.
+pub type TokenClientBuilder =
+    Box<dyn Fn(String) -> Result<Box<dyn LLMProvider>, LLMError> + Send + Sync>;
+
+/// Provider that keeps its client in step with a refreshing access token.
+pub struct OAuthProvider {
+    name: String,
+    tokens: TokenSource,
+    build: TokenClientBuilder,
+    client: Mutex<(String, Arc<dyn LLMProvider>)>,
+}
+
+impl OAuthProvider {
+    /// Create the provider with a client for the current access token.
+    pub async fn connect(
+        name: impl Into<String>,
+        tokens: TokenSource,
+        build: TokenClientBuilder,
+    ) -> Result<Self, LLMError> {
+        let token = tokens.access_token().await.map_err(auth_error)?;
+        let client: Arc<dyn LLMProvider> = Arc::from(build(token.clone())?);
+        Ok(Self {
+            name: name.into(),
+            tokens,
+            build,
+            client: Mutex::new((token, client)),
+        })
+    }
+
+    fn current(&self) -> Arc<dyn LLMProvider> {
+        self.client.lock().1.clone()
+    }
+
+    /// Client for `token`, rebuilt if the token changed since the last request.
+    fn client_for(&self, token: String) -> Result<(String, Arc<dyn LLMProvider>), LLMError> {
+        let mut current = self.client.lock();
+        if current.0 != token {
+            let client: Arc<dyn LLMProvider> = Arc::from((self.build)(token.clone())?);
+            *current = (token, client);
+        }
+        Ok(current.clone())
+    }
+
+    async fn fresh_client(&self) -> Result<(String, Arc<dyn LLMProvider>), LLMError> {
+        let token = self.tokens.access_token().await.map_err(auth_error)?;
+        self.client_for(token)
+    }
+
+    async fn refreshed_client(&self, rejected: &str) -> Result<Arc<dyn LLMProvider>, LLMError> {
+        let token = self
+            .tokens
+            .force_refresh(rejected)
+            .await
+            .map_err(auth_error)?;
+        Ok(self.client_for(token)?.1)
+    }
+}
+
+fn auth_error(err: anyhow::Error) -> LLMError {
+    LLMError::Authentication(format!("{:#}", err))
+}
+
+#[async_trait]
+impl LLMProvider for OAuthProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn supports_streaming(&self) -> bool {
+        self.current().supports_streaming()
+    }
+
+    fn supports_reasoning(&self, model: &str) -> bool {
+        self.current().supports_reasoning(model)
+    }
+
+    fn supports_reasoning_effort(&self, model: &str) -> bool {
+        self.current().supports_reasoning_effort(model)
+    }
+
+    fn supports_json_mode(&self, model: &str) -> bool {
+        self.current().supports_json_mode(model)
+    }
+
+    fn supports_response_schema(&self, model: &str) -> bool {
+        self.current().supports_response_schema(model)
+    }
+
+    fn supports_prompt_caching(&self, model: &str) -> bool {
+        self.current().supports_prompt_caching(model)
+    }
+
+    fn supports_code_execution(&self, model: &str) -> bool {
+        self.current().supports_code_execution(model)
+    }
+
+    fn supports_seed(&self, model: &str) -> bool {
+        self.current().supports_seed(model)
+    }
+
+    async fn generate(&self, request: LLMRequest) -> Result<LLMResponse, LLMError> {
+        let (token, client) = self.fresh_client().await?;
+        match client.generate(request.clone()).await {
+            Err(LLMError::Authentication(_)) => {
+                self.refreshed_client(&token).await?.generate(request).await
+            }
+            result => result,
+        }
+    }
+
+    async fn stream(&self, request: LLMRequest) -> Result<LLMStream, LLMError> {
+        // Authentication errors surface before the first event, so only the initial call is retried.
+        let (token, client) = self.fresh_client().await?;
+        match client.stream(request.clone()).await {
+            Err(LLMError::Authentication(_)) => {
+                self.refreshed_client(&token).await?.stream(request).await
+            }
+            result => result,
+        }
+    }
+
+    fn supported_models(&self) -> Vec<String> {
+        self.current().supported_models()
+    }
+
+    fn validate_request(&self, request: &LLMRequest) -> Result<(), LLMError> {
+        self.current().validate_request(request)
+    }
+
+    fn release_connections(&self) {
+        self.current().release_connections();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::core::OAuthSettings;
+    use crate::config::oauth::{OAuthToken, TokenStore};
+    use crate::llm::provider::{FinishReason, Message};
+    use chrono::{Duration as ChronoDuration, Utc};
+    use tempfile::TempDir;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Accepts `expected` as its key and rejects anything else.
+    struct TokenProvider {
+        token: String,
+        expected: &'static str,
+    }
+
+    #[async_trait]
+    impl LLMProvider for TokenProvider {
+        fn name(&self) -> &str {
+            "gateway"
+        }
+
+        async fn generate(&self, _request: LLMRequest) -> Result<LLMResponse, LLMError> {
+            if self.token != self.expected {
+                return Err(LLMError::Authentication("HTTP 401".to_string()));
+            }
+            Ok(LLMResponse {
+                content: Some(self.token.clone()),
+                tool_calls: None,
+                usage: None,
+                finish_reason: FinishReason::Stop,
+                reasoning: None,
+                code_execution: Vec::new(),
+            })
+        }
+
+        fn supported_models(&self) -> Vec<String> {
+            vec!["gpt-5".to_string()]
+        }
+
+        fn validate_request(&self, _request: &LLMRequest) -> Result<(), LLMError> {
+            Ok(())
+        }
+    }
+
+    /// Token endpoint that answers every request with `body`.
+    async fn token_endpoint(body: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+        let address = listener.local_addr().expect("address");
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buffer = [0u8; 4096];
+                let _ = socket.read(&mut buffer).await;
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{}/oauth/token", address)
+    }
+
+    fn request() -> LLMRequest {
+        LLMRequest {
+            messages: vec![Message::user("hi".to_string())],
+            system_prompt: None,
+            tools: None,
+            model: "gpt-5".to_string(),
+            max_tokens: None,
+            temperature: None,
+            stream: false,
+            tool_choice: None,
+            parallel_tool_calls: None,
+            parallel_tool_config: None,
+            reasoning_effort: None,
+            json_mode: false,
+            response_schema: None,
+            code_execution: false,
+            seed: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn refreshes_and_retries_after_rejected_token() {
+        let token_url =
+            token_endpoint(r#"{"access_token":"fresh","token_type":"Bearer","expires_in":3600}"#)
+                .await;
+        let settings = OAuthSettings {
+            client_id: "vtcode".to_string(),
+            device_authorization_url: String::new(),
+            token_url,
+            scopes: Vec::new(),
+            audience: None,
+            refresh_margin_secs: 60,
+        };
+        let dir = TempDir::new().expect("tempdir");
+        let store = TokenStore::new(dir.path());
+        let revoked = OAuthToken {
+            access_token: "revoked".to_string(),
+            refresh_token: Some("refresh".to_string()),
+            token_type: "Bearer".to_string(),
+            expires_at: Some(Utc::now() + ChronoDuration::hours(1)),
+            scope: None,
+        };
+        let tokens = TokenSource::new("gateway", settings, store.clone(), revoked).unwrap();
+
+        let provider = OAuthProvider::connect(
+            "gateway",
+            tokens,
+            Box::new(|token| {
+                Ok(Box::new(TokenProvider {
+                    token,
+                    expected: "fresh",
+                }) as Box<dyn LLMProvider>)
+            }),
+        )
+        .await
+        .expect("provider");
+
+        let response = provider.generate(request()).await.expect("retried");
+        assert_eq!(response.content.as_deref(), Some("fresh"));
+
+        let stored = store.load("gateway").expect("load").expect("stored token");
+        assert_eq!(stored.access_token, "fresh");
+        assert_eq!(stored.refresh_token.as_deref(), Some("refresh"));
+    }
+}
@@ -46,6 +46,7 @@
 //! tool_response.validate_for_provider("openai").unwrap();
 //! ```
 
+use crate::core::provenance::Provenance;
 use async_stream::try_stream;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
@@ -76,6 +77,28 @@ pub struct LLMRequest {
     /// Reasoning effort level for models that support it (low, medium, high)
     /// Applies to: Claude, GPT-5, Gemini, Qwen3, DeepSeek with reasoning capability
     pub reasoning_effort: Option<String>,
+
+    /// Constrain the response to a single JSON object (provider JSON mode)
+    /// Only honoured by providers whose `supports_json_mode` returns true
+    #[serde(default)]
+    pub json_mode: bool,
+
+    /// JSON schema the response must match, enforced by grammar-constrained
+    /// decoding. Takes precedence over `json_mode`.
+    /// Only honoured by providers whose `supports_response_schema` returns true
+    #[serde(default)]
+    pub response_schema: Option<Value>,
+
+    /// Offer the provider's hosted code interpreter to the model
+    /// Only honoured by providers whose `supports_code_execution` returns true
+    #[serde(default)]
+    pub code_execution: bool,
+
+    /// Sampling seed, so repeated requests give the same response
+    /// Only honoured by providers whose `supports_seed` returns true; left out
+    /// of the serialized request when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
 }
 
 /// Tool choice configuration that works across different providers
@@ -251,6 +274,9 @@ pub struct Message {
     pub content: String,
     pub tool_calls: Option<Vec<ToolCall>>,
     pub tool_call_id: Option<String>,
+    /// Where the content came from; kept locally and never sent to providers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
 }
 
 impl Message {
@@ -261,6 +287,7 @@ impl Message {
             content,
             tool_calls: None,
             tool_call_id: None,
+            provenance: None,
         }
     }
 
@@ -271,6 +298,7 @@ impl Message {
             content,
             tool_calls: None,
             tool_call_id: None,
+            provenance: None,
         }
     }
 
@@ -282,6 +310,7 @@ impl Message {
             content,
             tool_calls: Some(tool_calls),
             tool_call_id: None,
+            provenance: None,
         }
     }
 
@@ -292,6 +321,7 @@ impl Message {
             content,
             tool_calls: None,
             tool_call_id: None,
+            provenance: None,
         }
     }
 
@@ -310,6 +340,7 @@ impl Message {
             content,
             tool_calls: None,
             tool_call_id: Some(tool_call_id),
+            provenance: None,
         }
     }
 
@@ -324,6 +355,19 @@ impl Message {
         Self::tool_response(tool_call_id, content)
     }
 
+    /// Tag the message with where its content came from.
+    pub fn with_provenance(mut self, provenance: Provenance) -> Self {
+        self.provenance = Some(provenance);
+        self
+    }
+
+    /// The tagged provenance, or a guess from the role for untagged messages.
+    pub fn provenance(&self) -> Provenance {
+        self.provenance
+            .clone()
+            .unwrap_or_else(|| Provenance::infer(self))
+    }
+
     /// Validate this message for a specific provider
     /// Based on official API documentation constraints
     pub fn validate_for_provider(&self, provider: &str) -> Result<(), String> {
@@ -561,7 +605,7 @@ impl ToolDefinition {
 
 /// Universal tool call that matches the exact structure from OpenAI API
 /// Based on OpenAI Cookbook examples and official documentation
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ToolCall {
     /// Unique identifier for this tool call (e.g., "call_123")
     pub id: String,
@@ -575,7 +619,7 @@ pub struct ToolCall {
 }
 
 /// Function call within a tool call
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct FunctionCall {
     /// The name of the function to call
     pub name: String,
@@ -633,6 +677,31 @@ pub struct LLMResponse {
     pub usage: Option<Usage>,
     pub finish_reason: FinishReason,
     pub reasoning: Option<String>,
+    /// Steps of the provider's hosted code interpreter, in order
+    pub code_execution: Vec<CodeExecutionStep>,
+}
+
+/// One step of a run in the provider's hosted code interpreter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodeExecutionStep {
+    /// Code the model ran
+    Code {
+        language: Option<String>,
+        code: String,
+    },
+    /// What the code printed, or its error when it failed
+    Output { output: String, succeeded: bool },
+    /// A file the code produced
+    File(GeneratedFile),
+}
+
+/// A file produced by hosted code execution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedFile {
+    /// Name given by the provider, if any
+    pub name: Option<String>,
+    pub mime_type: Option<String>,
+    pub data: Vec<u8>,
 }
 
 #[derive(Debug, Clone)]
@@ -656,9 +725,20 @@ pub enum FinishReason {
 
 #[derive(Debug, Clone)]
 pub enum LLMStreamEvent {
-    Token { delta: String },
-    Reasoning { delta: String },
-    Completed { response: LLMResponse },
+    Token {
+        delta: String,
+    },
+    Reasoning {
+        delta: String,
+    },
+    /// A hosted code execution step, as soon as the provider reports it. The
+    /// same steps are repeated in the completed response.
+    CodeExecution {
+        step: CodeExecutionStep,
+    },
+    Completed {
+        response: LLMResponse,
+    },
 }
 
 pub type LLMStream = Pin<Box<dyn futures::Stream<Item = Result<LLMStreamEvent, LLMError>> + Send>>;
@@ -684,6 +764,33 @@ pub trait LLMProvider: Send + Sync {
         false
     }
 
+    /// Whether the provider can constrain responses to strict JSON for the model
+    fn supports_json_mode(&self, _model: &str) -> bool {
+        false
+    }
+
+    /// Whether the provider can constrain responses to a JSON schema for the
+    /// model, for example through a llama.cpp grammar
+    fn supports_response_schema(&self, _model: &str) -> bool {
+        false
+    }
+
+    /// Whether the provider reuses cached request prefixes for the model
+    fn supports_prompt_caching(&self, _model: &str) -> bool {
+        false
+    }
+
+    /// Whether the provider can run code in a hosted interpreter for the model
+    fn supports_code_execution(&self, _model: &str) -> bool {
+        false
+    }
+
+    /// Whether the provider samples deterministically from a request seed for
+    /// the model
+    fn supports_seed(&self, _model: &str) -> bool {
+        false
+    }
+
     /// Generate completion
     async fn generate(&self, request: LLMRequest) -> Result<LLMResponse, LLMError>;
 
@@ -702,6 +809,9 @@ pub trait LLMProvider: Send + Sync {
 
     /// Validate request for this provider
     fn validate_request(&self, request: &LLMRequest) -> Result<(), LLMError>;
+
+    /// Close idle pooled connections; the next request opens new ones
+    fn release_connections(&self) {}
 }
 
 #[derive(Debug, thiserror::Error)]
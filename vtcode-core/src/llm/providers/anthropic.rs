@@ -2,20 +2,20 @@ use crate::config::constants::{defaults, models, urls};
 use crate::config::core::{AnthropicPromptCacheSettings, PromptCachingConfig};
 use crate::llm::client::LLMClient;
 use crate::llm::error_display;
+use crate::llm::http_pool::PooledClient;
 use crate::llm::provider::{
     FinishReason, LLMError, LLMProvider, LLMRequest, LLMResponse, Message, MessageRole,
     ParallelToolConfig, ToolCall, ToolChoice, ToolDefinition,
 };
 use crate::llm::types as llm_types;
 use async_trait::async_trait;
-use reqwest::Client as HttpClient;
 use serde_json::{Value, json};
 
 use super::extract_reasoning_trace;
 
 pub struct AnthropicProvider {
     api_key: String,
-    http_client: HttpClient,
+    http_client: PooledClient,
     base_url: String,
     model: String,
     prompt_cache_enabled: bool,
@@ -63,7 +63,7 @@ impl AnthropicProvider {
 
         Self {
             api_key,
-            http_client: HttpClient::new(),
+            http_client: PooledClient::for_provider("anthropic"),
             base_url: urls::ANTHROPIC_API_BASE.to_string(),
             model,
             prompt_cache_enabled,
@@ -127,6 +127,10 @@ impl AnthropicProvider {
             parallel_tool_calls: None,
             parallel_tool_config: None,
             reasoning_effort: None,
+            json_mode: false,
+            response_schema: None,
+            code_execution: false,
+            seed: None,
         }
     }
 
@@ -354,6 +358,10 @@ impl AnthropicProvider {
             parallel_tool_calls,
             parallel_tool_config,
             reasoning_effort,
+            json_mode: false,
+            response_schema: None,
+            code_execution: false,
+            seed: None,
         })
     }
 
@@ -691,6 +699,7 @@ impl AnthropicProvider {
             "max_tokens" => FinishReason::Length,
             "stop_sequence" => FinishReason::Stop,
             "tool_use" => FinishReason::ToolCalls,
+            "refusal" => FinishReason::ContentFilter,
             other => FinishReason::Error(other.to_string()),
         };
 
@@ -741,6 +750,7 @@ impl AnthropicProvider {
             usage,
             finish_reason,
             reasoning,
+            code_execution: Vec::new(),
         })
     }
 }
@@ -751,6 +761,10 @@ impl LLMProvider for AnthropicProvider {
         "anthropic"
     }
 
+    fn supports_prompt_caching(&self, _model: &str) -> bool {
+        self.prompt_cache_enabled
+    }
+
     fn supports_reasoning(&self, _model: &str) -> bool {
         false
     }
@@ -772,6 +786,7 @@ impl LLMProvider for AnthropicProvider {
 
         let mut request_builder = self
             .http_client
+            .get()?
             .post(&url)
             .header("x-api-key", &self.api_key)
             .header("anthropic-version", urls::ANTHROPIC_API_VERSION);
@@ -794,6 +809,14 @@ impl LLMProvider for AnthropicProvider {
             let status = response.status();
             let error_text = response.text().await.unwrap_or_default();
 
+            if status.as_u16() == 401 || status.as_u16() == 403 {
+                let formatted_error = error_display::format_llm_error(
+                    "Anthropic",
+                    &format!("HTTP {}: {}", status, error_text),
+                );
+                return Err(LLMError::Authentication(formatted_error));
+            }
+
             // Handle specific HTTP status codes
             if status.as_u16() == 429
                 || error_text.contains("insufficient_quota")
@@ -852,6 +875,10 @@ impl LLMProvider for AnthropicProvider {
 
         Ok(())
     }
+
+    fn release_connections(&self) {
+        self.http_client.release();
+    }
 }
 
 #[cfg(test)]
@@ -896,6 +923,10 @@ mod tests {
             parallel_tool_calls: None,
             parallel_tool_config: None,
             reasoning_effort: None,
+            json_mode: false,
+            response_schema: None,
+            code_execution: false,
+            seed: None,
         }
     }
 
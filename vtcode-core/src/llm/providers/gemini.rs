@@ -13,21 +13,23 @@ use crate::gemini::{
 };
 use crate::llm::client::LLMClient;
 use crate::llm::error_display;
+use crate::llm::http_pool::PooledClient;
 use crate::llm::provider::{
-    FinishReason, FunctionCall, LLMError, LLMProvider, LLMRequest, LLMResponse, LLMStream,
-    LLMStreamEvent, Message, MessageRole, ToolCall, ToolChoice,
+    CodeExecutionStep, FinishReason, FunctionCall, GeneratedFile, LLMError, LLMProvider,
+    LLMRequest, LLMResponse, LLMStream, LLMStreamEvent, Message, MessageRole, ToolCall, ToolChoice,
 };
 use crate::llm::types as llm_types;
 use async_stream::try_stream;
 use async_trait::async_trait;
-use reqwest::Client as HttpClient;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
 use serde_json::{Map, Value, json};
 use std::collections::HashMap;
 use tokio::sync::mpsc;
 
 pub struct GeminiProvider {
     api_key: String,
-    http_client: HttpClient,
+    http_client: PooledClient,
     base_url: String,
     model: String,
     prompt_cache_enabled: bool,
@@ -75,7 +77,7 @@ impl GeminiProvider {
 
         Self {
             api_key,
-            http_client: HttpClient::new(),
+            http_client: PooledClient::for_provider("gemini"),
             base_url: urls::GEMINI_API_BASE.to_string(),
             model,
             prompt_cache_enabled,
@@ -104,6 +106,10 @@ impl LLMProvider for GeminiProvider {
         "gemini"
     }
 
+    fn supports_prompt_caching(&self, _model: &str) -> bool {
+        self.prompt_cache_enabled
+    }
+
     fn supports_streaming(&self) -> bool {
         true
     }
@@ -112,6 +118,18 @@ impl LLMProvider for GeminiProvider {
         false
     }
 
+    fn supports_json_mode(&self, _model: &str) -> bool {
+        true
+    }
+
+    fn supports_code_execution(&self, _model: &str) -> bool {
+        true
+    }
+
+    fn supports_seed(&self, _model: &str) -> bool {
+        true
+    }
+
     async fn generate(&self, request: LLMRequest) -> Result<LLMResponse, LLMError> {
         let gemini_request = self.convert_to_gemini_request(&request)?;
 
@@ -122,6 +140,7 @@ impl LLMProvider for GeminiProvider {
 
         let response = self
             .http_client
+            .get()?
             .post(&url)
             .json(&gemini_request)
             .send()
@@ -173,6 +192,7 @@ impl LLMProvider for GeminiProvider {
 
         let response = self
             .http_client
+            .get()?
             .post(&url)
             .json(&gemini_request)
             .send()
@@ -214,7 +234,13 @@ impl LLMProvider for GeminiProvider {
         let completion_sender = event_tx.clone();
 
         tokio::spawn(async move {
-            let mut processor = StreamingProcessor::new();
+            let step_sender = completion_sender.clone();
+            let mut processor =
+                StreamingProcessor::new().with_part_observer(Box::new(move |part| {
+                    if let Some(step) = code_execution_step(part) {
+                        let _ = step_sender.send(Ok(LLMStreamEvent::CodeExecution { step }));
+                    }
+                }));
             let token_sender = completion_sender.clone();
             let mut aggregated_text = String::new();
             let mut on_chunk = |chunk: &str| -> Result<(), StreamingError> {
@@ -302,6 +328,10 @@ impl LLMProvider for GeminiProvider {
         }
         Ok(())
     }
+
+    fn release_connections(&self) {
+        self.http_client.release();
+    }
 }
 
 impl GeminiProvider {
@@ -401,7 +431,7 @@ impl GeminiProvider {
             }
         }
 
-        let tools: Option<Vec<Tool>> = request.tools.as_ref().map(|definitions| {
+        let mut tools: Option<Vec<Tool>> = request.tools.as_ref().map(|definitions| {
             definitions
                 .iter()
                 .map(|tool| Tool {
@@ -410,9 +440,16 @@ impl GeminiProvider {
                         description: tool.function.description.clone(),
                         parameters: tool.function.parameters.clone(),
                     }],
+                    code_execution: None,
                 })
                 .collect()
         });
+        if request.code_execution {
+            tools.get_or_insert_with(Vec::new).push(Tool {
+                function_declarations: Vec::new(),
+                code_execution: Some(json!({})),
+            });
+        }
 
         let mut generation_config = Map::new();
         if let Some(max_tokens) = request.max_tokens {
@@ -421,6 +458,12 @@ impl GeminiProvider {
         if let Some(temp) = request.temperature {
             generation_config.insert("temperature".to_string(), json!(temp));
         }
+        if let Some(seed) = request.seed {
+            generation_config.insert("seed".to_string(), json!(seed));
+        }
+        if request.json_mode {
+            generation_config.insert("responseMimeType".to_string(), json!("application/json"));
+        }
         let has_tools = request
             .tools
             .as_ref()
@@ -469,25 +512,57 @@ impl GeminiProvider {
     fn convert_from_gemini_response(
         response: GenerateContentResponse,
     ) -> Result<LLMResponse, LLMError> {
+        // A blocked prompt comes back without candidates and names the reason
+        // in the prompt feedback.
+        let block_reason = response
+            .prompt_feedback
+            .as_ref()
+            .and_then(|feedback| feedback.get("blockReason"))
+            .and_then(|reason| reason.as_str())
+            .map(str::to_string);
         let mut candidates = response.candidates.into_iter();
-        let candidate = candidates.next().ok_or_else(|| {
+        let Some(candidate) = candidates.next() else {
+            if let Some(reason) = block_reason {
+                return Ok(LLMResponse {
+                    content: Some(format!("Prompt blocked by Gemini ({})", reason)),
+                    tool_calls: None,
+                    usage: None,
+                    finish_reason: FinishReason::ContentFilter,
+                    reasoning: None,
+                    code_execution: Vec::new(),
+                });
+            }
             let formatted_error =
                 error_display::format_llm_error("Gemini", "No candidate in response");
-            LLMError::Provider(formatted_error)
-        })?;
+            return Err(LLMError::Provider(formatted_error));
+        };
+
+        let finish_reason = match candidate.finish_reason.as_deref() {
+            Some("STOP") => FinishReason::Stop,
+            Some("MAX_TOKENS") => FinishReason::Length,
+            Some(
+                "SAFETY" | "RECITATION" | "BLOCKLIST" | "PROHIBITED_CONTENT" | "SPII"
+                | "IMAGE_SAFETY",
+            ) => FinishReason::ContentFilter,
+            Some("FUNCTION_CALL") => FinishReason::ToolCalls,
+            Some(other) => FinishReason::Error(other.to_string()),
+            None => FinishReason::Stop,
+        };
 
         if candidate.content.parts.is_empty() {
             return Ok(LLMResponse {
                 content: Some(String::new()),
                 tool_calls: None,
                 usage: None,
-                finish_reason: FinishReason::Stop,
+                finish_reason,
                 reasoning: None,
+                code_execution: Vec::new(),
             });
         }
 
         let mut text_content = String::new();
         let mut tool_calls = Vec::new();
+        let mut code_execution = Vec::new();
 
         for part in candidate.content.parts {
             match part {
@@ -518,18 +593,14 @@ impl GeminiProvider {
                 Part::FunctionResponse { .. } => {
                     // Ignore echoed tool responses to avoid duplicating tool output
                 }
+                Part::ExecutableCode { .. }
+                | Part::CodeExecutionResult { .. }
+                | Part::InlineData { .. } => {
+                    code_execution.extend(code_execution_step(&part));
+                }
             }
         }
 
-        let finish_reason = match candidate.finish_reason.as_deref() {
-            Some("STOP") => FinishReason::Stop,
-            Some("MAX_TOKENS") => FinishReason::Length,
-            Some("SAFETY") => FinishReason::ContentFilter,
-            Some("FUNCTION_CALL") => FinishReason::ToolCalls,
-            Some(other) => FinishReason::Error(other.to_string()),
-            None => FinishReason::Stop,
-        };
-
         Ok(LLMResponse {
             content: if text_content.is_empty() {
                 None
@@ -544,6 +615,7 @@ impl GeminiProvider {
             usage: None,
             finish_reason,
             reasoning: None,
+            code_execution,
         })
     }
 
@@ -634,6 +706,37 @@ impl GeminiProvider {
     }
 }
 
+/// Map a hosted code execution part to a transcript step.
+fn code_execution_step(part: &Part) -> Option<CodeExecutionStep> {
+    match part {
+        Part::ExecutableCode { executable_code } => Some(CodeExecutionStep::Code {
+            language: executable_code
+                .language
+                .as_ref()
+                .map(|language| language.to_lowercase()),
+            code: executable_code.code.clone(),
+        }),
+        Part::CodeExecutionResult {
+            code_execution_result,
+        } => Some(CodeExecutionStep::Output {
+            output: code_execution_result.output.clone().unwrap_or_default(),
+            succeeded: code_execution_result.outcome == "OUTCOME_OK",
+        }),
+        Part::InlineData { inline_data } => match BASE64.decode(inline_data.data.as_bytes()) {
+            Ok(data) => Some(CodeExecutionStep::File(GeneratedFile {
+                name: None,
+                mime_type: Some(inline_data.mime_type.clone()),
+                data,
+            })),
+            Err(err) => {
+                tracing::warn!("Skipping undecodable inline data from Gemini: {}", err);
+                None
+            }
+        },
+        _ => None,
+    }
+}
+
 #[async_trait]
 impl LLMClient for GeminiProvider {
     async fn generate(&mut self, prompt: &str) -> Result<llm_types::LLMResponse, LLMError> {
@@ -677,6 +780,7 @@ impl LLMClient for GeminiProvider {
                             content: content_text,
                             tool_calls: None,
                             tool_call_id: None,
+                            provenance: None,
                         });
                     }
 
@@ -718,6 +822,14 @@ impl LLMClient for GeminiProvider {
                         parallel_tool_calls: None,
                         parallel_tool_config: None,
                         reasoning_effort: None,
+                        json_mode: false,
+                        response_schema: None,
+                        code_execution: false,
+                        seed: gemini_request
+                            .generation_config
+                            .as_ref()
+                            .and_then(|config| config.get("seed"))
+                            .and_then(|v| v.as_u64()),
                     };
 
                     // Use the standard LLMProvider generate method
@@ -767,6 +879,7 @@ impl LLMClient for GeminiProvider {
                             content: prompt.to_string(),
                             tool_calls: None,
                             tool_call_id: None,
+                            provenance: None,
                         }],
                         system_prompt: None,
                         tools: None,
@@ -778,6 +891,10 @@ impl LLMClient for GeminiProvider {
                         parallel_tool_calls: None,
                         parallel_tool_config: None,
                         reasoning_effort: None,
+                        json_mode: false,
+                        response_schema: None,
+                        code_execution: false,
+                        seed: None,
                     }
                 }
             }
@@ -789,6 +906,7 @@ impl LLMClient for GeminiProvider {
                     content: prompt.to_string(),
                     tool_calls: None,
                     tool_call_id: None,
+                    provenance: None,
                 }],
                 system_prompt: None,
                 tools: None,
@@ -800,6 +918,10 @@ impl LLMClient for GeminiProvider {
                 parallel_tool_calls: None,
                 parallel_tool_config: None,
                 reasoning_effort: None,
+                json_mode: false,
+                response_schema: None,
+                code_execution: false,
+                seed: None,
             }
         };
 
@@ -880,6 +1002,10 @@ mod tests {
             parallel_tool_calls: None,
             parallel_tool_config: None,
             reasoning_effort: None,
+            json_mode: false,
+            response_schema: None,
+            code_execution: false,
+            seed: None,
         };
 
         let gemini_request = provider
@@ -913,6 +1039,99 @@ mod tests {
         assert_eq!(tool_part.name, "list_files");
     }
 
+    #[test]
+    fn convert_to_gemini_request_sets_json_mime_type_in_json_mode() {
+        let provider = GeminiProvider::new("test-key".to_string());
+        let request = LLMRequest {
+            messages: vec![Message::user("hello".to_string())],
+            system_prompt: None,
+            tools: None,
+            model: models::google::GEMINI_2_5_FLASH_PREVIEW.to_string(),
+            max_tokens: None,
+            temperature: None,
+            stream: false,
+            tool_choice: None,
+            parallel_tool_calls: None,
+            parallel_tool_config: None,
+            reasoning_effort: None,
+            json_mode: true,
+            response_schema: None,
+            code_execution: false,
+            seed: None,
+        };
+
+        let gemini_request = provider
+            .convert_to_gemini_request(&request)
+            .expect("conversion should succeed");
+        let generation_config = gemini_request
+            .generation_config
+            .expect("generation config should be present");
+        assert_eq!(generation_config["responseMimeType"], "application/json");
+    }
+
+    #[test]
+    fn code_execution_parts_become_steps() {
+        let request = LLMRequest {
+            messages: vec![Message::user("Sum 1..10".to_string())],
+            system_prompt: None,
+            tools: None,
+            model: models::GEMINI_2_5_FLASH.to_string(),
+            max_tokens: None,
+            temperature: None,
+            stream: false,
+            tool_choice: None,
+            parallel_tool_calls: None,
+            parallel_tool_config: None,
+            reasoning_effort: None,
+            json_mode: false,
+            response_schema: None,
+            code_execution: true,
+            seed: None,
+        };
+        let provider = GeminiProvider::new("test-key".to_string());
+        let gemini_request = provider
+            .convert_to_gemini_request(&request)
+            .expect("conversion should succeed");
+        let tools = serde_json::to_value(gemini_request.tools).unwrap();
+        assert_eq!(tools, json!([{ "codeExecution": {} }]));
+
+        let response: GenerateContentResponse = serde_json::from_value(json!({
+            "candidates": [{
+                "content": {
+                    "role": "model",
+                    "parts": [
+                        { "executableCode": { "language": "PYTHON", "code": "print(sum(range(11)))" } },
+                        { "codeExecutionResult": { "outcome": "OUTCOME_OK", "output": "55\n" } },
+                        { "inlineData": { "mimeType": "image/png", "data": "iVBO" } },
+                        { "text": "The sum is 55." }
+                    ]
+                },
+                "finishReason": "STOP"
+            }]
+        }))
+        .unwrap();
+        let converted = GeminiProvider::convert_from_gemini_response(response).unwrap();
+        assert_eq!(converted.content.as_deref(), Some("The sum is 55."));
+        assert_eq!(
+            converted.code_execution,
+            vec![
+                CodeExecutionStep::Code {
+                    language: Some("python".to_string()),
+                    code: "print(sum(range(11)))".to_string(),
+                },
+                CodeExecutionStep::Output {
+                    output: "55\n".to_string(),
+                    succeeded: true,
+                },
+                CodeExecutionStep::File(GeneratedFile {
+                    name: None,
+                    mime_type: Some("image/png".to_string()),
+                    data: vec![0x89, 0x50, 0x4e],
+                }),
+            ]
+        );
+    }
+
     #[test]
     fn convert_from_gemini_response_extracts_tool_calls() {
         let response = GenerateContentResponse {
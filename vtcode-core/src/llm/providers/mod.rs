@@ -1,17 +1,43 @@
+#[cfg(feature = "provider-anthropic")]
 pub mod anthropic;
+#[cfg(feature = "provider-gemini")]
 pub mod gemini;
+#[cfg(feature = "provider-ollama")]
+pub mod ollama;
+#[cfg(feature = "provider-openai")]
 pub mod openai;
+#[cfg(feature = "provider-openrouter")]
 pub mod openrouter;
+#[cfg(feature = "provider-xai")]
 pub mod xai;
 
+#[cfg(any(feature = "provider-openai", feature = "provider-openrouter"))]
 mod codex_prompt;
+#[cfg(any(
+    feature = "provider-anthropic",
+    feature = "provider-openai",
+    feature = "provider-openrouter"
+))]
 mod reasoning;
 
+#[cfg(any(feature = "provider-openai", feature = "provider-openrouter"))]
 pub(crate) use codex_prompt::gpt5_codex_developer_prompt;
+#[cfg(any(
+    feature = "provider-anthropic",
+    feature = "provider-openai",
+    feature = "provider-openrouter"
+))]
 pub(crate) use reasoning::extract_reasoning_trace;
 
+#[cfg(feature = "provider-anthropic")]
 pub use anthropic::AnthropicProvider;
+#[cfg(feature = "provider-gemini")]
 pub use gemini::GeminiProvider;
+#[cfg(feature = "provider-ollama")]
+pub use ollama::OllamaProvider;
+#[cfg(feature = "provider-openai")]
 pub use openai::OpenAIProvider;
+#[cfg(feature = "provider-openrouter")]
 pub use openrouter::OpenRouterProvider;
+#[cfg(feature = "provider-xai")]
 pub use xai::XAIProvider;
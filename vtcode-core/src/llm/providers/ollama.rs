@@ -0,0 +1,599 @@
+//! Models served by a local Ollama server
+//!
+//! Talks to Ollama's native `/api/chat` endpoint rather than its
+//! OpenAI-compatible one so tool calls and thinking traces come back
+//! structured. Streaming responses are newline-delimited JSON objects, one per
+//! chunk, with the token counts on the final `done` object. Ollama does not
+//! assign ids to tool calls, so they are generated here and tool results are
+//! sent back with the name of the function they answer.
+//!
+//! No API key is needed; when one is configured it is sent as a bearer token,
+//! which is what reverse proxies in front of a shared server usually expect.
+
+use crate::config::constants::{models, urls};
+use crate::config::core::PromptCachingConfig;
+use crate::llm::client::LLMClient;
+use crate::llm::error_display;
+use crate::llm::http_pool::PooledClient;
+use crate::llm::provider::{
+    FinishReason, LLMError, LLMProvider, LLMRequest, LLMResponse, LLMStream, LLMStreamEvent,
+    Message, MessageRole, ToolCall, ToolChoice, Usage,
+};
+use crate::llm::types as llm_types;
+use async_stream::try_stream;
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const PROVIDER: &str = "Ollama";
+
+pub struct OllamaProvider {
+    api_key: Option<String>,
+    http_client: PooledClient,
+    base_url: String,
+    model: String,
+}
+
+impl OllamaProvider {
+    pub fn new() -> Self {
+        Self::from_config(None, None, None, None)
+    }
+
+    pub fn with_model(model: String) -> Self {
+        Self::from_config(None, Some(model), None, None)
+    }
+
+    pub fn from_config(
+        api_key: Option<String>,
+        model: Option<String>,
+        base_url: Option<String>,
+        _prompt_cache: Option<PromptCachingConfig>,
+    ) -> Self {
+        Self {
+            api_key: api_key.filter(|key| !key.trim().is_empty()),
+            http_client: PooledClient::for_provider("ollama"),
+            base_url: normalize_base_url(base_url.as_deref().unwrap_or(urls::OLLAMA_API_BASE)),
+            model: model.unwrap_or_else(|| models::ollama::DEFAULT_MODEL.to_string()),
+        }
+    }
+
+    fn chat_url(&self) -> String {
+        format!("{}/api/chat", self.base_url)
+    }
+
+    fn convert_to_ollama_format(&self, request: &LLMRequest, stream: bool) -> Value {
+        let mut messages = Vec::with_capacity(request.messages.len() + 1);
+        if let Some(system) = request.system_prompt.as_deref()
+            && !system.trim().is_empty()
+        {
+            messages.push(json!({ "role": "system", "content": system }));
+        }
+
+        // Tool results are matched to their call by function name.
+        let mut call_names: HashMap<&str, &str> = HashMap::new();
+        for message in &request.messages {
+            if let Some(calls) = &message.tool_calls {
+                for call in calls {
+                    call_names.insert(call.id.as_str(), call.function.name.as_str());
+                }
+            }
+            messages.push(convert_message(message, &call_names));
+        }
+
+        let model = if request.model.trim().is_empty() {
+            self.model.as_str()
+        } else {
+            request.model.as_str()
+        };
+        let mut body = json!({
+            "model": model,
+            "messages": messages,
+            "stream": stream,
+        });
+
+        let tools_allowed = !matches!(request.tool_choice, Some(ToolChoice::None));
+        if let Some(tools) = &request.tools
+            && !tools.is_empty()
+            && tools_allowed
+        {
+            body["tools"] = serde_json::to_value(tools).unwrap_or(Value::Null);
+        }
+
+        let mut options = Map::new();
+        if let Some(temperature) = request.temperature {
+            options.insert("temperature".to_string(), json!(temperature));
+        }
+        if let Some(max_tokens) = request.max_tokens {
+            options.insert("num_predict".to_string(), json!(max_tokens));
+        }
+        if let Some(seed) = request.seed {
+            options.insert("seed".to_string(), json!(seed));
+        }
+        if !options.is_empty() {
+            body["options"] = Value::Object(options);
+        }
+        if let Some(schema) = &request.response_schema {
+            body["format"] = schema.clone();
+        } else if request.json_mode {
+            body["format"] = json!("json");
+        }
+        body
+    }
+
+    async fn send(&self, body: &Value) -> Result<reqwest::Response, LLMError> {
+        let mut builder = self.http_client.get()?.post(self.chat_url()).json(body);
+        if let Some(key) = &self.api_key {
+            builder = builder.bearer_auth(key);
+        }
+        let response = builder.send().await.map_err(|err| {
+            let formatted = error_display::format_llm_error(
+                PROVIDER,
+                &format!(
+                    "Network error: {err}. Is `ollama serve` running at {}?",
+                    self.base_url
+                ),
+            );
+            LLMError::Network(formatted)
+        })?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let body = response.text().await.unwrap_or_default();
+        let message = serde_json::from_str::<Value>(&body)
+            .ok()
+            .and_then(|value| value.get("error")?.as_str().map(str::to_string))
+            .unwrap_or(body);
+        let formatted =
+            error_display::format_llm_error(PROVIDER, &format!("HTTP {status}: {message}"));
+        Err(match status.as_u16() {
+            401 | 403 => LLMError::Authentication(formatted),
+            429 => LLMError::RateLimit,
+            400 | 404 => LLMError::InvalidRequest(formatted),
+            _ => LLMError::Provider(formatted),
+        })
+    }
+}
+
+impl Default for OllamaProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Accept the server root as well as the `/v1` and `/api` URLs people copy
+/// from other clients.
+fn normalize_base_url(url: &str) -> String {
+    let trimmed = url.trim().trim_end_matches('/');
+    let root = trimmed
+        .strip_suffix("/v1")
+        .or_else(|| trimmed.strip_suffix("/api"))
+        .unwrap_or(trimmed);
+    root.to_string()
+}
+
+fn convert_message(message: &Message, call_names: &HashMap<&str, &str>) -> Value {
+    let mut converted = json!({
+        "role": message.role.as_generic_str(),
+        "content": message.content,
+    });
+    match message.role {
+        MessageRole::Assistant => {
+            if let Some(calls) = message
+                .tool_calls
+                .as_ref()
+                .filter(|calls| !calls.is_empty())
+            {
+                converted["tool_calls"] = calls
+                    .iter()
+                    .map(|call| {
+                        let arguments = serde_json::from_str::<Value>(&call.function.arguments)
+                            .unwrap_or_else(|_| json!({}));
+                        json!({
+                            "function": {
+                                "name": call.function.name,
+                                "arguments": arguments,
+                            }
+                        })
+                    })
+                    .collect();
+            }
+        }
+        MessageRole::Tool => {
+            if let Some(name) = message
+                .tool_call_id
+                .as_deref()
+                .and_then(|id| call_names.get(id))
+            {
+                converted["tool_name"] = json!(name);
+            }
+        }
+        MessageRole::System | MessageRole::User => {}
+    }
+    converted
+}
+
+/// Folds `/api/chat` objects, whole or streamed, into one response.
+struct ResponseBuilder {
+    call_prefix: String,
+    content: String,
+    reasoning: String,
+    tool_calls: Vec<ToolCall>,
+    usage: Option<Usage>,
+    truncated: bool,
+}
+
+/// Text added by one chunk.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct ChunkDelta {
+    content: String,
+    thinking: String,
+}
+
+impl ResponseBuilder {
+    fn new() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos())
+            .unwrap_or_default();
+        Self {
+            call_prefix: format!("ollama_{nanos:x}"),
+            content: String::new(),
+            reasoning: String::new(),
+            tool_calls: Vec::new(),
+            usage: None,
+            truncated: false,
+        }
+    }
+
+    fn push(&mut self, chunk: &Value) -> Result<ChunkDelta, LLMError> {
+        if let Some(error) = chunk.get("error").and_then(Value::as_str) {
+            return Err(LLMError::Provider(error_display::format_llm_error(
+                PROVIDER, error,
+            )));
+        }
+
+        let mut delta = ChunkDelta::default();
+        if let Some(message) = chunk.get("message") {
+            let text = |key: &str| message.get(key).and_then(Value::as_str).unwrap_or("");
+            delta.content = text("content").to_string();
+            delta.thinking = text("thinking").to_string();
+            self.content.push_str(&delta.content);
+            self.reasoning.push_str(&delta.thinking);
+            for call in message
+                .get("tool_calls")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                self.push_tool_call(call);
+            }
+        }
+
+        if chunk.get("done").and_then(Value::as_bool) == Some(true) {
+            self.truncated = chunk.get("done_reason").and_then(Value::as_str) == Some("length");
+            let count = |key: &str| chunk.get(key).and_then(Value::as_u64).unwrap_or(0) as u32;
+            let (prompt, completion) = (count("prompt_eval_count"), count("eval_count"));
+            self.usage = Some(Usage {
+                prompt_tokens: prompt,
+                completion_tokens: completion,
+                total_tokens: prompt + completion,
+                cached_prompt_tokens: None,
+                cache_creation_tokens: None,
+                cache_read_tokens: None,
+            });
+        }
+        Ok(delta)
+    }
+
+    fn push_tool_call(&mut self, call: &Value) {
+        let Some(function) = call.get("function") else {
+            return;
+        };
+        let Some(name) = function.get("name").and_then(Value::as_str) else {
+            return;
+        };
+        let arguments = match function.get("arguments") {
+            Some(Value::String(raw)) => raw.clone(),
+            Some(value) => value.to_string(),
+            None => "{}".to_string(),
+        };
+        let id = format!("{}_{}", self.call_prefix, self.tool_calls.len());
+        self.tool_calls
+            .push(ToolCall::function(id, name.to_string(), arguments));
+    }
+
+    fn finish(self) -> LLMResponse {
+        let finish_reason = if !self.tool_calls.is_empty() {
+            FinishReason::ToolCalls
+        } else if self.truncated {
+            FinishReason::Length
+        } else {
+            FinishReason::Stop
+        };
+        LLMResponse {
+            content: (!self.content.is_empty()).then_some(self.content),
+            tool_calls: (!self.tool_calls.is_empty()).then_some(self.tool_calls),
+            usage: self.usage,
+            finish_reason,
+            reasoning: (!self.reasoning.trim().is_empty()).then_some(self.reasoning),
+            code_execution: Vec::new(),
+        }
+    }
+}
+
+fn parse_chunk(line: &[u8]) -> Result<Value, LLMError> {
+    serde_json::from_slice(line).map_err(|err| {
+        LLMError::Provider(error_display::format_llm_error(
+            PROVIDER,
+            &format!("Failed to parse response: {err}"),
+        ))
+    })
+}
+
+/// Remove and return the next complete line of a newline-delimited body.
+fn next_line(pending: &mut Vec<u8>) -> Option<Vec<u8>> {
+    let end = pending.iter().position(|&byte| byte == b'\n')?;
+    let mut line: Vec<u8> = pending.drain(..=end).collect();
+    line.pop();
+    Some(line)
+}
+
+#[async_trait]
+impl LLMProvider for OllamaProvider {
+    fn name(&self) -> &str {
+        "ollama"
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    fn supports_reasoning(&self, _model: &str) -> bool {
+        true
+    }
+
+    fn supports_json_mode(&self, _model: &str) -> bool {
+        true
+    }
+
+    fn supports_response_schema(&self, _model: &str) -> bool {
+        true
+    }
+
+    fn supports_seed(&self, _model: &str) -> bool {
+        true
+    }
+
+    async fn generate(&self, request: LLMRequest) -> Result<LLMResponse, LLMError> {
+        let body = self.convert_to_ollama_format(&request, false);
+        let bytes = self.send(&body).await?.bytes().await.map_err(|err| {
+            LLMError::Network(error_display::format_llm_error(
+                PROVIDER,
+                &format!("Network error: {err}"),
+            ))
+        })?;
+        let mut builder = ResponseBuilder::new();
+        builder.push(&parse_chunk(&bytes)?)?;
+        Ok(builder.finish())
+    }
+
+    async fn stream(&self, request: LLMRequest) -> Result<LLMStream, LLMError> {
+        let body = self.convert_to_ollama_format(&request, true);
+        let response = self.send(&body).await?;
+
+        let stream = try_stream! {
+            let mut body_stream = response.bytes_stream();
+            let mut pending = Vec::new();
+            let mut builder = ResponseBuilder::new();
+            let mut finished = false;
+
+            while !finished {
+                let mut lines = Vec::new();
+                match body_stream.next().await {
+                    Some(chunk) => {
+                        let chunk = chunk.map_err(|err| {
+                            LLMError::Network(error_display::format_llm_error(
+                                PROVIDER,
+                                &format!("Stream interrupted: {err}"),
+                            ))
+                        })?;
+                        pending.extend_from_slice(&chunk);
+                        while let Some(line) = next_line(&mut pending) {
+                            lines.push(line);
+                        }
+                    }
+                    None => {
+                        lines.push(std::mem::take(&mut pending));
+                        finished = true;
+                    }
+                }
+
+                for line in lines {
+                    if line.iter().all(u8::is_ascii_whitespace) {
+                        continue;
+                    }
+                    let delta = builder.push(&parse_chunk(&line)?)?;
+                    if !delta.thinking.is_empty() {
+                        yield LLMStreamEvent::Reasoning { delta: delta.thinking };
+                    }
+                    if !delta.content.is_empty() {
+                        yield LLMStreamEvent::Token { delta: delta.content };
+                    }
+                }
+            }
+
+            yield LLMStreamEvent::Completed { response: builder.finish() };
+        };
+
+        Ok(Box::pin(stream))
+    }
+
+    /// Suggestions only; any model pulled into the local server can be used.
+    fn supported_models(&self) -> Vec<String> {
+        models::ollama::SUPPORTED_MODELS
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    fn validate_request(&self, request: &LLMRequest) -> Result<(), LLMError> {
+        if request.messages.is_empty() {
+            let formatted = error_display::format_llm_error(PROVIDER, "Messages cannot be empty");
+            return Err(LLMError::InvalidRequest(formatted));
+        }
+        Ok(())
+    }
+
+    fn release_connections(&self) {
+        self.http_client.release();
+    }
+}
+
+#[async_trait]
+impl LLMClient for OllamaProvider {
+    async fn generate(&mut self, prompt: &str) -> Result<llm_types::LLMResponse, LLMError> {
+        let request = LLMRequest {
+            messages: vec![Message::user(prompt.to_string())],
+            system_prompt: None,
+            tools: None,
+            model: self.model.clone(),
+            max_tokens: None,
+            temperature: None,
+            stream: false,
+            tool_choice: None,
+            parallel_tool_calls: None,
+            parallel_tool_config: None,
+            reasoning_effort: None,
+            json_mode: false,
+            response_schema: None,
+            code_execution: false,
+            seed: None,
+        };
+        let response = LLMProvider::generate(self, request).await?;
+
+        Ok(llm_types::LLMResponse {
+            content: response.content.unwrap_or_default(),
+            model: self.model.clone(),
+            usage: response.usage.map(|u| llm_types::Usage {
+                prompt_tokens: u.prompt_tokens as usize,
+                completion_tokens: u.completion_tokens as usize,
+                total_tokens: u.total_tokens as usize,
+                cached_prompt_tokens: None,
+                cache_creation_tokens: None,
+                cache_read_tokens: None,
+            }),
+            reasoning: response.reasoning,
+        })
+    }
+
+    fn backend_kind(&self) -> llm_types::BackendKind {
+        llm_types::BackendKind::Ollama
+    }
+
+    fn model_id(&self) -> &str {
+        &self.model
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::provider::ToolDefinition;
+
+    #[test]
+    fn maps_tool_calls_and_results_onto_the_chat_api() {
+        let provider =
+            OllamaProvider::from_config(None, None, Some("http://gpu-box:11434/v1/".into()), None);
+        assert_eq!(provider.chat_url(), "http://gpu-box:11434/api/chat");
+
+        let call = ToolCall::function(
+            "call_1".to_string(),
+            "read_file".to_string(),
+            r#"{"path":"src/lib.rs"}"#.to_string(),
+        );
+        let request = LLMRequest {
+            messages: vec![
+                Message::user("show lib.rs".to_string()),
+                Message::assistant_with_tools(String::new(), vec![call]),
+                Message::tool_response("call_1".to_string(), "fn main() {}".to_string()),
+            ],
+            system_prompt: Some("be brief".to_string()),
+            tools: Some(vec![ToolDefinition::function(
+                "read_file".to_string(),
+                "Read a file".to_string(),
+                json!({"type": "object"}),
+            )]),
+            model: "qwen3:8b".to_string(),
+            max_tokens: Some(256),
+            temperature: None,
+            stream: true,
+            tool_choice: None,
+            parallel_tool_calls: None,
+            parallel_tool_config: None,
+            reasoning_effort: None,
+            json_mode: false,
+            response_schema: None,
+            code_execution: false,
+            seed: None,
+        };
+        let body = provider.convert_to_ollama_format(&request, true);
+        assert_eq!(body["model"], "qwen3:8b");
+        assert_eq!(body["messages"][0]["role"], "system");
+        assert_eq!(
+            body["messages"][2]["tool_calls"][0]["function"]["arguments"]["path"],
+            "src/lib.rs"
+        );
+        assert_eq!(body["messages"][3]["tool_name"], "read_file");
+        assert_eq!(body["tools"][0]["function"]["name"], "read_file");
+        assert_eq!(body["options"]["num_predict"], 256);
+        assert!(body.get("format").is_none());
+        assert!(body["options"].get("seed").is_none());
+
+        let schema = json!({"type": "object", "required": ["message"]});
+        let constrained = LLMRequest {
+            json_mode: true,
+            response_schema: Some(schema.clone()),
+            seed: Some(7),
+            ..request
+        };
+        let body = provider.convert_to_ollama_format(&constrained, false);
+        assert_eq!(body["format"], schema);
+        assert_eq!(body["options"]["seed"], 7);
+    }
+
+    #[test]
+    fn folds_streamed_chunks_into_a_response() {
+        let mut pending = concat!(
+            r#"{"message":{"role":"assistant","content":"","thinking":"look first"},"done":false}"#,
+            "\n",
+            r#"{"message":{"role":"assistant","content":"Reading","tool_calls":"#,
+            r#"[{"function":{"name":"read_file","arguments":{"path":"a.rs"}}}]},"done":false}"#,
+            "\n",
+            r#"{"message":{"role":"assistant","content":""},"done":true,"#,
+            r#""done_reason":"stop","prompt_eval_count":12,"eval_count":5}"#,
+        )
+        .as_bytes()
+        .to_vec();
+
+        let mut builder = ResponseBuilder::new();
+        let mut deltas = Vec::new();
+        while let Some(line) = next_line(&mut pending) {
+            deltas.push(builder.push(&parse_chunk(&line).unwrap()).unwrap());
+        }
+        builder.push(&parse_chunk(&pending).unwrap()).unwrap();
+        assert_eq!(deltas[0].thinking, "look first");
+        assert_eq!(deltas[1].content, "Reading");
+
+        let response = builder.finish();
+        assert_eq!(response.finish_reason, FinishReason::ToolCalls);
+        let calls = response.tool_calls.unwrap();
+        assert_eq!(calls[0].function.name, "read_file");
+        assert_eq!(calls[0].function.arguments, r#"{"path":"a.rs"}"#);
+        assert_eq!(response.usage.unwrap().total_tokens, 17);
+        assert_eq!(response.reasoning.as_deref(), Some("look first"));
+    }
+}
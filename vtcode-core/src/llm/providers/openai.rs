@@ -2,20 +2,22 @@ use crate::config::constants::{models, urls};
 use crate::config::core::{OpenAIPromptCacheSettings, PromptCachingConfig};
 use crate::llm::client::LLMClient;
 use crate::llm::error_display;
+use crate::llm::http_pool::PooledClient;
 use crate::llm::provider::{
-    FinishReason, LLMError, LLMProvider, LLMRequest, LLMResponse, Message, MessageRole, ToolCall,
-    ToolChoice, ToolDefinition,
+    CodeExecutionStep, FinishReason, GeneratedFile, LLMError, LLMProvider, LLMRequest, LLMResponse,
+    Message, MessageRole, ToolCall, ToolChoice, ToolDefinition,
 };
 use crate::llm::types as llm_types;
 use async_trait::async_trait;
-use reqwest::Client as HttpClient;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
 use serde_json::{Value, json};
 
 use super::{extract_reasoning_trace, gpt5_codex_developer_prompt};
 
 pub struct OpenAIProvider {
     api_key: String,
-    http_client: HttpClient,
+    http_client: PooledClient,
     base_url: String,
     model: String,
     prompt_cache_enabled: bool,
@@ -67,6 +69,12 @@ impl OpenAIProvider {
         provider
     }
 
+    /// Use the `[network]` settings of `provider` for an OpenAI-compatible API.
+    pub(crate) fn with_network_provider(mut self, provider: &'static str) -> Self {
+        self.http_client = PooledClient::for_provider(provider);
+        self
+    }
+
     fn with_model_internal(
         api_key: String,
         model: String,
@@ -77,7 +85,7 @@ impl OpenAIProvider {
 
         Self {
             api_key,
-            http_client: HttpClient::new(),
+            http_client: PooledClient::for_provider("openai"),
             base_url: urls::OPENAI_API_BASE.to_string(),
             model,
             prompt_cache_enabled,
@@ -119,6 +127,10 @@ impl OpenAIProvider {
             parallel_tool_calls: None,
             parallel_tool_config: None,
             reasoning_effort: None,
+            json_mode: false,
+            response_schema: None,
+            code_execution: false,
+            seed: None,
         }
     }
 
@@ -189,6 +201,7 @@ impl OpenAIProvider {
                             content: text_content,
                             tool_calls: Some(calls),
                             tool_call_id: None,
+                            provenance: None,
                         }
                     } else {
                         Message::assistant(text_content)
@@ -215,6 +228,7 @@ impl OpenAIProvider {
                         content: content_value,
                         tool_calls: None,
                         tool_call_id,
+                        provenance: None,
                     });
                 }
                 _ => {
@@ -261,6 +275,7 @@ impl OpenAIProvider {
             .get("temperature")
             .and_then(|v| v.as_f64())
             .map(|v| v as f32);
+        let seed = value.get("seed").and_then(|v| v.as_u64());
         let max_tokens = value
             .get("max_tokens")
             .and_then(|v| v.as_u64())
@@ -301,6 +316,10 @@ impl OpenAIProvider {
             parallel_tool_calls,
             parallel_tool_config: None,
             reasoning_effort,
+            json_mode: false,
+            response_schema: None,
+            code_execution: false,
+            seed,
         })
     }
 
@@ -418,21 +437,28 @@ impl OpenAIProvider {
             openai_request["max_tokens"] = json!(max_tokens);
         }
 
-        if let Some(tools) = &request.tools {
-            if !tools.is_empty() {
-                let tools_json: Vec<Value> = tools
-                    .iter()
-                    .map(|tool| {
-                        json!({
-                            "type": "function",
-                            "name": tool.function.name,
-                            "description": tool.function.description,
-                            "parameters": tool.function.parameters
-                        })
-                    })
-                    .collect();
-                openai_request["tools"] = Value::Array(tools_json);
-            }
+        let mut tools_json: Vec<Value> = request
+            .tools
+            .iter()
+            .flatten()
+            .map(|tool| {
+                json!({
+                    "type": "function",
+                    "name": tool.function.name,
+                    "description": tool.function.description,
+                    "parameters": tool.function.parameters
+                })
+            })
+            .collect();
+        if request.code_execution {
+            tools_json.push(json!({
+                "type": "code_interpreter",
+                "container": { "type": "auto" }
+            }));
+            openai_request["include"] = json!(["code_interpreter_call.outputs"]);
+        }
+        if !tools_json.is_empty() {
+            openai_request["tools"] = Value::Array(tools_json);
         }
 
         if let Some(tool_choice) = &request.tool_choice {
@@ -443,12 +469,27 @@ impl OpenAIProvider {
             openai_request["parallel_tool_calls"] = Value::Bool(parallel);
         }
 
+        if let Some(seed) = request.seed
+            && self.supports_seed(&request.model)
+        {
+            openai_request["seed"] = json!(seed);
+        }
+
         if let Some(effort) = request.reasoning_effort.as_deref() {
             if self.supports_reasoning_effort(&request.model) {
                 openai_request["reasoning"] = json!({ "effort": effort });
             }
         }
 
+        if let Some(schema) = &request.response_schema {
+            openai_request["response_format"] = json!({
+                "type": "json_schema",
+                "json_schema": { "name": "response", "schema": schema, "strict": false }
+            });
+        } else if request.json_mode {
+            openai_request["response_format"] = json!({ "type": "json_object" });
+        }
+
         Ok(openai_request)
     }
 
@@ -481,21 +522,28 @@ impl OpenAIProvider {
             openai_request["max_output_tokens"] = json!(max_tokens);
         }
 
-        if let Some(tools) = &request.tools {
-            if !tools.is_empty() {
-                let tools_json: Vec<Value> = tools
-                    .iter()
-                    .map(|tool| {
-                        json!({
-                            "type": "function",
-                            "name": tool.function.name,
-                            "description": tool.function.description,
-                            "parameters": tool.function.parameters
-                        })
-                    })
-                    .collect();
-                openai_request["tools"] = Value::Array(tools_json);
-            }
+        let mut tools_json: Vec<Value> = request
+            .tools
+            .iter()
+            .flatten()
+            .map(|tool| {
+                json!({
+                    "type": "function",
+                    "name": tool.function.name,
+                    "description": tool.function.description,
+                    "parameters": tool.function.parameters
+                })
+            })
+            .collect();
+        if request.code_execution {
+            tools_json.push(json!({
+                "type": "code_interpreter",
+                "container": { "type": "auto" }
+            }));
+            openai_request["include"] = json!(["code_interpreter_call.outputs"]);
+        }
+        if !tools_json.is_empty() {
+            openai_request["tools"] = Value::Array(tools_json);
         }
 
         if let Some(tool_choice) = &request.tool_choice {
@@ -516,6 +564,14 @@ impl OpenAIProvider {
             openai_request["reasoning"] = json!({ "effort": "medium" });
         }
 
+        if let Some(schema) = &request.response_schema {
+            openai_request["text"] = json!({
+                "format": { "type": "json_schema", "name": "response", "schema": schema, "strict": false }
+            });
+        } else if request.json_mode {
+            openai_request["text"] = json!({ "format": { "type": "json_object" } });
+        }
+
         Ok(openai_request)
     }
 
@@ -559,6 +615,13 @@ impl OpenAIProvider {
             _ => None,
         };
 
+        // Safety refusals arrive in their own field rather than as content.
+        let refusal = message
+            .get("refusal")
+            .and_then(|value| value.as_str())
+            .filter(|text| !text.is_empty())
+            .map(str::to_string);
+
         let tool_calls = message
             .get("tool_calls")
             .and_then(|tc| tc.as_array())
@@ -603,6 +666,10 @@ impl OpenAIProvider {
                 other => FinishReason::Error(other.to_string()),
             })
             .unwrap_or(FinishReason::Stop);
+        let (content, finish_reason) = match refusal {
+            Some(refusal) => (content.or(Some(refusal)), FinishReason::ContentFilter),
+            None => (content, finish_reason),
+        };
 
         Ok(LLMResponse {
             content,
@@ -639,6 +706,7 @@ impl OpenAIProvider {
             }),
             finish_reason,
             reasoning,
+            code_execution: Vec::new(),
         })
     }
 
@@ -666,13 +734,19 @@ impl OpenAIProvider {
 
         let mut content_fragments = Vec::new();
         let mut reasoning_fragments = Vec::new();
+        let mut refusal_fragments: Vec<String> = Vec::new();
         let mut tool_calls_vec = Vec::new();
+        let mut code_execution = Vec::new();
 
         for item in output {
             let item_type = item
                 .get("type")
                 .and_then(|value| value.as_str())
                 .unwrap_or("");
+            if item_type == "code_interpreter_call" {
+                code_execution.extend(code_interpreter_steps(item));
+                continue;
+            }
             if item_type != "message" {
                 continue;
             }
@@ -698,6 +772,13 @@ impl OpenAIProvider {
                                 }
                             }
                         }
+                        "refusal" => {
+                            if let Some(text) =
+                                entry.get("refusal").and_then(|value| value.as_str())
+                            {
+                                refusal_fragments.push(text.to_string());
+                            }
+                        }
                         "tool_call" => {
                             let (name_value, arguments_value) = if let Some(function) =
                                 entry.get("function").and_then(|value| value.as_object())
@@ -737,10 +818,12 @@ impl OpenAIProvider {
             }
         }
 
-        let content = if content_fragments.is_empty() {
-            None
-        } else {
+        let content = if !content_fragments.is_empty() {
             Some(content_fragments.join(""))
+        } else if !refusal_fragments.is_empty() {
+            Some(refusal_fragments.join(""))
+        } else {
+            None
         };
 
         let reasoning = if reasoning_fragments.is_empty() {
@@ -800,9 +883,11 @@ impl OpenAIProvider {
             .unwrap_or("stop");
 
         let finish_reason = match stop_reason {
+            _ if !refusal_fragments.is_empty() => FinishReason::ContentFilter,
             "stop" => FinishReason::Stop,
             "max_output_tokens" | "length" => FinishReason::Length,
             "tool_use" | "tool_calls" => FinishReason::ToolCalls,
+            "content_filter" => FinishReason::ContentFilter,
             other => FinishReason::Error(other.to_string()),
         };
 
@@ -812,8 +897,155 @@ impl OpenAIProvider {
             usage,
             finish_reason,
             reasoning,
+            code_execution,
         })
     }
+
+    /// Download a file the code interpreter wrote to its container.
+    async fn fetch_container_file(
+        &self,
+        citation: &ContainerFileCitation,
+    ) -> Result<GeneratedFile, LLMError> {
+        let url = format!(
+            "{}/containers/{}/files/{}/content",
+            self.base_url, citation.container_id, citation.file_id
+        );
+        let response = self
+            .http_client
+            .get()?
+            .get(&url)
+            .bearer_auth(&self.api_key)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| {
+                let formatted_error = error_display::format_llm_error(
+                    "OpenAI",
+                    &format!("Failed to download {}: {}", citation.file_id, e),
+                );
+                LLMError::Network(formatted_error)
+            })?;
+        let mime_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let data = response.bytes().await.map_err(|e| {
+            let formatted_error = error_display::format_llm_error(
+                "OpenAI",
+                &format!("Failed to download {}: {}", citation.file_id, e),
+            );
+            LLMError::Network(formatted_error)
+        })?;
+
+        Ok(GeneratedFile {
+            name: citation.filename.clone(),
+            mime_type,
+            data: data.to_vec(),
+        })
+    }
+}
+
+/// A file the model cited from its code interpreter container.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ContainerFileCitation {
+    container_id: String,
+    file_id: String,
+    filename: Option<String>,
+}
+
+/// The code, logs and images of one `code_interpreter_call` output item.
+fn code_interpreter_steps(item: &Value) -> Vec<CodeExecutionStep> {
+    let mut steps = Vec::new();
+    if let Some(code) = item.get("code").and_then(|value| value.as_str()) {
+        steps.push(CodeExecutionStep::Code {
+            language: Some("python".to_string()),
+            code: code.to_string(),
+        });
+    }
+
+    let succeeded = item.get("status").and_then(|value| value.as_str()) != Some("failed");
+    for output in item
+        .get("outputs")
+        .and_then(|value| value.as_array())
+        .into_iter()
+        .flatten()
+    {
+        match output.get("type").and_then(|value| value.as_str()) {
+            Some("logs") => {
+                if let Some(logs) = output.get("logs").and_then(|value| value.as_str()) {
+                    steps.push(CodeExecutionStep::Output {
+                        output: logs.to_string(),
+                        succeeded,
+                    });
+                }
+            }
+            Some("image") => {
+                if let Some(file) = output
+                    .get("url")
+                    .and_then(|value| value.as_str())
+                    .and_then(decode_data_url)
+                {
+                    steps.push(CodeExecutionStep::File(file));
+                }
+            }
+            _ => {}
+        }
+    }
+    steps
+}
+
+/// Decode a `data:<mime>;base64,<data>` URL.
+fn decode_data_url(url: &str) -> Option<GeneratedFile> {
+    let (header, data) = url.strip_prefix("data:")?.split_once(',')?;
+    let mime_type = header.strip_suffix(";base64")?;
+    let data = BASE64.decode(data.as_bytes()).ok()?;
+    Some(GeneratedFile {
+        name: None,
+        mime_type: (!mime_type.is_empty()).then(|| mime_type.to_string()),
+        data,
+    })
+}
+
+/// Files cited through `container_file_citation` annotations in message output.
+fn container_file_citations(response_json: &Value) -> Vec<ContainerFileCitation> {
+    let mut citations: Vec<ContainerFileCitation> = Vec::new();
+    let annotations = response_json
+        .get("output")
+        .and_then(|value| value.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|item| item.get("content").and_then(|value| value.as_array()))
+        .flatten()
+        .filter_map(|entry| entry.get("annotations").and_then(|value| value.as_array()))
+        .flatten();
+    for annotation in annotations {
+        if annotation.get("type").and_then(|value| value.as_str())
+            != Some("container_file_citation")
+        {
+            continue;
+        }
+        let (Some(container_id), Some(file_id)) = (
+            annotation
+                .get("container_id")
+                .and_then(|value| value.as_str()),
+            annotation.get("file_id").and_then(|value| value.as_str()),
+        ) else {
+            continue;
+        };
+        let citation = ContainerFileCitation {
+            container_id: container_id.to_string(),
+            file_id: file_id.to_string(),
+            filename: annotation
+                .get("filename")
+                .and_then(|value| value.as_str())
+                .map(str::to_string),
+        };
+        if !citations.contains(&citation) {
+            citations.push(citation);
+        }
+    }
+    citations
 }
 
 fn build_standard_responses_input_openai(request: &LLMRequest) -> Result<Vec<Value>, LLMError> {
@@ -1036,10 +1268,39 @@ impl LLMProvider for OpenAIProvider {
         "openai"
     }
 
+    fn supports_prompt_caching(&self, _model: &str) -> bool {
+        self.prompt_cache_enabled
+    }
+
     fn supports_reasoning(&self, _model: &str) -> bool {
         false
     }
 
+    fn supports_json_mode(&self, _model: &str) -> bool {
+        true
+    }
+
+    /// Self-hosted OpenAI-compatible servers (llama.cpp, vLLM, LM Studio)
+    /// turn `json_schema` response formats into a decoding grammar.
+    fn supports_response_schema(&self, _model: &str) -> bool {
+        self.base_url != urls::OPENAI_API_BASE
+    }
+
+    fn supports_code_execution(&self, _model: &str) -> bool {
+        true
+    }
+
+    /// Chat Completions takes a `seed`; it only pins sampling for models that
+    /// also take a temperature.
+    fn supports_seed(&self, model: &str) -> bool {
+        let requested = if model.trim().is_empty() {
+            self.model.as_str()
+        } else {
+            model
+        };
+        !Self::uses_responses_api(requested) && Self::supports_temperature_parameter(requested)
+    }
+
     fn supports_reasoning_effort(&self, model: &str) -> bool {
         let requested = if model.trim().is_empty() {
             self.model.as_str()
@@ -1057,12 +1318,13 @@ impl LLMProvider for OpenAIProvider {
             request.model = self.model.clone();
         }
 
-        if Self::uses_responses_api(&request.model) {
+        if Self::uses_responses_api(&request.model) || request.code_execution {
             let openai_request = self.convert_to_openai_responses_format(&request)?;
             let url = format!("{}/responses", self.base_url);
 
             let response = self
                 .http_client
+                .get()?
                 .post(&url)
                 .bearer_auth(&self.api_key)
                 .json(&openai_request)
@@ -1078,6 +1340,14 @@ impl LLMProvider for OpenAIProvider {
                 let status = response.status();
                 let error_text = response.text().await.unwrap_or_default();
 
+                if status.as_u16() == 401 || status.as_u16() == 403 {
+                    let formatted_error = error_display::format_llm_error(
+                        "OpenAI",
+                        &format!("HTTP {}: {}", status, error_text),
+                    );
+                    return Err(LLMError::Authentication(formatted_error));
+                }
+
                 if status.as_u16() == 429
                     || error_text.contains("insufficient_quota")
                     || error_text.contains("quota")
@@ -1101,13 +1371,22 @@ impl LLMProvider for OpenAIProvider {
                 LLMError::Provider(formatted_error)
             })?;
 
-            self.parse_openai_responses_response(openai_response)
+            let citations = container_file_citations(&openai_response);
+            let mut response = self.parse_openai_responses_response(openai_response)?;
+            for citation in citations {
+                match self.fetch_container_file(&citation).await {
+                    Ok(file) => response.code_execution.push(CodeExecutionStep::File(file)),
+                    Err(err) => tracing::warn!("Skipping code interpreter file: {}", err),
+                }
+            }
+            Ok(response)
         } else {
             let openai_request = self.convert_to_openai_format(&request)?;
             let url = format!("{}/chat/completions", self.base_url);
 
             let response = self
                 .http_client
+                .get()?
                 .post(&url)
                 .bearer_auth(&self.api_key)
                 .json(&openai_request)
@@ -1123,6 +1402,14 @@ impl LLMProvider for OpenAIProvider {
                 let status = response.status();
                 let error_text = response.text().await.unwrap_or_default();
 
+                if status.as_u16() == 401 || status.as_u16() == 403 {
+                    let formatted_error = error_display::format_llm_error(
+                        "OpenAI",
+                        &format!("HTTP {}: {}", status, error_text),
+                    );
+                    return Err(LLMError::Authentication(formatted_error));
+                }
+
                 if status.as_u16() == 429
                     || error_text.contains("insufficient_quota")
                     || error_text.contains("quota")
@@ -1181,6 +1468,10 @@ impl LLMProvider for OpenAIProvider {
 
         Ok(())
     }
+
+    fn release_connections(&self) {
+        self.http_client.release();
+    }
 }
 
 #[async_trait]
@@ -1213,3 +1504,84 @@ impl LLMClient for OpenAIProvider {
         &self.model
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_code_interpreter_output_and_file_citations() {
+        let provider = OpenAIProvider::new("test-key".to_string());
+        let response = json!({
+            "output": [
+                {
+                    "type": "code_interpreter_call",
+                    "status": "completed",
+                    "code": "print(6 * 7)",
+                    "container_id": "cntr_1",
+                    "outputs": [
+                        { "type": "logs", "logs": "42\n" },
+                        { "type": "image", "url": "data:image/png;base64,iVBO" }
+                    ]
+                },
+                {
+                    "type": "message",
+                    "content": [{
+                        "type": "output_text",
+                        "text": "Saved the table.",
+                        "annotations": [{
+                            "type": "container_file_citation",
+                            "container_id": "cntr_1",
+                            "file_id": "cfile_1",
+                            "filename": "table.csv"
+                        }]
+                    }]
+                }
+            ]
+        });
+
+        assert_eq!(
+            container_file_citations(&response),
+            vec![ContainerFileCitation {
+                container_id: "cntr_1".to_string(),
+                file_id: "cfile_1".to_string(),
+                filename: Some("table.csv".to_string()),
+            }]
+        );
+        let parsed = provider.parse_openai_responses_response(response).unwrap();
+        assert_eq!(parsed.content.as_deref(), Some("Saved the table."));
+        assert_eq!(
+            parsed.code_execution,
+            vec![
+                CodeExecutionStep::Code {
+                    language: Some("python".to_string()),
+                    code: "print(6 * 7)".to_string(),
+                },
+                CodeExecutionStep::Output {
+                    output: "42\n".to_string(),
+                    succeeded: true,
+                },
+                CodeExecutionStep::File(GeneratedFile {
+                    name: None,
+                    mime_type: Some("image/png".to_string()),
+                    data: vec![0x89, 0x50, 0x4e],
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn sends_seed_only_to_models_that_sample_with_it() {
+        let provider = OpenAIProvider::new("test-key".to_string());
+        let mut request = provider.default_request("hi");
+        request.seed = Some(7);
+
+        request.model = "qwen2.5-coder".to_string();
+        let body = provider.convert_to_openai_format(&request).unwrap();
+        assert_eq!(body["seed"], json!(7));
+
+        request.model = models::openai::GPT_5.to_string();
+        let body = provider.convert_to_openai_format(&request).unwrap();
+        assert!(body.get("seed").is_none());
+    }
+}
@@ -2,16 +2,19 @@ use crate::config::constants::{models, urls};
 use crate::config::core::{OpenRouterPromptCacheSettings, PromptCachingConfig};
 use crate::llm::client::LLMClient;
 use crate::llm::error_display;
+use crate::llm::http_pool::PooledClient;
 use crate::llm::provider::{
     FinishReason, LLMError, LLMProvider, LLMRequest, LLMResponse, LLMStream, LLMStreamEvent,
     Message, MessageRole, ToolCall, ToolChoice, ToolDefinition, Usage,
 };
+use crate::llm::sse::{SseDecoder, StreamErrorFrame};
 use crate::llm::types as llm_types;
 use async_stream::try_stream;
 use async_trait::async_trait;
 use futures::StreamExt;
 use reqwest::Client as HttpClient;
 use serde_json::{Map, Value, json};
+use std::time::Duration;
 
 use super::{extract_reasoning_trace, gpt5_codex_developer_prompt};
 
@@ -717,25 +720,72 @@ fn update_usage_from_value(source: &Value, usage: &mut Option<Usage>) {
     }
 }
 
-fn extract_data_payload(event: &str) -> Option<String> {
-    let mut data_lines: Vec<String> = Vec::new();
+/// Attempts at a streaming request whose connection keeps dropping.
+const STREAM_MAX_ATTEMPTS: usize = 3;
+/// Delay before reopening a dropped stream when the server sent no `retry:`.
+const STREAM_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Whether `payload` is the model's last chunk, so a connection closed right
+/// after it is a complete answer rather than a dropped stream.
+fn stream_payload_finishes(payload: &Value) -> bool {
+    let choice_finished = payload
+        .get("choices")
+        .and_then(Value::as_array)
+        .is_some_and(|choices| {
+            choices.iter().any(|choice| {
+                choice
+                    .get("finish_reason")
+                    .is_some_and(|reason| !reason.is_null())
+            })
+        });
+    choice_finished
+        || matches!(
+            payload.get("type").and_then(Value::as_str),
+            Some("response.completed" | "response.done" | "response.finished")
+        )
+}
 
-    for raw_line in event.lines() {
-        let line = raw_line.trim_end_matches('\r');
-        if line.is_empty() || line.starts_with(':') {
-            continue;
+async fn open_event_stream(
+    http_client: &HttpClient,
+    url: &str,
+    api_key: &str,
+    body: &Value,
+    last_event_id: Option<&str>,
+) -> Result<reqwest::Response, LLMError> {
+    let mut request = http_client.post(url).bearer_auth(api_key).json(body);
+    if let Some(id) = last_event_id {
+        request = request.header("Last-Event-ID", id);
+    }
+    let response = request.send().await.map_err(|e| {
+        let formatted_error =
+            error_display::format_llm_error("OpenRouter", &format!("Network error: {}", e));
+        LLMError::Network(formatted_error)
+    })?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+
+        if status.as_u16() == 401 || status.as_u16() == 403 {
+            let formatted_error = error_display::format_llm_error(
+                "OpenRouter",
+                &format!("HTTP {}: {}", status, error_text),
+            );
+            return Err(LLMError::Authentication(formatted_error));
         }
 
-        if let Some(value) = line.strip_prefix("data:") {
-            data_lines.push(value.trim_start().to_string());
+        if status.as_u16() == 429 || error_text.contains("quota") {
+            return Err(LLMError::RateLimit);
         }
-    }
 
-    if data_lines.is_empty() {
-        None
-    } else {
-        Some(data_lines.join("\n"))
+        let formatted_error = error_display::format_llm_error(
+            "OpenRouter",
+            &format!("HTTP {}: {}", status, error_text),
+        );
+        return Err(LLMError::Provider(formatted_error));
     }
+
+    Ok(response)
 }
 
 fn parse_stream_payload(
@@ -805,12 +855,13 @@ fn finalize_stream_response(
         usage,
         finish_reason,
         reasoning,
+        code_execution: Vec::new(),
     }
 }
 
 pub struct OpenRouterProvider {
     api_key: String,
-    http_client: HttpClient,
+    http_client: PooledClient,
     base_url: String,
     model: String,
     prompt_cache_enabled: bool,
@@ -858,7 +909,7 @@ impl OpenRouterProvider {
 
         Self {
             api_key,
-            http_client: HttpClient::new(),
+            http_client: PooledClient::for_provider("openrouter"),
             base_url: urls::OPENROUTER_API_BASE.to_string(),
             model,
             prompt_cache_enabled,
@@ -891,6 +942,10 @@ impl OpenRouterProvider {
             parallel_tool_calls: None,
             parallel_tool_config: None,
             reasoning_effort: None,
+            json_mode: false,
+            response_schema: None,
+            code_execution: false,
+            seed: None,
         }
     }
 
@@ -977,6 +1032,7 @@ impl OpenRouterProvider {
                             content: text_content,
                             tool_calls: Some(calls),
                             tool_call_id: None,
+                            provenance: None,
                         }
                     } else {
                         Message::assistant(text_content)
@@ -1003,6 +1059,7 @@ impl OpenRouterProvider {
                         content: content_value,
                         tool_calls: None,
                         tool_call_id,
+                        provenance: None,
                     });
                 }
                 _ => {
@@ -1054,6 +1111,7 @@ impl OpenRouterProvider {
             .get("temperature")
             .and_then(|v| v.as_f64())
             .map(|v| v as f32);
+        let seed = value.get("seed").and_then(|v| v.as_u64());
         let stream = value
             .get("stream")
             .and_then(|v| v.as_bool())
@@ -1090,6 +1148,10 @@ impl OpenRouterProvider {
             parallel_tool_calls,
             parallel_tool_config: None,
             reasoning_effort,
+            json_mode: false,
+            response_schema: None,
+            code_execution: false,
+            seed,
         })
     }
 
@@ -1419,6 +1481,14 @@ impl OpenRouterProvider {
             provider_request["reasoning"] = json!({ "effort": "medium" });
         }
 
+        if let Some(schema) = &request.response_schema {
+            provider_request["text"] = json!({
+                "format": { "type": "json_schema", "name": "response", "schema": schema, "strict": false }
+            });
+        } else if request.json_mode {
+            provider_request["text"] = json!({ "format": { "type": "json_object" } });
+        }
+
         Ok(provider_request)
     }
 
@@ -1490,6 +1560,10 @@ impl OpenRouterProvider {
             provider_request["temperature"] = json!(temperature);
         }
 
+        if let Some(seed) = request.seed {
+            provider_request["seed"] = json!(seed);
+        }
+
         if let Some(tools) = &request.tools {
             if !tools.is_empty() {
                 let tools_json: Vec<Value> = tools
@@ -1523,6 +1597,15 @@ impl OpenRouterProvider {
             }
         }
 
+        if let Some(schema) = &request.response_schema {
+            provider_request["response_format"] = json!({
+                "type": "json_schema",
+                "json_schema": { "name": "response", "schema": schema, "strict": false }
+            });
+        } else if request.json_mode {
+            provider_request["response_format"] = json!({ "type": "json_object" });
+        }
+
         Ok(provider_request)
     }
 
@@ -1610,6 +1693,7 @@ impl OpenRouterProvider {
                 usage,
                 finish_reason,
                 reasoning,
+                code_execution: Vec::new(),
             });
         }
 
@@ -1715,6 +1799,7 @@ impl OpenRouterProvider {
             usage,
             finish_reason,
             reasoning,
+            code_execution: Vec::new(),
         })
     }
 }
@@ -1725,6 +1810,10 @@ impl LLMProvider for OpenRouterProvider {
         "openrouter"
     }
 
+    fn supports_prompt_caching(&self, _model: &str) -> bool {
+        self.prompt_cache_enabled
+    }
+
     fn supports_streaming(&self) -> bool {
         true
     }
@@ -1752,6 +1841,21 @@ impl LLMProvider for OpenRouterProvider {
             .any(|candidate| *candidate == requested)
     }
 
+    fn supports_json_mode(&self, _model: &str) -> bool {
+        true
+    }
+
+    /// Forwarded to the upstream provider on the chat path; upstreams without
+    /// seed support ignore it.
+    fn supports_seed(&self, model: &str) -> bool {
+        let requested = if model.trim().is_empty() {
+            self.model.as_str()
+        } else {
+            model
+        };
+        !Self::is_gpt5_codex_model(requested)
+    }
+
     async fn stream(&self, request: LLMRequest) -> Result<LLMStream, LLMError> {
         let (provider_request, url) = if self.uses_responses_api_for(&request) {
             let mut req = self.convert_to_openrouter_responses_format(&request)?;
@@ -1763,126 +1867,68 @@ impl LLMProvider for OpenRouterProvider {
             (req, format!("{}/chat/completions", self.base_url))
         };
 
-        let response = self
-            .http_client
-            .post(&url)
-            .bearer_auth(&self.api_key)
-            .json(&provider_request)
-            .send()
-            .await
-            .map_err(|e| {
-                let formatted_error =
-                    error_display::format_llm_error("OpenRouter", &format!("Network error: {}", e));
-                LLMError::Network(formatted_error)
-            })?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-
-            if status.as_u16() == 429 || error_text.contains("quota") {
-                return Err(LLMError::RateLimit);
-            }
-
-            let formatted_error = error_display::format_llm_error(
-                "OpenRouter",
-                &format!("HTTP {}: {}", status, error_text),
-            );
-            return Err(LLMError::Provider(formatted_error));
-        }
-
-        fn find_sse_boundary(buffer: &str) -> Option<(usize, usize)> {
-            let newline_boundary = buffer.find("\n\n").map(|idx| (idx, 2));
-            let carriage_boundary = buffer.find("\r\n\r\n").map(|idx| (idx, 4));
-
-            match (newline_boundary, carriage_boundary) {
-                (Some((n_idx, n_len)), Some((c_idx, c_len))) => {
-                    if n_idx <= c_idx {
-                        Some((n_idx, n_len))
-                    } else {
-                        Some((c_idx, c_len))
-                    }
-                }
-                (Some(boundary), None) => Some(boundary),
-                (None, Some(boundary)) => Some(boundary),
-                (None, None) => None,
-            }
-        }
+        let http_client = self.http_client.get()?;
+        let api_key = self.api_key.clone();
+        let response =
+            open_event_stream(&http_client, &url, &api_key, &provider_request, None).await?;
 
         let stream = try_stream! {
-            let mut body_stream = response.bytes_stream();
-            let mut buffer = String::new();
+            let mut response = Some(response);
             let mut aggregated_content = String::new();
             let mut tool_call_builders: Vec<ToolCallBuilder> = Vec::new();
             let mut reasoning = ReasoningBuffer::default();
             let mut usage: Option<Usage> = None;
             let mut finish_reason = FinishReason::Stop;
-            let mut done = false;
-
-            while let Some(chunk_result) = body_stream.next().await {
-                let chunk = chunk_result.map_err(|err| {
-                    let formatted_error = error_display::format_llm_error(
-                        "OpenRouter",
-                        &format!("Streaming error: {}", err),
-                    );
-                    LLMError::Network(formatted_error)
-                })?;
-
-                buffer.push_str(&String::from_utf8_lossy(&chunk));
-
-                while let Some((split_idx, delimiter_len)) = find_sse_boundary(&buffer) {
-                    let event = buffer[..split_idx].to_string();
-                    buffer.drain(..split_idx + delimiter_len);
-
-                    if let Some(data_payload) = extract_data_payload(&event) {
-                        let trimmed_payload = data_payload.trim();
-                        if trimmed_payload == "[DONE]" {
-                            done = true;
+            let mut decoder = SseDecoder::default();
+            let mut yielded = false;
+            let mut attempt = 1;
+
+            loop {
+                let current = match response.take() {
+                    Some(current) => current,
+                    None => {
+                        open_event_stream(
+                            &http_client,
+                            &url,
+                            &api_key,
+                            &provider_request,
+                            decoder.last_event_id(),
+                        )
+                        .await?
+                    }
+                };
+                let mut body_stream = current.bytes_stream();
+                let mut done = false;
+                let mut finished = false;
+                let mut interruption: Option<StreamErrorFrame> = None;
+                let mut dropped: Option<String> = None;
+
+                'read: loop {
+                    let events = match body_stream.next().await {
+                        Some(Ok(chunk)) => decoder.push(&chunk),
+                        Some(Err(err)) => {
+                            dropped = Some(err.to_string());
                             break;
                         }
-
-                        if !trimmed_payload.is_empty() {
-                            let payload: Value = serde_json::from_str(trimmed_payload).map_err(|err| {
-                                let formatted_error = error_display::format_llm_error(
-                                    "OpenRouter",
-                                    &format!("Failed to parse stream payload: {}", err),
-                                );
-                                LLMError::Provider(formatted_error)
-                            })?;
-
-                            if let Some(delta) = parse_stream_payload(
-                                &payload,
-                                &mut aggregated_content,
-                                &mut tool_call_builders,
-                                &mut reasoning,
-                                &mut usage,
-                                &mut finish_reason,
-                            ) {
-                                for fragment in delta.into_fragments() {
-                                    match fragment {
-                                        StreamFragment::Content(text) if !text.is_empty() => {
-                                            yield LLMStreamEvent::Token { delta: text };
-                                        }
-                                        StreamFragment::Reasoning(text) if !text.is_empty() => {
-                                            yield LLMStreamEvent::Reasoning { delta: text };
-                                        }
-                                        _ => {}
-                                    }
-                                }
+                        None => {
+                            let last = decoder.finish();
+                            if last.is_none() {
+                                break;
                             }
+                            last.into_iter().collect()
                         }
-                    }
-                }
+                    };
 
-                if done {
-                    break;
-                }
-            }
+                    for event in events {
+                        if event.is_done() {
+                            done = true;
+                            break 'read;
+                        }
+                        let trimmed_payload = event.data.trim();
+                        if trimmed_payload.is_empty() {
+                            continue;
+                        }
 
-            if !done && !buffer.trim().is_empty() {
-                if let Some(data_payload) = extract_data_payload(&buffer) {
-                    let trimmed_payload = data_payload.trim();
-                    if trimmed_payload != "[DONE]" && !trimmed_payload.is_empty() {
                         let payload: Value = serde_json::from_str(trimmed_payload).map_err(|err| {
                             let formatted_error = error_display::format_llm_error(
                                 "OpenRouter",
@@ -1891,6 +1937,12 @@ impl LLMProvider for OpenRouterProvider {
                             LLMError::Provider(formatted_error)
                         })?;
 
+                        if let Some(frame) = StreamErrorFrame::detect(event.event.as_deref(), &payload) {
+                            interruption = Some(frame);
+                            break 'read;
+                        }
+                        finished |= stream_payload_finishes(&payload);
+
                         if let Some(delta) = parse_stream_payload(
                             &payload,
                             &mut aggregated_content,
@@ -1902,9 +1954,11 @@ impl LLMProvider for OpenRouterProvider {
                             for fragment in delta.into_fragments() {
                                 match fragment {
                                     StreamFragment::Content(text) if !text.is_empty() => {
+                                        yielded = true;
                                         yield LLMStreamEvent::Token { delta: text };
                                     }
                                     StreamFragment::Reasoning(text) if !text.is_empty() => {
+                                        yielded = true;
                                         yield LLMStreamEvent::Reasoning { delta: text };
                                     }
                                     _ => {}
@@ -1913,6 +1967,37 @@ impl LLMProvider for OpenRouterProvider {
                         }
                     }
                 }
+
+                if done || (finished && interruption.is_none()) {
+                    break;
+                }
+
+                // Reopen the request when nothing has reached the caller yet, or
+                // when the server gave event ids it can resume from.
+                let resumable = decoder.last_event_id().is_some();
+                let retryable = interruption.as_ref().is_none_or(|frame| frame.retryable);
+                if retryable && (!yielded || resumable) && attempt < STREAM_MAX_ATTEMPTS {
+                    attempt += 1;
+                    tokio::time::sleep(decoder.retry().unwrap_or(STREAM_RETRY_DELAY)).await;
+                    if !resumable {
+                        aggregated_content.clear();
+                        tool_call_builders.clear();
+                        reasoning = ReasoningBuffer::default();
+                        usage = None;
+                        finish_reason = FinishReason::Stop;
+                        decoder = SseDecoder::default();
+                    }
+                    continue;
+                }
+
+                if let Some(frame) = interruption {
+                    Err(frame.into_llm_error("OpenRouter"))?;
+                }
+                let reason = dropped.unwrap_or_else(|| "connection closed before the end of the stream".to_string());
+                Err(LLMError::Network(error_display::format_llm_error(
+                    "OpenRouter",
+                    &format!("Stream interrupted after {} attempt(s): {}", attempt, reason),
+                )))?;
             }
 
             let response = finalize_stream_response(
@@ -1953,6 +2038,7 @@ impl LLMProvider for OpenRouterProvider {
 
         let response = self
             .http_client
+            .get()?
             .post(&url)
             .bearer_auth(&self.api_key)
             .json(&provider_request)
@@ -1968,6 +2054,14 @@ impl LLMProvider for OpenRouterProvider {
             let status = response.status();
             let error_text = response.text().await.unwrap_or_default();
 
+            if status.as_u16() == 401 || status.as_u16() == 403 {
+                let formatted_error = error_display::format_llm_error(
+                    "OpenRouter",
+                    &format!("HTTP {}: {}", status, error_text),
+                );
+                return Err(LLMError::Authentication(formatted_error));
+            }
+
             if status.as_u16() == 429 || error_text.contains("quota") {
                 return Err(LLMError::RateLimit);
             }
@@ -2019,6 +2113,10 @@ impl LLMProvider for OpenRouterProvider {
 
         Ok(())
     }
+
+    fn release_connections(&self) {
+        self.http_client.release();
+    }
 }
 
 #[async_trait]
@@ -2131,7 +2229,9 @@ mod tests {
     #[test]
     fn test_extract_data_payload_joins_multiline_events() {
         let event = ": keep-alive\n".to_string() + "data: {\"a\":1}\n" + "data: {\"b\":2}\n";
-        let payload = extract_data_payload(&event);
+        let mut decoder = SseDecoder::default();
+        assert!(decoder.push(event.as_bytes()).is_empty());
+        let payload = decoder.finish().map(|event| event.data);
         assert_eq!(payload.as_deref(), Some("{\"a\":1}\n{\"b\":2}"));
     }
 
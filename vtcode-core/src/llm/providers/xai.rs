@@ -38,7 +38,8 @@ impl XAIProvider {
             Some(resolved_model.clone()),
             Some(resolved_base_url),
             prompt_cache_forward,
-        );
+        )
+        .with_network_provider("xai");
 
         Self {
             inner,
@@ -78,6 +79,10 @@ impl LLMProvider for XAIProvider {
         "xai"
     }
 
+    fn supports_prompt_caching(&self, _model: &str) -> bool {
+        self.prompt_cache_enabled
+    }
+
     fn supports_reasoning(&self, model: &str) -> bool {
         let requested = if model.trim().is_empty() {
             self.model.as_str()
@@ -91,6 +96,14 @@ impl LLMProvider for XAIProvider {
         false
     }
 
+    fn supports_json_mode(&self, _model: &str) -> bool {
+        true
+    }
+
+    fn supports_seed(&self, model: &str) -> bool {
+        self.inner.supports_seed(model)
+    }
+
     async fn generate(&self, mut request: LLMRequest) -> Result<LLMResponse, LLMError> {
         if !self.prompt_cache_enabled {
             // xAI prompt caching is managed by the platform; no additional parameters required.
@@ -132,6 +145,10 @@ impl LLMProvider for XAIProvider {
 
         Ok(())
     }
+
+    fn release_connections(&self) {
+        self.inner.release_connections();
+    }
 }
 
 #[async_trait]
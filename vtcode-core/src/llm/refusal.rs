@@ -0,0 +1,138 @@
+//! Content-safety refusals from providers
+//!
+//! Providers signal a refusal in different ways: OpenAI and OpenRouter finish
+//! with `content_filter` or return a `refusal` field, Anthropic stops with
+//! `refusal`, and Gemini names a safety category or blocks the prompt outright.
+//! The providers map all of these onto [`FinishReason::ContentFilter`], so a
+//! refusal can be reported as such instead of being treated as an answer.
+
+use crate::llm::provider::{FinishReason, LLMResponse, Message, MessageRole};
+
+/// Placeholder for tool output withheld from a sanitized retry.
+pub const WITHHELD_TOOL_OUTPUT: &str = "[tool output withheld after a content-safety refusal]";
+
+/// Longest provider explanation kept with a refusal.
+const MAX_EXPLANATION_CHARS: usize = 300;
+
+/// A request the provider declined to answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Refusal {
+    pub provider: String,
+    pub model: String,
+    /// Text the provider returned with the refusal, if any
+    pub explanation: Option<String>,
+}
+
+impl Refusal {
+    /// The refusal carried by `response`, if it is one.
+    pub fn detect(provider: &str, model: &str, response: &LLMResponse) -> Option<Self> {
+        if response.finish_reason != FinishReason::ContentFilter {
+            return None;
+        }
+        let explanation = response
+            .content
+            .as_deref()
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .map(|text| {
+                if text.chars().count() > MAX_EXPLANATION_CHARS {
+                    let cut: String = text.chars().take(MAX_EXPLANATION_CHARS).collect();
+                    format!("{}…", cut.trim_end())
+                } else {
+                    text.to_string()
+                }
+            });
+        Some(Self {
+            provider: provider.to_string(),
+            model: model.to_string(),
+            explanation,
+        })
+    }
+
+    /// One-line description for the transcript.
+    pub fn summary(&self) -> String {
+        match &self.explanation {
+            Some(text) => format!(
+                "{} ({}) declined this request: {}",
+                self.provider, self.model, text
+            ),
+            None => format!(
+                "{} ({}) declined this request on content-safety grounds.",
+                self.provider, self.model
+            ),
+        }
+    }
+}
+
+/// Withhold the tool output produced since the latest user message, which is
+/// the most likely trigger when a refusal arrives mid-turn.
+///
+/// Returns the number of tool messages withheld.
+pub fn sanitize_for_retry(history: &mut [Message]) -> usize {
+    let start = history
+        .iter()
+        .rposition(|message| message.role == MessageRole::User)
+        .map_or(0, |index| index + 1);
+    let mut withheld = 0;
+    for message in &mut history[start..] {
+        if message.role == MessageRole::Tool && message.content != WITHHELD_TOOL_OUTPUT {
+            message.content = WITHHELD_TOOL_OUTPUT.to_string();
+            withheld += 1;
+        }
+    }
+    withheld
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(finish_reason: FinishReason, content: Option<&str>) -> LLMResponse {
+        LLMResponse {
+            content: content.map(str::to_string),
+            tool_calls: None,
+            usage: None,
+            finish_reason,
+            reasoning: None,
+            code_execution: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn detects_content_filter_responses_only() {
+        let ok = response(FinishReason::Stop, Some("Done."));
+        assert!(Refusal::detect("openai", "gpt-5", &ok).is_none());
+
+        let refused = response(
+            FinishReason::ContentFilter,
+            Some("  I can't help with that. "),
+        );
+        let refusal = Refusal::detect("openai", "gpt-5", &refused).unwrap();
+        assert_eq!(
+            refusal.explanation.as_deref(),
+            Some("I can't help with that.")
+        );
+        assert_eq!(
+            refusal.summary(),
+            "openai (gpt-5) declined this request: I can't help with that."
+        );
+
+        let silent = response(FinishReason::ContentFilter, Some(""));
+        let refusal = Refusal::detect("gemini", "gemini-2.5-pro", &silent).unwrap();
+        assert!(refusal.summary().ends_with("on content-safety grounds."));
+    }
+
+    #[test]
+    fn sanitizing_withholds_only_current_turn_tool_output() {
+        let mut history = vec![
+            Message::user("read a".to_string()),
+            Message::tool_response("call_1".to_string(), "old output".to_string()),
+            Message::user("read b".to_string()),
+            Message::tool_response("call_2".to_string(), "new output".to_string()),
+        ];
+        assert_eq!(sanitize_for_retry(&mut history), 1);
+        assert_eq!(history[1].content, "old output");
+        assert_eq!(history[3].content, WITHHELD_TOOL_OUTPUT);
+        assert_eq!(sanitize_for_retry(&mut history), 0);
+    }
+}
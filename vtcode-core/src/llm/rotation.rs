@@ -0,0 +1,348 @@
+//! API key rotation across named credential profiles
+//!
+//! [`RotatingProvider`] holds one client per credential profile of a provider
+//! and forwards requests to the active one. When a key reports an exhausted
+//! quota (`LLMError::RateLimit`, which providers return for HTTP 429 and
+//! `insufficient_quota` responses) the request is retried with the next
+//! profile, and that profile stays active for later requests.
+
+use crate::core::trajectory::TrajectoryLogger;
+use crate::llm::provider::{LLMError, LLMProvider, LLMRequest, LLMResponse, LLMStream};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A switch from one credential profile to the next after a quota error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RotationEvent {
+    pub from: String,
+    pub to: String,
+}
+
+/// Shared view of which profile is serving requests.
+///
+/// Cloned out of the provider before it is boxed so the chat loop can report
+/// rotations to the user.
+#[derive(Debug, Clone, Default)]
+pub struct CredentialUsage {
+    inner: Arc<Mutex<UsageState>>,
+}
+
+#[derive(Debug, Default)]
+struct UsageState {
+    last_served: Option<String>,
+    rotations: Vec<RotationEvent>,
+}
+
+impl CredentialUsage {
+    /// Profile that served the most recent successful request.
+    pub fn last_served(&self) -> Option<String> {
+        self.inner.lock().last_served.clone()
+    }
+
+    /// Rotations since the previous call, oldest first.
+    pub fn take_rotations(&self) -> Vec<RotationEvent> {
+        std::mem::take(&mut self.inner.lock().rotations)
+    }
+}
+
+/// Provider that spreads requests over several API keys of one provider.
+pub struct RotatingProvider {
+    name: String,
+    profiles: Vec<(String, Box<dyn LLMProvider>)>,
+    current: AtomicUsize,
+    rotate_on_quota: bool,
+    usage: CredentialUsage,
+    trajectory: Option<TrajectoryLogger>,
+}
+
+impl RotatingProvider {
+    /// Create a provider from `(profile name, client)` pairs in rotation order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `profiles` is empty.
+    pub fn new(
+        name: impl Into<String>,
+        profiles: Vec<(String, Box<dyn LLMProvider>)>,
+        rotate_on_quota: bool,
+    ) -> Self {
+        assert!(
+            !profiles.is_empty(),
+            "RotatingProvider needs at least one credential profile"
+        );
+        Self {
+            name: name.into(),
+            profiles,
+            current: AtomicUsize::new(0),
+            rotate_on_quota,
+            usage: CredentialUsage::default(),
+            trajectory: None,
+        }
+    }
+
+    /// Log the profile serving each request to the trajectory log.
+    pub fn with_trajectory(mut self, trajectory: TrajectoryLogger) -> Self {
+        self.trajectory = Some(trajectory);
+        self
+    }
+
+    pub fn usage(&self) -> CredentialUsage {
+        self.usage.clone()
+    }
+
+    /// Name of the profile new requests are sent with.
+    pub fn active_profile(&self) -> &str {
+        &self.profiles[self.current_index()].0
+    }
+
+    fn current_index(&self) -> usize {
+        self.current.load(Ordering::Relaxed) % self.profiles.len()
+    }
+
+    fn active(&self) -> &dyn LLMProvider {
+        self.profiles[self.current_index()].1.as_ref()
+    }
+
+    fn attempts(&self) -> usize {
+        if self.rotate_on_quota {
+            self.profiles.len()
+        } else {
+            1
+        }
+    }
+
+    /// Make `to` the active profile after `from` ran out of quota.
+    fn rotate(&self, from: usize, to: usize, rotated_from: &mut Vec<String>) {
+        let event = RotationEvent {
+            from: self.profiles[from].0.clone(),
+            to: self.profiles[to].0.clone(),
+        };
+        self.current.store(to, Ordering::Relaxed);
+        rotated_from.push(event.from.clone());
+        self.usage.inner.lock().rotations.push(event);
+    }
+
+    fn record_served(&self, index: usize, rotated_from: &[String]) {
+        let profile = &self.profiles[index].0;
+        self.usage.inner.lock().last_served = Some(profile.clone());
+        if let Some(trajectory) = &self.trajectory {
+            trajectory.log_credential(&self.name, profile, rotated_from);
+        }
+    }
+}
+
+#[async_trait]
+impl LLMProvider for RotatingProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn supports_streaming(&self) -> bool {
+        self.active().supports_streaming()
+    }
+
+    fn supports_reasoning(&self, model: &str) -> bool {
+        self.active().supports_reasoning(model)
+    }
+
+    fn supports_reasoning_effort(&self, model: &str) -> bool {
+        self.active().supports_reasoning_effort(model)
+    }
+
+    fn supports_json_mode(&self, model: &str) -> bool {
+        self.active().supports_json_mode(model)
+    }
+
+    fn supports_response_schema(&self, model: &str) -> bool {
+        self.active().supports_response_schema(model)
+    }
+
+    fn supports_prompt_caching(&self, model: &str) -> bool {
+        self.active().supports_prompt_caching(model)
+    }
+
+    fn supports_code_execution(&self, model: &str) -> bool {
+        self.active().supports_code_execution(model)
+    }
+
+    fn supports_seed(&self, model: &str) -> bool {
+        self.active().supports_seed(model)
+    }
+
+    async fn generate(&self, request: LLMRequest) -> Result<LLMResponse, LLMError> {
+        let start = self.current_index();
+        let attempts = self.attempts();
+        let mut rotated_from = Vec::new();
+        for offset in 0..attempts {
+            let index = (start + offset) % self.profiles.len();
+            match self.profiles[index].1.generate(request.clone()).await {
+                Ok(response) => {
+                    self.record_served(index, &rotated_from);
+                    return Ok(response);
+                }
+                Err(LLMError::RateLimit) if offset + 1 < attempts => {
+                    self.rotate(index, (index + 1) % self.profiles.len(), &mut rotated_from);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Err(LLMError::RateLimit)
+    }
+
+    async fn stream(&self, request: LLMRequest) -> Result<LLMStream, LLMError> {
+        // Quota errors surface before the first event, so only the initial call is retried.
+        let start = self.current_index();
+        let attempts = self.attempts();
+        let mut rotated_from = Vec::new();
+        for offset in 0..attempts {
+            let index = (start + offset) % self.profiles.len();
+            match self.profiles[index].1.stream(request.clone()).await {
+                Ok(stream) => {
+                    self.record_served(index, &rotated_from);
+                    return Ok(stream);
+                }
+                Err(LLMError::RateLimit) if offset + 1 < attempts => {
+                    self.rotate(index, (index + 1) % self.profiles.len(), &mut rotated_from);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Err(LLMError::RateLimit)
+    }
+
+    fn supported_models(&self) -> Vec<String> {
+        self.active().supported_models()
+    }
+
+    fn validate_request(&self, request: &LLMRequest) -> Result<(), LLMError> {
+        self.active().validate_request(request)
+    }
+
+    fn release_connections(&self) {
+        for (_, provider) in &self.profiles {
+            provider.release_connections();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::provider::{FinishReason, Message};
+    use std::sync::atomic::AtomicBool;
+
+    struct KeyProvider {
+        key: &'static str,
+        exhausted: Arc<AtomicBool>,
+    }
+
+    #[async_trait]
+    impl LLMProvider for KeyProvider {
+        fn name(&self) -> &str {
+            "openai"
+        }
+
+        async fn generate(&self, _request: LLMRequest) -> Result<LLMResponse, LLMError> {
+            if self.exhausted.load(Ordering::Relaxed) {
+                return Err(LLMError::RateLimit);
+            }
+            Ok(LLMResponse {
+                content: Some(self.key.to_string()),
+                tool_calls: None,
+                usage: None,
+                finish_reason: FinishReason::Stop,
+                reasoning: None,
+                code_execution: Vec::new(),
+            })
+        }
+
+        fn supported_models(&self) -> Vec<String> {
+            vec!["gpt-5".to_string()]
+        }
+
+        fn validate_request(&self, _request: &LLMRequest) -> Result<(), LLMError> {
+            Ok(())
+        }
+    }
+
+    fn profile(key: &'static str, exhausted: &Arc<AtomicBool>) -> (String, Box<dyn LLMProvider>) {
+        (
+            key.to_string(),
+            Box::new(KeyProvider {
+                key,
+                exhausted: exhausted.clone(),
+            }),
+        )
+    }
+
+    fn request() -> LLMRequest {
+        LLMRequest {
+            messages: vec![Message::user("hi".to_string())],
+            system_prompt: None,
+            tools: None,
+            model: "gpt-5".to_string(),
+            max_tokens: None,
+            temperature: None,
+            stream: false,
+            tool_choice: None,
+            parallel_tool_calls: None,
+            parallel_tool_config: None,
+            reasoning_effort: None,
+            json_mode: false,
+            response_schema: None,
+            code_execution: false,
+            seed: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn rotates_to_next_profile_on_quota_exhaustion() {
+        let team_a = Arc::new(AtomicBool::new(true));
+        let team_b = Arc::new(AtomicBool::new(false));
+        let provider = RotatingProvider::new(
+            "openai",
+            vec![profile("team-a", &team_a), profile("team-b", &team_b)],
+            true,
+        );
+        let usage = provider.usage();
+
+        let response = provider.generate(request()).await.expect("rotated");
+        assert_eq!(response.content.as_deref(), Some("team-b"));
+        assert_eq!(provider.active_profile(), "team-b");
+        assert_eq!(usage.last_served().as_deref(), Some("team-b"));
+        assert_eq!(
+            usage.take_rotations(),
+            vec![RotationEvent {
+                from: "team-a".to_string(),
+                to: "team-b".to_string(),
+            }]
+        );
+        assert!(usage.take_rotations().is_empty());
+
+        team_b.store(true, Ordering::Relaxed);
+        assert!(matches!(
+            provider.generate(request()).await,
+            Err(LLMError::RateLimit)
+        ));
+    }
+
+    #[tokio::test]
+    async fn keeps_profile_when_rotation_is_disabled() {
+        let team_a = Arc::new(AtomicBool::new(true));
+        let team_b = Arc::new(AtomicBool::new(false));
+        let provider = RotatingProvider::new(
+            "openai",
+            vec![profile("team-a", &team_a), profile("team-b", &team_b)],
+            false,
+        );
+
+        assert!(matches!(
+            provider.generate(request()).await,
+            Err(LLMError::RateLimit)
+        ));
+        assert_eq!(provider.active_profile(), "team-a");
+        assert!(provider.usage().take_rotations().is_empty());
+    }
+}
@@ -0,0 +1,336 @@
+//! Server-sent events decoding shared by the streaming providers
+//!
+//! Long generations often pass through proxies that split frames at arbitrary
+//! byte offsets, send `: keep-alive` comments or bare `retry:` hints, and end
+//! a stream with an `{"error": ...}` frame instead of an HTTP error. This
+//! module decodes the byte stream incrementally, as the SSE specification
+//! describes, and recognizes those error frames so providers can report them
+//! instead of silently truncating the answer.
+
+use crate::llm::error_display;
+use crate::llm::provider::LLMError;
+use serde_json::Value;
+use std::time::Duration;
+
+/// One dispatched server-sent event.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SseEvent {
+    /// The `event:` name, when the server sent one.
+    pub event: Option<String>,
+    /// The `data:` lines of the event joined with `\n`.
+    pub data: String,
+    /// The `id:` of the event, when the server sent one.
+    pub id: Option<String>,
+}
+
+impl SseEvent {
+    /// OpenAI-style end of stream marker.
+    pub fn is_done(&self) -> bool {
+        self.data.trim() == "[DONE]"
+    }
+}
+
+/// Incremental decoder for a `text/event-stream` body.
+///
+/// Accepts `\n`, `\r\n` and `\r` line endings, even when a chunk ends between
+/// the `\r` and the `\n`, and only decodes complete lines as UTF-8 so a
+/// multibyte character split across chunks is kept intact. Comment lines are
+/// ignored; they are how servers and proxies send heartbeats.
+#[derive(Debug, Default)]
+pub struct SseDecoder {
+    pending: Vec<u8>,
+    skip_line_feed: bool,
+    event: Option<String>,
+    data: Vec<String>,
+    id: Option<String>,
+    last_event_id: Option<String>,
+    retry: Option<Duration>,
+}
+
+impl SseDecoder {
+    /// Feed the next chunk of the body and return the events it completed.
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        let mut events = Vec::new();
+        for &byte in chunk {
+            if std::mem::take(&mut self.skip_line_feed) && byte == b'\n' {
+                continue;
+            }
+            match byte {
+                b'\n' | b'\r' => {
+                    self.skip_line_feed = byte == b'\r';
+                    let line = std::mem::take(&mut self.pending);
+                    if let Some(event) = self.process_line(&String::from_utf8_lossy(&line)) {
+                        events.push(event);
+                    }
+                }
+                _ => self.pending.push(byte),
+            }
+        }
+        events
+    }
+
+    /// Flush the last event of a body that did not end with a blank line.
+    pub fn finish(&mut self) -> Option<SseEvent> {
+        if !self.pending.is_empty() {
+            let line = std::mem::take(&mut self.pending);
+            if let Some(event) = self.process_line(&String::from_utf8_lossy(&line)) {
+                return Some(event);
+            }
+        }
+        self.dispatch()
+    }
+
+    /// Id of the last event, to send as `Last-Event-ID` when reconnecting.
+    pub fn last_event_id(&self) -> Option<&str> {
+        self.last_event_id.as_deref()
+    }
+
+    /// Reconnection delay requested by the server with a `retry:` line.
+    pub fn retry(&self) -> Option<Duration> {
+        self.retry
+    }
+
+    fn process_line(&mut self, line: &str) -> Option<SseEvent> {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "data" => self.data.push(value.to_string()),
+            "event" => self.event = Some(value.to_string()),
+            "id" if !value.contains('\0') => self.id = Some(value.to_string()),
+            "retry" => {
+                if let Ok(millis) = value.trim().parse::<u64>() {
+                    self.retry = Some(Duration::from_millis(millis));
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<SseEvent> {
+        let id = self.id.take();
+        if id.is_some() {
+            self.last_event_id.clone_from(&id);
+        }
+        let event = self.event.take();
+        if self.data.is_empty() {
+            return None;
+        }
+        Some(SseEvent {
+            event,
+            data: std::mem::take(&mut self.data).join("\n"),
+            id,
+        })
+    }
+}
+
+/// Decode the complete UTF-8 prefix of `pending`, leaving an incomplete
+/// trailing character for the next chunk. Invalid bytes become U+FFFD.
+pub fn drain_utf8(pending: &mut Vec<u8>) -> String {
+    let valid = match std::str::from_utf8(pending) {
+        Ok(_) => pending.len(),
+        Err(err) if err.error_len().is_none() => err.valid_up_to(),
+        Err(_) => pending.len(),
+    };
+    let text = String::from_utf8_lossy(&pending[..valid]).into_owned();
+    pending.drain(..valid);
+    text
+}
+
+/// An error reported inside an otherwise successful stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamErrorFrame {
+    /// Error type, e.g. `overloaded_error` or `server_error`.
+    pub kind: Option<String>,
+    /// HTTP-like status or provider code, e.g. `429` or `RESOURCE_EXHAUSTED`.
+    pub code: Option<String>,
+    pub message: String,
+    /// Whether reopening the request can be expected to succeed.
+    pub retryable: bool,
+}
+
+impl StreamErrorFrame {
+    /// Recognize `event: error`, `{"type": "error", ...}` and `{"error": ...}`
+    /// frames. Returns `None` for ordinary payloads.
+    pub fn detect(event: Option<&str>, payload: &Value) -> Option<Self> {
+        let error = match payload.get("error") {
+            Some(error) if !error.is_null() => error,
+            _ if event == Some("error")
+                || payload.get("type").and_then(Value::as_str) == Some("error") =>
+            {
+                payload
+            }
+            _ => return None,
+        };
+        let text = |value: &Value| match value {
+            Value::String(text) => Some(text.clone()),
+            Value::Number(number) => Some(number.to_string()),
+            _ => None,
+        };
+        let message = match error {
+            Value::String(message) => message.clone(),
+            _ => error
+                .get("message")
+                .and_then(Value::as_str)
+                .unwrap_or("stream ended with an error")
+                .to_string(),
+        };
+        let kind = error
+            .get("type")
+            .and_then(Value::as_str)
+            .filter(|kind| *kind != "error")
+            .map(str::to_string);
+        let code = error
+            .get("code")
+            .or_else(|| error.get("status"))
+            .and_then(text);
+        let retryable = [kind.as_deref(), code.as_deref()]
+            .into_iter()
+            .flatten()
+            .any(is_transient);
+        Some(Self {
+            kind,
+            code,
+            message,
+            retryable,
+        })
+    }
+
+    pub fn is_rate_limit(&self) -> bool {
+        [self.kind.as_deref(), self.code.as_deref()]
+            .into_iter()
+            .flatten()
+            .any(|value| {
+                matches!(
+                    value,
+                    "429" | "rate_limit_error" | "rate_limit_exceeded" | "RESOURCE_EXHAUSTED"
+                )
+            })
+    }
+
+    pub fn into_llm_error(self, provider: &str) -> LLMError {
+        if self.is_rate_limit() {
+            return LLMError::RateLimit;
+        }
+        let detail = match (self.kind.as_deref(), self.code.as_deref()) {
+            (Some(kind), Some(code)) => format!(" ({}, code {})", kind, code),
+            (Some(kind), None) => format!(" ({})", kind),
+            (None, Some(code)) => format!(" (code {})", code),
+            (None, None) => String::new(),
+        };
+        LLMError::Provider(error_display::format_llm_error(
+            provider,
+            &format!("Stream error{}: {}", detail, self.message),
+        ))
+    }
+}
+
+fn is_transient(value: &str) -> bool {
+    matches!(
+        value,
+        "500"
+            | "502"
+            | "503"
+            | "504"
+            | "529"
+            | "overloaded_error"
+            | "server_error"
+            | "api_error"
+            | "timeout"
+            | "UNAVAILABLE"
+            | "INTERNAL"
+            | "DEADLINE_EXCEEDED"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn decodes_split_frames_with_heartbeats() {
+        let mut decoder = SseDecoder::default();
+        let body = ": keep-alive\r\n\r\nretry: 1500\nid: 7\r\nevent: message\rdata: {\"text\":\"héllo\"}\r\n\r\n:\n\ndata: [DONE]\n\n"
+            .as_bytes();
+        let mut events = Vec::new();
+        // Byte-by-byte splits every CRLF pair and the two-byte `é`.
+        for byte in body {
+            events.extend(decoder.push(std::slice::from_ref(byte)));
+        }
+        assert_eq!(
+            events,
+            vec![
+                SseEvent {
+                    event: Some("message".to_string()),
+                    data: "{\"text\":\"héllo\"}".to_string(),
+                    id: Some("7".to_string()),
+                },
+                SseEvent {
+                    data: "[DONE]".to_string(),
+                    ..SseEvent::default()
+                },
+            ]
+        );
+        assert!(events[1].is_done());
+        assert_eq!(decoder.last_event_id(), Some("7"));
+        assert_eq!(decoder.retry(), Some(Duration::from_millis(1500)));
+
+        let mut decoder = SseDecoder::default();
+        assert!(
+            decoder
+                .push(b": ping\ndata: {\"a\":1}\ndata: {\"b\":2}")
+                .is_empty()
+        );
+        assert_eq!(decoder.finish().unwrap().data, "{\"a\":1}\n{\"b\":2}");
+
+        let mut pending = "日本".as_bytes().to_vec();
+        pending.truncate(4);
+        assert_eq!(drain_utf8(&mut pending), "日");
+        assert_eq!(pending.len(), 1);
+    }
+
+    #[test]
+    fn detects_error_frames() {
+        assert!(StreamErrorFrame::detect(None, &json!({"choices": []})).is_none());
+        assert!(StreamErrorFrame::detect(None, &json!({"error": null})).is_none());
+
+        let frame = StreamErrorFrame::detect(
+            None,
+            &json!({"error": {"code": 502, "message": "Upstream closed"}}),
+        )
+        .unwrap();
+        assert_eq!(frame.code.as_deref(), Some("502"));
+        assert!(frame.retryable);
+        let message = frame.into_llm_error("OpenRouter").to_string();
+        assert!(message.contains("code 502"), "{message}");
+        assert!(message.contains("Upstream closed"), "{message}");
+
+        let frame = StreamErrorFrame::detect(
+            Some("error"),
+            &json!({"type": "error", "error": {"type": "overloaded_error", "message": "Overloaded"}}),
+        )
+        .unwrap();
+        assert_eq!(frame.kind.as_deref(), Some("overloaded_error"));
+        assert!(frame.retryable);
+
+        let frame = StreamErrorFrame::detect(Some("error"), &json!({"message": "bad tool schema"}))
+            .unwrap();
+        assert!(!frame.retryable);
+        let limited =
+            StreamErrorFrame::detect(None, &json!({"error": {"status": "RESOURCE_EXHAUSTED"}}))
+                .unwrap();
+        assert!(matches!(
+            limited.into_llm_error("Gemini"),
+            LLMError::RateLimit
+        ));
+    }
+}
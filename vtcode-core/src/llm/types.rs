@@ -8,6 +8,7 @@ pub enum BackendKind {
     Anthropic,
     OpenRouter,
     XAI,
+    Ollama,
 }
 
 /// Unified LLM response structure
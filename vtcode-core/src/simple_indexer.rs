@@ -4,6 +4,8 @@
 //! using regex patterns and markdown files for storage. No complex embeddings
 //! or databases - just direct file operations like a human using bash.
 
+use crate::core::sensitive_files::is_read_denied;
+use crate::utils::index_exclude;
 use anyhow::Result;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
@@ -255,10 +257,17 @@ impl SimpleIndexer {
             return Ok(());
         }
 
+        let exclusions = index_exclude::current();
         for entry in fs::read_dir(dir_path)? {
             let entry = entry?;
             let path = entry.path();
 
+            if exclusions.is_excluded(&path, path.is_dir())
+                || is_read_denied(&self.workspace_root, &path)
+            {
+                continue;
+            }
+
             if path.is_dir() {
                 // Skip common directories
                 if let Some(name) = path.file_name() {
@@ -3,6 +3,8 @@
 //! This module provides functionality to parse and apply patches in the format
 //! used by OpenAI Codex, which is designed to be easy to parse and safe to apply.
 
+use crate::tools::write_quota::WriteQuota;
+use crate::utils::workspace_path::resolve_in_workspace;
 use anyhow::{Context, Result, anyhow};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
@@ -47,6 +49,7 @@ pub struct Patch {
 
 /// Input structure for the apply_patch tool
 #[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 pub struct ApplyPatchInput {
     pub input: String,
 }
@@ -124,11 +127,11 @@ impl Patch {
                         }
 
                         // Start new hunk
-                        let header = if next_line.len() > 2 {
-                            Some(next_line[3..].trim().to_string())
-                        } else {
-                            None
-                        };
+                        let header = next_line
+                            .get(2..)
+                            .map(str::trim)
+                            .filter(|header| !header.is_empty())
+                            .map(str::to_string);
                         current_hunk = Some(PatchHunk {
                             header,
                             lines: Vec::new(),
@@ -139,13 +142,13 @@ impl Patch {
                         break;
                     } else if let Some(ref mut hunk) = current_hunk {
                         // Add line to current hunk
-                        let line_content = if next_line.len() > 1 {
-                            next_line[1..].to_string()
-                        } else {
-                            String::new()
-                        };
+                        // The marker may be followed by multi-byte text, so split
+                        // on the first character rather than the first byte.
+                        let mut chars = next_line.chars();
+                        let marker = chars.next();
+                        let line_content = chars.as_str().to_string();
 
-                        let patch_line = match next_line.chars().next() {
+                        let patch_line = match marker {
                             Some(' ') => PatchLine::Context(line_content),
                             Some('-') => PatchLine::Remove(line_content),
                             Some('+') => PatchLine::Add(line_content),
@@ -176,14 +179,16 @@ impl Patch {
         Ok(Patch { operations })
     }
 
-    /// Apply the patch to the file system
-    pub async fn apply(&self, root: &Path) -> Result<Vec<String>> {
+    /// Apply the patch to the file system, reserving each write against
+    /// `quota` before it is made
+    pub async fn apply(&self, root: &Path, quota: &WriteQuota) -> Result<Vec<String>> {
         let mut results = Vec::new();
 
         for operation in &self.operations {
             match operation {
                 PatchOperation::AddFile { path, content } => {
-                    let full_path = root.join(path);
+                    let full_path = resolve_in_workspace(root, path)?;
+                    quota.reserve(path, content.len() as u64, !full_path.exists())?;
                     if let Some(parent) = full_path.parent() {
                         tokio::fs::create_dir_all(parent).await.context(format!(
                             "failed to create parent directories: {}",
@@ -196,7 +201,7 @@ impl Patch {
                     results.push(format!("Added file: {}", path));
                 }
                 PatchOperation::DeleteFile { path } => {
-                    let full_path = root.join(path);
+                    let full_path = resolve_in_workspace(root, path)?;
                     if full_path.exists() {
                         if full_path.is_dir() {
                             tokio::fs::remove_dir_all(&full_path)
@@ -221,7 +226,7 @@ impl Patch {
                     new_path,
                     hunks,
                 } => {
-                    let full_path = root.join(path);
+                    let full_path = resolve_in_workspace(root, path)?;
 
                     // Read existing content
                     let existing_content = if full_path.exists() {
@@ -234,10 +239,14 @@ impl Patch {
 
                     // Apply hunks to content
                     let new_content = Self::apply_hunks_to_content(&existing_content, hunks)?;
+                    let written_path = new_path.as_deref().unwrap_or(path);
+                    let creates =
+                        new_path.is_some() && !resolve_in_workspace(root, written_path)?.exists();
+                    quota.reserve(written_path, new_content.len() as u64, creates)?;
 
                     // Write updated content
                     let target_path = if let Some(new_path_str) = new_path {
-                        let new_full_path = root.join(new_path_str);
+                        let new_full_path = resolve_in_workspace(root, new_path_str)?;
                         if let Some(parent) = new_full_path.parent() {
                             tokio::fs::create_dir_all(parent).await.context(format!(
                                 "failed to create parent directories: {}",
@@ -273,7 +282,7 @@ impl Patch {
     }
 
     /// Apply hunks to content
-    fn apply_hunks_to_content(content: &str, hunks: &[PatchHunk]) -> Result<String> {
+    pub fn apply_hunks_to_content(content: &str, hunks: &[PatchHunk]) -> Result<String> {
         let original_lines: Vec<&str> = content.lines().collect();
         let ends_with_newline = content.ends_with('\n');
         let mut lines: Vec<String> = original_lines.into_iter().map(|s| s.to_string()).collect();
@@ -331,9 +340,11 @@ impl Patch {
                         }
                     }
                     PatchLine::Add(text) => {
-                        // Add the line at the current position
-                        lines.insert(i, text.clone());
-                        i += 1;
+                        // Add the line at the current position; mismatched
+                        // context may have moved past the end of the file
+                        let at = i.min(lines.len());
+                        lines.insert(at, text.clone());
+                        i = at + 1;
                     }
                 }
             }
@@ -352,6 +363,8 @@ impl Patch {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tools::write_quota::QuotaExceeded;
+    use proptest::prelude::*;
     use tempfile::TempDir;
 
     #[test]
@@ -386,7 +399,7 @@ mod tests {
 *** End Patch"#;
 
         let patch = Patch::parse(patch_str)?;
-        let results = patch.apply(&workspace).await?;
+        let results = patch.apply(&workspace, &WriteQuota::default()).await?;
 
         assert_eq!(results.len(), 1);
         assert!(results[0].contains("Added file: hello.txt"));
@@ -414,7 +427,7 @@ mod tests {
 *** End Patch"#;
 
         let patch = Patch::parse(patch_str)?;
-        let results = patch.apply(&workspace).await?;
+        let results = patch.apply(&workspace, &WriteQuota::default()).await?;
 
         assert_eq!(results.len(), 1);
         assert!(results[0].contains("Deleted file: to_delete.txt"));
@@ -422,4 +435,123 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_apply_rejects_paths_outside_workspace() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let workspace = temp_dir.path().join("workspace");
+        tokio::fs::create_dir_all(&workspace).await?;
+
+        let patch =
+            Patch::parse("*** Begin Patch\n*** Add File: ../escaped.txt\n+nope\n*** End Patch")?;
+        assert!(
+            patch
+                .apply(&workspace, &WriteQuota::default())
+                .await
+                .is_err()
+        );
+        assert!(!temp_dir.path().join("escaped.txt").exists());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_apply_refuses_additions_over_the_write_quota() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let workspace = temp_dir.path().to_path_buf();
+        let quota = WriteQuota::new(crate::config::WriteQuotaConfig {
+            max_write_bytes: 8,
+            ..Default::default()
+        });
+
+        let patch =
+            Patch::parse("*** Begin Patch\n*** Add File: big.log\n+0123456789\n*** End Patch")?;
+        let err = patch.apply(&workspace, &quota).await.unwrap_err();
+        assert!(err.downcast_ref::<QuotaExceeded>().is_some());
+        assert!(!workspace.join("big.log").exists());
+        Ok(())
+    }
+
+    fn patch_line() -> impl Strategy<Value = String> {
+        let prefix = prop_oneof![
+            Just("*** Begin Patch"),
+            Just("*** End Patch"),
+            Just("*** Add File: "),
+            Just("*** Delete File: "),
+            Just("*** Update File: "),
+            Just("*** Move to: "),
+            Just("*** End of File"),
+            Just("@@"),
+            Just("@@ "),
+            Just("+"),
+            Just("-"),
+            Just(" "),
+            Just(""),
+        ];
+        (prefix, any::<String>()).prop_map(|(prefix, rest)| format!("{prefix}{rest}"))
+    }
+
+    fn hunk_line() -> impl Strategy<Value = PatchLine> {
+        let text = "[ab]{0,2}|[é日]";
+        prop_oneof![
+            text.prop_map(PatchLine::Context),
+            text.prop_map(PatchLine::Remove),
+            text.prop_map(PatchLine::Add),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn parse_never_panics(lines in prop::collection::vec(patch_line(), 0..24)) {
+            let _ = Patch::parse(&lines.join("\n"));
+        }
+
+        #[test]
+        fn apply_never_panics(
+            content in prop::collection::vec("[ab]{0,2}|[é日]", 0..8),
+            hunks in prop::collection::vec(prop::collection::vec(hunk_line(), 0..8), 0..4),
+        ) {
+            let hunks: Vec<PatchHunk> = hunks
+                .into_iter()
+                .map(|lines| PatchHunk { header: None, lines })
+                .collect();
+            let _ = Patch::apply_hunks_to_content(&content.join("\n"), &hunks);
+        }
+
+        #[test]
+        fn added_file_round_trips(body in prop::collection::vec("[^\r\n]*", 1..8)) {
+            let input = format!(
+                "*** Begin Patch\n*** Add File: f.txt\n{}\n*** End Patch",
+                body.iter().map(|line| format!("+{line}")).collect::<Vec<_>>().join("\n")
+            );
+            let patch = Patch::parse(&input).unwrap();
+            let expected = body.join("\n");
+            prop_assert_eq!(
+                &patch.operations[..],
+                &[PatchOperation::AddFile { path: "f.txt".to_string(), content: expected }]
+            );
+        }
+
+        #[test]
+        fn replacing_a_unique_line(len in 1usize..12, index in any::<prop::sample::Index>()) {
+            let original: Vec<String> = (0..len).map(|n| format!("line {n}")).collect();
+            let target = index.index(len);
+            let mut lines = Vec::new();
+            if target > 0 {
+                lines.push(PatchLine::Context(original[target - 1].clone()));
+            }
+            lines.push(PatchLine::Remove(original[target].clone()));
+            lines.push(PatchLine::Add("replaced".to_string()));
+
+            let content = format!("{}\n", original.join("\n"));
+            let updated = Patch::apply_hunks_to_content(
+                &content,
+                &[PatchHunk { header: None, lines }],
+            )
+            .unwrap();
+
+            let mut expected = original.clone();
+            expected[target] = "replaced".to_string();
+            prop_assert_eq!(updated, format!("{}\n", expected.join("\n")));
+        }
+    }
 }
@@ -7,6 +7,9 @@ use anyhow::{Context, Result};
 use serde_json::{Value, json};
 use std::collections::HashMap;
 use tokio;
+use tokio_util::sync::CancellationToken;
+
+use crate::tools::cancellation::blocking_command_output;
 
 /// AST-grep engine for syntax-aware code operations
 pub struct AstGrepEngine {
@@ -76,6 +79,7 @@ impl AstGrepEngine {
         language: Option<&str>,
         context_lines: Option<usize>,
         max_results: Option<usize>,
+        cancel: &CancellationToken,
     ) -> Result<Value> {
         let sgrep_path = self.sgrep_path.clone();
         let pattern = pattern.to_string();
@@ -84,6 +88,7 @@ impl AstGrepEngine {
         let _context_lines = context_lines.unwrap_or(0);
         let _max_results = max_results.unwrap_or(100);
 
+        let cancel = cancel.clone();
         let handle = tokio::task::spawn_blocking(move || {
             let mut cmd = std::process::Command::new(&sgrep_path);
             cmd.arg("run")
@@ -98,7 +103,7 @@ impl AstGrepEngine {
                 cmd.arg("--lang").arg(Self::map_language(&lang));
             }
 
-            cmd.output()
+            blocking_command_output(&mut cmd, &cancel)
         });
 
         let output = handle
@@ -119,6 +124,7 @@ impl AstGrepEngine {
     }
 
     /// Transform code using AST-grep patterns
+    #[allow(clippy::too_many_arguments)]
     pub async fn transform(
         &self,
         pattern: &str,
@@ -127,6 +133,7 @@ impl AstGrepEngine {
         language: Option<&str>,
         preview_only: bool,
         update_all: bool,
+        cancel: &CancellationToken,
     ) -> Result<Value> {
         let sgrep_path = self.sgrep_path.clone();
         let pattern = pattern.to_string();
@@ -134,6 +141,7 @@ impl AstGrepEngine {
         let path = path.to_string();
         let language = language.map(|s| s.to_string());
 
+        let cancel = cancel.clone();
         let handle = tokio::task::spawn_blocking(move || {
             let mut cmd = std::process::Command::new(&sgrep_path);
             cmd.arg("run")
@@ -154,7 +162,7 @@ impl AstGrepEngine {
                 cmd.arg("--update-all");
             }
 
-            cmd.output()
+            blocking_command_output(&mut cmd, &cancel)
         });
 
         let output = handle
@@ -181,6 +189,7 @@ impl AstGrepEngine {
         language: Option<&str>,
         severity_filter: Option<&str>,
         custom_rules: Option<Vec<HashMap<String, Value>>>,
+        cancel: &CancellationToken,
     ) -> Result<Value> {
         let sgrep_path = self.sgrep_path.clone();
         let path = path.to_string();
@@ -188,6 +197,7 @@ impl AstGrepEngine {
         let _severity_filter = severity_filter.map(|s| s.to_string());
         let _custom_rules = custom_rules.clone();
 
+        let cancel = cancel.clone();
         let handle = tokio::task::spawn_blocking(move || {
             let mut cmd = std::process::Command::new(&sgrep_path);
             cmd.arg("run")
@@ -200,7 +210,7 @@ impl AstGrepEngine {
                 cmd.arg("--lang").arg(Self::map_language(&lang));
             }
 
-            cmd.output()
+            blocking_command_output(&mut cmd, &cancel)
         });
 
         let output = handle
@@ -240,6 +250,7 @@ impl AstGrepEngine {
         path: &str,
         language: Option<&str>,
         refactor_type: &str,
+        cancel: &CancellationToken,
     ) -> Result<Value> {
         // Different refactoring suggestions based on type
         let (pattern, replacement) = match refactor_type {
@@ -263,6 +274,7 @@ impl AstGrepEngine {
         let pattern = pattern.to_string();
         let replacement = replacement.to_string();
 
+        let cancel = cancel.clone();
         let handle = tokio::task::spawn_blocking(move || {
             let mut cmd = std::process::Command::new(&sgrep_path);
             cmd.arg("run")
@@ -277,7 +289,7 @@ impl AstGrepEngine {
                 cmd.arg("--lang").arg(Self::map_language(&lang));
             }
 
-            cmd.output()
+            blocking_command_output(&mut cmd, &cancel)
         });
 
         let output = handle
@@ -298,6 +310,7 @@ impl AstGrepEngine {
     }
 
     /// Run a custom ast-grep command with full options
+    #[allow(clippy::too_many_arguments)]
     pub async fn run_custom(
         &self,
         pattern: &str,
@@ -308,6 +321,7 @@ impl AstGrepEngine {
         max_results: Option<usize>,
         interactive: bool,
         update_all: bool,
+        cancel: &CancellationToken,
     ) -> Result<Value> {
         let sgrep_path = self.sgrep_path.clone();
         let pattern = pattern.to_string();
@@ -317,6 +331,7 @@ impl AstGrepEngine {
         let context_lines = context_lines.unwrap_or(0);
         let max_results = max_results.unwrap_or(100);
 
+        let cancel = cancel.clone();
         let handle = tokio::task::spawn_blocking(move || {
             let mut cmd = std::process::Command::new(&sgrep_path);
             cmd.arg("run")
@@ -343,7 +358,7 @@ impl AstGrepEngine {
                 cmd.arg("--update-all");
             }
 
-            cmd.output()
+            blocking_command_output(&mut cmd, &cancel)
         });
 
         let output = handle
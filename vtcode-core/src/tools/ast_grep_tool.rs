@@ -4,13 +4,16 @@
 //! allowing it to be used as a standard agent tool.
 
 use super::ast_grep::AstGrepEngine;
+use super::cancellation::ToolContext;
 use super::traits::Tool;
 use crate::config::constants::tools;
+use crate::core::sensitive_files::is_read_denied;
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use serde_json::Value;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
 
 /// AST-grep tool that provides syntax-aware code search and transformation
 pub struct AstGrepTool {
@@ -62,17 +65,22 @@ impl AstGrepTool {
 #[async_trait]
 impl Tool for AstGrepTool {
     async fn execute(&self, args: Value) -> Result<Value> {
+        self.execute_with_context(args, &ToolContext::default())
+            .await
+    }
+
+    async fn execute_with_context(&self, args: Value, context: &ToolContext) -> Result<Value> {
         let operation = args
             .get("operation")
             .and_then(|v| v.as_str())
             .unwrap_or("search");
 
         match operation {
-            "search" => self.search(args).await,
-            "transform" => self.transform(args).await,
-            "lint" => self.lint(args).await,
-            "refactor" => self.refactor(args).await,
-            "custom" => self.custom(args).await,
+            "search" => self.search(args, &context.cancel).await,
+            "transform" => self.transform(args, &context.cancel).await,
+            "lint" => self.lint(args, &context.cancel).await,
+            "refactor" => self.refactor(args, &context.cancel).await,
+            "custom" => self.custom(args, &context.cancel).await,
             _ => Err(anyhow::anyhow!("Unknown AST-grep operation: {}", operation)),
         }
     }
@@ -135,7 +143,7 @@ impl Tool for AstGrepTool {
 
 impl AstGrepTool {
     /// Execute search operation
-    async fn search(&self, args: Value) -> Result<Value> {
+    async fn search(&self, args: Value, cancel: &CancellationToken) -> Result<Value> {
         let pattern = args
             .get("pattern")
             .and_then(|v| v.as_str())
@@ -158,13 +166,22 @@ impl AstGrepTool {
             .and_then(|v| v.as_u64())
             .map(|v| v as usize);
 
-        self.engine
-            .search(pattern, &path, language, context_lines, max_results)
-            .await
+        let mut result = self
+            .engine
+            .search(pattern, &path, language, context_lines, max_results, cancel)
+            .await?;
+        if let Some(matches) = result.get_mut("matches").and_then(Value::as_array_mut) {
+            matches.retain(|item| {
+                item.get("file")
+                    .and_then(Value::as_str)
+                    .is_none_or(|file| !is_read_denied(&self.workspace_root, Path::new(file)))
+            });
+        }
+        Ok(result)
     }
 
     /// Execute transform operation
-    async fn transform(&self, args: Value) -> Result<Value> {
+    async fn transform(&self, args: Value, cancel: &CancellationToken) -> Result<Value> {
         let pattern = args
             .get("pattern")
             .and_then(|v| v.as_str())
@@ -200,12 +217,13 @@ impl AstGrepTool {
                 language,
                 preview_only,
                 update_all,
+                cancel,
             )
             .await
     }
 
     /// Execute lint operation
-    async fn lint(&self, args: Value) -> Result<Value> {
+    async fn lint(&self, args: Value, cancel: &CancellationToken) -> Result<Value> {
         let path = args
             .get("path")
             .and_then(|v| v.as_str())
@@ -217,12 +235,12 @@ impl AstGrepTool {
         let severity_filter = args.get("severity_filter").and_then(|v| v.as_str());
 
         self.engine
-            .lint(&path, language, severity_filter, None)
+            .lint(&path, language, severity_filter, None, cancel)
             .await
     }
 
     /// Execute refactor operation
-    async fn refactor(&self, args: Value) -> Result<Value> {
+    async fn refactor(&self, args: Value, cancel: &CancellationToken) -> Result<Value> {
         let path = args
             .get("path")
             .and_then(|v| v.as_str())
@@ -236,11 +254,13 @@ impl AstGrepTool {
             .and_then(|v| v.as_str())
             .context("'refactor_type' is required")?;
 
-        self.engine.refactor(&path, language, refactor_type).await
+        self.engine
+            .refactor(&path, language, refactor_type, cancel)
+            .await
     }
 
     /// Execute custom operation
-    async fn custom(&self, args: Value) -> Result<Value> {
+    async fn custom(&self, args: Value, cancel: &CancellationToken) -> Result<Value> {
         let pattern = args
             .get("pattern")
             .and_then(|v| v.as_str())
@@ -282,6 +302,7 @@ impl AstGrepTool {
                 max_results,
                 interactive,
                 update_all,
+                cancel,
             )
             .await
     }
@@ -4,23 +4,33 @@
 //! commands and tools that require a shell environment.
 
 use super::traits::Tool;
+use super::workspace_env::WorkspaceEnv;
 use crate::config::constants::tools;
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use serde_json::{Value, json};
-use std::{path::PathBuf, process::Stdio, time::Duration};
+use std::{path::PathBuf, process::Stdio, sync::Arc, time::Duration};
 use tokio::{process::Command, time::timeout};
 
 /// Bash-like tool for command execution
 #[derive(Clone)]
 pub struct BashTool {
     workspace_root: PathBuf,
+    env: Arc<WorkspaceEnv>,
 }
 
 impl BashTool {
     /// Create a new bash tool
     pub fn new(workspace_root: PathBuf) -> Self {
-        Self { workspace_root }
+        Self {
+            workspace_root,
+            env: Arc::new(WorkspaceEnv::default()),
+        }
+    }
+
+    /// Variables from the workspace environment files passed to every command
+    pub fn set_env(&mut self, env: Arc<WorkspaceEnv>) {
+        self.env = env;
     }
 
     /// Execute command and capture its output
@@ -47,6 +57,7 @@ impl BashTool {
             cmd.args(&args);
         }
         cmd.current_dir(&work_dir);
+        self.env.apply(&mut cmd);
         cmd.stdout(Stdio::piped());
         cmd.stderr(Stdio::piped());
 
@@ -64,7 +75,7 @@ impl BashTool {
         let stdout = String::from_utf8_lossy(&output.stdout).to_string();
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
 
-        Ok(json!({
+        let mut result = json!({
             "success": output.status.success(),
             "exit_code": output.status.code().unwrap_or_default(),
             "stdout": stdout,
@@ -73,7 +84,11 @@ impl BashTool {
             "pty_enabled": false,
             "command": full_command,
             "working_directory": work_dir.display().to_string()
-        }))
+        });
+        if !self.env.is_empty() {
+            result["env_injected"] = json!(self.env.names());
+        }
+        Ok(result)
     }
 
     /// Validate command for security
@@ -0,0 +1,350 @@
+//! Toolchain requirements declared by workspace files, and version matching
+
+use serde::Serialize;
+use std::path::Path;
+
+/// A toolchain or package manager the workspace needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Toolchain {
+    Rust,
+    Node,
+    Pnpm,
+    Yarn,
+    Python,
+    Poetry,
+    Uv,
+}
+
+impl Toolchain {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Rust => "rust",
+            Self::Node => "node",
+            Self::Pnpm => "pnpm",
+            Self::Yarn => "yarn",
+            Self::Python => "python",
+            Self::Poetry => "poetry",
+            Self::Uv => "uv",
+        }
+    }
+}
+
+/// What the workspace asks for, and which file asked.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Requirement {
+    pub toolchain: Toolchain,
+    /// Channel, version or version range; `None` accepts any version
+    pub version: Option<String>,
+    /// File the requirement was read from
+    pub source: String,
+    /// Rust components from `rust-toolchain.toml`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub components: Vec<String>,
+    /// Rust targets from `rust-toolchain.toml`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<String>,
+}
+
+impl Requirement {
+    fn new(toolchain: Toolchain, version: Option<String>, source: &str) -> Self {
+        Self {
+            toolchain,
+            version: version.filter(|version| !version.is_empty()),
+            source: source.to_string(),
+            components: Vec::new(),
+            targets: Vec::new(),
+        }
+    }
+}
+
+/// Requirements declared at the workspace root, one per toolchain.
+pub fn detect_requirements(root: &Path) -> Vec<Requirement> {
+    let mut requirements = Vec::new();
+    requirements.extend(rust_requirement(root));
+    requirements.extend(node_requirements(root));
+    requirements.extend(python_requirements(root));
+    requirements
+}
+
+fn read(root: &Path, name: &str) -> Option<String> {
+    std::fs::read_to_string(root.join(name)).ok()
+}
+
+fn read_toml(root: &Path, name: &str) -> Option<toml::Value> {
+    read(root, name)?.parse().ok()
+}
+
+fn string_list(value: Option<&toml::Value>) -> Vec<String> {
+    value
+        .and_then(|value| value.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| item.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn rust_requirement(root: &Path) -> Option<Requirement> {
+    for name in ["rust-toolchain.toml", "rust-toolchain"] {
+        let Some(content) = read(root, name) else {
+            continue;
+        };
+        // The legacy `rust-toolchain` file may hold a bare channel name.
+        let Ok(parsed) = content.parse::<toml::Value>() else {
+            let channel = content.trim().to_string();
+            return Some(Requirement::new(Toolchain::Rust, Some(channel), name));
+        };
+        let toolchain = parsed.get("toolchain");
+        let channel = toolchain
+            .and_then(|table| table.get("channel"))
+            .and_then(|channel| channel.as_str())
+            .map(str::to_string);
+        let mut requirement = Requirement::new(Toolchain::Rust, channel, name);
+        requirement.components = string_list(toolchain.and_then(|table| table.get("components")));
+        requirement.targets = string_list(toolchain.and_then(|table| table.get("targets")));
+        return Some(requirement);
+    }
+
+    let manifest = read_toml(root, "Cargo.toml")?;
+    let rust_version = manifest
+        .get("package")
+        .or_else(|| manifest.get("workspace").and_then(|ws| ws.get("package")))
+        .and_then(|package| package.get("rust-version"))
+        .and_then(|version| version.as_str())
+        .map(|version| format!(">={}", version));
+    Some(Requirement::new(
+        Toolchain::Rust,
+        rust_version,
+        "Cargo.toml",
+    ))
+}
+
+fn node_requirements(root: &Path) -> Vec<Requirement> {
+    let mut requirements = Vec::new();
+    let package: Option<serde_json::Value> =
+        read(root, "package.json").and_then(|content| serde_json::from_str(&content).ok());
+
+    let pinned = [".nvmrc", ".node-version"]
+        .into_iter()
+        .find_map(|name| read(root, name).map(|content| (name, content.trim().to_string())));
+    let engines = package
+        .as_ref()
+        .and_then(|package| package.pointer("/engines/node"))
+        .and_then(|version| version.as_str());
+    match (pinned, engines) {
+        (Some((name, version)), _) => {
+            requirements.push(Requirement::new(Toolchain::Node, Some(version), name))
+        }
+        (None, Some(version)) => requirements.push(Requirement::new(
+            Toolchain::Node,
+            Some(version.to_string()),
+            "package.json",
+        )),
+        (None, None) if package.is_some() => {
+            requirements.push(Requirement::new(Toolchain::Node, None, "package.json"))
+        }
+        (None, None) => return requirements,
+    }
+
+    // `packageManager` ("pnpm@9.1.0") wins over the lockfile.
+    let declared = package
+        .as_ref()
+        .and_then(|package| package.get("packageManager"))
+        .and_then(|manager| manager.as_str())
+        .and_then(|manager| manager.split_once('@'));
+    let manager = match declared {
+        Some(("pnpm", version)) => Some((Toolchain::Pnpm, Some(version), "package.json")),
+        Some(("yarn", version)) => Some((Toolchain::Yarn, Some(version), "package.json")),
+        _ if root.join("pnpm-lock.yaml").exists() => {
+            Some((Toolchain::Pnpm, None, "pnpm-lock.yaml"))
+        }
+        _ if root.join("yarn.lock").exists() => Some((Toolchain::Yarn, None, "yarn.lock")),
+        _ => None,
+    };
+    if let Some((toolchain, version, source)) = manager {
+        // Strip the corepack integrity hash ("9.1.0+sha256.abc").
+        let version = version.map(|version| version.split('+').next().unwrap_or("").to_string());
+        requirements.push(Requirement::new(toolchain, version, source));
+    }
+    requirements
+}
+
+fn python_requirements(root: &Path) -> Vec<Requirement> {
+    let mut requirements = Vec::new();
+    let pyproject = read_toml(root, "pyproject.toml");
+
+    let pinned = read(root, ".python-version")
+        .and_then(|content| content.lines().next().map(|line| line.trim().to_string()));
+    let declared = pyproject.as_ref().and_then(|pyproject| {
+        pyproject
+            .get("project")
+            .and_then(|project| project.get("requires-python"))
+            .or_else(|| {
+                pyproject
+                    .get("tool")
+                    .and_then(|tool| tool.get("poetry"))
+                    .and_then(|poetry| poetry.get("dependencies"))
+                    .and_then(|deps| deps.get("python"))
+            })
+            .and_then(|version| version.as_str())
+            .map(str::to_string)
+    });
+    match (pinned, declared) {
+        (Some(version), _) => requirements.push(Requirement::new(
+            Toolchain::Python,
+            Some(version),
+            ".python-version",
+        )),
+        (None, Some(version)) => requirements.push(Requirement::new(
+            Toolchain::Python,
+            Some(version),
+            "pyproject.toml",
+        )),
+        (None, None) if pyproject.is_some() || root.join("requirements.txt").exists() => {
+            let source = if pyproject.is_some() {
+                "pyproject.toml"
+            } else {
+                "requirements.txt"
+            };
+            requirements.push(Requirement::new(Toolchain::Python, None, source));
+        }
+        (None, None) => return requirements,
+    }
+
+    let uses_poetry = pyproject
+        .as_ref()
+        .and_then(|pyproject| pyproject.get("tool"))
+        .is_some_and(|tool| tool.get("poetry").is_some());
+    if root.join("uv.lock").exists() {
+        requirements.push(Requirement::new(Toolchain::Uv, None, "uv.lock"));
+    } else if uses_poetry || root.join("poetry.lock").exists() {
+        let source = if uses_poetry {
+            "pyproject.toml"
+        } else {
+            "poetry.lock"
+        };
+        requirements.push(Requirement::new(Toolchain::Poetry, None, source));
+    }
+    requirements
+}
+
+/// First dotted version number in command output, e.g. `1.80.0` from
+/// `rustc 1.80.0 (051478957 2024-07-21)` or `20.11.0` from `v20.11.0`.
+pub fn parse_version(output: &str) -> Option<String> {
+    output.split_whitespace().find_map(|word| {
+        let word = word.trim_start_matches('v');
+        let version: String = word
+            .chars()
+            .take_while(|c| c.is_ascii_digit() || *c == '.')
+            .collect();
+        let version = version.trim_end_matches('.');
+        (version.contains('.') && version.starts_with(|c: char| c.is_ascii_digit()))
+            .then(|| version.to_string())
+    })
+}
+
+/// Whether `installed` meets `spec`.
+///
+/// Understands plain and wildcard versions (`20`, `3.11`, `18.x`), comparison
+/// clauses separated by commas or spaces (`>=3.9,<4`, `>=18 <21`), caret and
+/// tilde ranges (`^18.2`, `~18.2`, `~=3.10`) and `||` alternatives. Names such
+/// as `stable` or `lts/*` cannot be checked locally and are accepted.
+pub fn version_satisfies(installed: &str, spec: &str) -> bool {
+    let Some(installed) = numbers(installed) else {
+        return false;
+    };
+    spec.split("||").any(|alternative| {
+        let clauses = clauses(alternative);
+        !clauses.is_empty()
+            && clauses
+                .iter()
+                .all(|(op, version)| clause_holds(&installed, op, version))
+    }) || !spec.chars().any(|c| c.is_ascii_digit())
+}
+
+/// Version to install for `spec`: its lower bound, or `None` when the spec has
+/// no usable lower bound.
+pub fn install_version(spec: &str) -> Option<String> {
+    let alternative = spec.split("||").next()?;
+    clauses(alternative)
+        .into_iter()
+        .find(|(op, _)| matches!(op.as_str(), "" | ">=" | "==" | "=" | "^" | "~" | "~="))
+        .map(|(_, version)| version)
+        .filter(|version| version.starts_with(|c: char| c.is_ascii_digit()))
+        .map(|version| {
+            version
+                .trim_end_matches(".x")
+                .trim_end_matches(".*")
+                .to_string()
+        })
+}
+
+fn clauses(spec: &str) -> Vec<(String, String)> {
+    let mut clauses = Vec::new();
+    let mut pending_op: Option<String> = None;
+    for token in spec.split([',', ' ']).filter(|token| !token.is_empty()) {
+        let op: String = token
+            .chars()
+            .take_while(|c| matches!(c, '>' | '<' | '=' | '!' | '^' | '~'))
+            .collect();
+        let version = token[op.len()..].trim_start_matches('v');
+        if version.is_empty() {
+            // An operator separated from its version, as in `>= 3.9`.
+            pending_op = Some(op);
+            continue;
+        }
+        let op = match pending_op.take() {
+            Some(pending) => pending + &op,
+            None => op,
+        };
+        clauses.push((op, version.to_string()));
+    }
+    clauses
+}
+
+fn numbers(version: &str) -> Option<Vec<u64>> {
+    let parts: Vec<u64> = version
+        .trim_start_matches('v')
+        .split('.')
+        .map_while(|part| part.parse().ok())
+        .collect();
+    (!parts.is_empty()).then_some(parts)
+}
+
+fn clause_holds(installed: &[u64], op: &str, version: &str) -> bool {
+    let Some(wanted) = numbers(version) else {
+        // Names such as `stable` or `lts/*`.
+        return true;
+    };
+    let compare = |len: usize| {
+        let pad = |parts: &[u64]| {
+            let mut parts = parts.to_vec();
+            parts.resize(len, 0);
+            parts
+        };
+        pad(installed).cmp(&pad(&wanted))
+    };
+    let prefix_matches = |len: usize| installed.len() >= len && installed[..len] == wanted[..len];
+    let full = installed.len().max(wanted.len());
+    match op {
+        ">=" => compare(full).is_ge(),
+        ">" => compare(full).is_gt(),
+        "<=" => compare(full).is_le(),
+        "<" => compare(full).is_lt(),
+        "!=" => !prefix_matches(wanted.len()),
+        "^" => prefix_matches(1) && compare(full).is_ge(),
+        "~" | "~=" => {
+            let fixed = if op == "~" {
+                wanted.len().min(2)
+            } else {
+                wanted.len().saturating_sub(1).max(1)
+            };
+            prefix_matches(fixed) && compare(full).is_ge()
+        }
+        // Plain versions, `=` and `==` match as prefixes: `3.11` accepts 3.11.4.
+        _ => prefix_matches(wanted.len()),
+    }
+}
@@ -0,0 +1,649 @@
+//! Toolchain checks for fresh machines
+//!
+//! [`BootstrapEnvTool`] reads the toolchain requirements declared in the
+//! workspace (`rust-toolchain.toml`, `Cargo.toml`, `.nvmrc`, `package.json`,
+//! `.python-version`, `pyproject.toml` and lockfiles), checks which of them are
+//! installed at a matching version, and lists the exact commands that install
+//! the rest using the version managers already on the machine. The commands
+//! run only when the caller asks for it and `[tools.bootstrap] allow_install`
+//! is enabled, so a full-auto run can find missing toolchains before the first
+//! build instead of failing halfway through.
+
+mod detect;
+
+pub use detect::{
+    Requirement, Toolchain, detect_requirements, install_version, parse_version, version_satisfies,
+};
+
+use crate::config::BootstrapConfig;
+use crate::config::constants::tools;
+use crate::tools::traits::Tool;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+use tokio::time::timeout;
+
+/// How long a version probe such as `node --version` may take.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Trailing lines of output kept from a failed installation command.
+const OUTPUT_TAIL_LINES: usize = 20;
+
+const RUSTUP_INSTALLER: &str = "curl --proto '=https' --tlsv1.2 -sSf https://sh.rustup.rs | sh -s -- -y --profile minimal --default-toolchain none";
+const NVM_INSTALLER: &str =
+    "curl -o- https://raw.githubusercontent.com/nvm-sh/nvm/v0.40.1/install.sh | bash";
+const UV_INSTALLER: &str = "curl -LsSf https://astral.sh/uv/install.sh | sh";
+
+#[derive(Debug, Default, Deserialize)]
+struct BootstrapEnvInput {
+    /// Run the installation commands for missing toolchains
+    #[serde(default)]
+    install: bool,
+}
+
+/// Version managers and installers available on this machine.
+#[derive(Debug, Clone, Default)]
+pub struct Installers {
+    pub rustup: bool,
+    pub fnm: bool,
+    pub volta: bool,
+    /// nvm is a shell function; it counts when `$NVM_DIR/nvm.sh` exists
+    pub nvm: bool,
+    pub uv: bool,
+    pub pyenv: bool,
+    pub pipx: bool,
+}
+
+impl Installers {
+    pub fn detect() -> Self {
+        let nvm = std::env::var_os("NVM_DIR")
+            .map(PathBuf::from)
+            .is_some_and(|dir| dir.join("nvm.sh").is_file());
+        Self {
+            rustup: on_path("rustup"),
+            fnm: on_path("fnm"),
+            volta: on_path("volta"),
+            nvm,
+            uv: on_path("uv"),
+            pyenv: on_path("pyenv"),
+            pipx: on_path("pipx"),
+        }
+    }
+}
+
+fn on_path(binary: &str) -> bool {
+    std::env::var_os("PATH")
+        .is_some_and(|paths| std::env::split_paths(&paths).any(|dir| dir.join(binary).is_file()))
+}
+
+/// Result of checking one requirement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    Missing,
+    Mismatch,
+}
+
+#[derive(Debug, Clone)]
+struct Check {
+    status: CheckStatus,
+    installed: Option<String>,
+    detail: Option<String>,
+}
+
+impl Check {
+    fn missing(detail: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Missing,
+            installed: None,
+            detail: Some(detail.into()),
+        }
+    }
+}
+
+/// Commands that install `requirement` with the installers at hand.
+pub fn install_commands(requirement: &Requirement, installers: &Installers) -> Vec<String> {
+    let spec = requirement.version.as_deref();
+    // Plain versions and names are passed through; ranges install their lower bound.
+    let version = spec.and_then(|spec| {
+        if spec.starts_with(|c: char| c.is_ascii_alphanumeric()) && !spec.contains([' ', ',']) {
+            Some(spec.to_string())
+        } else {
+            install_version(spec)
+        }
+    });
+    let mut commands = Vec::new();
+    match requirement.toolchain {
+        Toolchain::Rust => {
+            let rustup = if installers.rustup {
+                "rustup"
+            } else {
+                commands.push(RUSTUP_INSTALLER.to_string());
+                "\"$HOME/.cargo/bin/rustup\""
+            };
+            // A minimum `rust-version` is met by the current stable release.
+            let channel = match spec {
+                Some(spec) if !spec.starts_with(">=") => spec,
+                _ => "stable",
+            };
+            let mut command = format!("{} toolchain install {} --profile minimal", rustup, channel);
+            for component in &requirement.components {
+                command.push_str(&format!(" --component {}", component));
+            }
+            for target in &requirement.targets {
+                command.push_str(&format!(" --target {}", target));
+            }
+            commands.push(command);
+        }
+        Toolchain::Node => {
+            if installers.fnm {
+                commands.push(match &version {
+                    Some(version) => format!("fnm install {}", version),
+                    None => "fnm install --lts".to_string(),
+                });
+            } else if installers.volta {
+                commands.push(match &version {
+                    Some(version) => format!("volta install node@{}", version),
+                    None => "volta install node".to_string(),
+                });
+            } else {
+                let nvm_sh = if installers.nvm {
+                    "$NVM_DIR/nvm.sh"
+                } else {
+                    commands.push(NVM_INSTALLER.to_string());
+                    "$HOME/.nvm/nvm.sh"
+                };
+                commands.push(format!(
+                    ". \"{}\" && nvm install {}",
+                    nvm_sh,
+                    version.as_deref().unwrap_or("--lts")
+                ));
+            }
+        }
+        Toolchain::Pnpm | Toolchain::Yarn => {
+            let name = requirement.toolchain.name();
+            commands.push(format!("corepack enable {}", name));
+            if let Some(version) = &version {
+                commands.push(format!("corepack prepare {}@{} --activate", name, version));
+            }
+        }
+        Toolchain::Python => {
+            let version = version.unwrap_or_default();
+            if installers.uv {
+                commands.push(format!("uv python install {}", version));
+            } else if installers.pyenv {
+                let version = if version.is_empty() { "3" } else { &version };
+                commands.push(format!("pyenv install --skip-existing {}", version));
+            } else {
+                commands.push(UV_INSTALLER.to_string());
+                commands.push(format!(
+                    "\"$HOME/.local/bin/uv\" python install {}",
+                    version
+                ));
+            }
+        }
+        Toolchain::Poetry => {
+            if !installers.pipx {
+                commands.push("python3 -m pip install --user pipx".to_string());
+                commands.push("python3 -m pipx install poetry".to_string());
+            } else {
+                commands.push("pipx install poetry".to_string());
+            }
+        }
+        Toolchain::Uv => commands.push(UV_INSTALLER.to_string()),
+    }
+    commands
+        .into_iter()
+        .map(|command| command.trim_end().to_string())
+        .collect()
+}
+
+/// Checks and installs the toolchains a workspace declares.
+#[derive(Clone)]
+pub struct BootstrapEnvTool {
+    workspace_root: PathBuf,
+    config: BootstrapConfig,
+}
+
+impl BootstrapEnvTool {
+    pub fn new(workspace_root: PathBuf) -> Self {
+        Self {
+            workspace_root,
+            config: BootstrapConfig::default(),
+        }
+    }
+
+    pub fn set_config(&mut self, config: BootstrapConfig) {
+        self.config = config;
+    }
+
+    async fn run(&self, input: BootstrapEnvInput) -> Result<Value> {
+        let requirements = detect_requirements(&self.workspace_root);
+        if requirements.is_empty() {
+            return Ok(json!({
+                "success": true,
+                "ready": true,
+                "requirements": [],
+                "message": "No toolchain files found (rust-toolchain.toml, Cargo.toml, .nvmrc, package.json, .python-version, pyproject.toml).",
+            }));
+        }
+
+        let installers = Installers::detect();
+        let mut checks = Vec::new();
+        for requirement in &requirements {
+            checks.push(self.check(requirement, &installers).await);
+        }
+
+        let mut installs = Vec::new();
+        let mut install_note = None;
+        let wants_install = checks.iter().any(|check| check.status != CheckStatus::Ok);
+        if input.install && wants_install {
+            if self.config.allow_install {
+                for (requirement, check) in requirements.iter().zip(&checks) {
+                    if check.status != CheckStatus::Ok {
+                        installs.push(self.install(requirement, &installers).await);
+                    }
+                }
+                let installers = Installers::detect();
+                checks.clear();
+                for requirement in &requirements {
+                    checks.push(self.check(requirement, &installers).await);
+                }
+            } else {
+                install_note = Some(
+                    "Installation is disabled. Set allow_install = true under [tools.bootstrap] in vtcode.toml, or run the commands yourself.",
+                );
+            }
+        }
+
+        let mut entries = Vec::new();
+        let mut pending = 0;
+        for (requirement, check) in requirements.iter().zip(&checks) {
+            let mut entry = json!({
+                "toolchain": requirement.toolchain,
+                "required": requirement.version.as_deref().unwrap_or("any"),
+                "source": requirement.source,
+                "status": check.status,
+            });
+            if let Some(installed) = &check.installed {
+                entry["installed"] = json!(installed);
+            }
+            if let Some(detail) = &check.detail {
+                entry["detail"] = json!(detail);
+            }
+            if check.status != CheckStatus::Ok {
+                pending += 1;
+                entry["install"] = json!(install_commands(requirement, &installers));
+            }
+            entries.push(entry);
+        }
+
+        let message = if pending == 0 {
+            format!(
+                "All {} required toolchain(s) are installed.",
+                requirements.len()
+            )
+        } else if !installs.is_empty() {
+            format!(
+                "{} toolchain(s) are still missing after installation. Binaries installed under ~/.cargo/bin or ~/.local/bin may need a new shell; see 'installs' for failures.",
+                pending
+            )
+        } else {
+            format!(
+                "{} toolchain(s) are missing or at the wrong version. Install them with the listed commands before building.",
+                pending
+            )
+        };
+
+        let mut result = json!({
+            "success": installs.iter().all(|install| install["success"] == json!(true)),
+            "ready": pending == 0,
+            "requirements": entries,
+            "message": message,
+        });
+        if !installs.is_empty() {
+            result["installs"] = json!(installs);
+        }
+        if let Some(note) = install_note {
+            result["install_skipped"] = json!(note);
+        }
+        Ok(result)
+    }
+
+    async fn check(&self, requirement: &Requirement, installers: &Installers) -> Check {
+        let spec = requirement.version.as_deref();
+        if requirement.toolchain == Toolchain::Rust
+            && installers.rustup
+            && let Some(channel) = spec.filter(|spec| !spec.starts_with(">="))
+        {
+            return self.check_rustup(requirement, channel).await;
+        }
+
+        let probes: &[&[&str]] = match requirement.toolchain {
+            Toolchain::Rust => &[&["rustc", "--version"]],
+            Toolchain::Node => &[&["node", "--version"]],
+            Toolchain::Pnpm => &[&["pnpm", "--version"]],
+            Toolchain::Yarn => &[&["yarn", "--version"]],
+            Toolchain::Python => &[&["python3", "--version"], &["python", "--version"]],
+            Toolchain::Poetry => &[&["poetry", "--version"]],
+            Toolchain::Uv => &[&["uv", "--version"]],
+        };
+        let mut output = None;
+        for probe in probes {
+            output = self.probe(probe).await;
+            if output.is_some() {
+                break;
+            }
+        }
+        let Some(output) = output else {
+            return Check::missing(format!("{} is not on PATH", probes[0][0]));
+        };
+        let installed = parse_version(&output);
+        match (spec, &installed) {
+            (Some(spec), Some(version)) if !version_satisfies(version, spec) => Check {
+                status: CheckStatus::Mismatch,
+                detail: Some(format!(
+                    "{} is installed, {} requires {}",
+                    version, requirement.source, spec
+                )),
+                installed,
+            },
+            _ => Check {
+                status: CheckStatus::Ok,
+                installed,
+                detail: None,
+            },
+        }
+    }
+
+    /// Check a pinned Rust channel and its components and targets through rustup.
+    async fn check_rustup(&self, requirement: &Requirement, channel: &str) -> Check {
+        let Some(output) = self
+            .probe(&["rustup", "run", channel, "rustc", "--version"])
+            .await
+        else {
+            return Check::missing(format!("toolchain {} is not installed", channel));
+        };
+        let installed = parse_version(&output);
+
+        let mut absent = Vec::new();
+        if !requirement.components.is_empty() {
+            let listed = self
+                .probe(&[
+                    "rustup",
+                    "component",
+                    "list",
+                    "--installed",
+                    "--toolchain",
+                    channel,
+                ])
+                .await
+                .unwrap_or_default();
+            absent.extend(
+                requirement
+                    .components
+                    .iter()
+                    .filter(|component| {
+                        !listed
+                            .lines()
+                            .any(|line| line.starts_with(component.as_str()))
+                    })
+                    .map(|component| format!("component {}", component)),
+            );
+        }
+        if !requirement.targets.is_empty() {
+            let listed = self
+                .probe(&[
+                    "rustup",
+                    "target",
+                    "list",
+                    "--installed",
+                    "--toolchain",
+                    channel,
+                ])
+                .await
+                .unwrap_or_default();
+            absent.extend(
+                requirement
+                    .targets
+                    .iter()
+                    .filter(|target| !listed.lines().any(|line| line.trim() == target.as_str()))
+                    .map(|target| format!("target {}", target)),
+            );
+        }
+
+        if absent.is_empty() {
+            Check {
+                status: CheckStatus::Ok,
+                installed,
+                detail: None,
+            }
+        } else {
+            Check {
+                status: CheckStatus::Mismatch,
+                installed,
+                detail: Some(format!("missing {}", absent.join(", "))),
+            }
+        }
+    }
+
+    /// Output of a successful version probe.
+    async fn probe(&self, command: &[&str]) -> Option<String> {
+        let output = timeout(
+            PROBE_TIMEOUT,
+            Command::new(command[0])
+                .args(&command[1..])
+                .current_dir(&self.workspace_root)
+                .stdin(Stdio::null())
+                .kill_on_drop(true)
+                .output(),
+        )
+        .await
+        .ok()?
+        .ok()?;
+        output.status.success().then(|| {
+            format!(
+                "{}{}",
+                String::from_utf8_lossy(&output.stdout),
+                String::from_utf8_lossy(&output.stderr)
+            )
+        })
+    }
+
+    /// Run the installation commands for one requirement, stopping at the first failure.
+    async fn install(&self, requirement: &Requirement, installers: &Installers) -> Value {
+        let mut steps = Vec::new();
+        let mut success = true;
+        for command in install_commands(requirement, installers) {
+            let mut step = json!({ "command": command });
+            match self.run_command(&command).await {
+                Ok((0, _)) => {}
+                Ok((code, tail)) => {
+                    step["exit_code"] = json!(code);
+                    step["output_tail"] = json!(tail);
+                    success = false;
+                }
+                Err(err) => {
+                    step["error"] = json!(format!("{:#}", err));
+                    success = false;
+                }
+            }
+            steps.push(step);
+            if !success {
+                break;
+            }
+        }
+        json!({
+            "toolchain": requirement.toolchain,
+            "success": success,
+            "steps": steps,
+        })
+    }
+
+    async fn run_command(&self, command: &str) -> Result<(i32, String)> {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c")
+            .arg(command)
+            .current_dir(&self.workspace_root)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        let limit = Duration::from_secs(self.config.timeout_secs);
+        let output = timeout(limit, cmd.output())
+            .await
+            .with_context(|| format!("'{}' timed out after {}s", command, limit.as_secs()))?
+            .with_context(|| format!("failed to run: {}", command))?;
+
+        let combined = format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let lines: Vec<&str> = combined.lines().collect();
+        let tail = lines[lines.len().saturating_sub(OUTPUT_TAIL_LINES)..].join("\n");
+        Ok((output.status.code().unwrap_or(-1), tail))
+    }
+}
+
+#[async_trait]
+impl Tool for BootstrapEnvTool {
+    async fn execute(&self, args: Value) -> Result<Value> {
+        let input: BootstrapEnvInput = if args.is_null() {
+            BootstrapEnvInput::default()
+        } else {
+            serde_json::from_value(args).context(
+                "Error: Invalid 'bootstrap_env' arguments. Optional: { install: boolean }",
+            )?
+        };
+        self.run(input).await
+    }
+
+    fn name(&self) -> &'static str {
+        tools::BOOTSTRAP_ENV
+    }
+
+    fn description(&self) -> &'static str {
+        "Check the toolchains the workspace declares and install the missing ones"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_requirements_from_workspace_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(
+            root.join("rust-toolchain.toml"),
+            "[toolchain]\nchannel = \"1.80.0\"\ncomponents = [\"clippy\"]\ntargets = [\"wasm32-unknown-unknown\"]\n",
+        )
+        .unwrap();
+        std::fs::write(root.join(".nvmrc"), "v20.11.0\n").unwrap();
+        std::fs::write(
+            root.join("package.json"),
+            r#"{"engines": {"node": ">=18"}, "packageManager": "pnpm@9.1.0+sha256.abc"}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("pyproject.toml"),
+            "[project]\nrequires-python = \">=3.10,<4\"\n[tool.poetry]\nname = \"x\"\n",
+        )
+        .unwrap();
+
+        let requirements = detect_requirements(root);
+        let summary: Vec<(Toolchain, Option<&str>, &str)> = requirements
+            .iter()
+            .map(|req| (req.toolchain, req.version.as_deref(), req.source.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (Toolchain::Rust, Some("1.80.0"), "rust-toolchain.toml"),
+                (Toolchain::Node, Some("v20.11.0"), ".nvmrc"),
+                (Toolchain::Pnpm, Some("9.1.0"), "package.json"),
+                (Toolchain::Python, Some(">=3.10,<4"), "pyproject.toml"),
+                (Toolchain::Poetry, None, "pyproject.toml"),
+            ]
+        );
+        assert_eq!(requirements[0].components, vec!["clippy"]);
+        assert_eq!(requirements[0].targets, vec!["wasm32-unknown-unknown"]);
+    }
+
+    #[test]
+    fn matches_versions_against_specs() {
+        assert_eq!(
+            parse_version("rustc 1.80.0 (051478957 2024-07-21)").as_deref(),
+            Some("1.80.0")
+        );
+        assert_eq!(parse_version("v20.11.0\n").as_deref(), Some("20.11.0"));
+        assert_eq!(
+            parse_version("Poetry (version 1.8.2)").as_deref(),
+            Some("1.8.2")
+        );
+
+        assert!(version_satisfies("20.11.0", "v20.11.0"));
+        assert!(version_satisfies("20.11.0", "20"));
+        assert!(!version_satisfies("18.19.0", "20"));
+        assert!(version_satisfies("3.12.1", ">=3.10,<4"));
+        assert!(!version_satisfies("3.9.18", ">= 3.10"));
+        assert!(version_satisfies("20.1.0", ">=18 <21"));
+        assert!(!version_satisfies("21.0.0", "^18 || ^20"));
+        assert!(version_satisfies("3.10.4", "~=3.10"));
+        assert!(version_satisfies("1.79.0", "stable"));
+
+        assert_eq!(install_version(">=18 <21").as_deref(), Some("18"));
+        assert_eq!(install_version("^3.11").as_deref(), Some("3.11"));
+        assert_eq!(install_version("<4"), None);
+    }
+
+    #[test]
+    fn install_commands_use_available_managers() {
+        let mut rust = Requirement {
+            toolchain: Toolchain::Rust,
+            version: Some("1.80.0".to_string()),
+            source: "rust-toolchain.toml".to_string(),
+            components: vec!["clippy".to_string()],
+            targets: Vec::new(),
+        };
+        let with_rustup = Installers {
+            rustup: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            install_commands(&rust, &with_rustup),
+            vec!["rustup toolchain install 1.80.0 --profile minimal --component clippy"]
+        );
+        rust.version = Some(">=1.75".to_string());
+        let fresh = install_commands(&rust, &Installers::default());
+        assert_eq!(fresh[0], RUSTUP_INSTALLER);
+        assert!(fresh[1].starts_with("\"$HOME/.cargo/bin/rustup\" toolchain install stable"));
+
+        let node = Requirement {
+            toolchain: Toolchain::Node,
+            version: Some(">=18 <21".to_string()),
+            source: "package.json".to_string(),
+            components: Vec::new(),
+            targets: Vec::new(),
+        };
+        let with_fnm = Installers {
+            fnm: true,
+            ..Default::default()
+        };
+        assert_eq!(install_commands(&node, &with_fnm), vec!["fnm install 18"]);
+        assert_eq!(
+            install_commands(&node, &Installers::default()),
+            vec![
+                NVM_INSTALLER.to_string(),
+                ". \"$HOME/.nvm/nvm.sh\" && nvm install 18".to_string()
+            ]
+        );
+    }
+}
@@ -0,0 +1,248 @@
+//! Function definitions and the calls inside them, read from syntax trees
+
+use crate::tools::imports::ImportLanguage;
+use tree_sitter::Node;
+
+/// A function or method and the names it calls.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FunctionDef {
+    pub name: String,
+    /// Enclosing type, impl, trait or class, if any
+    pub container: Option<String>,
+    /// 1-based first and last line
+    pub line: usize,
+    pub end_line: usize,
+    pub calls: Vec<CallSite>,
+}
+
+impl FunctionDef {
+    /// `Type::name` for methods, the bare name otherwise.
+    pub fn qualified_name(&self) -> String {
+        match &self.container {
+            Some(container) => format!("{container}::{}", self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// A call by name, as written at the call site.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CallSite {
+    pub name: String,
+    /// 1-based line of the call
+    pub line: usize,
+}
+
+/// The functions defined in a file, in source order.
+pub(crate) fn functions(
+    language: ImportLanguage,
+    root: Node<'_>,
+    source: &str,
+) -> Vec<FunctionDef> {
+    let mut walk = Walk {
+        language,
+        source,
+        functions: Vec::new(),
+    };
+    walk.visit(root, None, None);
+    walk.functions
+}
+
+struct Walk<'s> {
+    language: ImportLanguage,
+    source: &'s str,
+    functions: Vec<FunctionDef>,
+}
+
+impl Walk<'_> {
+    fn visit(&mut self, node: Node<'_>, container: Option<&str>, current: Option<usize>) {
+        let mut container = container.map(str::to_string);
+        let mut current = current;
+        if let Some(name) = self.container_name(node) {
+            container = Some(name);
+        } else if let Some(name) = self.function_name(node) {
+            let receiver = self.receiver_type(node);
+            self.functions.push(FunctionDef {
+                name,
+                container: receiver.or_else(|| container.clone()),
+                line: node.start_position().row + 1,
+                end_line: node.end_position().row + 1,
+                calls: Vec::new(),
+            });
+            current = Some(self.functions.len() - 1);
+        } else if let Some(index) = current
+            && let Some(callee) = self.callee(node)
+            && let Some(name) = callee_name(callee, self.source)
+        {
+            self.functions[index].calls.push(CallSite {
+                name,
+                line: node.start_position().row + 1,
+            });
+        }
+
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            self.visit(child, container.as_deref(), current);
+        }
+    }
+
+    fn text(&self, node: Node<'_>) -> String {
+        node.utf8_text(self.source.as_bytes())
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    fn field_text(&self, node: Node<'_>, field: &str) -> Option<String> {
+        node.child_by_field_name(field)
+            .map(|child| self.text(child))
+    }
+
+    /// Name of a type-like node whose functions are methods.
+    fn container_name(&self, node: Node<'_>) -> Option<String> {
+        match (self.language, node.kind()) {
+            (ImportLanguage::Rust, "impl_item") => {
+                let ty = node.child_by_field_name("type")?;
+                // `impl<T> Store<T>` is `Store`
+                let ty = ty.child_by_field_name("type").unwrap_or(ty);
+                Some(self.text(ty))
+            }
+            (ImportLanguage::Rust, "trait_item")
+            | (ImportLanguage::Python, "class_definition")
+            | (
+                ImportLanguage::JavaScript | ImportLanguage::TypeScript,
+                "class_declaration" | "class" | "abstract_class_declaration",
+            ) => self.field_text(node, "name"),
+            _ => None,
+        }
+    }
+
+    fn function_name(&self, node: Node<'_>) -> Option<String> {
+        match (self.language, node.kind()) {
+            (ImportLanguage::Rust, "function_item")
+            | (ImportLanguage::Python, "function_definition")
+            | (ImportLanguage::Go, "function_declaration" | "method_declaration")
+            | (
+                ImportLanguage::JavaScript | ImportLanguage::TypeScript,
+                "function_declaration" | "generator_function_declaration" | "method_definition",
+            ) => self.field_text(node, "name"),
+            // const handler = () => { ... }
+            (ImportLanguage::JavaScript | ImportLanguage::TypeScript, "variable_declarator") => {
+                let value = node.child_by_field_name("value")?;
+                matches!(
+                    value.kind(),
+                    "arrow_function" | "function_expression" | "function"
+                )
+                .then(|| self.field_text(node, "name"))
+                .flatten()
+            }
+            _ => None,
+        }
+    }
+
+    /// Receiver type of a Go method, e.g. `Server` for `func (s *Server) Run()`.
+    fn receiver_type(&self, node: Node<'_>) -> Option<String> {
+        if self.language != ImportLanguage::Go || node.kind() != "method_declaration" {
+            return None;
+        }
+        let receiver = self.field_text(node, "receiver")?;
+        let ty = receiver
+            .trim_matches(['(', ')'])
+            .split_whitespace()
+            .last()?
+            .trim_start_matches('*');
+        let ty = ty.split('[').next().unwrap_or(ty);
+        (!ty.is_empty()).then(|| ty.to_string())
+    }
+
+    /// The expression naming the function a call node invokes.
+    fn callee<'t>(&self, node: Node<'t>) -> Option<Node<'t>> {
+        match (self.language, node.kind()) {
+            (ImportLanguage::Python, "call") | (_, "call_expression") => {
+                node.child_by_field_name("function")
+            }
+            (ImportLanguage::JavaScript | ImportLanguage::TypeScript, "new_expression") => {
+                node.child_by_field_name("constructor")
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Last name segment of a callee: `load` for `config::load`, `self.load`,
+/// `cfg.load` or `load::<T>`.
+fn callee_name(node: Node<'_>, source: &str) -> Option<String> {
+    if matches!(
+        node.kind(),
+        "identifier"
+            | "field_identifier"
+            | "property_identifier"
+            | "private_property_identifier"
+            | "type_identifier"
+    ) {
+        return node.utf8_text(source.as_bytes()).ok().map(str::to_string);
+    }
+    ["name", "field", "attribute", "property", "function"]
+        .iter()
+        .find_map(|field| node.child_by_field_name(field))
+        .and_then(|child| callee_name(child, source))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::tree_sitter::TreeSitterAnalyzer;
+
+    fn parse(language: ImportLanguage, source: &str) -> Vec<FunctionDef> {
+        let mut analyzer = TreeSitterAnalyzer::new().expect("analyzer");
+        let tree = analyzer
+            .parse(source, language.tree_sitter())
+            .expect("parse");
+        functions(language, tree.root_node(), source)
+    }
+
+    fn summary(functions: &[FunctionDef]) -> Vec<(String, Vec<&str>)> {
+        functions
+            .iter()
+            .map(|function| {
+                (
+                    function.qualified_name(),
+                    function
+                        .calls
+                        .iter()
+                        .map(|call| call.name.as_str())
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn extracts_rust_calls_by_last_segment() {
+        let source = "impl<T> Store<T> {\n    fn load(&self) -> T {\n        let raw = fs::read(path());\n        self.decode::<T>(raw)\n    }\n}\n\nfn path() -> String {\n    String::new()\n}\n";
+        assert_eq!(
+            summary(&parse(ImportLanguage::Rust, source)),
+            vec![
+                ("Store::load".to_string(), vec!["read", "path", "decode"]),
+                ("path".to_string(), vec!["new"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn extracts_python_and_go_methods() {
+        let python = "class Service:\n    def start(self):\n        self.bind(port())\n\ndef port():\n    return int(env('PORT'))\n";
+        assert_eq!(
+            summary(&parse(ImportLanguage::Python, python)),
+            vec![
+                ("Service::start".to_string(), vec!["bind", "port"]),
+                ("port".to_string(), vec!["int", "env"]),
+            ]
+        );
+
+        let go = "package main\n\nfunc (s *Server) Run() error {\n\treturn s.listen(addr())\n}\n";
+        assert_eq!(
+            summary(&parse(ImportLanguage::Go, go)),
+            vec![("Server::Run".to_string(), vec!["listen", "addr"])]
+        );
+    }
+}
@@ -0,0 +1,698 @@
+//! Callers and callees of a function across the workspace
+//!
+//! [`CallGraphTool`] parses the workspace's Rust, Python, JavaScript/TypeScript
+//! and Go files with tree-sitter, records every function with the names it
+//! calls, and walks the resulting graph from a symbol up to its callers and
+//! down to its callees, a bounded number of hops each way. Calls are resolved
+//! by name: a definition in the calling file wins, then definitions the
+//! workspace [`SymbolIndex`] lists as importable, and only then every function
+//! with that name, in which case the edge is marked ambiguous. Parsed files are
+//! cached and only re-parsed when their modification time changes.
+
+mod extract;
+
+use crate::config::constants::tools;
+use crate::core::progress::Progress;
+use crate::tools::cancellation::ToolContext;
+use crate::tools::imports::{ImportLanguage, SymbolIndex, source_walker};
+use crate::tools::traits::Tool;
+use crate::tools::tree_sitter::TreeSitterAnalyzer;
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
+use extract::FunctionDef;
+use parking_lot::Mutex;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+const DEFAULT_DEPTH: usize = 2;
+const MAX_DEPTH: usize = 5;
+const DEFAULT_MAX_NODES: usize = 60;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Direction {
+    Callers,
+    Callees,
+    #[default]
+    Both,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Rendering {
+    #[default]
+    None,
+    Mermaid,
+    Dot,
+}
+
+#[derive(Debug, Deserialize)]
+struct CallGraphInput {
+    /// Function name, optionally qualified: `load`, `Config::load`, `Config.load`
+    symbol: String,
+    /// Only consider definitions in this file
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(default)]
+    direction: Direction,
+    #[serde(default)]
+    depth: Option<usize>,
+    #[serde(default)]
+    max_nodes: Option<usize>,
+    #[serde(default)]
+    render: Rendering,
+}
+
+#[derive(Debug)]
+struct ParsedFile {
+    language: ImportLanguage,
+    modified: Option<SystemTime>,
+    functions: Vec<FunctionDef>,
+}
+
+/// A function in the workspace: file and index into its definitions.
+type FunctionId = (PathBuf, usize);
+
+/// Parsed definitions by workspace-relative path.
+#[derive(Debug, Default)]
+struct CallIndex {
+    files: HashMap<PathBuf, ParsedFile>,
+}
+
+impl CallIndex {
+    fn refresh(
+        &mut self,
+        workspace: &Path,
+        max_files: usize,
+        analyzer: &mut TreeSitterAnalyzer,
+        progress: &mut Progress,
+    ) -> Result<()> {
+        let entries: Vec<_> = source_walker(workspace)
+            .flatten()
+            .filter(|entry| entry.file_type().is_some_and(|kind| kind.is_file()))
+            .filter_map(|entry| {
+                let language = ImportLanguage::from_path(entry.path())?;
+                Some((entry, language))
+            })
+            .take(max_files)
+            .collect();
+        progress.start_phase("indexing calls", Some(entries.len() as u64));
+
+        let mut seen = HashSet::new();
+        for (entry, language) in entries {
+            let metadata = entry.metadata().ok();
+            progress.advance(metadata.as_ref().map_or(0, |meta| meta.len()));
+            let relative = entry
+                .path()
+                .strip_prefix(workspace)
+                .unwrap_or(entry.path())
+                .to_path_buf();
+            let modified = metadata.and_then(|meta| meta.modified().ok());
+            seen.insert(relative.clone());
+            if let Some(existing) = self.files.get(&relative)
+                && existing.modified.is_some()
+                && existing.modified == modified
+            {
+                continue;
+            }
+            let Ok(source) = std::fs::read_to_string(entry.path()) else {
+                continue;
+            };
+            let functions = match analyzer.parse(&source, language.tree_sitter()) {
+                Ok(tree) => extract::functions(language, tree.root_node(), &source),
+                Err(_) => Vec::new(),
+            };
+            self.files.insert(
+                relative,
+                ParsedFile {
+                    language,
+                    modified,
+                    functions,
+                },
+            );
+        }
+        progress.finish_phase();
+        self.files.retain(|path, _| seen.contains(path));
+        Ok(())
+    }
+
+    fn function(&self, id: &FunctionId) -> &FunctionDef {
+        &self.files[&id.0].functions[id.1]
+    }
+
+    fn definitions_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = FunctionId> + 'a {
+        self.files.iter().flat_map(move |(path, file)| {
+            file.functions
+                .iter()
+                .enumerate()
+                .filter(move |(_, function)| function.name == name)
+                .map(move |(index, _)| (path.clone(), index))
+        })
+    }
+}
+
+/// How a call name made in one file resolves to definitions.
+struct Resolver<'a> {
+    calls: &'a CallIndex,
+    exports: &'a SymbolIndex,
+}
+
+impl Resolver<'_> {
+    /// Definitions a call to `name` from `caller_path` may reach, and whether
+    /// the choice is ambiguous.
+    fn resolve(&self, caller_path: &Path, name: &str) -> (Vec<FunctionId>, bool) {
+        let Some(caller) = self.calls.files.get(caller_path) else {
+            return (Vec::new(), false);
+        };
+        let family = caller.language.family();
+        let mut candidates: Vec<FunctionId> = self
+            .calls
+            .definitions_named(name)
+            .filter(|(path, _)| self.calls.files[path].language.family() == family)
+            .collect();
+        candidates.sort();
+        if candidates.len() <= 1 {
+            return (candidates, false);
+        }
+        let local: Vec<FunctionId> = candidates
+            .iter()
+            .filter(|(path, _)| path == caller_path)
+            .cloned()
+            .collect();
+        if !local.is_empty() {
+            let ambiguous = local.len() > 1;
+            return (local, ambiguous);
+        }
+        let exported: BTreeSet<&Path> = self
+            .exports
+            .lookup(family, name)
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        let public: Vec<FunctionId> = candidates
+            .iter()
+            .filter(|(path, _)| exported.contains(path.as_path()))
+            .cloned()
+            .collect();
+        if public.len() == 1 {
+            return (public, false);
+        }
+        (candidates, true)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Edge {
+    from: FunctionId,
+    to: FunctionId,
+    lines: Vec<usize>,
+    ambiguous: bool,
+}
+
+/// The part of the graph reachable from the roots.
+#[derive(Debug, Default)]
+struct Subgraph {
+    /// Node and its signed distance from the roots: callers negative
+    nodes: BTreeMap<FunctionId, isize>,
+    edges: BTreeSet<Edge>,
+    /// Names called from the roots that resolve to no workspace function
+    external: BTreeSet<String>,
+    truncated: bool,
+}
+
+impl Subgraph {
+    fn build(
+        resolver: &Resolver<'_>,
+        roots: &[FunctionId],
+        direction: Direction,
+        depth: usize,
+        max_nodes: usize,
+    ) -> Self {
+        let mut graph = Subgraph::default();
+        for root in roots {
+            graph.nodes.insert(root.clone(), 0);
+            for call in &resolver.calls.function(root).calls {
+                if resolver.resolve(&root.0, &call.name).0.is_empty() {
+                    graph.external.insert(call.name.clone());
+                }
+            }
+        }
+        if matches!(direction, Direction::Callees | Direction::Both) {
+            graph.walk(roots, depth, max_nodes, 1, |id| callees(resolver, id));
+        }
+        if matches!(direction, Direction::Callers | Direction::Both) {
+            let callers = callers_by_callee(resolver);
+            graph.walk(roots, depth, max_nodes, -1, |id| {
+                callers.get(id).cloned().unwrap_or_default()
+            });
+        }
+        graph
+    }
+
+    /// Breadth-first walk; `step` is 1 towards callees and -1 towards callers.
+    fn walk(
+        &mut self,
+        roots: &[FunctionId],
+        depth: usize,
+        max_nodes: usize,
+        step: isize,
+        neighbours: impl Fn(&FunctionId) -> Vec<Edge>,
+    ) {
+        let mut queue: VecDeque<(FunctionId, usize)> =
+            roots.iter().map(|root| (root.clone(), 0)).collect();
+        let mut visited: HashSet<FunctionId> = roots.iter().cloned().collect();
+        while let Some((id, distance)) = queue.pop_front() {
+            if distance >= depth {
+                continue;
+            }
+            for edge in neighbours(&id) {
+                let next = if step > 0 { &edge.to } else { &edge.from }.clone();
+                if !self.nodes.contains_key(&next) {
+                    if self.nodes.len() >= max_nodes {
+                        self.truncated = true;
+                        continue;
+                    }
+                    self.nodes
+                        .insert(next.clone(), step * (distance as isize + 1));
+                }
+                self.edges.insert(edge);
+                if visited.insert(next.clone()) {
+                    queue.push_back((next, distance + 1));
+                }
+            }
+        }
+    }
+}
+
+/// Outgoing edges of `id`, one per resolved callee.
+fn callees(resolver: &Resolver<'_>, id: &FunctionId) -> Vec<Edge> {
+    let mut edges: BTreeMap<FunctionId, Edge> = BTreeMap::new();
+    for call in &resolver.calls.function(id).calls {
+        let (targets, ambiguous) = resolver.resolve(&id.0, &call.name);
+        for target in targets {
+            let edge = edges.entry(target.clone()).or_insert_with(|| Edge {
+                from: id.clone(),
+                to: target,
+                lines: Vec::new(),
+                ambiguous,
+            });
+            edge.lines.push(call.line);
+        }
+    }
+    edges.into_values().collect()
+}
+
+/// Incoming edges for every function in the workspace.
+fn callers_by_callee(resolver: &Resolver<'_>) -> HashMap<FunctionId, Vec<Edge>> {
+    let mut incoming: HashMap<FunctionId, Vec<Edge>> = HashMap::new();
+    for (path, file) in &resolver.calls.files {
+        for index in 0..file.functions.len() {
+            for edge in callees(resolver, &(path.clone(), index)) {
+                incoming.entry(edge.to.clone()).or_default().push(edge);
+            }
+        }
+    }
+    incoming
+}
+
+fn display_path(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+fn node_id(id: &FunctionId, calls: &CallIndex) -> String {
+    format!("{}:{}", display_path(&id.0), calls.function(id).line)
+}
+
+#[derive(Clone)]
+pub struct CallGraphTool {
+    workspace_root: PathBuf,
+    index: Arc<Mutex<CallIndex>>,
+    exports: Arc<Mutex<SymbolIndex>>,
+}
+
+impl CallGraphTool {
+    /// `exports` is the importable-definition index `organize_imports` keeps.
+    pub fn new(workspace_root: PathBuf, exports: Arc<Mutex<SymbolIndex>>) -> Self {
+        Self {
+            workspace_root,
+            index: Arc::new(Mutex::new(CallIndex::default())),
+            exports,
+        }
+    }
+
+    /// Drop the parsed call index; it is rebuilt on the next query.
+    pub fn clear_index(&self) {
+        *self.index.lock() = CallIndex::default();
+    }
+
+    fn graph_blocking(&self, input: CallGraphInput, progress: &mut Progress) -> Result<Value> {
+        let depth = input.depth.unwrap_or(DEFAULT_DEPTH).clamp(1, MAX_DEPTH);
+        let max_nodes = input.max_nodes.unwrap_or(DEFAULT_MAX_NODES).max(1);
+        let (container, name) = split_symbol(&input.symbol);
+        let filter_path = input
+            .path
+            .as_deref()
+            .map(|path| self.relative_path(path))
+            .transpose()?;
+
+        let mut analyzer = TreeSitterAnalyzer::new()?;
+        let mut calls = self.index.lock();
+        let mut exports = self.exports.lock();
+        calls.refresh(
+            &self.workspace_root,
+            exports.max_files(),
+            &mut analyzer,
+            progress,
+        )?;
+
+        let mut roots: Vec<FunctionId> = calls
+            .definitions_named(name)
+            .filter(|(path, index)| {
+                filter_path.as_ref().is_none_or(|filter| filter == path)
+                    && container.is_none_or(|container| {
+                        calls.files[path].functions[*index].container.as_deref() == Some(container)
+                    })
+            })
+            .collect();
+        roots.sort();
+        if roots.is_empty() {
+            return Err(anyhow!(
+                "Error: No function named '{}' found{}. Check the spelling or search with grep_search first.",
+                input.symbol,
+                input
+                    .path
+                    .as_deref()
+                    .map(|path| format!(" in {path}"))
+                    .unwrap_or_default()
+            ));
+        }
+
+        let families: HashSet<ImportLanguage> = calls
+            .files
+            .values()
+            .map(|file| file.language.family())
+            .collect();
+        for family in families {
+            if family != ImportLanguage::Go {
+                exports.refresh_with_progress(
+                    &self.workspace_root,
+                    family,
+                    &mut analyzer,
+                    progress,
+                )?;
+            }
+        }
+
+        let resolver = Resolver {
+            calls: &calls,
+            exports: &exports,
+        };
+        let graph = Subgraph::build(&resolver, &roots, input.direction, depth, max_nodes);
+        let mut result = self.describe(&calls, &roots, &graph, depth);
+        match input.render {
+            Rendering::None => {}
+            Rendering::Mermaid => result["diagram"] = json!(render_mermaid(&calls, &graph)),
+            Rendering::Dot => result["diagram"] = json!(render_dot(&calls, &graph)),
+        }
+        Ok(result)
+    }
+
+    fn describe(
+        &self,
+        calls: &CallIndex,
+        roots: &[FunctionId],
+        graph: &Subgraph,
+        depth: usize,
+    ) -> Value {
+        let node = |id: &FunctionId, distance: isize| {
+            let function = calls.function(id);
+            json!({
+                "id": node_id(id, calls),
+                "name": function.qualified_name(),
+                "path": display_path(&id.0),
+                "line": function.line,
+                "end_line": function.end_line,
+                "depth": distance.unsigned_abs(),
+            })
+        };
+        let by_direction = |keep: fn(isize) -> bool| -> Vec<Value> {
+            let mut nodes: Vec<(&FunctionId, isize)> = graph
+                .nodes
+                .iter()
+                .filter(|(_, distance)| keep(**distance))
+                .map(|(id, distance)| (id, *distance))
+                .collect();
+            nodes.sort_by_key(|(id, distance)| (distance.unsigned_abs(), (*id).clone()));
+            nodes
+                .into_iter()
+                .map(|(id, distance)| node(id, distance))
+                .collect()
+        };
+        let edges: Vec<Value> = graph
+            .edges
+            .iter()
+            .map(|edge| {
+                json!({
+                    "from": node_id(&edge.from, calls),
+                    "to": node_id(&edge.to, calls),
+                    "lines": edge.lines,
+                    "ambiguous": edge.ambiguous,
+                })
+            })
+            .collect();
+        let mut result = json!({
+            "success": true,
+            "definitions": roots.iter().map(|root| node(root, 0)).collect::<Vec<_>>(),
+            "callers": by_direction(|distance| distance < 0),
+            "callees": by_direction(|distance| distance > 0),
+            "edges": edges,
+            "external_calls": graph.external,
+            "depth": depth,
+            "truncated": graph.truncated,
+        });
+        if roots.len() > 1 {
+            result["note"] = json!(
+                "Several functions match this name; pass path or a qualified symbol such as Type::method to narrow it down."
+            );
+        }
+        if graph.edges.iter().any(|edge| edge.ambiguous) {
+            result["ambiguity"] = json!(
+                "Edges marked ambiguous were resolved by name only and may point at a same-named function that is not actually called."
+            );
+        }
+        result
+    }
+
+    fn relative_path(&self, path: &str) -> Result<PathBuf> {
+        let path = Path::new(path);
+        let relative = if path.is_absolute() {
+            path.strip_prefix(&self.workspace_root)
+                .map_err(|_| anyhow!("Path {} is outside the workspace", path.display()))?
+        } else {
+            path
+        };
+        Ok(relative
+            .components()
+            .filter(|component| !matches!(component, Component::CurDir))
+            .collect())
+    }
+}
+
+/// `("Config", "load")` for `Config::load` or `Config.load`.
+fn split_symbol(symbol: &str) -> (Option<&str>, &str) {
+    let symbol = symbol.trim().trim_end_matches("()");
+    let split = symbol
+        .rfind("::")
+        .map(|at| (at, 2))
+        .or_else(|| symbol.rfind('.').map(|at| (at, 1)));
+    match split {
+        Some((at, len)) => {
+            let container = &symbol[..at];
+            let container = container.rsplit([':', '.']).next().unwrap_or(container);
+            (Some(container), &symbol[at + len..])
+        }
+        None => (None, symbol),
+    }
+}
+
+fn node_label(calls: &CallIndex, id: &FunctionId) -> String {
+    format!(
+        "{}\\n{}",
+        calls.function(id).qualified_name(),
+        node_id(id, calls)
+    )
+}
+
+fn render_mermaid(calls: &CallIndex, graph: &Subgraph) -> String {
+    let keys: HashMap<&FunctionId, usize> = graph
+        .nodes
+        .keys()
+        .enumerate()
+        .map(|(i, id)| (id, i))
+        .collect();
+    let mut out = String::from("flowchart LR\n");
+    for (id, distance) in &graph.nodes {
+        let label = node_label(calls, id)
+            .replace("\\n", "<br/>")
+            .replace('"', "'");
+        out.push_str(&format!("    n{}[\"{}\"]\n", keys[id], label));
+        if *distance == 0 {
+            out.push_str(&format!("    style n{} stroke-width:3px\n", keys[id]));
+        }
+    }
+    for edge in &graph.edges {
+        let arrow = if edge.ambiguous { "-.->" } else { "-->" };
+        out.push_str(&format!(
+            "    n{} {} n{}\n",
+            keys[&edge.from], arrow, keys[&edge.to]
+        ));
+    }
+    out
+}
+
+fn render_dot(calls: &CallIndex, graph: &Subgraph) -> String {
+    let keys: HashMap<&FunctionId, usize> = graph
+        .nodes
+        .keys()
+        .enumerate()
+        .map(|(i, id)| (id, i))
+        .collect();
+    let mut out = String::from("digraph call_graph {\n    rankdir=LR;\n    node [shape=box];\n");
+    for (id, distance) in &graph.nodes {
+        let label = node_label(calls, id).replace('"', "\\\"");
+        let style = if *distance == 0 { ", style=bold" } else { "" };
+        out.push_str(&format!(
+            "    n{} [label=\"{}\"{}];\n",
+            keys[id], label, style
+        ));
+    }
+    for edge in &graph.edges {
+        let style = if edge.ambiguous {
+            " [style=dashed]"
+        } else {
+            ""
+        };
+        out.push_str(&format!(
+            "    n{} -> n{}{};\n",
+            keys[&edge.from], keys[&edge.to], style
+        ));
+    }
+    out.push_str("}\n");
+    out
+}
+
+#[async_trait]
+impl Tool for CallGraphTool {
+    async fn execute(&self, args: Value) -> Result<Value> {
+        self.execute_with_context(args, &ToolContext::default())
+            .await
+    }
+
+    async fn execute_with_context(&self, args: Value, context: &ToolContext) -> Result<Value> {
+        let input: CallGraphInput = serde_json::from_value(args).context(
+            "Error: Invalid 'call_graph' arguments. Required: { symbol: string }. Optional: { path: string, direction: \"callers\" | \"callees\" | \"both\", depth: integer, max_nodes: integer, render: \"mermaid\" | \"dot\" }",
+        )?;
+        let tool = self.clone();
+        let mut progress = context.progress();
+        tokio::task::spawn_blocking(move || tool.graph_blocking(input, &mut progress))
+            .await
+            .context("call_graph task failed")?
+    }
+
+    fn name(&self) -> &'static str {
+        tools::CALL_GRAPH
+    }
+
+    fn description(&self) -> &'static str {
+        "Find the callers and callees of a function across the workspace"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn workspace() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::write(
+            dir.path().join("src/main.rs"),
+            "fn main() {\n    let config = config::load();\n    run(&config);\n}\n\nfn run(config: &Config) {\n    println!(\"{}\", config.name);\n    helper();\n}\n\nfn helper() {}\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("src/config.rs"),
+            "pub fn load() -> Config {\n    parse(read())\n}\n\nfn read() -> String {\n    std::fs::read_to_string(\"a\").unwrap()\n}\n\nfn parse(raw: String) -> Config {\n    Config::new(raw)\n}\n\nfn helper() {}\n",
+        )
+        .unwrap();
+        dir
+    }
+
+    fn tool(dir: &TempDir) -> CallGraphTool {
+        CallGraphTool::new(
+            dir.path().to_path_buf(),
+            Arc::new(Mutex::new(SymbolIndex::new(1000))),
+        )
+    }
+
+    fn names(result: &Value, key: &str) -> Vec<String> {
+        result[key]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|node| node["name"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn walks_callers_and_callees_within_depth() {
+        let dir = workspace();
+        let tool = tool(&dir);
+
+        let result = tool
+            .execute(json!({"symbol": "load", "depth": 1}))
+            .await
+            .unwrap();
+        assert_eq!(names(&result, "definitions"), vec!["load"]);
+        assert_eq!(names(&result, "callers"), vec!["main"]);
+        assert_eq!(names(&result, "callees"), vec!["read", "parse"]);
+        assert!(result["external_calls"].as_array().unwrap().is_empty());
+
+        let result = tool
+            .execute(
+                json!({"symbol": "read", "direction": "callers", "depth": 3, "render": "mermaid"}),
+            )
+            .await
+            .unwrap();
+        assert_eq!(names(&result, "callers"), vec!["load", "main"]);
+        assert!(names(&result, "callees").is_empty());
+        let diagram = result["diagram"].as_str().unwrap();
+        assert!(diagram.starts_with("flowchart LR\n"));
+        assert_eq!(diagram.matches("-->").count(), 2);
+    }
+
+    #[tokio::test]
+    async fn prefers_same_file_definitions() {
+        let dir = workspace();
+        let result = tool(&dir)
+            .execute(json!({"symbol": "run", "direction": "callees", "render": "dot"}))
+            .await
+            .unwrap();
+        let callees = result["callees"].as_array().unwrap();
+        assert_eq!(callees.len(), 1);
+        assert_eq!(callees[0]["path"], "src/main.rs");
+        assert_eq!(result["edges"][0]["ambiguous"], false);
+        assert!(result["diagram"].as_str().unwrap().contains("->"));
+
+        let missing = tool(&dir)
+            .execute(json!({"symbol": "Config::missing"}))
+            .await;
+        assert!(missing.is_err());
+    }
+}
@@ -0,0 +1,180 @@
+//! Cancelling tool calls that are already running
+//!
+//! Each call gets a [`ToolContext`] whose token is cancelled when the user
+//! presses Esc or Ctrl+C. Tools that can run for long (ripgrep over a large
+//! tree, ast-grep rewrites, srgn, index walks) check it at natural boundaries
+//! and stop with [`ToolCancelled`]; external processes started through
+//! [`command_output`] or [`blocking_command_output`] are killed. The registry
+//! drops a call that has not stopped [`CANCEL_GRACE`] after cancellation, so
+//! control returns to the user promptly even for tools that never check.
+
+use std::io::Read;
+use std::process::{Output, Stdio};
+use std::time::Duration;
+
+use crate::core::progress::{Progress, ProgressSink};
+use anyhow::Result;
+pub use tokio_util::sync::CancellationToken;
+
+/// How long a cancelled call may take to stop before it is abandoned.
+pub const CANCEL_GRACE: Duration = Duration::from_millis(500);
+
+/// How often blocking waits look at the token.
+const POLL_INTERVAL: Duration = Duration::from_millis(25);
+
+/// A tool call stopped because the user cancelled it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ToolCancelled;
+
+impl std::fmt::Display for ToolCancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Tool call cancelled by the user before it finished")
+    }
+}
+
+impl std::error::Error for ToolCancelled {}
+
+/// Per-call state handed to tools.
+#[derive(Clone, Default)]
+pub struct ToolContext {
+    pub cancel: CancellationToken,
+    /// Where tools that scan the workspace report their progress
+    pub progress: Option<ProgressSink>,
+}
+
+impl ToolContext {
+    pub fn new(cancel: CancellationToken) -> Self {
+        Self {
+            cancel,
+            progress: None,
+        }
+    }
+
+    pub fn with_progress(mut self, progress: Option<ProgressSink>) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// A [`Progress`] reporting to the session, or a silent one.
+    pub fn progress(&self) -> Progress {
+        self.progress
+            .clone()
+            .map_or_else(Progress::disabled, Progress::new)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// `Err(ToolCancelled)` once the call has been cancelled.
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            Err(ToolCancelled.into())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Run `command` to completion, killing it if `cancel` fires first.
+pub async fn command_output(
+    command: &mut tokio::process::Command,
+    cancel: &CancellationToken,
+) -> Result<Output> {
+    command.kill_on_drop(true);
+    tokio::select! {
+        output = command.output() => Ok(output?),
+        // Dropping the output future kills the child
+        _ = cancel.cancelled() => Err(ToolCancelled.into()),
+    }
+}
+
+/// [`command_output`] for code already running on a blocking thread.
+pub fn blocking_command_output(
+    command: &mut std::process::Command,
+    cancel: &CancellationToken,
+) -> Result<Output> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    // Drain both pipes so a chatty child cannot block on a full buffer
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if cancel.is_cancelled() {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(ToolCancelled.into());
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    };
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+fn drain<R: Read + Send + 'static>(pipe: Option<R>) -> std::thread::JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut buffer = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buffer);
+        }
+        buffer
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[tokio::test]
+    async fn cancelling_kills_a_running_command() {
+        let cancel = CancellationToken::new();
+        let trigger = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            trigger.cancel();
+        });
+        let started = Instant::now();
+        let mut command = tokio::process::Command::new("sleep");
+        command.arg("10");
+        let err = command_output(&mut command, &cancel).await.unwrap_err();
+        assert!(err.downcast_ref::<ToolCancelled>().is_some());
+        assert!(started.elapsed() < Duration::from_secs(2));
+
+        let cancel = CancellationToken::new();
+        let mut command = tokio::process::Command::new("echo");
+        command.arg("done");
+        let output = command_output(&mut command, &cancel).await.unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "done");
+    }
+
+    #[test]
+    fn blocking_waits_stop_on_cancellation() {
+        let cancel = CancellationToken::new();
+        let output =
+            blocking_command_output(std::process::Command::new("echo").arg("hi"), &cancel).unwrap();
+        assert_eq!(output.stdout, b"hi\n");
+
+        let trigger = cancel.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            trigger.cancel();
+        });
+        let started = Instant::now();
+        let result =
+            blocking_command_output(std::process::Command::new("sleep").arg("10"), &cancel);
+        assert!(result.is_err());
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert!(ToolContext::new(cancel).check().is_err());
+    }
+}
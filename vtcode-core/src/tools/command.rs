@@ -2,22 +2,32 @@
 
 use super::traits::{ModeTool, Tool};
 use super::types::*;
+use super::workspace_env::WorkspaceEnv;
 use crate::config::constants::tools;
 use anyhow::{Context, Result, anyhow};
 use async_trait::async_trait;
 use serde_json::{Value, json};
-use std::{path::PathBuf, process::Stdio, time::Duration};
+use std::{path::PathBuf, process::Stdio, sync::Arc, time::Duration};
 use tokio::{process::Command, time::timeout};
 
 /// Command execution tool using standard process handling
 #[derive(Clone)]
 pub struct CommandTool {
     workspace_root: PathBuf,
+    env: Arc<WorkspaceEnv>,
 }
 
 impl CommandTool {
     pub fn new(workspace_root: PathBuf) -> Self {
-        Self { workspace_root }
+        Self {
+            workspace_root,
+            env: Arc::new(WorkspaceEnv::default()),
+        }
+    }
+
+    /// Variables from the workspace environment files passed to every command
+    pub fn set_env(&mut self, env: Arc<WorkspaceEnv>) {
+        self.env = env;
     }
 
     async fn execute_terminal_command(&self, input: &EnhancedTerminalInput) -> Result<Value> {
@@ -61,6 +71,7 @@ impl CommandTool {
         };
 
         cmd.current_dir(work_dir);
+        self.env.apply(&mut cmd);
         cmd.stdout(Stdio::piped());
         cmd.stderr(Stdio::piped());
 
@@ -79,7 +90,7 @@ impl CommandTool {
         let stdout = String::from_utf8_lossy(&output.stdout).to_string();
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
 
-        Ok(json!({
+        let mut result = json!({
             "success": output.status.success(),
             "exit_code": output.status.code().unwrap_or_default(),
             "stdout": stdout,
@@ -88,7 +99,11 @@ impl CommandTool {
             "pty_enabled": false,
             "command": command_str,
             "used_shell": has_shell_metacharacters
-        }))
+        });
+        if !self.env.is_empty() {
+            result["env_injected"] = json!(self.env.names());
+        }
+        Ok(result)
     }
 
     fn validate_command(&self, command: &[String]) -> Result<()> {
@@ -0,0 +1,507 @@
+//! Format-preserving JSON edits
+//!
+//! Rewriting a JSON file through `serde_json` would sort its keys and reflow
+//! its indentation, so edits splice text instead: the document is scanned into
+//! a tree of byte spans and only the span of the targeted value (or member) is
+//! replaced. `//` and `/* */` comments and trailing commas are accepted, which
+//! covers JSONC files such as `tsconfig.json` and editor settings.
+
+use super::key_path::{Segment, display_path};
+use anyhow::{Context, Result, anyhow, bail};
+use serde_json::{Map, Value};
+use std::ops::Range;
+
+const DEFAULT_INDENT: &str = "  ";
+
+#[derive(Debug)]
+enum Node {
+    Object {
+        span: Range<usize>,
+        members: Vec<Member>,
+    },
+    Array {
+        span: Range<usize>,
+        items: Vec<Node>,
+    },
+    Scalar {
+        span: Range<usize>,
+    },
+}
+
+#[derive(Debug)]
+struct Member {
+    key: String,
+    key_start: usize,
+    value: Node,
+}
+
+impl Node {
+    fn span(&self) -> &Range<usize> {
+        match self {
+            Self::Object { span, .. } | Self::Array { span, .. } | Self::Scalar { span } => span,
+        }
+    }
+
+    fn child(&self, segment: &Segment) -> Option<&Node> {
+        match self {
+            Self::Object { members, .. } => {
+                let key = segment.key().ok()?;
+                members
+                    .iter()
+                    .rev()
+                    .find(|member| member.key == key)
+                    .map(|member| &member.value)
+            }
+            Self::Array { items, .. } => items.get(segment.index()?),
+            Self::Scalar { .. } => None,
+        }
+    }
+
+    fn to_value(&self, text: &str) -> Result<Value> {
+        Ok(match self {
+            Self::Object { members, .. } => {
+                let mut map = Map::new();
+                for member in members {
+                    map.insert(member.key.clone(), member.value.to_value(text)?);
+                }
+                Value::Object(map)
+            }
+            Self::Array { items, .. } => Value::Array(
+                items
+                    .iter()
+                    .map(|item| item.to_value(text))
+                    .collect::<Result<_>>()?,
+            ),
+            Self::Scalar { span } => serde_json::from_str(&text[span.clone()])
+                .with_context(|| format!("Invalid JSON value '{}'", &text[span.clone()]))?,
+        })
+    }
+}
+
+struct Scanner<'a> {
+    text: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            bytes: text.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    fn error(&self, message: &str) -> anyhow::Error {
+        let line = self.text[..self.pos.min(self.text.len())]
+            .lines()
+            .count()
+            .max(1);
+        anyhow!("Invalid JSON at line {line}: {message}")
+    }
+
+    fn skip_trivia(&mut self) -> Result<()> {
+        loop {
+            match self.bytes.get(self.pos) {
+                Some(b' ' | b'\t' | b'\n' | b'\r') => self.pos += 1,
+                Some(b'/') if self.bytes.get(self.pos + 1) == Some(&b'/') => {
+                    while self.bytes.get(self.pos).is_some_and(|byte| *byte != b'\n') {
+                        self.pos += 1;
+                    }
+                }
+                Some(b'/') if self.bytes.get(self.pos + 1) == Some(&b'*') => {
+                    let end = self.text[self.pos + 2..]
+                        .find("*/")
+                        .ok_or_else(|| self.error("unterminated comment"))?;
+                    self.pos += end + 4;
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn document(&mut self) -> Result<Node> {
+        self.skip_trivia()?;
+        let node = self.value()?;
+        self.skip_trivia()?;
+        if self.pos < self.bytes.len() {
+            return Err(self.error("unexpected content after the document"));
+        }
+        Ok(node)
+    }
+
+    fn value(&mut self) -> Result<Node> {
+        match self.bytes.get(self.pos) {
+            Some(b'{') => self.object(),
+            Some(b'[') => self.array(),
+            Some(b'"') => {
+                let start = self.pos;
+                self.string()?;
+                Ok(Node::Scalar {
+                    span: start..self.pos,
+                })
+            }
+            Some(_) => {
+                let start = self.pos;
+                while self.bytes.get(self.pos).is_some_and(|byte| {
+                    !matches!(
+                        byte,
+                        b',' | b'}' | b']' | b' ' | b'\t' | b'\n' | b'\r' | b'/'
+                    )
+                }) {
+                    self.pos += 1;
+                }
+                if start == self.pos {
+                    return Err(self.error("expected a value"));
+                }
+                Ok(Node::Scalar {
+                    span: start..self.pos,
+                })
+            }
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let start = self.pos;
+        self.pos += 1;
+        loop {
+            match self.bytes.get(self.pos) {
+                Some(b'\\') => self.pos += 2,
+                Some(b'"') => {
+                    self.pos += 1;
+                    return serde_json::from_str(&self.text[start..self.pos])
+                        .map_err(|err| self.error(&err.to_string()));
+                }
+                Some(_) => self.pos += 1,
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Node> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut members = Vec::new();
+        loop {
+            self.skip_trivia()?;
+            match self.bytes.get(self.pos) {
+                Some(b'}') => break,
+                Some(b'"') => {}
+                _ => return Err(self.error("expected a key or '}'")),
+            }
+            let key_start = self.pos;
+            let key = self.string()?;
+            self.skip_trivia()?;
+            if self.bytes.get(self.pos) != Some(&b':') {
+                return Err(self.error("expected ':' after key"));
+            }
+            self.pos += 1;
+            self.skip_trivia()?;
+            let value = self.value()?;
+            members.push(Member {
+                key,
+                key_start,
+                value,
+            });
+            self.skip_trivia()?;
+            match self.bytes.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => break,
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+        self.pos += 1;
+        Ok(Node::Object {
+            span: start..self.pos,
+            members,
+        })
+    }
+
+    fn array(&mut self) -> Result<Node> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut items = Vec::new();
+        loop {
+            self.skip_trivia()?;
+            if self.bytes.get(self.pos) == Some(&b']') {
+                break;
+            }
+            items.push(self.value()?);
+            self.skip_trivia()?;
+            match self.bytes.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b']') => break,
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+        self.pos += 1;
+        Ok(Node::Array {
+            span: start..self.pos,
+            items,
+        })
+    }
+}
+
+fn scan(text: &str) -> Result<Node> {
+    Scanner::new(text).document()
+}
+
+/// Parse a JSON or JSONC document.
+pub(super) fn parse(text: &str) -> Result<Value> {
+    scan(text)?.to_value(text)
+}
+
+/// Set `path` to `value`, creating missing parent objects.
+pub(super) fn set(text: &str, path: &[Segment], value: &Value) -> Result<String> {
+    let root = scan(text)?;
+    let mut node = &root;
+    let mut depth = 0;
+    while depth < path.len() {
+        match node.child(&path[depth]) {
+            Some(child) => node = child,
+            None => break,
+        }
+        depth += 1;
+    }
+
+    if depth == path.len() {
+        let span = node.span().clone();
+        let rendered = render(value, text, span.start)?;
+        return Ok(splice(text, span, &rendered));
+    }
+
+    // Wrap the value in objects for the missing keys below the first one.
+    let mut nested = value.clone();
+    for segment in path[depth + 1..].iter().rev() {
+        let key = segment.key().with_context(|| {
+            format!(
+                "Cannot create array element {} inside a missing parent",
+                display_path(path)
+            )
+        })?;
+        let mut map = Map::new();
+        map.insert(key.to_string(), nested);
+        nested = Value::Object(map);
+    }
+    let segment = &path[depth];
+    match node {
+        Node::Object { span, members } => {
+            let key = segment.key()?;
+            let entry = format!("{}: ", serde_json::to_string(key)?);
+            insert_entry(
+                text,
+                span,
+                members.last().map(|m| (m.key_start, m.value.span().end)),
+                &entry,
+                &nested,
+            )
+        }
+        Node::Array { span, items } => {
+            let index = segment.index().ok_or_else(|| {
+                anyhow!("{} is an array; use an index", display_path(&path[..depth]))
+            })?;
+            if index != items.len() {
+                bail!(
+                    "Index {index} is out of range for {} ({} items); use {} to append",
+                    display_path(&path[..depth]),
+                    items.len(),
+                    items.len()
+                );
+            }
+            insert_entry(
+                text,
+                span,
+                items
+                    .last()
+                    .map(|item| (item.span().start, item.span().end)),
+                "",
+                &nested,
+            )
+        }
+        Node::Scalar { .. } => bail!(
+            "{} is a scalar value and has no '{}'",
+            display_path(&path[..depth]),
+            segment
+        ),
+    }
+}
+
+/// Remove the member or element at `path`; `None` when it does not exist.
+pub(super) fn delete(text: &str, path: &[Segment]) -> Result<Option<String>> {
+    let Some((last, parents)) = path.split_last() else {
+        bail!("Cannot delete the document root");
+    };
+    let root = scan(text)?;
+    let Some(parent) = parents
+        .iter()
+        .try_fold(&root, |node, segment| node.child(segment))
+    else {
+        return Ok(None);
+    };
+    // (start of the entry, end of its value) for every entry of the parent
+    let (span, entries, position) = match parent {
+        Node::Object { span, members } => {
+            let Ok(key) = last.key() else {
+                return Ok(None);
+            };
+            let Some(position) = members.iter().rposition(|member| member.key == key) else {
+                return Ok(None);
+            };
+            let entries: Vec<(usize, usize)> = members
+                .iter()
+                .map(|member| (member.key_start, member.value.span().end))
+                .collect();
+            (span, entries, position)
+        }
+        Node::Array { span, items } => {
+            let Some(position) = last.index().filter(|index| *index < items.len()) else {
+                return Ok(None);
+            };
+            let entries: Vec<(usize, usize)> = items
+                .iter()
+                .map(|item| (item.span().start, item.span().end))
+                .collect();
+            (span, entries, position)
+        }
+        Node::Scalar { .. } => return Ok(None),
+    };
+
+    let removed = if entries.len() == 1 {
+        // Only entry: leave an empty container.
+        span.start + 1..span.end - 1
+    } else if position + 1 < entries.len() {
+        entries[position].0..entries[position + 1].0
+    } else {
+        entries[position - 1].1..entries[position].1
+    };
+    Ok(Some(splice(text, removed, "")))
+}
+
+fn insert_entry(
+    text: &str,
+    container: &Range<usize>,
+    last_entry: Option<(usize, usize)>,
+    prefix: &str,
+    value: &Value,
+) -> Result<String> {
+    let multiline = text[container.clone()].contains('\n');
+    match last_entry {
+        Some((entry_start, entry_end)) => {
+            let insertion = if multiline {
+                let indent = line_indent(text, entry_start);
+                let rendered = render_with_indent(value, text, indent)?;
+                format!(",\n{indent}{prefix}{rendered}")
+            } else {
+                format!(", {prefix}{}", serde_json::to_string(value)?)
+            };
+            Ok(splice(text, entry_end..entry_end, &insertion))
+        }
+        None => {
+            let indent = line_indent(text, container.start);
+            let unit = indent_unit(text);
+            let inner = format!("{indent}{unit}");
+            let rendered = render_with_indent(value, text, &inner)?;
+            let body = format!("\n{inner}{prefix}{rendered}\n{indent}");
+            Ok(splice(text, container.start + 1..container.end - 1, &body))
+        }
+    }
+}
+
+/// Render `value` to replace the value starting at byte `at`.
+fn render(value: &Value, text: &str, at: usize) -> Result<String> {
+    render_with_indent(value, text, line_indent(text, at))
+}
+
+fn render_with_indent(value: &Value, text: &str, indent: &str) -> Result<String> {
+    let unit = indent_unit(text);
+    let mut buffer = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(unit.as_bytes());
+    let mut serializer = serde_json::Serializer::with_formatter(&mut buffer, formatter);
+    serde::Serialize::serialize(value, &mut serializer)?;
+    let pretty = String::from_utf8(buffer)?;
+    Ok(pretty.replace('\n', &format!("\n{indent}")))
+}
+
+/// Leading whitespace of the line containing byte `at`.
+fn line_indent(text: &str, at: usize) -> &str {
+    let line_start = text[..at].rfind('\n').map_or(0, |index| index + 1);
+    let line = &text[line_start..];
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
+
+/// Indentation step used by the document, from its first indented line.
+fn indent_unit(text: &str) -> &str {
+    text.lines()
+        .map(|line| &line[..line.len() - line.trim_start_matches([' ', '\t']).len()])
+        .find(|indent| !indent.is_empty())
+        .unwrap_or(DEFAULT_INDENT)
+}
+
+fn splice(text: &str, range: Range<usize>, replacement: &str) -> String {
+    let mut updated = String::with_capacity(text.len() + replacement.len());
+    updated.push_str(&text[..range.start]);
+    updated.push_str(replacement);
+    updated.push_str(&text[range.end..]);
+    updated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::key_path::parse_key_path;
+    use super::*;
+    use serde_json::json;
+
+    const PACKAGE: &str = "{\n  // scripts run by npm\n  \"name\": \"app\",\n  \"scripts\": {\n    \"test\": \"jest\"\n  },\n  \"private\": true\n}\n";
+
+    #[test]
+    fn replaces_and_inserts_without_reformatting() {
+        let path = parse_key_path("scripts.test").unwrap();
+        let updated = set(PACKAGE, &path, &json!("vitest")).unwrap();
+        assert_eq!(updated, PACKAGE.replace("\"jest\"", "\"vitest\""));
+
+        let path = parse_key_path("scripts.lint").unwrap();
+        let updated = set(PACKAGE, &path, &json!("eslint .")).unwrap();
+        assert!(updated.contains("    \"test\": \"jest\",\n    \"lint\": \"eslint .\"\n  },"));
+        assert!(updated.contains("// scripts run by npm"));
+
+        let path = parse_key_path("engines.node").unwrap();
+        let updated = set(PACKAGE, &path, &json!(">=18")).unwrap();
+        assert!(
+            updated.ends_with(
+                "  \"private\": true,\n  \"engines\": {\n    \"node\": \">=18\"\n  }\n}\n"
+            )
+        );
+        assert_eq!(parse(&updated).unwrap()["engines"]["node"], ">=18");
+    }
+
+    #[test]
+    fn deletes_members_and_elements() {
+        let path = parse_key_path("name").unwrap();
+        let updated = delete(PACKAGE, &path).unwrap().unwrap();
+        assert!(updated.starts_with("{\n  // scripts run by npm\n  \"scripts\""));
+
+        let path = parse_key_path("private").unwrap();
+        let updated = delete(PACKAGE, &path).unwrap().unwrap();
+        assert!(updated.ends_with("    \"test\": \"jest\"\n  }\n}\n"));
+        parse(&updated).unwrap();
+
+        let compact = r#"{"files": ["a", "b", "c"]}"#;
+        let path = parse_key_path("files[1]").unwrap();
+        assert_eq!(
+            delete(compact, &path).unwrap().unwrap(),
+            r#"{"files": ["a", "c"]}"#
+        );
+        let path = parse_key_path("files[3]").unwrap();
+        assert_eq!(
+            set(compact, &path, &json!("d")).unwrap(),
+            r#"{"files": ["a", "b", "c", "d"]}"#
+        );
+        assert!(
+            delete(compact, &parse_key_path("missing").unwrap())
+                .unwrap()
+                .is_none()
+        );
+    }
+}
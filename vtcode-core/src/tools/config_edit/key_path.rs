@@ -0,0 +1,152 @@
+//! Dotted key paths such as `package.dependencies.serde` or `servers[0].port`
+
+use anyhow::{Result, anyhow, bail};
+use serde_json::Value;
+use std::fmt;
+
+/// One step of a key path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+impl Segment {
+    /// Array position named by this segment; plain numeric keys count too, so
+    /// `servers.0` and `servers[0]` are equivalent.
+    pub(super) fn index(&self) -> Option<usize> {
+        match self {
+            Self::Index(index) => Some(*index),
+            Self::Key(key) => key.parse().ok(),
+        }
+    }
+
+    pub(super) fn key(&self) -> Result<&str> {
+        match self {
+            Self::Key(key) => Ok(key),
+            Self::Index(index) => Err(anyhow!("[{index}] indexes an array, but found a table")),
+        }
+    }
+}
+
+impl fmt::Display for Segment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Key(key) => write!(f, "{key}"),
+            Self::Index(index) => write!(f, "[{index}]"),
+        }
+    }
+}
+
+/// Parse `a.b."c.d"[0].e` into segments. An empty path addresses the document root.
+pub(super) fn parse_key_path(path: &str) -> Result<Vec<Segment>> {
+    let mut segments = Vec::new();
+    let mut chars = path.trim().chars().peekable();
+    let mut expect_key = true;
+    while let Some(ch) = chars.next() {
+        match ch {
+            '.' if !expect_key => expect_key = true,
+            '[' => {
+                let digits: String = chars.by_ref().take_while(|ch| *ch != ']').collect();
+                let index = digits
+                    .trim()
+                    .parse()
+                    .map_err(|_| anyhow!("Invalid array index '[{digits}]' in '{path}'"))?;
+                segments.push(Segment::Index(index));
+                expect_key = false;
+            }
+            '"' | '\'' if expect_key => {
+                let mut key = String::new();
+                let mut closed = false;
+                while let Some(next) = chars.next() {
+                    match next {
+                        '\\' if ch == '"' => key.extend(chars.next()),
+                        _ if next == ch => {
+                            closed = true;
+                            break;
+                        }
+                        _ => key.push(next),
+                    }
+                }
+                if !closed {
+                    bail!("Unterminated quoted key in '{path}'");
+                }
+                segments.push(Segment::Key(key));
+                expect_key = false;
+            }
+            _ if expect_key && !matches!(ch, '.' | ']') => {
+                let mut key = String::from(ch);
+                while let Some(next) = chars.peek() {
+                    if matches!(next, '.' | '[') {
+                        break;
+                    }
+                    key.push(*next);
+                    chars.next();
+                }
+                segments.push(Segment::Key(key.trim().to_string()));
+                expect_key = false;
+            }
+            _ => bail!("Unexpected '{ch}' in key path '{path}'"),
+        }
+    }
+    if expect_key && !segments.is_empty() {
+        bail!("Key path '{path}' ends with '.'");
+    }
+    Ok(segments)
+}
+
+pub(super) fn display_path(path: &[Segment]) -> String {
+    let mut rendered = String::new();
+    for segment in path {
+        match segment {
+            Segment::Key(key) => {
+                if !rendered.is_empty() {
+                    rendered.push('.');
+                }
+                if key.is_empty() || key.contains(['.', '[', ']', '"']) {
+                    rendered.push_str(&format!("{key:?}"));
+                } else {
+                    rendered.push_str(key);
+                }
+            }
+            Segment::Index(index) => rendered.push_str(&format!("[{index}]")),
+        }
+    }
+    rendered
+}
+
+/// Look up `path` in a parsed document.
+pub(super) fn lookup<'a>(value: &'a Value, path: &[Segment]) -> Option<&'a Value> {
+    path.iter()
+        .try_fold(value, |current, segment| match current {
+            Value::Object(map) => map.get(segment.key().ok()?),
+            Value::Array(items) => items.get(segment.index()?),
+            _ => None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_quoted_keys_and_indices() {
+        assert_eq!(
+            parse_key_path(r#"servers[0].tags."a.b".1"#).unwrap(),
+            vec![
+                Segment::Key("servers".into()),
+                Segment::Index(0),
+                Segment::Key("tags".into()),
+                Segment::Key("a.b".into()),
+                Segment::Key("1".into()),
+            ]
+        );
+        assert!(parse_key_path("").unwrap().is_empty());
+        assert!(parse_key_path("a.").is_err());
+        assert!(parse_key_path("a..b").is_err());
+        assert_eq!(
+            display_path(&parse_key_path(r#"a."b.c"[2]"#).unwrap()),
+            r#"a."b.c"[2]"#
+        );
+    }
+}
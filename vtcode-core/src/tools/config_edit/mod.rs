@@ -0,0 +1,345 @@
+//! Structural edits to TOML, YAML and JSON configuration files
+//!
+//! [`ConfigEditTool`] reads, sets and deletes values by key path, e.g.
+//! `dependencies.serde` in `Cargo.toml` or `jobs.test.steps[0].run` in a CI
+//! workflow, instead of asking the model to patch configuration text. TOML and
+//! JSON edits splice only the changed value so comments, key order and
+//! indentation survive; YAML is re-serialized. Every edit is re-parsed before
+//! it is written, and the write is refused unless the key now holds the
+//! requested value.
+
+mod json;
+mod key_path;
+mod toml;
+mod yaml;
+
+use crate::config::constants::tools;
+use crate::tools::traits::Tool;
+use crate::tools::write_quota::WriteQuota;
+use crate::ui::diff_renderer::generate_unified_diff;
+use anyhow::{Context, Result, anyhow, bail};
+use async_trait::async_trait;
+use key_path::{Segment, display_path, lookup, parse_key_path};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::path::{Path, PathBuf};
+
+const MAX_DIFF_LINES: usize = 120;
+
+/// A configuration file format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        if matches!(name, "Pipfile" | "Cargo.lock" | "poetry.lock") {
+            return Some(Self::Toml);
+        }
+        match path.extension()?.to_str()? {
+            "toml" => Some(Self::Toml),
+            "yaml" | "yml" => Some(Self::Yaml),
+            "json" | "jsonc" | "json5" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Toml => "toml",
+            Self::Yaml => "yaml",
+            Self::Json => "json",
+        }
+    }
+
+    fn parse(self, text: &str) -> Result<Value> {
+        match self {
+            Self::Toml => toml::parse(text),
+            Self::Yaml => yaml::parse(text),
+            Self::Json => json::parse(text),
+        }
+    }
+
+    fn set(self, text: &str, path: &[Segment], value: &Value) -> Result<String> {
+        match self {
+            Self::Toml => toml::set(text, path, value),
+            Self::Yaml => yaml::set(text, path, value),
+            Self::Json => json::set(text, path, value),
+        }
+    }
+
+    fn delete(self, text: &str, path: &[Segment]) -> Result<Option<String>> {
+        match self {
+            Self::Toml => toml::delete(text, path),
+            Self::Yaml => yaml::delete(text, path),
+            Self::Json => json::delete(text, path),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ConfigAction {
+    Get,
+    Set,
+    Delete,
+}
+
+#[derive(Debug, Deserialize)]
+struct ConfigEditInput {
+    path: String,
+    action: ConfigAction,
+    #[serde(default)]
+    key: String,
+    #[serde(default)]
+    value: Option<Value>,
+    #[serde(default)]
+    format: Option<ConfigFormat>,
+    #[serde(default)]
+    dry_run: bool,
+}
+
+#[derive(Clone)]
+pub struct ConfigEditTool {
+    workspace_root: PathBuf,
+    quota: WriteQuota,
+}
+
+impl ConfigEditTool {
+    pub fn new(workspace_root: PathBuf) -> Self {
+        Self {
+            workspace_root,
+            quota: WriteQuota::default(),
+        }
+    }
+
+    /// Share the write limits of the file tools.
+    pub fn set_write_quota(&mut self, quota: WriteQuota) {
+        self.quota = quota;
+    }
+
+    fn run(&self, input: ConfigEditInput) -> Result<Value> {
+        let absolute = self.resolve_path(&input.path)?;
+        let display = absolute
+            .strip_prefix(&self.workspace_root)
+            .unwrap_or(&absolute)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let format = input
+            .format
+            .or_else(|| ConfigFormat::from_path(&absolute))
+            .ok_or_else(|| {
+                anyhow!("Cannot tell the format of {display}; pass format: toml, yaml or json")
+            })?;
+        let key = parse_key_path(&input.key)?;
+        let source = std::fs::read_to_string(&absolute)
+            .with_context(|| format!("Failed to read {}", display))?;
+        let document = format
+            .parse(&source)
+            .with_context(|| format!("Failed to parse {display}"))?;
+        let previous = lookup(&document, &key).cloned();
+
+        let mut result = json!({
+            "success": true,
+            "path": display,
+            "format": format.name(),
+            "key": display_path(&key),
+        });
+
+        let (updated, expected) = match input.action {
+            ConfigAction::Get => {
+                result["exists"] = json!(previous.is_some());
+                result["value"] = previous.unwrap_or(Value::Null);
+                return Ok(result);
+            }
+            ConfigAction::Set => {
+                let value = input
+                    .value
+                    .ok_or_else(|| anyhow!("action 'set' requires a value"))?;
+                if previous.as_ref() == Some(&value) {
+                    (source.clone(), Some(value))
+                } else {
+                    (format.set(&source, &key, &value)?, Some(value))
+                }
+            }
+            ConfigAction::Delete => {
+                let expected = shifted_element(&document, &key);
+                match format.delete(&source, &key)? {
+                    Some(updated) => (updated, expected),
+                    None => (source.clone(), expected),
+                }
+            }
+        };
+
+        let changed = updated != source;
+        if changed {
+            validate(format, &updated, &key, expected.as_ref())
+                .with_context(|| format!("Edit to {display} was not written"))?;
+            if !input.dry_run {
+                self.quota.reserve(&display, updated.len() as u64, false)?;
+                std::fs::write(&absolute, &updated)
+                    .with_context(|| format!("Failed to write {}", display))?;
+            }
+            result["diff"] = json!(truncate_diff(&generate_unified_diff(
+                &source, &updated, &display
+            )));
+            if format == ConfigFormat::Yaml && yaml::has_comments(&source) {
+                result["warning"] =
+                    json!("YAML is re-serialized on edit; comments in this file were not kept");
+            }
+        }
+        result["changed"] = json!(changed);
+        result["dry_run"] = json!(input.dry_run);
+        result["previous"] = previous.unwrap_or(Value::Null);
+        Ok(result)
+    }
+
+    fn resolve_path(&self, path: &str) -> Result<PathBuf> {
+        let candidate = self.workspace_root.join(path);
+        let canonical = candidate
+            .canonicalize()
+            .with_context(|| format!("File not found: {}", path))?;
+        let root = self
+            .workspace_root
+            .canonicalize()
+            .unwrap_or_else(|_| self.workspace_root.clone());
+        if !canonical.starts_with(&root) {
+            return Err(anyhow!("Path {} is outside the workspace", path));
+        }
+        Ok(self
+            .workspace_root
+            .join(canonical.strip_prefix(&root).unwrap_or(&canonical)))
+    }
+}
+
+/// Re-parse the edited text and check that `key` holds `expected`.
+fn validate(
+    format: ConfigFormat,
+    text: &str,
+    key: &[Segment],
+    expected: Option<&Value>,
+) -> Result<()> {
+    let document = format
+        .parse(text)
+        .context("the edited file does not parse")?;
+    let actual = lookup(&document, key);
+    if actual != expected {
+        bail!(
+            "{} reads back as {} after the edit",
+            display_path(key),
+            actual.map_or_else(|| "missing".to_string(), Value::to_string)
+        );
+    }
+    Ok(())
+}
+
+/// The array element that moves into `key` once the element there is deleted.
+fn shifted_element(document: &Value, key: &[Segment]) -> Option<Value> {
+    let (last, parents) = key.split_last()?;
+    match lookup(document, parents)? {
+        Value::Array(items) => items.get(last.index()? + 1).cloned(),
+        _ => None,
+    }
+}
+
+fn truncate_diff(diff: &str) -> String {
+    let total = diff.lines().count();
+    if total <= MAX_DIFF_LINES {
+        return diff.to_string();
+    }
+    let mut kept: Vec<&str> = diff.lines().take(MAX_DIFF_LINES).collect();
+    let note = format!("... {} more diff lines", total - MAX_DIFF_LINES);
+    kept.push(&note);
+    kept.join("\n")
+}
+
+#[async_trait]
+impl Tool for ConfigEditTool {
+    async fn execute(&self, args: Value) -> Result<Value> {
+        let input: ConfigEditInput = serde_json::from_value(args).context(
+            "Error: Invalid 'config_edit' arguments. Required: { path: string, action: 'get' | 'set' | 'delete' }. Optional: { key: string, value: any, format: 'toml' | 'yaml' | 'json', dry_run: boolean }",
+        )?;
+        let tool = self.clone();
+        tokio::task::spawn_blocking(move || tool.run(input))
+            .await
+            .context("config_edit task failed")?
+    }
+
+    fn name(&self) -> &'static str {
+        tools::CONFIG_EDIT
+    }
+
+    fn description(&self) -> &'static str {
+        "Get, set or delete values in TOML, YAML and JSON config files by key path"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn edits_config_files_in_the_workspace() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let manifest = "[package]\nname = \"demo\"\n\n[dependencies]\n# logging\nlog = \"0.4\"\n";
+        std::fs::write(dir.path().join("Cargo.toml"), manifest).expect("manifest");
+        let tool = ConfigEditTool::new(dir.path().to_path_buf());
+
+        let got = tool
+            .execute(json!({"path": "Cargo.toml", "action": "get", "key": "dependencies"}))
+            .await
+            .expect("get");
+        assert_eq!(got["value"], json!({"log": "0.4"}));
+
+        let preview = tool
+            .execute(json!({
+                "path": "Cargo.toml",
+                "action": "set",
+                "key": "dependencies.serde",
+                "value": "1.0",
+                "dry_run": true,
+            }))
+            .await
+            .expect("dry run");
+        assert_eq!(preview["changed"], json!(true));
+        assert!(
+            preview["diff"]
+                .as_str()
+                .unwrap()
+                .contains("+serde = \"1.0\"")
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("Cargo.toml")).expect("read"),
+            manifest
+        );
+
+        tool.execute(json!({
+            "path": "Cargo.toml",
+            "action": "set",
+            "key": "dependencies.serde",
+            "value": "1.0",
+        }))
+        .await
+        .expect("set");
+        let deleted = tool
+            .execute(json!({"path": "Cargo.toml", "action": "delete", "key": "package.name"}))
+            .await
+            .expect("delete");
+        assert_eq!(deleted["previous"], json!("demo"));
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("Cargo.toml")).expect("read"),
+            "[package]\n\n[dependencies]\n# logging\nlog = \"0.4\"\nserde = \"1.0\"\n"
+        );
+
+        assert!(
+            tool.execute(json!({"path": "../outside.toml", "action": "get"}))
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,373 @@
+//! Format-preserving TOML edits through `toml_edit`
+//!
+//! Replaced values keep the whitespace and trailing comment of the value they
+//! replace. Objects written into the document root become `[table]` sections;
+//! deeper objects are written as inline tables, the usual Cargo style for
+//! entries such as `serde = { version = "1", features = ["derive"] }`.
+
+use super::key_path::{Segment, display_path};
+use anyhow::{Context, Result, anyhow, bail};
+use serde_json::{Map, Number, Value};
+use toml_edit::{Array, ArrayOfTables, DocumentMut, InlineTable, Item, Table};
+
+/// Parse a TOML document into JSON; datetimes become strings.
+pub(super) fn parse(text: &str) -> Result<Value> {
+    let value: toml::Value = toml::from_str(text).context("Invalid TOML")?;
+    Ok(to_json(value))
+}
+
+fn to_json(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(text) => Value::String(text),
+        toml::Value::Integer(number) => Value::from(number),
+        toml::Value::Float(number) => Number::from_f64(number).map_or(Value::Null, Value::Number),
+        toml::Value::Boolean(flag) => Value::Bool(flag),
+        toml::Value::Datetime(datetime) => Value::String(datetime.to_string()),
+        toml::Value::Array(items) => Value::Array(items.into_iter().map(to_json).collect()),
+        toml::Value::Table(table) => Value::Object(
+            table
+                .into_iter()
+                .map(|(key, value)| (key, to_json(value)))
+                .collect::<Map<_, _>>(),
+        ),
+    }
+}
+
+/// Set `path` to `value`, creating missing tables.
+pub(super) fn set(text: &str, path: &[Segment], value: &Value) -> Result<String> {
+    if path.is_empty() {
+        bail!("Cannot replace the whole document; set individual keys instead");
+    }
+    let mut document: DocumentMut = text.parse().context("Invalid TOML")?;
+    set_in_table(document.as_table_mut(), path, 0, value, true)?;
+    Ok(document.to_string())
+}
+
+/// Remove `path`; `None` when it does not exist.
+pub(super) fn delete(text: &str, path: &[Segment]) -> Result<Option<String>> {
+    if path.is_empty() {
+        bail!("Cannot delete the document root");
+    }
+    let mut document: DocumentMut = text.parse().context("Invalid TOML")?;
+    let removed = delete_in_table(document.as_table_mut(), path);
+    Ok(removed.then(|| document.to_string()))
+}
+
+fn set_in_table(
+    table: &mut Table,
+    path: &[Segment],
+    depth: usize,
+    value: &Value,
+    root: bool,
+) -> Result<()> {
+    let key = path[depth].key()?;
+    if depth + 1 == path.len() {
+        let mut item = if root && value.is_object() {
+            Item::Table(to_table(value, path)?)
+        } else {
+            Item::Value(to_value(value, path)?)
+        };
+        if let Some(existing) = table.get_mut(key) {
+            keep_decor(existing, &mut item);
+            *existing = item;
+        } else {
+            table.insert(key, item);
+        }
+        return Ok(());
+    }
+    if !table.contains_key(key) {
+        if matches!(path[depth + 1], Segment::Index(_)) {
+            bail!(
+                "{} does not exist; set it to an array first",
+                display_path(&path[..=depth])
+            );
+        }
+        let mut child = Table::new();
+        child.set_implicit(true);
+        table.insert(key, Item::Table(child));
+    }
+    let child = table.get_mut(key).expect("inserted above");
+    set_in_item(child, path, depth + 1, value)
+}
+
+fn set_in_item(item: &mut Item, path: &[Segment], depth: usize, value: &Value) -> Result<()> {
+    match item {
+        Item::Table(table) => set_in_table(table, path, depth, value, false),
+        Item::Value(inner) => set_in_value(inner, path, depth, value),
+        Item::ArrayOfTables(tables) => set_in_tables(tables, path, depth, value),
+        Item::None => bail!("{} does not exist", display_path(&path[..depth])),
+    }
+}
+
+fn set_in_tables(
+    tables: &mut ArrayOfTables,
+    path: &[Segment],
+    depth: usize,
+    value: &Value,
+) -> Result<()> {
+    let index = array_index(path, depth)?;
+    if depth + 1 == path.len() {
+        let table = to_table(value, path)?;
+        match index.cmp(&tables.len()) {
+            std::cmp::Ordering::Less => {
+                *tables.get_mut(index).expect("index checked") = table;
+            }
+            std::cmp::Ordering::Equal => tables.push(table),
+            std::cmp::Ordering::Greater => return Err(out_of_range(path, depth, tables.len())),
+        }
+        return Ok(());
+    }
+    let len = tables.len();
+    let table = tables
+        .get_mut(index)
+        .ok_or_else(|| out_of_range(path, depth, len))?;
+    set_in_table(table, path, depth + 1, value, false)
+}
+
+fn set_in_value(
+    current: &mut toml_edit::Value,
+    path: &[Segment],
+    depth: usize,
+    value: &Value,
+) -> Result<()> {
+    let last = depth + 1 == path.len();
+    match current {
+        toml_edit::Value::InlineTable(table) => {
+            let key = path[depth].key()?;
+            if last {
+                let mut replacement = to_value(value, path)?;
+                match table.get_mut(key) {
+                    Some(existing) => {
+                        *replacement.decor_mut() = existing.decor().clone();
+                        *existing = replacement;
+                    }
+                    None => {
+                        table.insert(key, replacement);
+                    }
+                }
+                return Ok(());
+            }
+            if !table.contains_key(key) {
+                table.insert(key, toml_edit::Value::InlineTable(InlineTable::new()));
+            }
+            let child = table.get_mut(key).expect("inserted above");
+            set_in_value(child, path, depth + 1, value)
+        }
+        toml_edit::Value::Array(items) => {
+            let index = array_index(path, depth)?;
+            let len = items.len();
+            if last {
+                let replacement = to_value(value, path)?;
+                match index.cmp(&len) {
+                    std::cmp::Ordering::Less => {
+                        items.replace(index, replacement);
+                    }
+                    std::cmp::Ordering::Equal => items.push(replacement),
+                    std::cmp::Ordering::Greater => return Err(out_of_range(path, depth, len)),
+                }
+                return Ok(());
+            }
+            let child = items
+                .get_mut(index)
+                .ok_or_else(|| out_of_range(path, depth, len))?;
+            set_in_value(child, path, depth + 1, value)
+        }
+        _ => bail!(
+            "{} is a scalar value and has no '{}'",
+            display_path(&path[..depth]),
+            path[depth]
+        ),
+    }
+}
+
+fn delete_in_table(table: &mut Table, path: &[Segment]) -> bool {
+    let Ok(key) = path[0].key() else {
+        return false;
+    };
+    if path.len() == 1 {
+        return table.remove(key).is_some();
+    }
+    match table.get_mut(key) {
+        Some(item) => delete_in_item(item, &path[1..]),
+        None => false,
+    }
+}
+
+fn delete_in_item(item: &mut Item, path: &[Segment]) -> bool {
+    match item {
+        Item::Table(table) => delete_in_table(table, path),
+        Item::Value(value) => delete_in_value(value, path),
+        Item::ArrayOfTables(tables) => {
+            let Some(index) = path[0].index().filter(|index| *index < tables.len()) else {
+                return false;
+            };
+            if path.len() == 1 {
+                tables.remove(index);
+                return true;
+            }
+            tables
+                .get_mut(index)
+                .is_some_and(|table| delete_in_table(table, &path[1..]))
+        }
+        Item::None => false,
+    }
+}
+
+fn delete_in_value(value: &mut toml_edit::Value, path: &[Segment]) -> bool {
+    match value {
+        toml_edit::Value::InlineTable(table) => {
+            let Ok(key) = path[0].key() else {
+                return false;
+            };
+            if path.len() == 1 {
+                return table.remove(key).is_some();
+            }
+            table
+                .get_mut(key)
+                .is_some_and(|child| delete_in_value(child, &path[1..]))
+        }
+        toml_edit::Value::Array(items) => {
+            let Some(index) = path[0].index().filter(|index| *index < items.len()) else {
+                return false;
+            };
+            if path.len() == 1 {
+                items.remove(index);
+                return true;
+            }
+            items
+                .get_mut(index)
+                .is_some_and(|child| delete_in_value(child, &path[1..]))
+        }
+        _ => false,
+    }
+}
+
+/// Carry the surrounding whitespace and comments of `existing` over to `item`.
+fn keep_decor(existing: &Item, item: &mut Item) {
+    match (existing, item) {
+        (Item::Value(old), Item::Value(new)) => *new.decor_mut() = old.decor().clone(),
+        (Item::Table(old), Item::Table(new)) => {
+            *new.decor_mut() = old.decor().clone();
+            if let Some(position) = old.position() {
+                new.set_position(position);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn array_index(path: &[Segment], depth: usize) -> Result<usize> {
+    path[depth]
+        .index()
+        .ok_or_else(|| anyhow!("{} is an array; use an index", display_path(&path[..depth])))
+}
+
+fn out_of_range(path: &[Segment], depth: usize, len: usize) -> anyhow::Error {
+    anyhow!(
+        "Index {} is out of range for {} ({len} items); use {len} to append",
+        path[depth],
+        display_path(&path[..depth])
+    )
+}
+
+fn to_table(value: &Value, path: &[Segment]) -> Result<Table> {
+    let Value::Object(map) = value else {
+        bail!("{} must be set to an object", display_path(path));
+    };
+    let mut table = Table::new();
+    for (key, value) in map {
+        table.insert(key, Item::Value(to_value(value, path)?));
+    }
+    Ok(table)
+}
+
+fn to_value(value: &Value, path: &[Segment]) -> Result<toml_edit::Value> {
+    Ok(match value {
+        Value::Null => bail!(
+            "TOML has no null; use action 'delete' to remove {}",
+            display_path(path)
+        ),
+        Value::Bool(flag) => (*flag).into(),
+        Value::Number(number) => match number.as_i64() {
+            Some(integer) => integer.into(),
+            None => number
+                .as_f64()
+                .ok_or_else(|| anyhow!("Unsupported number {number}"))?
+                .into(),
+        },
+        Value::String(text) => text.as_str().into(),
+        Value::Array(items) => {
+            let mut array = Array::new();
+            for item in items {
+                array.push(to_value(item, path)?);
+            }
+            toml_edit::Value::Array(array)
+        }
+        Value::Object(map) => {
+            let mut table = InlineTable::new();
+            for (key, value) in map {
+                table.insert(key, to_value(value, path)?);
+            }
+            toml_edit::Value::InlineTable(table)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::key_path::parse_key_path;
+    use super::*;
+    use serde_json::json;
+
+    const MANIFEST: &str = "[package]\nname = \"demo\" # crate name\nversion = \"0.1.0\"\n\n[dependencies]\nanyhow = \"1\"\n\n[[bin]]\nname = \"demo\"\n";
+
+    #[test]
+    fn edits_keep_comments_and_layout() {
+        let path = parse_key_path("package.name").unwrap();
+        let updated = set(MANIFEST, &path, &json!("renamed")).unwrap();
+        assert_eq!(
+            updated,
+            MANIFEST.replacen("\"demo\" # crate name", "\"renamed\" # crate name", 1)
+        );
+
+        let path = parse_key_path("dependencies.serde").unwrap();
+        let updated = set(
+            MANIFEST,
+            &path,
+            &json!({"version": "1", "features": ["derive"]}),
+        )
+        .unwrap();
+        assert!(
+            updated
+                .contains("anyhow = \"1\"\nserde = { features = [\"derive\"], version = \"1\" }\n")
+        );
+
+        let path = parse_key_path("workspace.members").unwrap();
+        let updated = set(MANIFEST, &path, &json!(["crates/*"])).unwrap();
+        assert!(updated.contains("[workspace]\nmembers = [\"crates/*\"]"));
+
+        let path = parse_key_path("bin[0].path").unwrap();
+        let updated = set(MANIFEST, &path, &json!("src/main.rs")).unwrap();
+        assert_eq!(parse(&updated).unwrap()["bin"][0]["path"], "src/main.rs");
+    }
+
+    #[test]
+    fn deletes_keys_and_rejects_null() {
+        let path = parse_key_path("dependencies.anyhow").unwrap();
+        let updated = delete(MANIFEST, &path).unwrap().unwrap();
+        assert!(updated.contains("[dependencies]\n\n[[bin]]"));
+        assert!(
+            delete(MANIFEST, &parse_key_path("dependencies.missing").unwrap())
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            set(
+                MANIFEST,
+                &parse_key_path("package.name").unwrap(),
+                &Value::Null
+            )
+            .is_err()
+        );
+    }
+}
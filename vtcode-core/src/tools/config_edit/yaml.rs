@@ -0,0 +1,174 @@
+//! YAML edits through `serde_yaml`
+//!
+//! `serde_yaml` does not keep comments or anchors, so edited YAML files are
+//! re-serialized; the tool reports a warning when that drops comments.
+
+use super::key_path::{Segment, display_path};
+use anyhow::{Context, Result, anyhow, bail};
+use serde_yaml::{Mapping, Value as YamlValue};
+
+/// Parse a single-document YAML file into JSON.
+pub(super) fn parse(text: &str) -> Result<serde_json::Value> {
+    let value: YamlValue = load(text)?;
+    serde_json::to_value(value).context("YAML document cannot be represented as JSON")
+}
+
+fn load(text: &str) -> Result<YamlValue> {
+    if text.trim().is_empty() {
+        return Ok(YamlValue::Mapping(Mapping::new()));
+    }
+    serde_yaml::from_str(text).context("Invalid YAML")
+}
+
+/// Whether re-serializing `text` would drop comments.
+pub(super) fn has_comments(text: &str) -> bool {
+    text.lines().any(|line| {
+        let trimmed = line.trim_start();
+        trimmed.starts_with('#') || trimmed.contains(" #")
+    })
+}
+
+/// Set `path` to `value`, creating missing mappings.
+pub(super) fn set(text: &str, path: &[Segment], value: &serde_json::Value) -> Result<String> {
+    let mut document = load(text)?;
+    let replacement = serde_yaml::to_value(value)?;
+    let mut current = &mut document;
+    for (depth, segment) in path.iter().enumerate() {
+        let last = depth + 1 == path.len();
+        current = match current {
+            YamlValue::Mapping(map) => {
+                let key = mapping_key(map, segment.key()?);
+                if !map.contains_key(&key) {
+                    let placeholder = if last {
+                        YamlValue::Null
+                    } else {
+                        YamlValue::Mapping(Mapping::new())
+                    };
+                    map.insert(key.clone(), placeholder);
+                }
+                map.get_mut(&key).expect("inserted above")
+            }
+            YamlValue::Sequence(items) => {
+                let index = segment.index().ok_or_else(|| {
+                    anyhow!("{} is a list; use an index", display_path(&path[..depth]))
+                })?;
+                if index == items.len() {
+                    items.push(if last {
+                        YamlValue::Null
+                    } else {
+                        YamlValue::Mapping(Mapping::new())
+                    });
+                }
+                let len = items.len();
+                items.get_mut(index).ok_or_else(|| {
+                    anyhow!(
+                        "Index {index} is out of range for {} ({len} items); use {len} to append",
+                        display_path(&path[..depth])
+                    )
+                })?
+            }
+            _ => bail!(
+                "{} is a scalar value and has no '{}'",
+                display_path(&path[..depth]),
+                segment
+            ),
+        };
+    }
+    *current = replacement;
+    Ok(serde_yaml::to_string(&document)?)
+}
+
+/// Remove `path`; `None` when it does not exist.
+pub(super) fn delete(text: &str, path: &[Segment]) -> Result<Option<String>> {
+    let Some((last, parents)) = path.split_last() else {
+        bail!("Cannot delete the document root");
+    };
+    let mut document = load(text)?;
+    let mut current = &mut document;
+    for segment in parents {
+        let next = match current {
+            YamlValue::Mapping(map) => match segment.key() {
+                Ok(key) => {
+                    let key = mapping_key(map, key);
+                    map.get_mut(&key)
+                }
+                Err(_) => None,
+            },
+            YamlValue::Sequence(items) => segment.index().and_then(|index| items.get_mut(index)),
+            _ => None,
+        };
+        let Some(next) = next else {
+            return Ok(None);
+        };
+        current = next;
+    }
+    let removed = match current {
+        YamlValue::Mapping(map) => match last.key() {
+            Ok(key) => {
+                let key = mapping_key(map, key);
+                map.remove(&key).is_some()
+            }
+            Err(_) => false,
+        },
+        YamlValue::Sequence(items) => match last.index().filter(|index| *index < items.len()) {
+            Some(index) => {
+                items.remove(index);
+                true
+            }
+            None => false,
+        },
+        _ => false,
+    };
+    if !removed {
+        return Ok(None);
+    }
+    Ok(Some(serde_yaml::to_string(&document)?))
+}
+
+/// YAML keys may be numbers or booleans (`200:` in OpenAPI responses); match
+/// an existing non-string key before falling back to a string key.
+fn mapping_key(map: &Mapping, key: &str) -> YamlValue {
+    let string_key = YamlValue::String(key.to_string());
+    if map.contains_key(&string_key) {
+        return string_key;
+    }
+    map.keys()
+        .find(|existing| match existing {
+            YamlValue::Number(number) => number.to_string() == key,
+            YamlValue::Bool(flag) => flag.to_string() == key,
+            _ => false,
+        })
+        .cloned()
+        .unwrap_or(string_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::key_path::parse_key_path;
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn sets_and_deletes_nested_keys() {
+        let text = "name: ci\njobs:\n  test:\n    steps:\n      - run: cargo test\n";
+        let path = parse_key_path("jobs.test.steps[1].run").unwrap();
+        let updated = set(text, &path, &json!("cargo clippy")).unwrap();
+        let parsed = parse(&updated).unwrap();
+        assert_eq!(parsed["jobs"]["test"]["steps"][1]["run"], "cargo clippy");
+        assert_eq!(parsed["name"], "ci");
+
+        let path = parse_key_path("jobs.test.steps[0]").unwrap();
+        let updated = delete(&updated, &path).unwrap().unwrap();
+        assert_eq!(
+            parse(&updated).unwrap()["jobs"]["test"]["steps"],
+            json!([{"run": "cargo clippy"}])
+        );
+        assert!(
+            delete(text, &parse_key_path("jobs.lint").unwrap())
+                .unwrap()
+                .is_none()
+        );
+        assert!(has_comments("# pipeline\nname: ci\n"));
+        assert!(!has_comments(text));
+    }
+}
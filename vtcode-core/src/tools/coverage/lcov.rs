@@ -0,0 +1,126 @@
+//! LCOV reports, diff hunks and line ranges
+
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Hit counts per line of one source file, keyed by 1-based line number.
+pub(super) type LineHits = BTreeMap<usize, u64>;
+
+/// Parse an LCOV tracefile into line hit counts keyed by its `SF:` paths.
+///
+/// Records repeated for the same file, as written when several test binaries
+/// are merged, have their hit counts added together.
+pub(super) fn parse_lcov(content: &str) -> HashMap<String, LineHits> {
+    let mut files: HashMap<String, LineHits> = HashMap::new();
+    let mut current: Option<String> = None;
+    for line in content.lines() {
+        let line = line.trim();
+        if let Some(path) = line.strip_prefix("SF:") {
+            current = Some(path.to_string());
+            files.entry(path.to_string()).or_default();
+        } else if line == "end_of_record" {
+            current = None;
+        } else if let (Some(path), Some(data)) = (&current, line.strip_prefix("DA:")) {
+            let mut fields = data.split(',');
+            let number = fields.next().and_then(|value| value.trim().parse().ok());
+            let hits = fields
+                .next()
+                .and_then(|value| value.trim().parse::<f64>().ok());
+            if let (Some(number), Some(hits)) = (number, hits) {
+                let entry = files
+                    .get_mut(path)
+                    .expect("file entry exists")
+                    .entry(number)
+                    .or_insert(0);
+                *entry += hits.max(0.0) as u64;
+            }
+        }
+    }
+    files
+}
+
+/// Lines added or modified per file in `git diff --unified=0` output.
+pub(super) fn parse_diff_hunks(diff: &str) -> HashMap<String, BTreeSet<usize>> {
+    let mut files: HashMap<String, BTreeSet<usize>> = HashMap::new();
+    let mut current: Option<String> = None;
+    for line in diff.lines() {
+        if let Some(path) = line.strip_prefix("+++ ") {
+            current = path.strip_prefix("b/").map(str::to_string);
+        } else if let (Some(path), Some(header)) = (&current, line.strip_prefix("@@ ")) {
+            let Some(added) = header
+                .split_whitespace()
+                .find_map(|part| part.strip_prefix('+'))
+            else {
+                continue;
+            };
+            let mut parts = added.splitn(2, ',');
+            let start: usize = parts.next().and_then(|v| v.parse().ok()).unwrap_or(0);
+            let count: usize = parts.next().and_then(|v| v.parse().ok()).unwrap_or(1);
+            files
+                .entry(path.clone())
+                .or_default()
+                .extend(start..start + count);
+        }
+    }
+    files.retain(|_, lines| !lines.is_empty());
+    files
+}
+
+/// An inclusive range of 1-based line numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LineRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Collapse ascending line numbers into contiguous ranges.
+pub(super) fn to_ranges(lines: impl IntoIterator<Item = usize>) -> Vec<LineRange> {
+    let mut ranges: Vec<LineRange> = Vec::new();
+    for line in lines {
+        match ranges.last_mut() {
+            Some(range) if line <= range.end + 1 => range.end = range.end.max(line),
+            _ => ranges.push(LineRange {
+                start: line,
+                end: line,
+            }),
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_records_and_collapses_uncovered_lines() {
+        let report = "TN:\nSF:src/lib.rs\nDA:1,3\nDA:2,0\nDA:3,0\nDA:5,0\nLF:4\nLH:1\nend_of_record\nSF:src/lib.rs\nDA:3,1\nend_of_record\n";
+        let files = parse_lcov(report);
+        let hits = &files["src/lib.rs"];
+        assert_eq!(hits[&1], 3);
+        assert_eq!(hits[&3], 1);
+
+        let uncovered = hits
+            .iter()
+            .filter(|(_, hits)| **hits == 0)
+            .map(|(line, _)| *line);
+        assert_eq!(
+            to_ranges(uncovered),
+            vec![
+                LineRange { start: 2, end: 2 },
+                LineRange { start: 5, end: 5 }
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_added_lines_from_zero_context_diffs() {
+        let diff = "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -4,0 +5,3 @@ fn main() {\n+a\n+b\n+c\n@@ -20 +22 @@\n-x\n+y\n@@ -30,2 +31,0 @@\n-gone\n-gone\ndiff --git a/old.py b/old.py\n--- a/old.py\n+++ /dev/null\n@@ -1,2 +0,0 @@\n";
+        let files = parse_diff_hunks(diff);
+        assert_eq!(files.len(), 1);
+        assert_eq!(
+            files["src/lib.rs"].iter().copied().collect::<Vec<_>>(),
+            vec![5, 6, 7, 22]
+        );
+    }
+}
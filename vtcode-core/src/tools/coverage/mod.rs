@@ -0,0 +1,432 @@
+//! Coverage reports for recently modified files
+//!
+//! [`CoverageReportTool`] runs the coverage command configured for each
+//! language under `[tools.coverage]` (cargo-llvm-cov, coverage.py, istanbul via
+//! jest), reads the LCOV report it writes and returns the uncovered lines of the
+//! requested files. Without explicit paths it reports on files modified in the
+//! git working tree, and uncovered lines that fall inside the modified hunks are
+//! listed separately so the agent can propose tests for exactly what it changed.
+
+mod lcov;
+
+pub use lcov::LineRange;
+
+use crate::config::CoverageConfig;
+use crate::config::constants::tools;
+use crate::tools::traits::Tool;
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
+use lcov::{LineHits, parse_diff_hunks, parse_lcov, to_ranges};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::process::Command;
+use tokio::time::timeout;
+
+/// Uncovered ranges listed per file before the output is truncated.
+const MAX_RANGES_PER_FILE: usize = 50;
+/// Trailing lines of command output kept when a coverage run fails.
+const OUTPUT_TAIL_LINES: usize = 40;
+
+/// Language of a source file, as keyed in `[tools.coverage.languages]`.
+fn language_for(path: &str) -> Option<&'static str> {
+    match Path::new(path).extension()?.to_str()? {
+        "rs" => Some("rust"),
+        "py" => Some("python"),
+        "js" | "jsx" | "mjs" | "cjs" => Some("javascript"),
+        "ts" | "tsx" | "mts" | "cts" => Some("typescript"),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CoverageReportInput {
+    /// Files to report on; defaults to files modified in the working tree
+    #[serde(default)]
+    paths: Vec<String>,
+    /// Language whose coverage command to use for every file
+    language: Option<String>,
+    /// Run the coverage command first instead of reading the existing report
+    #[serde(default = "default_run")]
+    run: bool,
+}
+
+fn default_run() -> bool {
+    true
+}
+
+/// Coverage tool for the files the agent has just touched.
+#[derive(Clone)]
+pub struct CoverageReportTool {
+    workspace_root: PathBuf,
+    config: CoverageConfig,
+}
+
+impl CoverageReportTool {
+    pub fn new(workspace_root: PathBuf) -> Self {
+        Self {
+            workspace_root,
+            config: CoverageConfig::default(),
+        }
+    }
+
+    pub fn set_config(&mut self, config: CoverageConfig) {
+        self.config = config;
+    }
+
+    async fn report(&self, input: CoverageReportInput) -> Result<Value> {
+        let changed = self.changed_lines().await;
+        let targets: Vec<String> = if input.paths.is_empty() {
+            let modified = changed.as_ref().ok_or_else(|| {
+                anyhow!("Not a git repository; pass the files to check in 'paths'")
+            })?;
+            let mut paths: Vec<String> = modified.keys().cloned().collect();
+            paths.sort();
+            paths
+        } else {
+            input.paths.iter().map(|path| normalize(path)).collect()
+        };
+
+        // Files sharing a command (JavaScript and TypeScript by default) run it once.
+        let mut groups: Vec<(String, Vec<String>)> = Vec::new();
+        let mut unsupported = Vec::new();
+        for path in targets {
+            let language = input
+                .language
+                .clone()
+                .or_else(|| language_for(&path).map(str::to_string));
+            let Some(setup) = language
+                .as_deref()
+                .and_then(|language| self.config.languages.get(language))
+            else {
+                unsupported.push(path);
+                continue;
+            };
+            match groups
+                .iter_mut()
+                .find(|(language, _)| self.config.languages.get(language) == Some(setup))
+            {
+                Some((_, paths)) => paths.push(path),
+                None => groups.push((language.unwrap_or_default(), vec![path])),
+            }
+        }
+        if groups.is_empty() {
+            return Err(if unsupported.is_empty() {
+                anyhow!("No modified files to check; pass the files to check in 'paths'")
+            } else {
+                anyhow!(
+                    "No coverage command configured for {}. Add one under [tools.coverage.languages].",
+                    unsupported.join(", ")
+                )
+            });
+        }
+
+        let mut runs = Vec::new();
+        let mut files = Vec::new();
+        let mut not_in_report = Vec::new();
+        let mut uncovered_changed_lines = 0;
+        let mut files_with_gaps = 0;
+        for (language, paths) in groups {
+            let (mut run, coverage) = self.collect(&language, input.run).await;
+            let Some(coverage) = coverage else {
+                run["files"] = json!(paths);
+                runs.push(run);
+                continue;
+            };
+            runs.push(run);
+
+            for path in paths {
+                let Some(hits) = coverage.get(&path) else {
+                    not_in_report.push(path);
+                    continue;
+                };
+                let modified = changed.as_ref().map(|changed| changed.get(&path));
+                let entry = file_entry(&path, hits, modified.map(|lines| lines.cloned()));
+                let gaps = entry["uncovered_changed_lines"].as_u64().unwrap_or(0) as usize;
+                if gaps > 0 {
+                    uncovered_changed_lines += gaps;
+                    files_with_gaps += 1;
+                }
+                files.push(entry);
+            }
+        }
+
+        let message = if uncovered_changed_lines > 0 {
+            format!(
+                "{} modified line(s) in {} file(s) are not covered by tests. Propose tests that exercise the 'uncovered_changed' ranges.",
+                uncovered_changed_lines, files_with_gaps
+            )
+        } else if changed.is_some() && !files.is_empty() {
+            "All modified lines with coverage data are covered by tests.".to_string()
+        } else {
+            "Coverage collected; see 'uncovered' for lines without tests.".to_string()
+        };
+
+        Ok(json!({
+            "success": runs.iter().all(|run| run.get("error").is_none()),
+            "runs": runs,
+            "files": files,
+            "not_in_report": not_in_report,
+            "unsupported": unsupported,
+            "message": message,
+        }))
+    }
+
+    /// Run the coverage command for `language` if asked, then read its report.
+    async fn collect(
+        &self,
+        language: &str,
+        run_command: bool,
+    ) -> (Value, Option<HashMap<String, LineHits>>) {
+        let setup = &self.config.languages[language];
+        let report_path = self.workspace_root.join(&setup.report);
+        let mut run = json!({
+            "language": language,
+            "command": setup.command,
+            "report": setup.report,
+        });
+
+        let started = SystemTime::now();
+        if run_command {
+            match self.run_command(&setup.command).await {
+                Ok((exit_code, duration, tail)) => {
+                    run["exit_code"] = json!(exit_code);
+                    run["duration_ms"] = json!(duration.as_millis() as u64);
+                    if exit_code != 0 {
+                        run["output_tail"] = json!(tail);
+                    }
+                }
+                Err(err) => {
+                    run["error"] = json!(format!("{:#}", err));
+                    return (run, None);
+                }
+            }
+        }
+
+        let content = match tokio::fs::read_to_string(&report_path).await {
+            Ok(content) => content,
+            Err(_) => {
+                run["error"] = json!(format!(
+                    "No coverage report at {}. Check that the command writes LCOV output there.",
+                    setup.report
+                ));
+                return (run, None);
+            }
+        };
+        if run_command {
+            let modified = tokio::fs::metadata(&report_path)
+                .await
+                .and_then(|metadata| metadata.modified())
+                .ok();
+            if modified.is_some_and(|modified| modified < started) {
+                run["stale"] = json!(true);
+            }
+        }
+
+        let coverage = parse_lcov(&content)
+            .into_iter()
+            .map(|(path, hits)| (self.relative(&path), hits))
+            .collect();
+        (run, Some(coverage))
+    }
+
+    async fn run_command(&self, command: &str) -> Result<(i32, Duration, String)> {
+        let started = Instant::now();
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c")
+            .arg(command)
+            .current_dir(&self.workspace_root)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        let limit = Duration::from_secs(self.config.timeout_secs);
+        let output = timeout(limit, cmd.output())
+            .await
+            .with_context(|| {
+                format!(
+                    "coverage command '{}' timed out after {}s",
+                    command,
+                    limit.as_secs()
+                )
+            })?
+            .with_context(|| format!("failed to run coverage command: {}", command))?;
+
+        let combined = format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let lines: Vec<&str> = combined.lines().collect();
+        let tail = lines[lines.len().saturating_sub(OUTPUT_TAIL_LINES)..].join("\n");
+        Ok((output.status.code().unwrap_or(-1), started.elapsed(), tail))
+    }
+
+    /// Lines added or modified since `HEAD`, with untracked files counted in full.
+    ///
+    /// Returns `None` outside a git repository.
+    async fn changed_lines(&self) -> Option<HashMap<String, BTreeSet<usize>>> {
+        let diff = self
+            .git(&["diff", "--unified=0", "--no-color", "--no-ext-diff", "HEAD"])
+            .await?;
+        let mut changed = parse_diff_hunks(&diff);
+        if let Some(untracked) = self
+            .git(&["ls-files", "--others", "--exclude-standard"])
+            .await
+        {
+            for path in untracked.lines().filter(|line| !line.is_empty()) {
+                let Ok(content) = tokio::fs::read_to_string(self.workspace_root.join(path)).await
+                else {
+                    continue;
+                };
+                let count = content.lines().count();
+                if count > 0 {
+                    changed.insert(path.to_string(), (1..=count).collect());
+                }
+            }
+        }
+        Some(changed)
+    }
+
+    async fn git(&self, args: &[&str]) -> Option<String> {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(&self.workspace_root)
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .await
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Workspace-relative form of a path from a coverage report.
+    fn relative(&self, path: &str) -> String {
+        let candidate = Path::new(path);
+        if candidate.is_absolute() {
+            let root = self
+                .workspace_root
+                .canonicalize()
+                .unwrap_or_else(|_| self.workspace_root.clone());
+            for base in [&root, &self.workspace_root] {
+                if let Ok(relative) = candidate.strip_prefix(base) {
+                    return normalize(&relative.to_string_lossy());
+                }
+            }
+        }
+        normalize(path)
+    }
+}
+
+fn normalize(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    path.strip_prefix("./").unwrap_or(&path).to_string()
+}
+
+/// Coverage summary of one file, including gaps in its modified lines when known.
+fn file_entry(path: &str, hits: &LineHits, modified: Option<Option<BTreeSet<usize>>>) -> Value {
+    let uncovered: BTreeSet<usize> = hits
+        .iter()
+        .filter(|(_, hits)| **hits == 0)
+        .map(|(line, _)| *line)
+        .collect();
+    let found = hits.len();
+    let hit = found - uncovered.len();
+    let percent = if found == 0 {
+        100.0
+    } else {
+        (hit as f64 * 1000.0 / found as f64).round() / 10.0
+    };
+
+    let ranges = to_ranges(uncovered.iter().copied());
+    let mut entry = json!({
+        "path": path,
+        "lines_found": found,
+        "lines_hit": hit,
+        "line_coverage_percent": percent,
+        "uncovered": ranges.iter().take(MAX_RANGES_PER_FILE).collect::<Vec<_>>(),
+    });
+    if ranges.len() > MAX_RANGES_PER_FILE {
+        entry["uncovered_truncated"] = json!(true);
+    }
+    if let Some(modified) = modified {
+        let gaps: Vec<usize> = modified
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|line| uncovered.contains(line))
+            .collect();
+        entry["uncovered_changed_lines"] = json!(gaps.len());
+        entry["uncovered_changed"] = json!(to_ranges(gaps));
+    }
+    entry
+}
+
+#[async_trait]
+impl Tool for CoverageReportTool {
+    async fn execute(&self, args: Value) -> Result<Value> {
+        let input: CoverageReportInput = serde_json::from_value(args).context(
+            "Error: Invalid 'coverage_report' arguments. Optional: { paths: string[], language: string, run: boolean }",
+        )?;
+        self.report(input).await
+    }
+
+    fn name(&self) -> &'static str {
+        tools::COVERAGE_REPORT
+    }
+
+    fn description(&self) -> &'static str {
+        "Run test coverage and report uncovered lines in recently modified files"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CoverageCommand;
+    use indexmap::IndexMap;
+
+    #[tokio::test]
+    async fn reports_uncovered_lines_from_the_configured_command() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let root = dir.path().canonicalize().expect("canonical");
+        std::fs::create_dir_all(root.join("src")).expect("src");
+        std::fs::write(root.join("src/lib.rs"), "fn a() {}\n").expect("lib.rs");
+
+        let report = format!(
+            "SF:{}/src/lib.rs\\nDA:1,2\\nDA:2,0\\nDA:3,0\\nDA:7,0\\nend_of_record\\n",
+            root.display()
+        );
+        let mut languages = IndexMap::new();
+        languages.insert(
+            "rust".to_string(),
+            CoverageCommand {
+                command: format!("mkdir -p cov && printf '{}' > cov/lcov.info", report),
+                report: "cov/lcov.info".to_string(),
+            },
+        );
+        let mut tool = CoverageReportTool::new(root.clone());
+        tool.set_config(CoverageConfig {
+            timeout_secs: 30,
+            languages,
+        });
+
+        let result = tool
+            .execute(json!({ "paths": ["./src/lib.rs", "notes.txt"] }))
+            .await
+            .expect("coverage");
+        assert_eq!(result["runs"][0]["exit_code"], json!(0));
+        assert_eq!(result["unsupported"], json!(["notes.txt"]));
+        let file = &result["files"][0];
+        assert_eq!(file["path"], json!("src/lib.rs"));
+        assert_eq!(file["line_coverage_percent"], json!(25.0));
+        assert_eq!(
+            file["uncovered"],
+            json!([{ "start": 2, "end": 3 }, { "start": 7, "end": 7 }])
+        );
+    }
+}
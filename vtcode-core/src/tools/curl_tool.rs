@@ -2,6 +2,7 @@
 
 use super::traits::Tool;
 use crate::config::constants::tools;
+use crate::utils::network;
 use anyhow::{Context, Result, anyhow};
 use async_trait::async_trait;
 use futures::StreamExt;
@@ -13,7 +14,6 @@ use std::fs;
 use std::net::IpAddr;
 use std::path::PathBuf;
 use std::time::Duration;
-use tracing::warn;
 
 const DEFAULT_TIMEOUT_SECS: u64 = 10;
 const MAX_TIMEOUT_SECS: u64 = 30;
@@ -37,28 +37,31 @@ struct CurlToolArgs {
 /// Secure HTTP fetch tool with aggressive validation
 #[derive(Clone)]
 pub struct CurlTool {
-    client: Client,
+    /// The error when no client could be built with the `[network]` settings
+    client: Result<Client, String>,
     temp_root: PathBuf,
 }
 
 impl CurlTool {
     pub fn new() -> Self {
-        let client = Client::builder()
+        let client = network::client_builder(None)
             .redirect(reqwest::redirect::Policy::none())
             .timeout(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
             .user_agent("vtcode-sandboxed-curl/0.1")
             .build()
-            .unwrap_or_else(|error| {
-                warn!(
-                    ?error,
-                    "Failed to build dedicated curl client; falling back to default"
-                );
-                Client::new()
-            });
+            .map_err(|error| error.to_string());
         let temp_root = std::env::temp_dir().join(TEMP_SUBDIR);
         Self { client, temp_root }
     }
 
+    fn client(&self) -> Result<&Client> {
+        self.client.as_ref().map_err(|error| {
+            anyhow!(
+                "curl is unavailable: no HTTP client could be built with the [network] proxy and TLS settings ({error})"
+            )
+        })
+    }
+
     async fn run(&self, raw_args: Value) -> Result<Value> {
         let args: CurlToolArgs = serde_json::from_value(raw_args)
             .context("Invalid arguments for curl tool. Provide an object with at least a 'url'.")?;
@@ -87,7 +90,7 @@ impl CurlTool {
         }
 
         let request = self
-            .client
+            .client()?
             .request(method.clone(), url.clone())
             .timeout(Duration::from_secs(timeout))
             .header(
@@ -134,31 +137,7 @@ impl CurlTool {
             }));
         }
 
-        let mut total_bytes: usize = 0;
-        let mut buffer: Vec<u8> = Vec::new();
-        let mut truncated = false;
-
-        let mut stream = response.bytes_stream();
-        while let Some(chunk) = stream.next().await {
-            let bytes =
-                chunk.with_context(|| format!("Failed to read response chunk from {}", url))?;
-            total_bytes = total_bytes.saturating_add(bytes.len());
-            if buffer.len() < max_bytes {
-                let remaining = max_bytes - buffer.len();
-                if bytes.len() > remaining {
-                    buffer.extend_from_slice(&bytes[..remaining]);
-                    truncated = true;
-                } else {
-                    buffer.extend_from_slice(&bytes);
-                }
-            } else {
-                truncated = true;
-            }
-            if buffer.len() >= max_bytes {
-                truncated = true;
-                break;
-            }
-        }
+        let (buffer, total_bytes, truncated) = read_limited(response, &url, max_bytes).await?;
 
         let body_text = String::from_utf8_lossy(&buffer).to_string();
         let saved_path = if args.save_response.unwrap_or(false) && !buffer.is_empty() {
@@ -186,6 +165,67 @@ impl CurlTool {
         }))
     }
 
+    /// GET a text document under the same URL and content-type rules as the
+    /// tool, following up to `max_redirects` redirects that pass them too.
+    pub(crate) async fn get_text(
+        &self,
+        url: &str,
+        max_bytes: usize,
+        max_redirects: usize,
+    ) -> Result<TextResponse> {
+        let mut url = Url::parse(url).with_context(|| format!("Invalid URL '{}'", url))?;
+        let mut redirects = 0;
+        loop {
+            self.validate_url(&url)?;
+            let response = self
+                .client()?
+                .get(url.clone())
+                .timeout(Duration::from_secs(MAX_TIMEOUT_SECS))
+                .header(
+                    reqwest::header::ACCEPT,
+                    "text/html, text/markdown, text/plain, application/json",
+                )
+                .send()
+                .await
+                .with_context(|| format!("Failed to execute HTTPS request to {}", url))?;
+
+            let status = response.status();
+            if status.is_redirection() {
+                let location = response
+                    .headers()
+                    .get(reqwest::header::LOCATION)
+                    .and_then(|value| value.to_str().ok())
+                    .ok_or_else(|| anyhow!("Redirect from {} without a location", url))?;
+                if redirects == max_redirects {
+                    return Err(anyhow!("Too many redirects fetching {}", url));
+                }
+                redirects += 1;
+                url = url
+                    .join(location)
+                    .with_context(|| format!("Invalid redirect location '{}'", location))?;
+                continue;
+            }
+            if !status.is_success() {
+                return Err(anyhow!("Request to {} returned status {}", url, status));
+            }
+
+            let content_type = response
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or("")
+                .to_string();
+            self.validate_content_type(&content_type)?;
+            let (buffer, _, truncated) = read_limited(response, &url, max_bytes).await?;
+            return Ok(TextResponse {
+                url,
+                content_type,
+                body: String::from_utf8_lossy(&buffer).to_string(),
+                truncated,
+            });
+        }
+    }
+
     fn normalize_method(&self, method: Option<String>) -> Result<Method> {
         let requested = method.unwrap_or_else(|| "GET".to_string());
         let normalized = requested.trim().to_uppercase();
@@ -286,6 +326,49 @@ impl CurlTool {
     }
 }
 
+/// A text document read by [`CurlTool::get_text`].
+pub(crate) struct TextResponse {
+    /// Final URL after redirects
+    pub url: Url,
+    pub content_type: String,
+    pub body: String,
+    pub truncated: bool,
+}
+
+/// Read at most `max_bytes` of the body; returns the bytes kept, the bytes
+/// received and whether the body was cut.
+async fn read_limited(
+    response: reqwest::Response,
+    url: &Url,
+    max_bytes: usize,
+) -> Result<(Vec<u8>, usize, bool)> {
+    let mut total_bytes: usize = 0;
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut truncated = false;
+
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let bytes = chunk.with_context(|| format!("Failed to read response chunk from {}", url))?;
+        total_bytes = total_bytes.saturating_add(bytes.len());
+        if buffer.len() < max_bytes {
+            let remaining = max_bytes - buffer.len();
+            if bytes.len() > remaining {
+                buffer.extend_from_slice(&bytes[..remaining]);
+                truncated = true;
+            } else {
+                buffer.extend_from_slice(&bytes);
+            }
+        } else {
+            truncated = true;
+        }
+        if buffer.len() >= max_bytes {
+            truncated = true;
+            break;
+        }
+    }
+    Ok((buffer, total_bytes, truncated))
+}
+
 #[async_trait]
 impl Tool for CurlTool {
     async fn execute(&self, args: Value) -> Result<Value> {
@@ -0,0 +1,443 @@
+//! HTML documentation pages as markdown
+//!
+//! A small converter for rendered docs (rustdoc, MDN, Sphinx): it keeps the
+//! page's `<main>` element when there is one, drops navigation, scripts and
+//! other chrome, and maps headings, paragraphs, lists, links, code and tables
+//! to markdown. Relative links are made absolute against the page URL.
+
+use reqwest::Url;
+
+/// Elements dropped with everything inside them.
+const SKIPPED: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "nav", "footer", "aside", "button", "form",
+    "select", "iframe", "head",
+];
+
+/// Elements that start a new block.
+const BLOCKS: &[&str] = &[
+    "p",
+    "div",
+    "section",
+    "article",
+    "main",
+    "details",
+    "summary",
+    "dl",
+    "dt",
+    "dd",
+    "blockquote",
+    "table",
+    "figure",
+    "hr",
+];
+
+pub(super) fn html_to_markdown(html: &str, base: &Url) -> String {
+    let mut writer = Writer::new(base);
+    for token in Tokens::new(main_content(html)) {
+        writer.token(token);
+    }
+    writer.finish()
+}
+
+/// The `<main>` element of the page, or the whole page.
+fn main_content(html: &str) -> &str {
+    let lower = html.to_ascii_lowercase();
+    let Some(start) = lower.find("<main") else {
+        return html;
+    };
+    match lower.rfind("</main>") {
+        Some(end) if end > start => &html[start..end],
+        _ => &html[start..],
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Token<'a> {
+    Text(&'a str),
+    Open { name: String, attrs: &'a str },
+    Close { name: String },
+}
+
+struct Tokens<'a> {
+    html: &'a str,
+    pos: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(html: &'a str) -> Self {
+        Self { html, pos: 0 }
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        loop {
+            let rest = &self.html[self.pos..];
+            if rest.is_empty() {
+                return None;
+            }
+            if !rest.starts_with('<') {
+                let end = rest.find('<').unwrap_or(rest.len());
+                self.pos += end;
+                return Some(Token::Text(&rest[..end]));
+            }
+            if let Some(comment) = rest.strip_prefix("<!--") {
+                self.pos += 4 + comment.find("-->").map_or(comment.len(), |end| end + 3);
+                continue;
+            }
+            let Some(end) = rest.find('>') else {
+                self.pos = self.html.len();
+                return Some(Token::Text(rest));
+            };
+            self.pos += end + 1;
+            let inner = &rest[1..end];
+            if inner.starts_with('!') || inner.starts_with('?') {
+                continue;
+            }
+            if let Some(name) = inner.strip_prefix('/') {
+                return Some(Token::Close {
+                    name: name.trim().to_ascii_lowercase(),
+                });
+            }
+            let name_end = inner
+                .find(|c: char| c.is_whitespace() || c == '/')
+                .unwrap_or(inner.len());
+            let name = inner[..name_end].to_ascii_lowercase();
+            if name.is_empty() {
+                return Some(Token::Text("<"));
+            }
+            let attrs = &inner[name_end..];
+            // Raw text elements: skip to their closing tag so `<` inside is not parsed
+            if matches!(name.as_str(), "script" | "style") {
+                let close = format!("</{name}");
+                let after = &self.html[self.pos..];
+                let skip = after
+                    .to_ascii_lowercase()
+                    .find(&close)
+                    .unwrap_or(after.len());
+                self.pos += skip;
+            }
+            return Some(Token::Open { name, attrs });
+        }
+    }
+}
+
+fn attribute(attrs: &str, name: &str) -> Option<String> {
+    let lower = attrs.to_ascii_lowercase();
+    let mut search = 0;
+    while let Some(found) = lower[search..].find(name) {
+        let start = search + found;
+        search = start + name.len();
+        let boundary = start == 0 || lower.as_bytes()[start - 1].is_ascii_whitespace();
+        let rest = attrs[search..].trim_start();
+        if !boundary || !rest.starts_with('=') {
+            continue;
+        }
+        let value = rest[1..].trim_start();
+        let (value, _) = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => value[1..].split_once(quote)?,
+            _ => value.split_once(char::is_whitespace).unwrap_or((value, "")),
+        };
+        return Some(decode_entities(value));
+    }
+    None
+}
+
+fn self_closing(attrs: &str) -> bool {
+    attrs.trim_end().ends_with('/')
+}
+
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let window = &rest.as_bytes()[..rest.len().min(12)];
+        let Some(semi) = window.iter().position(|&byte| byte == b';') else {
+            out.push('&');
+            rest = &rest[1..];
+            continue;
+        };
+        let entity = &rest[1..semi];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            "mdash" => Some('—'),
+            "ndash" => Some('–'),
+            "hellip" => Some('…'),
+            "rarr" => Some('→'),
+            "sect" => Some('§'),
+            _ => entity.strip_prefix('#').and_then(|code| {
+                let value = match code.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => code.parse().ok(),
+                };
+                value.and_then(char::from_u32)
+            }),
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+struct Writer<'a> {
+    base: &'a Url,
+    out: String,
+    /// Open skipped elements; text is dropped while non-empty
+    skipping: Vec<String>,
+    pre: usize,
+    /// One entry per open list: `None` for `<ul>`, the next number for `<ol>`
+    lists: Vec<Option<usize>>,
+    /// Targets of open links; `None` for links rendered as plain text
+    links: Vec<Option<String>>,
+}
+
+impl<'a> Writer<'a> {
+    fn new(base: &'a Url) -> Self {
+        Self {
+            base,
+            out: String::new(),
+            skipping: Vec::new(),
+            pre: 0,
+            lists: Vec::new(),
+            links: Vec::new(),
+        }
+    }
+
+    fn token(&mut self, token: Token<'_>) {
+        if let Some(skipped) = self.skipping.last() {
+            match token {
+                Token::Open { name, attrs } if &name == skipped && !self_closing(attrs) => {
+                    self.skipping.push(name)
+                }
+                Token::Close { name } if &name == skipped => {
+                    self.skipping.pop();
+                }
+                _ => {}
+            }
+            return;
+        }
+        match token {
+            Token::Text(text) => self.text(&decode_entities(text)),
+            Token::Open { name, attrs } => self.open(name, attrs),
+            Token::Close { name } => self.close(&name),
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.pre > 0 {
+            self.out.push_str(text);
+            return;
+        }
+        let mut collapsed = String::with_capacity(text.len());
+        for (index, word) in text.split_whitespace().enumerate() {
+            if index > 0 {
+                collapsed.push(' ');
+            }
+            collapsed.push_str(word);
+        }
+        if text.starts_with(char::is_whitespace) && !self.at_line_start() {
+            self.out.push(' ');
+        }
+        self.out.push_str(&collapsed);
+        if text.ends_with(char::is_whitespace) && !collapsed.is_empty() {
+            self.out.push(' ');
+        }
+    }
+
+    fn at_line_start(&self) -> bool {
+        self.out.is_empty() || self.out.ends_with(['\n', ' '])
+    }
+
+    fn block(&mut self) {
+        let trimmed = self.out.trim_end_matches(' ').len();
+        self.out.truncate(trimmed);
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") {
+            self.out.push_str(if self.out.ends_with('\n') {
+                "\n"
+            } else {
+                "\n\n"
+            });
+        }
+    }
+
+    fn line(&mut self) {
+        let trimmed = self.out.trim_end_matches(' ').len();
+        self.out.truncate(trimmed);
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+    }
+
+    fn open(&mut self, name: String, attrs: &str) {
+        if SKIPPED.contains(&name.as_str()) {
+            if !self_closing(attrs) {
+                self.skipping.push(name);
+            }
+            return;
+        }
+        match name.as_str() {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.block();
+                let level = usize::from(name.as_bytes()[1] - b'0');
+                self.out.push_str(&"#".repeat(level));
+                self.out.push(' ');
+            }
+            "pre" => {
+                self.block();
+                self.out.push_str("```\n");
+                self.pre += 1;
+            }
+            "code" | "kbd" | "samp" if self.pre == 0 => self.out.push('`'),
+            "strong" | "b" if self.pre == 0 => self.out.push_str("**"),
+            "em" | "i" if self.pre == 0 => self.out.push('*'),
+            "br" => self.out.push('\n'),
+            "ul" => {
+                self.line();
+                self.lists.push(None);
+            }
+            "ol" => {
+                self.line();
+                self.lists.push(Some(1));
+            }
+            "li" => {
+                self.line();
+                let indent = "  ".repeat(self.lists.len().saturating_sub(1));
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}.", *number - 1)
+                    }
+                    _ => "-".to_string(),
+                };
+                self.out.push_str(&format!("{indent}{marker} "));
+            }
+            "tr" => self.line(),
+            "td" | "th" => self.out.push_str("| "),
+            "a" => {
+                let target = attribute(attrs, "href")
+                    .filter(|href| !href.starts_with('#') && !href.starts_with("javascript:"))
+                    .and_then(|href| self.base.join(&href).ok())
+                    .map(|url| url.to_string());
+                if target.is_some() && self.pre == 0 {
+                    self.out.push('[');
+                    self.links.push(target);
+                } else {
+                    self.links.push(None);
+                }
+            }
+            "img" => {
+                if let Some(alt) = attribute(attrs, "alt").filter(|alt| !alt.is_empty()) {
+                    self.text(&alt);
+                }
+            }
+            other if BLOCKS.contains(&other) => self.block(),
+            _ => {}
+        }
+    }
+
+    fn close(&mut self, name: &str) {
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => self.block(),
+            "pre" => {
+                self.pre = self.pre.saturating_sub(1);
+                self.line();
+                self.out.push_str("```");
+                self.block();
+            }
+            "code" | "kbd" | "samp" if self.pre == 0 => self.out.push('`'),
+            "strong" | "b" if self.pre == 0 => self.out.push_str("**"),
+            "em" | "i" if self.pre == 0 => self.out.push('*'),
+            "ul" | "ol" => {
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.block();
+                }
+            }
+            "td" | "th" => self.out.push(' '),
+            "tr" => self.out.push('|'),
+            "a" => {
+                if let Some(Some(target)) = self.links.pop() {
+                    let trimmed = self.out.trim_end().len();
+                    self.out.truncate(trimmed);
+                    if self.out.ends_with('[') {
+                        // A link around nothing (e.g. an icon) is dropped
+                        self.out.pop();
+                    } else {
+                        self.out.push_str(&format!("]({target})"));
+                    }
+                }
+            }
+            other if BLOCKS.contains(&other) => self.block(),
+            _ => {}
+        }
+    }
+
+    fn finish(self) -> String {
+        let mut result = String::with_capacity(self.out.len());
+        let mut blank_lines = 0;
+        for line in self.out.lines() {
+            let line = line.trim_end();
+            if line.is_empty() {
+                blank_lines += 1;
+                if blank_lines > 1 {
+                    continue;
+                }
+            } else {
+                blank_lines = 0;
+            }
+            result.push_str(line);
+            result.push('\n');
+        }
+        result.trim().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_the_main_content() {
+        let html = r##"<html><head><title>x</title><script>if (a < b) {}</script></head>
+<body><nav><a href="/">Home</a></nav>
+<main><h1>Struct <code>Client</code></h1>
+<p>An asynchronous <a href="struct.Request.html">Request</a> builder &amp; sender.
+<a class="anchor" href="#method.get">§</a></p>
+<pre><code>let client = Client::new();
+let res = client.get(url);</code></pre>
+<ul><li>First</li><li>Second <em>item</em></li></ul>
+<ol><li>one</li><li>two</li></ol>
+</main><footer>© docs</footer></body></html>"##;
+        let base = Url::parse("https://docs.rs/reqwest/0.12.5/reqwest/struct.Client.html").unwrap();
+        let markdown = html_to_markdown(html, &base);
+        assert_eq!(
+            markdown,
+            "# Struct `Client`\n\n\
+             An asynchronous [Request](https://docs.rs/reqwest/0.12.5/reqwest/struct.Request.html) builder & sender. §\n\n\
+             ```\nlet client = Client::new();\nlet res = client.get(url);\n```\n\n\
+             - First\n- Second *item*\n\n\
+             1. one\n2. two"
+        );
+    }
+}
@@ -0,0 +1,415 @@
+//! Documentation for the versions of the workspace's dependencies
+//!
+//! [`FetchDocsTool`] looks a package up in the workspace's lockfiles and
+//! manifests to find the version in use, fetches the matching docs page
+//! (docs.rs for crates, the published README or files for npm packages, the
+//! PyPI description or readthedocs pages for Python, MDN for web platform
+//! APIs) through the sandboxed [`CurlTool`], and converts HTML to markdown.
+//! Pages are cached in `~/.vtcode/cache/docs/<ecosystem>/<package>@<version>/`,
+//! so later sessions read them without the network.
+
+mod markdown;
+mod resolve;
+
+use crate::config::constants::tools;
+use crate::tools::curl_tool::CurlTool;
+use crate::tools::traits::Tool;
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
+use markdown::html_to_markdown;
+use reqwest::Url;
+use resolve::{Ecosystem, ResolvedPackage, resolve_package};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::path::PathBuf;
+
+const CACHE_DIR: &str = ".vtcode/cache/docs";
+/// Largest page read; rendered docs pages are mostly markup
+const MAX_PAGE_BYTES: usize = 4 * 1024 * 1024;
+const MAX_REDIRECTS: usize = 5;
+const DEFAULT_MAX_CHARS: usize = 20_000;
+const MAX_CHARS: usize = 100_000;
+
+#[derive(Debug, Deserialize)]
+struct FetchDocsInput {
+    /// Package name, or for MDN a docs path such as `Web/API/fetch`
+    package: String,
+    #[serde(default)]
+    ecosystem: Option<Ecosystem>,
+    /// Overrides the version found in the workspace
+    #[serde(default)]
+    version: Option<String>,
+    /// Page below the package's docs root, or a full URL on the docs host
+    #[serde(default)]
+    page: Option<String>,
+    /// Character offset to continue a long page from
+    #[serde(default)]
+    offset: usize,
+    #[serde(default)]
+    max_chars: Option<usize>,
+    /// Fetch again even when the page is cached
+    #[serde(default)]
+    refresh: bool,
+}
+
+/// A fetched page as markdown.
+struct DocsPage {
+    url: String,
+    markdown: String,
+}
+
+/// Fetches and caches documentation pages for the workspace's dependencies.
+#[derive(Clone)]
+pub struct FetchDocsTool {
+    workspace_root: PathBuf,
+    curl: CurlTool,
+    cache_root: PathBuf,
+}
+
+impl FetchDocsTool {
+    pub fn new(workspace_root: PathBuf) -> Self {
+        let cache_root = dirs::home_dir()
+            .unwrap_or_else(|| workspace_root.clone())
+            .join(CACHE_DIR);
+        Self::with_cache_root(workspace_root, cache_root)
+    }
+
+    /// Keep cached pages under `cache_root` instead of `~/.vtcode/cache/docs`.
+    pub fn with_cache_root(workspace_root: PathBuf, cache_root: PathBuf) -> Self {
+        Self {
+            workspace_root,
+            curl: CurlTool::new(),
+            cache_root,
+        }
+    }
+
+    async fn run(&self, input: FetchDocsInput) -> Result<Value> {
+        let package = resolve_package(
+            &self.workspace_root,
+            input.package.trim(),
+            input.ecosystem,
+            input.version.as_deref(),
+        )?;
+        let page = input
+            .page
+            .as_deref()
+            .map(str::trim)
+            .filter(|page| !page.is_empty());
+        let cache_path = self.cache_path(&package, page);
+
+        let cached = if input.refresh {
+            None
+        } else {
+            read_cached(&cache_path)
+        };
+        let (document, from_cache) = match cached {
+            Some(document) => (document, true),
+            None => {
+                let document = self.fetch(&package, page).await?;
+                write_cached(&cache_path, &document)?;
+                (document, false)
+            }
+        };
+
+        let max_chars = input
+            .max_chars
+            .unwrap_or(DEFAULT_MAX_CHARS)
+            .clamp(1, MAX_CHARS);
+        let total_chars = document.markdown.chars().count();
+        let content: String = document
+            .markdown
+            .chars()
+            .skip(input.offset)
+            .take(max_chars)
+            .collect();
+        let end = input.offset.saturating_add(max_chars);
+        let next_offset = (end < total_chars).then_some(end);
+
+        Ok(json!({
+            "success": true,
+            "package": package.name,
+            "ecosystem": package.ecosystem.name(),
+            "version": package.version,
+            "version_source": package.source,
+            "url": document.url,
+            "cached": from_cache,
+            "cache_path": cache_path.display().to_string(),
+            "content": content,
+            "offset": input.offset,
+            "total_chars": total_chars,
+            "truncated": next_offset.is_some(),
+            "next_offset": next_offset,
+        }))
+    }
+
+    fn cache_path(&self, package: &ResolvedPackage, page: Option<&str>) -> PathBuf {
+        self.cache_root
+            .join(package.ecosystem.name())
+            .join(format!(
+                "{}@{}",
+                cache_key(&package.name),
+                cache_key(&package.version)
+            ))
+            .join(format!(
+                "{}.md",
+                page.map_or_else(|| "index".to_string(), cache_key)
+            ))
+    }
+
+    async fn fetch(&self, package: &ResolvedPackage, page: Option<&str>) -> Result<DocsPage> {
+        let name = &package.name;
+        let version = &package.version;
+        match package.ecosystem {
+            Ecosystem::Crates => {
+                let url = match page {
+                    Some(page) => page_url(package.ecosystem, page, || {
+                        format!("https://docs.rs/{name}/{version}/{page}")
+                    })?,
+                    None => format!(
+                        "https://docs.rs/{name}/{version}/{}/",
+                        name.replace('-', "_")
+                    ),
+                };
+                self.fetch_page(&url).await
+            }
+            Ecosystem::Npm => {
+                let root = format!("https://cdn.jsdelivr.net/npm/{name}@{version}");
+                match page {
+                    Some(page) => {
+                        let url = page_url(package.ecosystem, page, || format!("{root}/{page}"))?;
+                        self.fetch_page(&url).await
+                    }
+                    None => {
+                        let mut last_error = None;
+                        for readme in ["README.md", "readme.md", "Readme.md"] {
+                            match self.fetch_page(&format!("{root}/{readme}")).await {
+                                Ok(document) => return Ok(document),
+                                Err(err) => last_error = Some(err),
+                            }
+                        }
+                        Err(last_error.unwrap_or_else(|| anyhow!("No README for {name}")))
+                    }
+                }
+            }
+            Ecosystem::Pypi => match page {
+                Some(page) => {
+                    // Project slugs are lowercase with dashes
+                    let slug = name.to_ascii_lowercase().replace(['_', '.'], "-");
+                    let mut attempts = vec![page_url(package.ecosystem, page, || {
+                        format!("https://{slug}.readthedocs.io/en/{version}/{page}")
+                    })?];
+                    if !page.starts_with("https://") && version != "stable" {
+                        attempts.push(format!("https://{slug}.readthedocs.io/en/stable/{page}"));
+                    }
+                    let mut last_error = None;
+                    for url in attempts {
+                        match self.fetch_page(&url).await {
+                            Ok(document) => return Ok(document),
+                            Err(err) => last_error = Some(err),
+                        }
+                    }
+                    Err(last_error.unwrap_or_else(|| anyhow!("No docs page '{page}'")))
+                }
+                None => self.fetch_pypi_description(package).await,
+            },
+            Ecosystem::Mdn => {
+                let path = page.unwrap_or(name).trim_start_matches('/');
+                let url = page_url(package.ecosystem, path, || {
+                    format!("https://developer.mozilla.org/en-US/docs/{path}")
+                })?;
+                self.fetch_page(&url).await
+            }
+        }
+    }
+
+    async fn fetch_page(&self, url: &str) -> Result<DocsPage> {
+        let response = self
+            .curl
+            .get_text(url, MAX_PAGE_BYTES, MAX_REDIRECTS)
+            .await?;
+        let mut markdown = if response.content_type.to_ascii_lowercase().contains("html") {
+            html_to_markdown(&response.body, &response.url)
+        } else {
+            response.body
+        };
+        if response.truncated {
+            markdown.push_str("\n\n[page cut at the size limit]");
+        }
+        Ok(DocsPage {
+            url: response.url.to_string(),
+            markdown,
+        })
+    }
+
+    /// The project description (usually its README) and documentation links
+    /// from the PyPI JSON API.
+    async fn fetch_pypi_description(&self, package: &ResolvedPackage) -> Result<DocsPage> {
+        let url = if package.version == "latest" {
+            format!("https://pypi.org/pypi/{}/json", package.name)
+        } else {
+            format!(
+                "https://pypi.org/pypi/{}/{}/json",
+                package.name, package.version
+            )
+        };
+        let response = self
+            .curl
+            .get_text(&url, MAX_PAGE_BYTES, MAX_REDIRECTS)
+            .await?;
+        let metadata: Value = serde_json::from_str(&response.body)
+            .with_context(|| format!("Unexpected response from {}", url))?;
+        let info = metadata
+            .get("info")
+            .ok_or_else(|| anyhow!("No project info in the response from {}", url))?;
+
+        let mut markdown = info
+            .get("description")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+        if markdown.is_empty() {
+            markdown = info
+                .get("summary")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+        }
+        if let Some(links) = info.get("project_urls").and_then(Value::as_object) {
+            markdown.push_str("\n\n## Project links\n");
+            for (label, link) in links {
+                if let Some(link) = link.as_str() {
+                    markdown.push_str(&format!("\n- {label}: {link}"));
+                }
+            }
+        }
+        Ok(DocsPage {
+            url: response.url.to_string(),
+            markdown,
+        })
+    }
+}
+
+/// `page` as a URL: a full URL must be on the ecosystem's docs host,
+/// anything else is a path for `relative`.
+fn page_url(ecosystem: Ecosystem, page: &str, relative: impl FnOnce() -> String) -> Result<String> {
+    if !page.starts_with("https://") {
+        return Ok(relative());
+    }
+    let url = Url::parse(page).with_context(|| format!("Invalid docs URL '{}'", page))?;
+    let host = url.host_str().unwrap_or_default();
+    let allowed = match ecosystem {
+        Ecosystem::Crates => host == "docs.rs",
+        Ecosystem::Npm => host == "cdn.jsdelivr.net",
+        Ecosystem::Pypi => host == "pypi.org" || host.ends_with(".readthedocs.io"),
+        Ecosystem::Mdn => host == "developer.mozilla.org",
+    };
+    if !allowed {
+        return Err(anyhow!(
+            "'{}' is not on the {} docs host; use the curl tool for other sites",
+            page,
+            ecosystem
+        ));
+    }
+    Ok(page.to_string())
+}
+
+/// A file name for `value`, e.g. `reqwest/struct.Client.html` →
+/// `reqwest_struct.Client.html`.
+fn cache_key(value: &str) -> String {
+    value
+        .trim_matches('/')
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '@') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>()
+        .trim_start_matches('.')
+        .to_string()
+}
+
+/// Cached pages start with an HTML comment holding the source URL.
+fn read_cached(path: &std::path::Path) -> Option<DocsPage> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let (header, markdown) = content.split_once('\n')?;
+    let url = header.strip_prefix("<!-- ")?.strip_suffix(" -->")?;
+    Some(DocsPage {
+        url: url.to_string(),
+        markdown: markdown.to_string(),
+    })
+}
+
+fn write_cached(path: &std::path::Path, document: &DocsPage) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    std::fs::write(
+        path,
+        format!("<!-- {} -->\n{}", document.url, document.markdown),
+    )
+    .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[async_trait]
+impl Tool for FetchDocsTool {
+    async fn execute(&self, args: Value) -> Result<Value> {
+        let input: FetchDocsInput = serde_json::from_value(args)
+            .context("Invalid fetch_docs arguments. Provide at least a 'package'.")?;
+        self.run(input).await
+    }
+
+    fn name(&self) -> &'static str {
+        tools::FETCH_DOCS
+    }
+
+    fn description(&self) -> &'static str {
+        "Fetches documentation for the version of a dependency the workspace uses"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn serves_cached_pages_in_slices() {
+        let workspace = tempfile::tempdir().unwrap();
+        let cache = tempfile::tempdir().unwrap();
+        std::fs::write(
+            workspace.path().join("Cargo.lock"),
+            "[[package]]\nname = \"serde\"\nversion = \"1.0.203\"\n",
+        )
+        .unwrap();
+        let page = cache
+            .path()
+            .join("crates/serde@1.0.203/serde_de_index.html.md");
+        std::fs::create_dir_all(page.parent().unwrap()).unwrap();
+        std::fs::write(
+            &page,
+            "<!-- https://docs.rs/serde/1.0.203/serde/de/index.html -->\n# Module de\n\nGeneric data structure deserialization framework.",
+        )
+        .unwrap();
+
+        let tool = FetchDocsTool::with_cache_root(
+            workspace.path().to_path_buf(),
+            cache.path().to_path_buf(),
+        );
+        let result = tool
+            .execute(json!({"package": "serde", "page": "serde/de/index.html", "max_chars": 11}))
+            .await
+            .unwrap();
+        assert_eq!(result["version"], "1.0.203");
+        assert_eq!(result["version_source"], "Cargo.lock");
+        assert_eq!(result["cached"], true);
+        assert_eq!(result["content"], "# Module de");
+        assert_eq!(result["next_offset"], 11);
+
+        let error = page_url(Ecosystem::Crates, "https://example.com/x", String::new);
+        assert!(error.is_err());
+    }
+}
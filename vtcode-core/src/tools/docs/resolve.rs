@@ -0,0 +1,318 @@
+//! Package versions from the workspace manifests and lockfiles
+
+use std::fmt;
+use std::path::Path;
+
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Where a package's documentation lives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(super) enum Ecosystem {
+    /// docs.rs
+    #[serde(alias = "crate", alias = "cargo", alias = "rust")]
+    Crates,
+    /// README and files of the published package, through jsDelivr
+    #[serde(alias = "node")]
+    Npm,
+    /// PyPI project description, or readthedocs pages
+    #[serde(alias = "python", alias = "pip")]
+    Pypi,
+    /// MDN Web Docs; the "package" is a docs path such as `Web/API/fetch`
+    Mdn,
+}
+
+impl Ecosystem {
+    pub(super) fn name(self) -> &'static str {
+        match self {
+            Self::Crates => "crates",
+            Self::Npm => "npm",
+            Self::Pypi => "pypi",
+            Self::Mdn => "mdn",
+        }
+    }
+}
+
+impl fmt::Display for Ecosystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A package with the version whose docs to fetch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct ResolvedPackage {
+    pub ecosystem: Ecosystem,
+    pub name: String,
+    /// Exact version, or `latest` when the workspace does not pin one
+    pub version: String,
+    /// File the version was read from
+    pub source: Option<String>,
+}
+
+/// Resolve `name` against the workspace. Without an ecosystem the first
+/// manifest that lists the package decides; an explicit `version` wins over
+/// the manifests.
+pub(super) fn resolve_package(
+    root: &Path,
+    name: &str,
+    ecosystem: Option<Ecosystem>,
+    version: Option<&str>,
+) -> Result<ResolvedPackage> {
+    let candidates = match ecosystem {
+        Some(ecosystem) => vec![ecosystem],
+        None => vec![Ecosystem::Crates, Ecosystem::Npm, Ecosystem::Pypi],
+    };
+    for ecosystem in &candidates {
+        let found = match ecosystem {
+            Ecosystem::Crates => rust_version(root, name),
+            Ecosystem::Npm => node_version(root, name),
+            Ecosystem::Pypi => python_version(root, name),
+            Ecosystem::Mdn => None,
+        };
+        if let Some((found, source)) = found {
+            return Ok(ResolvedPackage {
+                ecosystem: *ecosystem,
+                name: name.to_string(),
+                version: version.map_or(found, str::to_string),
+                source: version.is_none().then_some(source),
+            });
+        }
+    }
+
+    match ecosystem {
+        Some(ecosystem) => Ok(ResolvedPackage {
+            ecosystem,
+            name: name.to_string(),
+            version: version.unwrap_or("latest").to_string(),
+            source: None,
+        }),
+        None => Err(anyhow!(
+            "'{}' is not a dependency in Cargo.toml, package.json or the Python manifests; pass ecosystem (crates, npm, pypi or mdn)",
+            name
+        )),
+    }
+}
+
+fn read(root: &Path, name: &str) -> Option<String> {
+    std::fs::read_to_string(root.join(name)).ok()
+}
+
+fn read_toml(root: &Path, name: &str) -> Option<toml::Value> {
+    read(root, name)?.parse().ok()
+}
+
+/// Crate names compare with `-` and `_` interchangeable.
+fn crate_key(name: &str) -> String {
+    name.to_ascii_lowercase().replace('_', "-")
+}
+
+/// PEP 503 normalized project name.
+fn python_key(name: &str) -> String {
+    let mut key = String::new();
+    for c in name.trim().to_ascii_lowercase().chars() {
+        if matches!(c, '-' | '_' | '.') {
+            if !key.ends_with('-') {
+                key.push('-');
+            }
+        } else {
+            key.push(c);
+        }
+    }
+    key
+}
+
+/// Version from the `[[package]]` entries of a Cargo, Poetry or uv lockfile.
+fn locked_version(lock: &toml::Value, key: &str, normalize: fn(&str) -> String) -> Option<String> {
+    lock.get("package")?.as_array()?.iter().find_map(|package| {
+        let name = package.get("name")?.as_str()?;
+        if normalize(name) != key {
+            return None;
+        }
+        package.get("version")?.as_str().map(str::to_string)
+    })
+}
+
+/// Lowest version a requirement such as `^1.2`, `~=2.0` or `>=3, <4` admits.
+fn requirement_version(requirement: &str) -> Option<String> {
+    let first = requirement.split(',').next()?.trim();
+    let version = first.trim_start_matches(['^', '~', '=', '>', '<', '!', 'v', ' ']);
+    let version = version.split_whitespace().next()?;
+    version
+        .starts_with(|c: char| c.is_ascii_digit())
+        .then(|| version.trim_end_matches(".*").to_string())
+}
+
+fn rust_version(root: &Path, name: &str) -> Option<(String, String)> {
+    let key = crate_key(name);
+    if let Some(version) = read_toml(root, "Cargo.lock")
+        .as_ref()
+        .and_then(|lock| locked_version(lock, &key, crate_key))
+    {
+        return Some((version, "Cargo.lock".to_string()));
+    }
+
+    let manifest = read_toml(root, "Cargo.toml")?;
+    let tables = [
+        manifest.get("dependencies"),
+        manifest.get("dev-dependencies"),
+        manifest.get("build-dependencies"),
+        manifest
+            .get("workspace")
+            .and_then(|workspace| workspace.get("dependencies")),
+    ];
+    tables.into_iter().flatten().find_map(|table| {
+        let (_, spec) = table
+            .as_table()?
+            .iter()
+            .find(|(dep, _)| crate_key(dep) == key)?;
+        let requirement = match spec {
+            toml::Value::String(requirement) => requirement.as_str(),
+            other => other.get("version")?.as_str()?,
+        };
+        Some((requirement_version(requirement)?, "Cargo.toml".to_string()))
+    })
+}
+
+fn node_version(root: &Path, name: &str) -> Option<(String, String)> {
+    let installed = format!("node_modules/{name}");
+    if let Some(lock) = read(root, "package-lock.json")
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+    {
+        let version = lock
+            .pointer(&format!("/packages/{}", installed.replace('/', "~1")))
+            .or_else(|| lock.get("dependencies").and_then(|deps| deps.get(name)))
+            .and_then(|package| package.get("version"))
+            .and_then(Value::as_str);
+        if let Some(version) = version {
+            return Some((version.to_string(), "package-lock.json".to_string()));
+        }
+    }
+    if let Some(version) = read(root, &format!("{installed}/package.json"))
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+        .and_then(|package| package.get("version")?.as_str().map(str::to_string))
+    {
+        return Some((version, format!("{installed}/package.json")));
+    }
+
+    let manifest: Value = serde_json::from_str(&read(root, "package.json")?).ok()?;
+    ["dependencies", "devDependencies", "peerDependencies"]
+        .iter()
+        .find_map(|field| {
+            let requirement = manifest.get(field)?.get(name)?.as_str()?;
+            Some((
+                requirement_version(requirement)?,
+                "package.json".to_string(),
+            ))
+        })
+}
+
+fn python_version(root: &Path, name: &str) -> Option<(String, String)> {
+    let key = python_key(name);
+    for lockfile in ["uv.lock", "poetry.lock"] {
+        if let Some(version) = read_toml(root, lockfile)
+            .as_ref()
+            .and_then(|lock| locked_version(lock, &key, python_key))
+        {
+            return Some((version, lockfile.to_string()));
+        }
+    }
+
+    let requirement_lines = read(root, "requirements.txt")
+        .map(|content| content.lines().map(str::to_string).collect::<Vec<_>>())
+        .unwrap_or_default();
+    let pyproject = read_toml(root, "pyproject.toml");
+    let project_dependencies = pyproject
+        .as_ref()
+        .and_then(|pyproject| pyproject.get("project")?.get("dependencies")?.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| item.as_str().map(str::to_string))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    for (lines, source) in [
+        (requirement_lines, "requirements.txt"),
+        (project_dependencies, "pyproject.toml"),
+    ] {
+        for line in lines {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let split = line
+                .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+                .unwrap_or(line.len());
+            let (package, rest) = line.split_at(split);
+            if package.is_empty() || python_key(package) != key {
+                continue;
+            }
+            // Drop extras and environment markers: `pkg[extra]>=1.0; python_version<"3.9"`
+            let rest = rest.split(';').next().unwrap_or_default();
+            let rest = rest.rsplit(']').next().unwrap_or_default();
+            if let Some(version) = requirement_version(rest) {
+                return Some((version, source.to_string()));
+            }
+        }
+    }
+
+    // Poetry keeps plain requirements in its own table
+    let requirement = pyproject
+        .as_ref()?
+        .get("tool")?
+        .get("poetry")?
+        .get("dependencies")?
+        .as_table()?
+        .iter()
+        .find(|(dep, _)| python_key(dep) == key)
+        .and_then(|(_, spec)| match spec {
+            toml::Value::String(requirement) => Some(requirement.clone()),
+            other => other.get("version")?.as_str().map(str::to_string),
+        })?;
+    Some((
+        requirement_version(&requirement)?,
+        "pyproject.toml".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefers_lockfiles_over_requirements() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"app\"\n[dependencies]\nserde_json = \"1.0\"\ntokio = { version = \"^1.38\", features = [\"full\"] }\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("Cargo.lock"),
+            "[[package]]\nname = \"serde_json\"\nversion = \"1.0.120\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("requirements.txt"),
+            "Django[argon2]~=4.2 ; python_version >= \"3.8\"\n",
+        )
+        .unwrap();
+
+        let serde = resolve_package(dir.path(), "serde-json", None, None).unwrap();
+        assert_eq!(serde.ecosystem, Ecosystem::Crates);
+        assert_eq!(serde.version, "1.0.120");
+        assert_eq!(serde.source.as_deref(), Some("Cargo.lock"));
+
+        let tokio = resolve_package(dir.path(), "tokio", None, None).unwrap();
+        assert_eq!(tokio.version, "1.38");
+        assert_eq!(tokio.source.as_deref(), Some("Cargo.toml"));
+
+        let django = resolve_package(dir.path(), "django", None, None).unwrap();
+        assert_eq!(django.ecosystem, Ecosystem::Pypi);
+        assert_eq!(django.version, "4.2");
+
+        let react = resolve_package(dir.path(), "react", Some(Ecosystem::Npm), None).unwrap();
+        assert_eq!(react.version, "latest");
+        assert!(resolve_package(dir.path(), "react", None, None).is_err());
+    }
+}
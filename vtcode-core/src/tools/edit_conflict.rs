@@ -0,0 +1,427 @@
+//! Detecting edits that overlap changes made outside the agent
+//!
+//! [`FileSnapshots`] remembers the content of each file as the agent last read
+//! or wrote it. Before the file tools overwrite a file they compare it with
+//! that snapshot (modification time and size first, content second). When the
+//! file changed in the meantime, usually because the user saved it in an
+//! editor, the agent's version is merged with the one on disk using the
+//! snapshot as the common base. Changes to separate regions are rebased onto
+//! the user's version; overlapping changes are refused with an
+//! [`EditConflict`] carrying all three versions, which the registry reports
+//! as a structured `EditConflict` tool error.
+
+use anyhow::Result;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+/// Files larger than this are not tracked.
+const MAX_SNAPSHOT_BYTES: usize = 1024 * 1024;
+
+/// Line-pair budget of the diff between the changed middles of two versions.
+/// Larger changes are treated as one region.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Modification times closer than this to the snapshot are not trusted.
+const RACY_WINDOW: Duration = Duration::from_secs(2);
+
+/// One region both sides changed differently.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConflictHunk {
+    /// 1-based line of the region in the base version
+    pub line: usize,
+    pub base: String,
+    /// The version on disk
+    pub mine: String,
+    /// The version the agent meant to write
+    pub agent: String,
+}
+
+/// A three-way merge of a base version with two edited ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Merge {
+    /// The merged text, with conflict markers around overlapping regions
+    pub merged: String,
+    pub conflicts: Vec<ConflictHunk>,
+}
+
+impl Merge {
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// An edit refused because the file changed on disk in the same regions.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "Edit conflict: {path} was changed outside the agent since it was last read, and {} region(s) overlap the edit. Nothing was written.",
+    self.hunks.len()
+)]
+pub struct EditConflict {
+    pub path: String,
+    pub base: String,
+    pub mine: String,
+    pub agent: String,
+    /// Both versions with conflict markers around the overlapping regions
+    pub merged: String,
+    pub hunks: Vec<ConflictHunk>,
+}
+
+impl EditConflict {
+    /// Conflict details attached to the tool error.
+    pub fn to_json_value(&self) -> Value {
+        json!({
+            "path": self.path,
+            "hunks": self.hunks,
+            "base": self.base,
+            "mine": self.mine,
+            "agent": self.agent,
+            "merged": self.merged,
+        })
+    }
+
+    /// The conflict reported in a tool error, if the output is one.
+    pub fn from_tool_output(output: &Value) -> Option<Self> {
+        let conflict = output.pointer("/error/conflict")?;
+        let text = |key: &str| conflict.get(key)?.as_str().map(str::to_string);
+        Some(Self {
+            path: text("path")?,
+            base: text("base")?,
+            mine: text("mine")?,
+            agent: text("agent")?,
+            merged: text("merged")?,
+            hunks: serde_json::from_value(conflict.get("hunks")?.clone()).ok()?,
+        })
+    }
+}
+
+/// What to write after checking a file for outside changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Checked {
+    /// The file is as the agent last saw it
+    Unchanged(String),
+    /// The file changed elsewhere; the edit was merged onto it
+    Rebased(String),
+}
+
+#[derive(Debug, Clone)]
+struct Snapshot {
+    content: String,
+    modified: Option<SystemTime>,
+    len: u64,
+    recorded: SystemTime,
+}
+
+impl Snapshot {
+    /// Whether size and modification time show the file is unchanged. A file
+    /// modified within the timestamp granularity of the snapshot could have
+    /// changed again without a new time, so its content is compared instead.
+    fn matches(&self, metadata: &std::fs::Metadata) -> bool {
+        let Some(modified) = self.modified else {
+            return false;
+        };
+        let settled = self
+            .recorded
+            .duration_since(modified)
+            .is_ok_and(|age| age >= RACY_WINDOW);
+        settled && metadata.len() == self.len && metadata.modified().ok() == Some(modified)
+    }
+}
+
+/// Content of files as the agent last read or wrote them; shared by clones.
+#[derive(Debug, Clone, Default)]
+pub struct FileSnapshots {
+    files: Arc<Mutex<HashMap<PathBuf, Snapshot>>>,
+}
+
+impl FileSnapshots {
+    /// Remember `content` as the agent's view of `path`, which must have just
+    /// been read or written.
+    pub fn record(&self, path: &Path, content: &str) {
+        if content.len() > MAX_SNAPSHOT_BYTES {
+            self.forget(path);
+            return;
+        }
+        let metadata = std::fs::metadata(path).ok();
+        let snapshot = Snapshot {
+            content: content.to_string(),
+            modified: metadata.as_ref().and_then(|meta| meta.modified().ok()),
+            len: metadata.map_or(content.len() as u64, |meta| meta.len()),
+            recorded: SystemTime::now(),
+        };
+        self.files.lock().insert(path.to_path_buf(), snapshot);
+    }
+
+    pub fn forget(&self, path: &Path) {
+        self.files.lock().remove(path);
+    }
+
+    /// The content the agent last saw at `path`.
+    pub fn base(&self, path: &Path) -> Option<String> {
+        self.files
+            .lock()
+            .get(path)
+            .map(|snapshot| snapshot.content.clone())
+    }
+
+    /// Check `path` for changes made since the agent last saw it before
+    /// writing `intended` there, merging the two when they do not overlap.
+    /// Fails with an [`EditConflict`] when they do.
+    pub fn check(&self, path: &Path, display_path: &str, intended: &str) -> Result<Checked> {
+        let Some(snapshot) = self.files.lock().get(path).cloned() else {
+            return Ok(Checked::Unchanged(intended.to_string()));
+        };
+        let Ok(metadata) = std::fs::metadata(path) else {
+            return Ok(Checked::Unchanged(intended.to_string()));
+        };
+        if snapshot.matches(&metadata) {
+            return Ok(Checked::Unchanged(intended.to_string()));
+        }
+        let Ok(current) = std::fs::read_to_string(path) else {
+            return Ok(Checked::Unchanged(intended.to_string()));
+        };
+        if current == snapshot.content {
+            return Ok(Checked::Unchanged(intended.to_string()));
+        }
+        let merge = merge3(&snapshot.content, &current, intended);
+        if merge.is_clean() {
+            return Ok(Checked::Rebased(merge.merged));
+        }
+        Err(EditConflict {
+            path: display_path.to_string(),
+            base: snapshot.content,
+            mine: current,
+            agent: intended.to_string(),
+            merged: merge.merged,
+            hunks: merge.conflicts,
+        }
+        .into())
+    }
+}
+
+/// A run of base lines `start..end` replaced by `lines`.
+#[derive(Debug, Clone)]
+struct Hunk<'a> {
+    start: usize,
+    end: usize,
+    lines: &'a [&'a str],
+}
+
+/// Merge the changes from `base` to `mine` and from `base` to `agent`, line by
+/// line. Regions changed on only one side, or identically on both, merge
+/// cleanly; changes that overlap or touch become conflicts.
+pub fn merge3(base: &str, mine: &str, agent: &str) -> Merge {
+    let base_lines: Vec<&str> = base.split_inclusive('\n').collect();
+    let mine_lines: Vec<&str> = mine.split_inclusive('\n').collect();
+    let agent_lines: Vec<&str> = agent.split_inclusive('\n').collect();
+    let ours = diff(&base_lines, &mine_lines).unwrap_or_else(|| whole(&base_lines, &mine_lines));
+    let theirs =
+        diff(&base_lines, &agent_lines).unwrap_or_else(|| whole(&base_lines, &agent_lines));
+
+    let mut merged = String::new();
+    let mut conflicts = Vec::new();
+    let mut position = 0;
+    let (mut i, mut j) = (0, 0);
+    while i < ours.len() || j < theirs.len() {
+        let start = match (ours.get(i), theirs.get(j)) {
+            (Some(a), Some(b)) => a.start.min(b.start),
+            (Some(a), None) => a.start,
+            (None, Some(b)) => b.start,
+            (None, None) => break,
+        };
+        let (mut next_i, mut next_j, mut end) = (i, j, start);
+        loop {
+            let mut grew = false;
+            while let Some(hunk) = ours.get(next_i).filter(|hunk| hunk.start <= end) {
+                end = end.max(hunk.end);
+                next_i += 1;
+                grew = true;
+            }
+            while let Some(hunk) = theirs.get(next_j).filter(|hunk| hunk.start <= end) {
+                end = end.max(hunk.end);
+                next_j += 1;
+                grew = true;
+            }
+            if !grew {
+                break;
+            }
+        }
+
+        merged.push_str(&base_lines[position..start].concat());
+        let mine_region = apply(&base_lines, start, end, &ours[i..next_i]);
+        let agent_region = apply(&base_lines, start, end, &theirs[j..next_j]);
+        if next_j == j || mine_region == agent_region {
+            merged.push_str(&mine_region);
+        } else if next_i == i {
+            merged.push_str(&agent_region);
+        } else {
+            let base_region: String = base_lines[start..end].concat();
+            push_marked(&mut merged, "<<<<<<< mine", &mine_region);
+            push_marked(&mut merged, "||||||| base", &base_region);
+            push_marked(&mut merged, "=======", &agent_region);
+            merged.push_str(">>>>>>> agent\n");
+            conflicts.push(ConflictHunk {
+                line: start + 1,
+                base: base_region,
+                mine: mine_region,
+                agent: agent_region,
+            });
+        }
+        position = end;
+        i = next_i;
+        j = next_j;
+    }
+    merged.push_str(&base_lines[position..].concat());
+    Merge { merged, conflicts }
+}
+
+/// The whole file as one change, for versions too different to diff.
+fn whole<'a>(base: &[&str], lines: &'a [&'a str]) -> Vec<Hunk<'a>> {
+    vec![Hunk {
+        start: 0,
+        end: base.len(),
+        lines,
+    }]
+}
+
+/// Base lines `start..end` with `hunks` applied.
+fn apply(base: &[&str], start: usize, end: usize, hunks: &[Hunk<'_>]) -> String {
+    let mut text = String::new();
+    let mut position = start;
+    for hunk in hunks {
+        text.push_str(&base[position..hunk.start].concat());
+        text.push_str(&hunk.lines.concat());
+        position = hunk.end;
+    }
+    text.push_str(&base[position..end].concat());
+    text
+}
+
+fn push_marked(merged: &mut String, marker: &str, region: &str) {
+    merged.push_str(marker);
+    merged.push('\n');
+    merged.push_str(region);
+    if !region.is_empty() && !region.ends_with('\n') {
+        merged.push('\n');
+    }
+}
+
+/// Changes from `a` to `b` as hunks over `a`, from a longest common
+/// subsequence of lines. `None` when the changed middles are too large.
+fn diff<'a>(a: &[&str], b: &'a [&'a str]) -> Option<Vec<Hunk<'a>>> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let a_mid = &a[prefix..a.len() - suffix];
+    let b_mid = &b[prefix..b.len() - suffix];
+    let (n, m) = (a_mid.len(), b_mid.len());
+    if n.saturating_mul(m) > MAX_DIFF_CELLS {
+        return None;
+    }
+
+    // lengths[i][j]: longest common subsequence of a_mid[i..] and b_mid[j..]
+    let width = m + 1;
+    let mut lengths = vec![0u32; (n + 1) * width];
+    for x in (0..n).rev() {
+        for y in (0..m).rev() {
+            lengths[x * width + y] = if a_mid[x] == b_mid[y] {
+                lengths[(x + 1) * width + y + 1] + 1
+            } else {
+                lengths[(x + 1) * width + y].max(lengths[x * width + y + 1])
+            };
+        }
+    }
+
+    let mut hunks = Vec::new();
+    let (mut x, mut y) = (0, 0);
+    let (mut hunk_x, mut hunk_y) = (0, 0);
+    let mut flush = |x: usize, y: usize, hunk_x: usize, hunk_y: usize| {
+        if x > hunk_x || y > hunk_y {
+            hunks.push(Hunk {
+                start: prefix + hunk_x,
+                end: prefix + x,
+                lines: &b[prefix + hunk_y..prefix + y],
+            });
+        }
+    };
+    while x < n && y < m {
+        if a_mid[x] == b_mid[y] {
+            flush(x, y, hunk_x, hunk_y);
+            x += 1;
+            y += 1;
+            (hunk_x, hunk_y) = (x, y);
+        } else if lengths[(x + 1) * width + y] >= lengths[x * width + y + 1] {
+            x += 1;
+        } else {
+            y += 1;
+        }
+    }
+    flush(n, m, hunk_x, hunk_y);
+    Some(hunks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rebases_separate_changes_and_marks_overlapping_ones() {
+        let base = "fn main() {\n    one();\n    two();\n    three();\n}\n";
+        let mine = "// edited by hand\nfn main() {\n    one();\n    two();\n    three();\n}\n";
+        let agent = "fn main() {\n    one();\n    two();\n    four();\n}\n";
+        let merge = merge3(base, mine, agent);
+        assert!(merge.is_clean());
+        assert_eq!(
+            merge.merged,
+            "// edited by hand\nfn main() {\n    one();\n    two();\n    four();\n}\n"
+        );
+        assert_eq!(merge3(base, agent, agent).merged, agent);
+
+        let mine = "fn main() {\n    one();\n    two();\n    3();\n}\n";
+        let merge = merge3(base, mine, agent);
+        assert_eq!(merge.conflicts.len(), 1);
+        assert_eq!(merge.conflicts[0].line, 4);
+        assert_eq!(merge.conflicts[0].mine, "    3();\n");
+        assert_eq!(merge.conflicts[0].agent, "    four();\n");
+        assert!(merge.merged.contains(
+            "<<<<<<< mine\n    3();\n||||||| base\n    three();\n=======\n    four();\n>>>>>>> agent\n"
+        ));
+    }
+
+    #[test]
+    fn checks_files_against_the_last_seen_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        let snapshots = FileSnapshots::default();
+        std::fs::write(&path, "a\nb\nc\n").unwrap();
+        snapshots.record(&path, "a\nb\nc\n");
+        assert_eq!(
+            snapshots.check(&path, "notes.txt", "a\nb\nC\n").unwrap(),
+            Checked::Unchanged("a\nb\nC\n".to_string())
+        );
+
+        std::fs::write(&path, "A\nb\nc\n").unwrap();
+        assert_eq!(
+            snapshots.check(&path, "notes.txt", "a\nb\nC\n").unwrap(),
+            Checked::Rebased("A\nb\nC\n".to_string())
+        );
+        let conflict = snapshots
+            .check(&path, "notes.txt", "x\nb\nc\n")
+            .unwrap_err()
+            .downcast::<EditConflict>()
+            .unwrap();
+        assert_eq!(conflict.mine, "A\nb\nc\n");
+        assert_eq!(conflict.hunks.len(), 1);
+
+        let output = json!({ "error": { "conflict": conflict.to_json_value() } });
+        assert_eq!(EditConflict::from_tool_output(&output), Some(conflict));
+    }
+}
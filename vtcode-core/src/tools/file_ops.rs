@@ -1,8 +1,13 @@
 //! File operation tools with composable functionality
 
+use super::edit_conflict::{Checked, FileSnapshots};
+use super::path_grants::PathGrants;
 use super::traits::{CacheableTool, FileTool, ModeTool, Tool};
 use super::types::*;
+use super::write_quota::WriteQuota;
 use crate::tools::grep_search::GrepSearchManager;
+use crate::utils::index_exclude;
+use crate::utils::long_lines;
 use crate::utils::vtcodegitignore::should_exclude_file;
 use anyhow::{Context, Result, anyhow};
 use async_trait::async_trait;
@@ -16,12 +21,47 @@ use walkdir::WalkDir;
 #[derive(Clone)]
 pub struct FileOpsTool {
     workspace_root: PathBuf,
+    quota: WriteQuota,
+    snapshots: FileSnapshots,
+    grants: PathGrants,
 }
 
 impl FileOpsTool {
     pub fn new(workspace_root: PathBuf, _grep_search: Arc<GrepSearchManager>) -> Self {
         // grep_search was unused; keep param to avoid broad call-site churn
-        Self { workspace_root }
+        Self {
+            workspace_root,
+            quota: WriteQuota::default(),
+            snapshots: FileSnapshots::default(),
+            grants: PathGrants::default(),
+        }
+    }
+
+    /// Paths outside the workspace that writes may go to; shared by all clones.
+    pub fn path_grants(&self) -> &PathGrants {
+        &self.grants
+    }
+
+    /// Limits applied to `write_file` and `edit_file`; shared by all clones.
+    pub fn write_quota(&self) -> &WriteQuota {
+        &self.quota
+    }
+
+    /// File content as last read or written by the agent; shared by all
+    /// clones. Overwrites are checked against it for outside changes.
+    pub fn snapshots(&self) -> &FileSnapshots {
+        &self.snapshots
+    }
+
+    /// Write `content` to `path` as the resolution of an edit conflict,
+    /// bypassing the conflict check.
+    pub async fn resolve_conflict(&self, path: &str, content: &str) -> Result<()> {
+        let file_path = self.workspace_root.join(path);
+        tokio::fs::write(&file_path, content)
+            .await
+            .with_context(|| format!("Failed to write {}", path))?;
+        self.snapshots.record(&file_path, content);
+        Ok(())
     }
 
     /// Execute basic directory listing
@@ -62,7 +102,9 @@ impl FileOpsTool {
                 if !input.include_hidden && name.starts_with('.') {
                     continue;
                 }
-                if self.should_exclude(&path).await {
+                if self.should_exclude(&path).await
+                    || self.is_generated(&path, path.is_dir(), input)
+                {
                     continue;
                 }
 
@@ -181,6 +223,12 @@ impl FileOpsTool {
         Ok(out)
     }
 
+    /// Whether a listing should skip `path` as generated code, unless the
+    /// call asked for `include_generated`.
+    fn is_generated(&self, path: &Path, is_dir: bool, input: &ListInput) -> bool {
+        !input.include_generated && index_exclude::current().is_excluded(path, is_dir)
+    }
+
     /// Execute recursive file search
     async fn execute_recursive_search(&self, input: &ListInput) -> Result<Value> {
         // Allow recursive listing without pattern by defaulting to "*" (match all)
@@ -199,7 +247,9 @@ impl FileOpsTool {
             let entry = entry.map_err(|e| anyhow!("Walk error: {}", e))?;
             let path = entry.path();
 
-            if self.should_exclude(path).await {
+            if self.should_exclude(path).await
+                || self.is_generated(path, entry.file_type().is_dir(), input)
+            {
                 continue;
             }
 
@@ -258,7 +308,9 @@ impl FileOpsTool {
             let entry = entry.map_err(|e| anyhow!("Walk error: {}", e))?;
             let path = entry.path();
 
-            if self.should_exclude(path).await {
+            if self.should_exclude(path).await
+                || self.is_generated(path, entry.file_type().is_dir(), input)
+            {
                 continue;
             }
 
@@ -314,7 +366,10 @@ impl FileOpsTool {
             let entry = entry.map_err(|e| anyhow!("Walk error: {}", e))?;
             let path = entry.path();
 
-            if !path.is_file() || self.should_exclude(path).await {
+            if !path.is_file()
+                || self.should_exclude(path).await
+                || self.is_generated(path, false, input)
+            {
                 continue;
             }
 
@@ -360,6 +415,12 @@ impl FileOpsTool {
             }
 
             if candidate_path.exists() && candidate_path.is_file() {
+                if input.max_bytes.is_none()
+                    && let Some(result) = self.read_long_line_file(candidate_path, &input).await?
+                {
+                    return Ok(result);
+                }
+
                 // Check if chunking is needed
                 let should_chunk = if let Some(max_lines) = input.max_lines {
                     // User specified max_lines threshold
@@ -397,7 +458,9 @@ impl FileOpsTool {
                         }
                         String::from_utf8_lossy(&file_content).to_string()
                     } else {
-                        tokio::fs::read_to_string(candidate_path).await?
+                        let content = tokio::fs::read_to_string(candidate_path).await?;
+                        self.snapshots.record(candidate_path, &content);
+                        content
                     };
                     (content, false, None)
                 };
@@ -452,9 +515,35 @@ impl FileOpsTool {
 
     /// Write file with various modes and chunking support for large content
     pub async fn write_file(&self, args: Value) -> Result<Value> {
-        let input: WriteInput = serde_json::from_value(args)
+        let mut input: WriteInput = serde_json::from_value(args)
             .context("Error: Invalid 'write_file' arguments. Required: {{ path: string, content: string }}. Optional: {{ mode: 'overwrite'|'append'|'skip_if_exists' }}. Example: write_file({{\"path\": \"README.md\", \"content\": \"Hello\", \"mode\": \"overwrite\"}})")?;
-        let file_path = self.workspace_root.join(&input.path);
+        let file_path = self
+            .grants
+            .resolve_write(&self.workspace_root, &input.path)?;
+
+        let exists = file_path.exists();
+        let mut rebased = false;
+        if input.mode == "overwrite" && exists {
+            match self
+                .snapshots
+                .check(&file_path, &input.path, &input.content)?
+            {
+                Checked::Unchanged(_) => {}
+                Checked::Rebased(content) => {
+                    input.content = content;
+                    rebased = true;
+                }
+            }
+        }
+        let writes = match input.mode.as_str() {
+            "overwrite" | "append" => true,
+            "skip_if_exists" => !exists,
+            _ => false,
+        };
+        if writes {
+            self.quota
+                .reserve(&input.path, input.content.len() as u64, !exists)?;
+        }
 
         // Check if content needs chunking
         let content_size = input.content.len();
@@ -462,7 +551,12 @@ impl FileOpsTool {
             content_size > crate::config::constants::chunking::MAX_WRITE_CONTENT_SIZE;
 
         if should_chunk {
-            return self.write_file_chunked(&file_path, &input).await;
+            let mut result = self.write_file_chunked(&file_path, &input).await?;
+            self.record_write(&file_path, &input);
+            if rebased {
+                result["rebased"] = json!(true);
+            }
+            return Ok(result);
         }
 
         // Create parent directories if needed
@@ -501,16 +595,34 @@ impl FileOpsTool {
             }
         }
 
+        self.record_write(&file_path, &input);
+
         // Log write operation
         self.log_write_operation(&file_path, content_size, false)
             .await?;
 
-        Ok(json!({
+        let mut result = json!({
             "success": true,
             "path": input.path,
             "mode": input.mode,
             "bytes_written": input.content.len()
-        }))
+        });
+        if rebased {
+            result["rebased"] = json!(true);
+            result["note"] = json!(
+                "The file was changed outside the agent since it was read; the edit was merged onto those changes. Read it again before further edits."
+            );
+        }
+        Ok(result)
+    }
+
+    /// Update the snapshot of a file after `input` was written to it.
+    fn record_write(&self, file_path: &Path, input: &WriteInput) {
+        if input.mode == "append" {
+            self.snapshots.forget(file_path);
+        } else {
+            self.snapshots.record(file_path, &input.content);
+        }
     }
 
     /// Write large file in chunks for atomicity and memory efficiency
@@ -672,9 +784,12 @@ impl ModeTool for FileOpsTool {
 impl CacheableTool for FileOpsTool {
     fn cache_key(&self, args: &Value) -> String {
         format!(
-            "files:{}:{}",
+            "files:{}:{}:{}",
             args.get("path").and_then(|p| p.as_str()).unwrap_or(""),
-            args.get("mode").and_then(|m| m.as_str()).unwrap_or("list")
+            args.get("mode").and_then(|m| m.as_str()).unwrap_or("list"),
+            args.get("include_generated")
+                .and_then(|g| g.as_bool())
+                .unwrap_or(false)
         )
     }
 
@@ -847,6 +962,60 @@ impl FileOpsTool {
         Ok((chunked_content, true, Some(total_lines)))
     }
 
+    /// Read a file with very long lines (minified code, JSON blobs) by byte
+    /// range, or `None` when it is short enough or has ordinary lines.
+    async fn read_long_line_file(&self, file_path: &Path, input: &Input) -> Result<Option<Value>> {
+        let content = tokio::fs::read_to_string(file_path).await?;
+        if input.byte_offset.is_none()
+            && (content.len() <= long_lines::LONG_LINE_CHUNK_BYTES
+                || !long_lines::has_long_lines(&content))
+        {
+            return Ok(None);
+        }
+        let path = file_path
+            .strip_prefix(&self.workspace_root)
+            .unwrap_or(file_path)
+            .to_string_lossy();
+        let chunk = long_lines::byte_chunk(
+            &path,
+            &content,
+            input.byte_offset.unwrap_or(0),
+            input
+                .byte_length
+                .unwrap_or(long_lines::LONG_LINE_CHUNK_BYTES)
+                .max(1),
+        );
+        let mut result = json!({
+            "success": true,
+            "content": chunk.text,
+            "path": path,
+            "byte_range": [chunk.start, chunk.end],
+            "total_bytes": content.len(),
+            "metadata": {
+                "size": chunk.end - chunk.start
+            }
+        });
+        if let Some(hint) = chunk.hint {
+            result["starts_at"] = json!(hint);
+        }
+        if chunk.end < content.len() {
+            result["truncated"] = json!(true);
+            result["truncation_reason"] = json!("long_lines");
+            result["next_byte_offset"] = json!(chunk.end);
+            result["message"] = json!(format!(
+                "Showing bytes {}-{} of {}. The file has very long lines, so it is read by byte range; pass byte_offset {} to continue.",
+                chunk.start,
+                chunk.end,
+                content.len(),
+                chunk.end
+            ));
+        }
+        if chunk.start == 0 && chunk.end == content.len() {
+            self.snapshots.record(file_path, &content);
+        }
+        Ok(Some(result))
+    }
+
     /// Log chunking operations for debugging
     async fn log_chunking_operation(
         &self,
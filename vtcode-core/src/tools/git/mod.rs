@@ -0,0 +1,531 @@
+//! Git operations with structured results
+//!
+//! [`GitTool`] backs `git_status`, `git_diff`, `git_log`, `git_commit` and
+//! `git_branch`, so the model works with the repository through JSON instead
+//! of running `git` through `run_terminal_cmd` and parsing its text. The three
+//! read tools are read-only and allowed by default; committing and creating or
+//! switching branches change the repository and go through the usual tool
+//! policy prompt. Everything runs `git` in the workspace with prompts and
+//! pagers turned off.
+
+mod status;
+
+pub use status::{FileChange, StatusReport};
+
+use crate::config::constants::tools;
+use crate::tools::traits::Tool;
+use anyhow::{Context, Result, anyhow, bail};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+use tokio::time::timeout;
+
+const GIT_TIMEOUT: Duration = Duration::from_secs(30);
+/// Longest patch returned by `git_diff`, in characters.
+const MAX_PATCH_CHARS: usize = 40_000;
+const DEFAULT_LOG_COUNT: usize = 20;
+const MAX_LOG_COUNT: usize = 200;
+/// Separates the fields of one `git log` or `git branch` record.
+const FIELD_SEP: char = '\u{1f}';
+/// Ends one `git log` record.
+const RECORD_SEP: char = '\u{1e}';
+
+#[derive(Debug, Default, Deserialize)]
+struct GitDiffInput {
+    /// Diff the index against HEAD instead of the working tree against the index
+    #[serde(default)]
+    staged: bool,
+    /// Compare the working tree with this revision, or a `a..b` / `a...b` range
+    #[serde(default)]
+    rev: Option<String>,
+    #[serde(default)]
+    paths: Vec<String>,
+    #[serde(default)]
+    context_lines: Option<u32>,
+    /// Only return per-file line counts
+    #[serde(default)]
+    stat_only: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct GitLogInput {
+    #[serde(default)]
+    max_count: Option<usize>,
+    #[serde(default)]
+    rev: Option<String>,
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(default)]
+    author: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitCommitInput {
+    message: String,
+    /// Stage these paths before committing
+    #[serde(default)]
+    paths: Vec<String>,
+    /// Stage every change, untracked files included, before committing
+    #[serde(default)]
+    all: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct GitBranchInput {
+    #[serde(default)]
+    action: Option<String>,
+    #[serde(default)]
+    name: Option<String>,
+    /// Where a new branch starts; HEAD when omitted
+    #[serde(default)]
+    start_point: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct GitTool {
+    workspace_root: PathBuf,
+}
+
+impl GitTool {
+    pub fn new(workspace_root: PathBuf) -> Self {
+        Self { workspace_root }
+    }
+
+    /// Branch, upstream and the staged, unstaged, untracked and conflicted paths.
+    pub async fn status(&self, _args: Value) -> Result<Value> {
+        let output = self
+            .git(&["status", "--porcelain=v2", "--branch", "-z"])
+            .await?;
+        let report = StatusReport::parse(&output);
+        let mut result = serde_json::to_value(&report)?;
+        result["success"] = json!(true);
+        result["clean"] = json!(report.is_clean());
+        Ok(result)
+    }
+
+    /// Changed files with line counts, plus the patch unless `stat_only`.
+    pub async fn diff(&self, args: Value) -> Result<Value> {
+        let input: GitDiffInput = parse_args(
+            args,
+            "Error: Invalid 'git_diff' arguments. Optional: { staged: bool, rev: string, paths: string[], context_lines: number, stat_only: bool }",
+        )?;
+        let mut selection: Vec<String> = Vec::new();
+        if input.staged {
+            selection.push("--cached".to_string());
+        }
+        if let Some(rev) = input.rev.as_deref() {
+            reject_option(rev, "rev")?;
+            selection.push(rev.to_string());
+        }
+        selection.push("--".to_string());
+        selection.extend(input.paths.iter().cloned());
+
+        let numstat = self
+            .git_owned(&["diff", "--numstat", "-z"], &selection)
+            .await?;
+        let files = parse_numstat(&numstat);
+        let mut result = json!({
+            "success": true,
+            "files": files,
+            "file_count": files.len(),
+        });
+        if !input.stat_only {
+            let context = format!("-U{}", input.context_lines.unwrap_or(3).min(20));
+            let patch = self
+                .git_owned(&["diff", "--no-ext-diff", &context], &selection)
+                .await?;
+            let truncated = patch.chars().count() > MAX_PATCH_CHARS;
+            result["patch"] = json!(if truncated {
+                patch.chars().take(MAX_PATCH_CHARS).collect::<String>()
+            } else {
+                patch
+            });
+            result["truncated"] = json!(truncated);
+        }
+        Ok(result)
+    }
+
+    /// Recent commits, newest first.
+    pub async fn log(&self, args: Value) -> Result<Value> {
+        let input: GitLogInput = parse_args(
+            args,
+            "Error: Invalid 'git_log' arguments. Optional: { max_count: number, rev: string, path: string, author: string }",
+        )?;
+        let count = input
+            .max_count
+            .unwrap_or(DEFAULT_LOG_COUNT)
+            .clamp(1, MAX_LOG_COUNT);
+        let mut args = vec![
+            "log".to_string(),
+            format!("--max-count={count}"),
+            format!(
+                "--format=%H{FIELD_SEP}%an{FIELD_SEP}%ae{FIELD_SEP}%aI{FIELD_SEP}%s{RECORD_SEP}"
+            ),
+        ];
+        if let Some(author) = input.author {
+            args.push(format!("--author={author}"));
+        }
+        if let Some(rev) = input.rev.as_deref() {
+            reject_option(rev, "rev")?;
+            args.push(rev.to_string());
+        }
+        args.push("--".to_string());
+        args.extend(input.path);
+        let output = self.git_owned(&[], &args).await?;
+        let commits: Vec<Value> = output
+            .split(RECORD_SEP)
+            .filter_map(|record| {
+                let fields: Vec<&str> = record.trim_start().split(FIELD_SEP).collect();
+                let [hash, author, email, date, subject] = fields.as_slice() else {
+                    return None;
+                };
+                Some(json!({
+                    "hash": hash,
+                    "short": &hash[..hash.len().min(8)],
+                    "author": author,
+                    "email": email,
+                    "date": date,
+                    "subject": subject,
+                }))
+            })
+            .collect();
+        Ok(json!({
+            "success": true,
+            "commits": commits,
+            "count": commits.len(),
+        }))
+    }
+
+    /// Commit the staged changes, staging `paths` or everything first if asked.
+    pub async fn commit(&self, args: Value) -> Result<Value> {
+        let input: GitCommitInput = serde_json::from_value(args).context(
+            "Error: Invalid 'git_commit' arguments. Required: { message: string }. Optional: { paths: string[], all: bool }",
+        )?;
+        if input.message.trim().is_empty() {
+            bail!("Error: 'git_commit' needs a non-empty message");
+        }
+        if input.all {
+            self.git(&["add", "--all"]).await?;
+        } else if !input.paths.is_empty() {
+            let mut add = vec!["add".to_string(), "--".to_string()];
+            add.extend(input.paths.iter().cloned());
+            self.git_owned(&[], &add).await?;
+        }
+        let staged = self.git(&["diff", "--cached", "--name-only", "-z"]).await?;
+        if staged.split('\0').all(str::is_empty) {
+            bail!("Nothing is staged to commit. Pass 'paths' or set 'all' to stage changes first");
+        }
+        self.git_with_input(&["commit", "--quiet", "--file=-"], &input.message)
+            .await?;
+        let hash = self.git(&["rev-parse", "HEAD"]).await?.trim().to_string();
+        let files = parse_numstat(
+            &self
+                .git(&["show", "--numstat", "-z", "--format=", "HEAD"])
+                .await?,
+        );
+        let branch = self.current_branch().await;
+        Ok(json!({
+            "success": true,
+            "commit": hash,
+            "short": &hash[..hash.len().min(8)],
+            "branch": branch,
+            "summary": input.message.lines().next().unwrap_or_default(),
+            "files": files,
+        }))
+    }
+
+    /// List branches, or create or switch to one.
+    pub async fn branch(&self, args: Value) -> Result<Value> {
+        let input: GitBranchInput = parse_args(
+            args,
+            "Error: Invalid 'git_branch' arguments. Optional: { action: \"list\" | \"create\" | \"switch\", name: string, start_point: string }",
+        )?;
+        let action = input.action.as_deref().unwrap_or("list");
+        let name = || -> Result<&str> {
+            let name = input
+                .name
+                .as_deref()
+                .filter(|name| !name.trim().is_empty())
+                .ok_or_else(|| anyhow!("Error: 'git_branch' {action} needs a 'name'"))?;
+            reject_option(name, "name")?;
+            Ok(name)
+        };
+        match action {
+            "list" => {}
+            "create" => {
+                let mut create = vec!["switch", "--create", name()?];
+                if let Some(start) = input.start_point.as_deref() {
+                    reject_option(start, "start_point")?;
+                    create.push(start);
+                }
+                self.git(&create).await?;
+            }
+            "switch" => {
+                self.git(&["switch", name()?]).await?;
+            }
+            other => bail!(
+                "Error: Unknown action '{}'. Use one of: list, create, switch",
+                other
+            ),
+        }
+        let format = format!(
+            "--format=%(refname:short){FIELD_SEP}%(objectname:short){FIELD_SEP}%(HEAD){FIELD_SEP}%(upstream:short)"
+        );
+        let output = self.git(&["branch", "--list", &format]).await?;
+        let branches: Vec<Value> = output
+            .lines()
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split(FIELD_SEP).collect();
+                let [name, commit, head, upstream] = fields.as_slice() else {
+                    return None;
+                };
+                Some(json!({
+                    "name": name,
+                    "commit": commit,
+                    "current": *head == "*",
+                    "upstream": (!upstream.is_empty()).then_some(*upstream),
+                }))
+            })
+            .collect();
+        Ok(json!({
+            "success": true,
+            "action": action,
+            "current": self.current_branch().await,
+            "branches": branches,
+        }))
+    }
+
+    async fn current_branch(&self) -> Option<String> {
+        self.git(&["symbolic-ref", "--quiet", "--short", "HEAD"])
+            .await
+            .ok()
+            .map(|name| name.trim().to_string())
+    }
+
+    async fn git(&self, args: &[&str]) -> Result<String> {
+        self.run(args, &[], None).await
+    }
+
+    async fn git_owned(&self, args: &[&str], rest: &[String]) -> Result<String> {
+        self.run(args, rest, None).await
+    }
+
+    async fn git_with_input(&self, args: &[&str], input: &str) -> Result<String> {
+        self.run(args, &[], Some(input)).await
+    }
+
+    async fn run(&self, args: &[&str], rest: &[String], input: Option<&str>) -> Result<String> {
+        let mut command = Command::new("git");
+        command
+            .args([
+                "--no-pager",
+                "-c",
+                "color.ui=false",
+                "-c",
+                "core.quotepath=false",
+            ])
+            .args(args)
+            .args(rest)
+            .current_dir(&self.workspace_root)
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .env("GIT_EDITOR", "true")
+            .stdin(if input.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        let mut child = command
+            .spawn()
+            .context("Failed to run git; is it installed?")?;
+        if let Some(input) = input
+            && let Some(mut stdin) = child.stdin.take()
+        {
+            use tokio::io::AsyncWriteExt;
+            stdin.write_all(input.as_bytes()).await?;
+        }
+        let output = timeout(GIT_TIMEOUT, child.wait_with_output())
+            .await
+            .map_err(|_| anyhow!("git timed out after {}s", GIT_TIMEOUT.as_secs()))??;
+        if !output.status.success() {
+            let subcommand = args
+                .first()
+                .copied()
+                .or_else(|| rest.first().map(String::as_str))
+                .unwrap_or_default();
+            bail!(
+                "git {} failed: {}",
+                subcommand,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+fn parse_args<T: Default + for<'de> Deserialize<'de>>(args: Value, usage: &str) -> Result<T> {
+    if args.is_null() {
+        return Ok(T::default());
+    }
+    serde_json::from_value(args).with_context(|| usage.to_string())
+}
+
+/// Revisions and names are passed as arguments, so they must not read as
+/// options.
+fn reject_option(value: &str, field: &str) -> Result<()> {
+    if value.starts_with('-') {
+        bail!("Error: '{}' must not start with '-'", field);
+    }
+    Ok(())
+}
+
+/// Entries of `git diff --numstat -z`; binary files have no line counts.
+fn parse_numstat(output: &str) -> Vec<Value> {
+    let mut files = Vec::new();
+    let mut records = output.split('\0').filter(|record| !record.is_empty());
+    while let Some(record) = records.next() {
+        let mut fields = record.trim_start_matches('\n').splitn(3, '\t');
+        let (Some(added), Some(removed), Some(path)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        // A rename leaves the path empty and is followed by the old and new paths.
+        let (path, from) = if path.is_empty() {
+            let from = records.next().unwrap_or_default();
+            (records.next().unwrap_or_default(), Some(from))
+        } else {
+            (path, None)
+        };
+        let binary = added == "-";
+        let mut entry = json!({
+            "path": path,
+            "additions": added.parse::<u64>().ok(),
+            "deletions": removed.parse::<u64>().ok(),
+            "binary": binary,
+        });
+        if let Some(from) = from {
+            entry["from"] = json!(from);
+        }
+        files.push(entry);
+    }
+    files
+}
+
+#[async_trait]
+impl Tool for GitTool {
+    async fn execute(&self, args: Value) -> Result<Value> {
+        self.status(args).await
+    }
+
+    fn name(&self) -> &'static str {
+        tools::GIT_STATUS
+    }
+
+    fn description(&self) -> &'static str {
+        "Show the branch and the changed files of the workspace repository"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    async fn repo() -> Option<(TempDir, GitTool)> {
+        let dir = TempDir::new().unwrap();
+        let tool = GitTool::new(dir.path().to_path_buf());
+        tool.git(&["init", "--quiet", "--initial-branch=main"])
+            .await
+            .ok()?;
+        tool.git(&["config", "user.name", "test"]).await.ok()?;
+        tool.git(&["config", "user.email", "test@example.com"])
+            .await
+            .ok()?;
+        Some((dir, tool))
+    }
+
+    #[test]
+    fn parses_porcelain_status() {
+        let output = concat!(
+            "# branch.oid 1234\0# branch.head main\0# branch.upstream origin/main\0",
+            "# branch.ab +2 -1\0",
+            "1 M. N... 100644 100644 100644 aaa bbb src/lib.rs\0",
+            "1 .D N... 100644 100644 000000 aaa aaa old notes.md\0",
+            "2 R. N... 100644 100644 100644 aaa aaa R100 src/new.rs\0src/old.rs\0",
+            "u UU N... 100644 100644 100644 100644 aaa bbb ccc Cargo.toml\0",
+            "? scratch.txt\0",
+        );
+        let report = StatusReport::parse(output);
+        assert_eq!(report.branch.as_deref(), Some("main"));
+        assert_eq!(report.upstream.as_deref(), Some("origin/main"));
+        assert_eq!((report.ahead, report.behind), (2, 1));
+        assert_eq!(report.staged.len(), 2);
+        assert_eq!(report.staged[1].change, "renamed");
+        assert_eq!(report.staged[1].from.as_deref(), Some("src/old.rs"));
+        assert_eq!(report.unstaged[0].path, "old notes.md");
+        assert_eq!(report.unstaged[0].change, "deleted");
+        assert_eq!(report.conflicted, ["Cargo.toml"]);
+        assert_eq!(report.untracked, ["scratch.txt"]);
+        assert!(!report.is_clean());
+    }
+
+    #[tokio::test]
+    async fn stages_commits_and_branches() {
+        let Some((dir, tool)) = repo().await else {
+            return;
+        };
+        fs::write(dir.path().join("a.txt"), "one\n").unwrap();
+        let status = tool.status(Value::Null).await.unwrap();
+        assert_eq!(status["untracked"], json!(["a.txt"]));
+        assert!(
+            tool.commit(json!({ "message": "empty" }))
+                .await
+                .unwrap_err()
+                .to_string()
+                .contains("Nothing is staged")
+        );
+
+        let first = tool
+            .commit(json!({ "message": "Add a.txt\n\nBody", "all": true }))
+            .await
+            .unwrap();
+        assert_eq!(first["branch"], "main");
+        assert_eq!(first["summary"], "Add a.txt");
+        assert_eq!(first["files"][0]["additions"], 1);
+        assert_eq!(tool.status(Value::Null).await.unwrap()["clean"], true);
+
+        fs::write(dir.path().join("a.txt"), "one\ntwo\n").unwrap();
+        let diff = tool.diff(Value::Null).await.unwrap();
+        assert_eq!(diff["files"][0]["path"], "a.txt");
+        assert!(diff["patch"].as_str().unwrap().contains("+two"));
+        assert!(
+            tool.diff(json!({ "staged": true })).await.unwrap()["files"]
+                .as_array()
+                .unwrap()
+                .is_empty()
+        );
+
+        let branch = tool
+            .branch(json!({ "action": "create", "name": "feature" }))
+            .await
+            .unwrap();
+        assert_eq!(branch["current"], "feature");
+        assert_eq!(branch["branches"].as_array().unwrap().len(), 2);
+        tool.commit(json!({ "message": "Second line", "paths": ["a.txt"] }))
+            .await
+            .unwrap();
+
+        let log = tool.log(json!({ "max_count": 5 })).await.unwrap();
+        assert_eq!(log["count"], 2);
+        assert_eq!(log["commits"][0]["subject"], "Second line");
+        assert!(tool.log(json!({ "rev": "--output=/tmp/x" })).await.is_err());
+    }
+}
@@ -0,0 +1,125 @@
+use serde::Serialize;
+
+/// A changed path in the index or the working tree.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileChange {
+    pub path: String,
+    /// added, modified, deleted, renamed, copied or type_changed
+    pub change: &'static str,
+    /// Path before a rename or copy
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+}
+
+/// Parsed output of `git status --porcelain=v2 --branch -z`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct StatusReport {
+    /// Current branch, `None` on a detached HEAD
+    pub branch: Option<String>,
+    pub upstream: Option<String>,
+    pub ahead: u64,
+    pub behind: u64,
+    pub staged: Vec<FileChange>,
+    pub unstaged: Vec<FileChange>,
+    pub untracked: Vec<String>,
+    /// Paths with unresolved merge conflicts
+    pub conflicted: Vec<String>,
+}
+
+impl StatusReport {
+    pub fn is_clean(&self) -> bool {
+        self.staged.is_empty()
+            && self.unstaged.is_empty()
+            && self.untracked.is_empty()
+            && self.conflicted.is_empty()
+    }
+
+    pub fn parse(output: &str) -> Self {
+        let mut report = Self::default();
+        let mut records = output.split('\0').filter(|record| !record.is_empty());
+        while let Some(record) = records.next() {
+            if let Some(header) = record.strip_prefix("# ") {
+                report.parse_header(header);
+                continue;
+            }
+            let (kind, rest) = record.split_at(1);
+            let rest = rest.trim_start();
+            match kind {
+                "1" => {
+                    // XY sub mH mI mW hH hI path
+                    let fields: Vec<&str> = rest.splitn(8, ' ').collect();
+                    if let [xy, .., path] = fields.as_slice() {
+                        report.push_changes(xy, path, None);
+                    }
+                }
+                "2" => {
+                    // XY sub mH mI mW hH hI Xscore path, then the original path
+                    let fields: Vec<&str> = rest.splitn(9, ' ').collect();
+                    if let [xy, .., path] = fields.as_slice() {
+                        let from = records.next().map(str::to_string);
+                        report.push_changes(xy, path, from);
+                    }
+                }
+                "u" => {
+                    if let Some(path) = rest.splitn(10, ' ').nth(9) {
+                        report.conflicted.push(path.to_string());
+                    }
+                }
+                "?" => report.untracked.push(rest.to_string()),
+                _ => {}
+            }
+        }
+        report
+    }
+
+    fn parse_header(&mut self, header: &str) {
+        let Some((key, value)) = header.split_once(' ') else {
+            return;
+        };
+        match key {
+            "branch.head" if value != "(detached)" => self.branch = Some(value.to_string()),
+            "branch.upstream" => self.upstream = Some(value.to_string()),
+            "branch.ab" => {
+                for count in value.split_whitespace() {
+                    if let Some(ahead) = count.strip_prefix('+') {
+                        self.ahead = ahead.parse().unwrap_or_default();
+                    } else if let Some(behind) = count.strip_prefix('-') {
+                        self.behind = behind.parse().unwrap_or_default();
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn push_changes(&mut self, xy: &str, path: &str, from: Option<String>) {
+        let mut codes = xy.chars();
+        let (index, worktree) = (codes.next(), codes.next());
+        if let Some(change) = index.and_then(change_name) {
+            self.staged.push(FileChange {
+                path: path.to_string(),
+                change,
+                from: from.clone(),
+            });
+        }
+        if let Some(change) = worktree.and_then(change_name) {
+            self.unstaged.push(FileChange {
+                path: path.to_string(),
+                change,
+                from: None,
+            });
+        }
+    }
+}
+
+fn change_name(code: char) -> Option<&'static str> {
+    match code {
+        'A' => Some("added"),
+        'M' => Some("modified"),
+        'D' => Some("deleted"),
+        'R' => Some("renamed"),
+        'C' => Some("copied"),
+        'T' => Some("type_changed"),
+        _ => None,
+    }
+}
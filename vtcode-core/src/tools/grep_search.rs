@@ -11,16 +11,20 @@
 //! 4. If there is an in-flight search that is not a prefix of the latest thing
 //!    the user typed, it is cancelled.
 
+use crate::core::sensitive_files::is_read_denied;
+use crate::tools::cancellation::command_output;
+use crate::utils::index_exclude;
 use anyhow::Result;
 use serde_json;
 use std::num::NonZeroUsize;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
 use std::thread;
 use std::time::Duration;
+use tokio_util::sync::CancellationToken;
 
 /// Maximum number of search results to return
 const MAX_SEARCH_RESULTS: NonZeroUsize = NonZeroUsize::new(100).unwrap();
@@ -46,6 +50,8 @@ pub struct GrepSearchInput {
     pub context_lines: Option<usize>,
     pub include_hidden: Option<bool>,
     pub max_results: Option<usize>,
+    /// Also search paths excluded by `[index.exclude]`
+    pub include_generated: Option<bool>,
 }
 
 /// Result of a ripgrep search
@@ -204,6 +210,11 @@ impl GrepSearchManager {
             // Add the search path
             cmd.arg(search_dir.to_string_lossy().as_ref());
 
+            // Skip generated code excluded by [index.exclude]
+            for glob in index_exclude::current().ripgrep_globs() {
+                cmd.arg("--glob").arg(glob);
+            }
+
             // Output as JSON for easier parsing
             cmd.arg("--json");
 
@@ -225,6 +236,7 @@ impl GrepSearchManager {
                                 matches.push(val);
                             }
                         }
+                        drop_denied_paths(&search_dir, &mut matches);
                         let result = GrepSearchResult {
                             query: query.clone(),
                             matches,
@@ -249,10 +261,14 @@ impl GrepSearchManager {
         });
     }
 
-    /// Perform an actual ripgrep search with the given input parameters
-    pub async fn perform_search(&self, input: GrepSearchInput) -> Result<GrepSearchResult> {
-        // std::path::Path import removed as it's not directly used
-        use std::process::Command;
+    /// Perform an actual ripgrep search with the given input parameters,
+    /// killing ripgrep if `cancel` fires first
+    pub async fn perform_search(
+        &self,
+        input: GrepSearchInput,
+        cancel: &CancellationToken,
+    ) -> Result<GrepSearchResult> {
+        use tokio::process::Command;
 
         // Build the ripgrep command
         let mut cmd = Command::new("rg");
@@ -282,6 +298,12 @@ impl GrepSearchManager {
             cmd.arg("--glob").arg(glob_pattern);
         }
 
+        if !input.include_generated.unwrap_or(false) {
+            for glob in index_exclude::current().ripgrep_globs() {
+                cmd.arg("--glob").arg(glob);
+            }
+        }
+
         if let Some(context_lines) = input.context_lines {
             cmd.arg("--context").arg(context_lines.to_string());
         }
@@ -300,7 +322,7 @@ impl GrepSearchManager {
         cmd.arg("--json");
 
         // Execute the command
-        let output = cmd.output()?;
+        let output = command_output(&mut cmd, cancel).await?;
 
         if !output.status.success() {
             // If ripgrep is not found, return an error
@@ -324,6 +346,7 @@ impl GrepSearchManager {
                 }
             }
         }
+        drop_denied_paths(&self.search_dir, &mut matches);
 
         Ok(GrepSearchResult {
             query: input.pattern,
@@ -331,3 +354,78 @@ impl GrepSearchManager {
         })
     }
 }
+
+/// Remove ripgrep messages about sensitive files whose reads are denied, so a
+/// search across `root` cannot quote them.
+fn drop_denied_paths(root: &Path, matches: &mut Vec<serde_json::Value>) {
+    matches.retain(|message| {
+        message
+            .pointer("/data/path/text")
+            .and_then(|path| path.as_str())
+            .is_none_or(|path| !is_read_denied(root, Path::new(path)))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::sensitive_files::set_read_denied;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn workspace_search_does_not_surface_denied_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join(".env"), "API_TOKEN=hunter2\n").unwrap();
+        std::fs::create_dir(root.join("src")).unwrap();
+        std::fs::write(root.join("src/lib.rs"), "// API_TOKEN is read at startup\n").unwrap();
+        set_read_denied([".env"]);
+
+        let message = |path: &str| json!({"type": "match", "data": {"path": {"text": path}}});
+        let mut matches = vec![
+            message("./.env"),
+            message(&root.join(".env").to_string_lossy()),
+            message("src/lib.rs"),
+            json!({"type": "summary", "data": {}}),
+        ];
+        drop_denied_paths(root, &mut matches);
+        assert_eq!(
+            matches,
+            [
+                message("src/lib.rs"),
+                json!({"type": "summary", "data": {}})
+            ]
+        );
+
+        if std::process::Command::new("rg")
+            .arg("--version")
+            .output()
+            .is_err()
+        {
+            return;
+        }
+        let manager = GrepSearchManager::new(root.to_path_buf());
+        let input = GrepSearchInput {
+            pattern: "API_TOKEN".to_string(),
+            path: root.to_string_lossy().into_owned(),
+            case_sensitive: None,
+            literal: Some(true),
+            glob_pattern: None,
+            context_lines: None,
+            include_hidden: Some(true),
+            max_results: None,
+            include_generated: Some(true),
+        };
+        let result = manager
+            .perform_search(input, &CancellationToken::new())
+            .await
+            .unwrap();
+        let paths: Vec<&str> = result
+            .matches
+            .iter()
+            .filter_map(|message| message.pointer("/data/path/text")?.as_str())
+            .collect();
+        assert!(paths.iter().any(|path| path.ends_with("lib.rs")));
+        assert!(!paths.iter().any(|path| path.ends_with(".env")));
+    }
+}
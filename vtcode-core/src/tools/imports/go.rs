@@ -0,0 +1,320 @@
+//! Go import declarations
+//!
+//! Go packages are resolved from a table of standard library packages rather
+//! than the workspace index, mirroring what `goimports` does for the common case.
+
+use super::{
+    Binding, FileContext, ImportBlock, ImportStatement, ImportSyntax, ImportTarget, IndexedSymbol,
+    References, is_field_of_parent, node_text, walk_tree,
+};
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::Path;
+use tree_sitter::Node;
+
+/// Standard library packages by the name code refers to them with.
+const STDLIB_PACKAGES: &[(&str, &str)] = &[
+    ("bufio", "bufio"),
+    ("bytes", "bytes"),
+    ("context", "context"),
+    ("errors", "errors"),
+    ("filepath", "path/filepath"),
+    ("fmt", "fmt"),
+    ("http", "net/http"),
+    ("io", "io"),
+    ("json", "encoding/json"),
+    ("log", "log"),
+    ("math", "math"),
+    ("net", "net"),
+    ("os", "os"),
+    ("exec", "os/exec"),
+    ("path", "path"),
+    ("rand", "math/rand"),
+    ("reflect", "reflect"),
+    ("regexp", "regexp"),
+    ("sort", "sort"),
+    ("strconv", "strconv"),
+    ("strings", "strings"),
+    ("sync", "sync"),
+    ("atomic", "sync/atomic"),
+    ("testing", "testing"),
+    ("time", "time"),
+    ("url", "net/url"),
+    ("utf8", "unicode/utf8"),
+    ("base64", "encoding/base64"),
+    ("hex", "encoding/hex"),
+    ("sha256", "crypto/sha256"),
+    ("slices", "slices"),
+    ("maps", "maps"),
+    ("slog", "log/slog"),
+    ("signal", "os/signal"),
+    ("template", "text/template"),
+    ("ioutil", "io/ioutil"),
+    ("fs", "io/fs"),
+    ("flag", "flag"),
+    ("unicode", "unicode"),
+];
+
+pub(super) struct GoImports;
+
+impl ImportSyntax for GoImports {
+    fn parse_block(&self, lines: &[&str], _ctx: &FileContext<'_>) -> ImportBlock {
+        let package = lines
+            .iter()
+            .position(|line| line.starts_with("package "))
+            .map_or(0, |index| index + 1);
+        let mut block = ImportBlock {
+            start: package,
+            end: package,
+            ..ImportBlock::default()
+        };
+        let mut groups: Vec<Vec<ImportStatement>> = vec![Vec::new()];
+        let mut pending: Vec<String> = Vec::new();
+        let mut found = false;
+        let mut index = package;
+
+        while index < lines.len() {
+            let line = lines[index];
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                pending.clear();
+                if found && !groups.last().expect("group").is_empty() {
+                    groups.push(Vec::new());
+                }
+                index += 1;
+                continue;
+            }
+            if trimmed.starts_with("//") {
+                pending.push(trimmed.to_string());
+                index += 1;
+                continue;
+            }
+            if let Some(rest) = line.strip_prefix("import") {
+                let rest = rest.trim();
+                if !found {
+                    block.start = index - pending.len();
+                    found = true;
+                }
+                if rest.starts_with('(') {
+                    block.parenthesized = true;
+                    index += 1;
+                    while index < lines.len() && lines[index].trim() != ")" {
+                        let inner = lines[index].trim();
+                        if inner.is_empty() {
+                            if !groups.last().expect("group").is_empty() {
+                                groups.push(Vec::new());
+                            }
+                        } else if inner.starts_with("//") {
+                            pending.push(inner.to_string());
+                        } else {
+                            let attachments = std::mem::take(&mut pending);
+                            groups
+                                .last_mut()
+                                .expect("group")
+                                .push(parse_spec(inner, attachments));
+                        }
+                        index += 1;
+                    }
+                    index += 1;
+                } else {
+                    let attachments = std::mem::take(&mut pending);
+                    groups
+                        .last_mut()
+                        .expect("group")
+                        .push(parse_spec(rest, attachments));
+                    index += 1;
+                }
+                block.end = index;
+                continue;
+            }
+            break;
+        }
+
+        groups.retain(|group| !group.is_empty());
+        block.groups = groups;
+        block
+    }
+
+    fn remove_bindings(
+        &self,
+        _statement: &ImportStatement,
+        _unused: &HashSet<String>,
+    ) -> Option<ImportStatement> {
+        // Every Go import binds a single package name.
+        None
+    }
+
+    fn new_statement(
+        &self,
+        target: &ImportTarget,
+        _block: &ImportBlock,
+        _ctx: &FileContext<'_>,
+    ) -> ImportStatement {
+        parse_spec(&format!("\"{}\"", target.module), Vec::new())
+    }
+
+    fn render(&self, block: &ImportBlock) -> Vec<String> {
+        let statements: Vec<&ImportStatement> = block.groups.iter().flatten().collect();
+        if statements.is_empty() {
+            return Vec::new();
+        }
+        if !block.parenthesized && statements.len() == 1 {
+            let mut lines: Vec<String> = statements[0].text.lines().map(str::to_string).collect();
+            if let Some(last) = lines.last_mut() {
+                *last = format!("import {}", last);
+            }
+            return lines;
+        }
+        let mut lines = vec!["import (".to_string()];
+        for (index, group) in block.groups.iter().enumerate() {
+            if index > 0 {
+                lines.push(String::new());
+            }
+            for statement in group {
+                lines.extend(statement.text.lines().map(|line| format!("\t{}", line)));
+            }
+        }
+        lines.push(")".to_string());
+        lines
+    }
+
+    fn references(&self, root: Node<'_>, source: &str, skip: Range<usize>) -> References {
+        let mut references = References::default();
+        walk_tree(root, |node| {
+            if skip.contains(&node.start_byte()) {
+                return;
+            }
+            let Some(parent) = node.parent() else {
+                return;
+            };
+            let name = node_text(node, source).to_string();
+            match (node.kind(), parent.kind()) {
+                ("identifier", "selector_expression") if is_field_of_parent(node, "operand") => {
+                    references.used.insert(name);
+                }
+                ("package_identifier", "qualified_type") => {
+                    references.used.insert(name);
+                }
+                ("identifier", "parameter_declaration" | "var_spec" | "const_spec")
+                | ("identifier", "range_clause" | "function_declaration") => {
+                    references.defined.insert(name);
+                }
+                ("identifier", "expression_list")
+                    if parent
+                        .parent()
+                        .is_some_and(|grand| grand.kind() == "short_var_declaration")
+                        && is_field_of_parent(parent, "left") =>
+                {
+                    references.defined.insert(name);
+                }
+                _ => {}
+            }
+        });
+        references
+    }
+
+    fn exported_symbols(&self, _root: Node<'_>, _source: &str) -> Vec<IndexedSymbol> {
+        Vec::new()
+    }
+
+    fn resolve(
+        &self,
+        _ctx: &FileContext<'_>,
+        name: &str,
+        _definitions: &[(&Path, &IndexedSymbol)],
+    ) -> Vec<ImportTarget> {
+        STDLIB_PACKAGES
+            .iter()
+            .filter(|(package, _)| *package == name)
+            .map(|(_, path)| ImportTarget {
+                module: path.to_string(),
+                name: name.to_string(),
+            })
+            .collect()
+    }
+
+    fn is_candidate(&self, name: &str) -> bool {
+        STDLIB_PACKAGES.iter().any(|(package, _)| *package == name)
+    }
+
+    fn is_builtin(&self, _name: &str) -> bool {
+        false
+    }
+}
+
+/// Parse an import spec such as `"fmt"` or `yaml "gopkg.in/yaml.v3"`.
+fn parse_spec(spec: &str, attachments: Vec<String>) -> ImportStatement {
+    let mut lines = attachments;
+    lines.push(spec.to_string());
+    let text = lines.join("\n");
+
+    let (alias, path) = match (spec.find('"'), spec.rfind('"')) {
+        (Some(open), Some(close)) if open < close => {
+            let alias = spec[..open].trim();
+            (
+                (!alias.is_empty()).then(|| alias.to_string()),
+                spec[open + 1..close].to_string(),
+            )
+        }
+        _ => (None, spec.to_string()),
+    };
+
+    let (name, certain) = match &alias {
+        Some(alias) => (alias.clone(), true),
+        None => package_name(&path),
+    };
+    let removable = certain && !matches!(name.as_str(), "_" | ".") && path != "C";
+    let first = path.split('/').next().unwrap_or(&path);
+    ImportStatement {
+        text,
+        category: u8::from(first.contains('.')),
+        key: (0, path.clone()),
+        bindings: vec![Binding::new(name, removable)],
+        sortable: path != "C",
+    }
+}
+
+/// The package name an import path most likely binds, and whether that is certain.
+fn package_name(path: &str) -> (String, bool) {
+    let segments: Vec<&str> = path.split('/').collect();
+    let mut last = segments.last().copied().unwrap_or(path);
+    let is_version = |segment: &str| {
+        segment
+            .strip_prefix('v')
+            .is_some_and(|digits| !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()))
+    };
+    if is_version(last) && segments.len() > 1 {
+        last = segments[segments.len() - 2];
+    }
+    let certain = last.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    (last.to_string(), certain)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_grouped_import_declarations() {
+        let source = "package main\n\nimport (\n\t\"os\"\n\t\"fmt\"\n\n\tyaml \"gopkg.in/yaml.v3\"\n\t_ \"embed\"\n)\n\nfunc main() {}\n";
+        let lines: Vec<&str> = source.lines().collect();
+        let ctx = FileContext {
+            workspace: Path::new("."),
+            relative: Path::new("main.go"),
+        };
+        let block = GoImports.parse_block(&lines, &ctx);
+        assert_eq!((block.start, block.end), (2, 9));
+        assert!(block.parenthesized);
+        assert_eq!(block.groups.len(), 2);
+        assert_eq!(
+            block.groups[1][0].bindings,
+            vec![Binding::new("yaml", true)]
+        );
+        assert!(!block.groups[1][1].bindings[0].removable);
+        assert_eq!(
+            package_name("github.com/go-chi/chi/v5"),
+            ("chi".to_string(), true)
+        );
+        assert!(!package_name("gopkg.in/yaml.v3").1);
+    }
+}
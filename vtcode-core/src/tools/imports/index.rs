@@ -0,0 +1,288 @@
+//! Workspace index of importable definitions
+//!
+//! The index maps names to the files that publicly define them. It is filled
+//! per language family on first use and refreshed incrementally afterwards:
+//! only files whose modification time changed are parsed again. A running
+//! `vtcode daemon` keeps one index warm and serves it to new sessions, which
+//! [`SymbolIndex::adopt`] it before their first refresh.
+
+use super::ImportLanguage;
+use crate::core::progress::Progress;
+use crate::core::sensitive_files::is_read_denied;
+use crate::tools::tree_sitter::TreeSitterAnalyzer;
+use crate::utils::index_exclude;
+use anyhow::Result;
+use ignore::{Walk, WalkBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Directories never worth indexing even when they are not git-ignored.
+const SKIPPED_DIRS: &[&str] = &[
+    "node_modules",
+    "target",
+    "dist",
+    "build",
+    "__pycache__",
+    "vendor",
+];
+
+/// Walk the source files of `workspace`, skipping ignored, excluded and
+/// dependency directories and sensitive files.
+pub(crate) fn source_walker(workspace: &Path) -> Walk {
+    let exclusions = index_exclude::current();
+    let root = workspace.to_path_buf();
+    WalkBuilder::new(workspace)
+        .filter_entry(move |entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_none_or(|name| !SKIPPED_DIRS.contains(&name))
+                && !exclusions.is_excluded(
+                    entry.path(),
+                    entry.file_type().is_some_and(|kind| kind.is_dir()),
+                )
+                && !is_read_denied(&root, entry.path())
+        })
+        .build()
+}
+
+/// A public definition other files can import.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedSymbol {
+    pub name: String,
+    /// Rust traits are kept even when their name is not referenced
+    pub is_trait: bool,
+}
+
+impl IndexedSymbol {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            is_trait: false,
+        }
+    }
+
+    pub fn trait_symbol(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            is_trait: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexedFile {
+    language: ImportLanguage,
+    modified: Option<SystemTime>,
+    symbols: Vec<IndexedSymbol>,
+}
+
+/// Importable definitions by file, keyed by workspace-relative path.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolIndex {
+    files: HashMap<PathBuf, IndexedFile>,
+    max_files: usize,
+}
+
+impl SymbolIndex {
+    pub fn new(max_files: usize) -> Self {
+        Self {
+            files: HashMap::new(),
+            max_files,
+        }
+    }
+
+    pub fn set_max_files(&mut self, max_files: usize) {
+        self.max_files = max_files;
+    }
+
+    pub fn max_files(&self) -> usize {
+        self.max_files
+    }
+
+    /// Number of indexed files.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Forget every indexed file; the next refresh parses them again.
+    pub fn clear(&mut self) {
+        self.files.clear();
+    }
+
+    /// Take over the files of `other`, such as the index a daemon serves,
+    /// unless this index has files already. Files changed since `other` was
+    /// built are parsed again on the next refresh. Returns whether it was
+    /// adopted.
+    pub fn adopt(&mut self, other: SymbolIndex) -> bool {
+        if !self.files.is_empty() {
+            return false;
+        }
+        self.files = other.files;
+        true
+    }
+
+    /// Record the definitions of `path` directly, replacing earlier ones.
+    pub fn insert(
+        &mut self,
+        path: impl Into<PathBuf>,
+        language: ImportLanguage,
+        symbols: Vec<IndexedSymbol>,
+    ) {
+        self.files.insert(
+            path.into(),
+            IndexedFile {
+                language: language.family(),
+                modified: None,
+                symbols,
+            },
+        );
+    }
+
+    /// Files in `family` that define `name`.
+    pub fn lookup(&self, family: ImportLanguage, name: &str) -> Vec<(&Path, &IndexedSymbol)> {
+        let mut found: Vec<(&Path, &IndexedSymbol)> = self
+            .files
+            .iter()
+            .filter(|(_, file)| file.language == family)
+            .flat_map(|(path, file)| {
+                file.symbols
+                    .iter()
+                    .filter(|symbol| symbol.name == name)
+                    .map(move |symbol| (path.as_path(), symbol))
+            })
+            .collect();
+        found.sort_by(|a, b| a.0.cmp(b.0));
+        found
+    }
+
+    /// Whether any indexed Rust file defines `name` as a trait.
+    pub fn is_trait(&self, name: &str) -> bool {
+        self.lookup(ImportLanguage::Rust, name)
+            .iter()
+            .any(|(_, symbol)| symbol.is_trait)
+    }
+
+    /// Bring the entries for `family` up to date with the files on disk.
+    pub fn refresh(
+        &mut self,
+        workspace: &Path,
+        family: ImportLanguage,
+        analyzer: &mut TreeSitterAnalyzer,
+    ) -> Result<()> {
+        self.refresh_with_progress(workspace, family, analyzer, &mut Progress::disabled())
+    }
+
+    /// [`SymbolIndex::refresh`], reporting each file to `progress`.
+    pub fn refresh_with_progress(
+        &mut self,
+        workspace: &Path,
+        family: ImportLanguage,
+        analyzer: &mut TreeSitterAnalyzer,
+        progress: &mut Progress,
+    ) -> Result<()> {
+        let entries: Vec<_> = source_walker(workspace)
+            .flatten()
+            .filter(|entry| entry.file_type().is_some_and(|kind| kind.is_file()))
+            .filter_map(|entry| {
+                let language = ImportLanguage::from_path(entry.path())?;
+                (language.family() == family).then_some((entry, language))
+            })
+            .take(self.max_files)
+            .collect();
+        progress.start_phase(
+            &format!("indexing {} symbols", family.name()),
+            Some(entries.len() as u64),
+        );
+
+        let mut seen = HashSet::new();
+        for (entry, language) in entries {
+            let metadata = entry.metadata().ok();
+            progress.advance(metadata.as_ref().map_or(0, |meta| meta.len()));
+            let relative = entry
+                .path()
+                .strip_prefix(workspace)
+                .unwrap_or(entry.path())
+                .to_path_buf();
+            let modified = metadata.and_then(|meta| meta.modified().ok());
+            seen.insert(relative.clone());
+
+            if let Some(existing) = self.files.get(&relative)
+                && existing.modified.is_some()
+                && existing.modified == modified
+            {
+                continue;
+            }
+
+            let Ok(source) = std::fs::read_to_string(entry.path()) else {
+                continue;
+            };
+            let symbols = match analyzer.parse(&source, language.tree_sitter()) {
+                Ok(tree) => language
+                    .syntax()
+                    .exported_symbols(tree.root_node(), &source),
+                Err(_) => Vec::new(),
+            };
+            self.files.insert(
+                relative,
+                IndexedFile {
+                    language: family,
+                    modified,
+                    symbols,
+                },
+            );
+        }
+
+        progress.finish_phase();
+
+        self.files
+            .retain(|path, file| file.language != family || seen.contains(path));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refreshes_changed_files_only() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::create_dir_all(dir.path().join("src")).expect("src");
+        std::fs::write(
+            dir.path().join("src/lib.rs"),
+            "pub struct Config;\npub trait Load {}\n",
+        )
+        .expect("lib");
+        std::fs::write(dir.path().join("app.py"), "class App:\n    pass\n").expect("app");
+
+        let mut analyzer = TreeSitterAnalyzer::new().expect("analyzer");
+        let mut index = SymbolIndex::new(100);
+        index
+            .refresh(dir.path(), ImportLanguage::Rust, &mut analyzer)
+            .expect("refresh");
+
+        let found = index.lookup(ImportLanguage::Rust, "Config");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, Path::new("src/lib.rs"));
+        assert!(index.is_trait("Load"));
+        assert!(index.lookup(ImportLanguage::Python, "App").is_empty());
+
+        let mut session = SymbolIndex::new(100);
+        assert!(session.adopt(index.clone()));
+        assert_eq!(session.lookup(ImportLanguage::Rust, "Config").len(), 1);
+        assert!(!session.adopt(SymbolIndex::new(100)));
+
+        std::fs::remove_file(dir.path().join("src/lib.rs")).expect("remove");
+        index
+            .refresh(dir.path(), ImportLanguage::Rust, &mut analyzer)
+            .expect("refresh");
+        assert!(index.is_empty());
+    }
+}
@@ -0,0 +1,694 @@
+//! JavaScript and TypeScript ES module imports
+
+use super::{
+    Binding, FileContext, ImportBlock, ImportStatement, ImportSyntax, ImportTarget, IndexedSymbol,
+    LineRules, RawStatement, References, is_field_of_parent, node_text, scan_statements, walk_tree,
+};
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::{Component, Path};
+use tree_sitter::Node;
+
+/// Node.js core modules, grouped before packages.
+const NODE_BUILTINS: &[&str] = &[
+    "assert",
+    "buffer",
+    "child_process",
+    "crypto",
+    "dns",
+    "events",
+    "fs",
+    "http",
+    "https",
+    "module",
+    "net",
+    "os",
+    "path",
+    "perf_hooks",
+    "process",
+    "querystring",
+    "readline",
+    "stream",
+    "timers",
+    "tty",
+    "url",
+    "util",
+    "vm",
+    "worker_threads",
+    "zlib",
+];
+
+/// Capitalized globals and TypeScript utility types.
+const GLOBALS: &[&str] = &[
+    "AbortController",
+    "Array",
+    "ArrayBuffer",
+    "AsyncIterable",
+    "Awaited",
+    "BigInt",
+    "Blob",
+    "Boolean",
+    "Buffer",
+    "CustomEvent",
+    "Date",
+    "Document",
+    "Element",
+    "Error",
+    "Event",
+    "EventTarget",
+    "Exclude",
+    "Extract",
+    "File",
+    "FormData",
+    "Function",
+    "Headers",
+    "HTMLElement",
+    "InstanceType",
+    "Intl",
+    "Iterable",
+    "Iterator",
+    "KeyboardEvent",
+    "Map",
+    "Math",
+    "MouseEvent",
+    "NonNullable",
+    "Number",
+    "Object",
+    "Omit",
+    "Parameters",
+    "Partial",
+    "Pick",
+    "Promise",
+    "PromiseLike",
+    "Proxy",
+    "RangeError",
+    "Readonly",
+    "ReadonlyArray",
+    "Record",
+    "Reflect",
+    "RegExp",
+    "Request",
+    "Required",
+    "Response",
+    "ReturnType",
+    "Set",
+    "String",
+    "Symbol",
+    "SyntaxError",
+    "TextDecoder",
+    "TextEncoder",
+    "TypeError",
+    "Uint8Array",
+    "WeakMap",
+    "WeakSet",
+    "WebSocket",
+    "Window",
+    "Worker",
+];
+
+/// Declarations whose `name` field introduces a name.
+const DEFINITION_KINDS: &[&str] = &[
+    "class_declaration",
+    "abstract_class_declaration",
+    "class",
+    "function_declaration",
+    "generator_function_declaration",
+    "interface_declaration",
+    "type_alias_declaration",
+    "enum_declaration",
+    "variable_declarator",
+    "type_parameter",
+];
+
+/// Longest line written before named imports are wrapped onto separate lines.
+const MAX_LINE: usize = 100;
+
+const RULES: LineRules = LineRules {
+    starts_statement: |line| {
+        line.starts_with("import ") && !line.starts_with("import (") && !line.starts_with("import(")
+    },
+    is_complete: |body| {
+        let code = strip_comments(body);
+        let code = code.trim_end().trim_end_matches(';').trim_end();
+        let braces_closed = code.matches('{').count() <= code.matches('}').count();
+        braces_closed && (code.ends_with('\'') || code.ends_with('"'))
+    },
+    is_attachment: |line| {
+        let trimmed = line.trim_start();
+        trimmed.starts_with("//") || trimmed.starts_with("/*") || trimmed.starts_with('*')
+    },
+};
+
+pub(super) struct ScriptImports;
+
+impl ImportSyntax for ScriptImports {
+    fn parse_block(&self, lines: &[&str], _ctx: &FileContext<'_>) -> ImportBlock {
+        let first = preamble_end(lines);
+        let (start, end, raw_groups) = scan_statements(lines, first, &RULES);
+        let mut block = ImportBlock {
+            start,
+            end,
+            ..ImportBlock::default()
+        };
+        for raw_group in raw_groups {
+            let group = raw_group
+                .iter()
+                .map(|raw| match parse_import(raw) {
+                    Some(parsed) => parsed.statement(),
+                    None => ImportStatement {
+                        text: raw.text(),
+                        category: 1,
+                        key: (0, String::new()),
+                        bindings: Vec::new(),
+                        sortable: false,
+                    },
+                })
+                .collect();
+            block.groups.push(group);
+        }
+        block
+    }
+
+    fn remove_bindings(
+        &self,
+        statement: &ImportStatement,
+        unused: &HashSet<String>,
+    ) -> Option<ImportStatement> {
+        let mut parsed = reparse(&statement.text)?;
+        if parsed
+            .default
+            .as_ref()
+            .is_some_and(|name| unused.contains(name))
+        {
+            parsed.default = None;
+        }
+        if parsed
+            .namespace
+            .as_ref()
+            .is_some_and(|name| unused.contains(name))
+        {
+            parsed.namespace = None;
+        }
+        parsed
+            .named
+            .retain(|named| !unused.contains(named.bound_name()));
+        if parsed.default.is_none() && parsed.namespace.is_none() && parsed.named.is_empty() {
+            return None;
+        }
+        Some(parsed.statement())
+    }
+
+    fn merge(&self, statement: &ImportStatement, target: &ImportTarget) -> Option<ImportStatement> {
+        let mut parsed = reparse(&statement.text)?;
+        if parsed.spec != target.module || parsed.type_only || parsed.namespace.is_some() {
+            return None;
+        }
+        parsed.named.push(NamedImport {
+            name: target.name.clone(),
+            alias: None,
+            is_type: false,
+        });
+        Some(parsed.statement())
+    }
+
+    fn new_statement(
+        &self,
+        target: &ImportTarget,
+        block: &ImportBlock,
+        _ctx: &FileContext<'_>,
+    ) -> ImportStatement {
+        // Follow the quote and semicolon style of the existing imports.
+        let style = block
+            .groups
+            .iter()
+            .flatten()
+            .find_map(|statement| reparse(&statement.text));
+        ParsedImport {
+            attachments: Vec::new(),
+            type_only: false,
+            default: None,
+            namespace: None,
+            named: vec![NamedImport {
+                name: target.name.clone(),
+                alias: None,
+                is_type: false,
+            }],
+            spec: target.module.clone(),
+            quote: style.as_ref().map_or('\'', |s| s.quote),
+            semicolon: style.as_ref().is_none_or(|s| s.semicolon),
+            indent: None,
+        }
+        .statement()
+    }
+
+    fn references(&self, root: Node<'_>, source: &str, skip: Range<usize>) -> References {
+        let mut references = References::default();
+        walk_tree(root, |node| {
+            if !matches!(
+                node.kind(),
+                "identifier" | "type_identifier" | "shorthand_property_identifier"
+            ) || skip.contains(&node.start_byte())
+            {
+                return;
+            }
+            let Some(parent) = node.parent() else {
+                return;
+            };
+            let name = node_text(node, source).to_string();
+            let parent_kind = parent.kind();
+            let defines = (DEFINITION_KINDS.contains(&parent_kind)
+                && is_field_of_parent(node, "name"))
+                || matches!(
+                    parent_kind,
+                    "formal_parameters"
+                        | "required_parameter"
+                        | "optional_parameter"
+                        | "import_specifier"
+                        | "import_clause"
+                        | "namespace_import"
+                );
+            if defines {
+                references.defined.insert(name);
+            } else {
+                references.used.insert(name);
+            }
+        });
+        references
+    }
+
+    fn exported_symbols(&self, root: Node<'_>, source: &str) -> Vec<IndexedSymbol> {
+        let mut symbols = Vec::new();
+        let mut cursor = root.walk();
+        for export in root.named_children(&mut cursor) {
+            if export.kind() != "export_statement" || has_default(export) {
+                continue;
+            }
+            if let Some(declaration) = export.child_by_field_name("declaration") {
+                if declaration.kind() == "lexical_declaration" {
+                    let mut declarators = declaration.walk();
+                    for declarator in declaration.named_children(&mut declarators) {
+                        if let Some(name) = declarator.child_by_field_name("name")
+                            && name.kind() == "identifier"
+                        {
+                            symbols.push(IndexedSymbol::new(node_text(name, source)));
+                        }
+                    }
+                } else if let Some(name) = declaration.child_by_field_name("name") {
+                    symbols.push(IndexedSymbol::new(node_text(name, source)));
+                }
+                continue;
+            }
+            // `export { a, b as c }` without a `from` clause
+            if export.child_by_field_name("source").is_some() {
+                continue;
+            }
+            let mut children = export.walk();
+            for clause in export.named_children(&mut children) {
+                if clause.kind() != "export_clause" {
+                    continue;
+                }
+                let mut specifiers = clause.walk();
+                for specifier in clause.named_children(&mut specifiers) {
+                    let exported = specifier
+                        .child_by_field_name("alias")
+                        .or_else(|| specifier.child_by_field_name("name"));
+                    if let Some(exported) = exported {
+                        symbols.push(IndexedSymbol::new(node_text(exported, source)));
+                    }
+                }
+            }
+        }
+        symbols
+    }
+
+    fn resolve(
+        &self,
+        ctx: &FileContext<'_>,
+        name: &str,
+        definitions: &[(&Path, &IndexedSymbol)],
+    ) -> Vec<ImportTarget> {
+        definitions
+            .iter()
+            .filter_map(|(path, _)| relative_specifier(ctx.relative, path))
+            .map(|module| ImportTarget {
+                module,
+                name: name.to_string(),
+            })
+            .collect()
+    }
+
+    fn is_builtin(&self, name: &str) -> bool {
+        GLOBALS.contains(&name)
+    }
+}
+
+fn has_default(export: Node<'_>) -> bool {
+    let mut cursor = export.walk();
+    export
+        .children(&mut cursor)
+        .any(|child| child.kind() == "default")
+}
+
+/// Index of the first line after the shebang, leading comments and directives.
+fn preamble_end(lines: &[&str]) -> usize {
+    let mut end = 0;
+    let mut in_block_comment = false;
+    for (index, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        if in_block_comment {
+            in_block_comment = !trimmed.contains("*/");
+            end = index + 1;
+            continue;
+        }
+        if trimmed.is_empty() {
+            continue;
+        }
+        let directive = ["'use ", "\"use "]
+            .iter()
+            .any(|prefix| trimmed.starts_with(prefix));
+        if trimmed.starts_with("#!") || trimmed.starts_with("//") || directive {
+            end = index + 1;
+            continue;
+        }
+        if trimmed.starts_with("/*") {
+            in_block_comment = !trimmed.contains("*/");
+            end = index + 1;
+            continue;
+        }
+        break;
+    }
+    end
+}
+
+/// Drop `//` comments, leaving `//` inside specifiers such as URLs alone.
+fn strip_comments(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            let mut previous = ' ';
+            for (position, c) in line.char_indices() {
+                if c == '/'
+                    && line[position..].starts_with("//")
+                    && (previous.is_whitespace() || previous == ';' || position == 0)
+                {
+                    return &line[..position];
+                }
+                previous = c;
+            }
+            line
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn category(spec: &str) -> u8 {
+    let bare = spec.strip_prefix("node:");
+    if bare.is_some() || NODE_BUILTINS.contains(&spec.split('/').next().unwrap_or(spec)) {
+        return 0;
+    }
+    if ["./", "../", "/", "@/", "~/", "#"]
+        .iter()
+        .any(|prefix| spec.starts_with(prefix))
+        || spec == "."
+        || spec == ".."
+    {
+        return 2;
+    }
+    1
+}
+
+/// Specifier for importing `target` from `current`, both workspace-relative.
+fn relative_specifier(current: &Path, target: &Path) -> Option<String> {
+    let mut target = target.with_extension("");
+    if target.file_name().is_some_and(|name| name == "index") {
+        target.pop();
+    }
+    let from: Vec<Component> = current.parent()?.components().collect();
+    let to: Vec<Component> = target.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut parts: Vec<String> = vec!["..".to_string(); from.len() - common];
+    parts.extend(
+        to[common..]
+            .iter()
+            .map(|component| component.as_os_str().to_string_lossy().into_owned()),
+    );
+    if parts.is_empty() {
+        return Some(".".to_string());
+    }
+    if parts[0] != ".." {
+        parts.insert(0, ".".to_string());
+    }
+    Some(parts.join("/"))
+}
+
+#[derive(Debug, Clone)]
+struct NamedImport {
+    name: String,
+    alias: Option<String>,
+    is_type: bool,
+}
+
+impl NamedImport {
+    fn bound_name(&self) -> &str {
+        self.alias.as_deref().unwrap_or(&self.name)
+    }
+
+    fn render(&self) -> String {
+        let prefix = if self.is_type { "type " } else { "" };
+        match &self.alias {
+            Some(alias) => format!("{}{} as {}", prefix, self.name, alias),
+            None => format!("{}{}", prefix, self.name),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ParsedImport {
+    attachments: Vec<String>,
+    type_only: bool,
+    default: Option<String>,
+    namespace: Option<String>,
+    named: Vec<NamedImport>,
+    spec: String,
+    quote: char,
+    semicolon: bool,
+    /// Indentation of named imports written one per line
+    indent: Option<String>,
+}
+
+impl ParsedImport {
+    fn is_side_effect(&self) -> bool {
+        self.default.is_none() && self.namespace.is_none() && self.named.is_empty()
+    }
+
+    fn statement(&self) -> ImportStatement {
+        let bindings = self
+            .default
+            .iter()
+            .chain(self.namespace.iter())
+            .map(String::as_str)
+            .chain(self.named.iter().map(NamedImport::bound_name))
+            .map(|name| Binding::new(name, name != "React"))
+            .collect();
+        ImportStatement {
+            text: self.render(),
+            category: category(&self.spec),
+            key: (0, self.spec.to_lowercase()),
+            bindings,
+            sortable: !self.is_side_effect(),
+        }
+    }
+
+    fn render(&self) -> String {
+        let semicolon = if self.semicolon { ";" } else { "" };
+        let source = format!("{q}{}{q}{}", self.spec, semicolon, q = self.quote);
+        let mut clause = Vec::new();
+        if let Some(default) = &self.default {
+            clause.push(default.clone());
+        }
+        if let Some(namespace) = &self.namespace {
+            clause.push(format!("* as {}", namespace));
+        }
+        let named: Vec<String> = self.named.iter().map(NamedImport::render).collect();
+        let keyword = if self.type_only {
+            "import type"
+        } else {
+            "import"
+        };
+
+        let statement = if self.is_side_effect() {
+            format!("import {}", source)
+        } else {
+            let mut inline = clause.clone();
+            if !named.is_empty() {
+                inline.push(format!("{{ {} }}", named.join(", ")));
+            }
+            let single = format!("{} {} from {}", keyword, inline.join(", "), source);
+            if named.is_empty() || (self.indent.is_none() && single.len() <= MAX_LINE) {
+                single
+            } else {
+                let indent = self.indent.as_deref().unwrap_or("  ");
+                let body: String = named
+                    .iter()
+                    .map(|name| format!("{}{},\n", indent, name))
+                    .collect();
+                clause.push(format!("{{\n{}}}", body));
+                format!("{} {} from {}", keyword, clause.join(", "), source)
+            }
+        };
+        let mut lines = self.attachments.clone();
+        lines.push(statement);
+        lines.join("\n")
+    }
+}
+
+fn reparse(text: &str) -> Option<ParsedImport> {
+    let mut attachments = Vec::new();
+    let mut body = Vec::new();
+    for line in text.lines() {
+        if body.is_empty() && !(RULES.starts_statement)(line) {
+            attachments.push(line.to_string());
+        } else {
+            body.push(line.to_string());
+        }
+    }
+    parse_import(&RawStatement { attachments, body })
+}
+
+fn parse_import(raw: &RawStatement) -> Option<ParsedImport> {
+    let indent = raw
+        .body
+        .get(1)
+        .filter(|_| raw.body.len() > 2)
+        .map(|line| line[..line.len() - line.trim_start().len()].to_string())
+        .filter(|indent| !indent.is_empty());
+    let code = strip_comments(&raw.body.join("\n")).replace('\n', " ");
+    let code = code.trim();
+    let semicolon = code.ends_with(';');
+    let code = code.trim_end_matches(';').trim_end();
+    let quote = code.chars().last().filter(|c| *c == '\'' || *c == '"')?;
+    let open = code[..code.len() - 1].rfind(quote)?;
+    let spec = code[open + 1..code.len() - 1].to_string();
+    let head = code[..open].trim().strip_prefix("import")?.trim();
+
+    let mut parsed = ParsedImport {
+        attachments: raw.attachments.clone(),
+        type_only: false,
+        default: None,
+        namespace: None,
+        named: Vec::new(),
+        spec,
+        quote,
+        semicolon,
+        indent,
+    };
+    if head.is_empty() {
+        return Some(parsed);
+    }
+    let mut clause = head.strip_suffix("from")?.trim();
+    if let Some(rest) = clause.strip_prefix("type ") {
+        parsed.type_only = true;
+        clause = rest.trim();
+    }
+
+    let (outer, named) = match (clause.find('{'), clause.rfind('}')) {
+        (Some(open), Some(close)) if open < close => (
+            format!("{}{}", &clause[..open], &clause[close + 1..]),
+            Some(&clause[open + 1..close]),
+        ),
+        (None, None) => (clause.to_string(), None),
+        _ => return None,
+    };
+    for part in outer
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+    {
+        if let Some(namespace) = part.strip_prefix('*') {
+            parsed.namespace = Some(namespace.trim().strip_prefix("as")?.trim().to_string());
+        } else {
+            parsed.default = Some(part.to_string());
+        }
+    }
+    for part in named
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+    {
+        let (is_type, part) = match part.strip_prefix("type ") {
+            Some(rest) => (true, rest.trim()),
+            None => (false, part),
+        };
+        let (name, alias) = match part.split_once(" as ") {
+            Some((name, alias)) => (name.trim(), Some(alias.trim().to_string())),
+            None => (part, None),
+        };
+        parsed.named.push(NamedImport {
+            name: name.to_string(),
+            alias,
+            is_type,
+        });
+    }
+    Some(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw(lines: &[&str]) -> RawStatement {
+        RawStatement {
+            attachments: Vec::new(),
+            body: lines.iter().map(|line| line.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn rewrites_named_imports_keeping_style() {
+        let statement = parse_import(&raw(&[
+            "import Button, {",
+            "    type Props,",
+            "    useState as useLocalState,",
+            "} from \"./button\"",
+        ]))
+        .expect("parsed")
+        .statement();
+        assert_eq!(statement.category, 2);
+        let names: Vec<&str> = statement.bindings.iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(names, vec!["Button", "Props", "useLocalState"]);
+
+        let unused: HashSet<String> = ["Button".to_string()].into_iter().collect();
+        let rewritten = ScriptImports
+            .remove_bindings(&statement, &unused)
+            .expect("kept");
+        assert_eq!(
+            rewritten.text,
+            "import {\n    type Props,\n    useState as useLocalState,\n} from \"./button\""
+        );
+
+        let side_effect = parse_import(&raw(&["import './styles.css';"]))
+            .expect("parsed")
+            .statement();
+        assert!(!side_effect.sortable);
+        assert!(side_effect.bindings.is_empty());
+    }
+
+    #[test]
+    fn computes_relative_specifiers() {
+        assert_eq!(
+            relative_specifier(
+                Path::new("src/app/page.tsx"),
+                Path::new("src/components/Button.tsx")
+            )
+            .as_deref(),
+            Some("../components/Button")
+        );
+        assert_eq!(
+            relative_specifier(Path::new("src/index.ts"), Path::new("src/models/index.ts"))
+                .as_deref(),
+            Some("./models")
+        );
+        assert_eq!(category("node:fs"), 0);
+        assert_eq!(category("react"), 1);
+    }
+}
@@ -0,0 +1,847 @@
+//! Language-aware import management
+//!
+//! [`OrganizeImportsTool`] rewrites the import header of Rust, Python,
+//! JavaScript/TypeScript and Go files. It adds imports for referenced symbols
+//! that the workspace [`SymbolIndex`] resolves to exactly one definition,
+//! removes imports whose names no longer appear in the file, and sorts imports
+//! inside the blank-line separated groups the file already uses, so project
+//! conventions such as `std` / external / `crate` grouping are kept.
+//!
+//! Removal is deliberately conservative: an import is only dropped when its
+//! bound name does not occur anywhere else in the file, and Rust imports that
+//! look like traits are kept because method calls do not mention them.
+
+mod go;
+mod index;
+mod javascript;
+mod python;
+mod rust;
+
+pub(crate) use index::source_walker;
+pub use index::{IndexedSymbol, SymbolIndex};
+
+use crate::config::ImportsConfig;
+use crate::config::constants::tools;
+use crate::tools::traits::Tool;
+use crate::tools::tree_sitter::{LanguageSupport, SyntaxTreeCache, TreeSitterAnalyzer};
+use crate::tools::write_quota::WriteQuota;
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tree_sitter::Node;
+
+/// A language whose imports can be organized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportLanguage {
+    Rust,
+    Python,
+    JavaScript,
+    TypeScript,
+    Go,
+}
+
+impl ImportLanguage {
+    /// One language of each family.
+    pub const FAMILIES: [Self; 4] = [Self::Rust, Self::Python, Self::JavaScript, Self::Go];
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "rs" => Some(Self::Rust),
+            "py" | "pyi" => Some(Self::Python),
+            "js" | "jsx" | "mjs" | "cjs" => Some(Self::JavaScript),
+            "ts" | "tsx" | "mts" | "cts" => Some(Self::TypeScript),
+            "go" => Some(Self::Go),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Rust => "rust",
+            Self::Python => "python",
+            Self::JavaScript => "javascript",
+            Self::TypeScript => "typescript",
+            Self::Go => "go",
+        }
+    }
+
+    /// Languages whose files can import each other's symbols share a family.
+    pub fn family(self) -> Self {
+        match self {
+            Self::TypeScript => Self::JavaScript,
+            other => other,
+        }
+    }
+
+    pub(crate) fn tree_sitter(self) -> LanguageSupport {
+        match self {
+            Self::Rust => LanguageSupport::Rust,
+            Self::Python => LanguageSupport::Python,
+            Self::JavaScript => LanguageSupport::JavaScript,
+            Self::TypeScript => LanguageSupport::TypeScript,
+            Self::Go => LanguageSupport::Go,
+        }
+    }
+
+    fn syntax(self) -> &'static dyn ImportSyntax {
+        match self {
+            Self::Rust => &rust::RustImports,
+            Self::Python => &python::PythonImports,
+            Self::JavaScript | Self::TypeScript => &javascript::ScriptImports,
+            Self::Go => &go::GoImports,
+        }
+    }
+}
+
+/// Which passes to run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrganizeOptions {
+    pub add_missing: bool,
+    pub remove_unused: bool,
+    pub sort: bool,
+}
+
+impl From<&ImportsConfig> for OrganizeOptions {
+    fn from(config: &ImportsConfig) -> Self {
+        Self {
+            add_missing: config.add_missing,
+            remove_unused: config.remove_unused,
+            sort: config.sort,
+        }
+    }
+}
+
+/// What organizing a file changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ImportChanges {
+    /// Import statements that were added
+    pub added: Vec<String>,
+    /// Names whose imports were removed
+    pub removed: Vec<String>,
+    /// Whether sorting reordered any statements
+    pub sorted: bool,
+    /// Referenced names with several possible imports, left for the caller
+    pub ambiguous: BTreeMap<String, Vec<String>>,
+}
+
+impl ImportChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && !self.sorted
+    }
+}
+
+/// The file being organized.
+pub(crate) struct FileContext<'a> {
+    pub workspace: &'a Path,
+    /// Path of the file relative to the workspace root
+    pub relative: &'a Path,
+}
+
+/// One import statement as it appears in the source.
+#[derive(Debug, Clone)]
+pub(crate) struct ImportStatement {
+    /// Source text, including attached comments and attributes
+    pub text: String,
+    /// Import group category, lowest first (e.g. std, external, local)
+    pub category: u8,
+    /// Sort key within a group
+    pub key: (u8, String),
+    pub bindings: Vec<Binding>,
+    /// Side-effect imports keep their position
+    pub sortable: bool,
+}
+
+/// A name an import statement brings into scope.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Binding {
+    pub name: String,
+    /// Re-exports, glob imports and side-effect imports are never removed
+    pub removable: bool,
+}
+
+impl Binding {
+    pub fn new(name: impl Into<String>, removable: bool) -> Self {
+        Self {
+            name: name.into(),
+            removable,
+        }
+    }
+}
+
+/// The import header of a file.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ImportBlock {
+    /// First line of the header; the insertion point when there are no imports
+    pub start: usize,
+    /// Line after the last import statement
+    pub end: usize,
+    /// Statements in blank-line separated groups
+    pub groups: Vec<Vec<ImportStatement>>,
+    /// A glob import may already provide any name
+    pub has_glob: bool,
+    /// Go: imports are written as a parenthesized declaration
+    pub parenthesized: bool,
+}
+
+impl ImportBlock {
+    fn statements(&self) -> impl Iterator<Item = &ImportStatement> {
+        self.groups.iter().flatten()
+    }
+}
+
+/// An import to add for a referenced name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ImportTarget {
+    /// Module, package or path the name is imported from
+    pub module: String,
+    pub name: String,
+}
+
+/// Names a file references and defines, outside its import header.
+#[derive(Debug, Default)]
+pub(crate) struct References {
+    pub used: BTreeSet<String>,
+    pub defined: HashSet<String>,
+}
+
+/// Per-language import syntax and conventions.
+pub(crate) trait ImportSyntax: Sync {
+    fn parse_block(&self, lines: &[&str], ctx: &FileContext<'_>) -> ImportBlock;
+
+    /// Rewrite `statement` without the `unused` bindings; `None` drops it.
+    fn remove_bindings(
+        &self,
+        statement: &ImportStatement,
+        unused: &HashSet<String>,
+    ) -> Option<ImportStatement>;
+
+    /// Add `target` to an existing statement that imports from the same module.
+    fn merge(
+        &self,
+        _statement: &ImportStatement,
+        _target: &ImportTarget,
+    ) -> Option<ImportStatement> {
+        None
+    }
+
+    fn new_statement(
+        &self,
+        target: &ImportTarget,
+        block: &ImportBlock,
+        ctx: &FileContext<'_>,
+    ) -> ImportStatement;
+
+    fn render(&self, block: &ImportBlock) -> Vec<String> {
+        render_groups(&block.groups)
+    }
+
+    /// Collect referenced and locally defined names, skipping `skip` (the header).
+    fn references(&self, root: Node<'_>, source: &str, skip: Range<usize>) -> References;
+
+    /// Public top-level definitions other files can import.
+    fn exported_symbols(&self, root: Node<'_>, source: &str) -> Vec<IndexedSymbol>;
+
+    /// Imports that would provide `name`, given its definitions in the index.
+    fn resolve(
+        &self,
+        ctx: &FileContext<'_>,
+        name: &str,
+        definitions: &[(&Path, &IndexedSymbol)],
+    ) -> Vec<ImportTarget>;
+
+    /// Whether an unresolved reference to `name` should be looked up at all.
+    fn is_candidate(&self, name: &str) -> bool {
+        is_type_like(name)
+    }
+
+    fn is_builtin(&self, name: &str) -> bool;
+
+    /// Keep an import even when its name does not appear in the file.
+    fn keeps_unreferenced(&self, _name: &str, _index: &SymbolIndex) -> bool {
+        false
+    }
+}
+
+/// Organize the imports of `source`, returning the new text and what changed.
+pub(crate) fn organize_source(
+    language: ImportLanguage,
+    ctx: &FileContext<'_>,
+    source: &str,
+    index: &SymbolIndex,
+    analyzer: &mut TreeSitterAnalyzer,
+    options: OrganizeOptions,
+) -> Result<(String, ImportChanges)> {
+    let syntax = language.syntax();
+    let lines: Vec<&str> = source.lines().collect();
+    let mut block = syntax.parse_block(&lines, ctx);
+    let original_order: Vec<String> = block.statements().map(|s| s.text.clone()).collect();
+    let had_imports = !original_order.is_empty();
+    let mut changes = ImportChanges::default();
+
+    let header = line_byte_range(source, block.start, block.end);
+    let body = format!("{}\n{}", &source[..header.start], &source[header.end..]);
+    let tokens = identifier_tokens(&body);
+
+    if options.remove_unused {
+        for group in &mut block.groups {
+            let mut kept = Vec::with_capacity(group.len());
+            for statement in group.drain(..) {
+                let unused: HashSet<String> = statement
+                    .bindings
+                    .iter()
+                    .filter(|binding| {
+                        binding.removable
+                            && !tokens.contains(binding.name.as_str())
+                            && !syntax.keeps_unreferenced(&binding.name, index)
+                    })
+                    .map(|binding| binding.name.clone())
+                    .collect();
+                if unused.is_empty() {
+                    kept.push(statement);
+                    continue;
+                }
+                changes.removed.extend(
+                    statement
+                        .bindings
+                        .iter()
+                        .filter(|binding| unused.contains(&binding.name))
+                        .map(|binding| binding.name.clone()),
+                );
+                if let Some(rewritten) = syntax.remove_bindings(&statement, &unused) {
+                    kept.push(rewritten);
+                }
+            }
+            *group = kept;
+        }
+        block.groups.retain(|group| !group.is_empty());
+    }
+
+    if options.add_missing && !block.has_glob {
+        let tree = analyzer.parse(source, language.tree_sitter())?;
+        let references = syntax.references(tree.root_node(), source, header.clone());
+        let bound: HashSet<String> = block
+            .statements()
+            .flat_map(|statement| statement.bindings.iter().map(|b| b.name.clone()))
+            .collect();
+
+        for name in &references.used {
+            if references.defined.contains(name)
+                || bound.contains(name)
+                || syntax.is_builtin(name)
+                || !syntax.is_candidate(name)
+            {
+                continue;
+            }
+            let definitions: Vec<(&Path, &IndexedSymbol)> = index
+                .lookup(language.family(), name)
+                .into_iter()
+                .filter(|(path, _)| *path != ctx.relative)
+                .collect();
+            let mut targets = syntax.resolve(ctx, name, &definitions);
+            targets.dedup();
+            match targets.len() {
+                0 => {}
+                1 => {
+                    let target = targets.remove(0);
+                    let statement = add_import(syntax, &mut block, &target, ctx);
+                    changes.added.push(statement);
+                }
+                _ => {
+                    changes.ambiguous.insert(
+                        name.clone(),
+                        targets.into_iter().map(|target| target.module).collect(),
+                    );
+                }
+            }
+        }
+    }
+
+    if options.sort {
+        for group in &mut block.groups {
+            if group.iter().all(|statement| statement.sortable) {
+                group.sort_by(|a, b| a.key.cmp(&b.key));
+                group.dedup_by(|a, b| a.text == b.text);
+            }
+        }
+        let new_order: Vec<&str> = block.statements().map(|s| s.text.as_str()).collect();
+        let mut expected: Vec<&str> = original_order
+            .iter()
+            .map(String::as_str)
+            .filter(|text| new_order.contains(text))
+            .collect();
+        expected.dedup();
+        let kept_in_new: Vec<&str> = new_order
+            .iter()
+            .copied()
+            .filter(|text| original_order.iter().any(|original| original == text))
+            .collect();
+        changes.sorted = kept_in_new != expected;
+    }
+
+    if changes.is_empty() {
+        return Ok((source.to_string(), changes));
+    }
+
+    let rendered = syntax.render(&block);
+    let mut out: Vec<String> = lines[..block.start].iter().map(|l| l.to_string()).collect();
+    let mut rest = block.end;
+    if had_imports {
+        out.extend(rendered);
+        if block.groups.is_empty()
+            && out.last().is_some_and(|line| line.trim().is_empty())
+            && lines.get(rest).is_some_and(|line| line.trim().is_empty())
+        {
+            rest += 1;
+        }
+    } else if !rendered.is_empty() {
+        if out.last().is_some_and(|line| !line.trim().is_empty()) {
+            out.push(String::new());
+        }
+        out.extend(rendered);
+        if lines.get(rest).is_some_and(|line| !line.trim().is_empty()) {
+            out.push(String::new());
+        }
+    }
+    out.extend(lines[rest..].iter().map(|l| l.to_string()));
+
+    let mut text = out.join("\n");
+    if source.ends_with('\n') || (source.is_empty() && !text.is_empty()) {
+        text.push('\n');
+    }
+    Ok((text, changes))
+}
+
+/// Add `target` to the block, merging into an existing statement when possible.
+///
+/// Returns the text of the statement that now provides the name.
+fn add_import(
+    syntax: &dyn ImportSyntax,
+    block: &mut ImportBlock,
+    target: &ImportTarget,
+    ctx: &FileContext<'_>,
+) -> String {
+    for group in &mut block.groups {
+        for statement in group.iter_mut() {
+            if let Some(merged) = syntax.merge(statement, target) {
+                *statement = merged;
+                return statement.text.clone();
+            }
+        }
+    }
+
+    let statement = syntax.new_statement(target, block, ctx);
+    let text = statement.text.clone();
+    let category = statement.category;
+    if let Some(group) = block
+        .groups
+        .iter_mut()
+        .find(|group| group.iter().any(|s| s.category == category))
+    {
+        group.push(statement);
+        return text;
+    }
+    // Files that do not group imports by category get the new import appended.
+    let grouped_by_category = block
+        .groups
+        .iter()
+        .all(|group| group.iter().all(|s| s.category == group[0].category));
+    if !grouped_by_category {
+        block.groups.last_mut().expect("group").push(statement);
+        return text;
+    }
+    let position = block
+        .groups
+        .iter()
+        .position(|group| group.iter().all(|s| s.category > category))
+        .unwrap_or(block.groups.len());
+    block.groups.insert(position, vec![statement]);
+    text
+}
+
+/// Statements one per entry, with a blank line between groups.
+pub(crate) fn render_groups(groups: &[Vec<ImportStatement>]) -> Vec<String> {
+    let mut lines = Vec::new();
+    for (index, group) in groups.iter().enumerate() {
+        if index > 0 {
+            lines.push(String::new());
+        }
+        for statement in group {
+            lines.extend(statement.text.lines().map(str::to_string));
+        }
+    }
+    lines
+}
+
+/// Byte range covering lines `start..end` of `source`.
+fn line_byte_range(source: &str, start: usize, end: usize) -> Range<usize> {
+    let mut offset = 0;
+    let mut range = source.len()..source.len();
+    for (index, line) in source.split_inclusive('\n').enumerate() {
+        if index == start {
+            range.start = offset;
+        }
+        if index == end {
+            range.end = offset;
+            break;
+        }
+        offset += line.len();
+    }
+    range.start = range.start.min(range.end);
+    range
+}
+
+/// Every identifier-like token in `text`, including those in strings and comments.
+fn identifier_tokens(text: &str) -> HashSet<&str> {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+        .filter(|token| !token.is_empty())
+        .collect()
+}
+
+/// `CamelCase` names: types, classes, traits and components.
+pub(crate) fn is_type_like(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_uppercase()) && chars.any(|c| c.is_ascii_lowercase())
+}
+
+/// Visit every node under `root` in document order.
+pub(crate) fn walk_tree<'tree>(root: Node<'tree>, mut visit: impl FnMut(Node<'tree>)) {
+    let mut cursor = root.walk();
+    loop {
+        visit(cursor.node());
+        if cursor.goto_first_child() {
+            continue;
+        }
+        loop {
+            if cursor.goto_next_sibling() {
+                break;
+            }
+            if !cursor.goto_parent() {
+                return;
+            }
+        }
+    }
+}
+
+/// Whether `node` is the `field` child of its parent.
+pub(crate) fn is_field_of_parent(node: Node<'_>, field: &str) -> bool {
+    node.parent()
+        .and_then(|parent| parent.child_by_field_name(field))
+        .is_some_and(|child| child.id() == node.id())
+}
+
+pub(crate) fn node_text<'a>(node: Node<'_>, source: &'a str) -> &'a str {
+    &source[node.byte_range()]
+}
+
+/// Shared scanning of line-oriented import headers.
+pub(crate) struct LineRules {
+    pub starts_statement: fn(&str) -> bool,
+    pub is_complete: fn(&str) -> bool,
+    pub is_attachment: fn(&str) -> bool,
+}
+
+/// A statement's attached lines and its own lines.
+pub(crate) struct RawStatement {
+    pub attachments: Vec<String>,
+    pub body: Vec<String>,
+}
+
+impl RawStatement {
+    pub fn text(&self) -> String {
+        self.attachments
+            .iter()
+            .chain(self.body.iter())
+            .cloned()
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Scan statements starting at line `first`.
+///
+/// Comments and attributes directly above a statement travel with it; a blank
+/// line starts a new group, and anything else ends the header.
+pub(crate) fn scan_statements(
+    lines: &[&str],
+    first: usize,
+    rules: &LineRules,
+) -> (usize, usize, Vec<Vec<RawStatement>>) {
+    let mut groups: Vec<Vec<RawStatement>> = vec![Vec::new()];
+    let mut start = None;
+    let mut end = first;
+    let mut pending: Vec<String> = Vec::new();
+    let mut index = first;
+
+    while index < lines.len() {
+        let line = lines[index];
+        if (rules.starts_statement)(line) {
+            start.get_or_insert(index - pending.len());
+            let mut body = vec![line.to_string()];
+            index += 1;
+            while !(rules.is_complete)(&body.join("\n")) && index < lines.len() {
+                body.push(lines[index].to_string());
+                index += 1;
+            }
+            groups.last_mut().expect("group").push(RawStatement {
+                attachments: std::mem::take(&mut pending),
+                body,
+            });
+            end = index;
+            continue;
+        }
+        if line.trim().is_empty() {
+            if !pending.is_empty() {
+                break;
+            }
+            if start.is_some() && !groups.last().expect("group").is_empty() {
+                groups.push(Vec::new());
+            }
+            index += 1;
+            continue;
+        }
+        if (rules.is_attachment)(line) {
+            pending.push(line.to_string());
+            index += 1;
+            continue;
+        }
+        break;
+    }
+
+    groups.retain(|group| !group.is_empty());
+    (start.unwrap_or(first), end, groups)
+}
+
+/// Input for the `organize_imports` tool.
+#[derive(Debug, Deserialize)]
+struct OrganizeImportsInput {
+    path: String,
+    #[serde(default)]
+    add_missing: Option<bool>,
+    #[serde(default)]
+    remove_unused: Option<bool>,
+    #[serde(default)]
+    sort: Option<bool>,
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// Adds missing imports, removes unused ones and sorts the rest.
+#[derive(Clone)]
+pub struct OrganizeImportsTool {
+    workspace_root: PathBuf,
+    tree_cache: SyntaxTreeCache,
+    index: Arc<Mutex<SymbolIndex>>,
+    config: ImportsConfig,
+    quota: WriteQuota,
+}
+
+impl OrganizeImportsTool {
+    pub fn new(workspace_root: PathBuf, tree_cache: SyntaxTreeCache) -> Self {
+        let config = ImportsConfig::default();
+        Self {
+            workspace_root,
+            tree_cache,
+            index: Arc::new(Mutex::new(SymbolIndex::new(config.max_index_files))),
+            config,
+            quota: WriteQuota::default(),
+        }
+    }
+
+    /// Share the write limits of the file tools.
+    pub fn set_write_quota(&mut self, quota: WriteQuota) {
+        self.quota = quota;
+    }
+
+    pub fn set_config(&mut self, config: ImportsConfig) {
+        self.index.lock().set_max_files(config.max_index_files);
+        self.config = config;
+    }
+
+    pub fn config(&self) -> &ImportsConfig {
+        &self.config
+    }
+
+    /// The workspace index of importable definitions, shared with other tools.
+    pub fn symbol_index(&self) -> Arc<Mutex<SymbolIndex>> {
+        self.index.clone()
+    }
+
+    /// Organize `path` and write the result unless `dry_run` is set.
+    ///
+    /// Files in languages without import support are reported as unchanged.
+    pub async fn organize(
+        &self,
+        path: &str,
+        options: OrganizeOptions,
+        dry_run: bool,
+    ) -> Result<Value> {
+        let tool = self.clone();
+        let path = path.to_string();
+        tokio::task::spawn_blocking(move || tool.organize_blocking(&path, options, dry_run))
+            .await
+            .context("organize_imports task failed")?
+    }
+
+    fn organize_blocking(
+        &self,
+        path: &str,
+        options: OrganizeOptions,
+        dry_run: bool,
+    ) -> Result<Value> {
+        let absolute = self.resolve_path(path)?;
+        let relative = absolute
+            .strip_prefix(&self.workspace_root)
+            .unwrap_or(&absolute)
+            .to_path_buf();
+        let display = relative.to_string_lossy().replace('\\', "/");
+
+        let Some(language) = ImportLanguage::from_path(&absolute) else {
+            return Ok(json!({
+                "success": true,
+                "path": display,
+                "changed": false,
+                "skipped": true,
+                "reason": "Import management supports Rust, Python, JavaScript, TypeScript and Go files",
+            }));
+        };
+
+        let source = std::fs::read_to_string(&absolute)
+            .with_context(|| format!("Failed to read {}", display))?;
+        let mut analyzer = TreeSitterAnalyzer::new()?;
+        let needs_index = language != ImportLanguage::Go
+            && (options.add_missing || (options.remove_unused && language == ImportLanguage::Rust));
+
+        let (updated, changes) = {
+            let mut index = self.index.lock();
+            if needs_index {
+                index.refresh(&self.workspace_root, language.family(), &mut analyzer)?;
+            }
+            let ctx = FileContext {
+                workspace: &self.workspace_root,
+                relative: &relative,
+            };
+            organize_source(language, &ctx, &source, &index, &mut analyzer, options)?
+        };
+
+        let changed = updated != source;
+        if changed && !dry_run {
+            self.quota.reserve(&display, updated.len() as u64, false)?;
+            std::fs::write(&absolute, &updated)
+                .with_context(|| format!("Failed to write {}", display))?;
+            self.tree_cache.record_edit(&absolute, &source, &updated);
+        }
+
+        let mut result = json!({
+            "success": true,
+            "path": display,
+            "language": language.name(),
+            "changed": changed,
+            "dry_run": dry_run,
+            "added": changes.added,
+            "removed": changes.removed,
+            "sorted": changes.sorted,
+        });
+        if !changes.ambiguous.is_empty() {
+            result["ambiguous"] = json!(changes.ambiguous);
+        }
+        if dry_run && changed {
+            let ctx = FileContext {
+                workspace: &self.workspace_root,
+                relative: &relative,
+            };
+            let lines: Vec<&str> = updated.lines().collect();
+            let block = language.syntax().parse_block(&lines, &ctx);
+            result["preview"] = json!(lines[block.start..block.end].join("\n"));
+        }
+        Ok(result)
+    }
+
+    fn resolve_path(&self, path: &str) -> Result<PathBuf> {
+        let candidate = self.workspace_root.join(path);
+        let canonical = candidate
+            .canonicalize()
+            .with_context(|| format!("File not found: {}", path))?;
+        let root = self
+            .workspace_root
+            .canonicalize()
+            .unwrap_or_else(|_| self.workspace_root.clone());
+        if !canonical.starts_with(&root) {
+            return Err(anyhow!("Path {} is outside the workspace", path));
+        }
+        Ok(root.join(canonical.strip_prefix(&root).unwrap_or(&canonical)))
+    }
+}
+
+#[async_trait]
+impl Tool for OrganizeImportsTool {
+    async fn execute(&self, args: Value) -> Result<Value> {
+        let input: OrganizeImportsInput = serde_json::from_value(args).context(
+            "Error: Invalid 'organize_imports' arguments. Required: { path: string }. Optional: { add_missing, remove_unused, sort, dry_run: boolean }",
+        )?;
+        let options = OrganizeOptions {
+            add_missing: input.add_missing.unwrap_or(self.config.add_missing),
+            remove_unused: input.remove_unused.unwrap_or(self.config.remove_unused),
+            sort: input.sort.unwrap_or(self.config.sort),
+        };
+        self.organize(&input.path, options, input.dry_run).await
+    }
+
+    fn name(&self) -> &'static str {
+        tools::ORGANIZE_IMPORTS
+    }
+
+    fn description(&self) -> &'static str {
+        "Add missing imports, remove unused ones and sort them for a source file"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_header_byte_ranges() {
+        let source = "a\nb\nc\n";
+        assert_eq!(line_byte_range(source, 1, 2), 2..4);
+        assert_eq!(line_byte_range(source, 1, 3), 2..6);
+        assert_eq!(line_byte_range(source, 0, 0), 0..0);
+    }
+
+    #[tokio::test]
+    async fn organizes_files_in_the_workspace() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::create_dir_all(dir.path().join("pkg")).expect("pkg");
+        std::fs::write(dir.path().join("pkg/models.py"), "class User:\n    pass\n")
+            .expect("models");
+        std::fs::write(
+            dir.path().join("app.py"),
+            "import sys\nimport os\n\n\ndef load():\n    return User(os.getcwd())\n",
+        )
+        .expect("app");
+
+        let tool = OrganizeImportsTool::new(dir.path().to_path_buf(), SyntaxTreeCache::new());
+        let result = tool
+            .execute(json!({"path": "app.py"}))
+            .await
+            .expect("organize");
+        assert_eq!(result["changed"], json!(true));
+        assert_eq!(result["removed"], json!(["sys"]));
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("app.py")).expect("read"),
+            "import os\n\nfrom pkg.models import User\n\n\ndef load():\n    return User(os.getcwd())\n"
+        );
+
+        let skipped = tool
+            .execute(json!({"path": "pkg/models.py", "dry_run": true}))
+            .await
+            .expect("organize");
+        assert_eq!(skipped["changed"], json!(false));
+    }
+}
@@ -0,0 +1,648 @@
+//! Python `import` and `from ... import` statements
+
+use super::{
+    Binding, FileContext, ImportBlock, ImportStatement, ImportSyntax, ImportTarget, IndexedSymbol,
+    LineRules, RawStatement, References, is_field_of_parent, node_text, scan_statements, walk_tree,
+};
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::Path;
+use tree_sitter::Node;
+
+/// Top-level standard library modules, grouped before third-party imports.
+const STDLIB: &[&str] = &[
+    "abc",
+    "argparse",
+    "array",
+    "ast",
+    "asyncio",
+    "atexit",
+    "base64",
+    "binascii",
+    "bisect",
+    "builtins",
+    "bz2",
+    "calendar",
+    "cmath",
+    "codecs",
+    "collections",
+    "concurrent",
+    "configparser",
+    "contextlib",
+    "contextvars",
+    "copy",
+    "csv",
+    "ctypes",
+    "dataclasses",
+    "datetime",
+    "decimal",
+    "difflib",
+    "dis",
+    "email",
+    "enum",
+    "errno",
+    "fcntl",
+    "filecmp",
+    "fnmatch",
+    "fractions",
+    "functools",
+    "gc",
+    "getpass",
+    "gettext",
+    "glob",
+    "graphlib",
+    "gzip",
+    "hashlib",
+    "heapq",
+    "hmac",
+    "html",
+    "http",
+    "importlib",
+    "inspect",
+    "io",
+    "ipaddress",
+    "itertools",
+    "json",
+    "keyword",
+    "linecache",
+    "locale",
+    "logging",
+    "lzma",
+    "math",
+    "mimetypes",
+    "multiprocessing",
+    "numbers",
+    "operator",
+    "os",
+    "pathlib",
+    "pickle",
+    "pkgutil",
+    "platform",
+    "pprint",
+    "queue",
+    "random",
+    "re",
+    "secrets",
+    "select",
+    "selectors",
+    "shlex",
+    "shutil",
+    "signal",
+    "socket",
+    "sqlite3",
+    "ssl",
+    "stat",
+    "statistics",
+    "string",
+    "struct",
+    "subprocess",
+    "sys",
+    "sysconfig",
+    "tarfile",
+    "tempfile",
+    "textwrap",
+    "threading",
+    "time",
+    "timeit",
+    "tomllib",
+    "traceback",
+    "types",
+    "typing",
+    "unicodedata",
+    "unittest",
+    "urllib",
+    "uuid",
+    "warnings",
+    "weakref",
+    "xml",
+    "zipfile",
+    "zlib",
+    "zoneinfo",
+];
+
+/// Capitalized builtins that never need an import.
+const BUILTINS: &[&str] = &[
+    "True",
+    "False",
+    "None",
+    "NotImplemented",
+    "Ellipsis",
+    "Exception",
+    "BaseException",
+    "ArithmeticError",
+    "AssertionError",
+    "AttributeError",
+    "BufferError",
+    "ConnectionError",
+    "DeprecationWarning",
+    "EOFError",
+    "FileExistsError",
+    "FileNotFoundError",
+    "GeneratorExit",
+    "ImportError",
+    "IndexError",
+    "IsADirectoryError",
+    "KeyError",
+    "KeyboardInterrupt",
+    "LookupError",
+    "MemoryError",
+    "ModuleNotFoundError",
+    "NameError",
+    "NotADirectoryError",
+    "NotImplementedError",
+    "OSError",
+    "IOError",
+    "OverflowError",
+    "PermissionError",
+    "RecursionError",
+    "RuntimeError",
+    "StopAsyncIteration",
+    "StopIteration",
+    "SyntaxError",
+    "SystemExit",
+    "TimeoutError",
+    "TypeError",
+    "UnicodeDecodeError",
+    "UnicodeEncodeError",
+    "UserWarning",
+    "ValueError",
+    "Warning",
+    "ZeroDivisionError",
+];
+
+/// Longest line written before a `from` import is wrapped in parentheses.
+const MAX_LINE: usize = 88;
+
+const RULES: LineRules = LineRules {
+    starts_statement: |line| line.starts_with("import ") || line.starts_with("from "),
+    is_complete: |body| {
+        let code = strip_comments(body);
+        code.matches('(').count() <= code.matches(')').count() && !code.trim_end().ends_with('\\')
+    },
+    is_attachment: |line| line.starts_with('#'),
+};
+
+pub(super) struct PythonImports;
+
+impl ImportSyntax for PythonImports {
+    fn parse_block(&self, lines: &[&str], ctx: &FileContext<'_>) -> ImportBlock {
+        let first = preamble_end(lines);
+        let (start, end, raw_groups) = scan_statements(lines, first, &RULES);
+        let mut block = ImportBlock {
+            start,
+            end,
+            ..ImportBlock::default()
+        };
+        let package_init = ctx
+            .relative
+            .file_name()
+            .is_some_and(|name| name == "__init__.py");
+        for raw_group in raw_groups {
+            let mut group = Vec::with_capacity(raw_group.len());
+            for raw in &raw_group {
+                let parsed = parse_import(raw);
+                block.has_glob |= parsed.as_ref().is_some_and(|p| p.is_glob());
+                group.push(match parsed {
+                    Some(parsed) => parsed.statement(ctx, !package_init),
+                    None => ImportStatement {
+                        text: raw.text(),
+                        category: 2,
+                        key: (2, String::new()),
+                        bindings: Vec::new(),
+                        sortable: false,
+                    },
+                });
+            }
+            block.groups.push(group);
+        }
+        block
+    }
+
+    fn remove_bindings(
+        &self,
+        statement: &ImportStatement,
+        unused: &HashSet<String>,
+    ) -> Option<ImportStatement> {
+        let mut parsed = reparse(&statement.text)?;
+        parsed
+            .names
+            .retain(|name| !unused.contains(name.bound_name(parsed.from.is_some())));
+        if parsed.names.is_empty() {
+            return None;
+        }
+        let bindings = parsed.bindings(true);
+        Some(ImportStatement {
+            text: parsed.render(),
+            bindings,
+            ..statement.clone()
+        })
+    }
+
+    fn merge(&self, statement: &ImportStatement, target: &ImportTarget) -> Option<ImportStatement> {
+        let mut parsed = reparse(&statement.text)?;
+        if parsed.from.as_deref() != Some(target.module.as_str()) || parsed.is_glob() {
+            return None;
+        }
+        parsed.names.push(ImportedName {
+            path: target.name.clone(),
+            alias: None,
+        });
+        let mut bindings = statement.bindings.clone();
+        bindings.push(Binding::new(target.name.clone(), true));
+        Some(ImportStatement {
+            text: parsed.render(),
+            bindings,
+            ..statement.clone()
+        })
+    }
+
+    fn new_statement(
+        &self,
+        target: &ImportTarget,
+        _block: &ImportBlock,
+        ctx: &FileContext<'_>,
+    ) -> ImportStatement {
+        ParsedImport {
+            attachments: Vec::new(),
+            from: Some(target.module.clone()),
+            names: vec![ImportedName {
+                path: target.name.clone(),
+                alias: None,
+            }],
+        }
+        .statement(ctx, true)
+    }
+
+    fn references(&self, root: Node<'_>, source: &str, skip: Range<usize>) -> References {
+        let mut references = References::default();
+        walk_tree(root, |node| {
+            if node.kind() != "identifier" || skip.contains(&node.start_byte()) {
+                return;
+            }
+            let Some(parent) = node.parent() else {
+                return;
+            };
+            let name = node_text(node, source).to_string();
+            if in_import(node) {
+                references.defined.insert(name);
+                return;
+            }
+            match parent.kind() {
+                "attribute" if is_field_of_parent(node, "attribute") => {}
+                "keyword_argument" if is_field_of_parent(node, "name") => {}
+                "class_definition" | "function_definition" if is_field_of_parent(node, "name") => {
+                    references.defined.insert(name);
+                }
+                "assignment" if is_field_of_parent(node, "left") => {
+                    references.defined.insert(name);
+                }
+                "parameters"
+                | "typed_parameter"
+                | "default_parameter"
+                | "typed_default_parameter"
+                | "pattern_list"
+                | "tuple_pattern"
+                | "as_pattern_target"
+                | "global_statement"
+                | "nonlocal_statement" => {
+                    references.defined.insert(name);
+                }
+                _ => {
+                    references.used.insert(name);
+                }
+            }
+        });
+        references
+    }
+
+    fn exported_symbols(&self, root: Node<'_>, source: &str) -> Vec<IndexedSymbol> {
+        let mut symbols = Vec::new();
+        let mut cursor = root.walk();
+        for child in root.named_children(&mut cursor) {
+            let definition = if child.kind() == "decorated_definition" {
+                child.child_by_field_name("definition")
+            } else {
+                Some(child)
+            };
+            let Some(definition) = definition
+                .filter(|node| matches!(node.kind(), "class_definition" | "function_definition"))
+            else {
+                continue;
+            };
+            let Some(name) = definition.child_by_field_name("name") else {
+                continue;
+            };
+            let name = node_text(name, source);
+            if !name.starts_with('_') {
+                symbols.push(IndexedSymbol::new(name));
+            }
+        }
+        symbols
+    }
+
+    fn resolve(
+        &self,
+        ctx: &FileContext<'_>,
+        name: &str,
+        definitions: &[(&Path, &IndexedSymbol)],
+    ) -> Vec<ImportTarget> {
+        let current = module_path(ctx.relative);
+        definitions
+            .iter()
+            .filter_map(|(path, _)| module_path(path))
+            .filter(|module| Some(module) != current.as_ref())
+            .map(|module| ImportTarget {
+                module,
+                name: name.to_string(),
+            })
+            .collect()
+    }
+
+    fn is_builtin(&self, name: &str) -> bool {
+        BUILTINS.contains(&name)
+    }
+}
+
+/// Index of the first line after the shebang, leading comments and module docstring.
+fn preamble_end(lines: &[&str]) -> usize {
+    let mut index = 0;
+    let mut end = 0;
+    while index < lines.len() {
+        let trimmed = lines[index].trim();
+        if trimmed.is_empty() {
+            index += 1;
+            continue;
+        }
+        if trimmed.starts_with('#') {
+            index += 1;
+            end = index;
+            continue;
+        }
+        let quote = ["\"\"\"", "'''"].into_iter().find(|quote| {
+            trimmed
+                .trim_start_matches(['r', 'u', 'R', 'U'])
+                .starts_with(quote)
+        });
+        if let Some(quote) = quote {
+            let opening = trimmed.find(quote).unwrap_or(0) + quote.len();
+            if trimmed[opening..].contains(quote) {
+                index += 1;
+            } else {
+                index += 1;
+                while index < lines.len() && !lines[index].contains(quote) {
+                    index += 1;
+                }
+                index += 1;
+            }
+            end = index.min(lines.len());
+            continue;
+        }
+        break;
+    }
+    end
+}
+
+fn strip_comments(text: &str) -> String {
+    text.lines()
+        .map(|line| line.split_once('#').map_or(line, |(code, _)| code))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Identifiers inside import statements are definitions, not references.
+fn in_import(node: Node<'_>) -> bool {
+    let mut current = node.parent();
+    for _ in 0..3 {
+        match current {
+            Some(parent)
+                if matches!(
+                    parent.kind(),
+                    "import_statement" | "import_from_statement" | "future_import_statement"
+                ) =>
+            {
+                return true;
+            }
+            Some(parent) => current = parent.parent(),
+            None => return false,
+        }
+    }
+    false
+}
+
+/// Dotted module path of a workspace-relative Python file.
+fn module_path(relative: &Path) -> Option<String> {
+    let relative = relative.strip_prefix("src").unwrap_or(relative);
+    let mut parts: Vec<String> = relative
+        .with_extension("")
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    if parts.last().is_some_and(|last| last == "__init__") {
+        parts.pop();
+    }
+    let valid = !parts.is_empty()
+        && parts.iter().all(|part| {
+            part.chars()
+                .next()
+                .is_some_and(|c| c.is_alphabetic() || c == '_')
+                && part.chars().all(|c| c.is_alphanumeric() || c == '_')
+        });
+    valid.then(|| parts.join("."))
+}
+
+fn category(module: &str, ctx: &FileContext<'_>) -> u8 {
+    if module == "__future__" {
+        return 0;
+    }
+    if module.starts_with('.') {
+        return 3;
+    }
+    let top = module.split('.').next().unwrap_or(module);
+    if STDLIB.contains(&top) {
+        return 1;
+    }
+    let file = format!("{}.py", top);
+    let first_party = [ctx.workspace.to_path_buf(), ctx.workspace.join("src")]
+        .iter()
+        .any(|dir| dir.join(top).is_dir() || dir.join(&file).is_file());
+    if first_party { 3 } else { 2 }
+}
+
+/// A name in an import statement: `a.b` in `import a.b` or `c` in `from m import c`.
+#[derive(Debug, Clone)]
+struct ImportedName {
+    path: String,
+    alias: Option<String>,
+}
+
+impl ImportedName {
+    fn bound_name(&self, from: bool) -> &str {
+        match &self.alias {
+            Some(alias) => alias,
+            None if from => &self.path,
+            None => self.path.split('.').next().unwrap_or(&self.path),
+        }
+    }
+
+    fn render(&self) -> String {
+        match &self.alias {
+            Some(alias) => format!("{} as {}", self.path, alias),
+            None => self.path.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ParsedImport {
+    attachments: Vec<String>,
+    /// Module of a `from ... import` statement
+    from: Option<String>,
+    names: Vec<ImportedName>,
+}
+
+impl ParsedImport {
+    fn is_glob(&self) -> bool {
+        self.names.iter().any(|name| name.path == "*")
+    }
+
+    fn bindings(&self, removable: bool) -> Vec<Binding> {
+        self.names
+            .iter()
+            .filter(|name| name.path != "*")
+            .map(|name| Binding::new(name.bound_name(self.from.is_some()), removable))
+            .collect()
+    }
+
+    fn statement(&self, ctx: &FileContext<'_>, removable: bool) -> ImportStatement {
+        let module = match &self.from {
+            Some(module) => module.clone(),
+            None => self
+                .names
+                .first()
+                .map(|name| name.path.clone())
+                .unwrap_or_default(),
+        };
+        let removable = removable && module != "__future__";
+        ImportStatement {
+            text: self.render(),
+            category: category(&module, ctx),
+            key: (u8::from(self.from.is_some()), module.to_lowercase()),
+            bindings: self.bindings(removable),
+            sortable: true,
+        }
+    }
+
+    fn render(&self) -> String {
+        let names: Vec<String> = self.names.iter().map(ImportedName::render).collect();
+        let statement = match &self.from {
+            Some(module) => {
+                let single = format!("from {} import {}", module, names.join(", "));
+                if single.len() <= MAX_LINE {
+                    single
+                } else {
+                    let body: String = names
+                        .iter()
+                        .map(|name| format!("    {},\n", name))
+                        .collect();
+                    format!("from {} import (\n{})", module, body)
+                }
+            }
+            None => format!("import {}", names.join(", ")),
+        };
+        let mut lines = self.attachments.clone();
+        lines.push(statement);
+        lines.join("\n")
+    }
+}
+
+fn reparse(text: &str) -> Option<ParsedImport> {
+    let mut attachments = Vec::new();
+    let mut body = Vec::new();
+    for line in text.lines() {
+        if body.is_empty() && line.starts_with('#') {
+            attachments.push(line.to_string());
+        } else {
+            body.push(line.to_string());
+        }
+    }
+    parse_import(&RawStatement { attachments, body })
+}
+
+fn parse_import(raw: &RawStatement) -> Option<ParsedImport> {
+    let code = strip_comments(&raw.body.join("\n"))
+        .replace("\\\n", " ")
+        .replace(['(', ')', '\n'], " ");
+    let code = code.trim();
+    let (from, list) = if let Some(rest) = code.strip_prefix("from ") {
+        let (module, names) = rest.split_once(" import ")?;
+        (Some(module.trim().to_string()), names)
+    } else {
+        (None, code.strip_prefix("import ")?)
+    };
+    if list.contains(';') {
+        return None;
+    }
+    let names: Vec<ImportedName> = list
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| match part.split_once(" as ") {
+            Some((path, alias)) => ImportedName {
+                path: path.trim().to_string(),
+                alias: Some(alias.trim().to_string()),
+            },
+            None => ImportedName {
+                path: part.to_string(),
+                alias: None,
+            },
+        })
+        .collect();
+    if names.is_empty() {
+        return None;
+    }
+    Some(ParsedImport {
+        attachments: raw.attachments.clone(),
+        from,
+        names,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_rewrites_from_imports() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let ctx = FileContext {
+            workspace: dir.path(),
+            relative: Path::new("app/main.py"),
+        };
+        let source = "\"\"\"Entry point.\"\"\"\n\nfrom typing import (\n    Any,\n    Dict,  # mappings\n)\nimport os.path\n";
+        let lines: Vec<&str> = source.lines().collect();
+        let block = PythonImports.parse_block(&lines, &ctx);
+        assert_eq!((block.start, block.end), (2, 7));
+        let statements: Vec<&ImportStatement> = block.groups.iter().flatten().collect();
+        assert_eq!(statements[0].key, (1, "typing".to_string()));
+        assert_eq!(statements[1].bindings, vec![Binding::new("os", true)]);
+
+        let unused: HashSet<String> = ["Dict".to_string()].into_iter().collect();
+        let rewritten = PythonImports
+            .remove_bindings(statements[0], &unused)
+            .expect("kept");
+        assert_eq!(rewritten.text, "from typing import Any");
+    }
+
+    #[test]
+    fn maps_files_to_module_paths() {
+        assert_eq!(
+            module_path(Path::new("src/pkg/models.py")).as_deref(),
+            Some("pkg.models")
+        );
+        assert_eq!(
+            module_path(Path::new("pkg/__init__.py")).as_deref(),
+            Some("pkg")
+        );
+        assert_eq!(module_path(Path::new("my-scripts/run.py")), None);
+    }
+}
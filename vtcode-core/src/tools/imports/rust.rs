@@ -0,0 +1,847 @@
+//! Rust `use` declarations
+
+use super::{
+    Binding, FileContext, ImportBlock, ImportStatement, ImportSyntax, ImportTarget, IndexedSymbol,
+    LineRules, RawStatement, References, SymbolIndex, is_field_of_parent, node_text,
+    scan_statements, walk_tree,
+};
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use tree_sitter::Node;
+
+/// Names in the Rust prelude, never imported.
+const PRELUDE: &[&str] = &[
+    "AsMut",
+    "AsRef",
+    "Box",
+    "Clone",
+    "Copy",
+    "Default",
+    "DoubleEndedIterator",
+    "Drop",
+    "Eq",
+    "Err",
+    "ExactSizeIterator",
+    "Extend",
+    "Fn",
+    "FnMut",
+    "FnOnce",
+    "From",
+    "FromIterator",
+    "Into",
+    "IntoIterator",
+    "Iterator",
+    "None",
+    "Ok",
+    "Option",
+    "Ord",
+    "PartialEq",
+    "PartialOrd",
+    "Result",
+    "Self",
+    "Send",
+    "Sized",
+    "Some",
+    "String",
+    "Sync",
+    "ToOwned",
+    "ToString",
+    "TryFrom",
+    "TryInto",
+    "Unpin",
+    "Vec",
+    "Debug",
+    "Hash",
+];
+
+/// Traits that are commonly imported only for their methods.
+const KNOWN_TRAITS: &[&str] = &[
+    "AsyncBufReadExt",
+    "AsyncReadExt",
+    "AsyncWriteExt",
+    "BufRead",
+    "Context",
+    "Digest",
+    "FromStr",
+    "Itertools",
+    "ParallelIterator",
+    "Read",
+    "Rng",
+    "Seek",
+    "Write",
+    "Parser",
+    "Subcommand",
+    "Args",
+    "ValueEnum",
+    "Buf",
+    "BufMut",
+    "Borrow",
+    "Deref",
+    "DerefMut",
+    "ExecutableCommand",
+    "QueueableCommand",
+    "Stylize",
+    "Widget",
+    "StatefulWidget",
+    "Hasher",
+];
+
+/// Definition kinds whose `name` field introduces a name.
+const DEFINITION_KINDS: &[&str] = &[
+    "struct_item",
+    "enum_item",
+    "union_item",
+    "trait_item",
+    "type_item",
+    "function_item",
+    "function_signature_item",
+    "const_item",
+    "static_item",
+    "mod_item",
+    "enum_variant",
+    "associated_type",
+    "macro_definition",
+];
+
+const QUALIFIED_KINDS: &[&str] = &[
+    "scoped_identifier",
+    "scoped_type_identifier",
+    "scoped_use_list",
+    "use_as_clause",
+];
+
+const RULES: LineRules = LineRules {
+    starts_statement: starts_use,
+    is_complete: use_is_complete,
+    is_attachment: |line| {
+        let trimmed = line.trim_start();
+        trimmed.starts_with("//") || trimmed.starts_with("#[")
+    },
+};
+
+pub(super) struct RustImports;
+
+impl ImportSyntax for RustImports {
+    fn parse_block(&self, lines: &[&str], _ctx: &FileContext<'_>) -> ImportBlock {
+        let first = preamble_end(lines);
+        let (start, end, raw_groups) = scan_statements(lines, first, &RULES);
+        let mut block = ImportBlock {
+            start,
+            end,
+            ..ImportBlock::default()
+        };
+        for raw_group in raw_groups {
+            let group: Vec<ImportStatement> = raw_group.iter().map(parse_statement).collect();
+            block.has_glob |= group
+                .iter()
+                .any(|statement| parse_use(&statement.text).is_some_and(|u| u.has_glob()));
+            block.groups.push(group);
+        }
+        block
+    }
+
+    fn remove_bindings(
+        &self,
+        statement: &ImportStatement,
+        unused: &HashSet<String>,
+    ) -> Option<ImportStatement> {
+        let parsed = parse_use(&statement.text)?;
+        let leaves: Vec<UseLeaf> = parsed
+            .leaves
+            .into_iter()
+            .filter(|leaf| !unused.contains(&leaf.bound_name()))
+            .collect();
+        if leaves.is_empty() {
+            return None;
+        }
+        let tree = render_tree(&leaves);
+        let mut lines = parsed.attachments;
+        lines.push(format!("{}use {};", parsed.visibility, tree));
+        Some(ImportStatement {
+            text: lines.join("\n"),
+            category: statement.category,
+            key: sort_key(&tree),
+            bindings: leaves.iter().map(|leaf| leaf.binding(false)).collect(),
+            sortable: true,
+        })
+    }
+
+    fn new_statement(
+        &self,
+        target: &ImportTarget,
+        _block: &ImportBlock,
+        _ctx: &FileContext<'_>,
+    ) -> ImportStatement {
+        let path = format!("{}::{}", target.module, target.name);
+        ImportStatement {
+            text: format!("use {};", path),
+            category: category(&path),
+            key: sort_key(&path),
+            bindings: vec![Binding::new(target.name.clone(), true)],
+            sortable: true,
+        }
+    }
+
+    fn references(&self, root: Node<'_>, source: &str, skip: Range<usize>) -> References {
+        let mut references = References::default();
+        walk_tree(root, |node| {
+            if !matches!(node.kind(), "identifier" | "type_identifier")
+                || skip.contains(&node.start_byte())
+                || has_ancestor(node, &["attribute_item", "inner_attribute_item"])
+            {
+                return;
+            }
+            // Imports in nested modules and function bodies bind their names there.
+            if has_ancestor(node, &["use_declaration"]) {
+                references
+                    .defined
+                    .insert(node_text(node, source).to_string());
+                return;
+            }
+            let Some(parent) = node.parent() else {
+                return;
+            };
+            let name = node_text(node, source).to_string();
+            let parent_kind = parent.kind();
+            if QUALIFIED_KINDS.contains(&parent_kind) && is_field_of_parent(node, "name") {
+                return;
+            }
+            // Macro arguments are plain token trees: `uni::Message` is `uni`, `::`, `Message`.
+            if parent_kind == "token_tree"
+                && node
+                    .prev_sibling()
+                    .is_some_and(|previous| previous.kind() == "::")
+            {
+                return;
+            }
+            if (DEFINITION_KINDS.contains(&parent_kind) && is_field_of_parent(node, "name"))
+                || matches!(
+                    parent_kind,
+                    "type_parameters" | "constrained_type_parameter" | "type_parameter"
+                )
+            {
+                references.defined.insert(name);
+                return;
+            }
+            references.used.insert(name);
+        });
+        references
+    }
+
+    fn exported_symbols(&self, root: Node<'_>, source: &str) -> Vec<IndexedSymbol> {
+        let mut symbols = Vec::new();
+        let mut cursor = root.walk();
+        for item in root.named_children(&mut cursor) {
+            let kind = item.kind();
+            if !DEFINITION_KINDS.contains(&kind) || matches!(kind, "mod_item" | "enum_variant") {
+                continue;
+            }
+            let mut item_cursor = item.walk();
+            let public = item
+                .named_children(&mut item_cursor)
+                .any(|child| child.kind() == "visibility_modifier");
+            let Some(name) = item.child_by_field_name("name") else {
+                continue;
+            };
+            if !public {
+                continue;
+            }
+            let name = node_text(name, source);
+            symbols.push(if kind == "trait_item" {
+                IndexedSymbol::trait_symbol(name)
+            } else {
+                IndexedSymbol::new(name)
+            });
+        }
+        symbols
+    }
+
+    fn resolve(
+        &self,
+        ctx: &FileContext<'_>,
+        name: &str,
+        definitions: &[(&Path, &IndexedSymbol)],
+    ) -> Vec<ImportTarget> {
+        let Some(current) = RustModule::locate(ctx.workspace, ctx.relative) else {
+            return Vec::new();
+        };
+        definitions
+            .iter()
+            .filter_map(|(path, _)| {
+                let target = RustModule::locate(ctx.workspace, path)?;
+                let root = if target.crate_dir == current.crate_dir {
+                    "crate".to_string()
+                } else if target.is_library && current.depends_on(&target.crate_name) {
+                    target.crate_name.replace('-', "_")
+                } else {
+                    return None;
+                };
+                let module = std::iter::once(root)
+                    .chain(target.segments)
+                    .collect::<Vec<_>>()
+                    .join("::");
+                Some(ImportTarget {
+                    module,
+                    name: name.to_string(),
+                })
+            })
+            .collect()
+    }
+
+    fn is_builtin(&self, name: &str) -> bool {
+        PRELUDE.contains(&name)
+    }
+
+    fn keeps_unreferenced(&self, name: &str, index: &SymbolIndex) -> bool {
+        KNOWN_TRAITS.contains(&name) || looks_like_trait(name) || index.is_trait(name)
+    }
+}
+
+fn starts_use(line: &str) -> bool {
+    let mut rest = line;
+    if let Some(after) = rest.strip_prefix("pub") {
+        rest = after;
+        if rest.starts_with('(') {
+            let Some(close) = rest.find(')') else {
+                return false;
+            };
+            rest = &rest[close + 1..];
+        }
+        rest = rest.trim_start();
+    }
+    rest.starts_with("use ")
+}
+
+fn use_is_complete(body: &str) -> bool {
+    strip_comments(body).trim_end().ends_with(';')
+}
+
+fn strip_comments(text: &str) -> String {
+    text.lines()
+        .map(|line| line.split_once("//").map_or(line, |(code, _)| code))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Index of the first line after inner attributes, module docs, leading
+/// comments and `mod` declarations that precede the imports.
+fn preamble_end(lines: &[&str]) -> usize {
+    let mut end = 0;
+    let mut index = 0;
+    while index < lines.len() {
+        let trimmed = lines[index].trim();
+        if trimmed.is_empty() {
+            index += 1;
+            continue;
+        }
+        if trimmed.starts_with("//") || trimmed.starts_with("#![") || is_mod_declaration(trimmed) {
+            index += 1;
+            end = index;
+            continue;
+        }
+        if trimmed.starts_with("#[") {
+            let attributes_end = (index..lines.len())
+                .find(|&next| !lines[next].trim().starts_with("#["))
+                .unwrap_or(lines.len());
+            if lines
+                .get(attributes_end)
+                .is_some_and(|line| is_mod_declaration(line.trim()))
+            {
+                index = attributes_end + 1;
+                end = index;
+                continue;
+            }
+        }
+        break;
+    }
+    end
+}
+
+fn is_mod_declaration(line: &str) -> bool {
+    let rest = line.strip_prefix("pub").map_or(line, |rest| {
+        match rest
+            .strip_prefix('(')
+            .and_then(|inner| inner.split_once(')'))
+        {
+            Some((_, after)) => after,
+            None => rest,
+        }
+        .trim_start()
+    });
+    rest.strip_prefix("mod ")
+        .is_some_and(|name| name.trim_end().ends_with(';'))
+}
+
+/// rustfmt order: `self`, `super` and `crate` paths first, then the rest.
+fn sort_key(path: &str) -> (u8, String) {
+    let rank = match path.split("::").next().unwrap_or("") {
+        "self" => 0,
+        "super" => 1,
+        "crate" => 2,
+        _ => 3,
+    };
+    (rank, path.to_string())
+}
+
+fn category(path: &str) -> u8 {
+    match path
+        .trim_start_matches("::")
+        .split("::")
+        .next()
+        .unwrap_or("")
+    {
+        "std" | "core" | "alloc" => 0,
+        "crate" | "self" | "super" => 2,
+        _ => 1,
+    }
+}
+
+fn has_ancestor(node: Node<'_>, kinds: &[&str]) -> bool {
+    let mut current = node.parent();
+    while let Some(parent) = current {
+        if kinds.contains(&parent.kind()) {
+            return true;
+        }
+        current = parent.parent();
+    }
+    false
+}
+
+/// Extension traits and conversion traits such as `IsTerminal` or `IntoText`,
+/// which are imported for their methods and never named.
+fn looks_like_trait(name: &str) -> bool {
+    if name.ends_with("Ext") {
+        return true;
+    }
+    ["Into", "To", "As", "Is"].iter().any(|prefix| {
+        name.strip_prefix(prefix)
+            .and_then(|rest| rest.chars().next())
+            .is_some_and(|c| c.is_ascii_uppercase())
+    })
+}
+
+fn parse_statement(raw: &RawStatement) -> ImportStatement {
+    let text = raw.text();
+    match parse_use(&text) {
+        Some(parsed) => {
+            let conditional = parsed.attachments.iter().any(|line| line.contains("#["));
+            let removable = parsed.visibility.is_empty() && !conditional;
+            let tree = render_tree(&parsed.leaves);
+            ImportStatement {
+                category: category(&tree),
+                key: sort_key(&tree),
+                bindings: parsed
+                    .leaves
+                    .iter()
+                    .map(|leaf| leaf.binding(removable))
+                    .collect(),
+                sortable: true,
+                text,
+            }
+        }
+        None => ImportStatement {
+            category: 1,
+            key: (3, strip_comments(&raw.body.join(" "))),
+            bindings: Vec::new(),
+            sortable: true,
+            text,
+        },
+    }
+}
+
+/// A `use` declaration split into attached lines, visibility and leaves.
+struct ParsedUse {
+    attachments: Vec<String>,
+    /// `pub `, `pub(crate) ` or empty
+    visibility: String,
+    leaves: Vec<UseLeaf>,
+}
+
+impl ParsedUse {
+    fn has_glob(&self) -> bool {
+        self.leaves.iter().any(UseLeaf::is_glob)
+    }
+}
+
+/// One imported path, e.g. `std::io::Write` or `serde::Deserialize as De`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct UseLeaf {
+    path: Vec<String>,
+    alias: Option<String>,
+}
+
+impl UseLeaf {
+    fn is_glob(&self) -> bool {
+        self.path.last().is_some_and(|segment| segment == "*")
+    }
+
+    fn bound_name(&self) -> String {
+        if let Some(alias) = &self.alias {
+            return alias.clone();
+        }
+        match self.path.as_slice() {
+            [.., parent, last] if last == "self" => parent.clone(),
+            [.., last] => last.clone(),
+            [] => String::new(),
+        }
+    }
+
+    fn binding(&self, removable: bool) -> Binding {
+        let name = self.bound_name();
+        let removable = removable && !self.is_glob() && name != "_";
+        Binding::new(name, removable)
+    }
+}
+
+fn parse_use(text: &str) -> Option<ParsedUse> {
+    let mut attachments = Vec::new();
+    let mut body = Vec::new();
+    for line in text.lines() {
+        if body.is_empty() && !starts_use(line) {
+            attachments.push(line.to_string());
+        } else {
+            body.push(line);
+        }
+    }
+    let code = strip_comments(&body.join("\n"));
+    let code = code.trim().trim_end_matches(';');
+    let (visibility, tree) = code.split_once("use ")?;
+    let visibility = if visibility.trim().is_empty() {
+        String::new()
+    } else {
+        format!("{} ", visibility.trim())
+    };
+
+    let tokens = tokenize(tree);
+    let mut leaves = Vec::new();
+    let mut position = 0;
+    parse_tree(&tokens, &mut position, Vec::new(), &mut leaves)?;
+    if position != tokens.len() || leaves.is_empty() {
+        return None;
+    }
+    Some(ParsedUse {
+        attachments,
+        visibility,
+        leaves,
+    })
+}
+
+fn tokenize(tree: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = tree.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            ':' => {
+                if chars.peek() == Some(&':') {
+                    chars.next();
+                }
+                tokens.push("::".to_string());
+            }
+            '{' | '}' | ',' | '*' => tokens.push(c.to_string()),
+            _ => {
+                let mut ident = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if next.is_alphanumeric() || next == '_' || next == '#' {
+                        ident.push(next);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(ident);
+            }
+        }
+    }
+    tokens
+}
+
+fn parse_tree(
+    tokens: &[String],
+    position: &mut usize,
+    prefix: Vec<String>,
+    leaves: &mut Vec<UseLeaf>,
+) -> Option<()> {
+    let token = tokens.get(*position)?;
+    match token.as_str() {
+        "{" => {
+            *position += 1;
+            loop {
+                if tokens.get(*position)? == "}" {
+                    *position += 1;
+                    return Some(());
+                }
+                parse_tree(tokens, position, prefix.clone(), leaves)?;
+                if tokens.get(*position).is_some_and(|t| t == ",") {
+                    *position += 1;
+                }
+            }
+        }
+        "*" => {
+            *position += 1;
+            let mut path = prefix;
+            path.push("*".to_string());
+            leaves.push(UseLeaf { path, alias: None });
+            Some(())
+        }
+        "::" if prefix.is_empty() => {
+            *position += 1;
+            parse_tree(tokens, position, vec![String::new()], leaves)
+        }
+        "}" | "," | "::" => None,
+        segment => {
+            *position += 1;
+            let mut path = prefix;
+            path.push(segment.to_string());
+            match tokens.get(*position).map(String::as_str) {
+                Some("::") => {
+                    *position += 1;
+                    parse_tree(tokens, position, path, leaves)
+                }
+                Some("as") => {
+                    let alias = tokens.get(*position + 1)?.clone();
+                    *position += 2;
+                    leaves.push(UseLeaf {
+                        path,
+                        alias: Some(alias),
+                    });
+                    Some(())
+                }
+                _ => {
+                    leaves.push(UseLeaf { path, alias: None });
+                    Some(())
+                }
+            }
+        }
+    }
+}
+
+/// Render leaves as a single use tree, nesting shared prefixes in braces.
+fn render_tree(leaves: &[UseLeaf]) -> String {
+    let items = render_items(
+        leaves
+            .iter()
+            .map(|leaf| (leaf.path.clone(), leaf.alias.clone()))
+            .collect(),
+    );
+    if items.len() == 1 {
+        items.into_iter().next().unwrap_or_default()
+    } else {
+        format!("{{{}}}", items.join(", "))
+    }
+}
+
+fn render_items(leaves: Vec<(Vec<String>, Option<String>)>) -> Vec<String> {
+    let mut order: Vec<String> = Vec::new();
+    let mut members: Vec<Vec<(Vec<String>, Option<String>)>> = Vec::new();
+    for (path, alias) in leaves {
+        let Some((first, rest)) = path.split_first() else {
+            continue;
+        };
+        let slot = match order.iter().position(|existing| existing == first) {
+            Some(slot) => slot,
+            None => {
+                order.push(first.clone());
+                members.push(Vec::new());
+                order.len() - 1
+            }
+        };
+        members[slot].push((rest.to_vec(), alias));
+    }
+
+    order
+        .into_iter()
+        .zip(members)
+        .map(|(first, group)| {
+            let alias_suffix = |alias: &Option<String>| {
+                alias
+                    .as_ref()
+                    .map(|a| format!(" as {}", a))
+                    .unwrap_or_default()
+            };
+            if let [(rest, alias)] = group.as_slice() {
+                let path = if rest.is_empty() || rest.as_slice() == ["self"] {
+                    first.clone()
+                } else {
+                    format!("{}::{}", first, rest.join("::"))
+                };
+                return format!("{}{}", path, alias_suffix(alias));
+            }
+            let nested = render_items(
+                group
+                    .into_iter()
+                    .map(|(rest, alias)| {
+                        if rest.is_empty() {
+                            (vec!["self".to_string()], alias)
+                        } else {
+                            (rest, alias)
+                        }
+                    })
+                    .collect(),
+            );
+            match nested.as_slice() {
+                [only] => format!("{}::{}", first, only),
+                _ => format!("{}::{{{}}}", first, nested.join(", ")),
+            }
+        })
+        .collect()
+}
+
+/// Where a Rust file sits in its crate.
+struct RustModule {
+    crate_dir: PathBuf,
+    crate_name: String,
+    manifest: String,
+    is_library: bool,
+    segments: Vec<String>,
+}
+
+impl RustModule {
+    /// Locate `relative` (a workspace-relative `.rs` file under some crate's `src/`).
+    fn locate(workspace: &Path, relative: &Path) -> Option<Self> {
+        let absolute = workspace.join(relative);
+        let crate_dir = absolute
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| dir.starts_with(workspace))
+            .find(|dir| dir.join("Cargo.toml").is_file())?
+            .to_path_buf();
+        let within = absolute.strip_prefix(crate_dir.join("src")).ok()?;
+        let mut segments: Vec<String> = within
+            .with_extension("")
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        if segments.first().is_some_and(|first| first == "bin") {
+            return None;
+        }
+        if segments.len() == 1 && matches!(segments[0].as_str(), "lib" | "main") {
+            segments.clear();
+        }
+        if segments.last().is_some_and(|last| last == "mod") {
+            segments.pop();
+        }
+
+        let manifest = std::fs::read_to_string(crate_dir.join("Cargo.toml")).ok()?;
+        let crate_name = toml::from_str::<toml::Value>(&manifest)
+            .ok()
+            .and_then(|value| {
+                value
+                    .get("package")?
+                    .get("name")?
+                    .as_str()
+                    .map(str::to_string)
+            })?;
+        Some(Self {
+            is_library: crate_dir.join("src/lib.rs").is_file(),
+            crate_dir,
+            crate_name,
+            manifest,
+            segments,
+        })
+    }
+
+    fn depends_on(&self, crate_name: &str) -> bool {
+        self.manifest.lines().any(|line| {
+            line.trim_start()
+                .strip_prefix(crate_name)
+                .is_some_and(|rest| rest.trim_start().starts_with(['=', '.']))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removes_single_names_from_use_trees() {
+        let statement = parse_statement(&RawStatement {
+            attachments: Vec::new(),
+            body: vec![
+                "use std::collections::{".to_string(),
+                "    BTreeMap, HashMap,".to_string(),
+                "    hash_map::{self, Entry},".to_string(),
+                "};".to_string(),
+            ],
+        });
+        let names: Vec<&str> = statement.bindings.iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(names, vec!["BTreeMap", "HashMap", "hash_map", "Entry"]);
+
+        let unused: HashSet<String> = ["HashMap", "Entry"].iter().map(|s| s.to_string()).collect();
+        let rewritten = RustImports
+            .remove_bindings(&statement, &unused)
+            .expect("statement kept");
+        assert_eq!(
+            rewritten.text,
+            "use std::collections::{BTreeMap, hash_map};"
+        );
+
+        let all: HashSet<String> = ["BTreeMap", "HashMap", "hash_map", "Entry"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert!(RustImports.remove_bindings(&statement, &all).is_none());
+    }
+
+    #[test]
+    fn keeps_reexports_and_conditional_imports() {
+        let reexport = parse_statement(&RawStatement {
+            attachments: Vec::new(),
+            body: vec!["pub use crate::config::Config;".to_string()],
+        });
+        assert!(!reexport.bindings[0].removable);
+
+        let conditional = parse_statement(&RawStatement {
+            attachments: vec!["#[cfg(test)]".to_string()],
+            body: vec!["use crate::tests::Fixture as F;".to_string()],
+        });
+        assert_eq!(conditional.bindings, vec![Binding::new("F", false)]);
+    }
+
+    #[test]
+    fn locates_modules_within_crates() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("core/src/config")).expect("dirs");
+        std::fs::create_dir_all(root.join("src")).expect("dirs");
+        std::fs::write(
+            root.join("core/Cargo.toml"),
+            "[package]\nname = \"demo-core\"\n",
+        )
+        .expect("manifest");
+        std::fs::write(root.join("core/src/lib.rs"), "").expect("lib");
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"demo\"\n\n[dependencies]\ndemo-core = { path = \"core\" }\n",
+        )
+        .expect("manifest");
+
+        let module = RustModule::locate(root, Path::new("core/src/config/mod.rs")).expect("module");
+        assert_eq!(module.segments, vec!["config"]);
+        assert_eq!(module.crate_name, "demo-core");
+
+        let symbol = IndexedSymbol::new("Settings");
+        let definitions = [(Path::new("core/src/config/mod.rs"), &symbol)];
+        let ctx = FileContext {
+            workspace: root,
+            relative: Path::new("src/main.rs"),
+        };
+        assert_eq!(
+            RustImports.resolve(&ctx, "Settings", &definitions),
+            vec![ImportTarget {
+                module: "demo_core::config".to_string(),
+                name: "Settings".to_string(),
+            }]
+        );
+        let ctx = FileContext {
+            workspace: root,
+            relative: Path::new("core/src/lib.rs"),
+        };
+        assert_eq!(
+            RustImports.resolve(&ctx, "Settings", &definitions)[0].module,
+            "crate::config"
+        );
+    }
+}
@@ -0,0 +1,229 @@
+//! Canned tool responses for demos and tutorials
+//!
+//! `[tools.mocks]` lists entries that answer tool calls instead of the real
+//! tool. The registry asks [`ToolMocks`] before dispatching a call; the first
+//! entry whose tool name and argument matchers fit returns its response after
+//! the configured latency. Entries with a `responses` list play them in order
+//! across calls and then keep repeating the last one, so a script can show a
+//! failing test run followed by a passing one.
+
+use crate::config::{ToolMockEntry, ToolMocksConfig};
+use glob::Pattern;
+use serde_json::Value;
+use std::time::Duration;
+
+/// A response chosen for a tool call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockAnswer {
+    pub output: Value,
+    pub latency: Duration,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ToolMocks {
+    config: ToolMocksConfig,
+    /// Calls answered so far by each entry
+    served: Vec<usize>,
+}
+
+impl ToolMocks {
+    pub fn new(config: ToolMocksConfig) -> Self {
+        let served = vec![0; config.entries.len()];
+        Self { config, served }
+    }
+
+    /// Whether calls no entry matches must fail rather than run.
+    pub fn is_strict(&self) -> bool {
+        self.config.enabled && self.config.strict
+    }
+
+    /// One line describing the active mocks for the session banner.
+    pub fn summary(&self) -> Option<String> {
+        if !self.config.enabled {
+            return None;
+        }
+        let count = self.config.entries.len();
+        let rest = if self.config.strict {
+            "other tool calls are refused"
+        } else {
+            "other tool calls run normally"
+        };
+        Some(format!(
+            "Tool mocks are on: {count} canned response entr{} from [tools.mocks]; {rest}.",
+            if count == 1 { "y" } else { "ies" }
+        ))
+    }
+
+    /// The canned answer for `tool` called with `args`, if an entry matches.
+    pub fn answer(&mut self, tool: &str, args: &Value) -> Option<MockAnswer> {
+        if !self.config.enabled {
+            return None;
+        }
+        let index = self
+            .config
+            .entries
+            .iter()
+            .position(|entry| entry.tool == tool && args_match(entry, args))?;
+        let entry = &self.config.entries[index];
+        let served = self.served[index];
+        self.served[index] += 1;
+        let output = match entry.responses.as_slice() {
+            [] => entry
+                .response
+                .clone()
+                .unwrap_or_else(|| Value::Object(Default::default())),
+            responses => responses[served.min(responses.len() - 1)].clone(),
+        };
+        Some(MockAnswer {
+            output,
+            latency: Duration::from_millis(entry.latency_ms),
+        })
+    }
+}
+
+fn args_match(entry: &ToolMockEntry, args: &Value) -> bool {
+    entry.args.iter().all(|(key, expected)| {
+        args.get(key)
+            .is_some_and(|actual| value_matches(expected, actual))
+    })
+}
+
+fn value_matches(expected: &Value, actual: &Value) -> bool {
+    match (expected, actual) {
+        (Value::String(pattern), Value::String(text)) => text_matches(pattern, text),
+        (Value::String(pattern), Value::Array(words)) if words.iter().all(Value::is_string) => {
+            let joined = words
+                .iter()
+                .filter_map(Value::as_str)
+                .collect::<Vec<_>>()
+                .join(" ");
+            text_matches(pattern, &joined)
+        }
+        (Value::Object(expected), Value::Object(actual)) => expected.iter().all(|(key, value)| {
+            actual
+                .get(key)
+                .is_some_and(|inner| value_matches(value, inner))
+        }),
+        (Value::Array(expected), Value::Array(actual)) => {
+            expected.len() == actual.len()
+                && expected
+                    .iter()
+                    .zip(actual)
+                    .all(|(expected, actual)| value_matches(expected, actual))
+        }
+        (Value::Number(expected), Value::Number(actual)) => expected.as_f64() == actual.as_f64(),
+        _ => expected == actual,
+    }
+}
+
+/// Glob match, falling back to equality for patterns that do not parse.
+fn text_matches(pattern: &str, text: &str) -> bool {
+    match Pattern::new(pattern) {
+        Ok(glob) => glob.matches(text),
+        Err(_) => pattern == text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entry(tool: &str, args: Value, responses: Vec<Value>) -> ToolMockEntry {
+        ToolMockEntry {
+            tool: tool.to_string(),
+            args: args.as_object().cloned().unwrap_or_default(),
+            responses,
+            latency_ms: 250,
+            ..ToolMockEntry::default()
+        }
+    }
+
+    #[test]
+    fn matches_arguments_and_plays_responses_in_order() {
+        let mut mocks = ToolMocks::new(ToolMocksConfig {
+            enabled: true,
+            strict: false,
+            entries: vec![
+                entry(
+                    "run_terminal_cmd",
+                    json!({ "command": "cargo test*" }),
+                    vec![json!({ "exit_code": 101 }), json!({ "exit_code": 0 })],
+                ),
+                entry(
+                    "read_file",
+                    json!({ "path": "src/*.rs" }),
+                    vec![json!({ "content": "fn main() {}" })],
+                ),
+            ],
+        });
+
+        let test_run = json!({ "command": ["cargo", "test", "--all"] });
+        let first = mocks.answer("run_terminal_cmd", &test_run).unwrap();
+        assert_eq!(first.output, json!({ "exit_code": 101 }));
+        assert_eq!(first.latency, Duration::from_millis(250));
+        for _ in 0..2 {
+            let later = mocks.answer("run_terminal_cmd", &test_run).unwrap();
+            assert_eq!(later.output, json!({ "exit_code": 0 }));
+        }
+
+        assert!(
+            mocks
+                .answer(
+                    "run_terminal_cmd",
+                    &json!({ "command": ["cargo", "build"] })
+                )
+                .is_none()
+        );
+        assert!(
+            mocks
+                .answer(
+                    "read_file",
+                    &json!({ "path": "src/main.rs", "max_bytes": 100 })
+                )
+                .is_some()
+        );
+        assert!(
+            mocks
+                .answer("read_file", &json!({ "path": "README.md" }))
+                .is_none()
+        );
+        assert!(mocks.answer("read_file", &json!({})).is_none());
+    }
+
+    #[tokio::test]
+    async fn registry_answers_from_mocks_and_refuses_the_rest_when_strict() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut registry = crate::tools::ToolRegistry::new(dir.path().to_path_buf());
+        registry
+            .apply_config_policies(&crate::config::ToolsConfig {
+                mocks: ToolMocksConfig {
+                    enabled: true,
+                    strict: true,
+                    entries: vec![ToolMockEntry {
+                        tool: "read_file".to_string(),
+                        response: Some(
+                            json!({ "content": "fn demo() {}\n", "path": "src/demo.rs" }),
+                        ),
+                        ..ToolMockEntry::default()
+                    }],
+                },
+                ..Default::default()
+            })
+            .unwrap();
+        registry.allow_all_tools().unwrap();
+
+        let read = registry
+            .execute_tool("read_file", json!({ "path": "src/demo.rs" }))
+            .await
+            .unwrap();
+        assert_eq!(read["content"], "fn demo() {}\n");
+        assert_eq!(read["success"], true);
+
+        let listed = registry
+            .execute_tool("list_files", json!({ "path": "." }))
+            .await
+            .unwrap();
+        assert_eq!(listed["error"]["error_type"], "PolicyViolation");
+    }
+}
@@ -127,37 +127,74 @@ pub mod apply_patch;
 pub mod ast_grep;
 pub mod ast_grep_tool;
 pub mod bash_tool;
+pub mod bootstrap;
 pub mod cache;
+pub mod call_graph;
+pub mod cancellation;
 pub mod command;
+pub mod config_edit;
+pub mod coverage;
 pub mod curl_tool;
+pub mod docs;
+pub mod edit_conflict;
 pub mod file_ops;
 pub mod file_search;
+pub mod git;
 pub mod grep_search;
+pub mod imports;
+pub mod mocks;
+pub mod path_display;
+pub mod path_grants;
 pub mod plan;
+pub mod project_commands;
 pub mod registry;
 pub mod search;
+pub mod semantic_search;
 pub mod simple_search;
+pub mod sql;
 pub mod srgn;
 pub mod traits;
 pub mod tree_sitter;
 pub mod types;
+pub mod vulnerabilities;
+pub mod workspace_env;
+pub mod write_quota;
 
 // Re-export main types and traits for backward compatibility
 pub use ast_grep_tool::AstGrepTool;
 pub use bash_tool::BashTool;
+pub use bootstrap::BootstrapEnvTool;
 pub use cache::FileCache;
+pub use call_graph::CallGraphTool;
+pub use cancellation::{ToolCancelled, ToolContext};
+pub use config_edit::ConfigEditTool;
+pub use coverage::CoverageReportTool;
 pub use curl_tool::CurlTool;
+pub use docs::FetchDocsTool;
+pub use edit_conflict::{EditConflict, FileSnapshots};
+pub use git::GitTool;
 pub use grep_search::GrepSearchManager;
+pub use imports::OrganizeImportsTool;
+pub use mocks::ToolMocks;
+pub use path_display::PathDisplayPolicy;
+pub use path_grants::{PathGrants, WritePermissionRequired};
 pub use plan::{
     PlanCompletionState, PlanManager, PlanStep, PlanSummary, PlanUpdateResult, StepStatus,
     TaskPlan, UpdatePlanArgs,
 };
+pub use project_commands::ProjectCommandsTool;
 pub use registry::{ToolRegistration, ToolRegistry};
+pub use semantic_search::SemanticSearchTool;
 pub use simple_search::SimpleSearchTool;
+pub use sql::SqlTool;
 pub use srgn::SrgnTool;
 pub use traits::{Tool, ToolExecutor};
 pub use types::*;
+pub use vulnerabilities::VulnerabilityScanTool;
+pub use workspace_env::WorkspaceEnv;
+pub use write_quota::{QuotaExceeded, WriteQuota};
 
 // Re-export function declarations for external use
 pub use registry::build_function_declarations;
 pub use registry::build_function_declarations_for_level;
+pub use registry::tool_capability;
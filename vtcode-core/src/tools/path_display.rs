@@ -0,0 +1,198 @@
+//! One form for the paths in tool results
+//!
+//! Tools build their results independently, so the same file used to come
+//! back as `/home/me/project/src/lib.rs`, `src/lib.rs` or `./src/lib.rs`
+//! depending on the tool. The registry passes every result through
+//! [`PathDisplayPolicy`], which rewrites the path fields of the result to the
+//! form chosen by `[tools] path_display`: relative to the workspace without a
+//! leading `./` (the default) or absolute. Paths outside the workspace stay
+//! absolute in either mode. Free text such as command output is left alone.
+
+use crate::config::PathDisplay;
+use crate::utils::workspace_path::normalize;
+use serde_json::Value;
+use std::path::{Component, Path, PathBuf};
+
+/// Keys whose string values are paths.
+pub const PATH_KEYS: &[&str] = &[
+    "path",
+    "file",
+    "file_path",
+    "filepath",
+    "from",
+    "old_path",
+    "new_path",
+    "source_path",
+    "target_path",
+    "working_dir",
+    "working_directory",
+    "cwd",
+    "directory",
+    "cache_path",
+];
+
+/// Keys whose string array values are paths.
+pub const PATH_LIST_KEYS: &[&str] = &[
+    "paths",
+    "files",
+    "modified_files",
+    "created_files",
+    "deleted_files",
+    "untracked",
+    "conflicted",
+];
+
+/// Rewrites the path fields of tool results to one form.
+#[derive(Debug, Clone)]
+pub struct PathDisplayPolicy {
+    mode: PathDisplay,
+    root: PathBuf,
+    /// The root with symlinks resolved, as tools that canonicalize see it
+    canonical_root: Option<PathBuf>,
+}
+
+impl PathDisplayPolicy {
+    pub fn new(workspace_root: &Path, mode: PathDisplay) -> Self {
+        let root = normalize(workspace_root);
+        let canonical_root = workspace_root
+            .canonicalize()
+            .ok()
+            .filter(|canonical| *canonical != root);
+        Self {
+            mode,
+            root,
+            canonical_root,
+        }
+    }
+
+    pub fn mode(&self) -> PathDisplay {
+        self.mode
+    }
+
+    /// `raw` in the configured form.
+    pub fn display(&self, raw: &str) -> String {
+        if raw.is_empty() || raw.contains("://") {
+            return raw.to_string();
+        }
+        let path = Path::new(raw);
+        let relative = if path.is_absolute() {
+            let absolute = normalize(path);
+            let inside = std::iter::once(&self.root)
+                .chain(self.canonical_root.as_ref())
+                .find_map(|root| absolute.strip_prefix(root).ok().map(Path::to_path_buf));
+            match inside {
+                Some(relative) => relative,
+                None => return absolute.to_string_lossy().into_owned(),
+            }
+        } else if path
+            .components()
+            .any(|component| component == Component::ParentDir)
+        {
+            // Relative paths that climb out are left as the tool wrote them.
+            return raw.to_string();
+        } else {
+            normalize(path)
+        };
+        match self.mode {
+            PathDisplay::Relative if relative.as_os_str().is_empty() => ".".to_string(),
+            PathDisplay::Relative => relative.to_string_lossy().into_owned(),
+            PathDisplay::Absolute => self.root.join(relative).to_string_lossy().into_owned(),
+        }
+    }
+
+    /// Rewrite every path field of `output` in place.
+    pub fn apply(&self, output: &mut Value) {
+        walk(output, &mut |value| {
+            if let Value::String(raw) = value {
+                let shown = self.display(raw);
+                if shown != *raw {
+                    *raw = shown;
+                }
+            }
+        });
+    }
+
+    /// Values of the path fields of `output` that are not in the configured
+    /// form.
+    pub fn violations(&self, output: &Value) -> Vec<String> {
+        let mut found = Vec::new();
+        let mut output = output.clone();
+        walk(&mut output, &mut |value| {
+            if let Value::String(raw) = value
+                && self.display(raw) != *raw
+            {
+                found.push(raw.clone());
+            }
+        });
+        found
+    }
+}
+
+/// Call `visit` on every path value under a path key.
+fn walk(value: &mut Value, visit: &mut impl FnMut(&mut Value)) {
+    match value {
+        Value::Object(fields) => {
+            for (key, field) in fields.iter_mut() {
+                if PATH_KEYS.contains(&key.as_str()) && field.is_string() {
+                    visit(field);
+                } else if PATH_LIST_KEYS.contains(&key.as_str())
+                    && let Value::Array(items) = field
+                {
+                    for item in items.iter_mut() {
+                        if item.is_string() {
+                            visit(item);
+                        } else {
+                            walk(item, visit);
+                        }
+                    }
+                } else {
+                    walk(field, visit);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                walk(item, visit);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn rewrites_path_fields_only() {
+        let relative = PathDisplayPolicy::new(Path::new("/work/space"), PathDisplay::Relative);
+        assert_eq!(relative.display("/work/space/src/lib.rs"), "src/lib.rs");
+        assert_eq!(relative.display("./src/./lib.rs"), "src/lib.rs");
+        assert_eq!(relative.display("/work/space"), ".");
+        assert_eq!(relative.display("/etc/hosts"), "/etc/hosts");
+        assert_eq!(relative.display("../sibling/a.rs"), "../sibling/a.rs");
+
+        let mut output = json!({
+            "path": "/work/space/./src/main.rs",
+            "files": ["./a.rs", { "path": "/work/space/b.rs", "additions": 1 }],
+            "stdout": "compiled /work/space/src/main.rs",
+            "matches": [{ "file": "./c.rs", "line": 3 }],
+        });
+        assert_eq!(relative.violations(&output).len(), 4);
+        relative.apply(&mut output);
+        assert!(relative.violations(&output).is_empty());
+        assert_eq!(output["path"], "src/main.rs");
+        assert_eq!(
+            output["files"],
+            json!(["a.rs", { "path": "b.rs", "additions": 1 }])
+        );
+        assert_eq!(output["matches"][0]["file"], "c.rs");
+        assert_eq!(output["stdout"], "compiled /work/space/src/main.rs");
+
+        let absolute = PathDisplayPolicy::new(Path::new("/work/space"), PathDisplay::Absolute);
+        absolute.apply(&mut output);
+        assert_eq!(output["path"], "/work/space/src/main.rs");
+        assert_eq!(output["files"][0], "/work/space/a.rs");
+    }
+}
@@ -0,0 +1,162 @@
+//! Write access to files outside the workspace
+//!
+//! `write_file` and `edit_file` only write below the workspace root. A task
+//! may still need to change a file elsewhere, such as `~/.config/<tool>/config`;
+//! instead of failing outright, a write to such a path is refused with a
+//! [`WritePermissionRequired`] error, which the registry reports as a
+//! structured `PermissionRequired` tool error. The chat loop then asks the
+//! user about that exact path and, once approved, adds a grant to
+//! [`PathGrants`] and runs the call again. Grants may expire; the chat loop
+//! keeps them in the workspace trust store.
+
+use crate::utils::dot_config::WriteGrantRecord;
+use crate::utils::workspace_path::{absolute_path, resolve_in_workspace};
+use anyhow::Result;
+use parking_lot::Mutex;
+use serde_json::{Value, json};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A write outside the workspace that needs the user's approval first.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "Permission required: {path} is outside the workspace. Nothing was written; the user is asked whether to allow writing to it."
+)]
+pub struct WritePermissionRequired {
+    /// Absolute path the write was aimed at
+    pub path: String,
+}
+
+impl WritePermissionRequired {
+    /// Details attached to the tool error.
+    pub fn to_json_value(&self) -> Value {
+        json!({ "path": self.path })
+    }
+
+    /// The request reported in a tool error, if the output is one that has
+    /// not been answered yet.
+    pub fn from_tool_output(output: &Value) -> Option<Self> {
+        let request = output.pointer("/error/permission_required")?;
+        if request.get("granted").is_some() {
+            return None;
+        }
+        Some(Self {
+            path: request.get("path")?.as_str()?.to_string(),
+        })
+    }
+}
+
+/// Paths outside the workspace the file tools may write; shared by all clones.
+#[derive(Debug, Clone, Default)]
+pub struct PathGrants {
+    grants: Arc<Mutex<Vec<WriteGrantRecord>>>,
+}
+
+impl PathGrants {
+    /// Replace the grants, e.g. with the ones stored for this workspace.
+    pub fn set(&self, grants: Vec<WriteGrantRecord>) {
+        *self.grants.lock() = grants;
+    }
+
+    pub fn grant(&self, grant: WriteGrantRecord) {
+        let mut grants = self.grants.lock();
+        grants.retain(|existing| existing.path != grant.path);
+        grants.push(grant);
+    }
+
+    /// Unexpired grants.
+    pub fn active(&self) -> Vec<WriteGrantRecord> {
+        let now = unix_now();
+        self.grants
+            .lock()
+            .iter()
+            .filter(|grant| !grant.is_expired(now))
+            .cloned()
+            .collect()
+    }
+
+    pub fn allows(&self, path: &Path) -> bool {
+        let now = unix_now();
+        self.grants
+            .lock()
+            .iter()
+            .any(|grant| !grant.is_expired(now) && path.starts_with(&grant.path))
+    }
+
+    /// Where a write to `raw` goes: inside the workspace, or outside it under
+    /// an active grant. Other paths are refused with
+    /// [`WritePermissionRequired`].
+    pub fn resolve_write(&self, workspace: &Path, raw: &str) -> Result<PathBuf> {
+        let target = absolute_path(workspace, raw)?;
+        if let Ok(inside) = resolve_in_workspace(workspace, &target.to_string_lossy()) {
+            return Ok(inside);
+        }
+        if self.allows(&target) {
+            return Ok(target);
+        }
+        Err(WritePermissionRequired {
+            path: target.to_string_lossy().into_owned(),
+        }
+        .into())
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_outside_the_workspace_need_an_active_grant() {
+        let grants = PathGrants::default();
+        let workspace = Path::new("/work/space");
+        assert_eq!(
+            grants.resolve_write(workspace, "src/../lib.rs").unwrap(),
+            workspace.join("lib.rs")
+        );
+
+        let err = grants
+            .resolve_write(workspace, "../other/config.toml")
+            .unwrap_err();
+        let request = err.downcast_ref::<WritePermissionRequired>().unwrap();
+        assert_eq!(request.path, "/work/other/config.toml");
+        let mut output = json!({ "error": { "permission_required": request.to_json_value() } });
+        assert_eq!(
+            WritePermissionRequired::from_tool_output(&output).as_ref(),
+            Some(request)
+        );
+        output["error"]["permission_required"]["granted"] = json!(false);
+        assert!(WritePermissionRequired::from_tool_output(&output).is_none());
+
+        grants.grant(WriteGrantRecord {
+            path: "/work/other".to_string(),
+            granted_at: 0,
+            expires_at: Some(1),
+        });
+        assert!(
+            grants
+                .resolve_write(workspace, "/work/other/config.toml")
+                .is_err()
+        );
+        grants.grant(WriteGrantRecord {
+            path: "/work/other".to_string(),
+            granted_at: 0,
+            expires_at: None,
+        });
+        assert_eq!(
+            grants
+                .resolve_write(workspace, "/work/other/config.toml")
+                .unwrap(),
+            PathBuf::from("/work/other/config.toml")
+        );
+        assert!(grants.resolve_write(workspace, "/work/otherwise").is_err());
+        assert_eq!(grants.active().len(), 1);
+    }
+}
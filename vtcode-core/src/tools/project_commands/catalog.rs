@@ -0,0 +1,328 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use serde_yaml::Value as YamlValue;
+use std::fs;
+use std::path::Path;
+
+/// What a project command is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandKind {
+    Build,
+    Test,
+    Lint,
+    Fmt,
+}
+
+impl CommandKind {
+    pub const ALL: [CommandKind; 4] = [Self::Build, Self::Test, Self::Lint, Self::Fmt];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Build => "build",
+            Self::Test => "test",
+            Self::Lint => "lint",
+            Self::Fmt => "fmt",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.as_str().eq_ignore_ascii_case(value.trim()))
+    }
+
+    /// Kind of a Makefile target, just recipe or package.json script.
+    fn from_task_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "build" | "compile" => Some(Self::Build),
+            "test" | "tests" => Some(Self::Test),
+            "lint" | "clippy" => Some(Self::Lint),
+            "fmt" | "format" => Some(Self::Fmt),
+            _ => None,
+        }
+    }
+
+    /// Kind of a shell command from a CI step, e.g. `cargo clippy --workspace`.
+    fn from_command(command: &str) -> Option<Self> {
+        let words: Vec<&str> = command
+            .split_whitespace()
+            .skip_while(|word| is_env_assignment(word))
+            .filter(|word| !word.starts_with('+'))
+            .collect();
+        let program = words.first()?.rsplit('/').next()?;
+        let first = words.get(1).copied().unwrap_or_default();
+        let second = words.get(2).copied().unwrap_or_default();
+        match (program, first) {
+            ("cargo", "test" | "nextest") => Some(Self::Test),
+            ("cargo", "build" | "check") => Some(Self::Build),
+            ("cargo", "clippy") => Some(Self::Lint),
+            ("cargo", "fmt") => Some(Self::Fmt),
+            ("go", "test") => Some(Self::Test),
+            ("go", "build") => Some(Self::Build),
+            ("go", "vet") => Some(Self::Lint),
+            ("npm" | "pnpm" | "yarn" | "bun", "run") => Self::from_task_name(second),
+            ("npm" | "pnpm" | "yarn" | "bun", "test" | "t") => Some(Self::Test),
+            ("yarn" | "pnpm" | "bun", name) => Self::from_task_name(name),
+            ("make" | "just", target) => Self::from_task_name(target),
+            ("python" | "python3", "-m") if second == "pytest" => Some(Self::Test),
+            ("pytest" | "jest" | "vitest" | "tox" | "nox", _) => Some(Self::Test),
+            ("tsc", _) => Some(Self::Build),
+            ("ruff", "format") => Some(Self::Fmt),
+            ("ruff" | "eslint" | "flake8" | "pylint" | "mypy" | "golangci-lint", _) => {
+                Some(Self::Lint)
+            }
+            ("prettier" | "black" | "gofmt" | "rustfmt", _) => Some(Self::Fmt),
+            _ => None,
+        }
+    }
+}
+
+fn is_env_assignment(word: &str) -> bool {
+    word.split_once('=').is_some_and(|(name, _)| {
+        !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
+}
+
+/// A command the project itself uses, with where it was found.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProjectCommand {
+    pub kind: CommandKind,
+    pub command: String,
+    /// e.g. `Makefile` or `.github/workflows/ci.yml (job test)`
+    pub source: String,
+}
+
+/// Build, test, lint and fmt commands declared by the workspace, in order of
+/// preference: task runners (justfile, Makefile), package.json scripts, then
+/// the run steps of GitHub Actions workflows.
+#[derive(Debug, Clone, Default)]
+pub struct CommandCatalog {
+    pub commands: Vec<ProjectCommand>,
+}
+
+impl CommandCatalog {
+    pub fn discover(workspace: &Path) -> Self {
+        let mut catalog = Self::default();
+        for name in ["justfile", "Justfile", ".justfile"] {
+            if let Ok(text) = fs::read_to_string(workspace.join(name)) {
+                for recipe in just_recipes(&text) {
+                    catalog.add_task("just", &recipe, name);
+                }
+                break;
+            }
+        }
+        for name in ["GNUmakefile", "Makefile", "makefile"] {
+            if let Ok(text) = fs::read_to_string(workspace.join(name)) {
+                for target in make_targets(&text) {
+                    catalog.add_task("make", &target, name);
+                }
+                break;
+            }
+        }
+        if let Ok(text) = fs::read_to_string(workspace.join("package.json"))
+            && let Ok(package) = serde_json::from_str::<JsonValue>(&text)
+        {
+            catalog.add_package_scripts(workspace, &package);
+        }
+        let workflows = workspace.join(".github").join("workflows");
+        if let Ok(entries) = fs::read_dir(&workflows) {
+            let mut files: Vec<_> = entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.extension()
+                        .is_some_and(|ext| ext == "yml" || ext == "yaml")
+                })
+                .collect();
+            files.sort();
+            for path in files {
+                if let Ok(text) = fs::read_to_string(&path) {
+                    let file = path.file_name().unwrap_or_default().to_string_lossy();
+                    catalog.add_workflow(&format!(".github/workflows/{}", file), &text);
+                }
+            }
+        }
+        catalog
+    }
+
+    /// The preferred command of `kind`.
+    pub fn canonical(&self, kind: CommandKind) -> Option<&ProjectCommand> {
+        self.commands.iter().find(|command| command.kind == kind)
+    }
+
+    /// Command that checks a change still builds, falling back to the tests.
+    pub fn validation_command(&self) -> Option<&ProjectCommand> {
+        self.canonical(CommandKind::Build)
+            .or_else(|| self.canonical(CommandKind::Test))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    fn add(&mut self, kind: CommandKind, command: String, source: String) {
+        if !self
+            .commands
+            .iter()
+            .any(|existing| existing.kind == kind && existing.command == command)
+        {
+            self.commands.push(ProjectCommand {
+                kind,
+                command,
+                source,
+            });
+        }
+    }
+
+    fn add_task(&mut self, runner: &str, name: &str, source: &str) {
+        if let Some(kind) = CommandKind::from_task_name(name) {
+            self.add(kind, format!("{} {}", runner, name), source.to_string());
+        }
+    }
+
+    fn add_package_scripts(&mut self, workspace: &Path, package: &JsonValue) {
+        let Some(scripts) = package.get("scripts").and_then(JsonValue::as_object) else {
+            return;
+        };
+        let manager = package_manager(workspace, package);
+        for name in scripts.keys() {
+            let Some(kind) = CommandKind::from_task_name(name) else {
+                continue;
+            };
+            let command = if name == "test" {
+                format!("{} test", manager)
+            } else {
+                format!("{} run {}", manager, name)
+            };
+            self.add(kind, command, "package.json".to_string());
+        }
+    }
+
+    fn add_workflow(&mut self, source: &str, text: &str) {
+        let Ok(workflow) = serde_yaml::from_str::<YamlValue>(text) else {
+            return;
+        };
+        let Some(jobs) = workflow.get("jobs").and_then(YamlValue::as_mapping) else {
+            return;
+        };
+        for (job_id, job) in jobs {
+            let job_id = job_id.as_str().unwrap_or_default();
+            let Some(steps) = job.get("steps").and_then(YamlValue::as_sequence) else {
+                continue;
+            };
+            for step in steps {
+                let Some(run) = step.get("run").and_then(YamlValue::as_str) else {
+                    continue;
+                };
+                let directory = step.get("working-directory").and_then(YamlValue::as_str);
+                for command in shell_lines(run) {
+                    // Matrix and secret expressions cannot be run locally.
+                    if command.contains("${{") {
+                        continue;
+                    }
+                    let Some(kind) = CommandKind::from_command(&command) else {
+                        continue;
+                    };
+                    let command = match directory {
+                        Some(directory) => format!("cd {} && {}", directory, command),
+                        None => command,
+                    };
+                    self.add(kind, command, format!("{} (job {})", source, job_id));
+                }
+            }
+        }
+    }
+}
+
+/// Commands of a `run:` block, joining `\` continuations and splitting `&&`.
+fn shell_lines(script: &str) -> Vec<String> {
+    let mut joined = Vec::new();
+    let mut current = String::new();
+    for line in script.lines() {
+        let line = line.trim();
+        if let Some(start) = line.strip_suffix('\\') {
+            current.push_str(start.trim_end());
+            current.push(' ');
+            continue;
+        }
+        current.push_str(line);
+        joined.push(std::mem::take(&mut current));
+    }
+    joined.push(current);
+    joined
+        .iter()
+        .flat_map(|line| line.split("&&"))
+        .map(|command| command.trim().to_string())
+        .filter(|command| !command.is_empty() && !command.starts_with('#'))
+        .collect()
+}
+
+fn package_manager(workspace: &Path, package: &JsonValue) -> &'static str {
+    let declared = package
+        .get("packageManager")
+        .and_then(JsonValue::as_str)
+        .unwrap_or_default();
+    for manager in ["pnpm", "yarn", "bun"] {
+        if declared.starts_with(manager) {
+            return manager;
+        }
+    }
+    if workspace.join("pnpm-lock.yaml").exists() {
+        "pnpm"
+    } else if workspace.join("yarn.lock").exists() {
+        "yarn"
+    } else if workspace.join("bun.lockb").exists() || workspace.join("bun.lock").exists() {
+        "bun"
+    } else {
+        "npm"
+    }
+}
+
+fn make_targets(text: &str) -> Vec<String> {
+    let mut targets = Vec::new();
+    for line in text.lines() {
+        if line.starts_with(['\t', ' ', '#', '.']) {
+            continue;
+        }
+        let Some((names, rest)) = line.split_once(':') else {
+            continue;
+        };
+        // `NAME := value` and `NAME ::= value` are assignments, not rules.
+        if rest.starts_with('=') || rest.starts_with(":=") || names.contains('=') {
+            continue;
+        }
+        for name in names.split_whitespace() {
+            if !name.contains(['$', '%']) && !targets.iter().any(|target| target == name) {
+                targets.push(name.to_string());
+            }
+        }
+    }
+    targets
+}
+
+fn just_recipes(text: &str) -> Vec<String> {
+    let mut recipes = Vec::new();
+    for line in text.lines() {
+        if line.starts_with([' ', '\t', '#', '[']) || line.contains(":=") {
+            continue;
+        }
+        let Some((head, _)) = line.split_once(':') else {
+            continue;
+        };
+        let name = head
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .trim_start_matches('@');
+        if !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            && !matches!(name, "set" | "alias" | "export" | "import" | "mod")
+        {
+            recipes.push(name.to_string());
+        }
+    }
+    recipes
+}
@@ -0,0 +1,190 @@
+//! The project's own build, test, lint and fmt commands
+//!
+//! [`ProjectCommandsTool`] reads the justfile, Makefile, package.json scripts
+//! and GitHub Actions workflows of the workspace and returns the commands the
+//! project already uses, with the canonical one for each kind, so the agent
+//! runs `make test` or the exact `cargo test` flags CI uses instead of guessing.
+//! `vtcode batch` validates batches with the same catalog when `--validate` is
+//! not given.
+
+mod catalog;
+
+pub use catalog::{CommandCatalog, CommandKind, ProjectCommand};
+
+use crate::config::constants::tools;
+use crate::tools::traits::Tool;
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+use std::path::PathBuf;
+
+#[derive(Debug, Default, Deserialize)]
+struct ProjectCommandsInput {
+    /// Only list commands of this kind
+    #[serde(default)]
+    kind: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct ProjectCommandsTool {
+    workspace_root: PathBuf,
+}
+
+impl ProjectCommandsTool {
+    pub fn new(workspace_root: PathBuf) -> Self {
+        Self { workspace_root }
+    }
+
+    fn list(&self, input: ProjectCommandsInput) -> Result<Value> {
+        let kind = input
+            .kind
+            .as_deref()
+            .map(|value| {
+                CommandKind::parse(value).ok_or_else(|| {
+                    anyhow!(
+                        "Error: Unknown kind '{}'. Use one of: build, test, lint, fmt",
+                        value
+                    )
+                })
+            })
+            .transpose()?;
+        let catalog = CommandCatalog::discover(&self.workspace_root);
+        let commands: Vec<&ProjectCommand> = catalog
+            .commands
+            .iter()
+            .filter(|command| kind.is_none_or(|kind| command.kind == kind))
+            .collect();
+        let mut canonical = Map::new();
+        for candidate in CommandKind::ALL {
+            if kind.is_none_or(|kind| kind == candidate)
+                && let Some(command) = catalog.canonical(candidate)
+            {
+                canonical.insert(candidate.as_str().to_string(), json!(command.command));
+            }
+        }
+        let mut result = json!({
+            "success": true,
+            "canonical": canonical,
+            "commands": commands,
+        });
+        if commands.is_empty() {
+            result["message"] = json!(
+                "No matching commands in justfile, Makefile, package.json scripts or .github/workflows. Fall back to the language's standard commands."
+            );
+        }
+        Ok(result)
+    }
+}
+
+#[async_trait]
+impl Tool for ProjectCommandsTool {
+    async fn execute(&self, args: Value) -> Result<Value> {
+        let input: ProjectCommandsInput = if args.is_null() {
+            ProjectCommandsInput::default()
+        } else {
+            serde_json::from_value(args).context(
+                "Error: Invalid 'project_commands' arguments. Optional: { kind: \"build\" | \"test\" | \"lint\" | \"fmt\" }",
+            )?
+        };
+        self.list(input)
+    }
+
+    fn name(&self) -> &'static str {
+        tools::PROJECT_COMMANDS
+    }
+
+    fn description(&self) -> &'static str {
+        "List the build, test, lint and fmt commands the project already uses"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const WORKFLOW: &str = r#"
+name: ci
+on: [push]
+jobs:
+  test:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v4
+      - run: cargo fmt --all -- --check
+      - name: Clippy
+        run: |
+          cargo clippy --workspace \
+            --all-targets -- -D warnings
+      - run: cargo test --workspace --locked
+      - run: cargo test --target ${{ matrix.target }}
+      - run: npm test
+        working-directory: web
+"#;
+
+    #[test]
+    fn prefers_task_runners_and_reads_ci_flags() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("Makefile"),
+            "CARGO := cargo\n.PHONY: test lint\n\ntest: build\n\t$(CARGO) test\nbuild:\n\tcargo build\n",
+        )
+        .unwrap();
+        fs::create_dir_all(dir.path().join(".github/workflows")).unwrap();
+        fs::write(dir.path().join(".github/workflows/ci.yml"), WORKFLOW).unwrap();
+
+        let catalog = CommandCatalog::discover(dir.path());
+        let commands: Vec<(CommandKind, &str)> = catalog
+            .commands
+            .iter()
+            .map(|command| (command.kind, command.command.as_str()))
+            .collect();
+        assert_eq!(
+            commands,
+            vec![
+                (CommandKind::Test, "make test"),
+                (CommandKind::Build, "make build"),
+                (CommandKind::Fmt, "cargo fmt --all -- --check"),
+                (
+                    CommandKind::Lint,
+                    "cargo clippy --workspace --all-targets -- -D warnings"
+                ),
+                (CommandKind::Test, "cargo test --workspace --locked"),
+                (CommandKind::Test, "cd web && npm test"),
+            ]
+        );
+        assert_eq!(
+            catalog.canonical(CommandKind::Lint).unwrap().source,
+            ".github/workflows/ci.yml (job test)"
+        );
+        assert_eq!(catalog.validation_command().unwrap().command, "make build");
+    }
+
+    #[tokio::test]
+    async fn lists_package_scripts_with_the_lockfile_manager() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{"scripts": {"build": "tsc", "test": "vitest", "lint": "eslint .", "dev": "vite"}}"#,
+        )
+        .unwrap();
+        fs::write(dir.path().join("pnpm-lock.yaml"), "").unwrap();
+        fs::write(
+            dir.path().join("justfile"),
+            "set shell := [\"bash\", \"-c\"]\n\n# Run the tests\ntest *args:\n    pnpm test {{args}}\n",
+        )
+        .unwrap();
+
+        let tool = ProjectCommandsTool::new(dir.path().to_path_buf());
+        let result = tool.execute(json!({ "kind": "test" })).await.unwrap();
+        assert_eq!(result["canonical"], json!({ "test": "just test" }));
+        assert_eq!(result["commands"][1]["command"], "pnpm test");
+        assert_eq!(result["commands"].as_array().unwrap().len(), 2);
+
+        let all = tool.execute(Value::Null).await.unwrap();
+        assert_eq!(all["canonical"]["build"], "pnpm run build");
+        assert!(tool.execute(json!({ "kind": "deploy" })).await.is_err());
+    }
+}
@@ -1,9 +1,8 @@
-use anyhow::{Context, Result, anyhow};
-use serde_json::{Value, json};
-use std::path::PathBuf;
-
 use super::ToolRegistry;
 use super::utils;
+use crate::utils::workspace_path::resolve_in_workspace;
+use anyhow::{Context, Result, anyhow};
+use serde_json::{Value, json};
 
 impl ToolRegistry {
     pub(super) async fn execute_ast_grep(&self, args: Value) -> Result<Value> {
@@ -11,6 +10,7 @@ impl ToolRegistry {
             .ast_grep_engine
             .as_ref()
             .ok_or_else(|| anyhow!("AST-grep engine not available"))?;
+        let cancel = &self.tool_context().cancel;
 
         let operation = args
             .get("operation")
@@ -42,7 +42,7 @@ impl ToolRegistry {
                     .map(|v| v as usize);
 
                 engine
-                    .search(pattern, &path, language, context_lines, max_results)
+                    .search(pattern, &path, language, context_lines, max_results, cancel)
                     .await
             }
             "transform" => {
@@ -81,6 +81,7 @@ impl ToolRegistry {
                         language,
                         preview_only,
                         update_all,
+                        cancel,
                     )
                     .await
             }
@@ -95,7 +96,9 @@ impl ToolRegistry {
                 let language = args.get("language").and_then(|v| v.as_str());
                 let severity_filter = args.get("severity_filter").and_then(|v| v.as_str());
 
-                engine.lint(&path, language, severity_filter, None).await
+                engine
+                    .lint(&path, language, severity_filter, None, cancel)
+                    .await
             }
             "refactor" => {
                 let path = args
@@ -111,7 +114,9 @@ impl ToolRegistry {
                     .and_then(|v| v.as_str())
                     .context("'refactor_type' is required")?;
 
-                engine.refactor(&path, language, refactor_type).await
+                engine
+                    .refactor(&path, language, refactor_type, cancel)
+                    .await
             }
             "custom" => {
                 let pattern = args
@@ -155,6 +160,7 @@ impl ToolRegistry {
                         max_results,
                         interactive,
                         update_all,
+                        cancel,
                     )
                     .await
             }
@@ -195,20 +201,7 @@ impl ToolRegistry {
     }
 
     pub(super) fn normalize_path(&self, path: &str) -> Result<String> {
-        let path_buf = PathBuf::from(path);
-
-        if path_buf.is_absolute() {
-            if !path_buf.starts_with(&self.workspace_root) {
-                return Err(anyhow!(
-                    "Path {} is outside workspace root {}",
-                    path,
-                    self.workspace_root.display()
-                ));
-            }
-            Ok(path.to_string())
-        } else {
-            let resolved = self.workspace_root.join(path);
-            Ok(resolved.to_string_lossy().to_string())
-        }
+        let resolved = resolve_in_workspace(&self.workspace_root, path)?;
+        Ok(resolved.to_string_lossy().to_string())
     }
 }
@@ -23,7 +23,7 @@ pub(super) fn builtin_tool_registrations() -> Vec<ToolRegistration> {
     vec![
         ToolRegistration::new(
             tools::GREP_SEARCH,
-            CapabilityLevel::CodeSearch,
+            CapabilityLevel::FileListing,
             false,
             ToolRegistry::grep_search_executor,
         ),
@@ -71,19 +71,19 @@ pub(super) fn builtin_tool_registrations() -> Vec<ToolRegistration> {
         ),
         ToolRegistration::new(
             tools::AST_GREP_SEARCH,
-            CapabilityLevel::CodeSearch,
+            CapabilityLevel::FileListing,
             false,
             ToolRegistry::ast_grep_executor,
         ),
         ToolRegistration::new(
             tools::SIMPLE_SEARCH,
-            CapabilityLevel::CodeSearch,
+            CapabilityLevel::FileListing,
             false,
             ToolRegistry::simple_search_executor,
         ),
         ToolRegistration::new(
             tools::BASH,
-            CapabilityLevel::CodeSearch,
+            CapabilityLevel::Bash,
             false,
             ToolRegistry::bash_executor,
         )
@@ -97,9 +97,111 @@ pub(super) fn builtin_tool_registrations() -> Vec<ToolRegistration> {
         .with_llm_visibility(false),
         ToolRegistration::new(
             tools::SRGN,
-            CapabilityLevel::CodeSearch,
+            CapabilityLevel::Editing,
             false,
             ToolRegistry::srgn_executor,
         ),
+        ToolRegistration::new(
+            tools::ORGANIZE_IMPORTS,
+            CapabilityLevel::Editing,
+            false,
+            ToolRegistry::organize_imports_executor,
+        ),
+        ToolRegistration::new(
+            tools::CONFIG_EDIT,
+            CapabilityLevel::Editing,
+            false,
+            ToolRegistry::config_edit_executor,
+        ),
+        ToolRegistration::new(
+            tools::COVERAGE_REPORT,
+            CapabilityLevel::Bash,
+            false,
+            ToolRegistry::coverage_report_executor,
+        ),
+        ToolRegistration::new(
+            tools::BOOTSTRAP_ENV,
+            CapabilityLevel::Bash,
+            false,
+            ToolRegistry::bootstrap_env_executor,
+        ),
+        ToolRegistration::new(
+            tools::PROJECT_COMMANDS,
+            CapabilityLevel::FileReading,
+            false,
+            ToolRegistry::project_commands_executor,
+        ),
+        ToolRegistration::new(
+            tools::CALL_GRAPH,
+            CapabilityLevel::FileReading,
+            false,
+            ToolRegistry::call_graph_executor,
+        ),
+        ToolRegistration::new(
+            tools::FETCH_DOCS,
+            CapabilityLevel::FileReading,
+            false,
+            ToolRegistry::fetch_docs_executor,
+        ),
+        ToolRegistration::new(
+            tools::GIT_STATUS,
+            CapabilityLevel::FileReading,
+            false,
+            ToolRegistry::git_status_executor,
+        ),
+        ToolRegistration::new(
+            tools::GIT_DIFF,
+            CapabilityLevel::FileReading,
+            false,
+            ToolRegistry::git_diff_executor,
+        ),
+        ToolRegistration::new(
+            tools::GIT_LOG,
+            CapabilityLevel::FileReading,
+            false,
+            ToolRegistry::git_log_executor,
+        ),
+        ToolRegistration::new(
+            tools::GIT_COMMIT,
+            CapabilityLevel::Editing,
+            false,
+            ToolRegistry::git_commit_executor,
+        ),
+        ToolRegistration::new(
+            tools::GIT_BRANCH,
+            CapabilityLevel::Editing,
+            false,
+            ToolRegistry::git_branch_executor,
+        ),
+        ToolRegistration::new(
+            tools::SQL_SCHEMA,
+            CapabilityLevel::FileListing,
+            false,
+            ToolRegistry::sql_schema_executor,
+        ),
+        ToolRegistration::new(
+            tools::SQL_QUERY,
+            CapabilityLevel::FileListing,
+            false,
+            ToolRegistry::sql_query_executor,
+        ),
+        ToolRegistration::new(
+            tools::SQL_EXECUTE,
+            CapabilityLevel::Editing,
+            false,
+            ToolRegistry::sql_execute_executor,
+        ),
+        ToolRegistration::new(
+            tools::VULNERABILITY_SCAN,
+            CapabilityLevel::Bash,
+            false,
+            ToolRegistry::vulnerability_scan_executor,
+        ),
+        ToolRegistration::new(
+            tools::SEMANTIC_SEARCH,
+            CapabilityLevel::FileListing,
+            false,
+            ToolRegistry::semantic_search_executor,
+        ),
     ]
 }
@@ -1,6 +1,9 @@
+use parking_lot::Mutex;
 use serde_json::json;
+use std::sync::Arc;
 
 use crate::tools::cache::FILE_CACHE;
+use crate::tools::imports::SymbolIndex;
 
 use super::ToolRegistry;
 
@@ -21,4 +24,23 @@ impl ToolRegistry {
     pub async fn clear_cache(&self) {
         FILE_CACHE.clear().await;
     }
+
+    /// The workspace symbol index shared by organize_imports and call_graph.
+    pub fn symbol_index(&self) -> Arc<Mutex<SymbolIndex>> {
+        self.imports_tool.symbol_index()
+    }
+
+    /// Drop cached file contents, syntax trees and code indexes while the
+    /// session is idle. Everything is rebuilt on demand; returns the number of
+    /// cached entries that were released.
+    pub async fn release_idle_resources(&self) -> usize {
+        let released = FILE_CACHE.stats().await.entries
+            + self.tree_cache.len()
+            + self.imports_tool.symbol_index().lock().len();
+        FILE_CACHE.clear().await;
+        self.tree_cache.clear();
+        self.imports_tool.symbol_index().lock().clear();
+        self.call_graph_tool.clear_index();
+        released
+    }
 }
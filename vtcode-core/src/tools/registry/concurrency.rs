@@ -0,0 +1,237 @@
+//! Concurrency limits for tool executions (`[tools.concurrency]`)
+//!
+//! Every execution takes a slot from the global semaphore, plus one from the
+//! tool's own cap and from each configured command prefix it matches. Slots are
+//! taken from the most specific limit to the global one, so a call waiting on
+//! `cargo build` does not hold a global slot meanwhile. Clones of the registry
+//! share the semaphores.
+
+use crate::config::ConcurrencyConfig;
+use crate::config::constants::tools;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+#[derive(Clone)]
+struct Gate {
+    label: String,
+    semaphore: Arc<Semaphore>,
+    waiting: Arc<AtomicUsize>,
+}
+
+impl Gate {
+    fn new(label: String, limit: usize) -> Self {
+        let limit = limit.max(1);
+        Self {
+            label: format!("{} (max {})", label, limit),
+            semaphore: Arc::new(Semaphore::new(limit)),
+            waiting: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Take a slot, returning the queue position if none was free.
+    async fn enter(&self) -> (OwnedSemaphorePermit, Option<usize>) {
+        if let Ok(permit) = self.semaphore.clone().try_acquire_owned() {
+            return (permit, None);
+        }
+        let position = self.waiting.fetch_add(1, Ordering::SeqCst) + 1;
+        let permit = self
+            .semaphore
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("tool semaphores are never closed");
+        self.waiting.fetch_sub(1, Ordering::SeqCst);
+        (permit, Some(position))
+    }
+}
+
+/// Where a tool call waited before it could run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct QueueReport {
+    pub(crate) limit: String,
+    pub(crate) position: usize,
+    pub(crate) waited_ms: u64,
+}
+
+impl QueueReport {
+    pub(crate) fn to_json(&self) -> Value {
+        json!({
+            "limit": self.limit,
+            "position": self.position,
+            "waited_ms": self.waited_ms,
+        })
+    }
+}
+
+/// Slots held for the duration of one tool execution.
+pub(crate) struct ExecutionSlot {
+    permits: Vec<OwnedSemaphorePermit>,
+    queued: Option<QueueReport>,
+}
+
+impl ExecutionSlot {
+    /// Free the slots, returning where the call waited if it had to.
+    pub(crate) fn release(self) -> Option<QueueReport> {
+        drop(self.permits);
+        self.queued
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct ToolConcurrency {
+    global: Gate,
+    tools: HashMap<String, Gate>,
+    commands: Vec<(Vec<String>, Gate)>,
+    max_parallel_reads: usize,
+}
+
+impl ToolConcurrency {
+    pub(crate) fn new(config: &ConcurrencyConfig) -> Self {
+        let tools = config
+            .tools
+            .iter()
+            .map(|(name, limit)| (name.clone(), Gate::new(name.clone(), *limit)))
+            .collect();
+        let commands = config
+            .commands
+            .iter()
+            .filter_map(|(prefix, limit)| {
+                let words: Vec<String> = prefix.split_whitespace().map(str::to_string).collect();
+                (!words.is_empty()).then(|| (words, Gate::new(format!("`{}`", prefix), *limit)))
+            })
+            .collect();
+        Self {
+            global: Gate::new("all tools".to_string(), config.max_parallel),
+            tools,
+            commands,
+            max_parallel_reads: config.max_parallel_reads.max(1),
+        }
+    }
+
+    /// How many read-only calls of a batch may run at once.
+    pub(crate) fn max_parallel_reads(&self) -> usize {
+        self.max_parallel_reads
+    }
+
+    /// Wait until `tool` may run with `args` under every applicable limit.
+    pub(crate) async fn acquire(&self, tool: &str, args: &Value) -> ExecutionSlot {
+        let started = Instant::now();
+        let mut gates: Vec<&Gate> = Vec::new();
+        gates.extend(self.tools.get(tool));
+        if matches!(tool, tools::RUN_TERMINAL_CMD | tools::BASH) {
+            let words = command_words(args);
+            gates.extend(
+                self.commands
+                    .iter()
+                    .filter(|(prefix, _)| words.starts_with(prefix))
+                    .map(|(_, gate)| gate),
+            );
+        }
+        gates.push(&self.global);
+
+        let mut permits = Vec::with_capacity(gates.len());
+        let mut queued: Option<(String, usize)> = None;
+        for gate in gates {
+            let (permit, position) = gate.enter().await;
+            permits.push(permit);
+            if let (None, Some(position)) = (&queued, position) {
+                queued = Some((gate.label.clone(), position));
+            }
+        }
+
+        ExecutionSlot {
+            permits,
+            queued: queued.map(|(limit, position)| QueueReport {
+                limit,
+                position,
+                waited_ms: started.elapsed().as_millis() as u64,
+            }),
+        }
+    }
+}
+
+/// Command words of a terminal tool call, e.g. `["cargo", "build", "--release"]`.
+pub(crate) fn command_words(args: &Value) -> Vec<String> {
+    let mut words = Vec::new();
+    for key in ["command", "args"] {
+        match args.get(key) {
+            Some(Value::String(command)) => {
+                words.extend(command.split_whitespace().map(str::to_string));
+            }
+            Some(Value::Array(items)) => {
+                for item in items.iter().filter_map(Value::as_str) {
+                    words.extend(item.split_whitespace().map(str::to_string));
+                }
+            }
+            _ => {}
+        }
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indexmap::IndexMap;
+    use std::time::Duration;
+
+    fn limits(max_parallel: usize, commands: &[(&str, usize)]) -> ToolConcurrency {
+        ToolConcurrency::new(&ConcurrencyConfig {
+            max_parallel,
+            max_parallel_reads: 1,
+            tools: IndexMap::new(),
+            commands: commands
+                .iter()
+                .map(|(prefix, limit)| (prefix.to_string(), *limit))
+                .collect(),
+        })
+    }
+
+    #[tokio::test]
+    async fn queues_commands_over_their_cap() {
+        let limits = limits(4, &[("cargo build", 1)]);
+        let build = json!({ "command": ["cargo", "build", "--release"] });
+
+        let first = limits.acquire(tools::RUN_TERMINAL_CMD, &build).await;
+        assert!(first.queued.is_none());
+
+        let other = limits
+            .acquire(tools::RUN_TERMINAL_CMD, &json!({ "command": "cargo test" }))
+            .await;
+        assert!(other.release().is_none());
+
+        let waiting = {
+            let limits = limits.clone();
+            let build = build.clone();
+            tokio::spawn(async move { limits.acquire(tools::RUN_TERMINAL_CMD, &build).await })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiting.is_finished());
+
+        assert!(first.release().is_none());
+        let report = waiting.await.expect("join").release().expect("queued");
+        assert_eq!(report.limit, "`cargo build` (max 1)");
+        assert_eq!(report.position, 1);
+    }
+
+    #[tokio::test]
+    async fn global_limit_applies_to_every_tool() {
+        let limits = limits(1, &[]);
+        let held = limits.acquire(tools::READ_FILE, &json!({})).await;
+
+        let waiting = {
+            let limits = limits.clone();
+            tokio::spawn(async move { limits.acquire(tools::GREP_SEARCH, &json!({})).await })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiting.is_finished());
+
+        assert!(held.release().is_none());
+        let report = waiting.await.expect("join").release().expect("queued");
+        assert_eq!(report.limit, "all tools (max 1)");
+    }
+}
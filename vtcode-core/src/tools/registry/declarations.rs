@@ -21,6 +21,7 @@ pub fn build_function_declarations() -> Vec<FunctionDeclaration> {
                     "mode": {"type": "string", "description": "Search mode: 'exact' | 'fuzzy' | 'multi' | 'similarity'", "default": "exact"},
                     "max_results": {"type": "integer", "description": "Max results (token efficiency). Default: 100", "default": 100},
                     "case_sensitive": {"type": "boolean", "description": "Case sensitive search. Default: true", "default": true},
+                    "include_generated": {"type": "boolean", "description": "Also search generated/vendored paths excluded by [index.exclude] (target/, node_modules/, *.pb.go, ...)", "default": false},
                     // Multi-pattern search parameters
                     "patterns": {"type": "array", "items": {"type": "string"}, "description": "For mode='multi'. Example: ['fn \\w+','use \\w+']"},
                     "logic": {"type": "string", "description": "For mode='multi': 'AND' or 'OR'", "default": "AND"},
@@ -49,6 +50,7 @@ pub fn build_function_declarations() -> Vec<FunctionDeclaration> {
                     "per_page": {"type": "integer", "description": "Items per page. Default: 50", "default": 50},
                     "response_format": {"type": "string", "description": "'concise' (default) omits low-signal fields; 'detailed' includes them", "default": "concise"},
                     "include_hidden": {"type": "boolean", "description": "Include hidden files", "default": false},
+                    "include_generated": {"type": "boolean", "description": "Also list generated/vendored paths excluded by [index.exclude]", "default": false},
                     "name_pattern": {"type": "string", "description": "Optional pattern for 'recursive'/'find_name' modes. Use '*' or omit for all files. Example: '*.rs'", "default": "*"},
                     "content_pattern": {"type": "string", "description": "For 'find_content' mode. Example: 'fn main'"},
                     "file_extensions": {"type": "array", "items": {"type": "string"}, "description": "Filter by file extensions"},
@@ -62,14 +64,16 @@ pub fn build_function_declarations() -> Vec<FunctionDeclaration> {
         // File reading tool
         FunctionDeclaration {
             name: tools::READ_FILE.to_string(),
-            description: "Reads the contents of a specific file from the workspace with intelligent chunking for large files. This tool automatically handles large files by reading the first and last portions when files exceed size thresholds, ensuring efficient token usage while preserving important content. For files larger than 2,000 lines, it reads the first 800 and last 800 lines with a truncation indicator. Use chunk_lines or max_lines parameters to customize the threshold. The tool provides structured logging of chunking operations for debugging.".to_string(),
+            description: "Reads the contents of a specific file from the workspace with intelligent chunking for large files. This tool automatically handles large files by reading the first and last portions when files exceed size thresholds, ensuring efficient token usage while preserving important content. For files larger than 2,000 lines, it reads the first 800 and last 800 lines with a truncation indicator. Use chunk_lines or max_lines parameters to customize the threshold. Files with very long lines, such as minified bundles or JSON blobs, are read 16KB at a time: the result gives byte_range, next_byte_offset and starts_at (a JSON path or nesting depth) instead of line numbers. The tool provides structured logging of chunking operations for debugging.".to_string(),
             parameters: json!({
                 "type": "object",
                 "properties": {
                     "path": {"type": "string", "description": "File path to read"},
                     "max_bytes": {"type": "integer", "description": "Maximum bytes to read (optional)", "default": null},
                     "chunk_lines": {"type": "integer", "description": "Line threshold for chunking (optional, default: 2000)", "default": 2000},
-                    "max_lines": {"type": "integer", "description": "Alternative parameter for chunk_lines (optional)", "default": null}
+                    "max_lines": {"type": "integer", "description": "Alternative parameter for chunk_lines (optional)", "default": null},
+                    "byte_offset": {"type": "integer", "description": "Byte offset to read from in a file with very long lines (optional)", "default": null},
+                    "byte_length": {"type": "integer", "description": "Bytes to read from byte_offset (optional, default: 16000)", "default": null}
                 },
                 "required": ["path"]
             }),
@@ -223,6 +227,243 @@ pub fn build_function_declarations() -> Vec<FunctionDeclaration> {
                 "required": ["input"]
             }),
         },
+        // Import management tool
+        FunctionDeclaration {
+            name: tools::ORGANIZE_IMPORTS.to_string(),
+            description: "Organizes the imports of a Rust, Python, JavaScript/TypeScript or Go file. Adds imports for referenced symbols that resolve to exactly one definition in the workspace (or, for Go, a standard library package), removes imports whose names are no longer used, and sorts imports within the groups the file already uses. Names with several possible definitions are reported under 'ambiguous' instead of being guessed. Use this tool after moving or renaming symbols, or after an edit that introduced new references. Set dry_run to preview the resulting import block without writing the file.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "path": {"type": "string", "description": "File whose imports should be organized"},
+                    "add_missing": {"type": "boolean", "description": "Add imports for unresolved references (default from [tools.imports])"},
+                    "remove_unused": {"type": "boolean", "description": "Remove imports that are no longer referenced (default from [tools.imports])"},
+                    "sort": {"type": "boolean", "description": "Sort imports within their groups (default from [tools.imports])"},
+                    "dry_run": {"type": "boolean", "description": "Preview the import block without writing", "default": false}
+                },
+                "required": ["path"]
+            }),
+        },
+        // Config edit tool
+        FunctionDeclaration {
+            name: tools::CONFIG_EDIT.to_string(),
+            description: "Reads, sets or deletes a value in a TOML, YAML or JSON/JSONC config file by key path, e.g. key 'dependencies.serde' in Cargo.toml or 'jobs.test.steps[0].run' in a workflow. Missing parent tables are created on set. TOML and JSON edits keep comments, key order and indentation; YAML files are re-serialized. Every edit is re-parsed before it is written and the result includes a diff. Prefer this tool over edit_file for configuration changes. Use action 'get' with an empty key to read the whole document, and dry_run to preview an edit.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "path": {"type": "string", "description": "Config file to read or edit"},
+                    "action": {"type": "string", "enum": ["get", "set", "delete"], "description": "Operation to perform"},
+                    "key": {"type": "string", "description": "Dotted key path; quote keys containing dots (a.\"b.c\") and index arrays with [n]. Empty addresses the whole document", "default": ""},
+                    "value": {"description": "New value for 'set' (any JSON value)"},
+                    "format": {"type": "string", "enum": ["toml", "yaml", "json"], "description": "Override the format inferred from the file extension"},
+                    "dry_run": {"type": "boolean", "description": "Report the diff without writing", "default": false}
+                },
+                "required": ["path", "action"]
+            }),
+        },
+        // Coverage tool
+        FunctionDeclaration {
+            name: tools::COVERAGE_REPORT.to_string(),
+            description: "Runs the test coverage command configured for the project language (cargo-llvm-cov for Rust, coverage.py for Python, istanbul via jest for JavaScript/TypeScript) and reports uncovered line ranges for the given files. Without paths it checks every file modified in the git working tree and lists, per file, the uncovered lines that fall inside the modified hunks under 'uncovered_changed'. Use this tool after changing code to find the new lines that no test exercises, then write targeted tests for those ranges. Set run to false to read the last coverage report without re-running the tests.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "paths": {"type": "array", "items": {"type": "string"}, "description": "Files to report on (default: files modified since HEAD)"},
+                    "language": {"type": "string", "description": "Coverage command to use from [tools.coverage.languages] (default: inferred from file extensions)"},
+                    "run": {"type": "boolean", "description": "Run the coverage command before reading the report", "default": true}
+                },
+                "required": []
+            }),
+        },
+        // Dependency vulnerability scan
+        FunctionDeclaration {
+            name: tools::VULNERABILITY_SCAN.to_string(),
+            description: "Scans the workspace dependencies for known security advisories with cargo-audit (Cargo.lock), npm audit (package-lock.json) and pip-audit (requirements.txt or pyproject.toml), plus any existing audit reports configured under [tools.vulnerability_scan]. Returns each finding with its package, installed or affected version, advisory id, severity and 'fix' (the version to upgrade to) when one exists. Results are cached until a lockfile changes. Call this before adding or upgrading dependencies or when asked about security, never suggest a version listed here, and propose an upgrade plan from the 'fix' entries.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "refresh": {"type": "boolean", "description": "Scan again even if the cached results are current", "default": false},
+                    "fixable_only": {"type": "boolean", "description": "Only list findings with a fixed version available", "default": false}
+                },
+                "required": []
+            }),
+        },
+        // Embeddings search
+        FunctionDeclaration {
+            name: tools::SEMANTIC_SEARCH.to_string(),
+            description: "Finds code and docs by meaning: describe what the code does (e.g. 'where retries are scheduled after a failed request') and get the best-matching functions, classes and file sections, ranked by similarity, with their path, line range, symbol and a short snippet. The workspace is indexed with embeddings along tree-sitter definitions, and files changed since the last search are re-indexed first. Use this when you do not know the names or exact text to grep for; use grep_search for exact strings and ast_grep_search for code patterns, then read_file the hits.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "query": {"type": "string", "description": "What the code does or is about, in natural language or with likely identifiers"},
+                    "path": {"type": "string", "description": "Only search files under this directory or file"},
+                    "max_results": {"type": "integer", "description": "Chunks to return (up to 30)", "default": 8}
+                },
+                "required": ["query"]
+            }),
+        },
+        // Environment bootstrap tool
+        FunctionDeclaration {
+            name: tools::BOOTSTRAP_ENV.to_string(),
+            description: "Detects the toolchains the workspace declares (rust-toolchain.toml or Cargo.toml rust-version, .nvmrc/.node-version or package.json engines and packageManager, .python-version or pyproject.toml requires-python, plus pnpm, yarn, poetry and uv lockfiles) and checks that each is installed at a matching version. For anything missing or mismatched it returns the exact install commands for the version managers on this machine (rustup, fnm/volta/nvm, corepack, uv/pyenv, pipx). Call this before the first build or test run on an unfamiliar machine. Set install to true to run the commands; this only works when [tools.bootstrap] allow_install is enabled.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "install": {"type": "boolean", "description": "Run the install commands for missing toolchains (requires [tools.bootstrap] allow_install)", "default": false}
+                },
+                "required": []
+            }),
+        },
+        // Project command catalog
+        FunctionDeclaration {
+            name: tools::PROJECT_COMMANDS.to_string(),
+            description: "Lists the build, test, lint and fmt commands this project already uses, read from the justfile, Makefile, package.json scripts and the run steps of .github/workflows, with the canonical command for each kind (task runners first, then package scripts, then CI). Call this before building, testing, linting or formatting and run the canonical command instead of guessing flags such as `cargo test --workspace`; the CI entries show the exact flags the project's checks use.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "kind": {"type": "string", "enum": ["build", "test", "lint", "fmt"], "description": "Only list commands of this kind"}
+                },
+                "required": []
+            }),
+        },
+        // Call graph
+        FunctionDeclaration {
+            name: tools::CALL_GRAPH.to_string(),
+            description: "Finds the callers and callees of a function across the workspace (Rust, Python, JavaScript/TypeScript, Go), following calls up to depth hops in each direction. Calls are matched by name with tree-sitter, preferring definitions in the calling file and then importable ones; edges that could point at several same-named functions are marked ambiguous, and calls into libraries are listed under external_calls. Call this before changing a function's signature or behavior to see what depends on it, and set render to mermaid or dot for a diagram.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "symbol": {"type": "string", "description": "Function or method name; qualify methods as Type::method or Type.method"},
+                    "path": {"type": "string", "description": "Only use definitions in this file"},
+                    "direction": {"type": "string", "enum": ["callers", "callees", "both"], "description": "Which way to walk", "default": "both"},
+                    "depth": {"type": "integer", "description": "Maximum hops from the symbol (1-5)", "default": 2},
+                    "max_nodes": {"type": "integer", "description": "Stop adding functions after this many", "default": 60},
+                    "render": {"type": "string", "enum": ["mermaid", "dot"], "description": "Also return the graph as a Mermaid flowchart or Graphviz DOT under diagram"}
+                },
+                "required": ["symbol"]
+            }),
+        },
+        // Dependency documentation
+        FunctionDeclaration {
+            name: tools::FETCH_DOCS.to_string(),
+            description: "Fetches the documentation of a dependency at the version this workspace uses, as markdown. The version comes from the lockfile or manifest (Cargo.lock/Cargo.toml, package-lock.json/package.json, uv.lock, poetry.lock, requirements.txt or pyproject.toml) unless given. Crates come from docs.rs, npm packages from their published README or files, Python packages from their PyPI description or readthedocs pages, and web platform APIs from MDN. Pages are cached per package@version, so repeated calls are cheap. Call this before using an API you are unsure about in a pinned version instead of relying on memory; follow links in the markdown by passing them as page, and continue long pages with next_offset.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "package": {"type": "string", "description": "Package name as in the manifest; for mdn a docs path such as Web/API/fetch"},
+                    "ecosystem": {"type": "string", "enum": ["crates", "npm", "pypi", "mdn"], "description": "Where to look; found from the manifests when omitted"},
+                    "version": {"type": "string", "description": "Version to use instead of the one in the workspace"},
+                    "page": {"type": "string", "description": "Page below the docs root (e.g. reqwest/struct.Client.html on docs.rs, docs/api.md in an npm package, api.html on readthedocs) or a full URL on the same docs host"},
+                    "offset": {"type": "integer", "description": "Character offset to continue from (next_offset of the previous call)", "default": 0},
+                    "max_chars": {"type": "integer", "description": "Characters of markdown to return (up to 100000)", "default": 20000},
+                    "refresh": {"type": "boolean", "description": "Fetch again instead of using the cached page", "default": false}
+                },
+                "required": ["package"]
+            }),
+        },
+        // Git
+        FunctionDeclaration {
+            name: tools::GIT_STATUS.to_string(),
+            description: "Shows the current branch, its upstream with ahead/behind counts, and the staged, unstaged, untracked and conflicted paths of the workspace repository, with each change named (added, modified, deleted, renamed, copied). Use this instead of running `git status` in the terminal.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {},
+                "required": []
+            }),
+        },
+        FunctionDeclaration {
+            name: tools::GIT_DIFF.to_string(),
+            description: "Shows changes as per-file addition and deletion counts plus a unified patch. By default compares the working tree with the index; staged compares the index with HEAD, and rev compares the working tree with a revision or diffs a range such as main...HEAD. Long patches are cut and marked truncated; use paths or stat_only to narrow large diffs.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "staged": {"type": "boolean", "description": "Diff the staged changes against HEAD", "default": false},
+                    "rev": {"type": "string", "description": "Revision to compare with (e.g. HEAD~3) or a range (main..HEAD, main...HEAD)"},
+                    "paths": {"type": "array", "items": {"type": "string"}, "description": "Only diff these paths"},
+                    "context_lines": {"type": "integer", "description": "Context lines around each change (0-20)", "default": 3},
+                    "stat_only": {"type": "boolean", "description": "Return only the per-file counts", "default": false}
+                },
+                "required": []
+            }),
+        },
+        FunctionDeclaration {
+            name: tools::GIT_LOG.to_string(),
+            description: "Lists commits newest first with hash, author, ISO date and subject. Narrow it with rev (a branch or range such as main..HEAD), path (commits touching a file) or author.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "max_count": {"type": "integer", "description": "Commits to return (1-200)", "default": 20},
+                    "rev": {"type": "string", "description": "Branch, revision or range to list"},
+                    "path": {"type": "string", "description": "Only commits that touch this path"},
+                    "author": {"type": "string", "description": "Only commits whose author matches this pattern"}
+                },
+                "required": []
+            }),
+        },
+        FunctionDeclaration {
+            name: tools::GIT_COMMIT.to_string(),
+            description: "Commits the staged changes with the given message and returns the new commit hash, branch and changed files. Pass paths to stage specific files first, or all to stage every change including untracked files. Fails when nothing is staged. Hooks run as usual. Asks the user for confirmation under the default policy.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "message": {"type": "string", "description": "Commit message; the first line is the summary"},
+                    "paths": {"type": "array", "items": {"type": "string"}, "description": "Paths to stage before committing"},
+                    "all": {"type": "boolean", "description": "Stage all changes, untracked files included, before committing", "default": false}
+                },
+                "required": ["message"]
+            }),
+        },
+        FunctionDeclaration {
+            name: tools::GIT_BRANCH.to_string(),
+            description: "Lists local branches with their commit, upstream and which one is current, or creates and switches to a new branch, or switches to an existing one. Switching fails rather than discarding uncommitted changes that would be overwritten. Creating and switching ask the user for confirmation under the default policy.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "action": {"type": "string", "enum": ["list", "create", "switch"], "description": "What to do", "default": "list"},
+                    "name": {"type": "string", "description": "Branch to create or switch to"},
+                    "start_point": {"type": "string", "description": "Revision a new branch starts from; HEAD when omitted"}
+                },
+                "required": []
+            }),
+        },
+        // SQL tools, offered only when [tools.sql] is enabled
+        FunctionDeclaration {
+            name: tools::SQL_SCHEMA.to_string(),
+            description: "Lists the tables and views of a configured development database, or the columns of one table with their types, nullability and defaults. Call this before writing queries, models or migrations instead of guessing table and column names.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "database": {"type": "string", "description": "Database name from [tools.sql.databases]; optional when only one is configured"},
+                    "table": {"type": "string", "description": "Describe this table's columns (Postgres accepts schema.table)"}
+                },
+                "required": []
+            }),
+        },
+        FunctionDeclaration {
+            name: tools::SQL_QUERY.to_string(),
+            description: "Runs one read-only statement (SELECT, WITH, EXPLAIN, SHOW or PRAGMA) against a configured development database and returns the rows as a table: column names plus rows of string values, with null for SQL NULL. The session is read-only, so mutations fail; use sql_execute for those. Results are cut at max_rows and marked truncated.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "query": {"type": "string", "description": "A single read-only SQL statement"},
+                    "database": {"type": "string", "description": "Database name from [tools.sql.databases]; optional when only one is configured"},
+                    "max_rows": {"type": "integer", "description": "Maximum rows to return, capped by [tools.sql] max_rows"}
+                },
+                "required": ["query"]
+            }),
+        },
+        FunctionDeclaration {
+            name: tools::SQL_EXECUTE.to_string(),
+            description: "Runs SQL that changes a development database, such as a migration file or INSERT/UPDATE/DDL statements. Only works on databases configured with allow_writes, and asks the user for confirmation under the default policy. Postgres runs the whole batch in one transaction. Prefer the project's own migration command when it has one.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "statements": {"type": "string", "description": "SQL statements separated by semicolons"},
+                    "file": {"type": "string", "description": "Migration file to run, relative to the workspace; use instead of statements"},
+                    "database": {"type": "string", "description": "Database name from [tools.sql.databases]; optional when only one is configured"}
+                },
+                "required": []
+            }),
+        },
         FunctionDeclaration {
             name: tools::UPDATE_PLAN.to_string(),
             description: "Records or updates the agent's current multi-step plan. Provide a concise explanation (optional) and a list of plan steps with their status. Exactly one step may be marked 'in_progress'; all other steps must be 'pending' or 'completed'. Use this tool to keep the user informed about your approach for complex tasks, render the plan as a Markdown TODO list with checkboxes, and update it whenever progress changes.".to_string(),
@@ -279,3 +520,12 @@ pub fn build_function_declarations_for_level(level: CapabilityLevel) -> Vec<Func
         })
         .collect()
 }
+
+/// Capability level a built-in tool requires, including tools hidden from the
+/// model
+pub fn tool_capability(name: &str) -> Option<CapabilityLevel> {
+    builtin_tool_registrations()
+        .into_iter()
+        .find(|registration| registration.name() == name)
+        .map(|registration| registration.capability())
+}
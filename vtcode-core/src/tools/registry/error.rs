@@ -22,6 +22,11 @@ pub enum ToolErrorType {
     Timeout,
     ExecutionError,
     PolicyViolation,
+    QuotaExceeded,
+    EditConflict,
+    /// A write outside the workspace is waiting for the user's approval
+    PermissionRequired,
+    Cancelled,
 }
 
 impl ToolExecutionError {
@@ -149,5 +154,39 @@ fn generate_recovery_info(error_type: &ToolErrorType) -> (bool, Vec<String>) {
                 "Use alternative tools that comply with policies".to_string(),
             ],
         ),
+        ToolErrorType::QuotaExceeded => (
+            true,
+            vec![
+                "Write smaller content, or split it across several edits".to_string(),
+                "Do not write logs, build output or generated data into the workspace".to_string(),
+                "Ask the user to raise the [tools.write_quota] limit if the write is intended"
+                    .to_string(),
+            ],
+        ),
+        ToolErrorType::EditConflict => (
+            true,
+            vec![
+                "Read the file again; the user changed it while you were editing".to_string(),
+                "Re-apply your change on top of the current content".to_string(),
+                "Keep edits to the lines you need so they merge with the user's changes"
+                    .to_string(),
+            ],
+        ),
+        ToolErrorType::PermissionRequired => (
+            false,
+            vec![
+                "The user decides whether this path may be written; see the permission_required field"
+                    .to_string(),
+                "If access was declined, do not retry the write; ask the user or find another way"
+                    .to_string(),
+            ],
+        ),
+        ToolErrorType::Cancelled => (
+            false,
+            vec![
+                "The user stopped this call; do not retry it unless asked".to_string(),
+                "Ask the user how to continue, or narrow the operation first".to_string(),
+            ],
+        ),
     }
 }
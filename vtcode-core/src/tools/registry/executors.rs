@@ -11,7 +11,8 @@ use super::ToolRegistry;
 impl ToolRegistry {
     pub(super) fn grep_search_executor(&mut self, args: Value) -> BoxFuture<'_, Result<Value>> {
         let tool = self.search_tool.clone();
-        Box::pin(async move { tool.execute(args).await })
+        let context = self.tool_context().clone();
+        Box::pin(async move { tool.execute_with_context(args, &context).await })
     }
 
     pub(super) fn list_files_executor(&mut self, args: Value) -> BoxFuture<'_, Result<Value>> {
@@ -64,9 +65,105 @@ impl ToolRegistry {
 
     pub(super) fn srgn_executor(&mut self, args: Value) -> BoxFuture<'_, Result<Value>> {
         let tool = self.srgn_tool.clone();
+        let context = self.tool_context().clone();
+        Box::pin(async move { tool.execute_with_context(args, &context).await })
+    }
+
+    pub(super) fn organize_imports_executor(
+        &mut self,
+        args: Value,
+    ) -> BoxFuture<'_, Result<Value>> {
+        let tool = self.imports_tool.clone();
+        Box::pin(async move { tool.execute(args).await })
+    }
+
+    pub(super) fn config_edit_executor(&mut self, args: Value) -> BoxFuture<'_, Result<Value>> {
+        let tool = self.config_edit_tool.clone();
+        Box::pin(async move { tool.execute(args).await })
+    }
+
+    pub(super) fn coverage_report_executor(&mut self, args: Value) -> BoxFuture<'_, Result<Value>> {
+        let tool = self.coverage_tool.clone();
+        Box::pin(async move { tool.execute(args).await })
+    }
+
+    pub(super) fn bootstrap_env_executor(&mut self, args: Value) -> BoxFuture<'_, Result<Value>> {
+        let tool = self.bootstrap_tool.clone();
         Box::pin(async move { tool.execute(args).await })
     }
 
+    pub(super) fn project_commands_executor(
+        &mut self,
+        args: Value,
+    ) -> BoxFuture<'_, Result<Value>> {
+        let tool = self.project_commands_tool.clone();
+        Box::pin(async move { tool.execute(args).await })
+    }
+
+    pub(super) fn call_graph_executor(&mut self, args: Value) -> BoxFuture<'_, Result<Value>> {
+        let tool = self.call_graph_tool.clone();
+        Box::pin(async move { tool.execute(args).await })
+    }
+
+    pub(super) fn fetch_docs_executor(&mut self, args: Value) -> BoxFuture<'_, Result<Value>> {
+        let tool = self.fetch_docs_tool.clone();
+        Box::pin(async move { tool.execute(args).await })
+    }
+
+    pub(super) fn git_status_executor(&mut self, args: Value) -> BoxFuture<'_, Result<Value>> {
+        let tool = self.git_tool.clone();
+        Box::pin(async move { tool.status(args).await })
+    }
+
+    pub(super) fn git_diff_executor(&mut self, args: Value) -> BoxFuture<'_, Result<Value>> {
+        let tool = self.git_tool.clone();
+        Box::pin(async move { tool.diff(args).await })
+    }
+
+    pub(super) fn git_log_executor(&mut self, args: Value) -> BoxFuture<'_, Result<Value>> {
+        let tool = self.git_tool.clone();
+        Box::pin(async move { tool.log(args).await })
+    }
+
+    pub(super) fn git_commit_executor(&mut self, args: Value) -> BoxFuture<'_, Result<Value>> {
+        let tool = self.git_tool.clone();
+        Box::pin(async move { tool.commit(args).await })
+    }
+
+    pub(super) fn git_branch_executor(&mut self, args: Value) -> BoxFuture<'_, Result<Value>> {
+        let tool = self.git_tool.clone();
+        Box::pin(async move { tool.branch(args).await })
+    }
+
+    pub(super) fn sql_query_executor(&mut self, args: Value) -> BoxFuture<'_, Result<Value>> {
+        let tool = self.sql_tool.clone();
+        Box::pin(async move { tool.query(args).await })
+    }
+
+    pub(super) fn sql_schema_executor(&mut self, args: Value) -> BoxFuture<'_, Result<Value>> {
+        let tool = self.sql_tool.clone();
+        Box::pin(async move { tool.schema(args).await })
+    }
+
+    pub(super) fn sql_execute_executor(&mut self, args: Value) -> BoxFuture<'_, Result<Value>> {
+        let tool = self.sql_tool.clone();
+        Box::pin(async move { tool.execute_statements(args).await })
+    }
+
+    pub(super) fn vulnerability_scan_executor(
+        &mut self,
+        args: Value,
+    ) -> BoxFuture<'_, Result<Value>> {
+        let tool = self.vulnerability_tool.clone();
+        Box::pin(async move { tool.execute(args).await })
+    }
+
+    pub(super) fn semantic_search_executor(&mut self, args: Value) -> BoxFuture<'_, Result<Value>> {
+        let tool = self.semantic_search_tool.clone();
+        let context = self.tool_context().clone();
+        Box::pin(async move { tool.execute_with_context(args, &context).await })
+    }
+
     pub(super) fn update_plan_executor(&mut self, args: Value) -> BoxFuture<'_, Result<Value>> {
         let manager = self.plan_manager.clone();
         Box::pin(async move {
@@ -86,7 +183,9 @@ impl ToolRegistry {
             .and_then(|v| v.as_str())
             .ok_or_else(|| anyhow!("Error: Missing 'input' string with patch content. Example: apply_patch({{ input: '*** Begin Patch...*** End Patch' }})"))?;
         let patch = Patch::parse(input)?;
-        let results = patch.apply(&self.workspace_root).await?;
+        let results = patch
+            .apply(&self.workspace_root, self.file_ops_tool.write_quota())
+            .await?;
         Ok(json!({
             "success": true,
             "applied": results,
@@ -5,6 +5,7 @@ use shell_words::split;
 
 use crate::config::constants::tools;
 use crate::config::loader::ConfigManager;
+use crate::tools::edit_conflict::{EditConflict, merge3};
 use crate::tools::grep_search::GrepSearchResult;
 use crate::tools::types::EditInput;
 
@@ -22,6 +23,8 @@ impl ToolRegistry {
 
     pub async fn edit_file(&mut self, args: Value) -> Result<Value> {
         let input: EditInput = serde_json::from_value(args).context("invalid edit_file args")?;
+        let file_path = self.workspace_root.join(&input.path);
+        let last_seen = self.file_ops_tool.snapshots().base(&file_path);
 
         let read_args = json!({
             "path": input.path,
@@ -33,55 +36,53 @@ impl ToolRegistry {
             .as_str()
             .ok_or_else(|| anyhow!("Failed to read file content"))?;
 
-        let mut replacement_occurred = false;
-        let mut new_content = current_content.to_string();
+        let mut rebased = false;
+        let new_content = match replace_text(current_content, &input.old_str, &input.new_str) {
+            Some(new_content) => new_content,
+            None => {
+                // The text may be gone because the file was edited outside
+                // the agent since it was read; apply the edit to the version
+                // the agent saw and merge it onto the current one.
+                let edited_base =
+                    last_seen
+                        .filter(|base| base != current_content)
+                        .and_then(|base| {
+                            replace_text(&base, &input.old_str, &input.new_str)
+                                .map(|edited| (base, edited))
+                        });
+                let Some((base, edited)) = edited_base else {
+                    let content_preview = if current_content.len() > 500 {
+                        format!(
+                            "{}...{}",
+                            &current_content[..250],
+                            &current_content[current_content.len().saturating_sub(250)..]
+                        )
+                    } else {
+                        current_content.to_string()
+                    };
 
-        if current_content.contains(&input.old_str) {
-            new_content = current_content.replace(&input.old_str, &input.new_str);
-            replacement_occurred = new_content != current_content;
-        }
-
-        if !replacement_occurred {
-            let normalized_content = utils::normalize_whitespace(current_content);
-            let normalized_old_str = utils::normalize_whitespace(&input.old_str);
-
-            if normalized_content.contains(&normalized_old_str) {
-                let old_lines: Vec<&str> = input.old_str.lines().collect();
-                let content_lines: Vec<&str> = current_content.lines().collect();
-
-                for i in 0..=(content_lines.len().saturating_sub(old_lines.len())) {
-                    let window = &content_lines[i..i + old_lines.len()];
-                    if utils::lines_match(window, &old_lines) {
-                        let before = content_lines[..i].join("\n");
-                        let after = content_lines[i + old_lines.len()..].join("\n");
-                        let replacement_lines: Vec<&str> = input.new_str.lines().collect();
-
-                        new_content =
-                            format!("{}\n{}\n{}", before, replacement_lines.join("\n"), after);
-                        replacement_occurred = true;
-                        break;
+                    return Err(anyhow!(
+                        "Could not find text to replace in file.\n\nExpected to replace:\n{}\n\nFile content preview:\n{}",
+                        input.old_str,
+                        content_preview
+                    ));
+                };
+                let merge = merge3(&base, current_content, &edited);
+                if !merge.is_clean() {
+                    return Err(EditConflict {
+                        path: input.path.clone(),
+                        base,
+                        mine: current_content.to_string(),
+                        agent: edited,
+                        merged: merge.merged,
+                        hunks: merge.conflicts,
                     }
+                    .into());
                 }
+                rebased = true;
+                merge.merged
             }
-        }
-
-        if !replacement_occurred {
-            let content_preview = if current_content.len() > 500 {
-                format!(
-                    "{}...{}",
-                    &current_content[..250],
-                    &current_content[current_content.len().saturating_sub(250)..]
-                )
-            } else {
-                current_content.to_string()
-            };
-
-            return Err(anyhow!(
-                "Could not find text to replace in file.\n\nExpected to replace:\n{}\n\nFile content preview:\n{}",
-                input.old_str,
-                content_preview
-            ));
-        }
+        };
 
         let write_args = json!({
             "path": input.path,
@@ -89,7 +90,13 @@ impl ToolRegistry {
             "mode": "overwrite"
         });
 
-        self.file_ops_tool.write_file(write_args).await
+        let mut result = self.file_ops_tool.write_file(write_args).await?;
+        self.tree_cache
+            .record_edit(&file_path, current_content, &new_content);
+        if rebased {
+            result["rebased"] = json!(true);
+        }
+        Ok(result)
     }
 
     pub async fn delete_file(&mut self, _args: Value) -> Result<Value> {
@@ -240,3 +247,37 @@ impl ToolRegistry {
         self.execute_tool(tools::RUN_TERMINAL_CMD, args).await
     }
 }
+
+/// `content` with `old_str` replaced by `new_str`, matching whole lines with
+/// normalized whitespace when there is no exact match.
+fn replace_text(content: &str, old_str: &str, new_str: &str) -> Option<String> {
+    if content.contains(old_str) {
+        let replaced = content.replace(old_str, new_str);
+        if replaced != content {
+            return Some(replaced);
+        }
+    }
+
+    let normalized_content = utils::normalize_whitespace(content);
+    let normalized_old_str = utils::normalize_whitespace(old_str);
+    if !normalized_content.contains(&normalized_old_str) {
+        return None;
+    }
+    let old_lines: Vec<&str> = old_str.lines().collect();
+    let content_lines: Vec<&str> = content.lines().collect();
+    for i in 0..=(content_lines.len().saturating_sub(old_lines.len())) {
+        let window = &content_lines[i..i + old_lines.len()];
+        if utils::lines_match(window, &old_lines) {
+            let before = content_lines[..i].join("\n");
+            let after = content_lines[i + old_lines.len()..].join("\n");
+            let replacement_lines: Vec<&str> = new_str.lines().collect();
+            return Some(format!(
+                "{}\n{}\n{}",
+                before,
+                replacement_lines.join("\n"),
+                after
+            ));
+        }
+    }
+    None
+}
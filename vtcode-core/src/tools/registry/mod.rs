@@ -3,43 +3,78 @@
 mod astgrep;
 mod builtins;
 mod cache;
+mod concurrency;
 mod declarations;
 mod error;
 mod executors;
 mod legacy;
+mod parallel;
 mod policy;
 mod pty;
 mod registration;
 mod utils;
 
-pub use declarations::{build_function_declarations, build_function_declarations_for_level};
+pub use declarations::{
+    build_function_declarations, build_function_declarations_for_level, tool_capability,
+};
 pub use error::{ToolErrorType, ToolExecutionError, classify_error};
-pub use registration::{ToolExecutorFn, ToolHandler, ToolRegistration};
+pub use parallel::{ToolCallOutcome, ToolCallRequest};
+pub use registration::{
+    DEFAULT_TOOL_SCHEMA_VERSION, DeprecatedArgument, ToolExecutorFn, ToolHandler, ToolRegistration,
+};
 
 use builtins::register_builtin_tools;
+use concurrency::ToolConcurrency;
+pub(crate) use concurrency::command_words;
 use utils::normalize_tool_output;
 
+use crate::config::ConcurrencyConfig;
+use crate::config::PathDisplay;
 use crate::config::PtyConfig;
 use crate::config::ToolsConfig;
 use crate::config::constants::tools;
+use crate::core::progress::ProgressSink;
 use crate::tool_policy::{ToolPolicy, ToolPolicyManager};
 use crate::tools::ast_grep::AstGrepEngine;
 use crate::tools::grep_search::GrepSearchManager;
+use crate::tools::tree_sitter::SyntaxTreeCache;
 use anyhow::{Result, anyhow};
+use futures::FutureExt;
 use serde_json::Value;
+use std::any::Any;
 use std::collections::{HashMap, HashSet};
+use std::panic::AssertUnwindSafe;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::AtomicUsize;
+use tokio_util::sync::CancellationToken;
 
 use super::bash_tool::BashTool;
+use super::bootstrap::BootstrapEnvTool;
+use super::call_graph::CallGraphTool;
+use super::cancellation::{CANCEL_GRACE, ToolCancelled, ToolContext};
 use super::command::CommandTool;
+use super::config_edit::ConfigEditTool;
+use super::coverage::CoverageReportTool;
 use super::curl_tool::CurlTool;
+use super::docs::FetchDocsTool;
+use super::edit_conflict::EditConflict;
 use super::file_ops::FileOpsTool;
+use super::git::GitTool;
+use super::imports::{OrganizeImportsTool, OrganizeOptions};
+use super::mocks::ToolMocks;
+use super::path_display::PathDisplayPolicy;
+use super::path_grants::{PathGrants, WritePermissionRequired};
 use super::plan::PlanManager;
+use super::project_commands::ProjectCommandsTool;
 use super::search::SearchTool;
+use super::semantic_search::SemanticSearchTool;
 use super::simple_search::SimpleSearchTool;
+use super::sql::SqlTool;
 use super::srgn::SrgnTool;
+use super::vulnerabilities::VulnerabilityScanTool;
+use super::workspace_env::WorkspaceEnv;
+use super::write_quota::QuotaExceeded;
 
 #[cfg(test)]
 use super::traits::Tool;
@@ -60,12 +95,31 @@ pub struct ToolRegistry {
     tool_policy: Option<ToolPolicyManager>,
     pty_config: PtyConfig,
     active_pty_sessions: Arc<AtomicUsize>,
+    concurrency: ToolConcurrency,
     srgn_tool: SrgnTool,
+    imports_tool: OrganizeImportsTool,
+    config_edit_tool: ConfigEditTool,
+    coverage_tool: CoverageReportTool,
+    bootstrap_tool: BootstrapEnvTool,
+    project_commands_tool: ProjectCommandsTool,
+    call_graph_tool: CallGraphTool,
+    fetch_docs_tool: FetchDocsTool,
+    git_tool: GitTool,
+    sql_tool: SqlTool,
+    vulnerability_tool: VulnerabilityScanTool,
+    semantic_search_tool: SemanticSearchTool,
     plan_manager: PlanManager,
     tool_registrations: Vec<ToolRegistration>,
     tool_lookup: HashMap<&'static str, usize>,
     preapproved_tools: HashSet<String>,
     full_auto_allowlist: Option<HashSet<String>>,
+    tree_cache: SyntaxTreeCache,
+    /// Context of the call being executed
+    context: ToolContext,
+    progress: Option<ProgressSink>,
+    workspace_env: Arc<WorkspaceEnv>,
+    path_display: PathDisplayPolicy,
+    mocks: ToolMocks,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -91,6 +145,21 @@ impl ToolRegistry {
         let curl_tool = CurlTool::new();
         let srgn_tool = SrgnTool::new(workspace_root.clone());
         let plan_manager = PlanManager::new();
+        let tree_cache = SyntaxTreeCache::new();
+        let mut imports_tool = OrganizeImportsTool::new(workspace_root.clone(), tree_cache.clone());
+        imports_tool.set_write_quota(file_ops_tool.write_quota().clone());
+        let mut config_edit_tool = ConfigEditTool::new(workspace_root.clone());
+        config_edit_tool.set_write_quota(file_ops_tool.write_quota().clone());
+        let coverage_tool = CoverageReportTool::new(workspace_root.clone());
+        let bootstrap_tool = BootstrapEnvTool::new(workspace_root.clone());
+        let project_commands_tool = ProjectCommandsTool::new(workspace_root.clone());
+        let call_graph_tool =
+            CallGraphTool::new(workspace_root.clone(), imports_tool.symbol_index());
+        let fetch_docs_tool = FetchDocsTool::new(workspace_root.clone());
+        let git_tool = GitTool::new(workspace_root.clone());
+        let sql_tool = SqlTool::new(workspace_root.clone());
+        let vulnerability_tool = VulnerabilityScanTool::new(workspace_root.clone());
+        let semantic_search_tool = SemanticSearchTool::new(workspace_root.clone());
 
         let ast_grep_engine = match AstGrepEngine::new() {
             Ok(engine) => Some(Arc::new(engine)),
@@ -108,6 +177,7 @@ impl ToolRegistry {
             }
         };
 
+        let path_display = PathDisplayPolicy::new(&workspace_root, PathDisplay::default());
         let mut registry = Self {
             workspace_root,
             search_tool,
@@ -121,18 +191,71 @@ impl ToolRegistry {
             tool_policy: policy_manager,
             pty_config,
             active_pty_sessions: Arc::new(AtomicUsize::new(0)),
+            concurrency: ToolConcurrency::new(&ConcurrencyConfig::default()),
             srgn_tool,
+            imports_tool,
+            config_edit_tool,
+            coverage_tool,
+            bootstrap_tool,
+            project_commands_tool,
+            call_graph_tool,
+            fetch_docs_tool,
+            git_tool,
+            sql_tool,
+            vulnerability_tool,
+            semantic_search_tool,
             plan_manager,
             tool_registrations: Vec::new(),
             tool_lookup: HashMap::new(),
             preapproved_tools: HashSet::new(),
             full_auto_allowlist: None,
+            tree_cache,
+            context: ToolContext::default(),
+            progress: None,
+            workspace_env: Arc::new(WorkspaceEnv::default()),
+            path_display,
+            mocks: ToolMocks::default(),
         };
 
         register_builtin_tools(&mut registry);
         registry
     }
 
+    /// Report the progress of tools that index the workspace to `sink`.
+    pub fn set_progress_sink(&mut self, sink: ProgressSink) {
+        self.progress = Some(sink);
+    }
+
+    /// Pass the variables of the workspace `.env`/`.envrc` to terminal commands.
+    pub fn set_workspace_env(&mut self, env: WorkspaceEnv) {
+        let env = Arc::new(env);
+        self.bash_tool.set_env(env.clone());
+        self.command_tool.set_env(env.clone());
+        self.workspace_env = env;
+    }
+
+    pub fn workspace_env(&self) -> &WorkspaceEnv {
+        &self.workspace_env
+    }
+
+    /// Canned responses configured under `[tools.mocks]`.
+    pub fn mocks(&self) -> &ToolMocks {
+        &self.mocks
+    }
+
+    /// Context of the call being executed, for registry function tools.
+    pub fn tool_context(&self) -> &ToolContext {
+        &self.context
+    }
+
+    /// Syntax tree cache that edit tools record their changes into.
+    ///
+    /// Share it with a `TreeSitterAnalyzer` so analysis after an edit only
+    /// re-parses the changed region.
+    pub fn tree_cache(&self) -> SyntaxTreeCache {
+        self.tree_cache.clone()
+    }
+
     pub fn register_tool(&mut self, registration: ToolRegistration) -> Result<()> {
         if self.tool_lookup.contains_key(registration.name()) {
             return Err(anyhow!(format!(
@@ -187,6 +310,18 @@ impl ToolRegistry {
         self.tool_lookup.contains_key(name)
     }
 
+    /// Schema version declared by the registered tool, if it exists.
+    pub fn tool_schema_version(&self, name: &str) -> Option<u32> {
+        self.registration(name)
+            .map(|registration| registration.schema_version())
+    }
+
+    fn registration(&self, name: &str) -> Option<&ToolRegistration> {
+        self.tool_lookup
+            .get(name)
+            .and_then(|index| self.tool_registrations.get(*index))
+    }
+
     pub fn with_ast_grep(mut self, engine: Arc<AstGrepEngine>) -> Self {
         self.ast_grep_engine = Some(engine);
         self
@@ -208,15 +343,58 @@ impl ToolRegistry {
         Ok(())
     }
 
+    /// Start a new user turn for the per-turn file creation quota.
+    pub fn begin_turn(&self) {
+        self.file_ops_tool.write_quota().begin_turn();
+    }
+
+    /// Paths outside the workspace that `write_file` and `edit_file` may write.
+    pub fn path_grants(&self) -> &PathGrants {
+        self.file_ops_tool.path_grants()
+    }
+
+    /// Write the version the user picked for a file after an edit conflict.
+    pub async fn resolve_edit_conflict(&self, path: &str, content: &str) -> Result<()> {
+        self.file_ops_tool.resolve_conflict(path, content).await
+    }
+
     pub fn apply_config_policies(&mut self, tools_config: &ToolsConfig) -> Result<()> {
         if let Ok(policy_manager) = self.policy_manager_mut() {
             policy_manager.apply_tools_config(tools_config)?;
         }
+        self.imports_tool.set_config(tools_config.imports.clone());
+        self.file_ops_tool
+            .write_quota()
+            .set_config(tools_config.write_quota.clone());
+        self.coverage_tool.set_config(tools_config.coverage.clone());
+        self.bootstrap_tool
+            .set_config(tools_config.bootstrap.clone());
+        self.sql_tool.set_config(tools_config.sql.clone());
+        self.vulnerability_tool
+            .set_config(tools_config.vulnerability_scan.clone());
+        self.semantic_search_tool
+            .set_config(tools_config.semantic_search.clone());
+        self.concurrency = ToolConcurrency::new(&tools_config.concurrency);
+        self.path_display = PathDisplayPolicy::new(&self.workspace_root, tools_config.path_display);
+        self.mocks = ToolMocks::new(tools_config.mocks.clone());
 
         Ok(())
     }
 
     pub async fn execute_tool(&mut self, name: &str, args: Value) -> Result<Value> {
+        self.execute_tool_cancellable(name, args, CancellationToken::new())
+            .await
+    }
+
+    /// Execute a tool call that stops when `cancel` is cancelled. A call that
+    /// has not stopped [`CANCEL_GRACE`] later is dropped; either way the
+    /// output is a `Cancelled` tool error.
+    pub async fn execute_tool_cancellable(
+        &mut self,
+        name: &str,
+        args: Value,
+        cancel: CancellationToken,
+    ) -> Result<Value> {
         if let Some(allowlist) = &self.full_auto_allowlist {
             if !allowlist.contains(name) {
                 let error = ToolExecutionError::new(
@@ -231,6 +409,11 @@ impl ToolRegistry {
             }
         }
 
+        let (args, deprecation_warnings) = match self.registration(name) {
+            Some(registration) => registration.migrate_arguments(args),
+            None => (args, Vec::new()),
+        };
+
         let skip_policy_prompt = self.preapproved_tools.remove(name);
 
         if !skip_policy_prompt {
@@ -259,11 +442,32 @@ impl ToolRegistry {
             }
         };
 
-        let registration = match self
-            .tool_lookup
-            .get(name)
-            .and_then(|index| self.tool_registrations.get(*index))
-        {
+        if let Some(answer) = self.mocks.answer(name, &args) {
+            let mut output = tokio::select! {
+                _ = tokio::time::sleep(answer.latency) => normalize_tool_output(answer.output),
+                _ = cancel.cancelled() => ToolExecutionError::new(
+                    name.to_string(),
+                    ToolErrorType::Cancelled,
+                    ToolCancelled.to_string(),
+                )
+                .to_json_value(),
+            };
+            self.path_display.apply(&mut output);
+            return Ok(output);
+        }
+        if self.mocks.is_strict() {
+            let error = ToolExecutionError::new(
+                name.to_string(),
+                ToolErrorType::PolicyViolation,
+                format!(
+                    "Tool '{}' has no mock for these arguments and [tools.mocks] strict is set",
+                    name
+                ),
+            );
+            return Ok(error.to_json_value());
+        }
+
+        let registration = match self.registration(name) {
             Some(registration) => registration,
             None => {
                 let error = ToolExecutionError::new(
@@ -275,6 +479,8 @@ impl ToolRegistry {
             }
         };
 
+        let slot = self.concurrency.acquire(name, &args).await;
+
         let uses_pty = registration.uses_pty();
         if uses_pty {
             if let Err(err) = self.start_pty_session() {
@@ -288,33 +494,147 @@ impl ToolRegistry {
             }
         }
 
+        let edited_path = args.get("path").and_then(Value::as_str).map(str::to_string);
         let handler = registration.handler();
-        let result = match handler {
-            ToolHandler::RegistryFn(executor) => executor(self, args).await,
-            ToolHandler::TraitObject(tool) => tool.execute(args).await,
+        let context = ToolContext::new(cancel.clone()).with_progress(self.progress.clone());
+        self.context = context.clone();
+        // Arguments come from model output; a tool that panics on them fails
+        // this call instead of unwinding through the agent loop.
+        let call = async {
+            match handler {
+                ToolHandler::RegistryFn(executor) => executor(self, args).await,
+                ToolHandler::TraitObject(tool) => tool.execute_with_context(args, &context).await,
+            }
+        };
+        let abandon = async {
+            cancel.cancelled().await;
+            tokio::time::sleep(CANCEL_GRACE).await;
+        };
+        let result = tokio::select! {
+            outcome = AssertUnwindSafe(call).catch_unwind() => match outcome {
+                Ok(result) => result,
+                Err(panic) => Err(anyhow!("tool panicked: {}", panic_message(panic.as_ref()))),
+            },
+            _ = abandon => Err(ToolCancelled.into()),
         };
+        self.context = ToolContext::default();
 
         if uses_pty {
             self.end_pty_session();
         }
+        let queued = slot.release();
 
-        match result {
-            Ok(value) => Ok(normalize_tool_output(value)),
+        let mut output = match result {
+            Ok(value) => normalize_tool_output(value),
             Err(err) => {
-                let error_type = classify_error(&err);
-                let error = ToolExecutionError::with_original_error(
-                    name.to_string(),
-                    error_type,
-                    format!("Tool execution failed: {}", err),
-                    err.to_string(),
-                );
-                Ok(error.to_json_value())
+                if let Some(quota) = err.downcast_ref::<QuotaExceeded>() {
+                    let mut error = ToolExecutionError::new(
+                        name.to_string(),
+                        ToolErrorType::QuotaExceeded,
+                        quota.to_string(),
+                    )
+                    .to_json_value();
+                    error["error"]["quota"] = quota.to_json_value();
+                    error
+                } else if err.downcast_ref::<ToolCancelled>().is_some() {
+                    ToolExecutionError::new(
+                        name.to_string(),
+                        ToolErrorType::Cancelled,
+                        ToolCancelled.to_string(),
+                    )
+                    .to_json_value()
+                } else if let Some(request) = err.downcast_ref::<WritePermissionRequired>() {
+                    let mut error = ToolExecutionError::new(
+                        name.to_string(),
+                        ToolErrorType::PermissionRequired,
+                        request.to_string(),
+                    )
+                    .to_json_value();
+                    error["error"]["permission_required"] = request.to_json_value();
+                    error
+                } else if let Some(conflict) = err.downcast_ref::<EditConflict>() {
+                    let mut error = ToolExecutionError::new(
+                        name.to_string(),
+                        ToolErrorType::EditConflict,
+                        conflict.to_string(),
+                    )
+                    .to_json_value();
+                    error["error"]["conflict"] = conflict.to_json_value();
+                    error
+                } else {
+                    let error_type = classify_error(&err);
+                    let error = ToolExecutionError::with_original_error(
+                        name.to_string(),
+                        error_type,
+                        format!("Tool execution failed: {}", err),
+                        err.to_string(),
+                    );
+                    error.to_json_value()
+                }
             }
+        };
+
+        if let Some(imports) = self
+            .organize_after_edit(name, edited_path.as_deref(), &output)
+            .await
+            && let Some(object) = output.as_object_mut()
+        {
+            object.insert("imports".to_string(), imports);
+        }
+
+        if let Some(queued) = queued
+            && let Some(object) = output.as_object_mut()
+        {
+            object.insert("queue".to_string(), queued.to_json());
+        }
+
+        if !deprecation_warnings.is_empty()
+            && let Some(object) = output.as_object_mut()
+        {
+            object.insert(
+                "deprecation_warnings".to_string(),
+                Value::from(deprecation_warnings),
+            );
         }
+
+        self.path_display.apply(&mut output);
+        Ok(output)
     }
 }
 
 impl ToolRegistry {
+    /// Run the import pass configured by `[tools.imports] organize_on_edit`
+    /// after a successful file edit.
+    ///
+    /// Only missing imports are added and existing ones sorted; nothing is
+    /// removed, since the model may be part-way through a multi-step edit.
+    async fn organize_after_edit(
+        &self,
+        name: &str,
+        path: Option<&str>,
+        output: &Value,
+    ) -> Option<Value> {
+        let config = self.imports_tool.config();
+        if !config.organize_on_edit
+            || !matches!(name, tools::WRITE_FILE | tools::EDIT_FILE)
+            || output.get("success").and_then(Value::as_bool) != Some(true)
+        {
+            return None;
+        }
+        let path = path?;
+        let options = OrganizeOptions {
+            add_missing: config.add_missing,
+            remove_unused: false,
+            sort: config.sort,
+        };
+        let result = self
+            .imports_tool
+            .organize(path, options, false)
+            .await
+            .ok()?;
+        (result.get("changed").and_then(Value::as_bool) == Some(true)).then_some(result)
+    }
+
     /// Prompt for permission before starting long-running tool executions to avoid spinner conflicts
     pub fn preflight_tool_permission(&mut self, name: &str) -> Result<bool> {
         match self.evaluate_tool_policy(name)? {
@@ -372,6 +692,14 @@ impl ToolRegistry {
     }
 }
 
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -438,6 +766,44 @@ mod tests {
         Ok(())
     }
 
+    struct PanickingTool;
+
+    #[async_trait]
+    impl Tool for PanickingTool {
+        async fn execute(&self, args: Value) -> Result<Value> {
+            let path = args["path"].as_str().unwrap_or_default();
+            Ok(json!({ "first": &path[..1] }))
+        }
+
+        fn name(&self) -> &'static str {
+            "panicking_tool"
+        }
+
+        fn description(&self) -> &'static str {
+            "Slices its argument without checking it"
+        }
+    }
+
+    #[tokio::test]
+    async fn panicking_tool_fails_the_call() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut registry = ToolRegistry::new(temp_dir.path().to_path_buf());
+        registry.register_tool(ToolRegistration::from_tool_instance(
+            "panicking_tool",
+            CapabilityLevel::CodeSearch,
+            PanickingTool,
+        ))?;
+        registry.sync_policy_available_tools();
+        registry.allow_all_tools().ok();
+
+        let response = registry
+            .execute_tool("panicking_tool", json!({ "path": "é" }))
+            .await?;
+        let message = response["error"]["message"].as_str().unwrap_or_default();
+        assert!(message.contains("tool panicked"), "{response}");
+        Ok(())
+    }
+
     #[tokio::test]
     async fn full_auto_allowlist_enforced() -> Result<()> {
         let temp_dir = TempDir::new()?;
@@ -0,0 +1,274 @@
+//! Running several tool calls of one model response at once
+//!
+//! Read-only calls (reading, listing and searching files) cannot affect each
+//! other, so consecutive ones run concurrently on clones of the registry, up
+//! to `[tools.concurrency] max_parallel_reads` at a time. Every other call
+//! runs alone, in order, after the calls before it have finished. Outcomes are
+//! returned in the order of the requests whatever order the calls finish in,
+//! so the transcript reads the same from run to run.
+
+use anyhow::Result;
+use futures::StreamExt;
+use futures::stream;
+use serde_json::Value;
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+use super::ToolRegistry;
+use crate::config::types::CapabilityLevel;
+use crate::tool_policy::ToolPolicy;
+
+/// One tool call of a batch.
+#[derive(Debug, Clone)]
+pub struct ToolCallRequest {
+    pub name: String,
+    pub args: Value,
+}
+
+impl ToolCallRequest {
+    pub fn new(name: impl Into<String>, args: Value) -> Self {
+        Self {
+            name: name.into(),
+            args,
+        }
+    }
+}
+
+/// What one call of a batch returned and how long it ran, queueing included.
+#[derive(Debug)]
+pub struct ToolCallOutcome {
+    pub result: Result<Value>,
+    pub elapsed: Duration,
+}
+
+impl ToolRegistry {
+    /// Whether `name` only reads the workspace and may run alongside other
+    /// read-only calls.
+    pub fn is_read_only_tool(&self, name: &str) -> bool {
+        self.registration(name).is_some_and(|registration| {
+            matches!(
+                registration.capability(),
+                CapabilityLevel::FileReading | CapabilityLevel::FileListing
+            )
+        })
+    }
+
+    /// Execute `calls`, running consecutive read-only calls concurrently.
+    /// Outcomes are in the order of `calls`. A read-only call whose policy
+    /// would prompt runs on its own, so prompts never overlap.
+    pub async fn execute_tools_parallel(
+        &mut self,
+        calls: Vec<ToolCallRequest>,
+        cancel: CancellationToken,
+    ) -> Vec<ToolCallOutcome> {
+        let limit = self.concurrency.max_parallel_reads();
+        let mut outcomes = Vec::with_capacity(calls.len());
+        let mut calls = calls.into_iter().peekable();
+        while let Some(call) = calls.next() {
+            if limit < 2 || !self.runs_in_parallel(&call.name) {
+                outcomes.push(self.timed_call(call, cancel.clone()).await);
+                continue;
+            }
+            let mut batch = vec![call];
+            while let Some(next) = calls.next_if(|next| self.runs_in_parallel(&next.name)) {
+                batch.push(next);
+            }
+            let forks: Vec<(ToolRegistry, ToolCallRequest)> = batch
+                .into_iter()
+                .map(|call| (self.fork_for(&call.name), call))
+                .collect();
+            let finished: Vec<ToolCallOutcome> = stream::iter(forks)
+                .map(|(mut registry, call)| {
+                    let cancel = cancel.clone();
+                    async move { registry.timed_call(call, cancel).await }
+                })
+                .buffered(limit)
+                .collect()
+                .await;
+            outcomes.extend(finished);
+        }
+        outcomes
+    }
+
+    async fn timed_call(
+        &mut self,
+        call: ToolCallRequest,
+        cancel: CancellationToken,
+    ) -> ToolCallOutcome {
+        let started = Instant::now();
+        let result = self
+            .execute_tool_cancellable(&call.name, call.args, cancel)
+            .await;
+        ToolCallOutcome {
+            result,
+            elapsed: started.elapsed(),
+        }
+    }
+
+    /// Read-only and allowed without asking.
+    fn runs_in_parallel(&self, name: &str) -> bool {
+        if !self.is_read_only_tool(name) {
+            return false;
+        }
+        if self.preapproved_tools.contains(name) {
+            return true;
+        }
+        if let Some(allowlist) = &self.full_auto_allowlist {
+            return allowlist.contains(name);
+        }
+        self.tool_policy
+            .as_ref()
+            .is_none_or(|policy| policy.get_policy(name) == ToolPolicy::Allow)
+    }
+
+    /// Clone with no pending approvals, for calls made away from this
+    /// registry, such as a sub-agent's.
+    pub fn fork(&self) -> ToolRegistry {
+        let mut fork = self.clone();
+        fork.preapproved_tools.clear();
+        fork
+    }
+
+    /// Clone that runs one call of a batch. A pending approval of `name`
+    /// moves to the clone, as the call would have used it up here.
+    fn fork_for(&mut self, name: &str) -> ToolRegistry {
+        let mut fork = self.fork();
+        if self.preapproved_tools.remove(name) {
+            fork.preapproved_tools.insert(name.to_string());
+        }
+        fork
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::ToolRegistration;
+    use super::*;
+    use crate::config::{ConcurrencyConfig, ToolsConfig};
+    use crate::tools::traits::Tool;
+    use async_trait::async_trait;
+    use serde_json::json;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tempfile::TempDir;
+
+    /// Sleeps for `ms` and records how many calls were running at once.
+    struct SlowTool {
+        name: &'static str,
+        running: Arc<AtomicUsize>,
+        peak: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Tool for SlowTool {
+        async fn execute(&self, args: Value) -> Result<Value> {
+            let now = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(now, Ordering::SeqCst);
+            let ms = args["ms"].as_u64().unwrap_or_default();
+            tokio::time::sleep(Duration::from_millis(ms)).await;
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            Ok(json!({ "success": true, "tool": self.name, "ms": ms }))
+        }
+
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn description(&self) -> &'static str {
+            "Sleeps for the given number of milliseconds"
+        }
+    }
+
+    fn registry(max_parallel_reads: usize) -> Result<(TempDir, ToolRegistry, Arc<AtomicUsize>)> {
+        let temp_dir = TempDir::new()?;
+        let mut registry = ToolRegistry::new(temp_dir.path().to_path_buf());
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        for (name, capability) in [
+            ("slow_read", CapabilityLevel::FileReading),
+            ("slow_write", CapabilityLevel::Editing),
+        ] {
+            registry.register_tool(ToolRegistration::from_tool_instance(
+                name,
+                capability,
+                SlowTool {
+                    name,
+                    running: running.clone(),
+                    peak: peak.clone(),
+                },
+            ))?;
+        }
+        registry.sync_policy_available_tools();
+        registry.allow_all_tools().ok();
+        registry.apply_config_policies(&ToolsConfig {
+            concurrency: ConcurrencyConfig {
+                max_parallel: 8,
+                max_parallel_reads,
+                ..Default::default()
+            },
+            ..Default::default()
+        })?;
+        registry.allow_all_tools().ok();
+        Ok((temp_dir, registry, peak))
+    }
+
+    fn tools_in_order(outcomes: &[ToolCallOutcome]) -> Vec<(String, u64)> {
+        outcomes
+            .iter()
+            .map(|outcome| {
+                let output = outcome.result.as_ref().expect("tool ran");
+                (
+                    output["tool"].as_str().unwrap_or_default().to_string(),
+                    output["ms"].as_u64().unwrap_or_default(),
+                )
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn runs_read_only_calls_together_and_keeps_their_order() -> Result<()> {
+        let (_dir, mut registry, peak) = registry(3)?;
+        let calls = vec![
+            ToolCallRequest::new("slow_read", json!({ "ms": 120 })),
+            ToolCallRequest::new("slow_read", json!({ "ms": 10 })),
+            ToolCallRequest::new("slow_read", json!({ "ms": 60 })),
+            ToolCallRequest::new("slow_write", json!({ "ms": 5 })),
+            ToolCallRequest::new("slow_read", json!({ "ms": 1 })),
+        ];
+
+        let outcomes = registry
+            .execute_tools_parallel(calls, CancellationToken::new())
+            .await;
+
+        assert_eq!(
+            tools_in_order(&outcomes),
+            vec![
+                ("slow_read".to_string(), 120),
+                ("slow_read".to_string(), 10),
+                ("slow_read".to_string(), 60),
+                ("slow_write".to_string(), 5),
+                ("slow_read".to_string(), 1),
+            ]
+        );
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+        assert!(registry.is_read_only_tool("slow_read"));
+        assert!(!registry.is_read_only_tool("slow_write"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn a_limit_of_one_runs_calls_in_turn() -> Result<()> {
+        let (_dir, mut registry, peak) = registry(1)?;
+        let calls = (0..3)
+            .map(|ms| ToolCallRequest::new("slow_read", json!({ "ms": ms * 10 })))
+            .collect();
+
+        let outcomes = registry
+            .execute_tools_parallel(calls, CancellationToken::new())
+            .await;
+
+        assert_eq!(outcomes.len(), 3);
+        assert_eq!(peak.load(Ordering::SeqCst), 1);
+        Ok(())
+    }
+}
@@ -5,6 +5,9 @@ use futures::future::BoxFuture;
 use serde_json::Value;
 use std::sync::Arc;
 
+/// Schema version assigned to tools that do not declare one explicitly.
+pub const DEFAULT_TOOL_SCHEMA_VERSION: u32 = 1;
+
 pub type ToolExecutorFn =
     for<'a> fn(&'a mut ToolRegistry, Value) -> BoxFuture<'a, anyhow::Result<Value>>;
 
@@ -14,12 +17,22 @@ pub enum ToolHandler {
     TraitObject(Arc<dyn Tool>),
 }
 
+/// Argument that was renamed or retired in a later schema version of a tool.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeprecatedArgument {
+    pub name: &'static str,
+    pub replacement: Option<&'static str>,
+    pub since_version: u32,
+}
+
 #[derive(Clone)]
 pub struct ToolRegistration {
     name: &'static str,
     capability: CapabilityLevel,
     uses_pty: bool,
     expose_in_llm: bool,
+    schema_version: u32,
+    deprecated_arguments: Vec<DeprecatedArgument>,
     handler: ToolHandler,
 }
 
@@ -35,6 +48,8 @@ impl ToolRegistration {
             capability,
             uses_pty,
             expose_in_llm: true,
+            schema_version: DEFAULT_TOOL_SCHEMA_VERSION,
+            deprecated_arguments: Vec::new(),
             handler: ToolHandler::RegistryFn(executor),
         }
     }
@@ -45,6 +60,8 @@ impl ToolRegistration {
             capability,
             uses_pty: false,
             expose_in_llm: true,
+            schema_version: DEFAULT_TOOL_SCHEMA_VERSION,
+            deprecated_arguments: Vec::new(),
             handler: ToolHandler::TraitObject(tool),
         }
     }
@@ -66,6 +83,27 @@ impl ToolRegistration {
         self
     }
 
+    pub fn with_schema_version(mut self, version: u32) -> Self {
+        self.schema_version = version;
+        self
+    }
+
+    /// Mark `name` as deprecated since `since_version`. When a replacement is
+    /// given, calls using the old argument are migrated to it automatically.
+    pub fn with_deprecated_argument(
+        mut self,
+        name: &'static str,
+        replacement: Option<&'static str>,
+        since_version: u32,
+    ) -> Self {
+        self.deprecated_arguments.push(DeprecatedArgument {
+            name,
+            replacement,
+            since_version,
+        });
+        self
+    }
+
     pub fn name(&self) -> &'static str {
         self.name
     }
@@ -82,7 +120,100 @@ impl ToolRegistration {
         self.expose_in_llm
     }
 
+    pub fn schema_version(&self) -> u32 {
+        self.schema_version
+    }
+
+    pub fn deprecated_arguments(&self) -> &[DeprecatedArgument] {
+        &self.deprecated_arguments
+    }
+
     pub fn handler(&self) -> ToolHandler {
         self.handler.clone()
     }
+
+    /// Rewrite arguments written against an older schema into the current form,
+    /// returning the migrated arguments and one warning per deprecated argument used.
+    pub fn migrate_arguments(&self, mut args: Value) -> (Value, Vec<String>) {
+        let mut warnings = Vec::new();
+        let Some(object) = args.as_object_mut() else {
+            return (args, warnings);
+        };
+
+        for deprecated in &self.deprecated_arguments {
+            let Some(value) = object.remove(deprecated.name) else {
+                continue;
+            };
+            let prefix = format!(
+                "Argument '{}' of tool '{}' is deprecated since schema v{}",
+                deprecated.name, self.name, deprecated.since_version
+            );
+            match deprecated.replacement {
+                Some(replacement) if object.contains_key(replacement) => {
+                    warnings.push(format!(
+                        "{prefix}; ignored because '{replacement}' was also provided"
+                    ));
+                }
+                Some(replacement) => {
+                    object.insert(replacement.to_string(), value);
+                    warnings.push(format!("{prefix}; migrated to '{replacement}'"));
+                }
+                None => {
+                    object.insert(deprecated.name.to_string(), value);
+                    warnings.push(format!("{prefix} and will be removed"));
+                }
+            }
+        }
+
+        (args, warnings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn noop_executor<'a>(
+        _registry: &'a mut ToolRegistry,
+        args: Value,
+    ) -> BoxFuture<'a, anyhow::Result<Value>> {
+        Box::pin(async move { Ok(args) })
+    }
+
+    fn registration() -> ToolRegistration {
+        ToolRegistration::new("search", CapabilityLevel::CodeSearch, false, noop_executor)
+            .with_schema_version(3)
+            .with_deprecated_argument("pattern", Some("query"), 2)
+            .with_deprecated_argument("legacy_mode", None, 3)
+    }
+
+    #[test]
+    fn migrates_renamed_arguments() {
+        let (args, warnings) = registration().migrate_arguments(json!({"pattern": "foo"}));
+        assert_eq!(args, json!({"query": "foo"}));
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("since schema v2"));
+        assert!(warnings[0].contains("migrated to 'query'"));
+    }
+
+    #[test]
+    fn keeps_current_argument_when_both_forms_present() {
+        let (args, warnings) =
+            registration().migrate_arguments(json!({"pattern": "old", "query": "new"}));
+        assert_eq!(args, json!({"query": "new"}));
+        assert!(warnings[0].contains("ignored"));
+    }
+
+    #[test]
+    fn warns_without_migrating_retired_arguments() {
+        let registration = registration();
+        assert_eq!(registration.schema_version(), 3);
+        let (args, warnings) = registration.migrate_arguments(json!({"legacy_mode": true}));
+        assert_eq!(args, json!({"legacy_mode": true}));
+        assert!(warnings[0].contains("will be removed"));
+
+        let (_, warnings) = registration.migrate_arguments(json!({"query": "foo"}));
+        assert!(warnings.is_empty());
+    }
 }
@@ -1,5 +1,6 @@
 //! Search tool implementation with multiple modes
 
+use super::cancellation::ToolContext;
 use super::traits::{CacheableTool, ModeTool, Tool};
 use crate::config::constants::tools;
 use crate::tools::grep_search::{GrepSearchInput, GrepSearchManager};
@@ -24,8 +25,18 @@ impl SearchTool {
         }
     }
 
+    async fn dispatch(&self, mode: &str, args: Value, context: &ToolContext) -> Result<Value> {
+        match mode {
+            "exact" => self.execute_exact(args, context).await,
+            "fuzzy" => self.execute_fuzzy(args, context).await,
+            "multi" => self.execute_multi(args, context).await,
+            "similarity" => self.execute_similarity(args, context).await,
+            _ => Err(anyhow!("Unsupported search mode: {}", mode)),
+        }
+    }
+
     /// Execute exact search mode
-    async fn execute_exact(&self, args: Value) -> Result<Value> {
+    async fn execute_exact(&self, args: Value, context: &ToolContext) -> Result<Value> {
         let pattern = args
             .get("pattern")
             .and_then(|p| p.as_str())
@@ -52,9 +63,13 @@ impl SearchTool {
             glob_pattern: None,
             context_lines: Some(0),
             include_hidden: Some(false),
+            include_generated: args.get("include_generated").and_then(|v| v.as_bool()),
         };
 
-        let result = self.grep_search.perform_search(input.clone()).await?;
+        let result = self
+            .grep_search
+            .perform_search(input.clone(), &context.cancel)
+            .await?;
 
         // Response formatting
         let concise = args
@@ -95,7 +110,7 @@ impl SearchTool {
     }
 
     /// Execute fuzzy search mode
-    async fn execute_fuzzy(&self, args: Value) -> Result<Value> {
+    async fn execute_fuzzy(&self, args: Value, context: &ToolContext) -> Result<Value> {
         let pattern = args
             .get("pattern")
             .and_then(|p| p.as_str())
@@ -122,9 +137,13 @@ impl SearchTool {
             glob_pattern: None,
             context_lines: Some(0),
             include_hidden: Some(false),
+            include_generated: args.get("include_generated").and_then(|v| v.as_bool()),
         };
 
-        let result = self.grep_search.perform_search(input.clone()).await?;
+        let result = self
+            .grep_search
+            .perform_search(input.clone(), &context.cancel)
+            .await?;
 
         // Response formatting
         let concise = args
@@ -167,7 +186,7 @@ impl SearchTool {
     }
 
     /// Execute multi-pattern search mode
-    async fn execute_multi(&self, args: Value) -> Result<Value> {
+    async fn execute_multi(&self, args: Value, context: &ToolContext) -> Result<Value> {
         let args_obj = args
             .as_object()
             .ok_or_else(|| anyhow!("Error: Invalid 'multi' arguments. Required: {{ patterns: string[] }}. Optional: {{ logic: 'AND'|'OR' }}. Example: grep_search({{\"mode\": \"multi\", \"patterns\": [\"fn \\w+\", \"use \\w+\"], \"logic\": \"AND\"}})"))?;
@@ -192,7 +211,8 @@ impl SearchTool {
                     obj.insert("pattern".to_string(), json!(pattern_str));
                 }
 
-                match self.execute_exact(pattern_args).await {
+                context.check()?;
+                match self.execute_exact(pattern_args, context).await {
                     Ok(result) => {
                         if let Some(matches) = result.get("matches").and_then(|m| m.as_array()) {
                             all_results.extend(matches.clone());
@@ -220,7 +240,7 @@ impl SearchTool {
     }
 
     /// Execute similarity search mode
-    async fn execute_similarity(&self, args: Value) -> Result<Value> {
+    async fn execute_similarity(&self, args: Value, context: &ToolContext) -> Result<Value> {
         let args_obj = args
             .as_object()
             .ok_or_else(|| anyhow!("Error: Invalid 'similarity' arguments. Required: {{ reference_file: string }}. Optional: {{ content_type: 'structure'|'imports'|'functions'|'all' }}. Example: grep_search({{\"mode\": \"similarity\", \"reference_file\": \"src/lib.rs\", \"content_type\": \"functions\"}})"))?;
@@ -255,7 +275,7 @@ impl SearchTool {
             obj.insert("logic".to_string(), json!("OR"));
         }
 
-        self.execute_multi(search_args).await
+        self.execute_multi(search_args, context).await
     }
 
     /// Apply AND logic to search results
@@ -404,13 +424,18 @@ impl SearchTool {
 #[async_trait]
 impl Tool for SearchTool {
     async fn execute(&self, args: Value) -> Result<Value> {
+        self.execute_with_context(args, &ToolContext::default())
+            .await
+    }
+
+    async fn execute_with_context(&self, args: Value, context: &ToolContext) -> Result<Value> {
         let args_clone = args.clone();
         let mode = args_clone
             .get("mode")
             .and_then(|m| m.as_str())
             .unwrap_or("exact");
 
-        self.execute_mode(mode, args).await
+        self.dispatch(mode, args, context).await
     }
 
     fn name(&self) -> &'static str {
@@ -429,13 +454,7 @@ impl ModeTool for SearchTool {
     }
 
     async fn execute_mode(&self, mode: &str, args: Value) -> Result<Value> {
-        match mode {
-            "exact" => self.execute_exact(args).await,
-            "fuzzy" => self.execute_fuzzy(args).await,
-            "multi" => self.execute_multi(args).await,
-            "similarity" => self.execute_similarity(args).await,
-            _ => Err(anyhow!("Unsupported search mode: {}", mode)),
-        }
+        self.dispatch(mode, args, &ToolContext::default()).await
     }
 }
 
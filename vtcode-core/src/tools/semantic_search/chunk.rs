@@ -0,0 +1,196 @@
+//! Splitting files into chunks along their definitions
+//!
+//! A definition that fits in `max_lines` is one chunk, together with the doc
+//! comments and attributes right above it. A longer one is split at the
+//! definitions nested in it (methods of a class or impl), with the lines
+//! between them chunked under the outer definition's name. Lines outside any
+//! definition, and files tree-sitter cannot parse, are cut into windows of
+//! `max_lines`.
+
+use crate::tools::tree_sitter::OutlineEntry;
+
+/// A line range of a file, with the definition it belongs to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Chunk {
+    /// 1-based first line
+    pub start_line: usize,
+    /// 1-based last line, inclusive
+    pub end_line: usize,
+    pub symbol: Option<String>,
+    pub kind: Option<&'static str>,
+}
+
+/// Chunks covering the non-blank lines of `lines`.
+pub(super) fn chunk_file(outline: &[OutlineEntry], lines: &[&str], max_lines: usize) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+    if !lines.is_empty() {
+        let splitter = Splitter {
+            outline,
+            lines,
+            max_lines: max_lines.max(1),
+        };
+        splitter.cover(0, 1, lines.len(), None, &mut chunks);
+    }
+    chunks
+}
+
+struct Splitter<'a> {
+    outline: &'a [OutlineEntry],
+    lines: &'a [&'a str],
+    max_lines: usize,
+}
+
+impl Splitter<'_> {
+    /// Chunk lines `start..=end` using the definitions at `depth` inside them.
+    fn cover(
+        &self,
+        depth: usize,
+        start: usize,
+        end: usize,
+        parent: Option<&OutlineEntry>,
+        chunks: &mut Vec<Chunk>,
+    ) {
+        let mut cursor = start;
+        let nested = self.outline.iter().filter(|entry| {
+            entry.depth == depth && entry.line >= start && entry.end_line.min(end) >= entry.line
+        });
+        for entry in nested {
+            if entry.line < cursor || entry.line > end {
+                continue;
+            }
+            let entry_end = entry.end_line.min(end);
+            let entry_start = self.leading_comments(cursor, entry.line);
+            if entry_start > cursor {
+                self.windows(cursor, entry_start - 1, parent, chunks);
+            }
+            if entry_end - entry.line < self.max_lines {
+                chunks.push(Chunk {
+                    start_line: entry_start,
+                    end_line: entry_end,
+                    symbol: Some(entry.name.clone()),
+                    kind: Some(entry.kind_label()),
+                });
+            } else {
+                self.cover(depth + 1, entry_start, entry_end, Some(entry), chunks);
+            }
+            cursor = entry_end + 1;
+        }
+        if cursor <= end {
+            self.windows(cursor, end, parent, chunks);
+        }
+    }
+
+    /// First line of the comments and attributes directly above `line`, not
+    /// before `floor`.
+    fn leading_comments(&self, floor: usize, line: usize) -> usize {
+        let mut start = line;
+        while start > floor {
+            let above = self.lines[start - 2].trim_start();
+            let is_comment = ["//", "#", "/*", "*", "@", "--", "\"\"\""]
+                .iter()
+                .any(|prefix| above.starts_with(prefix));
+            if !is_comment {
+                break;
+            }
+            start -= 1;
+        }
+        start
+    }
+
+    /// Cut `start..=end` into windows, dropping blank lines at their edges.
+    fn windows(
+        &self,
+        start: usize,
+        end: usize,
+        parent: Option<&OutlineEntry>,
+        chunks: &mut Vec<Chunk>,
+    ) {
+        let blank = |line: usize| self.lines[line - 1].trim().is_empty();
+        let mut from = start;
+        while from <= end {
+            let to = (from + self.max_lines - 1).min(end);
+            let (mut first, mut last) = (from, to);
+            while first <= last && blank(first) {
+                first += 1;
+            }
+            while last > first && blank(last) {
+                last -= 1;
+            }
+            if first <= last {
+                chunks.push(Chunk {
+                    start_line: first,
+                    end_line: last,
+                    symbol: parent.map(|entry| entry.name.clone()),
+                    kind: parent.map(OutlineEntry::kind_label),
+                });
+            }
+            from = to + 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::tree_sitter::SymbolKind;
+
+    fn entry(
+        name: &str,
+        kind: SymbolKind,
+        line: usize,
+        end_line: usize,
+        depth: usize,
+    ) -> OutlineEntry {
+        OutlineEntry {
+            name: name.to_string(),
+            kind,
+            line,
+            end_line,
+            depth,
+        }
+    }
+
+    #[test]
+    fn splits_long_definitions_at_nested_ones() {
+        let source = "use std::fmt;\n\nstruct Parser;\n\nimpl Parser {\n    fn new() -> Self {\n        Parser\n    }\n\n    /// Parse the input.\n    fn parse(&self) {\n        todo!()\n    }\n}\n";
+        let lines: Vec<&str> = source.lines().collect();
+        let outline = [
+            entry("Parser", SymbolKind::Struct, 3, 3, 0),
+            entry("Parser", SymbolKind::Module, 5, 14, 0),
+            entry("new", SymbolKind::Function, 6, 8, 1),
+            entry("parse", SymbolKind::Function, 11, 13, 1),
+        ];
+
+        let spans = |chunks: Vec<Chunk>| -> Vec<(usize, usize, Option<String>)> {
+            chunks
+                .into_iter()
+                .map(|chunk| (chunk.start_line, chunk.end_line, chunk.symbol))
+                .collect()
+        };
+        let whole = chunk_file(&outline, &lines, 20);
+        assert_eq!(
+            spans(whole),
+            vec![
+                (1, 1, None),
+                (3, 3, Some("Parser".into())),
+                (5, 14, Some("Parser".into())),
+            ]
+        );
+
+        let split = chunk_file(&outline, &lines, 4);
+        assert_eq!(
+            spans(split),
+            vec![
+                (1, 1, None),
+                (3, 3, Some("Parser".into())),
+                (5, 5, Some("Parser".into())),
+                (6, 8, Some("new".into())),
+                (10, 13, Some("parse".into())),
+                (14, 14, Some("Parser".into())),
+            ]
+        );
+
+        let plain: Vec<&str> = (0..10).map(|_| "text").collect();
+        assert_eq!(spans(chunk_file(&[], &plain, 4)).len(), 3);
+    }
+}
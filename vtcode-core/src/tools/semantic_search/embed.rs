@@ -0,0 +1,292 @@
+//! Embedding providers for the semantic index
+//!
+//! `local` hashes identifiers, their camelCase and snake_case parts, and
+//! character trigrams of those parts into a fixed-size vector. It needs no
+//! model and no network and is deterministic, but it only matches related
+//! wording. `ollama` and `openai` call an embedding model, which also matches
+//! code that is described differently from the query.
+
+use crate::config::constants::urls;
+use crate::config::{EmbeddingProvider, SemanticSearchConfig};
+use crate::utils::network;
+use anyhow::{Context, Result, anyhow, bail};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::json;
+
+/// Dimensions of the local embedding
+const LOCAL_DIMENSIONS: usize = 512;
+const DEFAULT_OLLAMA_MODEL: &str = "nomic-embed-text";
+const DEFAULT_OPENAI_MODEL: &str = "text-embedding-3-small";
+const DEFAULT_OPENAI_KEY_ENV: &str = "OPENAI_API_KEY";
+
+/// Words too common in code and prose to say anything about a chunk.
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "const", "def", "do", "else", "fn", "for",
+    "from", "func", "function", "if", "impl", "in", "is", "it", "let", "mut", "of", "on", "or",
+    "pub", "return", "self", "the", "this", "to", "use", "var", "where", "with",
+];
+
+#[derive(Debug, Clone)]
+pub(super) enum Embedder {
+    Local,
+    Ollama {
+        client: Client,
+        url: String,
+        model: String,
+    },
+    OpenAI {
+        client: Client,
+        url: String,
+        model: String,
+        api_key: Option<String>,
+    },
+}
+
+impl Embedder {
+    pub fn from_config(config: &SemanticSearchConfig) -> Result<Self> {
+        let base_url = config
+            .base_url
+            .as_deref()
+            .map(|url| url.trim().trim_end_matches('/'));
+        Ok(match config.provider {
+            EmbeddingProvider::Local => Self::Local,
+            EmbeddingProvider::Ollama => {
+                let root = base_url.unwrap_or(urls::OLLAMA_API_BASE);
+                let root = root
+                    .strip_suffix("/v1")
+                    .or_else(|| root.strip_suffix("/api"))
+                    .unwrap_or(root);
+                Self::Ollama {
+                    client: network::client(Some("ollama"))?,
+                    url: format!("{root}/api/embed"),
+                    model: config
+                        .model
+                        .clone()
+                        .unwrap_or_else(|| DEFAULT_OLLAMA_MODEL.to_string()),
+                }
+            }
+            EmbeddingProvider::OpenAI => {
+                let key_env = config
+                    .api_key_env
+                    .as_deref()
+                    .unwrap_or(DEFAULT_OPENAI_KEY_ENV);
+                let api_key = std::env::var(key_env).ok().filter(|key| !key.is_empty());
+                if api_key.is_none() && base_url.is_none() {
+                    bail!(
+                        "Error: semantic_search uses OpenAI embeddings but {key_env} is not set. Set it, or choose provider = \"local\" or \"ollama\" under [tools.semantic_search]."
+                    );
+                }
+                Self::OpenAI {
+                    client: network::client(Some("openai"))?,
+                    url: format!("{}/embeddings", base_url.unwrap_or(urls::OPENAI_API_BASE)),
+                    model: config
+                        .model
+                        .clone()
+                        .unwrap_or_else(|| DEFAULT_OPENAI_MODEL.to_string()),
+                    api_key,
+                }
+            }
+        })
+    }
+
+    /// Provider and model; an index built by another embedder is discarded.
+    pub fn id(&self) -> String {
+        match self {
+            Self::Local => "local/hashed-v1".to_string(),
+            Self::Ollama { model, .. } => format!("ollama/{model}"),
+            Self::OpenAI { model, .. } => format!("openai/{model}"),
+        }
+    }
+
+    /// Unit-length vectors for `texts`, in order.
+    pub async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut vectors = match self {
+            Self::Local => texts.iter().map(|text| local_embedding(text)).collect(),
+            Self::Ollama { client, url, model } => {
+                #[derive(Deserialize)]
+                struct Response {
+                    embeddings: Vec<Vec<f32>>,
+                }
+                let response = client
+                    .post(url)
+                    .json(&json!({ "model": model, "input": texts }))
+                    .send()
+                    .await
+                    .with_context(|| {
+                        format!(
+                            "Error: Could not reach Ollama at {url}. Is `ollama serve` running and `ollama pull {model}` done?"
+                        )
+                    })?;
+                let response: Response = read_json(response, "Ollama").await?;
+                response.embeddings
+            }
+            Self::OpenAI {
+                client,
+                url,
+                model,
+                api_key,
+            } => {
+                #[derive(Deserialize)]
+                struct Item {
+                    index: usize,
+                    embedding: Vec<f32>,
+                }
+                #[derive(Deserialize)]
+                struct Response {
+                    data: Vec<Item>,
+                }
+                let mut request = client
+                    .post(url)
+                    .json(&json!({ "model": model, "input": texts }));
+                if let Some(key) = api_key {
+                    request = request.bearer_auth(key);
+                }
+                let response = request
+                    .send()
+                    .await
+                    .with_context(|| format!("Error: Could not reach {url} for embeddings"))?;
+                let mut response: Response = read_json(response, "The embeddings API").await?;
+                response.data.sort_by_key(|item| item.index);
+                response
+                    .data
+                    .into_iter()
+                    .map(|item| item.embedding)
+                    .collect()
+            }
+        };
+        if vectors.len() != texts.len() {
+            return Err(anyhow!(
+                "Error: The embedding model returned {} vectors for {} inputs",
+                vectors.len(),
+                texts.len()
+            ));
+        }
+        for vector in &mut vectors {
+            normalize(vector);
+        }
+        Ok(vectors)
+    }
+}
+
+async fn read_json<T: for<'de> Deserialize<'de>>(
+    response: reqwest::Response,
+    service: &str,
+) -> Result<T> {
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        let body: String = body.chars().take(400).collect();
+        bail!("Error: {service} answered {status} to the embedding request: {body}");
+    }
+    response.json().await.with_context(|| {
+        format!("Error: {service} sent an embedding response that could not be read")
+    })
+}
+
+fn normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|value| value * value).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for value in vector {
+            *value /= norm;
+        }
+    }
+}
+
+/// FNV-1a, stable across builds so stored vectors stay comparable.
+fn feature_hash(feature: &str) -> u64 {
+    feature.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Lowercase parts of `word`, split at `_`, `-` and camelCase humps.
+fn word_parts(word: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut previous_lower = false;
+    for ch in word.chars() {
+        if ch == '_' || ch == '-' {
+            previous_lower = false;
+            if !current.is_empty() {
+                parts.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if ch.is_uppercase() && previous_lower && !current.is_empty() {
+            parts.push(std::mem::take(&mut current));
+        }
+        previous_lower = ch.is_lowercase() || ch.is_ascii_digit();
+        current.extend(ch.to_lowercase());
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+fn local_embedding(text: &str) -> Vec<f32> {
+    let mut vector = vec![0.0f32; LOCAL_DIMENSIONS];
+    let mut add = |feature: &str, weight: f32| {
+        let hash = feature_hash(feature);
+        let slot = (hash % LOCAL_DIMENSIONS as u64) as usize;
+        let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+        vector[slot] += sign * weight;
+    };
+    for word in text
+        .split(|ch: char| !(ch.is_alphanumeric() || ch == '_' || ch == '-'))
+        .filter(|word| word.len() > 1)
+    {
+        let parts = word_parts(word);
+        if parts.len() > 1 {
+            add(&word.to_lowercase(), 0.5);
+        }
+        for part in parts {
+            if part.len() < 2 || STOPWORDS.contains(&part.as_str()) {
+                continue;
+            }
+            add(&part, 1.0);
+            let chars: Vec<char> = part.chars().collect();
+            if chars.len() >= 4 {
+                for gram in chars.windows(3) {
+                    add(&format!("#{}", gram.iter().collect::<String>()), 0.25);
+                }
+            }
+        }
+    }
+    // Dampen repeated features so one long identifier list does not dominate.
+    for value in &mut vector {
+        *value = value.signum() * value.abs().sqrt();
+    }
+    vector
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn similarity(a: &str, b: &str) -> f32 {
+        let (mut a, mut b) = (local_embedding(a), local_embedding(b));
+        normalize(&mut a);
+        normalize(&mut b);
+        a.iter().zip(&b).map(|(x, y)| x * y).sum()
+    }
+
+    #[test]
+    fn local_embedding_matches_identifier_parts() {
+        assert_eq!(
+            word_parts("parseConfigFile"),
+            vec!["parse", "config", "file"]
+        );
+        assert_eq!(word_parts("HTTP_client"), vec!["http", "client"]);
+
+        let query = "where is the config file parsed";
+        let related = similarity(query, "fn parse_config_file(path: &Path) -> Result<Config>");
+        let unrelated = similarity(query, "fn render_spinner(frame: usize) -> char");
+        assert!(related > 0.3, "{related}");
+        assert!(related > unrelated * 3.0, "{related} vs {unrelated}");
+    }
+}
@@ -0,0 +1,111 @@
+//! The embeddings index kept in `.vtcode/index/`
+//!
+//! `semantic.json` lists the indexed files with their modification time and
+//! chunks; `semantic.vectors` holds the chunk vectors as little-endian `f32`,
+//! in the order the chunks appear in `semantic.json`. An index written by a
+//! different embedder or format version is ignored and rebuilt.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::SystemTime;
+
+pub(super) const INDEX_DIR: &str = ".vtcode/index";
+const META_FILE: &str = "semantic.json";
+const VECTORS_FILE: &str = "semantic.vectors";
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct IndexedChunk {
+    pub start_line: usize,
+    pub end_line: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(skip)]
+    pub vector: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct IndexedFile {
+    pub modified: Option<SystemTime>,
+    pub size: u64,
+    pub chunks: Vec<IndexedChunk>,
+}
+
+impl IndexedFile {
+    /// Whether the file on disk still matches what was indexed.
+    pub fn is_current(&self, modified: Option<SystemTime>, size: u64) -> bool {
+        self.modified.is_some() && self.modified == modified && self.size == size
+    }
+}
+
+/// Indexed files by workspace-relative path with `/` separators.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct SemanticIndex {
+    version: u32,
+    pub embedder: String,
+    pub dimensions: usize,
+    pub files: BTreeMap<String, IndexedFile>,
+}
+
+impl SemanticIndex {
+    pub fn new(embedder: String) -> Self {
+        Self {
+            version: FORMAT_VERSION,
+            embedder,
+            dimensions: 0,
+            files: BTreeMap::new(),
+        }
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.files.values().map(|file| file.chunks.len()).sum()
+    }
+
+    /// The stored index for `embedder`, or an empty one when there is none,
+    /// it is unreadable, or another embedder wrote it.
+    pub fn load(workspace: &Path, embedder: &str) -> Self {
+        Self::read(workspace)
+            .ok()
+            .filter(|index| index.version == FORMAT_VERSION && index.embedder == embedder)
+            .unwrap_or_else(|| Self::new(embedder.to_string()))
+    }
+
+    fn read(workspace: &Path) -> Result<Self> {
+        let dir = workspace.join(INDEX_DIR);
+        let meta = std::fs::read(dir.join(META_FILE))?;
+        let mut index: Self = serde_json::from_slice(&meta)?;
+        let bytes = std::fs::read(dir.join(VECTORS_FILE))?;
+        anyhow::ensure!(
+            bytes.len() == index.chunk_count() * index.dimensions * 4,
+            "vector file does not match the index"
+        );
+        let mut values = bytes
+            .chunks_exact(4)
+            .map(|raw| f32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]));
+        for chunk in index.files.values_mut().flat_map(|file| &mut file.chunks) {
+            chunk.vector = values.by_ref().take(index.dimensions).collect();
+        }
+        Ok(index)
+    }
+
+    pub fn save(&self, workspace: &Path) -> Result<()> {
+        let dir = workspace.join(INDEX_DIR);
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        let mut bytes = Vec::with_capacity(self.chunk_count() * self.dimensions * 4);
+        for chunk in self.files.values().flat_map(|file| &file.chunks) {
+            for value in &chunk.vector {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        std::fs::write(dir.join(VECTORS_FILE), bytes)
+            .with_context(|| format!("Failed to write {}", dir.join(VECTORS_FILE).display()))?;
+        std::fs::write(dir.join(META_FILE), serde_json::to_vec(self)?)
+            .with_context(|| format!("Failed to write {}", dir.join(META_FILE).display()))?;
+        Ok(())
+    }
+}
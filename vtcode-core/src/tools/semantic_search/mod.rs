@@ -0,0 +1,508 @@
+//! Code search by meaning over an embeddings index
+//!
+//! [`SemanticSearchTool`] splits the workspace's source and documentation
+//! files into chunks along their tree-sitter definitions, embeds each chunk
+//! with the provider chosen under `[tools.semantic_search]` and stores the
+//! vectors in `.vtcode/index/`. A search first re-embeds the files whose
+//! modification time or size changed, then ranks every chunk by cosine
+//! similarity to the query. It complements `grep_search` and
+//! `ast_grep_search` when the words or shape of the code are not known.
+
+mod chunk;
+mod embed;
+mod index;
+
+use crate::config::SemanticSearchConfig;
+use crate::config::constants::tools;
+use crate::core::progress::Progress;
+use crate::tools::cancellation::ToolContext;
+use crate::tools::imports::source_walker;
+use crate::tools::traits::Tool;
+use crate::tools::tree_sitter::TreeSitterAnalyzer;
+use anyhow::{Context, Result, anyhow, bail};
+use async_trait::async_trait;
+use chunk::{Chunk, chunk_file};
+use embed::Embedder;
+use index::{INDEX_DIR, IndexedChunk, IndexedFile, SemanticIndex};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::sync::Mutex;
+
+const DEFAULT_MAX_RESULTS: usize = 8;
+const MAX_RESULTS: usize = 30;
+/// Lines of each result returned as its snippet
+const SNIPPET_LINES: usize = 12;
+const MAX_SNIPPET_LINE_CHARS: usize = 240;
+/// Characters of a chunk sent to the embedder
+const MAX_EMBED_CHARS: usize = 6_000;
+/// Larger files are usually generated or data and are not indexed
+const MAX_FILE_BYTES: u64 = 512 * 1024;
+
+/// Extensions of the files worth indexing: source code and documentation.
+const INDEXED_EXTENSIONS: &[&str] = &[
+    "rs", "py", "js", "jsx", "mjs", "cjs", "ts", "tsx", "go", "java", "kt", "swift", "c", "h",
+    "cc", "cpp", "hpp", "cs", "rb", "php", "scala", "lua", "sh", "bash", "zsh", "sql", "proto",
+    "graphql", "vue", "svelte", "css", "scss", "html", "md", "mdx", "rst", "txt", "toml", "yaml",
+    "yml",
+];
+
+#[derive(Debug, Deserialize)]
+struct SemanticSearchInput {
+    /// What the code does, in words
+    query: String,
+    /// Only search files under this path
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(default)]
+    max_results: Option<usize>,
+}
+
+/// A file that changed since it was indexed, chunked and ready to embed.
+struct PendingFile {
+    path: String,
+    modified: Option<SystemTime>,
+    size: u64,
+    chunks: Vec<Chunk>,
+    /// What is embedded for each chunk
+    texts: Vec<String>,
+}
+
+/// Files to embed again and every file the walk saw.
+struct Changes {
+    pending: Vec<PendingFile>,
+    seen: HashSet<String>,
+}
+
+#[derive(Clone)]
+pub struct SemanticSearchTool {
+    workspace_root: PathBuf,
+    config: SemanticSearchConfig,
+    /// Loaded on first search; `None` until then
+    index: Arc<Mutex<Option<SemanticIndex>>>,
+}
+
+impl SemanticSearchTool {
+    pub fn new(workspace_root: PathBuf) -> Self {
+        Self {
+            workspace_root,
+            config: SemanticSearchConfig::default(),
+            index: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn set_config(&mut self, config: SemanticSearchConfig) {
+        self.config = config;
+        self.index = Arc::new(Mutex::new(None));
+    }
+
+    async fn search(&self, input: SemanticSearchInput, mut progress: Progress) -> Result<Value> {
+        if !self.config.enabled {
+            bail!(
+                "semantic_search is disabled. Set [tools.semantic_search] enabled = true in vtcode.toml, or use grep_search."
+            );
+        }
+        let query = input.query.trim();
+        if query.is_empty() {
+            bail!("Error: 'query' must describe the code to find");
+        }
+        let scope = input
+            .path
+            .as_deref()
+            .map(|path| self.relative_path(path))
+            .transpose()?;
+        let max_results = input
+            .max_results
+            .unwrap_or(DEFAULT_MAX_RESULTS)
+            .clamp(1, MAX_RESULTS);
+        let embedder = Embedder::from_config(&self.config)?;
+
+        let mut guard = self.index.lock().await;
+        let index = match guard.take() {
+            Some(index) if index.embedder == embedder.id() => index,
+            _ => {
+                let root = self.workspace_root.clone();
+                let id = embedder.id();
+                tokio::task::spawn_blocking(move || SemanticIndex::load(&root, &id))
+                    .await
+                    .context("semantic_search task failed")?
+            }
+        };
+        let (mut index, changes, mut progress) = {
+            let root = self.workspace_root.clone();
+            let config = self.config.clone();
+            tokio::task::spawn_blocking(move || {
+                let changes = find_changes(&root, &config, &index, &mut progress);
+                (index, changes, progress)
+            })
+            .await
+            .context("semantic_search task failed")?
+        };
+        let changes = changes?;
+        let updated_files = changes.pending.len();
+        let outcome = self
+            .update(&mut index, &embedder, changes, &mut progress)
+            .await;
+        if updated_files > 0 {
+            index.save(&self.workspace_root)?;
+        }
+        let index = guard.insert(index);
+        outcome?;
+
+        let query_vector = embedder
+            .embed(&[query.to_string()])
+            .await?
+            .pop()
+            .ok_or_else(|| {
+                anyhow!("Error: The embedding model returned no vector for the query")
+            })?;
+        let mut ranked: Vec<(f32, &str, &IndexedChunk)> = index
+            .files
+            .iter()
+            .filter(|(path, _)| scope.as_deref().is_none_or(|scope| in_scope(path, scope)))
+            .flat_map(|(path, file)| file.chunks.iter().map(move |chunk| (path.as_str(), chunk)))
+            .map(|(path, chunk)| (dot(&query_vector, &chunk.vector), path, chunk))
+            .filter(|(score, _, _)| *score > 0.0)
+            .collect();
+        ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+        ranked.truncate(max_results);
+
+        let results: Vec<Value> = ranked
+            .into_iter()
+            .map(|(score, path, chunk)| {
+                let mut result = json!({
+                    "path": path,
+                    "start_line": chunk.start_line,
+                    "end_line": chunk.end_line,
+                    "score": (f64::from(score) * 1000.0).round() / 1000.0,
+                    "snippet": self.snippet(path, chunk),
+                });
+                if let Some(symbol) = &chunk.symbol {
+                    result["symbol"] = json!(symbol);
+                }
+                if let Some(kind) = &chunk.kind {
+                    result["kind"] = json!(kind);
+                }
+                result
+            })
+            .collect();
+        Ok(json!({
+            "success": true,
+            "query": query,
+            "results": results,
+            "embedder": index.embedder,
+            "indexed_files": index.files.len(),
+            "indexed_chunks": index.chunk_count(),
+            "updated_files": updated_files,
+        }))
+    }
+
+    /// Drop changed and deleted files from `index`, then embed the changed
+    /// ones, a few files per request. Files embedded before a failure stay in
+    /// the index.
+    async fn update(
+        &self,
+        index: &mut SemanticIndex,
+        embedder: &Embedder,
+        changes: Changes,
+        progress: &mut Progress,
+    ) -> Result<()> {
+        index.files.retain(|path, _| changes.seen.contains(path));
+        for file in &changes.pending {
+            index.files.remove(&file.path);
+        }
+        if changes.pending.is_empty() {
+            return Ok(());
+        }
+
+        let batch_size = self.config.batch_size.max(1);
+        progress.start_phase("embedding chunks", Some(changes.pending.len() as u64));
+        let mut pending = changes.pending.into_iter().peekable();
+        while pending.peek().is_some() {
+            let mut group = Vec::new();
+            let mut texts: Vec<String> = Vec::new();
+            while let Some(file) = pending
+                .next_if(|file| texts.is_empty() || texts.len() + file.texts.len() <= batch_size)
+            {
+                texts.extend(file.texts.iter().cloned());
+                group.push(file);
+            }
+            let mut vectors = Vec::with_capacity(texts.len());
+            for batch in texts.chunks(batch_size) {
+                vectors.extend(embedder.embed(batch).await?);
+            }
+            let mut vectors = vectors.into_iter();
+            for file in group {
+                let mut chunks = Vec::with_capacity(file.chunks.len());
+                for (chunk, vector) in file.chunks.into_iter().zip(vectors.by_ref()) {
+                    if index.dimensions == 0 {
+                        index.dimensions = vector.len();
+                    } else if vector.len() != index.dimensions {
+                        bail!(
+                            "Error: The embedding model returned {} dimensions instead of {}; delete {} to rebuild the index",
+                            vector.len(),
+                            index.dimensions,
+                            INDEX_DIR
+                        );
+                    }
+                    chunks.push(IndexedChunk {
+                        start_line: chunk.start_line,
+                        end_line: chunk.end_line,
+                        symbol: chunk.symbol,
+                        kind: chunk.kind.map(str::to_string),
+                        vector,
+                    });
+                }
+                index.files.insert(
+                    file.path,
+                    IndexedFile {
+                        modified: file.modified,
+                        size: file.size,
+                        chunks,
+                    },
+                );
+                progress.advance(file.size);
+            }
+        }
+        progress.finish_phase();
+        Ok(())
+    }
+
+    /// The first lines of `chunk`, as they are in the file now.
+    fn snippet(&self, path: &str, chunk: &IndexedChunk) -> String {
+        let Ok(text) = std::fs::read_to_string(self.workspace_root.join(path)) else {
+            return String::new();
+        };
+        let count = (chunk.end_line + 1 - chunk.start_line).min(SNIPPET_LINES);
+        text.lines()
+            .skip(chunk.start_line - 1)
+            .take(count)
+            .map(
+                |line| match line.char_indices().nth(MAX_SNIPPET_LINE_CHARS) {
+                    Some((cut, _)) => format!("{}…", &line[..cut]),
+                    None => line.to_string(),
+                },
+            )
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn relative_path(&self, path: &str) -> Result<String> {
+        let path = Path::new(path);
+        let relative = if path.is_absolute() {
+            path.strip_prefix(&self.workspace_root)
+                .map_err(|_| anyhow!("Path {} is outside the workspace", path.display()))?
+        } else {
+            path
+        };
+        Ok(relative
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("/"))
+    }
+}
+
+/// Walk the workspace and chunk the files that are new or changed.
+fn find_changes(
+    workspace: &Path,
+    config: &SemanticSearchConfig,
+    index: &SemanticIndex,
+    progress: &mut Progress,
+) -> Result<Changes> {
+    let entries: Vec<_> = source_walker(workspace)
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|kind| kind.is_file()))
+        .filter(|entry| {
+            entry
+                .path()
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .is_some_and(|extension| INDEXED_EXTENSIONS.contains(&extension))
+        })
+        .take(config.max_files)
+        .collect();
+    progress.start_phase("chunking files", Some(entries.len() as u64));
+
+    let mut analyzer = TreeSitterAnalyzer::new()?;
+    let mut changes = Changes {
+        pending: Vec::new(),
+        seen: HashSet::new(),
+    };
+    for entry in entries {
+        let metadata = entry.metadata().ok();
+        let size = metadata.as_ref().map_or(0, |meta| meta.len());
+        progress.advance(size);
+        if size > MAX_FILE_BYTES {
+            continue;
+        }
+        let path = entry
+            .path()
+            .strip_prefix(workspace)
+            .unwrap_or(entry.path())
+            .to_string_lossy()
+            .replace('\\', "/");
+        let modified = metadata.and_then(|meta| meta.modified().ok());
+        changes.seen.insert(path.clone());
+        if index
+            .files
+            .get(&path)
+            .is_some_and(|file| file.is_current(modified, size))
+        {
+            continue;
+        }
+        let Ok(text) = std::fs::read_to_string(entry.path()) else {
+            continue;
+        };
+        let outline = analyzer
+            .symbol_outline(entry.path(), &text)
+            .unwrap_or_default();
+        let lines: Vec<&str> = text.lines().collect();
+        let chunks = chunk_file(&outline, &lines, config.max_chunk_lines);
+        let texts = chunks
+            .iter()
+            .map(|chunk| embedding_text(&path, chunk, &lines))
+            .collect();
+        changes.pending.push(PendingFile {
+            path,
+            modified,
+            size,
+            chunks,
+            texts,
+        });
+    }
+    progress.finish_phase();
+    Ok(changes)
+}
+
+/// The chunk with its path and definition name, which often carry the
+/// meaning the code alone does not.
+fn embedding_text(path: &str, chunk: &Chunk, lines: &[&str]) -> String {
+    let mut text = String::from(path);
+    text.push('\n');
+    if let (Some(kind), Some(symbol)) = (chunk.kind, &chunk.symbol) {
+        text.push_str(&format!("{kind} {symbol}\n"));
+    }
+    for line in &lines[chunk.start_line - 1..chunk.end_line] {
+        if text.len() + line.len() >= MAX_EMBED_CHARS {
+            break;
+        }
+        text.push_str(line.trim_end());
+        text.push('\n');
+    }
+    text
+}
+
+fn in_scope(path: &str, scope: &str) -> bool {
+    scope.is_empty()
+        || path == scope
+        || path
+            .strip_prefix(scope)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+#[async_trait]
+impl Tool for SemanticSearchTool {
+    async fn execute(&self, args: Value) -> Result<Value> {
+        self.execute_with_context(args, &ToolContext::default())
+            .await
+    }
+
+    async fn execute_with_context(&self, args: Value, context: &ToolContext) -> Result<Value> {
+        let input: SemanticSearchInput = serde_json::from_value(args).context(
+            "Error: Invalid 'semantic_search' arguments. Required: { query: string }. Optional: { path: string, max_results: integer }",
+        )?;
+        self.search(input, context.progress()).await
+    }
+
+    fn name(&self) -> &'static str {
+        tools::SEMANTIC_SEARCH
+    }
+
+    fn description(&self) -> &'static str {
+        "Find code by what it does using an embeddings index of the workspace"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn tool(dir: &TempDir) -> SemanticSearchTool {
+        let mut tool = SemanticSearchTool::new(dir.path().to_path_buf());
+        tool.set_config(SemanticSearchConfig {
+            enabled: true,
+            ..SemanticSearchConfig::default()
+        });
+        tool
+    }
+
+    #[tokio::test]
+    async fn finds_definitions_and_reindexes_changed_files() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::write(
+            dir.path().join("src/config.rs"),
+            "/// Read the settings file from disk.\npub fn load_config_file(path: &Path) -> Config {\n    parse_settings(std::fs::read_to_string(path).unwrap())\n}\n\nfn render_spinner(frame: usize) -> char {\n    ['|', '/', '-'][frame % 3]\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("README.md"),
+            "# Demo\n\nSpinner frames are drawn while waiting.\n",
+        )
+        .unwrap();
+
+        let result = tool(&dir)
+            .execute(json!({ "query": "load the config settings file" }))
+            .await
+            .unwrap();
+        assert_eq!(result["indexed_files"], 2);
+        assert_eq!(result["updated_files"], 2);
+        let top = &result["results"][0];
+        assert_eq!(top["path"], "src/config.rs");
+        assert_eq!(top["symbol"], "load_config_file");
+        assert_eq!(top["start_line"], 1);
+        assert!(dir.path().join(INDEX_DIR).join("semantic.vectors").exists());
+
+        // A new tool loads the stored index and only embeds what changed.
+        let tool = tool(&dir);
+        let result = tool
+            .execute(json!({ "query": "spinner frame", "path": "src" }))
+            .await
+            .unwrap();
+        assert_eq!(result["updated_files"], 0);
+        assert_eq!(result["results"][0]["symbol"], "render_spinner");
+        assert!(
+            result["results"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .all(|hit| hit["path"] == "src/config.rs")
+        );
+
+        fs::remove_file(dir.path().join("README.md")).unwrap();
+        let result = tool.execute(json!({ "query": "spinner" })).await.unwrap();
+        assert_eq!(result["indexed_files"], 1);
+    }
+
+    #[tokio::test]
+    async fn refuses_when_disabled() {
+        let dir = TempDir::new().unwrap();
+        let result = SemanticSearchTool::new(dir.path().to_path_buf())
+            .execute(json!({ "query": "anything" }))
+            .await;
+        assert!(result.unwrap_err().to_string().contains("disabled"));
+    }
+}
@@ -5,11 +5,16 @@
 
 use super::traits::Tool;
 use crate::config::constants::tools;
+use crate::core::sensitive_files::is_read_denied;
 use crate::simple_indexer::SimpleIndexer;
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use serde_json::{Value, json};
-use std::{path::PathBuf, process::Stdio, time::Duration};
+use std::{
+    path::{Path, PathBuf},
+    process::Stdio,
+    time::Duration,
+};
 use tokio::{process::Command, time::timeout};
 
 /// Simple bash-like search tool
@@ -154,9 +159,16 @@ impl SimpleSearchTool {
             .await
             .context("Failed to execute grep")?;
 
-        // Parse and limit results
-        let lines: Vec<&str> = output.lines().collect();
-        let limited_lines: Vec<&str> = lines.into_iter().take(max_results).collect();
+        // Parse and limit results, leaving out sensitive files
+        let root = self.indexer.workspace_root();
+        let limited_lines: Vec<&str> = output
+            .lines()
+            .filter(|line| {
+                line.split_once(':')
+                    .is_none_or(|(path, _)| !is_read_denied(root, Path::new(path)))
+            })
+            .take(max_results)
+            .collect();
 
         Ok(json!({
             "command": "grep",
@@ -0,0 +1,356 @@
+//! Running statements through `sqlite3`, `psql` and `mysql`
+
+use super::statement::has_client_command;
+use crate::config::{SqlDatabaseConfig, SqlEngine};
+use anyhow::{Context, Result, anyhow, bail};
+use reqwest::Url;
+use serde_json::Value;
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::time::timeout;
+
+/// How a session is opened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    /// Read-only session; rows are printed as a table with a header line
+    Query,
+    /// Writable session; output is returned as text
+    Execute,
+}
+
+/// A configured database, with its URL resolved.
+#[derive(Debug, Clone)]
+pub struct Connection {
+    pub engine: SqlEngine,
+    target: String,
+}
+
+/// Rows of one query. `None` is SQL `NULL`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Table {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Option<String>>>,
+}
+
+impl Table {
+    pub fn rows_json(&self) -> Vec<Vec<Value>> {
+        self.rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|value| value.clone().map_or(Value::Null, Value::String))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl Connection {
+    pub fn resolve(name: &str, config: &SqlDatabaseConfig, workspace_root: &Path) -> Result<Self> {
+        let url = match (&config.url_env, &config.url) {
+            (Some(variable), _) => std::env::var(variable).with_context(|| {
+                format!(
+                    "Database '{}' reads its URL from ${}, which is not set",
+                    name, variable
+                )
+            })?,
+            (None, Some(url)) => url.clone(),
+            (None, None) => bail!("Database '{}' has neither url nor url_env", name),
+        };
+        let target = match config.engine {
+            SqlEngine::Sqlite => {
+                let path = url
+                    .strip_prefix("sqlite://")
+                    .or_else(|| url.strip_prefix("sqlite:"))
+                    .unwrap_or(&url);
+                workspace_root.join(path).to_string_lossy().into_owned()
+            }
+            SqlEngine::Postgres | SqlEngine::Mysql => url,
+        };
+        Ok(Self {
+            engine: config.engine,
+            target,
+        })
+    }
+
+    pub fn client(&self) -> &'static str {
+        match self.engine {
+            SqlEngine::Sqlite => "sqlite3",
+            SqlEngine::Postgres => "psql",
+            SqlEngine::Mysql => "mysql",
+        }
+    }
+
+    /// The client invocation for `sql`. Read-only Postgres sessions get the
+    /// statement through `--command`, which psql sends to the server as is;
+    /// everything else reads it from stdin.
+    fn command(&self, sql: &str, access: Access, limit: Duration) -> Result<Command> {
+        let mut command = Command::new(self.client());
+        let mut stdin = Stdio::piped();
+        match self.engine {
+            SqlEngine::Sqlite => {
+                command.args(["-bail", "-batch"]);
+                if access == Access::Query {
+                    command.args(["-readonly", "-csv", "-header"]);
+                }
+                command.arg(&self.target);
+            }
+            SqlEngine::Postgres => {
+                command.args(["--no-psqlrc", "--quiet", "--set", "ON_ERROR_STOP=1"]);
+                let mut options = format!("-c statement_timeout={}", limit.as_millis());
+                match access {
+                    Access::Query => {
+                        command.arg("--csv").arg("--command").arg(sql);
+                        options.push_str(" -c default_transaction_read_only=on");
+                        stdin = Stdio::null();
+                    }
+                    // A failing migration leaves nothing half applied.
+                    Access::Execute => {
+                        command.arg("--single-transaction");
+                    }
+                }
+                command
+                    .env("PGOPTIONS", options)
+                    .env("PGCONNECT_TIMEOUT", "10")
+                    .arg("--dbname")
+                    .arg(&self.target);
+            }
+            SqlEngine::Mysql => {
+                let url = Url::parse(&self.target).context("Invalid MySQL URL")?;
+                command.args(["--batch", "--connect-timeout=10"]);
+                if let Some(host) = url.host_str() {
+                    command.arg(format!("--host={}", host));
+                }
+                if let Some(port) = url.port() {
+                    command.arg(format!("--port={}", port));
+                }
+                if !url.username().is_empty() {
+                    command.arg(format!("--user={}", url.username()));
+                }
+                if let Some(password) = url.password() {
+                    command.env("MYSQL_PWD", password);
+                }
+                if access == Access::Query {
+                    command.arg("--init-command=SET SESSION TRANSACTION READ ONLY");
+                }
+                let database = url.path().trim_start_matches('/');
+                if !database.is_empty() {
+                    command.arg(database);
+                }
+            }
+        }
+        command
+            .stdin(stdin)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        Ok(command)
+    }
+
+    /// Run `sql` and return what the client printed. Fails with the client's
+    /// error output when a statement fails.
+    ///
+    /// Read-only sessions refuse client commands, which would run outside the
+    /// read-only transaction and without a policy prompt.
+    pub async fn run(&self, sql: &str, access: Access, limit: Duration) -> Result<String> {
+        if access == Access::Query && has_client_command(sql) {
+            bail!(
+                "Error: Read-only queries cannot contain backslashes or lines starting with '.', which {} would run as client commands",
+                self.client()
+            );
+        }
+        let mut child = self.command(sql, access, limit)?.spawn().map_err(|err| {
+            anyhow!(
+                "Failed to start '{}': {}. Install the {} client or put it on PATH",
+                self.client(),
+                err,
+                self.client()
+            )
+        })?;
+        let stdin = child.stdin.take();
+        let run = async move {
+            if let Some(mut stdin) = stdin {
+                stdin.write_all(sql.as_bytes()).await?;
+                stdin.write_all(b"\n").await?;
+            }
+            child.wait_with_output().await
+        };
+        let output = timeout(limit, run)
+            .await
+            .map_err(|_| anyhow!("Statement timed out after {}s", limit.as_secs()))?
+            .with_context(|| format!("Failed to run '{}'", self.client()))?;
+        let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !output.status.success() {
+            let message = stderr.trim();
+            bail!(
+                "{}",
+                if message.is_empty() {
+                    stdout.trim()
+                } else {
+                    message
+                }
+            );
+        }
+        Ok(stdout)
+    }
+
+    /// Run one read-only statement and parse its rows.
+    pub async fn query(&self, statement: &str, limit: Duration) -> Result<Table> {
+        let output = self.run(statement, Access::Query, limit).await?;
+        Ok(match self.engine {
+            SqlEngine::Sqlite | SqlEngine::Postgres => parse_csv(&output),
+            SqlEngine::Mysql => parse_tsv(&output),
+        })
+    }
+}
+
+/// Parse CSV with a header line as printed by `sqlite3 -csv` and `psql --csv`,
+/// where an unquoted empty field is `NULL` and `""` is an empty string.
+pub fn parse_csv(text: &str) -> Table {
+    let mut records: Vec<Vec<Option<String>>> = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    let mut in_quotes = false;
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' => {
+                in_quotes = true;
+                quoted = true;
+            }
+            ',' => record.push(take_field(&mut field, &mut quoted)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                record.push(take_field(&mut field, &mut quoted));
+                records.push(std::mem::take(&mut record));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || quoted || !record.is_empty() {
+        record.push(take_field(&mut field, &mut quoted));
+        records.push(record);
+    }
+    into_table(records)
+}
+
+fn take_field(field: &mut String, quoted: &mut bool) -> Option<String> {
+    let value = std::mem::take(field);
+    let was_quoted = std::mem::take(quoted);
+    (was_quoted || !value.is_empty()).then_some(value)
+}
+
+/// Parse the tab-separated output of `mysql --batch`, which escapes tabs,
+/// newlines and backslashes and prints `NULL` for null values.
+pub fn parse_tsv(text: &str) -> Table {
+    let records = text
+        .lines()
+        .map(|line| {
+            line.split('\t')
+                .map(|field| (field != "NULL").then(|| unescape_tsv(field)))
+                .collect()
+        })
+        .collect();
+    into_table(records)
+}
+
+fn unescape_tsv(field: &str) -> String {
+    let mut value = String::with_capacity(field.len());
+    let mut chars = field.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => value.push('\n'),
+            Some('t') => value.push('\t'),
+            Some('0') => value.push('\0'),
+            Some(other) => value.push(other),
+            None => value.push('\\'),
+        }
+    }
+    value
+}
+
+fn into_table(mut records: Vec<Vec<Option<String>>>) -> Table {
+    if records.is_empty() {
+        return Table::default();
+    }
+    let columns = records
+        .remove(0)
+        .into_iter()
+        .map(Option::unwrap_or_default)
+        .collect();
+    Table {
+        columns,
+        rows: records,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_client_output() {
+        let table = parse_csv("id,name,note\r\n1,\"a, \"\"b\"\"\",\n2,,\"\"\n3,\"two\nlines\",x");
+        assert_eq!(table.columns, vec!["id", "name", "note"]);
+        assert_eq!(
+            table.rows,
+            vec![
+                vec![Some("1".into()), Some("a, \"b\"".into()), None],
+                vec![Some("2".into()), None, Some(String::new())],
+                vec![
+                    Some("3".into()),
+                    Some("two\nlines".into()),
+                    Some("x".into())
+                ],
+            ]
+        );
+
+        let table = parse_tsv("id\tbody\n1\ttab\\there\n2\tNULL\n");
+        assert_eq!(table.columns, vec!["id", "body"]);
+        assert_eq!(table.rows[0][1].as_deref(), Some("tab\there"));
+        assert_eq!(table.rows[1][1], None);
+        assert_eq!(parse_csv(""), Table::default());
+    }
+
+    #[test]
+    fn postgres_reads_pass_the_statement_as_an_argument() {
+        let connection = Connection {
+            engine: SqlEngine::Postgres,
+            target: "postgres://localhost/dev".to_string(),
+        };
+        let limit = Duration::from_secs(5);
+        let args = |access| {
+            connection
+                .command("SELECT 1", access, limit)
+                .unwrap()
+                .as_std()
+                .get_args()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+        };
+        let query = args(Access::Query);
+        let position = query.iter().position(|arg| arg == "--command").unwrap();
+        assert_eq!(query[position + 1], "SELECT 1");
+        assert!(!args(Access::Execute).contains(&"--command".to_string()));
+    }
+}
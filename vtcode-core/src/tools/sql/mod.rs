@@ -0,0 +1,414 @@
+//! Queries against configured development databases
+//!
+//! [`SqlTool`] backs three tools that only exist once `[tools.sql]` is enabled:
+//! `sql_schema` lists tables and columns, `sql_query` runs one read-only
+//! statement and returns its rows as a table, and `sql_execute` runs
+//! migrations and other mutations. Statements go through the engine's own
+//! client (`sqlite3`, `psql` or `mysql`). Queries are checked to be reads and
+//! run in read-only sessions that refuse client commands such as psql's `\!`;
+//! mutations need `allow_writes` on the database and go through the usual
+//! tool policy prompt.
+
+mod client;
+mod statement;
+
+pub use client::{Access, Connection, Table, parse_csv, parse_tsv};
+pub use statement::{
+    first_keyword, has_client_command, is_limitable, is_read_only, split_statements,
+};
+
+use crate::config::constants::tools;
+use crate::config::{SqlConfig, SqlDatabaseConfig, SqlEngine};
+use crate::tools::traits::Tool;
+use anyhow::{Context, Result, anyhow, bail};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Trailing lines of client output kept from `sql_execute`.
+const OUTPUT_TAIL_LINES: usize = 40;
+
+#[derive(Debug, Deserialize)]
+struct SqlQueryInput {
+    #[serde(default)]
+    database: Option<String>,
+    query: String,
+    #[serde(default)]
+    max_rows: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SqlSchemaInput {
+    #[serde(default)]
+    database: Option<String>,
+    /// Describe the columns of this table instead of listing tables
+    #[serde(default)]
+    table: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SqlExecuteInput {
+    #[serde(default)]
+    database: Option<String>,
+    #[serde(default)]
+    statements: Option<String>,
+    /// Migration file to run, relative to the workspace
+    #[serde(default)]
+    file: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct SqlTool {
+    workspace_root: PathBuf,
+    config: SqlConfig,
+}
+
+impl SqlTool {
+    pub fn new(workspace_root: PathBuf) -> Self {
+        Self {
+            workspace_root,
+            config: SqlConfig::default(),
+        }
+    }
+
+    pub fn set_config(&mut self, config: SqlConfig) {
+        self.config = config;
+    }
+
+    fn time_limit(&self) -> Duration {
+        Duration::from_secs(self.config.timeout_secs.max(1))
+    }
+
+    /// The named database, or the only one configured when no name is given.
+    fn database(&self, name: Option<&str>) -> Result<(String, &SqlDatabaseConfig, Connection)> {
+        if !self.config.enabled {
+            bail!(
+                "SQL tools are disabled. Set [tools.sql] enabled = true and add databases under [tools.sql.databases] in vtcode.toml"
+            );
+        }
+        let names = || {
+            self.config
+                .databases
+                .keys()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let (name, config) =
+            match name {
+                Some(name) => self.config.databases.get_key_value(name).ok_or_else(|| {
+                    anyhow!("Unknown database '{}'. Configured: {}", name, names())
+                })?,
+                None if self.config.databases.len() == 1 => {
+                    self.config.databases.first().expect("one database")
+                }
+                None if self.config.databases.is_empty() => {
+                    bail!("No databases configured under [tools.sql.databases]")
+                }
+                None => bail!("Error: Missing 'database'. Configured: {}", names()),
+            };
+        let connection = Connection::resolve(name, config, &self.workspace_root)?;
+        Ok((name.clone(), config, connection))
+    }
+
+    /// Run one read-only statement and return its rows.
+    pub async fn query(&self, args: Value) -> Result<Value> {
+        let input: SqlQueryInput = serde_json::from_value(args).context(
+            "Error: Invalid 'sql_query' arguments. Required: { query: string }. Optional: { database: string, max_rows: number }",
+        )?;
+        let (name, _, connection) = self.database(input.database.as_deref())?;
+        let statements = split_statements(&input.query);
+        let [statement] = statements.as_slice() else {
+            bail!(
+                "Error: 'sql_query' runs exactly one statement, got {}",
+                statements.len()
+            );
+        };
+        if !is_read_only(statement) {
+            bail!(
+                "Error: 'sql_query' only runs read-only statements (SELECT, WITH, EXPLAIN, SHOW, PRAGMA). Use 'sql_execute' for mutations and migrations"
+            );
+        }
+        let max_rows = input
+            .max_rows
+            .unwrap_or(self.config.max_rows)
+            .min(self.config.max_rows)
+            .max(1);
+        // Fetch one row past the limit to know whether there were more.
+        let sql = if is_limitable(statement) {
+            format!(
+                "SELECT * FROM ({}) AS vtcode_rows LIMIT {}",
+                statement,
+                max_rows + 1
+            )
+        } else {
+            statement.clone()
+        };
+        let mut table = connection.query(&sql, self.time_limit()).await?;
+        let truncated = table.rows.len() > max_rows;
+        table.rows.truncate(max_rows);
+        Ok(json!({
+            "success": true,
+            "database": name,
+            "columns": table.columns,
+            "rows": table.rows_json(),
+            "row_count": table.rows.len(),
+            "truncated": truncated,
+        }))
+    }
+
+    /// List tables and views, or the columns of one table.
+    pub async fn schema(&self, args: Value) -> Result<Value> {
+        let input: SqlSchemaInput = if args.is_null() {
+            SqlSchemaInput::default()
+        } else {
+            serde_json::from_value(args).context(
+                "Error: Invalid 'sql_schema' arguments. Optional: { database: string, table: string }",
+            )?
+        };
+        let (name, _, connection) = self.database(input.database.as_deref())?;
+        let sql = match input.table.as_deref() {
+            Some(table) => columns_query(connection.engine, table),
+            None => tables_query(connection.engine).to_string(),
+        };
+        let table = connection.query(&sql, self.time_limit()).await?;
+        let key = if input.table.is_some() {
+            "columns"
+        } else {
+            "tables"
+        };
+        let entries: Vec<Value> = table
+            .rows_json()
+            .into_iter()
+            .map(|row| {
+                table
+                    .columns
+                    .iter()
+                    .cloned()
+                    .zip(row)
+                    .collect::<serde_json::Map<_, _>>()
+                    .into()
+            })
+            .collect();
+        if let Some(table_name) = &input.table
+            && entries.is_empty()
+        {
+            bail!("Table '{}' not found in database '{}'", table_name, name);
+        }
+        let mut result = json!({ "success": true, "database": name });
+        result[key] = json!(entries);
+        if let Some(table_name) = input.table {
+            result["table"] = json!(table_name);
+        }
+        Ok(result)
+    }
+
+    /// Run statements or a migration file on a database that allows writes.
+    pub async fn execute_statements(&self, args: Value) -> Result<Value> {
+        let input: SqlExecuteInput = serde_json::from_value(args).context(
+            "Error: Invalid 'sql_execute' arguments. Provide { statements: string } or { file: string }, plus optional { database: string }",
+        )?;
+        let (name, config, connection) = self.database(input.database.as_deref())?;
+        if !config.allow_writes {
+            bail!(
+                "Database '{}' is read-only for the agent. Set allow_writes = true under [tools.sql.databases.{}] to run mutations",
+                name,
+                name
+            );
+        }
+        let sql = match (input.statements, input.file.as_deref()) {
+            (Some(statements), None) => statements,
+            (None, Some(file)) => {
+                let path = self.workspace_root.join(file);
+                tokio::fs::read_to_string(&path)
+                    .await
+                    .with_context(|| format!("Failed to read migration {}", path.display()))?
+            }
+            _ => bail!("Error: Provide exactly one of 'statements' or 'file'"),
+        };
+        let count = split_statements(&sql).len();
+        if count == 0 {
+            bail!("Error: No SQL statements to run");
+        }
+        let output = connection
+            .run(&sql, Access::Execute, self.time_limit())
+            .await?;
+        let lines: Vec<&str> = output.lines().collect();
+        let tail = lines[lines.len().saturating_sub(OUTPUT_TAIL_LINES)..].join("\n");
+        Ok(json!({
+            "success": true,
+            "database": name,
+            "statements": count,
+            "file": input.file,
+            "output": tail,
+        }))
+    }
+}
+
+fn tables_query(engine: SqlEngine) -> &'static str {
+    match engine {
+        SqlEngine::Sqlite => {
+            "SELECT name, type FROM sqlite_master WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' ORDER BY name"
+        }
+        SqlEngine::Postgres => {
+            "SELECT table_schema || '.' || table_name AS name, lower(table_type) AS type FROM information_schema.tables WHERE table_schema NOT IN ('pg_catalog', 'information_schema') ORDER BY 1"
+        }
+        SqlEngine::Mysql => {
+            "SELECT table_name AS name, lower(table_type) AS type FROM information_schema.tables WHERE table_schema = DATABASE() ORDER BY table_name"
+        }
+    }
+}
+
+fn columns_query(engine: SqlEngine, table: &str) -> String {
+    let quoted = |value: &str| format!("'{}'", value.replace('\'', "''"));
+    match engine {
+        SqlEngine::Sqlite => format!(
+            "SELECT name, type, CASE WHEN \"notnull\" = 1 THEN 'NO' ELSE 'YES' END AS nullable, dflt_value AS default_value, pk > 0 AS primary_key FROM pragma_table_info({})",
+            quoted(table)
+        ),
+        SqlEngine::Postgres => {
+            let (schema, name) = match table.split_once('.') {
+                Some((schema, name)) => (Some(schema), name),
+                None => (None, table),
+            };
+            let schema_filter = schema
+                .map(|schema| format!(" AND table_schema = {}", quoted(schema)))
+                .unwrap_or_default();
+            format!(
+                "SELECT column_name AS name, data_type AS type, is_nullable AS nullable, column_default AS default_value FROM information_schema.columns WHERE table_name = {}{} ORDER BY ordinal_position",
+                quoted(name),
+                schema_filter
+            )
+        }
+        SqlEngine::Mysql => format!(
+            "SELECT column_name AS name, column_type AS type, is_nullable AS nullable, column_default AS default_value, column_key = 'PRI' AS primary_key FROM information_schema.columns WHERE table_schema = DATABASE() AND table_name = {} ORDER BY ordinal_position",
+            quoted(table)
+        ),
+    }
+}
+
+#[async_trait]
+impl Tool for SqlTool {
+    async fn execute(&self, args: Value) -> Result<Value> {
+        self.query(args).await
+    }
+
+    fn name(&self) -> &'static str {
+        tools::SQL_QUERY
+    }
+
+    fn description(&self) -> &'static str {
+        "Run a read-only SQL query against a configured development database"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SqlEngine;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn tool(dir: &TempDir, allow_writes: bool) -> SqlTool {
+        let mut config = SqlConfig {
+            enabled: true,
+            max_rows: 2,
+            ..SqlConfig::default()
+        };
+        config.databases.insert(
+            "dev".to_string(),
+            SqlDatabaseConfig {
+                engine: SqlEngine::Sqlite,
+                url: Some("sqlite://dev.db".to_string()),
+                url_env: None,
+                allow_writes,
+            },
+        );
+        let mut tool = SqlTool::new(dir.path().to_path_buf());
+        tool.set_config(config);
+        tool
+    }
+
+    #[tokio::test]
+    async fn queries_refuse_client_commands() {
+        let dir = TempDir::new().unwrap();
+        let tool = tool(&dir, false);
+        let marker = dir.path().join("pwned");
+        for query in [
+            format!("SELECT 1\n\\! touch {}", marker.display()),
+            format!("SELECT 1\n.shell touch {}", marker.display()),
+        ] {
+            let error = tool.query(json!({ "query": query })).await.unwrap_err();
+            assert!(error.to_string().contains("client commands"), "{error}");
+        }
+        assert!(
+            tool.schema(json!({ "table": "x\\' \\! id" }))
+                .await
+                .is_err()
+        );
+        assert!(!marker.exists());
+    }
+
+    #[tokio::test]
+    async fn queries_and_migrates_sqlite() {
+        if Command::new("sqlite3").arg("-version").output().is_err() {
+            return;
+        }
+        let dir = TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("001_users.sql"),
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL, email TEXT);\nINSERT INTO users (name) VALUES ('ada'), ('grace'), ('linus');\n",
+        )
+        .unwrap();
+
+        let read_only = tool(&dir, false);
+        let refused = read_only
+            .execute_statements(json!({ "file": "001_users.sql" }))
+            .await;
+        assert!(refused.unwrap_err().to_string().contains("allow_writes"));
+
+        let tool = tool(&dir, true);
+        let migrated = tool
+            .execute_statements(json!({ "file": "001_users.sql" }))
+            .await
+            .unwrap();
+        assert_eq!(migrated["statements"], 2);
+
+        let rows = tool
+            .query(json!({ "query": "SELECT id, name, email FROM users ORDER BY id;" }))
+            .await
+            .unwrap();
+        assert_eq!(rows["columns"], json!(["id", "name", "email"]));
+        assert_eq!(
+            rows["rows"],
+            json!([["1", "ada", null], ["2", "grace", null]])
+        );
+        assert_eq!(rows["truncated"], true);
+
+        assert!(
+            tool.query(json!({ "query": "DELETE FROM users" }))
+                .await
+                .unwrap_err()
+                .to_string()
+                .contains("sql_execute")
+        );
+        // Read-only sessions also stop writes the classifier lets through.
+        assert!(
+            tool.query(json!({ "query": "WITH x AS (SELECT 1) INSERT INTO users (name) SELECT 'eve' FROM x" }))
+                .await
+                .is_err()
+        );
+
+        let tables = tool.schema(Value::Null).await.unwrap();
+        assert_eq!(
+            tables["tables"],
+            json!([{ "name": "users", "type": "table" }])
+        );
+        let columns = tool.schema(json!({ "table": "users" })).await.unwrap();
+        assert_eq!(columns["columns"][1]["name"], "name");
+        assert_eq!(columns["columns"][1]["nullable"], "NO");
+        assert_eq!(columns["columns"][0]["primary_key"], "1");
+    }
+}
@@ -0,0 +1,183 @@
+//! Splitting SQL text into statements and telling reads from writes
+
+/// Split `sql` at top-level semicolons, skipping those inside quotes, quoted
+/// identifiers, comments and Postgres dollar-quoted bodies. Empty statements
+/// are dropped; comments are kept with the statement they precede.
+pub fn split_statements(sql: &str) -> Vec<String> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut statements = Vec::new();
+    let mut current = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '\'' | '"' | '`' => {
+                let end = closing_quote(&chars, i, c);
+                current.extend(&chars[i..end]);
+                i = end;
+                continue;
+            }
+            '-' if chars.get(i + 1) == Some(&'-') => {
+                let end = (i..chars.len())
+                    .find(|&j| chars[j] == '\n')
+                    .unwrap_or(chars.len());
+                current.extend(&chars[i..end]);
+                i = end;
+                continue;
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                let end = (i + 2..chars.len().saturating_sub(1))
+                    .find(|&j| chars[j] == '*' && chars[j + 1] == '/')
+                    .map_or(chars.len(), |j| j + 2);
+                current.extend(&chars[i..end]);
+                i = end;
+                continue;
+            }
+            '$' => {
+                if let Some(tag_end) = dollar_tag_end(&chars, i) {
+                    let tag: String = chars[i..tag_end].iter().collect();
+                    let body: String = chars[tag_end..].iter().collect();
+                    let end = body.find(&tag).map_or(chars.len(), |offset| {
+                        tag_end + body[..offset].chars().count() + tag.chars().count()
+                    });
+                    current.extend(&chars[i..end]);
+                    i = end;
+                    continue;
+                }
+            }
+            ';' => {
+                push_statement(&mut statements, &mut current);
+                i += 1;
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+        i += 1;
+    }
+    push_statement(&mut statements, &mut current);
+    statements
+}
+
+fn push_statement(statements: &mut Vec<String>, current: &mut String) {
+    let statement = std::mem::take(current);
+    if first_keyword(&statement).is_some() {
+        statements.push(statement.trim().to_string());
+    }
+}
+
+/// Index just past the quote that closes the one at `start`; a doubled quote
+/// is an escaped quote.
+fn closing_quote(chars: &[char], start: usize, quote: char) -> usize {
+    let mut i = start + 1;
+    while i < chars.len() {
+        if chars[i] == quote {
+            if chars.get(i + 1) == Some(&quote) {
+                i += 2;
+                continue;
+            }
+            return i + 1;
+        }
+        i += 1;
+    }
+    chars.len()
+}
+
+/// End of a `$tag$` opening at `start`, if there is one.
+fn dollar_tag_end(chars: &[char], start: usize) -> Option<usize> {
+    let mut i = start + 1;
+    while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+        i += 1;
+    }
+    let tag_is_name = i == start + 1 || !chars[start + 1].is_ascii_digit();
+    (chars.get(i) == Some(&'$') && tag_is_name).then_some(i + 1)
+}
+
+/// First keyword of a statement, lowercased, after comments and parentheses.
+pub fn first_keyword(statement: &str) -> Option<String> {
+    let mut rest = statement;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '(');
+        if let Some(comment) = rest.strip_prefix("--") {
+            rest = comment.split_once('\n').map_or("", |(_, after)| after);
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            rest = comment.split_once("*/").map_or("", |(_, after)| after);
+        } else {
+            break;
+        }
+    }
+    let keyword: String = rest
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect();
+    (!keyword.is_empty()).then(|| keyword.to_ascii_lowercase())
+}
+
+/// Whether a statement only reads. The sessions the tools open are read-only
+/// as well, so a data-modifying CTE or function call is still refused by the
+/// database.
+pub fn is_read_only(statement: &str) -> bool {
+    match first_keyword(statement).as_deref() {
+        Some("select" | "with" | "values" | "table" | "show" | "describe" | "desc") => true,
+        Some("explain") => !statement.to_ascii_lowercase().contains("analyze"),
+        // `PRAGMA name = value` changes settings; reading one does not.
+        Some("pragma") => !statement.contains('='),
+        _ => false,
+    }
+}
+
+/// Whether `statement` holds something the client would run itself instead of
+/// sending it to the server: a backslash meta-command of `psql` or `mysql`
+/// (`\!` runs a shell), or a dot command of `sqlite3`. Backslashes are
+/// refused inside literals too, because the clients disagree on whether a
+/// backslash escapes the closing quote.
+pub fn has_client_command(statement: &str) -> bool {
+    statement.contains('\\')
+        || statement
+            .lines()
+            .any(|line| line.trim_start().starts_with('.'))
+}
+
+/// Whether rows of the statement can be limited by wrapping it in a subquery.
+pub fn is_limitable(statement: &str) -> bool {
+    matches!(
+        first_keyword(statement).as_deref(),
+        Some("select" | "with" | "values" | "table")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_and_classifies_statements() {
+        let sql = "-- list users; then count them\nSELECT 'a;b', \"x;y\" FROM users;\n/* ; */ select count(*) from users ;\nCREATE FUNCTION f() RETURNS int AS $body$ SELECT 1; $body$ LANGUAGE sql;\n;  \n";
+        let statements = split_statements(sql);
+        assert_eq!(statements.len(), 3);
+        assert!(statements[0].ends_with("FROM users"));
+        assert_eq!(first_keyword(&statements[1]).as_deref(), Some("select"));
+        assert!(statements[2].contains("SELECT 1; $body$"));
+
+        assert!(is_read_only(&statements[0]));
+        assert!(!is_read_only(&statements[2]));
+        assert!(is_read_only("(SELECT 1) UNION (SELECT 2)"));
+        assert!(is_read_only("PRAGMA table_info(users)"));
+        assert!(!is_read_only("PRAGMA journal_mode = WAL"));
+        assert!(!is_read_only("EXPLAIN ANALYZE DELETE FROM users"));
+        assert!(!is_read_only("/* select */ DELETE FROM users"));
+        assert!(!is_read_only("UPDATE users SET name = 'select'"));
+        assert!(is_limitable("WITH t AS (SELECT 1) SELECT * FROM t"));
+        assert!(!is_limitable("SHOW TABLES"));
+        assert_eq!(split_statements("select 'it''s';").len(), 1);
+    }
+
+    #[test]
+    fn spots_client_commands() {
+        assert!(has_client_command("SELECT 1\n\\! touch ~/pwned"));
+        assert!(has_client_command("SELECT 'a\\' \\! id"));
+        assert!(has_client_command("SELECT 1\n  .shell id"));
+        assert!(!has_client_command("SELECT name FROM users WHERE id = 1"));
+        assert!(!has_client_command("SELECT u.name, 1.5 FROM users u"));
+    }
+}
@@ -85,6 +85,7 @@
 //! }
 //! ```
 
+use super::cancellation::{ToolContext, command_output};
 use super::traits::{FileTool, Tool};
 use crate::utils::vtcodegitignore::should_exclude_file;
 use anyhow::{Context, Result, anyhow};
@@ -95,6 +96,7 @@ use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::time::SystemTime;
 use tokio::process::Command;
+use tokio_util::sync::CancellationToken;
 
 /// Input structure for srgn operations
 #[derive(Debug, Deserialize)]
@@ -441,7 +443,7 @@ impl SrgnTool {
     }
 
     /// Execute srgn command
-    async fn execute_srgn(&self, args: &[String]) -> Result<String> {
+    async fn execute_srgn(&self, args: &[String], cancel: &CancellationToken) -> Result<String> {
         // For file-modifying operations, capture file paths and timestamps for verification
         let file_paths: Vec<PathBuf> = args
             .iter()
@@ -457,12 +459,13 @@ impl SrgnTool {
             })
             .collect();
 
-        let output = Command::new("srgn")
+        let mut command = Command::new("srgn");
+        command
             .args(args)
             .current_dir(&self.workspace_root)
             .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
+            .stderr(Stdio::piped());
+        let output = command_output(&mut command, cancel)
             .await
             .with_context(|| format!("Failed to execute srgn command with args: {:?}", args))?;
 
@@ -531,6 +534,11 @@ impl SrgnTool {
 #[async_trait]
 impl Tool for SrgnTool {
     async fn execute(&self, args: Value) -> Result<Value> {
+        self.execute_with_context(args, &ToolContext::default())
+            .await
+    }
+
+    async fn execute_with_context(&self, args: Value, context: &ToolContext) -> Result<Value> {
         let input: SrgnInput = serde_json::from_value(args)
             .with_context(|| "Failed to parse SrgnInput from arguments")?;
 
@@ -548,7 +556,7 @@ impl Tool for SrgnTool {
             .collect();
 
         // Execute srgn command
-        let output = self.execute_srgn(&cmd_args).await?;
+        let output = self.execute_srgn(&cmd_args, &context.cancel).await?;
 
         // Return result with modified files info
         Ok(json!({
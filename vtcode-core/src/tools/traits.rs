@@ -1,5 +1,6 @@
 //! Core traits for the composable tool system
 
+use crate::tools::cancellation::ToolContext;
 use anyhow::Result;
 use async_trait::async_trait;
 use serde_json::Value;
@@ -11,6 +12,13 @@ pub trait Tool: Send + Sync {
     /// Execute the tool with given arguments
     async fn execute(&self, args: Value) -> Result<Value>;
 
+    /// Execute with the call's context. Tools that can run for long override
+    /// this and check `context` for cancellation at natural boundaries.
+    async fn execute_with_context(&self, args: Value, context: &ToolContext) -> Result<Value> {
+        let _ = context;
+        self.execute(args).await
+    }
+
     /// Get the tool's name
     fn name(&self) -> &'static str;
 
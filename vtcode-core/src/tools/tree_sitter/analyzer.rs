@@ -3,6 +3,7 @@
 use crate::tools::tree_sitter::analysis::{
     CodeAnalysis, CodeMetrics, DependencyInfo, DependencyKind,
 };
+use crate::tools::tree_sitter::incremental::{CachedParse, SyntaxTreeCache};
 use crate::tools::tree_sitter::languages::*;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
@@ -96,6 +97,7 @@ pub struct TreeSitterAnalyzer {
     parsers: HashMap<LanguageSupport, Parser>,
     supported_languages: Vec<LanguageSupport>,
     current_file: String,
+    tree_cache: SyntaxTreeCache,
 }
 
 impl TreeSitterAnalyzer {
@@ -103,30 +105,49 @@ impl TreeSitterAnalyzer {
     pub fn new() -> Result<Self> {
         let mut parsers = HashMap::new();
 
-        // Initialize parsers for all supported languages
-        let languages = vec![
+        // Initialize parsers for every language whose grammar is compiled in
+        let mut languages = Vec::new();
+        for language in [
             LanguageSupport::Rust,
             LanguageSupport::Python,
             LanguageSupport::JavaScript,
             LanguageSupport::TypeScript,
             LanguageSupport::Go,
             LanguageSupport::Java,
-        ];
-
-        for language in &languages {
+        ] {
+            let Ok(ts_language) = get_language(language) else {
+                continue;
+            };
             let mut parser = Parser::new();
-            let ts_language = get_language(language.clone())?;
             parser.set_language(&ts_language)?;
-            parsers.insert(language.clone(), parser);
+            parsers.insert(language, parser);
+            languages.push(language);
         }
 
         Ok(Self {
             parsers,
             supported_languages: languages,
             current_file: String::new(),
+            tree_cache: SyntaxTreeCache::new(),
         })
     }
 
+    /// Share a syntax tree cache, typically the one edit tools record into
+    pub fn with_tree_cache(mut self, cache: SyntaxTreeCache) -> Self {
+        self.tree_cache = cache;
+        self
+    }
+
+    /// Replace the syntax tree cache used for incremental parsing
+    pub fn set_tree_cache(&mut self, cache: SyntaxTreeCache) {
+        self.tree_cache = cache;
+    }
+
+    /// Syntax tree cache used for incremental parsing
+    pub fn tree_cache(&self) -> &SyntaxTreeCache {
+        &self.tree_cache
+    }
+
     /// Get supported languages
     pub fn supported_languages(&self) -> &[LanguageSupport] {
         &self.supported_languages
@@ -170,6 +191,38 @@ impl TreeSitterAnalyzer {
         Ok(tree)
     }
 
+    /// Parse the contents of `file_path`, reusing the cached tree for the file.
+    ///
+    /// When an earlier tree exists the source difference is applied to it and
+    /// only the edited region is re-parsed; otherwise a full parse is done.
+    pub fn parse_incremental(
+        &mut self,
+        file_path: &Path,
+        source_code: &str,
+        language: LanguageSupport,
+    ) -> Result<Tree> {
+        let old_tree = match self.tree_cache.prepare(file_path, source_code, language) {
+            CachedParse::Fresh(tree) => return Ok(tree),
+            CachedParse::Stale(tree) => Some(tree),
+            CachedParse::Miss => None,
+        };
+
+        let parser = self
+            .parsers
+            .get_mut(&language)
+            .ok_or_else(|| TreeSitterError::UnsupportedLanguage(format!("{:?}", language)))?;
+
+        let tree = parser
+            .parse(source_code, old_tree.as_ref())
+            .ok_or_else(|| {
+                TreeSitterError::ParseError("Failed to parse source code".to_string())
+            })?;
+
+        self.tree_cache
+            .store(file_path, language, source_code, tree.clone());
+        Ok(tree)
+    }
+
     /// Extract symbols from a syntax tree
     pub fn extract_symbols(
         &mut self,
@@ -594,7 +647,7 @@ impl TreeSitterAnalyzer {
         let source_code = std::fs::read_to_string(file_path)
             .map_err(|e| TreeSitterError::FileReadError(e.to_string()))?;
 
-        let tree = self.parse(&source_code, language.clone())?;
+        let tree = self.parse_incremental(file_path, &source_code, language)?;
 
         // Convert tree-sitter tree to our SyntaxTree representation
         let root = self.convert_tree_to_syntax_node(tree.root_node(), &source_code);
@@ -724,7 +777,7 @@ impl TreeSitterAnalyzer {
 
         self.current_file = file_path.to_string_lossy().to_string();
 
-        let tree = self.parse(source_code, language.clone())?;
+        let tree = self.parse_incremental(file_path, source_code, language)?;
 
         // Extract actual symbols and dependencies
         let symbols = self.extract_symbols(&tree, source_code, language.clone())?;
@@ -746,25 +799,78 @@ impl TreeSitterAnalyzer {
 
 /// Helper function to get tree-sitter language
 fn get_language(language: LanguageSupport) -> Result<Language> {
-    let lang = match language {
-        LanguageSupport::Rust => tree_sitter_rust::LANGUAGE,
-        LanguageSupport::Python => tree_sitter_python::LANGUAGE,
-        LanguageSupport::JavaScript => tree_sitter_javascript::LANGUAGE,
-        LanguageSupport::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT,
-        LanguageSupport::Go => tree_sitter_go::LANGUAGE,
-        LanguageSupport::Java => tree_sitter_java::LANGUAGE,
+    match language {
+        LanguageSupport::Rust => {
+            #[cfg(feature = "tree-sitter-rust")]
+            {
+                Ok(tree_sitter_rust::LANGUAGE.into())
+            }
+            #[cfg(not(feature = "tree-sitter-rust"))]
+            {
+                Err(TreeSitterError::UnsupportedLanguage("Rust".to_string()).into())
+            }
+        }
+        LanguageSupport::Python => {
+            #[cfg(feature = "tree-sitter-python")]
+            {
+                Ok(tree_sitter_python::LANGUAGE.into())
+            }
+            #[cfg(not(feature = "tree-sitter-python"))]
+            {
+                Err(TreeSitterError::UnsupportedLanguage("Python".to_string()).into())
+            }
+        }
+        LanguageSupport::JavaScript => {
+            #[cfg(feature = "tree-sitter-javascript")]
+            {
+                Ok(tree_sitter_javascript::LANGUAGE.into())
+            }
+            #[cfg(not(feature = "tree-sitter-javascript"))]
+            {
+                Err(TreeSitterError::UnsupportedLanguage("JavaScript".to_string()).into())
+            }
+        }
+        LanguageSupport::TypeScript => {
+            #[cfg(feature = "tree-sitter-typescript")]
+            {
+                Ok(tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into())
+            }
+            #[cfg(not(feature = "tree-sitter-typescript"))]
+            {
+                Err(TreeSitterError::UnsupportedLanguage("TypeScript".to_string()).into())
+            }
+        }
+        LanguageSupport::Go => {
+            #[cfg(feature = "tree-sitter-go")]
+            {
+                Ok(tree_sitter_go::LANGUAGE.into())
+            }
+            #[cfg(not(feature = "tree-sitter-go"))]
+            {
+                Err(TreeSitterError::UnsupportedLanguage("Go".to_string()).into())
+            }
+        }
+        LanguageSupport::Java => {
+            #[cfg(feature = "tree-sitter-java")]
+            {
+                Ok(tree_sitter_java::LANGUAGE.into())
+            }
+            #[cfg(not(feature = "tree-sitter-java"))]
+            {
+                Err(TreeSitterError::UnsupportedLanguage("Java".to_string()).into())
+            }
+        }
         LanguageSupport::Swift => {
             #[cfg(feature = "swift")]
             {
-                tree_sitter_swift::LANGUAGE
+                Ok(tree_sitter_swift::LANGUAGE.into())
             }
             #[cfg(not(feature = "swift"))]
             {
-                return Err(TreeSitterError::UnsupportedLanguage("Swift".to_string()).into());
+                Err(TreeSitterError::UnsupportedLanguage("Swift".to_string()).into())
             }
         }
-    };
-    Ok(lang.into())
+    }
 }
 
 impl std::fmt::Display for LanguageSupport {
@@ -880,6 +986,31 @@ mod tests {
         assert!(!tree.root_node().has_error());
     }
 
+    #[test]
+    fn test_incremental_parse_matches_full_parse() {
+        let mut analyzer = create_test_analyzer();
+        let path = Path::new("virtual/lib.rs");
+        let before = "fn one() -> u32 { 1 }\nfn two() -> u32 { 2 }\n";
+        let after = "fn one() -> u32 { 1 }\nfn two() -> u32 { 2 + 40 }\nfn three() {}\n";
+
+        analyzer
+            .parse_incremental(path, before, LanguageSupport::Rust)
+            .unwrap();
+        assert!(analyzer.tree_cache().contains(path));
+
+        analyzer.tree_cache().record_edit(path, before, after);
+        let incremental = analyzer
+            .parse_incremental(path, after, LanguageSupport::Rust)
+            .unwrap();
+        let full = analyzer.parse(after, LanguageSupport::Rust).unwrap();
+
+        assert_eq!(
+            incremental.root_node().to_sexp(),
+            full.root_node().to_sexp()
+        );
+        assert_eq!(incremental.root_node().named_child_count(), 3);
+    }
+
     #[cfg(feature = "swift")]
     #[test]
     fn test_parse_swift_code() {
@@ -0,0 +1,266 @@
+//! Incremental re-parsing support for tree-sitter
+//!
+//! Keeps the last syntax tree produced for each file together with the source
+//! it was parsed from. When a file changes, the difference between the cached
+//! and the new source is expressed as a tree-sitter [`InputEdit`], applied to
+//! the old tree, and the parser reuses every subtree outside the edited range.
+
+use crate::tools::tree_sitter::analyzer::LanguageSupport;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tree_sitter::{InputEdit, Point, Tree};
+
+/// Maximum number of files whose trees are retained at once
+const MAX_CACHED_TREES: usize = 128;
+
+struct CachedTree {
+    language: LanguageSupport,
+    source: String,
+    tree: Tree,
+    /// Set when an edit was applied to `tree` but it has not been re-parsed yet
+    edited: bool,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<PathBuf, CachedTree>,
+    clock: u64,
+}
+
+impl CacheState {
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    fn evict_least_recently_used(&mut self) {
+        let oldest = self
+            .entries
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(path, _)| path.clone());
+        if let Some(path) = oldest {
+            self.entries.remove(&path);
+        }
+    }
+}
+
+/// What the cache can offer for a parse request.
+pub(crate) enum CachedParse {
+    /// The cached tree already matches the requested source
+    Fresh(Tree),
+    /// An edited old tree to pass to `Parser::parse` for incremental re-parsing
+    Stale(Tree),
+    /// Nothing usable is cached; a full parse is required
+    Miss,
+}
+
+/// Shared cache of syntax trees keyed by file path.
+///
+/// Cloning the cache is cheap and all clones share the same entries, so the
+/// tool registry can record edits that the analyzer later picks up.
+#[derive(Clone, Default)]
+pub struct SyntaxTreeCache {
+    state: Arc<Mutex<CacheState>>,
+}
+
+impl std::fmt::Debug for SyntaxTreeCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SyntaxTreeCache")
+            .field("entries", &self.len())
+            .finish()
+    }
+}
+
+impl SyntaxTreeCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of files with a cached tree.
+    pub fn len(&self) -> usize {
+        self.state.lock().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether a tree is cached for `path`.
+    pub fn contains(&self, path: &Path) -> bool {
+        self.state.lock().entries.contains_key(&cache_key(path))
+    }
+
+    /// Record that an edit tool rewrote `path` from `old_source` to `new_source`.
+    ///
+    /// The edit is applied to the cached tree right away so the next parse of
+    /// the file only re-parses the changed region. If the cached source does
+    /// not match `old_source` the entry is stale and is dropped instead.
+    pub fn record_edit(&self, path: &Path, old_source: &str, new_source: &str) {
+        let key = cache_key(path);
+        let mut state = self.state.lock();
+        let Some(entry) = state.entries.get_mut(&key) else {
+            return;
+        };
+        if entry.source != old_source {
+            state.entries.remove(&key);
+            return;
+        }
+        if let Some(edit) = compute_input_edit(old_source, new_source) {
+            entry.tree.edit(&edit);
+            entry.source = new_source.to_string();
+            entry.edited = true;
+        }
+    }
+
+    /// Forget the cached tree for `path`.
+    pub fn invalidate(&self, path: &Path) {
+        self.state.lock().entries.remove(&cache_key(path));
+    }
+
+    pub fn clear(&self) {
+        self.state.lock().entries.clear();
+    }
+
+    /// Look up the tree to reuse when parsing `source` for `path`.
+    pub(crate) fn prepare(
+        &self,
+        path: &Path,
+        source: &str,
+        language: LanguageSupport,
+    ) -> CachedParse {
+        let key = cache_key(path);
+        let mut state = self.state.lock();
+        let now = state.tick();
+        let Some(entry) = state.entries.get_mut(&key) else {
+            return CachedParse::Miss;
+        };
+        if entry.language != language {
+            state.entries.remove(&key);
+            return CachedParse::Miss;
+        }
+        entry.last_used = now;
+
+        if entry.source != source {
+            // The file changed outside the edit tools; diff against what we last saw.
+            if let Some(edit) = compute_input_edit(&entry.source, source) {
+                entry.tree.edit(&edit);
+            }
+            entry.source = source.to_string();
+            entry.edited = true;
+        }
+
+        if entry.edited {
+            CachedParse::Stale(entry.tree.clone())
+        } else {
+            CachedParse::Fresh(entry.tree.clone())
+        }
+    }
+
+    /// Store the tree produced for `source`.
+    pub(crate) fn store(&self, path: &Path, language: LanguageSupport, source: &str, tree: Tree) {
+        let key = cache_key(path);
+        let mut state = self.state.lock();
+        let now = state.tick();
+        if !state.entries.contains_key(&key) && state.entries.len() >= MAX_CACHED_TREES {
+            state.evict_least_recently_used();
+        }
+        state.entries.insert(
+            key,
+            CachedTree {
+                language,
+                source: source.to_string(),
+                tree,
+                edited: false,
+                last_used: now,
+            },
+        );
+    }
+}
+
+fn cache_key(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Describe the change from `old` to `new` as a single tree-sitter edit.
+///
+/// The edited range spans everything between the longest common prefix and
+/// the longest common suffix of the two texts. Returns `None` when the texts
+/// are identical.
+pub fn compute_input_edit(old: &str, new: &str) -> Option<InputEdit> {
+    if old == new {
+        return None;
+    }
+
+    let old_bytes = old.as_bytes();
+    let new_bytes = new.as_bytes();
+    let prefix = old_bytes
+        .iter()
+        .zip(new_bytes)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let max_suffix = old_bytes.len().min(new_bytes.len()) - prefix;
+    let suffix = old_bytes
+        .iter()
+        .rev()
+        .zip(new_bytes.iter().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let start_byte = prefix;
+    let old_end_byte = old_bytes.len() - suffix;
+    let new_end_byte = new_bytes.len() - suffix;
+
+    Some(InputEdit {
+        start_byte,
+        old_end_byte,
+        new_end_byte,
+        start_position: point_at(old_bytes, start_byte),
+        old_end_position: point_at(old_bytes, old_end_byte),
+        new_end_position: point_at(new_bytes, new_end_byte),
+    })
+}
+
+fn point_at(text: &[u8], offset: usize) -> Point {
+    let before = &text[..offset];
+    let row = before.iter().filter(|&&b| b == b'\n').count();
+    let column = match before.iter().rposition(|&b| b == b'\n') {
+        Some(newline) => offset - newline - 1,
+        None => offset,
+    };
+    Point { row, column }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn input_edit_covers_changed_region() {
+        let old = "fn a() {}\nfn b() {}\n";
+        let new = "fn a() {}\nfn bc() {}\n";
+        let edit = compute_input_edit(old, new).unwrap();
+
+        assert_eq!(edit.start_byte, 14);
+        assert_eq!(edit.old_end_byte, 14);
+        assert_eq!(edit.new_end_byte, 15);
+        assert_eq!(edit.start_position, Point { row: 1, column: 4 });
+        assert_eq!(edit.new_end_position, Point { row: 1, column: 5 });
+        assert!(compute_input_edit(old, old).is_none());
+    }
+
+    #[test]
+    fn input_edit_handles_deletions_across_lines() {
+        let old = "a\nbb\nccc\n";
+        let new = "a\nccc\n";
+        let edit = compute_input_edit(old, new).unwrap();
+
+        assert_eq!(&old[edit.start_byte..edit.old_end_byte], "bb\n");
+        assert_eq!(edit.new_end_byte, edit.start_byte);
+        assert_eq!(edit.old_end_position, Point { row: 2, column: 0 });
+    }
+}
@@ -7,19 +7,25 @@
 //!
 //! - **Multi-language Support**: Rust, Python, JavaScript, TypeScript, Go, Java
 //! - **Syntax Tree Analysis**: Parse code into structured syntax trees
+//! - **Incremental Parsing**: Re-parse only the regions touched by file edits
 //! - **Symbol Extraction**: Extract functions, classes, variables, and imports
+//! - **Symbol Outlines**: Line-ordered, nested definition lists for a file
 //! - **Code Navigation**: Navigate code structures with precision
 //! - **Semantic Analysis**: Understand code semantics beyond syntax
 //! - **Refactoring Support**: Intelligent code manipulation capabilities
 
 pub mod analysis;
 pub mod analyzer;
+pub mod incremental;
 pub mod languages;
 pub mod navigation;
+pub mod outline;
 pub mod refactoring;
 
 pub use analysis::*;
 pub use analyzer::*;
+pub use incremental::*;
 pub use languages::*;
 pub use navigation::*;
+pub use outline::*;
 pub use refactoring::*;
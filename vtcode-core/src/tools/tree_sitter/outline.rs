@@ -0,0 +1,161 @@
+//! Symbol outlines for a single file
+//!
+//! An outline is the flat, line-ordered list of definitions in a file
+//! (functions, methods, classes and other types) together with their nesting
+//! depth, suitable for rendering as a navigable tree.
+
+use crate::tools::tree_sitter::analyzer::TreeSitterAnalyzer;
+use crate::tools::tree_sitter::languages::SymbolKind;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// One definition in a file outline.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OutlineEntry {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// 1-based line of the definition
+    pub line: usize,
+    /// 1-based last line of the definition
+    pub end_line: usize,
+    /// Number of enclosing outline entries, e.g. 1 for a method in a class
+    pub depth: usize,
+}
+
+impl OutlineEntry {
+    /// Short label for the entry kind
+    pub fn kind_label(&self) -> &'static str {
+        match self.kind {
+            SymbolKind::Function if self.depth > 0 => "method",
+            SymbolKind::Function => "fn",
+            SymbolKind::Method => "method",
+            SymbolKind::Class => "class",
+            SymbolKind::Struct => "struct",
+            SymbolKind::Interface => "interface",
+            SymbolKind::Trait => "trait",
+            SymbolKind::Module => "mod",
+            SymbolKind::Type => "type",
+            SymbolKind::Variable => "var",
+            SymbolKind::Constant => "const",
+            SymbolKind::Import => "import",
+        }
+    }
+}
+
+fn is_outline_kind(kind: &SymbolKind) -> bool {
+    !matches!(
+        kind,
+        SymbolKind::Variable | SymbolKind::Constant | SymbolKind::Import
+    )
+}
+
+/// Whether a node kind is one of the definitions symbols are extracted from.
+fn is_definition_node(kind: &str) -> bool {
+    kind.ends_with("_item")
+        || kind.ends_with("_definition")
+        || kind.ends_with("_declaration")
+        || kind.contains("function")
+        || kind.contains("method")
+}
+
+impl TreeSitterAnalyzer {
+    /// Build the symbol outline of `file_path` from `source_code`.
+    ///
+    /// Parsing goes through the incremental tree cache, so repeated outlines of
+    /// a file that is being edited only re-parse the changed regions.
+    pub fn symbol_outline(
+        &mut self,
+        file_path: &Path,
+        source_code: &str,
+    ) -> Result<Vec<OutlineEntry>> {
+        let language = self.detect_language_from_path(file_path)?;
+        let tree = self.parse_incremental(file_path, source_code, language)?;
+        let symbols = self.extract_symbols(&tree, source_code, language)?;
+        let root = tree.root_node();
+
+        // Byte range of the definition node that starts at each symbol.
+        let mut spans = Vec::new();
+        for symbol in symbols.into_iter().filter(|s| is_outline_kind(&s.kind)) {
+            let start = symbol.position.byte_offset;
+            let mut end = start;
+            let mut end_row = symbol.position.row;
+            if let Some(mut node) = root.descendant_for_byte_range(start, start) {
+                while !is_definition_node(node.kind()) {
+                    match node.parent() {
+                        Some(parent)
+                            if parent.start_byte() == start && parent.id() != root.id() =>
+                        {
+                            node = parent;
+                        }
+                        _ => break,
+                    }
+                }
+                end = node.end_byte();
+                end_row = node.end_position().row;
+            }
+            spans.push((start, end, end_row, symbol));
+        }
+        spans.sort_by_key(|(start, end, _, _)| (*start, std::cmp::Reverse(*end)));
+
+        let mut entries = Vec::with_capacity(spans.len());
+        let mut open: Vec<usize> = Vec::new();
+        for (start, end, end_row, symbol) in spans {
+            while open.last().is_some_and(|open_end| *open_end <= start) {
+                open.pop();
+            }
+            entries.push(OutlineEntry {
+                name: symbol.name,
+                kind: symbol.kind,
+                line: symbol.position.row + 1,
+                end_line: end_row.max(symbol.position.row) + 1,
+                depth: open.len(),
+            });
+            open.push(end);
+        }
+
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outlines_nested_python_definitions() {
+        let mut analyzer = TreeSitterAnalyzer::new().expect("analyzer");
+        let source = "def top():\n    pass\n\nclass Service:\n    def start(self):\n        pass\n\n    def stop(self):\n        pass\n";
+        let outline = analyzer
+            .symbol_outline(Path::new("service.py"), source)
+            .expect("outline");
+
+        let summary: Vec<(&str, usize, usize)> = outline
+            .iter()
+            .map(|entry| (entry.name.as_str(), entry.line, entry.depth))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("top", 1, 0),
+                ("Service", 4, 0),
+                ("start", 5, 1),
+                ("stop", 8, 1)
+            ]
+        );
+        assert_eq!(outline[2].kind_label(), "method");
+        assert_eq!((outline[1].line, outline[1].end_line), (4, 9));
+    }
+
+    #[test]
+    fn outlines_rust_items_in_line_order() {
+        let mut analyzer = TreeSitterAnalyzer::new().expect("analyzer");
+        let source = "struct Config;\n\nfn load() -> Config {\n    Config\n}\n";
+        let outline = analyzer
+            .symbol_outline(Path::new("config.rs"), source)
+            .expect("outline");
+        let names: Vec<&str> = outline.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, vec!["Config", "load"]);
+        assert!(outline.iter().all(|entry| entry.depth == 0));
+    }
+}
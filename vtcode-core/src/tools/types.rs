@@ -58,7 +58,8 @@ pub struct OperationStats {
 }
 
 /// Input structures for various tools
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 pub struct Input {
     pub path: String,
     #[serde(default)]
@@ -71,9 +72,15 @@ pub struct Input {
     pub chunk_lines: Option<usize>,
     #[serde(default)]
     pub max_lines: Option<usize>,
+    /// Start of the byte range to read from a file with very long lines
+    #[serde(default)]
+    pub byte_offset: Option<usize>,
+    #[serde(default)]
+    pub byte_length: Option<usize>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 pub struct WriteInput {
     pub path: String,
     pub content: String,
@@ -87,7 +94,8 @@ pub struct WriteInput {
     pub ast_grep_refactor: bool,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 pub struct EditInput {
     pub path: String,
     pub old_str: String,
@@ -99,6 +107,7 @@ pub struct EditInput {
 }
 
 #[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 pub struct ListInput {
     pub path: String,
     #[serde(default = "default_max_items")]
@@ -127,9 +136,13 @@ pub struct ListInput {
     pub file_extensions: Option<Vec<String>>, // Filter by extensions
     #[serde(default)]
     pub case_sensitive: Option<bool>, // For pattern matching
+    /// Also list paths excluded by `[index.exclude]`
+    #[serde(default)]
+    pub include_generated: bool,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 pub struct EnhancedTerminalInput {
     pub command: Vec<String>,
     #[serde(default)]
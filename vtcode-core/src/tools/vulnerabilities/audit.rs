@@ -0,0 +1,301 @@
+//! Parsers for the JSON reports of cargo-audit, npm audit and pip-audit
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Characters of an advisory description kept as its title.
+const MAX_TITLE_CHARS: usize = 120;
+
+/// Advisory severity, as reported by npm audit. cargo-audit and pip-audit do
+/// not report one, so their findings are `Unknown`.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Default,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Unknown,
+    Low,
+    Moderate,
+    High,
+    Critical,
+}
+
+impl Severity {
+    fn parse(value: &str) -> Self {
+        match value.to_ascii_lowercase().as_str() {
+            "info" | "low" => Self::Low,
+            "moderate" | "medium" => Self::Moderate,
+            "high" => Self::High,
+            "critical" => Self::Critical,
+            _ => Self::Unknown,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Unknown => "unknown",
+            Self::Low => "low",
+            Self::Moderate => "moderate",
+            Self::High => "high",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+/// One advisory affecting one dependency.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Finding {
+    /// `cargo`, `npm` or `pip`
+    pub ecosystem: String,
+    pub package: String,
+    /// Installed version, when the report names it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Vulnerable version range, when the report names it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub affected: Option<String>,
+    /// Advisory identifier such as `RUSTSEC-2020-0071` or `GHSA-…`
+    pub id: String,
+    #[serde(default)]
+    pub severity: Severity,
+    pub title: String,
+    /// How to get a fixed version, e.g. `upgrade to >=0.2.23`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+impl Finding {
+    /// Whether a fixed version is available.
+    pub fn is_actionable(&self) -> bool {
+        self.fix.is_some()
+    }
+
+    /// One-line summary used in the context block.
+    pub fn summary(&self) -> String {
+        let mut line = format!(
+            "[{}] {} {}",
+            self.severity.as_str(),
+            self.ecosystem,
+            self.package
+        );
+        if let Some(version) = self.version.as_deref().or(self.affected.as_deref()) {
+            line.push(' ');
+            line.push_str(version);
+        }
+        line.push_str(&format!(": {} {}", self.id, self.title));
+        if let Some(fix) = &self.fix {
+            line.push_str(&format!(" (fix: {fix})"));
+        }
+        line
+    }
+}
+
+/// Parse a report from any of the supported tools, detected by its shape.
+/// Returns the ecosystem and its findings, or `None` for an unknown format.
+pub fn parse_report(text: &str) -> Option<(&'static str, Vec<Finding>)> {
+    let value: Value = serde_json::from_str(text.trim()).ok()?;
+    if value.pointer("/vulnerabilities/list").is_some() {
+        Some(("cargo", parse_cargo_audit(&value)))
+    } else if value.get("auditReportVersion").is_some()
+        || value.get("vulnerabilities").is_some_and(Value::is_object)
+    {
+        Some(("npm", parse_npm_audit(&value)))
+    } else if value.get("dependencies").is_some_and(Value::is_array) {
+        Some(("pip", parse_pip_audit(&value["dependencies"])))
+    } else if value.is_array() {
+        // pip-audit before 2.5 printed the dependency list on its own.
+        Some(("pip", parse_pip_audit(&value)))
+    } else {
+        None
+    }
+}
+
+/// `cargo audit --json`
+fn parse_cargo_audit(value: &Value) -> Vec<Finding> {
+    let entries = value
+        .pointer("/vulnerabilities/list")
+        .and_then(Value::as_array);
+    entries
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let advisory = entry.get("advisory")?;
+            let patched: Vec<&str> = entry
+                .pointer("/versions/patched")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .collect();
+            Some(Finding {
+                ecosystem: "cargo".to_string(),
+                package: string(entry.pointer("/package/name"))
+                    .or_else(|| string(advisory.get("package")))?,
+                version: string(entry.pointer("/package/version")),
+                affected: None,
+                id: string(advisory.get("id"))?,
+                severity: Severity::Unknown,
+                title: string(advisory.get("title")).unwrap_or_default(),
+                fix: (!patched.is_empty()).then(|| format!("upgrade to {}", patched.join(" or "))),
+                url: string(advisory.get("url")),
+            })
+        })
+        .collect()
+}
+
+/// `npm audit --json` (report version 2, npm 7 and later)
+fn parse_npm_audit(value: &Value) -> Vec<Finding> {
+    let Some(packages) = value.get("vulnerabilities").and_then(Value::as_object) else {
+        return Vec::new();
+    };
+    let mut findings = Vec::new();
+    for (name, entry) in packages {
+        let fix = match entry.get("fixAvailable") {
+            Some(Value::Bool(true)) => Some("npm audit fix".to_string()),
+            Some(fix @ Value::Object(_)) => string(fix.get("name")).map(|package| {
+                let version = string(fix.get("version")).unwrap_or_default();
+                let major = fix
+                    .get("isSemVerMajor")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false);
+                format!(
+                    "upgrade {package} to {version}{}",
+                    if major { " (major)" } else { "" }
+                )
+            }),
+            _ => None,
+        };
+        // Entries whose `via` only names other packages are reported under those.
+        let advisories = entry.get("via").and_then(Value::as_array);
+        for advisory in advisories
+            .into_iter()
+            .flatten()
+            .filter(|via| via.is_object())
+        {
+            let url = string(advisory.get("url"));
+            let id = url
+                .as_deref()
+                .and_then(|url| url.rsplit('/').next())
+                .map(str::to_string)
+                .or_else(|| advisory.get("source").map(Value::to_string))
+                .unwrap_or_else(|| "advisory".to_string());
+            findings.push(Finding {
+                ecosystem: "npm".to_string(),
+                package: name.clone(),
+                version: None,
+                affected: string(advisory.get("range")).or_else(|| string(entry.get("range"))),
+                id,
+                severity: advisory
+                    .get("severity")
+                    .or_else(|| entry.get("severity"))
+                    .and_then(Value::as_str)
+                    .map(Severity::parse)
+                    .unwrap_or_default(),
+                title: string(advisory.get("title")).unwrap_or_default(),
+                fix: fix.clone(),
+                url,
+            });
+        }
+    }
+    findings
+}
+
+/// `pip-audit -f json` dependency list
+fn parse_pip_audit(dependencies: &Value) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for dependency in dependencies.as_array().into_iter().flatten() {
+        let Some(package) = string(dependency.get("name")) else {
+            continue;
+        };
+        let version = string(dependency.get("version"));
+        let vulns = dependency.get("vulns").and_then(Value::as_array);
+        for vuln in vulns.into_iter().flatten() {
+            let Some(id) = string(vuln.get("id")) else {
+                continue;
+            };
+            let fixes: Vec<&str> = vuln
+                .get("fix_versions")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .collect();
+            let description = string(vuln.get("description")).unwrap_or_default();
+            findings.push(Finding {
+                ecosystem: "pip".to_string(),
+                package: package.clone(),
+                version: version.clone(),
+                affected: None,
+                id,
+                severity: Severity::Unknown,
+                title: first_sentence(&description),
+                fix: fixes.first().map(|fixed| format!("upgrade to {fixed}")),
+                url: None,
+            });
+        }
+    }
+    findings
+}
+
+fn string(value: Option<&Value>) -> Option<String> {
+    value
+        .and_then(Value::as_str)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+fn first_sentence(text: &str) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let sentence = text.split(". ").next().unwrap_or_default();
+    let mut title: String = sentence.chars().take(MAX_TITLE_CHARS).collect();
+    if title.len() < sentence.len() {
+        title.push('…');
+    }
+    title
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_cargo_npm_and_pip_reports() {
+        let cargo = r#"{"database":{},"vulnerabilities":{"found":true,"count":1,"list":[
+            {"advisory":{"id":"RUSTSEC-2020-0071","package":"time","title":"Potential segfault in the time crate","url":"https://github.com/time-rs/time/issues/293"},
+             "versions":{"patched":[">=0.2.23"],"unaffected":["=0.2.0"]},
+             "package":{"name":"time","version":"0.1.45"}}]},"warnings":{}}"#;
+        let (ecosystem, findings) = parse_report(cargo).unwrap();
+        assert_eq!(ecosystem, "cargo");
+        assert_eq!(findings[0].version.as_deref(), Some("0.1.45"));
+        assert_eq!(findings[0].fix.as_deref(), Some("upgrade to >=0.2.23"));
+
+        let npm = r#"{"auditReportVersion":2,"vulnerabilities":{
+            "minimist":{"name":"minimist","severity":"critical","isDirect":false,
+              "via":[{"source":1096466,"name":"minimist","title":"Prototype Pollution in minimist","url":"https://github.com/advisories/GHSA-xvch-5gv4-984h","severity":"critical","range":"<0.2.4"}],
+              "range":"<0.2.4","fixAvailable":true},
+            "mkdirp":{"name":"mkdirp","severity":"critical","via":["minimist"],"range":"0.4.1 - 0.5.1",
+              "fixAvailable":{"name":"mkdirp","version":"1.0.4","isSemVerMajor":true}}}}"#;
+        let (ecosystem, findings) = parse_report(npm).unwrap();
+        assert_eq!(ecosystem, "npm");
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].id, "GHSA-xvch-5gv4-984h");
+        assert_eq!(findings[0].severity, Severity::Critical);
+        assert!(findings[0].is_actionable());
+
+        let pip = r#"{"dependencies":[{"name":"flask","version":"0.5","vulns":[
+            {"id":"PYSEC-2019-179","fix_versions":["1.0"],"aliases":[],"description":"The Pallets Project Flask before 1.0 is affected by unexpected memory usage. More text."}]},
+            {"name":"jinja2","version":"3.1.4","vulns":[]}],"fixes":[]}"#;
+        let (ecosystem, findings) = parse_report(pip).unwrap();
+        assert_eq!(ecosystem, "pip");
+        assert_eq!(
+            findings[0].summary(),
+            "[unknown] pip flask 0.5: PYSEC-2019-179 The Pallets Project Flask before 1.0 \
+             is affected by unexpected memory usage (fix: upgrade to 1.0)"
+        );
+
+        assert!(parse_report("not json").is_none());
+    }
+}
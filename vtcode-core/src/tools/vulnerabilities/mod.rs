@@ -0,0 +1,489 @@
+//! Dependency vulnerability scanning
+//!
+//! [`VulnerabilityScanTool`] runs the audit tool for each manifest found at the
+//! workspace root (`cargo audit` for `Cargo.lock`, `npm audit` for
+//! `package-lock.json`, `pip-audit` for `requirements.txt` or `pyproject.toml`)
+//! and reads any existing reports listed under `[tools.vulnerability_scan]`.
+//! Results are cached in `.vtcode/cache/vulnerabilities.json` until a manifest
+//! changes or they grow older than `refresh_hours`, and
+//! [`VulnerabilityContextProvider`] adds the cached findings to each request so
+//! the agent does not recommend vulnerable versions and can plan upgrades.
+
+mod audit;
+
+pub use audit::{Finding, Severity, parse_report};
+
+use crate::config::VulnerabilityScanConfig;
+use crate::config::constants::tools;
+use crate::core::context_providers::{ContextBlock, ContextProvider, ContextQuery};
+use crate::tools::traits::Tool;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::process::Command;
+use tokio::time::timeout;
+
+const CACHE_FILE: &str = ".vtcode/cache/vulnerabilities.json";
+/// Trailing characters of stderr kept when an audit tool gives no report.
+const STDERR_TAIL_CHARS: usize = 400;
+
+/// An audit tool and the manifests that select it.
+struct Scanner {
+    ecosystem: &'static str,
+    /// Files whose presence selects the scanner, in order of preference
+    manifests: &'static [&'static str],
+    program: &'static str,
+    /// How the scan is cited as a source
+    label: &'static str,
+    install_hint: &'static str,
+}
+
+const SCANNERS: &[Scanner] = &[
+    Scanner {
+        ecosystem: "cargo",
+        manifests: &["Cargo.lock"],
+        program: "cargo",
+        label: "cargo audit",
+        install_hint: "cargo install cargo-audit",
+    },
+    Scanner {
+        ecosystem: "npm",
+        manifests: &["package-lock.json"],
+        program: "npm",
+        label: "npm audit",
+        install_hint: "install Node.js, which ships npm",
+    },
+    Scanner {
+        ecosystem: "pip",
+        manifests: &["requirements.txt", "pyproject.toml"],
+        program: "pip-audit",
+        label: "pip-audit",
+        install_hint: "pipx install pip-audit",
+    },
+];
+
+impl Scanner {
+    fn args(&self, manifest: &str) -> Vec<&'static str> {
+        match (self.ecosystem, manifest) {
+            ("cargo", _) => vec!["audit", "--json"],
+            ("npm", _) => vec!["audit", "--json"],
+            (_, "requirements.txt") => vec![
+                "-f",
+                "json",
+                "--progress-spinner",
+                "off",
+                "-r",
+                "requirements.txt",
+            ],
+            _ => vec!["-f", "json", "--progress-spinner", "off", "."],
+        }
+    }
+}
+
+/// An ecosystem that was not scanned, with the reason.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkippedScan {
+    pub ecosystem: String,
+    pub reason: String,
+}
+
+/// Result of one scan, as cached on disk.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanReport {
+    /// Seconds since the Unix epoch
+    pub scanned_at: u64,
+    /// Hash of the manifests the scan saw
+    pub fingerprint: String,
+    /// Audit tools run and reports read
+    pub sources: Vec<String>,
+    pub findings: Vec<Finding>,
+    pub skipped: Vec<SkippedScan>,
+}
+
+impl ScanReport {
+    /// Findings with a fixed version available.
+    pub fn actionable(&self) -> usize {
+        self.findings
+            .iter()
+            .filter(|finding| finding.is_actionable())
+            .count()
+    }
+
+    /// Context block listing the findings, `None` when there are none.
+    fn context_block(&self, max_findings: usize) -> Option<ContextBlock> {
+        if self.findings.is_empty() {
+            return None;
+        }
+        let mut content = String::from(
+            "Do not recommend or pin the versions below. When touching these dependencies, \
+             prefer the listed fixes and propose an upgrade plan for the rest.\n",
+        );
+        for finding in self.findings.iter().take(max_findings) {
+            content.push_str("- ");
+            content.push_str(&finding.summary());
+            content.push('\n');
+        }
+        let hidden = self.findings.len().saturating_sub(max_findings);
+        if hidden > 0 {
+            content.push_str(&format!(
+                "- …and {hidden} more; call {} for the full list\n",
+                tools::VULNERABILITY_SCAN
+            ));
+        }
+        Some(
+            ContextBlock::new(
+                format!(
+                    "Known dependency vulnerabilities ({} found, {} fixable)",
+                    self.findings.len(),
+                    self.actionable()
+                ),
+                content.trim_end(),
+            )
+            .with_source(self.sources.join(", ")),
+        )
+    }
+}
+
+/// Runs the audit tools and keeps the cache.
+#[derive(Clone)]
+pub struct VulnerabilityScanner {
+    workspace_root: PathBuf,
+    config: VulnerabilityScanConfig,
+}
+
+impl VulnerabilityScanner {
+    pub fn new(workspace_root: PathBuf, config: VulnerabilityScanConfig) -> Self {
+        Self {
+            workspace_root,
+            config,
+        }
+    }
+
+    /// The cached report, if any, and whether it is still current.
+    pub fn cached(&self) -> Option<(ScanReport, bool)> {
+        let content = std::fs::read_to_string(self.workspace_root.join(CACHE_FILE)).ok()?;
+        let report: ScanReport = serde_json::from_str(&content).ok()?;
+        let age = now_secs().saturating_sub(report.scanned_at);
+        let fresh =
+            report.fingerprint == self.fingerprint() && age < self.config.refresh_hours * 3600;
+        Some((report, fresh))
+    }
+
+    /// Run the scanners and read the configured reports, then cache the result.
+    pub async fn scan(&self) -> Result<ScanReport> {
+        let mut report = ScanReport {
+            scanned_at: now_secs(),
+            fingerprint: self.fingerprint(),
+            ..ScanReport::default()
+        };
+        if self.config.run_scanners {
+            for scanner in SCANNERS {
+                let Some(manifest) = scanner
+                    .manifests
+                    .iter()
+                    .find(|manifest| self.workspace_root.join(manifest).is_file())
+                else {
+                    continue;
+                };
+                match self.run(scanner, manifest).await {
+                    Ok(findings) => {
+                        report.sources.push(scanner.label.to_string());
+                        report.findings.extend(findings);
+                    }
+                    Err(err) => report.skipped.push(SkippedScan {
+                        ecosystem: scanner.ecosystem.to_string(),
+                        reason: format!("{err:#}"),
+                    }),
+                }
+            }
+        }
+        for path in &self.config.reports {
+            let parsed = std::fs::read_to_string(self.workspace_root.join(path))
+                .ok()
+                .and_then(|content| parse_report(&content));
+            match parsed {
+                Some((_, findings)) => {
+                    report.sources.push(path.clone());
+                    report.findings.extend(findings);
+                }
+                None => report.skipped.push(SkippedScan {
+                    ecosystem: path.clone(),
+                    reason: "report missing or not in a supported format".to_string(),
+                }),
+            }
+        }
+        report.findings.sort_by(|a, b| {
+            b.severity
+                .cmp(&a.severity)
+                .then_with(|| b.is_actionable().cmp(&a.is_actionable()))
+                .then_with(|| a.package.cmp(&b.package))
+        });
+        report.findings.dedup();
+
+        let cache = self.workspace_root.join(CACHE_FILE);
+        if let Some(parent) = cache.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        std::fs::write(&cache, serde_json::to_string_pretty(&report)?)
+            .with_context(|| format!("failed to write {}", cache.display()))?;
+        Ok(report)
+    }
+
+    /// Run one audit tool; its report is read whatever the exit code, since
+    /// the tools exit non-zero when they find something.
+    async fn run(&self, scanner: &Scanner, manifest: &str) -> Result<Vec<Finding>> {
+        let mut cmd = Command::new(scanner.program);
+        cmd.args(scanner.args(manifest))
+            .current_dir(&self.workspace_root)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        let limit = Duration::from_secs(self.config.timeout_secs);
+        let output = match timeout(limit, cmd.output()).await {
+            Err(_) => anyhow::bail!("timed out after {}s", limit.as_secs()),
+            Ok(Err(err)) if err.kind() == std::io::ErrorKind::NotFound => {
+                anyhow::bail!("{} not found; {}", scanner.program, scanner.install_hint)
+            }
+            Ok(output) => output.with_context(|| format!("failed to run {}", scanner.program))?,
+        };
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if let Some((_, findings)) = parse_report(&stdout) {
+            return Ok(findings);
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stderr = stderr.trim();
+        let tail: String = stderr
+            .chars()
+            .skip(stderr.chars().count().saturating_sub(STDERR_TAIL_CHARS))
+            .collect();
+        anyhow::bail!(
+            "no report from {} (exit {}): {}; if the subcommand is missing, {}",
+            scanner.program,
+            output.status.code().unwrap_or(-1),
+            tail,
+            scanner.install_hint
+        )
+    }
+
+    /// Hash of the manifest and report paths with their sizes and mtimes.
+    fn fingerprint(&self) -> String {
+        let mut hasher = DefaultHasher::new();
+        let reports = self.config.reports.iter().map(String::as_str);
+        let manifests = SCANNERS
+            .iter()
+            .flat_map(|scanner| scanner.manifests.iter().copied());
+        for path in manifests.chain(reports) {
+            path.hash(&mut hasher);
+            if let Ok(metadata) = std::fs::metadata(self.workspace_root.join(path)) {
+                metadata.len().hash(&mut hasher);
+                metadata.modified().ok().hash(&mut hasher);
+            }
+        }
+        format!("{:016x}", hasher.finish())
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct VulnerabilityScanInput {
+    /// Scan again even if the cached results are current
+    #[serde(default)]
+    refresh: bool,
+    /// Only report findings with a fixed version available
+    #[serde(default)]
+    fixable_only: bool,
+}
+
+/// Tool that scans the workspace dependencies for known advisories.
+#[derive(Clone)]
+pub struct VulnerabilityScanTool {
+    workspace_root: PathBuf,
+    config: VulnerabilityScanConfig,
+}
+
+impl VulnerabilityScanTool {
+    pub fn new(workspace_root: PathBuf) -> Self {
+        Self {
+            workspace_root,
+            config: VulnerabilityScanConfig::default(),
+        }
+    }
+
+    pub fn set_config(&mut self, config: VulnerabilityScanConfig) {
+        self.config = config;
+    }
+
+    fn scanner(&self) -> VulnerabilityScanner {
+        VulnerabilityScanner::new(self.workspace_root.clone(), self.config.clone())
+    }
+}
+
+#[async_trait]
+impl Tool for VulnerabilityScanTool {
+    async fn execute(&self, args: Value) -> Result<Value> {
+        let input: VulnerabilityScanInput = if args.is_null() {
+            VulnerabilityScanInput::default()
+        } else {
+            serde_json::from_value(args).context(
+                "Error: Invalid 'vulnerability_scan' arguments. Optional: { refresh: boolean, fixable_only: boolean }",
+            )?
+        };
+        let scanner = self.scanner();
+        let (report, cached) = match scanner.cached() {
+            Some((report, true)) if !input.refresh => (report, true),
+            _ => (scanner.scan().await?, false),
+        };
+
+        let actionable = report.actionable();
+        let findings: Vec<&Finding> = report
+            .findings
+            .iter()
+            .filter(|finding| !input.fixable_only || finding.is_actionable())
+            .collect();
+        let message = if report.sources.is_empty() {
+            "No manifests with a supported audit tool and no reports configured; see 'skipped'."
+                .to_string()
+        } else if report.findings.is_empty() {
+            "No known vulnerabilities in the scanned dependencies.".to_string()
+        } else {
+            format!(
+                "{} known vulnerabilit{}, {} with a fixed version. Propose upgrades using 'fix' \
+                 and avoid the affected versions.",
+                report.findings.len(),
+                if report.findings.len() == 1 {
+                    "y"
+                } else {
+                    "ies"
+                },
+                actionable
+            )
+        };
+        Ok(json!({
+            "success": true,
+            "cached": cached,
+            "sources": report.sources,
+            "findings": findings,
+            "actionable": actionable,
+            "skipped": report.skipped,
+            "message": message,
+        }))
+    }
+
+    fn name(&self) -> &'static str {
+        tools::VULNERABILITY_SCAN
+    }
+
+    fn description(&self) -> &'static str {
+        "Scan dependencies with cargo-audit, npm audit or pip-audit and list fixable advisories"
+    }
+}
+
+/// Adds the known advisories to each request.
+///
+/// Gathering never waits for a scan: a stale or missing cache starts one in
+/// the background and the previous results, if any, are used meanwhile.
+pub struct VulnerabilityContextProvider {
+    scanner: VulnerabilityScanner,
+    scanning: Arc<AtomicBool>,
+}
+
+impl VulnerabilityContextProvider {
+    pub fn new(workspace_root: &Path, config: VulnerabilityScanConfig) -> Self {
+        Self {
+            scanner: VulnerabilityScanner::new(workspace_root.to_path_buf(), config),
+            scanning: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    fn refresh_in_background(&self) {
+        if self.scanning.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let scanner = self.scanner.clone();
+        let scanning = Arc::clone(&self.scanning);
+        tokio::spawn(async move {
+            if let Err(err) = scanner.scan().await {
+                tracing::warn!("vulnerability scan failed: {err:#}");
+            }
+            scanning.store(false, Ordering::SeqCst);
+        });
+    }
+}
+
+#[async_trait]
+impl ContextProvider for VulnerabilityContextProvider {
+    fn name(&self) -> &str {
+        "vulnerabilities"
+    }
+
+    async fn gather(&self, _query: &ContextQuery) -> Result<Vec<ContextBlock>> {
+        let report = match self.scanner.cached() {
+            Some((report, true)) => report,
+            Some((report, false)) => {
+                self.refresh_in_background();
+                report
+            }
+            None => {
+                self.refresh_in_background();
+                return Ok(Vec::new());
+            }
+        };
+        Ok(report
+            .context_block(self.scanner.config.max_context_findings)
+            .into_iter()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn reads_configured_reports_and_caches_them() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let root = dir.path().to_path_buf();
+        std::fs::write(
+            root.join("pip-audit.json"),
+            r#"[{"name":"flask","version":"0.5","vulns":[{"id":"PYSEC-2019-179","fix_versions":["1.0"],"description":"Memory usage."}]}]"#,
+        )
+        .expect("report");
+        let mut tool = VulnerabilityScanTool::new(root.clone());
+        tool.set_config(VulnerabilityScanConfig {
+            enabled: true,
+            run_scanners: false,
+            reports: vec!["pip-audit.json".to_string()],
+            ..VulnerabilityScanConfig::default()
+        });
+
+        let result = tool.execute(json!({})).await.expect("scan");
+        assert_eq!(result["cached"], json!(false));
+        assert_eq!(result["actionable"], json!(1));
+        assert_eq!(result["findings"][0]["fix"], json!("upgrade to 1.0"));
+
+        let provider = VulnerabilityContextProvider::new(&root, tool.config.clone());
+        let query = ContextQuery {
+            text: "bump flask".to_string(),
+            workspace: root.clone(),
+        };
+        let blocks = provider.gather(&query).await.expect("gather");
+        assert!(blocks[0].content.contains("PYSEC-2019-179"));
+        assert_eq!(blocks[0].source.as_deref(), Some("pip-audit.json"));
+    }
+}
@@ -0,0 +1,221 @@
+//! Write quotas for the file tools
+//!
+//! [`WriteQuota`] enforces `[tools.write_quota]`: the size of a single write,
+//! the number of files created per turn and the bytes written per session.
+//! The file tools, `apply_patch`, `config_edit` and `organize_imports` share
+//! one quota; `srgn` and terminal commands write outside it. A write that
+//! would exceed a limit is refused before anything touches the disk with a
+//! [`QuotaExceeded`] error, which the registry reports to the model as a
+//! structured `QuotaExceeded` tool error.
+
+use crate::config::WriteQuotaConfig;
+use parking_lot::Mutex;
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::sync::Arc;
+
+/// Which limit a write ran into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaKind {
+    MaxWriteBytes,
+    MaxFilesCreatedPerTurn,
+    MaxSessionBytes,
+}
+
+impl QuotaKind {
+    /// Key of the limit in `[tools.write_quota]`.
+    pub fn config_key(self) -> &'static str {
+        match self {
+            Self::MaxWriteBytes => "max_write_bytes",
+            Self::MaxFilesCreatedPerTurn => "max_files_created_per_turn",
+            Self::MaxSessionBytes => "max_session_bytes",
+        }
+    }
+}
+
+/// A write refused because it would exceed a quota.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("{}", self.describe())]
+pub struct QuotaExceeded {
+    pub quota: QuotaKind,
+    pub path: String,
+    pub limit: u64,
+    /// Bytes (or files) this write needs
+    pub requested: u64,
+    /// Bytes (or files) already used against the limit
+    pub used: u64,
+}
+
+impl QuotaExceeded {
+    fn describe(&self) -> String {
+        let key = self.quota.config_key();
+        match self.quota {
+            QuotaKind::MaxWriteBytes => format!(
+                "Write quota exceeded: {} would be {} bytes, over the {}-byte limit per write ([tools.write_quota] {}). Nothing was written.",
+                self.path, self.requested, self.limit, key
+            ),
+            QuotaKind::MaxFilesCreatedPerTurn => format!(
+                "Write quota exceeded: creating {} would be new file {} this turn, over the limit of {} ([tools.write_quota] {}). Nothing was written.",
+                self.path,
+                self.used + self.requested,
+                self.limit,
+                key
+            ),
+            QuotaKind::MaxSessionBytes => format!(
+                "Write quota exceeded: {} needs {} bytes but only {} of the {}-byte session limit remain ([tools.write_quota] {}). Nothing was written.",
+                self.path,
+                self.requested,
+                self.limit.saturating_sub(self.used),
+                self.limit,
+                key
+            ),
+        }
+    }
+
+    /// Quota details attached to the tool error.
+    pub fn to_json_value(&self) -> Value {
+        json!({
+            "quota": self.quota,
+            "config_key": format!("tools.write_quota.{}", self.quota.config_key()),
+            "path": self.path,
+            "limit": self.limit,
+            "requested": self.requested,
+            "used": self.used,
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+struct QuotaState {
+    config: WriteQuotaConfig,
+    files_created_this_turn: u64,
+    session_bytes: u64,
+}
+
+/// Write limits shared by every clone of the file tools.
+#[derive(Debug, Clone, Default)]
+pub struct WriteQuota {
+    state: Arc<Mutex<QuotaState>>,
+}
+
+impl WriteQuota {
+    pub fn new(config: WriteQuotaConfig) -> Self {
+        let quota = Self::default();
+        quota.set_config(config);
+        quota
+    }
+
+    pub fn set_config(&self, config: WriteQuotaConfig) {
+        self.state.lock().config = config;
+    }
+
+    /// Reset the per-turn file count.
+    pub fn begin_turn(&self) {
+        self.state.lock().files_created_this_turn = 0;
+    }
+
+    /// Reserve a write of `bytes` to `path`, creating it when `creates` is set.
+    /// Nothing is reserved when the write is refused.
+    pub fn reserve(&self, path: &str, bytes: u64, creates: bool) -> Result<(), QuotaExceeded> {
+        let mut state = self.state.lock();
+        let config = &state.config;
+        let refuse = |quota, limit, requested, used| QuotaExceeded {
+            quota,
+            path: path.to_string(),
+            limit,
+            requested,
+            used,
+        };
+        if config.max_write_bytes > 0 && bytes > config.max_write_bytes {
+            return Err(refuse(
+                QuotaKind::MaxWriteBytes,
+                config.max_write_bytes,
+                bytes,
+                0,
+            ));
+        }
+        let per_turn = config.max_files_created_per_turn as u64;
+        if creates && per_turn > 0 && state.files_created_this_turn >= per_turn {
+            return Err(refuse(
+                QuotaKind::MaxFilesCreatedPerTurn,
+                per_turn,
+                1,
+                state.files_created_this_turn,
+            ));
+        }
+        if config.max_session_bytes > 0
+            && state.session_bytes.saturating_add(bytes) > config.max_session_bytes
+        {
+            return Err(refuse(
+                QuotaKind::MaxSessionBytes,
+                config.max_session_bytes,
+                bytes,
+                state.session_bytes,
+            ));
+        }
+        state.session_bytes += bytes;
+        if creates {
+            state.files_created_this_turn += 1;
+        }
+        Ok(())
+    }
+
+    /// Bytes written through the file tools this session.
+    pub fn session_bytes(&self) -> u64 {
+        self.state.lock().session_bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quota(max_write_bytes: u64, max_files: usize, max_session_bytes: u64) -> WriteQuota {
+        WriteQuota::new(WriteQuotaConfig {
+            max_write_bytes,
+            max_files_created_per_turn: max_files,
+            max_session_bytes,
+        })
+    }
+
+    #[test]
+    fn refuses_oversized_writes_and_session_overruns() {
+        let quota = quota(100, 0, 250);
+        let err = quota.reserve("big.log", 101, true).unwrap_err();
+        assert_eq!(err.quota, QuotaKind::MaxWriteBytes);
+        assert_eq!(quota.session_bytes(), 0);
+
+        quota.reserve("a.txt", 100, true).unwrap();
+        quota.reserve("b.txt", 100, true).unwrap();
+        let err = quota.reserve("c.txt", 60, true).unwrap_err();
+        assert_eq!(err.quota, QuotaKind::MaxSessionBytes);
+        assert_eq!((err.limit, err.requested, err.used), (250, 60, 200));
+        assert!(
+            err.to_string()
+                .contains("only 50 of the 250-byte session limit remain")
+        );
+        assert_eq!(
+            err.to_json_value()["config_key"],
+            "tools.write_quota.max_session_bytes"
+        );
+        quota.reserve("c.txt", 50, true).unwrap();
+    }
+
+    #[test]
+    fn counts_created_files_per_turn() {
+        let quota = quota(0, 2, 0);
+        quota.reserve("a.rs", 10, true).unwrap();
+        quota.reserve("a.rs", 10, false).unwrap();
+        quota.reserve("b.rs", 10, true).unwrap();
+        let err = quota.reserve("c.rs", 10, true).unwrap_err();
+        assert_eq!(err.quota, QuotaKind::MaxFilesCreatedPerTurn);
+        assert!(
+            err.to_string()
+                .contains("new file 3 this turn, over the limit of 2")
+        );
+
+        quota.begin_turn();
+        quota.reserve("c.rs", 10, true).unwrap();
+    }
+}
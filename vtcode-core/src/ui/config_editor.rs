@@ -0,0 +1,566 @@
+//! Full-screen form for editing `vtcode.toml`
+//!
+//! Fields are grouped by section (agent, tools, security, ui). Booleans toggle
+//! in place, enumerated values open a filterable picker and numbers or text
+//! open an inline input. Every change is validated before it is applied, and
+//! saving writes the file back through [`ConfigDocument`] so comments survive.
+
+use crate::config::editor::{
+    ConfigDocument, ConfigField, ConfigSection, FieldKind, editor_sections,
+};
+use anyhow::{Context, Result};
+use crossterm::{
+    ExecutableCommand,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+};
+use ratatui::{
+    Frame, Terminal,
+    backend::CrosstermBackend,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Tabs, Wrap},
+};
+use std::io;
+
+const LABEL_WIDTH: usize = 32;
+const PICKER_MAX_HEIGHT: u16 = 16;
+
+enum Mode {
+    Browse,
+    Input {
+        value: String,
+        error: Option<String>,
+    },
+    Pick {
+        filter: String,
+        selected: usize,
+    },
+    ConfirmQuit,
+}
+
+struct Status {
+    text: String,
+    error: bool,
+}
+
+/// State of the config editor, independent of the terminal.
+struct ConfigEditor {
+    document: ConfigDocument,
+    sections: Vec<ConfigSection>,
+    section: usize,
+    list_state: ListState,
+    mode: Mode,
+    status: Option<Status>,
+    done: bool,
+    saved: bool,
+}
+
+impl ConfigEditor {
+    fn new(document: ConfigDocument) -> Self {
+        let sections = editor_sections(&document);
+        let mut list_state = ListState::default();
+        list_state.select(Some(0));
+        Self {
+            document,
+            sections,
+            section: 0,
+            list_state,
+            mode: Mode::Browse,
+            status: None,
+            done: false,
+            saved: false,
+        }
+    }
+
+    fn fields(&self) -> &[ConfigField] {
+        &self.sections[self.section].fields
+    }
+
+    fn selected_field(&self) -> Option<&ConfigField> {
+        self.list_state
+            .selected()
+            .and_then(|index| self.fields().get(index))
+    }
+
+    fn set_status(&mut self, text: impl Into<String>, error: bool) {
+        self.status = Some(Status {
+            text: text.into(),
+            error,
+        });
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        if key.kind == KeyEventKind::Release {
+            return;
+        }
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            match key.code {
+                KeyCode::Char('c') => self.done = true,
+                KeyCode::Char('s') => self.save(),
+                _ => {}
+            }
+            return;
+        }
+        match self.mode {
+            Mode::Browse => self.handle_browse_key(key),
+            Mode::Input { .. } => self.handle_input_key(key),
+            Mode::Pick { .. } => self.handle_pick_key(key),
+            Mode::ConfirmQuit => self.handle_confirm_key(key),
+        }
+    }
+
+    fn handle_browse_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Right | KeyCode::Tab => self.switch_section(1),
+            KeyCode::Left | KeyCode::BackTab => self.switch_section(self.sections.len() - 1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::Enter | KeyCode::Char(' ') => self.begin_edit(),
+            KeyCode::Delete | KeyCode::Backspace | KeyCode::Char('r') => self.reset_selected(),
+            KeyCode::Char('s') => self.save(),
+            KeyCode::Char('q') | KeyCode::Esc => {
+                if self.document.is_dirty() {
+                    self.mode = Mode::ConfirmQuit;
+                } else {
+                    self.done = true;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_input_key(&mut self, key: KeyEvent) {
+        let Mode::Input { value, error } = &mut self.mode else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.mode = Mode::Browse,
+            KeyCode::Enter => {
+                let raw = value.clone();
+                if let Err(err) = self.apply(&raw)
+                    && let Mode::Input { error, .. } = &mut self.mode
+                {
+                    *error = Some(format!("{:#}", err));
+                }
+            }
+            KeyCode::Backspace => {
+                value.pop();
+                *error = None;
+            }
+            KeyCode::Char(ch) => {
+                value.push(ch);
+                *error = None;
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_pick_key(&mut self, key: KeyEvent) {
+        let options = self.picker_options();
+        let Mode::Pick { filter, selected } = &mut self.mode else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.mode = Mode::Browse,
+            KeyCode::Up => *selected = selected.saturating_sub(1),
+            KeyCode::Down => {
+                if *selected + 1 < options.len() {
+                    *selected += 1;
+                }
+            }
+            KeyCode::Backspace => {
+                filter.pop();
+                *selected = 0;
+            }
+            KeyCode::Char(ch) => {
+                filter.push(ch);
+                *selected = 0;
+            }
+            KeyCode::Enter => {
+                let choice = options
+                    .get(*selected)
+                    .cloned()
+                    .unwrap_or_else(|| filter.clone());
+                if let Err(err) = self.apply(&choice) {
+                    self.set_status(format!("{:#}", err), true);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_confirm_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('s') | KeyCode::Char('y') => {
+                self.save();
+                if !self.document.is_dirty() {
+                    self.done = true;
+                }
+            }
+            KeyCode::Char('q') | KeyCode::Char('n') => self.done = true,
+            KeyCode::Esc => {
+                self.mode = Mode::Browse;
+                self.status = None;
+            }
+            _ => {}
+        }
+    }
+
+    fn switch_section(&mut self, step: usize) {
+        self.section = (self.section + step) % self.sections.len();
+        self.list_state.select(Some(0));
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let len = self.fields().len();
+        if len == 0 {
+            return;
+        }
+        let current = self.list_state.selected().unwrap_or(0) as isize;
+        let next = (current + delta).rem_euclid(len as isize) as usize;
+        self.list_state.select(Some(next));
+    }
+
+    fn begin_edit(&mut self) {
+        let Some(field) = self.selected_field().cloned() else {
+            return;
+        };
+        let current = self.document.display_value(&field);
+        match &field.kind {
+            FieldKind::Bool => {
+                let toggled = if current == "true" { "false" } else { "true" };
+                if let Err(err) = self.apply(toggled) {
+                    self.set_status(format!("{:#}", err), true);
+                }
+            }
+            FieldKind::Choice { options, .. } => {
+                let selected = options
+                    .iter()
+                    .position(|option| *option == current)
+                    .unwrap_or(0);
+                self.mode = Mode::Pick {
+                    filter: String::new(),
+                    selected,
+                };
+            }
+            FieldKind::Integer { .. } | FieldKind::Text => {
+                self.mode = Mode::Input {
+                    value: current,
+                    error: None,
+                };
+            }
+        }
+    }
+
+    /// Store `raw` in the selected field and return to browsing.
+    fn apply(&mut self, raw: &str) -> Result<()> {
+        let Some(field) = self.selected_field().cloned() else {
+            return Ok(());
+        };
+        self.document.set(&field, raw)?;
+        self.mode = Mode::Browse;
+        self.set_status(
+            format!("{} = {}", field.key(), self.document.display_value(&field)),
+            false,
+        );
+        Ok(())
+    }
+
+    fn reset_selected(&mut self) {
+        let Some(field) = self.selected_field().cloned() else {
+            return;
+        };
+        match self.document.reset(&field) {
+            Ok(()) => self.set_status(format!("{} reset to default", field.key()), false),
+            Err(err) => self.set_status(format!("{:#}", err), true),
+        }
+    }
+
+    fn save(&mut self) {
+        match self.document.save() {
+            Ok(()) => {
+                self.saved = true;
+                let message = format!("Saved {}", self.document.path().display());
+                self.set_status(message, false);
+            }
+            Err(err) => self.set_status(format!("{:#}", err), true),
+        }
+    }
+
+    /// Options of the selected choice field matching the picker filter.
+    fn picker_options(&self) -> Vec<String> {
+        let Some(FieldKind::Choice { options, .. }) = self.selected_field().map(|f| &f.kind) else {
+            return Vec::new();
+        };
+        let filter = match &self.mode {
+            Mode::Pick { filter, .. } => filter.to_lowercase(),
+            _ => String::new(),
+        };
+        options
+            .iter()
+            .filter(|option| option.to_lowercase().contains(&filter))
+            .cloned()
+            .collect()
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [tabs_area, list_area, help_area, footer_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(3),
+            Constraint::Length(4),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let dirty = if self.document.is_dirty() { " *" } else { "" };
+        let titles: Vec<&str> = self.sections.iter().map(|section| section.title).collect();
+        let tabs = Tabs::new(titles)
+            .select(self.section)
+            .highlight_style(Style::default().add_modifier(Modifier::BOLD | Modifier::REVERSED))
+            .block(Block::default().borders(Borders::ALL).title(format!(
+                " vtcode config · {}{} ",
+                self.document.path().display(),
+                dirty
+            )));
+        frame.render_widget(tabs, tabs_area);
+
+        let items: Vec<ListItem> = self
+            .fields()
+            .iter()
+            .map(|field| {
+                let value = self.document.display_value(field);
+                let value_span = if self.document.is_set(field) {
+                    Span::styled(value, Style::default().add_modifier(Modifier::BOLD))
+                } else {
+                    Span::styled(
+                        format!("{} (default)", value),
+                        Style::default().fg(Color::DarkGray),
+                    )
+                };
+                ListItem::new(Line::from(vec![
+                    Span::raw(format!("{:<width$}", field.label, width = LABEL_WIDTH)),
+                    value_span,
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("› ");
+        frame.render_stateful_widget(list, list_area, &mut self.list_state);
+
+        frame.render_widget(self.help_paragraph(), help_area);
+        frame.render_widget(self.footer(), footer_area);
+
+        if matches!(self.mode, Mode::Pick { .. }) {
+            self.draw_picker(frame, list_area);
+        }
+    }
+
+    fn help_paragraph(&self) -> Paragraph<'_> {
+        let mut lines = Vec::new();
+        if let Some(field) = self.selected_field() {
+            lines.push(Line::from(vec![
+                Span::styled(field.key(), Style::default().fg(Color::Cyan)),
+                Span::raw(format!("  {}", field.help)),
+            ]));
+        }
+        if let Mode::Input { value, error } = &self.mode {
+            lines.push(Line::from(format!("› {}█", value)));
+            if let Some(error) = error {
+                lines.push(Line::styled(error.clone(), Style::default().fg(Color::Red)));
+            }
+        }
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: true })
+            .block(Block::default().borders(Borders::ALL))
+    }
+
+    fn footer(&self) -> Paragraph<'_> {
+        let hints = match self.mode {
+            Mode::Browse => "←/→ section · ↑/↓ field · Enter edit · r reset · s save · q quit",
+            Mode::Input { .. } => "Enter apply · Esc cancel",
+            Mode::Pick { .. } => "type to filter · ↑/↓ choose · Enter apply · Esc cancel",
+            Mode::ConfirmQuit => "Unsaved changes: s save and quit · q discard · Esc keep editing",
+        };
+        let mut spans = vec![Span::styled(hints, Style::default().fg(Color::DarkGray))];
+        if let Some(status) = &self.status {
+            let style = if status.error {
+                Style::default().fg(Color::Red)
+            } else {
+                Style::default().fg(Color::Green)
+            };
+            spans.push(Span::raw("  "));
+            spans.push(Span::styled(status.text.clone(), style));
+        }
+        Paragraph::new(Line::from(spans))
+    }
+
+    fn draw_picker(&self, frame: &mut Frame, area: Rect) {
+        let Mode::Pick { filter, selected } = &self.mode else {
+            return;
+        };
+        let options = self.picker_options();
+        let allow_custom = matches!(
+            self.selected_field().map(|field| &field.kind),
+            Some(FieldKind::Choice {
+                allow_custom: true,
+                ..
+            })
+        );
+
+        let width = area.width.saturating_sub(4).min(60);
+        let height = (options.len() as u16 + 2)
+            .clamp(3, PICKER_MAX_HEIGHT)
+            .min(area.height);
+        let popup = Rect {
+            x: area.x + (area.width.saturating_sub(width)) / 2,
+            y: area.y + (area.height.saturating_sub(height)) / 2,
+            width,
+            height,
+        };
+
+        let items: Vec<ListItem> = if options.is_empty() && allow_custom && !filter.is_empty() {
+            vec![ListItem::new(format!("use \"{}\"", filter))]
+        } else {
+            options
+                .iter()
+                .map(|option| ListItem::new(option.as_str()))
+                .collect()
+        };
+        let mut state = ListState::default();
+        state.select(Some(*selected));
+        let title = if filter.is_empty() {
+            " choose ".to_string()
+        } else {
+            format!(" filter: {} ", filter)
+        };
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_widget(Clear, popup);
+        frame.render_stateful_widget(list, popup, &mut state);
+    }
+}
+
+/// Restores the terminal when the editor exits, including on errors.
+struct EditorTerminalGuard;
+
+impl EditorTerminalGuard {
+    fn activate() -> Result<Self> {
+        enable_raw_mode().context("failed to enable raw mode")?;
+        if let Err(err) = io::stdout().execute(EnterAlternateScreen) {
+            let _ = disable_raw_mode();
+            return Err(err).context("failed to enter alternate screen");
+        }
+        Ok(Self)
+    }
+}
+
+impl Drop for EditorTerminalGuard {
+    fn drop(&mut self) {
+        let _ = io::stdout().execute(LeaveAlternateScreen);
+        let _ = disable_raw_mode();
+    }
+}
+
+/// Run the config editor on `document` until the user quits.
+///
+/// Returns whether the file was saved at least once.
+pub fn run_config_editor(document: ConfigDocument) -> Result<bool> {
+    let _guard = EditorTerminalGuard::activate()?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))
+        .context("failed to initialize ratatui terminal")?;
+    let mut editor = ConfigEditor::new(document);
+
+    while !editor.done {
+        terminal
+            .draw(|frame| editor.draw(frame))
+            .context("failed to draw config editor")?;
+        if let Event::Key(key) = event::read().context("failed to read terminal event")? {
+            editor.handle_key(key);
+        }
+    }
+    Ok(editor.saved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn press(editor: &mut ConfigEditor, code: KeyCode) {
+        editor.handle_key(KeyEvent::new(code, KeyModifiers::NONE));
+    }
+
+    fn type_text(editor: &mut ConfigEditor, text: &str) {
+        for ch in text.chars() {
+            press(editor, KeyCode::Char(ch));
+        }
+    }
+
+    fn select(editor: &mut ConfigEditor, key: &str) {
+        for (section_index, section) in editor.sections.iter().enumerate() {
+            if let Some(index) = section.fields.iter().position(|field| field.key() == key) {
+                editor.section = section_index;
+                editor.list_state.select(Some(index));
+                return;
+            }
+        }
+        panic!("missing field {key}");
+    }
+
+    fn editor() -> ConfigEditor {
+        let document = ConfigDocument::load(PathBuf::from("/nonexistent/vtcode.toml"))
+            .expect("empty document");
+        ConfigEditor::new(document)
+    }
+
+    #[test]
+    fn toggles_booleans_and_picks_filtered_choices() {
+        let mut editor = editor();
+        select(&mut editor, "security.human_in_the_loop");
+        press(&mut editor, KeyCode::Enter);
+        let field = editor.selected_field().cloned().expect("field");
+        assert_eq!(editor.document.display_value(&field), "false");
+
+        select(&mut editor, "tools.default_policy");
+        press(&mut editor, KeyCode::Enter);
+        type_text(&mut editor, "den");
+        press(&mut editor, KeyCode::Enter);
+        let field = editor.selected_field().cloned().expect("field");
+        assert_eq!(editor.document.display_value(&field), "deny");
+        assert!(matches!(editor.mode, Mode::Browse));
+    }
+
+    #[test]
+    fn keeps_invalid_input_open_and_confirms_unsaved_quit() {
+        let mut editor = editor();
+        select(&mut editor, "agent.max_conversation_turns");
+        press(&mut editor, KeyCode::Enter);
+        for _ in 0..5 {
+            press(&mut editor, KeyCode::Backspace);
+        }
+        type_text(&mut editor, "many");
+        press(&mut editor, KeyCode::Enter);
+        assert!(matches!(editor.mode, Mode::Input { error: Some(_), .. }));
+
+        press(&mut editor, KeyCode::Esc);
+        press(&mut editor, KeyCode::Enter);
+        for _ in 0..5 {
+            press(&mut editor, KeyCode::Backspace);
+        }
+        type_text(&mut editor, "40");
+        press(&mut editor, KeyCode::Enter);
+        assert!(editor.document.is_dirty());
+
+        press(&mut editor, KeyCode::Char('q'));
+        assert!(matches!(editor.mode, Mode::ConfirmQuit));
+        assert!(!editor.done);
+        press(&mut editor, KeyCode::Char('q'));
+        assert!(editor.done);
+        assert!(!editor.saved);
+    }
+}
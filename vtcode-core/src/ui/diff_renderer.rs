@@ -1,3 +1,4 @@
+use crate::utils::long_lines;
 use anstyle::{Reset, Style};
 use anstyle_git::parse as parse_git_style;
 use std::path::Path;
@@ -350,8 +351,26 @@ impl DiffChatRenderer {
     }
 }
 
+/// Unified diff of two versions of `filename`. Minified JSON and JavaScript
+/// are diffed as laid out by [`long_lines::pretty_print`], so a one-character
+/// change does not show up as the whole file replaced.
 pub fn generate_unified_diff(old_content: &str, new_content: &str, filename: &str) -> String {
-    let mut diff = format!("--- a/{}\n+++ b/{}\n", filename, filename);
+    let old_pretty = long_lines::pretty_print(filename, old_content);
+    let new_pretty = long_lines::pretty_print(filename, new_content);
+    if old_pretty.is_none() && new_pretty.is_none() {
+        return unified_diff(old_content, new_content, filename, filename);
+    }
+    let label = format!("{filename} (pretty-printed)");
+    unified_diff(
+        old_pretty.as_deref().unwrap_or(old_content),
+        new_pretty.as_deref().unwrap_or(new_content),
+        &label,
+        &label,
+    )
+}
+
+fn unified_diff(old_content: &str, new_content: &str, old_name: &str, new_name: &str) -> String {
+    let mut diff = format!("--- a/{}\n+++ b/{}\n", old_name, new_name);
 
     let old_lines: Vec<&str> = old_content.lines().collect();
     let new_lines: Vec<&str> = new_content.lines().collect();
@@ -0,0 +1,214 @@
+//! Response language and the built-in UI message catalog
+//!
+//! A [`Locale`] comes from `[agent.locale]` or the `/lang` command. It adds a
+//! directive to the system prompt so explanations are written in that language
+//! while code, identifiers and tool output stay untouched, and it selects the
+//! catalog used by [`text`] for the chat UI's own strings. Languages without a
+//! catalog fall back to English UI strings.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+
+/// Languages with a display name, as (primary subtag, English name, native name).
+const LANGUAGES: &[(&str, &str, &str)] = &[
+    ("de", "German", "Deutsch"),
+    ("en", "English", "English"),
+    ("es", "Spanish", "Español"),
+    ("fr", "French", "Français"),
+    ("it", "Italian", "Italiano"),
+    ("ja", "Japanese", "日本語"),
+    ("ko", "Korean", "한국어"),
+    ("pt", "Portuguese", "Português"),
+    ("ru", "Russian", "Русский"),
+    ("vi", "Vietnamese", "Tiếng Việt"),
+    ("zh", "Chinese", "中文"),
+];
+
+/// A BCP 47 language tag such as `ja` or `pt-BR`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Locale {
+    tag: String,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self {
+            tag: "en".to_string(),
+        }
+    }
+}
+
+impl Locale {
+    /// Parse a language tag, normalizing `ja_JP` to `ja-JP`.
+    pub fn parse(value: &str) -> Option<Self> {
+        let tag = value.trim().replace('_', "-");
+        let mut subtags = tag.split('-');
+        let language = subtags.next()?;
+        if !(2..=3).contains(&language.len()) || !language.chars().all(|c| c.is_ascii_alphabetic())
+        {
+            return None;
+        }
+        let mut normalized = language.to_ascii_lowercase();
+        for subtag in subtags {
+            if subtag.is_empty()
+                || subtag.len() > 8
+                || !subtag.chars().all(|c| c.is_ascii_alphanumeric())
+            {
+                return None;
+            }
+            normalized.push('-');
+            normalized.push_str(subtag);
+        }
+        Some(Self { tag: normalized })
+    }
+
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    /// Primary language subtag, e.g. `pt` for `pt-BR`.
+    pub fn language(&self) -> &str {
+        self.tag.split('-').next().unwrap_or(&self.tag)
+    }
+
+    pub fn is_english(&self) -> bool {
+        self.language() == "en"
+    }
+
+    /// Name used in the prompt and UI, e.g. `Japanese (日本語)`.
+    pub fn name(&self) -> String {
+        match LANGUAGES
+            .iter()
+            .find(|(code, _, _)| *code == self.language())
+        {
+            Some((_, english, native)) if english == native => english.to_string(),
+            Some((_, english, native)) => format!("{english} ({native})"),
+            None => self.tag.clone(),
+        }
+    }
+
+    /// System prompt section asking for replies in this language, or `None`
+    /// for English, which needs no directive.
+    pub fn response_directive(&self) -> Option<String> {
+        if self.is_english() {
+            return None;
+        }
+        let name = self.name();
+        Some(format!(
+            "[Response language]\n\
+             Reply in {name} (language tag `{tag}`): write explanations, plans, summaries and questions to the user in {name}.\n\
+             Leave code, identifiers, file paths, shell commands, tool arguments, configuration keys, log output and error messages exactly as written; never translate them.\n\
+             When editing code, keep comments in the language the file already uses.",
+            tag = self.tag
+        ))
+    }
+}
+
+/// Built-in UI strings with a translation in the catalog.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Message {
+    Thinking,
+    /// `{tool}`: tool name
+    RunningTool,
+    EscToInterrupt,
+    Goodbye,
+    /// `{tool}`: tool name
+    ApproveTool,
+    StatusHelp,
+    NavigationHint,
+    SelectionHint,
+    ForkHint,
+    /// `{language}`: language name
+    LanguageChanged,
+}
+
+impl Message {
+    fn english(self) -> &'static str {
+        match self {
+            Self::Thinking => "Thinking...",
+            Self::RunningTool => "Running tool: {tool}",
+            Self::EscToInterrupt => "Esc to interrupt",
+            Self::Goodbye => "Goodbye!",
+            Self::ApproveTool => "Approve '{tool}' tool? y/n (Esc to cancel)",
+            Self::StatusHelp => "? help · / command",
+            Self::NavigationHint => "↵ send · esc exit · alt+Pg↑/Pg↓ history",
+            Self::SelectionHint => "ctrl+e explain selection · ctrl+y copy",
+            Self::ForkHint => "alt+f fork here · ctrl+l latest",
+            Self::LanguageChanged => "Responses in this conversation will be in {language}.",
+        }
+    }
+
+    fn japanese(self) -> &'static str {
+        match self {
+            Self::Thinking => "考え中...",
+            Self::RunningTool => "ツールを実行中: {tool}",
+            Self::EscToInterrupt => "Esc で中断",
+            Self::Goodbye => "終了します。",
+            Self::ApproveTool => "'{tool}' ツールを実行しますか? y/n (Esc でキャンセル)",
+            Self::StatusHelp => "? ヘルプ · / コマンド",
+            Self::NavigationHint => "↵ 送信 · esc 終了 · alt+Pg↑/Pg↓ 履歴",
+            Self::SelectionHint => "ctrl+e 選択範囲を説明 · ctrl+y コピー",
+            Self::ForkHint => "alt+f ここから分岐 · ctrl+l 最新へ",
+            Self::LanguageChanged => "この会話の応答言語を{language}に切り替えました。",
+        }
+    }
+
+    fn translate(self, language: &str) -> &'static str {
+        match language {
+            "ja" => self.japanese(),
+            _ => self.english(),
+        }
+    }
+}
+
+/// Language of the UI catalog in use.
+static ACTIVE: Lazy<RwLock<String>> = Lazy::new(|| RwLock::new("en".to_string()));
+
+/// Show built-in UI strings in `locale`'s language from now on.
+pub fn set_ui_locale(locale: &Locale) {
+    *ACTIVE.write() = locale.language().to_string();
+}
+
+/// `message` in the active UI language.
+pub fn text(message: Message) -> &'static str {
+    message.translate(ACTIVE.read().as_str())
+}
+
+/// `message` in the active UI language with `{name}` placeholders filled in.
+pub fn format(message: Message, args: &[(&str, &str)]) -> String {
+    args.iter()
+        .fold(text(message).to_string(), |out, (name, value)| {
+            out.replace(&format!("{{{name}}}"), value)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_tags_and_builds_the_directive() {
+        let locale = Locale::parse("ja_JP").unwrap();
+        assert_eq!(locale.tag(), "ja-JP");
+        assert_eq!(locale.name(), "Japanese (日本語)");
+        let directive = locale.response_directive().unwrap();
+        assert!(directive.contains("Reply in Japanese (日本語)"));
+        assert!(directive.contains("never translate them"));
+
+        assert!(Locale::parse("EN").unwrap().response_directive().is_none());
+        assert_eq!(Locale::parse("tlh").unwrap().name(), "tlh");
+        assert!(Locale::parse("japanese").is_none());
+        assert!(Locale::parse("ja-").is_none());
+    }
+
+    #[test]
+    fn catalog_falls_back_to_english() {
+        assert_eq!(Message::Thinking.translate("ja"), "考え中...");
+        assert_eq!(Message::Thinking.translate("de"), "Thinking...");
+        let japanese = Message::RunningTool.translate("ja");
+        assert_eq!(
+            japanese.replace("{tool}", "grep_search"),
+            "ツールを実行中: grep_search"
+        );
+    }
+}
@@ -5,7 +5,7 @@ use crate::ui::theme::{self, ThemeStyles};
 use anstyle::Style;
 use anstyle_syntect::to_anstyle;
 use once_cell::sync::Lazy;
-use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag};
+use pulldown_cmark::{Alignment, CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag};
 use std::cmp::max;
 use std::collections::HashMap;
 use syntect::easy::HighlightLines;
@@ -13,10 +13,13 @@ use syntect::highlighting::{Theme, ThemeSet};
 use syntect::parsing::{SyntaxReference, SyntaxSet};
 use syntect::util::LinesWithEndings;
 use tracing::warn;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 const LIST_INDENT_WIDTH: usize = 2;
 const CODE_EXTRA_INDENT: &str = "    ";
 const MAX_THEME_CACHE_SIZE: usize = 32;
+/// Columns are not narrowed below this width when a table is fitted to the terminal.
+const MIN_TABLE_COLUMN_WIDTH: usize = 6;
 
 static SYNTAX_SET: Lazy<SyntaxSet> = Lazy::new(SyntaxSet::load_defaults_newlines);
 static THEME_CACHE: Lazy<parking_lot::RwLock<HashMap<String, Theme>>> = Lazy::new(|| {
@@ -77,6 +80,7 @@ impl MarkdownLine {
         self.segments = prefixed;
     }
 
+    #[cfg_attr(not(feature = "ui"), allow(dead_code))]
     pub(crate) fn is_empty(&self) -> bool {
         self.segments
             .iter()
@@ -110,6 +114,18 @@ struct ListState {
     kind: ListKind,
     depth: usize,
     continuation: String,
+    /// The current item is a checked task, whose text is dimmed
+    checked_task: bool,
+}
+
+/// A table being collected until its end tag, when the column widths are known.
+#[derive(Clone, Debug, Default)]
+struct TableState {
+    alignments: Vec<Alignment>,
+    header: Vec<Vec<MarkdownSegment>>,
+    rows: Vec<Vec<Vec<MarkdownSegment>>>,
+    row: Vec<Vec<MarkdownSegment>>,
+    cell: MarkdownLine,
 }
 
 #[derive(Clone, Debug)]
@@ -124,6 +140,21 @@ pub fn render_markdown_to_lines(
     base_style: Style,
     theme_styles: &ThemeStyles,
     highlight_config: Option<&SyntaxHighlightingConfig>,
+) -> Vec<MarkdownLine> {
+    render_markdown_to_lines_with_width(source, base_style, theme_styles, highlight_config, None)
+}
+
+/// Like [`render_markdown_to_lines`], narrowing and wrapping table columns so
+/// that tables fit within `max_width` columns.
+///
+/// Tables keep the pipe syntax of the source, aligned into columns, so a copied
+/// table is still a markdown table.
+pub fn render_markdown_to_lines_with_width(
+    source: &str,
+    base_style: Style,
+    theme_styles: &ThemeStyles,
+    highlight_config: Option<&SyntaxHighlightingConfig>,
+    max_width: Option<usize>,
 ) -> Vec<MarkdownLine> {
     let options = Options::ENABLE_STRIKETHROUGH
         | Options::ENABLE_TABLES
@@ -138,8 +169,31 @@ pub fn render_markdown_to_lines(
     let mut list_stack: Vec<ListState> = Vec::new();
     let mut pending_list_prefix: Option<String> = None;
     let mut code_block: Option<CodeBlockState> = None;
+    let mut table: Option<TableState> = None;
 
     for event in parser {
+        if table.is_some() {
+            if let Event::End(Tag::Table(_)) = event {
+                let state = table.take().unwrap_or_default();
+                let prefix =
+                    build_prefix_segments(blockquote_depth, &list_stack, theme_styles, base_style);
+                lines.extend(render_table(
+                    state,
+                    max_width,
+                    &prefix,
+                    theme_styles,
+                    base_style,
+                ));
+                push_blank_line(&mut lines);
+                continue;
+            }
+            if let Some(state) = table.as_mut()
+                && collect_table_event(&event, state, &style_stack, theme_styles, base_style)
+            {
+                continue;
+            }
+        }
+
         if let Some(state) = code_block.as_mut() {
             match event {
                 Event::Text(text) => {
@@ -180,6 +234,21 @@ pub fn render_markdown_to_lines(
         }
 
         match event {
+            Event::Start(Tag::Table(alignments)) => {
+                flush_current_line(
+                    &mut lines,
+                    &mut current_line,
+                    blockquote_depth,
+                    &list_stack,
+                    &mut pending_list_prefix,
+                    theme_styles,
+                    base_style,
+                );
+                table = Some(TableState {
+                    alignments,
+                    ..TableState::default()
+                });
+            }
             Event::Start(tag) => handle_start_tag(
                 tag,
                 &mut style_stack,
@@ -270,8 +339,17 @@ pub fn render_markdown_to_lines(
                     theme_styles,
                     base_style,
                 );
-                let marker = if checked { "[x] " } else { "[ ] " };
-                current_line.push_segment(base_style, marker);
+                // The brackets stay so copied items remain a markdown task list.
+                if checked {
+                    current_line.push_segment(theme_styles.primary.bold(), "[x] ");
+                    if let Some(state) = list_stack.last_mut() {
+                        state.checked_task = true;
+                    }
+                    let style = style_stack.last().copied().unwrap_or(base_style).dimmed();
+                    style_stack.push(style);
+                } else {
+                    current_line.push_segment(theme_styles.secondary, "[ ] ");
+                }
             }
             Event::Html(html) => append_text(
                 &html,
@@ -365,6 +443,7 @@ fn handle_start_tag(
                 kind,
                 depth,
                 continuation: String::new(),
+                checked_task: false,
             });
         }
         Tag::Item => {
@@ -469,6 +548,11 @@ fn handle_end_tag(
                 lines.push(std::mem::take(current_line));
             }
             *pending_list_prefix = None;
+            if let Some(state) = list_stack.last_mut()
+                && std::mem::take(&mut state.checked_task)
+            {
+                style_stack.pop();
+            }
         }
         Tag::Emphasis | Tag::Strong | Tag::Strikethrough | Tag::Link { .. } | Tag::Image { .. } => {
             style_stack.pop();
@@ -482,6 +566,222 @@ fn handle_end_tag(
     }
 }
 
+/// Add a table event to `state`; returns false for events handled by the
+/// regular pipeline, such as emphasis inside a cell.
+fn collect_table_event(
+    event: &Event,
+    state: &mut TableState,
+    style_stack: &[Style],
+    theme_styles: &ThemeStyles,
+    base_style: Style,
+) -> bool {
+    let style = style_stack.last().copied().unwrap_or(base_style);
+    match event {
+        Event::Start(Tag::TableHead | Tag::TableRow) => state.row.clear(),
+        Event::Start(Tag::TableCell) => state.cell = MarkdownLine::default(),
+        Event::End(Tag::TableCell) => {
+            let cell = std::mem::take(&mut state.cell).segments;
+            state.row.push(cell);
+        }
+        Event::End(Tag::TableHead) => state.header = std::mem::take(&mut state.row),
+        Event::End(Tag::TableRow) => {
+            let row = std::mem::take(&mut state.row);
+            state.rows.push(row);
+        }
+        Event::Text(text) | Event::Html(text) => {
+            state.cell.push_segment(style, &text.replace('\n', " "));
+        }
+        Event::Code(code) => state
+            .cell
+            .push_segment(inline_code_style(theme_styles, base_style), code),
+        Event::SoftBreak | Event::HardBreak => state.cell.push_segment(style, " "),
+        Event::FootnoteReference(reference) => {
+            state.cell.push_segment(style, &format!("[^{}]", reference))
+        }
+        _ => return false,
+    }
+    true
+}
+
+/// Lay out a table as aligned markdown rows, narrowing the widest columns and
+/// wrapping their cells when the table is wider than `max_width`.
+fn render_table(
+    state: TableState,
+    max_width: Option<usize>,
+    prefix: &[PrefixSegment],
+    theme_styles: &ThemeStyles,
+    base_style: Style,
+) -> Vec<MarkdownLine> {
+    let columns = state
+        .rows
+        .iter()
+        .map(Vec::len)
+        .chain([state.header.len(), state.alignments.len()])
+        .max()
+        .unwrap_or(0);
+    if columns == 0 {
+        return Vec::new();
+    }
+
+    let mut widths = vec![3usize; columns];
+    for row in std::iter::once(&state.header).chain(&state.rows) {
+        for (index, cell) in row.iter().enumerate() {
+            widths[index] = widths[index].max(segments_width(cell));
+        }
+    }
+    if let Some(max_width) = max_width {
+        let prefix_width: usize = prefix.iter().map(|segment| segment.text.width()).sum();
+        // "| " before each cell, " " after it, and the closing "|".
+        let available = max_width.saturating_sub(prefix_width + columns * 3 + 1);
+        while widths.iter().sum::<usize>() > available {
+            let Some((widest, width)) = widths
+                .iter()
+                .copied()
+                .enumerate()
+                .max_by_key(|(_, width)| *width)
+            else {
+                break;
+            };
+            if width <= MIN_TABLE_COLUMN_WIDTH {
+                break;
+            }
+            widths[widest] -= 1;
+        }
+    }
+
+    let border = theme_styles.secondary.dimmed();
+    let alignment = |index: usize| {
+        state
+            .alignments
+            .get(index)
+            .copied()
+            .unwrap_or(Alignment::None)
+    };
+    let row_lines = |row: &[Vec<MarkdownSegment>], header: bool| {
+        let mut lines = Vec::new();
+        let wrapped: Vec<Vec<Vec<MarkdownSegment>>> = (0..columns)
+            .map(|index| {
+                let cell = row.get(index).map(Vec::as_slice).unwrap_or_default();
+                wrap_segments(cell, widths[index])
+            })
+            .collect();
+        let height = wrapped.iter().map(Vec::len).max().unwrap_or(1);
+        for row_line in 0..height {
+            let mut line = MarkdownLine::default();
+            line.prepend_segments(prefix);
+            for (index, cell_lines) in wrapped.iter().enumerate() {
+                line.push_segment(border, "| ");
+                let content = cell_lines
+                    .get(row_line)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                let padding = widths[index].saturating_sub(segments_width(content));
+                let (before, after) = match alignment(index) {
+                    Alignment::Right => (padding, 0),
+                    Alignment::Center => (padding / 2, padding - padding / 2),
+                    Alignment::Left | Alignment::None => (0, padding),
+                };
+                line.push_segment(base_style, &" ".repeat(before));
+                for segment in content {
+                    let style = if header {
+                        segment.style.bold()
+                    } else {
+                        segment.style
+                    };
+                    line.push_segment(style, &segment.text);
+                }
+                line.push_segment(base_style, &" ".repeat(after + 1));
+            }
+            line.push_segment(border, "|");
+            lines.push(line);
+        }
+        lines
+    };
+
+    let mut lines = row_lines(&state.header, true);
+    let mut rule = MarkdownLine::default();
+    rule.prepend_segments(prefix);
+    for (index, width) in widths.iter().enumerate() {
+        let dashes = match alignment(index) {
+            Alignment::None => "-".repeat(width + 2),
+            Alignment::Left => format!(":{}", "-".repeat(width + 1)),
+            Alignment::Right => format!("{}:", "-".repeat(width + 1)),
+            Alignment::Center => format!(":{}:", "-".repeat(*width)),
+        };
+        rule.push_segment(border, &format!("|{}", dashes));
+    }
+    rule.push_segment(border, "|");
+    lines.push(rule);
+    for row in &state.rows {
+        lines.extend(row_lines(row, false));
+    }
+    lines
+}
+
+fn segments_width(segments: &[MarkdownSegment]) -> usize {
+    segments.iter().map(|segment| segment.text.width()).sum()
+}
+
+/// Wrap styled text to `width` columns at spaces, splitting words that are
+/// longer than a whole line. Always returns at least one line.
+fn wrap_segments(segments: &[MarkdownSegment], width: usize) -> Vec<Vec<MarkdownSegment>> {
+    let width = width.max(1);
+    let mut lines = vec![MarkdownLine::default()];
+    let mut used = 0usize;
+    for segment in segments {
+        for word in segment.text.split_inclusive(' ') {
+            let word_width = word.trim_end().width();
+            if used > 0 && used + word_width > width {
+                lines.push(MarkdownLine::default());
+                used = 0;
+            }
+            let word = if used == 0 { word.trim_start() } else { word };
+            let mut rest = word;
+            while rest.trim_end().width() > width - used {
+                let mut split = 0;
+                let mut taken = 0;
+                for (offset, ch) in rest.char_indices() {
+                    let ch_width = ch.width().unwrap_or(0);
+                    if taken + ch_width > width - used {
+                        break;
+                    }
+                    taken += ch_width;
+                    split = offset + ch.len_utf8();
+                }
+                if split == 0 && used == 0 {
+                    // A character wider than the column; place it on its own.
+                    split = rest
+                        .chars()
+                        .next()
+                        .map(char::len_utf8)
+                        .unwrap_or(rest.len());
+                }
+                if let Some(line) = lines.last_mut() {
+                    line.push_segment(segment.style, &rest[..split]);
+                }
+                lines.push(MarkdownLine::default());
+                used = 0;
+                rest = rest[split..].trim_start();
+            }
+            if let Some(line) = lines.last_mut() {
+                line.push_segment(segment.style, rest);
+            }
+            used += rest.width();
+        }
+    }
+    lines
+        .into_iter()
+        .map(|mut line| {
+            if let Some(last) = line.segments.last_mut() {
+                let trimmed = last.text.trim_end().len();
+                last.text.truncate(trimmed);
+            }
+            line.segments.retain(|segment| !segment.text.is_empty());
+            line.segments
+        })
+        .collect()
+}
+
 fn append_text(
     text: &str,
     current_line: &mut MarkdownLine,
@@ -791,3 +1091,66 @@ fn load_theme(theme_name: &str, cache: bool) -> Theme {
             .unwrap_or_default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain(lines: &[MarkdownLine]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|line| {
+                line.segments
+                    .iter()
+                    .map(|segment| segment.text.as_str())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn tables_align_and_wrap_to_the_width() {
+        let styles = theme::active_styles();
+        let source = "| Name | Status |\n|:-----|-------:|\n| `parser` | done |\n| lexer with a long name | 3 failing |\n";
+
+        let lines = render_markdown_to_lines(source, Style::default(), &styles, None);
+        assert_eq!(
+            plain(&lines),
+            [
+                "| Name                   |    Status |",
+                "|:-----------------------|----------:|",
+                "| parser                 |      done |",
+                "| lexer with a long name | 3 failing |",
+            ]
+        );
+
+        let narrow =
+            render_markdown_to_lines_with_width(source, Style::default(), &styles, None, Some(28));
+        assert_eq!(
+            plain(&narrow),
+            [
+                "| Name         |    Status |",
+                "|:-------------|----------:|",
+                "| parser       |      done |",
+                "| lexer with a | 3 failing |",
+                "| long name    |           |",
+            ]
+        );
+    }
+
+    #[test]
+    fn checked_tasks_keep_their_markers_and_are_dimmed() {
+        let styles = theme::active_styles();
+        let lines = render_markdown_to_lines(
+            "- [x] write parser\n- [ ] add tests\n",
+            Style::default(),
+            &styles,
+            None,
+        );
+        assert_eq!(plain(&lines), ["- [x] write parser", "- [ ] add tests"]);
+        let done = lines[0].segments.last().expect("task text");
+        assert!(done.style.get_effects().contains(anstyle::Effects::DIMMED));
+        let open = lines[1].segments.last().expect("task text");
+        assert!(!open.style.get_effects().contains(anstyle::Effects::DIMMED));
+    }
+}
@@ -1,15 +1,20 @@
 //! User interface utilities and shared UI components
 //!
 //! This module contains shared UI functionality including loading indicators,
-//! markdown rendering, and terminal utilities.
+//! markdown rendering, terminal utilities, and terminal multiplexer integration.
 
+#[cfg(feature = "ui")]
+pub mod config_editor;
 pub mod diff_renderer;
+pub mod locale;
 pub mod markdown;
+pub mod multiplexer;
 pub mod slash;
 pub mod spinner;
 pub mod styled;
 pub mod terminal;
 pub mod theme;
+#[cfg(feature = "ui")]
 pub mod tui;
 pub mod user_confirmation;
 
@@ -19,6 +24,7 @@ pub use spinner::*;
 pub use styled::*;
 pub use terminal::*;
 pub use theme::*;
+#[cfg(feature = "ui")]
 pub use tui::*;
 
 #[cfg(test)]
@@ -0,0 +1,219 @@
+//! Terminal multiplexer integration
+//!
+//! Detects whether VT Code is running inside tmux or zellij and provides
+//! adapters for opening commands in a separate pane or window. Outside a
+//! multiplexer [`Multiplexer::detect`] returns `None`, so callers can degrade
+//! to a no-op without special casing each backend.
+
+use anyhow::{Context, Result, bail};
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+/// Supported terminal multiplexers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MultiplexerKind {
+    Tmux,
+    Zellij,
+}
+
+impl MultiplexerKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Tmux => "tmux",
+            Self::Zellij => "zellij",
+        }
+    }
+}
+
+/// Where a command should be opened relative to the current pane.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PanePlacement {
+    /// Split the current pane side by side
+    Split,
+    /// Open a new tmux window or a floating zellij pane
+    Window,
+}
+
+/// Handle to the multiplexer hosting the current process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Multiplexer {
+    kind: MultiplexerKind,
+}
+
+impl Multiplexer {
+    /// Detect the active multiplexer from the environment.
+    pub fn detect() -> Option<Self> {
+        Self::from_env(|key| env::var(key).ok())
+    }
+
+    fn from_env(lookup: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        let is_set = |key: &str| lookup(key).is_some_and(|value| !value.is_empty());
+        if is_set("TMUX") {
+            Some(Self {
+                kind: MultiplexerKind::Tmux,
+            })
+        } else if is_set("ZELLIJ") || is_set("ZELLIJ_SESSION_NAME") {
+            Some(Self {
+                kind: MultiplexerKind::Zellij,
+            })
+        } else {
+            None
+        }
+    }
+
+    pub fn kind(&self) -> MultiplexerKind {
+        self.kind
+    }
+
+    /// Run `shell_command` in a new pane or window rooted at `cwd`.
+    pub fn open(&self, shell_command: &str, cwd: &Path, placement: PanePlacement) -> Result<()> {
+        let (program, args) = self.command_args(shell_command, cwd, placement);
+        let output = Command::new(program)
+            .args(&args)
+            .output()
+            .with_context(|| format!("failed to launch {}", program))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!(
+                "{} exited with {}: {}",
+                program,
+                output.status,
+                stderr.trim()
+            );
+        }
+        Ok(())
+    }
+
+    fn command_args(
+        &self,
+        shell_command: &str,
+        cwd: &Path,
+        placement: PanePlacement,
+    ) -> (&'static str, Vec<String>) {
+        let cwd = cwd.display().to_string();
+        match self.kind {
+            MultiplexerKind::Tmux => {
+                let mut args = match placement {
+                    PanePlacement::Split => vec!["split-window".to_string(), "-h".to_string()],
+                    PanePlacement::Window => vec!["new-window".to_string()],
+                };
+                args.extend(["-c".to_string(), cwd, shell_command.to_string()]);
+                ("tmux", args)
+            }
+            MultiplexerKind::Zellij => {
+                let mut args = vec!["run".to_string(), "--close-on-exit".to_string()];
+                match placement {
+                    PanePlacement::Split => {
+                        args.extend(["--direction".to_string(), "right".to_string()])
+                    }
+                    PanePlacement::Window => args.push("--floating".to_string()),
+                }
+                args.extend([
+                    "--cwd".to_string(),
+                    cwd,
+                    "--".to_string(),
+                    "sh".to_string(),
+                    "-c".to_string(),
+                    shell_command.to_string(),
+                ]);
+                ("zellij", args)
+            }
+        }
+    }
+}
+
+/// Shell command that pages the working tree diff, optionally limited to `path`.
+pub fn diff_pane_command(path: Option<&str>) -> String {
+    match path {
+        Some(path) => format!(
+            "git diff --color=always -- {} | less -R",
+            shell_words::quote(path)
+        ),
+        None => "git diff --color=always | less -R".to_string(),
+    }
+}
+
+/// Shell command that pages `path`, starting at the end of the file.
+pub fn file_pane_command(path: &Path) -> String {
+    format!(
+        "less -R +G {}",
+        shell_words::quote(&path.display().to_string())
+    )
+}
+
+/// Tracks the pane size so layout changes that arrive without a resize
+/// event (for example zooming a tmux pane) still trigger a re-layout.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PaneSizeWatcher {
+    last: Option<(u16, u16)>,
+}
+
+impl PaneSizeWatcher {
+    pub fn new(initial: Option<(u16, u16)>) -> Self {
+        Self { last: initial }
+    }
+
+    /// Record the latest size, returning `true` when it differs from the previous one.
+    pub fn observe(&mut self, size: (u16, u16)) -> bool {
+        let changed = self.last.is_some_and(|last| last != size);
+        self.last = Some(size);
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    fn detect_with(vars: &[(&str, &str)]) -> Option<Multiplexer> {
+        let map: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        Multiplexer::from_env(|key| map.get(key).cloned())
+    }
+
+    #[test]
+    fn detects_multiplexer_from_environment() {
+        assert_eq!(detect_with(&[]), None);
+        assert_eq!(detect_with(&[("TMUX", "")]), None);
+        assert_eq!(
+            detect_with(&[("TMUX", "/tmp/tmux-1000/default,1,0")]).map(|m| m.kind()),
+            Some(MultiplexerKind::Tmux)
+        );
+        assert_eq!(
+            detect_with(&[("ZELLIJ", "0")]).map(|m| m.kind()),
+            Some(MultiplexerKind::Zellij)
+        );
+    }
+
+    #[test]
+    fn builds_backend_specific_arguments() {
+        let cwd = PathBuf::from("/work");
+        let tmux = Multiplexer {
+            kind: MultiplexerKind::Tmux,
+        };
+        let (program, args) = tmux.command_args("git diff", &cwd, PanePlacement::Split);
+        assert_eq!(program, "tmux");
+        assert_eq!(args, ["split-window", "-h", "-c", "/work", "git diff"]);
+
+        let zellij = Multiplexer {
+            kind: MultiplexerKind::Zellij,
+        };
+        let (program, args) = zellij.command_args("git diff", &cwd, PanePlacement::Window);
+        assert_eq!(program, "zellij");
+        assert!(args.contains(&"--floating".to_string()));
+        assert_eq!(args.last().map(String::as_str), Some("git diff"));
+    }
+
+    #[test]
+    fn watcher_reports_only_changes() {
+        let mut watcher = PaneSizeWatcher::new(Some((80, 24)));
+        assert!(!watcher.observe((80, 24)));
+        assert!(watcher.observe((120, 40)));
+        assert!(!watcher.observe((120, 40)));
+    }
+}
@@ -1,10 +1,44 @@
 use once_cell::sync::Lazy;
+use std::path::Path;
+
+use crate::ui::theme;
+
+/// Most argument completions offered at once.
+const MAX_ARGUMENT_SUGGESTIONS: usize = 50;
 
 /// Metadata describing a slash command supported by the chat interface.
 #[derive(Clone, Copy, Debug)]
 pub struct SlashCommandInfo {
     pub name: &'static str,
     pub description: &'static str,
+    /// Expected argument syntax, shown in help and in the suggestion popover
+    pub usage: &'static str,
+    /// What the first argument may be, for completion and validation
+    pub argument: SlashArgument,
+}
+
+/// The kind of value a slash command takes as its first argument.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlashArgument {
+    None,
+    /// One of a fixed set of words
+    Choice {
+        values: &'static [&'static str],
+        required: bool,
+    },
+    /// A theme identifier
+    Theme,
+    /// A path relative to the workspace, or one of `keywords`
+    Path {
+        required: bool,
+        keywords: &'static [&'static str],
+    },
+    /// Free-form text
+    Text {
+        required: bool,
+    },
+    /// An optional positive number
+    Number,
 }
 
 /// Collection of slash command definitions in the order they should be displayed.
@@ -12,27 +46,186 @@ pub static SLASH_COMMANDS: Lazy<Vec<SlashCommandInfo>> = Lazy::new(|| {
     vec![
         SlashCommandInfo {
             name: "theme",
-            description: "Switch UI theme (usage: /theme <theme-id>)",
+            description: "Switch UI theme",
+            usage: "/theme <theme-id>",
+            argument: SlashArgument::Theme,
         },
         SlashCommandInfo {
             name: "list-themes",
             description: "List all available UI themes",
+            usage: "/list-themes",
+            argument: SlashArgument::None,
         },
         SlashCommandInfo {
             name: "command",
-            description: "Run a terminal command (usage: /command <program> [args...])",
+            description: "Run a terminal command",
+            usage: "/command <program> [args...]",
+            argument: SlashArgument::Text { required: true },
+        },
+        SlashCommandInfo {
+            name: "search",
+            description: "Search the workspace with grep_search",
+            usage: "/search <pattern>",
+            argument: SlashArgument::Text { required: true },
+        },
+        SlashCommandInfo {
+            name: "files",
+            description: "List files in a directory",
+            usage: "/files [path]",
+            argument: SlashArgument::Path {
+                required: false,
+                keywords: &[],
+            },
+        },
+        SlashCommandInfo {
+            name: "read",
+            description: "Show the contents of a file",
+            usage: "/read <path>",
+            argument: SlashArgument::Path {
+                required: true,
+                keywords: &[],
+            },
+        },
+        SlashCommandInfo {
+            name: "git",
+            description: "Run a read-only git command",
+            usage: "/git status|diff|log|show|branch|blame [args...]",
+            argument: SlashArgument::Choice {
+                values: &["status", "diff", "log", "show", "branch", "blame"],
+                required: true,
+            },
+        },
+        SlashCommandInfo {
+            name: "offline",
+            description: "Show or switch offline mode",
+            usage: "/offline [on|off|status]",
+            argument: SlashArgument::Choice {
+                values: &["on", "off", "status"],
+                required: false,
+            },
+        },
+        SlashCommandInfo {
+            name: "tools",
+            description: "Show or change which tools the model can use",
+            usage: "/tools [full|read|status]",
+            argument: SlashArgument::Choice {
+                values: &["full", "read", "status"],
+                required: false,
+            },
         },
         SlashCommandInfo {
             name: "sessions",
-            description: "List recent archived sessions (usage: /sessions [limit])",
+            description: "List recent archived sessions",
+            usage: "/sessions [limit]",
+            argument: SlashArgument::Number,
+        },
+        SlashCommandInfo {
+            name: "redact",
+            description: "Save a copy of this session with secrets, emails and paths replaced",
+            usage: "/redact [regex...]",
+            argument: SlashArgument::Text { required: false },
+        },
+        SlashCommandInfo {
+            name: "timeline",
+            description: "Step through the session turn by turn with the workspace diff at each turn",
+            usage: "/timeline",
+            argument: SlashArgument::None,
+        },
+        SlashCommandInfo {
+            name: "context",
+            description: "Show what the next request's context is made of and where each block came from",
+            usage: "/context",
+            argument: SlashArgument::None,
+        },
+        SlashCommandInfo {
+            name: "lang",
+            description: "Show or switch the response language for this conversation",
+            usage: "/lang [tag]",
+            argument: SlashArgument::Text { required: false },
+        },
+        SlashCommandInfo {
+            name: "briefing",
+            description: "Show the commits, changed files and CI status since your last session here",
+            usage: "/briefing",
+            argument: SlashArgument::None,
+        },
+        SlashCommandInfo {
+            name: "handoff",
+            description: "Write the goal, progress, files and next steps to .vtcode/handoff.md",
+            usage: "/handoff",
+            argument: SlashArgument::None,
+        },
+        SlashCommandInfo {
+            name: "evidence",
+            description: "List tool results that back the agent's claims, or expand one",
+            usage: "/evidence [n]",
+            argument: SlashArgument::Number,
+        },
+        SlashCommandInfo {
+            name: "rename",
+            description: "Rename the current session",
+            usage: "/rename <title>",
+            argument: SlashArgument::Text { required: true },
+        },
+        SlashCommandInfo {
+            name: "preview",
+            description: "Review each request before it is sent",
+            usage: "/preview on|off",
+            argument: SlashArgument::Choice {
+                values: &["on", "off"],
+                required: true,
+            },
+        },
+        SlashCommandInfo {
+            name: "dual",
+            description: "Also ask a second model and pick which answer to keep",
+            usage: "/dual [on|off|<model>]",
+            argument: SlashArgument::Text { required: false },
+        },
+        SlashCommandInfo {
+            name: "copy",
+            description: "Copy the last reply or its last code block",
+            usage: "/copy [reply|code]",
+            argument: SlashArgument::Choice {
+                values: &["reply", "code"],
+                required: false,
+            },
+        },
+        SlashCommandInfo {
+            name: "paste",
+            description: "Insert the clipboard into the input as a fenced block",
+            usage: "/paste",
+            argument: SlashArgument::None,
+        },
+        SlashCommandInfo {
+            name: "split",
+            description: "Open the diff or agent output in a tmux/zellij pane",
+            usage: "/split diff|output [--window]",
+            argument: SlashArgument::Choice {
+                values: &["diff", "output"],
+                required: true,
+            },
+        },
+        SlashCommandInfo {
+            name: "outline",
+            description: "Show the symbol outline of a file beside the chat",
+            usage: "/outline [path|on|off]",
+            argument: SlashArgument::Path {
+                required: false,
+                keywords: &["on", "off"],
+            },
         },
         SlashCommandInfo {
             name: "help",
             description: "Show slash command help",
+            usage: "/help",
+            argument: SlashArgument::None,
         },
         SlashCommandInfo {
             name: "exit",
             description: "Exit the session",
+            usage: "/exit",
+            argument: SlashArgument::None,
         },
     ]
 });
@@ -54,3 +247,178 @@ pub fn suggestions_for(prefix: &str) -> Vec<&'static SlashCommandInfo> {
         matches
     }
 }
+
+/// Looks up a slash command by name (case insensitive).
+pub fn find_command(name: &str) -> Option<&'static SlashCommandInfo> {
+    SLASH_COMMANDS
+        .iter()
+        .find(|info| info.name.eq_ignore_ascii_case(name))
+}
+
+/// Completions for the first argument of `info` that start with `partial`.
+///
+/// Paths are listed relative to `workspace`, with a trailing `/` on directories.
+pub fn argument_suggestions(
+    info: &SlashCommandInfo,
+    partial: &str,
+    workspace: &Path,
+) -> Vec<String> {
+    let query = partial.to_ascii_lowercase();
+    let words: Vec<&str> = match info.argument {
+        SlashArgument::Choice { values, .. } => values.to_vec(),
+        SlashArgument::Theme => theme::available_themes(),
+        SlashArgument::Path { keywords, .. } => {
+            let mut suggestions: Vec<String> = keywords
+                .iter()
+                .filter(|keyword| keyword.starts_with(&query))
+                .map(|keyword| keyword.to_string())
+                .collect();
+            suggestions.extend(path_suggestions(partial, workspace));
+            suggestions.truncate(MAX_ARGUMENT_SUGGESTIONS);
+            return suggestions;
+        }
+        SlashArgument::None | SlashArgument::Text { .. } | SlashArgument::Number => Vec::new(),
+    };
+    words
+        .into_iter()
+        .filter(|word| word.starts_with(&query))
+        .map(str::to_string)
+        .collect()
+}
+
+fn path_suggestions(partial: &str, workspace: &Path) -> Vec<String> {
+    let (directory, prefix) = match partial.rfind('/') {
+        Some(index) => partial.split_at(index + 1),
+        None => ("", partial),
+    };
+    let Ok(entries) = std::fs::read_dir(workspace.join(directory)) else {
+        return Vec::new();
+    };
+    let mut suggestions: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.')) {
+                return None;
+            }
+            let is_dir = entry.file_type().is_ok_and(|kind| kind.is_dir());
+            Some(format!(
+                "{directory}{name}{}",
+                if is_dir { "/" } else { "" }
+            ))
+        })
+        .collect();
+    suggestions.sort();
+    suggestions.truncate(MAX_ARGUMENT_SUGGESTIONS);
+    suggestions
+}
+
+/// Checks a `/command args` line before it is submitted, returning a hint that
+/// explains what is wrong. Input that is not a slash command always passes.
+pub fn validate_input(input: &str) -> Result<(), String> {
+    let Some(rest) = input.trim().strip_prefix('/') else {
+        return Ok(());
+    };
+    let mut parts = rest.split_whitespace();
+    let Some(name) = parts.next() else {
+        return Ok(());
+    };
+    let Some(info) = find_command(name) else {
+        return Err(format!("Unknown command /{name}. Try /help."));
+    };
+    let argument = parts.next();
+    let required = match info.argument {
+        SlashArgument::Choice { required, .. }
+        | SlashArgument::Path { required, .. }
+        | SlashArgument::Text { required } => required,
+        SlashArgument::Theme => true,
+        SlashArgument::None | SlashArgument::Number => false,
+    };
+    let Some(argument) = argument else {
+        if required {
+            return Err(format!("Missing argument. Usage: {}", info.usage));
+        }
+        return Ok(());
+    };
+    match info.argument {
+        SlashArgument::None => Err(format!("/{} takes no arguments", info.name)),
+        SlashArgument::Choice { values, .. } => {
+            if values
+                .iter()
+                .any(|value| value.eq_ignore_ascii_case(argument))
+            {
+                Ok(())
+            } else {
+                Err(format!("'{argument}' is not one of {}", values.join(", ")))
+            }
+        }
+        SlashArgument::Theme => {
+            let themes = theme::available_themes();
+            if themes.iter().any(|id| id.eq_ignore_ascii_case(argument)) {
+                Ok(())
+            } else {
+                Err(format!("Unknown theme '{argument}'. Try /list-themes."))
+            }
+        }
+        SlashArgument::Number => match argument.parse::<usize>() {
+            Ok(value) if value > 0 => Ok(()),
+            _ => Err(format!(
+                "'{argument}' is not a number. Usage: {}",
+                info.usage
+            )),
+        },
+        SlashArgument::Path { .. } | SlashArgument::Text { .. } => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn completes_arguments_by_kind() {
+        let offline = find_command("offline").unwrap();
+        assert_eq!(
+            argument_suggestions(offline, "o", Path::new(".")),
+            ["on", "off"]
+        );
+
+        let theme = find_command("theme").unwrap();
+        let themes = argument_suggestions(theme, "", Path::new("."));
+        assert_eq!(themes, theme::available_themes());
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/main.rs"), "").unwrap();
+        std::fs::write(dir.path().join("README.md"), "").unwrap();
+        std::fs::write(dir.path().join(".env"), "").unwrap();
+        let outline = find_command("outline").unwrap();
+        assert_eq!(
+            argument_suggestions(outline, "", dir.path()),
+            ["on", "off", "README.md", "src/"]
+        );
+        assert_eq!(
+            argument_suggestions(outline, "src/m", dir.path()),
+            ["src/main.rs"]
+        );
+    }
+
+    #[test]
+    fn validates_arguments_before_submission() {
+        assert!(validate_input("explain this").is_ok());
+        assert!(validate_input("/offline").is_ok());
+        assert!(validate_input("/git log --oneline").is_ok());
+        assert!(validate_input("/evidence 2").is_ok());
+        assert_eq!(
+            validate_input("/preview maybe").unwrap_err(),
+            "'maybe' is not one of on, off"
+        );
+        assert_eq!(
+            validate_input("/read").unwrap_err(),
+            "Missing argument. Usage: /read <path>"
+        );
+        assert!(validate_input("/theme no-such-theme").is_err());
+        assert!(validate_input("/sessions many").is_err());
+        assert!(validate_input("/nope").is_err());
+    }
+}
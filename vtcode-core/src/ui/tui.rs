@@ -2,37 +2,54 @@ use anyhow::{Context, Result};
 use crossterm::event::{Event as CrosstermEvent, EventStream};
 use futures::StreamExt;
 use ratatui::{Terminal, TerminalOptions, Viewport, backend::CrosstermBackend};
-use std::io;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
 
 use crate::config::types::UiSurfacePreference;
+use crate::ui::multiplexer::{Multiplexer, PaneSizeWatcher};
+use crate::utils::clipboard::osc52_sequence;
 
 mod events;
+#[cfg(test)]
+mod harness;
 mod render;
 mod state;
 mod ui;
 mod utils;
 
 pub use state::{
-    RatatuiCommand, RatatuiEvent, RatatuiHandle, RatatuiMessageKind, RatatuiSegment,
-    RatatuiSession, RatatuiTextStyle, RatatuiTheme,
+    RatatuiCommand, RatatuiCopyTarget, RatatuiEvent, RatatuiHandle, RatatuiMessageKind,
+    RatatuiOutlineEntry, RatatuiSegment, RatatuiSession, RatatuiTextStyle, RatatuiTheme,
+    RatatuiTimelineEntry,
 };
 pub use utils::{convert_style, parse_tui_color, theme_from_styles};
 
 use state::{RatatuiLoop, TerminalGuard, TerminalSurface};
 use utils::create_ticker;
 
+/// Start the chat UI. Redraws caused by commands (streamed output, status
+/// updates) are held back until `frame_interval` has passed since the previous
+/// frame; input still redraws immediately. Zero draws every change.
 pub fn spawn_session(
     theme: RatatuiTheme,
     placeholder: Option<String>,
     surface_preference: UiSurfacePreference,
+    frame_interval: Duration,
 ) -> Result<RatatuiSession> {
     let (command_tx, command_rx) = mpsc::unbounded_channel();
     let (event_tx, event_rx) = mpsc::unbounded_channel();
 
     tokio::spawn(async move {
-        if let Err(err) =
-            run_ratatui(command_rx, event_tx, theme, placeholder, surface_preference).await
+        if let Err(err) = run_ratatui(
+            command_rx,
+            event_tx,
+            theme,
+            placeholder,
+            surface_preference,
+            frame_interval,
+        )
+        .await
         {
             tracing::error!(error = ?err, "ratatui session terminated unexpectedly");
         }
@@ -50,6 +67,7 @@ async fn run_ratatui(
     theme: RatatuiTheme,
     placeholder: Option<String>,
     surface_preference: UiSurfacePreference,
+    frame_interval: Duration,
 ) -> Result<()> {
     let surface = TerminalSurface::detect(surface_preference)
         .context("failed to resolve terminal surface")?;
@@ -77,18 +95,35 @@ async fn run_ratatui(
     let mut command_rx = commands;
     let mut event_stream = EventStream::new();
     let mut redraw = true;
+    // Set by terminal input, which should never wait for the frame interval
+    let mut redraw_now = true;
+    let mut last_draw = Instant::now();
     let mut ticker = create_ticker();
+    // Multiplexers can resize panes (zoom, layout changes) without a resize event
+    // reaching us, so poll the pane size on ticks while running inside one.
+    let mut pane_watcher =
+        Multiplexer::detect().map(|_| PaneSizeWatcher::new(crossterm::terminal::size().ok()));
 
     loop {
         if app.drain_command_queue(&mut command_rx) {
             redraw = true;
         }
 
-        if redraw {
+        if let Some(text) = app.take_pending_clipboard() {
+            let backend = terminal.backend_mut();
+            backend
+                .write_all(osc52_sequence(&text).as_bytes())
+                .and_then(|_| backend.flush())
+                .context("failed to write clipboard sequence")?;
+        }
+
+        if redraw && (redraw_now || last_draw.elapsed() >= frame_interval) {
             terminal
                 .draw(|frame| app.draw(frame))
                 .context("failed to draw ratatui frame")?;
             redraw = false;
+            redraw_now = false;
+            last_draw = Instant::now();
         }
 
         if app.should_exit() {
@@ -109,25 +144,55 @@ async fn run_ratatui(
             event = event_stream.next() => {
                 match event {
                     Some(Ok(evt)) => {
-                        if matches!(evt, CrosstermEvent::Resize(_, _)) {
+                        let was_suspended = app.is_suspended();
+                        if let CrosstermEvent::Resize(width, height) = evt {
+                            if let Some(watcher) = pane_watcher.as_mut() {
+                                watcher.observe((width, height));
+                            }
                             terminal
                                 .autoresize()
                                 .context("failed to autoresize terminal viewport")?;
                         }
                         if app.handle_event(evt, &events)? {
                             redraw = true;
+                            redraw_now = true;
+                        }
+                        if was_suspended && !app.is_suspended() {
+                            // Pick up pane size changes missed while polling was paused
+                            if let Some(watcher) = pane_watcher.as_mut()
+                                && let Ok(size) = crossterm::terminal::size()
+                            {
+                                watcher.observe(size);
+                            }
+                            terminal
+                                .autoresize()
+                                .context("failed to autoresize terminal viewport")?;
                         }
                     }
                     Some(Err(_)) => {
                         redraw = true;
+                        redraw_now = true;
                     }
                     None => {}
                 }
             }
-            _ = ticker.tick() => {
+            // Paused while idle; the next input resumes it
+            _ = ticker.tick(), if !app.is_suspended() => {
                 if app.needs_tick() {
                     redraw = true;
                 }
+                if let Some(watcher) = pane_watcher.as_mut()
+                    && let Ok((width, height)) = crossterm::terminal::size()
+                    && watcher.observe((width, height))
+                {
+                    terminal
+                        .autoresize()
+                        .context("failed to autoresize terminal viewport")?;
+                    if app.handle_event(CrosstermEvent::Resize(width, height), &events)? {
+                        redraw = true;
+                        redraw_now = true;
+                    }
+                }
             }
         }
 
@@ -7,7 +7,8 @@ use std::time::Instant;
 use tokio::sync::mpsc::UnboundedSender;
 
 use super::state::{
-    ESCAPE_DOUBLE_MS, RatatuiEvent, RatatuiLoop, ScrollFocus, TranscriptScrollState,
+    ESCAPE_DOUBLE_MS, RatatuiCopyTarget, RatatuiEvent, RatatuiLoop, ScrollFocus,
+    TranscriptScrollState,
 };
 
 impl RatatuiLoop {
@@ -16,10 +17,14 @@ impl RatatuiLoop {
         event: CrosstermEvent,
         events: &UnboundedSender<RatatuiEvent>,
     ) -> Result<bool> {
-        match event {
+        let resumed = matches!(
+            event,
+            CrosstermEvent::Key(_) | CrosstermEvent::Mouse(_) | CrosstermEvent::Paste(_)
+        ) && self.resume();
+        let redraw = match event {
             CrosstermEvent::Key(key) => self.handle_key_event(key, events),
             CrosstermEvent::Resize(_, _) => {
-                self.transcript_autoscroll = true;
+                // The transcript keeps its position through the reflow; see `draw`
                 self.pty_autoscroll = true;
                 Ok(true)
             }
@@ -27,7 +32,66 @@ impl RatatuiLoop {
             CrosstermEvent::FocusGained | CrosstermEvent::FocusLost | CrosstermEvent::Paste(_) => {
                 Ok(false)
             }
+        }?;
+        Ok(redraw || resumed)
+    }
+
+    /// Keys handled while the outline panel has focus. Returns `None` for keys
+    /// that should fall through to the input after focus is released.
+    fn handle_outline_key(
+        &mut self,
+        key: KeyEvent,
+        events: &UnboundedSender<RatatuiEvent>,
+    ) -> Option<bool> {
+        match key.code {
+            KeyCode::Up => self.outline.select_previous(),
+            KeyCode::Down => self.outline.select_next(),
+            KeyCode::Char('k') if key.modifiers.is_empty() => self.outline.select_previous(),
+            KeyCode::Char('j') if key.modifiers.is_empty() => self.outline.select_next(),
+            KeyCode::Enter => {
+                self.outline.focused = false;
+                self.insert_outline_reference();
+            }
+            KeyCode::Char('a') if key.modifiers.is_empty() => {
+                if !self.input_enabled {
+                    return Some(true);
+                }
+                if let Some(question) = self.outline.selected_question() {
+                    self.outline.focused = false;
+                    let _ = events.send(RatatuiEvent::Submit(question));
+                    self.transcript_autoscroll = true;
+                }
+            }
+            KeyCode::Esc | KeyCode::Tab => self.outline.focused = false,
+            _ => {
+                self.outline.focused = false;
+                return None;
+            }
         }
+        Some(true)
+    }
+
+    /// Keys handled while the timeline view is open; it captures all input.
+    fn handle_timeline_key(&mut self, key: KeyEvent) -> bool {
+        let page = self
+            .transcript_area
+            .map_or(10, |area| area.height.saturating_sub(2).max(1) as i32);
+        match key.code {
+            KeyCode::Left | KeyCode::Char('h') => self.timeline.step(-1),
+            KeyCode::Right | KeyCode::Char('l') => self.timeline.step(1),
+            KeyCode::Home | KeyCode::Char('g') => self.timeline.jump_to(0),
+            KeyCode::End | KeyCode::Char('G') => self.timeline.jump_to(usize::MAX),
+            KeyCode::Up | KeyCode::Char('k') => self.timeline.scroll_by(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.timeline.scroll_by(1),
+            KeyCode::PageUp => self.timeline.scroll_by(-page),
+            KeyCode::PageDown => self.timeline.scroll_by(page),
+            KeyCode::Esc | KeyCode::Char('q') => self.timeline.close(),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.timeline.close()
+            }
+            _ => return false,
+        }
+        true
     }
 
     fn handle_key_event(
@@ -39,6 +103,39 @@ impl RatatuiLoop {
             return Ok(false);
         }
 
+        if self.timeline.is_open() {
+            return Ok(self.handle_timeline_key(key));
+        }
+
+        // The pager only takes the page keys; everything else edits the prompt
+        if self.pager.is_open() && !key.modifiers.contains(KeyModifiers::ALT) {
+            let page = self
+                .transcript_area
+                .map_or(10, |area| area.height.saturating_sub(2).max(1) as i32);
+            match key.code {
+                KeyCode::PageUp => {
+                    self.pager.scroll_by(-page);
+                    return Ok(true);
+                }
+                KeyCode::PageDown => {
+                    self.pager.scroll_by(page);
+                    return Ok(true);
+                }
+                _ => {}
+            }
+        }
+
+        if key.code == KeyCode::Char('o') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            return Ok(self.toggle_outline_focus());
+        }
+
+        if self.outline.focused
+            && self.outline.is_shown()
+            && let Some(handled) = self.handle_outline_key(key, events)
+        {
+            return Ok(handled);
+        }
+
         let suggestions_active = self.slash_suggestions.is_visible();
         if suggestions_active {
             match key.code {
@@ -71,7 +168,7 @@ impl RatatuiLoop {
 
         match key.code {
             KeyCode::Enter => {
-                if !self.input_enabled {
+                if !self.input_enabled || !self.check_slash_input() {
                     return Ok(true);
                 }
                 let text = self.input.take();
@@ -127,6 +224,57 @@ impl RatatuiLoop {
                 }
                 Ok(true)
             }
+            KeyCode::Char('e') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                if !self.input_enabled {
+                    return Ok(true);
+                }
+                let Some(selected) = self.selected_transcript_text() else {
+                    return Ok(false);
+                };
+                self.selection.clear();
+                let _ = events.send(RatatuiEvent::ExplainSelection(selected));
+                self.transcript_autoscroll = true;
+                Ok(true)
+            }
+            KeyCode::Char('y') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                let target = match self.selected_transcript_text() {
+                    Some(selected) => {
+                        self.selection.clear();
+                        RatatuiCopyTarget::Selection(selected)
+                    }
+                    None => RatatuiCopyTarget::LastReply,
+                };
+                let _ = events.send(RatatuiEvent::Copy(target));
+                Ok(true)
+            }
+            KeyCode::Char('y') if key.modifiers.contains(KeyModifiers::ALT) => {
+                let _ = events.send(RatatuiEvent::Copy(RatatuiCopyTarget::LastCodeBlock));
+                Ok(true)
+            }
+            KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::ALT) => {
+                if !self.input_enabled || self.transcript_scroll.is_at_bottom() {
+                    return Ok(false);
+                }
+                let Some(turn) = self.viewed_turn() else {
+                    return Ok(false);
+                };
+                let _ = events.send(RatatuiEvent::Fork(turn));
+                Ok(true)
+            }
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::ALT) => {
+                if !self.input_enabled {
+                    return Ok(false);
+                }
+                let _ = events.send(RatatuiEvent::Undo);
+                Ok(true)
+            }
+            KeyCode::Char('v') if key.modifiers.contains(KeyModifiers::ALT) => {
+                if !self.input_enabled {
+                    return Ok(false);
+                }
+                let _ = events.send(RatatuiEvent::ToggleVoice);
+                Ok(true)
+            }
             KeyCode::Char('l') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 self.transcript_scroll.scroll_to_bottom();
                 self.transcript_autoscroll = true;
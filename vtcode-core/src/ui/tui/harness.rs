@@ -0,0 +1,295 @@
+//! Virtual terminal harness for UI tests
+//!
+//! [`TuiHarness`] drives a [`RatatuiLoop`] the way `run_ratatui` does, but
+//! renders into ratatui's `TestBackend`: tests send [`RatatuiCommand`]s and
+//! key presses, then compare the screen with a snapshot in `snapshots/`.
+//! Snapshots are plain text, one row per line with trailing spaces removed and
+//! the cursor position at the end. Run the tests with
+//! `VTCODE_UPDATE_SNAPSHOTS=1` to write new or changed snapshots, and review
+//! the diff before committing them.
+
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use crossterm::event::{Event as CrosstermEvent, KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{Terminal, backend::TestBackend};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+use super::state::{
+    RatatuiCommand, RatatuiEvent, RatatuiLoop, RatatuiMessageKind, RatatuiSegment,
+    RatatuiTextStyle, RatatuiTheme,
+};
+
+const UPDATE_ENV: &str = "VTCODE_UPDATE_SNAPSHOTS";
+
+pub(crate) struct TuiHarness {
+    pub(crate) state: RatatuiLoop,
+    terminal: Terminal<TestBackend>,
+    events_tx: UnboundedSender<RatatuiEvent>,
+    events_rx: UnboundedReceiver<RatatuiEvent>,
+}
+
+impl TuiHarness {
+    pub(crate) fn new(width: u16, height: u16) -> Self {
+        let (events_tx, events_rx) = mpsc::unbounded_channel();
+        let mut harness = Self {
+            state: RatatuiLoop::new(RatatuiTheme::default(), None),
+            terminal: Terminal::new(TestBackend::new(width, height)).expect("test terminal"),
+            events_tx,
+            events_rx,
+        };
+        harness.draw();
+        harness
+    }
+
+    /// Apply `command` as if it came from the agent, then redraw.
+    pub(crate) fn command(&mut self, command: RatatuiCommand) -> &mut Self {
+        self.state.handle_command(command);
+        self.draw();
+        self
+    }
+
+    /// Append a plain line of `kind` to the transcript.
+    pub(crate) fn line(&mut self, kind: RatatuiMessageKind, text: &str) -> &mut Self {
+        self.command(RatatuiCommand::AppendLine {
+            kind,
+            segments: vec![RatatuiSegment {
+                text: text.to_string(),
+                style: RatatuiTextStyle::default(),
+            }],
+        })
+    }
+
+    pub(crate) fn key(&mut self, code: KeyCode) -> &mut Self {
+        self.key_with(code, KeyModifiers::NONE)
+    }
+
+    pub(crate) fn key_with(&mut self, code: KeyCode, modifiers: KeyModifiers) -> &mut Self {
+        self.event(CrosstermEvent::Key(KeyEvent::new(code, modifiers)))
+    }
+
+    /// Type `text` one character at a time.
+    pub(crate) fn type_text(&mut self, text: &str) -> &mut Self {
+        for ch in text.chars() {
+            self.key(KeyCode::Char(ch));
+        }
+        self
+    }
+
+    /// Resize the virtual terminal and deliver the resize event.
+    pub(crate) fn resize(&mut self, width: u16, height: u16) -> &mut Self {
+        self.terminal.backend_mut().resize(width, height);
+        self.terminal.autoresize().expect("resize test terminal");
+        self.event(CrosstermEvent::Resize(width, height))
+    }
+
+    fn event(&mut self, event: CrosstermEvent) -> &mut Self {
+        self.state
+            .handle_event(event, &self.events_tx)
+            .expect("handle event");
+        self.draw();
+        self
+    }
+
+    fn draw(&mut self) {
+        let state = &mut self.state;
+        self.terminal
+            .draw(|frame| state.draw(frame))
+            .expect("draw test frame");
+    }
+
+    /// Events sent to the agent since the last call.
+    pub(crate) fn take_events(&mut self) -> Vec<RatatuiEvent> {
+        let mut events = Vec::new();
+        while let Ok(event) = self.events_rx.try_recv() {
+            events.push(event);
+        }
+        events
+    }
+
+    /// Rows of the current frame, trailing spaces removed.
+    pub(crate) fn rows(&self) -> Vec<String> {
+        let buffer = self.terminal.backend().buffer();
+        (0..buffer.area.height)
+            .map(|y| {
+                let row: String = (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect();
+                row.trim_end().to_string()
+            })
+            .collect()
+    }
+
+    /// The current frame as snapshot text.
+    pub(crate) fn screen(&mut self) -> String {
+        let mut screen = self.rows().join("\n");
+        let cursor = self
+            .terminal
+            .get_cursor_position()
+            .expect("test cursor position");
+        let _ = write!(screen, "\n-- cursor: {},{}\n", cursor.x, cursor.y);
+        screen
+    }
+
+    /// Compare the current frame with `snapshots/<name>.snap`.
+    #[track_caller]
+    pub(crate) fn assert_snapshot(&mut self, name: &str) {
+        let actual = self.screen();
+        let path = snapshot_path(name);
+        let update = std::env::var_os(UPDATE_ENV).is_some();
+        match std::fs::read_to_string(&path) {
+            Ok(expected) if expected == actual => {}
+            _ if update => {
+                std::fs::create_dir_all(path.parent().expect("snapshot dir"))
+                    .expect("create snapshot dir");
+                std::fs::write(&path, &actual).expect("write snapshot");
+            }
+            Ok(expected) => panic!(
+                "snapshot {name} changed; rerun with {UPDATE_ENV}=1 to accept.\n--- expected\n{expected}\n--- actual\n{actual}"
+            ),
+            Err(_) => panic!(
+                "snapshot {name} is missing; rerun with {UPDATE_ENV}=1 to create it.\n{actual}"
+            ),
+        }
+    }
+}
+
+fn snapshot_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("src/ui/tui/snapshots")
+        .join(format!("{name}.snap"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transcript(harness: &mut TuiHarness, turns: usize) {
+        for turn in 0..turns {
+            harness.line(RatatuiMessageKind::User, &format!("question {turn}"));
+            harness.line(RatatuiMessageKind::Agent, &format!("answer {turn}"));
+        }
+    }
+
+    #[test]
+    fn transcript_scrolls_back_and_returns_to_the_bottom() {
+        let mut harness = TuiHarness::new(60, 16);
+        transcript(&mut harness, 12);
+        harness.assert_snapshot("transcript_bottom");
+
+        harness.key(KeyCode::PageUp).key(KeyCode::PageUp);
+        assert!(!harness.state.transcript_scroll.is_at_bottom());
+        harness.assert_snapshot("transcript_paged_up");
+        assert!(
+            harness
+                .take_events()
+                .iter()
+                .all(|event| matches!(event, RatatuiEvent::ScrollPageUp))
+        );
+
+        // New output does not pull the view away from what the user is reading
+        harness.line(RatatuiMessageKind::Agent, "late reply");
+        assert!(!harness.rows().iter().any(|row| row.contains("late reply")));
+
+        harness.key_with(KeyCode::Char('l'), KeyModifiers::CONTROL);
+        assert!(harness.state.transcript_scroll.is_at_bottom());
+        assert!(harness.rows().iter().any(|row| row.contains("late reply")));
+    }
+
+    #[test]
+    fn fork_truncates_the_transcript_after_the_viewed_turn() {
+        let mut harness = TuiHarness::new(60, 16);
+        transcript(&mut harness, 12);
+
+        // Nothing to fork from the latest turn
+        harness.key_with(KeyCode::Char('f'), KeyModifiers::ALT);
+        assert!(harness.take_events().is_empty());
+
+        harness.key(KeyCode::PageUp).key(KeyCode::PageUp);
+        harness.take_events();
+        harness.key_with(KeyCode::Char('f'), KeyModifiers::ALT);
+        let turn = match harness.take_events().as_slice() {
+            [RatatuiEvent::Fork(turn)] => *turn,
+            events => panic!("expected a fork event, got {events:?}"),
+        };
+        assert!(turn < 11);
+
+        harness.command(RatatuiCommand::TruncateTurns(turn + 1));
+        assert!(harness.state.transcript_scroll.is_at_bottom());
+        let rows = harness.rows();
+        assert!(
+            rows.iter()
+                .any(|row| row.contains(&format!("answer {turn}")))
+        );
+        assert!(
+            !rows
+                .iter()
+                .any(|row| row.contains(&format!("question {}", turn + 1)))
+        );
+    }
+
+    #[test]
+    fn pty_panel_follows_command_output() {
+        let mut harness = TuiHarness::new(60, 20);
+        harness.line(
+            RatatuiMessageKind::Tool,
+            r#"[TOOL] run_terminal_cmd {"command":["cargo","test"]}"#,
+        );
+        for line in 0..30 {
+            harness.line(RatatuiMessageKind::Pty, &format!("test case_{line} ... ok"));
+        }
+        harness.assert_snapshot("pty_panel_following");
+        assert!(
+            harness
+                .rows()
+                .iter()
+                .any(|row| row.contains("case_29 ... ok"))
+        );
+
+        harness.key_with(KeyCode::PageUp, KeyModifiers::SHIFT);
+        assert!(
+            !harness
+                .rows()
+                .iter()
+                .any(|row| row.contains("case_29 ... ok"))
+        );
+    }
+
+    #[test]
+    fn slash_suggestions_complete_and_validate() {
+        let mut harness = TuiHarness::new(70, 20);
+        harness.type_text("/the");
+        harness.assert_snapshot("slash_suggestions");
+
+        harness.key(KeyCode::Tab);
+        assert!(harness.state.input.value().starts_with("/theme "));
+
+        harness.type_text("no-such-theme").key(KeyCode::Enter);
+        assert!(harness.take_events().is_empty());
+        harness.assert_snapshot("slash_argument_error");
+
+        harness
+            .key(KeyCode::Esc)
+            .type_text("hello")
+            .key(KeyCode::Enter);
+        assert!(matches!(
+            harness.take_events().as_slice(),
+            [RatatuiEvent::Submit(text)] if text == "hello"
+        ));
+        assert!(harness.state.input.value().is_empty());
+        assert!(harness.screen().ends_with("-- cursor: 2,17\n"));
+    }
+
+    #[test]
+    fn resize_reflows_without_losing_the_prompt() {
+        let mut harness = TuiHarness::new(80, 12);
+        harness.line(
+            RatatuiMessageKind::Agent,
+            &format!("long answer {}", "wrapped words ".repeat(12)),
+        );
+        harness.type_text("draft");
+        harness.resize(36, 12);
+        harness.assert_snapshot("resized_narrow");
+        assert!(harness.rows().iter().any(|row| row.contains("draft")));
+    }
+}
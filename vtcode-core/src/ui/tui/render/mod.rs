@@ -9,14 +9,17 @@ use ratatui::{
     },
 };
 use std::cmp;
+use std::mem;
 use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
+use crate::ui::locale::{self, Message};
 use crate::ui::slash::SlashCommandInfo;
 
 use super::state::{
-    AppLayout, InputDisplay, InputLayout, MAX_SLASH_SUGGESTIONS, MESSAGE_INDENT, MessageBlock,
+    AppLayout, BlockRows, InputDisplay, InputLayout, MAX_SLASH_SUGGESTIONS, MESSAGE_INDENT,
+    MessageBlock, NARROW_WIDTH, OUTLINE_MAX_WIDTH, OUTLINE_MIN_TERMINAL_WIDTH, OUTLINE_MIN_WIDTH,
     PTY_CONTENT_VIEW_LINES, PtyPlacement, RatatuiLoop, RatatuiMessageKind, RatatuiSegment,
-    RatatuiTextStyle, StyledLine, TranscriptDisplay,
+    RatatuiTextStyle, StyledLine, TranscriptDisplay, WrappedBlock, plain_line_text,
 };
 use super::ui::PtyBlockBuilder;
 
@@ -37,41 +40,58 @@ impl RatatuiLoop {
             return;
         }
 
-        let items: Vec<&SlashCommandInfo> = self
-            .slash_suggestions
-            .items()
-            .iter()
-            .take(capacity)
-            .copied()
-            .collect();
-        if items.is_empty() {
-            return;
-        }
-
-        if let Some(selected) = self.slash_suggestions.selected_index() {
-            if selected >= items.len() {
-                let clamped = items.len().saturating_sub(1);
-                self.slash_suggestions.list_state().select(Some(clamped));
+        let (title, entries): (String, Vec<String>) = match self.slash_suggestions.command {
+            Some(command) => {
+                let entries = if self.slash_suggestions.arguments.is_empty() {
+                    vec![command.description.to_string()]
+                } else {
+                    self.slash_suggestions
+                        .arguments
+                        .iter()
+                        .take(capacity)
+                        .cloned()
+                        .collect()
+                };
+                (command.usage.to_string(), entries)
             }
-        }
+            None => {
+                let items: Vec<&SlashCommandInfo> = self
+                    .slash_suggestions
+                    .items()
+                    .iter()
+                    .take(capacity)
+                    .copied()
+                    .collect();
+                let max_name_len = items.iter().map(|info| info.name.len()).max().unwrap_or(0);
+                let entries = items
+                    .iter()
+                    .map(|info| {
+                        let mut line = format!("/{:<width$}", info.name, width = max_name_len);
+                        line.push(' ');
+                        line.push_str(info.description);
+                        line
+                    })
+                    .collect();
+                ("? help · / commands".to_string(), entries)
+            }
+        };
+        let error = self.slash_suggestions.error.clone();
 
-        let max_name_len = items.iter().map(|info| info.name.len()).max().unwrap_or(0);
-        let entries: Vec<String> = items
-            .iter()
-            .map(|info| {
-                let mut line = format!("/{:<width$}", info.name, width = max_name_len);
-                line.push(' ');
-                line.push_str(info.description);
-                line
-            })
-            .collect();
+        if let Some(selected) = self.slash_suggestions.selected_index()
+            && selected >= entries.len()
+        {
+            let clamped = entries.len().saturating_sub(1);
+            self.slash_suggestions.list_state().select(Some(clamped));
+        }
 
         let max_width = entries
             .iter()
+            .chain(std::iter::once(&title))
+            .chain(error.iter())
             .map(|value| UnicodeWidthStr::width(value.as_str()))
             .max()
             .unwrap_or(0);
-        let visible_height = entries.len().min(capacity) as u16 + 2;
+        let visible_height = entries.len().clamp(1, capacity) as u16 + 2;
         let height = visible_height.min(area.height);
         let required_width = cmp::max(4, cmp::min(area.width as usize, max_width + 4)) as u16;
         let suggestion_area = Rect::new(area.x, area.y, required_width, height);
@@ -79,19 +99,212 @@ impl RatatuiLoop {
 
         let list_items: Vec<ListItem> = entries.into_iter().map(ListItem::new).collect();
         let border_style = Style::default().fg(self.theme.primary.unwrap_or(Color::LightBlue));
-        let list = List::new(list_items)
+        let mut block = Block::default()
+            .title(Line::from(title))
+            .borders(Borders::ALL)
+            .border_style(border_style);
+        if let Some(error) = error {
+            block = block.title_bottom(Line::styled(error, Style::default().fg(Color::LightRed)));
+        }
+        let list = List::new(list_items).block(block).highlight_style(
+            Style::default()
+                .fg(self.theme.primary.unwrap_or(Color::LightBlue))
+                .add_modifier(Modifier::BOLD),
+        );
+        frame.render_stateful_widget(list, suggestion_area, self.slash_suggestions.list_state());
+    }
+
+    fn render_outline(&mut self, frame: &mut Frame, area: Rect) {
+        if area.width <= 2 || area.height < 3 {
+            return;
+        }
+        let accent = self.theme.primary.unwrap_or(Color::LightBlue);
+        let muted = Style::default()
+            .fg(self
+                .theme
+                .secondary
+                .or(self.theme.foreground)
+                .unwrap_or(Color::DarkGray))
+            .add_modifier(Modifier::DIM);
+
+        let items: Vec<ListItem> = self
+            .outline
+            .entries
+            .iter()
+            .map(|entry| {
+                ListItem::new(Line::from(vec![
+                    Span::raw("  ".repeat(entry.depth)),
+                    Span::styled(format!("{} ", entry.kind), muted),
+                    Span::raw(entry.name.clone()),
+                    Span::styled(format!(" :{}", entry.line), muted),
+                ]))
+            })
+            .collect();
+
+        let (border_style, title) = if self.outline.focused {
+            (
+                Style::default().fg(accent),
+                format!("{} · ↵ insert · a ask", self.outline.title),
+            )
+        } else {
+            (muted, format!("{} · ctrl+o", self.outline.title))
+        };
+        let mut highlight = Style::default().fg(accent).add_modifier(Modifier::BOLD);
+        if self.outline.focused {
+            highlight = highlight.add_modifier(Modifier::REVERSED);
+        }
+        let list = List::new(items)
             .block(
                 Block::default()
-                    .title(Line::from("? help · / commands"))
+                    .title(Line::from(Self::truncate_to_width(
+                        &title,
+                        area.width.saturating_sub(2) as usize,
+                    )))
                     .borders(Borders::ALL)
                     .border_style(border_style),
             )
-            .highlight_style(
+            .highlight_style(highlight);
+        frame.render_widget(ClearWidget, area);
+        frame.render_stateful_widget(list, area, &mut self.outline.list_state);
+    }
+
+    /// Draw the timeline view over the transcript: the selected turn's
+    /// request and reply, the files it changed, and the workspace diff.
+    fn render_timeline(&mut self, frame: &mut Frame, area: Rect) {
+        if area.width <= 2 || area.height < 3 {
+            return;
+        }
+        let Some(entry) = self.timeline.current() else {
+            return;
+        };
+        let accent = self.theme.primary.unwrap_or(Color::LightBlue);
+        let muted = Style::default()
+            .fg(self
+                .theme
+                .secondary
+                .or(self.theme.foreground)
+                .unwrap_or(Color::DarkGray))
+            .add_modifier(Modifier::DIM);
+        let label = Style::default().fg(accent).add_modifier(Modifier::BOLD);
+
+        let mut lines = Vec::new();
+        lines.push(Line::from(Span::styled(self.user_label.clone(), label)));
+        lines.extend(
+            entry
+                .request
+                .lines()
+                .map(|line| Line::from(line.to_string())),
+        );
+        lines.push(Line::default());
+        lines.push(Line::from(Span::styled(self.agent_label.clone(), label)));
+        if entry.reply.trim().is_empty() {
+            lines.push(Line::from(Span::styled("(no reply)", muted)));
+        } else {
+            lines.extend(entry.reply.lines().map(|line| Line::from(line.to_string())));
+        }
+        lines.push(Line::default());
+        let changed = if entry.changed.is_empty() {
+            "no files changed in this turn".to_string()
+        } else {
+            format!("changed in this turn: {}", entry.changed.join(", "))
+        };
+        lines.push(Line::from(Span::styled(changed, muted)));
+        lines.push(Line::default());
+        if entry.diff.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "Workspace matches the start of the session.",
+                muted,
+            )));
+        }
+        for line in entry.diff.lines() {
+            let style = if line.starts_with("+++") || line.starts_with("---") {
+                Style::default().add_modifier(Modifier::BOLD)
+            } else if line.starts_with('+') {
+                Style::default().fg(Color::Green)
+            } else if line.starts_with('-') {
+                Style::default().fg(Color::Red)
+            } else if line.starts_with("@@") {
+                Style::default().fg(Color::Cyan)
+            } else {
                 Style::default()
-                    .fg(self.theme.primary.unwrap_or(Color::LightBlue))
-                    .add_modifier(Modifier::BOLD),
-            );
-        frame.render_stateful_widget(list, suggestion_area, self.slash_suggestions.list_state());
+            };
+            lines.push(Line::from(Span::styled(line.to_string(), style)));
+        }
+
+        let max_scroll = lines.len().saturating_sub(1).min(u16::MAX as usize) as u16;
+        self.timeline.scroll = self.timeline.scroll.min(max_scroll);
+        let title = format!(
+            "Timeline · turn {}/{} · ←/→ scrub · ↑/↓ scroll · esc close",
+            self.timeline.selected + 1,
+            self.timeline.entries.len()
+        );
+        let paragraph = Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .title(Line::from(Self::truncate_to_width(
+                        &title,
+                        area.width.saturating_sub(2) as usize,
+                    )))
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(accent)),
+            )
+            .wrap(Wrap { trim: false })
+            .scroll((self.timeline.scroll, 0));
+        frame.render_widget(ClearWidget, area);
+        frame.render_widget(paragraph, area);
+    }
+
+    /// Draw the pager over the transcript, styling the first line and the
+    /// `──` section headers like titles.
+    fn render_pager(&mut self, frame: &mut Frame, area: Rect) {
+        if area.width <= 2 || area.height < 3 {
+            return;
+        }
+        let accent = self.theme.primary.unwrap_or(Color::LightBlue);
+        let heading = Style::default().fg(accent).add_modifier(Modifier::BOLD);
+        let lines: Vec<Line> = self
+            .pager
+            .lines
+            .iter()
+            .enumerate()
+            .map(|(index, line)| {
+                if index == 0 || line.starts_with("──") {
+                    Line::from(Span::styled(line.clone(), heading))
+                } else {
+                    Line::from(line.clone())
+                }
+            })
+            .collect();
+
+        let max_scroll = lines.len().saturating_sub(1).min(u16::MAX as usize) as u16;
+        self.pager.scroll = self.pager.scroll.min(max_scroll);
+        let paragraph = Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .title(Line::from(Self::truncate_to_width(
+                        &self.pager.title,
+                        area.width.saturating_sub(2) as usize,
+                    )))
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(accent)),
+            )
+            .wrap(Wrap { trim: false })
+            .scroll((self.pager.scroll, 0));
+        frame.render_widget(ClearWidget, area);
+        frame.render_widget(paragraph, area);
+    }
+
+    /// Split the transcript area to make room for the outline panel.
+    fn split_outline_area(&self, area: Rect) -> (Rect, Option<Rect>) {
+        if !self.outline.is_shown() || area.width < OUTLINE_MIN_TERMINAL_WIDTH || area.height < 3 {
+            return (area, None);
+        }
+        let width = (area.width / 3).clamp(OUTLINE_MIN_WIDTH, OUTLINE_MAX_WIDTH);
+        let segments = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(1), Constraint::Length(width)])
+            .split(area);
+        (segments[0], Some(segments[1]))
     }
 
     fn highlight_transcript(
@@ -99,11 +312,22 @@ impl RatatuiLoop {
         lines: Vec<Line<'static>>,
         _offset: usize,
     ) -> Vec<Line<'static>> {
-        if self.selection.range().is_none() {
+        let Some((start, end)) = self.selection.range() else {
             return lines;
-        }
+        };
 
+        let selected = Style::default().add_modifier(Modifier::REVERSED);
         lines
+            .into_iter()
+            .enumerate()
+            .map(|(index, line)| {
+                if (start..=end).contains(&index) {
+                    line.patch_style(selected)
+                } else {
+                    line
+                }
+            })
+            .collect()
     }
 
     fn update_pty_area(&mut self, text_area: Rect) {
@@ -156,9 +380,16 @@ impl RatatuiLoop {
         let AppLayout {
             message: message_area,
             input: input_layout,
+            outline: outline_area,
             status: status_area,
         } = self.build_app_layout(area);
 
+        if let Some(outline_area) = outline_area {
+            self.render_outline(frame, outline_area);
+        }
+        let timeline_area =
+            outline_area.map_or(message_area, |outline| message_area.union(outline));
+
         let foreground_style = self
             .theme
             .foreground
@@ -166,22 +397,21 @@ impl RatatuiLoop {
             .unwrap_or_default();
 
         let mut scrollbar_area = None;
+        let anchor = self.transcript_anchor();
 
         if message_area.width > 0 && message_area.height > 0 {
             let viewport_height = usize::from(message_area.height);
             let mut display = self.build_display(message_area.width);
-            let preserve_transcript = !self.transcript_autoscroll;
+            // Following new output has to survive both passes: the content
+            // grows first, and again when the scrollbar narrows the wrap width.
+            let follow_transcript = mem::take(&mut self.transcript_autoscroll);
             self.transcript_scroll.update_bounds(
                 display.total_height,
                 viewport_height,
-                preserve_transcript,
+                !follow_transcript,
             );
-            if !self.transcript_scroll.is_at_bottom() {
-                self.transcript_autoscroll = false;
-            }
-            if self.transcript_autoscroll {
+            if follow_transcript {
                 self.transcript_scroll.scroll_to_bottom();
-                self.transcript_autoscroll = false;
             }
 
             let mut needs_scrollbar =
@@ -189,18 +419,13 @@ impl RatatuiLoop {
             let text_area = if needs_scrollbar {
                 let adjusted_width = message_area.width.saturating_sub(1);
                 display = self.build_display(adjusted_width);
-                let preserve_transcript = !self.transcript_autoscroll;
                 self.transcript_scroll.update_bounds(
                     display.total_height,
                     viewport_height,
-                    preserve_transcript,
+                    !follow_transcript,
                 );
-                if !self.transcript_scroll.is_at_bottom() {
-                    self.transcript_autoscroll = false;
-                }
-                if self.transcript_autoscroll {
+                if follow_transcript {
                     self.transcript_scroll.scroll_to_bottom();
-                    self.transcript_autoscroll = false;
                 }
                 needs_scrollbar = self.transcript_scroll.has_overflow();
                 if needs_scrollbar {
@@ -217,7 +442,18 @@ impl RatatuiLoop {
                 message_area
             };
 
+            let viewport = (text_area.width, viewport_height);
+            if viewport != self.transcript_viewport {
+                if viewport.0 != self.transcript_viewport.0 {
+                    // Selected rows refer to the old wrapping
+                    self.selection.clear();
+                }
+                self.restore_transcript_anchor(anchor);
+                self.transcript_viewport = viewport;
+            }
+
             self.transcript_area = Some(text_area);
+            self.transcript_text = display.lines.iter().map(plain_line_text).collect();
 
             let offset = self.transcript_scroll.offset();
             let highlighted = self.highlight_transcript(display.lines.clone(), offset);
@@ -305,61 +541,81 @@ impl RatatuiLoop {
             }
         }
 
-        if let Some(status_area) = status_area {
-            if status_area.width > 0 {
-                let left_text = self.status_bar.left.clone();
-                let center_text = self.status_bar.center.clone();
-                let right_text = self.status_bar.right.clone();
-
-                let mut left_len = UnicodeWidthStr::width(left_text.as_str()) as u16;
-                let mut right_len = UnicodeWidthStr::width(right_text.as_str()) as u16;
-                if left_len > status_area.width {
-                    left_len = status_area.width;
-                }
-                if right_len > status_area.width.saturating_sub(left_len) {
-                    right_len = status_area.width.saturating_sub(left_len);
-                }
-                let center_len = status_area.width.saturating_sub(left_len + right_len);
-                let sections = Layout::default()
-                    .direction(Direction::Horizontal)
-                    .constraints([
-                        Constraint::Length(left_len),
-                        Constraint::Length(center_len),
-                        Constraint::Length(right_len),
-                    ])
-                    .split(status_area);
-
-                let mut status_style = Style::default()
-                    .fg(self.theme.foreground.unwrap_or(Color::Gray))
-                    .add_modifier(Modifier::DIM);
-                if let Some(background) = self.theme.background {
-                    status_style = status_style.bg(background);
+        if let Some(status_area) = status_area
+            && status_area.width > 0
+        {
+            let left_text = if self.selection.is_active() && !self.selection.is_dragging() {
+                locale::text(Message::SelectionHint).to_string()
+            } else if self.input_enabled
+                && !self.transcript_scroll.is_at_bottom()
+                && self.viewed_turn().is_some()
+            {
+                locale::text(Message::ForkHint).to_string()
+            } else {
+                self.status_bar.left.clone()
+            };
+            let mut center_text = self.status_bar.center.clone();
+            let mut right_text = self.status_bar.right.clone();
+            let mut left_text = left_text;
+            if usize::from(status_area.width) < NARROW_WIDTH {
+                // Show whole sections rather than a clipped piece of each
+                center_text.clear();
+                left_text = Self::truncate_to_width(&left_text, status_area.width as usize);
+                let used = UnicodeWidthStr::width(left_text.as_str())
+                    + UnicodeWidthStr::width(right_text.as_str());
+                if used >= status_area.width as usize {
+                    right_text.clear();
                 }
+            }
 
-                if let Some(area) = sections.get(0) {
-                    if area.width > 0 {
-                        let left = Paragraph::new(Line::from(left_text.clone()))
-                            .alignment(Alignment::Left)
-                            .style(status_style);
-                        frame.render_widget(left, *area);
-                    }
-                }
-                if let Some(area) = sections.get(1) {
-                    if area.width > 0 {
-                        let center = Paragraph::new(Line::from(center_text.clone()))
-                            .alignment(Alignment::Center)
-                            .style(status_style);
-                        frame.render_widget(center, *area);
-                    }
-                }
-                if let Some(area) = sections.get(2) {
-                    if area.width > 0 {
-                        let right = Paragraph::new(Line::from(right_text.clone()))
-                            .alignment(Alignment::Right)
-                            .style(status_style);
-                        frame.render_widget(right, *area);
-                    }
-                }
+            let mut left_len = UnicodeWidthStr::width(left_text.as_str()) as u16;
+            let mut right_len = UnicodeWidthStr::width(right_text.as_str()) as u16;
+            if left_len > status_area.width {
+                left_len = status_area.width;
+            }
+            if right_len > status_area.width.saturating_sub(left_len) {
+                right_len = status_area.width.saturating_sub(left_len);
+            }
+            let center_len = status_area.width.saturating_sub(left_len + right_len);
+            let sections = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([
+                    Constraint::Length(left_len),
+                    Constraint::Length(center_len),
+                    Constraint::Length(right_len),
+                ])
+                .split(status_area);
+
+            let mut status_style = Style::default()
+                .fg(self.theme.foreground.unwrap_or(Color::Gray))
+                .add_modifier(Modifier::DIM);
+            if let Some(background) = self.theme.background {
+                status_style = status_style.bg(background);
+            }
+
+            if let Some(area) = sections.first()
+                && area.width > 0
+            {
+                let left = Paragraph::new(Line::from(left_text.clone()))
+                    .alignment(Alignment::Left)
+                    .style(status_style);
+                frame.render_widget(left, *area);
+            }
+            if let Some(area) = sections.get(1)
+                && area.width > 0
+            {
+                let center = Paragraph::new(Line::from(center_text.clone()))
+                    .alignment(Alignment::Center)
+                    .style(status_style);
+                frame.render_widget(center, *area);
+            }
+            if let Some(area) = sections.get(2)
+                && area.width > 0
+            {
+                let right = Paragraph::new(Line::from(right_text.clone()))
+                    .alignment(Alignment::Right)
+                    .style(status_style);
+                frame.render_widget(right, *area);
             }
         }
 
@@ -367,6 +623,12 @@ impl RatatuiLoop {
             self.pty_area = None;
             self.pty_scroll.update_bounds(0, 0, false);
         }
+
+        if self.timeline.is_open() {
+            self.render_timeline(frame, timeline_area);
+        } else if self.pager.is_open() {
+            self.render_pager(frame, timeline_area);
+        }
     }
 
     fn build_app_layout(&self, area: Rect) -> AppLayout {
@@ -374,6 +636,7 @@ impl RatatuiLoop {
             return AppLayout {
                 message: Rect::new(area.x, area.y, area.width, 0),
                 input: None,
+                outline: None,
                 status: None,
             };
         }
@@ -392,6 +655,7 @@ impl RatatuiLoop {
             return AppLayout {
                 message: Rect::new(body_area.x, body_area.y, body_area.width, 0),
                 input: None,
+                outline: None,
                 status: status_area,
             };
         }
@@ -408,11 +672,13 @@ impl RatatuiLoop {
             .min(body_area.height);
         let message_height = body_area.height.saturating_sub(input_total_height);
         let message_area = Rect::new(body_area.x, body_area.y, body_area.width, message_height);
+        let (message_area, outline_area) = self.split_outline_area(message_area);
 
         if input_total_height == 0 {
             return AppLayout {
                 message: message_area,
                 input: None,
+                outline: outline_area,
                 status: status_area,
             };
         }
@@ -445,6 +711,7 @@ impl RatatuiLoop {
                 suggestion_area,
                 display,
             }),
+            outline: outline_area,
             status: status_area,
         }
     }
@@ -458,10 +725,13 @@ impl RatatuiLoop {
         }
 
         self.pty_block = None;
+        self.block_rows.clear();
+        self.wrapped_blocks
+            .resize_with(self.messages.len(), WrappedBlock::default);
         let mut lines = Vec::new();
         let mut total_height = 0usize;
         let width_usize = width as usize;
-        let indent_width = MESSAGE_INDENT.min(width_usize);
+        let indent_width = Self::message_indent(width_usize);
         let mut first_rendered = true;
 
         let mut conversation_line_offsets = Vec::new();
@@ -511,16 +781,7 @@ impl RatatuiLoop {
                     Vec::new()
                 }
             } else {
-                let block = &self.messages[index];
-                match kind {
-                    RatatuiMessageKind::User => self.build_user_block(block, width_usize),
-                    RatatuiMessageKind::Info
-                    | RatatuiMessageKind::Policy
-                    | RatatuiMessageKind::Tool => {
-                        self.build_panel_block(block, width_usize, self.kind_color(kind))
-                    }
-                    _ => self.build_response_block(block, width_usize, kind),
-                }
+                self.wrapped_block(index, width_usize)
             };
 
             if block_lines.is_empty() {
@@ -534,6 +795,11 @@ impl RatatuiLoop {
 
             let block_top = total_height;
             total_height += block_lines.len();
+            self.block_rows.push(BlockRows {
+                message: index,
+                top: block_top,
+                height: block_lines.len(),
+            });
             lines.append(&mut block_lines);
 
             if let Some(mut placement) = placement {
@@ -566,6 +832,9 @@ impl RatatuiLoop {
             for offset in &mut conversation_line_offsets {
                 *offset = offset.saturating_add(2);
             }
+            for rows in &mut self.block_rows {
+                rows.top += 2;
+            }
         }
 
         self.conversation_line_offsets = conversation_line_offsets;
@@ -576,6 +845,38 @@ impl RatatuiLoop {
         }
     }
 
+    /// Lines of message `index` wrapped to `width`, reusing the wrapping from
+    /// an earlier frame when the block has not changed since.
+    fn wrapped_block(&mut self, index: usize, width: usize) -> Vec<Line<'static>> {
+        if let Some(lines) = self.wrapped_blocks[index].get(width) {
+            return lines.to_vec();
+        }
+        let block = &self.messages[index];
+        let kind = block.kind;
+        let lines = match kind {
+            RatatuiMessageKind::User => self.build_user_block(block, width),
+            RatatuiMessageKind::CodeExecution
+            | RatatuiMessageKind::Info
+            | RatatuiMessageKind::Policy
+            | RatatuiMessageKind::Refusal
+            | RatatuiMessageKind::Tool => {
+                self.build_panel_block(block, width, self.kind_color(kind))
+            }
+            _ => self.build_response_block(block, width, kind),
+        };
+        self.wrapped_blocks[index].insert(width, lines.clone());
+        lines
+    }
+
+    /// Indentation of message blocks; narrow transcripts give it to the text.
+    fn message_indent(width: usize) -> usize {
+        if width < NARROW_WIDTH {
+            0
+        } else {
+            MESSAGE_INDENT.min(width)
+        }
+    }
+
     fn build_input_display(&self, width: u16) -> InputDisplay {
         if width == 0 {
             return InputDisplay {
@@ -633,14 +934,16 @@ impl RatatuiLoop {
     }
 
     fn build_user_block(&self, block: &MessageBlock, width: usize) -> Vec<Line<'static>> {
-        let indent = MESSAGE_INDENT.min(width);
-        if let Some(panel) = self.build_chat_panel(
-            block,
-            width,
-            indent,
-            &self.user_label,
-            RatatuiMessageKind::User,
-        ) {
+        let indent = Self::message_indent(width);
+        if width >= NARROW_WIDTH
+            && let Some(panel) = self.build_chat_panel(
+                block,
+                width,
+                indent,
+                &self.user_label,
+                RatatuiMessageKind::User,
+            )
+        {
             return panel;
         }
         let mut prefix_style = RatatuiTextStyle::default();
@@ -665,7 +968,7 @@ impl RatatuiLoop {
         let indent = if kind == RatatuiMessageKind::Agent {
             0
         } else {
-            MESSAGE_INDENT
+            Self::message_indent(width)
         };
         if kind == RatatuiMessageKind::Agent {
             return self.build_agent_block(block, width, marker);
@@ -855,6 +1158,26 @@ impl RatatuiLoop {
         }
 
         let border_style = Style::default().fg(accent);
+        if width < NARROW_WIDTH {
+            // A box would take four columns and two rows; mark the panel with
+            // a gutter instead
+            let content_width = width - 2;
+            let mut rendered = Vec::new();
+            for line in &block.lines {
+                let wrapped =
+                    self.wrap_segments(&line.segments, content_width, 0, self.theme.foreground);
+                if wrapped.is_empty() {
+                    rendered.push(Line::from(Span::styled("│", border_style)));
+                }
+                for wrapped_line in wrapped {
+                    let mut spans = vec![Span::styled("│ ", border_style)];
+                    spans.extend(wrapped_line.spans);
+                    rendered.push(Line::from(spans));
+                }
+            }
+            return rendered;
+        }
+
         let horizontal = "─".repeat(width.saturating_sub(2));
         let mut rendered = Vec::new();
         rendered.push(Line::from(vec![Span::styled(
@@ -1253,6 +1576,8 @@ impl RatatuiLoop {
             RatatuiMessageKind::Info => self.theme.foreground.unwrap_or(Color::Yellow),
             RatatuiMessageKind::Policy => self.theme.secondary.unwrap_or(Color::LightYellow),
             RatatuiMessageKind::Error => Color::LightRed,
+            RatatuiMessageKind::Refusal => Color::LightMagenta,
+            RatatuiMessageKind::CodeExecution => Color::LightBlue,
         }
     }
 }
@@ -1,5 +1,9 @@
 use crate::config::types::UiSurfacePreference;
-use crate::ui::slash::{SlashCommandInfo, suggestions_for};
+use crate::ui::locale::{self, Message};
+use crate::ui::slash::{
+    SlashCommandInfo, argument_suggestions, find_command, suggestions_for, validate_input,
+};
+use crate::utils::ansi_sanitize::{AnsiSanitizer, HyperlinkMode, sanitize_ansi};
 use ansi_to_tui::IntoText;
 use anyhow::{Context, Result};
 use crossterm::{
@@ -21,6 +25,7 @@ use std::collections::VecDeque;
 use std::env;
 use std::io::{self, IsTerminal};
 use std::mem;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 use tokio::sync::mpsc::error::TryRecvError;
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
@@ -29,10 +34,17 @@ use unicode_width::UnicodeWidthStr;
 pub(crate) const ESCAPE_DOUBLE_MS: u64 = 750;
 pub(crate) const REDRAW_INTERVAL_MS: u64 = 33;
 pub(crate) const MESSAGE_INDENT: usize = 2;
-pub(crate) const NAVIGATION_HINT_TEXT: &str = "↵ send · esc exit · alt+Pg↑/Pg↓ history";
 const DEFAULT_AGENT_LABEL: &str = "Assistant";
 const DEFAULT_USER_LABEL: &str = "You";
 pub(crate) const MAX_SLASH_SUGGESTIONS: usize = 6;
+pub(crate) const OUTLINE_MIN_TERMINAL_WIDTH: u16 = 80;
+pub(crate) const OUTLINE_MIN_WIDTH: u16 = 24;
+pub(crate) const OUTLINE_MAX_WIDTH: u16 = 40;
+/// Transcript width below which panels drop their borders and indentation
+pub(crate) const NARROW_WIDTH: usize = 40;
+/// Widths whose wrapping is kept per block; the transcript is wrapped for both
+/// the full width and the width left beside the scrollbar.
+const WRAP_CACHE_WIDTHS: usize = 2;
 const SURFACE_ENV_KEY: &str = "VT_RATATUI_SURFACE";
 const INLINE_FALLBACK_ROWS: u16 = 24;
 
@@ -73,6 +85,252 @@ mod tests {
         scroll.update_bounds(120, 20, true);
         assert_eq!(scroll.offset(), 60);
     }
+
+    fn text_line(text: String) -> StyledLine {
+        let mut line = StyledLine::default();
+        line.push_segment(RatatuiSegment {
+            text,
+            style: RatatuiTextStyle::default(),
+        });
+        line
+    }
+
+    /// Draw a frame of the given size and return its rows.
+    fn draw_at(state: &mut RatatuiLoop, width: u16, height: u16) -> Vec<String> {
+        let mut terminal =
+            ratatui::Terminal::new(ratatui::backend::TestBackend::new(width, height))
+                .expect("test terminal");
+        terminal.draw(|frame| state.draw(frame)).expect("draw");
+        let buffer = terminal.backend().buffer();
+        (0..height)
+            .map(|y| (0..width).map(|x| buffer[(x, y)].symbol()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn resize_keeps_the_transcript_anchor() {
+        let mut state = RatatuiLoop::new(RatatuiTheme::default(), None);
+        for turn in 0..30 {
+            state.push_line(RatatuiMessageKind::Info, text_line(format!("step {turn}")));
+            state.push_line(
+                RatatuiMessageKind::Agent,
+                text_line(format!("answer {turn} {}", "word ".repeat(30))),
+            );
+        }
+        draw_at(&mut state, 100, 30);
+        draw_at(&mut state, 60, 30);
+        assert!(state.transcript_scroll.is_at_bottom());
+
+        let anchored = state.block_rows[21];
+        state.transcript_scroll.jump_to(anchored.top + 1);
+        draw_at(&mut state, 60, 30);
+        assert_eq!(state.transcript_scroll.offset(), anchored.top + 1);
+
+        draw_at(&mut state, 120, 24);
+        let rows = state
+            .block_rows
+            .iter()
+            .find(|rows| rows.message == anchored.message)
+            .copied()
+            .unwrap();
+        assert_ne!(rows.height, anchored.height);
+        let offset = state.transcript_scroll.offset();
+        assert!((rows.top..rows.top + rows.height).contains(&offset));
+    }
+
+    #[test]
+    fn narrow_transcript_drops_borders_and_indent() {
+        let mut state = RatatuiLoop::new(RatatuiTheme::default(), None);
+        state.push_line(
+            RatatuiMessageKind::Info,
+            text_line("cargo build finished".to_string()),
+        );
+        state.push_line(
+            RatatuiMessageKind::Error,
+            text_line("linker failed".to_string()),
+        );
+        let rows = draw_at(&mut state, 30, 12);
+        assert!(rows.iter().all(|row| !row.contains('╭')), "{rows:#?}");
+        assert!(rows.iter().any(|row| row.starts_with("│ cargo build")));
+        assert!(rows.iter().any(|row| row.starts_with("! linker failed")));
+
+        let rows = draw_at(&mut state, 80, 12);
+        assert!(rows.iter().any(|row| row.starts_with('╭')), "{rows:#?}");
+    }
+
+    fn outline_entry(name: &str, line: usize) -> RatatuiOutlineEntry {
+        RatatuiOutlineEntry {
+            name: name.to_string(),
+            kind: "fn".to_string(),
+            line,
+            depth: 0,
+        }
+    }
+
+    #[test]
+    fn outline_selection_wraps_and_survives_refresh() {
+        let mut panel = OutlinePanel::default();
+        panel.set_entries(
+            "src/lib.rs".to_string(),
+            vec![outline_entry("parse", 3), outline_entry("render", 10)],
+        );
+        panel.select_previous();
+        assert_eq!(
+            panel.selected_reference().as_deref(),
+            Some("src/lib.rs:10 (`render`)")
+        );
+
+        panel.set_entries("src/lib.rs".to_string(), vec![outline_entry("parse", 4)]);
+        assert_eq!(panel.selected().map(|e| e.line), Some(4));
+
+        panel.set_entries(
+            "src/main.rs".to_string(),
+            vec![outline_entry("main", 1), outline_entry("run", 5)],
+        );
+        assert_eq!(panel.selected().map(|e| e.name.as_str()), Some("main"));
+    }
+
+    fn timeline_entry(turn: usize) -> RatatuiTimelineEntry {
+        RatatuiTimelineEntry {
+            turn,
+            request: format!("request {turn}"),
+            reply: String::new(),
+            changed: Vec::new(),
+            diff: String::new(),
+        }
+    }
+
+    #[test]
+    fn timeline_opens_at_latest_turn_and_clamps_steps() {
+        let mut state = RatatuiLoop::new(RatatuiTheme::default(), None);
+        state.handle_command(RatatuiCommand::ShowTimeline(
+            (1..=3).map(timeline_entry).collect(),
+        ));
+        assert_eq!(state.timeline.current().map(|e| e.turn), Some(3));
+        state.timeline.step(1);
+        assert_eq!(state.timeline.selected, 2);
+        state.timeline.scroll_by(5);
+        state.timeline.step(-5);
+        assert_eq!(state.timeline.current().map(|e| e.turn), Some(1));
+        assert_eq!(state.timeline.scroll, 0);
+        state.timeline.close();
+        assert!(!state.timeline.is_open());
+    }
+
+    #[test]
+    fn pager_scrolls_until_closed() {
+        let mut state = RatatuiLoop::new(RatatuiTheme::default(), None);
+        state.handle_command(RatatuiCommand::ShowPager {
+            title: "Request preview".to_string(),
+            lines: vec!["one".to_string(), "two".to_string()],
+        });
+        assert!(state.pager.is_open());
+        state.pager.scroll_by(-3);
+        assert_eq!(state.pager.scroll, 0);
+        state.pager.scroll_by(4);
+        assert_eq!(state.pager.scroll, 4);
+        state.handle_command(RatatuiCommand::ShowPager {
+            title: "Request preview".to_string(),
+            lines: vec!["edited".to_string()],
+        });
+        assert_eq!(state.pager.scroll, 0);
+        state.handle_command(RatatuiCommand::ClosePager);
+        assert!(!state.pager.is_open());
+    }
+
+    #[test]
+    fn pty_panel_collapses_spinner_frames() {
+        let mut panel = PtyPanel::new();
+        for frame in ["\r| building", "\r/ building", "\r- building\x1b["] {
+            panel.push_inline(frame);
+        }
+        assert_eq!(panel.trailing, "- building\x1b[");
+        panel.push_inline("2K\rdone\r\n");
+        let text = panel.view_text();
+        assert_eq!(text.lines.len(), 1);
+        assert_eq!(plain_line_text(&text.lines[0]), "done");
+    }
+
+    #[test]
+    fn suspend_drops_the_pty_panel_until_input() {
+        use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+        let mut state = RatatuiLoop::new(RatatuiTheme::default(), None);
+        state.ensure_pty_panel().push_inline("done\r\n");
+        assert!(state.handle_command(RatatuiCommand::Suspend));
+        assert!(state.is_suspended());
+        assert!(state.pty_panel.is_none());
+
+        let (events, _rx) = tokio::sync::mpsc::unbounded_channel();
+        assert!(!state.handle_event(Event::FocusGained, &events).unwrap());
+        assert!(state.is_suspended());
+        let key = KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE);
+        assert!(state.handle_event(Event::Key(key), &events).unwrap());
+        assert!(!state.is_suspended());
+    }
+
+    #[test]
+    fn selection_text_drops_panel_borders() {
+        let mut state = RatatuiLoop::new(RatatuiTheme::default(), None);
+        state.transcript_text = [
+            Line::from("╭──────╮"),
+            Line::from(vec![
+                Span::raw("│ "),
+                Span::raw("error[E0425]: x"),
+                Span::raw("  │"),
+            ]),
+            Line::from("│  --> src/lib.rs:3:9 │"),
+            Line::from("╰──────╯"),
+            Line::default(),
+        ]
+        .iter()
+        .map(plain_line_text)
+        .collect();
+
+        assert!(state.selected_transcript_text().is_none());
+        state.selection.begin(4);
+        state.selection.update(0);
+        assert_eq!(
+            state.selected_transcript_text().as_deref(),
+            Some("error[E0425]: x\n--> src/lib.rs:3:9")
+        );
+        state.selection.begin(4);
+        assert!(state.selected_transcript_text().is_none());
+    }
+
+    #[test]
+    fn outline_reference_is_appended_to_input() {
+        let mut state = RatatuiLoop::new(RatatuiTheme::default(), None);
+        state.handle_command(RatatuiCommand::SetOutline {
+            title: "src/lib.rs".to_string(),
+            entries: vec![outline_entry("parse", 3)],
+        });
+        assert!(state.toggle_outline_focus());
+        state.set_input_text("look at".to_string());
+        assert!(state.insert_outline_reference());
+        assert_eq!(state.input.value(), "look at src/lib.rs:3 (`parse`) ");
+    }
+
+    #[test]
+    fn slash_arguments_complete_and_hold_back_invalid_input() {
+        let mut state = RatatuiLoop::new(RatatuiTheme::default(), None);
+        state.set_input_text("/copy c".to_string());
+        assert_eq!(state.slash_suggestions.arguments, ["code"]);
+        assert!(state.apply_selected_suggestion());
+        assert_eq!(state.input.value(), "/copy code ");
+        assert!(!state.slash_suggestions.is_visible());
+
+        state.set_input_text("/preview maybe".to_string());
+        assert!(!state.check_slash_input());
+        assert_eq!(
+            state.slash_suggestions.error.as_deref(),
+            Some("'maybe' is not one of on, off")
+        );
+        state.set_input_text("/preview on".to_string());
+        assert!(state.slash_suggestions.error.is_none());
+        assert!(!state.apply_selected_suggestion());
+        assert!(state.check_slash_input());
+    }
 }
 
 impl RatatuiTextStyle {
@@ -146,10 +404,12 @@ impl Default for RatatuiTheme {
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum RatatuiMessageKind {
     Agent,
+    CodeExecution,
     Error,
     Info,
     Policy,
     Pty,
+    Refusal,
     Tool,
     User,
 }
@@ -190,12 +450,82 @@ pub enum RatatuiCommand {
     },
     SetCursorVisible(bool),
     SetInputEnabled(bool),
+    SetOutline {
+        title: String,
+        entries: Vec<RatatuiOutlineEntry>,
+    },
+    SetOutlineVisible(bool),
+    ClearOutline,
+    /// Directory that slash command path arguments are completed against
+    SetWorkspace(PathBuf),
+    /// Set the terminal clipboard with an OSC 52 sequence
+    SetClipboard(String),
+    /// Append text to the input buffer
+    InsertInput(String),
+    /// Open the timeline view at the most recent turn
+    ShowTimeline(Vec<RatatuiTimelineEntry>),
+    /// Show `lines` in a scrollable view over the transcript, replacing the
+    /// one already shown; typing still goes to the prompt
+    ShowPager {
+        title: String,
+        lines: Vec<String>,
+    },
+    ClosePager,
+    /// Keep the first `n` prompts of the transcript and drop everything after
+    TruncateTurns(usize),
+    /// Stop periodic work until the next key or mouse input
+    Suspend,
     Shutdown,
 }
 
+/// What a copy keybinding asked for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RatatuiCopyTarget {
+    /// Text of the transcript selection
+    Selection(String),
+    LastReply,
+    LastCodeBlock,
+}
+
+/// A symbol shown in the outline side panel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RatatuiOutlineEntry {
+    pub name: String,
+    /// Short kind label such as `fn` or `class`
+    pub kind: String,
+    /// 1-based line number
+    pub line: usize,
+    pub depth: usize,
+}
+
+/// One turn of the session shown in the timeline view
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RatatuiTimelineEntry {
+    /// 1-based turn number
+    pub turn: usize,
+    pub request: String,
+    pub reply: String,
+    /// Files changed during this turn
+    pub changed: Vec<String>,
+    /// Cumulative workspace diff at the end of the turn
+    pub diff: String,
+}
+
 #[derive(Debug, Clone)]
 pub enum RatatuiEvent {
     Submit(String),
+    /// Ctrl+E on a transcript selection: ask the agent to explain and fix it
+    ExplainSelection(String),
+    /// Ctrl+Y / Alt+Y: copy to the system clipboard
+    Copy(RatatuiCopyTarget),
+    /// Alt+F while scrolled back: fork the conversation after this turn,
+    /// counted from 0 among the prompts shown in the transcript
+    Fork(usize),
+    /// Alt+U: restore the files backed up before the last destructive command
+    Undo,
+    /// Alt+V: start recording voice input, or stop and transcribe it into
+    /// the prompt
+    ToggleVoice,
     Cancel,
     Exit,
     Interrupt,
@@ -379,6 +709,52 @@ impl RatatuiHandle {
         let _ = self.sender.send(RatatuiCommand::SetInputEnabled(enabled));
     }
 
+    pub fn set_outline(&self, title: String, entries: Vec<RatatuiOutlineEntry>) {
+        let _ = self
+            .sender
+            .send(RatatuiCommand::SetOutline { title, entries });
+    }
+
+    pub fn set_outline_visible(&self, visible: bool) {
+        let _ = self.sender.send(RatatuiCommand::SetOutlineVisible(visible));
+    }
+
+    pub fn set_workspace(&self, workspace: PathBuf) {
+        let _ = self.sender.send(RatatuiCommand::SetWorkspace(workspace));
+    }
+
+    pub fn clear_outline(&self) {
+        let _ = self.sender.send(RatatuiCommand::ClearOutline);
+    }
+
+    pub fn set_terminal_clipboard(&self, text: String) {
+        let _ = self.sender.send(RatatuiCommand::SetClipboard(text));
+    }
+
+    pub fn insert_input(&self, text: String) {
+        let _ = self.sender.send(RatatuiCommand::InsertInput(text));
+    }
+
+    pub fn show_timeline(&self, entries: Vec<RatatuiTimelineEntry>) {
+        let _ = self.sender.send(RatatuiCommand::ShowTimeline(entries));
+    }
+
+    pub fn show_pager(&self, title: String, lines: Vec<String>) {
+        let _ = self.sender.send(RatatuiCommand::ShowPager { title, lines });
+    }
+
+    pub fn close_pager(&self) {
+        let _ = self.sender.send(RatatuiCommand::ClosePager);
+    }
+
+    pub fn truncate_turns(&self, turns: usize) {
+        let _ = self.sender.send(RatatuiCommand::TruncateTurns(turns));
+    }
+
+    pub fn suspend(&self) {
+        let _ = self.sender.send(RatatuiCommand::Suspend);
+    }
+
     pub fn shutdown(&self) {
         let _ = self.sender.send(RatatuiCommand::Shutdown);
     }
@@ -686,9 +1062,9 @@ pub(crate) struct StatusBarContent {
 impl StatusBarContent {
     pub(crate) fn new() -> Self {
         Self {
-            left: "? help · / command".to_string(),
+            left: locale::text(Message::StatusHelp).to_string(),
             center: String::new(),
-            right: NAVIGATION_HINT_TEXT.to_string(),
+            right: locale::text(Message::NavigationHint).to_string(),
         }
     }
 
@@ -705,6 +1081,48 @@ impl StatusBarContent {
     }
 }
 
+/// Rows a message block occupies in the rendered transcript.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct BlockRows {
+    pub(crate) message: usize,
+    pub(crate) top: usize,
+    pub(crate) height: usize,
+}
+
+/// What the transcript viewport showed before a reflow.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum TranscriptAnchor {
+    /// Following the newest output
+    Bottom,
+    /// The top visible row was `row` of the `height` rows of `message`
+    Row {
+        message: usize,
+        row: usize,
+        height: usize,
+    },
+}
+
+/// Wrapped lines of a message block for the widths it was last rendered at.
+#[derive(Default)]
+pub(crate) struct WrappedBlock {
+    widths: Vec<(usize, Vec<Line<'static>>)>,
+}
+
+impl WrappedBlock {
+    pub(crate) fn get(&self, width: usize) -> Option<&[Line<'static>]> {
+        self.widths
+            .iter()
+            .find(|(cached, _)| *cached == width)
+            .map(|(_, lines)| lines.as_slice())
+    }
+
+    pub(crate) fn insert(&mut self, width: usize, lines: Vec<Line<'static>>) {
+        self.widths.retain(|(cached, _)| *cached != width);
+        self.widths.insert(0, (width, lines));
+        self.widths.truncate(WRAP_CACHE_WIDTHS);
+    }
+}
+
 #[derive(Clone, Copy)]
 pub(crate) struct PtyPlacement {
     pub(crate) top: usize,
@@ -761,33 +1179,84 @@ impl SelectionState {
     }
 }
 
+/// Text of a rendered transcript line without panel borders and indentation.
+pub(crate) fn plain_line_text(line: &Line<'_>) -> String {
+    let text: String = line
+        .spans
+        .iter()
+        .map(|span| span.content.as_ref())
+        .collect();
+    let trimmed = text.trim();
+    if trimmed
+        .chars()
+        .all(|ch| matches!(ch, '╭' | '╮' | '╰' | '╯' | '─'))
+    {
+        return String::new();
+    }
+    trimmed
+        .trim_start_matches('│')
+        .trim_end_matches('│')
+        .trim()
+        .to_string()
+}
+
 #[derive(Default)]
 pub(crate) struct SlashSuggestionState {
     pub(crate) items: Vec<&'static SlashCommandInfo>,
+    /// Command whose arguments are being typed
+    pub(crate) command: Option<&'static SlashCommandInfo>,
+    pub(crate) arguments: Vec<String>,
+    /// Why the last submission was held back
+    pub(crate) error: Option<String>,
     pub(crate) list_state: ListState,
 }
 
 impl SlashSuggestionState {
     pub(crate) fn clear(&mut self) {
         self.items.clear();
+        self.command = None;
+        self.arguments.clear();
+        self.error = None;
         self.list_state.select(None);
     }
 
     pub(crate) fn update(&mut self, query: &str) {
+        self.clear();
         self.items = suggestions_for(query);
-        if self.items.is_empty() {
-            self.list_state.select(None);
-        } else {
+        if !self.items.is_empty() {
+            self.list_state.select(Some(0));
+        }
+    }
+
+    /// Show the usage of `command` and completions for its first argument.
+    pub(crate) fn update_arguments(
+        &mut self,
+        command: &'static SlashCommandInfo,
+        partial: &str,
+        workspace: &Path,
+    ) {
+        self.clear();
+        self.command = Some(command);
+        self.arguments = argument_suggestions(command, partial, workspace);
+        if !self.arguments.is_empty() {
             self.list_state.select(Some(0));
         }
     }
 
     pub(crate) fn is_visible(&self) -> bool {
-        !self.items.is_empty()
+        !self.items.is_empty() || self.command.is_some() || self.error.is_some()
+    }
+
+    fn len(&self) -> usize {
+        if self.command.is_some() {
+            self.arguments.len()
+        } else {
+            self.items.len()
+        }
     }
 
     pub(crate) fn visible_capacity(&self) -> usize {
-        self.items.len().min(MAX_SLASH_SUGGESTIONS)
+        self.len().clamp(1, MAX_SLASH_SUGGESTIONS)
     }
 
     pub(crate) fn desired_height(&self) -> u16 {
@@ -817,33 +1286,25 @@ impl SlashSuggestionState {
     }
 
     pub(crate) fn select_previous(&mut self) -> bool {
-        if self.items.is_empty() {
+        let len = self.len();
+        if len == 0 {
             return false;
         }
         let current = self.list_state.selected().unwrap_or(0);
-        let len = self.items.len();
         let next = if current == 0 {
             len.saturating_sub(1)
         } else {
             current.saturating_sub(1)
         };
-        if len == 0 {
-            self.list_state.select(None);
-            return false;
-        }
-        if current != next {
-            self.list_state.select(Some(next));
-        } else {
-            self.list_state.select(Some(next));
-        }
+        self.list_state.select(Some(next));
         true
     }
 
     pub(crate) fn select_next(&mut self) -> bool {
-        if self.items.is_empty() {
+        let len = self.len();
+        if len == 0 {
             return false;
         }
-        let len = self.items.len();
         let current = self.list_state.selected().unwrap_or(0);
         let next = if current + 1 >= len { 0 } else { current + 1 };
         self.list_state.select(Some(next));
@@ -851,9 +1312,101 @@ impl SlashSuggestionState {
     }
 
     pub(crate) fn selected(&self) -> Option<&'static SlashCommandInfo> {
+        if self.command.is_some() {
+            return None;
+        }
         let index = self.list_state.selected()?;
         self.items.get(index).copied()
     }
+
+    pub(crate) fn selected_argument(&self) -> Option<&str> {
+        self.command?;
+        let index = self.list_state.selected()?;
+        self.arguments.get(index).map(String::as_str)
+    }
+}
+
+/// Symbol outline of the file under discussion, shown beside the transcript.
+#[derive(Default)]
+pub(crate) struct OutlinePanel {
+    pub(crate) title: String,
+    pub(crate) entries: Vec<RatatuiOutlineEntry>,
+    pub(crate) list_state: ListState,
+    pub(crate) visible: bool,
+    pub(crate) focused: bool,
+}
+
+impl OutlinePanel {
+    pub(crate) fn set_entries(&mut self, title: String, entries: Vec<RatatuiOutlineEntry>) {
+        let same_file = self.title == title;
+        let previous = self.list_state.selected().unwrap_or(0);
+        self.title = title;
+        self.entries = entries;
+        let selected = if same_file { previous } else { 0 };
+        if self.entries.is_empty() {
+            self.list_state.select(None);
+            self.focused = false;
+        } else {
+            self.list_state
+                .select(Some(selected.min(self.entries.len() - 1)));
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.title.clear();
+        self.entries.clear();
+        self.list_state.select(None);
+        self.focused = false;
+    }
+
+    pub(crate) fn is_shown(&self) -> bool {
+        self.visible && !self.entries.is_empty()
+    }
+
+    pub(crate) fn select_previous(&mut self) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let current = self.list_state.selected().unwrap_or(0);
+        let next = if current == 0 {
+            self.entries.len() - 1
+        } else {
+            current - 1
+        };
+        self.list_state.select(Some(next));
+    }
+
+    pub(crate) fn select_next(&mut self) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let current = self.list_state.selected().unwrap_or(0);
+        let next = if current + 1 >= self.entries.len() {
+            0
+        } else {
+            current + 1
+        };
+        self.list_state.select(Some(next));
+    }
+
+    pub(crate) fn selected(&self) -> Option<&RatatuiOutlineEntry> {
+        self.entries.get(self.list_state.selected()?)
+    }
+
+    /// Reference to the selected symbol, e.g. "src/lib.rs:12 (`parse`)"
+    pub(crate) fn selected_reference(&self) -> Option<String> {
+        let entry = self.selected()?;
+        Some(format!("{}:{} (`{}`)", self.title, entry.line, entry.name))
+    }
+
+    /// Question asking the agent about the selected symbol
+    pub(crate) fn selected_question(&self) -> Option<String> {
+        let entry = self.selected()?;
+        Some(format!(
+            "Explain the {} `{}` in {}:{}.",
+            entry.kind, entry.name, self.title, entry.line
+        ))
+    }
 }
 
 const PTY_MAX_LINES: usize = 200;
@@ -930,6 +1483,9 @@ impl PtyPanel {
         if !remaining.is_empty() {
             self.trailing.push_str(remaining);
         }
+        if self.trailing.contains('\r') {
+            self.collapse_trailing();
+        }
 
         if newline {
             if !self.trailing.is_empty() || text.is_empty() {
@@ -940,6 +1496,15 @@ impl PtyPanel {
         self.dirty = true;
     }
 
+    /// Apply carriage-return redraws to the unfinished line so a spinner
+    /// keeps one copy of its text rather than every frame.
+    fn collapse_trailing(&mut self) {
+        let mut sanitizer = AnsiSanitizer::new(HyperlinkMode::Osc8);
+        let mut collapsed = sanitizer.sanitize(&self.trailing).text;
+        collapsed.push_str(sanitizer.pending());
+        self.trailing = collapsed;
+    }
+
     pub(crate) fn commit_line(&mut self) {
         let line = mem::take(&mut self.trailing);
         self.lines.push_back(line);
@@ -1020,13 +1585,14 @@ impl PtyPanel {
             lines.join("\n")
         };
 
-        let parsed = if combined.is_empty() {
+        let sanitized = sanitize_ansi(&combined, HyperlinkMode::Footnote).with_footnotes();
+        let parsed = if sanitized.is_empty() {
             Text::default()
         } else {
-            combined
+            sanitized
                 .clone()
                 .into_text()
-                .unwrap_or_else(|_| Text::from(combined.clone()))
+                .unwrap_or_else(|_| Text::from(sanitized.clone()))
         };
 
         self.cached = parsed.clone();
@@ -1052,6 +1618,89 @@ pub(crate) struct InputDisplay {
     pub(crate) cursor: Option<(u16, u16)>,
     pub(crate) height: u16,
 }
+/// Full-screen view for stepping through the session turn by turn.
+#[derive(Default)]
+pub(crate) struct TimelineView {
+    pub(crate) entries: Vec<RatatuiTimelineEntry>,
+    pub(crate) selected: usize,
+    /// Lines scrolled past at the top of the current turn
+    pub(crate) scroll: u16,
+}
+
+impl TimelineView {
+    pub(crate) fn open(&mut self, entries: Vec<RatatuiTimelineEntry>) {
+        self.selected = entries.len().saturating_sub(1);
+        self.entries = entries;
+        self.scroll = 0;
+    }
+
+    pub(crate) fn close(&mut self) {
+        self.entries.clear();
+        self.selected = 0;
+        self.scroll = 0;
+    }
+
+    pub(crate) fn is_open(&self) -> bool {
+        !self.entries.is_empty()
+    }
+
+    pub(crate) fn current(&self) -> Option<&RatatuiTimelineEntry> {
+        self.entries.get(self.selected)
+    }
+
+    /// Move `delta` turns, staying within the session.
+    pub(crate) fn step(&mut self, delta: isize) {
+        self.jump_to(self.selected.saturating_add_signed(delta));
+    }
+
+    /// Select turn `index` (0-based), clamped to the last turn.
+    pub(crate) fn jump_to(&mut self, index: usize) {
+        let Some(last) = self.entries.len().checked_sub(1) else {
+            return;
+        };
+        let target = index.min(last);
+        if target != self.selected {
+            self.selected = target;
+            self.scroll = 0;
+        }
+    }
+
+    pub(crate) fn scroll_by(&mut self, delta: i32) {
+        self.scroll = (self.scroll as i32 + delta).clamp(0, u16::MAX as i32) as u16;
+    }
+}
+
+/// Read-only text shown over the transcript while the prompt stays usable,
+/// such as the request preview.
+#[derive(Default)]
+pub(crate) struct PagerView {
+    pub(crate) title: String,
+    pub(crate) lines: Vec<String>,
+    /// Lines scrolled past at the top
+    pub(crate) scroll: u16,
+}
+
+impl PagerView {
+    pub(crate) fn open(&mut self, title: String, lines: Vec<String>) {
+        self.title = title;
+        self.lines = lines;
+        self.scroll = 0;
+    }
+
+    pub(crate) fn close(&mut self) {
+        self.title.clear();
+        self.lines.clear();
+        self.scroll = 0;
+    }
+
+    pub(crate) fn is_open(&self) -> bool {
+        !self.lines.is_empty()
+    }
+
+    pub(crate) fn scroll_by(&mut self, delta: i32) {
+        self.scroll = (self.scroll as i32 + delta).clamp(0, u16::MAX as i32) as u16;
+    }
+}
 
 pub(crate) struct InputLayout {
     pub(crate) block_area: Rect,
@@ -1062,6 +1711,7 @@ pub(crate) struct InputLayout {
 pub(crate) struct AppLayout {
     pub(crate) message: Rect,
     pub(crate) input: Option<InputLayout>,
+    pub(crate) outline: Option<Rect>,
     pub(crate) status: Option<Rect>,
 }
 
@@ -1082,6 +1732,8 @@ pub(crate) struct RatatuiLoop {
     pub(crate) base_placeholder_style: RatatuiTextStyle,
     pub(crate) placeholder_style: RatatuiTextStyle,
     pub(crate) should_exit: bool,
+    /// Idle: ticks and pane size polling are paused until the next input
+    pub(crate) suspended: bool,
     pub(crate) theme: RatatuiTheme,
     pub(crate) last_escape: Option<Instant>,
     pub(crate) transcript_scroll: TranscriptScrollState,
@@ -1093,13 +1745,28 @@ pub(crate) struct RatatuiLoop {
     pub(crate) pty_area: Option<Rect>,
     pub(crate) pty_block: Option<PtyPlacement>,
     pub(crate) slash_suggestions: SlashSuggestionState,
+    pub(crate) outline: OutlinePanel,
+    pub(crate) timeline: TimelineView,
+    pub(crate) pager: PagerView,
     pub(crate) pty_panel: Option<PtyPanel>,
     pub(crate) status_bar: StatusBarContent,
     pub(crate) cursor_visible: bool,
     pub(crate) input_enabled: bool,
     pub(crate) selection: SelectionState,
+    /// Plain text of the last rendered transcript, indexed like the selection
+    pub(crate) transcript_text: Vec<String>,
+    /// Text waiting to be written to the terminal clipboard
+    pub(crate) pending_clipboard: Option<String>,
+    /// Root for completing path arguments of slash commands
+    pub(crate) workspace: Option<PathBuf>,
     pub(crate) agent_label: String,
     pub(crate) user_label: String,
+    /// Wrapping of each message block, indexed like `messages` and filled on render
+    pub(crate) wrapped_blocks: Vec<WrappedBlock>,
+    /// Rows of each rendered block in the last transcript build
+    pub(crate) block_rows: Vec<BlockRows>,
+    /// Width and height of the transcript text in the last frame
+    pub(crate) transcript_viewport: (u16, usize),
 }
 
 impl RatatuiLoop {
@@ -1137,6 +1804,7 @@ impl RatatuiLoop {
             base_placeholder_style: base_placeholder_style.clone(),
             placeholder_style: base_placeholder_style,
             should_exit: false,
+            suspended: false,
             theme,
             last_escape: None,
             transcript_scroll: TranscriptScrollState::default(),
@@ -1148,13 +1816,22 @@ impl RatatuiLoop {
             pty_area: None,
             pty_block: None,
             slash_suggestions: SlashSuggestionState::default(),
+            outline: OutlinePanel::default(),
+            timeline: TimelineView::default(),
+            pager: PagerView::default(),
             pty_panel: None,
             status_bar: StatusBarContent::new(),
             cursor_visible: true,
             input_enabled: true,
             selection: SelectionState::default(),
+            transcript_text: Vec::new(),
+            pending_clipboard: None,
+            workspace: None,
             agent_label: DEFAULT_AGENT_LABEL.to_string(),
             user_label: DEFAULT_USER_LABEL.to_string(),
+            wrapped_blocks: Vec::new(),
+            block_rows: Vec::new(),
+            transcript_viewport: (0, 0),
         }
     }
 
@@ -1170,6 +1847,15 @@ impl RatatuiLoop {
         false
     }
 
+    pub(crate) fn is_suspended(&self) -> bool {
+        self.suspended
+    }
+
+    /// Leave the idle state; true if the session was suspended.
+    pub(crate) fn resume(&mut self) -> bool {
+        std::mem::take(&mut self.suspended)
+    }
+
     pub(crate) fn handle_command(&mut self, command: RatatuiCommand) -> bool {
         match command {
             RatatuiCommand::AppendLine { kind, segments } => {
@@ -1248,6 +1934,7 @@ impl RatatuiLoop {
             RatatuiCommand::SetTheme { theme } => {
                 let previous_base = self.base_placeholder_style.clone();
                 self.theme = theme;
+                self.wrapped_blocks.clear();
                 let new_base = Self::default_placeholder_style(&self.theme);
                 self.base_placeholder_style = new_base.clone();
                 if self.placeholder_style == previous_base {
@@ -1276,6 +1963,53 @@ impl RatatuiLoop {
                 }
                 true
             }
+            RatatuiCommand::SetOutline { title, entries } => {
+                self.outline.set_entries(title, entries);
+                true
+            }
+            RatatuiCommand::SetOutlineVisible(visible) => {
+                self.outline.visible = visible;
+                if !visible {
+                    self.outline.focused = false;
+                }
+                true
+            }
+            RatatuiCommand::ClearOutline => {
+                self.outline.clear();
+                true
+            }
+            RatatuiCommand::SetWorkspace(workspace) => {
+                self.workspace = Some(workspace);
+                false
+            }
+            RatatuiCommand::SetClipboard(text) => {
+                self.pending_clipboard = Some(text);
+                false
+            }
+            RatatuiCommand::InsertInput(text) => {
+                let value = format!("{}{}", self.input.value(), text);
+                self.set_input_text(value);
+                true
+            }
+            RatatuiCommand::ShowTimeline(entries) => {
+                self.timeline.open(entries);
+                true
+            }
+            RatatuiCommand::ShowPager { title, lines } => {
+                self.pager.open(title, lines);
+                true
+            }
+            RatatuiCommand::ClosePager => {
+                self.pager.close();
+                true
+            }
+            RatatuiCommand::TruncateTurns(turns) => self.truncate_turns(turns),
+            RatatuiCommand::Suspend => {
+                // Terminal commands have finished by now; drop the output they left
+                self.pty_panel = None;
+                self.suspended = true;
+                true
+            }
             RatatuiCommand::Shutdown => {
                 self.should_exit = true;
                 true
@@ -1360,16 +2094,40 @@ impl RatatuiLoop {
     }
 
     pub(crate) fn refresh_slash_suggestions(&mut self) {
-        if let Some(rest) = self.input.value().strip_prefix('/') {
-            let trimmed = rest.trim_start();
-            if trimmed.chars().any(char::is_whitespace) {
-                self.slash_suggestions.clear();
-                return;
-            }
-            let query = trimmed.trim_end();
-            self.slash_suggestions.update(query);
-        } else {
+        let Some(rest) = self.input.value().strip_prefix('/') else {
             self.slash_suggestions.clear();
+            return;
+        };
+        let trimmed = rest.trim_start();
+        let Some((name, arguments)) = trimmed.split_once(char::is_whitespace) else {
+            self.slash_suggestions.update(trimmed.trim_end());
+            return;
+        };
+        let arguments = arguments.trim_start();
+        match find_command(name) {
+            // Complete the first argument only; later ones are free-form
+            Some(command) if !arguments.contains(char::is_whitespace) => {
+                let workspace = self
+                    .workspace
+                    .clone()
+                    .or_else(|| env::current_dir().ok())
+                    .unwrap_or_default();
+                self.slash_suggestions
+                    .update_arguments(command, arguments, &workspace);
+            }
+            _ => self.slash_suggestions.clear(),
+        }
+    }
+
+    /// Hold back a slash command with invalid arguments, showing why in the
+    /// suggestion popover. Returns whether the input can be submitted.
+    pub(crate) fn check_slash_input(&mut self) -> bool {
+        match validate_input(self.input.value()) {
+            Ok(()) => true,
+            Err(hint) => {
+                self.slash_suggestions.error = Some(hint);
+                false
+            }
         }
     }
 
@@ -1412,13 +2170,63 @@ impl RatatuiLoop {
                 changed = true;
             }
         }
+        if changed {
+            self.wrapped_blocks.clear();
+        }
         changed
     }
 
+    /// Text of the selected transcript lines, with surrounding blank lines dropped.
+    pub(crate) fn selected_transcript_text(&self) -> Option<String> {
+        let (start, end) = self.selection.range()?;
+        let last_index = self.transcript_text.len().checked_sub(1)?;
+        let lines = &self.transcript_text[start.min(last_index)..=end.min(last_index)];
+        let first = lines.iter().position(|line| !line.is_empty())?;
+        let last = lines.iter().rposition(|line| !line.is_empty())?;
+        Some(lines[first..=last].join("\n"))
+    }
+
+    pub(crate) fn take_pending_clipboard(&mut self) -> Option<String> {
+        self.pending_clipboard.take()
+    }
+
+    /// Ctrl+O: show and focus the outline, or hide it when already focused.
+    pub(crate) fn toggle_outline_focus(&mut self) -> bool {
+        if self.outline.entries.is_empty() {
+            return false;
+        }
+        if self.outline.visible && self.outline.focused {
+            self.outline.visible = false;
+            self.outline.focused = false;
+        } else {
+            self.outline.visible = true;
+            self.outline.focused = true;
+        }
+        true
+    }
+
+    /// Append a reference to the selected outline symbol to the input.
+    pub(crate) fn insert_outline_reference(&mut self) -> bool {
+        let Some(reference) = self.outline.selected_reference() else {
+            return false;
+        };
+        let current = self.input.value();
+        let value = if current.is_empty() || current.ends_with(' ') {
+            format!("{}{} ", current, reference)
+        } else {
+            format!("{} {} ", current, reference)
+        };
+        self.set_input_text(value);
+        true
+    }
+
     pub(crate) fn apply_selected_suggestion(&mut self) -> bool {
         if !self.input_enabled {
             return false;
         }
+        if let Some(argument) = self.slash_suggestions.selected_argument() {
+            return self.apply_selected_argument(argument.to_string());
+        }
         let Some(selected) = self.slash_suggestions.selected() else {
             return false;
         };
@@ -1443,6 +2251,21 @@ impl RatatuiLoop {
         true
     }
 
+    /// Replace the argument being typed with `argument`; returns false when
+    /// it is already complete so that Enter submits the command.
+    fn apply_selected_argument(&mut self, argument: String) -> bool {
+        let raw = self.input.value();
+        let name_end = raw.find(char::is_whitespace).unwrap_or(raw.len());
+        if raw[name_end..].trim() == argument {
+            return false;
+        }
+        // Directories stay open for the next path segment
+        let separator = if argument.ends_with('/') { "" } else { " " };
+        let new_value = format!("{} {argument}{separator}", &raw[..name_end]);
+        self.set_input_text(new_value);
+        true
+    }
+
     pub(crate) fn push_line(&mut self, kind: RatatuiMessageKind, line: StyledLine) {
         if kind == RatatuiMessageKind::Agent && !line.has_visible_content() {
             return;
@@ -1453,11 +2276,12 @@ impl RatatuiLoop {
                 return;
             }
         }
-        if let Some(block) = self.messages.last_mut() {
-            if block.kind == kind {
-                block.lines.push(line);
-                return;
-            }
+        if let Some(block) = self.messages.last_mut()
+            && block.kind == kind
+        {
+            block.lines.push(line);
+            self.invalidate_last_block();
+            return;
         }
 
         if kind == RatatuiMessageKind::User && !self.messages.is_empty() {
@@ -1478,11 +2302,12 @@ impl RatatuiLoop {
         if lines.is_empty() {
             return false;
         }
-        if let Some(block) = self.messages.last_mut() {
-            if block.kind == RatatuiMessageKind::Tool {
-                block.lines = lines;
-                return true;
-            }
+        if let Some(block) = self.messages.last_mut()
+            && block.kind == RatatuiMessageKind::Tool
+        {
+            block.lines = lines;
+            self.invalidate_last_block();
+            return true;
         }
         self.messages.push(MessageBlock {
             kind: RatatuiMessageKind::Tool,
@@ -1624,6 +2449,47 @@ impl RatatuiLoop {
         true
     }
 
+    /// The prompt at the top of the transcript view, counted from 0, or the
+    /// first one below it when the view starts before any prompt.
+    pub(crate) fn viewed_turn(&self) -> Option<usize> {
+        let top = self.transcript_scroll.offset();
+        let mut viewed = None;
+        let prompts = self.block_rows.iter().filter(|rows| {
+            self.messages
+                .get(rows.message)
+                .is_some_and(|block| block.kind == RatatuiMessageKind::User)
+        });
+        for (turn, rows) in prompts.enumerate() {
+            if rows.top > top {
+                return viewed.or(Some(turn));
+            }
+            viewed = Some(turn);
+        }
+        viewed
+    }
+
+    /// Drop the transcript from prompt `turns` on, after a fork.
+    pub(crate) fn truncate_turns(&mut self, turns: usize) -> bool {
+        let Some(cut) = self
+            .messages
+            .iter()
+            .enumerate()
+            .filter(|(_, block)| block.kind == RatatuiMessageKind::User)
+            .nth(turns)
+            .map(|(index, _)| index)
+        else {
+            return false;
+        };
+        self.messages.truncate(cut);
+        self.wrapped_blocks.truncate(cut);
+        self.selection.clear();
+        self.trim_empty_conversations();
+        self.active_conversation = self.conversation_offsets.len().saturating_sub(1);
+        self.transcript_autoscroll = true;
+        self.transcript_scroll.scroll_to_bottom();
+        true
+    }
+
     pub(crate) fn trim_empty_conversations(&mut self) {
         while self.conversation_offsets.len() > 1 {
             let last = *self.conversation_offsets.last().unwrap();
@@ -1760,9 +2626,62 @@ impl RatatuiLoop {
             self.messages.pop();
         }
 
+        self.invalidate_last_block();
         self.trim_empty_conversations();
     }
 
+    /// Drop the cached wrapping of the last block, which just changed, and of
+    /// blocks that no longer exist.
+    pub(crate) fn invalidate_last_block(&mut self) {
+        self.wrapped_blocks
+            .truncate(self.messages.len().saturating_sub(1));
+    }
+
+    /// The transcript position to keep when it is reflowed for a new size.
+    pub(crate) fn transcript_anchor(&self) -> TranscriptAnchor {
+        if self.transcript_scroll.should_follow_new_content() {
+            return TranscriptAnchor::Bottom;
+        }
+        let offset = self.transcript_scroll.offset();
+        self.block_rows
+            .iter()
+            .find(|rows| rows.top + rows.height > offset)
+            .map_or(TranscriptAnchor::Bottom, |rows| TranscriptAnchor::Row {
+                message: rows.message,
+                row: offset.saturating_sub(rows.top),
+                height: rows.height,
+            })
+    }
+
+    /// Scroll the reflowed transcript back to `anchor`. A block that now wraps
+    /// to a different height keeps the same proportion of it above the viewport.
+    pub(crate) fn restore_transcript_anchor(&mut self, anchor: TranscriptAnchor) {
+        match anchor {
+            TranscriptAnchor::Bottom => self.transcript_scroll.scroll_to_bottom(),
+            TranscriptAnchor::Row {
+                message,
+                row,
+                height,
+            } => {
+                let Some(rows) = self
+                    .block_rows
+                    .iter()
+                    .find(|rows| rows.message >= message)
+                    .copied()
+                else {
+                    self.transcript_scroll.scroll_to_bottom();
+                    return;
+                };
+                let row = if rows.message == message && height > 0 {
+                    (row * rows.height / height).min(rows.height.saturating_sub(1))
+                } else {
+                    0
+                };
+                self.transcript_scroll.jump_to(rows.top + row);
+            }
+        }
+    }
+
     pub(crate) fn ensure_pty_panel(&mut self) -> &mut PtyPanel {
         if self.pty_panel.is_none() {
             self.pty_panel = Some(PtyPanel::new());
@@ -1,19 +1,31 @@
 use crate::config::loader::SyntaxHighlightingConfig;
-use crate::ui::markdown::{MarkdownLine, MarkdownSegment, render_markdown_to_lines};
+use crate::ui::markdown::{MarkdownLine, MarkdownSegment, render_markdown_to_lines_with_width};
 use crate::ui::theme;
+#[cfg(feature = "ui")]
 use crate::ui::tui::{
     RatatuiHandle, RatatuiMessageKind, RatatuiSegment, RatatuiTextStyle,
     convert_style as convert_to_ratatui_style, theme_from_styles,
 };
+use crate::utils::ansi_sanitize::{AnsiSanitizer, HyperlinkMode, sanitize_ansi};
 use crate::utils::transcript;
+#[cfg(feature = "ui")]
 use ansi_to_tui::IntoText;
 use anstream::{AutoStream, ColorChoice};
 use anstyle::{Reset, Style};
 use anstyle_query::{clicolor, clicolor_force, no_color, term_supports_color};
 use anyhow::{Result, anyhow};
+#[cfg(feature = "ui")]
 use ratatui::style::{Modifier as RatatuiModifier, Style as RatatuiStyle};
 use std::io::{self, Write};
 
+/// Columns kept free beside rendered markdown for the transcript scrollbar and margins.
+const MARKDOWN_RIGHT_MARGIN: usize = 4;
+
+/// Width available to markdown tables in a message with `indent`.
+fn markdown_width(indent: &str) -> usize {
+    crate::ui::terminal::get_terminal_width().saturating_sub(indent.len() + MARKDOWN_RIGHT_MARGIN)
+}
+
 /// Styles available for rendering messages
 #[derive(Clone, Copy)]
 pub enum MessageStyle {
@@ -24,6 +36,10 @@ pub enum MessageStyle {
     Tool,
     User,
     Reasoning,
+    /// Provider declined the request on content-safety grounds
+    Refusal,
+    /// Code run by the provider's hosted interpreter, and its output
+    CodeExecution,
 }
 
 impl MessageStyle {
@@ -37,6 +53,8 @@ impl MessageStyle {
             Self::Tool => styles.tool,
             Self::User => styles.user,
             Self::Reasoning => styles.reasoning,
+            Self::Refusal => styles.error,
+            Self::CodeExecution => styles.output,
         }
     }
 
@@ -53,9 +71,12 @@ pub struct AnsiRenderer {
     writer: AutoStream<io::Stdout>,
     buffer: String,
     color: bool,
+    #[cfg(feature = "ui")]
     sink: Option<RatatuiSink>,
     last_line_was_empty: bool,
     highlight_config: SyntaxHighlightingConfig,
+    hyperlinks: HyperlinkMode,
+    inline_sanitizer: AnsiSanitizer,
 }
 
 impl AnsiRenderer {
@@ -68,17 +89,22 @@ impl AnsiRenderer {
         } else {
             ColorChoice::Never
         };
+        let hyperlinks = HyperlinkMode::detect();
         Self {
             writer: AutoStream::new(std::io::stdout(), choice),
             buffer: String::new(),
             color,
+            #[cfg(feature = "ui")]
             sink: None,
             last_line_was_empty: false,
             highlight_config: SyntaxHighlightingConfig::default(),
+            hyperlinks,
+            inline_sanitizer: AnsiSanitizer::new(hyperlinks),
         }
     }
 
     /// Create a renderer that forwards output to a ratatui session handle
+    #[cfg(feature = "ui")]
     pub fn with_ratatui(handle: RatatuiHandle, highlight_config: SyntaxHighlightingConfig) -> Self {
         let mut renderer = Self::stdout();
         renderer.highlight_config = highlight_config;
@@ -97,6 +123,7 @@ impl AnsiRenderer {
         self.last_line_was_empty
     }
 
+    #[cfg(feature = "ui")]
     fn message_kind(style: MessageStyle) -> RatatuiMessageKind {
         match style {
             MessageStyle::Info => RatatuiMessageKind::Info,
@@ -106,13 +133,21 @@ impl AnsiRenderer {
             MessageStyle::Tool => RatatuiMessageKind::Tool,
             MessageStyle::User => RatatuiMessageKind::User,
             MessageStyle::Reasoning => RatatuiMessageKind::Policy,
+            MessageStyle::Refusal => RatatuiMessageKind::Refusal,
+            MessageStyle::CodeExecution => RatatuiMessageKind::CodeExecution,
         }
     }
 
+    #[cfg(feature = "ui")]
     pub fn supports_streaming_markdown(&self) -> bool {
         self.sink.is_some()
     }
 
+    #[cfg(not(feature = "ui"))]
+    pub fn supports_streaming_markdown(&self) -> bool {
+        false
+    }
+
     /// Push text into the buffer
     pub fn push(&mut self, text: &str) {
         self.buffer.push_str(text);
@@ -120,6 +155,7 @@ impl AnsiRenderer {
 
     /// Flush the buffer with the given style
     pub fn flush(&mut self, style: MessageStyle) -> Result<()> {
+        #[cfg(feature = "ui")]
         if let Some(sink) = &mut self.sink {
             let indent = style.indent();
             let line = self.buffer.clone();
@@ -150,11 +186,14 @@ impl AnsiRenderer {
         }
         let indent = style.indent();
 
+        #[cfg(feature = "ui")]
         if let Some(sink) = &mut self.sink {
             sink.write_multiline(style.style(), indent, text, Self::message_kind(style))?;
             return Ok(());
         }
 
+        let sanitized = sanitize_ansi(text, self.hyperlinks).with_footnotes();
+        let text = sanitized.as_str();
         if text.contains('\n') {
             let trailing_newline = text.ends_with('\n');
             for line in text.lines() {
@@ -185,11 +224,13 @@ impl AnsiRenderer {
 
     /// Write styled text without a trailing newline
     pub fn inline_with_style(&mut self, style: MessageStyle, text: &str) -> Result<()> {
+        #[cfg(feature = "ui")]
         if let Some(sink) = &mut self.sink {
             sink.write_inline(style.style(), text, Self::message_kind(style));
             return Ok(());
         }
         let ansi_style = style.style();
+        let text = self.inline_sanitizer.sanitize(text).with_footnotes();
         if self.color {
             write!(self.writer, "{ansi_style}{}{Reset}", text)?;
         } else {
@@ -201,10 +242,13 @@ impl AnsiRenderer {
 
     /// Write a line with an explicit style
     pub fn line_with_style(&mut self, style: Style, text: &str) -> Result<()> {
+        #[cfg(feature = "ui")]
         if let Some(sink) = &mut self.sink {
             sink.write_multiline(style, "", text, RatatuiMessageKind::Info)?;
             return Ok(());
         }
+        let text = sanitize_ansi(text, self.hyperlinks).with_footnotes();
+        let text = text.as_str();
         if self.color {
             writeln!(self.writer, "{style}{}{Reset}", text)?;
         } else {
@@ -241,7 +285,13 @@ impl AnsiRenderer {
         } else {
             None
         };
-        let mut lines = render_markdown_to_lines(text, base_style, &styles, highlight_cfg);
+        let mut lines = render_markdown_to_lines_with_width(
+            text,
+            base_style,
+            &styles,
+            highlight_cfg,
+            Some(markdown_width(indent)),
+        );
         if lines.is_empty() {
             lines.push(MarkdownLine::default());
         }
@@ -251,6 +301,7 @@ impl AnsiRenderer {
         Ok(())
     }
 
+    #[cfg(feature = "ui")]
     pub fn stream_markdown_response(
         &mut self,
         text: &str,
@@ -265,7 +316,13 @@ impl AnsiRenderer {
         } else {
             None
         };
-        let mut lines = render_markdown_to_lines(text, base_style, &styles, highlight_cfg);
+        let mut lines = render_markdown_to_lines_with_width(
+            text,
+            base_style,
+            &styles,
+            highlight_cfg,
+            Some(markdown_width(indent)),
+        );
         if lines.is_empty() {
             lines.push(MarkdownLine::default());
         }
@@ -302,6 +359,15 @@ impl AnsiRenderer {
         Err(anyhow!("stream_markdown_response requires a ratatui sink"))
     }
 
+    #[cfg(not(feature = "ui"))]
+    pub fn stream_markdown_response(
+        &mut self,
+        _text: &str,
+        _previous_line_count: usize,
+    ) -> Result<usize> {
+        Err(anyhow!("stream_markdown_response requires a ratatui sink"))
+    }
+
     fn write_markdown_line(
         &mut self,
         style: MessageStyle,
@@ -313,6 +379,7 @@ impl AnsiRenderer {
                 .insert(0, MarkdownSegment::new(style.style(), indent));
         }
 
+        #[cfg(feature = "ui")]
         if let Some(sink) = &mut self.sink {
             sink.write_segments(&line.segments, Self::message_kind(style))?;
             self.last_line_was_empty = line.is_empty();
@@ -345,13 +412,19 @@ impl AnsiRenderer {
     }
 }
 
+#[cfg(feature = "ui")]
 struct RatatuiSink {
     handle: RatatuiHandle,
+    inline_sanitizer: AnsiSanitizer,
 }
 
+#[cfg(feature = "ui")]
 impl RatatuiSink {
     fn new(handle: RatatuiHandle) -> Self {
-        Self { handle }
+        Self {
+            handle,
+            inline_sanitizer: AnsiSanitizer::new(HyperlinkMode::Footnote),
+        }
     }
 
     fn resolve_fallback_style(&self, style: Style) -> RatatuiTextStyle {
@@ -449,7 +522,9 @@ impl RatatuiSink {
         }
 
         let fallback = self.resolve_fallback_style(style);
-        let (converted_lines, plain_lines) = self.convert_ansi_lines(text, &fallback);
+        // The transcript is a ratatui buffer, so links are listed as footnotes
+        let text = sanitize_ansi(text, HyperlinkMode::Footnote).with_footnotes();
+        let (converted_lines, plain_lines) = self.convert_ansi_lines(&text, &fallback);
 
         for (mut segments, mut plain) in converted_lines.into_iter().zip(plain_lines.into_iter()) {
             if !indent.is_empty() && !plain.is_empty() {
@@ -485,11 +560,12 @@ impl RatatuiSink {
     }
 
     fn write_inline(&mut self, style: Style, text: &str, kind: RatatuiMessageKind) {
+        let text = self.inline_sanitizer.sanitize(text).with_footnotes();
         if text.is_empty() {
             return;
         }
         let fallback = self.resolve_fallback_style(style);
-        let (converted_lines, _) = self.convert_ansi_lines(text, &fallback);
+        let (converted_lines, _) = self.convert_ansi_lines(&text, &fallback);
         let line_count = converted_lines.len();
 
         for (index, mut segments) in converted_lines.into_iter().enumerate() {
@@ -584,6 +660,7 @@ mod tests {
         assert_eq!(r.buffer, "hello");
     }
 
+    #[cfg(feature = "ui")]
     #[test]
     fn ansi_to_tui_retains_trailing_newline() {
         let parsed = "hello\n".into_text().expect("failed to parse ansi text");
@@ -0,0 +1,507 @@
+//! Terminal output sanitization for the transcript
+//!
+//! Tool output carries more than colors: progress bars move the cursor and
+//! erase lines, spinners redraw themselves after a carriage return, and some
+//! tools print OSC 8 hyperlinks. [`AnsiSanitizer`] keeps SGR (color and style)
+//! sequences, applies carriage returns, backspaces and line erasure to the
+//! current line the way a terminal would, drops every other control sequence,
+//! and renders hyperlinks according to a [`HyperlinkMode`]. An escape sequence
+//! cut off at the end of a chunk is held back and completed by the next one.
+
+use std::env;
+use std::io::{self, IsTerminal};
+
+const ESC: char = '\x1b';
+const BEL: char = '\x07';
+const BACKSPACE: char = '\x08';
+const OSC8_CLOSE: &str = "\x1b]8;;\x1b\\";
+/// Longest incomplete escape sequence held back for the next chunk.
+const MAX_PENDING_BYTES: usize = 4096;
+
+/// How OSC 8 hyperlinks are rendered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HyperlinkMode {
+    /// Keep the links as OSC 8 sequences for the terminal to make clickable
+    Osc8,
+    /// Show the link text with a `[n]` marker and list the URLs as footnotes
+    Footnote,
+}
+
+impl HyperlinkMode {
+    /// OSC 8 when stdout is a terminal known to support it, footnotes otherwise.
+    pub fn detect() -> Self {
+        if terminal_supports_hyperlinks() {
+            Self::Osc8
+        } else {
+            Self::Footnote
+        }
+    }
+}
+
+/// Whether stdout is a terminal that renders OSC 8 hyperlinks.
+///
+/// `FORCE_HYPERLINK=1` or `FORCE_HYPERLINK=0` overrides the detection.
+pub fn terminal_supports_hyperlinks() -> bool {
+    if let Ok(value) = env::var("FORCE_HYPERLINK") {
+        return value != "0";
+    }
+    if !io::stdout().is_terminal() {
+        return false;
+    }
+    if [
+        "WT_SESSION",
+        "KITTY_WINDOW_ID",
+        "WEZTERM_EXECUTABLE",
+        "DOMTERM",
+    ]
+    .iter()
+    .any(|name| env::var_os(name).is_some())
+    {
+        return true;
+    }
+    if let Ok(program) = env::var("TERM_PROGRAM")
+        && matches!(
+            program.as_str(),
+            "iTerm.app" | "WezTerm" | "vscode" | "ghostty" | "Hyper"
+        )
+    {
+        return true;
+    }
+    if let Some(version) = env::var("VTE_VERSION")
+        .ok()
+        .and_then(|value| value.parse::<u32>().ok())
+    {
+        return version >= 5000;
+    }
+    env::var("TERM").is_ok_and(|term| {
+        ["kitty", "alacritty", "foot", "wezterm", "ghostty"]
+            .iter()
+            .any(|name| term.contains(name))
+    })
+}
+
+/// Sanitized text and the footnotes for the links it references.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Sanitized {
+    pub text: String,
+    /// `[n] url` lines for the footnote markers placed in `text`
+    pub footnotes: Vec<String>,
+}
+
+impl Sanitized {
+    /// The text followed by its footnotes, one per line.
+    pub fn with_footnotes(mut self) -> String {
+        for footnote in &self.footnotes {
+            if !self.text.is_empty() && !self.text.ends_with('\n') {
+                self.text.push('\n');
+            }
+            self.text.push_str(footnote);
+        }
+        self.text
+    }
+}
+
+/// Sanitize a complete piece of output, closing any link left open.
+pub fn sanitize_ansi(text: &str, mode: HyperlinkMode) -> Sanitized {
+    let mut sanitizer = AnsiSanitizer::new(mode);
+    let mut sanitized = sanitizer.sanitize(text);
+    let tail = sanitizer.finish();
+    sanitized.text.push_str(&tail.text);
+    sanitized.footnotes.extend(tail.footnotes);
+    sanitized
+}
+
+struct OpenLink {
+    url: String,
+    /// Where the link text starts in the current chunk's output
+    text_start: Option<usize>,
+}
+
+/// Stateful sanitizer for output that arrives in chunks.
+///
+/// Footnote numbers continue across chunks, and a link opened in one chunk is
+/// closed in whichever chunk ends it.
+pub struct AnsiSanitizer {
+    mode: HyperlinkMode,
+    pending: String,
+    open_link: Option<OpenLink>,
+    footnote_count: usize,
+}
+
+impl AnsiSanitizer {
+    pub fn new(mode: HyperlinkMode) -> Self {
+        Self {
+            mode,
+            pending: String::new(),
+            open_link: None,
+            footnote_count: 0,
+        }
+    }
+
+    /// Input held back from the last chunk because it ended mid-sequence.
+    pub fn pending(&self) -> &str {
+        &self.pending
+    }
+
+    /// Sanitize the next chunk of output.
+    pub fn sanitize(&mut self, chunk: &str) -> Sanitized {
+        let input = std::mem::take(&mut self.pending) + chunk;
+        let mut out = Output::default();
+        let mut footnotes = Vec::new();
+        if let Some(link) = &mut self.open_link {
+            link.text_start = None;
+        }
+
+        let mut rest = input.as_str();
+        while let Some(ch) = rest.chars().next() {
+            let mut consumed = ch.len_utf8();
+            match ch {
+                ESC => match parse_escape(rest) {
+                    None => {
+                        self.hold(rest);
+                        break;
+                    }
+                    Some((sequence, len)) => {
+                        consumed = len;
+                        self.apply(sequence, &rest[..len], &mut out, &mut footnotes);
+                    }
+                },
+                '\n' => out.newline(),
+                '\r' => match rest[1..].chars().next() {
+                    Some('\n') => {}
+                    Some(_) => out.clear_line(),
+                    None => {
+                        self.hold(rest);
+                        break;
+                    }
+                },
+                BACKSPACE => out.backspace(),
+                '\t' => out.text.push('\t'),
+                ch if ch.is_control() => {}
+                ch => out.text.push(ch),
+            }
+            if let Some(link) = &mut self.open_link {
+                link.text_start = link.text_start.map(|start| start.min(out.text.len()));
+            }
+            rest = &rest[consumed..];
+        }
+
+        Sanitized {
+            text: out.text,
+            footnotes,
+        }
+    }
+
+    /// Close a link left open and drop any incomplete sequence.
+    pub fn finish(&mut self) -> Sanitized {
+        self.pending.clear();
+        let mut out = Output::default();
+        let mut footnotes = Vec::new();
+        self.close_link(&mut out, &mut footnotes);
+        Sanitized {
+            text: out.text,
+            footnotes,
+        }
+    }
+
+    fn hold(&mut self, rest: &str) {
+        if rest.len() <= MAX_PENDING_BYTES {
+            self.pending = rest.to_string();
+        }
+    }
+
+    fn apply(
+        &mut self,
+        sequence: Escape<'_>,
+        raw: &str,
+        out: &mut Output,
+        footnotes: &mut Vec<String>,
+    ) {
+        match sequence {
+            Escape::Csi { params, final_byte } => match final_byte {
+                // Private (`?`, `>`) and intermediate bytes make it something other than SGR
+                b'm' if params
+                    .bytes()
+                    .all(|byte| byte.is_ascii_digit() || matches!(byte, b';' | b':')) =>
+                {
+                    // A reset cancels every earlier style, so redraws need not replay them
+                    if matches!(params, "" | "0") {
+                        out.line_codes.clear();
+                    }
+                    out.push_code(raw);
+                }
+                b'K' if matches!(params, "1" | "2") => out.clear_line(),
+                _ => {}
+            },
+            Escape::Osc(body) => {
+                let Some(link) = body.strip_prefix("8;") else {
+                    return;
+                };
+                let url = link.split_once(';').map_or("", |(_, url)| url);
+                self.close_link(out, footnotes);
+                if url.is_empty() {
+                    return;
+                }
+                if self.mode == HyperlinkMode::Osc8 {
+                    out.push_code(&format!("\x1b]8;;{}\x1b\\", url));
+                }
+                self.open_link = Some(OpenLink {
+                    url: url.to_string(),
+                    text_start: Some(out.text.len()),
+                });
+            }
+            Escape::Other => {}
+        }
+    }
+
+    fn close_link(&mut self, out: &mut Output, footnotes: &mut Vec<String>) {
+        let Some(link) = self.open_link.take() else {
+            return;
+        };
+        match self.mode {
+            HyperlinkMode::Osc8 => out.push_code(OSC8_CLOSE),
+            HyperlinkMode::Footnote => {
+                let shown = link
+                    .text_start
+                    .and_then(|start| out.text.get(start..))
+                    .map(visible_text);
+                if shown.as_deref().map(str::trim) == Some(link.url.as_str()) {
+                    return;
+                }
+                self.footnote_count += 1;
+                out.text.push_str(&format!("[{}]", self.footnote_count));
+                footnotes.push(format!("[{}] {}", self.footnote_count, link.url));
+            }
+        }
+    }
+}
+
+/// Sanitized output with enough bookkeeping to rewrite the current line.
+#[derive(Default)]
+struct Output {
+    text: String,
+    /// Start of the current line in `text`
+    line_start: usize,
+    /// Sequences emitted on the current line, replayed when it is cleared
+    line_codes: String,
+    /// End of the last sequence, so backspace never splits one
+    code_end: usize,
+}
+
+impl Output {
+    fn push_code(&mut self, code: &str) {
+        self.text.push_str(code);
+        self.line_codes.push_str(code);
+        self.code_end = self.text.len();
+    }
+
+    fn newline(&mut self) {
+        self.text.push('\n');
+        self.line_start = self.text.len();
+        self.code_end = self.line_start;
+        self.line_codes.clear();
+    }
+
+    /// Discard the current line's text, keeping its styling.
+    fn clear_line(&mut self) {
+        self.text.truncate(self.line_start);
+        self.text.push_str(&self.line_codes);
+        self.code_end = self.text.len();
+    }
+
+    fn backspace(&mut self) {
+        if self.text.len() > self.code_end.max(self.line_start) {
+            self.text.pop();
+        }
+    }
+}
+
+enum Escape<'a> {
+    Csi { params: &'a str, final_byte: u8 },
+    Osc(&'a str),
+    Other,
+}
+
+/// Parse the escape sequence at the start of `input`, returning it with its
+/// length, or `None` if `input` ends before the sequence does.
+fn parse_escape(input: &str) -> Option<(Escape<'_>, usize)> {
+    let bytes = input.as_bytes();
+    match *bytes.get(1)? {
+        b'[' => {
+            for (index, &byte) in bytes.iter().enumerate().skip(2) {
+                match byte {
+                    0x20..=0x3f => continue,
+                    0x40..=0x7e => {
+                        let sequence = Escape::Csi {
+                            params: &input[2..index],
+                            final_byte: byte,
+                        };
+                        return Some((sequence, index + 1));
+                    }
+                    // Malformed: drop what was read and resume at this byte
+                    _ => return Some((Escape::Other, index)),
+                }
+            }
+            None
+        }
+        introducer @ (b']' | b'P' | b'X' | b'^' | b'_') => {
+            let body_start = 2;
+            let mut index = body_start;
+            while index < bytes.len() {
+                let terminator = match bytes[index] {
+                    byte if byte == BEL as u8 => 1,
+                    byte if byte == ESC as u8 => match bytes.get(index + 1) {
+                        Some(b'\\') => 2,
+                        Some(_) => return Some((Escape::Other, index)),
+                        None => return None,
+                    },
+                    _ => 0,
+                };
+                if terminator > 0 {
+                    let sequence = if introducer == b']' {
+                        Escape::Osc(&input[body_start..index])
+                    } else {
+                        Escape::Other
+                    };
+                    return Some((sequence, index + terminator));
+                }
+                index += 1;
+            }
+            None
+        }
+        b'(' | b')' | b'*' | b'+' => {
+            let designator = input[2..].chars().next()?;
+            Some((Escape::Other, 2 + designator.len_utf8()))
+        }
+        _ => {
+            let next = input[1..].chars().next()?;
+            Some((Escape::Other, 1 + next.len_utf8()))
+        }
+    }
+}
+
+/// `text` without the escape sequences it contains.
+fn visible_text(text: &str) -> String {
+    let mut visible = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(ch) = rest.chars().next() {
+        if ch == ESC {
+            if let Some((_, len)) = parse_escape(rest) {
+                rest = &rest[len..];
+                continue;
+            }
+            break;
+        }
+        visible.push(ch);
+        rest = &rest[ch.len_utf8()..];
+    }
+    visible
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn keeps_colors_and_collapses_redraws() {
+        let output = "\x1b[?25l\x1b[32mfetching\x1b[0m |\r\x1b[2K\x1b[32mfetching\x1b[0m /\r\x1b[2K\x1b[32mdone\x1b[0m\x1b[1A\x1b[3G\r\nnext\x08\x08xt line\x07\x1b]0;title\x07";
+        let sanitized = sanitize_ansi(output, HyperlinkMode::Footnote);
+        assert_eq!(sanitized.text, "\x1b[0m\x1b[32mdone\x1b[0m\nnext line");
+        assert!(sanitized.footnotes.is_empty());
+    }
+
+    #[test]
+    fn renders_links_as_footnotes_or_osc8() {
+        let output = "see \x1b]8;id=1;https://example.com/docs\x1b\\the docs\x1b]8;;\x1b\\ or \x1b]8;;https://example.com\x07https://example.com\x1b]8;;\x07";
+
+        let footnoted = sanitize_ansi(output, HyperlinkMode::Footnote);
+        assert_eq!(
+            footnoted.clone().with_footnotes(),
+            "see the docs[1] or https://example.com\n[1] https://example.com/docs"
+        );
+
+        let linked = sanitize_ansi(output, HyperlinkMode::Osc8);
+        assert!(linked.footnotes.is_empty());
+        assert!(
+            linked.text.starts_with(
+                "see \x1b]8;;https://example.com/docs\x1b\\the docs\x1b]8;;\x1b\\ or "
+            )
+        );
+    }
+
+    #[test]
+    fn holds_sequences_split_across_chunks() {
+        let mut sanitizer = AnsiSanitizer::new(HyperlinkMode::Footnote);
+        assert_eq!(sanitizer.sanitize("build \x1b[3").text, "build ");
+        assert_eq!(sanitizer.pending(), "\x1b[3");
+        assert_eq!(
+            sanitizer.sanitize("1mok\x1b]8;;https://a.dev").text,
+            "\x1b[31mok"
+        );
+        let closed = sanitizer.sanitize("\x1b\\link\x1b]8;;\x1b\\ 50%\r");
+        assert_eq!(closed.text, "link[1] 50%");
+        assert_eq!(closed.footnotes, vec!["[1] https://a.dev"]);
+        assert_eq!(sanitizer.sanitize("\n").text, "\n");
+    }
+
+    fn terminal_output() -> impl Strategy<Value = String> {
+        let piece = prop_oneof![
+            "[ -~é日]{0,6}",
+            Just("\x1b[".to_string()),
+            "\x1b\\[[0-9;?]{0,6}[A-Za-z]",
+            "\x1b\\][0-9;]{0,3}[ -~]{0,12}(\x07|\x1b\\\\)?",
+            Just("\x1b]8;;https://a.dev\x1b\\".to_string()),
+            Just("\x1b]8;;\x07".to_string()),
+            prop::sample::select(vec!["\r", "\n", "\r\n", "\x08", "\t", "\x07", "\x1b"])
+                .prop_map(str::to_string),
+        ];
+        prop::collection::vec(piece, 0..16).prop_map(|pieces| pieces.concat())
+    }
+
+    /// Every escape left in footnote mode output is an SGR sequence.
+    fn only_sgr_escapes(text: &str) -> bool {
+        text.split(ESC).skip(1).all(|rest| {
+            rest.strip_prefix('[').is_some_and(|params| {
+                params
+                    .find(|ch: char| !(ch.is_ascii_digit() || matches!(ch, ';' | ':')))
+                    .is_some_and(|end| params[end..].starts_with('m'))
+            })
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn sanitizing_never_panics_and_leaves_only_colors(
+            output in prop_oneof![terminal_output(), any::<String>()],
+            splits in prop::collection::vec(any::<prop::sample::Index>(), 0..4),
+        ) {
+            let whole = sanitize_ansi(&output, HyperlinkMode::Footnote);
+            prop_assert!(whole.text.chars().all(|ch| !ch.is_control() || matches!(ch, '\n' | '\t' | ESC)));
+            prop_assert!(only_sgr_escapes(&whole.text));
+            let _ = sanitize_ansi(&output, HyperlinkMode::Osc8);
+
+            let boundaries: Vec<usize> = output.char_indices().map(|(at, _)| at).collect();
+            let mut cuts: Vec<usize> = splits
+                .iter()
+                .filter(|_| !boundaries.is_empty())
+                .map(|index| boundaries[index.index(boundaries.len())])
+                .collect();
+            cuts.sort_unstable();
+            cuts.dedup();
+            let mut sanitizer = AnsiSanitizer::new(HyperlinkMode::Footnote);
+            let mut start = 0;
+            for cut in cuts.into_iter().chain([output.len()]) {
+                let chunk = sanitizer.sanitize(&output[start..cut]);
+                prop_assert!(only_sgr_escapes(&chunk.text));
+                start = cut;
+            }
+            sanitizer.finish();
+        }
+
+        #[test]
+        fn plain_text_is_unchanged(text in "[ -~é日\t\n]*") {
+            prop_assert_eq!(sanitize_ansi(&text, HyperlinkMode::Footnote).text, text);
+        }
+    }
+}
@@ -0,0 +1,243 @@
+//! Bug-report bundles
+//!
+//! `vtcode bug-report` collects what a maintainer needs to reproduce a
+//! problem into one zip file that can be attached to a GitHub issue. The
+//! command gathers and redacts the contents; this module holds the bundle
+//! itself, trims a session to its last turns and writes the archive. Entries
+//! are stored with deflate compression, which every unzip tool reads.
+
+use crate::llm::provider::MessageRole;
+use crate::utils::session_archive::SessionSnapshot;
+use anyhow::{Context, Result};
+use chrono::{Datelike, Local, Timelike};
+use flate2::Compression;
+use flate2::write::DeflateEncoder;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+const LOCAL_HEADER_SIGNATURE: u32 = 0x0403_4b50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x0201_4b50;
+const END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x0605_4b50;
+/// Version 2.0: deflate
+const ZIP_VERSION: u16 = 20;
+/// Bit 11: names are UTF-8
+const UTF8_FLAG: u16 = 1 << 11;
+const DEFLATE_METHOD: u16 = 8;
+
+/// Files that make up a bug report, in the order they were added.
+#[derive(Debug, Clone, Default)]
+pub struct BugReportBundle {
+    entries: Vec<(String, Vec<u8>)>,
+}
+
+impl BugReportBundle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a file; `name` may contain `/` for folders inside the archive.
+    pub fn add(&mut self, name: impl Into<String>, contents: impl Into<Vec<u8>>) {
+        self.entries.push((name.into(), contents.into()));
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|(name, _)| name.as_str())
+    }
+
+    pub fn write_zip(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        let bytes = self.to_zip()?;
+        fs::write(path, bytes).with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    fn to_zip(&self) -> Result<Vec<u8>> {
+        let (time, date) = dos_timestamp();
+        let mut out = Vec::new();
+        let mut central = Vec::new();
+        for (name, contents) in &self.entries {
+            let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(contents)?;
+            let compressed = encoder.finish()?;
+            let mut crc = flate2::Crc::new();
+            crc.update(contents);
+            let offset = out.len() as u32;
+
+            put_u32(&mut out, LOCAL_HEADER_SIGNATURE);
+            put_u16(&mut out, ZIP_VERSION);
+            put_common(&mut out, time, date, crc.sum(), &compressed, contents, name);
+            put_u16(&mut out, 0); // extra field length
+            out.extend_from_slice(name.as_bytes());
+            out.extend_from_slice(&compressed);
+
+            put_u32(&mut central, CENTRAL_HEADER_SIGNATURE);
+            put_u16(&mut central, ZIP_VERSION); // made by
+            put_u16(&mut central, ZIP_VERSION); // needed to extract
+            put_common(
+                &mut central,
+                time,
+                date,
+                crc.sum(),
+                &compressed,
+                contents,
+                name,
+            );
+            put_u16(&mut central, 0); // extra field length
+            put_u16(&mut central, 0); // comment length
+            put_u16(&mut central, 0); // disk number
+            put_u16(&mut central, 0); // internal attributes
+            put_u32(&mut central, 0); // external attributes
+            put_u32(&mut central, offset);
+            central.extend_from_slice(name.as_bytes());
+        }
+
+        let central_offset = out.len() as u32;
+        let count = self.entries.len() as u16;
+        out.extend_from_slice(&central);
+        put_u32(&mut out, END_OF_CENTRAL_DIRECTORY_SIGNATURE);
+        put_u16(&mut out, 0); // this disk
+        put_u16(&mut out, 0); // disk with the central directory
+        put_u16(&mut out, count);
+        put_u16(&mut out, count);
+        put_u32(&mut out, central.len() as u32);
+        put_u32(&mut out, central_offset);
+        put_u16(&mut out, 0); // comment length
+        Ok(out)
+    }
+}
+
+/// A copy of `snapshot` holding only its last `turns` turns, each starting at
+/// a user message. The rendered transcript cannot be split by turn, so it is
+/// left out; the messages carry the same content.
+pub fn last_turns(snapshot: &SessionSnapshot, turns: usize) -> SessionSnapshot {
+    let starts: Vec<usize> = snapshot
+        .messages
+        .iter()
+        .enumerate()
+        .filter(|(_, message)| message.role == MessageRole::User)
+        .map(|(index, _)| index)
+        .collect();
+    let from = match turns {
+        0 => snapshot.messages.len(),
+        _ if turns >= starts.len() => 0,
+        _ => starts[starts.len() - turns],
+    };
+    let mut trimmed = snapshot.clone();
+    trimmed.messages = snapshot.messages[from..].to_vec();
+    trimmed.transcript.clear();
+    trimmed
+}
+
+/// The last `lines` lines of `text`.
+pub fn tail_lines(text: &str, lines: usize) -> String {
+    let all: Vec<&str> = text.lines().collect();
+    let start = all.len().saturating_sub(lines);
+    let mut tail = all[start..].join("\n");
+    if !tail.is_empty() {
+        tail.push('\n');
+    }
+    tail
+}
+
+/// Fields shared by the local and central headers, up to the name length.
+fn put_common(
+    out: &mut Vec<u8>,
+    time: u16,
+    date: u16,
+    crc: u32,
+    compressed: &[u8],
+    contents: &[u8],
+    name: &str,
+) {
+    put_u16(out, UTF8_FLAG);
+    put_u16(out, DEFLATE_METHOD);
+    put_u16(out, time);
+    put_u16(out, date);
+    put_u32(out, crc);
+    put_u32(out, compressed.len() as u32);
+    put_u32(out, contents.len() as u32);
+    put_u16(out, name.len() as u16);
+}
+
+fn put_u16(out: &mut Vec<u8>, value: u16) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+/// Local time in MS-DOS format, which zip entries use.
+fn dos_timestamp() -> (u16, u16) {
+    let now = Local::now();
+    let time = ((now.hour() << 11) | (now.minute() << 5) | (now.second() / 2)) as u16;
+    let year = (now.year() - 1980).clamp(0, 127) as u32;
+    let date = ((year << 9) | (now.month() << 5) | now.day()) as u16;
+    (time, date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::session_archive::{SessionArchiveMetadata, SessionMessage};
+    use chrono::Utc;
+    use flate2::read::DeflateDecoder;
+    use std::io::Read;
+
+    #[test]
+    fn keeps_the_last_turns_and_writes_a_readable_zip() {
+        let messages = [
+            (MessageRole::User, "first"),
+            (MessageRole::Assistant, "one"),
+            (MessageRole::User, "second"),
+            (MessageRole::Assistant, "two"),
+            (MessageRole::Tool, "output"),
+        ]
+        .map(|(role, content)| SessionMessage::new(role, content))
+        .to_vec();
+        let snapshot = SessionSnapshot {
+            metadata: SessionArchiveMetadata::new("app", "/srv/app", "m", "p", "t", "low"),
+            started_at: Utc::now(),
+            ended_at: Utc::now(),
+            total_messages: messages.len(),
+            distinct_tools: Vec::new(),
+            transcript: vec!["line".to_string()],
+            messages,
+            title: None,
+            reproducibility: None,
+        };
+        let trimmed = last_turns(&snapshot, 1);
+        assert_eq!(trimmed.messages.len(), 3);
+        assert_eq!(trimmed.messages[0].content, "second");
+        assert!(trimmed.transcript.is_empty());
+        assert_eq!(last_turns(&snapshot, 5).messages.len(), 5);
+        assert_eq!(tail_lines("a\nb\nc\n", 2), "b\nc\n");
+
+        let mut bundle = BugReportBundle::new();
+        bundle.add("environment.json", "{\"os\":\"linux\"}");
+        bundle.add("logs/trajectory.jsonl", "x".repeat(1000));
+        let zip = bundle.to_zip().unwrap();
+        assert_eq!(&zip[..4], &LOCAL_HEADER_SIGNATURE.to_le_bytes());
+        let end = zip.len() - 22;
+        assert_eq!(
+            &zip[end..end + 4],
+            &END_OF_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes()
+        );
+        assert_eq!(u16::from_le_bytes([zip[end + 10], zip[end + 11]]), 2);
+
+        // First entry: 30-byte header, then the name, then the deflated data.
+        let compressed_len = u32::from_le_bytes(zip[18..22].try_into().unwrap()) as usize;
+        let start = 30 + "environment.json".len();
+        let mut contents = String::new();
+        DeflateDecoder::new(&zip[start..start + compressed_len])
+            .read_to_string(&mut contents)
+            .unwrap();
+        assert_eq!(contents, "{\"os\":\"linux\"}");
+    }
+}
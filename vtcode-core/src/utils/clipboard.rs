@@ -0,0 +1,210 @@
+//! System clipboard access for the chat interface
+//!
+//! Copies go through the platform clipboard tool (`pbcopy`, `wl-copy`,
+//! `xclip`, `xsel` or `clip.exe`). Over SSH that tool would write to the
+//! remote machine's clipboard, so remote sessions, and machines without a
+//! clipboard tool, fall back to an OSC 52 escape sequence that asks the local
+//! terminal emulator to set its clipboard instead. Reading the clipboard needs
+//! a system tool; OSC 52 reads are disabled by most terminals.
+
+use anyhow::{Context, Result, anyhow};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use std::env;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// How a copy reached the clipboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyMethod {
+    /// A platform clipboard tool, named by its program
+    System(&'static str),
+    /// The terminal has to apply an OSC 52 sequence
+    Osc52,
+}
+
+/// Clipboard tools tried in order as `(program, args)`.
+fn copy_commands() -> Vec<(&'static str, &'static [&'static str])> {
+    if cfg!(target_os = "macos") {
+        return vec![("pbcopy", &[])];
+    }
+    if cfg!(windows) {
+        return vec![("clip.exe", &[])];
+    }
+    let mut commands: Vec<(&'static str, &'static [&'static str])> = Vec::new();
+    if env::var_os("WAYLAND_DISPLAY").is_some() {
+        commands.push(("wl-copy", &[]));
+    }
+    commands.push(("xclip", &["-selection", "clipboard"]));
+    commands.push(("xsel", &["--clipboard", "--input"]));
+    // WSL exposes the Windows clipboard through clip.exe.
+    commands.push(("clip.exe", &[]));
+    commands
+}
+
+fn paste_commands() -> Vec<(&'static str, &'static [&'static str])> {
+    if cfg!(target_os = "macos") {
+        return vec![("pbpaste", &[])];
+    }
+    if cfg!(windows) {
+        return vec![(
+            "powershell.exe",
+            &["-NoProfile", "-Command", "Get-Clipboard"],
+        )];
+    }
+    let mut commands: Vec<(&'static str, &'static [&'static str])> = Vec::new();
+    if env::var_os("WAYLAND_DISPLAY").is_some() {
+        commands.push(("wl-paste", &["--no-newline"]));
+    }
+    commands.push(("xclip", &["-selection", "clipboard", "-o"]));
+    commands.push(("xsel", &["--clipboard", "--output"]));
+    commands.push((
+        "powershell.exe",
+        &["-NoProfile", "-Command", "Get-Clipboard"],
+    ));
+    commands
+}
+
+/// Whether the session runs over SSH, where the local clipboard is only
+/// reachable through the terminal.
+pub fn is_remote_session() -> bool {
+    ["SSH_CONNECTION", "SSH_CLIENT", "SSH_TTY"]
+        .iter()
+        .any(|key| env::var_os(key).is_some())
+}
+
+/// Copy `text` with a clipboard tool, or report that the caller has to emit
+/// [`osc52_sequence`] to the terminal.
+pub fn copy(text: &str) -> CopyMethod {
+    if is_remote_session() {
+        return CopyMethod::Osc52;
+    }
+    copy_commands()
+        .into_iter()
+        .find(|(program, args)| copy_with(program, args, text).is_ok())
+        .map(|(program, _)| CopyMethod::System(program))
+        .unwrap_or(CopyMethod::Osc52)
+}
+
+fn copy_with(program: &str, args: &[&str], text: &str) -> Result<()> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("failed to start {program}"))?;
+    child
+        .stdin
+        .take()
+        .context("clipboard tool has no stdin")?
+        .write_all(text.as_bytes())
+        .with_context(|| format!("failed to write to {program}"))?;
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(anyhow!("{program} exited with {status}"));
+    }
+    Ok(())
+}
+
+/// Read the system clipboard as text.
+pub fn paste() -> Result<String> {
+    for (program, args) in paste_commands() {
+        let Ok(output) = Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+        else {
+            continue;
+        };
+        if output.status.success() {
+            return Ok(String::from_utf8_lossy(&output.stdout).replace("\r\n", "\n"));
+        }
+    }
+    Err(anyhow!(
+        "no clipboard tool found (install wl-clipboard, xclip or xsel)"
+    ))
+}
+
+/// OSC 52 sequence that sets the terminal clipboard to `text`.
+///
+/// Inside tmux the sequence is wrapped in a DCS passthrough so it reaches the
+/// outer terminal.
+pub fn osc52_sequence(text: &str) -> String {
+    let sequence = format!("\x1b]52;c;{}\x07", STANDARD.encode(text));
+    if env::var_os("TMUX").is_some() {
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence
+    }
+}
+
+/// Contents of the last fenced code block in `text`.
+pub fn last_code_block(text: &str) -> Option<String> {
+    let mut last = None;
+    let mut current: Option<(String, Vec<&str>)> = None;
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        match current.as_mut() {
+            Some((fence, body)) => {
+                if trimmed.trim_end() == fence.as_str() {
+                    let (_, body) = current.take().expect("open block");
+                    last = Some(body.join("\n"));
+                } else {
+                    body.push(line);
+                }
+            }
+            None => {
+                let ticks = trimmed.chars().take_while(|ch| *ch == '`').count();
+                if ticks >= 3 {
+                    current = Some(("`".repeat(ticks), Vec::new()));
+                }
+            }
+        }
+    }
+    last
+}
+
+/// Wrap `text` in a fenced block whose fence is longer than any backtick run
+/// inside it.
+pub fn fenced_block(text: &str) -> String {
+    let mut longest = 0;
+    let mut run = 0;
+    for ch in text.chars() {
+        if ch == '`' {
+            run += 1;
+            longest = longest.max(run);
+        } else {
+            run = 0;
+        }
+    }
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{fence}\n{}\n{fence}\n", text.trim_end_matches('\n'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_last_code_block_and_fences_pastes() {
+        let reply = "Try this:\n```rust\nfn a() {}\n```\nthen:\n````sh\ncargo test\n```\n````\n";
+        assert_eq!(last_code_block(reply).as_deref(), Some("cargo test\n```"));
+        assert_eq!(last_code_block("no code here"), None);
+
+        assert_eq!(fenced_block("let x = 1;\n"), "```\nlet x = 1;\n```\n");
+        assert_eq!(
+            fenced_block("```\ninner\n```"),
+            "````\n```\ninner\n```\n````\n"
+        );
+    }
+
+    #[test]
+    fn osc52_encodes_text_as_base64() {
+        if env::var_os("TMUX").is_some() {
+            return;
+        }
+        assert_eq!(osc52_sequence("hi"), "\x1b]52;c;aGk=\x07");
+    }
+}
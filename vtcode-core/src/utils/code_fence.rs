@@ -0,0 +1,36 @@
+//! Unwrapping model output from a Markdown code fence
+//!
+//! Models asked for bare JSON or file content often wrap it in a fence
+//! anyway. [`strip_code_fence`] returns what is inside so callers can parse
+//! either form.
+
+/// The body of the code fence wrapping all of `text`, or `text` unchanged
+/// when it is not fenced. The info string of the opening fence, such as
+/// `json`, is dropped; the body keeps its trailing newline.
+pub fn strip_code_fence(text: &str) -> &str {
+    let trimmed = text.trim();
+    let Some(rest) = trimmed.strip_prefix("```") else {
+        return text;
+    };
+    let body = match rest.split_once('\n') {
+        Some((_, body)) => body,
+        None => rest.strip_prefix("json").unwrap_or(rest),
+    };
+    body.strip_suffix("```").unwrap_or(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unwraps_fenced_and_passes_bare_text_through() {
+        assert_eq!(strip_code_fence("```json\n{\"a\": 1}\n```"), "{\"a\": 1}\n");
+        assert_eq!(
+            strip_code_fence("  ```\nfn main() {}\n```\n"),
+            "fn main() {}\n"
+        );
+        assert_eq!(strip_code_fence("```json{\"a\": 1}```"), "{\"a\": 1}");
+        assert_eq!(strip_code_fence("{\"a\": 1}\n"), "{\"a\": 1}\n");
+    }
+}
@@ -31,19 +31,41 @@ pub struct UserPreferences {
     pub keybindings: HashMap<String, String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ProviderConfigs {
     pub openai: Option<ProviderConfig>,
     pub anthropic: Option<ProviderConfig>,
     pub gemini: Option<ProviderConfig>,
     pub openrouter: Option<ProviderConfig>,
     pub xai: Option<ProviderConfig>,
+    #[serde(default)]
+    pub ollama: Option<ProviderConfig>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct WorkspaceTrustStore {
     #[serde(default)]
     pub entries: HashMap<String, WorkspaceTrustRecord>,
+    /// Paths outside each workspace the user allowed the file tools to write
+    #[serde(default)]
+    pub write_grants: HashMap<String, Vec<WriteGrantRecord>>,
+}
+
+/// Write access to a path outside the workspace, approved at a prompt.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WriteGrantRecord {
+    /// Absolute path of the file, or of a directory whose files are covered
+    pub path: String,
+    pub granted_at: u64,
+    /// Unix time the grant lapses; `None` keeps it until revoked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+}
+
+impl WriteGrantRecord {
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -117,18 +139,6 @@ impl Default for UserPreferences {
     }
 }
 
-impl Default for ProviderConfigs {
-    fn default() -> Self {
-        Self {
-            openai: None,
-            anthropic: None,
-            gemini: None,
-            openrouter: None,
-            xai: None,
-        }
-    }
-}
-
 impl Default for WorkspaceTrustLevel {
     fn default() -> Self {
         Self::ToolsPolicy
@@ -179,15 +189,26 @@ impl DotManager {
     pub fn new() -> Result<Self, DotError> {
         let home_dir = dirs::home_dir().ok_or_else(|| DotError::HomeDirNotFound)?;
 
-        let config_dir = home_dir.join(".vtcode");
-        let cache_dir = config_dir.join("cache");
-        let config_file = config_dir.join("config.toml");
+        Ok(Self::with_root(home_dir.join(".vtcode")))
+    }
 
-        Ok(Self {
+    /// Manage the dot folder at `config_dir` instead of `~/.vtcode`
+    pub fn with_root(config_dir: PathBuf) -> Self {
+        Self {
+            cache_dir: config_dir.join("cache"),
+            config_file: config_dir.join("config.toml"),
             config_dir,
-            cache_dir,
-            config_file,
-        })
+        }
+    }
+
+    /// Get the dot folder itself
+    pub fn config_dir(&self) -> &Path {
+        &self.config_dir
+    }
+
+    /// Get the user configuration file
+    pub fn config_file(&self) -> &Path {
+        &self.config_file
     }
 
     /// Initialize the dot folder structure
@@ -268,6 +289,11 @@ impl DotManager {
         self.config_dir.join("sessions")
     }
 
+    /// Get the directory holding daemon sockets
+    pub fn daemon_dir(&self) -> PathBuf {
+        self.config_dir.join("daemon")
+    }
+
     /// Get backups directory
     pub fn backups_dir(&self) -> PathBuf {
         self.config_dir.join("backups")
@@ -507,6 +533,32 @@ pub fn update_theme_preference(theme: &str) -> Result<(), DotError> {
     manager.update_config(|cfg| cfg.preferences.theme = theme.to_string())
 }
 
+/// Short, stable file name stem for per-workspace state under the dot folder:
+/// the workspace directory name followed by a hash of its canonical path.
+pub fn workspace_key(workspace: &Path) -> String {
+    use sha2::{Digest, Sha256};
+
+    let workspace = workspace
+        .canonicalize()
+        .unwrap_or_else(|_| workspace.to_path_buf());
+    let digest = Sha256::digest(workspace.to_string_lossy().as_bytes());
+    let hash: String = digest
+        .iter()
+        .take(8)
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    // Unix socket paths are limited to about 100 bytes, so keep names short.
+    let label: String = workspace
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+        .chars()
+        .filter(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_'))
+        .take(24)
+        .collect();
+    format!("{label}-{hash}")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,628 @@
+//! Validation, repair and retention cleanup for the dot folder
+//!
+//! Months of use leave `~/.vtcode/` with truncated caches, redacted copies of
+//! deleted sessions and archives nobody reopens, and one unreadable store is
+//! enough to break startup. [`DotMaintenance`] checks each store in turn:
+//!
+//! - **config**: `config.toml`, including the workspace trust store. An
+//!   unreadable file is backed up and rebuilt from the sections that still
+//!   parse, so trust decisions survive.
+//! - **tool policy**: `tool-policy.json` in the dot folder and the workspace.
+//!   An unreadable file is renamed to `.json.bak` and reset to defaults.
+//! - **caches**: everything under `cache/`. Caches are rebuilt on demand, so
+//!   empty files and JSON that no longer parses are deleted.
+//! - **sessions**: archived sessions that no longer parse move to
+//!   `sessions/corrupt/`, and redacted copies whose session is gone are
+//!   deleted.
+//! - **daemon**: sockets left behind by daemons that are no longer running.
+//!
+//! With [`DotMaintenance::prune_older_than`], sessions, cache entries, logs,
+//! backups and trust entries for deleted workspaces older than the cutoff are
+//! removed as well. Every change is listed in the [`MaintenanceReport`].
+
+use crate::tool_policy::{AlternativeToolPolicyConfig, ToolPolicyConfig};
+use crate::utils::dot_config::{DotConfig, DotManager};
+use crate::utils::session_archive::SessionSnapshot;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const TOOL_POLICY_FILE: &str = "tool-policy.json";
+/// Subdirectory of the sessions directory holding archives that did not parse.
+const CORRUPT_SESSIONS_DIR: &str = "corrupt";
+const REDACTED_SESSIONS_DIR: &str = "redacted";
+/// Cache subdirectories created by [`DotManager::initialize`], kept even when empty.
+const STANDARD_CACHE_DIRS: &[&str] = &["prompts", "context", "models"];
+
+/// What maintenance found and changed in one store.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StoreReport {
+    pub store: &'static str,
+    /// Files or entries examined
+    pub checked: usize,
+    /// One line per repair, such as a reset file or a quarantined session
+    pub repairs: Vec<String>,
+    /// Entries removed by the retention cutoff
+    pub pruned: usize,
+    /// Bytes freed by repairs and pruning
+    pub bytes_freed: u64,
+}
+
+impl StoreReport {
+    fn new(store: &'static str) -> Self {
+        Self {
+            store,
+            ..Self::default()
+        }
+    }
+
+    fn remove(&mut self, path: &Path) -> Result<u64> {
+        let size = path_size(path);
+        if path.is_dir() {
+            fs::remove_dir_all(path)
+        } else {
+            fs::remove_file(path)
+        }
+        .with_context(|| format!("failed to remove {}", path.display()))?;
+        self.bytes_freed += size;
+        Ok(size)
+    }
+
+    fn prune(&mut self, path: &Path) -> Result<()> {
+        self.remove(path)?;
+        self.pruned += 1;
+        Ok(())
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.repairs.is_empty() && self.pruned == 0
+    }
+}
+
+/// Result of a maintenance run, one entry per store.
+#[derive(Debug, Clone, Default)]
+pub struct MaintenanceReport {
+    pub stores: Vec<StoreReport>,
+}
+
+impl MaintenanceReport {
+    pub fn repairs(&self) -> usize {
+        self.stores.iter().map(|store| store.repairs.len()).sum()
+    }
+
+    pub fn pruned(&self) -> usize {
+        self.stores.iter().map(|store| store.pruned).sum()
+    }
+
+    pub fn bytes_freed(&self) -> u64 {
+        self.stores.iter().map(|store| store.bytes_freed).sum()
+    }
+}
+
+/// Checks and repairs the stores of one dot folder.
+pub struct DotMaintenance {
+    manager: DotManager,
+    sessions_dir: PathBuf,
+    workspace: Option<PathBuf>,
+    prune_before: Option<SystemTime>,
+}
+
+impl DotMaintenance {
+    pub fn new(manager: DotManager) -> Self {
+        Self {
+            sessions_dir: manager.sessions_dir(),
+            manager,
+            workspace: None,
+            prune_before: None,
+        }
+    }
+
+    /// Check sessions in `dir` instead of the dot folder's `sessions/`.
+    pub fn with_sessions_dir(mut self, dir: PathBuf) -> Self {
+        self.sessions_dir = dir;
+        self
+    }
+
+    /// Also check the stores in `workspace/.vtcode/`.
+    pub fn with_workspace(mut self, workspace: PathBuf) -> Self {
+        self.workspace = Some(workspace);
+        self
+    }
+
+    /// Remove sessions, cache entries, logs and backups older than `age`.
+    pub fn prune_older_than(mut self, age: Duration) -> Self {
+        self.prune_before = SystemTime::now().checked_sub(age);
+        self
+    }
+
+    pub fn run(&self) -> Result<MaintenanceReport> {
+        Ok(MaintenanceReport {
+            stores: vec![
+                self.check_config()?,
+                self.check_tool_policies()?,
+                self.check_caches()?,
+                self.check_sessions()?,
+                self.check_daemon_sockets()?,
+                self.prune_directory("logs", &self.manager.logs_dir())?,
+                self.prune_directory("backups", &self.manager.backups_dir())?,
+            ],
+        })
+    }
+
+    fn is_expired(&self, path: &Path) -> bool {
+        let modified = fs::symlink_metadata(path).and_then(|meta| meta.modified());
+        match (self.prune_before, modified) {
+            (Some(cutoff), Ok(modified)) => modified < cutoff,
+            _ => false,
+        }
+    }
+
+    fn check_config(&self) -> Result<StoreReport> {
+        let mut report = StoreReport::new("config");
+        let path = self.manager.config_file();
+        if !path.exists() {
+            return Ok(report);
+        }
+        report.checked += 1;
+        let raw = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let mut config = match toml::from_str::<DotConfig>(&raw) {
+            Ok(config) => config,
+            Err(err) => {
+                let backup = self.manager.backups_dir().join(format!(
+                    "config_corrupt_{}.toml",
+                    Utc::now().format("%Y%m%dT%H%M%SZ")
+                ));
+                move_aside(path, &backup)?;
+                let (config, kept) = salvage_config(&raw);
+                report.repairs.push(format!(
+                    "config.toml did not parse ({}); backed up to {} and rebuilt keeping {}",
+                    first_line(&err.to_string()),
+                    backup.display(),
+                    if kept.is_empty() {
+                        "nothing".to_string()
+                    } else {
+                        kept.join(", ")
+                    }
+                ));
+                self.manager.save_config(&config)?;
+                config
+            }
+        };
+
+        let entries = &mut config.workspace_trust.entries;
+        report.checked += entries.len();
+        if let Some(cutoff) = self.prune_before {
+            let cutoff = cutoff
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            let before = entries.len();
+            entries.retain(|workspace, record| {
+                record.trusted_at >= cutoff || Path::new(workspace).exists()
+            });
+            let removed = before - entries.len();
+            if removed > 0 {
+                report.pruned += removed;
+                self.manager.save_config(&config)?;
+            }
+        }
+        Ok(report)
+    }
+
+    fn check_tool_policies(&self) -> Result<StoreReport> {
+        let mut report = StoreReport::new("tool policy");
+        let mut paths = vec![self.manager.config_dir().join(TOOL_POLICY_FILE)];
+        if let Some(workspace) = &self.workspace {
+            paths.push(workspace.join(".vtcode").join(TOOL_POLICY_FILE));
+        }
+        for path in paths.into_iter().filter(|path| path.exists()) {
+            report.checked += 1;
+            let raw = fs::read_to_string(&path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            if serde_json::from_str::<AlternativeToolPolicyConfig>(&raw).is_ok() {
+                continue;
+            }
+            let Err(err) = serde_json::from_str::<ToolPolicyConfig>(&raw) else {
+                continue;
+            };
+            let backup = path.with_extension("json.bak");
+            move_aside(&path, &backup)?;
+            let defaults = serde_json::to_string_pretty(&ToolPolicyConfig::default())?;
+            fs::write(&path, defaults)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            report.repairs.push(format!(
+                "{} did not parse ({}); backed up to {} and reset to defaults",
+                path.display(),
+                err,
+                backup.display()
+            ));
+        }
+        Ok(report)
+    }
+
+    fn check_caches(&self) -> Result<StoreReport> {
+        let mut report = StoreReport::new("caches");
+        let root = self.manager.cache_dir("");
+        for path in files_under(&root)? {
+            report.checked += 1;
+            let relative = path.strip_prefix(&root).unwrap_or(&path).display();
+            if self.is_expired(&path) {
+                report.prune(&path)?;
+            } else if let Some(problem) = cache_entry_problem(&path) {
+                let size = report.remove(&path)?;
+                report
+                    .repairs
+                    .push(format!("removed {relative} ({problem}, {size} bytes)"));
+            }
+        }
+        for dir in dirs_under(&root)?.into_iter().rev() {
+            let standard = dir
+                .strip_prefix(&root)
+                .ok()
+                .and_then(|relative| relative.to_str())
+                .is_some_and(|relative| STANDARD_CACHE_DIRS.contains(&relative));
+            if !standard && fs::read_dir(&dir).is_ok_and(|mut entries| entries.next().is_none()) {
+                let _ = fs::remove_dir(&dir);
+            }
+        }
+        Ok(report)
+    }
+
+    fn check_sessions(&self) -> Result<StoreReport> {
+        let mut report = StoreReport::new("sessions");
+        let dir = &self.sessions_dir;
+        let corrupt_dir = dir.join(CORRUPT_SESSIONS_DIR);
+        let cutoff = self.prune_before.map(DateTime::<Utc>::from);
+        for path in json_files_in(dir)? {
+            report.checked += 1;
+            let snapshot = fs::read_to_string(&path)
+                .ok()
+                .and_then(|raw| serde_json::from_str::<SessionSnapshot>(&raw).ok());
+            match snapshot {
+                Some(snapshot) => {
+                    if cutoff.is_some_and(|cutoff| snapshot.ended_at < cutoff) {
+                        report.prune(&path)?;
+                    }
+                }
+                None => {
+                    let name = path.file_name().unwrap_or_default();
+                    move_aside(&path, &corrupt_dir.join(name))?;
+                    report.repairs.push(format!(
+                        "moved unreadable session {} to {}",
+                        name.to_string_lossy(),
+                        corrupt_dir.display()
+                    ));
+                }
+            }
+        }
+        for path in json_files_in(&corrupt_dir)? {
+            if self.is_expired(&path) {
+                report.prune(&path)?;
+            }
+        }
+
+        for path in json_files_in(&dir.join(REDACTED_SESSIONS_DIR))? {
+            report.checked += 1;
+            let name = path.file_name().unwrap_or_default();
+            if dir.join(name).exists() {
+                continue;
+            }
+            if self.is_expired(&path) {
+                report.prune(&path)?;
+            } else {
+                report.remove(&path)?;
+                report.repairs.push(format!(
+                    "removed redacted copy {} of a deleted session",
+                    name.to_string_lossy()
+                ));
+            }
+        }
+        Ok(report)
+    }
+
+    fn check_daemon_sockets(&self) -> Result<StoreReport> {
+        let mut report = StoreReport::new("daemon");
+        let dir = self.manager.daemon_dir();
+        if !dir.exists() {
+            return Ok(report);
+        }
+        for entry in
+            fs::read_dir(&dir).with_context(|| format!("failed to read {}", dir.display()))?
+        {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("sock") {
+                continue;
+            }
+            report.checked += 1;
+            if !socket_is_live(&path) {
+                report.remove(&path)?;
+                report.repairs.push(format!(
+                    "removed stale daemon socket {}",
+                    path.file_name().unwrap_or_default().to_string_lossy()
+                ));
+            }
+        }
+        Ok(report)
+    }
+
+    /// Retention cleanup for directories that need no validation.
+    fn prune_directory(&self, store: &'static str, dir: &Path) -> Result<StoreReport> {
+        let mut report = StoreReport::new(store);
+        if !dir.exists() {
+            return Ok(report);
+        }
+        for entry in
+            fs::read_dir(dir).with_context(|| format!("failed to read {}", dir.display()))?
+        {
+            let path = entry?.path();
+            report.checked += 1;
+            if self.is_expired(&path) {
+                report.prune(&path)?;
+            }
+        }
+        Ok(report)
+    }
+}
+
+/// `config.toml` rebuilt from the sections of `raw` that still deserialize,
+/// with the names of the sections that were kept.
+fn salvage_config(raw: &str) -> (DotConfig, Vec<&'static str>) {
+    fn section<T: DeserializeOwned>(table: &toml::Table, name: &str) -> Option<T> {
+        table.get(name)?.clone().try_into().ok()
+    }
+
+    let mut config = DotConfig::default();
+    let mut kept = Vec::new();
+    let Ok(table) = raw.parse::<toml::Table>() else {
+        return (config, kept);
+    };
+    if let Some(preferences) = section(&table, "preferences") {
+        config.preferences = preferences;
+        kept.push("preferences");
+    }
+    if let Some(providers) = section(&table, "providers") {
+        config.providers = providers;
+        kept.push("providers");
+    }
+    if let Some(cache) = section(&table, "cache") {
+        config.cache = cache;
+        kept.push("cache");
+    }
+    if let Some(ui) = section(&table, "ui") {
+        config.ui = ui;
+        kept.push("ui");
+    }
+    if let Some(workspace_trust) = section(&table, "workspace_trust") {
+        config.workspace_trust = workspace_trust;
+        kept.push("workspace_trust");
+    }
+    (config, kept)
+}
+
+/// Why a cache entry should be dropped, if it should.
+fn cache_entry_problem(path: &Path) -> Option<&'static str> {
+    let bytes = fs::read(path).ok()?;
+    if bytes.is_empty() {
+        return Some("empty");
+    }
+    let is_json = path.extension().and_then(|ext| ext.to_str()) == Some("json");
+    if is_json && serde_json::from_slice::<serde_json::Value>(&bytes).is_err() {
+        return Some("invalid JSON");
+    }
+    None
+}
+
+fn move_aside(path: &Path, destination: &Path) -> Result<()> {
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    fs::rename(path, destination).with_context(|| {
+        format!(
+            "failed to move {} to {}",
+            path.display(),
+            destination.display()
+        )
+    })
+}
+
+fn json_files_in(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| format!("failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().and_then(|ext| ext.to_str()) == Some("json") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn files_under(root: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    walk(root, &mut |path, is_dir| {
+        if !is_dir {
+            files.push(path.to_path_buf());
+        }
+    })?;
+    Ok(files)
+}
+
+/// Directories below `root`, parents before children.
+fn dirs_under(root: &Path) -> Result<Vec<PathBuf>> {
+    let mut dirs = Vec::new();
+    walk(root, &mut |path, is_dir| {
+        if is_dir {
+            dirs.push(path.to_path_buf());
+        }
+    })?;
+    Ok(dirs)
+}
+
+fn walk(dir: &Path, visit: &mut dyn FnMut(&Path, bool)) -> Result<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(dir).with_context(|| format!("failed to read {}", dir.display()))? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            visit(&path, true);
+            walk(&path, visit)?;
+        } else {
+            visit(&path, false);
+        }
+    }
+    Ok(())
+}
+
+fn path_size(path: &Path) -> u64 {
+    let mut size = fs::symlink_metadata(path).map_or(0, |meta| meta.len());
+    if path.is_dir() {
+        let _ = walk(path, &mut |path, is_dir| {
+            if !is_dir {
+                size += fs::symlink_metadata(path).map_or(0, |meta| meta.len());
+            }
+        });
+    }
+    size
+}
+
+fn first_line(text: &str) -> &str {
+    text.lines().next().unwrap_or(text).trim()
+}
+
+#[cfg(unix)]
+fn socket_is_live(path: &Path) -> bool {
+    std::os::unix::net::UnixStream::connect(path).is_ok()
+}
+
+#[cfg(not(unix))]
+fn socket_is_live(_path: &Path) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::dot_config::{WorkspaceTrustLevel, WorkspaceTrustRecord};
+    use crate::utils::session_archive::SessionArchiveMetadata;
+
+    fn snapshot(ended_at: DateTime<Utc>) -> String {
+        serde_json::to_string(&SessionSnapshot {
+            metadata: SessionArchiveMetadata::new(
+                "app", "/tmp/app", "model", "provider", "dark", "low",
+            ),
+            started_at: ended_at,
+            ended_at,
+            total_messages: 0,
+            distinct_tools: Vec::new(),
+            transcript: Vec::new(),
+            messages: Vec::new(),
+            title: None,
+            reproducibility: None,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn repairs_corrupt_stores_and_keeps_trust() {
+        let temp = tempfile::tempdir().unwrap();
+        let manager = DotManager::with_root(temp.path().join(".vtcode"));
+        manager.initialize().unwrap();
+        let root = manager.config_dir().to_path_buf();
+
+        let mut config = DotConfig::default();
+        config.workspace_trust.entries.insert(
+            "/work/app".to_string(),
+            WorkspaceTrustRecord {
+                level: WorkspaceTrustLevel::FullAuto,
+                trusted_at: 1,
+            },
+        );
+        let raw = toml::to_string_pretty(&config).unwrap().replacen(
+            "show_timestamps = ",
+            "show_timestamps = \"sometimes\" # ",
+            1,
+        );
+        fs::write(manager.config_file(), raw).unwrap();
+        fs::write(root.join(TOOL_POLICY_FILE), "{\"version\": 1,").unwrap();
+        fs::write(root.join("cache/models/list.json"), "{\"trunc").unwrap();
+        fs::write(root.join("cache/models/empty.bin"), "").unwrap();
+        fs::write(root.join("cache/models/ok.json"), "{}").unwrap();
+        fs::write(root.join("sessions/session-a.json"), "not json").unwrap();
+        fs::write(root.join("sessions/session-b.json"), snapshot(Utc::now())).unwrap();
+        fs::create_dir_all(root.join("sessions/redacted")).unwrap();
+        fs::write(root.join("sessions/redacted/session-b.json"), "{}").unwrap();
+        fs::write(root.join("sessions/redacted/session-gone.json"), "{}").unwrap();
+
+        let report = DotMaintenance::new(DotManager::with_root(root.clone()))
+            .run()
+            .unwrap();
+        let store = |name: &str| report.stores.iter().find(|s| s.store == name).unwrap();
+        assert_eq!(store("config").repairs.len(), 1, "{report:?}");
+        assert!(store("config").repairs[0].contains("workspace_trust"));
+        assert_eq!(store("tool policy").repairs.len(), 1);
+        assert_eq!(store("caches").repairs.len(), 2);
+        assert_eq!(store("sessions").repairs.len(), 2);
+
+        let repaired = manager.load_config().unwrap();
+        assert!(repaired.workspace_trust.entries.contains_key("/work/app"));
+        assert!(root.join("tool-policy.json.bak").exists());
+        assert!(root.join("cache/models/ok.json").exists());
+        assert!(root.join("sessions/corrupt/session-a.json").exists());
+        assert!(root.join("sessions/redacted/session-b.json").exists());
+        assert!(!root.join("sessions/redacted/session-gone.json").exists());
+
+        let rerun = DotMaintenance::new(DotManager::with_root(root))
+            .run()
+            .unwrap();
+        assert_eq!(rerun.repairs(), 0, "{rerun:?}");
+    }
+
+    #[test]
+    fn prunes_entries_older_than_the_cutoff() {
+        let temp = tempfile::tempdir().unwrap();
+        let manager = DotManager::with_root(temp.path().join(".vtcode"));
+        manager.initialize().unwrap();
+        let root = manager.config_dir().to_path_buf();
+        let old = Utc::now() - chrono::Duration::days(60);
+        fs::write(root.join("sessions/session-old.json"), snapshot(old)).unwrap();
+        fs::write(root.join("sessions/session-new.json"), snapshot(Utc::now())).unwrap();
+        manager
+            .update_config(|config| {
+                config.workspace_trust.entries.insert(
+                    temp.path().join("deleted").display().to_string(),
+                    WorkspaceTrustRecord {
+                        level: WorkspaceTrustLevel::ToolsPolicy,
+                        trusted_at: 1,
+                    },
+                );
+            })
+            .unwrap();
+
+        let report = DotMaintenance::new(DotManager::with_root(root.clone()))
+            .prune_older_than(Duration::from_secs(30 * 24 * 60 * 60))
+            .run()
+            .unwrap();
+        assert_eq!(report.pruned(), 2, "{report:?}");
+        assert_eq!(report.repairs(), 0);
+        assert!(!root.join("sessions/session-old.json").exists());
+        assert!(root.join("sessions/session-new.json").exists());
+        assert!(
+            manager
+                .load_config()
+                .unwrap()
+                .workspace_trust
+                .entries
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,170 @@
+//! Environment a session ran in
+//!
+//! Each archived session records an [`EnvironmentManifest`]: the VT Code
+//! version, a hash of the configuration, the models in use, the versions of
+//! the external search tools, the platform, and the environment variables
+//! that change VT Code's behavior. Comparing the manifests of two sessions
+//! answers most "it worked yesterday" questions without asking the reporter.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::process::{Command, Stdio};
+
+/// External tools whose version is recorded.
+const TOOLS: &[&str] = &["rg", "ast-grep"];
+/// Variables recorded regardless of prefix.
+const ENV_NAMES: &[&str] = &["RUST_LOG", "NO_COLOR", "TERM", "COLORTERM", "CI"];
+const ENV_PREFIXES: &[&str] = &["VTCODE_", "VT_"];
+/// Variables whose names contain one of these are recorded as set, without
+/// their value.
+const SECRET_MARKERS: &[&str] = &["KEY", "TOKEN", "SECRET", "PASSWORD"];
+const SET_VALUE: &str = "<set>";
+const NOT_INSTALLED: &str = "not installed";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EnvironmentManifest {
+    pub vtcode_version: String,
+    /// Short hash of the loaded configuration, see `trajectory::config_hash`
+    #[serde(default)]
+    pub config_hash: String,
+    /// Model ids by role, e.g. `chat` or `router`
+    #[serde(default)]
+    pub models: BTreeMap<String, String>,
+    /// First line of `<tool> --version`, or "not installed"
+    #[serde(default)]
+    pub tools: BTreeMap<String, String>,
+    pub os: String,
+    pub arch: String,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+impl EnvironmentManifest {
+    /// Describe the current process. Runs each recorded tool once to read
+    /// its version.
+    pub fn capture(config_hash: impl Into<String>, models: BTreeMap<String, String>) -> Self {
+        let tools = TOOLS
+            .iter()
+            .map(|tool| {
+                let version = tool_version(tool).unwrap_or_else(|| NOT_INSTALLED.to_string());
+                (tool.to_string(), version)
+            })
+            .collect();
+        Self {
+            vtcode_version: env!("CARGO_PKG_VERSION").to_string(),
+            config_hash: config_hash.into(),
+            models: models
+                .into_iter()
+                .filter(|(_, model)| !model.trim().is_empty())
+                .collect(),
+            tools,
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            env: env_flags(std::env::vars()),
+        }
+    }
+
+    /// `label: value` lines for display.
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!("vtcode: {}", self.vtcode_version),
+            format!(
+                "config hash: {}",
+                if self.config_hash.is_empty() {
+                    "(none)"
+                } else {
+                    &self.config_hash
+                }
+            ),
+            format!("platform: {}/{}", self.os, self.arch),
+        ];
+        lines.extend(
+            self.models
+                .iter()
+                .map(|(role, model)| format!("model ({role}): {model}")),
+        );
+        lines.extend(
+            self.tools
+                .iter()
+                .map(|(tool, version)| format!("{tool}: {version}")),
+        );
+        lines.extend(
+            self.env
+                .iter()
+                .map(|(name, value)| format!("env {name}={value}")),
+        );
+        lines
+    }
+}
+
+/// The variables worth recording, with secret-looking values hidden.
+fn env_flags(vars: impl IntoIterator<Item = (String, String)>) -> BTreeMap<String, String> {
+    vars.into_iter()
+        .filter(|(name, _)| {
+            ENV_NAMES.contains(&name.as_str())
+                || ENV_PREFIXES.iter().any(|prefix| name.starts_with(prefix))
+        })
+        .map(|(name, value)| {
+            let upper = name.to_uppercase();
+            if SECRET_MARKERS.iter().any(|marker| upper.contains(marker)) {
+                (name, SET_VALUE.to_string())
+            } else {
+                (name, value)
+            }
+        })
+        .collect()
+}
+
+fn tool_version(tool: &str) -> Option<String> {
+    let output = Command::new(tool)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_behavior_flags_without_secrets() {
+        let vars = [
+            ("VTCODE_THEME", "ciapre-dark"),
+            ("VT_SESSION_DIR", "/tmp/sessions"),
+            ("VTCODE_API_KEY", "sk-123"),
+            ("RUST_LOG", "debug"),
+            ("HOME", "/home/me"),
+        ]
+        .map(|(name, value)| (name.to_string(), value.to_string()));
+        let env = env_flags(vars);
+        assert_eq!(env.len(), 4);
+        assert_eq!(env["VTCODE_API_KEY"], SET_VALUE);
+        assert_eq!(env["RUST_LOG"], "debug");
+        assert!(!env.contains_key("HOME"));
+
+        let manifest = EnvironmentManifest::capture(
+            "abc123",
+            BTreeMap::from([
+                ("chat".to_string(), "gemini-2.5-flash".to_string()),
+                ("router".to_string(), String::new()),
+            ]),
+        );
+        assert_eq!(manifest.models.len(), 1);
+        assert_eq!(manifest.tools.len(), TOOLS.len());
+        assert!(
+            manifest
+                .lines()
+                .contains(&"model (chat): gemini-2.5-flash".to_string())
+        );
+    }
+}
@@ -0,0 +1,329 @@
+//! Generated and vendored paths left out of search, indexing and listings
+//!
+//! `[index.exclude]` picks ecosystem profiles (or detects them with `auto`)
+//! and adds project patterns. [`IndexExclusions`] compiles them once so
+//! grep_search, list_files, the symbol index and the search index skip the
+//! same paths, even when a copy of them is not gitignored. The set for the
+//! current workspace is installed with [`configure`] and read with
+//! [`current`]; tools let a single call opt out with `include_generated`.
+
+use crate::config::core::IndexExcludeConfig;
+use glob::Pattern;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+/// Built-in exclusion profile for one ecosystem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExcludeProfile {
+    Rust,
+    Node,
+    Python,
+    Go,
+    Jvm,
+    Dotnet,
+    Protobuf,
+}
+
+impl ExcludeProfile {
+    pub const ALL: [ExcludeProfile; 7] = [
+        Self::Rust,
+        Self::Node,
+        Self::Python,
+        Self::Go,
+        Self::Jvm,
+        Self::Dotnet,
+        Self::Protobuf,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Rust => "rust",
+            Self::Node => "node",
+            Self::Python => "python",
+            Self::Go => "go",
+            Self::Jvm => "jvm",
+            Self::Dotnet => "dotnet",
+            Self::Protobuf => "protobuf",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|profile| profile.as_str().eq_ignore_ascii_case(value.trim()))
+    }
+
+    /// Patterns of the profile, in the syntax of `[index.exclude].patterns`.
+    pub fn patterns(self) -> &'static [&'static str] {
+        match self {
+            Self::Rust => &["target/"],
+            Self::Node => &[
+                "node_modules/",
+                ".next/",
+                ".nuxt/",
+                ".svelte-kit/",
+                ".turbo/",
+                "dist/",
+                "coverage/",
+                "*.min.js",
+            ],
+            Self::Python => &[
+                "__pycache__/",
+                ".venv/",
+                "venv/",
+                ".tox/",
+                ".mypy_cache/",
+                ".pytest_cache/",
+                ".ruff_cache/",
+                "*.egg-info/",
+                "*.pyc",
+            ],
+            Self::Go => &["vendor/"],
+            Self::Jvm => &["build/", ".gradle/", "target/"],
+            Self::Dotnet => &["bin/", "obj/"],
+            Self::Protobuf => &[
+                "*.pb.go",
+                "*.pb.cc",
+                "*.pb.h",
+                "*_pb2.py",
+                "*_pb2.pyi",
+                "*_pb2_grpc.py",
+                "*_pb.js",
+                "*_pb.d.ts",
+                "*_grpc_pb.js",
+            ],
+        }
+    }
+
+    /// Whether the workspace root has this ecosystem's manifest. Generated
+    /// protobuf code can sit in any project, so that profile always applies.
+    fn detect(self, root: &Path) -> bool {
+        let markers: &[&str] = match self {
+            Self::Rust => &["Cargo.toml"],
+            Self::Node => &["package.json"],
+            Self::Python => &[
+                "pyproject.toml",
+                "setup.py",
+                "setup.cfg",
+                "requirements.txt",
+            ],
+            Self::Go => &["go.mod"],
+            Self::Jvm => &[
+                "pom.xml",
+                "build.gradle",
+                "build.gradle.kts",
+                "settings.gradle",
+                "settings.gradle.kts",
+            ],
+            Self::Dotnet => {
+                return std::fs::read_dir(root).is_ok_and(|entries| {
+                    entries.flatten().any(|entry| {
+                        entry
+                            .path()
+                            .extension()
+                            .is_some_and(|ext| ext == "sln" || ext == "csproj" || ext == "fsproj")
+                    })
+                });
+            }
+            Self::Protobuf => return true,
+        };
+        markers.iter().any(|marker| root.join(marker).exists())
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Rule {
+    /// `name/`: a directory with this name anywhere
+    Dir(Pattern),
+    /// `*.ext`: a file with this name anywhere
+    Name(Pattern),
+    /// `path/glob`: a path relative to the workspace root
+    Path(Pattern),
+}
+
+/// Compiled exclusion patterns for one workspace.
+#[derive(Debug, Clone, Default)]
+pub struct IndexExclusions {
+    root: PathBuf,
+    patterns: Vec<String>,
+    rules: Vec<Rule>,
+}
+
+impl IndexExclusions {
+    /// Compile the profiles and patterns of `config` for `root`. Unknown
+    /// profile names and invalid globs are skipped.
+    pub fn from_config(root: &Path, config: &IndexExcludeConfig) -> Self {
+        let mut exclusions = Self {
+            root: root.to_path_buf(),
+            ..Self::default()
+        };
+        if !config.enabled {
+            return exclusions;
+        }
+        let mut profiles = Vec::new();
+        for name in &config.profiles {
+            if name.trim().eq_ignore_ascii_case("auto") {
+                profiles.extend(
+                    ExcludeProfile::ALL
+                        .into_iter()
+                        .filter(|profile| profile.detect(root)),
+                );
+            } else if let Some(profile) = ExcludeProfile::parse(name) {
+                profiles.push(profile);
+            }
+        }
+        for profile in profiles {
+            for pattern in profile.patterns() {
+                exclusions.add(pattern);
+            }
+        }
+        for pattern in &config.patterns {
+            exclusions.add(pattern);
+        }
+        exclusions
+    }
+
+    fn add(&mut self, pattern: &str) {
+        let pattern = pattern.trim();
+        if pattern.is_empty() || self.patterns.iter().any(|known| known == pattern) {
+            return;
+        }
+        let rule = if let Some(name) = pattern.strip_suffix('/')
+            && !name.contains('/')
+        {
+            Pattern::new(name).map(Rule::Dir)
+        } else if !pattern.contains('/') {
+            Pattern::new(pattern).map(Rule::Name)
+        } else {
+            // `dir/**` is matched as `dir`; files below it are excluded with it.
+            let path = pattern.trim_start_matches('/').trim_end_matches('/');
+            Pattern::new(path.strip_suffix("/**").unwrap_or(path)).map(Rule::Path)
+        };
+        if let Ok(rule) = rule {
+            self.patterns.push(pattern.to_string());
+            self.rules.push(rule);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Patterns in effect, in `[index.exclude].patterns` syntax.
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    /// Whether `path` (absolute, or relative to the workspace root) is excluded.
+    /// Files inside an excluded directory are excluded too.
+    pub fn is_excluded(&self, path: &Path, is_dir: bool) -> bool {
+        if self.rules.is_empty() {
+            return false;
+        }
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        let names: Vec<String> = relative
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+                _ => None,
+            })
+            .collect();
+        let Some((file_name, parents)) = names.split_last() else {
+            return false;
+        };
+        let directories = if is_dir { &names[..] } else { parents };
+        let joined = names.join("/");
+        self.rules.iter().any(|rule| match rule {
+            Rule::Dir(pattern) => directories.iter().any(|name| pattern.matches(name)),
+            Rule::Name(pattern) => !is_dir && pattern.matches(file_name),
+            Rule::Path(pattern) => {
+                pattern.matches(&joined)
+                    || (1..names.len()).any(|end| pattern.matches(&names[..end].join("/")))
+            }
+        })
+    }
+
+    /// ripgrep `--glob` arguments that skip the excluded paths.
+    pub fn ripgrep_globs(&self) -> Vec<String> {
+        self.rules
+            .iter()
+            .map(|rule| match rule {
+                Rule::Dir(pattern) => format!("!**/{}/**", pattern.as_str()),
+                Rule::Name(pattern) => format!("!**/{}", pattern.as_str()),
+                Rule::Path(pattern) => format!("!{}", pattern.as_str()),
+            })
+            .collect()
+    }
+}
+
+static CURRENT: Lazy<RwLock<Arc<IndexExclusions>>> =
+    Lazy::new(|| RwLock::new(Arc::new(IndexExclusions::default())));
+
+/// Install the exclusions for the session's workspace.
+pub fn configure(root: &Path, config: &IndexExcludeConfig) {
+    *CURRENT.write() = Arc::new(IndexExclusions::from_config(root, config));
+}
+
+/// Exclusions installed by [`configure`]; empty until then.
+pub fn current() -> Arc<IndexExclusions> {
+    CURRENT.read().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn auto_detects_profiles_and_matches_paths() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("Cargo.toml"), "[package]").unwrap();
+        let config = IndexExcludeConfig {
+            patterns: vec!["src/gen/**".to_string(), "snapshots/".to_string()],
+            ..IndexExcludeConfig::default()
+        };
+        let exclusions = IndexExclusions::from_config(dir.path(), &config);
+
+        assert!(exclusions.is_excluded(&dir.path().join("target"), true));
+        assert!(exclusions.is_excluded(Path::new("crates/a/target/debug/build.rs"), false));
+        assert!(exclusions.is_excluded(Path::new("api/v1/service.pb.go"), false));
+        assert!(exclusions.is_excluded(Path::new("src/gen/types.rs"), false));
+        assert!(exclusions.is_excluded(Path::new("src/gen"), true));
+        assert!(exclusions.is_excluded(Path::new("tests/snapshots/a.snap"), false));
+        assert!(!exclusions.is_excluded(Path::new("src/target.rs"), false));
+        assert!(!exclusions.is_excluded(Path::new("src/main.rs"), false));
+        // No package.json, so the node profile is not active.
+        assert!(!exclusions.is_excluded(Path::new("web/node_modules/x.js"), false));
+        assert!(
+            exclusions
+                .ripgrep_globs()
+                .contains(&"!**/target/**".to_string())
+        );
+    }
+
+    #[test]
+    fn explicit_profiles_and_disabling() {
+        let dir = TempDir::new().unwrap();
+        let config = IndexExcludeConfig {
+            profiles: vec![
+                "node".to_string(),
+                "python".to_string(),
+                "cobol".to_string(),
+            ],
+            ..IndexExcludeConfig::default()
+        };
+        let exclusions = IndexExclusions::from_config(dir.path(), &config);
+        assert!(exclusions.is_excluded(Path::new("web/node_modules/react/index.js"), false));
+        assert!(exclusions.is_excluded(Path::new("pkg/vtcode.egg-info"), true));
+        assert!(!exclusions.is_excluded(Path::new("api/service.pb.go"), false));
+
+        let disabled = IndexExcludeConfig {
+            enabled: false,
+            ..config
+        };
+        assert!(IndexExclusions::from_config(dir.path(), &disabled).is_empty());
+    }
+}
@@ -0,0 +1,356 @@
+//! Handling for files made of a few very long lines
+//!
+//! Minified bundles, source maps and JSON blobs defeat line-based tools: one
+//! "line" can hold the whole file. [`has_long_lines`] spots them,
+//! [`pretty_print`] lays JSON and JavaScript out one statement or member per
+//! line for display and diffing (files are always written back as they were),
+//! and [`byte_chunk`] pages through them by byte range, describing where each
+//! chunk starts with a [`structural_hint`] instead of a line number.
+
+use std::path::Path;
+
+/// Lines longer than this make a file "long-line".
+pub const LONG_LINE_BYTES: usize = 2_000;
+/// Byte range returned per read of a long-line file.
+pub const LONG_LINE_CHUNK_BYTES: usize = 16_000;
+/// How far back from the end of a chunk to look for a clean break.
+const BOUNDARY_LOOKBACK: usize = 512;
+const INDENT: &str = "  ";
+
+/// How to lay out a long-line file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StructuredKind {
+    Json,
+    JavaScript,
+}
+
+impl StructuredKind {
+    /// The kind of `path`, falling back to sniffing `content` for JSON.
+    pub fn detect(path: &str, content: &str) -> Option<Self> {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("json" | "map" | "jsonc" | "webmanifest") => Some(Self::Json),
+            Some("js" | "mjs" | "cjs" | "jsx" | "ts" | "tsx" | "css") => Some(Self::JavaScript),
+            _ => match content.trim_start().chars().next() {
+                Some('{' | '[') => Some(Self::Json),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// Whether any line of `content` is longer than [`LONG_LINE_BYTES`].
+pub fn has_long_lines(content: &str) -> bool {
+    content.lines().any(|line| line.len() > LONG_LINE_BYTES)
+}
+
+/// `content` laid out for reading and diffing, `None` when it has no long
+/// lines or is not JSON or JavaScript.
+///
+/// The layout only adds and removes whitespace outside strings and comments,
+/// so it never reorders keys, but JavaScript regex literals containing
+/// brackets may be broken up oddly.
+pub fn pretty_print(path: &str, content: &str) -> Option<String> {
+    if !has_long_lines(content) {
+        return None;
+    }
+    let kind = StructuredKind::detect(path, content)?;
+    Some(layout(content, kind))
+}
+
+fn layout(content: &str, kind: StructuredKind) -> String {
+    let mut out = String::with_capacity(content.len() + content.len() / 4);
+    let mut stack: Vec<char> = Vec::new();
+    let mut chars = content.chars().peekable();
+    let newline = |out: &mut String, depth: usize| {
+        while out.ends_with(' ') {
+            out.pop();
+        }
+        out.push('\n');
+        for _ in 0..depth {
+            out.push_str(INDENT);
+        }
+    };
+    let mut line_start = true;
+    while let Some(c) = chars.next() {
+        match c {
+            '"' | '\'' | '`' => {
+                out.push(c);
+                let mut escaped = false;
+                for next in chars.by_ref() {
+                    out.push(next);
+                    if escaped {
+                        escaped = false;
+                    } else if next == '\\' {
+                        escaped = true;
+                    } else if next == c {
+                        break;
+                    }
+                }
+            }
+            '/' if kind == StructuredKind::JavaScript && chars.peek() == Some(&'/') => {
+                out.push(c);
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        break;
+                    }
+                    out.push(next);
+                }
+                newline(&mut out, stack.len());
+                line_start = true;
+                continue;
+            }
+            '/' if kind == StructuredKind::JavaScript && chars.peek() == Some(&'*') => {
+                out.push(c);
+                let mut previous = '\0';
+                for next in chars.by_ref() {
+                    out.push(next);
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+            }
+            '{' | '[' => {
+                out.push(c);
+                stack.push(c);
+                let close = if c == '{' { '}' } else { ']' };
+                while chars.peek().is_some_and(|next| next.is_whitespace()) {
+                    chars.next();
+                }
+                if chars.peek() == Some(&close) {
+                    out.push(close);
+                    chars.next();
+                    stack.pop();
+                } else {
+                    newline(&mut out, stack.len());
+                    line_start = true;
+                    continue;
+                }
+            }
+            '}' | ']' => {
+                stack.pop();
+                if !line_start {
+                    newline(&mut out, stack.len());
+                }
+                out.push(c);
+            }
+            '(' => {
+                out.push(c);
+                stack.push(c);
+            }
+            ')' => {
+                if stack.last() == Some(&'(') {
+                    stack.pop();
+                }
+                out.push(c);
+            }
+            ',' if matches!(stack.last(), Some('{' | '[')) => {
+                out.push(c);
+                newline(&mut out, stack.len());
+                line_start = true;
+                continue;
+            }
+            ';' if stack.last() != Some(&'(') => {
+                out.push(c);
+                newline(&mut out, stack.len());
+                line_start = true;
+                continue;
+            }
+            ':' if kind == StructuredKind::Json => {
+                out.push_str(": ");
+                while chars.peek().is_some_and(|next| next.is_whitespace()) {
+                    chars.next();
+                }
+            }
+            c if c.is_whitespace() => {
+                if !line_start && !out.ends_with(' ') {
+                    out.push(' ');
+                }
+                continue;
+            }
+            _ => out.push(c),
+        }
+        line_start = false;
+    }
+    while out.ends_with([' ', '\n']) {
+        out.pop();
+    }
+    out.push('\n');
+    out
+}
+
+/// A byte range of a file and where it sits in the file's structure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ByteChunk {
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+    /// Where `start` falls, such as `$.dependencies.react` or `nesting depth 3`
+    pub hint: Option<String>,
+}
+
+/// About `length` bytes of `content` from `offset`, ending after a `,`, `;`,
+/// `}` or `]` when one is close to the end so chunks split between members.
+pub fn byte_chunk(path: &str, content: &str, offset: usize, length: usize) -> ByteChunk {
+    let start = floor_char_boundary(content, offset.min(content.len()));
+    let mut end = floor_char_boundary(content, start.saturating_add(length).min(content.len()));
+    if end < content.len() {
+        let window_start = floor_char_boundary(content, end.saturating_sub(BOUNDARY_LOOKBACK));
+        if let Some(pos) = content[window_start.max(start)..end].rfind([',', ';', '}', ']']) {
+            end = window_start.max(start) + pos + 1;
+        }
+    }
+    let hint = StructuredKind::detect(path, content).and_then(|kind| {
+        if start == 0 {
+            None
+        } else {
+            Some(structural_hint(content, start, kind))
+        }
+    });
+    ByteChunk {
+        start,
+        end,
+        text: content[start..end].to_string(),
+        hint,
+    }
+}
+
+/// Where `offset` falls in `content`: a JSON path for JSON, the bracket
+/// nesting depth otherwise.
+pub fn structural_hint(content: &str, offset: usize, kind: StructuredKind) -> String {
+    struct Frame {
+        open: char,
+        key: Option<String>,
+        index: usize,
+    }
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut last_string: Option<String> = None;
+    let mut chars = content[..offset.min(content.len())].chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' | '\'' | '`' => {
+                let mut text = String::new();
+                let mut escaped = false;
+                for next in chars.by_ref() {
+                    if escaped {
+                        escaped = false;
+                    } else if next == '\\' {
+                        escaped = true;
+                    } else if next == c {
+                        break;
+                    }
+                    text.push(next);
+                }
+                last_string = Some(text);
+            }
+            '{' | '[' | '(' => stack.push(Frame {
+                open: c,
+                key: None,
+                index: 0,
+            }),
+            '}' | ']' | ')' => {
+                stack.pop();
+            }
+            ':' => {
+                if let Some(frame) = stack.last_mut()
+                    && frame.open == '{'
+                {
+                    frame.key = last_string.take();
+                }
+            }
+            ',' => {
+                if let Some(frame) = stack.last_mut() {
+                    frame.index += 1;
+                    frame.key = None;
+                }
+            }
+            _ => {}
+        }
+    }
+    if kind == StructuredKind::JavaScript {
+        return format!("nesting depth {}", stack.len());
+    }
+    let mut path = String::from("$");
+    for frame in &stack {
+        match frame.open {
+            '[' => path.push_str(&format!("[{}]", frame.index)),
+            '{' => {
+                if let Some(key) = &frame.key {
+                    if key.chars().all(|c| c.is_alphanumeric() || c == '_') && !key.is_empty() {
+                        path.push('.');
+                        path.push_str(key);
+                    } else {
+                        path.push_str(&format!("[{key:?}]"));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    path
+}
+
+fn floor_char_boundary(content: &str, mut index: usize) -> usize {
+    while !content.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// `line` cut to `max_chars` characters with a note of how much was left out.
+pub fn clip_line(line: &str, max_chars: usize) -> std::borrow::Cow<'_, str> {
+    match line.char_indices().nth(max_chars) {
+        Some((cut, _)) => {
+            std::borrow::Cow::Owned(format!("{} … [+{} bytes]", &line[..cut], line.len() - cut))
+        }
+        None => std::borrow::Cow::Borrowed(line),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn minified_json() -> String {
+        let items: Vec<String> = (0..200)
+            .map(|i| format!(r#"{{"id":{i},"name":"item {i}, \"quoted\"","tags":[]}}"#))
+            .collect();
+        format!(r#"{{"version":1,"items":[{}]}}"#, items.join(","))
+    }
+
+    #[test]
+    fn pretty_prints_json_without_reordering() {
+        let content = minified_json();
+        assert!(has_long_lines(&content));
+        let pretty = pretty_print("data.json", &content).unwrap();
+        assert!(pretty.starts_with("{\n  \"version\": 1,\n  \"items\": [\n    {\n"));
+        assert!(pretty.contains("\"name\": \"item 3, \\\"quoted\\\"\",\n"));
+        assert!(pretty.contains("\"tags\": []\n"));
+        let squeezed: String = pretty.split_whitespace().collect();
+        let original: String = content.split_whitespace().collect();
+        assert_eq!(squeezed, original);
+        assert!(pretty_print("short.json", "{\"a\":1}").is_none());
+    }
+
+    #[test]
+    fn chunks_break_between_members_with_a_json_path() {
+        let content = minified_json();
+        let first = byte_chunk("data.json", &content, 0, 1_000);
+        assert!(first.text.ends_with([',', '}', ']']));
+        assert!(first.hint.is_none());
+        let second = byte_chunk("data.json", &content, first.end, 1_000);
+        assert_eq!(second.start, first.end);
+        assert!(second.hint.unwrap().starts_with("$.items["));
+
+        let js = "function a(){var x={b:[1,2,(3)]};return x}";
+        let offset = js.find("1,").unwrap();
+        assert_eq!(
+            structural_hint(js, offset, StructuredKind::JavaScript),
+            "nesting depth 3"
+        );
+    }
+}
@@ -9,8 +9,10 @@
 //! - **User Preferences**: Theme settings, UI preferences, cache configuration
 //! - **Provider Configuration**: LLM provider settings and API keys
 //! - **Dotfile Management**: `.vtcode` directory and configuration files
+//! - **Maintenance** (`dot_maintenance`): Repair unreadable stores and prune old state
+//! - **Session archive** (`session_compaction`): Summarize, compress and index old sessions
 //!
-//! ### Safety Utilities (`safety`)
+//! ### Safety Utilities (`safety`, `workspace_path`)
 //! - **Path Validation**: Workspace boundary checking
 //! - **Command Sanitization**: Safe command execution
 //! - **Input Validation**: User input sanitization
@@ -49,14 +51,13 @@
 //!
 //! ### Path Safety
 //! ```rust,no_run
-//! use vtcode_core::utils::safety::validate_workspace_path;
+//! use vtcode_core::utils::workspace_path::resolve_in_workspace;
 //! use std::path::PathBuf;
 //!
 //! let workspace = PathBuf::from("/home/user/project");
-//! let file_path = PathBuf::from("src/main.rs");
 //!
 //! // Validate path is within workspace
-//! match validate_workspace_path(&workspace, &file_path) {
+//! match resolve_in_workspace(&workspace, "src/../src/main.rs") {
 //!     Ok(valid_path) => println!("Safe path: {}", valid_path.display()),
 //!     Err(e) => eprintln!("Unsafe path: {}", e),
 //! }
@@ -92,10 +93,24 @@
 //! ```
 
 pub mod ansi;
+pub mod ansi_sanitize;
+pub mod bug_report;
+pub mod clipboard;
+pub mod code_fence;
 pub mod colors;
 pub mod dot_config;
+pub mod dot_maintenance;
+pub mod environment_manifest;
+pub mod index_exclude;
+pub mod long_lines;
+pub mod network;
+pub mod redact;
 pub mod safety;
+pub mod scratch;
 pub mod session_archive;
+pub mod session_compaction;
+pub mod session_title;
 pub mod transcript;
 pub mod utils;
 pub mod vtcodegitignore;
+pub mod workspace_path;
@@ -0,0 +1,255 @@
+//! Proxy and TLS settings shared by every HTTP client
+//!
+//! [`configure`] installs the `[network]` settings once at startup. Provider
+//! clients, OAuth sign-in and the curl tool all start from
+//! [`client_builder`], so a corporate proxy or CA bundle applies to every
+//! request, streaming ones included. Proxy URLs and certificate files are
+//! checked when the settings are installed, so a typo fails at startup rather
+//! than on the first request.
+
+use crate::config::core::NetworkConfig;
+use anyhow::{Context, Result, bail};
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use reqwest::{Certificate, Client, ClientBuilder, NoProxy, Proxy, Url};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::warn;
+
+/// Settings for the clients of one provider, or for all other clients.
+#[derive(Debug, Clone, Default)]
+struct ClientSettings {
+    proxy: Option<String>,
+    certificates: Vec<Certificate>,
+    accept_invalid_certs: bool,
+}
+
+/// Validated `[network]` settings.
+#[derive(Debug, Clone, Default)]
+pub struct NetworkSettings {
+    global: ClientSettings,
+    no_proxy: Option<String>,
+    providers: HashMap<String, ClientSettings>,
+}
+
+impl NetworkSettings {
+    /// Check proxy URLs and load certificate files; relative certificate
+    /// paths are resolved against `base`.
+    pub fn from_config(config: &NetworkConfig, base: &Path) -> Result<Self> {
+        let global = ClientSettings {
+            proxy: checked_proxy(config.proxy.as_deref(), "[network] proxy")?,
+            certificates: load_certificates(&config.ca_certificates, base)?,
+            accept_invalid_certs: config.danger_accept_invalid_certs,
+        };
+        let mut providers = HashMap::new();
+        for (name, overrides) in &config.providers {
+            let scope = format!("[network.providers.{name}] proxy");
+            let mut certificates = global.certificates.clone();
+            certificates.extend(load_certificates(&overrides.ca_certificates, base)?);
+            let settings = ClientSettings {
+                proxy: checked_proxy(overrides.proxy.as_deref(), &scope)?
+                    .or_else(|| global.proxy.clone()),
+                certificates,
+                accept_invalid_certs: overrides
+                    .danger_accept_invalid_certs
+                    .unwrap_or(global.accept_invalid_certs),
+            };
+            providers.insert(name.to_lowercase(), settings);
+        }
+        let no_proxy = (!config.no_proxy.is_empty()).then(|| config.no_proxy.join(","));
+        Ok(Self {
+            global,
+            no_proxy,
+            providers,
+        })
+    }
+
+    fn settings(&self, provider: Option<&str>) -> &ClientSettings {
+        provider
+            .and_then(|name| self.providers.get(&name.to_lowercase()))
+            .unwrap_or(&self.global)
+    }
+
+    /// Proxy URL used for `provider`, or for other clients when `None`.
+    pub fn proxy(&self, provider: Option<&str>) -> Option<&str> {
+        self.settings(provider).proxy.as_deref()
+    }
+
+    /// Where certificate verification is off: "all requests" and provider
+    /// names. Empty when verification is on everywhere.
+    pub fn insecure_scopes(&self) -> Vec<String> {
+        let mut scopes = Vec::new();
+        if self.global.accept_invalid_certs {
+            scopes.push("all requests".to_string());
+        }
+        let mut providers: Vec<&String> = self
+            .providers
+            .iter()
+            .filter(|(_, settings)| {
+                settings.accept_invalid_certs && !self.global.accept_invalid_certs
+            })
+            .map(|(name, _)| name)
+            .collect();
+        providers.sort();
+        scopes.extend(
+            providers
+                .into_iter()
+                .map(|name| format!("provider '{name}'")),
+        );
+        scopes
+    }
+
+    /// Apply the settings for `provider` to `builder`.
+    pub fn apply(&self, builder: ClientBuilder, provider: Option<&str>) -> ClientBuilder {
+        let settings = self.settings(provider);
+        let mut builder = builder;
+        if let Some(url) = settings.proxy.as_deref() {
+            match Proxy::all(url) {
+                Ok(proxy) => {
+                    let no_proxy = self.no_proxy.as_deref().and_then(NoProxy::from_string);
+                    builder = builder.proxy(proxy.no_proxy(no_proxy));
+                }
+                Err(error) => warn!(%error, url, "ignoring invalid proxy URL"),
+            }
+        }
+        for certificate in &settings.certificates {
+            builder = builder.add_root_certificate(certificate.clone());
+        }
+        if settings.accept_invalid_certs {
+            warn!(
+                provider = provider.unwrap_or("*"),
+                "TLS certificate verification is disabled"
+            );
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+        builder
+    }
+}
+
+static CURRENT: Lazy<RwLock<Arc<NetworkSettings>>> =
+    Lazy::new(|| RwLock::new(Arc::new(NetworkSettings::default())));
+
+/// Validate and install the `[network]` settings for this process.
+pub fn configure(config: &NetworkConfig, base: &Path) -> Result<Arc<NetworkSettings>> {
+    let settings = Arc::new(NetworkSettings::from_config(config, base)?);
+    *CURRENT.write() = settings.clone();
+    Ok(settings)
+}
+
+/// Settings installed by [`configure`]; the system defaults until then.
+pub fn current() -> Arc<NetworkSettings> {
+    CURRENT.read().clone()
+}
+
+/// Client builder with the proxy and TLS settings for `provider`, or for
+/// non-provider requests when `None`.
+pub fn client_builder(provider: Option<&str>) -> ClientBuilder {
+    current().apply(Client::builder(), provider)
+}
+
+/// Client with the proxy and TLS settings for `provider`. Fails rather than
+/// fall back to a client that would bypass the configured proxy or CAs.
+pub fn client(provider: Option<&str>) -> Result<Client> {
+    client_builder(provider).build().with_context(|| {
+        format!(
+            "failed to build an HTTP client with the [network] settings for {}",
+            provider.unwrap_or("non-provider requests")
+        )
+    })
+}
+
+fn checked_proxy(url: Option<&str>, setting: &str) -> Result<Option<String>> {
+    let Some(url) = url.map(str::trim).filter(|url| !url.is_empty()) else {
+        return Ok(None);
+    };
+    let parsed = Url::parse(url).with_context(|| format!("{setting} '{url}' is not a URL"))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        bail!("{setting} '{url}' must be an http:// or https:// URL");
+    }
+    Proxy::all(url).with_context(|| format!("{setting} '{url}' is not a usable proxy"))?;
+    Ok(Some(url.to_string()))
+}
+
+fn load_certificates(paths: &[PathBuf], base: &Path) -> Result<Vec<Certificate>> {
+    let mut certificates = Vec::new();
+    for path in paths {
+        let path = if path.is_absolute() {
+            path.clone()
+        } else {
+            base.join(path)
+        };
+        let pem = std::fs::read(&path)
+            .with_context(|| format!("failed to read CA certificates {}", path.display()))?;
+        let bundle = Certificate::from_pem_bundle(&pem)
+            .with_context(|| format!("{} is not a PEM certificate bundle", path.display()))?;
+        if bundle.is_empty() {
+            bail!("{} contains no certificates", path.display());
+        }
+        certificates.extend(bundle);
+    }
+    Ok(certificates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::core::ProviderNetworkConfig;
+
+    #[test]
+    fn provider_overrides_fall_back_to_global_settings() {
+        let mut config = NetworkConfig {
+            proxy: Some("http://proxy.corp.example:3128".to_string()),
+            no_proxy: vec!["localhost".to_string()],
+            ..Default::default()
+        };
+        config.providers.insert(
+            "Anthropic".to_string(),
+            ProviderNetworkConfig {
+                proxy: Some("https://egress.corp.example:8443".to_string()),
+                danger_accept_invalid_certs: Some(true),
+                ..Default::default()
+            },
+        );
+        config
+            .providers
+            .insert("openai".to_string(), ProviderNetworkConfig::default());
+        let settings = NetworkSettings::from_config(&config, Path::new(".")).unwrap();
+
+        assert_eq!(
+            settings.proxy(Some("anthropic")),
+            Some("https://egress.corp.example:8443")
+        );
+        assert_eq!(
+            settings.proxy(Some("openai")),
+            Some("http://proxy.corp.example:3128")
+        );
+        assert_eq!(settings.proxy(None), Some("http://proxy.corp.example:3128"));
+        assert_eq!(settings.insecure_scopes(), vec!["provider 'anthropic'"]);
+        assert!(
+            settings
+                .apply(Client::builder(), Some("anthropic"))
+                .build()
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn rejects_bad_proxies_and_missing_certificates() {
+        let proxy = NetworkConfig {
+            proxy: Some("proxy.corp.example".to_string()),
+            ..Default::default()
+        };
+        assert!(NetworkSettings::from_config(&proxy, Path::new(".")).is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("empty.pem"), "").unwrap();
+        for file in ["missing.pem", "empty.pem"] {
+            let certs = NetworkConfig {
+                ca_certificates: vec![PathBuf::from(file)],
+                ..Default::default()
+            };
+            assert!(NetworkSettings::from_config(&certs, dir.path()).is_err());
+        }
+    }
+}
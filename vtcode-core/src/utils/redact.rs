@@ -0,0 +1,386 @@
+//! Transcript redaction for sharing sessions
+//!
+//! [`Redactor`] replaces secrets, email addresses, absolute paths and
+//! user-supplied patterns with placeholders such as `<SECRET_1>`. A value gets
+//! the same placeholder everywhere it appears, across every message passed to
+//! the same redactor, so a redacted transcript still shows that two tool calls
+//! touched the same file or used the same key.
+
+use crate::config::core::RedactionConfig;
+use crate::utils::session_archive::SessionSnapshot;
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+pub const WORKSPACE_PLACEHOLDER: &str = "<WORKSPACE>";
+pub const HOME_PLACEHOLDER: &str = "<HOME>";
+
+/// Well-known credential formats, then generic `key = value` assignments whose
+/// value (the first capture group) is replaced.
+static SECRET_PATTERNS: Lazy<Vec<Regex>> = Lazy::new(|| {
+    [
+        r"-----BEGIN [A-Z ]*PRIVATE KEY-----[\s\S]*?-----END [A-Z ]*PRIVATE KEY-----",
+        r"\bsk-(?:ant-|proj-)?[A-Za-z0-9_\-]{20,}",
+        r"\bgh[pousr]_[A-Za-z0-9]{30,}\b",
+        r"\bgithub_pat_[A-Za-z0-9_]{30,}\b",
+        r"\bAKIA[0-9A-Z]{16}\b",
+        r"\bAIza[0-9A-Za-z_\-]{35}\b",
+        r"\bxox[abprs]-[A-Za-z0-9\-]{10,}",
+        r"\beyJ[A-Za-z0-9_\-]{8,}\.eyJ[A-Za-z0-9_\-]{8,}\.[A-Za-z0-9_\-]{8,}",
+        r"(?i)\bbearer\s+([A-Za-z0-9._~+/\-]{16,}=*)",
+        r#"(?i)(?:api[_-]?key|secret|token|passw(?:or)?d|access[_-]?key)["']?\s*[:=]\s*["']?([^\s"',;]{8,})"#,
+        r"(?i)\b[a-z][a-z0-9+.\-]*://[^\s:/@]+:([^\s/@]+)@",
+    ]
+    .iter()
+    .map(|pattern| Regex::new(pattern).expect("valid secret pattern"))
+    .collect()
+});
+
+static EMAIL_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b[A-Za-z0-9._%+\-]+@[A-Za-z0-9.\-]+\.[A-Za-z]{2,}\b")
+        .expect("valid email pattern")
+});
+
+/// Absolute Unix paths with at least two components, and Windows drive paths.
+/// The leading group keeps URLs such as `https://host/a/b` from matching.
+static PATH_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"(?:^|[\s"'`(\[=:,])((?:/[\w.@+~\-]+){2,}/?|[A-Za-z]:\\(?:[\w.@+~\- ]+\\)*[\w.@+~\-]+)"#,
+    )
+    .expect("valid path pattern")
+});
+
+/// What a placeholder stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RedactionKind {
+    Secret,
+    Email,
+    Path,
+    Custom,
+}
+
+impl RedactionKind {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Secret => "SECRET",
+            Self::Email => "EMAIL",
+            Self::Path => "PATH",
+            Self::Custom => "REDACTED",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Secret => "secrets",
+            Self::Email => "emails",
+            Self::Path => "paths",
+            Self::Custom => "custom patterns",
+        }
+    }
+}
+
+struct Rule {
+    kind: RedactionKind,
+    regex: Regex,
+}
+
+/// Counts of what a redactor replaced.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RedactionReport {
+    /// Distinct values replaced, per kind
+    pub distinct: BTreeMap<RedactionKind, usize>,
+    /// Occurrences replaced, per kind
+    pub occurrences: BTreeMap<RedactionKind, usize>,
+}
+
+impl RedactionReport {
+    pub fn is_empty(&self) -> bool {
+        self.occurrences.is_empty()
+    }
+
+    /// One-line summary such as `3 secrets (5 occurrences), 1 emails`.
+    pub fn summary(&self) -> String {
+        if self.is_empty() {
+            return "nothing matched".to_string();
+        }
+        self.distinct
+            .iter()
+            .map(|(kind, distinct)| {
+                let occurrences = self.occurrences.get(kind).copied().unwrap_or(0);
+                if occurrences > *distinct {
+                    format!("{distinct} {} ({occurrences} occurrences)", kind.name())
+                } else {
+                    format!("{distinct} {}", kind.name())
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Stable placeholder assignment shared by every rule.
+#[derive(Default)]
+struct Placeholders {
+    assigned: HashMap<(RedactionKind, String), String>,
+    /// Numbered placeholders handed out so far, per kind
+    numbered: HashMap<RedactionKind, usize>,
+    report: RedactionReport,
+}
+
+impl Placeholders {
+    fn get(&mut self, kind: RedactionKind, value: &str) -> String {
+        *self.report.occurrences.entry(kind).or_default() += 1;
+        if let Some(existing) = self.assigned.get(&(kind, value.to_string())) {
+            return existing.clone();
+        }
+        *self.report.distinct.entry(kind).or_default() += 1;
+        let number = self.numbered.entry(kind).or_default();
+        *number += 1;
+        let placeholder = format!("<{}_{}>", kind.label(), number);
+        self.assigned
+            .insert((kind, value.to_string()), placeholder.clone());
+        placeholder
+    }
+
+    /// Count a replacement whose placeholder is fixed, such as `<WORKSPACE>`.
+    fn record(&mut self, kind: RedactionKind, value: &str, placeholder: &str) {
+        *self.report.occurrences.entry(kind).or_default() += 1;
+        if !self.assigned.contains_key(&(kind, value.to_string())) {
+            *self.report.distinct.entry(kind).or_default() += 1;
+            self.assigned
+                .insert((kind, value.to_string()), placeholder.to_string());
+        }
+    }
+}
+
+/// Replaces sensitive values with stable placeholders.
+pub struct Redactor {
+    rules: Vec<Rule>,
+    known_paths: Vec<(String, &'static str)>,
+    paths: bool,
+    placeholders: Placeholders,
+}
+
+impl Redactor {
+    /// Build a redactor from `[security.redaction]`; invalid custom patterns are errors.
+    pub fn new(config: &RedactionConfig) -> Result<Self> {
+        let mut rules = Vec::new();
+        if config.secrets {
+            rules.extend(SECRET_PATTERNS.iter().map(|regex| Rule {
+                kind: RedactionKind::Secret,
+                regex: regex.clone(),
+            }));
+        }
+        let mut redactor = Self {
+            rules,
+            known_paths: Vec::new(),
+            paths: config.paths,
+            placeholders: Placeholders::default(),
+        };
+        for pattern in &config.patterns {
+            redactor = redactor.with_pattern(pattern)?;
+        }
+        if config.emails {
+            redactor.rules.push(Rule {
+                kind: RedactionKind::Email,
+                regex: EMAIL_PATTERN.clone(),
+            });
+        }
+        if let Some(home) = dirs::home_dir() {
+            redactor = redactor.with_known_path(&home, HOME_PLACEHOLDER);
+        }
+        Ok(redactor)
+    }
+
+    /// Also redact matches of `pattern`.
+    pub fn with_pattern(mut self, pattern: &str) -> Result<Self> {
+        let regex = Regex::new(pattern)
+            .with_context(|| format!("Invalid redaction pattern '{pattern}'"))?;
+        self.rules.push(Rule {
+            kind: RedactionKind::Custom,
+            regex,
+        });
+        Ok(self)
+    }
+
+    /// Replace `path` and anything below it with `placeholder` (e.g. `<WORKSPACE>/src`).
+    pub fn with_known_path(mut self, path: &Path, placeholder: &'static str) -> Self {
+        let path = path
+            .to_string_lossy()
+            .trim_end_matches(['/', '\\'])
+            .to_string();
+        if self.paths && path.len() > 1 {
+            self.known_paths.push((path, placeholder));
+            // Longest first so the workspace wins over the home directory containing it.
+            self.known_paths.sort_by(|a, b| b.0.len().cmp(&a.0.len()));
+        }
+        self
+    }
+
+    pub fn redact(&mut self, text: &str) -> String {
+        let Self {
+            rules,
+            known_paths,
+            paths,
+            placeholders,
+        } = self;
+        let mut text = text.to_string();
+        for rule in rules.iter() {
+            text = replace_matches(&text, &rule.regex, rule.kind, placeholders);
+        }
+        for (path, placeholder) in known_paths.iter() {
+            text = replace_known_path(&text, path, placeholder, placeholders);
+        }
+        if *paths {
+            text = replace_matches(&text, &PATH_PATTERN, RedactionKind::Path, placeholders);
+        }
+        text
+    }
+
+    /// Redact every message, transcript line, the title, the workspace path and
+    /// the recorded environment variables of a session.
+    pub fn redact_snapshot(&mut self, snapshot: &SessionSnapshot) -> SessionSnapshot {
+        let mut redacted = snapshot.clone();
+        redacted.metadata.workspace_path = self.redact(&snapshot.metadata.workspace_path);
+        for value in redacted
+            .metadata
+            .environment
+            .iter_mut()
+            .flat_map(|environment| environment.env.values_mut())
+        {
+            *value = self.redact(value);
+        }
+        redacted.title = snapshot.title.as_deref().map(|title| self.redact(title));
+        for line in &mut redacted.transcript {
+            *line = self.redact(line);
+        }
+        for message in &mut redacted.messages {
+            message.content = self.redact(&message.content);
+            for call in message.tool_calls.iter_mut().flatten() {
+                call.function.arguments = self.redact(&call.function.arguments);
+            }
+        }
+        redacted
+    }
+
+    pub fn report(&self) -> &RedactionReport {
+        &self.placeholders.report
+    }
+}
+
+/// Replace the first capture group of each match (or the whole match when the
+/// pattern has no groups), leaving existing placeholders alone.
+fn replace_matches(
+    text: &str,
+    regex: &Regex,
+    kind: RedactionKind,
+    placeholders: &mut Placeholders,
+) -> String {
+    let group = usize::from(regex.captures_len() > 1);
+    let mut output = String::with_capacity(text.len());
+    let mut last = 0;
+    for captures in regex.captures_iter(text) {
+        let Some(found) = captures.get(group) else {
+            continue;
+        };
+        let value = found.as_str();
+        if value.is_empty() || is_placeholder(value) {
+            continue;
+        }
+        output.push_str(&text[last..found.start()]);
+        output.push_str(&placeholders.get(kind, value));
+        last = found.end();
+    }
+    output.push_str(&text[last..]);
+    output
+}
+
+/// Replace `path` where it is a whole path component, so `/srv/app` does not
+/// rewrite `/srv/application`.
+fn replace_known_path(
+    text: &str,
+    path: &str,
+    placeholder: &str,
+    placeholders: &mut Placeholders,
+) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut last = 0;
+    for (start, _) in text.match_indices(path) {
+        let end = start + path.len();
+        let continues = text[end..]
+            .chars()
+            .next()
+            .is_some_and(|ch| ch.is_alphanumeric() || matches!(ch, '_' | '-' | '.'));
+        if start < last || continues {
+            continue;
+        }
+        output.push_str(&text[last..start]);
+        output.push_str(placeholder);
+        last = end;
+        placeholders.record(RedactionKind::Path, path, placeholder);
+    }
+    output.push_str(&text[last..]);
+    output
+}
+
+fn is_placeholder(value: &str) -> bool {
+    value.starts_with('<') && value.ends_with('>')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placeholders_are_stable_across_messages() {
+        let config = RedactionConfig {
+            patterns: vec![r"ACME-\d+".to_string()],
+            ..RedactionConfig::default()
+        };
+        let mut redactor = Redactor::new(&config)
+            .unwrap()
+            .with_known_path(Path::new("/srv/work/app"), WORKSPACE_PLACEHOLDER);
+
+        let first = redactor.redact(
+            "export OPENAI_API_KEY=sk-abcdefghijklmnopqrstuvwx and mail ops@example.com about ACME-42",
+        );
+        assert_eq!(
+            first,
+            "export OPENAI_API_KEY=<SECRET_1> and mail <EMAIL_1> about <REDACTED_1>"
+        );
+
+        let second = redactor.redact(
+            "Edited /srv/work/app/src/main.rs and /srv/work/application/x; see /var/log/app/error.log and https://example.com/a/b. Key sk-abcdefghijklmnopqrstuvwx, cc dev@example.com",
+        );
+        assert_eq!(
+            second,
+            "Edited <WORKSPACE>/src/main.rs and <PATH_1>; see <PATH_2> and https://example.com/a/b. Key <SECRET_1>, cc <EMAIL_2>"
+        );
+        assert_eq!(redactor.report().distinct[&RedactionKind::Secret], 1);
+        assert_eq!(redactor.report().occurrences[&RedactionKind::Secret], 2);
+    }
+
+    #[test]
+    fn honours_disabled_categories_and_rejects_bad_patterns() {
+        let config = RedactionConfig {
+            secrets: false,
+            emails: false,
+            paths: false,
+            patterns: Vec::new(),
+        };
+        let mut redactor = Redactor::new(&config).unwrap();
+        let text = "password = hunter2hunter2 at /etc/app/conf for a@b.io";
+        assert_eq!(redactor.redact(text), text);
+        assert!(redactor.report().is_empty());
+
+        let config = RedactionConfig {
+            patterns: vec!["(unclosed".to_string()],
+            ..RedactionConfig::default()
+        };
+        assert!(Redactor::new(&config).is_err());
+    }
+}
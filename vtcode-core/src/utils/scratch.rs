@@ -0,0 +1,128 @@
+//! Saving files produced by provider-hosted code execution
+//!
+//! Files land in the configured scratch directory under the name the provider
+//! gave them, reduced to a plain file name, or `output-N` with an extension
+//! guessed from the MIME type. Existing files are never overwritten; a numeric
+//! suffix is added instead.
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+use crate::llm::provider::GeneratedFile;
+
+const FALLBACK_STEM: &str = "output";
+
+/// Write `file` into `dir`, creating the directory if needed, and return the
+/// path it was saved to.
+pub fn save_generated_file(dir: &Path, file: &GeneratedFile) -> Result<PathBuf> {
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create scratch directory {}", dir.display()))?;
+
+    let name = file
+        .name
+        .as_deref()
+        .and_then(sanitize_name)
+        .unwrap_or_else(|| {
+            let extension = file
+                .mime_type
+                .as_deref()
+                .and_then(extension_for_mime)
+                .unwrap_or("bin");
+            format!("{FALLBACK_STEM}.{extension}")
+        });
+    let path = unused_path(dir, &name);
+    std::fs::write(&path, &file.data)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// The last path component of `name` with characters that are awkward in
+/// file names replaced.
+fn sanitize_name(name: &str) -> Option<String> {
+    let base = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    let cleaned: String = base
+        .chars()
+        .map(|ch| {
+            if ch.is_alphanumeric() || matches!(ch, '.' | '-' | '_') {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let cleaned = cleaned.trim_start_matches('.');
+    if cleaned.is_empty() {
+        None
+    } else {
+        Some(cleaned.to_string())
+    }
+}
+
+fn extension_for_mime(mime_type: &str) -> Option<&'static str> {
+    let essence = mime_type.split(';').next().unwrap_or(mime_type).trim();
+    Some(match essence {
+        "image/png" => "png",
+        "image/jpeg" => "jpg",
+        "image/gif" => "gif",
+        "image/svg+xml" => "svg",
+        "image/webp" => "webp",
+        "text/csv" => "csv",
+        "text/plain" => "txt",
+        "text/html" => "html",
+        "application/json" => "json",
+        "application/pdf" => "pdf",
+        _ => return None,
+    })
+}
+
+/// `dir/name`, or `dir/stem-N.ext` for the first N that does not exist yet.
+fn unused_path(dir: &Path, name: &str) -> PathBuf {
+    let candidate = dir.join(name);
+    if !candidate.exists() {
+        return candidate;
+    }
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, Some(extension)),
+        _ => (name, None),
+    };
+    (1..)
+        .map(|index| match extension {
+            Some(extension) => dir.join(format!("{stem}-{index}.{extension}")),
+            None => dir.join(format!("{stem}-{index}")),
+        })
+        .find(|path| !path.exists())
+        .expect("an unused file name exists")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn saves_without_overwriting_and_sanitizes_names() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path().join("scratch");
+        let chart = GeneratedFile {
+            name: None,
+            mime_type: Some("image/png".to_string()),
+            data: vec![1, 2, 3],
+        };
+
+        let first = save_generated_file(&dir, &chart).unwrap();
+        let second = save_generated_file(&dir, &chart).unwrap();
+        assert_eq!(first, dir.join("output.png"));
+        assert_eq!(second, dir.join("output-1.png"));
+        assert_eq!(std::fs::read(&second).unwrap(), vec![1, 2, 3]);
+
+        let named = GeneratedFile {
+            name: Some("/mnt/data/../sales report.csv".to_string()),
+            mime_type: None,
+            data: b"a,b\n".to_vec(),
+        };
+        assert_eq!(
+            save_generated_file(&dir, &named).unwrap(),
+            dir.join("sales_report.csv")
+        );
+    }
+}
@@ -1,6 +1,10 @@
-use crate::llm::provider::{Message, MessageRole};
+use crate::core::provenance::Provenance;
+use crate::core::reproducibility::ReproducibilityRecord;
+use crate::llm::provider::{Message, MessageRole, ToolCall};
 use crate::utils::dot_config::DotManager;
-use anyhow::{Context, Result};
+use crate::utils::environment_manifest::EnvironmentManifest;
+use crate::utils::session_title;
+use anyhow::{Context, Result, anyhow};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::env;
@@ -11,6 +15,8 @@ use std::process;
 const SESSION_FILE_PREFIX: &str = "session";
 const SESSION_FILE_EXTENSION: &str = "json";
 pub const SESSION_DIR_ENV: &str = "VT_SESSION_DIR";
+/// Subdirectory of the sessions directory holding redacted copies.
+const REDACTED_DIR: &str = "redacted";
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct SessionArchiveMetadata {
@@ -20,6 +26,9 @@ pub struct SessionArchiveMetadata {
     pub provider: String,
     pub theme: String,
     pub reasoning_effort: String,
+    /// Versions, models and flags the session ran with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<EnvironmentManifest>,
 }
 
 impl SessionArchiveMetadata {
@@ -38,8 +47,14 @@ impl SessionArchiveMetadata {
             provider: provider.into(),
             theme: theme.into(),
             reasoning_effort: reasoning_effort.into(),
+            environment: None,
         }
     }
+
+    pub fn with_environment(mut self, environment: EnvironmentManifest) -> Self {
+        self.environment = Some(environment);
+        self
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -48,6 +63,12 @@ pub struct SessionMessage {
     pub content: String,
     #[serde(default)]
     pub tool_call_id: Option<String>,
+    /// Tool calls made by an assistant message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ToolCall>>,
+    /// Where the content came from, see [`Provenance`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
 }
 
 impl SessionMessage {
@@ -56,6 +77,8 @@ impl SessionMessage {
             role,
             content: content.into(),
             tool_call_id: None,
+            tool_calls: None,
+            provenance: None,
         }
     }
 
@@ -68,6 +91,8 @@ impl SessionMessage {
             role,
             content: content.into(),
             tool_call_id,
+            tool_calls: None,
+            provenance: None,
         }
     }
 }
@@ -78,11 +103,25 @@ impl From<&Message> for SessionMessage {
             role: message.role.clone(),
             content: message.content.clone(),
             tool_call_id: message.tool_call_id.clone(),
+            tool_calls: message.tool_calls.clone(),
+            provenance: message.provenance.clone(),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+impl From<&SessionMessage> for Message {
+    fn from(message: &SessionMessage) -> Self {
+        Self {
+            role: message.role.clone(),
+            content: message.content.clone(),
+            tool_calls: message.tool_calls.clone(),
+            tool_call_id: message.tool_call_id.clone(),
+            provenance: message.provenance.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SessionSnapshot {
     pub metadata: SessionArchiveMetadata,
     pub started_at: DateTime<Utc>,
@@ -92,6 +131,12 @@ pub struct SessionSnapshot {
     pub transcript: Vec<String>,
     #[serde(default)]
     pub messages: Vec<SessionMessage>,
+    /// Short title generated from the first exchange or set with `/rename`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Seeds the session ran with in reproducibility mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reproducibility: Option<ReproducibilityRecord>,
 }
 
 #[derive(Debug, Clone)]
@@ -109,6 +154,23 @@ impl SessionListing {
             .unwrap_or_else(|| self.path.display().to_string())
     }
 
+    /// Stored title, or one derived from the first exchange for sessions
+    /// archived before titles existed.
+    pub fn title(&self) -> Option<String> {
+        if let Some(title) = &self.snapshot.title {
+            return Some(title.clone());
+        }
+        let first = |role: MessageRole| {
+            self.snapshot
+                .messages
+                .iter()
+                .find(|message| message.role == role && !message.content.trim().is_empty())
+                .map(|message| message.content.as_str())
+                .unwrap_or_default()
+        };
+        session_title::title_from_exchange(first(MessageRole::User), first(MessageRole::Assistant))
+    }
+
     pub fn first_prompt_preview(&self) -> Option<String> {
         self.preview_for_role(MessageRole::User)
     }
@@ -179,6 +241,8 @@ pub struct SessionArchive {
     path: PathBuf,
     metadata: SessionArchiveMetadata,
     started_at: DateTime<Utc>,
+    title: Option<String>,
+    reproducibility: Option<ReproducibilityRecord>,
 }
 
 impl SessionArchive {
@@ -191,17 +255,50 @@ impl SessionArchive {
             path,
             metadata,
             started_at,
+            title: None,
+            reproducibility: None,
         })
     }
 
-    pub fn finalize(
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// A separate archive for turns forked off this session, with the same
+    /// metadata and a title naming the branch.
+    pub fn branch(&self, label: &str) -> Result<Self> {
+        let sessions_dir = resolve_sessions_dir()?;
+        let path = generate_unique_archive_path(&sessions_dir, &self.metadata, Utc::now());
+        let title = match &self.title {
+            Some(title) => format!("{title} ({label})"),
+            None => label.to_string(),
+        };
+        Ok(Self {
+            path,
+            metadata: self.metadata.clone(),
+            started_at: self.started_at,
+            title: Some(title),
+            reproducibility: self.reproducibility.clone(),
+        })
+    }
+
+    pub fn set_title(&mut self, title: impl Into<String>) {
+        self.title = Some(title.into());
+    }
+
+    pub fn set_reproducibility(&mut self, record: ReproducibilityRecord) {
+        self.reproducibility = Some(record);
+    }
+
+    /// Snapshot of the session so far, ending now.
+    pub fn snapshot(
         &self,
         transcript: Vec<String>,
         total_messages: usize,
         distinct_tools: Vec<String>,
         messages: Vec<SessionMessage>,
-    ) -> Result<PathBuf> {
-        let snapshot = SessionSnapshot {
+    ) -> SessionSnapshot {
+        SessionSnapshot {
             metadata: self.metadata.clone(),
             started_at: self.started_at,
             ended_at: Utc::now(),
@@ -209,24 +306,89 @@ impl SessionArchive {
             distinct_tools,
             transcript,
             messages,
-        };
-
-        let payload = serde_json::to_string_pretty(&snapshot)
-            .context("failed to serialize session snapshot")?;
-        if let Some(parent) = self.path.parent() {
-            fs::create_dir_all(parent).with_context(|| {
-                format!("failed to create session directory: {}", parent.display())
-            })?;
+            title: self.title.clone(),
+            reproducibility: self.reproducibility.clone(),
         }
-        fs::write(&self.path, payload)
-            .with_context(|| format!("failed to write session archive: {}", self.path.display()))?;
+    }
 
+    pub fn finalize(
+        &self,
+        transcript: Vec<String>,
+        total_messages: usize,
+        distinct_tools: Vec<String>,
+        messages: Vec<SessionMessage>,
+    ) -> Result<PathBuf> {
+        let snapshot = self.snapshot(transcript, total_messages, distinct_tools, messages);
+        write_snapshot(&self.path, &snapshot)?;
         Ok(self.path.clone())
     }
 
     pub fn path(&self) -> &Path {
         &self.path
     }
+
+    /// Identifier shown by `/sessions`, the archive file name without extension.
+    pub fn identifier(&self) -> String {
+        self.path
+            .file_stem()
+            .and_then(|value| value.to_str())
+            .map(|value| value.to_string())
+            .unwrap_or_else(|| self.path.display().to_string())
+    }
+}
+
+/// Write `snapshot` as pretty JSON, creating the parent directory.
+pub fn write_snapshot(path: &Path, snapshot: &SessionSnapshot) -> Result<()> {
+    let payload =
+        serde_json::to_string_pretty(snapshot).context("failed to serialize session snapshot")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create session directory: {}", parent.display()))?;
+    }
+    fs::write(path, payload)
+        .with_context(|| format!("failed to write session archive: {}", path.display()))
+}
+
+/// Find an archived session by identifier, unique identifier prefix, or
+/// `latest` for the most recent one.
+pub fn find_session(id: &str) -> Result<SessionListing> {
+    let id = id.trim().trim_end_matches(".json");
+    let listings = list_recent_sessions(0)?;
+    if id.eq_ignore_ascii_case("latest") {
+        return listings
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("No archived sessions found"));
+    }
+    let mut matches: Vec<SessionListing> = listings
+        .into_iter()
+        .filter(|listing| listing.identifier().starts_with(id))
+        .collect();
+    if let Some(position) = matches
+        .iter()
+        .position(|listing| listing.identifier() == id)
+    {
+        return Ok(matches.swap_remove(position));
+    }
+    match matches.len() {
+        0 => Err(anyhow!(
+            "No archived session matches '{}'. Run /sessions to list them.",
+            id
+        )),
+        1 => Ok(matches.remove(0)),
+        count => Err(anyhow!(
+            "'{}' matches {} sessions; use a longer identifier",
+            id,
+            count
+        )),
+    }
+}
+
+/// Where the redacted copy of session `identifier` is written by default.
+pub fn redacted_copy_path(identifier: &str) -> Result<PathBuf> {
+    Ok(resolve_sessions_dir()?
+        .join(REDACTED_DIR)
+        .join(format!("{}.{}", identifier, SESSION_FILE_EXTENSION)))
 }
 
 pub fn list_recent_sessions(limit: usize) -> Result<Vec<SessionListing>> {
@@ -271,7 +433,9 @@ pub fn list_recent_sessions(limit: usize) -> Result<Vec<SessionListing>> {
     Ok(listings)
 }
 
-fn resolve_sessions_dir() -> Result<PathBuf> {
+/// The sessions directory: `VT_SESSION_DIR` or `~/.vtcode/sessions`, created
+/// if missing.
+pub fn resolve_sessions_dir() -> Result<PathBuf> {
     if let Some(custom) = env::var_os(SESSION_DIR_ENV) {
         let path = PathBuf::from(custom);
         fs::create_dir_all(&path)
@@ -375,7 +539,8 @@ mod tests {
             "dark",
             "medium",
         );
-        let archive = SessionArchive::new(metadata.clone())?;
+        let mut archive = SessionArchive::new(metadata.clone())?;
+        archive.set_title("Greeting");
         let transcript = vec!["line one".to_string(), "line two".to_string()];
         let messages = vec![
             SessionMessage::new(MessageRole::User, "Hello world"),
@@ -398,6 +563,7 @@ mod tests {
         assert_eq!(snapshot.total_messages, 4);
         assert_eq!(snapshot.distinct_tools, vec!["tool_a".to_string()]);
         assert_eq!(snapshot.messages, messages);
+        assert_eq!(snapshot.title.as_deref(), Some("Greeting"));
         Ok(())
     }
 
@@ -417,6 +583,7 @@ mod tests {
 
         let started_at = Utc
             .with_ymd_and_hms(2025, 9, 25, 10, 15, 30)
+            .single()
             .expect("valid datetime")
             .with_nanosecond(123_456_000)
             .expect("nanosecond set");
@@ -450,6 +617,7 @@ mod tests {
 
         let started_at = Utc
             .with_ymd_and_hms(2025, 9, 25, 10, 15, 30)
+            .single()
             .expect("valid datetime")
             .with_nanosecond(654_321_000)
             .expect("nanosecond set");
@@ -536,6 +704,8 @@ mod tests {
                 SessionMessage::new(MessageRole::User, "  prompt line\nsecond"),
                 SessionMessage::new(MessageRole::Assistant, long_response.clone()),
             ],
+            title: None,
+            reproducibility: None,
         };
         let listing = SessionListing {
             path: PathBuf::from("session-workspace.json"),
@@ -548,5 +718,6 @@ mod tests {
         );
         let expected = super::truncate_preview(&long_response, 80);
         assert_eq!(listing.first_reply_preview(), Some(expected));
+        assert_eq!(listing.title(), Some("Prompt line".to_string()));
     }
 }
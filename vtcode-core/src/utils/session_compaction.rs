@@ -0,0 +1,570 @@
+//! Compaction and archival of old sessions
+//!
+//! Every session leaves a full transcript in `~/.vtcode/sessions/`, tool
+//! output included, and a resumable copy in the workspace. Months later they
+//! are rarely reopened but still take space. [`SessionCompactor`] takes each
+//! archived session that ended before the cutoff and:
+//!
+//! - writes an extractive summary (the prompts, the final reply and the tools
+//!   used) in place of the rendered transcript,
+//! - cuts tool results longer than `max_tool_output_chars`,
+//! - gzips what remains into `archive/<YYYY-MM>/<id>.json.gz` and removes the
+//!   original and its redacted copy,
+//! - records it in `archive/index.json`, which [`ArchiveIndex::search`] reads.
+//!
+//! In the workspace, resumable copies (`.vtcode/sessions/`) and command
+//! backups (`.vtcode/backups/commands/`) older than the cutoff belong to
+//! sessions that are long finished, so they are dropped.
+
+use crate::config::SessionsConfig;
+use crate::core::command_backup::BACKUP_DIR;
+use crate::core::session_store::SESSION_STORE_DIR;
+use crate::llm::provider::MessageRole;
+use crate::utils::session_archive::{SessionMessage, SessionSnapshot};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Subdirectory of the sessions directory holding compacted sessions.
+pub const ARCHIVE_DIR: &str = "archive";
+const INDEX_FILE: &str = "index.json";
+const REDACTED_DIR: &str = "redacted";
+/// Automatic runs are at most this far apart.
+const AUTO_INTERVAL_HOURS: i64 = 24;
+const PROMPT_CHARS: usize = 200;
+const LATER_PROMPTS: usize = 4;
+const REPLY_CHARS: usize = 400;
+
+/// One compacted session in the archive index.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ArchivedSession {
+    pub identifier: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub workspace_label: String,
+    pub workspace_path: String,
+    pub provider: String,
+    pub model: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    pub total_messages: usize,
+    pub tools: Vec<String>,
+    pub summary: String,
+    /// Compressed snapshot, relative to the archive directory
+    pub file: String,
+    pub original_bytes: u64,
+    pub archived_bytes: u64,
+}
+
+impl ArchivedSession {
+    fn haystack(&self) -> String {
+        [
+            self.identifier.as_str(),
+            self.title.as_deref().unwrap_or_default(),
+            &self.workspace_label,
+            &self.workspace_path,
+            &self.model,
+            &self.summary,
+            &self.tools.join(" "),
+        ]
+        .join("\n")
+        .to_lowercase()
+    }
+}
+
+/// `archive/index.json`: the compacted sessions and when compaction last ran.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArchiveIndex {
+    #[serde(default)]
+    pub last_compacted_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub sessions: Vec<ArchivedSession>,
+}
+
+impl ArchiveIndex {
+    /// The index under `sessions_dir`, empty when there is none yet.
+    pub fn load(sessions_dir: &Path) -> Result<Self> {
+        let path = sessions_dir.join(ARCHIVE_DIR).join(INDEX_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let raw = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        serde_json::from_str(&raw).with_context(|| format!("failed to parse {}", path.display()))
+    }
+
+    fn save(&self, sessions_dir: &Path) -> Result<()> {
+        let dir = sessions_dir.join(ARCHIVE_DIR);
+        fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+        let path = dir.join(INDEX_FILE);
+        let temp = path.with_extension("json.tmp");
+        fs::write(&temp, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("failed to write {}", temp.display()))?;
+        fs::rename(&temp, &path).with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// Whether an automatic run is due.
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.last_compacted_at
+            .is_none_or(|last| now - last >= Duration::hours(AUTO_INTERVAL_HOURS))
+    }
+
+    /// Sessions whose title, summary, workspace, model or tools contain every
+    /// word of `query`, newest first.
+    pub fn search(&self, query: &str) -> Vec<&ArchivedSession> {
+        let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        let mut found: Vec<&ArchivedSession> = self
+            .sessions
+            .iter()
+            .filter(|session| {
+                let haystack = session.haystack();
+                words.iter().all(|word| haystack.contains(word.as_str()))
+            })
+            .collect();
+        found.sort_by(|a, b| b.ended_at.cmp(&a.ended_at));
+        found
+    }
+}
+
+/// The automatic run made at session start: compacts when `[sessions]
+/// auto_compact` is on and the last run is a day old, otherwise does nothing.
+pub fn compact_if_due(
+    sessions_dir: PathBuf,
+    config: &SessionsConfig,
+    workspace: PathBuf,
+) -> Result<Option<CompactionReport>> {
+    if !config.auto_compact || !ArchiveIndex::load(&sessions_dir)?.is_due(Utc::now()) {
+        return Ok(None);
+    }
+    SessionCompactor::new(sessions_dir, config)
+        .with_workspace(workspace)
+        .run()
+        .map(Some)
+}
+
+/// Read back the compacted snapshot of `session`.
+pub fn read_archived(sessions_dir: &Path, session: &ArchivedSession) -> Result<SessionSnapshot> {
+    let path = sessions_dir.join(ARCHIVE_DIR).join(&session.file);
+    let file =
+        fs::File::open(&path).with_context(|| format!("failed to open {}", path.display()))?;
+    let mut raw = String::new();
+    GzDecoder::new(file)
+        .read_to_string(&mut raw)
+        .with_context(|| format!("failed to decompress {}", path.display()))?;
+    serde_json::from_str(&raw).with_context(|| format!("failed to parse {}", path.display()))
+}
+
+/// What a compaction run changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompactionReport {
+    /// Sessions moved into the archive
+    pub compacted: usize,
+    /// Resumable copies, redacted copies and command backups removed
+    pub dropped: usize,
+    /// Size of the compacted sessions before and after
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+    /// Bytes freed by dropped files
+    pub bytes_dropped: u64,
+}
+
+impl CompactionReport {
+    pub fn bytes_freed(&self) -> u64 {
+        (self.bytes_before + self.bytes_dropped).saturating_sub(self.bytes_after)
+    }
+}
+
+/// Compacts the sessions that ended before a cutoff.
+pub struct SessionCompactor {
+    sessions_dir: PathBuf,
+    workspace: Option<PathBuf>,
+    cutoff: DateTime<Utc>,
+    prune_before: Option<DateTime<Utc>>,
+    max_tool_output_chars: usize,
+}
+
+impl SessionCompactor {
+    pub fn new(sessions_dir: PathBuf, config: &SessionsConfig) -> Self {
+        let cutoff = i64::try_from(config.compact_after_days)
+            .ok()
+            .and_then(Duration::try_days)
+            .and_then(|age| Utc::now().checked_sub_signed(age))
+            .unwrap_or(DateTime::<Utc>::MIN_UTC);
+        Self {
+            sessions_dir,
+            workspace: None,
+            cutoff,
+            prune_before: None,
+            max_tool_output_chars: config.max_tool_output_chars,
+        }
+    }
+
+    /// Also drop the old resumable copies and command backups of `workspace`.
+    pub fn with_workspace(mut self, workspace: PathBuf) -> Self {
+        self.workspace = Some(workspace);
+        self
+    }
+
+    /// Also delete archived sessions that ended more than `age` ago.
+    pub fn prune_older_than(mut self, age: std::time::Duration) -> Self {
+        self.prune_before = Duration::from_std(age)
+            .ok()
+            .and_then(|age| Utc::now().checked_sub_signed(age));
+        self
+    }
+
+    pub fn run(&self) -> Result<CompactionReport> {
+        let mut report = CompactionReport::default();
+        let mut index = ArchiveIndex::load(&self.sessions_dir)?;
+        let mut originals = Vec::new();
+
+        for path in session_files(&self.sessions_dir)? {
+            let Some(snapshot) = fs::read_to_string(&path)
+                .ok()
+                .and_then(|raw| serde_json::from_str::<SessionSnapshot>(&raw).ok())
+            else {
+                // Unreadable sessions are left to `vtcode maintenance`.
+                continue;
+            };
+            if snapshot.ended_at >= self.cutoff {
+                continue;
+            }
+            let identifier = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or_default()
+                .to_string();
+            let entry = self.archive(&identifier, &path, snapshot)?;
+            report.compacted += 1;
+            report.bytes_before += entry.original_bytes;
+            report.bytes_after += entry.archived_bytes;
+            index
+                .sessions
+                .retain(|session| session.identifier != entry.identifier);
+            index.sessions.push(entry);
+            originals.push(path);
+        }
+
+        if let Some(cutoff) = self.prune_before {
+            let archive = self.sessions_dir.join(ARCHIVE_DIR);
+            let mut kept = Vec::new();
+            for session in std::mem::take(&mut index.sessions) {
+                if session.ended_at >= cutoff {
+                    kept.push(session);
+                    continue;
+                }
+                let path = archive.join(&session.file);
+                if path.exists() {
+                    self.drop_file(&path, &mut report)?;
+                }
+            }
+            index.sessions = kept;
+        }
+
+        index.last_compacted_at = Some(Utc::now());
+        index.save(&self.sessions_dir)?;
+
+        // Originals go only once the index lists their archived copies.
+        for path in originals {
+            let redacted = path.parent().map(|dir| {
+                dir.join(REDACTED_DIR)
+                    .join(path.file_name().unwrap_or_default())
+            });
+            fs::remove_file(&path)
+                .with_context(|| format!("failed to remove {}", path.display()))?;
+            if let Some(redacted) = redacted.filter(|redacted| redacted.exists()) {
+                self.drop_file(&redacted, &mut report)?;
+            }
+        }
+
+        if let Some(workspace) = &self.workspace {
+            for dir in [SESSION_STORE_DIR, BACKUP_DIR] {
+                self.drop_older(&workspace.join(dir), &mut report)?;
+            }
+        }
+        Ok(report)
+    }
+
+    fn archive(
+        &self,
+        identifier: &str,
+        path: &Path,
+        mut snapshot: SessionSnapshot,
+    ) -> Result<ArchivedSession> {
+        let original_bytes = fs::metadata(path).map_or(0, |meta| meta.len());
+        let summary = summarize(&snapshot.messages);
+        snapshot.transcript = summary.lines().map(str::to_string).collect();
+        for message in &mut snapshot.messages {
+            if message.role == MessageRole::Tool {
+                cut(&mut message.content, self.max_tool_output_chars);
+            }
+        }
+
+        let file = format!("{}/{identifier}.json.gz", snapshot.ended_at.format("%Y-%m"));
+        let target = self.sessions_dir.join(ARCHIVE_DIR).join(&file);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&serde_json::to_vec(&snapshot)?)?;
+        let compressed = encoder.finish()?;
+        fs::write(&target, &compressed)
+            .with_context(|| format!("failed to write {}", target.display()))?;
+
+        let metadata = &snapshot.metadata;
+        Ok(ArchivedSession {
+            identifier: identifier.to_string(),
+            title: snapshot.title.clone(),
+            workspace_label: metadata.workspace_label.clone(),
+            workspace_path: metadata.workspace_path.clone(),
+            provider: metadata.provider.clone(),
+            model: metadata.model.clone(),
+            started_at: snapshot.started_at,
+            ended_at: snapshot.ended_at,
+            total_messages: snapshot.total_messages,
+            tools: snapshot.distinct_tools.clone(),
+            summary,
+            file,
+            original_bytes,
+            archived_bytes: compressed.len() as u64,
+        })
+    }
+
+    fn drop_older(&self, dir: &Path, report: &mut CompactionReport) -> Result<()> {
+        if !dir.is_dir() {
+            return Ok(());
+        }
+        for entry in
+            fs::read_dir(dir).with_context(|| format!("failed to read {}", dir.display()))?
+        {
+            let path = entry?.path();
+            let modified = fs::metadata(&path).and_then(|meta| meta.modified());
+            if modified.is_ok_and(|modified| DateTime::<Utc>::from(modified) < self.cutoff) {
+                self.drop_file(&path, report)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn drop_file(&self, path: &Path, report: &mut CompactionReport) -> Result<()> {
+        let size = dir_size(path);
+        if path.is_dir() {
+            fs::remove_dir_all(path)
+        } else {
+            fs::remove_file(path)
+        }
+        .with_context(|| format!("failed to remove {}", path.display()))?;
+        report.dropped += 1;
+        report.bytes_dropped += size;
+        Ok(())
+    }
+}
+
+/// The first prompt, a few later ones, the last reply and its length, as
+/// plain lines.
+fn summarize(messages: &[SessionMessage]) -> String {
+    let prompts: Vec<&str> = messages
+        .iter()
+        .filter(|message| message.role == MessageRole::User)
+        .map(|message| message.content.trim())
+        .filter(|content| !content.is_empty())
+        .collect();
+    let mut lines = Vec::new();
+    if let Some(first) = prompts.first() {
+        lines.push(format!("Asked: {}", one_line(first, PROMPT_CHARS)));
+    }
+    for prompt in prompts.iter().skip(1).take(LATER_PROMPTS) {
+        lines.push(format!("Then: {}", one_line(prompt, PROMPT_CHARS)));
+    }
+    if prompts.len() > LATER_PROMPTS + 1 {
+        lines.push(format!(
+            "… {} more prompts",
+            prompts.len() - LATER_PROMPTS - 1
+        ));
+    }
+    let reply = messages.iter().rev().find(|message| {
+        message.role == MessageRole::Assistant && !message.content.trim().is_empty()
+    });
+    if let Some(reply) = reply {
+        lines.push(format!(
+            "Last reply: {}",
+            one_line(&reply.content, REPLY_CHARS)
+        ));
+    }
+    lines.join("\n")
+}
+
+fn one_line(text: &str, max_chars: usize) -> String {
+    let joined = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if joined.chars().count() <= max_chars {
+        return joined;
+    }
+    let mut cut: String = joined.chars().take(max_chars.saturating_sub(1)).collect();
+    cut.push('…');
+    cut
+}
+
+fn cut(content: &mut String, max_chars: usize) {
+    let total = content.chars().count();
+    if total <= max_chars {
+        return;
+    }
+    let kept: String = content.chars().take(max_chars).collect();
+    *content = format!(
+        "{kept}\n[… {} more characters removed by session compaction]",
+        total - max_chars
+    );
+}
+
+fn session_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| format!("failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        let is_session = path.is_file()
+            && path.extension().and_then(|ext| ext.to_str()) == Some("json")
+            && path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("session"));
+        if is_session {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn dir_size(path: &Path) -> u64 {
+    if !path.is_dir() {
+        return fs::symlink_metadata(path).map_or(0, |meta| meta.len());
+    }
+    fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| dir_size(&entry.path()))
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::session_archive::SessionArchiveMetadata;
+
+    fn snapshot(ended_at: DateTime<Utc>, tool_output: &str) -> SessionSnapshot {
+        SessionSnapshot {
+            metadata: SessionArchiveMetadata::new(
+                "app",
+                "/work/app",
+                "model",
+                "provider",
+                "dark",
+                "low",
+            ),
+            started_at: ended_at,
+            ended_at,
+            total_messages: 3,
+            distinct_tools: vec!["run_terminal_cmd".to_string()],
+            transcript: vec!["rendered line".to_string(); 50],
+            messages: vec![
+                SessionMessage::new(MessageRole::User, "Why does the flaky parser test fail?"),
+                SessionMessage::new(MessageRole::Tool, tool_output),
+                SessionMessage::new(
+                    MessageRole::Assistant,
+                    "The fixture depends on HashMap order.",
+                ),
+            ],
+            title: Some("Flaky parser test".to_string()),
+            reproducibility: None,
+        }
+    }
+
+    #[test]
+    fn compacts_old_sessions_into_a_searchable_archive() {
+        let temp = tempfile::tempdir().unwrap();
+        let sessions = temp.path().join("sessions");
+        let workspace = temp.path().join("work");
+        fs::create_dir_all(sessions.join(REDACTED_DIR)).unwrap();
+        fs::create_dir_all(workspace.join(SESSION_STORE_DIR)).unwrap();
+
+        let old = Utc::now() - Duration::days(90);
+        let log = "test output line\n".repeat(500);
+        let write = |name: &str, snapshot: &SessionSnapshot| {
+            fs::write(
+                sessions.join(name),
+                serde_json::to_string_pretty(snapshot).unwrap(),
+            )
+            .unwrap();
+        };
+        write("session-app-old.json", &snapshot(old, &log));
+        write("session-app-new.json", &snapshot(Utc::now(), &log));
+        fs::write(
+            sessions.join(REDACTED_DIR).join("session-app-old.json"),
+            "{}",
+        )
+        .unwrap();
+        let resumable = workspace.join(SESSION_STORE_DIR).join("abc.json");
+        fs::write(&resumable, "{}").unwrap();
+        let old_time = std::time::SystemTime::now() - std::time::Duration::from_secs(90 * 86_400);
+        fs::File::options()
+            .write(true)
+            .open(&resumable)
+            .unwrap()
+            .set_modified(old_time)
+            .unwrap();
+
+        let report = SessionCompactor::new(sessions.clone(), &SessionsConfig::default())
+            .with_workspace(workspace.clone())
+            .run()
+            .unwrap();
+        assert_eq!(report.compacted, 1, "{report:?}");
+        assert_eq!(report.dropped, 2, "{report:?}");
+        assert!(report.bytes_after * 4 < report.bytes_before, "{report:?}");
+        assert!(!sessions.join("session-app-old.json").exists());
+        assert!(sessions.join("session-app-new.json").exists());
+        assert!(!resumable.exists());
+
+        let index = ArchiveIndex::load(&sessions).unwrap();
+        assert!(!index.is_due(Utc::now()));
+        let found = index.search("FLAKY parser");
+        assert_eq!(found.len(), 1);
+        assert!(
+            found[0]
+                .summary
+                .starts_with("Asked: Why does the flaky parser test fail?")
+        );
+        assert!(
+            found[0]
+                .summary
+                .contains("Last reply: The fixture depends on HashMap order.")
+        );
+        assert!(index.search("tokenizer").is_empty());
+
+        let archived = read_archived(&sessions, found[0]).unwrap();
+        assert_eq!(archived.messages.len(), 3);
+        assert!(
+            archived.messages[1]
+                .content
+                .contains("removed by session compaction")
+        );
+        assert_eq!(archived.transcript.len(), 2);
+
+        let rerun = SessionCompactor::new(sessions.clone(), &SessionsConfig::default())
+            .run()
+            .unwrap();
+        assert_eq!(rerun.compacted, 0);
+        assert_eq!(ArchiveIndex::load(&sessions).unwrap().sessions.len(), 1);
+    }
+}
@@ -0,0 +1,153 @@
+//! Short human-readable session titles
+//!
+//! Titles are derived locally from the first exchange: the first meaningful
+//! line of the prompt with conversational filler removed, or the opening of
+//! the reply when the prompt is too vague to describe the session ("hi",
+//! "continue").
+
+/// Longest title kept, in characters.
+pub const MAX_TITLE_CHARS: usize = 48;
+
+const PROMPT_FILLER: &[&str] = &[
+    "hey",
+    "hi",
+    "hello",
+    "ok",
+    "okay",
+    "so",
+    "please",
+    "can you",
+    "could you",
+    "would you",
+    "will you",
+    "i want to",
+    "i want you to",
+    "i need to",
+    "i need you to",
+    "i'd like to",
+    "i would like to",
+    "help me",
+    "let's",
+    "lets",
+];
+
+const REPLY_FILLER: &[&str] = &[
+    "sure",
+    "okay",
+    "ok",
+    "certainly",
+    "of course",
+    "i'll",
+    "i will",
+    "let me",
+    "here's",
+    "here is",
+];
+
+/// Title for a session from its first prompt and reply.
+pub fn title_from_exchange(prompt: &str, reply: &str) -> Option<String> {
+    let from_prompt = first_line(prompt).map(|line| strip_filler(&line, PROMPT_FILLER));
+    if let Some(line) = from_prompt.as_deref()
+        && line.split_whitespace().count() >= 2
+    {
+        return normalize_title(line);
+    }
+    first_line(reply)
+        .map(|line| strip_filler(&line, REPLY_FILLER))
+        .and_then(|line| normalize_title(&line))
+        .or_else(|| from_prompt.and_then(|line| normalize_title(&line)))
+}
+
+/// Clean up a title: single spaces, no surrounding punctuation, a leading
+/// capital, and at most [`MAX_TITLE_CHARS`] characters cut at a word boundary.
+pub fn normalize_title(raw: &str) -> Option<String> {
+    let cleaned: String = raw
+        .chars()
+        .filter(|ch| !matches!(ch, '`' | '*' | '#' | '"'))
+        .collect();
+    let mut title = String::new();
+    for word in cleaned.split_whitespace() {
+        let extra = if title.is_empty() { 0 } else { 1 };
+        if title.chars().count() + extra + word.chars().count() > MAX_TITLE_CHARS {
+            if title.is_empty() {
+                title = word.chars().take(MAX_TITLE_CHARS).collect();
+            }
+            break;
+        }
+        if extra == 1 {
+            title.push(' ');
+        }
+        title.push_str(word);
+    }
+    let title = title.trim_matches(|ch: char| ch.is_ascii_punctuation() && ch != ')');
+    let mut chars = title.chars();
+    let first = chars.next()?;
+    Some(first.to_uppercase().chain(chars).collect())
+}
+
+fn first_line(text: &str) -> Option<String> {
+    let mut in_code = false;
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code || trimmed.is_empty() || trimmed.starts_with('/') {
+            continue;
+        }
+        return Some(trimmed.to_string());
+    }
+    None
+}
+
+/// Drop leading filler phrases and anything up to the first sentence break.
+fn strip_filler(line: &str, filler: &[&str]) -> String {
+    let sentence = line
+        .split_inclusive(['.', '?', '!', '\n'])
+        .find(|part| part.split_whitespace().count() > 1)
+        .unwrap_or(line);
+    let mut rest = sentence.trim();
+    loop {
+        let lower = rest.to_lowercase();
+        let Some(after) = filler.iter().find_map(|phrase| {
+            let after = rest.get(phrase.len()..)?;
+            let matches = lower.starts_with(*phrase)
+                && after.chars().next().is_none_or(|ch| !ch.is_alphanumeric());
+            matches.then_some(after)
+        }) else {
+            break;
+        };
+        rest = after.trim_start_matches(|ch: char| ch == ',' || ch.is_whitespace());
+    }
+    rest.trim_end_matches(|ch: char| ch.is_ascii_punctuation() || ch.is_whitespace())
+        .trim_end_matches(" please")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn titles_come_from_the_prompt_or_the_reply() {
+        assert_eq!(
+            title_from_exchange("Hey, can you fix the flaky retry test? It fails on CI.", "")
+                .as_deref(),
+            Some("Fix the flaky retry test")
+        );
+        assert_eq!(
+            title_from_exchange(
+                "continue",
+                "Sure! I'll add pagination to the sessions list."
+            )
+            .as_deref(),
+            Some("Add pagination to the sessions list")
+        );
+        assert_eq!(title_from_exchange("hi", "").as_deref(), None);
+        assert_eq!(
+            normalize_title("  **Release   prep**  ").as_deref(),
+            Some("Release prep")
+        );
+    }
+}
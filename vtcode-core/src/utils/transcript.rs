@@ -1,9 +1,12 @@
 use once_cell::sync::Lazy;
 use parking_lot::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 const MAX_LINES: usize = 4000;
 
 static TRANSCRIPT: Lazy<RwLock<Vec<String>>> = Lazy::new(|| RwLock::new(Vec::new()));
+/// Bumped on every change so watchers can skip unchanged snapshots.
+static REVISION: AtomicU64 = AtomicU64::new(0);
 
 pub fn append(line: &str) {
     let mut log = TRANSCRIPT.write();
@@ -12,6 +15,7 @@ pub fn append(line: &str) {
         log.drain(0..drop_count);
     }
     log.push(line.to_string());
+    REVISION.fetch_add(1, Ordering::Relaxed);
 }
 
 pub fn replace_last(count: usize, lines: &[String]) {
@@ -27,6 +31,7 @@ pub fn replace_last(count: usize, lines: &[String]) {
         }
         log.push(line.clone());
     }
+    REVISION.fetch_add(1, Ordering::Relaxed);
 }
 
 pub fn snapshot() -> Vec<String> {
@@ -39,6 +44,11 @@ pub fn len() -> usize {
 
 pub fn clear() {
     TRANSCRIPT.write().clear();
+    REVISION.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn revision() -> u64 {
+    REVISION.load(Ordering::Relaxed)
 }
 
 #[cfg(test)]
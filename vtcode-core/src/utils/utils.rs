@@ -3,7 +3,7 @@
 //! This module contains common utility functions that are used across different parts
 //! of the VTCode agent, helping to reduce code duplication and improve maintainability.
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use console::style;
 use regex::Regex;
 use std::fs;
@@ -239,3 +239,11 @@ pub fn safe_replace_text(
 
     Ok(content.replace(old_str, new_str))
 }
+
+/// Replace `path` with `data` by writing a sibling `.tmp` file and renaming
+/// it over the target, so readers never see a partial file.
+pub fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, data).with_context(|| format!("failed to write {}", tmp.display()))?;
+    fs::rename(&tmp, path).with_context(|| format!("failed to replace {}", path.display()))
+}
@@ -0,0 +1,171 @@
+//! Lexical resolution of model-supplied paths against the workspace
+//!
+//! Tool arguments come straight from model output, so a path may be relative
+//! or absolute, contain `.` and `..`, or be garbage. [`resolve_in_workspace`]
+//! turns it into a clean absolute path under the workspace without touching the
+//! filesystem, and refuses anything that climbs out of it. Symlinks are not
+//! followed; callers that need that still canonicalize the result.
+
+use anyhow::{Result, anyhow};
+use std::path::{Component, Path, PathBuf};
+
+/// Resolve `raw` against `workspace`, collapsing `.` and `..`.
+///
+/// The result always starts with `workspace` and contains no `.` or `..`
+/// components. Paths that escape the workspace, absolute paths elsewhere and
+/// paths containing NUL bytes are rejected.
+pub fn resolve_in_workspace(workspace: &Path, raw: &str) -> Result<PathBuf> {
+    if raw.contains('\0') {
+        return Err(anyhow!("Path contains a NUL byte"));
+    }
+    let root = normalize(workspace);
+    let candidate = Path::new(raw);
+    let relative = if candidate.is_absolute() {
+        let absolute = normalize(candidate);
+        match absolute.strip_prefix(&root) {
+            Ok(relative) => relative.to_path_buf(),
+            Err(_) => {
+                return Err(anyhow!(
+                    "Path {} is outside workspace root {}",
+                    raw,
+                    root.display()
+                ));
+            }
+        }
+    } else {
+        candidate.to_path_buf()
+    };
+
+    let mut resolved = root.clone();
+    let mut depth = 0usize;
+    for component in relative.components() {
+        match component {
+            Component::Normal(part) => {
+                resolved.push(part);
+                depth += 1;
+            }
+            Component::ParentDir if depth > 0 => {
+                resolved.pop();
+                depth -= 1;
+            }
+            Component::ParentDir => {
+                return Err(anyhow!(
+                    "Path {} is outside workspace root {}",
+                    raw,
+                    root.display()
+                ));
+            }
+            Component::CurDir | Component::RootDir | Component::Prefix(_) => {}
+        }
+    }
+    Ok(resolved)
+}
+
+/// Absolute, normalized form of `raw` without the workspace boundary:
+/// relative paths are taken from `workspace` and a leading `~/` from the home
+/// directory. Used where a path outside the workspace may be allowed
+/// explicitly.
+pub fn absolute_path(workspace: &Path, raw: &str) -> Result<PathBuf> {
+    if raw.contains('\0') {
+        return Err(anyhow!("Path contains a NUL byte"));
+    }
+    let expanded = match raw.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()
+            .ok_or_else(|| anyhow!("Could not determine home directory for {}", raw))?
+            .join(rest),
+        None => PathBuf::from(raw),
+    };
+    Ok(normalize(&workspace.join(expanded)))
+}
+
+/// `path` with `.` dropped and `..` applied, never climbing above the root.
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push(component);
+                }
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn workspace() -> PathBuf {
+        PathBuf::from("/work/space")
+    }
+
+    fn segment() -> impl Strategy<Value = String> {
+        prop_oneof![
+            Just("..".to_string()),
+            Just(".".to_string()),
+            Just(String::new()),
+            "[a-z]{1,3}",
+        ]
+    }
+
+    #[test]
+    fn resolves_relative_and_absolute_paths() -> Result<()> {
+        let ws = workspace();
+        assert_eq!(
+            resolve_in_workspace(&ws, "src/./lib/../main.rs")?,
+            ws.join("src/main.rs")
+        );
+        assert_eq!(
+            resolve_in_workspace(&ws, "/work/space/a/../b")?,
+            ws.join("b")
+        );
+        assert_eq!(resolve_in_workspace(&ws, "")?, ws);
+        assert!(resolve_in_workspace(&ws, "../space/secret").is_err());
+        assert!(resolve_in_workspace(&ws, "/work/space/../other").is_err());
+        assert!(resolve_in_workspace(&ws, "/etc/passwd").is_err());
+        assert!(resolve_in_workspace(&ws, "a\0b").is_err());
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn never_panics_on_arbitrary_input(raw in any::<String>()) {
+            let _ = resolve_in_workspace(&workspace(), &raw);
+        }
+
+        #[test]
+        fn stays_inside_the_workspace(segments in prop::collection::vec(segment(), 0..12)) {
+            let ws = workspace();
+            let raw = format!("./{}", segments.join("/"));
+            let mut depth: isize = 0;
+            let mut escapes = false;
+            for segment in &segments {
+                match segment.as_str() {
+                    ".." => depth -= 1,
+                    "." | "" => {}
+                    _ => depth += 1,
+                }
+                escapes |= depth < 0;
+            }
+
+            match resolve_in_workspace(&ws, &raw) {
+                Ok(resolved) => {
+                    prop_assert!(!escapes);
+                    prop_assert!(resolved.starts_with(&ws));
+                    let clean = resolved.components().all(|component| {
+                        !matches!(component, Component::ParentDir | Component::CurDir)
+                    });
+                    prop_assert!(clean);
+                    let again = resolve_in_workspace(&ws, &resolved.to_string_lossy());
+                    prop_assert_eq!(again.ok(), Some(resolved));
+                }
+                Err(_) => prop_assert!(escapes),
+            }
+        }
+    }
+}
@@ -0,0 +1,15 @@
+//! Push-to-talk voice input
+//!
+//! Enabled by the `voice` feature. [`Recording`] captures the default
+//! microphone until it is stopped, [`encode_wav`] turns the capture into the
+//! 16 kHz mono WAV that whisper models expect, and [`Transcriber`] turns that
+//! into text with the backend chosen under `[ui.voice]`. The transcript goes
+//! into the prompt for review; nothing is sent on its own.
+
+mod record;
+mod transcribe;
+mod wav;
+
+pub use record::{Audio, Recording};
+pub use transcribe::Transcriber;
+pub use wav::encode_wav;
@@ -0,0 +1,128 @@
+//! Capturing the default microphone
+//!
+//! A cpal stream cannot move between threads, so each recording owns a thread
+//! that opens the stream, keeps it running until it is told to stop or the
+//! time limit passes, and hands back the samples.
+
+use anyhow::{Context, Result, anyhow, bail};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SampleFormat, SizedSample, Stream, StreamConfig};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Interleaved samples as captured, scaled to `-1.0..=1.0`.
+#[derive(Debug, Clone, Default)]
+pub struct Audio {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+impl Audio {
+    pub fn duration(&self) -> Duration {
+        let frames = self.samples.len() / usize::from(self.channels.max(1));
+        Duration::from_secs_f64(frames as f64 / f64::from(self.sample_rate.max(1)))
+    }
+}
+
+/// A recording in progress.
+pub struct Recording {
+    stop: Sender<()>,
+    thread: JoinHandle<Result<Audio>>,
+}
+
+impl Recording {
+    /// Start capturing the default input device. Capture ends by itself after
+    /// `limit`; the audio up to then is kept until [`Recording::stop`].
+    pub fn start(limit: Duration) -> Result<Self> {
+        let (stop, stopped) = mpsc::channel();
+        let (ready, started) = mpsc::channel();
+        let thread = std::thread::Builder::new()
+            .name("vtcode-voice".to_string())
+            .spawn(move || {
+                let (stream, audio) = open_stream()?;
+                stream
+                    .play()
+                    .context("Error: Could not start recording from the microphone")?;
+                let _ = ready.send(());
+                // A stop request, the time limit and a dropped handle all end it.
+                let _ = stopped.recv_timeout(limit);
+                drop(stream);
+                let audio =
+                    std::mem::take(&mut *audio.lock().unwrap_or_else(|err| err.into_inner()));
+                Ok(audio)
+            })
+            .context("Error: Could not start the recording thread")?;
+        if started.recv().is_err() {
+            // The thread gave up before the stream ran; report why.
+            return Err(match thread.join() {
+                Ok(Err(err)) => err,
+                _ => anyhow!("Error: Recording stopped before it started"),
+            });
+        }
+        Ok(Self { stop, thread })
+    }
+
+    /// Stop capturing and return what was recorded. Blocks until the stream
+    /// is closed.
+    pub fn stop(self) -> Result<Audio> {
+        let _ = self.stop.send(());
+        self.thread
+            .join()
+            .map_err(|_| anyhow!("Error: The recording thread panicked"))?
+    }
+}
+
+fn open_stream() -> Result<(Stream, Arc<Mutex<Audio>>)> {
+    let device = cpal::default_host().default_input_device().context(
+        "Error: No microphone found. Check that an input device is connected and allowed.",
+    )?;
+    let supported = device
+        .default_input_config()
+        .context("Error: The microphone did not report a usable format")?;
+    let format = supported.sample_format();
+    let config: StreamConfig = supported.into();
+    let audio = Arc::new(Mutex::new(Audio {
+        samples: Vec::new(),
+        sample_rate: config.sample_rate.0,
+        channels: config.channels,
+    }));
+    let stream = match format {
+        SampleFormat::F32 => build::<f32>(&device, &config, &audio),
+        SampleFormat::I16 => build::<i16>(&device, &config, &audio),
+        SampleFormat::U16 => build::<u16>(&device, &config, &audio),
+        SampleFormat::I32 => build::<i32>(&device, &config, &audio),
+        SampleFormat::I8 => build::<i8>(&device, &config, &audio),
+        SampleFormat::U8 => build::<u8>(&device, &config, &audio),
+        other => bail!("Error: The microphone's sample format {other} is not supported"),
+    }?;
+    Ok((stream, audio))
+}
+
+fn build<T>(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    audio: &Arc<Mutex<Audio>>,
+) -> Result<Stream>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let audio = Arc::clone(audio);
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _| {
+                if let Ok(mut audio) = audio.lock() {
+                    audio
+                        .samples
+                        .extend(data.iter().map(|sample| sample.to_sample::<f32>()));
+                }
+            },
+            |err| tracing::warn!("voice input stream error: {err}"),
+            None,
+        )
+        .context("Error: Could not open the microphone")
+}
@@ -0,0 +1,195 @@
+//! Turning recorded speech into text
+//!
+//! `whisper_cpp` runs a local whisper.cpp build on a temporary WAV file, so
+//! audio stays on the machine. `openai` uploads it to an OpenAI-compatible
+//! `/audio/transcriptions` endpoint, which also covers local servers that
+//! speak that API.
+
+use crate::config::constants::urls;
+use crate::config::{TranscriptionBackend, VoiceConfig};
+use crate::utils::network;
+use anyhow::{Context, Result, bail};
+use reqwest::Client;
+use serde::Deserialize;
+use std::io::Write;
+use tokio::process::Command;
+
+const DEFAULT_OPENAI_KEY_ENV: &str = "OPENAI_API_KEY";
+const MULTIPART_BOUNDARY: &str = "vtcode-voice-3f9a1c7e";
+
+#[derive(Debug, Clone)]
+pub enum Transcriber {
+    WhisperCpp {
+        command: String,
+        model: String,
+        language: Option<String>,
+    },
+    OpenAI {
+        client: Client,
+        url: String,
+        model: String,
+        api_key: Option<String>,
+        language: Option<String>,
+    },
+}
+
+impl Transcriber {
+    pub fn from_config(config: &VoiceConfig) -> Result<Self> {
+        let language = config
+            .language
+            .as_deref()
+            .map(str::trim)
+            .filter(|language| !language.is_empty())
+            .map(str::to_string);
+        Ok(match config.backend {
+            TranscriptionBackend::WhisperCpp => {
+                let Some(model) = config.whisper_model.clone() else {
+                    bail!(
+                        "Error: Set whisper_model under [ui.voice] to the ggml model whisper.cpp should load, or choose backend = \"openai\"."
+                    );
+                };
+                Self::WhisperCpp {
+                    command: config.whisper_command.clone(),
+                    model,
+                    language,
+                }
+            }
+            TranscriptionBackend::OpenAI => {
+                let base_url = config
+                    .base_url
+                    .as_deref()
+                    .map(|url| url.trim().trim_end_matches('/'));
+                let key_env = config
+                    .api_key_env
+                    .as_deref()
+                    .unwrap_or(DEFAULT_OPENAI_KEY_ENV);
+                let api_key = std::env::var(key_env).ok().filter(|key| !key.is_empty());
+                if api_key.is_none() && base_url.is_none() {
+                    bail!(
+                        "Error: Voice input uses OpenAI transcription but {key_env} is not set. Set it, or choose backend = \"whisper_cpp\" under [ui.voice]."
+                    );
+                }
+                Self::OpenAI {
+                    client: network::client(Some("openai"))?,
+                    url: format!(
+                        "{}/audio/transcriptions",
+                        base_url.unwrap_or(urls::OPENAI_API_BASE)
+                    ),
+                    model: config.model.clone(),
+                    api_key,
+                    language,
+                }
+            }
+        })
+    }
+
+    /// The text spoken in `wav`, trimmed; empty when nothing was heard.
+    pub async fn transcribe(&self, wav: Vec<u8>) -> Result<String> {
+        let text = match self {
+            Self::WhisperCpp {
+                command,
+                model,
+                language,
+            } => {
+                let mut file = tempfile::Builder::new()
+                    .prefix("vtcode-voice-")
+                    .suffix(".wav")
+                    .tempfile()
+                    .context("Error: Could not create a file for the recording")?;
+                file.write_all(&wav)
+                    .context("Error: Could not write the recording")?;
+                let mut whisper = Command::new(command);
+                whisper
+                    .arg("-m")
+                    .arg(model)
+                    .arg("-f")
+                    .arg(file.path())
+                    .args(["--no-timestamps", "--no-prints"])
+                    .kill_on_drop(true);
+                if let Some(language) = language {
+                    whisper.args(["-l", language]);
+                }
+                let output = whisper.output().await.with_context(|| {
+                    format!(
+                        "Error: Could not run `{command}`. Install whisper.cpp or set whisper_command under [ui.voice]."
+                    )
+                })?;
+                if !output.status.success() {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    let stderr: String = stderr.trim().chars().take(400).collect();
+                    bail!("Error: `{command}` failed ({}): {stderr}", output.status);
+                }
+                String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            }
+            Self::OpenAI {
+                client,
+                url,
+                model,
+                api_key,
+                language,
+            } => {
+                #[derive(Deserialize)]
+                struct Response {
+                    text: String,
+                }
+                let mut fields = vec![("model", model.as_str())];
+                if let Some(language) = language {
+                    fields.push(("language", language.as_str()));
+                }
+                let mut request = client
+                    .post(url)
+                    .header(
+                        reqwest::header::CONTENT_TYPE,
+                        format!("multipart/form-data; boundary={MULTIPART_BOUNDARY}"),
+                    )
+                    .body(multipart_body(&fields, &wav));
+                if let Some(key) = api_key {
+                    request = request.bearer_auth(key);
+                }
+                let response = request
+                    .send()
+                    .await
+                    .with_context(|| format!("Error: Could not reach {url} for transcription"))?;
+                let status = response.status();
+                if !status.is_success() {
+                    let body = response.text().await.unwrap_or_default();
+                    let body: String = body.chars().take(400).collect();
+                    bail!("Error: The transcription API answered {status}: {body}");
+                }
+                let response: Response = response.json().await.context(
+                    "Error: The transcription API sent a response that could not be read",
+                )?;
+                response.text
+            }
+        };
+        Ok(text.trim().to_string())
+    }
+}
+
+/// A `multipart/form-data` body with text `fields` and the recording as
+/// `file`.
+fn multipart_body(fields: &[(&str, &str)], wav: &[u8]) -> Vec<u8> {
+    let mut body = Vec::with_capacity(wav.len() + 512);
+    for (name, value) in fields {
+        body.extend_from_slice(
+            format!(
+                "--{MULTIPART_BOUNDARY}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n"
+            )
+            .as_bytes(),
+        );
+    }
+    body.extend_from_slice(
+        format!(
+            "--{MULTIPART_BOUNDARY}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"speech.wav\"\r\nContent-Type: audio/wav\r\n\r\n"
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(wav);
+    body.extend_from_slice(format!("\r\n--{MULTIPART_BOUNDARY}--\r\n").as_bytes());
+    body
+}
@@ -0,0 +1,87 @@
+//! Encoding captured audio as the 16 kHz mono 16-bit WAV whisper models read
+
+use super::record::Audio;
+
+/// Sample rate whisper models are trained on.
+pub const WHISPER_SAMPLE_RATE: u32 = 16_000;
+
+/// `audio` mixed down to mono, resampled to 16 kHz and encoded as WAV.
+pub fn encode_wav(audio: &Audio) -> Vec<u8> {
+    let mono = downmix(&audio.samples, audio.channels);
+    let samples = resample(&mono, audio.sample_rate, WHISPER_SAMPLE_RATE);
+
+    let data_len = (samples.len() * 2) as u32;
+    let mut wav = Vec::with_capacity(44 + samples.len() * 2);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&1u16.to_le_bytes()); // mono
+    wav.extend_from_slice(&WHISPER_SAMPLE_RATE.to_le_bytes());
+    wav.extend_from_slice(&(WHISPER_SAMPLE_RATE * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        let value = (sample.clamp(-1.0, 1.0) * f32::from(i16::MAX)).round() as i16;
+        wav.extend_from_slice(&value.to_le_bytes());
+    }
+    wav
+}
+
+fn downmix(samples: &[f32], channels: u16) -> Vec<f32> {
+    let channels = usize::from(channels.max(1));
+    samples
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect()
+}
+
+/// Linear interpolation; plenty for speech headed to a recognizer.
+fn resample(samples: &[f32], from: u32, to: u32) -> Vec<f32> {
+    if from == to || from == 0 || samples.is_empty() {
+        return samples.to_vec();
+    }
+    let step = f64::from(from) / f64::from(to);
+    let len = (samples.len() as f64 / step).floor() as usize;
+    (0..len)
+        .map(|index| {
+            let position = index as f64 * step;
+            let base = position.floor() as usize;
+            let next = (base + 1).min(samples.len() - 1);
+            let fraction = (position - base as f64) as f32;
+            samples[base] + (samples[next] - samples[base]) * fraction
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_stereo_48k_as_mono_16k() {
+        // One second of stereo at 48 kHz, left channel at half scale.
+        let audio = Audio {
+            samples: (0..48_000).flat_map(|_| [0.5, 0.0]).collect(),
+            sample_rate: 48_000,
+            channels: 2,
+        };
+        let wav = encode_wav(&audio);
+
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(&wav[8..16], b"WAVEfmt ");
+        assert_eq!(u16::from_le_bytes([wav[22], wav[23]]), 1);
+        assert_eq!(
+            u32::from_le_bytes([wav[24], wav[25], wav[26], wav[27]]),
+            WHISPER_SAMPLE_RATE
+        );
+        let data_len = u32::from_le_bytes([wav[40], wav[41], wav[42], wav[43]]) as usize;
+        assert_eq!(data_len, 16_000 * 2);
+        assert_eq!(wav.len(), 44 + data_len);
+        let first = i16::from_le_bytes([wav[44], wav[45]]);
+        assert_eq!(first, (0.25 * f32::from(i16::MAX)).round() as i16);
+    }
+}
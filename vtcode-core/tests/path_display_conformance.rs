@@ -0,0 +1,200 @@
+//! Every built-in tool reports paths in the form chosen by `[tools] path_display`.
+//!
+//! Each registered tool runs once against a small workspace. Its output must
+//! have no path field left out of form, and no other field may carry the
+//! workspace's absolute path except free text such as command output and
+//! error messages. A new built-in tool fails this suite until it has a case
+//! below.
+
+use serde_json::{Value, json};
+use std::path::Path;
+use tempfile::TempDir;
+use vtcode_core::config::{PathDisplay, ToolsConfig};
+use vtcode_core::tools::{PathDisplayPolicy, ToolRegistry};
+
+/// Fields that hold free text rather than paths.
+const FREE_TEXT_KEYS: &[&str] = &[
+    "stdout",
+    "stderr",
+    "output",
+    "content",
+    "patch",
+    "message",
+    "error",
+    "details",
+    "original_error",
+    "diff",
+    "summary",
+    "result",
+];
+
+fn cases() -> Vec<(&'static str, Value)> {
+    vec![
+        ("grep_search", json!({ "pattern": "fn main", "path": "." })),
+        ("list_files", json!({ "path": "src" })),
+        (
+            "update_plan",
+            json!({ "plan": [{ "step": "Read", "status": "in_progress" }] }),
+        ),
+        ("run_terminal_cmd", json!({ "command": ["ls", "src"] })),
+        ("curl", json!({ "url": "http://127.0.0.1:9/" })),
+        ("read_file", json!({ "path": "./src/main.rs" })),
+        (
+            "write_file",
+            json!({ "path": "./src/new.rs", "content": "pub fn new() {}\n" }),
+        ),
+        (
+            "edit_file",
+            json!({ "path": "src/new.rs", "old_str": "new()", "new_str": "fresh()" }),
+        ),
+        (
+            "ast_grep_search",
+            json!({ "pattern": "fn $NAME() {}", "path": "src" }),
+        ),
+        (
+            "simple_search",
+            json!({ "command": "find", "pattern": "main.rs" }),
+        ),
+        ("bash", json!({ "bash_command": "ls", "path": "src" })),
+        (
+            "apply_patch",
+            json!({ "input": "*** Begin Patch\n*** Add File: notes.txt\n+hello\n*** End Patch\n" }),
+        ),
+        (
+            "srgn",
+            json!({ "path": "src/main.rs", "scope": "main", "replacement": "main" }),
+        ),
+        ("organize_imports", json!({ "path": "src/main.rs" })),
+        (
+            "config_edit",
+            json!({ "path": "Cargo.toml", "action": "get", "key": "package.name" }),
+        ),
+        ("coverage_report", json!({ "run": false })),
+        ("bootstrap_env", json!({})),
+        ("project_commands", json!({})),
+        ("call_graph", json!({ "symbol": "main" })),
+        ("fetch_docs", json!({ "package": "" })),
+        ("git_status", json!({})),
+        ("git_diff", json!({})),
+        ("git_log", json!({})),
+        (
+            "git_commit",
+            json!({ "message": "Add sources", "all": true }),
+        ),
+        ("git_branch", json!({})),
+        ("sql_schema", json!({})),
+        ("sql_query", json!({ "query": "SELECT 1" })),
+        ("sql_execute", json!({ "statements": "SELECT 1" })),
+        ("vulnerability_scan", json!({ "refresh": false })),
+        ("semantic_search", json!({ "query": "entry point" })),
+    ]
+}
+
+fn workspace() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let root = dir.path();
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::write(
+        root.join("Cargo.toml"),
+        "[package]\nname = \"sample\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )
+    .unwrap();
+    std::fs::write(
+        root.join("src/main.rs"),
+        "fn helper() {}\n\nfn main() {\n    helper();\n}\n",
+    )
+    .unwrap();
+    let _ = std::process::Command::new("git")
+        .args(["init", "--quiet"])
+        .current_dir(root)
+        .output();
+    for setting in [["user.name", "test"], ["user.email", "test@example.com"]] {
+        let _ = std::process::Command::new("git")
+            .arg("config")
+            .args(setting)
+            .current_dir(root)
+            .output();
+    }
+    dir
+}
+
+/// Non-free-text string fields of `value` containing `root`, as `key: value`.
+fn leaked_roots(value: &Value, root: &str, key: &str, found: &mut Vec<String>) {
+    match value {
+        Value::String(text) if text.contains(root) && !FREE_TEXT_KEYS.contains(&key) => {
+            found.push(format!("{key}: {text}"));
+        }
+        Value::Object(fields) => {
+            for (field, inner) in fields {
+                if !FREE_TEXT_KEYS.contains(&field.as_str()) {
+                    leaked_roots(inner, root, field, found);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                leaked_roots(item, root, key, found);
+            }
+        }
+        _ => {}
+    }
+}
+
+async fn run_all(mode: PathDisplay) -> Vec<String> {
+    let dir = workspace();
+    let root = dir.path();
+    let mut registry = ToolRegistry::new(root.to_path_buf());
+    registry
+        .apply_config_policies(&ToolsConfig {
+            path_display: mode,
+            ..ToolsConfig::default()
+        })
+        .unwrap();
+    registry.allow_all_tools().unwrap();
+    let policy = PathDisplayPolicy::new(root, mode);
+
+    let cases = cases();
+    let mut problems: Vec<String> = registry
+        .available_tools()
+        .into_iter()
+        .filter(|tool| !cases.iter().any(|(name, _)| name == tool))
+        .map(|tool| format!("{tool}: no conformance case"))
+        .collect();
+    for (tool, args) in cases {
+        let output = registry.execute_tool(tool, args).await.unwrap();
+        for value in policy.violations(&output) {
+            problems.push(format!("{tool}: path field not {mode:?}: {value}"));
+        }
+        if mode == PathDisplay::Relative {
+            let mut leaked = Vec::new();
+            for shown in [root.to_path_buf(), root.canonicalize().unwrap()] {
+                leaked_roots(&output, &shown.to_string_lossy(), "", &mut leaked);
+            }
+            for field in leaked {
+                problems.push(format!("{tool}: absolute workspace path in {field}"));
+            }
+        }
+    }
+    problems
+}
+
+fn assert_conforms(problems: Vec<String>) {
+    assert!(problems.is_empty(), "{}", problems.join("\n"));
+}
+
+#[tokio::test]
+async fn tool_outputs_use_workspace_relative_paths() {
+    assert_conforms(run_all(PathDisplay::Relative).await);
+}
+
+#[tokio::test]
+async fn tool_outputs_use_absolute_paths_when_configured() {
+    assert_conforms(run_all(PathDisplay::Absolute).await);
+}
+
+#[test]
+fn policy_keeps_paths_outside_the_workspace() {
+    let policy = PathDisplayPolicy::new(Path::new("/work/space"), PathDisplay::Absolute);
+    assert_eq!(policy.display("src/lib.rs"), "/work/space/src/lib.rs");
+    assert_eq!(policy.display("/tmp/cache.json"), "/tmp/cache.json");
+}
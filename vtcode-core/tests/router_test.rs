@@ -1,7 +1,7 @@
 use vtcode_core::config::core::PromptCachingConfig;
 use vtcode_core::config::loader::VTCodeConfig;
 use vtcode_core::config::types::{
-    AgentConfig as CoreAgentConfig, ReasoningEffortLevel, UiSurfacePreference,
+    AgentConfig as CoreAgentConfig, ReasoningEffortLevel, RenderMode, UiSurfacePreference,
 };
 use vtcode_core::core::router::{Router, TaskClass};
 
@@ -10,11 +10,13 @@ fn core_cfg(model: &str) -> CoreAgentConfig {
         model: model.to_string(),
         api_key: "test".to_string(),
         provider: "gemini".to_string(),
+        base_url: None,
         workspace: std::env::current_dir().unwrap(),
         verbose: false,
         theme: vtcode_core::ui::theme::DEFAULT_THEME_ID.to_string(),
         reasoning_effort: ReasoningEffortLevel::default(),
         ui_surface: UiSurfacePreference::default(),
+        render_mode: RenderMode::default(),
         prompt_cache: PromptCachingConfig::default(),
     }
 }
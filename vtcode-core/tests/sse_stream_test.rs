@@ -0,0 +1,224 @@
+//! Streams through a simulated flaky SSE server: heartbeats, frames split at
+//! arbitrary bytes, mid-stream error frames and dropped connections.
+
+use futures::StreamExt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use vtcode_core::llm::provider::{LLMError, LLMProvider, LLMRequest, LLMStreamEvent, Message};
+use vtcode_core::llm::providers::OpenRouterProvider;
+
+/// What the server sends on one connection.
+struct Script {
+    chunks: Vec<&'static [u8]>,
+    /// Close the socket without ending the chunked body.
+    drop_connection: bool,
+}
+
+fn complete(chunks: Vec<&'static [u8]>) -> Script {
+    Script {
+        chunks,
+        drop_connection: false,
+    }
+}
+
+fn dropped(chunks: Vec<&'static [u8]>) -> Script {
+    Script {
+        chunks,
+        drop_connection: true,
+    }
+}
+
+/// Serves one script per connection and records each request's `Last-Event-ID`.
+async fn serve(scripts: Vec<Script>) -> (String, Arc<Mutex<Vec<Option<String>>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let seen = requests.clone();
+    tokio::spawn(async move {
+        for script in scripts {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let head = read_request(&mut socket).await;
+            let last_event_id = head.lines().find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                name.eq_ignore_ascii_case("last-event-id")
+                    .then(|| value.trim().to_string())
+            });
+            seen.lock().unwrap().push(last_event_id);
+
+            socket
+                .write_all(
+                    b"HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\ntransfer-encoding: chunked\r\nconnection: close\r\n\r\n",
+                )
+                .await
+                .unwrap();
+            for chunk in script.chunks {
+                let mut frame = format!("{:x}\r\n", chunk.len()).into_bytes();
+                frame.extend_from_slice(chunk);
+                frame.extend_from_slice(b"\r\n");
+                socket.write_all(&frame).await.unwrap();
+                socket.flush().await.unwrap();
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+            if !script.drop_connection {
+                socket.write_all(b"0\r\n\r\n").await.unwrap();
+            }
+            drop(socket);
+        }
+    });
+    (format!("http://{}", address), requests)
+}
+
+async fn read_request(socket: &mut tokio::net::TcpStream) -> String {
+    let mut received = Vec::new();
+    let mut buffer = [0u8; 4096];
+    loop {
+        let read = socket.read(&mut buffer).await.unwrap();
+        received.extend_from_slice(&buffer[..read]);
+        let text = String::from_utf8_lossy(&received).into_owned();
+        if let Some(end) = text.find("\r\n\r\n") {
+            let length = text[..end]
+                .lines()
+                .find_map(|line| {
+                    let (name, value) = line.split_once(':')?;
+                    name.eq_ignore_ascii_case("content-length")
+                        .then(|| value.trim().parse::<usize>().ok())?
+                })
+                .unwrap_or(0);
+            if received.len() >= end + 4 + length || read == 0 {
+                return text[..end].to_string();
+            }
+        }
+        if read == 0 {
+            return text;
+        }
+    }
+}
+
+fn request() -> LLMRequest {
+    LLMRequest {
+        messages: vec![Message::user("hi".to_string())],
+        system_prompt: None,
+        tools: None,
+        model: "openai/gpt-4o-mini".to_string(),
+        max_tokens: None,
+        temperature: None,
+        stream: true,
+        tool_choice: None,
+        parallel_tool_calls: None,
+        parallel_tool_config: None,
+        reasoning_effort: None,
+        json_mode: false,
+        response_schema: None,
+        code_execution: false,
+        seed: None,
+    }
+}
+
+/// Tokens received and the final content or error.
+async fn run(base_url: String) -> (String, Result<Option<String>, LLMError>) {
+    let provider =
+        OpenRouterProvider::from_config(Some("test".to_string()), None, Some(base_url), None);
+    let mut stream = provider.stream(request()).await.unwrap();
+    let mut tokens = String::new();
+    while let Some(event) = stream.next().await {
+        match event {
+            Ok(LLMStreamEvent::Token { delta }) => tokens.push_str(&delta),
+            Ok(LLMStreamEvent::Completed { response }) => return (tokens, Ok(response.content)),
+            Ok(_) => {}
+            Err(err) => return (tokens, Err(err)),
+        }
+    }
+    (tokens, Ok(None))
+}
+
+const FINISH: &[u8] = b"data: {\"choices\":[{\"delta\":{},\"finish_reason\":\"stop\"}]}\n\n";
+
+#[tokio::test]
+async fn tolerates_heartbeats_and_split_frames() {
+    let (base_url, requests) = serve(vec![complete(vec![
+        b": OPENROUTER PROCESSING\n\n",
+        b"retry: 1000\r\n\r\ndata: {\"choices\":[{\"delta\":{\"content\":\"h\xc3",
+        b"\xa9llo\"}}]}\r",
+        b"\n\r\n: keep-alive\n\n",
+        FINISH,
+        b"data: [DONE]\n\n",
+    ])])
+    .await;
+
+    let (tokens, result) = run(base_url).await;
+    assert_eq!(tokens, "héllo");
+    assert_eq!(result.unwrap().as_deref(), Some("héllo"));
+    assert_eq!(requests.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn surfaces_mid_stream_error_frames() {
+    let (base_url, requests) = serve(vec![complete(vec![
+        b"data: {\"choices\":[{\"delta\":{\"content\":\"partial\"}}]}\n\n",
+        b": keep-alive\n\n",
+        b"data: {\"error\":{\"code\":502,\"message\":\"Upstream provider closed the connection\"}}\n\n",
+    ])])
+    .await;
+
+    let (tokens, result) = run(base_url).await;
+    assert_eq!(tokens, "partial");
+    let message = result.unwrap_err().to_string();
+    assert!(message.contains("code 502"), "{message}");
+    assert!(
+        message.contains("Upstream provider closed the connection"),
+        "{message}"
+    );
+    assert_eq!(requests.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn retries_a_stream_dropped_before_the_first_token() {
+    let (base_url, requests) = serve(vec![
+        dropped(vec![b"retry: 10\n: keep-alive\n\n"]),
+        complete(vec![
+            b"data: {\"choices\":[{\"delta\":{\"content\":\"answer\"}}]}\n\n",
+            FINISH,
+        ]),
+    ])
+    .await;
+
+    let (tokens, result) = run(base_url).await;
+    assert_eq!(tokens, "answer");
+    assert_eq!(result.unwrap().as_deref(), Some("answer"));
+    assert_eq!(*requests.lock().unwrap(), vec![None, None]);
+}
+
+#[tokio::test]
+async fn resumes_from_the_last_event_id() {
+    let (base_url, requests) = serve(vec![
+        dropped(vec![
+            b"retry: 10\nid: 1\ndata: {\"choices\":[{\"delta\":{\"content\":\"Hello\"}}]}\n\n",
+        ]),
+        complete(vec![
+            b"id: 2\ndata: {\"choices\":[{\"delta\":{\"content\":\" world\"}}]}\n\n",
+            FINISH,
+            b"data: [DONE]\n\n",
+        ]),
+    ])
+    .await;
+
+    let (tokens, result) = run(base_url).await;
+    assert_eq!(tokens, "Hello world");
+    assert_eq!(result.unwrap().as_deref(), Some("Hello world"));
+    assert_eq!(*requests.lock().unwrap(), vec![None, Some("1".to_string())]);
+}
+
+#[tokio::test]
+async fn reports_a_truncated_stream_instead_of_completing() {
+    let (base_url, requests) = serve(vec![dropped(vec![
+        b"data: {\"choices\":[{\"delta\":{\"content\":\"half an ans\"}}]}\n\n",
+    ])])
+    .await;
+
+    let (tokens, result) = run(base_url).await;
+    assert_eq!(tokens, "half an ans");
+    assert!(matches!(result, Err(LLMError::Network(_))), "{result:?}");
+    assert_eq!(requests.lock().unwrap().len(), 1);
+}